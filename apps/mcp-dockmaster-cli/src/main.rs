@@ -1,12 +1,21 @@
-use std::path;
+use std::path::{self, PathBuf};
 
 use clap::{Parser, Subcommand};
 use log::{error, info};
 use mcp_core::{
+    config_file::ConflictPolicy,
     core::{
-        mcp_core::MCPCore, mcp_core_database_ext::McpCoreDatabaseExt,
+        mcp_core::{InitError, MCPCore},
+        mcp_core_config_file_ext::McpCoreConfigFileExt,
+        mcp_core_database_ext::McpCoreDatabaseExt,
+        mcp_core_diagnostics_ext::{DiagnosticsOptions, McpCoreDiagnosticsExt},
         mcp_core_proxy_ext::McpCoreProxyExt,
+        mcp_core_read_only_ext::McpCoreReadOnlyExt,
+        mcp_core_selftest_ext::McpCoreSelftestExt,
     },
+    database::state::DatabaseStatus,
+    events::{DockmasterEvent, InstallProgress},
+    http_server::handlers::{handle_register_tool, InstallOrigin},
     init_logging,
     utils::default_storage_path,
 };
@@ -39,6 +48,13 @@ enum Commands {
         entry_point: String,
     },
 
+    /// Install a server from the registry
+    Install {
+        /// Registry tool ID
+        #[arg(short, long)]
+        tool_id: String,
+    },
+
     /// List registered tools
     List,
 
@@ -87,10 +103,45 @@ enum Commands {
         /// Tool ID
         #[arg(short, long)]
         server_id: String,
+        /// Restart it even after it failed to start too many times in a row
+        #[arg(long)]
+        force: bool,
     },
 
     /// Clear the database
     Clear,
+
+    /// Export a diagnostics bundle to attach to bug reports
+    Diagnostics {
+        /// Only list the files the bundle would contain
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Check the app end to end with a temporary mock server, printing a report to
+    /// paste into bug reports
+    Selftest,
+
+    /// Show the state of the database: installed servers, schema version and pending
+    /// migrations
+    Status,
+
+    /// Run the proxy until stopped, for headless deployments
+    Serve {
+        /// Configuration file (toml, yaml or json) declaring the servers, applied
+        /// again each time it changes
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+
+        /// Servers changed both in the app and in the file: file_wins, db_wins or warn
+        #[arg(long)]
+        conflict_policy: Option<ConflictPolicy>,
+
+        /// Refuse to install, uninstall or reconfigure servers over JSON-RPC. The mode
+        /// stays on until turned off from the app or with the admin token.
+        #[arg(long)]
+        read_only: bool,
+    },
 }
 
 #[tokio::main]
@@ -117,7 +168,14 @@ async fn main() {
         path::absolute("mcp-proxy-server").unwrap(),
         "mcp-dockmaster-cli".to_string(),
     );
-    mcp_core.init().await.unwrap();
+    if let Err(e) = mcp_core.init().await {
+        match e {
+            // Points at the running instance
+            InitError::InstanceLock(e) => eprintln!("{}", e),
+            e => eprintln!("failed to initialize MCP Dockmaster: {:?}", e),
+        }
+        std::process::exit(1);
+    }
 
     // Handle commands
     match cli.command {
@@ -129,6 +187,39 @@ async fn main() {
             println!("Tool registration is not directly supported through the CLI.");
             println!("Please use the MCP Dockmaster UI to register tools.");
         }
+        Commands::Install { tool_id } => {
+            info!("Installing tool: {}", tool_id);
+
+            // Render install progress while the install runs
+            let mut events = mcp_core.event_bus.subscribe();
+            let install = handle_register_tool(
+                mcp_core.clone(),
+                serde_json::json!({ "tool_id": tool_id }),
+                InstallOrigin::Api,
+            );
+            tokio::pin!(install);
+            let result = loop {
+                tokio::select! {
+                    result = &mut install => break result,
+                    Ok(envelope) = events.recv() => print_install_progress(&envelope.event),
+                }
+            };
+            while let Ok(envelope) = events.try_recv() {
+                print_install_progress(&envelope.event);
+            }
+            match result {
+                Ok(response) => {
+                    println!("{}", response.message);
+                    for warning in response.warnings {
+                        println!("Warning: {}", warning);
+                    }
+                }
+                Err(e) => {
+                    error!("Error installing tool: {}", e.message);
+                    println!("Error installing tool: {}", e.message);
+                }
+            }
+        }
         Commands::List => {
             info!("Listing tools");
 
@@ -211,11 +302,14 @@ async fn main() {
             println!("Tool uninstallation is not directly supported through the CLI.");
             println!("Please use the MCP Dockmaster UI to uninstall tools.");
         }
-        Commands::Restart { server_id } => {
+        Commands::Restart { server_id, force } => {
             info!("Restarting server: {}", server_id);
 
             // Restart the server using the direct function
-            match mcp_core.restart_server_command(server_id.clone()).await {
+            match mcp_core
+                .restart_server_command(server_id.clone(), force)
+                .await
+            {
                 Ok(_) => {
                     println!("Server restarted successfully");
                 }
@@ -239,5 +333,130 @@ async fn main() {
                 }
             }
         }
+        Commands::Diagnostics { dry_run } => {
+            info!("Exporting diagnostics");
+
+            let options = DiagnosticsOptions {
+                app_version: Some(env!("CARGO_PKG_VERSION").to_string()),
+                log_dirs: Vec::new(),
+            };
+            let preview = mcp_core.preview_diagnostics(&options).await;
+            println!("The diagnostics bundle contains:");
+            for file in &preview.files {
+                println!("  {} ({} bytes)", file.path, file.size);
+            }
+            println!("{} secret values redacted", preview.redacted_values);
+
+            if !dry_run {
+                match mcp_core.export_diagnostics(&options).await {
+                    Ok(export) => {
+                        println!("Diagnostics written to {}", export.path.display());
+                    }
+                    Err(e) => {
+                        error!("Error exporting diagnostics: {}", e);
+                        println!("Error exporting diagnostics: {}", e);
+                    }
+                }
+            }
+        }
+        Commands::Selftest => {
+            let report = mcp_core.run_selftest().await;
+            print!("{}", report.to_text());
+            if !report.passed {
+                mcp_core.release_instance_lock();
+                std::process::exit(1);
+            }
+        }
+        Commands::Status => {
+            let state = mcp_core.database_state().await;
+            println!("Database: {}", state.status);
+            if let Some(error) = &state.error {
+                println!("  {}", error);
+            }
+            if let Some(version) = &state.schema_version {
+                println!("Schema version: {}", version);
+            }
+            println!(
+                "Servers: {} installed, {} tools",
+                state.server_count, state.tool_count
+            );
+            if state.pending_migrations > 0 {
+                println!("Schema migrations pending: {}", state.pending_migrations);
+            }
+            if state.legacy_server_count > 0 {
+                println!(
+                    "Servers of a previous version not migrated: {}",
+                    state.legacy_server_count
+                );
+            }
+            if state.status == DatabaseStatus::Unreadable {
+                mcp_core.release_instance_lock();
+                std::process::exit(1);
+            }
+        }
+        Commands::Serve {
+            config,
+            conflict_policy,
+            read_only,
+        } => {
+            if read_only {
+                if let Err(e) = mcp_core.set_read_only(true).await {
+                    error!("Failed to enable the read-only mode: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            let watcher = config.map(|path| mcp_core.watch_config_file(path, conflict_policy));
+            info!("Serving on port {}", mcp_core.port);
+
+            shutdown_signal().await;
+            info!("Shutting down");
+            if let Some(watcher) = watcher {
+                watcher.abort();
+            }
+            if let Err(e) = mcp_core.kill_all_processes().await {
+                error!("Error stopping servers: {}", e);
+            }
+            mcp_core.release_instance_lock();
+        }
+    }
+}
+
+/// Wait for Ctrl-C, or for SIGTERM as sent by service managers
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = terminate.recv() => {}
+                }
+                return;
+            }
+            Err(e) => error!("Failed to listen for SIGTERM: {}", e),
+        }
+    }
+    if let Err(e) = tokio::signal::ctrl_c().await {
+        error!("Failed to listen for Ctrl-C: {}", e);
+    }
+}
+
+fn print_install_progress(event: &DockmasterEvent) {
+    match event {
+        DockmasterEvent::InstallProgress(InstallProgress { stage, detail, .. }) => match detail {
+            Some(detail) => println!("[{:?}] {}", stage, detail),
+            None => println!("[{:?}]", stage),
+        },
+        DockmasterEvent::OperationUpdate(_)
+        | DockmasterEvent::OrphansCleaned(_)
+        | DockmasterEvent::ApprovalRequested(_)
+        | DockmasterEvent::ApprovalResolved(_)
+        | DockmasterEvent::ToolsUpdated(_)
+        | DockmasterEvent::ServerReconnected(_)
+        | DockmasterEvent::StartupProgress(_)
+        | DockmasterEvent::UpdateAvailable(_)
+        | DockmasterEvent::ToolsBudgetExceeded(_)
+        | DockmasterEvent::EnvironmentChanged(_) => {}
     }
 }
@@ -1,25 +1,78 @@
+use std::collections::HashMap;
+
 use mcp_core::{
+    approvals::ApprovalRequest,
+    capabilities::Capabilities,
+    config_history::{ConfigApplyResult, ConfigSnapshot},
     core::{
-        mcp_core::MCPCore, mcp_core_database_ext::McpCoreDatabaseExt,
-        mcp_core_installers_ext::McpCoreInstallersExt, mcp_core_proxy_ext::McpCoreProxyExt,
+        mcp_core::MCPCore,
+        mcp_core_approvals_ext::McpCoreApprovalsExt,
+        mcp_core_bulk_ext::McpCoreBulkExt,
+        mcp_core_config_history_ext::McpCoreConfigHistoryExt,
+        mcp_core_dashboard_ext::McpCoreDashboardExt,
+        mcp_core_database_ext::McpCoreDatabaseExt,
+        mcp_core_diagnose_ext::McpCoreDiagnoseExt,
+        mcp_core_diagnostics_ext::{DiagnosticsOptions, McpCoreDiagnosticsExt},
+        mcp_core_footprint_ext::McpCoreFootprintExt,
+        mcp_core_health_ext::McpCoreHealthExt,
+        mcp_core_hooks_ext::McpCoreHooksExt,
+        mcp_core_identity_ext::McpCoreIdentityExt,
+        mcp_core_installers_ext::McpCoreInstallersExt,
+        mcp_core_proxy_ext::McpCoreProxyExt,
+        mcp_core_rate_limits_ext::McpCoreRateLimitsExt,
+        mcp_core_read_only_ext::McpCoreReadOnlyExt,
+        mcp_core_registry_sources_ext::McpCoreRegistrySourcesExt,
+        mcp_core_relocation_ext::McpCoreRelocationExt,
+        mcp_core_schedules_ext::McpCoreSchedulesExt,
+        mcp_core_selftest_ext::McpCoreSelftestExt,
+        mcp_core_server_info_ext::McpCoreServerInfoExt,
+        mcp_core_tool_limits_ext::McpCoreToolLimitsExt,
+        mcp_core_tools_ext::McpCoreToolsExt,
+        mcp_core_tools_snapshot_ext::McpCoreToolsSnapshotExt,
+        mcp_core_undo_ext::McpCoreUndoExt,
+        mcp_core_webhooks_ext::McpCoreWebhooksExt,
     },
+    database::state::DatabaseState,
+    diagnose::ServerDiagnosis,
+    diagnostics::{DiagnosticsExport, DiagnosticsPreview},
+    footprint::{ToolsFootprint, FOOTPRINT_TOP_TOOLS},
+    health::HealthCheckSettings,
+    hooks::HooksConfig,
+    identity::{ServerIdentity, ServerIdentityOverrides},
+    lifecycle::ServerHistory,
+    models::registration::parse_registration_request,
     models::types::{
-        DiscoverServerToolsRequest, ServerConfigUpdateRequest, ServerRegistrationRequest,
+        ActionPolicy, AuditEntry, BulkServerResponse, DashboardSummary, DiscoverServerToolsRequest,
+        GatedAction, OrphanCleanupReport, ServerConfigUpdateRequest, ServerInitInfo,
         ServerRegistrationResponse, ServerToolInfo, ServerUninstallResponse, ServerUpdateRequest,
-        ToolConfigUpdateResponse, ToolExecutionRequest, ToolExecutionResponse,
-        ToolUninstallRequest, ToolUpdateResponse,
+        ToolAnnotations, ToolConfigUpdateResponse, ToolDescription, ToolExecutionRequest,
+        ToolExecutionResponse, ToolUninstallRequest, ToolUpdateResponse,
     },
+    operations::Operation,
+    rate_limits::RateLimit,
+    registry::registry_sources::RegistrySource,
+    relocation::{MigrationReport, PathMigrationResults},
+    schedules::{Schedule, ScheduleDefinition, ScheduleRun},
+    selftest::SelftestReport,
+    server_logs::ServerLogSnapshot,
+    tool_limits::{ToolFilter, ToolLimitReport},
     types::{IsProcessRunningRequest, RuntimeServer},
+    undo::{UndoEntry, UndoResult},
+    utils::description::DescriptionOptions,
+    webhooks::{CreatedWebhook, DeadLetter, Webhook, WebhookDelivery},
 };
-use tauri::State;
+use tauri::{AppHandle, Manager, State};
 
 /// Register a new tool with the MCP server
 #[tauri::command]
 pub async fn register_server(
     mcp_core: State<'_, MCPCore>,
-    request: ServerRegistrationRequest,
+    request: serde_json::Value,
 ) -> Result<ServerRegistrationResponse, String> {
-    mcp_core.register_server(request).await
+    let (request, warnings) = parse_registration_request(request)?;
+    let mut response = mcp_core.register_server(request).await?;
+    response.warnings.extend(warnings);
+    Ok(response)
 }
 
 /// List all registered tools
@@ -36,6 +89,45 @@ pub async fn list_all_server_tools(
     mcp_core.list_all_server_tools().await
 }
 
+/// Get everything known about one tool, with its full description
+#[tauri::command]
+pub async fn describe_tool(
+    mcp_core: State<'_, MCPCore>,
+    server_id: String,
+    tool_name: String,
+) -> Result<ToolDescription, String> {
+    mcp_core
+        .describe_tool(&server_id, &tool_name)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Set the rate limit of a tool, limits left out are removed
+#[tauri::command]
+pub async fn set_tool_rate_limit(
+    mcp_core: State<'_, MCPCore>,
+    server_id: String,
+    tool_name: String,
+    limit: RateLimit,
+) -> Result<(), String> {
+    mcp_core
+        .set_tool_rate_limit(&server_id, &tool_name, limit)
+        .await
+}
+
+/// Set the annotations of a tool over its server's, empty annotations remove them
+#[tauri::command]
+pub async fn set_tool_annotations(
+    mcp_core: State<'_, MCPCore>,
+    server_id: String,
+    tool_name: String,
+    annotations: ToolAnnotations,
+) -> Result<(), String> {
+    mcp_core
+        .set_tool_annotations(&server_id, &tool_name, annotations)
+        .await
+}
+
 /// Discover tools from a specific MCP server
 #[tauri::command]
 pub async fn discover_tools(
@@ -63,6 +155,39 @@ pub async fn update_server_status(
     mcp_core.update_server_status(request).await
 }
 
+/// Counts for the home screen, cheap enough to poll
+#[tauri::command]
+pub async fn get_dashboard_summary(
+    mcp_core: State<'_, MCPCore>,
+) -> Result<DashboardSummary, String> {
+    mcp_core.dashboard_summary().await
+}
+
+/// Size of the tool list in the context of the models, with its most expensive tools
+#[tauri::command]
+pub async fn get_tools_footprint(
+    mcp_core: State<'_, MCPCore>,
+    top: Option<usize>,
+) -> Result<ToolsFootprint, String> {
+    mcp_core
+        .tools_footprint(top.unwrap_or(FOOTPRINT_TOP_TOOLS))
+        .await
+}
+
+/// Budget of tokens of the tool list, if one is set
+#[tauri::command]
+pub async fn get_tools_budget(mcp_core: State<'_, MCPCore>) -> Result<Option<usize>, String> {
+    Ok(mcp_core.tools_budget().await)
+}
+
+#[tauri::command]
+pub async fn set_tools_budget(
+    mcp_core: State<'_, MCPCore>,
+    budget: Option<usize>,
+) -> Result<(), String> {
+    mcp_core.set_tools_budget(budget).await
+}
+
 /// Update a tool's configuration (environment variables)
 #[tauri::command]
 pub async fn update_server_config(
@@ -72,6 +197,170 @@ pub async fn update_server_config(
     mcp_core.update_server_config(request).await
 }
 
+/// Update a server's configuration and restart it, rolling back if it fails to start
+#[tauri::command]
+pub async fn apply_server_config(
+    mcp_core: State<'_, MCPCore>,
+    request: ServerConfigUpdateRequest,
+) -> Result<ConfigApplyResult, String> {
+    mcp_core.apply_server_config(request).await
+}
+
+/// Get the configurations a server had before its last changes, newest first
+#[tauri::command]
+pub async fn get_server_config_history(
+    mcp_core: State<'_, MCPCore>,
+    server_id: String,
+    limit: Option<i64>,
+) -> Result<Vec<ConfigSnapshot>, String> {
+    mcp_core
+        .config_history(&server_id, limit.unwrap_or(20))
+        .await
+}
+
+/// Put back a previous configuration of a server and restart it
+#[tauri::command]
+pub async fn rollback_server_config(
+    mcp_core: State<'_, MCPCore>,
+    server_id: String,
+    snapshot_id: i64,
+) -> Result<ConfigApplyResult, String> {
+    mcp_core
+        .rollback_server_config(&server_id, snapshot_id)
+        .await
+}
+
+/// Changes to servers that can still be undone, newest first
+#[tauri::command]
+pub async fn list_undoable_changes(mcp_core: State<'_, MCPCore>) -> Result<Vec<UndoEntry>, String> {
+    mcp_core.undoable_changes().await
+}
+
+/// Undo an uninstall, disable or configuration change
+#[tauri::command]
+pub async fn undo_change(
+    mcp_core: State<'_, MCPCore>,
+    entry_id: i64,
+) -> Result<UndoResult, String> {
+    mcp_core.undo_change(entry_id).await
+}
+
+/// Hours a change can be undone for
+#[tauri::command]
+pub async fn get_undo_retention(mcp_core: State<'_, MCPCore>) -> Result<u64, String> {
+    Ok(mcp_core.undo_retention_hours().await)
+}
+
+#[tauri::command]
+pub async fn set_undo_retention(mcp_core: State<'_, MCPCore>, hours: u64) -> Result<(), String> {
+    mcp_core.set_undo_retention_hours(hours).await
+}
+
+/// How often the running servers are checked and what happens to unhealthy ones
+#[tauri::command]
+pub async fn get_health_check_settings(
+    mcp_core: State<'_, MCPCore>,
+) -> Result<HealthCheckSettings, String> {
+    Ok(mcp_core.health_check_settings().await)
+}
+
+#[tauri::command]
+pub async fn set_health_check_settings(
+    mcp_core: State<'_, MCPCore>,
+    settings: HealthCheckSettings,
+) -> Result<(), String> {
+    mcp_core.set_health_check_settings(settings).await
+}
+
+/// Seconds a server has to start and answer the initialize handshake
+#[tauri::command]
+pub async fn get_startup_timeout(mcp_core: State<'_, MCPCore>) -> Result<u64, String> {
+    Ok(mcp_core.startup_timeout_secs().await)
+}
+
+#[tauri::command]
+pub async fn set_startup_timeout(mcp_core: State<'_, MCPCore>, secs: u64) -> Result<(), String> {
+    mcp_core.set_startup_timeout_secs(secs).await
+}
+
+/// Seconds a server has to exit once stopped before it is killed
+#[tauri::command]
+pub async fn get_shutdown_grace(mcp_core: State<'_, MCPCore>) -> Result<u64, String> {
+    Ok(mcp_core.shutdown_grace_secs().await)
+}
+
+#[tauri::command]
+pub async fn set_shutdown_grace(mcp_core: State<'_, MCPCore>, secs: u64) -> Result<(), String> {
+    mcp_core.set_shutdown_grace_secs(secs).await
+}
+
+/// Consecutive failed starts after which a server isn't started again
+#[tauri::command]
+pub async fn get_max_failed_starts(mcp_core: State<'_, MCPCore>) -> Result<u32, String> {
+    Ok(mcp_core.max_failed_starts().await)
+}
+
+#[tauri::command]
+pub async fn set_max_failed_starts(mcp_core: State<'_, MCPCore>, limit: u32) -> Result<(), String> {
+    mcp_core.set_max_failed_starts(limit).await
+}
+
+/// Run the troubleshooting checklist on a server
+#[tauri::command]
+pub async fn diagnose_server(
+    mcp_core: State<'_, MCPCore>,
+    server_id: String,
+) -> Result<ServerDiagnosis, String> {
+    mcp_core.diagnose_server(&server_id).await
+}
+
+/// Write a checklist report and the redacted server configuration to the data directory
+#[tauri::command]
+pub async fn export_server_diagnosis(
+    mcp_core: State<'_, MCPCore>,
+    diagnosis: ServerDiagnosis,
+) -> Result<DiagnosticsExport, String> {
+    mcp_core.export_server_diagnosis(&diagnosis).await
+}
+
+/// Name, version and instructions a server gave when it last started
+#[tauri::command]
+pub async fn get_server_info(
+    mcp_core: State<'_, MCPCore>,
+    server_id: String,
+) -> Result<Option<ServerInitInfo>, String> {
+    mcp_core.server_info(&server_id).await
+}
+
+/// What a server wrote to stderr, the lines read at or after `since` when given
+#[tauri::command]
+pub async fn get_server_logs(
+    mcp_core: State<'_, MCPCore>,
+    server_id: String,
+    since: Option<u64>,
+) -> Result<ServerLogSnapshot, String> {
+    mcp_core.get_server_logs(server_id, since).await
+}
+
+/// When a server started, crashed, restarted and stopped, with its uptime
+#[tauri::command]
+pub async fn get_server_history(
+    mcp_core: State<'_, MCPCore>,
+    server_id: String,
+) -> Result<ServerHistory, String> {
+    mcp_core.get_server_history(server_id).await
+}
+
+/// Pass the instructions of a server on to MCP clients or not
+#[tauri::command]
+pub async fn set_share_instructions(
+    mcp_core: State<'_, MCPCore>,
+    server_id: String,
+    share: bool,
+) -> Result<(), String> {
+    mcp_core.set_share_instructions(&server_id, share).await
+}
+
 /// Uninstall a registered tool
 #[tauri::command]
 pub async fn uninstall_server(
@@ -87,6 +376,29 @@ pub async fn check_database_exists_command(mcp_core: State<'_, MCPCore>) -> Resu
     mcp_core.check_database_exists().await
 }
 
+/// Get the version, JSON-RPC methods and features of mcp-core, for the app to check at startup
+#[tauri::command]
+pub async fn get_capabilities() -> Result<Capabilities, String> {
+    Ok(mcp_core::capabilities::capabilities())
+}
+
+/// Save which tools of a server are listed, None for the default, applied at once when it
+/// runs
+#[tauri::command]
+pub async fn set_tool_filter(
+    mcp_core: State<'_, MCPCore>,
+    server_id: String,
+    filter: Option<ToolFilter>,
+) -> Result<Option<ToolLimitReport>, String> {
+    mcp_core.set_tool_filter(&server_id, filter).await
+}
+
+/// Get whether the database is new, empty, in use, waits for a migration or can't be read
+#[tauri::command]
+pub async fn get_database_state(mcp_core: State<'_, MCPCore>) -> Result<DatabaseState, String> {
+    Ok(mcp_core.database_state().await)
+}
+
 /// Clear all data from the database
 #[tauri::command]
 pub async fn clear_database_command(mcp_core: State<'_, MCPCore>) -> Result<(), String> {
@@ -98,8 +410,386 @@ pub async fn clear_database_command(mcp_core: State<'_, MCPCore>) -> Result<(),
 pub async fn restart_server_command(
     mcp_core: State<'_, MCPCore>,
     server_id: String,
+    force: Option<bool>,
 ) -> Result<ToolUpdateResponse, String> {
-    mcp_core.restart_server_command(server_id).await
+    mcp_core
+        .restart_server_command(server_id, force.unwrap_or(false))
+        .await
+}
+
+/// Start an enabled server that isn't running
+#[tauri::command(rename_all = "camelCase")]
+pub async fn start_server(
+    mcp_core: State<'_, MCPCore>,
+    server_id: String,
+) -> Result<ToolUpdateResponse, String> {
+    mcp_core.start_server(server_id).await
+}
+
+/// Stop a server but keep it enabled
+#[tauri::command(rename_all = "camelCase")]
+pub async fn stop_server(
+    mcp_core: State<'_, MCPCore>,
+    server_id: String,
+) -> Result<ToolUpdateResponse, String> {
+    mcp_core.stop_server(server_id).await
+}
+
+/// Enable or disable several servers at once
+#[tauri::command(rename_all = "camelCase")]
+pub async fn set_servers_enabled(
+    mcp_core: State<'_, MCPCore>,
+    server_ids: Vec<String>,
+    enabled: bool,
+) -> Result<BulkServerResponse, String> {
+    mcp_core.set_servers_enabled(server_ids, enabled).await
+}
+
+/// Restart the given servers, or every server that should be running
+#[tauri::command(rename_all = "camelCase")]
+pub async fn restart_servers(
+    mcp_core: State<'_, MCPCore>,
+    server_ids: Option<Vec<String>>,
+) -> Result<BulkServerResponse, String> {
+    mcp_core.restart_servers(server_ids).await
+}
+
+/// Restart every server that should be running
+#[tauri::command]
+pub async fn restart_all_servers(
+    mcp_core: State<'_, MCPCore>,
+) -> Result<BulkServerResponse, String> {
+    mcp_core.restart_all_servers().await
+}
+
+/// List the tracked management operations, most recent first
+#[tauri::command]
+pub async fn list_operations(mcp_core: State<'_, MCPCore>) -> Result<Vec<Operation>, String> {
+    Ok(mcp_core.operations.list())
+}
+
+/// Cancel a running management operation
+#[tauri::command(rename_all = "camelCase")]
+pub async fn cancel_operation(
+    mcp_core: State<'_, MCPCore>,
+    operation_id: String,
+) -> Result<Operation, String> {
+    mcp_core.operations.cancel(&operation_id)
+}
+
+/// Get the orphaned processes cleaned up at startup, or by the last cleanup asked for
+#[tauri::command]
+pub async fn get_orphan_cleanup_report(
+    mcp_core: State<'_, MCPCore>,
+) -> Result<OrphanCleanupReport, String> {
+    let mcp_state = mcp_core.mcp_state.read().await;
+    let report = mcp_state.orphan_cleanup_report.read().await.clone();
+    Ok(report)
+}
+
+/// Terminate the server processes and containers left behind by a crashed run
+#[tauri::command]
+pub async fn cleanup_orphans(mcp_core: State<'_, MCPCore>) -> Result<OrphanCleanupReport, String> {
+    Ok(mcp_core.cleanup_orphans().await)
+}
+
+/// List the actions requested by MCP clients that wait for the user's approval
+#[tauri::command]
+pub async fn list_pending_approvals(
+    mcp_core: State<'_, MCPCore>,
+) -> Result<Vec<ApprovalRequest>, String> {
+    Ok(mcp_core.approvals.pending())
+}
+
+/// Approve or reject an action requested by an MCP client
+#[tauri::command(rename_all = "camelCase")]
+pub async fn resolve_approval(
+    mcp_core: State<'_, MCPCore>,
+    approval_id: String,
+    approved: bool,
+) -> Result<ApprovalRequest, String> {
+    mcp_core.resolve_approval(&approval_id, approved).await
+}
+
+/// Get the policy of each action MCP clients can request
+#[tauri::command]
+pub async fn get_action_policies(
+    mcp_core: State<'_, MCPCore>,
+) -> Result<HashMap<GatedAction, ActionPolicy>, String> {
+    Ok(mcp_core.action_policies().await)
+}
+
+/// Set the policy of an action MCP clients can request
+#[tauri::command]
+pub async fn set_action_policy(
+    mcp_core: State<'_, MCPCore>,
+    action: GatedAction,
+    policy: ActionPolicy,
+) -> Result<(), String> {
+    mcp_core.set_action_policy(action, policy).await
+}
+
+/// Get the most recent decisions on actions requested by MCP clients
+#[tauri::command]
+pub async fn get_audit_log(
+    mcp_core: State<'_, MCPCore>,
+    limit: Option<i64>,
+) -> Result<Vec<AuditEntry>, String> {
+    mcp_core.audit_log(limit.unwrap_or(100)).await
+}
+
+/// Get the hooks run around tool calls, in the format `set_tool_hooks` accepts
+#[tauri::command]
+pub async fn get_tool_hooks(mcp_core: State<'_, MCPCore>) -> Result<HooksConfig, String> {
+    Ok(mcp_core.tool_hooks().await)
+}
+
+/// Replace the hooks run around tool calls
+#[tauri::command]
+pub async fn set_tool_hooks(
+    mcp_core: State<'_, MCPCore>,
+    config: HooksConfig,
+) -> Result<(), String> {
+    mcp_core.set_tool_hooks(config).await
+}
+
+/// List the scheduled tool calls with their status
+#[tauri::command]
+pub async fn list_schedules(mcp_core: State<'_, MCPCore>) -> Result<Vec<Schedule>, String> {
+    mcp_core.list_schedules().await
+}
+
+/// Create a scheduled tool call
+#[tauri::command]
+pub async fn create_schedule(
+    mcp_core: State<'_, MCPCore>,
+    schedule: ScheduleDefinition,
+) -> Result<Schedule, String> {
+    mcp_core.create_schedule(schedule).await
+}
+
+/// Replace the definition of a scheduled tool call
+#[tauri::command]
+pub async fn update_schedule(
+    mcp_core: State<'_, MCPCore>,
+    schedule: ScheduleDefinition,
+) -> Result<Schedule, String> {
+    mcp_core.update_schedule(schedule).await
+}
+
+/// Delete a scheduled tool call and its run history
+#[tauri::command]
+pub async fn delete_schedule(
+    mcp_core: State<'_, MCPCore>,
+    schedule_id: String,
+) -> Result<(), String> {
+    mcp_core.delete_schedule(&schedule_id).await
+}
+
+/// Get the most recent runs of a scheduled tool call, newest first
+#[tauri::command]
+pub async fn get_schedule_runs(
+    mcp_core: State<'_, MCPCore>,
+    schedule_id: String,
+    limit: Option<i64>,
+) -> Result<Vec<ScheduleRun>, String> {
+    mcp_core
+        .schedule_runs(&schedule_id, limit.unwrap_or(20))
+        .await
+}
+
+/// List the webhooks, without their secrets
+#[tauri::command]
+pub async fn list_webhooks(mcp_core: State<'_, MCPCore>) -> Result<Vec<Webhook>, String> {
+    mcp_core.list_webhooks().await
+}
+
+/// Create a webhook, answering with its secret
+#[tauri::command]
+pub async fn create_webhook(
+    mcp_core: State<'_, MCPCore>,
+    webhook: Webhook,
+) -> Result<CreatedWebhook, String> {
+    mcp_core.create_webhook(webhook).await
+}
+
+/// Replace a webhook, keeping its secret unless a new one is given
+#[tauri::command]
+pub async fn update_webhook(
+    mcp_core: State<'_, MCPCore>,
+    webhook: Webhook,
+) -> Result<Webhook, String> {
+    mcp_core.update_webhook(webhook).await
+}
+
+#[tauri::command]
+pub async fn delete_webhook(
+    mcp_core: State<'_, MCPCore>,
+    webhook_id: String,
+) -> Result<(), String> {
+    mcp_core.delete_webhook(&webhook_id).await
+}
+
+/// Send a test payload to a webhook
+#[tauri::command]
+pub async fn test_webhook(
+    mcp_core: State<'_, MCPCore>,
+    webhook_id: String,
+) -> Result<WebhookDelivery, String> {
+    mcp_core.test_webhook(&webhook_id).await
+}
+
+/// Get the payloads that could not be delivered, newest first
+#[tauri::command]
+pub async fn get_webhook_dead_letters(
+    mcp_core: State<'_, MCPCore>,
+    limit: Option<i64>,
+) -> Result<Vec<DeadLetter>, String> {
+    mcp_core.webhook_dead_letters(limit.unwrap_or(100)).await
+}
+
+/// List the registries used next to the default one, without their credentials
+#[tauri::command]
+pub async fn list_registry_sources(
+    mcp_core: State<'_, MCPCore>,
+) -> Result<Vec<RegistrySource>, String> {
+    mcp_core.list_registry_sources().await
+}
+
+/// Create or replace a registry source, keeping its credentials unless new ones are given
+#[tauri::command]
+pub async fn save_registry_source(
+    mcp_core: State<'_, MCPCore>,
+    source: RegistrySource,
+) -> Result<RegistrySource, String> {
+    mcp_core.save_registry_source(source).await
+}
+
+/// Delete a registry source, servers installed from it are kept
+#[tauri::command]
+pub async fn delete_registry_source(
+    mcp_core: State<'_, MCPCore>,
+    source_id: String,
+) -> Result<(), String> {
+    mcp_core.delete_registry_source(&source_id).await
+}
+
+/// Whether the proxy refuses to install or reconfigure servers
+#[tauri::command]
+pub async fn get_read_only(mcp_core: State<'_, MCPCore>) -> Result<bool, String> {
+    Ok(mcp_core.is_read_only().await)
+}
+
+/// Turn the read-only mode on or off, the app doesn't need the admin token
+#[tauri::command]
+pub async fn set_read_only(mcp_core: State<'_, MCPCore>, read_only: bool) -> Result<(), String> {
+    mcp_core.set_read_only(read_only).await
+}
+
+/// Diagnostics options including the app version and logs
+fn diagnostics_options(app_handle: &AppHandle) -> DiagnosticsOptions {
+    DiagnosticsOptions {
+        app_version: Some(app_handle.package_info().version.to_string()),
+        log_dirs: app_handle.path().app_log_dir().into_iter().collect(),
+    }
+}
+
+/// List the files a diagnostics export would contain, without writing it
+#[tauri::command]
+pub async fn preview_diagnostics(
+    app_handle: AppHandle,
+    mcp_core: State<'_, MCPCore>,
+) -> Result<DiagnosticsPreview, String> {
+    Ok(mcp_core
+        .preview_diagnostics(&diagnostics_options(&app_handle))
+        .await)
+}
+
+/// Write a diagnostics bundle for bug reports to the data directory
+#[tauri::command]
+pub async fn export_diagnostics(
+    app_handle: AppHandle,
+    mcp_core: State<'_, MCPCore>,
+) -> Result<DiagnosticsExport, String> {
+    mcp_core
+        .export_diagnostics(&diagnostics_options(&app_handle))
+        .await
+}
+
+/// Check the app end to end with a temporary mock server, see `mcp_core::selftest`
+#[tauri::command]
+pub async fn run_selftest(mcp_core: State<'_, MCPCore>) -> Result<SelftestReport, String> {
+    Ok(mcp_core.run_selftest().await)
+}
+
+/// Get the servers with missing paths since the data directory changed machine
+#[tauri::command]
+pub async fn get_migration_report(
+    mcp_core: State<'_, MCPCore>,
+) -> Result<Option<MigrationReport>, String> {
+    mcp_core.migration_report().await
+}
+
+/// Move the missing paths of servers under the new home directory, all servers of the
+/// migration report when `server_ids` is missing
+#[tauri::command]
+pub async fn migrate_server_paths(
+    mcp_core: State<'_, MCPCore>,
+    server_ids: Option<Vec<String>>,
+) -> Result<PathMigrationResults, String> {
+    mcp_core.migrate_server_paths(server_ids).await
+}
+
+/// Get how tool descriptions are shortened in the tool list
+#[tauri::command]
+pub async fn get_description_options(
+    mcp_core: State<'_, MCPCore>,
+) -> Result<DescriptionOptions, String> {
+    Ok(mcp_core.description_options().await)
+}
+
+/// Set how tool descriptions are shortened in the tool list
+#[tauri::command]
+pub async fn set_description_options(
+    mcp_core: State<'_, MCPCore>,
+    options: DescriptionOptions,
+) -> Result<(), String> {
+    mcp_core.set_description_options(options).await
+}
+
+/// Identity presented to MCP clients and what the user changed of it
+#[tauri::command]
+pub async fn get_server_identity(
+    mcp_core: State<'_, MCPCore>,
+) -> Result<(ServerIdentity, ServerIdentityOverrides), String> {
+    Ok((
+        mcp_core.server_identity().await,
+        mcp_core.server_identity_overrides().await,
+    ))
+}
+
+/// Change the name, version or instructions presented to MCP clients
+#[tauri::command]
+pub async fn set_server_identity(
+    mcp_core: State<'_, MCPCore>,
+    overrides: ServerIdentityOverrides,
+) -> Result<ServerIdentity, String> {
+    mcp_core.set_server_identity_overrides(overrides).await
+}
+
+/// Whether the tool list is saved to serve it at the next start
+#[tauri::command]
+pub async fn get_tools_snapshot_enabled(mcp_core: State<'_, MCPCore>) -> Result<bool, String> {
+    Ok(mcp_core.is_tools_snapshot_enabled().await)
+}
+
+/// Save the tool list to serve it at the next start or not, disabling removes the file
+#[tauri::command]
+pub async fn set_tools_snapshot_enabled(
+    mcp_core: State<'_, MCPCore>,
+    enabled: bool,
+) -> Result<(), String> {
+    mcp_core.set_tools_snapshot_enabled(enabled).await
 }
 
 // Check if Claude is installed
@@ -144,13 +834,14 @@ pub async fn get_generic_config(mcp_core: State<'_, MCPCore>) -> Result<String,
     mcp_core.get_generic_config()
 }
 
-/// Import a server from a GitHub repository URL
+/// Import a server from a GitHub repository URL, started unless `enabled` is false
 #[tauri::command]
 pub async fn import_server_from_url(
     mcp_core: State<'_, MCPCore>,
     url: String,
+    enabled: Option<bool>,
 ) -> Result<ServerRegistrationResponse, String> {
-    mcp_core.import_server_from_url(url).await
+    mcp_core.import_server_from_url(url, enabled).await
 }
 
 #[tauri::command]
@@ -1,8 +1,25 @@
 use crate::features::mcp_proxy::{
-    check_database_exists_command, clear_database_command, discover_tools, execute_proxy_tool,
-    get_tools_visibility_state, import_server_from_url, list_all_server_tools, list_servers,
-    register_server, restart_server_command, set_tools_hidden, uninstall_server,
-    update_server_config, update_server_status,
+    apply_server_config, cancel_operation, check_database_exists_command, cleanup_orphans,
+    clear_database_command, create_schedule, create_webhook, delete_registry_source,
+    delete_schedule, delete_webhook, describe_tool, diagnose_server, discover_tools,
+    execute_proxy_tool, export_diagnostics, export_server_diagnosis, get_action_policies,
+    get_audit_log, get_capabilities, get_dashboard_summary, get_database_state,
+    get_description_options, get_health_check_settings, get_max_failed_starts,
+    get_migration_report, get_orphan_cleanup_report, get_read_only, get_schedule_runs,
+    get_server_config_history, get_server_history, get_server_identity, get_server_info,
+    get_server_logs, get_shutdown_grace, get_startup_timeout, get_tool_hooks, get_tools_budget,
+    get_tools_footprint, get_tools_snapshot_enabled, get_tools_visibility_state,
+    get_undo_retention, get_webhook_dead_letters, import_server_from_url, list_all_server_tools,
+    list_operations, list_pending_approvals, list_registry_sources, list_schedules, list_servers,
+    list_undoable_changes, list_webhooks, migrate_server_paths, preview_diagnostics,
+    register_server, resolve_approval, restart_all_servers, restart_server_command,
+    restart_servers, rollback_server_config, run_selftest, save_registry_source, set_action_policy,
+    set_description_options, set_health_check_settings, set_max_failed_starts, set_read_only,
+    set_server_identity, set_servers_enabled, set_share_instructions, set_shutdown_grace,
+    set_startup_timeout, set_tool_annotations, set_tool_filter, set_tool_hooks,
+    set_tool_rate_limit, set_tools_budget, set_tools_hidden, set_tools_snapshot_enabled,
+    set_undo_retention, start_server, stop_server, test_webhook, undo_change, uninstall_server,
+    update_schedule, update_server_config, update_server_status, update_webhook,
 };
 use commands::{get_app_identifier, get_mcp_proxy_server_binary_path};
 use features::mcp_proxy::{
@@ -11,7 +28,7 @@ use features::mcp_proxy::{
 };
 use log::{error, info};
 use mcp_core::core::mcp_core::MCPCore;
-use mcp_core_utils::{init_mcp_core, uninit_mcp_core};
+use mcp_core_utils::{forward_core_events, init_mcp_core, uninit_mcp_core};
 use tauri::{Emitter, Manager, RunEvent};
 use tray::create_tray;
 use updater::{check_for_updates, check_for_updates_command};
@@ -73,6 +90,7 @@ mod commands {
 fn init_services(app_handle: tauri::AppHandle) {
     tokio::spawn(async move {
         let mcp_core = app_handle.state::<MCPCore>();
+        forward_core_events(app_handle.clone(), &mcp_core);
         let result = mcp_core.init().await;
         if let Err(e) = result {
             error!("Failed to initialize MCP services: {:?}", e);
@@ -172,14 +190,88 @@ pub async fn run() {
             commands::check_initialization_complete,
             register_server,
             list_servers,
+            get_dashboard_summary,
+            get_tools_footprint,
+            get_tools_budget,
+            set_tools_budget,
             list_all_server_tools,
+            describe_tool,
+            set_tool_rate_limit,
+            set_tool_annotations,
             discover_tools,
             execute_proxy_tool,
             update_server_status,
             update_server_config,
+            apply_server_config,
+            get_server_config_history,
+            rollback_server_config,
+            list_undoable_changes,
+            undo_change,
+            get_undo_retention,
+            set_undo_retention,
+            diagnose_server,
+            export_server_diagnosis,
+            get_server_info,
+            set_share_instructions,
             restart_server_command,
+            start_server,
+            stop_server,
+            set_servers_enabled,
+            restart_servers,
+            restart_all_servers,
+            list_operations,
+            cancel_operation,
+            get_orphan_cleanup_report,
+            cleanup_orphans,
+            list_pending_approvals,
+            resolve_approval,
+            get_action_policies,
+            set_action_policy,
+            get_audit_log,
+            get_tool_hooks,
+            set_tool_hooks,
+            list_schedules,
+            create_schedule,
+            update_schedule,
+            delete_schedule,
+            get_schedule_runs,
+            list_webhooks,
+            create_webhook,
+            update_webhook,
+            delete_webhook,
+            test_webhook,
+            get_webhook_dead_letters,
+            get_read_only,
+            set_read_only,
+            list_registry_sources,
+            save_registry_source,
+            delete_registry_source,
+            preview_diagnostics,
+            export_diagnostics,
+            run_selftest,
+            get_migration_report,
+            migrate_server_paths,
+            get_description_options,
+            set_description_options,
+            get_server_identity,
+            set_server_identity,
+            get_tools_snapshot_enabled,
+            set_tools_snapshot_enabled,
             uninstall_server,
             check_database_exists_command,
+            get_database_state,
+            get_capabilities,
+            set_tool_filter,
+            get_server_logs,
+            get_server_history,
+            get_health_check_settings,
+            set_health_check_settings,
+            get_startup_timeout,
+            set_startup_timeout,
+            get_shutdown_grace,
+            set_shutdown_grace,
+            get_max_failed_starts,
+            set_max_failed_starts,
             clear_database_command,
             check_claude_installed,
             check_cursor_installed,
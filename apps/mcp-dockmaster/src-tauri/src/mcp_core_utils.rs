@@ -3,9 +3,11 @@ use std::{fs, path::PathBuf};
 use log::{error, info};
 use mcp_core::{
     core::{mcp_core::MCPCore, mcp_core_proxy_ext::McpCoreProxyExt},
+    mcp_state::shutdown::ShutdownOutcome,
     utils::process::kill_all_processes_by_name,
 };
-use tauri::{utils::platform, Manager};
+use tauri::{utils::platform, Emitter, Manager};
+use tokio::sync::broadcast::error::RecvError;
 
 pub struct MCPCoreOptions {
     #[allow(dead_code)]
@@ -137,6 +139,26 @@ pub async fn init_mcp_core(app_handle: &tauri::AppHandle) -> Result<(), String>
     Ok(())
 }
 
+/// Forward core events (install progress, ...) to the frontend as `mcp-core-event`
+pub fn forward_core_events(app_handle: tauri::AppHandle, mcp_core: &MCPCore) {
+    let mut receiver = mcp_core.event_bus.subscribe();
+    tokio::spawn(async move {
+        loop {
+            match receiver.recv().await {
+                Ok(envelope) => {
+                    if let Err(e) = app_handle.emit("mcp-core-event", &envelope) {
+                        error!("failed to emit core event: {}", e);
+                    }
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    error!("frontend missed {} core events", skipped);
+                }
+                Err(RecvError::Closed) => break,
+            }
+        }
+    });
+}
+
 pub async fn uninit_mcp_core(app_handle: &tauri::AppHandle) {
     info!("uninit_mcp_core, getting handle");
     let app_handle_clone = app_handle.clone();
@@ -146,12 +168,21 @@ pub async fn uninit_mcp_core(app_handle: &tauri::AppHandle) {
     // Kill all MCP Server processes
     if let Some(mcp_core) = mcp_core {
         info!("killing all MCP processes");
-        let result = mcp_core.kill_all_processes().await;
-        if let Err(e) = result {
-            error!("failed to kill all MCP processes: {}", e);
-        } else {
-            info!("killing all MCP processes done");
+        match mcp_core.kill_all_processes().await {
+            Ok(outcomes) => {
+                let forced = outcomes
+                    .values()
+                    .filter(|outcome| **outcome == ShutdownOutcome::Forced)
+                    .count();
+                info!(
+                    "killing all MCP processes done, {} stopped gracefully, {} killed",
+                    outcomes.len() - forced,
+                    forced
+                );
+            }
+            Err(e) => error!("failed to kill all MCP processes: {}", e),
         }
+        mcp_core.release_instance_lock();
     }
 
     /*
@@ -1,3 +1,5 @@
+use log::{error, info};
+use mcp_core::core::{mcp_core::MCPCore, mcp_core_bulk_ext::McpCoreBulkExt};
 use tauri::{
     menu::{MenuBuilder, MenuItemBuilder},
     tray::{MouseButton, TrayIconBuilder, TrayIconEvent},
@@ -15,11 +17,14 @@ pub fn create_tray(app: &tauri::AppHandle) -> tauri::Result<()> {
     let show_menu_item = MenuItemBuilder::with_id("show", "Show").build(app)?;
     let check_for_updates_menu_item =
         MenuItemBuilder::with_id("check_for_updates", "Check for Updates").build(app)?;
+    let restart_servers_menu_item =
+        MenuItemBuilder::with_id("restart_servers", "Restart All Servers").build(app)?;
     let menu = MenuBuilder::new(app)
         .items(&[
             &quit_menu_item,
             &show_menu_item,
             &check_for_updates_menu_item,
+            &restart_servers_menu_item,
         ])
         .build()?;
     let is_template = cfg!(target_os = "macos");
@@ -53,6 +58,23 @@ pub fn create_tray(app: &tauri::AppHandle) -> tauri::Result<()> {
                     std::process::exit(0);
                 });
             }
+            "restart_servers" => {
+                let app_handle = tray.app_handle().clone();
+                tauri::async_runtime::spawn(async move {
+                    let Some(mcp_core) = app_handle.try_state::<MCPCore>() else {
+                        error!("Cannot restart servers, the core is not initialized");
+                        return;
+                    };
+                    match mcp_core.restart_servers(None).await {
+                        Ok(response) => info!(
+                            "Restarted servers from the tray: {} of {} succeeded",
+                            response.results.iter().filter(|r| r.success).count(),
+                            response.results.len()
+                        ),
+                        Err(e) => error!("Failed to restart servers from the tray: {}", e),
+                    }
+                });
+            }
             "check_for_updates" => {
                 let app_handle_clone = tray.app_handle().clone();
                 tauri::async_runtime::spawn(async move {
@@ -1,4 +1,7 @@
 use log::{error, info};
+use mcp_core::core::mcp_core::MCPCore;
+use mcp_core::events::{AppUpdate, DockmasterEvent};
+use tauri::Manager;
 use tauri_plugin_dialog::{DialogExt, MessageDialogButtons};
 use tauri_plugin_updater::UpdaterExt;
 
@@ -26,6 +29,14 @@ pub async fn check_for_updates(
         None
     }) {
         info!("update available: {}", update.version);
+        // Lets webhooks announce the update
+        if let Some(mcp_core) = app_handle.try_state::<MCPCore>() {
+            mcp_core
+                .event_bus
+                .publish(DockmasterEvent::UpdateAvailable(AppUpdate {
+                    version: update.version.clone(),
+                }));
+        }
         let app_handle_clone = app_handle.clone();
         let dialog = app_handle
             .dialog()
@@ -33,6 +33,7 @@ import {
 } from "./components/icons";
 import { MessageSquare } from "lucide-react";
 import { Toaster } from "./components/ui/sonner";
+import { ApprovalDialog } from "./components/approval-dialog";
 import { cn } from "./lib/utils";
 import { TooltipProvider } from "./components/ui/tooltip";
 import { useEffect, useState } from "react";
@@ -123,6 +124,7 @@ const AppRoutes = () => {
     <QueryClientProvider client={queryClient}>
       <TooltipProvider delayDuration={0}>
         <InitMcpOverlay>
+          <ApprovalDialog />
           <SidebarProvider>
             <AppSidebar />
             <SidebarInset>
@@ -1,5 +1,5 @@
 import React, { useState, useEffect } from "react";
-import MCPClient, { RuntimeServer, ServerToolInfo, RuntimeEnvConfig } from "../lib/mcpClient";
+import MCPClient, { BulkServerResult, RuntimeServer, ServerToolInfo, RuntimeEnvConfig } from "../lib/mcpClient";
 import { 
   dispatchServerStatusChanged, 
   dispatchServerUninstalled, 
@@ -26,6 +26,10 @@ import {
 } from "./ui/dialog";
 import { Label } from "./ui/label";
 import { Input } from "./ui/input";
+import { ActionPolicies } from "./action-policies";
+import { DiagnoseServer } from "./diagnose-server";
+import { supports } from "@/lib/capabilities";
+import { useAppStore } from "@/store/app";
 
 // Add a simple notification component
 interface NotificationProps {
@@ -81,6 +85,7 @@ const getColorTagStyle = (color: string) => {
 };
 
 const InstalledServers: React.FC = () => {
+  const capabilities = useAppStore((state) => state.capabilities);
   const [servers, setServers] = useState<RuntimeServer[]>([]);
   const [serverTools, setServerTools] = useState<ServerToolInfo[]>([]);
   const [loading, setLoading] = useState(true);
@@ -386,8 +391,8 @@ const InstalledServers: React.FC = () => {
             const serverTools = await MCPClient.listAllServerTools();
             // Only update tools for this specific server
             setServerTools(prev => {
-              const otherTools = prev.filter(tool => tool.server_id !== id);
-              const newTools = serverTools.filter(tool => tool.server_id === id);
+              const otherTools = prev.filter(tool => tool.serverId !== id);
+              const newTools = serverTools.filter(tool => tool.serverId === id);
               return [...otherTools, ...newTools];
             });
           }
@@ -490,6 +495,21 @@ const InstalledServers: React.FC = () => {
     dispatchServerColorTagsChanged(serverId);
   };
   
+  // Report the outcome of a bulk operation, listing the servers that failed
+  const notifyBulkResults = (
+    results: BulkServerResult[],
+    summary: (succeeded: number) => string,
+  ) => {
+    const failed = results.filter(result => !result.success);
+    const succeeded = results.length - failed.length;
+    if (succeeded > 0) {
+      addNotification(summary(succeeded), "success");
+    }
+    for (const result of failed) {
+      addNotification(`${result.server_id}: ${result.message}`, "error");
+    }
+  };
+
   // Function to update server enabled states based on color tag
   const updateServersByColorTag = async (color: string | null) => {
     // Set loading state
@@ -504,13 +524,14 @@ const InstalledServers: React.FC = () => {
         // Track servers that need to be enabled
         const serversToUpdate = servers.filter(server => !server.enabled);
         
-        // Enable all servers that are currently disabled
-        for (const server of serversToUpdate) {
-          await toggleToolStatus(server.id);
-        }
-        
+        // Enable all servers that are currently disabled in one call
         if (serversToUpdate.length > 0) {
-          addNotification(`Enabled ${serversToUpdate.length} servers`, "success");
+          const response = await MCPClient.setServersEnabled(
+            serversToUpdate.map(server => server.id),
+            true,
+          );
+          await loadData();
+          notifyBulkResults(response.results, count => `Enabled ${count} servers`);
         }
         return;
       }
@@ -534,23 +555,28 @@ const InstalledServers: React.FC = () => {
         }
       }
       
-      // Enable servers with the selected color tag
-      for (const server of serversToEnable) {
-        await toggleToolStatus(server.id);
+      // Enable servers with the selected color tag and disable the others, each
+      // group in one call
+      const results = [];
+      if (serversToEnable.length > 0) {
+        const response = await MCPClient.setServersEnabled(
+          serversToEnable.map(server => server.id),
+          true,
+        );
+        results.push(...response.results);
       }
-      
-      // Disable servers without the selected color tag
-      for (const server of serversToDisable) {
-        await toggleToolStatus(server.id);
+      if (serversToDisable.length > 0) {
+        const response = await MCPClient.setServersEnabled(
+          serversToDisable.map(server => server.id),
+          false,
+        );
+        results.push(...response.results);
       }
       
       // Show completion notification
-      const totalUpdated = serversToEnable.length + serversToDisable.length;
-      if (totalUpdated > 0) {
-        addNotification(
-          `Updated ${totalUpdated} servers for ${color} group`,
-          "success"
-        );
+      if (results.length > 0) {
+        await loadData();
+        notifyBulkResults(results, count => `Updated ${count} servers for ${color} group`);
       }
     } catch (error) {
       console.error("Error updating servers by color tag:", error);
@@ -788,7 +814,7 @@ const InstalledServers: React.FC = () => {
     if (!infoPopupVisible || !currentInfoServer) return null;
     
     // Filter tools for this server
-    const toolsForServer = serverTools.filter((t) => t.server_id === currentInfoServer.id);
+    const toolsForServer = serverTools.filter((t) => t.serverId === currentInfoServer.id);
     
     return (
       <Dialog open={infoPopupVisible} onOpenChange={closeInfoPopup}>
@@ -853,6 +879,25 @@ const InstalledServers: React.FC = () => {
                     </div>
                   </div>
                 )}
+                {currentInfoServer.provenance && (
+                  <div className="grid grid-cols-4 items-start gap-4">
+                    <Label className="text-right text-xs pt-1">Installed From</Label>
+                    <div className="col-span-3 text-sm space-y-1">
+                      <div>
+                        {currentInfoServer.provenance.source}
+                        {currentInfoServer.provenance.source_id && ` (${currentInfoServer.provenance.source_id})`}
+                      </div>
+                      {currentInfoServer.provenance.client && (
+                        <div className="text-muted-foreground text-xs">
+                          Requested by {currentInfoServer.provenance.client}
+                        </div>
+                      )}
+                      <div className="text-muted-foreground text-xs">
+                        {new Date(currentInfoServer.provenance.installed_at).toLocaleString()} with version {currentInfoServer.provenance.app_version}
+                      </div>
+                    </div>
+                  </div>
+                )}
                 {currentInfoServer.entry_point && (
                   <div className="grid grid-cols-4 items-start gap-4">
                     <Label className="text-right text-xs pt-1">Entry Point</Label>
@@ -1082,8 +1127,9 @@ const InstalledServers: React.FC = () => {
     return Object.entries(server.configuration.env).some((entry) => {
       const value = entry[1];
       // If the env var is required and has no default value, it needs attention
+      // Defaults may be numbers or booleans, so `false` and `0` count as set
       return value.required && 
-        (!value.default || value.default.trim() === '');
+        (value.default === undefined || value.default === null || String(value.default).trim() === '');
     });
   };
   
@@ -1216,6 +1262,7 @@ const InstalledServers: React.FC = () => {
         <p className="text-muted-foreground text-sm">
           Manage your installed AI applications and MCP tools.
         </p>
+        <ActionPolicies />
       </div>
 
       {/* Add color filters */}
@@ -1267,6 +1314,10 @@ const InstalledServers: React.FC = () => {
                         Server Information
                       </TooltipContent>
                     </Tooltip>
+
+                    {supports(capabilities, "server/diagnose") && (
+                      <DiagnoseServer serverId={server.id} />
+                    )}
                     
                     {server.configuration &&
                       server.configuration.env &&
@@ -1332,6 +1383,52 @@ const InstalledServers: React.FC = () => {
                       </TooltipContent>
                     </Tooltip>
                   )}
+                  {server.llm_initiated && (
+                    <Tooltip>
+                      <TooltipTrigger>
+                        <Badge 
+                          variant="outline" 
+                          className="bg-violet-100 text-violet-800 border-violet-300 ml-2"
+                        >
+                          Installed by AI
+                        </Badge>
+                      </TooltipTrigger>
+                      <TooltipContent>
+                        A model installed this server through the built-in tools{server.provenance?.client ? ` (${server.provenance.client})` : ""}. Review it before relying on it.
+                      </TooltipContent>
+                    </Tooltip>
+                  )}
+                  {server.call_mode && server.call_mode.mode !== "live" && (
+                    <Tooltip>
+                      <TooltipTrigger>
+                        <Badge 
+                          variant="outline" 
+                          className="bg-red-100 text-red-800 border-red-300 ml-2"
+                        >
+                          {server.call_mode.mode === "replay" ? "Replaying" : "Recording"}
+                        </Badge>
+                      </TooltipTrigger>
+                      <TooltipContent>
+                        {server.call_mode.mode === "replay"
+                          ? `Tool calls are answered from recordings and never reach this server${server.call_mode.on_miss === "pass_through" ? ", except calls that were never recorded" : ""}.`
+                          : "The results of tool calls to this server are recorded for replay."}
+                      </TooltipContent>
+                    </Tooltip>
+                  )}
+                  {server.server_info && (
+                    <Tooltip>
+                      <TooltipTrigger>
+                        <Badge variant="outline" className="ml-2">
+                          {server.server_info.name} {server.server_info.version}
+                        </Badge>
+                      </TooltipTrigger>
+                      <TooltipContent className="max-w-md whitespace-pre-wrap">
+                        {server.server_info.instructions
+                          ? `${server.server_info.instructions}${server.configuration?.share_instructions ? "\n\nShared with MCP clients." : ""}`
+                          : "The server gives no instructions."}
+                      </TooltipContent>
+                    </Tooltip>
+                  )}
                 </div>
 
                 <div className="server-status-indicator">
@@ -1344,6 +1441,8 @@ const InstalledServers: React.FC = () => {
                             ? "running" 
                             : server.status === 'starting' 
                               ? "starting" 
+                              : server.status === 'needs_configuration'
+                                ? "error"
                               : server.status.startsWith("Error:") 
                                 ? "error" 
                                 : "stopped"
@@ -1358,6 +1457,8 @@ const InstalledServers: React.FC = () => {
                             ? "Stopped" 
                             : server.status === 'starting' 
                               ? "Starting..." 
+                              : server.status === 'needs_configuration'
+                                ? "Needs configuration"
                               : server.status.startsWith("Error:") 
                                 ? server.status 
                                 : "Stopped"}
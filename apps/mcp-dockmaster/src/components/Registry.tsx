@@ -337,35 +337,11 @@ const Registry: React.FC = () => {
     try {
       const entryPoint = getDefaultEntryPoint(server.name);
 
-      let authentication = null;
-      if (server.config && server.config.env) {
-        // Create a copy of the original env configuration
-        const envConfig = { ...server.config.env };
-        
-        // Update with custom values if provided
-        if (customEnvVars) {
-          Object.entries(customEnvVars).forEach(([key, value]) => {
-            if (typeof envConfig[key] === 'object') {
-              // Update the default value while preserving other properties
-              envConfig[key] = {
-                ...envConfig[key],
-                default: value
-              };
-            } else {
-              envConfig[key] = value;
-            }
-          });
-        }
-        
-        authentication = { env: envConfig };
-      }
-
       console.log(
         "Registering server:",
         JSON.stringify(server, null, 2),
         server.runtime,
         entryPoint,
-        authentication,
       );
 
       // Create a modified configuration with updated ENV values
@@ -380,11 +356,17 @@ const Registry: React.FC = () => {
         
         // Update the env values with user input
         Object.entries(customEnvVars).forEach(([key, value]) => {
-          if (modifiedConfig.env && typeof modifiedConfig.env[key] === 'object') {
+          if (!modifiedConfig.env) {
+            return;
+          }
+          if (typeof modifiedConfig.env[key] === 'object') {
+            // Update the default value while preserving other properties
             modifiedConfig.env[key] = {
               ...modifiedConfig.env[key],
               default: value
             };
+          } else {
+            modifiedConfig.env[key] = value;
           }
         });
       }
@@ -396,7 +378,7 @@ const Registry: React.FC = () => {
         tools_type: server.runtime,
         configuration: modifiedConfig,
         distribution: server.distribution,
-        authentication: authentication,
+        provenance: { source: 'registry', source_id: server.id },
       });
 
       if (response.success) {
@@ -0,0 +1,83 @@
+import { useEffect, useState } from "react";
+import { toast } from "sonner";
+import { Button } from "./ui/button";
+import {
+  DropdownMenu,
+  DropdownMenuContent,
+  DropdownMenuRadioGroup,
+  DropdownMenuRadioItem,
+  DropdownMenuTrigger,
+} from "./ui/dropdown-menu";
+import MCPClient, { ActionPolicy, GatedAction } from "../lib/mcpClient";
+
+const ACTIONS: { action: GatedAction; label: string }[] = [
+  { action: "register", label: "Install servers" },
+  { action: "uninstall", label: "Uninstall servers" },
+  { action: "configure", label: "Configure servers" },
+  { action: "destructive_tool", label: "Call destructive tools" },
+];
+
+const POLICY_LABELS: Record<ActionPolicy, string> = {
+  allow: "Allow",
+  confirm: "Ask me",
+  deny: "Deny",
+};
+
+// What AI assistants may do through the built-in Dockmaster tools
+export const ActionPolicies = () => {
+  const [policies, setPolicies] = useState<Partial<
+    Record<GatedAction, ActionPolicy>
+  > | null>(null);
+
+  useEffect(() => {
+    MCPClient.getActionPolicies()
+      .then(setPolicies)
+      .catch((error) => {
+        console.error("Error getting action policies:", error);
+      });
+  }, []);
+
+  const updatePolicy = async (action: GatedAction, policy: ActionPolicy) => {
+    try {
+      await MCPClient.setActionPolicy(action, policy);
+      setPolicies((prev) => ({ ...prev, [action]: policy }));
+    } catch (error) {
+      toast.error("Could not save the permission", {
+        description: String(error),
+      });
+    }
+  };
+
+  if (!policies) return null;
+
+  return (
+    <div className="flex flex-wrap items-center gap-2">
+      <span className="text-muted-foreground text-sm">
+        AI assistants may:
+      </span>
+      {ACTIONS.map(({ action, label }) => (
+        <DropdownMenu key={action}>
+          <DropdownMenuTrigger asChild>
+            <Button variant="outline" size="sm">
+              {label}: {POLICY_LABELS[policies[action] ?? "confirm"]}
+            </Button>
+          </DropdownMenuTrigger>
+          <DropdownMenuContent>
+            <DropdownMenuRadioGroup
+              value={policies[action]}
+              onValueChange={(value) =>
+                updatePolicy(action, value as ActionPolicy)
+              }
+            >
+              {(Object.keys(POLICY_LABELS) as ActionPolicy[]).map((policy) => (
+                <DropdownMenuRadioItem key={policy} value={policy}>
+                  {POLICY_LABELS[policy]}
+                </DropdownMenuRadioItem>
+              ))}
+            </DropdownMenuRadioGroup>
+          </DropdownMenuContent>
+        </DropdownMenu>
+      ))}
+    </div>
+  );
+};
@@ -0,0 +1,93 @@
+import { useEffect, useState } from "react";
+import { listen } from "@tauri-apps/api/event";
+import { toast } from "sonner";
+import {
+  Dialog,
+  DialogContent,
+  DialogDescription,
+  DialogFooter,
+  DialogHeader,
+  DialogTitle,
+} from "./ui/dialog";
+import { Button } from "./ui/button";
+import MCPClient, { ApprovalRequest, CoreEvent } from "../lib/mcpClient";
+
+// Asks the user about actions MCP clients requested under the "confirm" policy
+export const ApprovalDialog = () => {
+  const [pending, setPending] = useState<ApprovalRequest[]>([]);
+  const [resolving, setResolving] = useState(false);
+
+  const loadPending = async () => {
+    try {
+      setPending(await MCPClient.listPendingApprovals());
+    } catch (error) {
+      console.error("Error listing pending approvals:", error);
+    }
+  };
+
+  useEffect(() => {
+    loadPending();
+    const unlisten = listen<CoreEvent>("mcp-core-event", (event) => {
+      const { type } = event.payload.event;
+      if (type === "approval_requested" || type === "approval_resolved") {
+        loadPending();
+      }
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  const current = pending[0];
+
+  const resolve = async (approved: boolean) => {
+    if (!current) return;
+    setResolving(true);
+    try {
+      await MCPClient.resolveApproval(current.id, approved);
+    } catch (error) {
+      toast.error("Could not answer the request", {
+        description: String(error),
+      });
+    } finally {
+      setResolving(false);
+      loadPending();
+    }
+  };
+
+  return (
+    <Dialog open={!!current} onOpenChange={() => {}}>
+      {current && (
+        <DialogContent>
+          <DialogHeader>
+            <DialogTitle>An AI assistant is asking for permission</DialogTitle>
+            <DialogDescription>
+              {current.client ?? "An MCP client"} wants to{" "}
+              {current.action} a server.
+            </DialogDescription>
+          </DialogHeader>
+          <div className="rounded-md bg-muted p-4 text-sm">
+            {current.summary}
+          </div>
+          {pending.length > 1 && (
+            <p className="text-muted-foreground text-xs">
+              {pending.length - 1} more request(s) waiting
+            </p>
+          )}
+          <DialogFooter>
+            <Button
+              variant="outline"
+              disabled={resolving}
+              onClick={() => resolve(false)}
+            >
+              Deny
+            </Button>
+            <Button disabled={resolving} onClick={() => resolve(true)}>
+              Approve
+            </Button>
+          </DialogFooter>
+        </DialogContent>
+      )}
+    </Dialog>
+  );
+};
@@ -0,0 +1,110 @@
+import { useState } from "react";
+import { toast } from "sonner";
+import { Check, Minus, Stethoscope, X } from "lucide-react";
+import {
+  Dialog,
+  DialogContent,
+  DialogDescription,
+  DialogFooter,
+  DialogHeader,
+  DialogTitle,
+} from "./ui/dialog";
+import { Button } from "./ui/button";
+import { Tooltip, TooltipContent, TooltipTrigger } from "./ui/tooltip";
+import MCPClient, { DiagnosisStep, ServerDiagnosis } from "../lib/mcpClient";
+
+const StepIcon = ({ status }: { status: DiagnosisStep["status"] }) => {
+  if (status === "pass") {
+    return <Check className="h-4 w-4 shrink-0 text-emerald-600" />;
+  }
+  if (status === "fail") {
+    return <X className="h-4 w-4 shrink-0 text-red-600" />;
+  }
+  return <Minus className="h-4 w-4 shrink-0 text-slate-400" />;
+};
+
+// Runs the troubleshooting checklist on a server and shows each step
+export const DiagnoseServer = ({ serverId }: { serverId: string }) => {
+  const [diagnosis, setDiagnosis] = useState<ServerDiagnosis | null>(null);
+  const [busy, setBusy] = useState(false);
+
+  const runChecklist = async () => {
+    setBusy(true);
+    try {
+      setDiagnosis(await MCPClient.diagnoseServer(serverId));
+    } catch (error) {
+      toast.error("Could not diagnose the server", {
+        description: String(error),
+      });
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  const exportReport = async () => {
+    if (!diagnosis) return;
+    setBusy(true);
+    try {
+      const result = await MCPClient.exportServerDiagnosis(diagnosis);
+      toast.success("Report exported", { description: result.path });
+    } catch (error) {
+      toast.error("Could not export the report", {
+        description: String(error),
+      });
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  return (
+    <>
+      <Tooltip>
+        <TooltipTrigger>
+          <Button variant="ghost" onClick={runChecklist} disabled={busy}>
+            <Stethoscope className="h-4 w-4" />
+          </Button>
+        </TooltipTrigger>
+        <TooltipContent>Diagnose</TooltipContent>
+      </Tooltip>
+      <Dialog
+        open={diagnosis !== null}
+        onOpenChange={(open) => !open && !busy && setDiagnosis(null)}
+      >
+        <DialogContent>
+          <DialogHeader>
+            <DialogTitle>Diagnose {diagnosis?.server_name}</DialogTitle>
+            <DialogDescription>
+              {diagnosis?.healthy
+                ? "Every check passed."
+                : "Some checks failed, follow the hints below."}
+            </DialogDescription>
+          </DialogHeader>
+          <ul className="max-h-96 space-y-3 overflow-y-auto text-sm">
+            {diagnosis?.steps.map((step) => (
+              <li key={step.id} className="flex gap-2">
+                <StepIcon status={step.status} />
+                <div className="min-w-0">
+                  <div className="font-medium">{step.title}</div>
+                  <div className="whitespace-pre-wrap break-words text-xs text-muted-foreground">
+                    {step.details}
+                  </div>
+                  {step.hint && (
+                    <div className="text-xs text-amber-700">{step.hint}</div>
+                  )}
+                </div>
+              </li>
+            ))}
+          </ul>
+          <DialogFooter>
+            <Button variant="outline" onClick={exportReport} disabled={busy}>
+              Export report
+            </Button>
+            <Button onClick={runChecklist} disabled={busy}>
+              Run again
+            </Button>
+          </DialogFooter>
+        </DialogContent>
+      </Dialog>
+    </>
+  );
+};
@@ -0,0 +1,94 @@
+import { useState } from "react";
+import { toast } from "sonner";
+import {
+  Dialog,
+  DialogContent,
+  DialogDescription,
+  DialogFooter,
+  DialogHeader,
+  DialogTitle,
+} from "./ui/dialog";
+import { Button } from "./ui/button";
+import MCPClient, { DiagnosticsPreview } from "../lib/mcpClient";
+
+const formatSize = (bytes: number) =>
+  bytes < 1024 ? `${bytes} B` : `${(bytes / 1024).toFixed(1)} KB`;
+
+// Shows what a diagnostics bundle contains, then writes it on confirmation
+export const ExportDiagnostics = () => {
+  const [preview, setPreview] = useState<DiagnosticsPreview | null>(null);
+  const [busy, setBusy] = useState(false);
+
+  const openPreview = async () => {
+    setBusy(true);
+    try {
+      setPreview(await MCPClient.previewDiagnostics());
+    } catch (error) {
+      toast.error("Could not collect diagnostics", {
+        description: String(error),
+      });
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  const exportBundle = async () => {
+    setBusy(true);
+    try {
+      const result = await MCPClient.exportDiagnostics();
+      setPreview(null);
+      toast.success("Diagnostics exported", { description: result.path });
+    } catch (error) {
+      toast.error("Could not export diagnostics", {
+        description: String(error),
+      });
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  return (
+    <>
+      <Button variant="outline" onClick={openPreview} disabled={busy}>
+        Export diagnostics
+      </Button>
+      <Dialog
+        open={preview !== null}
+        onOpenChange={(open) => !open && !busy && setPreview(null)}
+      >
+        <DialogContent>
+          <DialogHeader>
+            <DialogTitle>Export diagnostics</DialogTitle>
+            <DialogDescription>
+              The bundle contains the files below.{" "}
+              {preview?.redacted_values ?? 0} secret values were removed from
+              them.
+            </DialogDescription>
+          </DialogHeader>
+          <ul className="max-h-64 overflow-y-auto font-mono text-xs">
+            {preview?.files.map((file) => (
+              <li key={file.path} className="flex justify-between gap-4">
+                <span>{file.path}</span>
+                <span className="text-muted-foreground">
+                  {formatSize(file.size)}
+                </span>
+              </li>
+            ))}
+          </ul>
+          <DialogFooter>
+            <Button
+              variant="outline"
+              onClick={() => setPreview(null)}
+              disabled={busy}
+            >
+              Cancel
+            </Button>
+            <Button onClick={exportBundle} disabled={busy}>
+              Export
+            </Button>
+          </DialogFooter>
+        </DialogContent>
+      </Dialog>
+    </>
+  );
+};
@@ -2,11 +2,13 @@ import { useEffect, useState } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
 import { dispatchServerStatusChanged } from "../lib/events";
+import MCPClient from "../lib/mcpClient";
 import { useAppStore } from "@/store/app";
 import { Progress } from "./ui/progress";
 import { TermsConsentDialog } from "./terms-consent-dialog";
 import { getUserConsent } from "../lib/localStorage";
 import { toast } from "sonner";
+import { missingMethods } from "../lib/capabilities";
 
 const INITIALIZATION_TOAST_ID = "MCP-INITIALIZATION-TOAST";
 
@@ -26,6 +28,7 @@ const InitMCPOverlay: React.FC<LoadingOverlayProps> = ({
   const setAppState = useAppStore((state) => state.setAppState);
   const userConsented = useAppStore((state) => state.userConsented);
   const setUserConsented = useAppStore((state) => state.setUserConsented);
+  const setCapabilities = useAppStore((state) => state.setCapabilities);
 
   useEffect(() => {
     setAppState("pending");
@@ -89,6 +92,68 @@ const InitMCPOverlay: React.FC<LoadingOverlayProps> = ({
     };
   }, [progress]);
 
+  // Explain why ports may have been busy after a crash
+  useEffect(() => {
+    if (appState !== "ready") return;
+    MCPClient.getOrphanCleanupReport()
+      .then((report) => {
+        if (
+          report.processes_terminated === 0 &&
+          report.containers_removed === 0
+        ) {
+          return;
+        }
+        toast.info("Cleaned up leftover server processes", {
+          description: `${report.processes_terminated} process(es) and ${report.containers_removed} container(s) from a previous session were stopped: ${report.servers.join(", ")}`,
+          closeButton: true,
+        });
+      })
+      .catch((error) => {
+        console.error("Error getting orphan cleanup report:", error);
+      });
+  }, [appState]);
+
+  // Hide what this version of mcp-core can't do, and say so
+  useEffect(() => {
+    if (appState !== "ready") return;
+    MCPClient.getCapabilities()
+      .then((capabilities) => {
+        setCapabilities(capabilities);
+        const missing = missingMethods(capabilities);
+        if (missing.length > 0) {
+          toast.warning(`MCP Dockmaster core ${capabilities.version} doesn't match this app`, {
+            description: `Missing or different: ${missing.join(", ")}`,
+            closeButton: true,
+          });
+        }
+      })
+      .catch((error) => {
+        console.error("Error getting the core capabilities:", error);
+      });
+  }, [appState]);
+
+  // Tell why installed servers may be missing
+  useEffect(() => {
+    if (appState !== "ready") return;
+    MCPClient.getDatabaseState()
+      .then((state) => {
+        if (state.status === "unreadable") {
+          toast.error("The database can't be read", {
+            description: state.error?.message,
+            closeButton: true,
+          });
+        } else if (state.status === "needs_migration") {
+          toast.info("The database needs a migration", {
+            description: `${state.legacy_server_count} server(s) of a previous version and ${state.pending_migrations} schema change(s) are waiting to be applied`,
+            closeButton: true,
+          });
+        }
+      })
+      .catch((error) => {
+        console.error("Error getting the database state:", error);
+      });
+  }, [appState]);
+
   // Check if user has consented when app is ready
   useEffect(() => {
     if (appState === "ready") {
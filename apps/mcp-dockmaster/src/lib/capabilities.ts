@@ -0,0 +1,40 @@
+import { Capabilities } from "./mcpClient";
+
+/**
+ * JSON-RPC methods of mcp-core the UI relies on, with the schema version it reads
+ */
+export const EXPECTED_METHODS: Record<string, number> = {
+  "registry/list": 1,
+  "registry/sources": 1,
+  "server/config": 1,
+  "server/config_history": 1,
+  "server/diagnose": 1,
+  "server/status": 1,
+  "servers/bulk_update": 1,
+  "servers/restart_all": 1,
+  "diagnostics/export": 1,
+  "system/selftest": 1,
+  "system/migration_report": 1,
+  "database/state": 1,
+  "schedules/list": 1,
+  "webhooks/list": 1,
+  "history/undoable": 1,
+};
+
+/**
+ * Whether mcp-core has a method with the schema version the UI expects
+ *
+ * Everything is supported until the capabilities are known, as with older cores.
+ */
+export const supports = (capabilities: Capabilities | null, method: string): boolean => {
+  if (!capabilities) return true;
+  const expected = EXPECTED_METHODS[method];
+  const found = capabilities.methods.find((m) => m.name === method);
+  return !!found && (expected === undefined || found.schema_version === expected);
+};
+
+/**
+ * Expected methods mcp-core lacks or serves with another schema version
+ */
+export const missingMethods = (capabilities: Capabilities): string[] =>
+  Object.keys(EXPECTED_METHODS).filter((method) => !supports(capabilities, method));
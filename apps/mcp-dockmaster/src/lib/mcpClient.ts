@@ -39,47 +39,195 @@ export interface RegistryServer {
   }[];
   weight: number;
   featured: boolean;
+  // Id of the registry source listing the server, "default" for the default registry
+  source?: string;
 }
 
 export interface ServerRegistrationRequest {
   server_id: string;
   server_name: string;
   description: string;
-  authentication?: any;
   tools_type: string;  // "nodejs", "python", "docker"
   configuration?: {
     command: string;
     args: string[];
-    env: Record<string, string>;
+    env: Record<string, EnvValue | RuntimeEnvConfig>;
+    priority?: ProcessPriority;
+    cpu_limit?: number;
+    max_memory_mb?: number;
+    start_mode?: StartMode;
+    cwd?: string;
+    sandbox?: SandboxOptions;
+    inherit_env?: boolean;
+    env_passthrough?: string[];
+    truncate_descriptions?: boolean;
+    strict_output_schema?: boolean;
+    share_instructions?: boolean;
   };
   distribution?: {
     type: string;
     package: string;
   };
+  provenance?: Pick<ServerProvenance, 'source' | 'source_id'>;
+  // Start the server once registered, true unless set to false to configure it first
+  enabled?: boolean;
 }
 
 // new code needs adjusting
+export type EnvValue = string | number | boolean;
+
 export interface RuntimeEnvConfig {
   default: string;
   description: string;
   required: boolean;
+  // How booleans are passed to the server process: "true"/"false" or "1"/"0"
+  bool_format?: 'literal' | 'numeric';
 }
 
+// JSON Schema of a tool argument, other keywords (items, enum, oneOf, $ref...) are
+// passed through as the server wrote them
 export interface InputSchemaProperty {
-  description: string;
-  type: string;
+  description?: string;
+  type?: string | string[];
+  default?: unknown;
+  [keyword: string]: unknown;
 }
 
 export interface InputSchema {
-  properties: Record<string, InputSchemaProperty>;
-  required: string[];
-  type: string;
+  properties?: Record<string, InputSchemaProperty>;
+  required?: string[];
+  type?: string;
+  $defs?: Record<string, unknown>;
+  [keyword: string]: unknown;
 }
 
+// Scheduling priority of a server process, mapped to nice values, Windows priority
+// classes or docker CPU shares
+export type ProcessPriority = 'low' | 'normal' | 'high';
+
 export interface ToolConfiguration {
   command?: string;
   args?: string[];
   env?: Record<string, RuntimeEnvConfig>;
+  priority?: ProcessPriority;
+  // Maximum number of CPU cores the server may use
+  cpu_limit?: number;
+  // Memory the server may use, in megabytes
+  max_memory_mb?: number;
+  // Runs the server in restricted mode when set
+  sandbox?: SandboxOptions;
+  // Inherit the whole Dockmaster environment, for servers that break without it
+  inherit_env?: boolean;
+  // Dockmaster environment variables passed to the server besides the baseline
+  env_passthrough?: string[];
+  // Truncate long tool descriptions in the tool list, defaults to true
+  truncate_descriptions?: boolean;
+  // Fail tool calls whose result does not match the tool output schema
+  strict_output_schema?: boolean;
+  // Pass the instructions the server gives on to MCP clients
+  share_instructions?: boolean;
+  // Which tools of the server are listed, at most 500 of them unless set
+  tool_filter?: ToolFilter;
+  // Docker platforms the image is built for, like "linux/amd64"
+  platforms?: string[];
+  // "lazy" servers start on their first tool call
+  start_mode?: StartMode;
+  // Absolute directory the server is started in, relative paths resolve against it
+  cwd?: string;
+  // Use the server even when it doesn't answer initialize properly, and list its
+  // tools even when it declares no tool support
+  lenient_initialize?: boolean;
+}
+
+export type StartMode = "eager" | "lazy";
+
+// Tools of a server listed by name, with glob patterns where * matches any text
+export interface ToolFilter {
+  include?: string[];
+  exclude?: string[];
+  max_tools?: number;
+}
+
+// A line a server wrote to stderr, timestamp in Unix milliseconds
+export interface LogLine {
+  timestamp: number;
+  line: string;
+}
+
+// The buffered stderr of a server, oldest line first
+export interface ServerLogSnapshot {
+  server_id: string;
+  lines: LogLine[];
+  dropped_lines: number;
+  following: boolean;
+}
+
+export type LifecycleKind =
+  | 'started'
+  | 'start_failed'
+  | 'crashed'
+  | 'restarted_by_supervisor'
+  | 'restarted'
+  | 'config_restart'
+  | 'stopped_by_user';
+
+// A transition of a server, at in Unix milliseconds
+export interface LifecycleEvent {
+  server_id: string;
+  kind: LifecycleKind;
+  at: number;
+  exit_code?: number;
+  detail?: string;
+}
+
+// How long a server has been up and how often it restarted in the last hour
+export interface ServerUptime {
+  started_at?: number;
+  uptime_secs?: number;
+  restarts_last_hour: number;
+  // Restarts in the history kept of the server
+  restart_count: number;
+  // Exit code of the last crash
+  last_exit_code?: number;
+  // Why the server last failed to start or crashed
+  last_error?: string;
+  // The last few events, oldest first
+  recent: LifecycleEvent[];
+}
+
+// Outcome of the health checks of a running server, last_health_check in Unix ms
+export interface ServerHealth {
+  last_health_check: number;
+  healthy: boolean;
+  consecutive_failures: number;
+  last_error?: string;
+}
+
+// How often running servers are checked, interval_secs 0 turns the checks off
+export interface HealthCheckSettings {
+  interval_secs: number;
+  unhealthy_after: number;
+  restart_unhealthy: boolean;
+}
+
+export interface ServerHistory {
+  server_id: string;
+  events: LifecycleEvent[];
+  uptime: ServerUptime;
+}
+
+// What discovery left out of the tools of a server
+export interface ToolLimitReport {
+  listed: number;
+  filtered: number;
+  truncated: number;
+  max_tools: number;
+}
+
+export interface SandboxOptions {
+  // Absolute paths the server may write to, besides temporary directories
+  allowed_paths?: string[];
+  network?: boolean;
 }
 
 export interface Distribution {
@@ -97,30 +245,566 @@ export interface ServerDefinition {
   distribution?: Distribution;
 }
 
-export type ServerStatus = 'running' | 'stopped' | 'starting' | string;
+// needs_configuration: not started because required environment variables have no value
+// { faulted: error }: failed to start too many times in a row, restart it with force
+export type ServerStatus = 'running' | 'stopped' | 'starting' | 'needs_configuration' | string;
 
 export interface RuntimeServer extends ServerDefinition {
   id: string;  // Using string instead of ToolId since we don't need the full Rust implementation
   status: ServerStatus;
+  // Whether the user wants the server running, a disabled server never runs
+  desired_running: boolean;
   tool_count: number;
+  provenance?: ServerProvenance;
+  // Set when a model installed the server through the built-in tools
+  llm_initiated: boolean;
+  // Whether tool calls go to the server, are recorded, or are replayed
+  call_mode: ServerCallMode;
+  // What the server said about itself when it last started
+  server_info?: ServerInitInfo;
+  uptime: ServerUptime;
+  // Set once the running server was checked
+  health?: ServerHealth;
   sourceUrl?: string;
   colorTags?: string[]; // Add this line to store color tags
 }
 
+export interface ServerInitInfo {
+  name: string;
+  version: string;
+  protocol_version?: string;
+  // How to use the server, meant for the models
+  instructions?: string;
+  // Milliseconds since the Unix epoch
+  initialized_at: number;
+  // Capabilities the server declared, its tools aren't listed without "tools"
+  capabilities?: Record<string, unknown>;
+}
+
+export type CallMode = 'live' | 'record' | 'replay';
+
+export interface ServerCallMode {
+  mode: CallMode;
+  // What a replayed call without a recording does
+  on_miss: 'error' | 'pass_through';
+}
+
+export type ProvenanceSource = 'registry' | 'url' | 'manual' | 'llm' | 'import' | 'config_file';
+
+export interface ServerProvenance {
+  source: ProvenanceSource;
+  // Registry id or URL the server was installed from
+  source_id?: string;
+  // MCP client whose session asked for the install
+  client?: string;
+  // Milliseconds since the Unix epoch
+  installed_at: number;
+  app_version: string;
+}
+
 export interface ServerToolInfo {
   id: string;
   name: string;
   description: string;
   inputSchema?: InputSchema;
+  // JSON Schema of the structuredContent the tool returns
+  outputSchema?: Record<string, unknown>;
+  serverId: string;
+  proxyId?: string;
+  isActive: boolean;
+  annotations?: ToolAnnotations;
+}
+
+// Hints about a tool's behavior, from its server or set by the user
+export interface ToolAnnotations {
+  title?: string;
+  readOnlyHint?: boolean;
+  destructiveHint?: boolean;
+  idempotentHint?: boolean;
+  openWorldHint?: boolean;
+  [hint: string]: unknown;
+}
+
+// How often a tool was called since Dockmaster started
+export interface ToolUsage {
+  calls: number;
+  errors: number;
+  totalDurationMs: number;
+  // Unix time in seconds
+  lastCalledAt?: number;
+  // Calls refused by the rate limit of the tool
+  rateLimited: number;
+}
+
+// Counts shown on the home screen, computed from memory
+export interface DashboardSummary {
+  servers: number;
+  running: number;
+  starting: number;
+  stopped: number;
+  errored: number;
+  needs_configuration: number;
+  tools: number;
+  tools_hidden: number;
+  tools_disabled: number;
+  calls_last_hour: number;
+  top_tools: { server_id: string; tool_name: string; calls: number }[];
+  servers_in_error: { server_id: string; name: string; error: string }[];
+  // Estimated tokens of the tool list served to clients
+  tools_tokens: number;
+  // Servers to hide to bring the tool list under its budget, largest first
+  servers_over_budget: string[];
+  // Restarts of every server in the last hour
+  restarts_last_hour: number;
+  // Servers restarted in the last hour, most restarted first
+  flapping_servers: { server_id: string; restarts: number; uptime_secs?: number }[];
+}
+
+// Size of the tool list in the context of the models
+export interface ToolsFootprint {
+  bytes: number;
+  tokens: number;
+  // Largest first
+  servers: {
+    server_id: string;
+    tools: number;
+    bytes: number;
+    tokens: number;
+    over_budget: boolean;
+  }[];
+  top_tools: { server_id: string; tool_name: string; bytes: number; tokens: number }[];
+  budget: number | null;
+  over_budget: boolean;
+}
+
+// Limits of a tool's calls, unset limits don't apply
+export interface RateLimit {
+  per_minute?: number;
+  per_hour?: number;
+  max_concurrent?: number;
+}
+
+// A call refused by the rate limit of its tool
+export interface RateLimited {
   server_id: string;
-  proxy_id?: string;
+  tool_name: string;
+  limit: "per_minute" | "per_hour" | "max_concurrent";
+  // Not set when waiting for a running call to finish
+  retry_after_ms?: number;
+  // Milliseconds since the Unix epoch
+  reset_at?: number;
+}
+
+// Everything known about one tool, see MCPClient.describeTool
+export interface ToolDescription {
+  proxyId: string;
+  serverId: string;
+  id: string;
+  name: string;
+  // Never truncated, unlike the tool list
+  description: string;
+  inputSchema?: InputSchema;
+  // JSON Schema of the structuredContent the tool returns
+  outputSchema?: Record<string, unknown>;
+  defaults: Record<string, unknown>;
+  isActive: boolean;
+  serverStatus: ServerStatus;
+  usage: ToolUsage;
+  rateLimit?: RateLimit;
+  annotations?: ToolAnnotations;
 }
 
 export interface ServerRegistrationResponse {
   success: boolean;
   message: string;
   server_id?: string;
-  tool_id?: string;
+  // Status once registered, "stopped" when registered disabled
+  status?: ServerStatus;
+  tool_count?: number;
+  // Milliseconds the server took to start and answer the initialize handshake
+  startup_ms?: number;
+  // Unrecognized request fields and other non-fatal problems
+  warnings?: string[];
+  // Id of the install operation, see CoreEvent
+  operation_id?: string;
+}
+
+export type InstallStage =
+  | "resolving"
+  | "pulling"
+  | "starting"
+  | "discovering"
+  | "done"
+  | "failed";
+
+export type OperationState =
+  | "queued"
+  | "running"
+  | "awaiting_approval"
+  | "succeeded"
+  | "failed"
+  | "cancelled";
+
+export interface Operation {
+  id: string;
+  kind: string;
+  server_id?: string;
+  state: OperationState;
+  progress?: number;
+  result?: any;
+  error?: string;
+  cancellable: boolean;
+  created_at: number;
+  updated_at: number;
+}
+
+export interface OrphanCleanupReport {
+  processes_terminated: number;
+  containers_removed: number;
+  servers: string[];
+}
+
+export type DatabaseStatus = "unreadable" | "needs_migration" | "fresh" | "empty" | "ready";
+
+// Whether the database is new, empty, in use, waits for a migration or can't be read
+export interface DatabaseState {
+  status: DatabaseStatus;
+  // Most recent applied migration
+  schema_version?: string;
+  pending_migrations: number;
+  server_count: number;
+  tool_count: number;
+  // Servers of the legacy storage not migrated yet
+  legacy_server_count: number;
+  error?: {
+    kind: "connection" | "migrations" | "query";
+    message: string;
+  };
+}
+
+// A JSON-RPC method of mcp-core with the version of its params and result
+export interface MethodCapability {
+  name: string;
+  schema_version: number;
+}
+
+// Version, JSON-RPC methods and features of mcp-core
+export interface Capabilities {
+  version: string;
+  methods: MethodCapability[];
+  // Cargo features of mcp-core, by name
+  features: Record<string, boolean>;
+}
+
+// Management actions MCP clients can request through the built-in tools, and calls of
+// tools whose annotations say they may destroy data
+export type GatedAction = "register" | "uninstall" | "configure" | "destructive_tool";
+
+export type ActionPolicy = "allow" | "deny" | "confirm";
+
+// An action requested by an MCP client that waits for the user
+export interface ApprovalRequest {
+  // Also the id of the operation running the action
+  id: string;
+  action: GatedAction;
+  server_id?: string;
+  client?: string;
+  summary: string;
+  created_at: number;
+}
+
+export interface AuditEntry {
+  id: number;
+  timestamp: number;
+  // tool_call entries are decisions of hooks and on calls of destructive tools
+  action: GatedAction | "tool_call";
+  server_id?: string;
+  client?: string;
+  decision: "allowed" | "denied" | "approved" | "rejected" | "modified";
+  detail?: string;
+}
+
+export type HookStage = "pre" | "post";
+
+export interface ToolHook {
+  id: string;
+  stage: HookStage;
+  // Every server when missing
+  server_id?: string;
+  command: string;
+  args?: string[];
+  timeout_ms?: number;
+  on_failure?: "fail_closed" | "fail_open";
+  enabled?: boolean;
+}
+
+// Exported and imported as is
+export interface HooksConfig {
+  hooks: ToolHook[];
+}
+
+export interface PolicyError {
+  hook: string;
+  stage: HookStage;
+  reason: string;
+}
+
+// What happens to the runs of a job missed while the app was closed
+export type MisfirePolicy = "skip" | "run_once";
+
+// A scheduled tool call, as created and updated by the user
+export interface ScheduleDefinition {
+  id: string;
+  // Five-field cron expression, evaluated in UTC
+  cron: string;
+  // As `server_id:tool_name`
+  tool_id: string;
+  arguments?: Record<string, any>;
+  enabled?: boolean;
+  misfire?: MisfirePolicy;
+}
+
+// Times are in milliseconds since the Unix epoch
+export interface Schedule extends ScheduleDefinition {
+  last_run_at: number | null;
+  last_success: boolean | null;
+  last_error: string | null;
+  consecutive_failures: number;
+  next_run_at: number | null;
+}
+
+export interface ScheduleRun {
+  id: number;
+  schedule_id: string;
+  started_at: number;
+  duration_ms: number;
+  success: boolean;
+  error: string | null;
+}
+
+export type WebhookEventKind =
+  | "status_change"
+  | "crash"
+  | "install"
+  | "update_available"
+  | "test";
+
+export interface Webhook {
+  id: string;
+  url: string;
+  // Every event when empty
+  events?: WebhookEventKind[];
+  enabled?: boolean;
+  // Only sent, never returned. Generated on creation when missing, kept on update
+  secret?: string;
+}
+
+// The secret is only returned here
+export interface CreatedWebhook {
+  webhook: Webhook;
+  secret: string;
+}
+
+export interface WebhookPayload {
+  id: string;
+  event: WebhookEventKind;
+  timestamp: number;
+  text: string;
+  data: Record<string, any>;
+}
+
+export interface WebhookDelivery {
+  webhook_id: string;
+  delivery_id: string;
+  event: WebhookEventKind;
+  success: boolean;
+  attempts: number;
+  error: string | null;
+}
+
+export interface DeadLetter {
+  id: number;
+  webhook_id: string;
+  payload: WebhookPayload;
+  error: string;
+  attempts: number;
+  failed_at: number;
+}
+
+export type RegistryCredentials =
+  | { type: 'basic'; username: string; password: string }
+  | { type: 'bearer'; token: string };
+
+export interface RegistrySource {
+  id: string;
+  url: string;
+  // A registry JSON, or an index listing one JSON file per server
+  kind?: 'registry' | 'index';
+  headers?: Record<string, string>;
+  // Only sent, never returned. Kept when saving without credentials
+  credentials?: RegistryCredentials;
+}
+
+export interface DiagnosticsFile {
+  path: string;
+  size: number;
+}
+
+// Files a diagnostics export contains, secrets already removed
+export interface DiagnosticsPreview {
+  files: DiagnosticsFile[];
+  redacted_values: number;
+}
+
+export interface DiagnosticsExport extends DiagnosticsPreview {
+  path: string;
+}
+
+// One check of the troubleshooting checklist of a server
+export interface DiagnosisStep {
+  id: string;
+  title: string;
+  status: 'pass' | 'fail' | 'skipped';
+  details: string;
+  // What to do about a failed step
+  hint?: string;
+}
+
+export interface ServerDiagnosis {
+  server_id: string;
+  server_name: string;
+  // Milliseconds since the epoch
+  created_at: number;
+  healthy: boolean;
+  steps: DiagnosisStep[];
+}
+
+export interface SelftestStep extends DiagnosisStep {
+  duration_ms: number;
+}
+
+// Result of the end-to-end check run with a temporary mock server
+export interface SelftestReport {
+  // Milliseconds since the epoch
+  created_at: number;
+  passed: boolean;
+  app: string;
+  core_version: string;
+  os: string;
+  arch: string;
+  steps: SelftestStep[];
+}
+
+// Machine and user the data directory is used by
+export interface EnvironmentFingerprint {
+  hostname: string;
+  home_dir: string;
+  os: string;
+}
+
+// Absolute path of a server that doesn't exist on this machine
+export interface MissingPath {
+  server_id: string;
+  // Like "command", "args[1]" or "env.CONFIG"
+  field: string;
+  path: string;
+  // Same path under the new home directory
+  rewrite?: string;
+  rewrite_exists: boolean;
+}
+
+// Servers with missing paths since the data directory changed machine
+export interface MigrationReport {
+  // Milliseconds since the epoch
+  created_at: number;
+  previous: EnvironmentFingerprint;
+  current: EnvironmentFingerprint;
+  missing_paths: MissingPath[];
+}
+
+// How tool descriptions are shortened in the tool list
+export interface DescriptionOptions {
+  // 0 keeps descriptions whole
+  max_length: number;
+  strip_markdown: boolean;
+}
+
+// Name, version and instructions MCP clients get from Dockmaster
+export interface ServerIdentity {
+  name: string;
+  version: string;
+  instructions: string;
+  // End the instructions with the servers whose tools are exposed
+  summarize_servers: boolean;
+}
+
+// What the user changed of the identity, unset fields keep the defaults
+export interface ServerIdentityOverrides {
+  name?: string;
+  version?: string;
+  instructions?: string;
+  summarize_servers?: boolean;
+}
+
+// Payload of the "mcp-core-event" window event
+export interface CoreEvent {
+  seq: number;
+  event:
+    | {
+        type: "install_progress";
+        operation_id: string;
+        server_id: string;
+        stage: InstallStage;
+        detail?: string;
+      }
+    | ({ type: "operation_update" } & Operation)
+    | ({ type: "orphans_cleaned" } & OrphanCleanupReport)
+    | ({ type: "approval_requested" } & ApprovalRequest)
+    | {
+        type: "approval_resolved";
+        id: string;
+        action: GatedAction;
+        server_id?: string;
+        approved: boolean;
+      }
+    | { type: "tools_updated"; server_ids: string[] }
+    | {
+        type: "server_reconnected";
+        server_id: string;
+        cause: string;
+        retried: boolean;
+      }
+    | {
+        // A server started at launch, or failed to, done out of total so far
+        type: "startup_progress";
+        server_id: string;
+        success: boolean;
+        error?: string;
+        tool_count: number;
+        done: number;
+        total: number;
+      }
+    | {
+        type: "tools_budget_exceeded";
+        budget: number;
+        tokens: number;
+        server_ids: string[];
+      }
+    | ({ type: "environment_changed" } & MigrationReport);
+}
+
+// Outcome of a bulk operation for one server
+export interface BulkServerResult {
+  server_id: string;
+  success: boolean;
+  message: string;
+}
+
+export interface BulkServerResponse {
+  results: BulkServerResult[];
+  // Some servers didn't finish in time, they are still being started or stopped
+  partial: boolean;
+  timed_out: string[];
 }
 
 interface ToolExecutionRequest {
@@ -132,6 +816,14 @@ interface ToolExecutionResponse {
   success: boolean;
   result?: any;
   error?: string;
+  // For instance a result not matching the tool output schema
+  warnings?: string[];
+  // Set when a hook rejected the call
+  policy_error?: PolicyError;
+  // Set when the rate limit of the tool refused the call
+  rate_limited?: RateLimited;
+  // Set when the client cancelled the call
+  cancelled?: boolean;
 }
 
 interface ServerUpdateRequest {
@@ -147,6 +839,10 @@ interface ServerUpdateResponse {
 interface ServerConfigUpdateRequest {
   server_id: string;
   config: Record<string, string>;
+  // Whether the server inherits the environment of Dockmaster, unchanged when left out
+  inherit_env?: boolean;
+  // Directory the server is started in, unchanged when left out and removed when empty
+  cwd?: string;
 }
 
 interface ServerConfigUpdateResponse {
@@ -154,6 +850,43 @@ interface ServerConfigUpdateResponse {
   message: string;
 }
 
+export interface ConfigApplyResult {
+  success: boolean;
+  message: string;
+  /** Whether the previous configuration was put back as the server failed to start */
+  rolled_back: boolean;
+  /** Why the server failed to start with the requested configuration */
+  error?: string;
+  snapshot_id?: number;
+}
+
+export interface ConfigSnapshot {
+  id: number;
+  server_id: string;
+  /** When the configuration was replaced, in milliseconds since the epoch */
+  created_at: number;
+  configuration: ToolConfiguration;
+}
+
+export type UndoAction = "uninstall" | "disable" | "configure" | "annotations";
+
+export interface UndoEntry {
+  id: number;
+  action: UndoAction;
+  server_id: string;
+  summary: string;
+  /** In milliseconds since the epoch */
+  created_at: number;
+  /** When the change can no longer be undone, in milliseconds since the epoch */
+  expires_at: number;
+}
+
+export interface UndoResult {
+  success: boolean;
+  message: string;
+  entry: UndoEntry;
+}
+
 interface ServerUninstallRequest {
   server_id: string;
 }
@@ -161,6 +894,8 @@ interface ServerUninstallRequest {
 interface ServerUninstallResponse {
   success: boolean;
   message: string;
+  // Tools of the server that are no longer listed
+  tools_removed: number;
 }
 
 interface DiscoverServerToolsRequest {
@@ -185,6 +920,31 @@ export class MCPClient {
     return await invoke<RuntimeServer[]>('list_servers');
   }
 
+  /**
+   * Server and tool counts, cheap enough to poll unlike listServers
+   */
+  static async getDashboardSummary(): Promise<DashboardSummary> {
+    return await invoke<DashboardSummary>('get_dashboard_summary');
+  }
+
+  /**
+   * Size of the tool list in the context of the models, with its most expensive tools
+   */
+  static async getToolsFootprint(top?: number): Promise<ToolsFootprint> {
+    return await invoke<ToolsFootprint>('get_tools_footprint', { top });
+  }
+
+  /**
+   * Budget of tokens of the tool list, null when none is set
+   */
+  static async getToolsBudget(): Promise<number | null> {
+    return await invoke<number | null>('get_tools_budget');
+  }
+
+  static async setToolsBudget(budget: number | null): Promise<void> {
+    return await invoke<void>('set_tools_budget', { budget });
+  }
+
   /**
    * List all available tools from all running MCP servers
    */
@@ -192,6 +952,35 @@ export class MCPClient {
     return await invoke<ServerToolInfo[]>('list_all_server_tools');
   }
 
+  /**
+   * Get everything known about one tool, with its full description
+   */
+  static async describeTool(serverId: string, toolName: string): Promise<ToolDescription> {
+    return await invoke<ToolDescription>('describe_tool', { serverId, toolName });
+  }
+
+  /**
+   * Set the rate limit of a tool, limits left out are removed
+   */
+  static async setToolRateLimit(
+    serverId: string,
+    toolName: string,
+    limit: RateLimit
+  ): Promise<void> {
+    return await invoke<void>('set_tool_rate_limit', { serverId, toolName, limit });
+  }
+
+  /**
+   * Set the annotations of a tool over its server's, empty annotations remove them
+   */
+  static async setToolAnnotations(
+    serverId: string,
+    toolName: string,
+    annotations: ToolAnnotations
+  ): Promise<void> {
+    return await invoke<void>('set_tool_annotations', { serverId, toolName, annotations });
+  }
+
   /**
    * Execute a registered tool
    */
@@ -213,8 +1002,464 @@ export class MCPClient {
     return await invoke<ServerConfigUpdateResponse>('update_server_config', { request });
   }
 
-  static async restartTool(serverId: string): Promise<ServerUpdateResponse> {
-    return await invoke<ServerUpdateResponse>('restart_server_command', { serverId });
+  /**
+   * Update a server's configuration and restart it, rolling back if it fails to start
+   */
+  static async applyServerConfig(request: ServerConfigUpdateRequest): Promise<ConfigApplyResult> {
+    return await invoke<ConfigApplyResult>('apply_server_config', { request });
+  }
+
+  /**
+   * Get the configurations a server had before its last changes, newest first
+   */
+  static async getServerConfigHistory(serverId: string, limit?: number): Promise<ConfigSnapshot[]> {
+    return await invoke<ConfigSnapshot[]>('get_server_config_history', { serverId, limit });
+  }
+
+  /**
+   * Put back a previous configuration of a server and restart it
+   */
+  static async rollbackServerConfig(serverId: string, snapshotId: number): Promise<ConfigApplyResult> {
+    return await invoke<ConfigApplyResult>('rollback_server_config', { serverId, snapshotId });
+  }
+
+  /**
+   * Changes to servers that can still be undone, newest first
+   */
+  static async listUndoableChanges(): Promise<UndoEntry[]> {
+    return await invoke<UndoEntry[]>('list_undoable_changes');
+  }
+
+  /**
+   * Undo an uninstall, disable or configuration change
+   */
+  static async undoChange(entryId: number): Promise<UndoResult> {
+    return await invoke<UndoResult>('undo_change', { entryId });
+  }
+
+  /**
+   * Hours a change can be undone for
+   */
+  static async getUndoRetention(): Promise<number> {
+    return await invoke<number>('get_undo_retention');
+  }
+
+  static async setUndoRetention(hours: number): Promise<void> {
+    return await invoke<void>('set_undo_retention', { hours });
+  }
+
+  /**
+   * How often the running servers are checked and what happens to unhealthy ones
+   */
+  static async getHealthCheckSettings(): Promise<HealthCheckSettings> {
+    return await invoke<HealthCheckSettings>('get_health_check_settings');
+  }
+
+  static async setHealthCheckSettings(settings: HealthCheckSettings): Promise<void> {
+    return await invoke<void>('set_health_check_settings', { settings });
+  }
+
+  /**
+   * Seconds a server has to start and answer the initialize handshake
+   */
+  static async getStartupTimeout(): Promise<number> {
+    return await invoke<number>('get_startup_timeout');
+  }
+
+  static async setStartupTimeout(secs: number): Promise<void> {
+    return await invoke<void>('set_startup_timeout', { secs });
+  }
+
+  /**
+   * Seconds a server has to exit once stopped before it is killed
+   */
+  static async getShutdownGrace(): Promise<number> {
+    return await invoke<number>('get_shutdown_grace');
+  }
+
+  static async setShutdownGrace(secs: number): Promise<void> {
+    return await invoke<void>('set_shutdown_grace', { secs });
+  }
+
+  /**
+   * Consecutive failed starts after which a server isn't started again until its
+   * configuration changes or it is restarted with force
+   */
+  static async getMaxFailedStarts(): Promise<number> {
+    return await invoke<number>('get_max_failed_starts');
+  }
+
+  static async setMaxFailedStarts(limit: number): Promise<void> {
+    return await invoke<void>('set_max_failed_starts', { limit });
+  }
+
+  /**
+   * Run the troubleshooting checklist on a server
+   */
+  static async diagnoseServer(serverId: string): Promise<ServerDiagnosis> {
+    return await invoke<ServerDiagnosis>('diagnose_server', { serverId });
+  }
+
+  /**
+   * Write a checklist report to the data directory, secrets removed
+   */
+  static async exportServerDiagnosis(diagnosis: ServerDiagnosis): Promise<DiagnosticsExport> {
+    return await invoke<DiagnosticsExport>('export_server_diagnosis', { diagnosis });
+  }
+
+  /**
+   * Name, version and instructions a server gave when it last started
+   */
+  static async getServerInfo(serverId: string): Promise<ServerInitInfo | null> {
+    return await invoke<ServerInitInfo | null>('get_server_info', { serverId });
+  }
+
+  /**
+   * What a server wrote to stderr, the lines read at or after `since` when given
+   */
+  static async getServerLogs(serverId: string, since?: number): Promise<ServerLogSnapshot> {
+    return await invoke<ServerLogSnapshot>('get_server_logs', { serverId, since });
+  }
+
+  /**
+   * When a server started, crashed, restarted and stopped, with its uptime
+   */
+  static async getServerHistory(serverId: string): Promise<ServerHistory> {
+    return await invoke<ServerHistory>('get_server_history', { serverId });
+  }
+
+  /**
+   * Pass the instructions of a server on to MCP clients or not
+   */
+  static async setShareInstructions(serverId: string, share: boolean): Promise<void> {
+    return await invoke<void>('set_share_instructions', { serverId, share });
+  }
+
+  /**
+   * Restart a server, force restarting it even after it failed to start too many times
+   */
+  static async restartTool(serverId: string, force = false): Promise<ServerUpdateResponse> {
+    return await invoke<ServerUpdateResponse>('restart_server_command', { serverId, force });
+  }
+
+  /**
+   * Start an enabled server that isn't running
+   */
+  static async startServer(serverId: string): Promise<ServerUpdateResponse> {
+    return await invoke<ServerUpdateResponse>('start_server', { serverId });
+  }
+
+  /**
+   * Stop a server but keep it enabled, it isn't started with Dockmaster until started again
+   */
+  static async stopServer(serverId: string): Promise<ServerUpdateResponse> {
+    return await invoke<ServerUpdateResponse>('stop_server', { serverId });
+  }
+
+  /**
+   * Enable or disable several servers at once
+   */
+  static async setServersEnabled(serverIds: string[], enabled: boolean): Promise<BulkServerResponse> {
+    return await invoke<BulkServerResponse>('set_servers_enabled', { serverIds, enabled });
+  }
+
+  /**
+   * Restart the given servers, or every server that should be running when omitted
+   */
+  static async restartServers(serverIds?: string[]): Promise<BulkServerResponse> {
+    return await invoke<BulkServerResponse>('restart_servers', { serverIds: serverIds ?? null });
+  }
+
+  /**
+   * List tracked management operations, most recent first
+   */
+  static async listOperations(): Promise<Operation[]> {
+    return await invoke<Operation[]>('list_operations');
+  }
+
+  /**
+   * Cancel a running management operation
+   */
+  static async cancelOperation(operationId: string): Promise<Operation> {
+    return await invoke<Operation>('cancel_operation', { operationId });
+  }
+
+  /**
+   * Get the orphaned processes cleaned up at startup, or by the last cleanup asked for
+   */
+  static async getOrphanCleanupReport(): Promise<OrphanCleanupReport> {
+    return await invoke<OrphanCleanupReport>('get_orphan_cleanup_report');
+  }
+
+  /**
+   * Terminate the server processes and containers left behind by a crashed run
+   */
+  static async cleanupOrphans(): Promise<OrphanCleanupReport> {
+    return await invoke<OrphanCleanupReport>('cleanup_orphans');
+  }
+
+  /**
+   * Get the version, JSON-RPC methods and features of mcp-core
+   */
+  static async getCapabilities(): Promise<Capabilities> {
+    return await invoke<Capabilities>('get_capabilities');
+  }
+
+  /**
+   * Save which tools of a server are listed, null for the default
+   */
+  static async setToolFilter(
+    serverId: string,
+    filter: ToolFilter | null
+  ): Promise<ToolLimitReport | null> {
+    return await invoke<ToolLimitReport | null>('set_tool_filter', { serverId, filter });
+  }
+
+  /**
+   * Get whether the database is new, empty, in use, waits for a migration or can't be read
+   */
+  static async getDatabaseState(): Promise<DatabaseState> {
+    return await invoke<DatabaseState>('get_database_state');
+  }
+
+  /**
+   * List the actions requested by MCP clients that wait for approval
+   */
+  static async listPendingApprovals(): Promise<ApprovalRequest[]> {
+    return await invoke<ApprovalRequest[]>('list_pending_approvals');
+  }
+
+  /**
+   * Approve or reject an action requested by an MCP client
+   */
+  static async resolveApproval(approvalId: string, approved: boolean): Promise<ApprovalRequest> {
+    return await invoke<ApprovalRequest>('resolve_approval', { approvalId, approved });
+  }
+
+  /**
+   * Get the policy of each action MCP clients can request
+   */
+  static async getActionPolicies(): Promise<Record<GatedAction, ActionPolicy>> {
+    return await invoke<Record<GatedAction, ActionPolicy>>('get_action_policies');
+  }
+
+  /**
+   * Set the policy of an action MCP clients can request
+   */
+  static async setActionPolicy(action: GatedAction, policy: ActionPolicy): Promise<void> {
+    return await invoke<void>('set_action_policy', { action, policy });
+  }
+
+  /**
+   * Get the most recent decisions on actions requested by MCP clients
+   */
+  static async getAuditLog(limit?: number): Promise<AuditEntry[]> {
+    return await invoke<AuditEntry[]>('get_audit_log', { limit });
+  }
+
+  /**
+   * Get the hooks run around tool calls, for instance to export them
+   */
+  static async getToolHooks(): Promise<HooksConfig> {
+    return await invoke<HooksConfig>('get_tool_hooks');
+  }
+
+  /**
+   * Replace the hooks run around tool calls
+   */
+  static async setToolHooks(config: HooksConfig): Promise<void> {
+    return await invoke<void>('set_tool_hooks', { config });
+  }
+
+  /**
+   * List the scheduled tool calls with their status
+   */
+  static async listSchedules(): Promise<Schedule[]> {
+    return await invoke<Schedule[]>('list_schedules');
+  }
+
+  /**
+   * Create a scheduled tool call
+   */
+  static async createSchedule(schedule: ScheduleDefinition): Promise<Schedule> {
+    return await invoke<Schedule>('create_schedule', { schedule });
+  }
+
+  /**
+   * Replace the definition of a scheduled tool call
+   */
+  static async updateSchedule(schedule: ScheduleDefinition): Promise<Schedule> {
+    return await invoke<Schedule>('update_schedule', { schedule });
+  }
+
+  /**
+   * Delete a scheduled tool call and its run history
+   */
+  static async deleteSchedule(scheduleId: string): Promise<void> {
+    return await invoke<void>('delete_schedule', { scheduleId });
+  }
+
+  /**
+   * Get the most recent runs of a scheduled tool call, newest first
+   */
+  static async getScheduleRuns(scheduleId: string, limit?: number): Promise<ScheduleRun[]> {
+    return await invoke<ScheduleRun[]>('get_schedule_runs', { scheduleId, limit });
+  }
+
+  /**
+   * List the webhooks, without their secrets
+   */
+  static async listWebhooks(): Promise<Webhook[]> {
+    return await invoke<Webhook[]>('list_webhooks');
+  }
+
+  /**
+   * Create a webhook, returning its secret this one time
+   */
+  static async createWebhook(webhook: Webhook): Promise<CreatedWebhook> {
+    return await invoke<CreatedWebhook>('create_webhook', { webhook });
+  }
+
+  /**
+   * Replace a webhook, keeping its secret unless a new one is given
+   */
+  static async updateWebhook(webhook: Webhook): Promise<Webhook> {
+    return await invoke<Webhook>('update_webhook', { webhook });
+  }
+
+  static async deleteWebhook(webhookId: string): Promise<void> {
+    return await invoke<void>('delete_webhook', { webhookId });
+  }
+
+  /**
+   * Send a test payload to a webhook
+   */
+  static async testWebhook(webhookId: string): Promise<WebhookDelivery> {
+    return await invoke<WebhookDelivery>('test_webhook', { webhookId });
+  }
+
+  /**
+   * Get the payloads that could not be delivered, newest first
+   */
+  static async getWebhookDeadLetters(limit?: number): Promise<DeadLetter[]> {
+    return await invoke<DeadLetter[]>('get_webhook_dead_letters', { limit });
+  }
+
+  /**
+   * List the registries used next to the default one, without their credentials
+   */
+  static async listRegistrySources(): Promise<RegistrySource[]> {
+    return await invoke<RegistrySource[]>('list_registry_sources');
+  }
+
+  /**
+   * Create or replace a registry source
+   */
+  static async saveRegistrySource(source: RegistrySource): Promise<RegistrySource> {
+    return await invoke<RegistrySource>('save_registry_source', { source });
+  }
+
+  /**
+   * Delete a registry source, servers installed from it are kept
+   */
+  static async deleteRegistrySource(sourceId: string): Promise<void> {
+    return await invoke<void>('delete_registry_source', { sourceId });
+  }
+
+  /**
+   * Whether the proxy refuses to install or reconfigure servers
+   */
+  static async getReadOnly(): Promise<boolean> {
+    return await invoke<boolean>('get_read_only');
+  }
+
+  /**
+   * Turn the read-only mode on or off
+   */
+  static async setReadOnly(readOnly: boolean): Promise<void> {
+    return await invoke<void>('set_read_only', { readOnly });
+  }
+
+  /**
+   * List the files a diagnostics export would contain
+   */
+  static async previewDiagnostics(): Promise<DiagnosticsPreview> {
+    return await invoke<DiagnosticsPreview>('preview_diagnostics');
+  }
+
+  /**
+   * Write a diagnostics bundle for bug reports to the data directory
+   */
+  static async exportDiagnostics(): Promise<DiagnosticsExport> {
+    return await invoke<DiagnosticsExport>('export_diagnostics');
+  }
+
+  /**
+   * Check the app end to end with a temporary mock server
+   */
+  static async runSelftest(): Promise<SelftestReport> {
+    return await invoke<SelftestReport>('run_selftest');
+  }
+
+  /**
+   * Get the servers with missing paths since the data directory changed machine
+   */
+  static async getMigrationReport(): Promise<MigrationReport | null> {
+    return await invoke<MigrationReport | null>('get_migration_report');
+  }
+
+  /**
+   * Move the missing paths of servers under the new home directory, all the servers of
+   * the migration report by default
+   */
+  static async migrateServerPaths(
+    serverIds?: string[]
+  ): Promise<Record<string, ConfigApplyResult>> {
+    return await invoke<Record<string, ConfigApplyResult>>('migrate_server_paths', {
+      serverIds,
+    });
+  }
+
+  /**
+   * Get how tool descriptions are shortened in the tool list
+   */
+  static async getDescriptionOptions(): Promise<DescriptionOptions> {
+    return await invoke<DescriptionOptions>('get_description_options');
+  }
+
+  /**
+   * Set how tool descriptions are shortened in the tool list
+   */
+  static async setDescriptionOptions(options: DescriptionOptions): Promise<void> {
+    return await invoke<void>('set_description_options', { options });
+  }
+
+  /**
+   * Identity presented to MCP clients and what the user changed of it
+   */
+  static async getServerIdentity(): Promise<[ServerIdentity, ServerIdentityOverrides]> {
+    return await invoke<[ServerIdentity, ServerIdentityOverrides]>('get_server_identity');
+  }
+
+  /**
+   * Change the name, version or instructions presented to MCP clients
+   */
+  static async setServerIdentity(overrides: ServerIdentityOverrides): Promise<ServerIdentity> {
+    return await invoke<ServerIdentity>('set_server_identity', { overrides });
+  }
+
+  /**
+   * Whether the tool list is saved to serve it at the next start
+   */
+  static async getToolsSnapshotEnabled(): Promise<boolean> {
+    return await invoke<boolean>('get_tools_snapshot_enabled');
+  }
+
+  /**
+   * Save the tool list to serve it at the next start or not
+   */
+  static async setToolsSnapshotEnabled(enabled: boolean): Promise<void> {
+    return await invoke<void>('set_tools_snapshot_enabled', { enabled });
   }
 
   /**
@@ -239,11 +1484,15 @@ export class MCPClient {
   }
   
   /**
-   * Import a server from a GitHub repository URL
+   * Import a server from a GitHub repository URL, started unless enabled is false
    */
-  static async importServerFromUrl(url: string): Promise<ServerRegistrationResponse> {
-    return await invoke<ServerRegistrationResponse>('import_server_from_url', { 
-      url 
+  static async importServerFromUrl(
+    url: string,
+    enabled?: boolean
+  ): Promise<ServerRegistrationResponse> {
+    return await invoke<ServerRegistrationResponse>('import_server_from_url', {
+      url,
+      enabled
     });
   }
   
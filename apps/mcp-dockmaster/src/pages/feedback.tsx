@@ -2,9 +2,13 @@ import { useState } from "react";
 import { Button } from "@/components/ui/button";
 import { Input } from "@/components/ui/input";
 import { Textarea } from "@/components/ui/textarea";
+import { ExportDiagnostics } from "@/components/export-diagnostics";
+import { supports } from "@/lib/capabilities";
+import { useAppStore } from "@/store/app";
 
 const Feedback = () => {
   const [formSuccess, setFormSuccess] = useState(false);
+  const capabilities = useAppStore((state) => state.capabilities);
 
   return (
     <div className="text-muted-foreground mx-auto flex h-full w-full max-w-4xl flex-col gap-8 px-6 py-10 text-sm">
@@ -86,6 +90,19 @@ const Feedback = () => {
           </form>
         )}
       </section>
+      {supports(capabilities, "diagnostics/export") && (
+        <section className="feedback-section">
+          <h2 className="text-foreground mb-4 border-b border-gray-300 pb-2 text-lg font-semibold">
+            Reporting a bug
+          </h2>
+          <p className="mb-4">
+            Export a diagnostics bundle and attach it to your report. It contains
+            your server configurations with secrets removed, recent logs and
+            version information.
+          </p>
+          <ExportDiagnostics />
+        </section>
+      )}
     </div>
   );
 };
@@ -253,8 +253,8 @@ const Home: React.FC = () => {
   }, []);
   const checkMCPServers = async () => {
     try {
-      const servers = await MCPClient.listServers();
-      setMCPServers(servers.length > 0);
+      const summary = await MCPClient.getDashboardSummary();
+      setMCPServers(summary.servers > 0);
     } catch (error) {
       console.error("Failed to check MCP servers:", error);
       setMCPServers(false);
@@ -2,12 +2,16 @@
 
 import { create } from "zustand";
 import { getUserConsent } from "../lib/localStorage";
+import { Capabilities } from "../lib/mcpClient";
 
 interface AppStore {
   appState: "pending" | "ready" | "error";
   setAppState: (state: "pending" | "ready" | "error") => void;
   userConsented: boolean;
   setUserConsented: (consented: boolean) => void;
+  // What mcp-core answers, null until known
+  capabilities: Capabilities | null;
+  setCapabilities: (capabilities: Capabilities) => void;
 }
 
 export const useAppStore = create<AppStore>((set) => ({
@@ -15,4 +19,6 @@ export const useAppStore = create<AppStore>((set) => ({
   setAppState: (state: "pending" | "ready" | "error") => set({ appState: state }),
   userConsented: !!getUserConsent()?.termsAccepted,
   setUserConsented: (consented: boolean) => set({ userConsented: consented }),
+  capabilities: null,
+  setCapabilities: (capabilities: Capabilities) => set({ capabilities }),
 }));
@@ -10,7 +10,7 @@ export class MCPConfig {
       throw new Error("MCPConfig is not initialized");
     }
     const result = await proxyRequest('server/config', {
-      tool_id,
+      server_id: tool_id,
       config
     });
     return {
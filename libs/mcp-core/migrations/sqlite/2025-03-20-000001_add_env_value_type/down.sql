@@ -0,0 +1,3 @@
+-- Remove env value type columns from server_env table
+ALTER TABLE server_env DROP COLUMN env_bool_format;
+ALTER TABLE server_env DROP COLUMN env_type;
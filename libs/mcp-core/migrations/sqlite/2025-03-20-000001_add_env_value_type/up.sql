@@ -0,0 +1,3 @@
+-- Keep the JSON type of env values and how booleans are rendered at spawn time
+ALTER TABLE server_env ADD COLUMN env_type TEXT NOT NULL DEFAULT 'string';
+ALTER TABLE server_env ADD COLUMN env_bool_format TEXT NOT NULL DEFAULT 'literal';
@@ -0,0 +1,2 @@
+-- Drop the server_processes table
+DROP TABLE server_processes;
@@ -0,0 +1,9 @@
+-- Processes spawned for each server, used to find orphans left behind by a crash
+CREATE TABLE server_processes (
+    server_id TEXT PRIMARY KEY NOT NULL,
+    spawn_id TEXT NOT NULL,
+    pid BIGINT,
+    start_time BIGINT,
+    command TEXT,
+    container_name TEXT
+);
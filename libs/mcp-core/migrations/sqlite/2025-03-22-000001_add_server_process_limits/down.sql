@@ -0,0 +1,3 @@
+-- Remove process limit columns from servers table
+ALTER TABLE servers DROP COLUMN cpu_limit;
+ALTER TABLE servers DROP COLUMN priority;
@@ -0,0 +1,3 @@
+-- Scheduling priority and CPU core limit applied when spawning a server
+ALTER TABLE servers ADD COLUMN priority TEXT;
+ALTER TABLE servers ADD COLUMN cpu_limit INTEGER;
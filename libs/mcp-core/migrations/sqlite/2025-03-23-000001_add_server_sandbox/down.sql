@@ -0,0 +1,2 @@
+-- Remove sandbox column from servers table
+ALTER TABLE servers DROP COLUMN sandbox;
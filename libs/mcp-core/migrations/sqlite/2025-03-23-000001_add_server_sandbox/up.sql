@@ -0,0 +1,2 @@
+-- Sandbox options of servers running in restricted mode, stored as JSON
+ALTER TABLE servers ADD COLUMN sandbox TEXT;
@@ -0,0 +1,2 @@
+-- Drop the server_provenance table
+DROP TABLE server_provenance;
@@ -0,0 +1,9 @@
+-- Where each installed server came from, recorded at registration time
+CREATE TABLE server_provenance (
+    server_id TEXT PRIMARY KEY NOT NULL,
+    source TEXT NOT NULL,
+    source_id TEXT,
+    client TEXT,
+    installed_at BIGINT NOT NULL,
+    app_version TEXT NOT NULL
+);
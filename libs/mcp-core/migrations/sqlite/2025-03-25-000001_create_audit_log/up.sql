@@ -0,0 +1,10 @@
+-- Decisions taken on management actions requested by models
+CREATE TABLE audit_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    timestamp BIGINT NOT NULL,
+    action TEXT NOT NULL,
+    server_id TEXT,
+    client TEXT,
+    decision TEXT NOT NULL,
+    detail TEXT
+);
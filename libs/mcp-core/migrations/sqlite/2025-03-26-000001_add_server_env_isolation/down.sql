@@ -0,0 +1,3 @@
+-- Remove environment isolation columns from servers table
+ALTER TABLE servers DROP COLUMN env_passthrough;
+ALTER TABLE servers DROP COLUMN inherit_env;
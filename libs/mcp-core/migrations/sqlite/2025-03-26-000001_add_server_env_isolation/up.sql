@@ -0,0 +1,4 @@
+-- Environment isolation settings of servers
+ALTER TABLE servers ADD COLUMN inherit_env BOOLEAN;
+-- Names of the Dockmaster variables passed through, stored as JSON
+ALTER TABLE servers ADD COLUMN env_passthrough TEXT;
@@ -0,0 +1,2 @@
+-- Remove description truncation column from servers table
+ALTER TABLE servers DROP COLUMN truncate_descriptions;
@@ -0,0 +1,2 @@
+-- Whether the tool descriptions of a server are truncated
+ALTER TABLE servers ADD COLUMN truncate_descriptions BOOLEAN;
@@ -0,0 +1,3 @@
+-- Remove the output schema columns
+ALTER TABLE servers DROP COLUMN strict_output_schema;
+ALTER TABLE server_tools DROP COLUMN output_schema;
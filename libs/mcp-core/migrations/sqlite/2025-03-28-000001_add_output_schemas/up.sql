@@ -0,0 +1,4 @@
+-- Output schema declared by each tool
+ALTER TABLE server_tools ADD COLUMN output_schema TEXT;
+-- Whether results not matching the output schema fail the tool call
+ALTER TABLE servers ADD COLUMN strict_output_schema BOOLEAN;
@@ -0,0 +1,2 @@
+-- Remove the server id aliases
+DROP TABLE server_id_aliases;
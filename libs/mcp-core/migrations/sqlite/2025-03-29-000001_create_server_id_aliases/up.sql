@@ -0,0 +1,5 @@
+-- Former ids of servers whose id was normalized, so old references still resolve
+CREATE TABLE server_id_aliases (
+    alias TEXT PRIMARY KEY NOT NULL,
+    server_id TEXT NOT NULL
+);
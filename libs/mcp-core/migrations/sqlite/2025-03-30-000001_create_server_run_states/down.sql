@@ -0,0 +1,2 @@
+-- Drop the desired run states
+DROP TABLE server_run_states;
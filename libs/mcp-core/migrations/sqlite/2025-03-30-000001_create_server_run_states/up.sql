@@ -0,0 +1,6 @@
+-- Whether the user wants each server running, kept apart from `enabled` so that a
+-- server stopped by the user stays stopped across launches
+CREATE TABLE server_run_states (
+    server_id TEXT PRIMARY KEY NOT NULL,
+    desired_running BOOLEAN NOT NULL
+);
@@ -0,0 +1,2 @@
+-- Drop the call modes
+DROP TABLE server_call_modes;
@@ -0,0 +1,7 @@
+-- Whether tool calls of each server are recorded or replayed, servers without a row
+-- are live
+CREATE TABLE server_call_modes (
+    server_id TEXT PRIMARY KEY NOT NULL,
+    mode TEXT NOT NULL,
+    on_miss TEXT NOT NULL
+);
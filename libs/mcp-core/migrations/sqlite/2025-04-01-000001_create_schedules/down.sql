@@ -0,0 +1,3 @@
+-- Drop the schedule tables
+DROP TABLE schedule_runs;
+DROP TABLE schedules;
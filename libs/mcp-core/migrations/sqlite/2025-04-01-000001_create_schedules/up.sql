@@ -0,0 +1,23 @@
+-- Tool calls run on a schedule, with the outcome of their last run
+CREATE TABLE schedules (
+    id TEXT PRIMARY KEY NOT NULL,
+    cron TEXT NOT NULL,
+    tool_id TEXT NOT NULL,
+    arguments TEXT NOT NULL,
+    enabled BOOLEAN NOT NULL DEFAULT 1,
+    misfire TEXT NOT NULL DEFAULT 'skip',
+    last_run_at BIGINT,
+    last_success BOOLEAN,
+    last_error TEXT,
+    consecutive_failures INTEGER NOT NULL DEFAULT 0
+);
+
+-- Run history of the scheduled calls
+CREATE TABLE schedule_runs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    schedule_id TEXT NOT NULL,
+    started_at BIGINT NOT NULL,
+    duration_ms BIGINT NOT NULL,
+    success BOOLEAN NOT NULL,
+    error TEXT
+);
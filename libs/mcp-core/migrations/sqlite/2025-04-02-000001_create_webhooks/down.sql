@@ -0,0 +1,3 @@
+-- Drop the webhook tables
+DROP TABLE webhook_dead_letters;
+DROP TABLE webhooks;
@@ -0,0 +1,18 @@
+-- Webhooks notified of server lifecycle events, with the key of their signatures
+CREATE TABLE webhooks (
+    id TEXT PRIMARY KEY NOT NULL,
+    url TEXT NOT NULL,
+    events TEXT NOT NULL,
+    enabled BOOLEAN NOT NULL DEFAULT 1,
+    secret TEXT NOT NULL
+);
+
+-- Payloads that could not be delivered after every retry
+CREATE TABLE webhook_dead_letters (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    webhook_id TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    error TEXT NOT NULL,
+    attempts INTEGER NOT NULL,
+    failed_at BIGINT NOT NULL
+);
@@ -0,0 +1,3 @@
+-- Drop the registry sources and the source of installed servers
+ALTER TABLE server_provenance DROP COLUMN registry_source;
+DROP TABLE registry_sources;
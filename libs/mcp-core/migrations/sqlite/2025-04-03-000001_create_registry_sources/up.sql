@@ -0,0 +1,11 @@
+-- Registries listed next to the default one, with the credentials sent to them
+CREATE TABLE registry_sources (
+    id TEXT PRIMARY KEY NOT NULL,
+    url TEXT NOT NULL,
+    kind TEXT NOT NULL DEFAULT 'registry',
+    headers TEXT NOT NULL DEFAULT '{}',
+    credentials TEXT
+);
+
+-- Source of servers installed from a registry other than the default one
+ALTER TABLE server_provenance ADD COLUMN registry_source TEXT;
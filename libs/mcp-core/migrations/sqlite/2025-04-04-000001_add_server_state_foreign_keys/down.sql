@@ -0,0 +1,30 @@
+-- Rebuild the server state tables without foreign keys
+CREATE TABLE server_provenance_old (
+    server_id TEXT PRIMARY KEY NOT NULL,
+    source TEXT NOT NULL,
+    source_id TEXT,
+    client TEXT,
+    installed_at BIGINT NOT NULL,
+    app_version TEXT NOT NULL,
+    registry_source TEXT
+);
+INSERT INTO server_provenance_old SELECT * FROM server_provenance;
+DROP TABLE server_provenance;
+ALTER TABLE server_provenance_old RENAME TO server_provenance;
+
+CREATE TABLE server_run_states_old (
+    server_id TEXT PRIMARY KEY NOT NULL,
+    desired_running BOOLEAN NOT NULL
+);
+INSERT INTO server_run_states_old SELECT * FROM server_run_states;
+DROP TABLE server_run_states;
+ALTER TABLE server_run_states_old RENAME TO server_run_states;
+
+CREATE TABLE server_call_modes_old (
+    server_id TEXT PRIMARY KEY NOT NULL,
+    mode TEXT NOT NULL,
+    on_miss TEXT NOT NULL
+);
+INSERT INTO server_call_modes_old SELECT * FROM server_call_modes;
+DROP TABLE server_call_modes;
+ALTER TABLE server_call_modes_old RENAME TO server_call_modes;
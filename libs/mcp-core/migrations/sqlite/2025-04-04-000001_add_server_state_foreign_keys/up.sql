@@ -0,0 +1,44 @@
+-- Remove the state of a server along with it. SQLite can't add a foreign key to an
+-- existing table, so the tables are rebuilt; state of servers that no longer exist
+-- is not copied.
+CREATE TABLE server_provenance_new (
+    server_id TEXT PRIMARY KEY NOT NULL,
+    source TEXT NOT NULL,
+    source_id TEXT,
+    client TEXT,
+    installed_at BIGINT NOT NULL,
+    app_version TEXT NOT NULL,
+    registry_source TEXT,
+    FOREIGN KEY (server_id) REFERENCES servers(id) ON DELETE CASCADE
+);
+INSERT INTO server_provenance_new
+    SELECT server_id, source, source_id, client, installed_at, app_version, registry_source
+    FROM server_provenance
+    WHERE server_id IN (SELECT id FROM servers);
+DROP TABLE server_provenance;
+ALTER TABLE server_provenance_new RENAME TO server_provenance;
+
+CREATE TABLE server_run_states_new (
+    server_id TEXT PRIMARY KEY NOT NULL,
+    desired_running BOOLEAN NOT NULL,
+    FOREIGN KEY (server_id) REFERENCES servers(id) ON DELETE CASCADE
+);
+INSERT INTO server_run_states_new
+    SELECT server_id, desired_running
+    FROM server_run_states
+    WHERE server_id IN (SELECT id FROM servers);
+DROP TABLE server_run_states;
+ALTER TABLE server_run_states_new RENAME TO server_run_states;
+
+CREATE TABLE server_call_modes_new (
+    server_id TEXT PRIMARY KEY NOT NULL,
+    mode TEXT NOT NULL,
+    on_miss TEXT NOT NULL,
+    FOREIGN KEY (server_id) REFERENCES servers(id) ON DELETE CASCADE
+);
+INSERT INTO server_call_modes_new
+    SELECT server_id, mode, on_miss
+    FROM server_call_modes
+    WHERE server_id IN (SELECT id FROM servers);
+DROP TABLE server_call_modes;
+ALTER TABLE server_call_modes_new RENAME TO server_call_modes;
@@ -0,0 +1,2 @@
+-- Remove the tool annotations column
+ALTER TABLE server_tools DROP COLUMN annotations;
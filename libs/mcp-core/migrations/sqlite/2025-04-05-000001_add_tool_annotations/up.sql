@@ -0,0 +1,2 @@
+-- Hints of each tool about its behavior, as JSON
+ALTER TABLE server_tools ADD COLUMN annotations TEXT;
@@ -0,0 +1,2 @@
+-- Keys stay upper case
+DROP INDEX idx_server_env_key_nocase;
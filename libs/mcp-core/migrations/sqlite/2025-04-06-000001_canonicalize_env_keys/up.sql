@@ -0,0 +1,8 @@
+-- Environment keys are upper case. Keys of a server differing only by case keep the
+-- value written last, the rows are rewritten on every save so that is the highest rowid
+DELETE FROM server_env
+WHERE rowid NOT IN (
+    SELECT MAX(rowid) FROM server_env GROUP BY server_id, UPPER(TRIM(env_key))
+);
+UPDATE server_env SET env_key = UPPER(TRIM(env_key));
+CREATE UNIQUE INDEX idx_server_env_key_nocase ON server_env (server_id, env_key COLLATE NOCASE);
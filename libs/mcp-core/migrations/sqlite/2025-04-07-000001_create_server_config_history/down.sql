@@ -0,0 +1,2 @@
+-- Drop the configuration history
+DROP TABLE server_config_history;
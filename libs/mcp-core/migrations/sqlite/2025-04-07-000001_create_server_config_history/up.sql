@@ -0,0 +1,9 @@
+-- Configurations servers had before each change, so that a change can be rolled back
+CREATE TABLE server_config_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    server_id TEXT NOT NULL,
+    created_at BIGINT NOT NULL,
+    configuration TEXT NOT NULL,
+    FOREIGN KEY (server_id) REFERENCES servers(id) ON DELETE CASCADE
+);
+CREATE INDEX idx_server_config_history_server_id ON server_config_history (server_id);
@@ -0,0 +1,3 @@
+-- Drop the server info
+ALTER TABLE servers DROP COLUMN share_instructions;
+DROP TABLE server_info;
@@ -0,0 +1,13 @@
+-- Name, version and instructions each server gave in its last initialize handshake
+CREATE TABLE server_info (
+    server_id TEXT PRIMARY KEY NOT NULL,
+    name TEXT NOT NULL,
+    version TEXT NOT NULL,
+    protocol_version TEXT,
+    instructions TEXT,
+    initialized_at BIGINT NOT NULL,
+    FOREIGN KEY (server_id) REFERENCES servers(id) ON DELETE CASCADE
+);
+
+-- Whether the instructions of a server are passed on to MCP clients
+ALTER TABLE servers ADD COLUMN share_instructions BOOLEAN;
@@ -0,0 +1,2 @@
+-- Drop the undo journal
+DROP TABLE undo_journal;
@@ -0,0 +1,12 @@
+-- Changes to servers that can be undone, with what each change replaced. Entries
+-- outlive the servers they undo the uninstall of, so there is no foreign key.
+CREATE TABLE undo_journal (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    action TEXT NOT NULL,
+    server_id TEXT NOT NULL,
+    summary TEXT NOT NULL,
+    created_at BIGINT NOT NULL,
+    expires_at BIGINT NOT NULL,
+    before_image TEXT NOT NULL
+);
+CREATE INDEX idx_undo_journal_expires_at ON undo_journal (expires_at);
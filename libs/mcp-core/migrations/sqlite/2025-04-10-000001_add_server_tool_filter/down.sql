@@ -0,0 +1,2 @@
+-- Remove the tool filter of the servers
+ALTER TABLE servers DROP COLUMN tool_filter;
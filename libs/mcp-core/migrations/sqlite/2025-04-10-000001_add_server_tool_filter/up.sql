@@ -0,0 +1,2 @@
+-- Include and exclude patterns and maximum count of the tools of a server, as JSON
+ALTER TABLE servers ADD COLUMN tool_filter TEXT;
@@ -0,0 +1,2 @@
+-- Drop the lifecycle history
+DROP TABLE server_lifecycle_events;
@@ -0,0 +1,11 @@
+-- Last lifecycle transitions of each server, to show how often it restarts
+CREATE TABLE server_lifecycle_events (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    server_id TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    at BIGINT NOT NULL,
+    exit_code INTEGER,
+    detail TEXT,
+    FOREIGN KEY (server_id) REFERENCES servers(id) ON DELETE CASCADE
+);
+CREATE INDEX idx_server_lifecycle_events_server_id ON server_lifecycle_events (server_id);
@@ -0,0 +1,2 @@
+-- Remove the docker platforms of the servers
+ALTER TABLE servers DROP COLUMN platforms;
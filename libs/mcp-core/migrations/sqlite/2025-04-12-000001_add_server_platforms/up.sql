@@ -0,0 +1,2 @@
+-- Docker platforms the image of a server is built for, as JSON
+ALTER TABLE servers ADD COLUMN platforms TEXT;
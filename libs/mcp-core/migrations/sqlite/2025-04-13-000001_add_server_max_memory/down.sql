@@ -0,0 +1,2 @@
+-- Remove the memory limit of the servers
+ALTER TABLE servers DROP COLUMN max_memory_mb;
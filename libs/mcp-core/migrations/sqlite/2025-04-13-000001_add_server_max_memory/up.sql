@@ -0,0 +1,2 @@
+-- Memory limit of the servers, in megabytes
+ALTER TABLE servers ADD COLUMN max_memory_mb INTEGER;
@@ -0,0 +1,2 @@
+-- Remove the start mode of the servers
+ALTER TABLE servers DROP COLUMN start_mode;
@@ -0,0 +1,2 @@
+-- Whether servers start with Dockmaster or on their first tool call
+ALTER TABLE servers ADD COLUMN start_mode TEXT;
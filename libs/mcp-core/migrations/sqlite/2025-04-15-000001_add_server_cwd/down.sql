@@ -0,0 +1,2 @@
+-- Remove the working directory of the servers
+ALTER TABLE servers DROP COLUMN cwd;
@@ -0,0 +1,2 @@
+-- Directory servers are started in
+ALTER TABLE servers ADD COLUMN cwd TEXT;
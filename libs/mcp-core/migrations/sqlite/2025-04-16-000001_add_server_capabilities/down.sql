@@ -0,0 +1,3 @@
+-- Forget the capabilities servers declared and the lenient initialize option
+ALTER TABLE server_info DROP COLUMN capabilities;
+ALTER TABLE servers DROP COLUMN lenient_initialize;
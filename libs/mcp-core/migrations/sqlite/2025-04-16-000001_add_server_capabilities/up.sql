@@ -0,0 +1,4 @@
+-- Capabilities servers declared in their initialize answer, as JSON
+ALTER TABLE server_info ADD COLUMN capabilities TEXT;
+-- List the tools of servers that declare no tool support
+ALTER TABLE servers ADD COLUMN lenient_initialize BOOLEAN;
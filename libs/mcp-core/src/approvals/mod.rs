@@ -0,0 +1,150 @@
+//! User approval of management actions requested by models.
+//!
+//! The built-in register, uninstall and configure tools let a connected model manage
+//! servers. Each of these [`GatedAction`]s has an [`ActionPolicy`] chosen by the user.
+//! Actions under the `confirm` policy run as operations that stay in the
+//! `awaiting_approval` state until the user answers the [`ApprovalRequest`] in the app.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use tokio::sync::oneshot;
+
+use crate::events::{DockmasterEvent, EventBus};
+use crate::models::types::GatedAction;
+
+/// How long a tool call waits for the user before answering that approval is pending
+pub const APPROVAL_WAIT: Duration = Duration::from_secs(60);
+
+/// Settings key of the policy of an action
+pub fn policy_setting_key(action: GatedAction) -> String {
+    format!("approval_policy_{}", action.as_str())
+}
+
+/// An action waiting for the user's decision
+///
+/// Its id is the id of the operation that runs the action once approved.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ApprovalRequest {
+    pub id: String,
+    pub action: GatedAction,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub server_id: Option<String>,
+    /// MCP client that requested the action
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client: Option<String>,
+    /// Human readable description shown to the user
+    pub summary: String,
+    /// Creation time, in milliseconds since the Unix epoch
+    pub created_at: u64,
+}
+
+impl ApprovalRequest {
+    pub fn new(
+        id: String,
+        action: GatedAction,
+        server_id: Option<String>,
+        client: Option<String>,
+        summary: String,
+    ) -> Self {
+        Self {
+            id,
+            action,
+            server_id,
+            client,
+            summary,
+            created_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0),
+        }
+    }
+}
+
+/// The user's answer to an approval request
+#[derive(Clone, Debug, Serialize)]
+pub struct ApprovalResolution {
+    pub id: String,
+    pub action: GatedAction,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub server_id: Option<String>,
+    pub approved: bool,
+}
+
+struct PendingApproval {
+    request: ApprovalRequest,
+    responder: oneshot::Sender<bool>,
+}
+
+/// Approval requests waiting for the user
+#[derive(Clone)]
+pub struct ApprovalRegistry {
+    pending: Arc<Mutex<HashMap<String, PendingApproval>>>,
+    event_bus: EventBus,
+}
+
+impl ApprovalRegistry {
+    pub fn new(event_bus: EventBus) -> Self {
+        Self {
+            pending: Arc::new(Mutex::new(HashMap::new())),
+            event_bus,
+        }
+    }
+
+    /// Ask the user to approve an action
+    ///
+    /// The returned receiver gets the user's decision. Dropping it withdraws the request.
+    pub fn request(&self, request: ApprovalRequest) -> oneshot::Receiver<bool> {
+        let (responder, receiver) = oneshot::channel();
+        self.pending.lock().unwrap().insert(
+            request.id.clone(),
+            PendingApproval {
+                request: request.clone(),
+                responder,
+            },
+        );
+        self.event_bus
+            .publish(DockmasterEvent::ApprovalRequested(request));
+        receiver
+    }
+
+    /// Record the user's decision on a pending request
+    pub fn resolve(&self, id: &str, approved: bool) -> Result<ApprovalRequest, String> {
+        let pending = self
+            .pending
+            .lock()
+            .unwrap()
+            .remove(id)
+            .ok_or_else(|| format!("No pending approval '{}'", id))?;
+        let request = pending.request;
+        if pending.responder.send(approved).is_err() {
+            return Err(format!(
+                "Approval '{}' was withdrawn, its operation is no longer running",
+                id
+            ));
+        }
+        self.event_bus
+            .publish(DockmasterEvent::ApprovalResolved(ApprovalResolution {
+                id: request.id.clone(),
+                action: request.action,
+                server_id: request.server_id.clone(),
+                approved,
+            }));
+        Ok(request)
+    }
+
+    /// Requests still waiting for the user, oldest first
+    pub fn pending(&self) -> Vec<ApprovalRequest> {
+        let mut pending = self.pending.lock().unwrap();
+        // Requests of cancelled operations can no longer be answered
+        pending.retain(|_, approval| !approval.responder.is_closed());
+        let mut requests: Vec<ApprovalRequest> = pending
+            .values()
+            .map(|approval| approval.request.clone())
+            .collect();
+        requests.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+        requests
+    }
+}
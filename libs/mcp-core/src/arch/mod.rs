@@ -0,0 +1,270 @@
+//! Architecture of the machine and of the runtimes servers are launched with.
+//!
+//! On Apple Silicon the node found on the PATH may be an x86_64 build run under
+//! Rosetta, e.g. one installed by an x86_64 homebrew, and a docker image may have no
+//! variant for the machine. The launch plan of a server records the architectures of
+//! the binary its command runs with, following the interpreter of scripts like `npx`,
+//! and warns when it will be translated. Docker servers whose registry entry lists
+//! the platforms of their image run with an explicit `--platform`, or fail to start
+//! when none of them matches the machine. See [`LaunchArch`].
+
+use std::ffi::OsStr;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+use crate::diagnose::find_program;
+
+/// Bytes read from the start of a binary to tell its architectures
+const HEADER_BYTES: u64 = 4096;
+
+/// A processor architecture servers run on
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Arch {
+    X86_64,
+    Arm64,
+}
+
+impl Arch {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Arch::X86_64 => "x86_64",
+            Arch::Arm64 => "arm64",
+        }
+    }
+
+    /// Architecture named `name` by Rust, docker, Node.js or the OS
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "x86_64" | "amd64" | "x64" => Some(Arch::X86_64),
+            "arm64" | "aarch64" => Some(Arch::Arm64),
+            _ => None,
+        }
+    }
+
+    /// Linux docker platform of the architecture
+    pub fn docker_platform(&self) -> &'static str {
+        match self {
+            Arch::X86_64 => "linux/amd64",
+            Arch::Arm64 => "linux/arm64",
+        }
+    }
+}
+
+/// Architecture of this machine, None when it isn't one servers are known to run on
+///
+/// Under Rosetta Dockmaster itself is an x86_64 process, the machine is then told by
+/// the processor.
+pub fn host_arch() -> Option<Arch> {
+    static HOST: Lazy<Option<Arch>> = Lazy::new(|| {
+        if cfg!(target_os = "macos") && sysctl("hw.optional.arm64").as_deref() == Some("1") {
+            return Some(Arch::Arm64);
+        }
+        Arch::parse(std::env::consts::ARCH)
+    });
+    *HOST
+}
+
+fn sysctl(name: &str) -> Option<String> {
+    let output = std::process::Command::new("/usr/sbin/sysctl")
+        .args(["-n", name])
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Architectures of an ELF, Mach-O or PE executable starting with `header`, more than
+/// one for a universal binary and none when the format isn't known
+pub fn executable_archs(header: &[u8]) -> Vec<Arch> {
+    let u16_le = |at: usize| {
+        header
+            .get(at..at + 2)
+            .map(|b| u16::from_le_bytes([b[0], b[1]]))
+    };
+    let u32_le = |at: usize| {
+        header
+            .get(at..at + 4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    };
+    let u32_be = |at: usize| {
+        header
+            .get(at..at + 4)
+            .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    };
+    let mach_o = |cpu_type: u32| match cpu_type {
+        0x0100_0007 => Some(Arch::X86_64),
+        0x0100_000c => Some(Arch::Arm64),
+        _ => None,
+    };
+
+    match header {
+        [0x7f, b'E', b'L', b'F', ..] => {
+            let machine = match header.get(5) {
+                Some(2) => header.get(18..20).map(|b| u16::from_be_bytes([b[0], b[1]])),
+                _ => u16_le(18),
+            };
+            match machine {
+                Some(62) => vec![Arch::X86_64],
+                Some(183) => vec![Arch::Arm64],
+                _ => Vec::new(),
+            }
+        }
+        [0xcf, 0xfa, 0xed, 0xfe, ..] => u32_le(4).and_then(mach_o).into_iter().collect(),
+        // Universal binary, with 32 or 64-bit offsets. Java class files share the magic
+        // but have a version of 45 or more where the count of architectures is.
+        [0xca, 0xfe, 0xba, magic @ (0xbe | 0xbf), ..] => {
+            let entry_size = if *magic == 0xbe { 20 } else { 32 };
+            let count = u32_be(4).filter(|count| *count < 45).unwrap_or(0) as usize;
+            let mut archs: Vec<Arch> = (0..count)
+                .filter_map(|index| u32_be(8 + index * entry_size).and_then(mach_o))
+                .collect();
+            archs.dedup();
+            archs
+        }
+        [b'M', b'Z', ..] => {
+            let Some(pe) = u32_le(0x3c).map(|offset| offset as usize) else {
+                return Vec::new();
+            };
+            if header.get(pe..pe + 4) != Some(b"PE\0\0".as_slice()) {
+                return Vec::new();
+            }
+            match u16_le(pe + 4) {
+                Some(0x8664) => vec![Arch::X86_64],
+                Some(0xaa64) => vec![Arch::Arm64],
+                _ => Vec::new(),
+            }
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Interpreter named by the `#!` line of a script starting with `header`, as the
+/// program and whether it is looked up on the PATH through `env`
+fn shebang_interpreter(header: &[u8]) -> Option<(String, bool)> {
+    let line = header.strip_prefix(b"#!")?;
+    let line = line.split(|byte| *byte == b'\n').next()?;
+    let line = String::from_utf8_lossy(line);
+    let mut words = line.split_whitespace();
+    let interpreter = words.next()?;
+    if Path::new(interpreter).file_name() != Some(OsStr::new("env")) {
+        return Some((interpreter.to_string(), false));
+    }
+    // Skip the options of env and the variables it sets
+    words
+        .find(|word| !word.starts_with('-') && !word.contains('='))
+        .map(|program| (program.to_string(), true))
+}
+
+fn read_header(path: &Path) -> Option<Vec<u8>> {
+    let mut header = Vec::new();
+    File::open(path)
+        .ok()?
+        .take(HEADER_BYTES)
+        .read_to_end(&mut header)
+        .ok()?;
+    Some(header)
+}
+
+/// Binary `command` runs with, looked up in the directories of `path`, and the
+/// architectures it is built for
+///
+/// A script is run by its interpreter, e.g. `npx` by `node`.
+pub fn resolve_runtime(command: &str, path: Option<&OsStr>) -> Option<(PathBuf, Vec<Arch>)> {
+    let program = find_program(command, path)?;
+    let header = read_header(&program)?;
+    let Some((interpreter, on_path)) = shebang_interpreter(&header) else {
+        return Some((program, executable_archs(&header)));
+    };
+    let runtime = if on_path {
+        find_program(&interpreter, path)?
+    } else {
+        PathBuf::from(interpreter)
+    };
+    let archs = read_header(&runtime)
+        .map(|header| executable_archs(&header))
+        .unwrap_or_default();
+    Some((runtime, archs))
+}
+
+/// Whether a binary built for `archs` runs translated on `host`, i.e. under Rosetta
+/// on a Mac
+pub fn runs_translated(host: Option<Arch>, archs: &[Arch]) -> bool {
+    host == Some(Arch::Arm64) && !archs.is_empty() && !archs.contains(&Arch::Arm64)
+}
+
+/// Platform of `declared` a docker image runs natively with on `host`
+///
+/// Platforms are written like `linux/arm64` or `linux/arm64/v8`.
+pub fn select_docker_platform(declared: &[String], host: Arch) -> Option<&String> {
+    declared.iter().find(|platform| {
+        platform
+            .split('/')
+            .nth(1)
+            .and_then(Arch::parse)
+            .is_some_and(|arch| arch == host)
+    })
+}
+
+/// Architectures a server is launched with, shown in its launch plan and
+/// `server/status`
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LaunchArch {
+    /// Architecture of this machine
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host: Option<Arch>,
+    /// Binary the command runs with, the interpreter of a script like `npx`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub runtime: Option<PathBuf>,
+    /// Architectures the runtime is built for, more than one for a universal binary
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub runtime_archs: Vec<Arch>,
+    /// Whether the runtime runs under Rosetta
+    #[serde(default)]
+    pub translated: bool,
+    /// Platform passed to `docker run`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub docker_platform: Option<String>,
+}
+
+impl LaunchArch {
+    /// Architectures of the runtime `command` runs with on `host`
+    pub fn for_command(host: Option<Arch>, command: &str, path: Option<&OsStr>) -> Self {
+        let (runtime, runtime_archs) = match resolve_runtime(command, path) {
+            Some((runtime, archs)) => (Some(runtime), archs),
+            None => (None, Vec::new()),
+        };
+        Self {
+            host,
+            translated: cfg!(target_os = "macos") && runs_translated(host, &runtime_archs),
+            runtime,
+            runtime_archs,
+            docker_platform: None,
+        }
+    }
+
+    /// Why the server may misbehave, when its runtime runs under Rosetta
+    pub fn warning(&self) -> Option<String> {
+        if !self.translated {
+            return None;
+        }
+        let runtime = self
+            .runtime
+            .as_ref()
+            .map(|runtime| runtime.display().to_string())
+            .unwrap_or_default();
+        let archs: Vec<&str> = self.runtime_archs.iter().map(Arch::as_str).collect();
+        Some(format!(
+            "{} is built for {}, it runs under Rosetta on this arm64 machine and native \
+             modules built for arm64 will fail to load",
+            runtime,
+            archs.join(", ")
+        ))
+    }
+}
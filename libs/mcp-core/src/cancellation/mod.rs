@@ -0,0 +1,119 @@
+//! Cancelling tool calls the client gave up on.
+//!
+//! A tool call made with a JSON-RPC id is tracked in [`PendingCalls`] under a call id
+//! built by [`call_id`] from that id and the session it came on: the SSE session, or
+//! the `Mcp-Session-Id` header of a POST to `/mcp`. Calls posted to `/mcp` without the
+//! header can't be cancelled. A second call under the id of a running one is refused.
+//!
+//! A `notifications/cancelled` naming the request ends the wait for the server right
+//! away and the call answers with `cancelled` set. The server is sent a
+//! `notifications/cancelled` of its own for the request it got. Cancelling a call that
+//! already finished, or was never made, does nothing.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use serde_json::Value;
+use tokio::sync::oneshot;
+
+/// Error of a call cancelled by its client
+pub const CANCELLED_ERROR: &str = "Tool call cancelled by the client";
+
+/// Header naming the session of a request posted to `/mcp`
+pub const SESSION_ID_HEADER: &str = "mcp-session-id";
+
+/// Id a call is tracked under, from its session and the JSON-RPC id of its request
+pub fn call_id(session_id: &str, request_id: &Value) -> String {
+    format!("{}/{}", session_id, request_id)
+}
+
+/// Id of the request a `notifications/cancelled` cancels
+pub fn cancelled_request_id(params: &Value) -> Option<&Value> {
+    params
+        .get("requestId")
+        .filter(|id| id.is_string() || id.is_number())
+}
+
+/// A running call, told apart from a later call under the same id by its number
+struct RunningCall {
+    number: u64,
+    cancel: oneshot::Sender<()>,
+}
+
+/// The tool calls running, by call id
+#[derive(Clone, Default)]
+pub struct PendingCalls {
+    calls: Arc<Mutex<HashMap<String, RunningCall>>>,
+    next_number: Arc<AtomicU64>,
+}
+
+impl PendingCalls {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Track a call until the returned guard is dropped, failing when a call runs
+    /// under the same id
+    pub fn register(&self, call_id: &str) -> Result<PendingCall, String> {
+        let mut calls = self.calls.lock().unwrap();
+        if calls.contains_key(call_id) {
+            return Err(format!(
+                "A tool call with id {} is already running",
+                call_id
+            ));
+        }
+        let number = self.next_number.fetch_add(1, Ordering::Relaxed);
+        let (cancel, receiver) = oneshot::channel();
+        calls.insert(call_id.to_string(), RunningCall { number, cancel });
+        Ok(PendingCall {
+            call_id: call_id.to_string(),
+            number,
+            calls: self.clone(),
+            receiver,
+        })
+    }
+
+    /// Cancel a running call, returning false when no such call runs
+    pub fn cancel(&self, call_id: &str) -> bool {
+        match self.calls.lock().unwrap().remove(call_id) {
+            Some(call) => call.cancel.send(()).is_ok(),
+            None => false,
+        }
+    }
+
+    /// Whether a call runs under `call_id`
+    pub fn is_pending(&self, call_id: &str) -> bool {
+        self.calls.lock().unwrap().contains_key(call_id)
+    }
+}
+
+/// A tracked call, no longer tracked once dropped
+pub struct PendingCall {
+    call_id: String,
+    number: u64,
+    calls: PendingCalls,
+    receiver: oneshot::Receiver<()>,
+}
+
+impl PendingCall {
+    /// Wait until the call is cancelled, forever if it isn't
+    pub async fn cancelled(&mut self) {
+        if (&mut self.receiver).await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    }
+}
+
+impl Drop for PendingCall {
+    fn drop(&mut self) {
+        let mut calls = self.calls.calls.lock().unwrap();
+        // A cancelled call may have been followed by another one under its id
+        if calls
+            .get(&self.call_id)
+            .is_some_and(|call| call.number == self.number)
+        {
+            calls.remove(&self.call_id);
+        }
+    }
+}
@@ -0,0 +1,131 @@
+//! What this build of mcp-core answers, for the app to check at startup.
+//!
+//! The app and mcp-core are released separately, and a method the app calls but the core
+//! doesn't have shows up as a blank screen. [`METHODS`] is the dispatch table of the
+//! JSON-RPC server: a method missing from it is not found, whether it has a handler or
+//! not. The schema version of a method is bumped whenever its params or its result
+//! change in a way older callers can't read.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+/// Version of the mcp-core crate
+pub const CORE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// A JSON-RPC method with the version of its params and result
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct MethodCapability {
+    pub name: &'static str,
+    pub schema_version: u32,
+}
+
+const fn method(name: &'static str, schema_version: u32) -> MethodCapability {
+    MethodCapability {
+        name,
+        schema_version,
+    }
+}
+
+/// JSON-RPC methods dispatched by the server, in the order of the dispatch
+pub const METHODS: &[MethodCapability] = &[
+    method("initialize", 1),
+    method("tools/list", 1),
+    method("tools/hidden", 1),
+    method("tools/footprint", 1),
+    method("dashboard/summary", 1),
+    method("tools/describe", 1),
+    method("tools/set_rate_limit", 1),
+    method("tools/set_annotations", 1),
+    method("tools/call", 1),
+    method("notifications/initialized", 1),
+    method("notifications/cancelled", 1),
+    method("prompts/list", 1),
+    method("resources/list", 1),
+    method("resources/read", 1),
+    method("prompts/get", 1),
+    method("registry/install", 1),
+    method("registry/import", 1),
+    method("registry/list", 1),
+    method("registry/preview_install", 1),
+    method("registry/sources", 1),
+    method("registry/save_source", 1),
+    method("registry/delete_source", 1),
+    method("server/config", 1),
+    method("server/config_history", 1),
+    method("server/config_schema", 1),
+    method("server/restart", 1),
+    method("server/start", 1),
+    method("server/stop", 1),
+    method("server/delete", 1),
+    method("servers/bulk_update", 1),
+    method("servers/restart_all", 1),
+    method("server/restart_all", 1),
+    method("server/status", 1),
+    method("server/set_mode", 1),
+    method("server/set_tool_filter", 1),
+    method("server/logs", 1),
+    method("server/history", 1),
+    method("server/launch_plan", 1),
+    method("server/diagnose", 1),
+    method("server/resources", 1),
+    method("operations/get", 1),
+    method("operations/list", 1),
+    method("operations/cancel", 1),
+    method("diagnostics/export", 1),
+    method("system/capabilities", 1),
+    method("system/dump_state", 1),
+    method("system/selftest", 1),
+    method("system/migration_report", 1),
+    method("system/migrate_paths", 1),
+    method("system/cleanup_orphans", 1),
+    method("database/state", 1),
+    method("database/migrate_legacy", 1),
+    method("database/gc", 1),
+    method("hooks/list", 1),
+    method("hooks/set", 1),
+    method("schedules/list", 1),
+    method("schedules/get", 1),
+    method("schedules/create", 1),
+    method("schedules/update", 1),
+    method("schedules/delete", 1),
+    method("schedules/runs", 1),
+    method("webhooks/list", 1),
+    method("webhooks/create", 1),
+    method("webhooks/update", 1),
+    method("webhooks/delete", 1),
+    method("webhooks/test", 1),
+    method("webhooks/dead_letters", 1),
+    method("server/info", 1),
+    method("read_only/set", 1),
+    method("history/undoable", 1),
+    method("history/undo", 1),
+];
+
+/// What the core answers, see [`capabilities`]
+#[derive(Clone, Debug, Serialize)]
+pub struct Capabilities {
+    pub version: &'static str,
+    pub methods: Vec<MethodCapability>,
+    /// Cargo features of the crate, by name
+    pub features: BTreeMap<&'static str, bool>,
+}
+
+/// Whether a JSON-RPC method is in the dispatch table
+pub fn is_supported_method(method: &str) -> bool {
+    METHODS.iter().any(|supported| supported.name == method)
+}
+
+/// Version, methods and features of this build
+pub fn capabilities() -> Capabilities {
+    Capabilities {
+        version: CORE_VERSION,
+        methods: METHODS.to_vec(),
+        features: BTreeMap::from([
+            ("http-server", cfg!(feature = "http-server")),
+            ("lock-debug", cfg!(feature = "lock-debug")),
+            ("registry", cfg!(feature = "registry")),
+            ("webhooks", cfg!(feature = "webhooks")),
+        ]),
+    }
+}
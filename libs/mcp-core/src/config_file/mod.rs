@@ -0,0 +1,318 @@
+//! Servers declared in a configuration file, for headless deployments.
+//!
+//! A `dockmaster.toml`, `.yaml` or `.json` file lists servers by id, with their command,
+//! arguments and env. Strings may reference the environment of Dockmaster with
+//! `${env:NAME}`. The file is reconciled with the installed servers: servers it declares
+//! are added or updated, and servers it stops declaring are removed. Servers installed
+//! interactively are never touched unless the file declares them.
+//!
+//! The servers last applied from the file are remembered, so that a server changed in
+//! the app since then is told apart from a server changed in the file. Such conflicts
+//! are resolved by the [`ConflictPolicy`].
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::models::env_key::canonicalize_env;
+use crate::models::server_id::validate_server_id;
+use crate::models::types::{ServerConfiguration, ServerDefinition, ServerEnvironment};
+
+/// Settings key of the servers last applied from the configuration file
+pub const CONFIG_FILE_APPLIED_SETTING: &str = "config_file_applied";
+
+/// How often a watched configuration file is checked for changes
+pub const CONFIG_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// What to do with a server changed both in the app and in the configuration file
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictPolicy {
+    /// Apply the file, discarding the change made in the app
+    FileWins,
+    /// Keep the server as it is in the app
+    DbWins,
+    /// Keep the server as it is in the app and log a warning
+    #[default]
+    Warn,
+}
+
+impl std::str::FromStr for ConflictPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "file_wins" | "file-wins" => Ok(ConflictPolicy::FileWins),
+            "db_wins" | "db-wins" => Ok(ConflictPolicy::DbWins),
+            "warn" => Ok(ConflictPolicy::Warn),
+            _ => Err(format!(
+                "Unknown conflict policy '{}', expected file_wins, db_wins or warn",
+                s
+            )),
+        }
+    }
+}
+
+/// Contents of a configuration file
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DeclarativeConfig {
+    #[serde(default)]
+    pub settings: ConfigSettings,
+    #[serde(default)]
+    pub servers: BTreeMap<String, DeclaredServer>,
+}
+
+/// Settings of a configuration file
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigSettings {
+    /// Policy used when none is given on the command line
+    #[serde(default)]
+    pub conflict_policy: Option<ConflictPolicy>,
+    /// Hide the tools of the servers from MCP clients
+    #[serde(default)]
+    pub tools_hidden: Option<bool>,
+    /// Keep a latency histogram of the calls of each tool in the Prometheus metrics
+    #[serde(default)]
+    pub tool_latency_metrics: Option<bool>,
+}
+
+/// A server declared in a configuration file
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DeclaredServer {
+    /// Display name, the id when not set
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub description: String,
+    #[serde(rename = "type", alias = "tools_type")]
+    pub tools_type: String,
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub env: HashMap<String, ServerEnvironment>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl DeclaredServer {
+    /// The definition saved for the server `server_id`
+    pub fn to_definition(&self, server_id: &str) -> ServerDefinition {
+        ServerDefinition {
+            name: self.name.clone().unwrap_or_else(|| server_id.to_string()),
+            description: self.description.clone(),
+            enabled: self.enabled,
+            tools_type: self.tools_type.clone(),
+            entry_point: None,
+            configuration: Some(ServerConfiguration {
+                command: Some(self.command.clone()),
+                args: Some(self.args.clone()),
+                env: (!self.env.is_empty()).then(|| self.env.clone()),
+                ..Default::default()
+            }),
+            distribution: None,
+        }
+    }
+}
+
+impl DeclarativeConfig {
+    /// Definitions of the declared servers, by id
+    pub fn definitions(&self) -> BTreeMap<String, ServerDefinition> {
+        self.servers
+            .iter()
+            .map(|(id, server)| (id.clone(), server.to_definition(id)))
+            .collect()
+    }
+}
+
+/// Read and parse a configuration file, its format follows its extension
+pub fn load_config_file(path: &Path) -> Result<DeclarativeConfig, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let extension = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    parse_config(&text, &extension).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// Parse a configuration in the format `extension`, `toml`, `yaml`, `yml` or `json`
+pub fn parse_config(text: &str, extension: &str) -> Result<DeclarativeConfig, String> {
+    let mut value: Value = match extension {
+        "toml" => toml::from_str(text).map_err(|e| format!("Invalid TOML: {}", e))?,
+        "yaml" | "yml" => serde_yaml::from_str(text).map_err(|e| format!("Invalid YAML: {}", e))?,
+        "json" => serde_json::from_str(text).map_err(|e| format!("Invalid JSON: {}", e))?,
+        _ => {
+            return Err(format!(
+                "Unsupported configuration format '{}', expected toml, yaml or json",
+                extension
+            ))
+        }
+    };
+    resolve_env_references(&mut value, &|name| std::env::var(name).ok())?;
+    let mut config: DeclarativeConfig =
+        serde_json::from_value(value).map_err(|e| format!("Invalid configuration: {}", e))?;
+    for (id, server) in &mut config.servers {
+        validate_server_id(id).map_err(|e| format!("Invalid server id '{}': {}", id, e))?;
+        server.env = canonicalize_env(std::mem::take(&mut server.env))
+            .map_err(|e| format!("Invalid env of server '{}': {}", id, e))?;
+    }
+    Ok(config)
+}
+
+/// Replace `${env:NAME}` in every string of `value` with the variable `NAME`
+pub fn resolve_env_references(
+    value: &mut Value,
+    lookup: &dyn Fn(&str) -> Option<String>,
+) -> Result<(), String> {
+    match value {
+        Value::String(s) => {
+            if s.contains("${env:") {
+                *s = resolve_string(s, lookup)?;
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                resolve_env_references(item, lookup)?;
+            }
+        }
+        Value::Object(map) => {
+            for item in map.values_mut() {
+                resolve_env_references(item, lookup)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn resolve_string(s: &str, lookup: &dyn Fn(&str) -> Option<String>) -> Result<String, String> {
+    let mut resolved = String::new();
+    let mut rest = s;
+    while let Some(start) = rest.find("${env:") {
+        resolved.push_str(&rest[..start]);
+        let reference = &rest[start + "${env:".len()..];
+        let end = reference
+            .find('}')
+            .ok_or_else(|| format!("Unterminated env reference in '{}'", s))?;
+        let name = &reference[..end];
+        let value =
+            lookup(name).ok_or_else(|| format!("Environment variable {} is not set", name))?;
+        resolved.push_str(&value);
+        rest = &reference[end + 1..];
+    }
+    resolved.push_str(rest);
+    Ok(resolved)
+}
+
+/// A server changed both in the app and in the configuration file
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ConfigConflict {
+    pub server_id: String,
+    pub reason: String,
+    /// Whether the file was applied, as decided by the policy
+    pub file_applied: bool,
+}
+
+/// Changes that bring the installed servers in line with the configuration file
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct ReconcilePlan {
+    pub add: Vec<String>,
+    pub update: Vec<String>,
+    pub remove: Vec<String>,
+    pub unchanged: Vec<String>,
+    pub conflicts: Vec<ConfigConflict>,
+}
+
+/// Compare the servers declared in the file, the servers last applied from it and
+/// the installed servers
+pub fn plan_reconcile(
+    declared: &BTreeMap<String, ServerDefinition>,
+    applied: &BTreeMap<String, ServerDefinition>,
+    installed: &HashMap<String, ServerDefinition>,
+    policy: ConflictPolicy,
+) -> ReconcilePlan {
+    let mut plan = ReconcilePlan::default();
+    let file_wins = policy == ConflictPolicy::FileWins;
+    let conflict = |plan: &mut ReconcilePlan, server_id: &str, reason: &str| {
+        plan.conflicts.push(ConfigConflict {
+            server_id: server_id.to_string(),
+            reason: reason.to_string(),
+            file_applied: file_wins,
+        });
+        file_wins
+    };
+
+    for (server_id, wanted) in declared {
+        let last_applied = applied.get(server_id);
+        match installed.get(server_id) {
+            None => {
+                if last_applied.is_none()
+                    || conflict(&mut plan, server_id, "Uninstalled in the app")
+                {
+                    plan.add.push(server_id.clone());
+                }
+            }
+            Some(current) if same_server(current, wanted) => {
+                plan.unchanged.push(server_id.clone());
+            }
+            Some(current) => {
+                let reason = match last_applied {
+                    Some(last) if same_server(current, last) => None,
+                    Some(_) => Some("Changed in the app"),
+                    None => Some("Installed in the app"),
+                };
+                if reason.is_none_or(|reason| conflict(&mut plan, server_id, reason)) {
+                    plan.update.push(server_id.clone());
+                }
+            }
+        }
+    }
+
+    for (server_id, last) in applied {
+        if declared.contains_key(server_id) {
+            continue;
+        }
+        if let Some(current) = installed.get(server_id) {
+            if same_server(current, last) || conflict(&mut plan, server_id, "Changed in the app") {
+                plan.remove.push(server_id.clone());
+            }
+        }
+    }
+    plan
+}
+
+/// Whether two definitions describe the same server
+fn same_server(a: &ServerDefinition, b: &ServerDefinition) -> bool {
+    serde_json::to_value(a).ok() == serde_json::to_value(b).ok()
+}
+
+/// A server the reconciliation failed to add, update or remove
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ConfigFailure {
+    pub server_id: String,
+    pub error: String,
+}
+
+/// Outcome of applying a configuration file
+#[derive(Clone, Debug, Default, Serialize)]
+#[non_exhaustive]
+pub struct ReconcileReport {
+    pub path: String,
+    pub added: Vec<String>,
+    pub updated: Vec<String>,
+    pub removed: Vec<String>,
+    pub unchanged: Vec<String>,
+    pub conflicts: Vec<ConfigConflict>,
+    pub failed: Vec<ConfigFailure>,
+}
@@ -0,0 +1,41 @@
+//! Configurations servers had before each change, to undo a change that broke them.
+//!
+//! Applying a configuration change records the configuration it replaces as a
+//! [`ConfigSnapshot`] and restarts the server. When the server then fails to start, the
+//! snapshot is put back and the server restarted again, and the [`ConfigApplyResult`]
+//! says so with `rolled_back` and the error the changed configuration gave. Any snapshot
+//! can also be put back by hand. Only the last [`CONFIG_HISTORY_KEPT`] snapshots of
+//! each server are kept.
+
+use serde::Serialize;
+
+use crate::models::types::ServerConfiguration;
+
+/// Snapshots kept in the history of each server
+pub const CONFIG_HISTORY_KEPT: i64 = 20;
+
+/// A configuration a server had before it was changed
+#[derive(Clone, Debug, Serialize)]
+pub struct ConfigSnapshot {
+    pub id: i64,
+    pub server_id: String,
+    /// When the configuration was replaced, in milliseconds since the epoch
+    pub created_at: u64,
+    pub configuration: ServerConfiguration,
+}
+
+/// Outcome of a configuration change applied with a restart
+#[derive(Clone, Debug, Serialize)]
+pub struct ConfigApplyResult {
+    /// Whether the server runs with the requested configuration
+    pub success: bool,
+    pub message: String,
+    /// Whether the previous configuration was put back as the server failed to start
+    pub rolled_back: bool,
+    /// Why the server failed to start with the requested configuration
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Snapshot of the configuration the change replaced
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snapshot_id: Option<i64>,
+}
@@ -1,17 +1,45 @@
-use std::{collections::HashMap, path::PathBuf, sync::Arc};
+use std::{collections::HashMap, future::Future, path::PathBuf, sync::Arc, time::Duration};
 
 use log::{error, info, warn};
 use tokio::sync::RwLock;
 
+use crate::approvals::ApprovalRegistry;
+use crate::cancellation::PendingCalls;
+use crate::core::mcp_core_builder::MCPCoreBuilder;
 use crate::core::mcp_core_database_ext::McpCoreDatabaseExt;
+use crate::core::mcp_core_health_ext::McpCoreHealthExt;
+#[cfg(feature = "lock-debug")]
+use crate::core::mcp_core_lock_debug_ext::McpCoreLockDebugExt;
 use crate::core::mcp_core_proxy_ext::McpCoreProxyExt;
+use crate::core::mcp_core_relocation_ext::McpCoreRelocationExt;
+use crate::core::mcp_core_schedules_ext::McpCoreSchedulesExt;
+use crate::core::mcp_core_tools_snapshot_ext::McpCoreToolsSnapshotExt;
+use crate::core::mcp_core_webhooks_ext::McpCoreWebhooksExt;
 use crate::database::db_manager::DBManager;
+use crate::events::{DockmasterEvent, EventBus, ToolsUpdate};
+use crate::footprint::ToolsFootprint;
+use crate::health::DEFAULT_HEALTH_CHECK_INTERVAL_SECS;
+use crate::identity::ServerIdentity;
+use crate::instance_lock::{InstanceLock, InstanceLockError};
+use crate::lock_debug::TrackedRwLock;
+#[cfg(feature = "lock-debug")]
+use crate::lock_debug::LOCK_HOLD_WARNING;
+use crate::models::types::ServerDefinition;
+use crate::operations::OperationRegistry;
+use crate::rate_limits::RateLimiter;
+use crate::registry::registry_cache::RegistryCache;
 use crate::registry::server_registry::ServerRegistry;
+use crate::registry::storage::{REGISTRY_LOOKUP_TIMEOUT, STORAGE_RETRY_INTERVAL};
+use crate::utils::clock::Clock;
+use crate::utils::fan_out::{fan_out, FanOut, FanOutLimits};
+use crate::webhooks::WebhookSender;
 
-use crate::mcp_state::mcp_state::MCPState;
+use crate::mcp_server::mcp_tools_service::MCPToolsService;
+use crate::mcp_state::mcp_state::{MCPState, PROCESS_CHECK_INTERVAL};
 
 /// Errors that can occur during initialization
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum InitError {
     /// Error initializing the database
     ApplyMigrations(String),
@@ -19,6 +47,8 @@ pub enum InitError {
     StartHttpServer(String),
     /// Error initializing the MCP server
     InitMcpServer(String),
+    /// Another instance runs on the same data directory, or the lock can't be taken
+    InstanceLock(InstanceLockError),
 }
 
 #[derive(Clone)]
@@ -29,13 +59,37 @@ pub struct MCPCore {
     /// Manager for SQLite database operations
     pub database_manager: Arc<RwLock<DBManager>>,
     /// Registry containing server metadata and configurations
-    pub tool_registry: Arc<RwLock<ServerRegistry>>,
+    pub tool_registry: Arc<TrackedRwLock<ServerRegistry>>,
     /// Central state management for the MCP server
-    pub mcp_state: Arc<RwLock<MCPState>>,
+    pub mcp_state: Arc<TrackedRwLock<MCPState>>,
     /// HTTP server port
     pub port: u16,
     /// App name
     pub app_name: String,
+    /// Bus for progress and state change events
+    pub event_bus: EventBus,
+    /// Long-running management actions tracked as operations
+    pub operations: OperationRegistry,
+    /// Actions requested by models that wait for the user's approval
+    pub approvals: ApprovalRegistry,
+    /// Cache of the server registry, not shared with other cores
+    pub registry_cache: Arc<RegistryCache>,
+    /// Clock used for timeouts and cache expiry
+    pub clock: Arc<dyn Clock>,
+    /// Sends the webhook notifications
+    pub webhook_sender: Arc<dyn WebhookSender>,
+    /// Counts the calls of the tools with a rate limit
+    pub rate_limiter: RateLimiter,
+    /// Tool calls that their client may cancel, see [`crate::cancellation`]
+    pub pending_calls: PendingCalls,
+    /// Time limits of the operations run on several servers at once
+    pub fan_out_limits: FanOutLimits,
+    /// Identity presented to MCP clients unless the user changed it
+    pub server_identity: ServerIdentity,
+    /// Lock on the data directory, taken by `init`, see [`crate::instance_lock`]
+    pub instance_lock: Arc<std::sync::Mutex<Option<InstanceLock>>>,
+    /// Size of the tool list when the cache was last updated, see [`crate::footprint`]
+    pub tools_footprint: Arc<std::sync::RwLock<Option<ToolsFootprint>>>,
 }
 
 impl MCPCore {
@@ -75,25 +129,11 @@ impl MCPCore {
     ) -> Self {
         info!("Creating new MCPCore instance");
         let db_manager = DBManager::with_path(database_path).unwrap();
-        let database_manager = Arc::new(RwLock::new(db_manager.clone()));
-
-        let tool_registry = ServerRegistry::with_db_manager(db_manager.clone());
-        let tool_registry_arc = Arc::new(RwLock::new(tool_registry));
-        let server_tools_arc = Arc::new(RwLock::new(HashMap::new()));
-        let mcp_clients_arc = Arc::new(RwLock::new(HashMap::new()));
-        let mcp_state_arc = Arc::new(RwLock::new(MCPState::new(
-            tool_registry_arc.clone(),
-            server_tools_arc.clone(),
-            mcp_clients_arc.clone(),
-        )));
-        Self {
-            proxy_server_binary_path,
-            database_manager,
-            mcp_state: mcp_state_arc,
-            tool_registry: tool_registry_arc,
-            port,
-            app_name,
-        }
+        MCPCoreBuilder::new(db_manager)
+            .proxy_server_binary_path(proxy_server_binary_path)
+            .port(port)
+            .app_name(app_name)
+            .build()
     }
 
     /// Initializes the MCP server by starting the HTTP server and background services
@@ -103,32 +143,228 @@ impl MCPCore {
     /// - Background services for managing tools and processes
     pub async fn init(&self) -> Result<(), InitError> {
         info!("Initializing MCP server");
+        // Before anything touches the database or spawns a server
+        if let Err(e) = self.acquire_instance_lock().await {
+            error!("{}", e);
+            return Err(InitError::InstanceLock(e));
+        }
         info!("Applying database migrations");
         if let Err(e) = self.apply_database_migrations().await {
             error!("Failed to apply database migrations: {}", e);
             return Err(InitError::ApplyMigrations(e.to_string()));
         }
-        
+        if let Err(e) = self.migrate_legacy_database(false).await {
+            warn!("Failed to migrate the servers of the legacy storage: {}", e);
+        }
+        // Before the servers start with paths of another machine
+        if let Err(e) = self.check_environment().await {
+            warn!("Failed to compare the environment with the last run: {}", e);
+        }
+        match self.collect_database_garbage(true).await {
+            Ok(report) if !report.is_empty() => warn!(
+                "The database has {} unused rows, call database/gc to remove them",
+                report.total()
+            ),
+            Ok(_) => {}
+            Err(e) => warn!("Failed to check the database for unused rows: {}", e),
+        }
+        // Served until the servers below have started
+        self.load_tools_snapshot().await;
+
         // Update registry cache before starting the server
         info!("Updating registry cache before server initialization");
-        match crate::registry::registry_cache::RegistryCache::instance().update_registry_cache().await {
+        match self.registry_cache.update_registry_cache().await {
             Ok(_) => info!("Registry cache successfully updated"),
             Err(e) => warn!("Warning: Failed to update registry cache: {}", e.message),
         }
+        self.spawn_storage_retry();
+        self.spawn_process_monitor();
+        self.spawn_health_monitor();
+        #[cfg(feature = "lock-debug")]
+        self.spawn_lock_watchdog(LOCK_HOLD_WARNING);
         info!("Initializing Background MCP servers");
-        if let Err(e) = self.init_mcp_server().await {
+        let result = self.init_mcp_server().await;
+        self.replace_stale_tools().await;
+        if let Err(e) = result {
             error!("Failed to initialize MCP server: {}", e);
             return Err(InitError::InitMcpServer(e.to_string()));
         }
-        info!("Starting HTTP server");
-        if let Err(e) = crate::http_server::start_http_server(self.clone(), self.port).await {
-            error!("Failed to start HTTP server: {}", e);
-            return Err(InitError::StartHttpServer(e.to_string()));
+        info!("Starting the scheduler");
+        self.start_scheduler();
+        self.start_webhook_dispatcher();
+        #[cfg(feature = "http-server")]
+        {
+            info!("Starting HTTP server");
+            if let Err(e) = crate::http_server::start_http_server(self.clone(), self.port).await {
+                error!("Failed to start HTTP server: {}", e);
+                return Err(InitError::StartHttpServer(e.to_string()));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Read the registry on a blocking thread, `None` when the database didn't answer
+    /// within [`REGISTRY_LOOKUP_TIMEOUT`]
+    ///
+    /// For request handlers, which answer from memory rather than wait for a stalled
+    /// database. The read goes on in the background and still fills the cache.
+    pub async fn registry_lookup<T, F>(&self, read: F) -> Option<Result<T, String>>
+    where
+        T: Send + 'static,
+        F: FnOnce(&ServerRegistry) -> Result<T, String> + Send + 'static,
+    {
+        let tool_registry = self.tool_registry.clone();
+        let lookup = tokio::task::spawn_blocking(move || read(&tool_registry.blocking_read()));
+        tokio::select! {
+            result = lookup => Some(
+                result.unwrap_or_else(|e| Err(format!("Registry lookup failed: {}", e))),
+            ),
+            _ = self.clock.sleep(REGISTRY_LOOKUP_TIMEOUT) => None,
+        }
+    }
+
+    /// Installed servers for request handlers, from memory once they were loaded
+    ///
+    /// Until then the database is read, within [`REGISTRY_LOOKUP_TIMEOUT`]: when it
+    /// doesn't answer no server is returned.
+    pub async fn installed_servers(&self) -> Result<HashMap<String, ServerDefinition>, String> {
+        if let Some(servers) = self.tool_registry.read().await.cached_servers() {
+            return Ok(servers);
+        }
+        match self
+            .registry_lookup(|registry| registry.get_all_servers())
+            .await
+        {
+            Some(servers) => servers,
+            None => {
+                warn!(
+                    "The database didn't answer within {:?}, listing no installed server",
+                    REGISTRY_LOOKUP_TIMEOUT
+                );
+                Ok(HashMap::new())
+            }
         }
+    }
 
+    /// Run an operation on several servers, `concurrency` at a time, within the
+    /// [`FanOutLimits`] of the core, see [`crate::utils::fan_out`]
+    pub async fn fan_out<T, F, L>(
+        &self,
+        members: Vec<(String, F)>,
+        concurrency: usize,
+        on_late: L,
+    ) -> FanOut<T>
+    where
+        T: Send + 'static,
+        F: Future<Output = T> + Send + 'static,
+        L: FnMut(String, T) + Send + 'static,
+    {
+        let fan_out = fan_out(
+            self.clock.clone(),
+            members,
+            self.fan_out_limits,
+            concurrency,
+            on_late,
+        )
+        .await;
+        if fan_out.partial {
+            warn!(
+                "Not waiting any longer for {}, they go on in the background",
+                fan_out.timed_out.join(", ")
+            );
+        }
+        fan_out
+    }
+
+    /// Take the lock on the data directory, kept until released or the core is dropped
+    pub async fn acquire_instance_lock(&self) -> Result<(), InstanceLockError> {
+        if self.instance_lock.lock().unwrap().is_some() {
+            return Ok(());
+        }
+        let data_dir = self.database_manager.read().await.data_dir();
+        let lock = InstanceLock::acquire(&data_dir, self.port, &self.app_name)?;
+        *self.instance_lock.lock().unwrap() = Some(lock);
         Ok(())
     }
 
+    /// Release the lock on the data directory, when shutting down
+    pub fn release_instance_lock(&self) {
+        if self.instance_lock.lock().unwrap().take().is_some() {
+            info!("Released the instance lock");
+        }
+    }
+
+    /// The id of the server `id` refers to, following the alias of a former id
+    pub async fn resolve_server_id(&self, id: &str) -> String {
+        self.tool_registry.read().await.resolve_server_id(id)
+    }
+
+    /// Retry in the background the writes queued while the database is unavailable
+    fn spawn_storage_retry(&self) {
+        let tool_registry = self.tool_registry.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(STORAGE_RETRY_INTERVAL);
+            loop {
+                interval.tick().await;
+                let applied = tool_registry.read().await.retry_storage();
+                if applied > 0 {
+                    info!("Wrote {} pending change(s) to the database", applied);
+                }
+            }
+        });
+    }
+
+    /// Notice in the background the servers whose process exited, and kill those over
+    /// their memory limit
+    fn spawn_process_monitor(&self) {
+        let mcp_state = self.mcp_state.clone();
+        let event_bus = self.event_bus.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(PROCESS_CHECK_INTERVAL);
+            loop {
+                interval.tick().await;
+                let mcp_state = mcp_state.read().await.clone();
+                let mut server_ids = mcp_state.enforce_memory_limits().await;
+                server_ids.extend(mcp_state.reap_exited_servers().await);
+                if server_ids.is_empty() {
+                    continue;
+                }
+                if let Some(tools_service) = MCPToolsService::get_instance().await {
+                    if let Err(e) = tools_service.update_cache().await {
+                        error!("Failed to update tools cache after servers exited: {}", e);
+                    }
+                }
+                event_bus.publish(DockmasterEvent::ToolsUpdated(ToolsUpdate { server_ids }));
+            }
+        });
+    }
+
+    /// Check in the background that the running servers answer, see [`crate::health`]
+    ///
+    /// The settings are read again before each round, so changes apply without a
+    /// restart.
+    fn spawn_health_monitor(&self) {
+        let core = self.clone();
+        tokio::spawn(async move {
+            loop {
+                let settings = core.health_check_settings().await;
+                let Some(interval) = settings.interval() else {
+                    // Off, look at the settings again later
+                    let idle = Duration::from_secs(DEFAULT_HEALTH_CHECK_INTERVAL_SECS);
+                    tokio::time::sleep(idle).await;
+                    continue;
+                };
+                tokio::time::sleep(interval).await;
+                let mcp_state = core.mcp_state.read().await.clone();
+                let server_ids = mcp_state.check_server_health(&settings).await;
+                if !server_ids.is_empty() {
+                    core.publish_tools_update(server_ids).await;
+                }
+            }
+        });
+    }
+
     /// Get the current tool visibility state
     pub async fn are_tools_hidden(&self) -> bool {
         let mcp_state = self.mcp_state.read().await;
@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+
+use log::{info, warn};
+
+use super::mcp_core::MCPCore;
+use crate::approvals::{policy_setting_key, ApprovalRequest};
+use crate::models::types::{ActionPolicy, AuditDecision, AuditEntry, GatedAction};
+
+pub trait McpCoreApprovalsExt {
+    fn action_policy(
+        &self,
+        action: GatedAction,
+    ) -> impl std::future::Future<Output = ActionPolicy> + Send;
+    fn action_policies(
+        &self,
+    ) -> impl std::future::Future<Output = HashMap<GatedAction, ActionPolicy>> + Send;
+    fn set_action_policy(
+        &self,
+        action: GatedAction,
+        policy: ActionPolicy,
+    ) -> impl std::future::Future<Output = Result<(), String>> + Send;
+    fn resolve_approval(
+        &self,
+        id: &str,
+        approved: bool,
+    ) -> impl std::future::Future<Output = Result<ApprovalRequest, String>> + Send;
+    fn record_audit(&self, entry: AuditEntry) -> impl std::future::Future<Output = ()> + Send;
+    fn audit_log(
+        &self,
+        limit: i64,
+    ) -> impl std::future::Future<Output = Result<Vec<AuditEntry>, String>> + Send;
+}
+
+impl McpCoreApprovalsExt for MCPCore {
+    /// Policy of an action, or its default when the user hasn't chosen one
+    async fn action_policy(&self, action: GatedAction) -> ActionPolicy {
+        let registry = self.tool_registry.read().await;
+        registry
+            .get_setting(&policy_setting_key(action))
+            .ok()
+            .and_then(|value| ActionPolicy::from_stored(&value))
+            .unwrap_or_else(|| action.default_policy())
+    }
+
+    /// Policies of all gated actions
+    async fn action_policies(&self) -> HashMap<GatedAction, ActionPolicy> {
+        let mut policies = HashMap::new();
+        for action in GatedAction::ALL {
+            policies.insert(action, self.action_policy(action).await);
+        }
+        policies
+    }
+
+    async fn set_action_policy(
+        &self,
+        action: GatedAction,
+        policy: ActionPolicy,
+    ) -> Result<(), String> {
+        let registry = self.tool_registry.read().await;
+        registry.save_setting(&policy_setting_key(action), policy.as_str())
+    }
+
+    /// Answer a pending approval request and record the decision in the audit log
+    async fn resolve_approval(&self, id: &str, approved: bool) -> Result<ApprovalRequest, String> {
+        let request = self.approvals.resolve(id, approved)?;
+        info!(
+            "User {} {} request {}",
+            if approved { "approved" } else { "rejected" },
+            request.action.as_str(),
+            request.id
+        );
+        let decision = if approved {
+            AuditDecision::Approved
+        } else {
+            AuditDecision::Rejected
+        };
+        self.record_audit(
+            AuditEntry::new(
+                request.action,
+                request.server_id.clone(),
+                request.client.clone(),
+                decision,
+            )
+            .with_detail(request.summary.clone()),
+        )
+        .await;
+        Ok(request)
+    }
+
+    /// Append an entry to the audit log, failures are only logged
+    async fn record_audit(&self, entry: AuditEntry) {
+        let registry = self.tool_registry.read().await;
+        if let Err(e) = registry.append_audit_entry(&entry) {
+            warn!("Failed to write audit log entry: {}", e);
+        }
+    }
+
+    async fn audit_log(&self, limit: i64) -> Result<Vec<AuditEntry>, String> {
+        let registry = self.tool_registry.read().await;
+        registry.get_audit_log(limit)
+    }
+}
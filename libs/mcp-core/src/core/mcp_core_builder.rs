@@ -0,0 +1,157 @@
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
+
+use tokio::sync::RwLock;
+
+use super::mcp_core::MCPCore;
+use crate::approvals::ApprovalRegistry;
+use crate::cancellation::PendingCalls;
+use crate::database::db_manager::DBManager;
+use crate::events::EventBus;
+use crate::identity::ServerIdentity;
+use crate::lock_debug::{
+    TrackedRwLock, LOCK_MCP_CLIENTS, LOCK_MCP_STATE, LOCK_SERVER_TOOLS, LOCK_TOOL_REGISTRY,
+};
+use crate::mcp_state::mcp_state::MCPState;
+use crate::mcp_state::process_launcher::{ProcessLauncher, StdioProcessLauncher};
+use crate::operations::OperationRegistry;
+use crate::rate_limits::RateLimiter;
+use crate::registry::registry_cache::RegistryCache;
+use crate::registry::registry_client::{default_registry_client, RegistryClient};
+use crate::registry::server_registry::ServerRegistry;
+use crate::utils::clock::{Clock, SystemClock};
+use crate::utils::fan_out::FanOutLimits;
+use crate::webhooks::{default_webhook_sender, WebhookSender};
+
+/// Builds an [`MCPCore`] with replaceable dependencies
+///
+/// Every dependency defaults to its production implementation: the shared HTTP
+/// registry cache, child processes spawned over stdio, the system clock and webhooks
+/// posted over HTTP. Tests replace them to exercise failure paths without network
+/// access or real processes.
+pub struct MCPCoreBuilder {
+    db_manager: DBManager,
+    proxy_server_binary_path: PathBuf,
+    port: u16,
+    app_name: String,
+    registry_client: Option<Arc<dyn RegistryClient>>,
+    launcher: Arc<dyn ProcessLauncher>,
+    clock: Arc<dyn Clock>,
+    webhook_sender: Arc<dyn WebhookSender>,
+    fan_out_limits: FanOutLimits,
+    server_identity: ServerIdentity,
+}
+
+impl MCPCoreBuilder {
+    /// Start building a core storing its state with `db_manager`
+    pub fn new(db_manager: DBManager) -> Self {
+        Self {
+            db_manager,
+            proxy_server_binary_path: PathBuf::new(),
+            port: 11011,
+            app_name: "mcp-dockmaster".to_string(),
+            registry_client: None,
+            launcher: Arc::new(StdioProcessLauncher),
+            clock: Arc::new(SystemClock),
+            webhook_sender: default_webhook_sender(),
+            fan_out_limits: FanOutLimits::default(),
+            server_identity: ServerIdentity::default(),
+        }
+    }
+
+    pub fn proxy_server_binary_path(mut self, path: PathBuf) -> Self {
+        self.proxy_server_binary_path = path;
+        self
+    }
+
+    /// HTTP server port
+    pub fn port(mut self, port: u16) -> Self {
+        self.port = port;
+        self
+    }
+
+    pub fn app_name(mut self, app_name: String) -> Self {
+        self.app_name = app_name;
+        self
+    }
+
+    /// Fetch the server registry with `client` rather than the default one
+    pub fn registry_client(mut self, client: Arc<dyn RegistryClient>) -> Self {
+        self.registry_client = Some(client);
+        self
+    }
+
+    /// Start server processes with `launcher`
+    pub fn process_launcher(mut self, launcher: Arc<dyn ProcessLauncher>) -> Self {
+        self.launcher = launcher;
+        self
+    }
+
+    /// Clock used for startup timeouts and registry cache expiry
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Send webhook notifications with `sender`
+    pub fn webhook_sender(mut self, sender: Arc<dyn WebhookSender>) -> Self {
+        self.webhook_sender = sender;
+        self
+    }
+
+    /// Time limits of the operations run on several servers at once
+    pub fn fan_out_limits(mut self, limits: FanOutLimits) -> Self {
+        self.fan_out_limits = limits;
+        self
+    }
+
+    /// Name, version and instructions presented to MCP clients, the user can still
+    /// override each of them
+    pub fn server_identity(mut self, identity: ServerIdentity) -> Self {
+        self.server_identity = identity;
+        self
+    }
+
+    pub fn build(self) -> MCPCore {
+        let database_manager = Arc::new(RwLock::new(self.db_manager.clone()));
+
+        let tool_registry = ServerRegistry::with_db_manager(self.db_manager);
+        let tool_registry_arc = Arc::new(TrackedRwLock::new(LOCK_TOOL_REGISTRY, tool_registry));
+        let event_bus = EventBus::new();
+        let mcp_state = MCPState {
+            launcher: self.launcher,
+            event_bus: event_bus.clone(),
+            clock: self.clock.clone(),
+            ..MCPState::new(
+                tool_registry_arc.clone(),
+                Arc::new(TrackedRwLock::new(LOCK_SERVER_TOOLS, HashMap::new())),
+                Arc::new(TrackedRwLock::new(LOCK_MCP_CLIENTS, HashMap::new())),
+            )
+        };
+        let registry_client = self.registry_client.unwrap_or_else(default_registry_client);
+        let registry_cache = Arc::new(
+            RegistryCache::new(registry_client, self.clock.clone())
+                .with_metrics(mcp_state.metrics.clone()),
+        );
+
+        MCPCore {
+            proxy_server_binary_path: self.proxy_server_binary_path,
+            database_manager,
+            mcp_state: Arc::new(TrackedRwLock::new(LOCK_MCP_STATE, mcp_state)),
+            tool_registry: tool_registry_arc,
+            port: self.port,
+            app_name: self.app_name,
+            operations: OperationRegistry::new(event_bus.clone()),
+            approvals: ApprovalRegistry::new(event_bus.clone()),
+            event_bus,
+            registry_cache,
+            clock: self.clock,
+            webhook_sender: self.webhook_sender,
+            rate_limiter: RateLimiter::new(),
+            pending_calls: PendingCalls::new(),
+            fan_out_limits: self.fan_out_limits,
+            server_identity: self.server_identity,
+            instance_lock: Arc::new(std::sync::Mutex::new(None)),
+            tools_footprint: Arc::new(std::sync::RwLock::new(None)),
+        }
+    }
+}
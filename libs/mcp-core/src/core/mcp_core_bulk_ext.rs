@@ -0,0 +1,214 @@
+use std::future::Future;
+
+use log::{error, info};
+
+use super::mcp_core::MCPCore;
+use crate::lifecycle::LifecycleKind;
+use crate::models::types::{BulkServerResponse, BulkServerResult};
+
+/// Servers started or stopped at the same time by a bulk operation
+pub const BULK_CONCURRENCY: usize = 4;
+
+/// Operations on several servers at once
+///
+/// The new state of every server is saved in a single transaction before any process
+/// is touched, processes are then started or stopped [`BULK_CONCURRENCY`] at a time,
+/// and a single [`ToolsUpdated`](crate::events::DockmasterEvent::ToolsUpdated) event is
+/// published at the end. Servers that take too long are left to finish in the
+/// background and published on their own, see [`MCPCore::fan_out`].
+pub trait McpCoreBulkExt {
+    /// Enable and start, or disable and stop, each of `server_ids`
+    fn set_servers_enabled(
+        &self,
+        server_ids: Vec<String>,
+        enabled: bool,
+    ) -> impl std::future::Future<Output = Result<BulkServerResponse, String>> + Send;
+    /// Restart each of `server_ids`, or every enabled server the user wants running
+    /// when None
+    fn restart_servers(
+        &self,
+        server_ids: Option<Vec<String>>,
+    ) -> impl std::future::Future<Output = Result<BulkServerResponse, String>> + Send;
+}
+
+impl McpCoreBulkExt for MCPCore {
+    async fn set_servers_enabled(
+        &self,
+        server_ids: Vec<String>,
+        enabled: bool,
+    ) -> Result<BulkServerResponse, String> {
+        info!(
+            "Setting {} servers {}",
+            server_ids.len(),
+            if enabled { "enabled" } else { "disabled" }
+        );
+        let (known, not_found) = self.installed_servers(server_ids).await?;
+        self.tool_registry
+            .read()
+            .await
+            .set_servers_enabled(&known, enabled)?;
+
+        let mcp_state = self.mcp_state.read().await.clone();
+        let mut response = self
+            .run_bulk(&known, |server_id| {
+                let mcp_state = mcp_state.clone();
+                async move {
+                    let result = if enabled {
+                        mcp_state.restart_server(&server_id).await
+                    } else if mcp_state.mcp_clients.read().await.contains_key(&server_id) {
+                        mcp_state.stop_server(&server_id).await
+                    } else {
+                        // Not running, there is nothing to stop
+                        Ok(())
+                    };
+                    let done = if enabled { "enabled" } else { "disabled" };
+                    bulk_result(server_id, result, done)
+                }
+            })
+            .await;
+        response.results.extend(not_found);
+        Ok(response)
+    }
+
+    async fn restart_servers(
+        &self,
+        server_ids: Option<Vec<String>>,
+    ) -> Result<BulkServerResponse, String> {
+        let (known, not_found) = match server_ids {
+            Some(server_ids) => {
+                let (known, not_found) = self.installed_servers(server_ids).await?;
+                // Restarting a server is asking for it to run
+                self.tool_registry
+                    .read()
+                    .await
+                    .save_server_run_states(&known, true)?;
+                (known, not_found)
+            }
+            None => {
+                let registry = self.tool_registry.read().await;
+                let run_states = registry.get_server_run_states()?;
+                let mut known: Vec<String> = registry
+                    .get_all_servers()?
+                    .into_iter()
+                    .filter(|(id, server)| {
+                        server.enabled && run_states.get(id).copied().unwrap_or(true)
+                    })
+                    .map(|(id, _)| id)
+                    .collect();
+                known.sort();
+                (known, Vec::new())
+            }
+        };
+        info!("Restarting {} servers", known.len());
+
+        let registry = self.tool_registry.read().await;
+        let disabled: Vec<String> = known
+            .iter()
+            .filter(|id| registry.get_server(id).is_ok_and(|server| !server.enabled))
+            .cloned()
+            .collect();
+        drop(registry);
+
+        let mcp_state = self.mcp_state.read().await.clone();
+        let mut response = self
+            .run_bulk(&known, |server_id| {
+                let mcp_state = mcp_state.clone();
+                let is_disabled = disabled.contains(&server_id);
+                async move {
+                    if is_disabled {
+                        let message = format!("Server '{}' is disabled", server_id);
+                        return bulk_result(server_id, Err(message), "restarted");
+                    }
+                    let result = mcp_state
+                        .restart_server_because(&server_id, LifecycleKind::Restarted)
+                        .await;
+                    bulk_result(server_id, result, "restarted")
+                }
+            })
+            .await;
+        response.results.extend(not_found);
+        Ok(response)
+    }
+}
+
+impl MCPCore {
+    /// Split the requested servers into the ids of installed servers, without
+    /// duplicates, and failed results for the others
+    async fn installed_servers(
+        &self,
+        server_ids: Vec<String>,
+    ) -> Result<(Vec<String>, Vec<BulkServerResult>), String> {
+        let servers = self.tool_registry.read().await.get_all_servers()?;
+        let mut known = Vec::new();
+        let mut not_found = Vec::new();
+        for requested in server_ids {
+            let server_id = self.resolve_server_id(&requested).await;
+            if !servers.contains_key(&server_id) {
+                not_found.push(BulkServerResult {
+                    message: format!("Server '{}' not found", requested),
+                    server_id: requested,
+                    success: false,
+                });
+            } else if !known.contains(&server_id) {
+                known.push(server_id);
+            }
+        }
+        Ok((known, not_found))
+    }
+
+    /// Run `operation` on each of `server_ids`, [`BULK_CONCURRENCY`] at a time, and
+    /// publish the servers whose tools changed
+    async fn run_bulk<F, Fut>(&self, server_ids: &[String], operation: F) -> BulkServerResponse
+    where
+        F: Fn(String) -> Fut,
+        Fut: Future<Output = BulkServerResult> + Send + 'static,
+    {
+        let members = server_ids
+            .iter()
+            .map(|server_id| (server_id.clone(), operation(server_id.clone())))
+            .collect();
+        let core = self.clone();
+        let fan_out = self
+            .fan_out(members, BULK_CONCURRENCY, move |server_id, _| {
+                let core = core.clone();
+                tokio::spawn(async move { core.publish_tools_update(vec![server_id]).await });
+            })
+            .await;
+
+        let finished = fan_out.results.iter().map(|(id, _)| id.clone()).collect();
+        self.publish_tools_update(finished).await;
+        BulkServerResponse {
+            results: fan_out
+                .results
+                .into_iter()
+                .map(|(_, result)| result)
+                .collect(),
+            partial: fan_out.partial,
+            timed_out: fan_out.timed_out,
+        }
+    }
+
+    /// Refresh the tools served to MCP clients once and tell subscribers
+    pub(crate) async fn publish_tools_update(&self, server_ids: Vec<String>) {
+        let mcp_state = self.mcp_state.read().await.clone();
+        mcp_state.publish_tools_update(server_ids).await;
+    }
+}
+
+fn bulk_result(server_id: String, result: Result<(), String>, done: &str) -> BulkServerResult {
+    match result {
+        Ok(()) => BulkServerResult {
+            message: format!("Server '{}' {}", server_id, done),
+            server_id,
+            success: true,
+        },
+        Err(e) => {
+            error!("Bulk operation failed for {}: {}", server_id, e);
+            BulkServerResult {
+                server_id,
+                success: false,
+                message: e,
+            }
+        }
+    }
+}
@@ -0,0 +1,264 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use log::{error, info, warn};
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+
+use super::mcp_core::MCPCore;
+use super::mcp_core_bulk_ext::BULK_CONCURRENCY;
+use super::mcp_core_proxy_ext::McpCoreProxyExt;
+use crate::config_file::{
+    load_config_file, plan_reconcile, ConfigFailure, ConflictPolicy, ReconcileReport,
+    CONFIG_FILE_APPLIED_SETTING, CONFIG_POLL_INTERVAL,
+};
+use crate::lifecycle::LifecycleKind;
+use crate::models::config_check::{resolve_command, CONFIGURATION_FIELD};
+use crate::models::types::{
+    ProvenanceSource, ServerDefinition, ServerProvenance, ServerRegistrationRequest,
+    ToolUninstallRequest,
+};
+
+/// Reloads of a file that changed twice in a row are applied one after the other
+static APPLY_LOCK: Mutex<()> = Mutex::const_new(());
+
+/// Servers managed declaratively by a configuration file, see [`crate::config_file`]
+pub trait McpCoreConfigFileExt {
+    /// Reconcile the installed servers with the file at `path`
+    ///
+    /// `policy` overrides the conflict policy of the file.
+    fn apply_config_file(
+        &self,
+        path: &Path,
+        policy: Option<ConflictPolicy>,
+    ) -> impl std::future::Future<Output = Result<ReconcileReport, String>> + Send;
+    /// Apply the file at `path` as a `config_reload` operation, returning its id
+    fn reload_config_file(&self, path: PathBuf, policy: Option<ConflictPolicy>) -> String;
+    /// Apply the file at `path` now and again each time it changes
+    fn watch_config_file(&self, path: PathBuf, policy: Option<ConflictPolicy>) -> JoinHandle<()>;
+}
+
+impl McpCoreConfigFileExt for MCPCore {
+    async fn apply_config_file(
+        &self,
+        path: &Path,
+        policy: Option<ConflictPolicy>,
+    ) -> Result<ReconcileReport, String> {
+        let _guard = APPLY_LOCK.lock().await;
+        let config = load_config_file(path)?;
+        let policy = policy
+            .or(config.settings.conflict_policy)
+            .unwrap_or_default();
+        let declared = config.definitions();
+        for (server_id, server) in &declared {
+            resolve_command(server.configuration.as_ref(), None, CONFIGURATION_FIELD)
+                .map_err(|e| format!("Server '{}': {}", server_id, e))?;
+        }
+
+        let registry = self.tool_registry.read().await;
+        let applied: BTreeMap<String, ServerDefinition> = registry
+            .get_setting(CONFIG_FILE_APPLIED_SETTING)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        let installed = registry.get_all_servers()?;
+        drop(registry);
+        let plan = plan_reconcile(&declared, &applied, &installed, policy);
+
+        let mut report = ReconcileReport {
+            path: path.display().to_string(),
+            unchanged: plan.unchanged,
+            conflicts: plan.conflicts,
+            ..Default::default()
+        };
+        for conflict in &report.conflicts {
+            match (conflict.file_applied, policy) {
+                (true, _) => info!(
+                    "{}: {}, applying the configuration file",
+                    conflict.server_id, conflict.reason
+                ),
+                (false, ConflictPolicy::Warn) => warn!(
+                    "{}: {}, keeping it and ignoring the configuration file",
+                    conflict.server_id, conflict.reason
+                ),
+                (false, _) => info!(
+                    "{}: {}, keeping it and ignoring the configuration file",
+                    conflict.server_id, conflict.reason
+                ),
+            }
+        }
+
+        let source_id = Some(report.path.clone());
+        for server_id in plan.add {
+            let server = &config.servers[&server_id];
+            let request = ServerRegistrationRequest {
+                server_id: server_id.clone(),
+                server_name: declared[&server_id].name.clone(),
+                description: server.description.clone(),
+                tools_type: server.tools_type.clone(),
+                configuration: declared[&server_id].configuration.clone(),
+                distribution: None,
+                provenance: Some(ServerProvenance::new(
+                    ProvenanceSource::ConfigFile,
+                    source_id.clone(),
+                )),
+                authentication: None,
+                enabled: Some(server.enabled),
+            };
+            let result = self.register_server(request).await.map(|_| ());
+            record(&mut report.added, &mut report.failed, server_id, result);
+        }
+        let updates = plan
+            .update
+            .into_iter()
+            .map(|server_id| {
+                let core = self.clone();
+                let server = declared[&server_id].clone();
+                let id = server_id.clone();
+                (
+                    server_id,
+                    async move { core.apply_server(&id, &server).await },
+                )
+            })
+            .collect();
+        let updates = self
+            .fan_out(updates, BULK_CONCURRENCY, |server_id, result| {
+                if let Err(e) = result {
+                    error!("Failed to apply the configuration of {}: {}", server_id, e);
+                }
+            })
+            .await;
+        for (server_id, result) in updates.results {
+            record(&mut report.updated, &mut report.failed, server_id, result);
+        }
+        // Saved, and still being started or stopped
+        report.updated.extend(updates.timed_out);
+        for server_id in plan.remove {
+            let result = self
+                .uninstall_server(ToolUninstallRequest {
+                    server_id: server_id.clone(),
+                })
+                .await
+                .map(|_| ());
+            record(&mut report.removed, &mut report.failed, server_id, result);
+        }
+
+        if let Some(hidden) = config.settings.tools_hidden {
+            self.mcp_state.read().await.set_tools_hidden(hidden).await?;
+        }
+        if let Some(enabled) = config.settings.tool_latency_metrics {
+            self.mcp_state
+                .read()
+                .await
+                .metrics
+                .set_tool_latency(enabled);
+        }
+        let applied = serde_json::to_string(&declared)
+            .map_err(|e| format!("Failed to serialize the applied servers: {}", e))?;
+        self.tool_registry
+            .read()
+            .await
+            .save_setting(CONFIG_FILE_APPLIED_SETTING, &applied)?;
+
+        let mut changed = report.added.clone();
+        changed.extend(report.updated.iter().cloned());
+        changed.extend(report.removed.iter().cloned());
+        if !changed.is_empty() {
+            self.publish_tools_update(changed).await;
+        }
+        info!(
+            "Applied {}: {} added, {} updated, {} removed, {} unchanged, {} conflicts, {} failed",
+            report.path,
+            report.added.len(),
+            report.updated.len(),
+            report.removed.len(),
+            report.unchanged.len(),
+            report.conflicts.len(),
+            report.failed.len()
+        );
+        Ok(report)
+    }
+
+    fn reload_config_file(&self, path: PathBuf, policy: Option<ConflictPolicy>) -> String {
+        let core = self.clone();
+        self.operations
+            .spawn("config_reload", None, false, move |_| async move {
+                let report = core.apply_config_file(&path, policy).await.map_err(|e| {
+                    error!("Failed to apply the configuration file: {}", e);
+                    e
+                })?;
+                serde_json::to_value(report).map_err(|e| e.to_string())
+            })
+    }
+
+    fn watch_config_file(&self, path: PathBuf, policy: Option<ConflictPolicy>) -> JoinHandle<()> {
+        let core = self.clone();
+        info!("Watching configuration file {}", path.display());
+        tokio::spawn(async move {
+            let mut last_contents: Option<String> = None;
+            let mut read_failed = false;
+            let mut interval = tokio::time::interval(CONFIG_POLL_INTERVAL);
+            loop {
+                interval.tick().await;
+                // Servers stay as they are while the file can't be read
+                let contents = match tokio::fs::read_to_string(&path).await {
+                    Ok(contents) => contents,
+                    Err(e) => {
+                        if !read_failed {
+                            warn!("Failed to read {}: {}", path.display(), e);
+                            read_failed = true;
+                        }
+                        continue;
+                    }
+                };
+                read_failed = false;
+                if last_contents.as_ref() == Some(&contents) {
+                    continue;
+                }
+                info!("Configuration file {} changed", path.display());
+                last_contents = Some(contents);
+                core.reload_config_file(path.clone(), policy);
+            }
+        })
+    }
+}
+
+impl MCPCore {
+    /// Save the definition of an installed server and start or stop it to match
+    async fn apply_server(&self, server_id: &str, server: &ServerDefinition) -> Result<(), String> {
+        let registry = self.tool_registry.read().await;
+        registry.save_server(server_id, server)?;
+        registry.save_server_run_state(server_id, server.enabled)?;
+        drop(registry);
+
+        let mcp_state = self.mcp_state.read().await.clone();
+        if server.enabled {
+            mcp_state
+                .restart_server_because(server_id, LifecycleKind::ConfigRestart)
+                .await
+        } else if mcp_state.mcp_clients.read().await.contains_key(server_id) {
+            mcp_state.stop_server(server_id).await
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Record the outcome of a change to one server
+fn record(
+    done: &mut Vec<String>,
+    failed: &mut Vec<ConfigFailure>,
+    server_id: String,
+    result: Result<(), String>,
+) {
+    match result {
+        Ok(()) => done.push(server_id),
+        Err(error) => {
+            error!(
+                "Failed to apply the configuration of {}: {}",
+                server_id, error
+            );
+            failed.push(ConfigFailure { server_id, error });
+        }
+    }
+}
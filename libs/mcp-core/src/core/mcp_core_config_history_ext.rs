@@ -0,0 +1,226 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use log::{error, info, warn};
+
+use super::mcp_core::MCPCore;
+use super::mcp_core_proxy_ext::McpCoreProxyExt;
+use crate::config_history::{ConfigApplyResult, ConfigSnapshot};
+use crate::lifecycle::LifecycleKind;
+use crate::models::types::{ServerConfigUpdateRequest, ServerConfiguration, ToolUpdateResponse};
+
+/// Configuration changes that are undone when the server can't start with them, see
+/// [`crate::config_history`]
+pub trait McpCoreConfigHistoryExt {
+    /// Update the configuration of a server and restart it, putting the previous
+    /// configuration back when the server fails to start
+    fn apply_server_config(
+        &self,
+        request: ServerConfigUpdateRequest,
+    ) -> impl std::future::Future<Output = Result<ConfigApplyResult, String>> + Send;
+    /// Most recent configurations a server had before each change, newest first
+    fn config_history(
+        &self,
+        server_id: &str,
+        limit: i64,
+    ) -> impl std::future::Future<Output = Result<Vec<ConfigSnapshot>, String>> + Send;
+    /// Put back the configuration of a snapshot and restart the server, like a change
+    fn rollback_server_config(
+        &self,
+        server_id: &str,
+        snapshot_id: i64,
+    ) -> impl std::future::Future<Output = Result<ConfigApplyResult, String>> + Send;
+}
+
+impl McpCoreConfigHistoryExt for MCPCore {
+    async fn apply_server_config(
+        &self,
+        mut request: ServerConfigUpdateRequest,
+    ) -> Result<ConfigApplyResult, String> {
+        request.server_id = self.resolve_server_id(&request.server_id).await;
+        let server_id = request.server_id.clone();
+        let previous = match self.tool_registry.read().await.get_server(&server_id) {
+            Ok(server) => server.configuration.unwrap_or_default(),
+            Err(e) => return Ok(not_applied(e)),
+        };
+
+        let response = self.update_server_config(request).await?;
+        if !response.success {
+            return Ok(not_applied(response.message));
+        }
+        let snapshot_id = self.snapshot_config(&server_id, &previous).await?;
+        Ok(self
+            .restart_or_roll_back(&server_id, previous, snapshot_id)
+            .await)
+    }
+
+    async fn config_history(
+        &self,
+        server_id: &str,
+        limit: i64,
+    ) -> Result<Vec<ConfigSnapshot>, String> {
+        let server_id = self.resolve_server_id(server_id).await;
+        self.tool_registry
+            .read()
+            .await
+            .get_config_history(&server_id, limit)
+    }
+
+    async fn rollback_server_config(
+        &self,
+        server_id: &str,
+        snapshot_id: i64,
+    ) -> Result<ConfigApplyResult, String> {
+        let server_id = self.resolve_server_id(server_id).await;
+        let snapshot = self
+            .tool_registry
+            .read()
+            .await
+            .get_config_snapshot(snapshot_id)?;
+        if snapshot.server_id != server_id {
+            return Ok(not_applied(format!(
+                "Configuration snapshot {} is not a snapshot of server '{}'",
+                snapshot_id, server_id
+            )));
+        }
+        info!(
+            "Rolling back configuration of {} to snapshot {}",
+            server_id, snapshot_id
+        );
+
+        let previous = match self
+            .replace_config(&server_id, snapshot.configuration)
+            .await
+        {
+            Ok(previous) => previous,
+            Err(e) => return Ok(not_applied(e)),
+        };
+        // The rollback is a change like any other, and can be undone the same way
+        let current_id = self.snapshot_config(&server_id, &previous).await?;
+        Ok(self
+            .restart_or_roll_back(&server_id, previous, current_id)
+            .await)
+    }
+}
+
+impl MCPCore {
+    /// Record the configuration a change replaced in the history of the server
+    pub(crate) async fn snapshot_config(
+        &self,
+        server_id: &str,
+        configuration: &ServerConfiguration,
+    ) -> Result<i64, String> {
+        self.tool_registry
+            .read()
+            .await
+            .record_config_snapshot(server_id, now_ms(), configuration)
+    }
+
+    /// Save a new configuration for a server, returning the one it replaced
+    pub(crate) async fn replace_config(
+        &self,
+        server_id: &str,
+        configuration: ServerConfiguration,
+    ) -> Result<ServerConfiguration, String> {
+        let registry = self.tool_registry.write().await;
+        let mut server = registry.get_server(server_id)?;
+        let previous = server.configuration.replace(configuration);
+        registry.save_server(server_id, &server)?;
+        Ok(previous.unwrap_or_default())
+    }
+
+    /// Restart a server after its configuration changed, putting `previous` back and
+    /// restarting again when it fails to start
+    pub(crate) async fn restart_or_roll_back(
+        &self,
+        server_id: &str,
+        previous: ServerConfiguration,
+        snapshot_id: i64,
+    ) -> ConfigApplyResult {
+        let restart = self
+            .restart_server_as(server_id.to_string(), LifecycleKind::ConfigRestart)
+            .await;
+        let failure = match restart_error(restart) {
+            None => {
+                return ConfigApplyResult {
+                    success: true,
+                    message: format!(
+                        "Configuration of '{}' updated and server restarted",
+                        server_id
+                    ),
+                    rolled_back: false,
+                    error: None,
+                    snapshot_id: Some(snapshot_id),
+                };
+            }
+            Some(failure) => failure,
+        };
+        warn!(
+            "Server {} failed to start with its new configuration, rolling back: {}",
+            server_id, failure
+        );
+
+        if let Err(e) = self.replace_config(server_id, previous).await {
+            error!("Failed to roll back configuration of {}: {}", server_id, e);
+            return ConfigApplyResult {
+                success: false,
+                message: format!(
+                    "Server '{}' failed to start and its previous configuration could not \
+                     be restored: {}",
+                    server_id, e
+                ),
+                rolled_back: false,
+                error: Some(failure),
+                snapshot_id: Some(snapshot_id),
+            };
+        }
+        let restored = self
+            .restart_server_as(server_id.to_string(), LifecycleKind::ConfigRestart)
+            .await;
+        let message = match restart_error(restored) {
+            None => format!(
+                "Server '{}' failed to start with the new configuration, the previous one \
+                 was restored",
+                server_id
+            ),
+            Some(e) => format!(
+                "Server '{}' failed to start with the new configuration, the previous one \
+                 was restored but failed to start too: {}",
+                server_id, e
+            ),
+        };
+        ConfigApplyResult {
+            success: false,
+            message,
+            rolled_back: true,
+            error: Some(failure),
+            snapshot_id: Some(snapshot_id),
+        }
+    }
+}
+
+/// Why a restart failed, None when the server started
+fn restart_error(restart: Result<ToolUpdateResponse, String>) -> Option<String> {
+    match restart {
+        Ok(response) if response.success => None,
+        Ok(response) => Some(response.message),
+        Err(e) => Some(e),
+    }
+}
+
+/// Result of a change refused before anything was saved
+fn not_applied(message: String) -> ConfigApplyResult {
+    ConfigApplyResult {
+        success: false,
+        message,
+        rolled_back: false,
+        error: None,
+        snapshot_id: None,
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
@@ -0,0 +1,129 @@
+use super::mcp_core::MCPCore;
+use crate::lifecycle::{now_millis, ServerUptime};
+use crate::models::types::{
+    DashboardSummary, ServerErrorSummary, ServerRestartCount, ServerStatus, ToolCallCount,
+};
+
+/// Tools listed in [`DashboardSummary::top_tools`]
+pub const DASHBOARD_TOP_TOOLS: usize = 5;
+
+/// Characters of the last error kept for each server in error
+pub const DASHBOARD_ERROR_SNIPPET: usize = 200;
+
+/// Counts for the home screen, cheap enough to poll
+///
+/// Everything comes from memory under read locks: the servers as the registry last
+/// loaded them, the statuses of the running clients and the usage counters of the
+/// proxy. The size of the tool list is the one measured when the tools cache was last
+/// updated. The database is only read when no server or lifecycle history was loaded yet,
+/// and exited processes are not reaped, which [`MCPState::server_status`] does.
+///
+/// [`MCPState::server_status`]: crate::mcp_state::mcp_state::MCPState::server_status
+pub trait McpCoreDashboardExt {
+    fn dashboard_summary(
+        &self,
+    ) -> impl std::future::Future<Output = Result<DashboardSummary, String>> + Send;
+}
+
+impl McpCoreDashboardExt for MCPCore {
+    async fn dashboard_summary(&self) -> Result<DashboardSummary, String> {
+        let mcp_state = self.mcp_state.read().await;
+        let servers = {
+            let registry = mcp_state.tool_registry.read().await;
+            match registry.cached_servers() {
+                Some(servers) => servers,
+                None => registry.get_all_servers()?,
+            }
+        };
+
+        let mut summary = DashboardSummary {
+            servers: servers.len(),
+            ..Default::default()
+        };
+        {
+            let mcp_clients = mcp_state.mcp_clients.read().await;
+            let launch_errors = mcp_state.launch_errors.read().await;
+            let mut server_ids: Vec<&String> = servers.keys().collect();
+            server_ids.sort();
+            for server_id in server_ids {
+                let status = match mcp_clients.get(server_id) {
+                    Some(mcp_client) => mcp_client.server_status.clone(),
+                    None => match launch_errors.get(server_id) {
+                        Some(error) => ServerStatus::Error(error.clone()),
+                        None => ServerStatus::Stopped,
+                    },
+                };
+                match status {
+                    ServerStatus::Running => summary.running += 1,
+                    ServerStatus::Starting => summary.starting += 1,
+                    ServerStatus::Stopped => summary.stopped += 1,
+                    ServerStatus::NeedsConfiguration => summary.needs_configuration += 1,
+                    ServerStatus::Error(error) | ServerStatus::Faulted(error) => {
+                        summary.errored += 1;
+                        summary.servers_in_error.push(ServerErrorSummary {
+                            server_id: server_id.clone(),
+                            name: servers[server_id].name.clone(),
+                            error: error.chars().take(DASHBOARD_ERROR_SNIPPET).collect(),
+                        });
+                    }
+                }
+            }
+        }
+
+        {
+            let registry = mcp_state.tool_registry.read().await;
+            let mcp_clients = mcp_state.mcp_clients.read().await;
+            let now = now_millis();
+            for server_id in servers.keys() {
+                let started_at = mcp_clients
+                    .get(server_id)
+                    .map(|mcp_client| mcp_client.started_at);
+                let history = registry.get_lifecycle_history(server_id);
+                let uptime = ServerUptime::new(started_at, &history, now);
+                if uptime.restarts_last_hour > 0 {
+                    summary.restarts_last_hour += uptime.restarts_last_hour;
+                    summary.flapping_servers.push(ServerRestartCount {
+                        server_id: server_id.clone(),
+                        restarts: uptime.restarts_last_hour,
+                        uptime_secs: uptime.uptime_secs,
+                    });
+                }
+            }
+            summary.flapping_servers.sort_by(|a, b| {
+                b.restarts
+                    .cmp(&a.restarts)
+                    .then_with(|| a.server_id.cmp(&b.server_id))
+            });
+        }
+
+        let hidden = *mcp_state.are_tools_hidden.read().await;
+        for (server_id, tools) in mcp_state.server_tools.read().await.iter() {
+            let enabled = servers.get(server_id).is_some_and(|server| server.enabled);
+            summary.tools += tools.len();
+            summary.tools_disabled += tools
+                .iter()
+                .filter(|tool| !enabled || !tool.is_active)
+                .count();
+        }
+        if hidden {
+            summary.tools_hidden = summary.tools;
+        }
+
+        summary.calls_last_hour = mcp_state.tool_stats.recent_calls();
+        summary.top_tools = mcp_state
+            .tool_stats
+            .most_used(DASHBOARD_TOP_TOOLS)
+            .into_iter()
+            .map(|(server_id, tool_name, usage)| ToolCallCount {
+                server_id,
+                tool_name,
+                calls: usage.calls,
+            })
+            .collect();
+        if let Some(footprint) = self.tools_footprint.read().unwrap().as_ref() {
+            summary.tools_tokens = footprint.tokens;
+            summary.servers_over_budget = footprint.servers_over_budget();
+        }
+        Ok(summary)
+    }
+}
@@ -1,19 +1,39 @@
 use super::mcp_core::MCPCore;
+use crate::database::gc::GarbageReport;
+use crate::database::legacy::{LegacyMigrationReport, LEGACY_MIGRATION_SETTING};
+use crate::database::state::{DatabaseState, DatabaseStatus};
 
 pub trait McpCoreDatabaseExt {
     fn check_database_exists(
         &self,
     ) -> impl std::future::Future<Output = Result<bool, String>> + Send;
+    fn database_state(&self) -> impl std::future::Future<Output = DatabaseState> + Send;
     fn apply_database_migrations(
         &self,
     ) -> impl std::future::Future<Output = Result<(), String>> + Send;
     fn clear_database(&self) -> impl std::future::Future<Output = Result<(), String>> + Send;
+    fn migrate_legacy_database(
+        &self,
+        force: bool,
+    ) -> impl std::future::Future<Output = Result<LegacyMigrationReport, String>> + Send;
+    fn collect_database_garbage(
+        &self,
+        dry_run: bool,
+    ) -> impl std::future::Future<Output = Result<GarbageReport, String>> + Send;
 }
 
 impl McpCoreDatabaseExt for MCPCore {
-    /// Check if the database exists and has data
+    /// Check if the database exists and has data, see [`Self::database_state`]
     async fn check_database_exists(&self) -> Result<bool, String> {
-        self.database_manager.read().await.check_exists()
+        let state = self.database_state().await;
+        match (state.status, state.error) {
+            (DatabaseStatus::Unreadable, Some(e)) => Err(e.to_string()),
+            _ => Ok(state.server_count > 0),
+        }
+    }
+    /// Whether the database is new, empty, in use, waits for a migration or can't be read
+    async fn database_state(&self) -> DatabaseState {
+        self.database_manager.read().await.state()
     }
     async fn apply_database_migrations(&self) -> Result<(), String> {
         self.database_manager.write().await.apply_migrations()
@@ -25,4 +45,23 @@ impl McpCoreDatabaseExt for MCPCore {
             Err(e) => Err(format!("Failed to clear database: {}", e)),
         }
     }
+    /// Save the servers of the storage of older builds, once unless `force`
+    async fn migrate_legacy_database(&self, force: bool) -> Result<LegacyMigrationReport, String> {
+        let db = self.database_manager.read().await;
+        if !force
+            && db
+                .get_setting(LEGACY_MIGRATION_SETTING)
+                .is_ok_and(|v| v == "true")
+        {
+            return Ok(LegacyMigrationReport::default());
+        }
+        let report = db.migrate_legacy_tools()?;
+        db.save_setting(LEGACY_MIGRATION_SETTING, "true")?;
+        Ok(report)
+    }
+    /// Remove the rows of removed servers and of servers that can't start, see
+    /// [`crate::database::gc`]
+    async fn collect_database_garbage(&self, dry_run: bool) -> Result<GarbageReport, String> {
+        self.database_manager.read().await.collect_garbage(dry_run)
+    }
 }
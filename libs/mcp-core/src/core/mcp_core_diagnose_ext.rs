@@ -0,0 +1,443 @@
+use std::ffi::OsStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use log::info;
+use serde_json::json;
+
+use super::mcp_core::MCPCore;
+use super::mcp_core_diagnostics_ext::{now_secs, runtime_version};
+use crate::arch::{host_arch, Arch, LaunchArch};
+use crate::diagnose::{
+    describe_fatal_lines, fatal_stderr_lines, find_program, missing_required_env, node_major,
+    DiagnosisStatus, DiagnosisStep, ServerDiagnosis, DIAGNOSE_PROBE_TOOL, DIAGNOSE_STEP_TIMEOUT,
+    MIN_NODE_MAJOR,
+};
+use crate::diagnostics::{DiagnosticsBundle, DiagnosticsExport};
+use crate::mcp_state::mcp_state::MCPClient;
+use crate::mcp_state::reconnect::DeadConnection;
+use crate::mcp_state::startup_capture::StartupCapture;
+use crate::models::config_check::{resolve_command, CONFIGURATION_FIELD};
+use crate::models::types::{ServerDefinition, ServerStatus};
+use crate::utils::command::server_path;
+
+/// Troubleshooting checklist of a server, see [`crate::diagnose`]
+pub trait McpCoreDiagnoseExt {
+    /// Run the checklist on a server, failing only when the server doesn't exist
+    fn diagnose_server(
+        &self,
+        server_id: &str,
+    ) -> impl std::future::Future<Output = Result<ServerDiagnosis, String>> + Send;
+    /// Write a report to the `diagnostics` folder of the data directory, with the
+    /// redacted configuration of the server
+    fn export_server_diagnosis(
+        &self,
+        diagnosis: &ServerDiagnosis,
+    ) -> impl std::future::Future<Output = Result<DiagnosticsExport, String>> + Send;
+}
+
+impl McpCoreDiagnoseExt for MCPCore {
+    async fn diagnose_server(&self, server_id: &str) -> Result<ServerDiagnosis, String> {
+        let server_id = self.resolve_server_id(server_id).await;
+        let server = self.tool_registry.read().await.get_server(&server_id)?;
+        info!("Diagnosing server {}", server_id);
+
+        let mut steps = vec![
+            check_command(&server),
+            check_runtime(&server.tools_type).await,
+            check_env(&server),
+        ];
+
+        let mcp_state = self.mcp_state.read().await.clone();
+        let status = mcp_state.server_status(&server_id).await;
+        let mcp_client = mcp_state.mcp_clients.read().await.get(&server_id).cloned();
+        steps.push(check_process(&server, &status, mcp_client.as_ref()));
+        steps.push(check_initialize(&status));
+        match mcp_client.as_ref() {
+            Some(mcp_client) if matches!(status, ServerStatus::Running) => {
+                steps.push(self.check_tools_list(mcp_client).await);
+                steps.push(self.check_round_trip(mcp_client).await);
+            }
+            _ => {
+                let details = "The server is not running".to_string();
+                steps.push(DiagnosisStep::skipped(
+                    "tools_list",
+                    TOOLS_LIST_TITLE,
+                    details.clone(),
+                ));
+                steps.push(DiagnosisStep::skipped(
+                    "round_trip",
+                    ROUND_TRIP_TITLE,
+                    details,
+                ));
+            }
+        }
+        let stderr = match &mcp_client {
+            Some(mcp_client) => StartupCapture::open(&mcp_client.spawn_id).stderr_tail(),
+            None => match status {
+                ServerStatus::Error(error) | ServerStatus::Faulted(error) => error,
+                _ => String::new(),
+            },
+        };
+        steps.push(check_stderr(&stderr));
+
+        Ok(ServerDiagnosis {
+            healthy: steps
+                .iter()
+                .all(|step| step.status != DiagnosisStatus::Fail),
+            server_id,
+            server_name: server.name,
+            created_at: now_ms(),
+            steps,
+        })
+    }
+
+    async fn export_server_diagnosis(
+        &self,
+        diagnosis: &ServerDiagnosis,
+    ) -> Result<DiagnosticsExport, String> {
+        let mut bundle = DiagnosticsBundle::new();
+        // The configuration goes first so its secrets are scrubbed from the report
+        if let Ok(server) = self
+            .tool_registry
+            .read()
+            .await
+            .get_server(&diagnosis.server_id)
+        {
+            bundle.add_server_definitions("server.json", [(&diagnosis.server_id, &server)]);
+        }
+        bundle.add_json("diagnosis.json", diagnosis);
+        bundle.add_json(
+            "system.json",
+            &json!({
+                "app": self.app_name,
+                "core_version": env!("CARGO_PKG_VERSION"),
+                "os": std::env::consts::OS,
+                "arch": std::env::consts::ARCH,
+            }),
+        );
+
+        let path = self.diagnostics_dir().await?.join(format!(
+            "dockmaster-diagnosis-{}-{}.zip",
+            diagnosis.server_id,
+            now_secs()
+        ));
+        let export = bundle.redact().write(&path)?;
+        info!(
+            "Exported the diagnosis of {} to {}",
+            diagnosis.server_id,
+            export.path.display()
+        );
+        Ok(export)
+    }
+}
+
+const COMMAND_TITLE: &str = "Command found on the PATH";
+const RUNTIME_TITLE: &str = "Runtime installed and recent enough";
+const ENV_TITLE: &str = "Required environment variables set";
+const PROCESS_TITLE: &str = "Process running";
+const INITIALIZE_TITLE: &str = "Initialize handshake answered";
+const TOOLS_LIST_TITLE: &str = "Tools listed";
+const ROUND_TRIP_TITLE: &str = "Requests answered";
+const STDERR_TITLE: &str = "No fatal error in stderr";
+
+impl MCPCore {
+    /// Ask the server for its tools
+    async fn check_tools_list(&self, mcp_client: &MCPClient) -> DiagnosisStep {
+        let listed = tokio::select! {
+            listed = mcp_client.client.list_tools(None) => Some(listed),
+            _ = self.clock.sleep(DIAGNOSE_STEP_TIMEOUT) => None,
+        };
+        match listed {
+            Some(Ok(result)) => DiagnosisStep::pass(
+                "tools_list",
+                TOOLS_LIST_TITLE,
+                format!(
+                    "The server lists {} tools",
+                    result["tools"].as_array().map_or(0, Vec::len)
+                ),
+            ),
+            Some(Err(e)) => DiagnosisStep::fail(
+                "tools_list",
+                TOOLS_LIST_TITLE,
+                format!("tools/list failed: {}", e),
+                "The server runs but can't list its tools, restart it and check its stderr"
+                    .to_string(),
+            ),
+            None => DiagnosisStep::fail(
+                "tools_list",
+                TOOLS_LIST_TITLE,
+                no_answer("tools/list"),
+                "The server is stuck, restart it".to_string(),
+            ),
+        }
+    }
+
+    /// Call a tool the server doesn't have, any answer, errors included, proves the
+    /// server reads requests and writes responses
+    async fn check_round_trip(&self, mcp_client: &MCPClient) -> DiagnosisStep {
+        let started = self.clock.now();
+        let called = tokio::select! {
+            called = mcp_client
+                .client
+                .call_tool(DIAGNOSE_PROBE_TOOL, json!({}), None) => Some(called),
+            _ = self.clock.sleep(DIAGNOSE_STEP_TIMEOUT) => None,
+        };
+        let elapsed = self.clock.now().saturating_duration_since(started);
+        let dead = match called {
+            None => {
+                return DiagnosisStep::fail(
+                    "round_trip",
+                    ROUND_TRIP_TITLE,
+                    no_answer("A tool call"),
+                    "The server is stuck, restart it".to_string(),
+                )
+            }
+            Some(Ok(_)) => None,
+            Some(Err(e)) => DeadConnection::of(&e.to_string()).map(|_| e.to_string()),
+        };
+        match dead {
+            None => DiagnosisStep::pass(
+                "round_trip",
+                ROUND_TRIP_TITLE,
+                format!(
+                    "A call to a missing tool was answered in {} ms",
+                    elapsed.as_millis()
+                ),
+            ),
+            Some(e) => DiagnosisStep::fail(
+                "round_trip",
+                ROUND_TRIP_TITLE,
+                format!("The connection to the server is lost: {}", e),
+                "Restart the server".to_string(),
+            ),
+        }
+    }
+}
+
+/// The command of the server can be found
+fn check_command(server: &ServerDefinition) -> DiagnosisStep {
+    let command = match resolve_command(
+        server.configuration.as_ref(),
+        server.entry_point.as_deref(),
+        CONFIGURATION_FIELD,
+    ) {
+        Ok((command, _)) => command,
+        Err(errors) => {
+            return DiagnosisStep::fail(
+                "command",
+                COMMAND_TITLE,
+                errors.to_string(),
+                "Set the command of the server in its configuration".to_string(),
+            )
+        }
+    };
+    let path = server_path();
+    match find_program(&command, path.as_deref().map(OsStr::new)) {
+        Some(program) => DiagnosisStep::pass(
+            "command",
+            COMMAND_TITLE,
+            format!("'{}' is {}", command, program.display()),
+        ),
+        None => {
+            let hint = match server.tools_type.as_str() {
+                "node" | "nodejs" => format!("Install Node.js, which provides '{}'", command),
+                "python" => format!("Install uv, which provides '{}'", command),
+                "docker" => "Install Docker".to_string(),
+                _ => format!(
+                    "Install '{}' or use its absolute path as the command",
+                    command
+                ),
+            };
+            DiagnosisStep::fail(
+                "command",
+                COMMAND_TITLE,
+                format!("'{}' was not found on the PATH", command),
+                hint,
+            )
+        }
+    }
+}
+
+/// The runtime of the server type is installed, in a version it can run on
+async fn check_runtime(tools_type: &str) -> DiagnosisStep {
+    let (name, program, arg, install_hint) = match tools_type {
+        "node" | "nodejs" => (
+            "Node.js",
+            "node",
+            "-v",
+            format!("Install Node.js {} or later", MIN_NODE_MAJOR),
+        ),
+        "python" => ("uv", "uv", "version", "Install uv".to_string()),
+        "docker" => ("Docker", "docker", "-v", "Install Docker".to_string()),
+        _ => {
+            return DiagnosisStep::skipped(
+                "runtime",
+                RUNTIME_TITLE,
+                format!("No runtime is checked for servers of type '{}'", tools_type),
+            )
+        }
+    };
+    let Some(version) = runtime_version(program, arg).await else {
+        return DiagnosisStep::fail(
+            "runtime",
+            RUNTIME_TITLE,
+            format!("{} is not installed", name),
+            install_hint,
+        );
+    };
+    match node_major(&version).filter(|_| program == "node") {
+        Some(major) if major < MIN_NODE_MAJOR => DiagnosisStep::fail(
+            "runtime",
+            RUNTIME_TITLE,
+            format!(
+                "Node.js {} is older than version {}",
+                version, MIN_NODE_MAJOR
+            ),
+            format!("Update Node.js to version {} or later", MIN_NODE_MAJOR),
+        ),
+        _ => {
+            let mut details = format!("{} {}", name, version);
+            let path = server_path();
+            let arch =
+                LaunchArch::for_command(host_arch(), program, path.as_deref().map(OsStr::new));
+            if !arch.runtime_archs.is_empty() {
+                let archs: Vec<&str> = arch.runtime_archs.iter().map(Arch::as_str).collect();
+                details = format!("{} ({})", details, archs.join(", "));
+            }
+            if let Some(warning) = arch.warning() {
+                details = format!("{}. {}", details, warning);
+            }
+            DiagnosisStep::pass("runtime", RUNTIME_TITLE, details)
+        }
+    }
+}
+
+/// Every required environment variable has a value
+fn check_env(server: &ServerDefinition) -> DiagnosisStep {
+    let missing = missing_required_env(server.configuration.as_ref());
+    if missing.is_empty() {
+        return DiagnosisStep::pass(
+            "env",
+            ENV_TITLE,
+            "Every required variable has a value".to_string(),
+        );
+    }
+    DiagnosisStep::fail(
+        "env",
+        ENV_TITLE,
+        format!("No value for {}", missing.join(", ")),
+        "Set the missing variables in the configuration of the server".to_string(),
+    )
+}
+
+fn check_process(
+    server: &ServerDefinition,
+    status: &ServerStatus,
+    mcp_client: Option<&MCPClient>,
+) -> DiagnosisStep {
+    match status {
+        ServerStatus::Running => {
+            let details = match mcp_client.and_then(|client| client.process.as_ref()) {
+                Some(process) => format!("Process {} is running", process.pid),
+                None => "The process is running".to_string(),
+            };
+            DiagnosisStep::pass("process", PROCESS_TITLE, details)
+        }
+        ServerStatus::Starting => DiagnosisStep::fail(
+            "process",
+            PROCESS_TITLE,
+            "The server is still starting".to_string(),
+            "Wait for it to start, then run the checklist again".to_string(),
+        ),
+        ServerStatus::Stopped if !server.enabled => DiagnosisStep::fail(
+            "process",
+            PROCESS_TITLE,
+            "The server is disabled".to_string(),
+            "Enable the server".to_string(),
+        ),
+        ServerStatus::Stopped => DiagnosisStep::fail(
+            "process",
+            PROCESS_TITLE,
+            "The server is stopped".to_string(),
+            "Start the server".to_string(),
+        ),
+        ServerStatus::NeedsConfiguration => DiagnosisStep::fail(
+            "process",
+            PROCESS_TITLE,
+            "The server was not started, required variables have no value".to_string(),
+            "Set the missing variables, the server starts once they are saved".to_string(),
+        ),
+        ServerStatus::Error(error) => DiagnosisStep::fail(
+            "process",
+            PROCESS_TITLE,
+            error.clone(),
+            "Fix the failed steps above and restart the server".to_string(),
+        ),
+        ServerStatus::Faulted(error) => DiagnosisStep::fail(
+            "process",
+            PROCESS_TITLE,
+            format!(
+                "The server failed to start too many times in a row: {}",
+                error
+            ),
+            "Fix the failed steps above, then edit its configuration or restart it with force"
+                .to_string(),
+        ),
+    }
+}
+
+/// A running server answered the handshake when it started, a server that failed to
+/// start says why in its error
+fn check_initialize(status: &ServerStatus) -> DiagnosisStep {
+    match status {
+        ServerStatus::Running => DiagnosisStep::pass(
+            "initialize",
+            INITIALIZE_TITLE,
+            "The server answered the handshake when it started".to_string(),
+        ),
+        ServerStatus::Error(error) | ServerStatus::Faulted(error) => DiagnosisStep::fail(
+            "initialize",
+            INITIALIZE_TITLE,
+            error.clone(),
+            "Run the command of the server in a terminal to see why it doesn't start".to_string(),
+        ),
+        _ => DiagnosisStep::skipped(
+            "initialize",
+            INITIALIZE_TITLE,
+            "The server is not running".to_string(),
+        ),
+    }
+}
+
+fn check_stderr(stderr: &str) -> DiagnosisStep {
+    let fatal = fatal_stderr_lines(stderr);
+    if fatal.is_empty() {
+        let details = if stderr.is_empty() {
+            "Nothing was written to stderr"
+        } else {
+            "No fatal error in the end of stderr"
+        };
+        return DiagnosisStep::pass("stderr", STDERR_TITLE, details.to_string());
+    }
+    DiagnosisStep::fail(
+        "stderr",
+        STDERR_TITLE,
+        describe_fatal_lines(&fatal),
+        "The server reported a fatal error, fix what it says and restart it".to_string(),
+    )
+}
+
+fn no_answer(request: &str) -> String {
+    format!(
+        "{} got no answer within {} seconds",
+        request,
+        DIAGNOSE_STEP_TIMEOUT.as_secs()
+    )
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
@@ -0,0 +1,185 @@
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use log::info;
+use serde_json::json;
+use sysinfo::System;
+
+use super::mcp_core::MCPCore;
+use super::mcp_core_proxy_ext::McpCoreProxyExt;
+use crate::diagnostics::{
+    proxy_server_log_dir, DiagnosticsBundle, DiagnosticsExport, DiagnosticsPreview,
+    RedactedDiagnostics,
+};
+use crate::utils::command::CommandWrappedInShellBuilder;
+
+/// Entries of the audit log included in a diagnostics bundle
+const DIAGNOSTICS_AUDIT_ENTRIES: i64 = 100;
+
+/// What to add to a diagnostics bundle besides the core state
+#[derive(Clone, Debug, Default)]
+pub struct DiagnosticsOptions {
+    /// Version of the app embedding the core
+    pub app_version: Option<String>,
+    /// Directories whose `.log` files are included, besides the proxy server logs
+    pub log_dirs: Vec<PathBuf>,
+}
+
+pub trait McpCoreDiagnosticsExt {
+    fn collect_diagnostics(
+        &self,
+        options: &DiagnosticsOptions,
+    ) -> impl std::future::Future<Output = RedactedDiagnostics> + Send;
+    fn preview_diagnostics(
+        &self,
+        options: &DiagnosticsOptions,
+    ) -> impl std::future::Future<Output = DiagnosticsPreview> + Send;
+    fn export_diagnostics(
+        &self,
+        options: &DiagnosticsOptions,
+    ) -> impl std::future::Future<Output = Result<DiagnosticsExport, String>> + Send;
+}
+
+impl McpCoreDiagnosticsExt for MCPCore {
+    /// Gather the state needed to reproduce an issue, with secrets removed
+    ///
+    /// Parts that can't be read are replaced by their error so that a broken database
+    /// or registry still produces a useful bundle.
+    async fn collect_diagnostics(&self, options: &DiagnosticsOptions) -> RedactedDiagnostics {
+        let mut bundle = DiagnosticsBundle::new();
+
+        bundle.add_json(
+            "system.json",
+            &json!({
+                "app": self.app_name,
+                "app_version": options.app_version,
+                "core_version": env!("CARGO_PKG_VERSION"),
+                "os": std::env::consts::OS,
+                "os_version": System::long_os_version(),
+                "arch": std::env::consts::ARCH,
+                "runtimes": {
+                    "node": runtime_version("node", "-v").await,
+                    "uv": runtime_version("uv", "version").await,
+                    "docker": runtime_version("docker", "-v").await,
+                },
+            }),
+        );
+
+        // Configurations go first so their secrets are known to the redaction pass
+        let servers = self.tool_registry.read().await.get_all_servers();
+        match &servers {
+            Ok(servers) => bundle.add_server_definitions("servers/config.json", servers),
+            Err(e) => bundle.add_text("servers/config.error.txt", e.clone()),
+        }
+
+        match self.list_servers().await {
+            Ok(servers) => {
+                let statuses: Vec<_> = servers
+                    .iter()
+                    .map(|server| {
+                        json!({
+                            "id": server.id,
+                            "enabled": server.definition.enabled,
+                            "status": server.status,
+                            "tool_count": server.tool_count,
+                        })
+                    })
+                    .collect();
+                bundle.add_json("servers/status.json", &statuses);
+            }
+            Err(e) => bundle.add_text("servers/status.error.txt", e),
+        }
+
+        {
+            let registry = self.tool_registry.read().await;
+            match registry.get_server_processes() {
+                Ok(processes) => bundle.add_json("servers/processes.json", &processes),
+                Err(e) => bundle.add_text("servers/processes.error.txt", e),
+            }
+            match registry.get_audit_log(DIAGNOSTICS_AUDIT_ENTRIES) {
+                Ok(entries) => bundle.add_json("audit_log.json", &entries),
+                Err(e) => bundle.add_text("audit_log.error.txt", e),
+            }
+        }
+
+        bundle.add_json("operations.json", &self.operations.list());
+
+        bundle.add_json(
+            "registry.json",
+            &json!({
+                "cache_age_secs": self.registry_cache.age().map(|age| age.as_secs()),
+            }),
+        );
+
+        match self.database_manager.read().await.info() {
+            Ok(info) => bundle.add_json("database.json", &info),
+            Err(e) => bundle.add_text("database.error.txt", e),
+        }
+
+        bundle.add_log_tails("logs/proxy", &proxy_server_log_dir());
+        for (index, dir) in options.log_dirs.iter().enumerate() {
+            bundle.add_log_tails(&format!("logs/{}", index), dir);
+        }
+
+        bundle.redact()
+    }
+
+    /// List the files an export would contain, without writing anything
+    async fn preview_diagnostics(&self, options: &DiagnosticsOptions) -> DiagnosticsPreview {
+        self.collect_diagnostics(options).await.preview()
+    }
+
+    /// Write a diagnostics bundle to the `diagnostics` folder of the data directory
+    async fn export_diagnostics(
+        &self,
+        options: &DiagnosticsOptions,
+    ) -> Result<DiagnosticsExport, String> {
+        let path = self
+            .diagnostics_dir()
+            .await?
+            .join(format!("dockmaster-diagnostics-{}.zip", now_secs()));
+
+        let export = self.collect_diagnostics(options).await.write(&path)?;
+        info!(
+            "Exported diagnostics to {} ({} files, {} values redacted)",
+            export.path.display(),
+            export.files.len(),
+            export.redacted_values
+        );
+        Ok(export)
+    }
+}
+
+impl MCPCore {
+    /// The `diagnostics` folder of the data directory, where exports are written
+    pub(crate) async fn diagnostics_dir(&self) -> Result<PathBuf, String> {
+        self.database_manager
+            .read()
+            .await
+            .path()
+            .parent()
+            .map(|dir| dir.join("diagnostics"))
+            .ok_or_else(|| "Failed to determine the data directory".to_string())
+    }
+}
+
+pub(crate) fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// First line printed by `program arg`, `None` if it isn't installed
+pub(crate) async fn runtime_version(program: &str, arg: &str) -> Option<String> {
+    let mut command_builder = CommandWrappedInShellBuilder::new(program);
+    command_builder.arg(arg);
+    let output = command_builder.build().output().await.ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .map(|line| line.trim().to_string())
+}
@@ -0,0 +1,97 @@
+use log::warn;
+
+use super::mcp_core::MCPCore;
+use super::mcp_core_tools_snapshot_ext::McpCoreToolsSnapshotExt;
+use crate::events::{DockmasterEvent, ToolsBudgetWarning};
+use crate::footprint::{
+    measure, CharsPerToken, ToolsFootprint, FOOTPRINT_TOP_TOOLS, TOOLS_BUDGET_SETTING,
+};
+use crate::mcp_server::tools::get_builtin_tool_infos;
+
+/// Size of the tool list in the context of the models, see [`crate::footprint`]
+pub trait McpCoreFootprintExt {
+    /// Size of the tool list as `tools/list` serves it, with its `top` most expensive tools
+    fn tools_footprint(
+        &self,
+        top: usize,
+    ) -> impl std::future::Future<Output = Result<ToolsFootprint, String>> + Send;
+    /// Budget of tokens of the tool list, if one is set
+    fn tools_budget(&self) -> impl std::future::Future<Output = Option<usize>> + Send;
+    /// Set the budget of tokens of the tool list, `None` or 0 removes it
+    fn set_tools_budget(
+        &self,
+        budget: Option<usize>,
+    ) -> impl std::future::Future<Output = Result<(), String>> + Send;
+    /// Measure the tool list again, warning when it went over the budget
+    ///
+    /// The warning is only published when the servers over the budget changed, not
+    /// every time the tools cache is updated.
+    fn check_tools_budget(&self) -> impl std::future::Future<Output = ()> + Send;
+}
+
+impl McpCoreFootprintExt for MCPCore {
+    async fn tools_footprint(&self, top: usize) -> Result<ToolsFootprint, String> {
+        // Built-in tools are listed first, unless tools are hidden
+        let mut tools = if self.mcp_state.read().await.are_tools_hidden().await {
+            Vec::new()
+        } else {
+            get_builtin_tool_infos()
+        };
+        tools.extend(self.served_server_tools().await?.0);
+        let budget = self.tools_budget().await;
+        Ok(measure(&tools, &CharsPerToken, budget, top))
+    }
+
+    async fn tools_budget(&self) -> Option<usize> {
+        self.tool_registry
+            .read()
+            .await
+            .get_setting(TOOLS_BUDGET_SETTING)
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .filter(|budget| *budget > 0)
+    }
+
+    async fn set_tools_budget(&self, budget: Option<usize>) -> Result<(), String> {
+        self.tool_registry
+            .read()
+            .await
+            .save_setting(TOOLS_BUDGET_SETTING, &budget.unwrap_or(0).to_string())?;
+        self.check_tools_budget().await;
+        Ok(())
+    }
+
+    async fn check_tools_budget(&self) {
+        let footprint = match self.tools_footprint(FOOTPRINT_TOP_TOOLS).await {
+            Ok(footprint) => footprint,
+            Err(e) => {
+                warn!("Failed to measure the tool list: {}", e);
+                return;
+            }
+        };
+        let server_ids = footprint.servers_over_budget();
+        let previous = self
+            .tools_footprint
+            .write()
+            .unwrap()
+            .replace(footprint.clone());
+        let (Some(budget), true) = (footprint.budget, footprint.over_budget) else {
+            return;
+        };
+        if previous.is_some_and(|previous| {
+            previous.over_budget && previous.servers_over_budget() == server_ids
+        }) {
+            return;
+        }
+        warn!(
+            "The tool list takes about {} tokens, over the budget of {}",
+            footprint.tokens, budget
+        );
+        self.event_bus
+            .publish(DockmasterEvent::ToolsBudgetExceeded(ToolsBudgetWarning {
+                budget,
+                tokens: footprint.tokens,
+                server_ids,
+            }));
+    }
+}
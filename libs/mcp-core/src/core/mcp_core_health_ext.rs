@@ -0,0 +1,46 @@
+use log::warn;
+
+use super::mcp_core::MCPCore;
+use crate::health::{HealthCheckSettings, HEALTH_CHECK_SETTING};
+
+/// Periodic checks of the running servers, see [`crate::health`]
+pub trait McpCoreHealthExt {
+    /// Saved settings of the checks, the defaults when missing or unreadable
+    fn health_check_settings(
+        &self,
+    ) -> impl std::future::Future<Output = HealthCheckSettings> + Send;
+    /// Save the settings of the checks, used from the next round on
+    fn set_health_check_settings(
+        &self,
+        settings: HealthCheckSettings,
+    ) -> impl std::future::Future<Output = Result<(), String>> + Send;
+}
+
+impl McpCoreHealthExt for MCPCore {
+    async fn health_check_settings(&self) -> HealthCheckSettings {
+        let Ok(value) = self
+            .tool_registry
+            .read()
+            .await
+            .get_setting(HEALTH_CHECK_SETTING)
+        else {
+            return HealthCheckSettings::default();
+        };
+        serde_json::from_str(&value).unwrap_or_else(|e| {
+            warn!("Ignoring unreadable health check settings: {}", e);
+            HealthCheckSettings::default()
+        })
+    }
+
+    async fn set_health_check_settings(&self, settings: HealthCheckSettings) -> Result<(), String> {
+        if settings.unhealthy_after == 0 {
+            return Err("A server is unhealthy after at least one failed check".to_string());
+        }
+        let value = serde_json::to_string(&settings)
+            .map_err(|e| format!("Failed to serialize health check settings: {}", e))?;
+        self.tool_registry
+            .read()
+            .await
+            .save_setting(HEALTH_CHECK_SETTING, &value)
+    }
+}
@@ -0,0 +1,153 @@
+use log::{info, warn};
+use serde_json::Value;
+
+use super::mcp_core::MCPCore;
+use super::mcp_core_approvals_ext::McpCoreApprovalsExt;
+use crate::hooks::{
+    run_hook, HookFailurePolicy, HookInput, HookOutcome, HookStage, HooksConfig, PolicyError,
+    TOOL_HOOKS_SETTING,
+};
+use crate::models::types::{AuditAction, AuditDecision, AuditEntry};
+
+/// Hooks run around tool calls, see [`crate::hooks`]
+pub trait McpCoreHooksExt {
+    /// Configured hooks, none when the setting is missing or can't be read
+    fn tool_hooks(&self) -> impl std::future::Future<Output = HooksConfig> + Send;
+    /// Replace the configured hooks
+    fn set_tool_hooks(
+        &self,
+        config: HooksConfig,
+    ) -> impl std::future::Future<Output = Result<(), String>> + Send;
+    /// Run the hooks of `stage` for a call of `tool` on `server_id`
+    ///
+    /// Returns the arguments (`pre`) or the result (`post`) as changed by the hooks.
+    fn run_tool_hooks(
+        &self,
+        stage: HookStage,
+        server_id: &str,
+        tool: &str,
+        arguments: &Value,
+        result: Option<&Value>,
+    ) -> impl std::future::Future<Output = Result<Value, PolicyError>> + Send;
+}
+
+impl McpCoreHooksExt for MCPCore {
+    async fn tool_hooks(&self) -> HooksConfig {
+        let registry = self.tool_registry.read().await;
+        let Ok(json) = registry.get_setting(TOOL_HOOKS_SETTING) else {
+            return HooksConfig::default();
+        };
+        serde_json::from_str(&json).unwrap_or_else(|e| {
+            warn!("Ignoring the saved hooks, they can't be read: {}", e);
+            HooksConfig::default()
+        })
+    }
+
+    async fn set_tool_hooks(&self, config: HooksConfig) -> Result<(), String> {
+        config.validate()?;
+        let json = serde_json::to_string(&config)
+            .map_err(|e| format!("Failed to serialize the hooks: {}", e))?;
+        self.tool_registry
+            .read()
+            .await
+            .save_setting(TOOL_HOOKS_SETTING, &json)?;
+        info!("Saved {} tool hook(s)", config.hooks.len());
+        Ok(())
+    }
+
+    async fn run_tool_hooks(
+        &self,
+        stage: HookStage,
+        server_id: &str,
+        tool: &str,
+        arguments: &Value,
+        result: Option<&Value>,
+    ) -> Result<Value, PolicyError> {
+        let mut arguments = arguments.clone();
+        let mut result = result.cloned();
+        let hooks = self.tool_hooks().await.hooks;
+        for hook in hooks
+            .iter()
+            .filter(|hook| hook.applies_to(stage, server_id))
+        {
+            let input = HookInput {
+                stage,
+                server_id,
+                tool,
+                arguments: &arguments,
+                result: result.as_ref(),
+            };
+            let outcome = run_hook(hook, &input).await;
+            let entry = |decision: AuditDecision, detail: String| {
+                AuditEntry::new(
+                    AuditAction::ToolCall,
+                    Some(server_id.to_string()),
+                    None,
+                    decision,
+                )
+                .with_detail(format!(
+                    "Hook '{}' ({}) on {}: {}",
+                    hook.id,
+                    stage.as_str(),
+                    tool,
+                    detail
+                ))
+            };
+
+            match outcome {
+                Ok(HookOutcome::Pass) => {
+                    self.record_audit(entry(AuditDecision::Allowed, "passed".to_string()))
+                        .await;
+                }
+                Ok(HookOutcome::Modify(value)) => {
+                    self.record_audit(entry(AuditDecision::Modified, "modified".to_string()))
+                        .await;
+                    match stage {
+                        HookStage::Pre => arguments = value,
+                        HookStage::Post => result = Some(value),
+                    }
+                }
+                Ok(HookOutcome::Reject(reason)) => {
+                    info!(
+                        "Hook {} rejected {} on {}: {}",
+                        hook.id, tool, server_id, reason
+                    );
+                    self.record_audit(entry(AuditDecision::Denied, reason.clone()))
+                        .await;
+                    return Err(PolicyError {
+                        hook: hook.id.clone(),
+                        stage,
+                        reason,
+                    });
+                }
+                Err(e) => {
+                    warn!(
+                        "Hook {} failed on {} of {}: {}",
+                        hook.id, tool, server_id, e
+                    );
+                    match hook.on_failure {
+                        HookFailurePolicy::FailOpen => {
+                            let detail = format!("failed, passing the call: {}", e);
+                            self.record_audit(entry(AuditDecision::Allowed, detail))
+                                .await;
+                        }
+                        HookFailurePolicy::FailClosed => {
+                            let detail = format!("failed, rejecting the call: {}", e);
+                            self.record_audit(entry(AuditDecision::Denied, detail))
+                                .await;
+                            return Err(PolicyError {
+                                hook: hook.id.clone(),
+                                stage,
+                                reason: format!("The hook failed: {}", e),
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        Ok(match stage {
+            HookStage::Pre => arguments,
+            HookStage::Post => result.unwrap_or(Value::Null),
+        })
+    }
+}
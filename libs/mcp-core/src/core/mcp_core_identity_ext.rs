@@ -0,0 +1,90 @@
+use log::{info, warn};
+
+use super::mcp_core::MCPCore;
+use super::mcp_core_server_info_ext::McpCoreServerInfoExt;
+use crate::identity::{ServerIdentity, ServerIdentityOverrides, SERVER_IDENTITY_SETTING};
+use crate::mcp_server::mcp_tools_service::MCPToolsService;
+
+/// Identity presented to MCP clients, see [`crate::identity`]
+pub trait McpCoreIdentityExt {
+    /// What the user changed of the identity, nothing when the setting is missing or
+    /// can't be read
+    fn server_identity_overrides(
+        &self,
+    ) -> impl std::future::Future<Output = ServerIdentityOverrides> + Send;
+    /// Identity of the builder with what the user changed of it
+    fn server_identity(&self) -> impl std::future::Future<Output = ServerIdentity> + Send;
+    /// Save what the user changed of the identity, returning the resulting identity
+    fn set_server_identity_overrides(
+        &self,
+        overrides: ServerIdentityOverrides,
+    ) -> impl std::future::Future<Output = Result<ServerIdentity, String>> + Send;
+    /// Identity as `initialize` answers with it: the instructions end with the summary
+    /// of the exposed servers and the instructions servers share
+    fn advertised_identity(&self) -> impl std::future::Future<Output = ServerIdentity> + Send;
+}
+
+impl McpCoreIdentityExt for MCPCore {
+    async fn server_identity_overrides(&self) -> ServerIdentityOverrides {
+        let registry = self.tool_registry.read().await;
+        let Ok(json) = registry.get_setting(SERVER_IDENTITY_SETTING) else {
+            return ServerIdentityOverrides::default();
+        };
+        serde_json::from_str(&json).unwrap_or_else(|e| {
+            warn!(
+                "Ignoring the saved server identity, it can't be read: {}",
+                e
+            );
+            ServerIdentityOverrides::default()
+        })
+    }
+
+    async fn server_identity(&self) -> ServerIdentity {
+        let overrides = self.server_identity_overrides().await;
+        self.server_identity.clone().with_overrides(&overrides)
+    }
+
+    async fn set_server_identity_overrides(
+        &self,
+        overrides: ServerIdentityOverrides,
+    ) -> Result<ServerIdentity, String> {
+        overrides.validate()?;
+        let json = serde_json::to_string(&overrides)
+            .map_err(|e| format!("Failed to serialize the server identity: {}", e))?;
+        self.tool_registry
+            .read()
+            .await
+            .save_setting(SERVER_IDENTITY_SETTING, &json)?;
+        let identity = self.server_identity().await;
+        info!("MCP clients now see {} {}", identity.name, identity.version);
+        // Clients that connect from now on get the new identity
+        if let Some(tools_service) = MCPToolsService::get_instance().await {
+            tools_service.refresh_identity().await;
+        }
+        Ok(identity)
+    }
+
+    async fn advertised_identity(&self) -> ServerIdentity {
+        let mut identity = self.server_identity().await;
+        let mut servers: Vec<(String, usize)> = Vec::new();
+        let mcp_state = self.mcp_state.read().await;
+        // Hidden tools are not exposed
+        if !mcp_state.are_tools_hidden().await {
+            let server_tools = mcp_state.server_tools.read().await;
+            servers.extend(
+                server_tools
+                    .iter()
+                    .filter(|(_, tools)| !tools.is_empty())
+                    .map(|(server_id, tools)| (server_id.clone(), tools.len())),
+            );
+        }
+        drop(mcp_state);
+        servers.sort();
+        identity.instructions = format!(
+            "{}{}",
+            identity.advertised_instructions(&servers),
+            self.shared_server_instructions().await
+        );
+        identity
+    }
+}
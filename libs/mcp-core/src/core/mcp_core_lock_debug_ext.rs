@@ -0,0 +1,97 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::Duration;
+
+use log::warn;
+
+use super::mcp_core::MCPCore;
+use crate::lock_debug::{
+    lock_holds, spawn_lock_watchdog, ServerStateDump, StateDump, LOCK_MCP_CLIENTS, LOCK_MCP_STATE,
+    LOCK_SERVER_TOOLS, LOCK_TRACKING,
+};
+
+/// Debugging of freezes, see [`crate::lock_debug`]
+pub trait McpCoreLockDebugExt {
+    /// The servers, their calls in flight and the locks held, without waiting on any lock
+    fn dump_state(&self) -> StateDump;
+    /// Log a warning with a dump of the state whenever a lock is held longer than
+    /// `threshold`, only with the `lock-debug` feature
+    fn spawn_lock_watchdog(&self, threshold: Duration) -> tokio::task::JoinHandle<()>;
+}
+
+impl McpCoreLockDebugExt for MCPCore {
+    fn dump_state(&self) -> StateDump {
+        // Before the guards taken below are recorded
+        let holds = lock_holds();
+        let mut contended = BTreeSet::new();
+        let mut servers: BTreeMap<String, ServerStateDump> = BTreeMap::new();
+
+        match self.mcp_state.try_read() {
+            Ok(mcp_state) => {
+                for (server_id, in_flight) in mcp_state.metrics.in_flight() {
+                    server_dump(&mut servers, &server_id).in_flight = in_flight;
+                }
+                match mcp_state.mcp_clients.try_read() {
+                    Ok(clients) => {
+                        for (server_id, client) in clients.iter() {
+                            server_dump(&mut servers, server_id).status =
+                                Some(client.server_status.clone());
+                        }
+                    }
+                    Err(_) => {
+                        contended.insert(LOCK_MCP_CLIENTS);
+                    }
+                }
+                match mcp_state.server_tools.try_read() {
+                    Ok(server_tools) => {
+                        for (server_id, tools) in server_tools.iter() {
+                            server_dump(&mut servers, server_id).tool_count = Some(tools.len());
+                        }
+                    }
+                    Err(_) => {
+                        contended.insert(LOCK_SERVER_TOOLS);
+                    }
+                }
+            }
+            Err(_) => {
+                contended.insert(LOCK_MCP_STATE);
+            }
+        }
+
+        StateDump {
+            lock_tracking: LOCK_TRACKING,
+            lock_holds: holds,
+            contended,
+            servers: servers.into_values().collect(),
+        }
+    }
+
+    fn spawn_lock_watchdog(&self, threshold: Duration) -> tokio::task::JoinHandle<()> {
+        let mcp_core = self.clone();
+        spawn_lock_watchdog(threshold, move |long_holds| {
+            for hold in &long_holds {
+                warn!(
+                    "Lock '{}' held for {:?} by {} ({:?})",
+                    hold.lock, hold.held_for, hold.holder, hold.mode
+                );
+            }
+            match serde_json::to_string(&mcp_core.dump_state()) {
+                Ok(dump) => warn!("State while locks are held too long: {}", dump),
+                Err(e) => warn!("Failed to dump the state: {}", e),
+            }
+        })
+    }
+}
+
+fn server_dump<'a>(
+    servers: &'a mut BTreeMap<String, ServerStateDump>,
+    server_id: &str,
+) -> &'a mut ServerStateDump {
+    servers
+        .entry(server_id.to_string())
+        .or_insert_with(|| ServerStateDump {
+            server_id: server_id.to_string(),
+            status: None,
+            tool_count: None,
+            in_flight: 0,
+        })
+}
@@ -0,0 +1,25 @@
+use std::collections::HashMap;
+
+use super::mcp_core::MCPCore;
+
+/// Prometheus metrics, see [`crate::metrics`]
+pub trait McpCoreMetricsExt {
+    /// The metrics in the Prometheus text format, as served on `/metrics`
+    fn render_metrics(&self) -> impl std::future::Future<Output = Result<String, String>> + Send;
+}
+
+impl McpCoreMetricsExt for MCPCore {
+    async fn render_metrics(&self) -> Result<String, String> {
+        let servers: HashMap<String, String> = self
+            .tool_registry
+            .read()
+            .await
+            .get_all_servers()?
+            .into_iter()
+            .map(|(server_id, server)| (server_id, server.name))
+            .collect();
+        let mcp_state = self.mcp_state.read().await;
+        let running = mcp_state.mcp_clients.read().await.len();
+        Ok(mcp_state.metrics.render(&servers, running))
+    }
+}
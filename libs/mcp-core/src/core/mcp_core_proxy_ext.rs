@@ -1,24 +1,54 @@
+use crate::cancellation::CANCELLED_ERROR;
+use crate::events::{DockmasterEvent, InstallProgressReporter, InstallStage, StartupProgress};
+use crate::hooks::{HookStage, PolicyError};
+use crate::lifecycle::{now_millis, LifecycleKind, ServerHistory, ServerUptime};
+use crate::mcp_server::mcp_tools_service::MCPToolsService;
+use crate::mcp_state::process_launcher::STARTUP_TIMEOUT_SETTING;
+use crate::mcp_state::reconnect::DeadConnection;
+use crate::mcp_state::server_notifications::ServerNotification;
+use crate::mcp_state::shutdown::{ShutdownOutcome, SHUTDOWN_GRACE_SETTING};
+use crate::mcp_state::start_failures::MAX_FAILED_STARTS_SETTING;
+use crate::models::env_key::canonicalize_env;
+use crate::models::registration::ResolvedRegistration;
+use crate::models::server_id::slugify_server_id;
+use crate::models::tool_id::parse_proxy_tool_id;
 use crate::models::types::{
-    DiscoverServerToolsRequest, Distribution, RuntimeServer, ServerConfigUpdateRequest,
-    ServerConfiguration, ServerDefinition, ServerEnvironment, ServerId, ServerRegistrationRequest,
-    ServerRegistrationResponse, ServerStatus, ServerToolInfo, ServerUninstallResponse,
-    ServerUpdateRequest, ToolConfigUpdateResponse, ToolExecutionRequest, ToolExecutionResponse,
-    ToolUninstallRequest, ToolUpdateResponse,
-};
-use crate::utils::github::{
-    extract_env_vars_from_readme, fetch_github_file, parse_github_url, GitHubRepo,
+    BulkServerResponse, CallMode, DiscoverServerToolsRequest, Distribution, EnvBoolFormat,
+    EnvValue, OrphanCleanupReport, ProvenanceSource, ReplayMiss, RuntimeServer,
+    ServerConfigUpdateRequest, ServerConfiguration, ServerEnvironment, ServerId, ServerProvenance,
+    ServerRegistrationRequest, ServerRegistrationResponse, ServerStatus, ServerToolInfo,
+    ServerUninstallResponse, ServerUpdateRequest, StartMode, ToolConfigUpdateResponse,
+    ToolExecutionRequest, ToolExecutionResponse, ToolUninstallRequest, ToolUpdateResponse,
 };
+use crate::rate_limits::RateLimited;
+use crate::recordings::RecordedCall;
+use crate::registry::storage::StorageUnavailable;
+use crate::server_logs::ServerLogSnapshot;
+use crate::undo::BeforeImage;
+use crate::utils::description::normalize_description;
+use crate::utils::github::GitHubRepo;
+#[cfg(feature = "registry")]
+use crate::utils::github::{extract_env_vars_from_readme, fetch_github_file, parse_github_url};
+use crate::utils::json_schema::check_structured_content;
+use crate::utils::payload::log_payload;
+use crate::MCPError;
 use anyhow::Result;
 use async_trait::async_trait;
-use futures::future;
-use log::{error, info};
+use log::{error, info, warn};
+#[cfg(feature = "registry")]
 use reqwest::Client;
 use serde_json::Value;
 use std::collections::{HashMap, HashSet};
-use crate::mcp_server::mcp_tools_service::MCPToolsService;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 use toml::Table;
 
 use super::mcp_core::MCPCore;
+use super::mcp_core_bulk_ext::McpCoreBulkExt;
+use super::mcp_core_hooks_ext::McpCoreHooksExt;
+use super::mcp_core_rate_limits_ext::McpCoreRateLimitsExt;
+use super::mcp_core_recordings_ext::McpCoreRecordingsExt;
+use super::mcp_core_tools_ext::McpCoreToolsExt;
 
 #[async_trait]
 pub trait McpCoreProxyExt {
@@ -26,6 +56,12 @@ pub trait McpCoreProxyExt {
         &self,
         tool: ServerRegistrationRequest,
     ) -> Result<ServerRegistrationResponse, String>;
+    /// Register a new server, publishing install progress on the event bus
+    async fn register_server_with_progress(
+        &self,
+        tool: ServerRegistrationRequest,
+        progress: Option<InstallProgressReporter>,
+    ) -> Result<ServerRegistrationResponse, String>;
     async fn list_servers(&self) -> Result<Vec<RuntimeServer>, String>;
     async fn list_all_server_tools(&self) -> Result<Vec<ServerToolInfo>, String>;
     async fn list_server_tools(
@@ -36,6 +72,15 @@ pub trait McpCoreProxyExt {
         &self,
         request: ToolExecutionRequest,
     ) -> Result<ToolExecutionResponse, String>;
+    /// Execute a tool, cancelled by [`MCPCore::cancel_tool_call`] with `call_id`
+    ///
+    /// `meta` is sent to the server as the `_meta` of the call.
+    async fn execute_cancellable_proxy_tool(
+        &self,
+        request: ToolExecutionRequest,
+        call_id: Option<String>,
+        meta: Option<Value>,
+    ) -> Result<ToolExecutionResponse, String>;
     async fn update_server_status(
         &self,
         request: ServerUpdateRequest,
@@ -48,13 +93,52 @@ pub trait McpCoreProxyExt {
         &self,
         request: ToolUninstallRequest,
     ) -> Result<ServerUninstallResponse, String>;
-    async fn restart_server_command(&self, tool_id: String) -> Result<ToolUpdateResponse, String>;
+    /// Restart a server, `force` restarting it even when it is faulted, see
+    /// [`crate::mcp_state::start_failures`]
+    async fn restart_server_command(
+        &self,
+        tool_id: String,
+        force: bool,
+    ) -> Result<ToolUpdateResponse, String>;
+    /// Start an enabled server that isn't running
+    async fn start_server(&self, server_id: String) -> Result<ToolUpdateResponse, String>;
+    /// Stop a server but keep it enabled, it stays stopped until started again
+    async fn stop_server(&self, server_id: String) -> Result<ToolUpdateResponse, String>;
+    /// Restart every enabled server the user wants running, same as
+    /// [`McpCoreBulkExt::restart_servers`] without ids
+    async fn restart_all_servers(&self) -> Result<BulkServerResponse, String>;
+    /// What a server wrote to stderr, the lines read at or after `since` (Unix
+    /// milliseconds) when given
+    async fn get_server_logs(
+        &self,
+        tool_id: String,
+        since: Option<u64>,
+    ) -> Result<ServerLogSnapshot, String>;
+    /// Lifecycle events of a server kept in memory, with its uptime
+    async fn get_server_history(&self, tool_id: String) -> Result<ServerHistory, String>;
     async fn init_mcp_server(&self) -> Result<()>;
-    async fn kill_all_processes(&self) -> Result<()>;
-    /// Import a server from a GitHub repository URL
+    /// Terminate the server processes and containers left behind by a crashed run
+    async fn cleanup_orphans(&self) -> OrphanCleanupReport;
+    /// Stop all running processes, returning how each server stopped
+    async fn kill_all_processes(&self) -> Result<HashMap<String, ShutdownOutcome>>;
+    /// Seconds a server has to start and answer the initialize handshake
+    async fn startup_timeout_secs(&self) -> u64;
+    /// Set the seconds a server has to start and answer the initialize handshake
+    async fn set_startup_timeout_secs(&self, secs: u64) -> Result<(), String>;
+    /// Seconds a server has to exit once asked to before it is killed
+    async fn shutdown_grace_secs(&self) -> u64;
+    /// Set the seconds a server has to exit once asked to before it is killed
+    async fn set_shutdown_grace_secs(&self, secs: u64) -> Result<(), String>;
+    /// Consecutive failed starts after which a server isn't started again
+    async fn max_failed_starts(&self) -> u32;
+    /// Set the consecutive failed starts after which a server isn't started again
+    async fn set_max_failed_starts(&self, limit: u32) -> Result<(), String>;
+    /// Import a server from a GitHub repository URL, started unless `enabled` is false
+    #[cfg(feature = "registry")]
     async fn import_server_from_url(
         &self,
         github_url: String,
+        enabled: Option<bool>,
     ) -> Result<ServerRegistrationResponse, String>;
     /// Process a Node.js project from package.json content
     async fn process_nodejs_project(
@@ -62,6 +146,7 @@ pub trait McpCoreProxyExt {
         package_json_content: String,
         repo_info: &GitHubRepo,
         env_vars: HashSet<String>,
+        enabled: Option<bool>,
     ) -> Result<ServerRegistrationResponse, String>;
     /// Process a Python project from pyproject.toml content
     async fn process_python_project(
@@ -69,6 +154,7 @@ pub trait McpCoreProxyExt {
         pyproject_toml_content: String,
         repo_info: &GitHubRepo,
         env_vars: HashSet<String>,
+        enabled: Option<bool>,
     ) -> Result<ServerRegistrationResponse, String>;
 }
 
@@ -78,6 +164,15 @@ impl McpCoreProxyExt for MCPCore {
     async fn register_server(
         &self,
         request: ServerRegistrationRequest,
+    ) -> Result<ServerRegistrationResponse, String> {
+        self.register_server_with_progress(request, None).await
+    }
+
+    /// Register a new tool with the MCP server, reporting each install stage
+    async fn register_server_with_progress(
+        &self,
+        request: ServerRegistrationRequest,
+        progress: Option<InstallProgressReporter>,
     ) -> Result<ServerRegistrationResponse, String> {
         // Log configuration details if present
         if let Some(config) = &request.configuration {
@@ -89,45 +184,18 @@ impl McpCoreProxyExt for MCPCore {
         } else {
             info!("Configuration not provided");
         }
-        let registry = self.tool_registry.write().await;
-
-        // Generate a simple tool ID (in production, use UUIDs)
-        let server_id = request.server_id.clone();
-        info!("Generated server ID: {}", server_id);
 
-        // Create the Tool struct
-        let server = ServerDefinition {
-            name: request.server_name.clone(),
-            description: request.description.clone(),
-            enabled: true, // Default to enabled
-            tools_type: request.tools_type.clone(),
-            entry_point: None,
-            configuration: request.configuration,
-            distribution: request.distribution,
+        // Refuse configurations that could never start before anything is saved
+        let registration = match ResolvedRegistration::resolve(request) {
+            Ok(registration) => registration,
+            Err(e) => {
+                if let Some(progress) = &progress {
+                    progress.report(InstallStage::Failed, Some(e.clone()));
+                }
+                return Err(e);
+            }
         };
-
-        // Save the tool in the registry
-        registry.save_server(&server_id, &server)?;
-        drop(registry);
-
-        let mcp_state_clone = self.mcp_state.clone();
-        {
-            // Create a default empty tools list
-            let mcp_state = mcp_state_clone.write().await;
-            let mut server_tools = mcp_state.server_tools.write().await;
-            server_tools.insert(server_id.clone(), Vec::new());
-        } // All locks are released at this point
-
-        // Now call restart_server after the locks have been released
-        let mcp_state = mcp_state_clone.read().await;
-        let _ = mcp_state.restart_server(&server_id).await;
-
-        info!("Tool registration completed for: {}", request.server_name);
-        Ok(ServerRegistrationResponse {
-            success: true,
-            message: format!("Tool '{}' registered successfully", request.server_name),
-            tool_id: Some(server_id),
-        })
+        self.register_resolved(registration, progress).await
     }
 
     /// List all registered tools
@@ -136,28 +204,55 @@ impl McpCoreProxyExt for MCPCore {
         let registry = mcp_state.tool_registry.read().await;
 
         let tool_map = registry.get_all_servers()?;
+        let mut provenances = registry.get_server_provenances().unwrap_or_else(|e| {
+            warn!("Failed to read server provenance: {}", e);
+            HashMap::new()
+        });
+        let run_states = registry.get_server_run_states().unwrap_or_else(|e| {
+            warn!("Failed to read server run states: {}", e);
+            HashMap::new()
+        });
+        let call_modes = registry.get_server_call_modes().unwrap_or_else(|e| {
+            warn!("Failed to read server call modes: {}", e);
+            HashMap::new()
+        });
+        let mut server_infos = registry.get_server_infos().unwrap_or_else(|e| {
+            warn!("Failed to read server info: {}", e);
+            HashMap::new()
+        });
         let mut tools = Vec::new();
+        let now = now_millis();
 
         for (id, tool_struct) in tool_map {
-            let status = {
-                let mcp_clients = mcp_state.mcp_clients.read().await;
-                if let Some(mcp_client) = mcp_clients.get(&id) {
-                    mcp_client.server_status.clone()
-                } else {
-                    ServerStatus::Stopped
-                }
-            };
+            let status = mcp_state.server_status(&id).await;
+            let started_at = mcp_state
+                .mcp_clients
+                .read()
+                .await
+                .get(&id)
+                .map(|mcp_client| mcp_client.started_at);
+            let uptime = ServerUptime::new(started_at, &registry.get_lifecycle_history(&id), now);
 
             let tool_count = {
                 let server_tools = mcp_state.server_tools.read().await;
                 server_tools.get(&id).map_or(0, |tools| tools.len())
             };
 
+            let provenance = provenances.remove(&id);
+            let call_mode = call_modes.get(&id).copied().unwrap_or_default();
+            let server_info = server_infos.remove(&id);
             tools.push(RuntimeServer {
                 definition: tool_struct,
+                desired_running: run_states.get(&id).copied().unwrap_or(true),
                 id: ServerId::new(id),
                 status,
                 tool_count,
+                llm_initiated: provenance.as_ref().is_some_and(|p| p.is_llm_initiated()),
+                provenance,
+                call_mode,
+                server_info,
+                uptime,
+                health: mcp_state.server_health.get(&id),
             });
         }
 
@@ -167,18 +262,48 @@ impl McpCoreProxyExt for MCPCore {
     /// List all available tools from all running MCP servers
     async fn list_all_server_tools(&self) -> Result<Vec<ServerToolInfo>, String> {
         let mcp_state = self.mcp_state.read().await;
-        
+
         // Check if tools are hidden
         if mcp_state.are_tools_hidden().await {
             // Return empty list when tools are hidden
             return Ok(Vec::new());
         }
-        
+
+        // Long descriptions are shortened, the full text stays available through
+        // `tools/describe`
+        let options = self.description_options().await;
+        let overrides = self.tool_annotation_overrides().await;
+        let full_description_servers: HashSet<String> = self
+            .tool_registry
+            .read()
+            .await
+            .get_all_servers()
+            .map(|servers| {
+                servers
+                    .into_iter()
+                    .filter(|(_, server)| {
+                        server
+                            .configuration
+                            .as_ref()
+                            .and_then(|c| c.truncate_descriptions)
+                            == Some(false)
+                    })
+                    .map(|(id, _)| id)
+                    .collect()
+            })
+            .unwrap_or_default();
+
         let server_tools = mcp_state.server_tools.read().await;
         let mut all_tools = Vec::new();
 
-        for tools in (*server_tools).values() {
-            all_tools.extend(tools.iter().cloned());
+        for (server_id, tools) in (*server_tools).iter() {
+            let truncate = !full_description_servers.contains(server_id);
+            all_tools.extend(tools.iter().map(|tool| {
+                let mut tool = tool.clone();
+                tool.description = normalize_description(&tool.description, &options, truncate);
+                tool.apply_annotation_overrides(&overrides);
+                tool
+            }));
         }
         Ok(all_tools)
     }
@@ -188,8 +313,9 @@ impl McpCoreProxyExt for MCPCore {
         &self,
         request: DiscoverServerToolsRequest,
     ) -> Result<Vec<ServerToolInfo>, String> {
+        let server_id = self.resolve_server_id(&request.server_id).await;
         let mcp_state = self.mcp_state.read().await;
-        mcp_state.discover_server_tools(&request.server_id).await
+        mcp_state.discover_server_tools(&server_id).await
     }
 
     /// Execute a tool from an MCP server
@@ -197,51 +323,94 @@ impl McpCoreProxyExt for MCPCore {
         &self,
         request: ToolExecutionRequest,
     ) -> Result<ToolExecutionResponse, String> {
-        let mcp_state = self.mcp_state.read().await;
-        // Extract server_id and tool_id from the proxy_id
-        let parts: Vec<&str> = request.tool_id.split(':').collect();
-        println!("parts: {:?}", parts);
-        if parts.len() != 2 {
-            return Err("Invalid tool_id format. Expected 'server_id:tool_id'".to_string());
-        }
-
-        let server_id = parts[0];
-        let tool_id = parts[1];
-
-        // Execute the tool on the server
-        let mcp_clients = mcp_state.mcp_clients.read().await;
-        let mcp_client = mcp_clients.get(server_id);
-        if mcp_client.is_none() {
-            return Err(format!("Server with ID '{}' not found", server_id));
-        }
-        let mcp_client = mcp_client.unwrap();
+        self.execute_cancellable_proxy_tool(request, None, None)
+            .await
+    }
 
-        // Check if server is stopped
-        // if matches!(mcp_client.server_status, ServerStatus::Stopped) {
-        //     return Err(format!("Server '{}' is stopped", server_id));
-        // }
+    async fn execute_cancellable_proxy_tool(
+        &self,
+        request: ToolExecutionRequest,
+        call_id: Option<String>,
+        meta: Option<Value>,
+    ) -> Result<ToolExecutionResponse, String> {
+        // Tracked from the start, a call cancelled early doesn't reach the server
+        let mut pending = call_id
+            .map(|call_id| self.pending_calls.register(&call_id))
+            .transpose()?;
+
+        // Extract server_id and the tool name from the proxy_id
+        let Some((server_id, tool_id)) = parse_proxy_tool_id(&request.tool_id) else {
+            return Err("Invalid tool_id format. Expected 'server_id:tool_name'".to_string());
+        };
 
-        let result = match mcp_client
-            .client
-            .call_tool(tool_id, request.parameters.clone())
+        let server_id = self.resolve_server_id(server_id).await;
+        let server_id = server_id.as_str();
+
+        let arguments = match self
+            .run_tool_hooks(
+                HookStage::Pre,
+                server_id,
+                tool_id,
+                &request.parameters,
+                None,
+            )
+            .await
+        {
+            Ok(arguments) => arguments,
+            Err(policy_error) => return Ok(rejected_by_hook(policy_error)),
+        };
+        // The call is only timed once the server answered
+        let lazy_start = self.start_lazy_server(server_id).await?;
+        let permit = match self.acquire_tool_call(server_id, tool_id).await {
+            Ok(permit) => permit,
+            Err(rate_limited) => return Ok(refused_by_rate_limit(rate_limited)),
+        };
+        let call = self.call_server_tool_reconnecting(server_id, tool_id, &arguments, meta);
+        let result = match pending.as_mut() {
+            Some(pending) => tokio::select! {
+                result = call => result,
+                _ = pending.cancelled() => {
+                    info!("Call of {} on {} cancelled by its client", tool_id, server_id);
+                    return Ok(cancelled_call());
+                }
+            },
+            None => call.await,
+        };
+        drop(permit);
+        drop(pending);
+        let (result, mut warnings) = result?;
+        warnings.extend(lazy_start);
+        let result = match self
+            .run_tool_hooks(
+                HookStage::Post,
+                server_id,
+                tool_id,
+                &arguments,
+                Some(&result),
+            )
             .await
         {
             Ok(result) => result,
-            Err(e) => return Err(format!("Tool execution error: {}", e)),
+            Err(policy_error) => return Ok(rejected_by_hook(policy_error)),
         };
 
         Ok(ToolExecutionResponse {
             success: true,
-            result: Some(serde_json::to_value(result).unwrap()),
+            result: Some(result),
             error: None,
+            warnings,
+            policy_error: None,
+            rate_limited: None,
+            cancelled: false,
         })
     }
 
     /// Update a tool's status (enabled/disabled)
     async fn update_server_status(
         &self,
-        request: ServerUpdateRequest,
+        mut request: ServerUpdateRequest,
     ) -> Result<ToolUpdateResponse, String> {
+        request.server_id = self.resolve_server_id(&request.server_id).await;
         info!(
             "Updating tool status for: {} to {}",
             request.server_id, request.enabled
@@ -256,14 +425,7 @@ impl McpCoreProxyExt for MCPCore {
                 // Extract and clone the necessary values
                 let tools_type = tool.tools_type.clone();
                 let entry_point = tool.entry_point.clone().unwrap_or_default();
-                let status = {
-                    let mcp_clients = mcp_state.mcp_clients.read().await;
-                    if let Some(mcp_client) = mcp_clients.get(&request.server_id) {
-                        mcp_client.server_status.clone()
-                    } else {
-                        ServerStatus::Stopped
-                    }
-                };
+                let status = mcp_state.server_status(&request.server_id).await;
 
                 Some((tools_type, entry_point, status))
             } else {
@@ -281,13 +443,19 @@ impl McpCoreProxyExt for MCPCore {
 
         // Now handle the process based on the enabled status
         // First update the tool's enabled status in registry
-        {
+        let was_enabled = {
             let mcp_state = self.mcp_state.read().await;
             let registry = mcp_state.tool_registry.write().await;
             let mut tool = registry.get_server(&request.server_id)?;
+            let was_enabled = tool.enabled;
             tool.enabled = request.enabled;
             registry.save_server(&request.server_id, &tool)?;
-        } // Registry lock is dropped here
+            // Enabling a server is asking for it to run, disabling it to stop
+            if let Err(e) = registry.save_server_run_state(&request.server_id, request.enabled) {
+                warn!("Failed to record run state of {}: {}", request.server_id, e);
+            }
+            was_enabled
+        }; // Registry lock is dropped here
 
         let result = if request.enabled {
             // If enabling, first set status to Starting
@@ -301,7 +469,7 @@ impl McpCoreProxyExt for MCPCore {
             self.mcp_state
                 .read()
                 .await
-                .kill_process(&request.server_id)
+                .stop_server(&request.server_id)
                 .await
         };
 
@@ -314,10 +482,17 @@ impl McpCoreProxyExt for MCPCore {
         }
         // Update the tools cache
         let tools_service = MCPToolsService::get_instance().await;
-        if let Err(e) =  tools_service.expect("REASON").update_cache().await {
-            error!("Failed to update tools cache after updating tool status: {}", e);
+        if let Err(e) = tools_service.expect("REASON").update_cache().await {
+            error!(
+                "Failed to update tools cache after updating tool status: {}",
+                e
+            );
             return Err(e);
         }
+        if was_enabled && !request.enabled {
+            self.record_undoable(&request.server_id, BeforeImage::Enabled)
+                .await;
+        }
 
         // Return success
         Ok(ToolUpdateResponse {
@@ -342,8 +517,10 @@ impl McpCoreProxyExt for MCPCore {
     /// Update a tool's configuration (environment variables)
     async fn update_server_config(
         &self,
-        request: ServerConfigUpdateRequest,
+        mut request: ServerConfigUpdateRequest,
     ) -> Result<ToolConfigUpdateResponse, String> {
+        request.server_id = self.resolve_server_id(&request.server_id).await;
+        request.config = canonicalize_env(std::mem::take(&mut request.config))?;
         let mcp_state = self.mcp_state.read().await;
         info!("Updating configuration for tool: {}", request.server_id);
 
@@ -375,44 +552,67 @@ impl McpCoreProxyExt for MCPCore {
 
         // Get the current tool data
         let mut tool = registry.get_server(&request.server_id)?;
+        let previous = tool.configuration.clone().unwrap_or_default();
 
-        // Create or update the configuration object
-        if tool.configuration.is_none() {
-            tool.configuration = Some(ServerConfiguration {
-                command: None,
-                args: None,
-                env: Some(HashMap::new()),
-            });
+        // Create or update the configuration object, the options below apply either way
+        let configuration = tool
+            .configuration
+            .get_or_insert_with(ServerConfiguration::default);
+        if let Some(inherit_env) = request.inherit_env {
+            info!(
+                "Setting inherit_env of tool {} to {}",
+                request.server_id, inherit_env
+            );
+            configuration.inherit_env = Some(inherit_env);
+        }
+        if let Some(cwd) = &request.cwd {
+            info!("Setting cwd of tool {} to '{}'", request.server_id, cwd);
+            configuration.cwd = Some(cwd.trim().to_string()).filter(|cwd| !cwd.is_empty());
         }
 
-        if let Some(configuration) = &mut tool.configuration {
-            // Create or update the env object
-            if configuration.env.is_none() {
-                configuration.env = Some(HashMap::new());
-            }
+        // Create or update the env object
+        if configuration.env.is_none() {
+            configuration.env = Some(HashMap::new());
+        }
 
-            if let Some(env_map) = &mut configuration.env {
-                // Update each environment variable from the config HashMap
-                for (key, value) in &request.config {
-                    info!(
-                        "Setting environment variable for tool {}: {}={}",
-                        request.server_id, key, value
-                    );
-                    // Convert to ToolEnvironment
-                    env_map.insert(
-                        key.clone(),
-                        ServerEnvironment {
+        if let Some(env_map) = &mut configuration.env {
+            // Update each environment variable from the config HashMap
+            for (key, value) in &request.config {
+                info!(
+                    "Setting environment variable {} of tool {}",
+                    key, request.server_id
+                );
+                if ServerEnvironment::is_template(value) {
+                    // A full template replaces the entry, metadata included
+                    env_map.insert(key.clone(), ServerEnvironment::from_value(value)?);
+                } else {
+                    // A plain value keeps the existing description and format
+                    let new_value = EnvValue::from_json(value)?;
+                    env_map
+                        .entry(key.clone())
+                        .and_modify(|env| env.default = new_value.clone())
+                        .or_insert_with(|| ServerEnvironment {
                             description: "".to_string(),
-                            default: Some(value.clone()),
+                            default: new_value,
                             required: false,
-                        },
-                    );
+                            bool_format: EnvBoolFormat::default(),
+                        });
                 }
             }
         }
 
         // Save the updated tool
         registry.save_server(&request.server_id, &tool)?;
+        drop(registry);
+        // The new configuration may fix what kept it from starting
+        mcp_state.start_failures.reset(&request.server_id);
+        self.record_undoable(
+            &request.server_id,
+            BeforeImage::Configuration {
+                configuration: previous,
+            },
+        )
+        .await;
 
         // Return success
         Ok(ToolConfigUpdateResponse {
@@ -426,82 +626,253 @@ impl McpCoreProxyExt for MCPCore {
         &self,
         request: ToolUninstallRequest,
     ) -> Result<ServerUninstallResponse, String> {
+        let server_id = self.resolve_server_id(&request.server_id).await;
+        let server_id = server_id.as_str();
+        // What undoing the uninstall registers again
+        let before_image = {
+            let registry = self.tool_registry.read().await;
+            registry
+                .get_server(server_id)
+                .ok()
+                .map(|definition| BeforeImage::Server {
+                    definition,
+                    provenance: registry
+                        .get_server_provenances()
+                        .ok()
+                        .and_then(|mut provenances| provenances.remove(server_id)),
+                })
+        };
         let mcp_state = self.mcp_state.read().await;
-        let registry = mcp_state.tool_registry.write().await;
 
-        // First check if the tool exists
-        if registry.get_server(&request.server_id).is_err() {
-            return Ok(ServerUninstallResponse {
-                success: false,
-                message: format!("Tool with ID '{}' not found", request.server_id),
-            });
-        }
+        // Hold the server's restart lock so no restart can respawn it while it is removed
+        let response = mcp_state
+            .restart_gate
+            .exclusive(server_id, || async {
+                {
+                    let registry = mcp_state.tool_registry.read().await;
+
+                    // First check if the tool exists
+                    if registry.get_server(server_id).is_err() {
+                        return Ok(ServerUninstallResponse {
+                            success: false,
+                            message: format!("Tool with ID '{}' not found", server_id),
+                            tools_removed: 0,
+                        });
+                    }
 
-        // Kill the process if it's running
-        if let Err(e) = mcp_state.kill_process(&request.server_id).await {
-            error!(
-                "Failed to kill process for server {}: {}",
-                request.server_id, e
-            );
-        }
+                    // Don't stop the server when its removal can't be saved
+                    if registry.storage().is_degraded() {
+                        return Err(StorageUnavailable(format!(
+                            "{} can't be uninstalled until the database is back",
+                            server_id
+                        ))
+                        .to_string());
+                    }
+                }
 
-        // Delete the tool using registry's delete_tool method
-        if let Err(e) = registry.delete_server(&request.server_id) {
-            return Ok(ServerUninstallResponse {
-                success: false,
-                message: format!("Failed to delete tool: {}", e),
-            });
-        }
+                let tools_removed = match mcp_state.server_tools.read().await.get(server_id) {
+                    Some(tools) => tools.len(),
+                    None => mcp_state
+                        .tool_registry
+                        .read()
+                        .await
+                        .get_server_tools(server_id)
+                        .map_or(0, |tools| tools.len()),
+                };
 
-        Ok(ServerUninstallResponse {
-            success: true,
-            message: "Tool uninstalled successfully".to_string(),
-        })
+                // Stop the process if it's running, the registry isn't held while it exits
+                match mcp_state.kill_process(server_id).await {
+                    Ok(outcome) => info!("Stopped server {}: {:?}", server_id, outcome),
+                    Err(e) => error!("Failed to kill process for server {}: {}", server_id, e),
+                }
+
+                let registry = mcp_state.tool_registry.write().await;
+                // Delete the tool using registry's delete_tool method
+                if let Err(e) = registry.delete_server(server_id) {
+                    return Ok(ServerUninstallResponse {
+                        success: false,
+                        message: format!("Failed to delete tool: {}", e),
+                        tools_removed: 0,
+                    });
+                }
+                mcp_state.server_logs.remove(server_id);
+
+                Ok(ServerUninstallResponse {
+                    success: true,
+                    message: "Tool uninstalled successfully".to_string(),
+                    tools_removed,
+                })
+            })
+            .await?;
+        if response.success {
+            // MCP clients stop seeing its tools right away
+            self.publish_tools_update(vec![server_id.to_string()]).await;
+        }
+        if let (true, Some(image)) = (response.success, before_image) {
+            self.record_undoable(server_id, image).await;
+        }
+        Ok(response)
     }
 
     /// Restart a server by its ID
     async fn restart_server_command(
         &self,
         server_id: String,
+        force: bool,
     ) -> Result<ToolUpdateResponse, String> {
-        let mcp_state = self.mcp_state.read().await;
-        info!("Received request to restart tool: {}", server_id);
+        if force {
+            let server_id = self.resolve_server_id(&server_id).await;
+            info!("Forcing a restart of tool: {}", server_id);
+            self.mcp_state.read().await.start_failures.reset(&server_id);
+        }
+        self.restart_server_as(server_id, LifecycleKind::Restarted)
+            .await
+    }
 
-        // Check if the tool exists
-        let tool_exists = {
+    /// Start a server by its ID, waiting for it to answer and listing its tools
+    async fn start_server(&self, server_id: String) -> Result<ToolUpdateResponse, String> {
+        let server_id = self.resolve_server_id(&server_id).await;
+        let mcp_state = self.mcp_state.read().await.clone();
+        info!("Received request to start tool: {}", server_id);
+
+        {
             let registry = mcp_state.tool_registry.read().await;
-            registry.get_server(&server_id).is_ok()
-        };
+            let Ok(server) = registry.get_server(&server_id) else {
+                return Ok(ToolUpdateResponse {
+                    success: false,
+                    message: format!("Tool with ID '{}' not found", server_id),
+                });
+            };
+            if !server.enabled {
+                return Ok(ToolUpdateResponse {
+                    success: false,
+                    message: format!("Tool '{}' is disabled, enable it to start it", server_id),
+                });
+            }
+            if let Err(e) = registry.save_server_run_state(&server_id, true) {
+                warn!("Failed to record run state of {}: {}", server_id, e);
+            }
+        }
 
-        if !tool_exists {
-            error!("Tool with ID '{}' not found for restart", server_id);
+        match mcp_state.start_server_if_stopped(&server_id).await {
+            Ok(true) => {}
+            Ok(false) if mcp_state.mcp_clients.read().await.contains_key(&server_id) => {
+                return Ok(ToolUpdateResponse {
+                    success: true,
+                    message: format!("Tool '{}' is already running", server_id),
+                });
+            }
+            Ok(false) => {
+                let missing = mcp_state
+                    .needs_configuration
+                    .read()
+                    .await
+                    .get(&server_id)
+                    .cloned()
+                    .unwrap_or_default();
+                return Ok(ToolUpdateResponse {
+                    success: false,
+                    message: format!(
+                        "Tool '{}' needs a value for {} to start",
+                        server_id,
+                        missing.join(", ")
+                    ),
+                });
+            }
+            Err(e) => {
+                error!("Failed to start tool {}: {}", server_id, e);
+                return Ok(ToolUpdateResponse {
+                    success: false,
+                    message: format!("Failed to start tool: {}", e),
+                });
+            }
+        }
+        self.publish_tools_update(vec![server_id.clone()]).await;
+
+        info!("Successfully started tool: {}", server_id);
+        Ok(ToolUpdateResponse {
+            success: true,
+            message: format!("Tool '{}' started", server_id),
+        })
+    }
+
+    /// Stop a server by its ID, its tools stay saved for its next start
+    async fn stop_server(&self, server_id: String) -> Result<ToolUpdateResponse, String> {
+        let server_id = self.resolve_server_id(&server_id).await;
+        let mcp_state = self.mcp_state.read().await.clone();
+        info!("Received request to stop tool: {}", server_id);
+
+        {
+            let registry = mcp_state.tool_registry.read().await;
+            if registry.get_server(&server_id).is_err() {
+                return Ok(ToolUpdateResponse {
+                    success: false,
+                    message: format!("Tool with ID '{}' not found", server_id),
+                });
+            }
+            // Not started again with Dockmaster
+            if let Err(e) = registry.save_server_run_state(&server_id, false) {
+                warn!("Failed to record run state of {}: {}", server_id, e);
+            }
+        }
+
+        if !mcp_state.mcp_clients.read().await.contains_key(&server_id) {
+            return Ok(ToolUpdateResponse {
+                success: true,
+                message: format!("Tool '{}' is not running", server_id),
+            });
+        }
+        if let Err(e) = mcp_state.stop_server(&server_id).await {
+            error!("Failed to stop tool {}: {}", server_id, e);
             return Ok(ToolUpdateResponse {
                 success: false,
-                message: format!("Tool with ID '{}' not found", server_id),
+                message: format!("Failed to stop tool: {}", e),
             });
         }
+        self.publish_tools_update(vec![server_id.clone()]).await;
 
-        info!("Tool '{}' found, attempting to restart", server_id);
+        info!("Successfully stopped tool: {}", server_id);
+        Ok(ToolUpdateResponse {
+            success: true,
+            message: format!("Tool '{}' stopped", server_id),
+        })
+    }
 
-        // Restart the tool using MCPState
-        let restart_result = mcp_state.restart_server(&server_id).await;
+    async fn restart_all_servers(&self) -> Result<BulkServerResponse, String> {
+        self.restart_servers(None).await
+    }
 
-        match restart_result {
-            Ok(_) => {
-                info!("Successfully restarted tool: {}", server_id);
-                Ok(ToolUpdateResponse {
-                    success: true,
-                    message: format!("Tool '{}' restarted successfully", server_id),
-                })
-            }
-            Err(e) => {
-                error!("Failed to restart tool {}: {}", server_id, e);
-                Ok(ToolUpdateResponse {
-                    success: false,
-                    message: format!("Failed to restart tool: {}", e),
-                })
-            }
+    /// Buffered stderr of a server, see [`crate::server_logs`]
+    async fn get_server_logs(
+        &self,
+        server_id: String,
+        since: Option<u64>,
+    ) -> Result<ServerLogSnapshot, String> {
+        let server_id = self.resolve_server_id(&server_id).await;
+        let mcp_state = self.mcp_state.read().await;
+        if let Err(e) = mcp_state.tool_registry.read().await.get_server(&server_id) {
+            return Err(format!("Tool with ID '{}' not found: {}", server_id, e));
         }
+        Ok(mcp_state.server_logs.snapshot(&server_id, since))
+    }
+
+    /// When a server started, crashed, restarted and stopped, see [`crate::lifecycle`]
+    async fn get_server_history(&self, server_id: String) -> Result<ServerHistory, String> {
+        let server_id = self.resolve_server_id(&server_id).await;
+        let mcp_state = self.mcp_state.read().await;
+        let events = {
+            let registry = mcp_state.tool_registry.read().await;
+            if let Err(e) = registry.get_server(&server_id) {
+                return Err(format!("Tool with ID '{}' not found: {}", server_id, e));
+            }
+            registry.get_lifecycle_history(&server_id)
+        };
+        let uptime = mcp_state.uptime(&server_id).await;
+        Ok(ServerHistory {
+            server_id,
+            events,
+            uptime,
+        })
     }
 
     /// Initialize and start background mcp services
@@ -513,6 +884,9 @@ impl McpCoreProxyExt for MCPCore {
             error!("Failed to initialize state from database: {}", e);
         }
 
+        // Nothing is running yet, any recorded process is left over from a crash
+        self.cleanup_orphans().await;
+
         // Get all tools from database
         let tools = match self.tool_registry.read().await.get_all_servers() {
             Ok(tools) => tools,
@@ -521,39 +895,85 @@ impl McpCoreProxyExt for MCPCore {
                 return Err(anyhow::anyhow!("Failed to get tools from database: {}", e));
             }
         };
-        
+
+        let run_states = self
+            .tool_registry
+            .read()
+            .await
+            .get_server_run_states()
+            .unwrap_or_else(|e| {
+                warn!("Failed to read server run states: {}", e);
+                HashMap::new()
+            });
+
         info!("MCP state initialized, preparing to restart enabled tools");
 
-        // Update the state with the new registry
-        // Create a vector of futures for parallel execution
-        let mut restart_futures = Vec::new();
+        // Enabled servers to start
+        let mut to_start = Vec::new();
 
-        // Prepare restart tasks for all enabled tools
         for (tool_id_str, metadata) in tools {
-            if metadata.enabled {
+            let lazy = metadata
+                .configuration
+                .as_ref()
+                .and_then(|configuration| configuration.start_mode)
+                == Some(StartMode::Lazy);
+            if metadata.enabled && !run_states.get(&tool_id_str).copied().unwrap_or(true) {
+                info!(
+                    "Server {} was stopped by the user, not starting it",
+                    tool_id_str
+                );
+            } else if metadata.enabled && lazy {
+                let count = self
+                    .mcp_state
+                    .read()
+                    .await
+                    .serve_saved_tools(&tool_id_str)
+                    .await;
+                info!(
+                    "Server {} starts on its first tool call, listing its {} saved tools",
+                    tool_id_str, count
+                );
+            } else if metadata.enabled {
                 info!("Found enabled tool: {}", tool_id_str);
-                let tool_id = tool_id_str.clone();
-                let mcp_state_arc_clone = self.mcp_state.clone();
+                to_start.push(tool_id_str);
+            }
+        }
 
-                // Create a future for each tool restart
+        // Every server starts at once, each is served and reported as soon as it is up
+        let total = to_start.len();
+        let done = Arc::new(AtomicUsize::new(0));
+        let restart_futures: Vec<_> = to_start
+            .into_iter()
+            .map(|tool_id| {
+                let core = self.clone();
+                let done = done.clone();
                 let restart_future = async move {
-                    let mcp_state_clone_write_guard = mcp_state_arc_clone.read().await;
-                    match mcp_state_clone_write_guard.restart_server(&tool_id).await {
-                        Ok(()) => {
-                            info!("Successfully spawned process for tool: {}", tool_id);
-                        }
-                        Err(e) => {
-                            error!("Failed to spawn process for tool {}: {}", tool_id, e);
-                        }
+                    let mcp_state = core.mcp_state.read().await.clone();
+                    let result = mcp_state.restart_server(&tool_id).await;
+                    match &result {
+                        Ok(()) => info!("Successfully spawned process for tool: {}", tool_id),
+                        Err(e) => error!("Failed to spawn process for tool {}: {}", tool_id, e),
                     }
-
-                    // Return the tool_id for logging purposes
-                    tool_id
+                    let tool_count = mcp_state
+                        .server_tools
+                        .read()
+                        .await
+                        .get(&tool_id)
+                        .map_or(0, |tools| tools.len());
+                    core.publish_tools_update(vec![tool_id.clone()]).await;
+                    core.event_bus
+                        .publish(DockmasterEvent::StartupProgress(StartupProgress {
+                            success: result.is_ok(),
+                            error: result.err(),
+                            tool_count,
+                            done: done.fetch_add(1, Ordering::SeqCst) + 1,
+                            total,
+                            server_id: tool_id,
+                        }));
                 };
-
-                restart_futures.push(restart_future);
-            }
-        }
+                (tool_id, restart_future)
+            })
+            .collect();
 
         // Execute all restart tasks in parallel
         if !restart_futures.is_empty() {
@@ -561,10 +981,14 @@ impl McpCoreProxyExt for MCPCore {
                 "Starting parallel initialization of {} tools",
                 restart_futures.len()
             );
-            let results = future::join_all(restart_futures).await;
+            let started = self
+                .fan_out(restart_futures, total, |server_id, _| {
+                    info!("Server {} started after the others", server_id);
+                })
+                .await;
             info!(
                 "Completed parallel initialization of {} tools",
-                results.len()
+                started.results.len()
             );
         } else {
             info!("No enabled tools found to initialize");
@@ -573,19 +997,83 @@ impl McpCoreProxyExt for MCPCore {
         Ok(())
     }
 
-    /// Kill all running processes
-    async fn kill_all_processes(&self) -> Result<()> {
+    async fn cleanup_orphans(&self) -> OrphanCleanupReport {
+        let report = self
+            .mcp_state
+            .read()
+            .await
+            .cleanup_orphaned_processes()
+            .await;
+        if !report.is_empty() {
+            warn!(
+                "Cleaned up {} orphaned process(es) and {} container(s) left by a previous run: {}",
+                report.processes_terminated,
+                report.containers_removed,
+                report.servers.join(", ")
+            );
+            self.event_bus
+                .publish(DockmasterEvent::OrphansCleaned(report.clone()));
+        }
+        report
+    }
+
+    /// Stop all running processes, returning how each server stopped
+    async fn kill_all_processes(&self) -> Result<HashMap<String, ShutdownOutcome>> {
         let mcp_state = self.mcp_state.read().await;
         match mcp_state.kill_all_processes().await {
-            Ok(_) => Ok(()),
+            Ok(outcomes) => Ok(outcomes),
             Err(e) => Err(anyhow::anyhow!("Failed to kill all processes: {}", e)),
         }
     }
 
+    async fn startup_timeout_secs(&self) -> u64 {
+        let mcp_state = self.mcp_state.read().await;
+        mcp_state.startup_timeout().await.as_secs()
+    }
+
+    async fn set_startup_timeout_secs(&self, secs: u64) -> Result<(), String> {
+        if secs == 0 {
+            return Err("Servers must be given at least a second to start".to_string());
+        }
+        self.tool_registry
+            .read()
+            .await
+            .save_setting(STARTUP_TIMEOUT_SETTING, &secs.to_string())
+    }
+
+    async fn shutdown_grace_secs(&self) -> u64 {
+        let mcp_state = self.mcp_state.read().await;
+        mcp_state.shutdown_grace_period().await.as_secs()
+    }
+
+    async fn set_shutdown_grace_secs(&self, secs: u64) -> Result<(), String> {
+        self.tool_registry
+            .read()
+            .await
+            .save_setting(SHUTDOWN_GRACE_SETTING, &secs.to_string())
+    }
+
+    async fn max_failed_starts(&self) -> u32 {
+        let mcp_state = self.mcp_state.read().await;
+        mcp_state.max_failed_starts().await
+    }
+
+    async fn set_max_failed_starts(&self, limit: u32) -> Result<(), String> {
+        if limit == 0 {
+            return Err("Servers must be allowed at least one failed start".to_string());
+        }
+        self.tool_registry
+            .read()
+            .await
+            .save_setting(MAX_FAILED_STARTS_SETTING, &limit.to_string())
+    }
+
     /// Import a server from a GitHub repository URL
+    #[cfg(feature = "registry")]
     async fn import_server_from_url(
         &self,
         github_url: String,
+        enabled: Option<bool>,
     ) -> Result<ServerRegistrationResponse, String> {
         info!("Importing server from URL: {}", github_url);
 
@@ -628,7 +1116,7 @@ impl McpCoreProxyExt for MCPCore {
         if let Ok(package_json_content) = package_json_result {
             info!("Found package.json, processing as Node.js project");
             return self
-                .process_nodejs_project(package_json_content, &repo_info, env_vars)
+                .process_nodejs_project(package_json_content, &repo_info, env_vars, enabled)
                 .await;
         }
 
@@ -639,7 +1127,7 @@ impl McpCoreProxyExt for MCPCore {
         if let Ok(pyproject_toml_content) = pyproject_toml_result {
             info!("Found pyproject.toml, processing as Python project");
             return self
-                .process_python_project(pyproject_toml_content, &repo_info, env_vars)
+                .process_python_project(pyproject_toml_content, &repo_info, env_vars, enabled)
                 .await;
         }
 
@@ -656,6 +1144,7 @@ impl McpCoreProxyExt for MCPCore {
         package_json_content: String,
         repo_info: &GitHubRepo,
         env_vars: HashSet<String>,
+        enabled: Option<bool>,
     ) -> Result<ServerRegistrationResponse, String> {
         // Parse package.json
         let package_json: Value = serde_json::from_str(&package_json_content)
@@ -676,7 +1165,7 @@ impl McpCoreProxyExt for MCPCore {
             .to_string();
 
         // Create server ID from owner/repo
-        let server_id = format!("{}/{}", repo_info.owner, repo_info.repo);
+        let server_id = slugify_server_id(&format!("{}/{}", repo_info.owner, repo_info.repo));
 
         // Create server name from package name
         let server_name = format!("{} MCP Server", package_name);
@@ -688,8 +1177,9 @@ impl McpCoreProxyExt for MCPCore {
                 var_name.clone(),
                 ServerEnvironment {
                     description: format!("Extracted from README.md: {}", var_name),
-                    default: Some("".to_string()), // Empty default value
+                    default: Some(EnvValue::from("")), // Empty default value
                     required: true,
+                    bool_format: EnvBoolFormat::default(),
                 },
             );
         }
@@ -699,6 +1189,20 @@ impl McpCoreProxyExt for MCPCore {
             command: Some("npx".to_string()),
             args: Some(vec!["-y".to_string(), package_name.clone()]),
             env: Some(env_map),
+            priority: None,
+            cpu_limit: None,
+            max_memory_mb: None,
+            sandbox: None,
+            inherit_env: None,
+            env_passthrough: None,
+            truncate_descriptions: None,
+            strict_output_schema: None,
+            share_instructions: None,
+            tool_filter: None,
+            platforms: None,
+            start_mode: None,
+            cwd: None,
+            lenient_initialize: None,
         });
 
         // Create distribution
@@ -715,6 +1219,12 @@ impl McpCoreProxyExt for MCPCore {
             tools_type: "node".to_string(),
             configuration,
             distribution,
+            provenance: Some(ServerProvenance::new(
+                ProvenanceSource::Url,
+                Some(repo_info.url()),
+            )),
+            authentication: None,
+            enabled,
         };
 
         // Register the server
@@ -727,6 +1237,7 @@ impl McpCoreProxyExt for MCPCore {
         pyproject_toml_content: String,
         repo_info: &GitHubRepo,
         env_vars: HashSet<String>,
+        enabled: Option<bool>,
     ) -> Result<ServerRegistrationResponse, String> {
         // Parse pyproject.toml
         let pyproject_toml: Table = pyproject_toml_content
@@ -768,7 +1279,7 @@ impl McpCoreProxyExt for MCPCore {
         };
 
         // Create server ID from owner/repo
-        let server_id = format!("{}/{}", repo_info.owner, repo_info.repo);
+        let server_id = slugify_server_id(&format!("{}/{}", repo_info.owner, repo_info.repo));
 
         // Create server name from package name
         let server_name = format!("{} MCP Server", package_name);
@@ -780,8 +1291,9 @@ impl McpCoreProxyExt for MCPCore {
                 var_name.clone(),
                 ServerEnvironment {
                     description: format!("Extracted from README.md: {}", var_name),
-                    default: Some("".to_string()), // Empty default value
+                    default: Some(EnvValue::from("")), // Empty default value
                     required: true,
+                    bool_format: EnvBoolFormat::default(),
                 },
             );
         }
@@ -792,6 +1304,20 @@ impl McpCoreProxyExt for MCPCore {
                 command: Some("uvx".to_string()),
                 args: Some(vec!["run".to_string(), script]),
                 env: Some(env_map),
+                priority: None,
+                cpu_limit: None,
+                max_memory_mb: None,
+                sandbox: None,
+                inherit_env: None,
+                env_passthrough: None,
+                truncate_descriptions: None,
+                strict_output_schema: None,
+                share_instructions: None,
+                tool_filter: None,
+                platforms: None,
+                start_mode: None,
+                cwd: None,
+                lenient_initialize: None,
             })
         } else {
             // Fallback to python -m if no script found
@@ -799,6 +1325,20 @@ impl McpCoreProxyExt for MCPCore {
                 command: Some("python".to_string()),
                 args: Some(vec!["-m".to_string(), package_name.replace("-", "_")]),
                 env: Some(env_map),
+                priority: None,
+                cpu_limit: None,
+                max_memory_mb: None,
+                sandbox: None,
+                inherit_env: None,
+                env_passthrough: None,
+                truncate_descriptions: None,
+                strict_output_schema: None,
+                share_instructions: None,
+                tool_filter: None,
+                platforms: None,
+                start_mode: None,
+                cwd: None,
+                lenient_initialize: None,
             })
         };
 
@@ -816,9 +1356,533 @@ impl McpCoreProxyExt for MCPCore {
             tools_type: "python".to_string(),
             configuration,
             distribution,
+            provenance: Some(ServerProvenance::new(
+                ProvenanceSource::Url,
+                Some(repo_info.url()),
+            )),
+            authentication: None,
+            enabled,
         };
 
         // Register the server
         self.register_server(request).await
     }
 }
+
+impl MCPCore {
+    /// Restart a server by its ID, `cause` telling its lifecycle history why
+    pub(crate) async fn restart_server_as(
+        &self,
+        server_id: String,
+        cause: LifecycleKind,
+    ) -> Result<ToolUpdateResponse, String> {
+        let server_id = self.resolve_server_id(&server_id).await;
+        let mcp_state = self.mcp_state.read().await;
+        info!("Received request to restart tool: {}", server_id);
+
+        // Check if the tool exists
+        let tool_exists = {
+            let registry = mcp_state.tool_registry.read().await;
+            registry.get_server(&server_id).is_ok()
+        };
+
+        if !tool_exists {
+            error!("Tool with ID '{}' not found for restart", server_id);
+            return Ok(ToolUpdateResponse {
+                success: false,
+                message: format!("Tool with ID '{}' not found", server_id),
+            });
+        }
+
+        info!("Tool '{}' found, attempting to restart", server_id);
+        if let Err(e) = mcp_state
+            .tool_registry
+            .read()
+            .await
+            .save_server_run_state(&server_id, true)
+        {
+            warn!("Failed to record run state of {}: {}", server_id, e);
+        }
+
+        // Restart the tool using MCPState
+        let restart_result = mcp_state.restart_server_because(&server_id, cause).await;
+
+        match restart_result {
+            Ok(_) => {
+                info!("Successfully restarted tool: {}", server_id);
+                Ok(ToolUpdateResponse {
+                    success: true,
+                    message: format!("Tool '{}' restarted successfully", server_id),
+                })
+            }
+            Err(e) => {
+                error!("Failed to restart tool {}: {}", server_id, e);
+                Ok(ToolUpdateResponse {
+                    success: false,
+                    message: format!("Failed to restart tool: {}", e),
+                })
+            }
+        }
+    }
+
+    /// Save a checked registration and start the server unless it was registered disabled
+    ///
+    /// Every registration ends up here, see [`crate::models::registration`].
+    pub(crate) async fn register_resolved(
+        &self,
+        mut registration: ResolvedRegistration,
+        progress: Option<InstallProgressReporter>,
+    ) -> Result<ServerRegistrationResponse, String> {
+        let mut warnings = std::mem::take(&mut registration.warnings);
+        let registry = self.tool_registry.write().await;
+
+        // Ids from registry entries, imports and clients are made safe to use in proxy
+        // ids, paths and container names
+        let server_id =
+            match registry.assign_server_id(&registration.server_id, &registration.server_name) {
+                Ok(server_id) => server_id,
+                Err(e) => {
+                    if let Some(progress) = &progress {
+                        progress.report(InstallStage::Failed, Some(e.clone()));
+                    }
+                    return Err(e);
+                }
+            };
+        if server_id != registration.server_id {
+            warn!(
+                "Server id '{}' is not valid, registering as '{}'",
+                registration.server_id, server_id
+            );
+            warnings.push(format!(
+                "Server id '{}' is not valid, the server was registered as '{}'",
+                registration.server_id, server_id
+            ));
+        }
+        info!("Generated server ID: {}", server_id);
+
+        let enabled = registration.enabled;
+        let server = registration.definition();
+
+        // Save the tool in the registry
+        if let Err(e) = registry.save_server(&server_id, &server) {
+            if let Some(progress) = &progress {
+                progress.report(InstallStage::Failed, Some(e.clone()));
+            }
+            return Err(e);
+        }
+
+        let provenance = &registration.provenance;
+        if provenance.is_llm_initiated() {
+            warn!(
+                "Server {} was registered by a model (client: {})",
+                server_id,
+                provenance.client.as_deref().unwrap_or("unknown")
+            );
+        }
+        if let Err(e) = registry.save_server_provenance(&server_id, provenance) {
+            warn!("Failed to record provenance of {}: {}", server_id, e);
+        }
+        if let Err(e) = registry.save_server_run_state(&server_id, enabled) {
+            warn!("Failed to record run state of {}: {}", server_id, e);
+        }
+        drop(registry);
+
+        // Disabled servers are configured first, enabling them starts them as usual
+        if !enabled {
+            info!("Server {} registered disabled, not starting it", server_id);
+            if let Some(progress) = &progress {
+                progress.report(InstallStage::Done, Some("not started".to_string()));
+            }
+            return Ok(ServerRegistrationResponse {
+                success: true,
+                message: format!(
+                    "Tool '{}' registered, not started",
+                    registration.server_name
+                ),
+                server_id: Some(server_id),
+                status: Some(ServerStatus::Stopped),
+                tool_count: Some(0),
+                startup_ms: None,
+                warnings,
+                operation_id: progress.map(|p| p.operation_id().to_string()),
+            });
+        }
+
+        // Packages are fetched by the runtime (npx, uvx, docker) when the server first starts
+        if let (Some(progress), Some(distribution)) = (&progress, &server.distribution) {
+            progress.report(
+                InstallStage::Pulling,
+                Some(format!("{} {}", distribution.r#type, distribution.package)),
+            );
+        }
+
+        let mcp_state_clone = self.mcp_state.clone();
+        {
+            // Create a default empty tools list
+            let mcp_state = mcp_state_clone.write().await;
+            let mut server_tools = mcp_state.server_tools.write().await;
+            server_tools.insert(server_id.clone(), Vec::new());
+        } // All locks are released at this point
+
+        // Now call restart_server after the locks have been released
+        let mcp_state = mcp_state_clone.read().await;
+        let restart_result = mcp_state
+            .restart_server_with_progress(&server_id, progress.as_ref())
+            .await;
+
+        let missing = mcp_state
+            .needs_configuration
+            .read()
+            .await
+            .get(&server_id)
+            .cloned();
+        if let Some(progress) = &progress {
+            match &restart_result {
+                Ok(()) if missing.is_some() => {
+                    progress.report(InstallStage::Done, Some("needs configuration".to_string()))
+                }
+                Ok(()) => progress.report(InstallStage::Done, None),
+                Err(e) => progress.report(InstallStage::Failed, Some(e.clone())),
+            }
+        }
+
+        // The server stays registered, the failure is reported for the user to fix it
+        let message = match restart_result {
+            Ok(()) if missing.is_some() => {
+                warnings.push(format!(
+                    "Set {} in the configuration of the server to start it",
+                    missing.unwrap_or_default().join(", ")
+                ));
+                format!(
+                    "Tool '{}' registered, it needs configuration before it starts",
+                    registration.server_name
+                )
+            }
+            Ok(()) => format!(
+                "Tool '{}' registered successfully",
+                registration.server_name
+            ),
+            Err(e) => {
+                warnings.push(format!("Server failed to start: {}", e));
+                format!(
+                    "Tool '{}' registered but failed to start",
+                    registration.server_name
+                )
+            }
+        };
+
+        info!(
+            "Tool registration completed for: {}",
+            registration.server_name
+        );
+        let status = mcp_state.server_status(&server_id).await;
+        let tool_count = mcp_state
+            .server_tools
+            .read()
+            .await
+            .get(&server_id)
+            .map_or(0, |tools| tools.len());
+        let startup_ms = mcp_state
+            .mcp_clients
+            .read()
+            .await
+            .get(&server_id)
+            .map(|client| client.startup_ms);
+        drop(mcp_state);
+        // MCP clients see its tools right away
+        self.publish_tools_update(vec![server_id.clone()]).await;
+        Ok(ServerRegistrationResponse {
+            success: true,
+            message,
+            server_id: Some(server_id),
+            status: Some(status),
+            tool_count: Some(tool_count),
+            startup_ms,
+            warnings,
+            operation_id: progress.map(|p| p.operation_id().to_string()),
+        })
+    }
+}
+
+impl MCPCore {
+    /// Cancel the tool call tracked under `call_id`, see [`crate::cancellation`]
+    ///
+    /// Returns false when no such call runs, it may have finished already.
+    pub fn cancel_tool_call(&self, call_id: &str) -> bool {
+        let cancelled = self.pending_calls.cancel(call_id);
+        if cancelled {
+            info!("Cancelling tool call {}", call_id);
+        } else {
+            log::debug!("No running tool call {} to cancel", call_id);
+        }
+        cancelled
+    }
+
+    /// Handle a notification `server_id` sent on its own, see
+    /// `MCPState::dispatch_server_notification`
+    pub async fn dispatch_server_notification(
+        &self,
+        server_id: &str,
+        message: &Value,
+    ) -> Option<ServerNotification> {
+        let mcp_state = self.mcp_state.read().await.clone();
+        mcp_state
+            .dispatch_server_notification(server_id, message)
+            .await
+    }
+
+    /// Start a lazy server that isn't running for a call to one of its tools
+    ///
+    /// Returns a warning that the call waited for the server to start. Servers stopped
+    /// by the user are left stopped.
+    async fn start_lazy_server(&self, server_id: &str) -> Result<Option<String>, String> {
+        let mcp_state = self.mcp_state.read().await.clone();
+        if mcp_state.mcp_clients.read().await.contains_key(server_id) {
+            return Ok(None);
+        }
+        let (server, run_states) = {
+            let registry = self.tool_registry.read().await;
+            let Ok(server) = registry.get_server(server_id) else {
+                return Ok(None);
+            };
+            (server, registry.get_server_run_states().unwrap_or_default())
+        };
+        let lazy = server
+            .configuration
+            .as_ref()
+            .and_then(|configuration| configuration.start_mode)
+            == Some(StartMode::Lazy);
+        let stopped_by_user = run_states.get(server_id) == Some(&false);
+        if !server.enabled || !lazy || stopped_by_user {
+            return Ok(None);
+        }
+
+        info!(
+            "Starting lazy server {} for a call to one of its tools",
+            server_id
+        );
+        if !mcp_state.start_server_if_stopped(server_id).await? {
+            // Started by a call that came first, or waiting for configuration
+            return match mcp_state.mcp_clients.read().await.contains_key(server_id) {
+                true => Ok(None),
+                false => Err(format!("Server {} did not start", server_id)),
+            };
+        }
+        let startup_ms = mcp_state
+            .mcp_clients
+            .read()
+            .await
+            .get(server_id)
+            .map(|mcp_client| mcp_client.startup_ms)
+            .unwrap_or_default();
+        self.publish_tools_update(vec![server_id.to_string()]).await;
+        Ok(Some(format!(
+            "Server {} was started for this call, it took {} ms",
+            server_id, startup_ms
+        )))
+    }
+
+    /// [`Self::call_server_tool`], restarting the server if its connection died and
+    /// calling the tool again when the server never got the call
+    async fn call_server_tool_reconnecting(
+        &self,
+        server_id: &str,
+        tool_id: &str,
+        arguments: &Value,
+        meta: Option<Value>,
+    ) -> Result<(Value, Vec<String>), String> {
+        let spawn_id = self.mcp_state.read().await.spawn_id_of(server_id).await;
+        let called = self
+            .call_server_tool(server_id, tool_id, arguments, meta.clone())
+            .await;
+        let error = match called {
+            Err(e) => e,
+            result => return result,
+        };
+        let (Some(spawn_id), Some(dead)) = (spawn_id, DeadConnection::of(&error)) else {
+            return Err(error);
+        };
+        let retry = dead.may_retry(false);
+        self.mcp_state
+            .read()
+            .await
+            .reconnect_server(server_id, &spawn_id, &error, retry)
+            .await
+            .map_err(|e| format!("{}. {}", error, e))?;
+        if !retry {
+            return Err(format!("{}. The server was restarted", error));
+        }
+
+        let (result, mut warnings) = self
+            .call_server_tool(server_id, tool_id, arguments, meta)
+            .await?;
+        warnings.push(format!(
+            "Server {} was restarted after its connection died",
+            server_id
+        ));
+        Ok((result, warnings))
+    }
+
+    /// Call a tool on its server, or answer from its recordings in replay mode
+    ///
+    /// Returns the result with the problems that didn't fail the call.
+    async fn call_server_tool(
+        &self,
+        server_id: &str,
+        tool_id: &str,
+        arguments: &Value,
+        meta: Option<Value>,
+    ) -> Result<(Value, Vec<String>), String> {
+        let mcp_state = self.mcp_state.read().await;
+        let call_mode = self.tool_registry.read().await.server_call_mode(server_id);
+        if call_mode.mode == CallMode::Replay {
+            match self
+                .recording_store(server_id)
+                .await
+                .find(tool_id, arguments)
+            {
+                Some(result) => {
+                    info!("Replaying {} on {} from its recording", tool_id, server_id);
+                    let warning = format!("Replayed from the recordings of {}", server_id);
+                    return Ok((result, vec![warning]));
+                }
+                None if call_mode.on_miss == ReplayMiss::Error => {
+                    return Err(format!(
+                        "No recording of {} on {} with these arguments",
+                        tool_id, server_id
+                    ));
+                }
+                None => info!(
+                    "No recording of {} on {}, calling the server",
+                    tool_id, server_id
+                ),
+            }
+        }
+
+        // Execute the tool on the server
+        let mcp_clients = mcp_state.mcp_clients.read().await;
+        let mcp_client = mcp_clients.get(server_id);
+        if mcp_client.is_none() {
+            return Err(format!("Server with ID '{}' not found", server_id));
+        }
+        let mcp_client = mcp_client.unwrap();
+
+        // Check if server is stopped
+        // if matches!(mcp_client.server_status, ServerStatus::Stopped) {
+        //     return Err(format!("Server '{}' is stopped", server_id));
+        // }
+
+        log_payload(
+            &format!("Calling tool {} on {} with", tool_id, server_id),
+            arguments,
+        );
+        let in_flight = mcp_state.metrics.start_call(server_id);
+        let started = std::time::Instant::now();
+        let result = mcp_client
+            .client
+            .call_tool(tool_id, arguments.clone(), meta)
+            .await;
+        drop(in_flight);
+        mcp_state
+            .tool_stats
+            .record(server_id, tool_id, started.elapsed(), result.is_ok());
+        mcp_state
+            .metrics
+            .record_call(server_id, tool_id, started.elapsed(), result.is_ok());
+        let result = match result {
+            Ok(result) => result,
+            // Errors answered by the server already say so
+            Err(e @ MCPError::ToolExecutionError(_)) => return Err(e.to_string()),
+            Err(e) => return Err(format!("Tool execution error: {}", e)),
+        };
+        log_payload(
+            &format!("Tool {} on {} returned", tool_id, server_id),
+            &result,
+        );
+
+        // Check the structured result against the output schema the tool declared
+        let output_schema = mcp_state
+            .server_tools
+            .read()
+            .await
+            .get(server_id)
+            .and_then(|tools| {
+                tools
+                    .iter()
+                    .find(|tool| tool.name == tool_id || tool.id == tool_id)
+                    .and_then(|tool| tool.output_schema.clone())
+            });
+        let mut warnings = Vec::new();
+        if let Some(output_schema) = output_schema {
+            let problems = check_structured_content(&result, &output_schema);
+            if !problems.is_empty() {
+                let message = format!(
+                    "Result of {} doesn't match its output schema: {}",
+                    tool_id,
+                    problems.join("; ")
+                );
+                let strict = self
+                    .tool_registry
+                    .read()
+                    .await
+                    .get_server(server_id)
+                    .ok()
+                    .and_then(|server| server.configuration)
+                    .and_then(|configuration| configuration.strict_output_schema)
+                    .unwrap_or(false);
+                if strict {
+                    return Err(message);
+                }
+                warn!("{}", message);
+                warnings.push(message);
+            }
+        }
+
+        if call_mode.mode == CallMode::Record {
+            let call = RecordedCall::new(tool_id, arguments, result.clone());
+            if let Err(e) = self.recording_store(server_id).await.append(&call) {
+                warn!("Failed to record {} on {}: {}", tool_id, server_id, e);
+            }
+        }
+
+        Ok((result, warnings))
+    }
+}
+
+/// Response to a call rejected by a hook
+fn rejected_by_hook(policy_error: PolicyError) -> ToolExecutionResponse {
+    ToolExecutionResponse {
+        success: false,
+        result: None,
+        error: Some(policy_error.to_string()),
+        warnings: Vec::new(),
+        policy_error: Some(policy_error),
+        rate_limited: None,
+        cancelled: false,
+    }
+}
+
+fn refused_by_rate_limit(rate_limited: RateLimited) -> ToolExecutionResponse {
+    ToolExecutionResponse {
+        success: false,
+        result: None,
+        error: Some(rate_limited.to_string()),
+        warnings: Vec::new(),
+        policy_error: None,
+        rate_limited: Some(rate_limited),
+        cancelled: false,
+    }
+}
+
+/// Response to a call its client cancelled
+fn cancelled_call() -> ToolExecutionResponse {
+    ToolExecutionResponse {
+        success: false,
+        result: None,
+        error: Some(CANCELLED_ERROR.to_string()),
+        warnings: Vec::new(),
+        policy_error: None,
+        rate_limited: None,
+        cancelled: true,
+    }
+}
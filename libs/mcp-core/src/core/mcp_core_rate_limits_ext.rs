@@ -0,0 +1,114 @@
+use log::{info, warn};
+
+use super::mcp_core::MCPCore;
+use crate::rate_limits::{RateLimit, RateLimited, RatePermit, ToolRateLimit, RATE_LIMITS_SETTING};
+
+/// Rate limits of tool calls, see [`crate::rate_limits`]
+pub trait McpCoreRateLimitsExt {
+    /// Configured limits, none when the setting is missing or can't be read
+    fn tool_rate_limits(&self) -> impl std::future::Future<Output = Vec<ToolRateLimit>> + Send;
+    /// Limit of a tool, None when it has none
+    fn tool_rate_limit(
+        &self,
+        server_id: &str,
+        tool_name: &str,
+    ) -> impl std::future::Future<Output = Option<RateLimit>> + Send;
+    /// Set the limit of a tool, removing it when every limit is unset
+    fn set_tool_rate_limit(
+        &self,
+        server_id: &str,
+        tool_name: &str,
+        limit: RateLimit,
+    ) -> impl std::future::Future<Output = Result<(), String>> + Send;
+    /// Let a call through unless its tool reached a limit, counting it in the stats
+    /// when refused
+    ///
+    /// No permit is needed when the tool has no limit.
+    fn acquire_tool_call(
+        &self,
+        server_id: &str,
+        tool_name: &str,
+    ) -> impl std::future::Future<Output = Result<Option<RatePermit>, RateLimited>> + Send;
+}
+
+impl McpCoreRateLimitsExt for MCPCore {
+    async fn tool_rate_limits(&self) -> Vec<ToolRateLimit> {
+        let registry = self.tool_registry.read().await;
+        let Ok(json) = registry.get_setting(RATE_LIMITS_SETTING) else {
+            return Vec::new();
+        };
+        serde_json::from_str(&json).unwrap_or_else(|e| {
+            warn!("Ignoring the saved rate limits, they can't be read: {}", e);
+            Vec::new()
+        })
+    }
+
+    async fn tool_rate_limit(&self, server_id: &str, tool_name: &str) -> Option<RateLimit> {
+        self.tool_rate_limits()
+            .await
+            .into_iter()
+            .find(|l| l.server_id == server_id && l.tool_name == tool_name)
+            .map(|l| l.limit)
+    }
+
+    async fn set_tool_rate_limit(
+        &self,
+        server_id: &str,
+        tool_name: &str,
+        limit: RateLimit,
+    ) -> Result<(), String> {
+        limit.validate()?;
+        let server_id = self.resolve_server_id(server_id).await;
+        let server_id = server_id.as_str();
+        if self
+            .tool_registry
+            .read()
+            .await
+            .get_server(server_id)
+            .is_err()
+        {
+            return Err(format!("Server '{}' not found", server_id));
+        }
+        let mut limits = self.tool_rate_limits().await;
+        limits.retain(|l| l.server_id != server_id || l.tool_name != tool_name);
+        if !limit.is_unlimited() {
+            limits.push(ToolRateLimit {
+                server_id: server_id.to_string(),
+                tool_name: tool_name.to_string(),
+                limit,
+            });
+        }
+        let json = serde_json::to_string(&limits)
+            .map_err(|e| format!("Failed to serialize the rate limits: {}", e))?;
+        self.tool_registry
+            .read()
+            .await
+            .save_setting(RATE_LIMITS_SETTING, &json)?;
+        self.rate_limiter.reset(server_id, tool_name);
+        info!("Saved the rate limit of {} on {}", tool_name, server_id);
+        Ok(())
+    }
+
+    async fn acquire_tool_call(
+        &self,
+        server_id: &str,
+        tool_name: &str,
+    ) -> Result<Option<RatePermit>, RateLimited> {
+        let Some(limit) = self.tool_rate_limit(server_id, tool_name).await else {
+            return Ok(None);
+        };
+        let now = self.clock.now();
+        match self.rate_limiter.acquire(server_id, tool_name, &limit, now) {
+            Ok(permit) => Ok(Some(permit)),
+            Err(rate_limited) => {
+                warn!("{}", rate_limited);
+                self.mcp_state
+                    .read()
+                    .await
+                    .tool_stats
+                    .record_rate_limited(server_id, tool_name);
+                Err(rate_limited)
+            }
+        }
+    }
+}
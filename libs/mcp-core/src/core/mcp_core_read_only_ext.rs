@@ -0,0 +1,32 @@
+use log::info;
+
+use super::mcp_core::MCPCore;
+use crate::read_only::READ_ONLY_SETTING;
+
+/// Read-only mode, see [`crate::read_only`]
+pub trait McpCoreReadOnlyExt {
+    fn is_read_only(&self) -> impl std::future::Future<Output = bool> + Send;
+    fn set_read_only(
+        &self,
+        read_only: bool,
+    ) -> impl std::future::Future<Output = Result<(), String>> + Send;
+}
+
+impl McpCoreReadOnlyExt for MCPCore {
+    async fn is_read_only(&self) -> bool {
+        let registry = self.tool_registry.read().await;
+        registry
+            .get_setting(READ_ONLY_SETTING)
+            .is_ok_and(|value| value == "true")
+    }
+
+    async fn set_read_only(&self, read_only: bool) -> Result<(), String> {
+        let registry = self.tool_registry.read().await;
+        registry.save_setting(READ_ONLY_SETTING, &read_only.to_string())?;
+        info!(
+            "Read-only mode {}",
+            if read_only { "enabled" } else { "disabled" }
+        );
+        Ok(())
+    }
+}
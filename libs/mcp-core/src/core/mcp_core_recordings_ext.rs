@@ -0,0 +1,55 @@
+use log::{info, warn};
+
+use super::mcp_core::MCPCore;
+use crate::models::types::{CallMode, ServerCallMode};
+use crate::recordings::RecordingStore;
+
+/// Recording and replay of the tool calls of servers, see [`crate::recordings`]
+pub trait McpCoreRecordingsExt {
+    /// Set whether the tool calls of `server_id` are recorded or replayed
+    fn set_server_call_mode(
+        &self,
+        server_id: &str,
+        call_mode: ServerCallMode,
+    ) -> impl std::future::Future<Output = Result<ServerCallMode, String>> + Send;
+    /// Store of the recorded calls of `server_id`
+    fn recording_store(
+        &self,
+        server_id: &str,
+    ) -> impl std::future::Future<Output = RecordingStore> + Send;
+}
+
+impl McpCoreRecordingsExt for MCPCore {
+    async fn set_server_call_mode(
+        &self,
+        server_id: &str,
+        call_mode: ServerCallMode,
+    ) -> Result<ServerCallMode, String> {
+        let server_id = self.resolve_server_id(server_id).await;
+        let registry = self.tool_registry.read().await;
+        registry.get_server(&server_id)?;
+        registry.save_server_call_mode(&server_id, &call_mode)?;
+        drop(registry);
+
+        match call_mode.mode {
+            CallMode::Live => info!("Tool calls of {} go to the server", server_id),
+            CallMode::Record => info!(
+                "Recording the tool calls of {} to {}",
+                server_id,
+                self.recording_store(&server_id).await.path().display()
+            ),
+            // Easy to forget, the server no longer sees any call
+            CallMode::Replay => warn!(
+                "Replaying the tool calls of {} from {}",
+                server_id,
+                self.recording_store(&server_id).await.path().display()
+            ),
+        }
+        Ok(call_mode)
+    }
+
+    async fn recording_store(&self, server_id: &str) -> RecordingStore {
+        let data_dir = self.database_manager.read().await.data_dir();
+        RecordingStore::new(&data_dir, server_id)
+    }
+}
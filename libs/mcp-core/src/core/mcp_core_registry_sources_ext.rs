@@ -0,0 +1,123 @@
+use std::sync::Arc;
+
+use log::{info, warn};
+
+use super::mcp_core::MCPCore;
+use crate::models::types::{ErrorResponse, RegistryToolsResponse};
+use crate::registry::registry_sources::{merge_registries, RegistrySource, REDACTED_HEADER_VALUE};
+
+/// Registries listed next to the default one, see [`crate::registry::registry_sources`]
+pub trait McpCoreRegistrySourcesExt {
+    /// Every registry source, credentials left out
+    fn list_registry_sources(
+        &self,
+    ) -> impl std::future::Future<Output = Result<Vec<RegistrySource>, String>> + Send;
+    /// Create or replace a registry source, keeping its credentials unless new ones are given
+    /// and the value of each header given back redacted
+    fn save_registry_source(
+        &self,
+        source: RegistrySource,
+    ) -> impl std::future::Future<Output = Result<RegistrySource, String>> + Send;
+    fn delete_registry_source(
+        &self,
+        source_id: &str,
+    ) -> impl std::future::Future<Output = Result<(), String>> + Send;
+    /// The default registry merged with the tools of every source
+    ///
+    /// A source that can't be fetched is left out with a warning. The default registry
+    /// failing is only an error when there is no other source.
+    fn merged_registry(
+        &self,
+    ) -> impl std::future::Future<Output = Result<RegistryToolsResponse, ErrorResponse>> + Send;
+}
+
+impl McpCoreRegistrySourcesExt for MCPCore {
+    async fn list_registry_sources(&self) -> Result<Vec<RegistrySource>, String> {
+        let sources = self.tool_registry.read().await.get_registry_sources()?;
+        Ok(sources
+            .into_iter()
+            .map(|source| RegistrySource {
+                credentials: None,
+                ..source
+            })
+            .collect())
+    }
+
+    async fn save_registry_source(
+        &self,
+        mut source: RegistrySource,
+    ) -> Result<RegistrySource, String> {
+        source.validate()?;
+        let registry = self.tool_registry.read().await;
+        let saved = registry
+            .get_registry_sources()?
+            .into_iter()
+            .find(|s| s.id == source.id);
+        for (name, value) in source.headers.iter_mut() {
+            if value != REDACTED_HEADER_VALUE {
+                continue;
+            }
+            match saved.as_ref().and_then(|s| s.headers.get(name)) {
+                Some(saved_value) => *value = saved_value.clone(),
+                None => return Err(format!("No saved value for header '{}'", name)),
+            }
+        }
+        if source.credentials.is_none() {
+            source.credentials = saved.and_then(|s| s.credentials);
+        }
+        registry.save_registry_source(&source)?;
+        info!("Saved registry source {} at {}", source.id, source.url);
+        Ok(RegistrySource {
+            credentials: None,
+            ..source
+        })
+    }
+
+    async fn delete_registry_source(&self, source_id: &str) -> Result<(), String> {
+        self.tool_registry
+            .read()
+            .await
+            .delete_registry_source(source_id)?;
+        info!("Deleted registry source {}", source_id);
+        Ok(())
+    }
+
+    async fn merged_registry(&self) -> Result<RegistryToolsResponse, ErrorResponse> {
+        let lookup = self
+            .registry_lookup(|registry| registry.get_registry_sources())
+            .await;
+        let sources = match lookup {
+            Some(Ok(sources)) => sources,
+            Some(Err(e)) => {
+                warn!("Failed to read the registry sources: {}", e);
+                Vec::new()
+            }
+            None => {
+                warn!("The database didn't answer, listing the registry sources last read");
+                // The lookup still holds the registry, a waiting writer mustn't block this
+                self.tool_registry
+                    .try_read()
+                    .ok()
+                    .and_then(|registry| registry.cached_registry_sources())
+                    .unwrap_or_default()
+            }
+        };
+        let registry = match self.registry_cache.get_registry_tools().await {
+            Ok(registry) => Arc::unwrap_or_clone(registry),
+            Err(e) if !sources.is_empty() => {
+                warn!("Listing only the registry sources: {}", e.message);
+                RegistryToolsResponse::default()
+            }
+            Err(e) => return Err(e),
+        };
+
+        let mut fetched = Vec::new();
+        for source in sources {
+            match self.registry_cache.fetch_source(&source).await {
+                Ok(tools) => fetched.push((source.id, tools)),
+                Err(e) => warn!("Leaving out registry source {}: {}", source.id, e.message),
+            }
+        }
+        Ok(merge_registries(registry, fetched))
+    }
+}
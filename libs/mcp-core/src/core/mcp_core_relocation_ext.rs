@@ -0,0 +1,200 @@
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use log::{info, warn};
+
+use super::mcp_core::MCPCore;
+use crate::config_history::ConfigApplyResult;
+use crate::events::DockmasterEvent;
+use crate::registry::server_registry::ServerRegistry;
+use crate::relocation::{
+    find_missing_paths, rewrite_server_paths, EnvironmentFingerprint, MigrationReport, MissingPath,
+    PathMigrationResults, ENVIRONMENT_FINGERPRINT_SETTING, MIGRATION_REPORT_SETTING,
+};
+
+/// Paths left behind when the data directory changed machine, see [`crate::relocation`]
+pub trait McpCoreRelocationExt {
+    /// Compare the machine with the one of the last run, reporting the missing paths of
+    /// the servers when it changed
+    fn check_environment(
+        &self,
+    ) -> impl std::future::Future<Output = Result<Option<MigrationReport>, String>> + Send;
+    /// Report of the last change of machine, if any
+    fn migration_report(
+        &self,
+    ) -> impl std::future::Future<Output = Result<Option<MigrationReport>, String>> + Send;
+    /// Rewrite the missing paths under the previous home directory to the new one, for
+    /// `server_ids` or every server of the report, restarting the running ones
+    fn migrate_server_paths(
+        &self,
+        server_ids: Option<Vec<String>>,
+    ) -> impl std::future::Future<Output = Result<PathMigrationResults, String>> + Send;
+}
+
+impl McpCoreRelocationExt for MCPCore {
+    async fn check_environment(&self) -> Result<Option<MigrationReport>, String> {
+        let current = EnvironmentFingerprint::current();
+        let registry = self.tool_registry.read().await;
+        let previous = registry
+            .get_setting(ENVIRONMENT_FINGERPRINT_SETTING)
+            .ok()
+            .and_then(|json| serde_json::from_str::<EnvironmentFingerprint>(&json).ok());
+        let json = serde_json::to_string(&current)
+            .map_err(|e| format!("Failed to serialize the environment fingerprint: {}", e))?;
+        let previous = match previous {
+            Some(previous) if previous != current => previous,
+            Some(_) => return Ok(None),
+            None => {
+                registry.save_setting(ENVIRONMENT_FINGERPRINT_SETTING, &json)?;
+                return Ok(None);
+            }
+        };
+        warn!(
+            "The data directory was last used on {} ({}, {}), checking the paths of the servers",
+            previous.hostname, previous.os, previous.home_dir
+        );
+
+        let servers = registry.get_all_servers()?;
+        let report = MigrationReport {
+            created_at: now_ms(),
+            missing_paths: find_missing_paths(&servers, &previous, &current, Path::exists),
+            previous,
+            current,
+        };
+        save_report(&registry, &report)?;
+        registry.save_setting(ENVIRONMENT_FINGERPRINT_SETTING, &json)?;
+        drop(registry);
+
+        if !report.missing_paths.is_empty() {
+            warn!(
+                "{} paths of servers {} are missing, {} can be moved under the new home \
+                 directory with system/migrate_paths",
+                report.missing_paths.len(),
+                report.server_ids().join(", "),
+                report.rewritable()
+            );
+            self.event_bus
+                .publish(DockmasterEvent::EnvironmentChanged(report.clone()));
+        }
+        Ok(Some(report))
+    }
+
+    async fn migration_report(&self) -> Result<Option<MigrationReport>, String> {
+        let Ok(json) = self
+            .tool_registry
+            .read()
+            .await
+            .get_setting(MIGRATION_REPORT_SETTING)
+        else {
+            return Ok(None);
+        };
+        serde_json::from_str(&json)
+            .map_err(|e| format!("Failed to read the migration report: {}", e))
+    }
+
+    async fn migrate_server_paths(
+        &self,
+        server_ids: Option<Vec<String>>,
+    ) -> Result<PathMigrationResults, String> {
+        let Some(mut report) = self.migration_report().await? else {
+            return Err("The data directory didn't change machine".to_string());
+        };
+        let server_ids = match server_ids {
+            Some(server_ids) => server_ids,
+            None => report.server_ids(),
+        };
+
+        let mut results = PathMigrationResults::new();
+        for server_id in server_ids {
+            let missing: Vec<_> = report
+                .missing_paths
+                .iter()
+                .filter(|missing| missing.server_id == server_id)
+                .cloned()
+                .collect();
+            let result = self.migrate_paths_of(&server_id, &missing).await;
+            // Unless they were put back as the server failed to start with them
+            if !result.rolled_back && result.snapshot_id.is_some() {
+                report
+                    .missing_paths
+                    .retain(|missing| missing.server_id != server_id || missing.rewrite.is_none());
+            }
+            results.insert(server_id, result);
+        }
+        save_report(&*self.tool_registry.read().await, &report)?;
+        Ok(results)
+    }
+}
+
+impl MCPCore {
+    /// Rewrite the paths of a server, restarting it when it runs
+    async fn migrate_paths_of(
+        &self,
+        server_id: &str,
+        missing: &[MissingPath],
+    ) -> ConfigApplyResult {
+        let not_applied = |message: String| ConfigApplyResult {
+            success: false,
+            message,
+            rolled_back: false,
+            error: None,
+            snapshot_id: None,
+        };
+        let (previous, running) = {
+            let registry = self.tool_registry.write().await;
+            let mut server = match registry.get_server(server_id) {
+                Ok(server) => server,
+                Err(e) => return not_applied(e),
+            };
+            let previous = server.configuration.clone().unwrap_or_default();
+            let rewritten = rewrite_server_paths(&mut server, missing);
+            if rewritten == 0 {
+                return not_applied(format!(
+                    "Server '{}' has no paths under the previous home directory",
+                    server_id
+                ));
+            }
+            if let Err(e) = registry.save_server(server_id, &server) {
+                return not_applied(e);
+            }
+            info!(
+                "Moved {} paths of {} under the new home directory",
+                rewritten, server_id
+            );
+            let running = server.enabled && registry.desired_running(server_id);
+            (previous, running)
+        };
+
+        let snapshot_id = match self.snapshot_config(server_id, &previous).await {
+            Ok(snapshot_id) => snapshot_id,
+            Err(e) => return not_applied(e),
+        };
+        if !running {
+            return ConfigApplyResult {
+                success: true,
+                message: format!(
+                    "Paths of '{}' moved under the new home directory",
+                    server_id
+                ),
+                rolled_back: false,
+                error: None,
+                snapshot_id: Some(snapshot_id),
+            };
+        }
+        self.restart_or_roll_back(server_id, previous, snapshot_id)
+            .await
+    }
+}
+
+fn save_report(registry: &ServerRegistry, report: &MigrationReport) -> Result<(), String> {
+    let json = serde_json::to_string(report)
+        .map_err(|e| format!("Failed to serialize the migration report: {}", e))?;
+    registry.save_setting(MIGRATION_REPORT_SETTING, &json)
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
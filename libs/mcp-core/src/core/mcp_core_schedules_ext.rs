@@ -0,0 +1,226 @@
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use log::{info, warn};
+use serde_json::Value;
+use tokio::task::JoinHandle;
+
+use super::mcp_core::MCPCore;
+use super::mcp_core_proxy_ext::McpCoreProxyExt;
+use crate::models::types::ToolExecutionRequest;
+use crate::schedules::{
+    due_schedules, misfired_schedules, Schedule, ScheduleDefinition, ScheduleRun, SCHEDULER_TICK,
+};
+
+/// Tool calls run on a schedule, see [`crate::schedules`]
+pub trait McpCoreSchedulesExt {
+    /// Every job with its status
+    fn list_schedules(
+        &self,
+    ) -> impl std::future::Future<Output = Result<Vec<Schedule>, String>> + Send;
+    fn get_schedule(
+        &self,
+        schedule_id: &str,
+    ) -> impl std::future::Future<Output = Result<Schedule, String>> + Send;
+    /// Create a job, failing when its id is taken
+    fn create_schedule(
+        &self,
+        schedule: ScheduleDefinition,
+    ) -> impl std::future::Future<Output = Result<Schedule, String>> + Send;
+    /// Replace the definition of an existing job, keeping its status and history
+    fn update_schedule(
+        &self,
+        schedule: ScheduleDefinition,
+    ) -> impl std::future::Future<Output = Result<Schedule, String>> + Send;
+    fn delete_schedule(
+        &self,
+        schedule_id: &str,
+    ) -> impl std::future::Future<Output = Result<(), String>> + Send;
+    /// Most recent runs of a job, newest first
+    fn schedule_runs(
+        &self,
+        schedule_id: &str,
+        limit: i64,
+    ) -> impl std::future::Future<Output = Result<Vec<ScheduleRun>, String>> + Send;
+    /// Run a job now and record the outcome in its history
+    fn run_schedule(
+        &self,
+        schedule: &ScheduleDefinition,
+    ) -> impl std::future::Future<Output = ScheduleRun> + Send;
+    /// Run the jobs in the background as they come due
+    fn start_scheduler(&self) -> JoinHandle<()>;
+}
+
+impl McpCoreSchedulesExt for MCPCore {
+    async fn list_schedules(&self) -> Result<Vec<Schedule>, String> {
+        let schedules = self.tool_registry.read().await.get_schedules()?;
+        let now = now_ms();
+        Ok(schedules
+            .into_iter()
+            .map(|schedule| schedule.with_next_run(now))
+            .collect())
+    }
+
+    async fn get_schedule(&self, schedule_id: &str) -> Result<Schedule, String> {
+        self.list_schedules()
+            .await?
+            .into_iter()
+            .find(|schedule| schedule.definition.id == schedule_id)
+            .ok_or_else(|| format!("Schedule '{}' not found", schedule_id))
+    }
+
+    async fn create_schedule(&self, schedule: ScheduleDefinition) -> Result<Schedule, String> {
+        schedule.validate()?;
+        if self.get_schedule(&schedule.id).await.is_ok() {
+            return Err(format!("Schedule '{}' already exists", schedule.id));
+        }
+        self.tool_registry.read().await.save_schedule(&schedule)?;
+        info!(
+            "Scheduled {} to run {} ({})",
+            schedule.tool_id, schedule.cron, schedule.id
+        );
+        self.get_schedule(&schedule.id).await
+    }
+
+    async fn update_schedule(&self, schedule: ScheduleDefinition) -> Result<Schedule, String> {
+        schedule.validate()?;
+        self.get_schedule(&schedule.id).await?;
+        self.tool_registry.read().await.save_schedule(&schedule)?;
+        info!("Updated schedule {}", schedule.id);
+        self.get_schedule(&schedule.id).await
+    }
+
+    async fn delete_schedule(&self, schedule_id: &str) -> Result<(), String> {
+        self.tool_registry
+            .read()
+            .await
+            .delete_schedule(schedule_id)?;
+        info!("Deleted schedule {}", schedule_id);
+        Ok(())
+    }
+
+    async fn schedule_runs(
+        &self,
+        schedule_id: &str,
+        limit: i64,
+    ) -> Result<Vec<ScheduleRun>, String> {
+        self.tool_registry
+            .read()
+            .await
+            .get_schedule_runs(schedule_id, limit)
+    }
+
+    async fn run_schedule(&self, schedule: &ScheduleDefinition) -> ScheduleRun {
+        info!("Running schedule {}: {}", schedule.id, schedule.tool_id);
+        let started_at = now_ms();
+        let started = Instant::now();
+        let outcome = self
+            .execute_proxy_tool(ToolExecutionRequest {
+                tool_id: schedule.tool_id.clone(),
+                parameters: schedule.arguments.clone(),
+            })
+            .await;
+        let error = match outcome {
+            Ok(response) if response.success => response.result.as_ref().and_then(tool_error),
+            Ok(response) => Some(
+                response
+                    .error
+                    .unwrap_or_else(|| "Unknown error".to_string()),
+            ),
+            Err(e) => Some(e),
+        };
+        let run = ScheduleRun {
+            id: 0,
+            schedule_id: schedule.id.clone(),
+            started_at,
+            duration_ms: started.elapsed().as_millis() as u64,
+            success: error.is_none(),
+            error,
+        };
+        if let Some(error) = &run.error {
+            warn!("Schedule {} failed: {}", schedule.id, error);
+        }
+        if let Err(e) = self.tool_registry.read().await.record_schedule_run(&run) {
+            warn!(
+                "Failed to record the run of schedule {}: {}",
+                schedule.id, e
+            );
+        }
+        run
+    }
+
+    fn start_scheduler(&self) -> JoinHandle<()> {
+        let core = self.clone();
+        tokio::spawn(async move {
+            let running = Arc::new(Mutex::new(HashSet::new()));
+            let mut last_tick = now_ms();
+            match core.tool_registry.read().await.get_schedules() {
+                Ok(schedules) => {
+                    for schedule in misfired_schedules(&schedules, last_tick) {
+                        info!(
+                            "Schedule {} missed runs while the app was closed, running it once",
+                            schedule.definition.id
+                        );
+                        spawn_run(&core, &schedule.definition, &running);
+                    }
+                }
+                Err(e) => warn!("Failed to read the schedules: {}", e),
+            }
+
+            let mut interval = tokio::time::interval(SCHEDULER_TICK);
+            loop {
+                interval.tick().await;
+                let now = now_ms();
+                let schedules = match core.tool_registry.read().await.get_schedules() {
+                    Ok(schedules) => schedules,
+                    Err(e) => {
+                        warn!("Failed to read the schedules: {}", e);
+                        continue;
+                    }
+                };
+                for schedule in due_schedules(&schedules, last_tick, now) {
+                    spawn_run(&core, &schedule.definition, &running);
+                }
+                last_tick = now;
+            }
+        })
+    }
+}
+
+/// Run a job in the background, unless its previous run is still going
+fn spawn_run(core: &MCPCore, schedule: &ScheduleDefinition, running: &Arc<Mutex<HashSet<String>>>) {
+    if !running.lock().unwrap().insert(schedule.id.clone()) {
+        warn!(
+            "Skipping a run of schedule {}, its previous run is still going",
+            schedule.id
+        );
+        return;
+    }
+    let core = core.clone();
+    let schedule = schedule.clone();
+    let running = running.clone();
+    tokio::spawn(async move {
+        core.run_schedule(&schedule).await;
+        running.lock().unwrap().remove(&schedule.id);
+    });
+}
+
+/// Error reported by a tool in its result rather than as a failed call
+fn tool_error(result: &Value) -> Option<String> {
+    if result.get("isError").and_then(Value::as_bool) != Some(true) {
+        return None;
+    }
+    let text = result
+        .get("content")
+        .and_then(Value::as_array)
+        .and_then(|content| content.iter().find_map(|item| item.get("text")?.as_str()));
+    Some(text.unwrap_or("The tool reported an error").to_string())
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
@@ -0,0 +1,438 @@
+use std::ffi::OsStr;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use log::{info, warn};
+use serde_json::{json, Value};
+
+use super::mcp_core::MCPCore;
+use super::mcp_core_diagnostics_ext::runtime_version;
+use super::mcp_core_proxy_ext::McpCoreProxyExt;
+use super::mcp_core_server_info_ext::McpCoreServerInfoExt;
+use crate::diagnose::{find_program, node_major, DiagnosisStep, MIN_NODE_MAJOR};
+use crate::models::types::{
+    ServerConfiguration, ServerRegistrationRequest, ServerStatus, ToolExecutionRequest,
+    ToolUninstallRequest,
+};
+use crate::selftest::{
+    SelftestReport, SelftestStep, MOCK_SERVER_FILE, MOCK_SERVER_SCRIPT, SELFTEST_SERVER_ID,
+    SELFTEST_SERVER_NAME, SELFTEST_STEP_TIMEOUT, SELFTEST_TOOL,
+};
+use crate::utils::command::server_path;
+
+/// End-to-end check of the app, see [`crate::selftest`]
+pub trait McpCoreSelftestExt {
+    /// Run the self-test, failing only in the report
+    fn run_selftest(&self) -> impl Future<Output = SelftestReport> + Send;
+}
+
+impl McpCoreSelftestExt for MCPCore {
+    async fn run_selftest(&self) -> SelftestReport {
+        info!("Running the self-test");
+        let created_at = now_ms();
+        let dir = self
+            .database_manager
+            .read()
+            .await
+            .data_dir()
+            .join("selftest");
+        let script = dir.join(MOCK_SERVER_FILE);
+        let message = format!("selftest-{}", created_at);
+
+        let mut run = SelftestRun::default();
+        run.step(
+            "mock_server",
+            MOCK_SERVER_TITLE,
+            self,
+            write_mock_server(&script),
+        )
+        .await;
+        run.step(
+            "register",
+            REGISTER_TITLE,
+            self,
+            self.register_mock_server(&script),
+        )
+        .await;
+        run.step(
+            "initialize",
+            INITIALIZE_TITLE,
+            self,
+            self.check_mock_initialize(),
+        )
+        .await;
+        run.step(
+            "discovery",
+            DISCOVERY_TITLE,
+            self,
+            self.check_mock_discovery(),
+        )
+        .await;
+        run.step(
+            "proxy_call",
+            PROXY_CALL_TITLE,
+            self,
+            self.call_mock_tool(&message),
+        )
+        .await;
+        run.step(
+            "http_tools_list",
+            HTTP_LIST_TITLE,
+            self,
+            self.http_tools_list(),
+        )
+        .await;
+        run.step(
+            "http_tools_call",
+            HTTP_CALL_TITLE,
+            self,
+            self.http_tools_call(&message),
+        )
+        .await;
+        // Always run, to leave nothing behind
+        run.failed = false;
+        run.step(
+            "cleanup",
+            CLEANUP_TITLE,
+            self,
+            self.remove_mock_server(&dir),
+        )
+        .await;
+
+        let report = SelftestReport::new(&self.app_name, created_at, run.steps);
+        if report.passed {
+            info!("The self-test passed");
+        } else {
+            warn!("The self-test failed:\n{}", report.to_text());
+        }
+        report
+    }
+}
+
+const MOCK_SERVER_TITLE: &str = "Mock server ready to run";
+const REGISTER_TITLE: &str = "Mock server registered and started";
+const INITIALIZE_TITLE: &str = "Initialize handshake answered";
+const DISCOVERY_TITLE: &str = "Tools discovered";
+const PROXY_CALL_TITLE: &str = "Tool called through the proxy";
+const HTTP_LIST_TITLE: &str = "Tools listed by the local endpoint";
+const HTTP_CALL_TITLE: &str = "Tool called through the local endpoint";
+const CLEANUP_TITLE: &str = "Mock server removed";
+
+/// Outcome of a step: its details, or the details and a hint when it failed
+type StepResult = Result<String, (String, String)>;
+
+/// Steps run so far, the steps after a failed one are skipped
+#[derive(Default)]
+struct SelftestRun {
+    steps: Vec<SelftestStep>,
+    failed: bool,
+}
+
+impl SelftestRun {
+    async fn step(
+        &mut self,
+        id: &str,
+        title: &str,
+        core: &MCPCore,
+        check: impl Future<Output = StepResult>,
+    ) {
+        if self.failed {
+            self.steps.push(SelftestStep {
+                step: DiagnosisStep::skipped(id, title, "An earlier step failed".to_string()),
+                duration_ms: 0,
+            });
+            return;
+        }
+        let started = core.clock.now();
+        let result = tokio::select! {
+            result = check => result,
+            _ = core.clock.sleep(SELFTEST_STEP_TIMEOUT) => Err((
+                format!("No result within {} seconds", SELFTEST_STEP_TIMEOUT.as_secs()),
+                "Dockmaster is stuck, restart it and run the self-test again".to_string(),
+            )),
+        };
+        let duration_ms = core
+            .clock
+            .now()
+            .saturating_duration_since(started)
+            .as_millis() as u64;
+        let step = match result {
+            Ok(details) => DiagnosisStep::pass(id, title, details),
+            Err((details, hint)) => {
+                self.failed = true;
+                DiagnosisStep::fail(id, title, details, hint)
+            }
+        };
+        self.steps.push(SelftestStep { step, duration_ms });
+    }
+}
+
+impl MCPCore {
+    /// Register the mock server, replacing one left by a self-test that was interrupted
+    async fn register_mock_server(&self, script: &Path) -> StepResult {
+        let hint = "Check the logs of Dockmaster for why the server doesn't start".to_string();
+        self.uninstall_mock_server().await?;
+        let response = self
+            .register_server(ServerRegistrationRequest {
+                server_id: SELFTEST_SERVER_ID.to_string(),
+                server_name: "Dockmaster self-test".to_string(),
+                description: "Temporary server of the self-test".to_string(),
+                tools_type: "node".to_string(),
+                configuration: Some(ServerConfiguration {
+                    command: Some("node".to_string()),
+                    args: Some(vec![script.to_string_lossy().to_string()]),
+                    ..Default::default()
+                }),
+                distribution: None,
+                provenance: None,
+                authentication: None,
+                enabled: None,
+            })
+            .await
+            .map_err(|e| (format!("Registration failed: {}", e), hint.clone()))?;
+        match response.status {
+            _ if !response.success => Err((response.message, hint)),
+            Some(ServerStatus::Running) => Ok(format!(
+                "Started with {} tools",
+                response.tool_count.unwrap_or_default()
+            )),
+            Some(ServerStatus::Error(error)) => Err((error, hint)),
+            status => Err((
+                format!("The server is {:?} after registration", status),
+                hint,
+            )),
+        }
+    }
+
+    async fn check_mock_initialize(&self) -> StepResult {
+        let hint = "The proxy can't talk to servers, check the logs of Dockmaster".to_string();
+        match self.server_info(SELFTEST_SERVER_ID).await {
+            Ok(Some(info)) if info.name == SELFTEST_SERVER_NAME => Ok(format!(
+                "{} {} with protocol {}",
+                info.name, info.version, info.protocol_version
+            )),
+            Ok(Some(info)) => Err((
+                format!("The handshake was answered by '{}'", info.name),
+                hint,
+            )),
+            Ok(None) => Err(("No answer to the handshake was recorded".to_string(), hint)),
+            Err(e) => Err((e, hint)),
+        }
+    }
+
+    async fn check_mock_discovery(&self) -> StepResult {
+        let mcp_state = self.mcp_state.read().await;
+        let server_tools = mcp_state.server_tools.read().await;
+        let tools = server_tools
+            .get(SELFTEST_SERVER_ID)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        if tools.iter().any(|tool| tool.name == SELFTEST_TOOL) {
+            return Ok(format!("{} tools discovered", tools.len()));
+        }
+        Err((
+            format!(
+                "'{}' is not among the {} tools discovered",
+                SELFTEST_TOOL,
+                tools.len()
+            ),
+            "The proxy didn't list the tools of the server, check the logs of Dockmaster"
+                .to_string(),
+        ))
+    }
+
+    async fn call_mock_tool(&self, message: &str) -> StepResult {
+        let hint = "Tool calls don't reach servers, check the hooks and the logs".to_string();
+        let response = self
+            .execute_proxy_tool(ToolExecutionRequest {
+                tool_id: format!("{}:{}", SELFTEST_SERVER_ID, SELFTEST_TOOL),
+                parameters: json!({ "message": message }),
+            })
+            .await
+            .map_err(|e| (e, hint.clone()))?;
+        if !response.success {
+            return Err((response.error.unwrap_or_default(), hint));
+        }
+        check_echo(response.result.as_ref(), message, &hint)
+    }
+
+    #[cfg(feature = "http-server")]
+    async fn http_tools_list(&self) -> StepResult {
+        let result = self.post_local_endpoint("tools/list", json!({})).await?;
+        let listed = result
+            .get("tools")
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        if listed
+            .iter()
+            .any(|tool| tool.get("name").and_then(Value::as_str) == Some(SELFTEST_TOOL))
+        {
+            return Ok(format!("{} tools listed", listed.len()));
+        }
+        Err((
+            format!(
+                "'{}' is not among the {} tools listed",
+                SELFTEST_TOOL,
+                listed.len()
+            ),
+            "MCP clients don't see the tools of the servers, report this bug".to_string(),
+        ))
+    }
+
+    #[cfg(feature = "http-server")]
+    async fn http_tools_call(&self, message: &str) -> StepResult {
+        let result = self
+            .post_local_endpoint(
+                "tools/call",
+                json!({ "name": SELFTEST_TOOL, "arguments": { "message": message } }),
+            )
+            .await?;
+        check_echo(
+            Some(&result),
+            message,
+            "MCP clients can't call the tools of the servers, report this bug",
+        )
+    }
+
+    /// Send a request to the endpoint MCP clients use, returning its result
+    #[cfg(feature = "http-server")]
+    async fn post_local_endpoint(
+        &self,
+        method: &str,
+        params: Value,
+    ) -> Result<Value, (String, String)> {
+        let url = format!("http://127.0.0.1:{}/mcp-proxy", self.port);
+        let hint = format!(
+            "Check that nothing else uses port {} and that Dockmaster is started",
+            self.port
+        );
+        let response = reqwest::Client::new()
+            .post(&url)
+            .json(&json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }))
+            .send()
+            .await
+            .map_err(|e| (format!("Failed to reach {}: {}", url, e), hint.clone()))?;
+        let mut body: Value = response
+            .json()
+            .await
+            .map_err(|e| (format!("Invalid answer from {}: {}", url, e), hint.clone()))?;
+        if let Some(error) = body.get("error").filter(|error| !error.is_null()) {
+            return Err((format!("{} failed: {}", method, error), hint));
+        }
+        Ok(body["result"].take())
+    }
+
+    #[cfg(not(feature = "http-server"))]
+    async fn http_tools_list(&self) -> StepResult {
+        Ok(NO_HTTP_SERVER.to_string())
+    }
+
+    #[cfg(not(feature = "http-server"))]
+    async fn http_tools_call(&self, _message: &str) -> StepResult {
+        Ok(NO_HTTP_SERVER.to_string())
+    }
+
+    async fn remove_mock_server(&self, dir: &Path) -> StepResult {
+        self.uninstall_mock_server().await?;
+        if dir.exists() {
+            std::fs::remove_dir_all(dir).map_err(|e| {
+                (
+                    format!("Failed to remove {}: {}", dir.display(), e),
+                    "Remove the folder by hand".to_string(),
+                )
+            })?;
+        }
+        Ok("Nothing of the self-test is left".to_string())
+    }
+
+    async fn uninstall_mock_server(&self) -> Result<(), (String, String)> {
+        if self
+            .tool_registry
+            .read()
+            .await
+            .get_server(SELFTEST_SERVER_ID)
+            .is_err()
+        {
+            return Ok(());
+        }
+        let response = self
+            .uninstall_server(ToolUninstallRequest {
+                server_id: SELFTEST_SERVER_ID.to_string(),
+            })
+            .await
+            .map_err(|e| (e, format!("Uninstall '{}' by hand", SELFTEST_SERVER_ID)))?;
+        if response.success {
+            Ok(())
+        } else {
+            Err((
+                response.message,
+                format!("Uninstall '{}' by hand", SELFTEST_SERVER_ID),
+            ))
+        }
+    }
+}
+
+#[cfg(not(feature = "http-server"))]
+const NO_HTTP_SERVER: &str = "Not checked, the core was built without the HTTP server";
+
+/// Write the mock server where it can be run, with Node.js installed
+async fn write_mock_server(script: &Path) -> StepResult {
+    let path = server_path();
+    let Some(node) = find_program("node", path.as_deref().map(OsStr::new)) else {
+        return Err((
+            "'node' was not found on the PATH".to_string(),
+            format!("Install Node.js {} or later", MIN_NODE_MAJOR),
+        ));
+    };
+    let version = runtime_version("node", "-v").await.unwrap_or_default();
+    if let Some(major) = node_major(&version).filter(|major| *major < MIN_NODE_MAJOR) {
+        return Err((
+            format!("Node.js {} is older than version {}", major, MIN_NODE_MAJOR),
+            format!("Update Node.js to version {} or later", MIN_NODE_MAJOR),
+        ));
+    }
+    let written: Result<PathBuf, std::io::Error> = script
+        .parent()
+        .map(std::fs::create_dir_all)
+        .transpose()
+        .and_then(|_| std::fs::write(script, MOCK_SERVER_SCRIPT))
+        .map(|_| script.to_path_buf());
+    match written {
+        Ok(script) => Ok(format!(
+            "Node.js {} at {}, mock server written to {}",
+            version,
+            node.display(),
+            script.display()
+        )),
+        Err(e) => Err((
+            format!("Failed to write {}: {}", script.display(), e),
+            "Check that the data directory of Dockmaster is writable".to_string(),
+        )),
+    }
+}
+
+/// The result of a call of the mock tool has the message it was given
+fn check_echo(result: Option<&Value>, message: &str, hint: &str) -> StepResult {
+    let text = result
+        .and_then(|result| result.get("content"))
+        .and_then(Value::as_array)
+        .and_then(|content| content.first())
+        .and_then(|item| item.get("text"))
+        .and_then(Value::as_str);
+    match text {
+        Some(text) if text == message => Ok("The tool returned the message it was given".into()),
+        _ => Err((
+            format!("Unexpected result: {}", result.cloned().unwrap_or_default()),
+            hint.to_string(),
+        )),
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
@@ -0,0 +1,72 @@
+use log::info;
+
+use super::mcp_core::MCPCore;
+use crate::models::types::ServerInitInfo;
+use crate::server_info::shared_instructions;
+
+/// What servers said about themselves when they started, see [`crate::server_info`]
+pub trait McpCoreServerInfoExt {
+    /// Last initialize answer of `server_id`, None until it started once
+    fn server_info(
+        &self,
+        server_id: &str,
+    ) -> impl std::future::Future<Output = Result<Option<ServerInitInfo>, String>> + Send;
+    /// Set whether the instructions of `server_id` are passed on to MCP clients
+    fn set_share_instructions(
+        &self,
+        server_id: &str,
+        share: bool,
+    ) -> impl std::future::Future<Output = Result<(), String>> + Send;
+    /// Instructions of the enabled servers that share them, appended to the
+    /// instructions of Dockmaster
+    fn shared_server_instructions(&self) -> impl std::future::Future<Output = String> + Send;
+}
+
+impl McpCoreServerInfoExt for MCPCore {
+    async fn server_info(&self, server_id: &str) -> Result<Option<ServerInitInfo>, String> {
+        let server_id = self.resolve_server_id(server_id).await;
+        let registry = self.tool_registry.read().await;
+        registry.get_server(&server_id)?;
+        Ok(registry.get_server_infos()?.remove(&server_id))
+    }
+
+    async fn set_share_instructions(&self, server_id: &str, share: bool) -> Result<(), String> {
+        let server_id = self.resolve_server_id(server_id).await;
+        let registry = self.tool_registry.write().await;
+        let mut server = registry.get_server(&server_id)?;
+        server
+            .configuration
+            .get_or_insert_with(Default::default)
+            .share_instructions = Some(share);
+        registry.save_server(&server_id, &server)?;
+        info!(
+            "Instructions of {} are {}",
+            server_id,
+            if share { "shared" } else { "no longer shared" }
+        );
+        Ok(())
+    }
+
+    async fn shared_server_instructions(&self) -> String {
+        let registry = self.tool_registry.read().await;
+        let (Ok(servers), Ok(mut infos)) =
+            (registry.get_all_servers(), registry.get_server_infos())
+        else {
+            return String::new();
+        };
+        let mut shared: Vec<(String, ServerInitInfo)> = servers
+            .into_iter()
+            .filter(|(_, server)| {
+                server.enabled
+                    && server
+                        .configuration
+                        .as_ref()
+                        .and_then(|c| c.share_instructions)
+                        == Some(true)
+            })
+            .filter_map(|(id, _)| infos.remove(&id).map(|info| (id, info)))
+            .collect();
+        shared.sort_by(|a, b| a.0.cmp(&b.0));
+        shared_instructions(&shared)
+    }
+}
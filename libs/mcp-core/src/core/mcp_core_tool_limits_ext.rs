@@ -0,0 +1,73 @@
+use log::{error, info};
+
+use super::mcp_core::MCPCore;
+use crate::events::{DockmasterEvent, ToolsUpdate};
+use crate::mcp_server::mcp_tools_service::MCPToolsService;
+use crate::models::types::ServerStatus;
+use crate::tool_limits::{ToolFilter, ToolLimitReport};
+
+/// Bounds on the tools of each server, see [`crate::tool_limits`]
+pub trait McpCoreToolLimitsExt {
+    /// What discovery left out of the tools of `server_id`, None until they were listed
+    fn tool_limit_report(
+        &self,
+        server_id: &str,
+    ) -> impl std::future::Future<Output = Option<ToolLimitReport>> + Send;
+    /// Save the tool filter of `server_id`, None for the default, and apply it to the
+    /// server when it runs, returning what is left out then
+    fn set_tool_filter(
+        &self,
+        server_id: &str,
+        filter: Option<ToolFilter>,
+    ) -> impl std::future::Future<Output = Result<Option<ToolLimitReport>, String>> + Send;
+}
+
+impl McpCoreToolLimitsExt for MCPCore {
+    async fn tool_limit_report(&self, server_id: &str) -> Option<ToolLimitReport> {
+        let server_id = self.resolve_server_id(server_id).await;
+        let mcp_state = self.mcp_state.read().await;
+        let report = mcp_state.tool_limits.read().await.get(&server_id).cloned();
+        report
+    }
+
+    async fn set_tool_filter(
+        &self,
+        server_id: &str,
+        filter: Option<ToolFilter>,
+    ) -> Result<Option<ToolLimitReport>, String> {
+        if let Some(filter) = &filter {
+            filter.validate()?;
+        }
+        let server_id = self.resolve_server_id(server_id).await;
+        {
+            let registry = self.tool_registry.write().await;
+            let mut server = registry.get_server(&server_id)?;
+            server
+                .configuration
+                .get_or_insert_with(Default::default)
+                .tool_filter = filter.filter(|filter| *filter != ToolFilter::default());
+            registry.save_server(&server_id, &server)?;
+        }
+        info!("Saved the tool filter of {}", server_id);
+
+        // Listed again, the server doesn't need to be reinstalled nor restarted
+        let mcp_state = self.mcp_state.read().await.clone();
+        if !matches!(
+            mcp_state.server_status(&server_id).await,
+            ServerStatus::Running
+        ) {
+            return Ok(None);
+        }
+        mcp_state.discover_server_tools(&server_id).await?;
+        if let Some(tools_service) = MCPToolsService::get_instance().await {
+            if let Err(e) = tools_service.update_cache().await {
+                error!("Failed to update tools cache after filtering tools: {}", e);
+            }
+        }
+        self.event_bus
+            .publish(DockmasterEvent::ToolsUpdated(ToolsUpdate {
+                server_ids: vec![server_id.clone()],
+            }));
+        Ok(self.tool_limit_report(&server_id).await)
+    }
+}
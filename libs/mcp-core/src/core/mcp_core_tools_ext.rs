@@ -0,0 +1,248 @@
+use std::fmt;
+
+use log::{info, warn};
+
+use super::mcp_core::MCPCore;
+use super::mcp_core_rate_limits_ext::McpCoreRateLimitsExt;
+use crate::models::tool_id::proxy_tool_id;
+use crate::models::types::{ToolAnnotationOverride, ToolAnnotations, ToolDescription};
+use crate::undo::BeforeImage;
+use crate::utils::description::{
+    DescriptionOptions, DESCRIPTION_MAX_LENGTH_SETTING, DESCRIPTION_STRIP_MARKDOWN_SETTING,
+};
+
+/// Settings key of the annotations set by the user on tools
+pub const TOOL_ANNOTATIONS_SETTING: &str = "tool_annotation_overrides";
+
+/// Why a tool couldn't be described
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DescribeToolError {
+    /// No server is installed with this id
+    UnknownServer(String),
+    /// The server is installed but doesn't provide the tool
+    UnknownTool {
+        server_id: String,
+        tool_name: String,
+    },
+}
+
+impl DescribeToolError {
+    /// Machine readable reason, returned in the error data of `tools/describe`
+    pub fn reason(&self) -> &'static str {
+        match self {
+            DescribeToolError::UnknownServer(_) => "unknown_server",
+            DescribeToolError::UnknownTool { .. } => "unknown_tool",
+        }
+    }
+}
+
+impl fmt::Display for DescribeToolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DescribeToolError::UnknownServer(server_id) => {
+                write!(f, "Server '{}' not found", server_id)
+            }
+            DescribeToolError::UnknownTool {
+                server_id,
+                tool_name,
+            } => write!(
+                f,
+                "Tool '{}' not found on server '{}'",
+                tool_name, server_id
+            ),
+        }
+    }
+}
+
+pub trait McpCoreToolsExt {
+    fn description_options(&self) -> impl std::future::Future<Output = DescriptionOptions> + Send;
+    fn set_description_options(
+        &self,
+        options: DescriptionOptions,
+    ) -> impl std::future::Future<Output = Result<(), String>> + Send;
+    fn describe_tool(
+        &self,
+        server_id: &str,
+        tool_name: &str,
+    ) -> impl std::future::Future<Output = Result<ToolDescription, DescribeToolError>> + Send;
+    /// Annotations set by the user, none when the setting is missing or can't be read
+    fn tool_annotation_overrides(
+        &self,
+    ) -> impl std::future::Future<Output = Vec<ToolAnnotationOverride>> + Send;
+    /// Set the annotations of a tool over its server's, removing them when empty
+    fn set_tool_annotations(
+        &self,
+        server_id: &str,
+        tool_name: &str,
+        annotations: ToolAnnotations,
+    ) -> impl std::future::Future<Output = Result<(), String>> + Send;
+}
+
+impl McpCoreToolsExt for MCPCore {
+    /// How descriptions are shortened in the tool list, defaults for unset values
+    async fn description_options(&self) -> DescriptionOptions {
+        let registry = self.tool_registry.read().await;
+        let defaults = DescriptionOptions::default();
+        DescriptionOptions {
+            max_length: registry
+                .get_setting(DESCRIPTION_MAX_LENGTH_SETTING)
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(defaults.max_length),
+            strip_markdown: registry
+                .get_setting(DESCRIPTION_STRIP_MARKDOWN_SETTING)
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(defaults.strip_markdown),
+        }
+    }
+
+    async fn set_description_options(&self, options: DescriptionOptions) -> Result<(), String> {
+        let registry = self.tool_registry.read().await;
+        registry.save_setting(
+            DESCRIPTION_MAX_LENGTH_SETTING,
+            &options.max_length.to_string(),
+        )?;
+        registry.save_setting(
+            DESCRIPTION_STRIP_MARKDOWN_SETTING,
+            &options.strip_markdown.to_string(),
+        )
+    }
+
+    /// A tool with its full description and schema, the status of its server, its
+    /// usage and its rate limit
+    ///
+    /// `tool_name` matches either the name the server reported or the tool id.
+    async fn describe_tool(
+        &self,
+        server_id: &str,
+        tool_name: &str,
+    ) -> Result<ToolDescription, DescribeToolError> {
+        let overrides = self.tool_annotation_overrides().await;
+        let mcp_state = self.mcp_state.read().await;
+        let tool = {
+            let server_tools = mcp_state.server_tools.read().await;
+            match server_tools.get(server_id) {
+                // By name first, an id never hides the tool of that name
+                Some(tools) => tools
+                    .iter()
+                    .find(|tool| tool.name == tool_name)
+                    .or_else(|| tools.iter().find(|tool| tool.id == tool_name))
+                    .cloned(),
+                None => None,
+            }
+        };
+        let Some(mut tool) = tool else {
+            let installed = self
+                .tool_registry
+                .read()
+                .await
+                .get_server(server_id)
+                .is_ok();
+            return Err(if installed {
+                DescribeToolError::UnknownTool {
+                    server_id: server_id.to_string(),
+                    tool_name: tool_name.to_string(),
+                }
+            } else {
+                DescribeToolError::UnknownServer(server_id.to_string())
+            });
+        };
+
+        let server_status = mcp_state.server_status(server_id).await;
+        let defaults = tool
+            .input_schema
+            .as_ref()
+            .map(|schema| {
+                schema
+                    .properties
+                    .iter()
+                    .filter_map(|(name, property)| {
+                        property.default.clone().map(|value| (name.clone(), value))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        tool.apply_annotation_overrides(&overrides);
+        let rate_limit = self.tool_rate_limit(server_id, &tool.name).await;
+        Ok(ToolDescription {
+            proxy_id: proxy_tool_id(server_id, &tool.name),
+            usage: mcp_state.tool_stats.get(server_id, &tool.name),
+            server_id: tool.server_id,
+            id: tool.id,
+            name: tool.name,
+            description: tool.description,
+            input_schema: tool.input_schema,
+            output_schema: tool.output_schema,
+            defaults,
+            is_active: tool.is_active,
+            server_status,
+            rate_limit,
+            annotations: tool.annotations,
+        })
+    }
+
+    async fn tool_annotation_overrides(&self) -> Vec<ToolAnnotationOverride> {
+        let registry = self.tool_registry.read().await;
+        let Ok(json) = registry.get_setting(TOOL_ANNOTATIONS_SETTING) else {
+            return Vec::new();
+        };
+        serde_json::from_str(&json).unwrap_or_else(|e| {
+            warn!(
+                "Ignoring the saved tool annotations, they can't be read: {}",
+                e
+            );
+            Vec::new()
+        })
+    }
+
+    async fn set_tool_annotations(
+        &self,
+        server_id: &str,
+        tool_name: &str,
+        annotations: ToolAnnotations,
+    ) -> Result<(), String> {
+        let server_id = self.resolve_server_id(server_id).await;
+        let server_id = server_id.as_str();
+        if self
+            .tool_registry
+            .read()
+            .await
+            .get_server(server_id)
+            .is_err()
+        {
+            return Err(format!("Server '{}' not found", server_id));
+        }
+        let mut overrides = self.tool_annotation_overrides().await;
+        let previous = overrides
+            .iter()
+            .find(|o| o.server_id == server_id && o.tool_name == tool_name)
+            .map(|o| o.annotations.clone())
+            .unwrap_or_default();
+        if previous == annotations {
+            return Ok(());
+        }
+        let image = BeforeImage::Annotations {
+            tool_name: tool_name.to_string(),
+            annotations: previous,
+        };
+        overrides.retain(|o| o.server_id != server_id || o.tool_name != tool_name);
+        if !annotations.is_empty() {
+            overrides.push(ToolAnnotationOverride {
+                server_id: server_id.to_string(),
+                tool_name: tool_name.to_string(),
+                annotations,
+            });
+        }
+        let json = serde_json::to_string(&overrides)
+            .map_err(|e| format!("Failed to serialize the tool annotations: {}", e))?;
+        self.tool_registry
+            .read()
+            .await
+            .save_setting(TOOL_ANNOTATIONS_SETTING, &json)?;
+        info!("Saved the annotations of {} on {}", tool_name, server_id);
+        self.record_undoable(server_id, image).await;
+        Ok(())
+    }
+}
@@ -0,0 +1,135 @@
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use log::{info, warn};
+
+use super::mcp_core::MCPCore;
+use super::mcp_core_proxy_ext::McpCoreProxyExt;
+use crate::models::types::ServerToolInfo;
+use crate::tools_snapshot::{
+    load_snapshot, remove_snapshot, save_snapshot, snapshot_path, TOOLS_SNAPSHOT_SETTING,
+};
+
+/// Tool list of the last run, served while the servers start, see
+/// [`crate::tools_snapshot`]
+pub trait McpCoreToolsSnapshotExt {
+    /// Read the snapshot of the last run, to serve it until the servers have started
+    ///
+    /// Returns how many tools it holds. Nothing is loaded when the snapshot is
+    /// disabled or the tools are hidden.
+    fn load_tools_snapshot(&self) -> impl std::future::Future<Output = usize> + Send;
+    /// Tools of the servers for `tools/list`, and whether they come from the snapshot
+    fn served_server_tools(
+        &self,
+    ) -> impl std::future::Future<Output = Result<(Vec<ServerToolInfo>, bool), String>> + Send;
+    /// Write the live tool list to the snapshot, unless the snapshot is still served
+    fn save_tools_snapshot(&self) -> impl std::future::Future<Output = Result<(), String>> + Send;
+    /// Stop serving the snapshot once the servers have started, and write the live list
+    fn replace_stale_tools(&self) -> impl std::future::Future<Output = ()> + Send;
+    fn is_tools_snapshot_enabled(&self) -> impl std::future::Future<Output = bool> + Send;
+    /// Enable or disable the snapshot, disabling it removes the file
+    fn set_tools_snapshot_enabled(
+        &self,
+        enabled: bool,
+    ) -> impl std::future::Future<Output = Result<(), String>> + Send;
+}
+
+impl McpCoreToolsSnapshotExt for MCPCore {
+    async fn load_tools_snapshot(&self) -> usize {
+        let hidden = {
+            let registry = self.tool_registry.read().await;
+            registry
+                .get_setting("tools_hidden")
+                .is_ok_and(|value| value == "true")
+        };
+        if hidden || !self.is_tools_snapshot_enabled().await {
+            return 0;
+        }
+        let Some(tools) = load_snapshot(&self.tools_snapshot_path().await) else {
+            return 0;
+        };
+        info!(
+            "Serving {} tools of the last run until the servers start",
+            tools.len()
+        );
+        let count = tools.len();
+        let mcp_state = self.mcp_state.read().await;
+        *mcp_state.stale_tools.write().await = Some(tools);
+        count
+    }
+
+    async fn served_server_tools(&self) -> Result<(Vec<ServerToolInfo>, bool), String> {
+        {
+            let mcp_state = self.mcp_state.read().await;
+            let stale_tools = mcp_state.stale_tools.read().await;
+            if let Some(tools) = stale_tools.as_ref() {
+                if !mcp_state.are_tools_hidden().await {
+                    return Ok((tools.clone(), true));
+                }
+            }
+        }
+        Ok((self.list_all_server_tools().await?, false))
+    }
+
+    async fn save_tools_snapshot(&self) -> Result<(), String> {
+        {
+            let mcp_state = self.mcp_state.read().await;
+            // The hidden list is empty, the last visible one is kept
+            if mcp_state.stale_tools.read().await.is_some() || mcp_state.are_tools_hidden().await {
+                return Ok(());
+            }
+        }
+        if !self.is_tools_snapshot_enabled().await {
+            return Ok(());
+        }
+        let tools = self.list_all_server_tools().await?;
+        if save_snapshot(&self.tools_snapshot_path().await, &tools, now_ms())? {
+            info!("Saved the snapshot of {} tools", tools.len());
+        }
+        Ok(())
+    }
+
+    async fn replace_stale_tools(&self) {
+        let stale_tools = self.mcp_state.read().await.stale_tools.write().await.take();
+        if stale_tools.is_some() {
+            info!("Servers started, serving their live tools");
+        }
+        if let Err(e) = self.save_tools_snapshot().await {
+            warn!("Failed to save the tools snapshot: {}", e);
+        }
+    }
+
+    async fn is_tools_snapshot_enabled(&self) -> bool {
+        let registry = self.tool_registry.read().await;
+        !registry
+            .get_setting(TOOLS_SNAPSHOT_SETTING)
+            .is_ok_and(|value| value == "false")
+    }
+
+    async fn set_tools_snapshot_enabled(&self, enabled: bool) -> Result<(), String> {
+        self.tool_registry
+            .read()
+            .await
+            .save_setting(TOOLS_SNAPSHOT_SETTING, &enabled.to_string())?;
+        if enabled {
+            info!("Tools snapshot enabled");
+            return self.save_tools_snapshot().await;
+        }
+        info!("Tools snapshot disabled");
+        self.mcp_state.read().await.stale_tools.write().await.take();
+        remove_snapshot(&self.tools_snapshot_path().await)
+    }
+}
+
+impl MCPCore {
+    async fn tools_snapshot_path(&self) -> PathBuf {
+        snapshot_path(self.database_manager.read().await.path())
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
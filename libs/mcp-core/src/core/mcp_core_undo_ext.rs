@@ -0,0 +1,251 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use log::{info, warn};
+
+use super::mcp_core::MCPCore;
+use super::mcp_core_proxy_ext::McpCoreProxyExt;
+use super::mcp_core_tools_ext::McpCoreToolsExt;
+use crate::models::registration::ResolvedRegistration;
+use crate::models::types::{
+    ProvenanceSource, ServerConfiguration, ServerDefinition, ServerProvenance, ServerUpdateRequest,
+};
+use crate::sealed::SealingKey;
+use crate::selftest::SELFTEST_SERVER_ID;
+use crate::undo::{
+    BeforeImage, UndoEntry, UndoResult, DEFAULT_UNDO_RETENTION_HOURS, UNDO_RETENTION_SETTING,
+};
+
+const HOUR_MS: u64 = 60 * 60 * 1000;
+
+/// Changes to servers that can be undone, see [`crate::undo`]
+pub trait McpCoreUndoExt {
+    /// Changes that can still be undone, newest first
+    fn undoable_changes(
+        &self,
+    ) -> impl std::future::Future<Output = Result<Vec<UndoEntry>, String>> + Send;
+    /// Undo a change, removing it from the journal when it was undone
+    ///
+    /// Fails when the entry is unknown, expired or can't be read. A change that can't be
+    /// put back, such as an uninstalled server that was installed again since, is
+    /// reported in the result and stays in the journal.
+    fn undo_change(
+        &self,
+        entry_id: i64,
+    ) -> impl std::future::Future<Output = Result<UndoResult, String>> + Send;
+    /// Hours a change can be undone for
+    fn undo_retention_hours(&self) -> impl std::future::Future<Output = u64> + Send;
+    /// Set the hours a change can be undone for, changes recorded before keep theirs
+    fn set_undo_retention_hours(
+        &self,
+        hours: u64,
+    ) -> impl std::future::Future<Output = Result<(), String>> + Send;
+}
+
+impl McpCoreUndoExt for MCPCore {
+    async fn undoable_changes(&self) -> Result<Vec<UndoEntry>, String> {
+        self.tool_registry.read().await.get_undo_entries(now_ms())
+    }
+
+    async fn undo_change(&self, entry_id: i64) -> Result<UndoResult, String> {
+        let (entry, sealed) = self.tool_registry.read().await.get_undo_entry(entry_id)?;
+        if entry.expires_at <= now_ms() {
+            return Err(format!("Undo entry {} has expired", entry_id));
+        }
+        let image = self.sealing_key().await?.open(&sealed)?;
+        let image: BeforeImage = serde_json::from_str(&image)
+            .map_err(|e| format!("Failed to read undo entry {}: {}", entry_id, e))?;
+        info!("Undoing: {}", entry.summary);
+
+        let server_id = entry.server_id.clone();
+        let (success, message) = match image {
+            BeforeImage::Server {
+                definition,
+                provenance,
+            } => {
+                self.reinstall_server(&server_id, definition, provenance)
+                    .await
+            }
+            BeforeImage::Enabled => {
+                let request = ServerUpdateRequest {
+                    server_id,
+                    enabled: true,
+                };
+                match self.update_server_status(request).await {
+                    Ok(response) => (response.success, response.message),
+                    Err(e) => (false, e),
+                }
+            }
+            BeforeImage::Configuration { configuration } => {
+                self.restore_configuration(&server_id, configuration).await
+            }
+            BeforeImage::Annotations {
+                tool_name,
+                annotations,
+            } => match self
+                .set_tool_annotations(&server_id, &tool_name, annotations)
+                .await
+            {
+                Ok(()) => (
+                    true,
+                    format!("Annotations of {}:{} restored", server_id, tool_name),
+                ),
+                Err(e) => (false, e),
+            },
+        };
+
+        if success {
+            if let Err(e) = self.tool_registry.read().await.delete_undo_entry(entry_id) {
+                warn!("Failed to remove undo entry {}: {}", entry_id, e);
+            }
+        } else {
+            warn!("Failed to undo entry {}: {}", entry_id, message);
+        }
+        Ok(UndoResult {
+            success,
+            message,
+            entry,
+        })
+    }
+
+    async fn undo_retention_hours(&self) -> u64 {
+        self.tool_registry
+            .read()
+            .await
+            .get_setting(UNDO_RETENTION_SETTING)
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_UNDO_RETENTION_HOURS)
+    }
+
+    async fn set_undo_retention_hours(&self, hours: u64) -> Result<(), String> {
+        if hours == 0 {
+            return Err("Changes must be kept for at least an hour".to_string());
+        }
+        self.tool_registry
+            .read()
+            .await
+            .save_setting(UNDO_RETENTION_SETTING, &hours.to_string())
+    }
+}
+
+impl MCPCore {
+    /// Record a change in the undo journal, only warning when it can't be recorded
+    ///
+    /// Must be called without holding the registry lock.
+    pub(crate) async fn record_undoable(&self, server_id: &str, image: BeforeImage) {
+        // The temporary server of the self-test is nothing to undo
+        if server_id == SELFTEST_SERVER_ID {
+            return;
+        }
+        if let Err(e) = self.try_record_undoable(server_id, image).await {
+            warn!("Failed to record undo entry for {}: {}", server_id, e);
+        }
+    }
+
+    async fn try_record_undoable(&self, server_id: &str, image: BeforeImage) -> Result<(), String> {
+        let json = serde_json::to_string(&image)
+            .map_err(|e| format!("Failed to serialize the before-image: {}", e))?;
+        let sealed = self.sealing_key().await?.seal(&json);
+        let created_at = now_ms();
+        let entry = UndoEntry {
+            id: 0,
+            action: image.action(),
+            server_id: server_id.to_string(),
+            summary: image.summary(server_id),
+            created_at,
+            expires_at: created_at + self.undo_retention_hours().await * HOUR_MS,
+        };
+        self.tool_registry
+            .read()
+            .await
+            .record_undo_entry(&entry, &sealed)?;
+        Ok(())
+    }
+
+    async fn sealing_key(&self) -> Result<SealingKey, String> {
+        SealingKey::load_or_create(&self.database_manager.read().await.data_dir())
+    }
+
+    /// Register an uninstalled server again, as it was
+    async fn reinstall_server(
+        &self,
+        server_id: &str,
+        definition: ServerDefinition,
+        provenance: Option<ServerProvenance>,
+    ) -> (bool, String) {
+        if self
+            .tool_registry
+            .read()
+            .await
+            .get_server(server_id)
+            .is_ok()
+        {
+            return (
+                false,
+                format!(
+                    "Server '{}' was installed again since it was uninstalled",
+                    server_id
+                ),
+            );
+        }
+        let registration = ResolvedRegistration {
+            server_id: server_id.to_string(),
+            server_name: definition.name,
+            description: definition.description,
+            tools_type: definition.tools_type,
+            configuration: definition.configuration.unwrap_or_default(),
+            distribution: definition.distribution,
+            provenance: provenance
+                .unwrap_or_else(|| ServerProvenance::new(ProvenanceSource::Manual, None)),
+            enabled: definition.enabled,
+            warnings: Vec::new(),
+        };
+        match self.register_resolved(registration, None).await {
+            Ok(response) => (response.success, response.message),
+            Err(e) => (false, e),
+        }
+    }
+
+    /// Put a configuration back like a change, restarting the server when it is enabled
+    async fn restore_configuration(
+        &self,
+        server_id: &str,
+        configuration: ServerConfiguration,
+    ) -> (bool, String) {
+        let enabled = match self.tool_registry.read().await.get_server(server_id) {
+            Ok(server) => server.enabled,
+            Err(e) => return (false, e),
+        };
+        let previous = match self.replace_config(server_id, configuration).await {
+            Ok(previous) => previous,
+            Err(e) => return (false, e),
+        };
+        let snapshot_id = match self.snapshot_config(server_id, &previous).await {
+            Ok(snapshot_id) => snapshot_id,
+            Err(e) => return (false, e),
+        };
+        let image = BeforeImage::Configuration {
+            configuration: previous.clone(),
+        };
+        let (success, message) = if enabled {
+            let result = self
+                .restart_or_roll_back(server_id, previous, snapshot_id)
+                .await;
+            (result.success, result.message)
+        } else {
+            (true, format!("Configuration of '{}' restored", server_id))
+        };
+        // Restoring is a change like any other, and can be undone the same way
+        if success {
+            self.record_undoable(server_id, image).await;
+        }
+        (success, message)
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
@@ -0,0 +1,248 @@
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use log::{info, warn};
+use serde_json::{json, Value};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+
+use super::mcp_core::MCPCore;
+use crate::models::types::ServerStatus;
+use crate::webhooks::{
+    deliver, generate_secret, status_changes, webhook_event, CreatedWebhook, DeadLetter, Webhook,
+    WebhookDelivery, WebhookEventKind, WebhookPayload, WEBHOOK_RETRY_DELAYS,
+    WEBHOOK_STATUS_INTERVAL,
+};
+
+/// Webhook notifications, see [`crate::webhooks`]
+pub trait McpCoreWebhooksExt {
+    /// Every webhook, secrets left out
+    fn list_webhooks(
+        &self,
+    ) -> impl std::future::Future<Output = Result<Vec<Webhook>, String>> + Send;
+    /// Create a webhook, failing when its id is taken
+    fn create_webhook(
+        &self,
+        webhook: Webhook,
+    ) -> impl std::future::Future<Output = Result<CreatedWebhook, String>> + Send;
+    /// Replace an existing webhook, keeping its secret unless a new one is given
+    fn update_webhook(
+        &self,
+        webhook: Webhook,
+    ) -> impl std::future::Future<Output = Result<Webhook, String>> + Send;
+    fn delete_webhook(
+        &self,
+        webhook_id: &str,
+    ) -> impl std::future::Future<Output = Result<(), String>> + Send;
+    /// Send a test payload to a webhook once, without retries
+    fn test_webhook(
+        &self,
+        webhook_id: &str,
+    ) -> impl std::future::Future<Output = Result<WebhookDelivery, String>> + Send;
+    /// Most recent payloads that could not be delivered, newest first
+    fn webhook_dead_letters(
+        &self,
+        limit: i64,
+    ) -> impl std::future::Future<Output = Result<Vec<DeadLetter>, String>> + Send;
+    /// Send an event to every webhook that wants it, in the background
+    fn notify_webhooks(
+        &self,
+        event: WebhookEventKind,
+        data: Value,
+    ) -> impl std::future::Future<Output = ()> + Send;
+    /// Send the events of the core to the webhooks as they happen
+    fn start_webhook_dispatcher(&self) -> JoinHandle<()>;
+}
+
+impl McpCoreWebhooksExt for MCPCore {
+    async fn list_webhooks(&self) -> Result<Vec<Webhook>, String> {
+        let webhooks = self.tool_registry.read().await.get_webhooks()?;
+        Ok(webhooks
+            .into_iter()
+            .map(|webhook| Webhook {
+                secret: None,
+                ..webhook
+            })
+            .collect())
+    }
+
+    async fn create_webhook(&self, mut webhook: Webhook) -> Result<CreatedWebhook, String> {
+        webhook.validate()?;
+        let registry = self.tool_registry.read().await;
+        if registry.get_webhooks()?.iter().any(|w| w.id == webhook.id) {
+            return Err(format!("Webhook '{}' already exists", webhook.id));
+        }
+        let secret = webhook.secret.get_or_insert_with(generate_secret).clone();
+        registry.save_webhook(&webhook)?;
+        info!("Created webhook {} for {}", webhook.id, webhook.url);
+        Ok(CreatedWebhook {
+            webhook: Webhook {
+                secret: None,
+                ..webhook
+            },
+            secret,
+        })
+    }
+
+    async fn update_webhook(&self, mut webhook: Webhook) -> Result<Webhook, String> {
+        webhook.validate()?;
+        let registry = self.tool_registry.read().await;
+        let current = registry
+            .get_webhooks()?
+            .into_iter()
+            .find(|w| w.id == webhook.id)
+            .ok_or_else(|| format!("Webhook '{}' not found", webhook.id))?;
+        if webhook.secret.is_none() {
+            webhook.secret = current.secret;
+        }
+        registry.save_webhook(&webhook)?;
+        info!("Updated webhook {}", webhook.id);
+        Ok(Webhook {
+            secret: None,
+            ..webhook
+        })
+    }
+
+    async fn delete_webhook(&self, webhook_id: &str) -> Result<(), String> {
+        self.tool_registry.read().await.delete_webhook(webhook_id)?;
+        info!("Deleted webhook {}", webhook_id);
+        Ok(())
+    }
+
+    async fn test_webhook(&self, webhook_id: &str) -> Result<WebhookDelivery, String> {
+        let webhook = self
+            .tool_registry
+            .read()
+            .await
+            .get_webhooks()?
+            .into_iter()
+            .find(|w| w.id == webhook_id)
+            .ok_or_else(|| format!("Webhook '{}' not found", webhook_id))?;
+        let payload = WebhookPayload::new(WebhookEventKind::Test, json!({}));
+        let delivery = deliver(
+            self.webhook_sender.as_ref(),
+            self.clock.as_ref(),
+            &webhook,
+            &payload,
+            &[],
+        )
+        .await;
+        info!(
+            "Test of webhook {}: {}",
+            webhook_id,
+            delivery.error.as_deref().unwrap_or("delivered")
+        );
+        Ok(delivery)
+    }
+
+    async fn webhook_dead_letters(&self, limit: i64) -> Result<Vec<DeadLetter>, String> {
+        self.tool_registry
+            .read()
+            .await
+            .get_webhook_dead_letters(limit)
+    }
+
+    async fn notify_webhooks(&self, event: WebhookEventKind, data: Value) {
+        let webhooks = match self.tool_registry.read().await.get_webhooks() {
+            Ok(webhooks) => webhooks,
+            Err(e) => {
+                warn!("Failed to read the webhooks: {}", e);
+                return;
+            }
+        };
+        for webhook in webhooks.into_iter().filter(|w| w.wants(event)) {
+            let core = self.clone();
+            let payload = WebhookPayload::new(event, data.clone());
+            tokio::spawn(async move {
+                let delivery = deliver(
+                    core.webhook_sender.as_ref(),
+                    core.clock.as_ref(),
+                    &webhook,
+                    &payload,
+                    &WEBHOOK_RETRY_DELAYS,
+                )
+                .await;
+                let Some(error) = delivery.error else {
+                    return;
+                };
+                warn!(
+                    "Failed to deliver {} to webhook {} after {} attempts: {}",
+                    event.as_str(),
+                    webhook.id,
+                    delivery.attempts,
+                    error
+                );
+                let dead_letter = DeadLetter {
+                    id: 0,
+                    webhook_id: webhook.id,
+                    payload,
+                    error,
+                    attempts: delivery.attempts,
+                    failed_at: now_ms(),
+                };
+                let registry = core.tool_registry.read().await;
+                if let Err(e) = registry.record_webhook_dead_letter(&dead_letter) {
+                    warn!("Failed to record the dead letter: {}", e);
+                }
+            });
+        }
+    }
+
+    fn start_webhook_dispatcher(&self) -> JoinHandle<()> {
+        let core = self.clone();
+        tokio::spawn(async move {
+            let mut events = core.event_bus.subscribe();
+            let mut statuses: Option<HashMap<String, ServerStatus>> = None;
+            let mut interval = tokio::time::interval(WEBHOOK_STATUS_INTERVAL);
+            loop {
+                tokio::select! {
+                    received = events.recv() => match received {
+                        Ok(envelope) => {
+                            if let Some((event, data)) = webhook_event(&envelope.event) {
+                                core.notify_webhooks(event, data).await;
+                            }
+                        }
+                        Err(RecvError::Lagged(missed)) => {
+                            warn!("Webhooks missed {} events", missed);
+                        }
+                        Err(RecvError::Closed) => break,
+                    },
+                    _ = interval.tick() => {
+                        let current = server_statuses(&core).await;
+                        if let Some(previous) = &statuses {
+                            for (event, data) in status_changes(previous, &current) {
+                                core.notify_webhooks(event, data).await;
+                            }
+                        }
+                        statuses = Some(current);
+                    }
+                }
+            }
+        })
+    }
+}
+
+/// Status of every installed server
+async fn server_statuses(core: &MCPCore) -> HashMap<String, ServerStatus> {
+    let server_ids: Vec<String> = match core.tool_registry.read().await.get_all_servers() {
+        Ok(servers) => servers.into_keys().collect(),
+        Err(e) => {
+            warn!("Failed to read the servers: {}", e);
+            return HashMap::new();
+        }
+    };
+    let mcp_state = core.mcp_state.read().await.clone();
+    let mut statuses = HashMap::new();
+    for server_id in server_ids {
+        let status = mcp_state.server_status(&server_id).await;
+        statuses.insert(server_id, status);
+    }
+    statuses
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
@@ -1,5 +1,32 @@
 pub mod mcp_core;
+pub mod mcp_core_approvals_ext;
+pub mod mcp_core_builder;
+pub mod mcp_core_bulk_ext;
+pub mod mcp_core_config_file_ext;
+pub mod mcp_core_config_history_ext;
+pub mod mcp_core_dashboard_ext;
 pub mod mcp_core_database_ext;
+pub mod mcp_core_diagnose_ext;
+pub mod mcp_core_diagnostics_ext;
+pub mod mcp_core_footprint_ext;
+pub mod mcp_core_health_ext;
+pub mod mcp_core_hooks_ext;
+pub mod mcp_core_identity_ext;
 pub mod mcp_core_installers_ext;
+pub mod mcp_core_lock_debug_ext;
+pub mod mcp_core_metrics_ext;
 pub mod mcp_core_proxy_ext;
+pub mod mcp_core_rate_limits_ext;
+pub mod mcp_core_read_only_ext;
+pub mod mcp_core_recordings_ext;
+pub mod mcp_core_registry_sources_ext;
+pub mod mcp_core_relocation_ext;
 pub mod mcp_core_runtimes_ext;
+pub mod mcp_core_schedules_ext;
+pub mod mcp_core_selftest_ext;
+pub mod mcp_core_server_info_ext;
+pub mod mcp_core_tool_limits_ext;
+pub mod mcp_core_tools_ext;
+pub mod mcp_core_tools_snapshot_ext;
+pub mod mcp_core_undo_ext;
+pub mod mcp_core_webhooks_ext;
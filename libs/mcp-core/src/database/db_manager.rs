@@ -1,33 +1,103 @@
+use diesel::connection::SimpleConnection;
 use diesel::prelude::*;
-use diesel::r2d2::{self, ConnectionManager, Pool};
+use diesel::r2d2::{self, ConnectionManager, CustomizeConnection, HandleEvent, Pool};
 use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
-use log::info;
-use std::collections::HashMap;
+use log::{info, warn};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
+use crate::config_history::{ConfigSnapshot, CONFIG_HISTORY_KEPT};
+use crate::database::gc::{
+    duplicate_env_keys, invalid_server_reason, GarbageReport, InvalidServer, SERVER_STATE_TABLES,
+};
+use crate::database::legacy::{
+    parse_legacy_tool, LegacyMigrationReport, LegacySkip, LegacyToolRow, LEGACY_MIGRATION_SETTING,
+    LEGACY_TOOLS_TABLE,
+};
+use crate::database::state::{DatabaseState, DatabaseStateError};
+use crate::lifecycle::{LifecycleEvent, LifecycleKind, LIFECYCLE_EVENTS_PERSISTED};
+use crate::models::env_key::canonicalize_env;
+use crate::models::server_id::{normalize_server_id, unique_server_id, validate_server_id};
 use crate::models::tool_db::{
-    DBAppSetting, DBServer, DBServerEnv, DBServerTool, NewAppSetting, NewServer, NewServerEnv, 
-    NewServerTool, UpdateServer, UpdateServerTool,
+    DBAppSetting, DBAuditLogEntry, DBConfigSnapshot, DBLifecycleEvent, DBRegistrySource,
+    DBSchedule, DBScheduleRun, DBServer, DBServerCallMode, DBServerEnv, DBServerIdAlias,
+    DBServerInfo, DBServerProcess, DBServerProvenance, DBServerRunState, DBServerTool, DBUndoEntry,
+    DBWebhook, DBWebhookDeadLetter, NewAppSetting, NewAuditLogEntry, NewConfigSnapshot,
+    NewLifecycleEvent, NewRegistrySource, NewSchedule, NewScheduleRun, NewServer, NewServerEnv,
+    NewServerTool, NewUndoEntry, NewWebhook, NewWebhookDeadLetter, UpdateServer, UpdateServerTool,
 };
 use crate::models::types::{
-    Distribution, InputSchema, ServerConfiguration, ServerDefinition, ServerEnvironment,
-    ServerToolInfo,
+    AuditAction, AuditDecision, AuditEntry, CallMode, DatabaseInfo, Distribution, EnvBoolFormat,
+    EnvValue, ProcessPriority, ProvenanceSource, ReplayMiss, ServerCallMode, ServerConfiguration,
+    ServerDefinition, ServerEnvironment, ServerInitInfo, ServerProvenance, ServerToolInfo,
+    SpawnedProcessRecord, StartMode,
+};
+use crate::registry::registry_sources::{RegistrySource, RegistrySourceKind};
+use crate::schedules::{
+    MisfirePolicy, Schedule, ScheduleDefinition, ScheduleRun, ScheduleStatus, SCHEDULE_RUNS_KEPT,
 };
 use crate::schema::app_settings::dsl as settings_dsl;
+use crate::schema::audit_log::dsl as audit_dsl;
+use crate::schema::registry_sources::dsl as sources_dsl;
+use crate::schema::schedule_runs::dsl as schedule_runs_dsl;
+use crate::schema::schedules::dsl as schedules_dsl;
+use crate::schema::server_call_modes::dsl as call_modes_dsl;
+use crate::schema::server_config_history::dsl as config_history_dsl;
 use crate::schema::server_env::dsl as env_dsl;
+use crate::schema::server_id_aliases::dsl as aliases_dsl;
+use crate::schema::server_info::dsl as server_info_dsl;
+use crate::schema::server_lifecycle_events::dsl as lifecycle_dsl;
+use crate::schema::server_processes::dsl as processes_dsl;
+use crate::schema::server_provenance::dsl as provenance_dsl;
+use crate::schema::server_run_states::dsl as run_states_dsl;
 use crate::schema::server_tools::dsl as server_tools_dsl;
 use crate::schema::servers::dsl as tools_dsl;
+use crate::schema::undo_journal::dsl as undo_dsl;
+use crate::schema::webhook_dead_letters::dsl as dead_letters_dsl;
+use crate::schema::webhooks::dsl as webhooks_dsl;
+use crate::undo::{UndoAction, UndoEntry, UNDO_JOURNAL_KEPT};
+use crate::webhooks::{DeadLetter, Webhook, WEBHOOK_DEAD_LETTERS_KEPT};
 
 pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations/sqlite");
 
 type SqlitePool = Pool<ConnectionManager<SqliteConnection>>;
 
+/// Options SQLite keeps per connection, set on each connection of the pool
+#[derive(Debug)]
+struct ConnectionOptions;
+
+impl CustomizeConnection<SqliteConnection, r2d2::Error> for ConnectionOptions {
+    fn on_acquire(&self, conn: &mut SqliteConnection) -> Result<(), r2d2::Error> {
+        conn.batch_execute("PRAGMA foreign_keys = ON; PRAGMA busy_timeout = 5000;")
+            .map_err(r2d2::Error::QueryError)
+    }
+}
+
+/// Delay of each checkout of a connection, in milliseconds, see [`DBManager::delay_queries`]
+#[derive(Debug)]
+struct QueryDelay(Arc<AtomicU64>);
+
+impl HandleEvent for QueryDelay {
+    fn handle_checkout(&self, _event: r2d2::event::CheckoutEvent) {
+        let delay = self.0.load(Ordering::Relaxed);
+        if delay > 0 {
+            std::thread::sleep(Duration::from_millis(delay));
+        }
+    }
+}
+
 #[derive(Clone)]
 /// Database manager for persisting application state
 pub struct DBManager {
     pool: Arc<SqlitePool>,
+    path: PathBuf,
+    /// Whether the database file was created when opening it
+    created: bool,
+    query_delay: Arc<AtomicU64>,
 }
 
 impl DBManager {
@@ -53,7 +123,8 @@ impl DBManager {
         let database_url = format!("sqlite://{}", db_path.to_string_lossy());
 
         // Ensure the database file exists
-        if !db_path.exists() {
+        let created = !db_path.exists();
+        if created {
             info!(
                 "database file does not exist, creating it, {}",
                 db_path.to_string_lossy()
@@ -71,14 +142,20 @@ impl DBManager {
         let manager = ConnectionManager::<SqliteConnection>::new(database_url);
 
         // Create the connection pool with more conservative settings
+        let query_delay = Arc::new(AtomicU64::new(0));
         let pool = r2d2::Pool::builder()
             .max_size(5)
             .connection_timeout(std::time::Duration::from_secs(5))
+            .connection_customizer(Box::new(ConnectionOptions))
+            .event_handler(Box::new(QueryDelay(query_delay.clone())))
             .build(manager)
             .map_err(|e| format!("failed to create connection pool: {}", e))?;
 
         let db_manager = Self {
             pool: Arc::new(pool),
+            path: db_path.clone(),
+            created,
+            query_delay,
         };
 
         info!("database initialized at: {:?}", db_path);
@@ -130,22 +207,149 @@ impl DBManager {
             .execute(&mut conn)
             .map_err(|e| format!("Failed to optimize: {}", e))?;
 
-        diesel::sql_query("PRAGMA busy_timeout = 5000")
-            .execute(&mut conn)
-            .map_err(|e| format!("Failed to set busy_timeout: {}", e))?;
-
         diesel::sql_query("PRAGMA mmap_size=262144000")
             .execute(&mut conn)
             .map_err(|e| format!("Failed to set mmap_size: {}", e))?;
 
-        diesel::sql_query("PRAGMA foreign_keys = ON")
-            .execute(&mut conn)
-            .map_err(|e| format!("Failed to enable foreign keys: {}", e))?;
+        for (old_id, new_id) in self.normalize_server_ids()? {
+            warn!(
+                "Server id '{}' is not valid, renamed to '{}'",
+                old_id, new_id
+            );
+        }
 
         info!("Migrations applied successfully");
         Ok(())
     }
 
+    /// Give a valid id to every server whose id isn't, returning the renames
+    ///
+    /// See [`normalize_server_id`], the old ids are kept as aliases.
+    pub fn normalize_server_ids(&self) -> Result<Vec<(String, String)>, String> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        let ids: Vec<String> = tools_dsl::servers
+            .select(tools_dsl::id)
+            .load(&mut conn)
+            .map_err(|e| format!("Failed to query server ids: {}", e))?;
+        drop(conn);
+
+        let mut taken: HashSet<String> = ids.iter().cloned().collect();
+        let mut renames = Vec::new();
+        for old_id in ids {
+            if validate_server_id(&old_id).is_ok() {
+                continue;
+            }
+            let new_id = unique_server_id(&normalize_server_id(&old_id), |id| taken.contains(id));
+            self.rename_server(&old_id, &new_id)?;
+            taken.remove(&old_id);
+            taken.insert(new_id.clone());
+            renames.push((old_id, new_id));
+        }
+        Ok(renames)
+    }
+
+    /// Move every row of a server to a new id, keeping the old one as an alias
+    pub fn rename_server(&self, old_id: &str, new_id: &str) -> Result<(), String> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            // Rows referencing the server are moved after it
+            diesel::sql_query("PRAGMA defer_foreign_keys = ON").execute(conn)?;
+
+            diesel::update(tools_dsl::servers.filter(tools_dsl::id.eq(old_id)))
+                .set(tools_dsl::id.eq(new_id))
+                .execute(conn)?;
+            diesel::update(env_dsl::server_env.filter(env_dsl::server_id.eq(old_id)))
+                .set(env_dsl::server_id.eq(new_id))
+                .execute(conn)?;
+            diesel::update(
+                server_tools_dsl::server_tools.filter(server_tools_dsl::server_id.eq(old_id)),
+            )
+            .set(server_tools_dsl::server_id.eq(new_id))
+            .execute(conn)?;
+            diesel::update(
+                server_tools_dsl::server_tools.filter(server_tools_dsl::proxy_id.eq(old_id)),
+            )
+            .set(server_tools_dsl::proxy_id.eq(new_id))
+            .execute(conn)?;
+            // Proxy ids are `server_id:tool_name`
+            diesel::sql_query(
+                "UPDATE server_tools SET proxy_id = ? || ':' || name \
+                 WHERE server_id = ? AND proxy_id = ? || ':' || name",
+            )
+            .bind::<diesel::sql_types::Text, _>(new_id)
+            .bind::<diesel::sql_types::Text, _>(new_id)
+            .bind::<diesel::sql_types::Text, _>(old_id)
+            .execute(conn)?;
+            diesel::update(
+                processes_dsl::server_processes.filter(processes_dsl::server_id.eq(old_id)),
+            )
+            .set(processes_dsl::server_id.eq(new_id))
+            .execute(conn)?;
+            diesel::update(
+                provenance_dsl::server_provenance.filter(provenance_dsl::server_id.eq(old_id)),
+            )
+            .set(provenance_dsl::server_id.eq(new_id))
+            .execute(conn)?;
+            diesel::update(
+                run_states_dsl::server_run_states.filter(run_states_dsl::server_id.eq(old_id)),
+            )
+            .set(run_states_dsl::server_id.eq(new_id))
+            .execute(conn)?;
+            diesel::update(
+                call_modes_dsl::server_call_modes.filter(call_modes_dsl::server_id.eq(old_id)),
+            )
+            .set(call_modes_dsl::server_id.eq(new_id))
+            .execute(conn)?;
+            diesel::update(
+                config_history_dsl::server_config_history
+                    .filter(config_history_dsl::server_id.eq(old_id)),
+            )
+            .set(config_history_dsl::server_id.eq(new_id))
+            .execute(conn)?;
+            diesel::update(
+                server_info_dsl::server_info.filter(server_info_dsl::server_id.eq(old_id)),
+            )
+            .set(server_info_dsl::server_id.eq(new_id))
+            .execute(conn)?;
+            diesel::update(
+                lifecycle_dsl::server_lifecycle_events.filter(lifecycle_dsl::server_id.eq(old_id)),
+            )
+            .set(lifecycle_dsl::server_id.eq(new_id))
+            .execute(conn)?;
+            diesel::update(undo_dsl::undo_journal.filter(undo_dsl::server_id.eq(old_id)))
+                .set(undo_dsl::server_id.eq(new_id))
+                .execute(conn)?;
+
+            // Older aliases follow the server, and the new id is no longer an alias
+            diesel::update(
+                aliases_dsl::server_id_aliases.filter(aliases_dsl::server_id.eq(old_id)),
+            )
+            .set(aliases_dsl::server_id.eq(new_id))
+            .execute(conn)?;
+            diesel::delete(aliases_dsl::server_id_aliases.filter(aliases_dsl::alias.eq(new_id)))
+                .execute(conn)?;
+            diesel::replace_into(aliases_dsl::server_id_aliases)
+                .values(&DBServerIdAlias {
+                    alias: old_id.to_string(),
+                    server_id: new_id.to_string(),
+                })
+                .execute(conn)?;
+
+            // The audit log is kept as is, it records what happened at the time
+
+            Ok(())
+        })
+        .map_err(|e| format!("Failed to rename server {} to {}: {}", old_id, new_id, e))
+    }
+
     /// Get a server by ID
     pub fn get_server(&self, tool_id_str: &str) -> Result<ServerDefinition, String> {
         let mut conn = self
@@ -168,14 +372,7 @@ impl DBManager {
         // Convert environment variables into a HashMap
         let mut env_map = HashMap::new();
         for row in env_rows {
-            env_map.insert(
-                row.env_key,
-                ServerEnvironment {
-                    description: row.env_description,
-                    default: Some(row.env_value),
-                    required: row.env_required,
-                },
-            );
+            env_map.insert(row.env_key.clone(), Self::env_from_row(row));
         }
 
         // 3) Convert DBTool -> domain-level Tool
@@ -208,6 +405,38 @@ impl DBManager {
                 } else {
                     Some(env_map)
                 },
+                priority: db_tool
+                    .priority
+                    .as_deref()
+                    .and_then(ProcessPriority::from_stored),
+                cpu_limit: db_tool.cpu_limit.and_then(|n| u32::try_from(n).ok()),
+                max_memory_mb: db_tool.max_memory_mb.and_then(|n| u32::try_from(n).ok()),
+                sandbox: db_tool
+                    .sandbox
+                    .as_deref()
+                    .and_then(|s| serde_json::from_str(s).ok()),
+                inherit_env: db_tool.inherit_env,
+                env_passthrough: db_tool
+                    .env_passthrough
+                    .as_deref()
+                    .and_then(|s| serde_json::from_str(s).ok()),
+                truncate_descriptions: db_tool.truncate_descriptions,
+                strict_output_schema: db_tool.strict_output_schema,
+                share_instructions: db_tool.share_instructions,
+                tool_filter: db_tool
+                    .tool_filter
+                    .as_deref()
+                    .and_then(|s| serde_json::from_str(s).ok()),
+                platforms: db_tool
+                    .platforms
+                    .as_deref()
+                    .and_then(|s| serde_json::from_str(s).ok()),
+                start_mode: db_tool
+                    .start_mode
+                    .as_deref()
+                    .and_then(StartMode::from_stored),
+                cwd: db_tool.cwd,
+                lenient_initialize: db_tool.lenient_initialize,
             }),
             distribution,
         };
@@ -215,6 +444,16 @@ impl DBManager {
         Ok(server)
     }
 
+    /// Convert a `server_env` row into its typed environment entry
+    fn env_from_row(row: DBServerEnv) -> ServerEnvironment {
+        ServerEnvironment {
+            description: row.env_description,
+            default: Some(EnvValue::from_stored(&row.env_value, &row.env_type)),
+            required: row.env_required,
+            bool_format: EnvBoolFormat::from_stored(&row.env_bool_format),
+        }
+    }
+
     /// Get all servers
     pub fn get_all_servers(&self) -> Result<HashMap<String, ServerDefinition>, String> {
         let mut conn = self
@@ -238,14 +477,7 @@ impl DBManager {
             HashMap::new();
         for row in all_env_rows {
             let tool_env_map = env_map_by_tool.entry(row.server_id.clone()).or_default();
-            tool_env_map.insert(
-                row.env_key.clone(),
-                ServerEnvironment {
-                    description: row.env_description,
-                    default: Some(row.env_value),
-                    required: row.env_required,
-                },
-            );
+            tool_env_map.insert(row.env_key.clone(), Self::env_from_row(row));
         }
 
         // 3) Convert DBTool -> domain-level Tool for each tool
@@ -283,6 +515,38 @@ impl DBManager {
                     } else {
                         Some(env_map)
                     },
+                    priority: db_tool
+                        .priority
+                        .as_deref()
+                        .and_then(ProcessPriority::from_stored),
+                    cpu_limit: db_tool.cpu_limit.and_then(|n| u32::try_from(n).ok()),
+                    max_memory_mb: db_tool.max_memory_mb.and_then(|n| u32::try_from(n).ok()),
+                    sandbox: db_tool
+                        .sandbox
+                        .as_deref()
+                        .and_then(|s| serde_json::from_str(s).ok()),
+                    inherit_env: db_tool.inherit_env,
+                    env_passthrough: db_tool
+                        .env_passthrough
+                        .as_deref()
+                        .and_then(|s| serde_json::from_str(s).ok()),
+                    truncate_descriptions: db_tool.truncate_descriptions,
+                    strict_output_schema: db_tool.strict_output_schema,
+                    share_instructions: db_tool.share_instructions,
+                    tool_filter: db_tool
+                        .tool_filter
+                        .as_deref()
+                        .and_then(|s| serde_json::from_str(s).ok()),
+                    platforms: db_tool
+                        .platforms
+                        .as_deref()
+                        .and_then(|s| serde_json::from_str(s).ok()),
+                    start_mode: db_tool
+                        .start_mode
+                        .as_deref()
+                        .and_then(StartMode::from_stored),
+                    cwd: db_tool.cwd,
+                    lenient_initialize: db_tool.lenient_initialize,
                 }),
                 distribution,
             };
@@ -300,6 +564,12 @@ impl DBManager {
             .get()
             .map_err(|e| format!("Failed to get database connection: {}", e))?;
 
+        // Refused before anything is written, the unique index would refuse them later
+        let env = match tool.configuration.as_ref().and_then(|c| c.env.as_ref()) {
+            Some(env) => canonicalize_env(env.iter().map(|(k, v)| (k.clone(), v)))?,
+            None => HashMap::new(),
+        };
+
         // Convert domain `Tool` into row data
         let distribution_type_str = tool.distribution.as_ref().map(|d| d.r#type.clone());
         let distribution_package_str = tool.distribution.as_ref().map(|d| d.package.clone());
@@ -314,17 +584,73 @@ impl DBManager {
             None
         };
 
-        let command_opt = tool
-            .configuration
-            .as_ref()
-            .and_then(|c| c.command.clone());
-            
+        let command_opt = tool.configuration.as_ref().and_then(|c| c.command.clone());
+
         // Only set command_str to None if we have no configuration or command is None
         let command_str = match &command_opt {
             Some(cmd) if !cmd.is_empty() => cmd.clone(),
-            _ => String::new() // Empty string will be mapped to None below
+            _ => String::new(), // Empty string will be mapped to None below
         };
 
+        let priority_str = tool
+            .configuration
+            .as_ref()
+            .and_then(|c| c.priority)
+            .map(|p| p.as_str());
+        let cpu_limit = tool
+            .configuration
+            .as_ref()
+            .and_then(|c| c.cpu_limit)
+            .map(|n| n.min(i32::MAX as u32) as i32);
+        let max_memory_mb = tool
+            .configuration
+            .as_ref()
+            .and_then(|c| c.max_memory_mb)
+            .map(|n| n.min(i32::MAX as u32) as i32);
+        let sandbox_str = tool
+            .configuration
+            .as_ref()
+            .and_then(|c| c.sandbox.as_ref())
+            .map(|sandbox| serde_json::to_string(sandbox).unwrap_or_default());
+        let inherit_env = tool.configuration.as_ref().and_then(|c| c.inherit_env);
+        let env_passthrough_str = tool
+            .configuration
+            .as_ref()
+            .and_then(|c| c.env_passthrough.as_ref())
+            .map(|names| serde_json::to_string(names).unwrap_or_default());
+        let truncate_descriptions = tool
+            .configuration
+            .as_ref()
+            .and_then(|c| c.truncate_descriptions);
+        let strict_output_schema = tool
+            .configuration
+            .as_ref()
+            .and_then(|c| c.strict_output_schema);
+        let share_instructions = tool
+            .configuration
+            .as_ref()
+            .and_then(|c| c.share_instructions);
+        let tool_filter_str = tool
+            .configuration
+            .as_ref()
+            .and_then(|c| c.tool_filter.as_ref())
+            .map(|filter| serde_json::to_string(filter).unwrap_or_default());
+        let platforms_str = tool
+            .configuration
+            .as_ref()
+            .and_then(|c| c.platforms.as_ref())
+            .map(|platforms| serde_json::to_string(platforms).unwrap_or_default());
+        let start_mode_str = tool
+            .configuration
+            .as_ref()
+            .and_then(|c| c.start_mode)
+            .map(|mode| mode.as_str());
+        let cwd = tool.configuration.as_ref().and_then(|c| c.cwd.as_deref());
+        let lenient_initialize = tool
+            .configuration
+            .as_ref()
+            .and_then(|c| c.lenient_initialize);
+
         // Prepare upsert struct
         let new_tool = NewServer {
             id: server_id_str,
@@ -341,6 +667,20 @@ impl DBManager {
             args: args_as_str.as_deref(),
             distribution_type: distribution_type_str.as_deref(),
             distribution_package: distribution_package_str.as_deref(),
+            priority: priority_str,
+            cpu_limit,
+            max_memory_mb,
+            sandbox: sandbox_str.as_deref(),
+            inherit_env,
+            env_passthrough: env_passthrough_str.as_deref(),
+            truncate_descriptions,
+            strict_output_schema,
+            share_instructions,
+            tool_filter: tool_filter_str.as_deref(),
+            platforms: platforms_str.as_deref(),
+            start_mode: start_mode_str,
+            cwd,
+            lenient_initialize,
         };
 
         // For updates, we need to create an UpdateTool struct
@@ -358,6 +698,20 @@ impl DBManager {
             args: Some(args_as_str.as_deref()),
             distribution_type: Some(distribution_type_str.as_deref()),
             distribution_package: Some(distribution_package_str.as_deref()),
+            priority: Some(priority_str),
+            cpu_limit: Some(cpu_limit),
+            max_memory_mb: Some(max_memory_mb),
+            sandbox: Some(sandbox_str.as_deref()),
+            inherit_env: Some(inherit_env),
+            env_passthrough: Some(env_passthrough_str.as_deref()),
+            truncate_descriptions: Some(truncate_descriptions),
+            strict_output_schema: Some(strict_output_schema),
+            share_instructions: Some(share_instructions),
+            tool_filter: Some(tool_filter_str.as_deref()),
+            platforms: Some(platforms_str.as_deref()),
+            start_mode: Some(start_mode_str),
+            cwd: Some(cwd),
+            lenient_initialize: Some(lenient_initialize),
         };
 
         // Insert or update main row
@@ -376,29 +730,33 @@ impl DBManager {
             .map_err(|e| format!("Failed to clear old env: {}", e))?;
 
         // 2) Insert new environment variables
-        if let Some(config) = &tool.configuration {
-            if let Some(env) = &config.env {
-                let new_env_rows: Vec<NewServerEnv> = env
-                    .iter()
-                    .map(|(k, v)| {
-                        let default_value = v.default.clone().unwrap_or_default();
-                        NewServerEnv {
-                            server_id: server_id_str.to_string(),
-                            env_key: k.to_string(),
-                            env_value: default_value,
-                            env_description: v.description.clone(),
-                            env_required: v.required,
-                        }
-                    })
-                    .collect();
+        let new_env_rows: Vec<NewServerEnv> = env
+            .iter()
+            .map(|(k, v)| NewServerEnv {
+                server_id: server_id_str.to_string(),
+                env_key: k.to_string(),
+                // Stored untouched, the bool format is applied at spawn time
+                env_value: v
+                    .default
+                    .as_ref()
+                    .map(|d| d.to_string())
+                    .unwrap_or_default(),
+                env_description: v.description.clone(),
+                env_required: v.required,
+                env_type: v
+                    .default
+                    .as_ref()
+                    .map_or("string", |d| d.value_type())
+                    .to_string(),
+                env_bool_format: v.bool_format.as_str().to_string(),
+            })
+            .collect();
 
-                if !new_env_rows.is_empty() {
-                    diesel::insert_into(env_dsl::server_env)
-                        .values(&new_env_rows)
-                        .execute(&mut conn)
-                        .map_err(|e| format!("Failed to save env vars: {}", e))?;
-                }
-            }
+        if !new_env_rows.is_empty() {
+            diesel::insert_into(env_dsl::server_env)
+                .values(&new_env_rows)
+                .execute(&mut conn)
+                .map_err(|e| format!("Failed to save env vars: {}", e))?;
         }
 
         Ok(())
@@ -416,6 +774,24 @@ impl DBManager {
             .execute(&mut conn)
             .map_err(|e| format!("Failed to delete tool environment variables: {}", e))?;
 
+        diesel::delete(
+            provenance_dsl::server_provenance.filter(provenance_dsl::server_id.eq(tool_id_str)),
+        )
+        .execute(&mut conn)
+        .map_err(|e| format!("Failed to delete server provenance: {}", e))?;
+
+        diesel::delete(
+            run_states_dsl::server_run_states.filter(run_states_dsl::server_id.eq(tool_id_str)),
+        )
+        .execute(&mut conn)
+        .map_err(|e| format!("Failed to delete server run state: {}", e))?;
+
+        diesel::delete(
+            call_modes_dsl::server_call_modes.filter(call_modes_dsl::server_id.eq(tool_id_str)),
+        )
+        .execute(&mut conn)
+        .map_err(|e| format!("Failed to delete server call mode: {}", e))?;
+
         // Delete the tool
         diesel::delete(tools_dsl::servers.filter(tools_dsl::id.eq(tool_id_str)))
             .execute(&mut conn)
@@ -441,6 +817,35 @@ impl DBManager {
             // Delete server tools
             diesel::delete(server_tools_dsl::server_tools).execute(conn)?;
 
+            // Delete spawned process records
+            diesel::delete(processes_dsl::server_processes).execute(conn)?;
+
+            // Delete provenance records
+            diesel::delete(provenance_dsl::server_provenance).execute(conn)?;
+
+            // Delete the desired run states
+            diesel::delete(run_states_dsl::server_run_states).execute(conn)?;
+
+            // Delete the call modes
+            diesel::delete(call_modes_dsl::server_call_modes).execute(conn)?;
+
+            // Delete the configuration history
+            diesel::delete(config_history_dsl::server_config_history).execute(conn)?;
+
+            // Delete what servers said about themselves
+            diesel::delete(server_info_dsl::server_info).execute(conn)?;
+
+            // Delete the lifecycle history
+            diesel::delete(lifecycle_dsl::server_lifecycle_events).execute(conn)?;
+
+            // Delete the changes that could be undone
+            diesel::delete(undo_dsl::undo_journal).execute(conn)?;
+
+            // Delete the former ids of servers
+            diesel::delete(aliases_dsl::server_id_aliases).execute(conn)?;
+
+            // The audit log is kept, it records decisions rather than server state
+
             Ok(())
         })
         .map_err(|e| format!("Transaction failed: {}", e))?;
@@ -449,6 +854,157 @@ impl DBManager {
         Ok(())
     }
 
+    /// Find the rows of removed servers, servers that can't start and duplicated
+    /// environment keys, and remove them unless `dry_run`
+    ///
+    /// See [`crate::database::gc`].
+    pub fn collect_garbage(&self, dry_run: bool) -> Result<GarbageReport, String> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        let servers: Vec<(String, String, Option<String>, Option<String>)> = tools_dsl::servers
+            .select((
+                tools_dsl::id,
+                tools_dsl::tools_type,
+                tools_dsl::command,
+                tools_dsl::entry_point,
+            ))
+            .load(&mut conn)
+            .map_err(|e| format!("Failed to query servers: {}", e))?;
+        let env: Vec<(String, String)> = env_dsl::server_env
+            .select((env_dsl::server_id, env_dsl::env_key))
+            .order((env_dsl::server_id, env_dsl::env_key))
+            .load(&mut conn)
+            .map_err(|e| format!("Failed to query env vars: {}", e))?;
+        let tools: Vec<(String, String)> = server_tools_dsl::server_tools
+            .select((server_tools_dsl::server_id, server_tools_dsl::id))
+            .order((server_tools_dsl::server_id, server_tools_dsl::id))
+            .load(&mut conn)
+            .map_err(|e| format!("Failed to query server tools: {}", e))?;
+        let mut state = Vec::new();
+        for ids in [
+            provenance_dsl::server_provenance
+                .select(provenance_dsl::server_id)
+                .load::<String>(&mut conn),
+            run_states_dsl::server_run_states
+                .select(run_states_dsl::server_id)
+                .load::<String>(&mut conn),
+            call_modes_dsl::server_call_modes
+                .select(call_modes_dsl::server_id)
+                .load::<String>(&mut conn),
+        ] {
+            state.push(ids.map_err(|e| format!("Failed to query server state: {}", e))?);
+        }
+
+        let mut report = GarbageReport {
+            dry_run,
+            ..Default::default()
+        };
+        let server_ids: HashSet<&str> = servers.iter().map(|s| s.0.as_str()).collect();
+        for (server_id, tools_type, command, entry_point) in &servers {
+            if let Some(reason) =
+                invalid_server_reason(tools_type, command.as_deref(), entry_point.as_deref())
+            {
+                report.invalid_servers.push(InvalidServer {
+                    server_id: server_id.clone(),
+                    reason: reason.to_string(),
+                });
+            }
+        }
+
+        let mut env_keys: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+        for (server_id, env_key) in &env {
+            if server_ids.contains(server_id.as_str()) {
+                env_keys
+                    .entry(server_id.as_str())
+                    .or_default()
+                    .push(env_key.clone());
+            } else {
+                report
+                    .orphaned_env
+                    .push((server_id.clone(), env_key.clone()));
+            }
+        }
+        for (server_id, keys) in env_keys {
+            for key in duplicate_env_keys(&keys) {
+                report.duplicate_env.push((server_id.to_string(), key));
+            }
+        }
+        report.orphaned_tools = tools
+            .into_iter()
+            .filter(|(server_id, _)| !server_ids.contains(server_id.as_str()))
+            .collect();
+        for (table, ids) in SERVER_STATE_TABLES.iter().zip(state) {
+            for server_id in ids {
+                if !server_ids.contains(server_id.as_str()) {
+                    report.orphaned_state.push((table.to_string(), server_id));
+                }
+            }
+        }
+
+        if dry_run || report.is_empty() {
+            return Ok(report);
+        }
+
+        let mut removed: Vec<String> = report
+            .orphaned_env
+            .iter()
+            .chain(&report.orphaned_tools)
+            .map(|(server_id, _)| server_id.clone())
+            .chain(
+                report
+                    .orphaned_state
+                    .iter()
+                    .map(|(_, server_id)| server_id.clone()),
+            )
+            .chain(report.invalid_servers.iter().map(|s| s.server_id.clone()))
+            .collect();
+        removed.sort_unstable();
+        removed.dedup();
+
+        conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            diesel::delete(env_dsl::server_env.filter(env_dsl::server_id.eq_any(&removed)))
+                .execute(conn)?;
+            diesel::delete(
+                server_tools_dsl::server_tools.filter(server_tools_dsl::server_id.eq_any(&removed)),
+            )
+            .execute(conn)?;
+            diesel::delete(
+                provenance_dsl::server_provenance
+                    .filter(provenance_dsl::server_id.eq_any(&removed)),
+            )
+            .execute(conn)?;
+            diesel::delete(
+                run_states_dsl::server_run_states
+                    .filter(run_states_dsl::server_id.eq_any(&removed)),
+            )
+            .execute(conn)?;
+            diesel::delete(
+                call_modes_dsl::server_call_modes
+                    .filter(call_modes_dsl::server_id.eq_any(&removed)),
+            )
+            .execute(conn)?;
+            diesel::delete(tools_dsl::servers.filter(tools_dsl::id.eq_any(&removed)))
+                .execute(conn)?;
+
+            for (server_id, env_key) in &report.duplicate_env {
+                diesel::delete(
+                    env_dsl::server_env
+                        .filter(env_dsl::server_id.eq(server_id))
+                        .filter(env_dsl::env_key.eq(env_key)),
+                )
+                .execute(conn)?;
+            }
+            Ok(())
+        })
+        .map_err(|e| format!("Failed to remove unused rows: {}", e))?;
+
+        info!("Removed {} unused database rows", report.total());
+        Ok(report)
+    }
+
     /// Check if the database exists and has data
     pub fn check_exists(&self) -> Result<bool, String> {
         let mut conn = self
@@ -464,6 +1020,124 @@ impl DBManager {
         Ok(count > 0)
     }
 
+    /// Delay every query by `delay`, to see how callers cope with a stalled database
+    pub fn delay_queries(&self, delay: Duration) {
+        self.query_delay
+            .store(delay.as_millis() as u64, Ordering::Relaxed);
+    }
+
+    /// Path of the database file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Location, size and schema version of the database
+    pub fn info(&self) -> Result<DatabaseInfo, String> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        let schema_version = conn
+            .applied_migrations()
+            .map_err(|e| format!("Failed to read applied migrations: {}", e))?
+            .iter()
+            .map(|version| version.to_string())
+            .max();
+        let server_count: i64 = tools_dsl::servers
+            .count()
+            .get_result(&mut conn)
+            .map_err(|e| format!("Failed to count servers: {}", e))?;
+        let tool_count: i64 = server_tools_dsl::server_tools
+            .count()
+            .get_result(&mut conn)
+            .map_err(|e| format!("Failed to count server tools: {}", e))?;
+
+        Ok(DatabaseInfo {
+            path: self.path.to_string_lossy().to_string(),
+            size_bytes: fs::metadata(&self.path).map(|m| m.len()).unwrap_or(0),
+            schema_version,
+            server_count,
+            tool_count,
+        })
+    }
+
+    /// Whether the database is new, empty, in use, waits for a migration or can't be
+    /// read, see [`crate::database::state`]
+    pub fn state(&self) -> DatabaseState {
+        self.read_state().unwrap_or_else(|e| {
+            warn!("{}", e);
+            DatabaseState::unreadable(e)
+        })
+    }
+
+    fn read_state(&self) -> Result<DatabaseState, DatabaseStateError> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| DatabaseStateError::Connection(e.to_string()))?;
+
+        let schema_version = conn
+            .applied_migrations()
+            .map_err(|e| DatabaseStateError::Migrations(e.to_string()))?
+            .iter()
+            .map(|version| version.to_string())
+            .max();
+        let pending_migrations = conn
+            .pending_migrations(MIGRATIONS)
+            .map_err(|e| DatabaseStateError::Migrations(e.to_string()))?
+            .len();
+        // The tables don't exist before the first migration
+        if schema_version.is_none() {
+            return Ok(DatabaseState::from_counts(
+                self.created,
+                None,
+                pending_migrations,
+                0,
+                0,
+                0,
+            ));
+        }
+
+        let server_count: i64 = tools_dsl::servers
+            .count()
+            .get_result(&mut conn)
+            .map_err(|e| DatabaseStateError::Query(e.to_string()))?;
+        let tool_count: i64 = server_tools_dsl::server_tools
+            .count()
+            .get_result(&mut conn)
+            .map_err(|e| DatabaseStateError::Query(e.to_string()))?;
+        drop(conn);
+
+        let legacy_migrated = self
+            .get_setting(LEGACY_MIGRATION_SETTING)
+            .is_ok_and(|value| value == "true");
+        let legacy_server_count = if legacy_migrated {
+            0
+        } else {
+            self.legacy_tool_rows()
+                .map_err(DatabaseStateError::Query)?
+                .map_or(0, |rows| rows.len())
+        };
+
+        Ok(DatabaseState::from_counts(
+            self.created,
+            schema_version,
+            pending_migrations,
+            server_count,
+            tool_count,
+            legacy_server_count,
+        ))
+    }
+
+    /// Directory of the database, where other data of the app is kept too
+    pub fn data_dir(&self) -> PathBuf {
+        self.path
+            .parent()
+            .map(|parent| parent.to_path_buf())
+            .unwrap_or_default()
+    }
+
     /// Safely close the database connection
     pub fn close(self) -> Result<(), String> {
         // The connection pool will be dropped when self is dropped
@@ -488,6 +1162,14 @@ impl DBManager {
         } else {
             None
         };
+        let output_schema_json = tool.output_schema.as_ref().map(|schema| schema.to_string());
+        let annotations_json = match &tool.annotations {
+            Some(annotations) => Some(
+                serde_json::to_string(annotations)
+                    .map_err(|e| format!("Failed to serialize annotations: {}", e))?,
+            ),
+            None => None,
+        };
 
         // Create the new server tool record
         let new_tool = NewServerTool {
@@ -498,6 +1180,8 @@ impl DBManager {
             server_id: tool.server_id.clone(),
             proxy_id: tool.proxy_id.clone(),
             is_active: tool.is_active,
+            output_schema: output_schema_json.clone(),
+            annotations: annotations_json.clone(),
         };
 
         // Insert or update the server tool
@@ -511,6 +1195,8 @@ impl DBManager {
                 input_schema: Some(input_schema_json),
                 proxy_id: Some(tool.proxy_id.clone()),
                 is_active: Some(tool.is_active),
+                output_schema: Some(output_schema_json),
+                annotations: Some(annotations_json),
             })
             .execute(&mut conn)
             .map_err(|e| format!("Failed to save server tool: {}", e))?;
@@ -535,25 +1221,7 @@ impl DBManager {
             .first::<DBServerTool>(&mut conn)
             .map_err(|e| format!("Failed to get server tool {}: {}", tool_id, e))?;
 
-        // Parse the input_schema from JSON if it exists
-        let input_schema = if let Some(ref schema_json) = db_tool.input_schema {
-            Some(
-                serde_json::from_str::<InputSchema>(schema_json)
-                    .map_err(|e| format!("Failed to parse input schema: {}", e))?,
-            )
-        } else {
-            None
-        };
-
-        Ok(ServerToolInfo {
-            id: db_tool.id,
-            name: db_tool.name,
-            description: db_tool.description,
-            input_schema,
-            server_id: db_tool.server_id,
-            proxy_id: db_tool.proxy_id,
-            is_active: db_tool.is_active,
-        })
+        server_tool_from_row(db_tool)
     }
 
     /// Get all ServerToolInfo for a server
@@ -568,30 +1236,7 @@ impl DBManager {
             .load::<DBServerTool>(&mut conn)
             .map_err(|e| format!("Failed to get server tools for {}: {}", server_id, e))?;
 
-        let mut tools = Vec::new();
-        for db_tool in db_tools {
-            // Parse the input_schema from JSON if it exists
-            let input_schema = if let Some(ref schema_json) = db_tool.input_schema {
-                Some(
-                    serde_json::from_str::<InputSchema>(schema_json)
-                        .map_err(|e| format!("Failed to parse input schema: {}", e))?,
-                )
-            } else {
-                None
-            };
-
-            tools.push(ServerToolInfo {
-                id: db_tool.id,
-                name: db_tool.name,
-                description: db_tool.description,
-                input_schema,
-                server_id: db_tool.server_id,
-                proxy_id: db_tool.proxy_id,
-                is_active: db_tool.is_active,
-            });
-        }
-
-        Ok(tools)
+        db_tools.into_iter().map(server_tool_from_row).collect()
     }
 
     /// Delete a ServerToolInfo by ID and server_id
@@ -646,4 +1291,1225 @@ impl DBManager {
 
         Ok(())
     }
+
+    /// Save or replace the process record of a server
+    pub fn save_server_process(&self, record: &SpawnedProcessRecord) -> Result<(), String> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        let row = DBServerProcess {
+            server_id: record.server_id.clone(),
+            spawn_id: record.spawn_id.clone(),
+            pid: record.pid.map(i64::from),
+            start_time: record.start_time.map(|t| t as i64),
+            command: record.command.clone(),
+            container_name: record.container_name.clone(),
+        };
+
+        diesel::replace_into(processes_dsl::server_processes)
+            .values(&row)
+            .execute(&mut conn)
+            .map_err(|e| format!("Failed to save server process: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Get all recorded server processes
+    pub fn get_server_processes(&self) -> Result<Vec<SpawnedProcessRecord>, String> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        let rows = processes_dsl::server_processes
+            .select(DBServerProcess::as_select())
+            .load::<DBServerProcess>(&mut conn)
+            .map_err(|e| format!("Failed to query server processes: {}", e))?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| SpawnedProcessRecord {
+                server_id: row.server_id,
+                spawn_id: row.spawn_id,
+                pid: row.pid.and_then(|pid| u32::try_from(pid).ok()),
+                start_time: row.start_time.map(|t| t as u64),
+                command: row.command,
+                container_name: row.container_name,
+            })
+            .collect())
+    }
+
+    /// Delete the process record of a server
+    pub fn delete_server_process(&self, server_id: &str) -> Result<(), String> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        diesel::delete(
+            processes_dsl::server_processes.filter(processes_dsl::server_id.eq(server_id)),
+        )
+        .execute(&mut conn)
+        .map_err(|e| format!("Failed to delete server process: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Save or replace the provenance of a server
+    pub fn save_server_provenance(
+        &self,
+        server_id: &str,
+        provenance: &ServerProvenance,
+    ) -> Result<(), String> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        let row = DBServerProvenance {
+            server_id: server_id.to_string(),
+            source: provenance.source.as_str().to_string(),
+            source_id: provenance.source_id.clone(),
+            client: provenance.client.clone(),
+            installed_at: provenance.installed_at as i64,
+            app_version: provenance.app_version.clone(),
+            registry_source: provenance.registry_source.clone(),
+        };
+
+        diesel::replace_into(provenance_dsl::server_provenance)
+            .values(&row)
+            .execute(&mut conn)
+            .map_err(|e| format!("Failed to save server provenance: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Save whether the user wants a server running
+    pub fn save_server_run_state(
+        &self,
+        server_id: &str,
+        desired_running: bool,
+    ) -> Result<(), String> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        diesel::replace_into(run_states_dsl::server_run_states)
+            .values(&DBServerRunState {
+                server_id: server_id.to_string(),
+                desired_running,
+            })
+            .execute(&mut conn)
+            .map_err(|e| format!("Failed to save server run state: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Save whether the user wants each of `server_ids` running, in one transaction
+    pub fn save_server_run_states(
+        &self,
+        server_ids: &[String],
+        desired_running: bool,
+    ) -> Result<(), String> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            for server_id in server_ids {
+                diesel::replace_into(run_states_dsl::server_run_states)
+                    .values(&DBServerRunState {
+                        server_id: server_id.clone(),
+                        desired_running,
+                    })
+                    .execute(conn)?;
+            }
+            Ok(())
+        })
+        .map_err(|e| format!("Failed to save server run states: {}", e))
+    }
+
+    /// Enable or disable each of `server_ids`, and record that the user wants them
+    /// running or not, in one transaction
+    pub fn set_servers_enabled(&self, server_ids: &[String], enabled: bool) -> Result<(), String> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            diesel::update(tools_dsl::servers.filter(tools_dsl::id.eq_any(server_ids)))
+                .set(tools_dsl::enabled.eq(enabled))
+                .execute(conn)?;
+            for server_id in server_ids {
+                diesel::replace_into(run_states_dsl::server_run_states)
+                    .values(&DBServerRunState {
+                        server_id: server_id.clone(),
+                        desired_running: enabled,
+                    })
+                    .execute(conn)?;
+            }
+            Ok(())
+        })
+        .map_err(|e| format!("Failed to update servers: {}", e))
+    }
+
+    /// Get whether the user wants each server running, for the servers with a saved
+    /// run state
+    pub fn get_server_run_states(&self) -> Result<HashMap<String, bool>, String> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        let rows = run_states_dsl::server_run_states
+            .select(DBServerRunState::as_select())
+            .load::<DBServerRunState>(&mut conn)
+            .map_err(|e| format!("Failed to query server run states: {}", e))?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| (row.server_id, row.desired_running))
+            .collect())
+    }
+
+    /// Save whether the tool calls of a server are recorded or replayed
+    pub fn save_server_call_mode(
+        &self,
+        server_id: &str,
+        call_mode: &ServerCallMode,
+    ) -> Result<(), String> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        diesel::replace_into(call_modes_dsl::server_call_modes)
+            .values(&DBServerCallMode {
+                server_id: server_id.to_string(),
+                mode: call_mode.mode.as_str().to_string(),
+                on_miss: call_mode.on_miss.as_str().to_string(),
+            })
+            .execute(&mut conn)
+            .map_err(|e| format!("Failed to save server call mode: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Get the call mode of every server that has one, by server id
+    pub fn get_server_call_modes(&self) -> Result<HashMap<String, ServerCallMode>, String> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        let rows = call_modes_dsl::server_call_modes
+            .select(DBServerCallMode::as_select())
+            .load::<DBServerCallMode>(&mut conn)
+            .map_err(|e| format!("Failed to query server call modes: {}", e))?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| {
+                let call_mode = ServerCallMode {
+                    mode: CallMode::from_stored(&row.mode)?,
+                    on_miss: ReplayMiss::from_stored(&row.on_miss).unwrap_or_default(),
+                };
+                Some((row.server_id, call_mode))
+            })
+            .collect())
+    }
+
+    /// Get the provenance of every server that has one, by server id
+    pub fn get_server_provenances(&self) -> Result<HashMap<String, ServerProvenance>, String> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        let rows = provenance_dsl::server_provenance
+            .select(DBServerProvenance::as_select())
+            .load::<DBServerProvenance>(&mut conn)
+            .map_err(|e| format!("Failed to query server provenance: {}", e))?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| {
+                let source = ProvenanceSource::from_stored(&row.source)?;
+                Some((
+                    row.server_id,
+                    ServerProvenance {
+                        source,
+                        source_id: row.source_id,
+                        client: row.client,
+                        installed_at: row.installed_at.max(0) as u64,
+                        app_version: row.app_version,
+                        registry_source: row.registry_source,
+                    },
+                ))
+            })
+            .collect())
+    }
+
+    /// Save or replace what a server said about itself when it last started
+    pub fn save_server_info(&self, server_id: &str, info: &ServerInitInfo) -> Result<(), String> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        let row = DBServerInfo {
+            server_id: server_id.to_string(),
+            name: info.name.clone(),
+            version: info.version.clone(),
+            protocol_version: info.protocol_version.clone(),
+            instructions: info.instructions.clone(),
+            initialized_at: info.initialized_at as i64,
+            capabilities: info.capabilities.as_ref().map(|c| c.to_string()),
+        };
+
+        diesel::replace_into(server_info_dsl::server_info)
+            .values(&row)
+            .execute(&mut conn)
+            .map_err(|e| format!("Failed to save server info: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Get what every server that started once said about itself, by server id
+    pub fn get_server_infos(&self) -> Result<HashMap<String, ServerInitInfo>, String> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        let rows = server_info_dsl::server_info
+            .select(DBServerInfo::as_select())
+            .load::<DBServerInfo>(&mut conn)
+            .map_err(|e| format!("Failed to query server info: {}", e))?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                (
+                    row.server_id,
+                    ServerInitInfo {
+                        name: row.name,
+                        version: row.version,
+                        protocol_version: row.protocol_version,
+                        instructions: row.instructions,
+                        initialized_at: row.initialized_at.max(0) as u64,
+                        capabilities: row
+                            .capabilities
+                            .and_then(|capabilities| serde_json::from_str(&capabilities).ok()),
+                    },
+                )
+            })
+            .collect())
+    }
+
+    /// Save or replace the server an alias points to
+    pub fn save_server_id_alias(&self, alias: &str, server_id: &str) -> Result<(), String> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        diesel::replace_into(aliases_dsl::server_id_aliases)
+            .values(&DBServerIdAlias {
+                alias: alias.to_string(),
+                server_id: server_id.to_string(),
+            })
+            .execute(&mut conn)
+            .map_err(|e| format!("Failed to save server id alias: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Get the server an alias points to, None when `alias` isn't one
+    pub fn get_server_id_alias(&self, alias: &str) -> Result<Option<String>, String> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        aliases_dsl::server_id_aliases
+            .filter(aliases_dsl::alias.eq(alias))
+            .select(aliases_dsl::server_id)
+            .first::<String>(&mut conn)
+            .optional()
+            .map_err(|e| format!("Failed to query server id alias: {}", e))
+    }
+
+    /// Append an entry to the audit log, its id is assigned by the database
+    pub fn append_audit_entry(&self, entry: &AuditEntry) -> Result<(), String> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        let row = NewAuditLogEntry {
+            timestamp: entry.timestamp as i64,
+            action: entry.action.as_str(),
+            server_id: entry.server_id.as_deref(),
+            client: entry.client.as_deref(),
+            decision: entry.decision.as_str(),
+            detail: entry.detail.as_deref(),
+        };
+
+        diesel::insert_into(audit_dsl::audit_log)
+            .values(&row)
+            .execute(&mut conn)
+            .map_err(|e| format!("Failed to append audit entry: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Get the most recent audit log entries, newest first
+    pub fn get_audit_log(&self, limit: i64) -> Result<Vec<AuditEntry>, String> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        let rows = audit_dsl::audit_log
+            .order(audit_dsl::id.desc())
+            .limit(limit)
+            .select(DBAuditLogEntry::as_select())
+            .load::<DBAuditLogEntry>(&mut conn)
+            .map_err(|e| format!("Failed to query audit log: {}", e))?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| {
+                Some(AuditEntry {
+                    id: row.id as i64,
+                    timestamp: row.timestamp.max(0) as u64,
+                    action: AuditAction::from_stored(&row.action)?,
+                    server_id: row.server_id,
+                    client: row.client,
+                    decision: AuditDecision::from_stored(&row.decision)?,
+                    detail: row.detail,
+                })
+            })
+            .collect())
+    }
+
+    /// Create a scheduled job or update its definition, keeping its status
+    pub fn save_schedule(&self, schedule: &ScheduleDefinition) -> Result<(), String> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        let row = NewSchedule {
+            id: &schedule.id,
+            cron: &schedule.cron,
+            tool_id: &schedule.tool_id,
+            arguments: schedule.arguments.to_string(),
+            enabled: schedule.enabled,
+            misfire: schedule.misfire.as_str(),
+        };
+        diesel::insert_into(schedules_dsl::schedules)
+            .values(&row)
+            .on_conflict(schedules_dsl::id)
+            .do_update()
+            .set(&row)
+            .execute(&mut conn)
+            .map_err(|e| format!("Failed to save schedule: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Get every scheduled job, by id
+    pub fn get_schedules(&self) -> Result<Vec<Schedule>, String> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        let rows = schedules_dsl::schedules
+            .order(schedules_dsl::id.asc())
+            .select(DBSchedule::as_select())
+            .load::<DBSchedule>(&mut conn)
+            .map_err(|e| format!("Failed to query schedules: {}", e))?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| Schedule {
+                definition: ScheduleDefinition {
+                    id: row.id,
+                    cron: row.cron,
+                    tool_id: row.tool_id,
+                    arguments: serde_json::from_str(&row.arguments).unwrap_or_default(),
+                    enabled: row.enabled,
+                    misfire: MisfirePolicy::from_stored(&row.misfire).unwrap_or_default(),
+                },
+                status: ScheduleStatus {
+                    last_run_at: row.last_run_at.map(|at| at.max(0) as u64),
+                    last_success: row.last_success,
+                    last_error: row.last_error,
+                    consecutive_failures: row.consecutive_failures.max(0) as u32,
+                    next_run_at: None,
+                },
+            })
+            .collect())
+    }
+
+    /// Delete a scheduled job with its run history
+    pub fn delete_schedule(&self, schedule_id: &str) -> Result<(), String> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        let deleted = conn
+            .transaction::<_, diesel::result::Error, _>(|conn| {
+                diesel::delete(
+                    schedule_runs_dsl::schedule_runs
+                        .filter(schedule_runs_dsl::schedule_id.eq(schedule_id)),
+                )
+                .execute(conn)?;
+                diesel::delete(schedules_dsl::schedules.filter(schedules_dsl::id.eq(schedule_id)))
+                    .execute(conn)
+            })
+            .map_err(|e| format!("Failed to delete schedule: {}", e))?;
+        if deleted == 0 {
+            return Err(format!("Schedule '{}' not found", schedule_id));
+        }
+
+        Ok(())
+    }
+
+    /// Append a run to the history of its job and update the status of the job
+    ///
+    /// Only the last [`SCHEDULE_RUNS_KEPT`] runs of each job are kept.
+    pub fn record_schedule_run(&self, run: &ScheduleRun) -> Result<(), String> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            diesel::insert_into(schedule_runs_dsl::schedule_runs)
+                .values(&NewScheduleRun {
+                    schedule_id: &run.schedule_id,
+                    started_at: run.started_at as i64,
+                    duration_ms: run.duration_ms as i64,
+                    success: run.success,
+                    error: run.error.as_deref(),
+                })
+                .execute(conn)?;
+
+            let job = schedules_dsl::schedules.filter(schedules_dsl::id.eq(&run.schedule_id));
+            let failures = if run.success {
+                0
+            } else {
+                job.select(schedules_dsl::consecutive_failures)
+                    .first::<i32>(conn)
+                    .optional()?
+                    .unwrap_or(0)
+                    + 1
+            };
+            diesel::update(job)
+                .set((
+                    schedules_dsl::last_run_at.eq(run.started_at as i64),
+                    schedules_dsl::last_success.eq(run.success),
+                    schedules_dsl::last_error.eq(run.error.as_deref()),
+                    schedules_dsl::consecutive_failures.eq(failures),
+                ))
+                .execute(conn)?;
+
+            let kept: Vec<i32> = schedule_runs_dsl::schedule_runs
+                .filter(schedule_runs_dsl::schedule_id.eq(&run.schedule_id))
+                .order(schedule_runs_dsl::id.desc())
+                .limit(SCHEDULE_RUNS_KEPT)
+                .select(schedule_runs_dsl::id)
+                .load(conn)?;
+            diesel::delete(
+                schedule_runs_dsl::schedule_runs
+                    .filter(schedule_runs_dsl::schedule_id.eq(&run.schedule_id))
+                    .filter(schedule_runs_dsl::id.ne_all(kept)),
+            )
+            .execute(conn)?;
+            Ok(())
+        })
+        .map_err(|e| format!("Failed to record schedule run: {}", e))
+    }
+
+    /// Get the most recent runs of a job, newest first
+    pub fn get_schedule_runs(
+        &self,
+        schedule_id: &str,
+        limit: i64,
+    ) -> Result<Vec<ScheduleRun>, String> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        let rows = schedule_runs_dsl::schedule_runs
+            .filter(schedule_runs_dsl::schedule_id.eq(schedule_id))
+            .order(schedule_runs_dsl::id.desc())
+            .limit(limit)
+            .select(DBScheduleRun::as_select())
+            .load::<DBScheduleRun>(&mut conn)
+            .map_err(|e| format!("Failed to query schedule runs: {}", e))?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| ScheduleRun {
+                id: row.id as i64,
+                schedule_id: row.schedule_id,
+                started_at: row.started_at.max(0) as u64,
+                duration_ms: row.duration_ms.max(0) as u64,
+                success: row.success,
+                error: row.error,
+            })
+            .collect())
+    }
+
+    /// Append the configuration a server had before a change to its history, returning
+    /// the id of the snapshot
+    ///
+    /// Only the last [`CONFIG_HISTORY_KEPT`] snapshots of each server are kept.
+    pub fn record_config_snapshot(
+        &self,
+        server_id: &str,
+        created_at: u64,
+        configuration: &ServerConfiguration,
+    ) -> Result<i64, String> {
+        let configuration = serde_json::to_string(configuration)
+            .map_err(|e| format!("Failed to serialize configuration: {}", e))?;
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            diesel::insert_into(config_history_dsl::server_config_history)
+                .values(&NewConfigSnapshot {
+                    server_id,
+                    created_at: created_at as i64,
+                    configuration,
+                })
+                .execute(conn)?;
+
+            let kept: Vec<i32> = config_history_dsl::server_config_history
+                .filter(config_history_dsl::server_id.eq(server_id))
+                .order(config_history_dsl::id.desc())
+                .limit(CONFIG_HISTORY_KEPT)
+                .select(config_history_dsl::id)
+                .load(conn)?;
+            let snapshot_id = kept[0] as i64;
+            diesel::delete(
+                config_history_dsl::server_config_history
+                    .filter(config_history_dsl::server_id.eq(server_id))
+                    .filter(config_history_dsl::id.ne_all(kept)),
+            )
+            .execute(conn)?;
+            Ok(snapshot_id)
+        })
+        .map_err(|e| format!("Failed to record configuration snapshot: {}", e))
+    }
+
+    /// Get the most recent configuration snapshots of a server, newest first
+    pub fn get_config_history(
+        &self,
+        server_id: &str,
+        limit: i64,
+    ) -> Result<Vec<ConfigSnapshot>, String> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        let rows = config_history_dsl::server_config_history
+            .filter(config_history_dsl::server_id.eq(server_id))
+            .order(config_history_dsl::id.desc())
+            .limit(limit)
+            .select(DBConfigSnapshot::as_select())
+            .load::<DBConfigSnapshot>(&mut conn)
+            .map_err(|e| format!("Failed to query configuration history: {}", e))?;
+
+        rows.into_iter().map(config_snapshot_from_row).collect()
+    }
+
+    /// Get a configuration snapshot by its id
+    pub fn get_config_snapshot(&self, snapshot_id: i64) -> Result<ConfigSnapshot, String> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        let row = config_history_dsl::server_config_history
+            .filter(config_history_dsl::id.eq(snapshot_id as i32))
+            .select(DBConfigSnapshot::as_select())
+            .first::<DBConfigSnapshot>(&mut conn)
+            .optional()
+            .map_err(|e| format!("Failed to query configuration history: {}", e))?
+            .ok_or_else(|| format!("Configuration snapshot {} not found", snapshot_id))?;
+
+        config_snapshot_from_row(row)
+    }
+
+    /// Append a lifecycle event to the history of its server
+    ///
+    /// Only the last [`LIFECYCLE_EVENTS_PERSISTED`] events of each server are kept.
+    pub fn append_lifecycle_event(&self, event: &LifecycleEvent) -> Result<(), String> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            diesel::insert_into(lifecycle_dsl::server_lifecycle_events)
+                .values(&NewLifecycleEvent {
+                    server_id: &event.server_id,
+                    kind: event.kind.as_str(),
+                    at: event.at as i64,
+                    exit_code: event.exit_code,
+                    detail: event.detail.as_deref(),
+                })
+                .execute(conn)?;
+
+            let kept: Vec<i32> = lifecycle_dsl::server_lifecycle_events
+                .filter(lifecycle_dsl::server_id.eq(&event.server_id))
+                .order(lifecycle_dsl::id.desc())
+                .limit(LIFECYCLE_EVENTS_PERSISTED)
+                .select(lifecycle_dsl::id)
+                .load(conn)?;
+            diesel::delete(
+                lifecycle_dsl::server_lifecycle_events
+                    .filter(lifecycle_dsl::server_id.eq(&event.server_id))
+                    .filter(lifecycle_dsl::id.ne_all(kept)),
+            )
+            .execute(conn)?;
+            Ok(())
+        })
+        .map_err(|e| format!("Failed to record lifecycle event: {}", e))
+    }
+
+    /// Get the saved lifecycle events of every server, oldest first
+    pub fn get_lifecycle_events(&self) -> Result<Vec<LifecycleEvent>, String> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        let rows = lifecycle_dsl::server_lifecycle_events
+            .order(lifecycle_dsl::id.asc())
+            .select(DBLifecycleEvent::as_select())
+            .load::<DBLifecycleEvent>(&mut conn)
+            .map_err(|e| format!("Failed to query lifecycle events: {}", e))?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(lifecycle_event_from_row)
+            .collect())
+    }
+
+    /// Record a change in the undo journal, returning the entry id
+    ///
+    /// Expired entries are removed and only the last [`UNDO_JOURNAL_KEPT`] are kept.
+    pub fn record_undo_entry(&self, entry: &UndoEntry, before_image: &str) -> Result<i64, String> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            diesel::insert_into(undo_dsl::undo_journal)
+                .values(&NewUndoEntry {
+                    action: entry.action.as_str(),
+                    server_id: &entry.server_id,
+                    summary: &entry.summary,
+                    created_at: entry.created_at as i64,
+                    expires_at: entry.expires_at as i64,
+                    before_image,
+                })
+                .execute(conn)?;
+
+            let kept: Vec<i32> = undo_dsl::undo_journal
+                .filter(undo_dsl::expires_at.gt(entry.created_at as i64))
+                .order(undo_dsl::id.desc())
+                .limit(UNDO_JOURNAL_KEPT)
+                .select(undo_dsl::id)
+                .load(conn)?;
+            let entry_id = kept.first().copied().unwrap_or_default() as i64;
+            diesel::delete(undo_dsl::undo_journal.filter(undo_dsl::id.ne_all(kept)))
+                .execute(conn)?;
+            Ok(entry_id)
+        })
+        .map_err(|e| format!("Failed to record undo entry: {}", e))
+    }
+
+    /// Get the entries of the undo journal that haven't expired at `now`, newest first
+    pub fn get_undo_entries(&self, now: u64) -> Result<Vec<UndoEntry>, String> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        let rows = undo_dsl::undo_journal
+            .filter(undo_dsl::expires_at.gt(now as i64))
+            .order(undo_dsl::id.desc())
+            .select(DBUndoEntry::as_select())
+            .load::<DBUndoEntry>(&mut conn)
+            .map_err(|e| format!("Failed to query the undo journal: {}", e))?;
+
+        rows.into_iter()
+            .map(|row| undo_entry_from_row(row).map(|(entry, _)| entry))
+            .collect()
+    }
+
+    /// Get an entry of the undo journal with its before-image
+    pub fn get_undo_entry(&self, entry_id: i64) -> Result<(UndoEntry, String), String> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        let row = undo_dsl::undo_journal
+            .filter(undo_dsl::id.eq(entry_id as i32))
+            .select(DBUndoEntry::as_select())
+            .first::<DBUndoEntry>(&mut conn)
+            .optional()
+            .map_err(|e| format!("Failed to query the undo journal: {}", e))?
+            .ok_or_else(|| format!("Undo entry {} not found", entry_id))?;
+        undo_entry_from_row(row)
+    }
+
+    /// Remove an entry from the undo journal, once undone
+    pub fn delete_undo_entry(&self, entry_id: i64) -> Result<(), String> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        diesel::delete(undo_dsl::undo_journal.filter(undo_dsl::id.eq(entry_id as i32)))
+            .execute(&mut conn)
+            .map_err(|e| format!("Failed to delete undo entry {}: {}", entry_id, e))?;
+        Ok(())
+    }
+
+    /// Create a webhook or replace it
+    ///
+    /// Webhooks are always saved with a secret, see [`Webhook::secret`].
+    pub fn save_webhook(&self, webhook: &Webhook) -> Result<(), String> {
+        let Some(secret) = webhook.secret.as_deref() else {
+            return Err(format!("Webhook '{}' has no secret", webhook.id));
+        };
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        let row = NewWebhook {
+            id: &webhook.id,
+            url: &webhook.url,
+            events: serde_json::to_string(&webhook.events)
+                .map_err(|e| format!("Failed to serialize webhook events: {}", e))?,
+            enabled: webhook.enabled,
+            secret,
+        };
+        diesel::insert_into(webhooks_dsl::webhooks)
+            .values(&row)
+            .on_conflict(webhooks_dsl::id)
+            .do_update()
+            .set(&row)
+            .execute(&mut conn)
+            .map_err(|e| format!("Failed to save webhook: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Get every webhook with its secret, by id
+    pub fn get_webhooks(&self) -> Result<Vec<Webhook>, String> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        let rows = webhooks_dsl::webhooks
+            .order(webhooks_dsl::id.asc())
+            .select(DBWebhook::as_select())
+            .load::<DBWebhook>(&mut conn)
+            .map_err(|e| format!("Failed to query webhooks: {}", e))?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| Webhook {
+                events: serde_json::from_str(&row.events).unwrap_or_else(|e| {
+                    warn!("Sending every event to webhook {}: {}", row.id, e);
+                    Vec::new()
+                }),
+                id: row.id,
+                url: row.url,
+                enabled: row.enabled,
+                secret: Some(row.secret),
+            })
+            .collect())
+    }
+
+    /// Delete a webhook, its dead letters are kept
+    pub fn delete_webhook(&self, webhook_id: &str) -> Result<(), String> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        let deleted =
+            diesel::delete(webhooks_dsl::webhooks.filter(webhooks_dsl::id.eq(webhook_id)))
+                .execute(&mut conn)
+                .map_err(|e| format!("Failed to delete webhook: {}", e))?;
+        if deleted == 0 {
+            return Err(format!("Webhook '{}' not found", webhook_id));
+        }
+
+        Ok(())
+    }
+
+    /// Append a payload that could not be delivered to the dead-letter log
+    ///
+    /// Only the last [`WEBHOOK_DEAD_LETTERS_KEPT`] entries are kept.
+    pub fn record_webhook_dead_letter(&self, dead_letter: &DeadLetter) -> Result<(), String> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        let payload = serde_json::to_string(&dead_letter.payload)
+            .map_err(|e| format!("Failed to serialize webhook payload: {}", e))?;
+        conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            diesel::insert_into(dead_letters_dsl::webhook_dead_letters)
+                .values(&NewWebhookDeadLetter {
+                    webhook_id: &dead_letter.webhook_id,
+                    payload,
+                    error: &dead_letter.error,
+                    attempts: dead_letter.attempts as i32,
+                    failed_at: dead_letter.failed_at as i64,
+                })
+                .execute(conn)?;
+
+            let kept: Vec<i32> = dead_letters_dsl::webhook_dead_letters
+                .order(dead_letters_dsl::id.desc())
+                .limit(WEBHOOK_DEAD_LETTERS_KEPT)
+                .select(dead_letters_dsl::id)
+                .load(conn)?;
+            diesel::delete(
+                dead_letters_dsl::webhook_dead_letters.filter(dead_letters_dsl::id.ne_all(kept)),
+            )
+            .execute(conn)?;
+            Ok(())
+        })
+        .map_err(|e| format!("Failed to record webhook dead letter: {}", e))
+    }
+
+    /// Get the most recent dead letters of every webhook, newest first
+    pub fn get_webhook_dead_letters(&self, limit: i64) -> Result<Vec<DeadLetter>, String> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        let rows = dead_letters_dsl::webhook_dead_letters
+            .order(dead_letters_dsl::id.desc())
+            .limit(limit)
+            .select(DBWebhookDeadLetter::as_select())
+            .load::<DBWebhookDeadLetter>(&mut conn)
+            .map_err(|e| format!("Failed to query webhook dead letters: {}", e))?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| {
+                let payload = serde_json::from_str(&row.payload)
+                    .map_err(|e| warn!("Skipping unreadable dead letter {}: {}", row.id, e))
+                    .ok()?;
+                Some(DeadLetter {
+                    id: row.id as i64,
+                    webhook_id: row.webhook_id,
+                    payload,
+                    error: row.error,
+                    attempts: row.attempts.max(0) as u32,
+                    failed_at: row.failed_at.max(0) as u64,
+                })
+            })
+            .collect())
+    }
+
+    /// Create a registry source or replace it
+    pub fn save_registry_source(&self, source: &RegistrySource) -> Result<(), String> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        let row = NewRegistrySource {
+            id: &source.id,
+            url: &source.url,
+            kind: source.kind.as_str(),
+            headers: serde_json::to_string(&source.headers)
+                .map_err(|e| format!("Failed to serialize registry source headers: {}", e))?,
+            credentials: source
+                .credentials
+                .as_ref()
+                .map(serde_json::to_string)
+                .transpose()
+                .map_err(|e| format!("Failed to serialize registry source credentials: {}", e))?,
+        };
+        diesel::insert_into(sources_dsl::registry_sources)
+            .values(&row)
+            .on_conflict(sources_dsl::id)
+            .do_update()
+            .set(&row)
+            .execute(&mut conn)
+            .map_err(|e| format!("Failed to save registry source: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Get every registry source with its credentials, by id
+    pub fn get_registry_sources(&self) -> Result<Vec<RegistrySource>, String> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        let rows = sources_dsl::registry_sources
+            .order(sources_dsl::id.asc())
+            .select(DBRegistrySource::as_select())
+            .load::<DBRegistrySource>(&mut conn)
+            .map_err(|e| format!("Failed to query registry sources: {}", e))?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| {
+                let Some(kind) = RegistrySourceKind::from_stored(&row.kind) else {
+                    warn!(
+                        "Skipping registry source {} of unknown kind {}",
+                        row.id, row.kind
+                    );
+                    return None;
+                };
+                let credentials = match row.credentials.as_deref().map(serde_json::from_str) {
+                    Some(Ok(credentials)) => Some(credentials),
+                    Some(Err(e)) => {
+                        warn!(
+                            "Ignoring the credentials of registry source {}: {}",
+                            row.id, e
+                        );
+                        None
+                    }
+                    None => None,
+                };
+                Some(RegistrySource {
+                    headers: serde_json::from_str(&row.headers).unwrap_or_else(|e| {
+                        warn!("Ignoring the headers of registry source {}: {}", row.id, e);
+                        Default::default()
+                    }),
+                    id: row.id,
+                    url: row.url,
+                    kind,
+                    credentials,
+                })
+            })
+            .collect())
+    }
+
+    pub fn delete_registry_source(&self, source_id: &str) -> Result<(), String> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        let deleted =
+            diesel::delete(sources_dsl::registry_sources.filter(sources_dsl::id.eq(source_id)))
+                .execute(&mut conn)
+                .map_err(|e| format!("Failed to delete registry source: {}", e))?;
+        if deleted == 0 {
+            return Err(format!("Registry source '{}' not found", source_id));
+        }
+
+        Ok(())
+    }
+
+    /// Rows of the legacy `tools` table, None when the database has none
+    pub fn legacy_tool_rows(&self) -> Result<Option<Vec<LegacyToolRow>>, String> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        #[derive(QueryableByName)]
+        struct TableName {
+            #[diesel(sql_type = diesel::sql_types::Text)]
+            #[allow(dead_code)]
+            name: String,
+        }
+        let tables =
+            diesel::sql_query("SELECT name FROM sqlite_master WHERE type = 'table' AND name = ?")
+                .bind::<diesel::sql_types::Text, _>(LEGACY_TOOLS_TABLE)
+                .load::<TableName>(&mut conn)
+                .map_err(|e| format!("Failed to look for the legacy tables: {}", e))?;
+        if tables.is_empty() {
+            return Ok(None);
+        }
+
+        diesel::sql_query(format!(
+            "SELECT CAST(id AS TEXT) AS id, data FROM {} ORDER BY id",
+            LEGACY_TOOLS_TABLE
+        ))
+        .load::<LegacyToolRow>(&mut conn)
+        .map(Some)
+        .map_err(|e| format!("Failed to read the legacy tools table: {}", e))
+    }
+
+    /// Save the servers of the legacy `tools` table into the current tables
+    ///
+    /// The legacy table is copied to a JSON file next to the database first and is left
+    /// as is. Servers whose id is taken are skipped, so running it again only migrates
+    /// what failed before.
+    pub fn migrate_legacy_tools(&self) -> Result<LegacyMigrationReport, String> {
+        let Some(rows) = self.legacy_tool_rows()? else {
+            return Ok(LegacyMigrationReport::default());
+        };
+        let backup_path = self.backup_legacy_tools(&rows)?;
+        let mut report = LegacyMigrationReport {
+            found: true,
+            backup_path: Some(backup_path.to_string_lossy().to_string()),
+            ..Default::default()
+        };
+
+        let mut taken: HashSet<String> = self.get_all_servers()?.into_keys().collect();
+        for row in rows {
+            let server_id = normalize_server_id(&row.id);
+            let outcome = if taken.contains(&server_id) {
+                Err(format!(
+                    "A server with id '{}' is already installed",
+                    server_id
+                ))
+            } else {
+                parse_legacy_tool(&row.data).and_then(|server| {
+                    self.save_server(&server_id, &server)?;
+                    if server_id != row.id {
+                        self.save_server_id_alias(&row.id, &server_id)?;
+                    }
+                    Ok(())
+                })
+            };
+            match outcome {
+                Ok(()) => {
+                    if server_id != row.id {
+                        report.renamed.push((row.id, server_id.clone()));
+                    }
+                    taken.insert(server_id.clone());
+                    report.migrated.push(server_id);
+                }
+                Err(reason) => {
+                    warn!("Legacy server '{}' not migrated: {}", row.id, reason);
+                    report.skipped.push(LegacySkip { id: row.id, reason });
+                }
+            }
+        }
+
+        info!(
+            "Migrated {} legacy servers, skipped {}, backup at {:?}",
+            report.migrated.len(),
+            report.skipped.len(),
+            backup_path
+        );
+        Ok(report)
+    }
+
+    /// Write the rows of the legacy table to a JSON file next to the database
+    fn backup_legacy_tools(&self, rows: &[LegacyToolRow]) -> Result<PathBuf, String> {
+        let stem = self
+            .path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_else(|| "mcp_dockmaster".to_string());
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or(0);
+        let path = self
+            .path
+            .with_file_name(format!("{}.legacy-tools-{}.json", stem, timestamp));
+        let json = serde_json::to_string_pretty(rows)
+            .map_err(|e| format!("Failed to serialize the legacy tools: {}", e))?;
+        fs::write(&path, json)
+            .map_err(|e| format!("Failed to back up the legacy tools to {:?}: {}", path, e))?;
+        Ok(path)
+    }
+}
+
+/// Read a saved tool back, through the same conversion as discovered tools
+fn server_tool_from_row(db_tool: DBServerTool) -> Result<ServerToolInfo, String> {
+    let parse = |json: Option<&str>, field: &str| match json {
+        Some(json) => serde_json::from_str::<serde_json::Value>(json)
+            .map_err(|e| format!("Failed to parse {} of tool {}: {}", field, db_tool.id, e)),
+        None => Ok(serde_json::Value::Null),
+    };
+    let spec = serde_json::json!({
+        "name": db_tool.name,
+        "description": db_tool.description,
+        "inputSchema": parse(db_tool.input_schema.as_deref(), "input schema")?,
+        "outputSchema": parse(db_tool.output_schema.as_deref(), "output schema")?,
+        "annotations": parse(db_tool.annotations.as_deref(), "annotations")?,
+    });
+    let mut tool = ServerToolInfo::from_spec_value(&db_tool.server_id, spec)?;
+    tool.id = db_tool.id;
+    tool.proxy_id = db_tool.proxy_id;
+    tool.is_active = db_tool.is_active;
+    Ok(tool)
+}
+
+/// Convert a row of the `undo_journal` table, with its before-image
+fn undo_entry_from_row(row: DBUndoEntry) -> Result<(UndoEntry, String), String> {
+    let action = UndoAction::from_stored(&row.action)
+        .ok_or_else(|| format!("Unknown action '{}' of undo entry {}", row.action, row.id))?;
+    let entry = UndoEntry {
+        id: row.id as i64,
+        action,
+        server_id: row.server_id,
+        summary: row.summary,
+        created_at: row.created_at.max(0) as u64,
+        expires_at: row.expires_at.max(0) as u64,
+    };
+    Ok((entry, row.before_image))
+}
+
+/// Convert a row of the `server_config_history` table
+fn config_snapshot_from_row(row: DBConfigSnapshot) -> Result<ConfigSnapshot, String> {
+    let configuration = serde_json::from_str(&row.configuration)
+        .map_err(|e| format!("Failed to parse configuration snapshot {}: {}", row.id, e))?;
+    Ok(ConfigSnapshot {
+        id: row.id as i64,
+        server_id: row.server_id,
+        created_at: row.created_at.max(0) as u64,
+        configuration,
+    })
+}
+
+/// Convert a row of the `server_lifecycle_events` table, skipping unknown kinds
+fn lifecycle_event_from_row(row: DBLifecycleEvent) -> Option<LifecycleEvent> {
+    let Some(kind) = LifecycleKind::parse(&row.kind) else {
+        warn!(
+            "Skipping lifecycle event {} of unknown kind '{}'",
+            row.id, row.kind
+        );
+        return None;
+    };
+    Some(LifecycleEvent {
+        server_id: row.server_id,
+        kind,
+        at: row.at.max(0) as u64,
+        exit_code: row.exit_code,
+        detail: row.detail,
+    })
 }
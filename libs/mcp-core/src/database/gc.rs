@@ -0,0 +1,91 @@
+//! Rows the database no longer needs.
+//!
+//! Builds that predate the foreign keys, and uninstalls that failed halfway, left the
+//! environment variables, tools and state of removed servers behind.
+//! [`DBManager::collect_garbage`](super::db_manager::DBManager::collect_garbage) finds
+//! them, along with servers that can never start and environment keys saved twice, and
+//! removes them unless it is a dry run. A dry run is logged at every startup.
+
+use serde::Serialize;
+
+/// Tables holding one row of state per server
+pub const SERVER_STATE_TABLES: [&str; 3] = [
+    "server_provenance",
+    "server_run_states",
+    "server_call_modes",
+];
+
+/// What a garbage collection found, and removed unless `dry_run`
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+#[non_exhaustive]
+pub struct GarbageReport {
+    pub dry_run: bool,
+    /// Environment variables of servers that don't exist, as (server id, key)
+    pub orphaned_env: Vec<(String, String)>,
+    /// Tools of servers that don't exist, as (server id, tool id)
+    pub orphaned_tools: Vec<(String, String)>,
+    /// State of servers that don't exist, as (table, server id)
+    pub orphaned_state: Vec<(String, String)>,
+    /// Environment variables whose key is another key of the server with blanks
+    /// around it, as (server id, key)
+    pub duplicate_env: Vec<(String, String)>,
+    /// Servers that can't be started, removed with their rows
+    pub invalid_servers: Vec<InvalidServer>,
+}
+
+impl GarbageReport {
+    /// Number of rows found
+    pub fn total(&self) -> usize {
+        self.orphaned_env.len()
+            + self.orphaned_tools.len()
+            + self.orphaned_state.len()
+            + self.duplicate_env.len()
+            + self.invalid_servers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total() == 0
+    }
+}
+
+/// A server that can't be started
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[non_exhaustive]
+pub struct InvalidServer {
+    pub server_id: String,
+    pub reason: String,
+}
+
+/// Why a stored server can never start, if it can't
+pub fn invalid_server_reason(
+    tools_type: &str,
+    command: Option<&str>,
+    entry_point: Option<&str>,
+) -> Option<&'static str> {
+    if tools_type.trim().is_empty() {
+        return Some("The server has no type");
+    }
+    let blank = |value: Option<&str>| value.map_or(true, |v| v.trim().is_empty());
+    if blank(command) && blank(entry_point) {
+        return Some("The server has neither a command nor an entry point");
+    }
+    None
+}
+
+/// Keys of a server's environment that duplicate another once trimmed, in order
+///
+/// The key saved without blanks is kept, or else the first one.
+pub fn duplicate_env_keys(keys: &[String]) -> Vec<String> {
+    let mut duplicates = Vec::new();
+    for (i, key) in keys.iter().enumerate() {
+        let trimmed = key.trim();
+        let kept = keys
+            .iter()
+            .position(|k| k == trimmed)
+            .or_else(|| keys.iter().position(|k| k.trim() == trimmed));
+        if kept != Some(i) {
+            duplicates.push(key.clone());
+        }
+    }
+    duplicates
+}
@@ -0,0 +1,152 @@
+//! Servers saved by builds that predate the Diesel schema.
+//!
+//! The former `DatabaseManager` kept each server as a JSON document in a `tools` table
+//! (`id TEXT PRIMARY KEY, data TEXT`) of the same database file. The Diesel migrations
+//! never read that table, so these servers disappeared after an update.
+//! [`DBManager::migrate_legacy_tools`](super::db_manager::DBManager::migrate_legacy_tools)
+//! converts them into the current tables, keeping a JSON backup of the table.
+
+use std::collections::HashMap;
+
+use diesel::sql_types::Text;
+use diesel::QueryableByName;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::models::env_key::canonicalize_env;
+use crate::models::types::{
+    Distribution, ServerConfiguration, ServerDefinition, ServerEnvironment,
+};
+
+/// Table the former `DatabaseManager` stored servers in
+pub const LEGACY_TOOLS_TABLE: &str = "tools";
+
+/// Setting recording that the legacy servers were migrated at startup
+pub const LEGACY_MIGRATION_SETTING: &str = "legacy_tools_migrated";
+
+/// Outcome of a migration of legacy servers
+#[derive(Clone, Debug, Default, Serialize)]
+#[non_exhaustive]
+pub struct LegacyMigrationReport {
+    /// Whether a legacy table was found
+    pub found: bool,
+    /// Ids of the servers migrated, as saved
+    pub migrated: Vec<String>,
+    pub skipped: Vec<LegacySkip>,
+    /// Legacy ids that weren't valid server ids, with the id they were saved under
+    pub renamed: Vec<(String, String)>,
+    /// File holding a copy of the legacy table, taken before the migration
+    pub backup_path: Option<String>,
+}
+
+/// Row of the legacy table, copied as is to the backup
+#[derive(Clone, Debug, QueryableByName, Serialize)]
+pub struct LegacyToolRow {
+    #[diesel(sql_type = Text)]
+    pub id: String,
+    #[diesel(sql_type = Text)]
+    pub data: String,
+}
+
+/// A legacy server that wasn't migrated
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[non_exhaustive]
+pub struct LegacySkip {
+    pub id: String,
+    pub reason: String,
+}
+
+/// Convert the JSON document of a legacy server
+///
+/// Accepts the field names used over time: `tools_type`, `tool_type` or `type` for the
+/// runtime, and the environment under `configuration.env`, `config.env` or `env`,
+/// with plain values or objects holding a `value` or a `default`.
+pub fn parse_legacy_tool(data: &str) -> Result<ServerDefinition, String> {
+    let value: Value =
+        serde_json::from_str(data).map_err(|e| format!("Invalid JSON document: {}", e))?;
+    let tool = value
+        .as_object()
+        .ok_or_else(|| "The document is not an object".to_string())?;
+
+    let name = string_field(tool, "name").ok_or_else(|| "Missing name".to_string())?;
+    let tools_type = ["tools_type", "tool_type", "type"]
+        .iter()
+        .find_map(|key| string_field(tool, key))
+        .ok_or_else(|| "Missing tool type".to_string())?;
+
+    let configuration = tool.get("configuration").and_then(Value::as_object);
+    let command = configuration.and_then(|c| string_field(c, "command"));
+    let args = configuration
+        .and_then(|c| c.get("args"))
+        .map(|args| {
+            serde_json::from_value::<Vec<String>>(args.clone())
+                .map_err(|e| format!("Invalid args: {}", e))
+        })
+        .transpose()?;
+
+    let env = [
+        configuration.and_then(|c| c.get("env")),
+        tool.get("config").and_then(|c| c.get("env")),
+        tool.get("env"),
+    ]
+    .into_iter()
+    .flatten()
+    .find(|env| env.is_object())
+    .map(parse_legacy_env)
+    .transpose()?;
+
+    let distribution = tool
+        .get("distribution")
+        .filter(|d| !d.is_null())
+        .map(|d| {
+            serde_json::from_value::<Distribution>(d.clone())
+                .map_err(|e| format!("Invalid distribution: {}", e))
+        })
+        .transpose()?;
+
+    let configuration =
+        (command.is_some() || args.is_some() || env.is_some()).then(|| ServerConfiguration {
+            command,
+            args,
+            env,
+            ..Default::default()
+        });
+
+    Ok(ServerDefinition {
+        name,
+        description: string_field(tool, "description").unwrap_or_default(),
+        enabled: tool.get("enabled").and_then(Value::as_bool).unwrap_or(true),
+        tools_type,
+        entry_point: string_field(tool, "entry_point"),
+        configuration,
+        distribution,
+    })
+}
+
+fn parse_legacy_env(env: &Value) -> Result<HashMap<String, ServerEnvironment>, String> {
+    let mut parsed = HashMap::new();
+    for (key, value) in env.as_object().into_iter().flatten() {
+        // Older builds stored the value itself under `value`
+        let value = match value.as_object() {
+            Some(entry) if entry.contains_key("value") => {
+                let mut entry = entry.clone();
+                let current = entry.remove("value");
+                entry.insert("default".to_string(), current.unwrap_or(Value::Null));
+                Value::Object(entry)
+            }
+            _ => value.clone(),
+        };
+        let environment = ServerEnvironment::from_value(&value)
+            .map_err(|e| format!("Invalid env variable {}: {}", key, e))?;
+        parsed.insert(key.clone(), environment);
+    }
+    canonicalize_env(parsed)
+}
+
+fn string_field(object: &Map<String, Value>, key: &str) -> Option<String> {
+    object
+        .get(key)
+        .and_then(Value::as_str)
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+}
@@ -1 +1,4 @@
 pub mod db_manager;
+pub mod gc;
+pub mod legacy;
+pub mod state;
@@ -0,0 +1,121 @@
+//! What the database holds, for the app to decide whether to show onboarding.
+//!
+//! [`DBManager::state`](super::db_manager::DBManager::state) never fails: a database
+//! that can't be read is a [`DatabaseStatus::Unreadable`] state, with a
+//! [`DatabaseStateError`] saying which step failed.
+
+use std::fmt;
+
+use serde::Serialize;
+
+/// Where the database is at, from the most to the least pressing
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DatabaseStatus {
+    /// The database can't be opened or queried
+    Unreadable,
+    /// Schema migrations or servers of the legacy storage wait to be applied
+    NeedsMigration,
+    /// The database file was created by this run and has no servers
+    Fresh,
+    /// The database was used before but has no servers
+    Empty,
+    /// Servers are installed
+    Ready,
+}
+
+impl fmt::Display for DatabaseStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            DatabaseStatus::Unreadable => "unreadable",
+            DatabaseStatus::NeedsMigration => "needs migration",
+            DatabaseStatus::Fresh => "fresh install",
+            DatabaseStatus::Empty => "no servers installed",
+            DatabaseStatus::Ready => "ready",
+        })
+    }
+}
+
+/// Step that failed while reading the state of the database
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", content = "message", rename_all = "snake_case")]
+pub enum DatabaseStateError {
+    /// No connection could be opened
+    Connection(String),
+    /// The migrations applied or pending couldn't be listed
+    Migrations(String),
+    /// A table couldn't be read
+    Query(String),
+}
+
+impl fmt::Display for DatabaseStateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DatabaseStateError::Connection(e) => write!(f, "Failed to open the database: {}", e),
+            DatabaseStateError::Migrations(e) => {
+                write!(f, "Failed to read the database migrations: {}", e)
+            }
+            DatabaseStateError::Query(e) => write!(f, "Failed to read the database: {}", e),
+        }
+    }
+}
+
+/// State of the database with the counts it was decided from
+#[derive(Clone, Debug, Serialize)]
+pub struct DatabaseState {
+    pub status: DatabaseStatus,
+    /// Most recent applied migration
+    pub schema_version: Option<String>,
+    /// Schema migrations of this build not applied yet
+    pub pending_migrations: usize,
+    pub server_count: i64,
+    pub tool_count: i64,
+    /// Servers of the legacy storage not migrated yet
+    pub legacy_server_count: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<DatabaseStateError>,
+}
+
+impl DatabaseState {
+    /// State of a database that couldn't be read
+    pub fn unreadable(error: DatabaseStateError) -> Self {
+        Self {
+            status: DatabaseStatus::Unreadable,
+            schema_version: None,
+            pending_migrations: 0,
+            server_count: 0,
+            tool_count: 0,
+            legacy_server_count: 0,
+            error: Some(error),
+        }
+    }
+
+    /// Decide the status from the counts, `created` when this run created the file
+    pub fn from_counts(
+        created: bool,
+        schema_version: Option<String>,
+        pending_migrations: usize,
+        server_count: i64,
+        tool_count: i64,
+        legacy_server_count: usize,
+    ) -> Self {
+        let status = if pending_migrations > 0 || legacy_server_count > 0 {
+            DatabaseStatus::NeedsMigration
+        } else if server_count > 0 {
+            DatabaseStatus::Ready
+        } else if created {
+            DatabaseStatus::Fresh
+        } else {
+            DatabaseStatus::Empty
+        };
+        Self {
+            status,
+            schema_version,
+            pending_migrations,
+            server_count,
+            tool_count,
+            legacy_server_count,
+            error: None,
+        }
+    }
+}
@@ -0,0 +1,185 @@
+//! Checklist run on a server that doesn't work, with a hint for each failed step.
+//!
+//! Most support requests come down to the same checks, run in order by
+//! `server/diagnose`: the command is found on the PATH, the runtime is recent enough,
+//! the required environment variables are set, the process runs, it answered the
+//! initialize handshake, it lists its tools, it answers a call and its stderr has no
+//! fatal error. Steps that can't run because an earlier one failed are skipped. The
+//! [`ServerDiagnosis`] can be exported along with the redacted configuration of the
+//! server.
+
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::types::ServerConfiguration;
+
+/// Time each request sent to the server is given to answer
+pub const DIAGNOSE_STEP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Oldest Node.js major version servers are known to run on
+pub const MIN_NODE_MAJOR: u32 = 18;
+
+/// Tool called to check that the server answers, no server has a tool of this name
+pub const DIAGNOSE_PROBE_TOOL: &str = "__dockmaster_diagnose_probe__";
+
+/// Lines of stderr, lowercased, that mean the server hit an error it can't recover from
+pub const FATAL_STDERR_PATTERNS: [&str; 11] = [
+    "panicked at",
+    "fatal error",
+    "fatal:",
+    "traceback (most recent call last)",
+    "segmentation fault",
+    "out of memory",
+    "cannot find module",
+    "modulenotfounderror",
+    "eaddrinuse",
+    "permission denied",
+    "command not found",
+];
+
+/// Fatal lines reported in the details of the stderr step
+const FATAL_LINES_SHOWN: usize = 5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiagnosisStatus {
+    Pass,
+    Fail,
+    /// Not run, an earlier step failed or it doesn't apply to the server
+    Skipped,
+}
+
+/// Result of one check of the checklist
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DiagnosisStep {
+    /// Stable name of the check, like `command` or `tools_list`
+    pub id: String,
+    pub title: String,
+    pub status: DiagnosisStatus,
+    pub details: String,
+    /// What the user can do about a failed step
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+}
+
+impl DiagnosisStep {
+    pub fn pass(id: &str, title: &str, details: String) -> Self {
+        Self::new(id, title, DiagnosisStatus::Pass, details, None)
+    }
+
+    pub fn fail(id: &str, title: &str, details: String, hint: String) -> Self {
+        Self::new(id, title, DiagnosisStatus::Fail, details, Some(hint))
+    }
+
+    pub fn skipped(id: &str, title: &str, details: String) -> Self {
+        Self::new(id, title, DiagnosisStatus::Skipped, details, None)
+    }
+
+    fn new(
+        id: &str,
+        title: &str,
+        status: DiagnosisStatus,
+        details: String,
+        hint: Option<String>,
+    ) -> Self {
+        Self {
+            id: id.to_string(),
+            title: title.to_string(),
+            status,
+            details,
+            hint,
+        }
+    }
+}
+
+/// Report of the checklist run on a server
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ServerDiagnosis {
+    pub server_id: String,
+    pub server_name: String,
+    /// When the checklist ran, in milliseconds since the epoch
+    pub created_at: u64,
+    /// Whether no step failed
+    pub healthy: bool,
+    pub steps: Vec<DiagnosisStep>,
+}
+
+/// Where `program` is found, looking in the directories of `path` unless it is a path
+pub fn find_program(program: &str, path: Option<&OsStr>) -> Option<PathBuf> {
+    let candidate = Path::new(program);
+    if candidate.components().count() > 1 {
+        return candidate.is_file().then(|| candidate.to_path_buf());
+    }
+    let mut extensions = vec![String::new()];
+    if cfg!(windows) {
+        let pathext = std::env::var("PATHEXT").unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".into());
+        extensions.extend(pathext.split(';').map(|ext| ext.to_lowercase()));
+    }
+    std::env::split_paths(path?).find_map(|dir| {
+        extensions
+            .iter()
+            .map(|ext| dir.join(format!("{}{}", program, ext)))
+            .find(|file| file.is_file())
+    })
+}
+
+/// Major version of a `node -v` output like `v20.11.1`
+pub fn node_major(version: &str) -> Option<u32> {
+    version
+        .trim()
+        .trim_start_matches('v')
+        .split('.')
+        .next()?
+        .parse()
+        .ok()
+}
+
+/// Lines of `stderr` matching one of the [`FATAL_STDERR_PATTERNS`]
+pub fn fatal_stderr_lines(stderr: &str) -> Vec<&str> {
+    stderr
+        .lines()
+        .filter(|line| {
+            let line = line.to_lowercase();
+            FATAL_STDERR_PATTERNS
+                .iter()
+                .any(|pattern| line.contains(pattern))
+        })
+        .map(str::trim)
+        .collect()
+}
+
+/// Required environment variables of a configuration that have no value, sorted
+pub fn missing_required_env(configuration: Option<&ServerConfiguration>) -> Vec<String> {
+    let mut missing: Vec<String> = configuration
+        .and_then(|configuration| configuration.env.as_ref())
+        .map(|env| {
+            env.iter()
+                .filter(|(_, env)| env.required)
+                .filter(|(_, env)| env.env_string().is_none_or(|value| value.trim().is_empty()))
+                .map(|(key, _)| key.clone())
+                .collect()
+        })
+        .unwrap_or_default();
+    missing.sort();
+    missing
+}
+
+/// Details of the stderr step for the fatal lines found
+pub fn describe_fatal_lines(lines: &[&str]) -> String {
+    let mut details = lines
+        .iter()
+        .take(FATAL_LINES_SHOWN)
+        .copied()
+        .collect::<Vec<_>>()
+        .join("\n");
+    if lines.len() > FATAL_LINES_SHOWN {
+        details.push_str(&format!(
+            "\n... and {} more",
+            lines.len() - FATAL_LINES_SHOWN
+        ));
+    }
+    details
+}
@@ -0,0 +1,278 @@
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::models::types::{EnvValue, ServerDefinition};
+use crate::utils::zip::write_zip;
+
+/// Replacement for every value removed by the redaction pass
+pub const REDACTED: &str = "<redacted>";
+
+/// Only the end of each log file is included
+pub const LOG_TAIL_BYTES: u64 = 256 * 1024;
+
+/// Secrets shorter than this are not scrubbed from free text, they would match all over
+/// the logs (`1`, `true`, ...) and are redacted from the configurations anyway
+const MIN_SCRUBBED_SECRET_LEN: usize = 4;
+
+/// Argument names whose value is treated as a secret
+const SECRET_ARG_MARKERS: [&str; 6] = ["token", "secret", "password", "passwd", "key", "auth"];
+
+/// Where `mcp-proxy-server` writes its logs, which include the traffic with MCP clients
+pub fn proxy_server_log_dir() -> PathBuf {
+    std::env::temp_dir().join("mcp-server-logs")
+}
+
+/// A file of a diagnostics bundle
+#[derive(Clone, Debug, Serialize)]
+pub struct DiagnosticsFile {
+    /// Path inside the archive
+    pub path: String,
+    pub size: u64,
+}
+
+/// What an export would contain, shown to the user before anything is written
+#[derive(Clone, Debug, Serialize)]
+pub struct DiagnosticsPreview {
+    pub files: Vec<DiagnosticsFile>,
+    /// Number of secret values removed from the bundle
+    pub redacted_values: usize,
+}
+
+/// A diagnostics bundle written to disk
+#[derive(Clone, Debug, Serialize)]
+pub struct DiagnosticsExport {
+    pub path: PathBuf,
+    pub files: Vec<DiagnosticsFile>,
+    pub redacted_values: usize,
+}
+
+/// Files collected for a bug report, along with the secrets they may contain
+///
+/// A bundle can only be previewed or written once [`DiagnosticsBundle::redact`] has
+/// removed the secrets from every file.
+#[derive(Default)]
+pub struct DiagnosticsBundle {
+    files: Vec<(String, Vec<u8>)>,
+    secrets: Vec<String>,
+}
+
+impl DiagnosticsBundle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_json<T: Serialize>(&mut self, path: &str, value: &T) {
+        let content = serde_json::to_vec_pretty(value)
+            .unwrap_or_else(|e| format!("failed to serialize: {}", e).into_bytes());
+        self.files.push((path.to_string(), content));
+    }
+
+    pub fn add_text(&mut self, path: &str, content: String) {
+        self.files.push((path.to_string(), content.into_bytes()));
+    }
+
+    /// Remember a value that must not appear anywhere in the bundle
+    pub fn add_secret(&mut self, secret: String) {
+        if !secret.is_empty() && !self.secrets.contains(&secret) {
+            self.secrets.push(secret);
+        }
+    }
+
+    /// Add a redacted copy of a server configuration, keeping its secrets for the
+    /// redaction pass over the other files
+    pub fn add_server_definitions<'a>(
+        &mut self,
+        path: &str,
+        servers: impl IntoIterator<Item = (&'a String, &'a ServerDefinition)>,
+    ) {
+        let mut redacted = serde_json::Map::new();
+        for (server_id, server) in servers {
+            let (server, secrets) = redact_server_definition(server);
+            for secret in secrets {
+                self.add_secret(secret);
+            }
+            redacted.insert(
+                server_id.clone(),
+                serde_json::to_value(server).unwrap_or_default(),
+            );
+        }
+        self.add_json(path, &redacted);
+    }
+
+    /// Add the tail of every `.log` file found directly in `dir`
+    pub fn add_log_tails(&mut self, prefix: &str, dir: &Path) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.is_file() && is_log_file(path))
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            let content = match read_tail(&path, LOG_TAIL_BYTES) {
+                Ok(content) => content,
+                Err(e) => format!("failed to read {}: {}", path.display(), e),
+            };
+            self.add_text(&format!("{}/{}", prefix, name), content);
+        }
+    }
+
+    /// Scrub every known secret from every file
+    pub fn redact(self) -> RedactedDiagnostics {
+        let mut secrets: Vec<&String> = self
+            .secrets
+            .iter()
+            .filter(|secret| secret.len() >= MIN_SCRUBBED_SECRET_LEN)
+            .collect();
+        // Longest first so that a secret containing another one is removed whole
+        secrets.sort_by_key(|secret| std::cmp::Reverse(secret.len()));
+
+        let files = self
+            .files
+            .into_iter()
+            .map(|(path, content)| {
+                let mut text = String::from_utf8_lossy(&content).into_owned();
+                for secret in &secrets {
+                    if text.contains(secret.as_str()) {
+                        text = text.replace(secret.as_str(), REDACTED);
+                    }
+                }
+                (path, text.into_bytes())
+            })
+            .collect();
+
+        RedactedDiagnostics {
+            files,
+            redacted_values: self.secrets.len(),
+        }
+    }
+}
+
+/// A bundle without secrets, ready to be previewed and written
+pub struct RedactedDiagnostics {
+    files: Vec<(String, Vec<u8>)>,
+    redacted_values: usize,
+}
+
+impl RedactedDiagnostics {
+    pub fn preview(&self) -> DiagnosticsPreview {
+        DiagnosticsPreview {
+            files: self
+                .files
+                .iter()
+                .map(|(path, content)| DiagnosticsFile {
+                    path: path.clone(),
+                    size: content.len() as u64,
+                })
+                .collect(),
+            redacted_values: self.redacted_values,
+        }
+    }
+
+    /// Content of a file of the bundle
+    pub fn file(&self, path: &str) -> Option<&[u8]> {
+        self.files
+            .iter()
+            .find(|(file_path, _)| file_path == path)
+            .map(|(_, content)| content.as_slice())
+    }
+
+    /// Write the bundle as a zip archive at `path`
+    pub fn write(&self, path: &Path) -> Result<DiagnosticsExport, String> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        write_zip(path, &self.files)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+
+        let preview = self.preview();
+        Ok(DiagnosticsExport {
+            path: path.to_path_buf(),
+            files: preview.files,
+            redacted_values: preview.redacted_values,
+        })
+    }
+}
+
+/// Copy of a server definition without secrets, and the secrets that were removed
+///
+/// Every environment value is redacted since any of them can be a credential, as well
+/// as the values of arguments named like a secret (`--api-key <value>`,
+/// `--token=<value>`, `API_KEY=<value>`).
+pub fn redact_server_definition(server: &ServerDefinition) -> (ServerDefinition, Vec<String>) {
+    let mut server = server.clone();
+    let mut secrets = Vec::new();
+
+    if let Some(configuration) = server.configuration.as_mut() {
+        if let Some(env) = configuration.env.as_mut() {
+            for value in env.values_mut() {
+                if let Some(default) = value.default.take() {
+                    secrets.push(default.to_string());
+                    value.default = Some(EnvValue::String(REDACTED.to_string()));
+                }
+            }
+        }
+
+        if let Some(args) = configuration.args.as_mut() {
+            let mut redact_next = false;
+            for arg in args.iter_mut() {
+                if redact_next {
+                    redact_next = false;
+                    if !arg.starts_with('-') {
+                        secrets.push(std::mem::replace(arg, REDACTED.to_string()));
+                        continue;
+                    }
+                }
+                match arg.split_once('=') {
+                    Some((name, value)) if is_secret_name(name) && !value.is_empty() => {
+                        secrets.push(value.to_string());
+                        *arg = format!("{}={}", name, REDACTED);
+                    }
+                    Some(_) => {}
+                    None => redact_next = arg.starts_with('-') && is_secret_name(arg),
+                }
+            }
+        }
+    }
+
+    (server, secrets)
+}
+
+/// Whether an argument or key name looks like it holds a secret
+pub fn is_secret_name(name: &str) -> bool {
+    let name = name.to_lowercase();
+    SECRET_ARG_MARKERS
+        .iter()
+        .any(|marker| name.contains(marker))
+}
+
+fn is_log_file(path: &Path) -> bool {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    // Rotated files are named `<name>.log.<date>`
+    name.ends_with(".log") || name.contains(".log.")
+}
+
+/// Last `max_bytes` of a file, starting at a line boundary when truncated
+fn read_tail(path: &Path, max_bytes: u64) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    let start = len.saturating_sub(max_bytes);
+    file.seek(SeekFrom::Start(start))?;
+
+    let mut content = Vec::new();
+    file.read_to_end(&mut content)?;
+    let mut text = String::from_utf8_lossy(&content).into_owned();
+    if start > 0 {
+        if let Some(newline) = text.find('\n') {
+            text.drain(..=newline);
+        }
+    }
+    Ok(text)
+}
@@ -0,0 +1,307 @@
+//! In-process event bus.
+//!
+//! Core components publish [`DockmasterEvent`]s on the [`EventBus`] owned by `MCPCore`.
+//! The desktop app, the CLI and the HTTP server subscribe to it and forward the events
+//! to their users (window events, terminal output and SSE streams respectively).
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+use crate::approvals::{ApprovalRequest, ApprovalResolution};
+use crate::models::types::OrphanCleanupReport;
+use crate::operations::{Operation, OperationHandle};
+use crate::relocation::MigrationReport;
+
+/// Number of events a slow subscriber can lag behind before missing some
+const CHANNEL_CAPACITY: usize = 256;
+/// Number of recent events kept for subscribers that join late
+const HISTORY_CAPACITY: usize = 256;
+
+/// Events published by the core
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DockmasterEvent {
+    /// Progress of a server installation
+    InstallProgress(InstallProgress),
+    /// State change of a tracked operation
+    OperationUpdate(Operation),
+    /// Processes left behind by a previous run were cleaned up at startup
+    OrphansCleaned(OrphanCleanupReport),
+    /// A model requested an action the user has to approve
+    ApprovalRequested(ApprovalRequest),
+    /// The user approved or rejected a requested action
+    ApprovalResolved(ApprovalResolution),
+    /// Servers were enabled, disabled or restarted together, or exited, their tools changed
+    ToolsUpdated(ToolsUpdate),
+    /// A server whose connection died was restarted
+    ServerReconnected(ServerReconnect),
+    /// An enabled server started, or failed to, while Dockmaster starts
+    StartupProgress(StartupProgress),
+    /// A new version of the app can be installed
+    UpdateAvailable(AppUpdate),
+    /// The tool list takes more tokens than the budget the user set
+    ToolsBudgetExceeded(ToolsBudgetWarning),
+    /// The data directory was last used on another machine and paths of servers are missing
+    EnvironmentChanged(MigrationReport),
+}
+
+impl DockmasterEvent {
+    /// Operation the event belongs to, if any
+    pub fn operation_id(&self) -> Option<&str> {
+        match self {
+            DockmasterEvent::InstallProgress(progress) => Some(&progress.operation_id),
+            DockmasterEvent::OperationUpdate(operation) => Some(&operation.id),
+            DockmasterEvent::OrphansCleaned(_) => None,
+            DockmasterEvent::ApprovalRequested(request) => Some(&request.id),
+            DockmasterEvent::ApprovalResolved(resolution) => Some(&resolution.id),
+            DockmasterEvent::ToolsUpdated(_) => None,
+            DockmasterEvent::ServerReconnected(_) => None,
+            DockmasterEvent::StartupProgress(_) => None,
+            DockmasterEvent::UpdateAvailable(_) => None,
+            DockmasterEvent::ToolsBudgetExceeded(_) => None,
+            DockmasterEvent::EnvironmentChanged(_) => None,
+        }
+    }
+
+    /// Whether this is the last event of its operation
+    pub fn is_terminal(&self) -> bool {
+        match self {
+            DockmasterEvent::InstallProgress(progress) => progress.stage.is_terminal(),
+            DockmasterEvent::OperationUpdate(operation) => operation.state.is_finished(),
+            DockmasterEvent::OrphansCleaned(_)
+            | DockmasterEvent::ApprovalRequested(_)
+            | DockmasterEvent::ApprovalResolved(_)
+            | DockmasterEvent::ToolsUpdated(_)
+            | DockmasterEvent::ServerReconnected(_)
+            | DockmasterEvent::StartupProgress(_)
+            | DockmasterEvent::UpdateAvailable(_)
+            | DockmasterEvent::ToolsBudgetExceeded(_)
+            | DockmasterEvent::EnvironmentChanged(_) => false,
+        }
+    }
+}
+
+/// Stages of a server installation, in order
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InstallStage {
+    /// Looking up the server definition and checking prerequisites
+    Resolving,
+    /// Fetching the distribution package
+    Pulling,
+    /// Spawning the server process and running the initialize handshake
+    Starting,
+    /// Listing the tools exposed by the server
+    Discovering,
+    Done,
+    Failed,
+}
+
+impl InstallStage {
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, InstallStage::Done | InstallStage::Failed)
+    }
+
+    /// Rough completion percentage reached at the start of the stage
+    pub fn percent(&self) -> Option<u8> {
+        match self {
+            InstallStage::Resolving => Some(10),
+            InstallStage::Pulling => Some(30),
+            InstallStage::Starting => Some(50),
+            InstallStage::Discovering => Some(80),
+            InstallStage::Done => Some(100),
+            InstallStage::Failed => None,
+        }
+    }
+}
+
+/// A single install progress update
+#[derive(Clone, Debug, Serialize)]
+pub struct InstallProgress {
+    pub operation_id: String,
+    pub server_id: String,
+    pub stage: InstallStage,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// Servers whose tools changed in a bulk operation
+#[derive(Clone, Debug, Serialize)]
+pub struct ToolsUpdate {
+    pub server_ids: Vec<String>,
+}
+
+/// Restart of a server whose connection died, see [`crate::mcp_state::reconnect`]
+#[derive(Clone, Debug, Serialize)]
+pub struct ServerReconnect {
+    pub server_id: String,
+    /// Error that showed the connection was dead
+    pub cause: String,
+    /// Whether the failed request was sent again to the new process
+    pub retried: bool,
+}
+
+/// A server done starting at launch, the servers start concurrently and are reported
+/// in the order they finish
+#[derive(Clone, Debug, Serialize)]
+pub struct StartupProgress {
+    pub server_id: String,
+    /// Whether it runs
+    pub success: bool,
+    /// Why it didn't start
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Tools it listed
+    pub tool_count: usize,
+    /// Servers done starting so far, this one included
+    pub done: usize,
+    /// Servers started at launch
+    pub total: usize,
+}
+
+/// New version of the app found by the updater
+#[derive(Clone, Debug, Serialize)]
+pub struct AppUpdate {
+    pub version: String,
+}
+
+/// Tool list over its budget, see [`crate::footprint`]
+#[derive(Clone, Debug, Serialize)]
+pub struct ToolsBudgetWarning {
+    pub budget: usize,
+    /// Estimated tokens of the tool list
+    pub tokens: usize,
+    /// Servers to hide to get back under the budget, largest first
+    pub server_ids: Vec<String>,
+}
+
+/// An event with its position in the bus
+#[derive(Clone, Debug, Serialize)]
+pub struct EventEnvelope {
+    /// Monotonic sequence number, unique per bus
+    pub seq: u64,
+    pub event: DockmasterEvent,
+}
+
+struct EventHistory {
+    next_seq: u64,
+    events: VecDeque<EventEnvelope>,
+}
+
+/// Broadcast bus for [`DockmasterEvent`]s
+///
+/// A bounded history of recent events is kept so that a client which receives an
+/// operation id can subscribe afterwards without missing the first updates.
+#[derive(Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<EventEnvelope>,
+    history: Arc<Mutex<EventHistory>>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+        Self {
+            sender,
+            history: Arc::new(Mutex::new(EventHistory {
+                next_seq: 1,
+                events: VecDeque::with_capacity(HISTORY_CAPACITY),
+            })),
+        }
+    }
+
+    /// Publish an event to all current subscribers
+    pub fn publish(&self, event: DockmasterEvent) {
+        let mut history = self.history.lock().unwrap();
+        let envelope = EventEnvelope {
+            seq: history.next_seq,
+            event,
+        };
+        history.next_seq += 1;
+        if history.events.len() == HISTORY_CAPACITY {
+            history.events.pop_front();
+        }
+        history.events.push_back(envelope.clone());
+        // Sending while holding the history lock keeps history and live events in order
+        let _ = self.sender.send(envelope);
+    }
+
+    /// Subscribe to events published from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<EventEnvelope> {
+        self.sender.subscribe()
+    }
+
+    /// Subscribe and get the retained events matching `filter`
+    ///
+    /// Every event is delivered exactly once: either in the returned history or
+    /// through the receiver.
+    pub fn subscribe_with_history<F>(
+        &self,
+        filter: F,
+    ) -> (Vec<EventEnvelope>, broadcast::Receiver<EventEnvelope>)
+    where
+        F: Fn(&DockmasterEvent) -> bool,
+    {
+        let history = self.history.lock().unwrap();
+        let receiver = self.sender.subscribe();
+        let past = history
+            .events
+            .iter()
+            .filter(|envelope| filter(&envelope.event))
+            .cloned()
+            .collect();
+        (past, receiver)
+    }
+}
+
+/// Publishes the install progress of one operation
+#[derive(Clone)]
+pub struct InstallProgressReporter {
+    bus: EventBus,
+    operation_id: String,
+    server_id: String,
+    operation: Option<OperationHandle>,
+}
+
+impl InstallProgressReporter {
+    pub fn new(bus: EventBus, operation_id: String, server_id: String) -> Self {
+        Self {
+            bus,
+            operation_id,
+            server_id,
+            operation: None,
+        }
+    }
+
+    /// Also report the stages as the progress percentage of a tracked operation
+    pub fn with_operation(mut self, operation: Option<OperationHandle>) -> Self {
+        self.operation = operation;
+        self
+    }
+
+    pub fn operation_id(&self) -> &str {
+        &self.operation_id
+    }
+
+    pub fn report(&self, stage: InstallStage, detail: Option<String>) {
+        if let (Some(operation), Some(percent)) = (&self.operation, stage.percent()) {
+            operation.set_progress(percent);
+        }
+        self.bus
+            .publish(DockmasterEvent::InstallProgress(InstallProgress {
+                operation_id: self.operation_id.clone(),
+                server_id: self.server_id.clone(),
+                stage,
+                detail,
+            }));
+    }
+}
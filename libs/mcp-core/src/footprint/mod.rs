@@ -0,0 +1,163 @@
+//! How much model context the aggregated tool list takes.
+//!
+//! Clients put every advertised tool, with its description and input schema, in the
+//! context of the model. [`measure`] sizes the list as clients get it, after the
+//! visibility switch and description truncation, per server and in total. Tokens are
+//! estimated by a [`TokenEstimator`], [`CharsPerToken`] unless another is passed.
+//!
+//! A budget of tokens can be set with [`TOOLS_BUDGET_SETTING`]. When the list goes
+//! over it, the largest servers whose tools would bring it back under are flagged as
+//! the ones to hide, and a [`crate::events::DockmasterEvent::ToolsBudgetExceeded`] is
+//! published.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::mcp_server::tools::BUILTIN_SERVER_ID;
+use crate::models::types::ServerToolInfo;
+
+/// Settings key of the budget of tokens of the tool list, no budget when missing or 0
+pub const TOOLS_BUDGET_SETTING: &str = "tools_token_budget";
+
+/// Most expensive tools listed in a footprint, unless asked otherwise
+pub const FOOTPRINT_TOP_TOOLS: usize = 10;
+
+/// Estimates how many tokens a model needs for a text
+pub trait TokenEstimator: Send + Sync {
+    fn estimate(&self, text: &str) -> usize;
+}
+
+/// About four characters per token, close enough for English text and JSON
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CharsPerToken;
+
+impl TokenEstimator for CharsPerToken {
+    fn estimate(&self, text: &str) -> usize {
+        text.chars().count().div_ceil(4)
+    }
+}
+
+/// Size of one advertised tool
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolFootprint {
+    pub server_id: String,
+    pub tool_name: String,
+    /// Bytes of the tool's JSON in `tools/list`
+    pub bytes: usize,
+    pub tokens: usize,
+}
+
+/// Size of the advertised tools of one server
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServerFootprint {
+    pub server_id: String,
+    pub tools: usize,
+    pub bytes: usize,
+    pub tokens: usize,
+    /// Hiding this server is part of bringing the list back under the budget
+    pub over_budget: bool,
+}
+
+/// Size of the aggregated tool list
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolsFootprint {
+    pub bytes: usize,
+    pub tokens: usize,
+    /// Largest first
+    pub servers: Vec<ServerFootprint>,
+    /// The most expensive tools, largest first
+    pub top_tools: Vec<ToolFootprint>,
+    pub budget: Option<usize>,
+    pub over_budget: bool,
+}
+
+impl ToolsFootprint {
+    /// Servers flagged as the ones to hide, largest first
+    pub fn servers_over_budget(&self) -> Vec<String> {
+        self.servers
+            .iter()
+            .filter(|server| server.over_budget)
+            .map(|server| server.server_id.clone())
+            .collect()
+    }
+}
+
+/// A tool as `tools/list` advertises it to clients
+pub fn advertised_tool(tool: &ServerToolInfo) -> Value {
+    let mut value = json!({
+        "name": tool.name,
+        "description": tool.description,
+        "inputSchema": tool.input_schema_value(),
+    });
+    if let Some(annotations) = &tool.annotations {
+        value["annotations"] = json!(annotations);
+    }
+    value
+}
+
+/// Size up a tool list, listing its `top` most expensive tools
+pub fn measure(
+    tools: &[ServerToolInfo],
+    estimator: &dyn TokenEstimator,
+    budget: Option<usize>,
+    top: usize,
+) -> ToolsFootprint {
+    let mut footprint = ToolsFootprint {
+        budget,
+        ..Default::default()
+    };
+    let mut servers: BTreeMap<&str, ServerFootprint> = BTreeMap::new();
+    let mut tool_footprints = Vec::with_capacity(tools.len());
+    for tool in tools {
+        let text = advertised_tool(tool).to_string();
+        let tool_footprint = ToolFootprint {
+            server_id: tool.server_id.clone(),
+            tool_name: tool.name.clone(),
+            bytes: text.len(),
+            tokens: estimator.estimate(&text),
+        };
+        let server = servers
+            .entry(&tool.server_id)
+            .or_insert_with(|| ServerFootprint {
+                server_id: tool.server_id.clone(),
+                tools: 0,
+                bytes: 0,
+                tokens: 0,
+                over_budget: false,
+            });
+        server.tools += 1;
+        server.bytes += tool_footprint.bytes;
+        server.tokens += tool_footprint.tokens;
+        footprint.bytes += tool_footprint.bytes;
+        footprint.tokens += tool_footprint.tokens;
+        tool_footprints.push(tool_footprint);
+    }
+
+    footprint.servers = servers.into_values().collect();
+    footprint
+        .servers
+        .sort_by(|a, b| b.tokens.cmp(&a.tokens).then(a.server_id.cmp(&b.server_id)));
+    tool_footprints.sort_by(|a, b| b.tokens.cmp(&a.tokens).then(a.tool_name.cmp(&b.tool_name)));
+    tool_footprints.truncate(top);
+    footprint.top_tools = tool_footprints;
+
+    if let Some(budget) = budget.filter(|budget| footprint.tokens > *budget) {
+        footprint.over_budget = true;
+        // The built-in tools can't be hidden on their own
+        let mut excess = footprint.tokens - budget;
+        for server in footprint
+            .servers
+            .iter_mut()
+            .filter(|server| server.server_id != BUILTIN_SERVER_ID)
+        {
+            if excess == 0 {
+                break;
+            }
+            server.over_budget = true;
+            excess = excess.saturating_sub(server.tokens);
+        }
+    }
+    footprint
+}
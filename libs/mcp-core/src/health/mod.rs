@@ -0,0 +1,127 @@
+//! Periodic checks that running servers still answer, to notice a hung server before
+//! a tool call times out.
+//!
+//! Every [`HealthCheckSettings::interval_secs`] the tools of each running server are
+//! listed, a request every server answers without side effects. A server that didn't
+//! answer within [`HEALTH_CHECK_TIMEOUT`] counts a failure, and is unhealthy after
+//! [`HealthCheckSettings::unhealthy_after`] failures in a row. Unhealthy servers are
+//! restarted when [`HealthCheckSettings::restart_unhealthy`] is set. The settings are
+//! saved under [`HEALTH_CHECK_SETTING`].
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// Setting holding the [`HealthCheckSettings`] as JSON
+pub const HEALTH_CHECK_SETTING: &str = "health_check";
+
+/// Seconds between two rounds of checks, unless set otherwise
+pub const DEFAULT_HEALTH_CHECK_INTERVAL_SECS: u64 = 30;
+
+/// Failures in a row after which a server is unhealthy, unless set otherwise
+pub const DEFAULT_UNHEALTHY_AFTER: u32 = 3;
+
+/// Time a server has to answer a check
+pub const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How the running servers are checked
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HealthCheckSettings {
+    /// Seconds between two rounds of checks, 0 turns the checks off
+    pub interval_secs: u64,
+    /// Failures in a row after which a server is unhealthy
+    pub unhealthy_after: u32,
+    /// Whether unhealthy servers are restarted
+    pub restart_unhealthy: bool,
+}
+
+impl Default for HealthCheckSettings {
+    fn default() -> Self {
+        Self {
+            interval_secs: DEFAULT_HEALTH_CHECK_INTERVAL_SECS,
+            unhealthy_after: DEFAULT_UNHEALTHY_AFTER,
+            restart_unhealthy: false,
+        }
+    }
+}
+
+impl HealthCheckSettings {
+    /// Time between two rounds of checks, None when they are off
+    pub fn interval(&self) -> Option<Duration> {
+        (self.interval_secs > 0).then(|| Duration::from_secs(self.interval_secs))
+    }
+}
+
+/// Outcome of the checks of a running server
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServerHealth {
+    /// When the server was last checked, in milliseconds since the epoch
+    pub last_health_check: u64,
+    pub healthy: bool,
+    pub consecutive_failures: u32,
+    /// Why the last check failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+/// Health of every running server, by server id
+///
+/// A server has no health until it was checked once, and loses it when it stops.
+#[derive(Clone, Default)]
+pub struct HealthMonitor {
+    health: Arc<Mutex<HashMap<String, ServerHealth>>>,
+}
+
+impl HealthMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record that a server answered a check at `at`
+    pub fn record_success(&self, server_id: &str, at: u64) {
+        self.health.lock().unwrap().insert(
+            server_id.to_string(),
+            ServerHealth {
+                last_health_check: at,
+                healthy: true,
+                consecutive_failures: 0,
+                last_error: None,
+            },
+        );
+    }
+
+    /// Record that a server failed a check at `at`, returning its health
+    pub fn record_failure(
+        &self,
+        server_id: &str,
+        at: u64,
+        error: String,
+        unhealthy_after: u32,
+    ) -> ServerHealth {
+        let mut health = self.health.lock().unwrap();
+        let consecutive_failures = health
+            .get(server_id)
+            .map_or(0, |health| health.consecutive_failures)
+            + 1;
+        let server_health = ServerHealth {
+            last_health_check: at,
+            healthy: consecutive_failures < unhealthy_after.max(1),
+            consecutive_failures,
+            last_error: Some(error),
+        };
+        health.insert(server_id.to_string(), server_health.clone());
+        server_health
+    }
+
+    pub fn get(&self, server_id: &str) -> Option<ServerHealth> {
+        self.health.lock().unwrap().get(server_id).cloned()
+    }
+
+    /// Forget the health of a server that stopped or started again
+    pub fn remove(&self, server_id: &str) {
+        self.health.lock().unwrap().remove(server_id);
+    }
+}
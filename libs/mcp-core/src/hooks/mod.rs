@@ -0,0 +1,232 @@
+//! Hooks run around the tool calls of servers to enforce policies.
+//!
+//! A hook is an external command run before a call reaches its server (`pre`) or
+//! before its result reaches the client (`post`), for one server or for all of them.
+//! It receives a [`HookInput`] as JSON on stdin and answers on stdout with
+//! `{"decision": "pass"}`, `{"decision": "modify", "value": ...}` to replace the
+//! arguments or the result, or `{"decision": "reject", "reason": "..."}`. An empty
+//! answer passes. A hook that fails, times out or answers something else is handled
+//! according to its [`HookFailurePolicy`]. Since hooks run arbitrary commands,
+//! `hooks/set` only replaces them when given the admin token.
+
+use std::collections::HashSet;
+use std::process::Stdio;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+/// Settings key of the configured hooks
+pub const TOOL_HOOKS_SETTING: &str = "tool_hooks";
+
+/// Time a hook has to answer when its configuration doesn't say
+pub const DEFAULT_HOOK_TIMEOUT_MS: u64 = 5000;
+
+/// `reason` in the data of the JSON-RPC error of a call rejected by a hook
+pub const POLICY_REJECTED_REASON: &str = "policy_rejected";
+
+/// When a hook runs
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HookStage {
+    /// Before the call reaches the server, the hook sees and may change the arguments
+    Pre,
+    /// Before the result reaches the client, the hook sees and may change the result
+    Post,
+}
+
+impl HookStage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HookStage::Pre => "pre",
+            HookStage::Post => "post",
+        }
+    }
+}
+
+/// What happens to a call when its hook fails or times out
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HookFailurePolicy {
+    /// The call is rejected
+    #[default]
+    FailClosed,
+    /// The call goes on as if the hook had passed it
+    FailOpen,
+}
+
+/// A configured hook
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ToolHook {
+    /// Name of the hook in errors and in the audit log
+    pub id: String,
+    pub stage: HookStage,
+    /// Server the hook applies to, every server when None
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_id: Option<String>,
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+    #[serde(default)]
+    pub on_failure: HookFailurePolicy,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_timeout_ms() -> u64 {
+    DEFAULT_HOOK_TIMEOUT_MS
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl ToolHook {
+    /// Whether the hook runs at `stage` for the calls of `server_id`
+    pub fn applies_to(&self, stage: HookStage, server_id: &str) -> bool {
+        self.enabled
+            && self.stage == stage
+            && self.server_id.as_deref().is_none_or(|id| id == server_id)
+    }
+}
+
+/// Hook configuration, as exported and imported
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HooksConfig {
+    /// Hooks of a stage run in this order, each one seeing the changes of the previous
+    pub hooks: Vec<ToolHook>,
+}
+
+impl HooksConfig {
+    /// Check that ids are unique and that each hook can be run
+    pub fn validate(&self) -> Result<(), String> {
+        let mut ids = HashSet::new();
+        for hook in &self.hooks {
+            if hook.id.trim().is_empty() {
+                return Err("Every hook needs an id".to_string());
+            }
+            if !ids.insert(hook.id.as_str()) {
+                return Err(format!("Duplicate hook id '{}'", hook.id));
+            }
+            if hook.command.trim().is_empty() {
+                return Err(format!("Hook '{}' has no command", hook.id));
+            }
+            if hook.timeout_ms == 0 {
+                return Err(format!("Hook '{}' needs a timeout above 0 ms", hook.id));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// What a hook receives on stdin
+#[derive(Clone, Debug, Serialize)]
+pub struct HookInput<'a> {
+    pub stage: HookStage,
+    pub server_id: &'a str,
+    pub tool: &'a str,
+    pub arguments: &'a Value,
+    /// Result of the call, for `post` hooks
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<&'a Value>,
+}
+
+/// What a hook decided
+#[derive(Clone, Debug, PartialEq)]
+pub enum HookOutcome {
+    Pass,
+    /// Replace the arguments or the result with this value
+    Modify(Value),
+    /// Reject the call, with the reason given by the hook
+    Reject(String),
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "decision", rename_all = "lowercase")]
+enum HookReply {
+    Pass,
+    Modify {
+        value: Value,
+    },
+    Reject {
+        #[serde(default)]
+        reason: Option<String>,
+    },
+}
+
+/// Parse the answer of a hook
+pub fn parse_hook_reply(stdout: &str) -> Result<HookOutcome, String> {
+    if stdout.trim().is_empty() {
+        return Ok(HookOutcome::Pass);
+    }
+    let reply: HookReply =
+        serde_json::from_str(stdout.trim()).map_err(|e| format!("Invalid answer: {}", e))?;
+    Ok(match reply {
+        HookReply::Pass => HookOutcome::Pass,
+        HookReply::Modify { value } => HookOutcome::Modify(value),
+        HookReply::Reject { reason } => {
+            HookOutcome::Reject(reason.unwrap_or_else(|| "No reason given".to_string()))
+        }
+    })
+}
+
+/// Run `hook` on `input`, killing it if it doesn't answer in time
+pub async fn run_hook(hook: &ToolHook, input: &HookInput<'_>) -> Result<HookOutcome, String> {
+    let payload = serde_json::to_vec(input).map_err(|e| e.to_string())?;
+    let mut child = Command::new(&hook.command)
+        .args(&hook.args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("Failed to start {}: {}", hook.command, e))?;
+
+    let mut stdin = child
+        .stdin
+        .take()
+        .ok_or("Failed to open the stdin of the hook")?;
+    let run = async move {
+        // A hook may answer without reading its input
+        if let Err(e) = stdin.write_all(&payload).await {
+            if e.kind() != std::io::ErrorKind::BrokenPipe {
+                return Err(e);
+            }
+        }
+        drop(stdin);
+        child.wait_with_output().await
+    };
+    let output = tokio::time::timeout(Duration::from_millis(hook.timeout_ms), run)
+        .await
+        .map_err(|_| format!("No answer within {} ms", hook.timeout_ms))?
+        .map_err(|e| format!("Failed to run {}: {}", hook.command, e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    parse_hook_reply(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// A call rejected by a hook
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct PolicyError {
+    pub hook: String,
+    pub stage: HookStage,
+    pub reason: String,
+}
+
+impl std::fmt::Display for PolicyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Rejected by hook '{}': {}", self.hook, self.reason)
+    }
+}
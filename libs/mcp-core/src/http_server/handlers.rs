@@ -1,63 +1,123 @@
-use std::time::{Duration, Instant};
-use std::collections::HashMap;
-
 use axum::response::IntoResponse;
-use axum::{http::StatusCode, Extension, Json};
-use lazy_static::lazy_static;
-use log::info;
-use reqwest;
-use serde::{Deserialize, Serialize};
-use serde_json::{json, Value};
-use tokio::sync::Mutex;
-
+use axum::{
+    http::{header, HeaderMap, StatusCode},
+    Extension, Json,
+};
+use log::{debug, info, warn};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{json, Map, Value};
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::cancellation::{call_id, cancelled_request_id, CANCELLED_ERROR, SESSION_ID_HEADER};
+use crate::capabilities::{capabilities, is_supported_method};
+use crate::config_history::{ConfigApplyResult, CONFIG_HISTORY_KEPT};
 use crate::core::mcp_core::MCPCore;
+use crate::core::mcp_core_bulk_ext::McpCoreBulkExt;
+use crate::core::mcp_core_config_history_ext::McpCoreConfigHistoryExt;
+use crate::core::mcp_core_dashboard_ext::McpCoreDashboardExt;
+use crate::core::mcp_core_database_ext::McpCoreDatabaseExt;
+use crate::core::mcp_core_diagnose_ext::McpCoreDiagnoseExt;
+use crate::core::mcp_core_diagnostics_ext::{DiagnosticsOptions, McpCoreDiagnosticsExt};
+use crate::core::mcp_core_footprint_ext::McpCoreFootprintExt;
+use crate::core::mcp_core_hooks_ext::McpCoreHooksExt;
+use crate::core::mcp_core_lock_debug_ext::McpCoreLockDebugExt;
+use crate::core::mcp_core_metrics_ext::McpCoreMetricsExt;
 use crate::core::mcp_core_proxy_ext::McpCoreProxyExt;
+use crate::core::mcp_core_rate_limits_ext::McpCoreRateLimitsExt;
+use crate::core::mcp_core_read_only_ext::McpCoreReadOnlyExt;
+use crate::core::mcp_core_recordings_ext::McpCoreRecordingsExt;
+use crate::core::mcp_core_registry_sources_ext::McpCoreRegistrySourcesExt;
+use crate::core::mcp_core_relocation_ext::McpCoreRelocationExt;
+use crate::core::mcp_core_runtimes_ext::McpCoreRuntimesExt;
+use crate::core::mcp_core_schedules_ext::McpCoreSchedulesExt;
+use crate::core::mcp_core_selftest_ext::McpCoreSelftestExt;
+use crate::core::mcp_core_tool_limits_ext::McpCoreToolLimitsExt;
+use crate::core::mcp_core_tools_ext::McpCoreToolsExt;
+use crate::core::mcp_core_tools_snapshot_ext::McpCoreToolsSnapshotExt;
+use crate::core::mcp_core_undo_ext::McpCoreUndoExt;
+use crate::core::mcp_core_webhooks_ext::McpCoreWebhooksExt;
+use crate::events::{DockmasterEvent, EventEnvelope, InstallProgressReporter, InstallStage};
+use crate::footprint::FOOTPRINT_TOP_TOOLS;
+use crate::hooks::{HooksConfig, POLICY_REJECTED_REASON};
+use crate::metrics::PROMETHEUS_CONTENT_TYPE;
+use crate::models::config_check::{
+    check_configuration_value, resolve_command, ConfigErrors, CONFIGURATION_FIELD,
+    REGISTRY_CONFIG_FIELD,
+};
+use crate::models::env_template::{ConfigSchema, InstallPreview};
+use crate::models::server_id::{legacy_server_id_warning, request_server_id};
+use crate::models::tool_id::proxy_tool_id;
 use crate::models::types::{
-    Distribution, ErrorResponse, InputSchema, RegistryToolsResponse, ServerConfiguration,
+    Distribution, ErrorResponse, InputSchema, ProvenanceSource, RegistryToolsResponse,
+    ServerCallMode, ServerConfiguration, ServerImportRequest, ServerProvenance,
     ServerRegistrationRequest, ServerRegistrationResponse, ServerToolInfo, ServerToolsResponse,
-    ToolExecutionRequest, InputSchemaProperty
+    ToolAnnotations, ToolExecutionRequest, ToolUninstallRequest, TOOL_WARNINGS_META_KEY,
+};
+use crate::models::unknown_fields::{
+    parse_with_unknown_fields, registration_fields_at, unknown_field_warnings, KnownFields,
 };
+use crate::operations::OperationHandle;
+use crate::rate_limits::{RateLimit, RATE_LIMITED_REASON};
+use crate::read_only::{allowed_in_read_only, is_admin_token, read_only_error};
+use crate::registry::registry_sources::{RegistrySource, DEFAULT_SOURCE_ID};
+use crate::registry::storage::{StorageUnavailable, STORAGE_UNAVAILABLE_REASON};
+use crate::schedules::{ScheduleDefinition, SCHEDULE_RUNS_KEPT};
+use crate::tool_limits::ToolFilter;
 use crate::types::{ConfigUpdateRequest, ServerConfigUpdateRequest};
+use crate::utils::payload::log_payload;
+use crate::webhooks::Webhook;
 use mcp_sdk_server::Router;
 
 use axum::{
-    response::sse::{Event, Sse},
     extract::Query,
+    response::sse::{Event, Sse},
 };
 use futures::stream::Stream;
+use futures::StreamExt;
 use std::convert::Infallible;
 use tokio::io::{self, AsyncWriteExt};
-use futures::StreamExt;
 use uuid::Uuid;
 
+use crate::mcp_server::tools::get_builtin_tool_infos;
 use mcp_sdk_server::router::RouterService;
-use crate::mcp_server::tools::{
-    TOOL_REGISTER_SERVER, get_register_server_tool,
-    TOOL_SEARCH_SERVER, get_search_server_tool,
-    TOOL_CONFIGURE_SERVER, get_configure_server_tool,
-    TOOL_UNINSTALL_SERVER, get_uninstall_server_tool,
-    TOOL_LIST_INSTALLED_SERVERS, get_list_installed_servers_tool
-};
 
 use std::sync::Arc;
 use tokio::sync::Mutex as TokioMutex;
 
 use crate::mcp_server::mcp_router::MCPDockmasterRouter;
-use crate::mcp_server::session_manager::SESSION_MANAGER;
+use crate::mcp_server::mcp_tools_service::MCPToolsService;
+use crate::mcp_state::server_notifications::{progress_topic, requested_progress_token};
+use crate::sessions::{sse::SseTransport, ClientIdentity, ClientSession, PostedToolCall};
 use mcp_sdk_server::{ByteTransport, Server};
 use tokio_util::codec::FramedRead;
 
-
 /// JSON-RPC request structure
 #[derive(Deserialize, Debug)]
 pub struct JsonRpcRequest {
     #[allow(dead_code)]
     pub jsonrpc: String,
-    pub id: Value,
+    /// None for a notification, which gets no response. An explicit null id is
+    /// Some(Value::Null): only a missing id makes a notification
+    #[serde(default, deserialize_with = "present_id")]
+    pub id: Option<Value>,
     pub method: String,
     pub params: Option<Value>,
 }
 
+/// Any id that is present, null included
+fn present_id<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Value>, D::Error> {
+    Value::deserialize(deserializer).map(Some)
+}
+
+/// Body of a JSON-RPC POST, a single request or a batch of them
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum JsonRpcPayload {
+    /// Entries are parsed one by one, an invalid one only fails itself
+    Batch(Vec<Value>),
+    Single(JsonRpcRequest),
+}
+
 /// JSON-RPC response structure
 #[derive(Serialize, Debug)]
 pub struct JsonRpcResponse {
@@ -95,68 +155,184 @@ pub enum JsonRpcMethod {
     Unknown(String),
 }
 
-// Cache structure to store registry data and timestamp
-struct RegistryCache {
-    data: Option<Value>,
-    timestamp: Option<Instant>,
-}
-
-// Initialize the static cache with lazy_static
-lazy_static! {
-    static ref REGISTRY_CACHE: Mutex<RegistryCache> = Mutex::new(RegistryCache {
-        data: None,
-        timestamp: None,
-    });
+/// Liveness of the HTTP server, with the availability of the database
+pub async fn health_check(Extension(mcp_core): Extension<MCPCore>) -> impl IntoResponse {
+    let storage = mcp_core.tool_registry.read().await.storage().status();
+    let status = if storage.degraded { "degraded" } else { "ok" };
+    (
+        StatusCode::OK,
+        Json(json!({ "status": status, "storage": storage })),
+    )
 }
 
-// Cache duration constant (1 minutes)
-const CACHE_DURATION: Duration = Duration::from_secs(60);
-
-pub async fn health_check() -> impl IntoResponse {
-    (StatusCode::OK, "MCP Server is running!")
+/// Prometheus metrics of the servers, see [`crate::metrics`]
+pub async fn metrics_handler(Extension(mcp_core): Extension<MCPCore>) -> impl IntoResponse {
+    match mcp_core.render_metrics().await {
+        Ok(metrics) => (
+            StatusCode::OK,
+            [(header::CONTENT_TYPE, PROMETHEUS_CONTENT_TYPE)],
+            metrics,
+        ),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            [(header::CONTENT_TYPE, "text/plain; charset=utf-8")],
+            format!("Failed to collect the metrics: {}", e),
+        ),
+    }
 }
 
 pub async fn handle_mcp_request(
     Extension(mcp_core): Extension<MCPCore>,
     Extension(mcp_router): Extension<Arc<MCPDockmasterRouter>>,
-    Json(request): Json<JsonRpcRequest>,
-) -> Json<JsonRpcResponse> {
+    headers: HeaderMap,
+    Json(payload): Json<JsonRpcPayload>,
+) -> axum::response::Response {
+    // Tool calls are only cancellable within a session, see `crate::cancellation`
+    let session_id = headers
+        .get(SESSION_ID_HEADER)
+        .and_then(|value| value.to_str().ok());
+    let requests = match payload {
+        JsonRpcPayload::Single(request) => {
+            return match dispatch_mcp_request(mcp_core, &mcp_router, session_id, request).await {
+                Some(response) => Json(response).into_response(),
+                None => StatusCode::ACCEPTED.into_response(),
+            };
+        }
+        JsonRpcPayload::Batch(requests) if requests.is_empty() => {
+            return Json(invalid_request(json!("Empty batch"))).into_response();
+        }
+        JsonRpcPayload::Batch(requests) => requests,
+    };
+    info!("Received a batch of {} MCP requests", requests.len());
+
+    // In order, a client may pipeline requests that depend on each other
+    let mut responses = Vec::new();
+    for request in requests {
+        let request = match serde_json::from_value::<JsonRpcRequest>(request) {
+            Ok(request) => request,
+            Err(e) => {
+                responses.push(invalid_request(json!(e.to_string())));
+                continue;
+            }
+        };
+        // Notifications are handled but not answered
+        let response = dispatch_mcp_request(mcp_core.clone(), &mcp_router, session_id, request);
+        if let Some(response) = response.await {
+            responses.push(response);
+        }
+    }
+    if responses.is_empty() {
+        return StatusCode::ACCEPTED.into_response();
+    }
+    Json(responses).into_response()
+}
+
+/// Response to a batch entry that isn't a request
+fn invalid_request(data: Value) -> JsonRpcResponse {
+    JsonRpcResponse {
+        jsonrpc: "2.0".to_string(),
+        id: Value::Null,
+        result: None,
+        error: Some(JsonRpcError {
+            code: -32600,
+            message: "Invalid Request".to_string(),
+            data: Some(data),
+        }),
+    }
+}
+
+/// Answer one JSON-RPC request, on its own or from a batch, None for a notification
+///
+/// `session_id` is the session the request was posted in, if the client named one.
+async fn dispatch_mcp_request(
+    mcp_core: MCPCore,
+    mcp_router: &MCPDockmasterRouter,
+    session_id: Option<&str>,
+    request: JsonRpcRequest,
+) -> Option<JsonRpcResponse> {
     info!("Received MCP request: method={}", request.method);
 
+    // Checked here so that new methods are refused in read-only mode unless allowed
+    let read_only = !allowed_in_read_only(&request.method) && mcp_core.is_read_only().await;
+
     let result: Result<Value, Value> = match request.method.as_str() {
+        _ if read_only => Err(read_only_error(&request.method)),
+        _ if !is_supported_method(&request.method) => Err(method_not_found(&request.method)),
         // Use our MCP router for the initialize method
         "initialize" => {
             // Use the router's capabilities for the response
             let capabilities = mcp_router.capabilities();
             let name = mcp_router.name();
             let instructions = mcp_router.instructions();
-            
+
             // Return the initialization response
             Ok(json!({
                 "protocolVersion": "2024-11-05",
                 "serverInfo": {
                     "name": name,
-                    "version": "1.0.0"
+                    "version": mcp_router.version()
                 },
                 "instructions": instructions,
                 "capabilities": capabilities
             }))
-        },
+        }
         "tools/list" => match handle_list_tools(mcp_core).await {
             Ok(response) => Ok(serde_json::to_value(response).unwrap()),
             Err(error) => Err(serde_json::to_value(error).unwrap()),
         },
         "tools/hidden" => handle_tools_hidden(mcp_core).await,
+        "tools/footprint" => handle_tools_footprint(mcp_core, request.params).await,
+        "dashboard/summary" => match mcp_core.dashboard_summary().await {
+            Ok(summary) => Ok(json!(summary)),
+            Err(e) => Err(json!({
+                "code": -32000,
+                "message": format!("Failed to summarize the servers: {}", e)
+            })),
+        },
+        "tools/describe" => match request.params {
+            Some(params) => handle_describe_tool(mcp_core, params).await,
+            None => Err(json!({
+                "code": -32602,
+                "message": "Missing proxyId or serverId and toolName parameters"
+            })),
+        },
+        "tools/set_rate_limit" => match request.params {
+            Some(params) => handle_set_rate_limit(mcp_core, params).await,
+            None => Err(json!({
+                "code": -32602,
+                "message": "Missing server_id and tool_name parameters"
+            })),
+        },
+        "tools/set_annotations" => match request.params {
+            Some(params) => handle_set_tool_annotations(mcp_core, params).await,
+            None => Err(json!({
+                "code": -32602,
+                "message": "Missing server_id, tool_name and annotations parameters"
+            })),
+        },
         "tools/call" => {
             if let Some(params) = request.params {
-                handle_invoke_tool(mcp_core, params).await
+                let call_id = session_id
+                    .zip(request.id.as_ref())
+                    .map(|(session_id, id)| call_id(session_id, id));
+                handle_invoke_tool(mcp_core, params, call_id).await
             } else {
                 Err(json!({
                     "code": -32602,
                     "message": "Missing parameters"
                 }))
             }
-        },
+        }
+        "notifications/initialized" => Ok(json!({})),
+        "notifications/cancelled" => {
+            let params = request.params.unwrap_or(Value::Null);
+            if let (Some(session_id), Some(request_id)) =
+                (session_id, cancelled_request_id(&params))
+            {
+                mcp_core.cancel_tool_call(&call_id(session_id, request_id));
+            }
+            Ok(json!({}))
+        }
         "prompts/list" => handle_list_prompts().await,
         "resources/list" => handle_list_resources().await,
         "resources/read" => {
@@ -168,7 +344,7 @@ pub async fn handle_mcp_request(
                     "message": "Invalid params - missing parameters for resource reading"
                 }))
             }
-        },
+        }
         "prompts/get" => {
             if let Some(params) = request.params {
                 handle_get_prompt(params).await
@@ -178,223 +354,390 @@ pub async fn handle_mcp_request(
                     "message": "Invalid params - missing parameters for prompt retrieval"
                 }))
             }
-        },
+        }
         "registry/install" => {
             if let Some(params) = request.params {
-                match handle_register_tool(mcp_core, params).await {
-                    Ok(response) => Ok(serde_json::to_value(response).unwrap()),
-                    Err(error) => Err(serde_json::to_value(error).unwrap()),
-                }
+                handle_install_request(mcp_core, params).await
             } else {
                 Err(json!({
                     "code": -32602,
                     "message": "Missing parameters for tool installation"
                 }))
             }
-        },
+        }
         "registry/import" => {
-            if let Some(params) = request.params {
-                handle_import_server_from_url(mcp_core, params).await
+            if let Some(mut params) = request.params {
+                if take_async_flag(&mut params, &["async"]) {
+                    Ok(spawn_operation(
+                        &mcp_core,
+                        "import",
+                        None,
+                        true,
+                        handle_import_server_from_url(mcp_core.clone(), params),
+                    ))
+                } else {
+                    handle_import_server_from_url(mcp_core, params).await
+                }
             } else {
                 Err(json!({
                     "code": -32602,
                     "message": "Missing parameters for server import"
                 }))
             }
-        },
+        }
         "registry/list" => handle_list_all_tools(mcp_core).await,
+        "registry/preview_install" => match request.params {
+            Some(params) => handle_preview_install(mcp_core, params).await,
+            None => Err(json!({
+                "code": -32602,
+                "message": "Missing tool_id parameter"
+            })),
+        },
+        "registry/sources" => match mcp_core.list_registry_sources().await {
+            Ok(sources) => Ok(json!({ "sources": sources })),
+            Err(e) => Err(json!({
+                "code": -32000,
+                "message": format!("Failed to read the registry sources: {}", e)
+            })),
+        },
+        "registry/save_source" => handle_save_registry_source(mcp_core, request.params).await,
+        "registry/delete_source" => handle_delete_registry_source(mcp_core, request.params).await,
         "server/config" => {
-            if let Some(params) = request.params {
-                handle_get_server_config(mcp_core, params).await
+            if let Some(mut params) = request.params {
+                if take_async_flag(&mut params, &["async"]) {
+                    let server_id = request_server_id(&params).map(|s| s.to_string());
+                    // Not cancellable: aborting between the update and the restart would
+                    // leave the new config saved but not applied
+                    Ok(spawn_operation(
+                        &mcp_core,
+                        "configure",
+                        server_id,
+                        false,
+                        handle_get_server_config(mcp_core.clone(), params),
+                    ))
+                } else {
+                    handle_get_server_config(mcp_core, params).await
+                }
             } else {
                 Err(json!({
                     "code": -32602,
                     "message": "Invalid params - missing parameters for server config"
                 }))
             }
+        }
+        "server/config_history" => match request.params {
+            Some(params) => handle_config_history(mcp_core, params).await,
+            None => Err(json!({
+                "code": -32602,
+                "message": "Missing server_id parameter"
+            })),
         },
-        _ => Err(json!({
-            "code": -32601,
-            "message": format!("Method '{}' not found", request.method)
+        "server/config_schema" => match request.params {
+            Some(params) => handle_config_schema(mcp_core, params).await,
+            None => Err(json!({
+                "code": -32602,
+                "message": "Missing server_id parameter"
+            })),
+        },
+        "server/restart" => {
+            if let Some(mut params) = request.params {
+                if take_async_flag(&mut params, &["async"]) {
+                    let server_id = params
+                        .get("server_id")
+                        .and_then(|v| v.as_str())
+                        .map(|s| s.to_string());
+                    Ok(spawn_operation(
+                        &mcp_core,
+                        "restart",
+                        server_id,
+                        false,
+                        handle_restart_server(mcp_core.clone(), params),
+                    ))
+                } else {
+                    handle_restart_server(mcp_core, params).await
+                }
+            } else {
+                Err(json!({
+                    "code": -32602,
+                    "message": "Invalid params - missing parameters for server restart"
+                }))
+            }
+        }
+        "server/start" => match request.params {
+            Some(params) => handle_start_server(mcp_core, params).await,
+            None => Err(json!({
+                "code": -32602,
+                "message": "Missing server_id parameter"
+            })),
+        },
+        "server/stop" => match request.params {
+            Some(params) => handle_stop_server(mcp_core, params).await,
+            None => Err(json!({
+                "code": -32602,
+                "message": "Missing server_id parameter"
+            })),
+        },
+        "server/delete" => match request.params {
+            Some(params) => handle_delete_server(mcp_core, params).await,
+            None => Err(json!({
+                "code": -32602,
+                "message": "Missing server_id parameter"
+            })),
+        },
+        "servers/bulk_update" => match request.params {
+            Some(params) => handle_bulk_update(mcp_core, params).await,
+            None => Err(json!({
+                "code": -32602,
+                "message": "Missing server_ids and enabled parameters"
+            })),
+        },
+        "servers/restart_all" => handle_restart_all(mcp_core, request.params).await,
+        "server/restart_all" => handle_restart_all(mcp_core, request.params).await,
+        "server/status" => match request.params {
+            Some(params) => handle_server_status(mcp_core, params).await,
+            None => Err(json!({
+                "code": -32602,
+                "message": "Missing server_id parameter"
+            })),
+        },
+        "server/set_mode" => match request.params {
+            Some(params) => handle_set_server_mode(mcp_core, params).await,
+            None => Err(json!({
+                "code": -32602,
+                "message": "Missing server_id and mode parameters"
+            })),
+        },
+        "server/set_tool_filter" => match request.params {
+            Some(params) => handle_set_tool_filter(mcp_core, params).await,
+            None => Err(json!({
+                "code": -32602,
+                "message": "Missing server_id parameter"
+            })),
+        },
+        "server/history" => match request.params {
+            Some(params) => handle_server_history(mcp_core, params).await,
+            None => Err(json!({
+                "code": -32602,
+                "message": "Missing server_id parameter"
+            })),
+        },
+        "server/logs" => match request.params {
+            Some(params) => handle_server_logs(mcp_core, params).await,
+            None => Err(json!({
+                "code": -32602,
+                "message": "Missing server_id parameter"
+            })),
+        },
+        "server/launch_plan" => match request.params {
+            Some(params) => handle_launch_plan(mcp_core, params).await,
+            None => Err(json!({
+                "code": -32602,
+                "message": "Missing server_id parameter"
+            })),
+        },
+        "server/diagnose" => match request.params {
+            Some(params) => handle_diagnose_server(mcp_core, params).await,
+            None => Err(json!({
+                "code": -32602,
+                "message": "Missing server_id parameter"
+            })),
+        },
+        "server/resources" => match request.params {
+            Some(params) => handle_server_resources(mcp_core, params).await,
+            None => Err(json!({
+                "code": -32602,
+                "message": "Missing server_id parameter"
+            })),
+        },
+        "operations/get" => match request.params {
+            Some(params) => handle_get_operation(mcp_core, params),
+            None => Err(json!({
+                "code": -32602,
+                "message": "Missing operation_id parameter"
+            })),
+        },
+        "operations/list" => Ok(json!({ "operations": mcp_core.operations.list() })),
+        "operations/cancel" => match request.params {
+            Some(params) => handle_cancel_operation(mcp_core, params),
+            None => Err(json!({
+                "code": -32602,
+                "message": "Missing operation_id parameter"
+            })),
+        },
+        "diagnostics/export" => handle_export_diagnostics(mcp_core, request.params).await,
+        "system/capabilities" => Ok(json!(capabilities())),
+        "system/dump_state" => handle_dump_state(mcp_core, request.params),
+        "system/selftest" => {
+            let report = mcp_core.run_selftest().await;
+            let text = report.to_text();
+            let mut response = json!(report);
+            response["text"] = json!(text);
+            Ok(response)
+        }
+        "system/migration_report" => match mcp_core.migration_report().await {
+            Ok(report) => Ok(json!({ "report": report })),
+            Err(e) => Err(json!({
+                "code": -32000,
+                "message": format!("Failed to get the migration report: {}", e)
+            })),
+        },
+        "system/migrate_paths" => handle_migrate_paths(mcp_core, request.params).await,
+        "system/cleanup_orphans" => Ok(json!(mcp_core.cleanup_orphans().await)),
+        "database/state" => Ok(json!(mcp_core.database_state().await)),
+        "database/migrate_legacy" => handle_migrate_legacy(mcp_core).await,
+        "database/gc" => handle_database_gc(mcp_core, request.params).await,
+        "hooks/list" => Ok(json!(mcp_core.tool_hooks().await)),
+        "hooks/set" => match request.params {
+            Some(params) => handle_set_hooks(mcp_core, params).await,
+            None => Err(json!({
+                "code": -32602,
+                "message": "Missing hooks parameter"
+            })),
+        },
+        "schedules/list" => match mcp_core.list_schedules().await {
+            Ok(schedules) => Ok(json!({ "schedules": schedules })),
+            Err(e) => Err(json!({
+                "code": -32000,
+                "message": format!("Failed to list the schedules: {}", e)
+            })),
+        },
+        "schedules/get" => handle_get_schedule(mcp_core, request.params).await,
+        "schedules/create" => handle_save_schedule(mcp_core, request.params, true).await,
+        "schedules/update" => handle_save_schedule(mcp_core, request.params, false).await,
+        "schedules/delete" => handle_delete_schedule(mcp_core, request.params).await,
+        "schedules/runs" => handle_schedule_runs(mcp_core, request.params).await,
+        "webhooks/list" => match mcp_core.list_webhooks().await {
+            Ok(webhooks) => Ok(json!({ "webhooks": webhooks })),
+            Err(e) => Err(json!({
+                "code": -32000,
+                "message": format!("Failed to list the webhooks: {}", e)
+            })),
+        },
+        "webhooks/create" => handle_save_webhook(mcp_core, request.params, true).await,
+        "webhooks/update" => handle_save_webhook(mcp_core, request.params, false).await,
+        "webhooks/delete" => handle_delete_webhook(mcp_core, request.params).await,
+        "webhooks/test" => handle_test_webhook(mcp_core, request.params).await,
+        "webhooks/dead_letters" => {
+            let limit = request
+                .params
+                .as_ref()
+                .and_then(|params| params.get("limit"))
+                .and_then(|v| v.as_i64())
+                .unwrap_or(100);
+            match mcp_core.webhook_dead_letters(limit).await {
+                Ok(dead_letters) => Ok(json!({ "dead_letters": dead_letters })),
+                Err(e) => Err(json!({
+                    "code": -32000,
+                    "message": format!("Failed to read the dead letters: {}", e)
+                })),
+            }
+        }
+        "server/info" => Ok(json!({
+            "name": mcp_router.name(),
+            "version": mcp_router.version(),
+            "storage": mcp_core.tool_registry.read().await.storage().status(),
+            "read_only": mcp_core.is_read_only().await,
         })),
+        "read_only/set" => handle_set_read_only(mcp_core, request.params).await,
+        "history/undoable" => match mcp_core.undoable_changes().await {
+            Ok(entries) => Ok(json!({ "entries": entries })),
+            Err(e) => Err(json!({
+                "code": -32000,
+                "message": format!("Failed to read the undo journal: {}", e)
+            })),
+        },
+        "history/undo" => handle_undo(mcp_core, request.params).await,
+        _ => Err(method_not_found(&request.method)),
     };
 
+    // Nothing answers a notification, not even its errors
+    let Some(id) = request.id else {
+        if let Err(error) = result {
+            debug!("Notification {} failed: {}", request.method, error);
+        }
+        return None;
+    };
     match result {
-        Ok(result) => Json(JsonRpcResponse {
+        Ok(result) => Some(JsonRpcResponse {
             jsonrpc: "2.0".to_string(),
-            id: request.id,
+            id,
             result: Some(result),
             error: None,
         }),
         Err(error) => {
-            let error_obj = error.as_object().unwrap();
-            Json(JsonRpcResponse {
+            let error_obj = error_object(&request.method, error);
+            let message = error_obj
+                .get("message")
+                .and_then(|v| v.as_str())
+                .unwrap_or("Unknown error")
+                .to_string();
+            // Let clients tell a database outage from other failures
+            let data = error_obj.get("data").cloned().or_else(|| {
+                StorageUnavailable::is(&message)
+                    .then(|| json!({ "reason": STORAGE_UNAVAILABLE_REASON }))
+            });
+            Some(JsonRpcResponse {
                 jsonrpc: "2.0".to_string(),
-                id: request.id,
+                id,
                 result: None,
                 error: Some(JsonRpcError {
                     code: error_obj
                         .get("code")
                         .and_then(|v| v.as_i64())
                         .unwrap_or(-32000) as i32,
-                    message: error_obj
-                        .get("message")
-                        .and_then(|v| v.as_str())
-                        .unwrap_or("Unknown error")
-                        .to_string(),
-                    data: None,
+                    message,
+                    data,
                 }),
             })
         }
     }
 }
 
+/// Fields of the error a handler answered, an internal error when it isn't an object
+fn error_object(method: &str, error: Value) -> Map<String, Value> {
+    match error {
+        Value::Object(error) => error,
+        error => {
+            warn!("Method {} failed with a malformed error: {}", method, error);
+            let message = match error {
+                Value::String(message) => message,
+                error => error.to_string(),
+            };
+            Map::from_iter([
+                ("code".to_string(), json!(-32603)),
+                ("message".to_string(), json!(message)),
+            ])
+        }
+    }
+}
+
+fn method_not_found(method: &str) -> Value {
+    json!({
+        "code": -32601,
+        "message": format!("Method '{}' not found", method)
+    })
+}
+
 async fn handle_list_tools(mcp_core: MCPCore) -> Result<ServerToolsResponse, ErrorResponse> {
-    // Get the installed tools from MCPCore
-    let result = mcp_core.list_all_server_tools().await;
+    // Get the installed tools from MCPCore, those of the last run while the servers start
+    let result = mcp_core.served_server_tools().await;
 
     // Check if tools are hidden
     let mcp_state = mcp_core.mcp_state.read().await;
     let are_tools_hidden = mcp_state.are_tools_hidden.read().await;
-    
-    // Create a list of built-in tools converted to ServerToolInfo format
+
+    // Built-in tools are listed first, unless tools are hidden
     let built_in_tools = if *are_tools_hidden {
-        vec![] // Return empty array if tools are hidden
+        vec![]
     } else {
-        vec![
-            ServerToolInfo {
-                id: TOOL_REGISTER_SERVER.to_string(),
-                name: TOOL_REGISTER_SERVER.to_string(),
-                description: get_register_server_tool().description.clone(),
-                server_id: "builtin".to_string(),
-                proxy_id: None,
-                is_active: true,
-                input_schema: Some(InputSchema {
-                    r#type: "object".to_string(),
-                    properties: HashMap::from_iter(
-                        serde_json::from_value::<HashMap<String, InputSchemaProperty>>(
-                            get_register_server_tool().input_schema.get("properties")
-                                .cloned()
-                                .unwrap_or_else(|| json!({}))
-                        ).unwrap_or_default()
-                    ),
-                    required: get_register_server_tool().input_schema.get("required")
-                        .and_then(|v| v.as_array())
-                        .map(|arr| arr.iter()
-                            .filter_map(|item| item.as_str().map(|s| s.to_string()))
-                            .collect())
-                        .unwrap_or_default(),
-                    ..Default::default()
-                }),
-            },
-            ServerToolInfo {
-                id: TOOL_SEARCH_SERVER.to_string(),
-                name: TOOL_SEARCH_SERVER.to_string(),
-                description: get_search_server_tool().description.clone(),
-                server_id: "builtin".to_string(),
-                proxy_id: None,
-                is_active: true,
-                input_schema: Some(InputSchema {
-                    r#type: "object".to_string(),
-                    properties: HashMap::from_iter(
-                        serde_json::from_value::<HashMap<String, InputSchemaProperty>>(
-                            get_search_server_tool().input_schema.get("properties")
-                                .cloned()
-                                .unwrap_or_else(|| json!({}))
-                        ).unwrap_or_default()
-                    ),
-                    required: get_search_server_tool().input_schema.get("required")
-                        .and_then(|v| v.as_array())
-                        .map(|arr| arr.iter()
-                            .filter_map(|item| item.as_str().map(|s| s.to_string()))
-                            .collect())
-                        .unwrap_or_default(),
-                    ..Default::default()
-                }),
-            },
-            ServerToolInfo {
-                id: TOOL_CONFIGURE_SERVER.to_string(),
-                name: TOOL_CONFIGURE_SERVER.to_string(),
-                description: get_configure_server_tool().description.clone(),
-                server_id: "builtin".to_string(),
-                proxy_id: None,
-                is_active: true,
-                input_schema: Some(InputSchema {
-                    r#type: "object".to_string(),
-                    properties: HashMap::from_iter(
-                        serde_json::from_value::<HashMap<String, InputSchemaProperty>>(
-                            get_configure_server_tool().input_schema.get("properties")
-                                .cloned()
-                                .unwrap_or_else(|| json!({}))
-                        ).unwrap_or_default()
-                    ),
-                    required: get_configure_server_tool().input_schema.get("required")
-                        .and_then(|v| v.as_array())
-                        .map(|arr| arr.iter()
-                            .filter_map(|item| item.as_str().map(|s| s.to_string()))
-                            .collect())
-                        .unwrap_or_default(),
-                    ..Default::default()
-                }),
-            },
-            ServerToolInfo {
-                id: TOOL_UNINSTALL_SERVER.to_string(),
-                name: TOOL_UNINSTALL_SERVER.to_string(),
-                description: get_uninstall_server_tool().description.clone(),
-                server_id: "builtin".to_string(),
-                proxy_id: None,
-                is_active: true,
-                input_schema: Some(InputSchema {
-                    r#type: "object".to_string(),
-                    properties: HashMap::from_iter(
-                        serde_json::from_value::<HashMap<String, InputSchemaProperty>>(
-                            get_uninstall_server_tool().input_schema.get("properties")
-                                .cloned()
-                                .unwrap_or_else(|| json!({}))
-                        ).unwrap_or_default()
-                    ),
-                    required: get_uninstall_server_tool().input_schema.get("required")
-                        .and_then(|v| v.as_array())
-                        .map(|arr| arr.iter()
-                            .filter_map(|item| item.as_str().map(|s| s.to_string()))
-                            .collect())
-                        .unwrap_or_default(),
-                    ..Default::default()
-                }),
-            },
-            ServerToolInfo {
-                id: TOOL_LIST_INSTALLED_SERVERS.to_string(),
-                name: TOOL_LIST_INSTALLED_SERVERS.to_string(),
-                description: get_list_installed_servers_tool().description.clone(),
-                server_id: "builtin".to_string(),
-                proxy_id: None,
-                is_active: true,
-                input_schema: Some(InputSchema {
-                    r#type: "object".to_string(),
-                    properties: HashMap::from_iter(
-                        serde_json::from_value::<HashMap<String, InputSchemaProperty>>(
-                            get_list_installed_servers_tool().input_schema.get("properties")
-                                .cloned()
-                                .unwrap_or_else(|| json!({}))
-                        ).unwrap_or_default()
-                    ),
-                    required: get_list_installed_servers_tool().input_schema.get("required")
-                        .and_then(|v| v.as_array())
-                        .map(|arr| arr.iter()
-                            .filter_map(|item| item.as_str().map(|s| s.to_string()))
-                            .collect())
-                        .unwrap_or_default(),
-                    ..Default::default()
-                }),
-            }
-        ]
+        get_builtin_tool_infos()
     };
 
     match result {
-        Ok(tools) => {
+        Ok((tools, stale)) => {
             // Add built-in tools first, then user-installed tools
             let mut all_tools = built_in_tools;
-            
+
             // Add the user-installed tools
             let tools_with_defaults: Vec<ServerToolInfo> = tools
                 .into_iter()
@@ -411,11 +754,12 @@ async fn handle_list_tools(mcp_core: MCPCore) -> Result<ServerToolsResponse, Err
                     tool
                 })
                 .collect();
-            
+
             all_tools.extend(tools_with_defaults);
 
             Ok(ServerToolsResponse {
                 tools: all_tools,
+                stale,
             })
         }
         Err(e) => Err(ErrorResponse {
@@ -434,11 +778,39 @@ struct ToolRegistrationRequestByName {
     r#type: String,
     configuration: Option<ServerConfiguration>,
     distribution: Option<Distribution>,
+    #[serde(default)]
+    authentication: Option<Value>,
+    #[serde(default, alias = "auto_start")]
+    enabled: Option<bool>,
 }
 #[derive(Deserialize, Debug)]
 
 struct ToolRegistrationRequestById {
     tool_id: String,
+    #[serde(default, alias = "auto_start")]
+    enabled: Option<bool>,
+}
+
+impl KnownFields for ToolRegistrationRequestByName {
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "name",
+        "description",
+        "type",
+        "configuration",
+        "distribution",
+        "authentication",
+        "enabled",
+        "auto_start",
+    ];
+
+    fn unknown_fields_at(value: &Value, prefix: &str) -> Vec<String> {
+        registration_fields_at(value, Self::FIELDS, prefix)
+    }
+}
+
+impl KnownFields for ToolRegistrationRequestById {
+    const FIELDS: &'static [&'static str] = &["tool_id", "enabled", "auto_start"];
 }
 
 #[derive(Deserialize, Debug)]
@@ -449,15 +821,163 @@ enum ToolRegistrationRequest {
     ById(ToolRegistrationRequestById),
 }
 
+/// Who asked for an installation, recorded in the server provenance
+#[derive(Clone, Debug)]
+pub enum InstallOrigin {
+    /// The app, the CLI or a JSON-RPC client
+    Api,
+    /// A model, through the built-in tools of an MCP session
+    McpSession { client: Option<String> },
+}
+
+impl InstallOrigin {
+    fn provenance(&self, source: ProvenanceSource, source_id: Option<String>) -> ServerProvenance {
+        match self {
+            InstallOrigin::Api => ServerProvenance::new(source, source_id),
+            InstallOrigin::McpSession { client } => {
+                ServerProvenance::llm(client.clone(), source_id)
+            }
+        }
+    }
+}
+
 pub async fn handle_register_tool(
     mcp_core: MCPCore,
     params: Value,
+    origin: InstallOrigin,
+) -> Result<ServerRegistrationResponse, ErrorResponse> {
+    let operation_id = Uuid::new_v4().to_string();
+    install_tool(mcp_core, params, operation_id, None, origin).await
+}
+
+/// Handle `registry/install`
+///
+/// Besides the registration fields, the params accept:
+/// - `async` (or `stream`): run the install as a tracked operation and return its id
+///   right away
+/// - `sessionId`: SSE session that should receive the progress as notifications
+///
+/// Progress can always be followed on `/mcp/events?operationId=<id>`.
+async fn handle_install_request(mcp_core: MCPCore, mut params: Value) -> Result<Value, Value> {
+    let run_async = take_async_flag(&mut params, &["async", "stream"]);
+    let session_id = params
+        .as_object_mut()
+        .and_then(|obj| obj.remove("sessionId"))
+        .and_then(|v| v.as_str().map(|s| s.to_string()));
+    let operation_id = Uuid::new_v4().to_string();
+
+    // Fail before starting anything, with the fields at fault in the error data
+    if let Err(errors) = check_registration_params(&params) {
+        return Err(errors.to_rpc_error());
+    }
+
+    if let Some(session_id) = session_id {
+        tokio::spawn(forward_operation_to_session(
+            mcp_core.clone(),
+            operation_id.clone(),
+            session_id,
+        ));
+    }
+
+    if run_async {
+        let server_id = params
+            .get("tool_id")
+            .or_else(|| params.get("id"))
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string());
+        let core = mcp_core.clone();
+        let id = mcp_core.operations.spawn_with_id(
+            operation_id.clone(),
+            "install",
+            server_id,
+            true,
+            move |operation| async move {
+                install_tool(
+                    core,
+                    params,
+                    operation_id,
+                    Some(operation),
+                    InstallOrigin::Api,
+                )
+                .await
+                .map(|response| serde_json::to_value(response).unwrap())
+                .map_err(|error| error.message)
+            },
+        );
+        return Ok(operation_started(&id));
+    }
+
+    match install_tool(mcp_core, params, operation_id, None, InstallOrigin::Api).await {
+        Ok(response) => Ok(serde_json::to_value(response).unwrap()),
+        Err(error) => Err(serde_json::to_value(error).unwrap()),
+    }
+}
+
+/// Remove the flags asking for a background operation from the params
+fn take_async_flag(params: &mut Value, flags: &[&str]) -> bool {
+    let Some(obj) = params.as_object_mut() else {
+        return false;
+    };
+    flags.iter().fold(false, |run_async, flag| {
+        let set = obj.remove(*flag).and_then(|v| v.as_bool()).unwrap_or(false);
+        run_async || set
+    })
+}
+
+/// Response returned when an action was started as a background operation
+fn operation_started(operation_id: &str) -> Value {
+    json!({
+        "operation_id": operation_id,
+        "status": "queued",
+        "events": format!("/mcp/events?operationId={}", operation_id)
+    })
+}
+
+/// Run a JSON-RPC handler as a tracked operation
+fn spawn_operation<Fut>(
+    mcp_core: &MCPCore,
+    kind: &str,
+    server_id: Option<String>,
+    cancellable: bool,
+    handler: Fut,
+) -> Value
+where
+    Fut: std::future::Future<Output = Result<Value, Value>> + Send + 'static,
+{
+    let operation_id = mcp_core
+        .operations
+        .spawn(kind, server_id, cancellable, |_| async move {
+            handler.await.map_err(|error| {
+                error
+                    .get("message")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("Unknown error")
+                    .to_string()
+            })
+        });
+    operation_started(&operation_id)
+}
+
+/// Install a tool, publishing its progress under `operation_id`
+async fn install_tool(
+    mcp_core: MCPCore,
+    params: Value,
+    operation_id: String,
+    operation: Option<OperationHandle>,
+    origin: InstallOrigin,
 ) -> Result<ServerRegistrationResponse, ErrorResponse> {
-    println!("[INSTALLATION] handle_register_tool: params {:?}", params);
+    log_payload("Registering server", &params);
+    if let Err(errors) = check_registration_params(&params) {
+        return Err(ErrorResponse {
+            code: -32602,
+            message: errors.to_string(),
+        });
+    }
+    let raw_params = params.clone();
     let params = match serde_json::from_value(params) {
         Ok(params) => params,
         Err(error) => {
-            println!("[INSTALLATION] handle_register_tool: error {:?}", error);
+            debug!("Invalid registration params: {}", error);
             return Err(ErrorResponse {
                 code: -32602,
                 message: format!(
@@ -468,186 +988,320 @@ pub async fn handle_register_tool(
         }
     };
 
-    match params {
+    let unknown = match &params {
+        ToolRegistrationRequest::ByName(_) => {
+            ToolRegistrationRequestByName::unknown_fields(&raw_params)
+        }
+        ToolRegistrationRequest::ById(_) => {
+            ToolRegistrationRequestById::unknown_fields(&raw_params)
+        }
+    };
+    if !unknown.is_empty() {
+        warn!(
+            "Ignoring unknown registration fields: {}",
+            unknown.join(", ")
+        );
+    }
+    let warnings = unknown_field_warnings(&unknown);
+
+    let (tool, progress) = match params {
         ToolRegistrationRequest::ByName(request) => {
-            println!(
-                "[INSTALLATION] handle_register_tool: request (BY NAME) {:?}",
-                request
+            debug!("Registering server {} by name", request.id);
+            let progress = InstallProgressReporter::new(
+                mcp_core.event_bus.clone(),
+                operation_id,
+                request.id.clone(),
+            )
+            .with_operation(operation);
+            progress.report(
+                InstallStage::Resolving,
+                Some("using the provided configuration".to_string()),
             );
-            let tool_id = request.id;
-            let tool_name = request.name;
-            let description = request.description;
-            let tools_type = request.r#type;
-            let configuration = request.configuration;
-            let distribution = request.distribution;
 
             let tool = ServerRegistrationRequest {
-                server_id: tool_id.clone(),
-                server_name: tool_name,
-                description,
-                tools_type,
-                configuration,
-                distribution,
+                server_id: request.id,
+                server_name: request.name,
+                description: request.description,
+                tools_type: request.r#type,
+                configuration: request.configuration,
+                distribution: request.distribution,
+                provenance: Some(origin.provenance(ProvenanceSource::Manual, None)),
+                authentication: request.authentication,
+                enabled: request.enabled,
             };
-
-            println!("[POST] handle_register_tool: tool {:?}", tool);
-            let r = mcp_core.register_server(tool).await;
-            println!("[INSTALLATION] handle_register_tool: r {:?}", r);
-            Ok(ServerRegistrationResponse {
-                success: true,
-                message: "Tool installed successfully".to_string(),
-                tool_id: Some(tool_id),
-            })
+            (tool, progress)
         }
         ToolRegistrationRequest::ById(request) => {
-            println!(
-                "[INSTALLATION] handle_register_tool: request (BY ID) {:?}",
-                request
-            );
+            debug!("Registering server {} from the registry", request.tool_id);
             let tool_id = request.tool_id;
+            let enabled = request.enabled;
+            let progress = InstallProgressReporter::new(
+                mcp_core.event_bus.clone(),
+                operation_id,
+                tool_id.clone(),
+            )
+            .with_operation(operation);
+            progress.report(
+                InstallStage::Resolving,
+                Some("looking up the registry".to_string()),
+            );
 
-            let registry = fetch_tool_from_registry().await?;
+            let registry = match fetch_tool_from_registry(&mcp_core).await {
+                Ok(registry) => registry,
+                Err(error) => {
+                    progress.report(InstallStage::Failed, Some(error.message.clone()));
+                    return Err(error);
+                }
+            };
 
-            let tool = registry
+            let tool = match registry
                 .tools
                 .iter()
-                .find(|tool| tool.id.as_str() == tool_id);
-            if tool.is_none() {
+                .find(|tool| tool.id.as_str() == tool_id)
+            {
+                Some(tool) => tool,
+                None => {
+                    let message = format!("Tool {} not found", tool_id);
+                    progress.report(InstallStage::Failed, Some(message.clone()));
+                    return Err(ErrorResponse {
+                        code: -32000,
+                        message,
+                    });
+                }
+            };
+            debug!("Building server {} from its registry entry", tool.id);
+            if let Err(errors) = resolve_command(Some(&tool.config), None, REGISTRY_CONFIG_FIELD) {
+                let message = format!("Tool {} cannot be installed: {}", tool_id, errors);
+                progress.report(InstallStage::Failed, Some(message.clone()));
                 return Err(ErrorResponse {
                     code: -32000,
-                    message: format!("Tool {} not found", tool_id),
+                    message,
                 });
             }
-            let tool = tool.unwrap();
-            println!("Building tool from registry: {:?}", tool);
-            let r = mcp_core
-                .register_server(ServerRegistrationRequest {
-                    server_id: tool_id.clone(),
-                    server_name: tool.name.clone(),
-                    description: tool.description.clone(),
-                    tools_type: tool.runtime.clone(),
-                    configuration: Some(tool.config.clone()),
-                    distribution: Some(tool.distribution.clone()),
-                })
-                .await;
-            println!("[INSTALLATION] handle_register_tool: r {:?}", r);
-            Ok(ServerRegistrationResponse {
-                success: true,
-                message: "Tool installed successfully".to_string(),
-                tool_id: Some(tool_id.clone()),
-            })
-        }
-    }
-}
-
-pub async fn fetch_tool_from_registry() -> Result<RegistryToolsResponse, ErrorResponse> {
-    // Check if we have a valid cache
-    let use_cache = {
-        let cache = REGISTRY_CACHE.lock().await;
-        if let (Some(data), Some(timestamp)) = (&cache.data, cache.timestamp) {
-            if timestamp.elapsed() < CACHE_DURATION {
-                // Cache is still valid
-                match serde_json::from_value::<RegistryToolsResponse>(data.clone()) {
-                    Ok(response) => Some(response),
-                    Err(_) => None,
-                }
-            } else {
-                None
-            }
-        } else {
-            None
+            // Kept so that the server is looked up in the same source later on
+            let mut provenance =
+                origin.provenance(ProvenanceSource::Registry, Some(tool_id.clone()));
+            provenance.registry_source = tool
+                .source
+                .clone()
+                .filter(|source| source != DEFAULT_SOURCE_ID);
+            let tool = ServerRegistrationRequest {
+                server_id: tool_id.clone(),
+                server_name: tool.name.clone(),
+                description: tool.description.clone(),
+                tools_type: tool.runtime.clone(),
+                configuration: Some(tool.config.clone()),
+                distribution: Some(tool.distribution.clone()),
+                provenance: Some(provenance),
+                authentication: None,
+                enabled,
+            };
+            (tool, progress)
         }
     };
 
-    // If we have valid cached data, return it
-    if let Some(cached_data) = use_cache {
-        return Ok(cached_data);
+    if let Some(missing) = missing_runtime(&tool.tools_type).await {
+        progress.report(
+            InstallStage::Resolving,
+            Some(format!("{} does not seem to be installed", missing)),
+        );
     }
 
-    // Cache is invalid or doesn't exist, fetch fresh data
-    // Fetch tools from remote URL
-    // All Tools: Stable & Unstable
-    let tools_url =
-        "https://pub-5e2d77d67aac45ef811998185d312005.r2.dev/registry/registry.all.json";
-    // Stable Only
-    // let tools_url =
-    //     "https://pub-5e2d77d67aac45ef811998185d312005.r2.dev/registry/registry.stable.json";
-    // Unstable Only
-    // let tools_url =
-    //     "https://pub-5e2d77d67aac45ef811998185d312005.r2.dev/registry/registry.unstable.json";
-
-    let client = reqwest::Client::builder().build().unwrap_or_default();
-
-    let response = client
-        .get(tools_url)
-        .header("Accept-Encoding", "gzip")
-        .header("User-Agent", "MCP-Core/1.0")
-        .send()
-        .await
-        .map_err(|e| ErrorResponse {
-            code: -32000,
-            message: format!("Failed to fetch tools from registry: {}", e),
-        })?;
-
-    let raw = response.json().await.map_err(|e| ErrorResponse {
-        code: -32000,
-        message: format!("Failed to parse tools from registry: {}", e),
-    })?;
-
-    let tool_wrapper: RegistryToolsResponse =
-        serde_json::from_value(raw).map_err(|e| ErrorResponse {
+    let server_id = tool.server_id.clone();
+    let r = mcp_core
+        .register_server_with_progress(tool, Some(progress))
+        .await;
+    debug!(
+        "Registration of {} finished: {}",
+        server_id,
+        match &r {
+            Ok(response) => response.message.as_str(),
+            Err(e) => e.as_str(),
+        }
+    );
+    match r {
+        Ok(mut response) => {
+            response.message = "Tool installed successfully".to_string();
+            response.warnings.extend(warnings);
+            Ok(response)
+        }
+        Err(e) => Err(ErrorResponse {
             code: -32000,
-            message: format!("Failed to parse tools from registry: {}", e),
-        })?;
-
-    println!("[TOOLS] found # tools {:?}", tool_wrapper.tools.len());
-
-    // let result = RegistryToolsResponse { tools };
-
-    // Update the cache with new data
-    {
-        let mut cache = REGISTRY_CACHE.lock().await;
-        cache.data = Some(serde_json::to_value(&tool_wrapper).unwrap_or_default());
-        cache.timestamp = Some(Instant::now());
+            message: format!("Failed to install tool: {}", e),
+        }),
     }
-
-    Ok(tool_wrapper)
 }
 
-async fn handle_list_all_tools(mcp_core: MCPCore) -> Result<Value, Value> {
-    let mcp_state = mcp_core.mcp_state.read().await;
-    let registry = mcp_state.tool_registry.read().await;
-    let installed_tools = registry.get_all_servers()?;
-    let registry_tools_result = fetch_tool_from_registry().await;
+/// Check the configuration of a registration by name before it is deserialized
+///
+/// Installs from the registry are checked once the registry entry is known.
+fn check_registration_params(params: &Value) -> Result<(), ConfigErrors> {
+    if params.get("tool_id").is_some() {
+        return Ok(());
+    }
+    check_configuration_value(params.get(CONFIGURATION_FIELD), CONFIGURATION_FIELD)
+}
 
-    let mut registry_tools = match registry_tools_result {
-        Ok(response) => serde_json::to_value(response).unwrap_or(json!({"tools": []})),
-        Err(error) => return Err(serde_json::to_value(error).unwrap()),
+/// Name of the runtime a server type needs when it can't be found
+async fn missing_runtime(tools_type: &str) -> Option<&'static str> {
+    let (name, installed) = match tools_type {
+        "node" | "nodejs" => ("Node.js", MCPCore::is_nodejs_installed().await),
+        "python" => ("uv", MCPCore::is_uv_installed().await),
+        "docker" => ("Docker", MCPCore::is_docker_installed().await),
+        _ => return None,
     };
+    match installed {
+        Ok(true) => None,
+        _ => Some(name),
+    }
+}
 
-    for tool in registry_tools
-        .get_mut("tools")
-        .unwrap()
-        .as_array_mut()
-        .unwrap()
-    {
-        let tool_name = tool.get("name").unwrap().as_str().unwrap();
-        if installed_tools.contains_key(tool_name) {
-            tool.as_object_mut()
-                .unwrap()
-                .insert("installed".to_string(), json!(true));
-        } else {
-            tool.as_object_mut()
-                .unwrap()
-                .insert("installed".to_string(), json!(false));
+/// Forward the progress of an operation to a client session as notifications
+async fn forward_operation_to_session(mcp_core: MCPCore, operation_id: String, session_id: String) {
+    let sessions = mcp_core.mcp_state.read().await.sessions.clone();
+    let (past, mut receiver) = mcp_core
+        .event_bus
+        .subscribe_with_history(|event| event.operation_id() == Some(operation_id.as_str()));
+    let mut pending: std::collections::VecDeque<EventEnvelope> = past.into();
+
+    loop {
+        let envelope = match pending.pop_front() {
+            Some(envelope) => envelope,
+            None => match receiver.recv().await {
+                Ok(envelope) => {
+                    if envelope.event.operation_id() != Some(operation_id.as_str()) {
+                        continue;
+                    }
+                    envelope
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!("Session {} missed {} progress events", session_id, skipped);
+                    continue;
+                }
+                Err(RecvError::Closed) => return,
+            },
+        };
+
+        let method = match envelope.event {
+            DockmasterEvent::InstallProgress(_) => "notifications/install_progress",
+            DockmasterEvent::OperationUpdate(_) => "notifications/operation_update",
+            DockmasterEvent::ApprovalRequested(_) => "notifications/approval_requested",
+            DockmasterEvent::ApprovalResolved(_) => "notifications/approval_resolved",
+            // Not tied to an operation
+            DockmasterEvent::OrphansCleaned(_)
+            | DockmasterEvent::ToolsUpdated(_)
+            | DockmasterEvent::ServerReconnected(_)
+            | DockmasterEvent::StartupProgress(_)
+            | DockmasterEvent::UpdateAvailable(_)
+            | DockmasterEvent::ToolsBudgetExceeded(_)
+            | DockmasterEvent::EnvironmentChanged(_) => continue,
+        };
+        let notification = json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": envelope.event
+        });
+        let message = serde_json::to_string(&notification).unwrap_or_default();
+        if let Err(e) = sessions.send(&session_id, &message).await {
+            warn!("Stopped forwarding install progress: {}", e);
+            return;
+        }
+        if mcp_core.operations.is_final_event(&envelope.event) {
+            return;
         }
     }
+}
 
-    Ok(registry_tools)
+/// Get the server registry through the registry cache of `mcp_core`, merged with the
+/// registry sources
+pub async fn fetch_tool_from_registry(
+    mcp_core: &MCPCore,
+) -> Result<RegistryToolsResponse, ErrorResponse> {
+    mcp_core.merged_registry().await
 }
 
-async fn handle_list_prompts() -> Result<Value, Value> {
-    Ok(json!({
+/// What installing a registry entry would set up, with the variables it declares
+async fn handle_preview_install(mcp_core: MCPCore, params: Value) -> Result<Value, Value> {
+    let Some(tool_id) = params.get("tool_id").and_then(|v| v.as_str()) else {
+        return Err(json!({
+            "code": -32602,
+            "message": "Missing tool_id parameter"
+        }));
+    };
+    let registry = fetch_tool_from_registry(&mcp_core)
+        .await
+        .map_err(|error| serde_json::to_value(error).unwrap())?;
+    match registry.tools.iter().find(|tool| tool.id == tool_id) {
+        Some(tool) => Ok(json!(InstallPreview::from_registry_tool(tool))),
+        None => Err(json!({
+            "code": -32000,
+            "message": format!("Tool {} not found", tool_id)
+        })),
+    }
+}
+
+/// Variables of an installed server and the required ones without a value
+async fn handle_config_schema(mcp_core: MCPCore, params: Value) -> Result<Value, Value> {
+    let Some(server_id) = params.get("server_id").and_then(|v| v.as_str()) else {
+        return Err(json!({
+            "code": -32602,
+            "message": "Missing server_id parameter"
+        }));
+    };
+    let server_id = mcp_core.resolve_server_id(server_id).await;
+    let server = mcp_core
+        .registry_lookup({
+            let server_id = server_id.clone();
+            move |registry| registry.get_server(&server_id)
+        })
+        .await
+        .unwrap_or_else(|| Err("The database didn't answer".to_string()))
+        .map_err(|e| {
+            json!({
+                "code": -32000,
+                "message": format!("Failed to read the configuration of {}: {}", server_id, e)
+            })
+        })?;
+    Ok(json!(ConfigSchema::new(
+        &server_id,
+        server.configuration.as_ref()
+    )))
+}
+
+/// Tools of the registry, each marked as installed or not
+///
+/// Installed servers come from memory, a stalled database never holds the answer back,
+/// see [`MCPCore::installed_servers`].
+pub async fn handle_list_all_tools(mcp_core: MCPCore) -> Result<Value, Value> {
+    let installed_tools = mcp_core.installed_servers().await?;
+    let registry_tools_result = fetch_tool_from_registry(&mcp_core).await;
+
+    let mut registry_tools = match registry_tools_result {
+        Ok(response) => serde_json::to_value(response).unwrap_or(json!({"tools": []})),
+        Err(error) => return Err(serde_json::to_value(error).unwrap()),
+    };
+
+    // Entries without a name are left as they are
+    let tools = registry_tools
+        .get_mut("tools")
+        .and_then(Value::as_array_mut)
+        .into_iter()
+        .flatten();
+    for tool in tools {
+        let installed = match tool.get("name").and_then(Value::as_str) {
+            Some(tool_name) => installed_tools.contains_key(tool_name),
+            None => continue,
+        };
+        if let Some(tool) = tool.as_object_mut() {
+            tool.insert("installed".to_string(), json!(installed));
+        }
+    }
+
+    Ok(registry_tools)
+}
+
+async fn handle_list_prompts() -> Result<Value, Value> {
+    Ok(json!({
         "prompts": []
     }))
 }
@@ -658,183 +1312,1164 @@ async fn handle_list_resources() -> Result<Value, Value> {
     }))
 }
 
-async fn handle_read_resource(_params: Value) -> Result<Value, Value> {
-    Err(json!({
-        "code": -32601,
-        "message": "Resource reading not implemented yet"
-    }))
+async fn handle_read_resource(_params: Value) -> Result<Value, Value> {
+    Err(json!({
+        "code": -32601,
+        "message": "Resource reading not implemented yet"
+    }))
+}
+
+async fn handle_get_prompt(_params: Value) -> Result<Value, Value> {
+    Err(json!({
+        "code": -32601,
+        "message": "Prompt retrieval not implemented yet"
+    }))
+}
+
+async fn handle_invoke_tool(
+    mcp_core: MCPCore,
+    params: Value,
+    call_id: Option<String>,
+) -> Result<Value, Value> {
+    let tool_name = match params.get("name").and_then(|v| v.as_str()) {
+        Some(name) => name,
+        None => {
+            return Err(json!({
+                "code": -32602,
+                "message": "Missing name in parameters"
+            }))
+        }
+    };
+
+    let arguments = match params.get("arguments") {
+        Some(args) => args.clone(),
+        None => json!({}),
+    };
+
+    // Find which server has the requested tool, the server is called with its name
+    let found = {
+        let mcp_state = mcp_core.mcp_state.read().await.clone();
+        let tool = mcp_state.find_callable_tool(tool_name).await;
+        tool.map(|tool| proxy_tool_id(&tool.server_id, &tool.name))
+    };
+
+    match found {
+        Some(proxy_id) => {
+            let request = ToolExecutionRequest {
+                tool_id: proxy_id,
+                parameters: arguments,
+            };
+
+            match mcp_core
+                .execute_cancellable_proxy_tool(request, call_id, None)
+                .await
+            {
+                Ok(response) => {
+                    if response.success {
+                        let mut result = response.result.unwrap_or(json!(null));
+                        if !response.warnings.is_empty() {
+                            if let Some(result) = result.as_object_mut() {
+                                let meta = result.entry("_meta").or_insert_with(|| json!({}));
+                                if let Some(meta) = meta.as_object_mut() {
+                                    meta.insert(
+                                        TOOL_WARNINGS_META_KEY.to_string(),
+                                        json!(response.warnings),
+                                    );
+                                }
+                            }
+                        }
+                        Ok(result)
+                    } else if response.cancelled {
+                        Err(json!({
+                            "code": -32800,
+                            "message": CANCELLED_ERROR
+                        }))
+                    } else if let Some(rate_limited) = response.rate_limited {
+                        Err(json!({
+                            "code": -32000,
+                            "message": rate_limited.to_string(),
+                            "data": {
+                                "reason": RATE_LIMITED_REASON,
+                                "limit": rate_limited.limit,
+                                "retry_after_ms": rate_limited.retry_after_ms,
+                                "reset_at": rate_limited.reset_at,
+                            }
+                        }))
+                    } else if let Some(policy_error) = response.policy_error {
+                        Err(json!({
+                            "code": -32000,
+                            "message": policy_error.to_string(),
+                            "data": {
+                                "reason": POLICY_REJECTED_REASON,
+                                "hook": policy_error.hook,
+                                "stage": policy_error.stage,
+                                "detail": policy_error.reason,
+                            }
+                        }))
+                    } else {
+                        Err(json!({
+                            "code": -32000,
+                            "message": response.error.unwrap_or_else(|| "Unknown error".to_string())
+                        }))
+                    }
+                }
+                Err(e) => Err(json!({
+                    "code": -32000,
+                    "message": format!("Failed to execute tool: {}", e)
+                })),
+            }
+        }
+        None => Err(json!({
+            "code": -32601,
+            "message": format!("Tool '{}' not found", tool_name)
+        })),
+    }
+}
+
+async fn handle_import_server_from_url(mcp_core: MCPCore, params: Value) -> Result<Value, Value> {
+    let (request, unknown) = match parse_with_unknown_fields::<ServerImportRequest>(params) {
+        Ok(parsed) => parsed,
+        Err(error) => {
+            return Err(json!({
+                "code": -32602,
+                "message": format!("Missing URL parameter: {}", error)
+            }));
+        }
+    };
+    if !unknown.is_empty() {
+        warn!("Ignoring unknown import fields: {}", unknown.join(", "));
+    }
+    info!("Importing server from URL: {}", request.url);
+
+    match mcp_core
+        .import_server_from_url(request.url, request.enabled)
+        .await
+    {
+        Ok(mut response) => {
+            if response.success {
+                response.warnings.extend(unknown_field_warnings(&unknown));
+                Ok(json!({
+                    "success": true,
+                    "message": response.message,
+                    "server_id": response.server_id,
+                    "status": response.status,
+                    "warnings": response.warnings
+                }))
+            } else {
+                Err(json!({
+                    "code": -32000,
+                    "message": response.message
+                }))
+            }
+        }
+        Err(e) => Err(json!({
+            "code": -32000,
+            "message": format!("Failed to import server: {}", e)
+        })),
+    }
+}
+
+pub async fn handle_get_server_config(mcp_core: MCPCore, params: Value) -> Result<Value, Value> {
+    log_payload("Updating server configuration", &params);
+    let legacy_warning = legacy_server_id_warning(&params);
+    let (config, unknown) = match parse_with_unknown_fields::<ConfigUpdateRequest>(params) {
+        Ok(parsed) => parsed,
+        Err(error) => {
+            return Err(json!({
+                "code": -32602,
+                "message": format!("Invalid params - missing parameters for server config: {}", error)
+            }));
+        }
+    };
+    if !unknown.is_empty() {
+        warn!("Ignoring unknown config fields: {}", unknown.join(", "));
+    }
+
+    // Update the tool configuration and restart it, undone if it then fails to start
+    let result = mcp_core
+        .apply_server_config(ServerConfigUpdateRequest {
+            server_id: config.server_id.clone(),
+            config: config.config,
+            inherit_env: config.inherit_env,
+            cwd: config.cwd,
+        })
+        .await
+        .map_err(|e| {
+            json!({
+                "code": -32000,
+                "message": format!("Failed to update configuration: {}", e)
+            })
+        })?;
+    let mut warnings = unknown_field_warnings(&unknown);
+    warnings.extend(legacy_warning);
+    config_apply_response(&config.server_id, result, warnings)
+}
+
+/// Answer with the outcome of a configuration change, an error unless the server runs
+/// with the new configuration or the change was rolled back
+fn config_apply_response(
+    server_id: &str,
+    result: ConfigApplyResult,
+    warnings: Vec<String>,
+) -> Result<Value, Value> {
+    if !result.success && !result.rolled_back {
+        let mut error = json!({
+            "code": -32000,
+            "message": result.message
+        });
+        if let Some(failure) = result.error {
+            error["data"] = json!({ "error": failure });
+        }
+        return Err(error);
+    }
+    let mut response = json!(result);
+    response["server_id"] = json!(server_id);
+    response["warnings"] = json!(warnings);
+    Ok(response)
+}
+
+/// Handle `server/config_history`: the last configurations of a server, or with
+/// `rollback_to` the rollback to one of them
+async fn handle_config_history(mcp_core: MCPCore, params: Value) -> Result<Value, Value> {
+    let Some(server_id) = params.get("server_id").and_then(|v| v.as_str()) else {
+        return Err(json!({
+            "code": -32602,
+            "message": "Missing server_id parameter"
+        }));
+    };
+
+    if let Some(rollback_to) = params.get("rollback_to") {
+        let Some(snapshot_id) = rollback_to.as_i64() else {
+            return Err(json!({
+                "code": -32602,
+                "message": "Invalid params - rollback_to must be the id of a snapshot"
+            }));
+        };
+        let result = mcp_core
+            .rollback_server_config(server_id, snapshot_id)
+            .await
+            .map_err(|e| {
+                json!({
+                    "code": -32000,
+                    "message": format!("Failed to roll back configuration: {}", e)
+                })
+            })?;
+        return config_apply_response(server_id, result, Vec::new());
+    }
+
+    let limit = params
+        .get("limit")
+        .and_then(|v| v.as_i64())
+        .unwrap_or(CONFIG_HISTORY_KEPT);
+    match mcp_core.config_history(server_id, limit).await {
+        Ok(snapshots) => Ok(json!({ "snapshots": snapshots })),
+        Err(e) => Err(json!({
+            "code": -32000,
+            "message": format!("Failed to read the configuration history: {}", e)
+        })),
+    }
+}
+
+async fn handle_restart_server(mcp_core: MCPCore, params: Value) -> Result<Value, Value> {
+    let Some(server_id) = params.get("server_id").and_then(|v| v.as_str()) else {
+        return Err(json!({
+            "code": -32602,
+            "message": "Missing server_id parameter"
+        }));
+    };
+
+    // Restarts a faulted server too
+    let force = params
+        .get("force")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    match mcp_core
+        .restart_server_command(server_id.to_string(), force)
+        .await
+    {
+        Ok(response) if response.success => Ok(json!({ "message": response.message })),
+        Ok(response) => Err(json!({
+            "code": -32000,
+            "message": response.message
+        })),
+        Err(e) => Err(json!({
+            "code": -32000,
+            "message": format!("Failed to restart server: {}", e)
+        })),
+    }
+}
+
+async fn handle_start_server(mcp_core: MCPCore, params: Value) -> Result<Value, Value> {
+    let Some(server_id) = params.get("server_id").and_then(|v| v.as_str()) else {
+        return Err(json!({
+            "code": -32602,
+            "message": "Missing server_id parameter"
+        }));
+    };
+
+    match mcp_core.start_server(server_id.to_string()).await {
+        Ok(response) if response.success => Ok(json!({ "message": response.message })),
+        Ok(response) => Err(json!({
+            "code": -32000,
+            "message": response.message
+        })),
+        Err(e) => Err(json!({
+            "code": -32000,
+            "message": format!("Failed to start server: {}", e)
+        })),
+    }
+}
+
+async fn handle_stop_server(mcp_core: MCPCore, params: Value) -> Result<Value, Value> {
+    let Some(server_id) = params.get("server_id").and_then(|v| v.as_str()) else {
+        return Err(json!({
+            "code": -32602,
+            "message": "Missing server_id parameter"
+        }));
+    };
+
+    match mcp_core.stop_server(server_id.to_string()).await {
+        Ok(response) if response.success => Ok(json!({ "message": response.message })),
+        Ok(response) => Err(json!({
+            "code": -32000,
+            "message": response.message
+        })),
+        Err(e) => Err(json!({
+            "code": -32000,
+            "message": format!("Failed to stop server: {}", e)
+        })),
+    }
+}
+
+/// Uninstall a server, answering how many tools are no longer listed
+async fn handle_delete_server(mcp_core: MCPCore, params: Value) -> Result<Value, Value> {
+    let Some(server_id) = params.get("server_id").and_then(|v| v.as_str()) else {
+        return Err(json!({
+            "code": -32602,
+            "message": "Missing server_id parameter"
+        }));
+    };
+
+    let request = ToolUninstallRequest {
+        server_id: server_id.to_string(),
+    };
+    match mcp_core.uninstall_server(request).await {
+        Ok(response) if response.success => Ok(json!({
+            "message": response.message,
+            "tools_removed": response.tools_removed
+        })),
+        Ok(response) => Err(json!({
+            "code": -32000,
+            "message": response.message
+        })),
+        Err(e) => Err(json!({
+            "code": -32000,
+            "message": format!("Failed to delete server: {}", e)
+        })),
+    }
+}
+
+/// Ids listed in the `server_ids` parameter, None when it is missing
+fn server_ids_param(params: &Value) -> Result<Option<Vec<String>>, Value> {
+    match params.get("server_ids") {
+        None | Some(Value::Null) => Ok(None),
+        Some(ids) => serde_json::from_value(ids.clone()).map(Some).map_err(|_| {
+            json!({
+                "code": -32602,
+                "message": "server_ids should be an array of server ids"
+            })
+        }),
+    }
+}
+
+async fn handle_bulk_update(mcp_core: MCPCore, params: Value) -> Result<Value, Value> {
+    let Some(server_ids) = server_ids_param(&params)? else {
+        return Err(json!({
+            "code": -32602,
+            "message": "Missing server_ids parameter"
+        }));
+    };
+    let Some(enabled) = params.get("enabled").and_then(|v| v.as_bool()) else {
+        return Err(json!({
+            "code": -32602,
+            "message": "Missing enabled parameter"
+        }));
+    };
+
+    match mcp_core.set_servers_enabled(server_ids, enabled).await {
+        Ok(response) => Ok(serde_json::to_value(response).unwrap()),
+        Err(e) => Err(json!({
+            "code": -32000,
+            "message": format!("Failed to update servers: {}", e)
+        })),
+    }
+}
+
+async fn handle_restart_all(mcp_core: MCPCore, params: Option<Value>) -> Result<Value, Value> {
+    let server_ids = match &params {
+        Some(params) => server_ids_param(params)?,
+        None => None,
+    };
+
+    match mcp_core.restart_servers(server_ids).await {
+        Ok(response) => Ok(serde_json::to_value(response).unwrap()),
+        Err(e) => Err(json!({
+            "code": -32000,
+            "message": format!("Failed to restart servers: {}", e)
+        })),
+    }
+}
+
+/// Migrate the servers of the legacy storage again and start those that are enabled
+async fn handle_migrate_legacy(mcp_core: MCPCore) -> Result<Value, Value> {
+    let report = mcp_core.migrate_legacy_database(true).await.map_err(|e| {
+        json!({
+            "code": -32000,
+            "message": format!("Failed to migrate legacy servers: {}", e)
+        })
+    })?;
+
+    let registry = mcp_core.tool_registry.read().await;
+    let enabled: Vec<String> = report
+        .migrated
+        .iter()
+        .filter(|id| registry.get_server(id).is_ok_and(|server| server.enabled))
+        .cloned()
+        .collect();
+    drop(registry);
+    let started = if enabled.is_empty() {
+        Vec::new()
+    } else {
+        match mcp_core.restart_servers(Some(enabled)).await {
+            Ok(response) => response.results,
+            Err(e) => {
+                warn!("Failed to start the migrated servers: {}", e);
+                Vec::new()
+            }
+        }
+    };
+
+    let mut response = serde_json::to_value(report).unwrap();
+    response["started"] = serde_json::to_value(started).unwrap();
+    Ok(response)
+}
+
+/// Report the unused rows of the database, and remove them when `dry_run` is false
+async fn handle_database_gc(mcp_core: MCPCore, params: Option<Value>) -> Result<Value, Value> {
+    let dry_run = params
+        .as_ref()
+        .and_then(|params| params.get("dry_run"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true);
+    match mcp_core.collect_database_garbage(dry_run).await {
+        Ok(report) => Ok(serde_json::to_value(report).unwrap()),
+        Err(e) => Err(json!({
+            "code": -32000,
+            "message": format!("Failed to collect database garbage: {}", e)
+        })),
+    }
+}
+
+async fn handle_migrate_paths(mcp_core: MCPCore, params: Option<Value>) -> Result<Value, Value> {
+    let server_ids = match params.as_ref().and_then(|params| params.get("server_ids")) {
+        None | Some(Value::Null) => None,
+        Some(server_ids) => Some(serde_json::from_value(server_ids.clone()).map_err(|e| {
+            json!({
+                "code": -32602,
+                "message": format!("Invalid server_ids parameter: {}", e)
+            })
+        })?),
+    };
+    let results = mcp_core
+        .migrate_server_paths(server_ids)
+        .await
+        .map_err(|e| {
+            json!({
+                "code": -32000,
+                "message": format!("Failed to migrate the paths of the servers: {}", e)
+            })
+        })?;
+    let report = mcp_core.migration_report().await.ok().flatten();
+    Ok(json!({ "results": results, "report": report }))
+}
+
+async fn handle_server_status(mcp_core: MCPCore, params: Value) -> Result<Value, Value> {
+    let Some(server_id) = params.get("server_id").and_then(|v| v.as_str()) else {
+        return Err(json!({
+            "code": -32602,
+            "message": "Missing server_id parameter"
+        }));
+    };
+
+    let servers = mcp_core.list_servers().await.map_err(|e| {
+        json!({
+            "code": -32000,
+            "message": format!("Failed to get server status: {}", e)
+        })
+    })?;
+    let Some(server) = servers.into_iter().find(|s| s.id.as_str() == server_id) else {
+        return Err(json!({
+            "code": -32000,
+            "message": format!("Server '{}' not found", server_id)
+        }));
+    };
+
+    let mcp_state = mcp_core.mcp_state.read().await.clone();
+    let security_mode = match mcp_state.security_mode(server_id).await {
+        Ok(mode) => serde_json::to_value(mode).unwrap(),
+        // A restricted server that cannot be sandboxed here won't start
+        Err(e) => json!({ "mode": "restricted", "error": e }),
+    };
+    let arch = match mcp_state.launch_arch(server_id).await {
+        Ok(arch) => serde_json::to_value(arch).unwrap(),
+        // A docker image without a variant for this machine won't start
+        Err(e) => json!({ "error": e }),
+    };
+    let tool_limits = mcp_core.tool_limit_report(server_id).await;
+    let warnings = tool_limits
+        .as_ref()
+        .map(|report| report.warnings())
+        .unwrap_or_default();
+
+    Ok(json!({
+        "server_id": server_id,
+        "status": server.status,
+        "enabled": server.definition.enabled,
+        "desired_running": server.desired_running,
+        "tool_count": server.tool_count,
+        "security_mode": security_mode,
+        "arch": arch,
+        "provenance": server.provenance,
+        "llm_initiated": server.llm_initiated,
+        "call_mode": server.call_mode,
+        "uptime": server.uptime,
+        "health": server.health,
+        "tool_limits": tool_limits,
+        "warnings": warnings,
+    }))
+}
+
+/// Record the tool calls of a server, replay them, or go back to live calls
+async fn handle_set_server_mode(mcp_core: MCPCore, params: Value) -> Result<Value, Value> {
+    let Some(server_id) = params.get("server_id").and_then(|v| v.as_str()) else {
+        return Err(json!({
+            "code": -32602,
+            "message": "Missing server_id parameter"
+        }));
+    };
+    let call_mode: ServerCallMode = serde_json::from_value(params.clone()).map_err(|e| {
+        json!({
+            "code": -32602,
+            "message": format!("Invalid mode: {}", e)
+        })
+    })?;
+
+    match mcp_core.set_server_call_mode(server_id, call_mode).await {
+        Ok(call_mode) => Ok(json!({
+            "server_id": server_id,
+            "call_mode": call_mode,
+        })),
+        Err(e) => Err(json!({
+            "code": -32000,
+            "message": format!("Failed to set the mode of {}: {}", server_id, e)
+        })),
+    }
+}
+
+/// Save which tools of a server are listed, a null filter going back to the default
+async fn handle_set_tool_filter(mcp_core: MCPCore, params: Value) -> Result<Value, Value> {
+    let Some(server_id) = params.get("server_id").and_then(|v| v.as_str()) else {
+        return Err(json!({
+            "code": -32602,
+            "message": "Missing server_id parameter"
+        }));
+    };
+    let filter: Option<ToolFilter> =
+        serde_json::from_value(params.get("filter").cloned().unwrap_or_default()).map_err(|e| {
+            json!({
+                "code": -32602,
+                "message": format!("Invalid filter: {}", e)
+            })
+        })?;
+
+    match mcp_core.set_tool_filter(server_id, filter).await {
+        Ok(tool_limits) => Ok(json!({
+            "success": true,
+            "server_id": server_id,
+            "tool_limits": tool_limits,
+        })),
+        Err(e) => Err(json!({
+            "code": -32000,
+            "message": format!("Failed to set the tool filter of {}: {}", server_id, e)
+        })),
+    }
+}
+
+/// Replace the hooks run around tool calls, with a configuration as listed by `hooks/list`.
+/// Hooks run arbitrary commands, so this needs the admin token
+async fn handle_set_hooks(mcp_core: MCPCore, mut params: Value) -> Result<Value, Value> {
+    let token = params
+        .as_object_mut()
+        .and_then(|params| params.remove("admin_token"))
+        .unwrap_or_default();
+    if !token.as_str().is_some_and(is_admin_token) {
+        warn!("Refused to change the hooks without the admin token");
+        return Err(json!({
+            "code": -32000,
+            "message": "Changing the hooks requires the admin token"
+        }));
+    }
+    let config: HooksConfig = serde_json::from_value(params).map_err(|e| {
+        json!({
+            "code": -32602,
+            "message": format!("Invalid hooks: {}", e)
+        })
+    })?;
+
+    match mcp_core.set_tool_hooks(config.clone()).await {
+        Ok(()) => Ok(json!(config)),
+        Err(e) => Err(json!({
+            "code": -32000,
+            "message": format!("Failed to save the hooks: {}", e)
+        })),
+    }
+}
+
+fn schedule_id_param(params: &Option<Value>) -> Result<String, Value> {
+    params
+        .as_ref()
+        .and_then(|params| params.get("schedule_id"))
+        .and_then(|v| v.as_str())
+        .map(str::to_string)
+        .ok_or_else(|| {
+            json!({
+                "code": -32602,
+                "message": "Missing schedule_id parameter"
+            })
+        })
+}
+
+async fn handle_get_schedule(mcp_core: MCPCore, params: Option<Value>) -> Result<Value, Value> {
+    let schedule_id = schedule_id_param(&params)?;
+    match mcp_core.get_schedule(&schedule_id).await {
+        Ok(schedule) => Ok(json!(schedule)),
+        Err(e) => Err(json!({
+            "code": -32000,
+            "message": e
+        })),
+    }
+}
+
+/// Handle `history/undo`: undo the change of an entry of `history/undoable`
+async fn handle_undo(mcp_core: MCPCore, params: Option<Value>) -> Result<Value, Value> {
+    let Some(entry_id) = params
+        .as_ref()
+        .and_then(|params| params.get("entry_id"))
+        .and_then(|v| v.as_i64())
+    else {
+        return Err(json!({
+            "code": -32602,
+            "message": "Missing entry_id parameter"
+        }));
+    };
+    match mcp_core.undo_change(entry_id).await {
+        Ok(result) => Ok(json!(result)),
+        Err(e) => Err(json!({
+            "code": -32000,
+            "message": e
+        })),
+    }
+}
+
+/// Handle `schedules/create` and `schedules/update`, whose params are the job itself
+async fn handle_save_schedule(
+    mcp_core: MCPCore,
+    params: Option<Value>,
+    create: bool,
+) -> Result<Value, Value> {
+    let Some(params) = params else {
+        return Err(json!({
+            "code": -32602,
+            "message": "Missing schedule parameter"
+        }));
+    };
+    let definition: ScheduleDefinition = serde_json::from_value(params).map_err(|e| {
+        json!({
+            "code": -32602,
+            "message": format!("Invalid schedule: {}", e)
+        })
+    })?;
+
+    let saved = match create {
+        true => mcp_core.create_schedule(definition).await,
+        false => mcp_core.update_schedule(definition).await,
+    };
+    match saved {
+        Ok(schedule) => Ok(json!(schedule)),
+        Err(e) => Err(json!({
+            "code": -32000,
+            "message": format!("Failed to save the schedule: {}", e)
+        })),
+    }
+}
+
+async fn handle_delete_schedule(mcp_core: MCPCore, params: Option<Value>) -> Result<Value, Value> {
+    let schedule_id = schedule_id_param(&params)?;
+    match mcp_core.delete_schedule(&schedule_id).await {
+        Ok(()) => Ok(json!({ "success": true })),
+        Err(e) => Err(json!({
+            "code": -32000,
+            "message": format!("Failed to delete the schedule: {}", e)
+        })),
+    }
+}
+
+async fn handle_schedule_runs(mcp_core: MCPCore, params: Option<Value>) -> Result<Value, Value> {
+    let schedule_id = schedule_id_param(&params)?;
+    let limit = params
+        .as_ref()
+        .and_then(|params| params.get("limit"))
+        .and_then(|v| v.as_i64())
+        .unwrap_or(SCHEDULE_RUNS_KEPT);
+    match mcp_core.schedule_runs(&schedule_id, limit).await {
+        Ok(runs) => Ok(json!({ "runs": runs })),
+        Err(e) => Err(json!({
+            "code": -32000,
+            "message": format!("Failed to read the runs of the schedule: {}", e)
+        })),
+    }
+}
+
+fn webhook_id_param(params: &Option<Value>) -> Result<String, Value> {
+    params
+        .as_ref()
+        .and_then(|params| params.get("webhook_id"))
+        .and_then(|v| v.as_str())
+        .map(str::to_string)
+        .ok_or_else(|| {
+            json!({
+                "code": -32602,
+                "message": "Missing webhook_id parameter"
+            })
+        })
+}
+
+/// Handle `webhooks/create` and `webhooks/update`, whose params are the webhook itself
+///
+/// Creating answers with the secret of the webhook, the only time it is sent.
+async fn handle_save_webhook(
+    mcp_core: MCPCore,
+    params: Option<Value>,
+    create: bool,
+) -> Result<Value, Value> {
+    let Some(params) = params else {
+        return Err(json!({
+            "code": -32602,
+            "message": "Missing webhook parameter"
+        }));
+    };
+    let webhook: Webhook = serde_json::from_value(params).map_err(|e| {
+        json!({
+            "code": -32602,
+            "message": format!("Invalid webhook: {}", e)
+        })
+    })?;
+
+    let saved = match create {
+        true => mcp_core
+            .create_webhook(webhook)
+            .await
+            .map(|created| json!(created)),
+        false => mcp_core
+            .update_webhook(webhook)
+            .await
+            .map(|updated| json!(updated)),
+    };
+    saved.map_err(|e| {
+        json!({
+            "code": -32000,
+            "message": format!("Failed to save the webhook: {}", e)
+        })
+    })
+}
+
+async fn handle_delete_webhook(mcp_core: MCPCore, params: Option<Value>) -> Result<Value, Value> {
+    let webhook_id = webhook_id_param(&params)?;
+    match mcp_core.delete_webhook(&webhook_id).await {
+        Ok(()) => Ok(json!({ "success": true })),
+        Err(e) => Err(json!({
+            "code": -32000,
+            "message": format!("Failed to delete the webhook: {}", e)
+        })),
+    }
+}
+
+/// Handle `webhooks/test`, answering with the outcome of the delivery
+async fn handle_test_webhook(mcp_core: MCPCore, params: Option<Value>) -> Result<Value, Value> {
+    let webhook_id = webhook_id_param(&params)?;
+    match mcp_core.test_webhook(&webhook_id).await {
+        Ok(delivery) => Ok(json!(delivery)),
+        Err(e) => Err(json!({
+            "code": -32000,
+            "message": e
+        })),
+    }
+}
+
+/// Handle `registry/save_source`, answering with the source without its credentials
+async fn handle_save_registry_source(
+    mcp_core: MCPCore,
+    params: Option<Value>,
+) -> Result<Value, Value> {
+    let Some(params) = params else {
+        return Err(json!({
+            "code": -32602,
+            "message": "Missing registry source parameter"
+        }));
+    };
+    let source: RegistrySource = serde_json::from_value(params).map_err(|e| {
+        json!({
+            "code": -32602,
+            "message": format!("Invalid registry source: {}", e)
+        })
+    })?;
+
+    match mcp_core.save_registry_source(source).await {
+        Ok(source) => Ok(json!(source)),
+        Err(e) => Err(json!({
+            "code": -32000,
+            "message": format!("Failed to save the registry source: {}", e)
+        })),
+    }
+}
+
+async fn handle_delete_registry_source(
+    mcp_core: MCPCore,
+    params: Option<Value>,
+) -> Result<Value, Value> {
+    let Some(source_id) = params
+        .as_ref()
+        .and_then(|params| params.get("source_id"))
+        .and_then(|v| v.as_str())
+    else {
+        return Err(json!({
+            "code": -32602,
+            "message": "Missing source_id parameter"
+        }));
+    };
+    match mcp_core.delete_registry_source(source_id).await {
+        Ok(()) => Ok(json!({ "success": true })),
+        Err(e) => Err(json!({
+            "code": -32000,
+            "message": format!("Failed to delete the registry source: {}", e)
+        })),
+    }
+}
+
+/// Handle `read_only/set`, which needs the admin token
+async fn handle_set_read_only(mcp_core: MCPCore, params: Option<Value>) -> Result<Value, Value> {
+    let params = params.unwrap_or_default();
+    let Some(read_only) = params.get("read_only").and_then(|v| v.as_bool()) else {
+        return Err(json!({
+            "code": -32602,
+            "message": "Missing read_only parameter"
+        }));
+    };
+    let token = params.get("admin_token").and_then(|v| v.as_str());
+    if !token.is_some_and(is_admin_token) {
+        warn!("Refused to change the read-only mode without the admin token");
+        return Err(json!({
+            "code": -32000,
+            "message": "Changing the read-only mode requires the admin token"
+        }));
+    }
+    match mcp_core.set_read_only(read_only).await {
+        Ok(()) => Ok(json!({ "read_only": read_only })),
+        Err(e) => Err(json!({
+            "code": -32000,
+            "message": format!("Failed to change the read-only mode: {}", e)
+        })),
+    }
+}
+
+/// Handle `system/dump_state`, which needs the admin token and never waits on a lock
+fn handle_dump_state(mcp_core: MCPCore, params: Option<Value>) -> Result<Value, Value> {
+    let params = params.unwrap_or_default();
+    let token = params.get("admin_token").and_then(|v| v.as_str());
+    if !token.is_some_and(is_admin_token) {
+        warn!("Refused to dump the state without the admin token");
+        return Err(json!({
+            "code": -32000,
+            "message": "Dumping the state requires the admin token"
+        }));
+    }
+    Ok(json!(mcp_core.dump_state()))
+}
+
+async fn handle_launch_plan(mcp_core: MCPCore, params: Value) -> Result<Value, Value> {
+    let Some(server_id) = params.get("server_id").and_then(|v| v.as_str()) else {
+        return Err(json!({
+            "code": -32602,
+            "message": "Missing server_id parameter"
+        }));
+    };
+
+    let mcp_state = mcp_core.mcp_state.read().await.clone();
+    match mcp_state.launch_plan(server_id).await {
+        Ok(plan) => Ok(serde_json::to_value(plan).unwrap()),
+        Err(e) => Err(json!({
+            "code": -32000,
+            "message": format!("Failed to resolve launch plan: {}", e)
+        })),
+    }
 }
 
-async fn handle_get_prompt(_params: Value) -> Result<Value, Value> {
-    Err(json!({
-        "code": -32601,
-        "message": "Prompt retrieval not implemented yet"
-    }))
+/// Handle `server/diagnose`, with `export` the report is also written to the data
+/// directory
+async fn handle_diagnose_server(mcp_core: MCPCore, params: Value) -> Result<Value, Value> {
+    let Some(server_id) = params.get("server_id").and_then(|v| v.as_str()) else {
+        return Err(json!({
+            "code": -32602,
+            "message": "Missing server_id parameter"
+        }));
+    };
+
+    let diagnosis = mcp_core.diagnose_server(server_id).await.map_err(|e| {
+        json!({
+            "code": -32000,
+            "message": format!("Failed to diagnose server: {}", e)
+        })
+    })?;
+    let mut response = json!(diagnosis);
+    if params.get("export").and_then(|v| v.as_bool()) == Some(true) {
+        let export = mcp_core
+            .export_server_diagnosis(&diagnosis)
+            .await
+            .map_err(|e| {
+                json!({
+                    "code": -32000,
+                    "message": format!("Failed to export the diagnosis: {}", e)
+                })
+            })?;
+        response["export"] = json!(export);
+    }
+    Ok(response)
 }
 
-async fn handle_invoke_tool(mcp_core: MCPCore, params: Value) -> Result<Value, Value> {
-    let tool_name = match params.get("name").and_then(|v| v.as_str()) {
-        Some(name) => name,
-        None => {
-            return Err(json!({
-                "code": -32602,
-                "message": "Missing name in parameters"
-            }))
-        }
+/// Handle `tools/describe`, returning everything known about one tool
+///
+/// The tool is given either as a `proxyId` (`server_id:tool_name`) or as
+/// `serverId` and `toolName`. The snake_case names are accepted too.
+/// Handle `tools/set_rate_limit`, limits left out are removed
+async fn handle_set_rate_limit(mcp_core: MCPCore, params: Value) -> Result<Value, Value> {
+    let server_id = params.get("server_id").and_then(|v| v.as_str());
+    let tool_name = params.get("tool_name").and_then(|v| v.as_str());
+    let (Some(server_id), Some(tool_name)) = (server_id, tool_name) else {
+        return Err(json!({
+            "code": -32602,
+            "message": "Missing server_id and tool_name parameters"
+        }));
     };
+    let limit: RateLimit = serde_json::from_value(params.clone()).map_err(|e| {
+        json!({
+            "code": -32602,
+            "message": format!("Invalid rate limit: {}", e)
+        })
+    })?;
 
-    let arguments = match params.get("arguments") {
-        Some(args) => args.clone(),
-        None => json!({}),
+    match mcp_core
+        .set_tool_rate_limit(server_id, tool_name, limit.clone())
+        .await
+    {
+        Ok(()) => Ok(json!({ "success": true, "rate_limit": limit })),
+        Err(e) => Err(json!({
+            "code": -32000,
+            "message": format!("Failed to set the rate limit: {}", e)
+        })),
+    }
+}
+
+async fn handle_set_tool_annotations(mcp_core: MCPCore, params: Value) -> Result<Value, Value> {
+    let server_id = params.get("server_id").and_then(|v| v.as_str());
+    let tool_name = params.get("tool_name").and_then(|v| v.as_str());
+    let (Some(server_id), Some(tool_name)) = (server_id, tool_name) else {
+        return Err(json!({
+            "code": -32602,
+            "message": "Missing server_id and tool_name parameters"
+        }));
     };
+    let annotations: ToolAnnotations = serde_json::from_value(
+        params.get("annotations").cloned().unwrap_or(json!({})),
+    )
+    .map_err(|e| {
+        json!({
+            "code": -32602,
+            "message": format!("Invalid annotations: {}", e)
+        })
+    })?;
 
-    let mcp_state = mcp_core.mcp_state.read().await;
-    let server_tools = mcp_state.server_tools.read().await;
+    match mcp_core
+        .set_tool_annotations(server_id, tool_name, annotations.clone())
+        .await
+    {
+        Ok(()) => Ok(json!({ "success": true, "annotations": annotations })),
+        Err(e) => Err(json!({
+            "code": -32000,
+            "message": format!("Failed to set the tool annotations: {}", e)
+        })),
+    }
+}
 
-    // Find which server has the requested tool
-    let mut server_id = None;
+async fn handle_describe_tool(mcp_core: MCPCore, params: Value) -> Result<Value, Value> {
+    let param = |camel: &str, snake: &str| {
+        params
+            .get(camel)
+            .or_else(|| params.get(snake))
+            .and_then(|v| v.as_str())
+    };
+    let ids = match param("proxyId", "proxy_id") {
+        Some(proxy_id) => proxy_id.split_once(':'),
+        None => param("serverId", "server_id").zip(param("toolName", "tool_name")),
+    };
+    let Some((server_id, tool_name)) = ids else {
+        return Err(json!({
+            "code": -32602,
+            "message": "Expected a proxyId of the form 'server_id:tool_name', or serverId and toolName parameters"
+        }));
+    };
 
-    for (sid, tools) in &*server_tools {
-        for tool in tools {
-            if tool.id == tool_name {
-                server_id = Some(sid.clone());
-                break;
-            }
+    match mcp_core.describe_tool(server_id, tool_name).await {
+        Ok(tool) => Ok(serde_json::to_value(tool).unwrap()),
+        Err(e) => Err(json!({
+            "code": -32000,
+            "message": e.to_string(),
+            "data": { "reason": e.reason() }
+        })),
+    }
+}
 
-            // Also check by name if id doesn't match
-            if tool.name == tool_name {
-                server_id = Some(sid.clone());
-                break;
-            }
-        }
+async fn handle_server_resources(mcp_core: MCPCore, params: Value) -> Result<Value, Value> {
+    let Some(server_id) = params.get("server_id").and_then(|v| v.as_str()) else {
+        return Err(json!({
+            "code": -32602,
+            "message": "Missing server_id parameter"
+        }));
+    };
 
-        if server_id.is_some() {
-            break;
-        }
+    let mcp_state = mcp_core.mcp_state.read().await.clone();
+    match mcp_state.server_resources(server_id).await {
+        Ok(resources) => Ok(serde_json::to_value(resources).unwrap()),
+        Err(e) => Err(json!({
+            "code": -32000,
+            "message": format!("Failed to get server resources: {}", e)
+        })),
     }
+}
 
-    // Drop the locks before proceeding
-    drop(server_tools);
-    drop(mcp_state);
+/// Handle `server/history`, every lifecycle event of a server kept in memory
+async fn handle_server_history(mcp_core: MCPCore, params: Value) -> Result<Value, Value> {
+    let Some(server_id) = params.get("server_id").and_then(|v| v.as_str()) else {
+        return Err(json!({
+            "code": -32602,
+            "message": "Missing server_id parameter"
+        }));
+    };
 
-    match server_id {
-        Some(server_id) => {
-            let request = ToolExecutionRequest {
-                tool_id: format!("{}:{}", server_id, tool_name),
-                parameters: arguments,
-            };
+    match mcp_core.get_server_history(server_id.to_string()).await {
+        Ok(history) => Ok(json!(history)),
+        Err(e) => Err(json!({
+            "code": -32000,
+            "message": format!("Failed to get server history: {}", e)
+        })),
+    }
+}
 
-            match mcp_core.execute_proxy_tool(request).await {
-                Ok(response) => {
-                    if response.success {
-                        Ok(response.result.unwrap_or(json!(null)))
-                    } else {
-                        Err(json!({
-                            "code": -32000,
-                            "message": response.error.unwrap_or_else(|| "Unknown error".to_string())
-                        }))
-                    }
-                }
-                Err(e) => Err(json!({
-                    "code": -32000,
-                    "message": format!("Failed to execute tool: {}", e)
-                })),
-            }
-        }
-        None => Err(json!({
-            "code": -32601,
-            "message": format!("Tool '{}' not found", tool_name)
+/// Handle `server/logs`, which needs the admin token since servers may print secrets
+async fn handle_server_logs(mcp_core: MCPCore, params: Value) -> Result<Value, Value> {
+    let token = params.get("admin_token").and_then(|v| v.as_str());
+    if !token.is_some_and(is_admin_token) {
+        warn!("Refused to read server logs without the admin token");
+        return Err(json!({
+            "code": -32000,
+            "message": "Reading server logs requires the admin token"
+        }));
+    }
+    let Some(server_id) = params.get("server_id").and_then(|v| v.as_str()) else {
+        return Err(json!({
+            "code": -32602,
+            "message": "Missing server_id parameter"
+        }));
+    };
+    let since = params.get("since").and_then(|v| v.as_u64());
+
+    match mcp_core.get_server_logs(server_id.to_string(), since).await {
+        Ok(logs) => Ok(json!(logs)),
+        Err(e) => Err(json!({
+            "code": -32000,
+            "message": format!("Failed to get server logs: {}", e)
         })),
     }
 }
 
-async fn handle_import_server_from_url(mcp_core: MCPCore, params: Value) -> Result<Value, Value> {
-    match params.get("url").and_then(|v| v.as_str()) {
-        Some(url) => {
-            info!("Importing server from URL: {}", url);
+fn handle_get_operation(mcp_core: MCPCore, params: Value) -> Result<Value, Value> {
+    let Some(operation_id) = params.get("operation_id").and_then(|v| v.as_str()) else {
+        return Err(json!({
+            "code": -32602,
+            "message": "Missing operation_id parameter"
+        }));
+    };
 
-            match mcp_core.import_server_from_url(url.to_string()).await {
-                Ok(response) => {
-                    if response.success {
-                        Ok(json!({
-                            "success": true,
-                            "message": response.message,
-                            "server_id": response.tool_id
-                        }))
-                    } else {
-                        Err(json!({
-                            "code": -32000,
-                            "message": response.message
-                        }))
-                    }
-                }
-                Err(e) => Err(json!({
-                    "code": -32000,
-                    "message": format!("Failed to import server: {}", e)
-                })),
-            }
-        }
+    match mcp_core.operations.get(operation_id) {
+        Some(operation) => Ok(serde_json::to_value(operation).unwrap()),
         None => Err(json!({
-            "code": -32602,
-            "message": "Missing URL parameter"
+            "code": -32000,
+            "message": format!("Operation '{}' not found", operation_id)
         })),
     }
 }
 
-pub async fn handle_get_server_config(mcp_core: MCPCore, params: Value) -> Result<Value, Value> {
-    info!("handle_get_server_config: params {:?}", params);
-    let config: ConfigUpdateRequest = match serde_json::from_value(params) {
-        Ok(config) => config,
-        Err(error) => {
-            return Err(json!({
-                "code": -32602,
-                "message": format!("Invalid params - missing parameters for server config: {}", error)
-            }));
-        }
+fn handle_cancel_operation(mcp_core: MCPCore, params: Value) -> Result<Value, Value> {
+    let Some(operation_id) = params.get("operation_id").and_then(|v| v.as_str()) else {
+        return Err(json!({
+            "code": -32602,
+            "message": "Missing operation_id parameter"
+        }));
     };
 
-    // Update the tool configuration
-    match mcp_core
-        .update_server_config(ServerConfigUpdateRequest {
-            server_id: config.tool_id.to_string(),
-            config: config.config,
-        })
-        .await
-    {
-        Ok(response) => {
-            if !response.success {
-                return Err(json!({
-                    "code": -32000,
-                    "message": response.message
-                }));
-            }
+    match mcp_core.operations.cancel(operation_id) {
+        Ok(operation) => Ok(serde_json::to_value(operation).unwrap()),
+        Err(message) => Err(json!({
+            "code": -32000,
+            "message": message
+        })),
+    }
+}
 
-            // After successful config update, restart the tool
-            match mcp_core
-                .restart_server_command(config.tool_id.to_string())
-                .await
-            {
-                Ok(restart_response) => {
-                    if restart_response.success {
-                        Ok(json!({
-                            "message": format!("Configuration updated and tool restarted successfully: {}", restart_response.message)
-                        }))
-                    } else {
-                        Err(json!({
-                            "code": -32000,
-                            "message": format!("Config updated but restart failed: {}", restart_response.message)
-                        }))
-                    }
-                }
-                Err(e) => Err(json!({
-                    "code": -32000,
-                    "message": format!("Config updated but restart error: {}", e)
-                })),
-            }
-        }
+/// Handle `diagnostics/export`
+///
+/// With `preview: true` only the list of files the bundle would contain is returned,
+/// otherwise the bundle is written under the data directory and its path returned.
+async fn handle_export_diagnostics(
+    mcp_core: MCPCore,
+    params: Option<Value>,
+) -> Result<Value, Value> {
+    let preview = params
+        .as_ref()
+        .and_then(|params| params.get("preview"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    let options = DiagnosticsOptions::default();
+
+    if preview {
+        let preview = mcp_core.preview_diagnostics(&options).await;
+        return Ok(serde_json::to_value(preview).unwrap());
+    }
+    match mcp_core.export_diagnostics(&options).await {
+        Ok(export) => Ok(serde_json::to_value(export).unwrap()),
         Err(e) => Err(json!({
             "code": -32000,
-            "message": format!("Failed to update configuration: {}", e)
+            "message": format!("Failed to export diagnostics: {}", e)
         })),
     }
 }
@@ -844,73 +2479,159 @@ async fn handle_tools_hidden(mcp_core: MCPCore) -> Result<Value, Value> {
     Ok(json!({ "hidden": hidden }))
 }
 
+/// Handle `tools/footprint`: size of the tool list, with the `top` most expensive tools
+async fn handle_tools_footprint(mcp_core: MCPCore, params: Option<Value>) -> Result<Value, Value> {
+    let top = params
+        .as_ref()
+        .and_then(|params| params.get("top"))
+        .and_then(|v| v.as_u64())
+        .map_or(FOOTPRINT_TOP_TOOLS, |top| top as usize);
+    match mcp_core.tools_footprint(top).await {
+        Ok(footprint) => Ok(json!(footprint)),
+        Err(e) => Err(json!({
+            "code": -32000,
+            "message": format!("Failed to measure the tool list: {}", e)
+        })),
+    }
+}
+
 /// SSE endpoint handler with bidirectional communication
 pub async fn sse_handler(
-    Extension(_mcp_core): Extension<MCPCore>,
+    Extension(mcp_core): Extension<MCPCore>,
     Extension(mcp_router): Extension<Arc<MCPDockmasterRouter>>,
 ) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
     let session_id = Uuid::new_v4().to_string();
+    let sessions = mcp_core.mcp_state.read().await.sessions.clone();
     info!("New SSE connection established: {}", session_id);
-    
+
     const BUFFER_SIZE: usize = 1 << 12; // 4KB
-    // Create channels for command and response
+                                        // Create channels for command and response
     let (c2s_read, c2s_write) = io::simplex(BUFFER_SIZE);
     let (s2c_read, s2c_write) = io::simplex(BUFFER_SIZE);
     // Create a separate channel for notifications
     let (notification_read, notification_write) = io::simplex(BUFFER_SIZE);
-    
+
     // Wrap writers in Arc<Mutex>
     let command_writer = Arc::new(TokioMutex::new(c2s_write));
     let notification_writer = Arc::new(TokioMutex::new(notification_write));
-    
+
     // Register both channels
-    SESSION_MANAGER.register_session(
-        session_id.clone(),
-        command_writer,
-        notification_writer
-    ).await;
-    
+    let transport = SseTransport::new(command_writer, notification_writer);
+    sessions
+        .register(ClientSession::new(session_id.clone(), Arc::new(transport)))
+        .await;
+
     // Spawn a task to handle incoming messages from the client
     {
         let session_id = session_id.clone();
         let router_clone = mcp_router.clone();
-        
+
         tokio::spawn(async move {
             // Dereference the Arc to get the actual router
-            let router_service = RouterService((*router_clone).clone());
+            let router_service = RouterService(router_clone.for_session(&session_id));
             let server = Server::new(router_service);
             let byte_transport = ByteTransport::new(c2s_read, s2c_write);
-            
+
             let result = server.run(byte_transport).await;
-            
+
             if let Err(e) = &result {
                 log::error!("Server run error for session {}: {:?}", session_id, e);
             }
-            
-            SESSION_MANAGER.remove_session(&session_id).await;
-            
+
+            sessions.remove(&session_id).await;
+
             result
         });
     }
-    
+
     // Create an initial event with the session ID
     let initial_event = futures::stream::once(futures::future::ok(
         Event::default()
             .event("endpoint")
-            .data(format!("?sessionId={session_id}"))
+            .data(format!("?sessionId={session_id}")),
     ));
-    
+
     // Create streams for both s2c and notification channels
-    let message_stream = create_message_stream(s2c_read);
-    let notification_stream = create_message_stream(notification_read);
-    
+    let message_stream = create_message_stream(s2c_read, Some(mcp_router.tools_service()));
+    let notification_stream = create_message_stream(notification_read, None);
+
     // Merge all streams together
-    let combined_stream = initial_event
-        .chain(futures::stream::select(message_stream, notification_stream));
-    
+    let combined_stream =
+        initial_event.chain(futures::stream::select(message_stream, notification_stream));
+
     Sse::new(combined_stream)
 }
 
+/// Query parameters of the events endpoint
+#[derive(Debug, Deserialize)]
+pub struct EventsParam {
+    #[serde(rename = "operationId")]
+    pub operation_id: Option<String>,
+}
+
+/// SSE endpoint streaming core events
+///
+/// With `operationId`, the events already published for that operation are replayed
+/// first and the stream ends after its final event. Without it, all
+/// events published from now on are streamed.
+pub async fn events_handler(
+    Extension(mcp_core): Extension<MCPCore>,
+    Query(params): Query<EventsParam>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let operation_id = params.operation_id;
+    let (past, receiver) = {
+        let operation_id = operation_id.clone();
+        mcp_core.event_bus.subscribe_with_history(move |event| {
+            operation_id.is_some() && event.operation_id() == operation_id.as_deref()
+        })
+    };
+
+    let state = (
+        std::collections::VecDeque::from(past),
+        receiver,
+        operation_id,
+        mcp_core.operations.clone(),
+        false,
+    );
+    let stream = futures::stream::unfold(
+        state,
+        |(mut pending, mut receiver, operation_id, operations, finished)| async move {
+            if finished {
+                return None;
+            }
+            let envelope: EventEnvelope = match pending.pop_front() {
+                Some(envelope) => envelope,
+                None => loop {
+                    match receiver.recv().await {
+                        Ok(envelope) => {
+                            if operation_id.is_none()
+                                || envelope.event.operation_id() == operation_id.as_deref()
+                            {
+                                break envelope;
+                            }
+                        }
+                        Err(RecvError::Lagged(skipped)) => {
+                            warn!("Events stream lagged, {} events skipped", skipped);
+                        }
+                        Err(RecvError::Closed) => return None,
+                    }
+                },
+            };
+            let finished = operation_id.is_some() && operations.is_final_event(&envelope.event);
+            let event = Event::default()
+                .event("event")
+                .id(envelope.seq.to_string())
+                .data(serde_json::to_string(&envelope.event).unwrap_or_default());
+            Some((
+                Ok::<_, Infallible>(event),
+                (pending, receiver, operation_id, operations, finished),
+            ))
+        },
+    );
+
+    Sse::new(stream)
+}
+
 /// Query parameter struct for session ID
 #[derive(Debug, Deserialize)]
 pub struct SessionIdParam {
@@ -920,45 +2641,43 @@ pub struct SessionIdParam {
 
 /// Handler for JSON-RPC requests via POST to the SSE endpoint
 pub async fn sse_post_handler(
+    Extension(mcp_core): Extension<MCPCore>,
     Query(params): Query<SessionIdParam>,
     body: axum::body::Body,
 ) -> (StatusCode, &'static str) {
     let session_id = &params.session_id;
     info!("Received POST request for session {}", session_id);
-    
-    let writer = {
-        let sessions = SESSION_MANAGER.sessions.lock().await;
-        match sessions.get(session_id) {
-            Some(channels) => channels.command.clone(),
+
+    let sessions = mcp_core.mcp_state.read().await.sessions.clone();
+    let writer = match sessions.get(session_id).await {
+        Some(session) => match session.transport.inbound() {
+            Some(writer) => writer,
             None => {
-                log::error!("Session {} not found", session_id);
-                return (StatusCode::NOT_FOUND, "Session not found");
+                log::error!("Session {} doesn't accept posted messages", session_id);
+                return (
+                    StatusCode::BAD_REQUEST,
+                    "Session doesn't accept posted messages",
+                );
             }
+        },
+        None => {
+            log::error!("Session {} not found", session_id);
+            return (StatusCode::NOT_FOUND, "Session not found");
         }
     };
-    
-    // Convert the body to a byte stream
+
+    // Read the whole body, the message is parsed before the session sees it
     const BODY_BYTES_LIMIT: usize = 1 << 22; // 4MB
     let mut body = body.into_data_stream();
-    let mut size = 0;
-    
-    // Lock the writer for the entire request
-    let mut writer = writer.lock().await;
-    
-    // Forward each chunk to the session's channel
+    let mut bytes = Vec::new();
     while let Some(chunk) = body.next().await {
         match chunk {
             Ok(chunk) => {
-                size += chunk.len();
-                if size > BODY_BYTES_LIMIT {
+                if bytes.len() + chunk.len() > BODY_BYTES_LIMIT {
                     log::error!("Payload too large for session {}", session_id);
                     return (StatusCode::PAYLOAD_TOO_LARGE, "Payload too large");
                 }
-                
-                if let Err(e) = writer.write_all(&chunk).await {
-                    log::error!("Failed to write to session {}: {}", session_id, e);
-                    return (StatusCode::INTERNAL_SERVER_ERROR, "Failed to write to session");
-                }
+                bytes.extend_from_slice(&chunk);
             }
             Err(_) => {
                 log::error!("Invalid request body for session {}", session_id);
@@ -966,53 +2685,158 @@ pub async fn sse_post_handler(
             }
         }
     }
-    
+    let mut message = serde_json::from_slice::<Value>(&bytes).ok();
+
+    // Cancelling doesn't wait behind the call it cancels
+    if let Some(message) = message
+        .as_ref()
+        .filter(|m| m["method"] == "notifications/cancelled")
+    {
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+        if let Some(request_id) = cancelled_request_id(&params) {
+            mcp_core.cancel_tool_call(&call_id(session_id, request_id));
+        }
+        return (StatusCode::ACCEPTED, "");
+    }
+    // Recorded before the session sees the request, its progress may come right away
+    let progress_token = message.as_ref().and_then(requested_progress_token);
+    if let Some(token) = progress_token {
+        sessions
+            .subscribe(session_id, &progress_topic(session_id, token))
+            .await;
+    }
+    // The router learns the id of the call it runs from its arguments
+    let call = message
+        .as_ref()
+        .and_then(tool_call_id)
+        .map(|id| PostedToolCall {
+            id: id.clone(),
+            progress_token: progress_token.cloned(),
+        });
+    if let (Some(call), Some(message)) = (call, message.as_mut()) {
+        if call.attach(message) {
+            bytes = serde_json::to_vec(message).unwrap_or(bytes);
+        }
+    }
+
+    // Lock the writer for the entire message
+    let mut writer = writer.lock().await;
+
+    if let Err(e) = writer.write_all(&bytes).await {
+        log::error!("Failed to write to session {}: {}", session_id, e);
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to write to session",
+        );
+    }
+
     // Add a newline to separate messages
     if let Err(e) = writer.write_u8(b'\n').await {
         log::error!("Failed to write newline to session {}: {}", session_id, e);
-        return (StatusCode::INTERNAL_SERVER_ERROR, "Failed to write to session");
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to write to session",
+        );
     }
-    
+
     // Flush the writer to ensure the data is sent
     if let Err(e) = writer.flush().await {
         log::error!("Failed to flush session {}: {}", session_id, e);
         return (StatusCode::INTERNAL_SERVER_ERROR, "Failed to flush session");
     }
-    
+
+    // Release the session's pipe before touching the registry
+    drop(writer);
+    let initialize = message.as_ref().and_then(ClientIdentity::from_initialize);
+    if let Some((client, capabilities)) = initialize {
+        info!(
+            "Session {} belongs to client {}",
+            session_id,
+            client.display_name()
+        );
+        sessions.set_client(session_id, client, capabilities).await;
+    }
+    if message
+        .as_ref()
+        .is_some_and(|m| m["method"] == "notifications/initialized")
+    {
+        sessions.mark_initialized(session_id).await;
+    }
+
     // Return a success response
     (StatusCode::ACCEPTED, "")
 }
 
+/// JSON-RPC id of a `tools/call` request, handed to the router of the session
+fn tool_call_id(message: &Value) -> Option<&Value> {
+    if message.get("method")?.as_str()? != "tools/call" {
+        return None;
+    }
+    message.get("params")?.get("name")?.as_str()?;
+    message
+        .get("id")
+        .filter(|id| id.is_string() || id.is_number())
+}
+
 /// Creates a message stream from a read half of a simplex channel
 fn create_message_stream(
     read_half: io::ReadHalf<io::SimplexStream>,
+    tools_service: Option<Arc<MCPToolsService>>,
 ) -> impl Stream<Item = Result<Event, Infallible>> {
-    futures::stream::unfold(read_half, |read_half| async move {
-        let mut framed = FramedRead::new(read_half, crate::jsonrpc_frame_codec::JsonRpcFrameCodec);
-        
-        if let Some(result) = framed.next().await {
-            let read_half = framed.into_inner();
-            match result {
-                Ok(bytes) => {
-                    let event = match std::str::from_utf8(&bytes) {
-                        Ok(message) => {
-                            Event::default().event("message").data(message)
-                        },
-                        Err(e) => {
-                            log::error!("Error parsing UTF-8: {}", e);
-                            Event::default().event("error").data(format!("UTF-8 error: {}", e))
-                        }
-                    };
-                    Some((Ok::<_, Infallible>(event), read_half))
-                },
-                Err(e) => {
-                    log::error!("Error reading frame: {}", e);
-                    let event = Event::default().event("error").data(format!("Frame error: {}", e));
-                    Some((Ok::<_, Infallible>(event), read_half))
+    futures::stream::unfold(
+        (read_half, tools_service),
+        |(read_half, tools_service)| async move {
+            let mut framed =
+                FramedRead::new(read_half, crate::jsonrpc_frame_codec::JsonRpcFrameCodec);
+
+            if let Some(result) = framed.next().await {
+                let read_half = framed.into_inner();
+                match result {
+                    Ok(bytes) => {
+                        let event = match std::str::from_utf8(&bytes) {
+                            Ok(message) => {
+                                let rewritten = tools_service
+                                    .as_deref()
+                                    .and_then(|service| outgoing_message(message, service));
+                                let message = rewritten.as_deref().unwrap_or(message);
+                                Event::default().event("message").data(message)
+                            }
+                            Err(e) => {
+                                log::error!("Error parsing UTF-8: {}", e);
+                                Event::default()
+                                    .event("error")
+                                    .data(format!("UTF-8 error: {}", e))
+                            }
+                        };
+                        Some((Ok::<_, Infallible>(event), (read_half, tools_service)))
+                    }
+                    Err(e) => {
+                        log::error!("Error reading frame: {}", e);
+                        let event = Event::default()
+                            .event("error")
+                            .data(format!("Frame error: {}", e));
+                        Some((Ok::<_, Infallible>(event), (read_half, tools_service)))
+                    }
                 }
+            } else {
+                None
             }
-        } else {
-            None
-        }
-    })
+        },
+    )
+}
+
+/// A message of the router as the client gets it: `tools/list` results with the
+/// annotations of their tools and `initialize` results with the advertised version.
+/// None when the message is sent as is
+fn outgoing_message(message: &str, tools_service: &MCPToolsService) -> Option<String> {
+    if !message.contains("\"tools\"") && !message.contains("\"serverInfo\"") {
+        return None;
+    }
+    let mut value: Value = serde_json::from_str(message).ok()?;
+    let annotated = tools_service.annotate_tools_list(&mut value);
+    let identified = tools_service.identify_server(&mut value);
+    if !annotated && !identified {
+        return None;
+    }
+    serde_json::to_string(&value).ok()
 }
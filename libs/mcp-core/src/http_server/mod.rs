@@ -2,5 +2,5 @@ pub mod handlers;
 mod routes;
 
 // Re-export public items
-pub use self::handlers::{JsonRpcError, JsonRpcRequest, JsonRpcResponse};
-pub use self::routes::start_http_server;
+pub use self::handlers::{JsonRpcError, JsonRpcPayload, JsonRpcRequest, JsonRpcResponse};
+pub use self::routes::{create_router, serve_http, start_http_server};
@@ -5,52 +5,75 @@ use axum::{
 use log::{error, info};
 use std::net::SocketAddr;
 use std::sync::Arc;
+use tokio::net::TcpListener;
 use tower_http::cors::CorsLayer;
 
 use crate::core::mcp_core::MCPCore;
-use crate::http_server::handlers::{handle_mcp_request, health_check, sse_handler, sse_post_handler};
+use crate::http_server::handlers::{
+    events_handler, handle_mcp_request, health_check, metrics_handler, sse_handler,
+    sse_post_handler,
+};
 use crate::mcp_server::mcp_router::MCPDockmasterRouter;
 
 pub async fn start_http_server(mcp_core: MCPCore, port: u16) -> Result<(), String> {
+    let addr = SocketAddr::from(([127, 0, 0, 1], port));
+    let listener = TcpListener::bind(&addr)
+        .await
+        .map_err(|e| format!("Failed to bind to address: {}", e))?;
+
+    serve_http(mcp_core, listener).await?;
+    Ok(())
+}
+
+/// Serve the HTTP routes on `listener` in the background, returning the address served
+///
+/// Tests bind port 0 and talk to the returned address.
+pub async fn serve_http(mcp_core: MCPCore, listener: TcpListener) -> Result<SocketAddr, String> {
+    let addr = listener
+        .local_addr()
+        .map_err(|e| format!("Failed to get the server address: {}", e))?;
+    let app = create_router(mcp_core).await;
+    info!("MCP HTTP server starting on {}", addr);
+
+    // Start the server in a separate task
+    tokio::spawn(async move {
+        // With axum 0.8.1, we need to create a service
+        let service = app.into_make_service();
+        if let Err(e) = axum::serve(listener, service).await {
+            error!("MCP HTTP server error: {}", e);
+        } else {
+            info!("MCP HTTP server terminated normally");
+        }
+    });
+
+    Ok(addr)
+}
+
+/// Routes of the HTTP server
+pub async fn create_router(mcp_core: MCPCore) -> Router {
     // Create our MCP router that will handle RPC requests
     let mcp_router = MCPDockmasterRouter::new(mcp_core.clone()).await;
-    
+
     // Update the tools cache
     if let Err(e) = mcp_router.update_tools_cache("initialization").await {
         error!("Failed to update tools cache after initialization: {}", e);
     }
-    
+    // Clients are told when servers add or remove tools
+    mcp_router.forward_tools_updates();
+
     // Wrap the router in an Arc for sharing
     let mcp_router = Arc::new(mcp_router);
 
     // Set up the HTTP routes
-    let app = Router::new()
+    Router::new()
         .route("/", get(health_check))
         .route("/health", get(health_check))
+        .route("/metrics", get(metrics_handler))
         .route("/mcp/sse", get(sse_handler).post(sse_post_handler))
+        .route("/mcp/events", get(events_handler))
         .route("/mcp-proxy", post(handle_mcp_request))
         .route("/mcp", post(handle_mcp_request))
         .layer(Extension(mcp_core))
         .layer(Extension(mcp_router))
-        .layer(CorsLayer::permissive());
-
-    let addr = SocketAddr::from(([127, 0, 0, 1], port));
-    info!("MCP HTTP server starting on {}", addr);
-
-    let listener = tokio::net::TcpListener::bind(&addr)
-        .await
-        .map_err(|e| format!("Failed to bind to address: {}", e))?;
-
-    // Start the server in a separate task
-    tokio::spawn(async move {
-        // With axum 0.8.1, we need to create a service
-        let service = app.into_make_service();
-        if let Err(e) = axum::serve(listener, service).await {
-            error!("MCP HTTP server error: {}", e);
-        } else {
-            info!("MCP HTTP server terminated normally");
-        }
-    });
-
-    Ok(())
+        .layer(CorsLayer::permissive())
 }
@@ -0,0 +1,128 @@
+//! Name, version and instructions Dockmaster presents to MCP clients.
+//!
+//! The defaults come from the [`MCPCoreBuilder`], so each profile or workspace
+//! embedding a core can present an identity of its own, and the user can override each
+//! of them in the `server_identity` setting. The instructions can end with a summary of
+//! the servers whose tools are exposed, refreshed with the tool list.
+//!
+//! [`MCPCoreBuilder`]: crate::core::mcp_core_builder::MCPCoreBuilder
+
+use serde::{Deserialize, Serialize};
+
+/// Setting holding the [`ServerIdentityOverrides`] as JSON
+pub const SERVER_IDENTITY_SETTING: &str = "server_identity";
+
+/// Name advertised unless the builder or the user set another
+pub const DEFAULT_SERVER_NAME: &str = "mcp-dockmaster-server";
+
+/// Instructions advertised unless the builder or the user set others
+pub const DEFAULT_INSTRUCTIONS: &str = "MCP Dockmaster aggregates the MCP servers installed \
+    by the user and exposes their tools as its own. Its built-in tools search the registry \
+    for servers, install, configure and uninstall them, and list the installed ones.";
+
+/// Servers named in the summary, the others are counted
+pub const SUMMARY_MAX_SERVERS: usize = 20;
+
+/// What `initialize` answers with
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServerIdentity {
+    pub name: String,
+    pub version: String,
+    pub instructions: String,
+    /// End the instructions with the servers whose tools are exposed
+    pub summarize_servers: bool,
+}
+
+impl Default for ServerIdentity {
+    fn default() -> Self {
+        Self {
+            name: DEFAULT_SERVER_NAME.to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            instructions: DEFAULT_INSTRUCTIONS.to_string(),
+            summarize_servers: true,
+        }
+    }
+}
+
+/// What the user changed of the identity, unset fields keep the defaults
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServerIdentityOverrides {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instructions: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summarize_servers: Option<bool>,
+}
+
+impl ServerIdentityOverrides {
+    /// Refuse a blank name or version, clients show them to the user
+    pub fn validate(&self) -> Result<(), String> {
+        for (field, value) in [("name", &self.name), ("version", &self.version)] {
+            if value
+                .as_deref()
+                .is_some_and(|value| value.trim().is_empty())
+            {
+                return Err(format!("The server {} can't be empty", field));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ServerIdentity {
+    /// This identity with what the user changed of it
+    pub fn with_overrides(mut self, overrides: &ServerIdentityOverrides) -> Self {
+        if let Some(name) = &overrides.name {
+            self.name = name.trim().to_string();
+        }
+        if let Some(version) = &overrides.version {
+            self.version = version.trim().to_string();
+        }
+        if let Some(instructions) = &overrides.instructions {
+            self.instructions = instructions.trim().to_string();
+        }
+        if let Some(summarize_servers) = overrides.summarize_servers {
+            self.summarize_servers = summarize_servers;
+        }
+        self
+    }
+
+    /// Instructions followed by the summary of `servers`, as (server id, tool count),
+    /// when enabled
+    pub fn advertised_instructions(&self, servers: &[(String, usize)]) -> String {
+        let summary = if self.summarize_servers {
+            servers_summary(servers)
+        } else {
+            String::new()
+        };
+        match (self.instructions.is_empty(), summary.is_empty()) {
+            (_, true) => self.instructions.clone(),
+            (true, false) => summary,
+            (false, false) => format!("{}\n\n{}", self.instructions, summary),
+        }
+    }
+}
+
+/// "Aggregates: filesystem (3 tools), github (14 tools).", empty without servers
+pub fn servers_summary(servers: &[(String, usize)]) -> String {
+    if servers.is_empty() {
+        return String::new();
+    }
+    let named: Vec<String> = servers
+        .iter()
+        .take(SUMMARY_MAX_SERVERS)
+        .map(|(server_id, tool_count)| match tool_count {
+            1 => format!("{} (1 tool)", server_id),
+            _ => format!("{} ({} tools)", server_id, tool_count),
+        })
+        .collect();
+    let summary = format!("Aggregates: {}", named.join(", "));
+    match servers.len().saturating_sub(SUMMARY_MAX_SERVERS) {
+        0 => format!("{}.", summary),
+        1 => format!("{} and 1 more server.", summary),
+        more => format!("{} and {} more servers.", summary, more),
+    }
+}
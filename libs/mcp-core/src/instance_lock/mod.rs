@@ -0,0 +1,178 @@
+//! Lock keeping a single Dockmaster running per profile.
+//!
+//! Two instances on the same data directory would share the SQLite file, fight over
+//! the HTTP port and spawn every server twice. `MCPCore::init` takes the lock before
+//! anything else: a file in the data directory with the pid, process start time and
+//! port of the instance holding it. A second instance finds it and stops with the
+//! address of the running one. The file of an instance that crashed is left behind,
+//! it is replaced once its process is found gone, a recycled pid doesn't count.
+
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::utils::process::{current_process, is_process_alive, ProcessInfo};
+
+/// Name of the lock file in the data directory
+pub const INSTANCE_LOCK_FILE: &str = "dockmaster.lock";
+
+/// Instance holding the lock, as written in the lock file
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstanceLockInfo {
+    pub pid: u32,
+    /// Start time of the process, in seconds since the Unix epoch
+    pub process_start_time: u64,
+    /// Port of its HTTP server
+    pub port: u16,
+    pub app_name: String,
+    /// When it took the lock, in milliseconds since the Unix epoch
+    pub locked_at: u64,
+}
+
+impl InstanceLockInfo {
+    /// Lock of this process
+    pub fn current(port: u16, app_name: &str) -> Self {
+        let process = current_process();
+        Self {
+            pid: std::process::id(),
+            process_start_time: process.map(|p| p.start_time).unwrap_or(0),
+            port,
+            app_name: app_name.to_string(),
+            locked_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0),
+        }
+    }
+
+    /// Address of the JSON-RPC server of the instance
+    pub fn address(&self) -> String {
+        format!("http://127.0.0.1:{}/mcp-proxy", self.port)
+    }
+
+    /// Whether the process that took the lock still runs
+    pub fn is_alive(&self) -> bool {
+        is_process_alive(&ProcessInfo {
+            pid: self.pid,
+            start_time: self.process_start_time,
+            command: String::new(),
+        })
+    }
+}
+
+/// Why the lock could not be taken
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InstanceLockError {
+    /// Another instance runs on the same data directory
+    Held(InstanceLockInfo),
+    /// The lock file can't be written
+    Io(String),
+}
+
+impl fmt::Display for InstanceLockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Held(holder) => write!(
+                f,
+                "MCP Dockmaster is already running for this profile ({}, pid {}), \
+                 use it at {} or stop it first",
+                holder.app_name,
+                holder.pid,
+                holder.address()
+            ),
+            Self::Io(e) => write!(f, "Failed to take the instance lock: {}", e),
+        }
+    }
+}
+
+/// The lock of this instance, released when dropped
+#[derive(Debug)]
+pub struct InstanceLock {
+    path: PathBuf,
+    info: InstanceLockInfo,
+}
+
+impl InstanceLock {
+    /// Take the lock of `data_dir`, replacing the lock of an instance that is gone
+    pub fn acquire(data_dir: &Path, port: u16, app_name: &str) -> Result<Self, InstanceLockError> {
+        Self::acquire_with(
+            data_dir,
+            InstanceLockInfo::current(port, app_name),
+            InstanceLockInfo::is_alive,
+        )
+    }
+
+    /// Take the lock for `info`, `is_alive` telling whether the holder of an existing
+    /// lock still runs
+    pub fn acquire_with(
+        data_dir: &Path,
+        info: InstanceLockInfo,
+        is_alive: impl Fn(&InstanceLockInfo) -> bool,
+    ) -> Result<Self, InstanceLockError> {
+        let path = data_dir.join(INSTANCE_LOCK_FILE);
+        let json = serde_json::to_string_pretty(&info)
+            .map_err(|e| InstanceLockError::Io(e.to_string()))?;
+        // Once more after removing a stale lock, another instance may take it meanwhile
+        for _ in 0..2 {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut file) => {
+                    file.write_all(json.as_bytes())
+                        .map_err(|e| InstanceLockError::Io(e.to_string()))?;
+                    info!("Took the instance lock {}", path.display());
+                    return Ok(Self { path, info });
+                }
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => {}
+                Err(e) => return Err(InstanceLockError::Io(e.to_string())),
+            }
+            match read_lock(&path) {
+                Some(holder) if is_alive(&holder) => return Err(InstanceLockError::Held(holder)),
+                Some(holder) => warn!(
+                    "Replacing the instance lock of pid {}, its process is gone",
+                    holder.pid
+                ),
+                None => warn!("Replacing the instance lock, it can't be read"),
+            }
+            match fs::remove_file(&path) {
+                Ok(()) => {}
+                Err(e) if e.kind() == ErrorKind::NotFound => {}
+                Err(e) => return Err(InstanceLockError::Io(e.to_string())),
+            }
+        }
+        Err(InstanceLockError::Io(format!(
+            "{} keeps being recreated",
+            path.display()
+        )))
+    }
+
+    /// Instance holding the lock
+    pub fn info(&self) -> &InstanceLockInfo {
+        &self.info
+    }
+
+    /// Path of the lock file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for InstanceLock {
+    fn drop(&mut self) {
+        // The file is only ours while it holds our lock
+        if read_lock(&self.path).as_ref() == Some(&self.info) {
+            if let Err(e) = fs::remove_file(&self.path) {
+                warn!("Failed to release the instance lock: {}", e);
+            }
+        }
+    }
+}
+
+/// Instance holding the lock at `path`, None when there is none or it can't be read
+pub fn read_lock(path: &Path) -> Option<InstanceLockInfo> {
+    let json = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&json).ok()
+}
@@ -0,0 +1,114 @@
+//! Reading JSON-RPC messages from the stdout of a server, however they are split.
+//!
+//! Most servers write one message per line, but some pretty-print their messages over
+//! several lines, and large messages reach us in chunks. [`JsonMessageCodec`] parses
+//! complete JSON values out of the bytes read so far instead of splitting on newlines,
+//! and keeps lines that aren't JSON apart so they can be logged. A message isn't
+//! buffered past [`DEFAULT_MAX_MESSAGE_BYTES`], or the limit the codec is built with.
+
+use bytes::{Buf, BytesMut};
+use serde_json::Value;
+use tokio_util::codec::Decoder;
+
+/// Bytes a single message may take, unless the codec is built with another limit
+pub const DEFAULT_MAX_MESSAGE_BYTES: usize = 10 * 1024 * 1024;
+
+/// What a server wrote to stdout
+#[derive(Clone, Debug, PartialEq)]
+pub enum StdoutMessage {
+    /// A complete JSON value
+    Json(Value),
+    /// A line that isn't JSON, like a log line printed to stdout
+    Text(String),
+}
+
+/// Decodes [`StdoutMessage`]s from a byte stream
+///
+/// Fails with [`std::io::ErrorKind::InvalidData`] once a message grows past the
+/// limit, the stream can't be read further.
+#[derive(Clone, Copy, Debug)]
+pub struct JsonMessageCodec {
+    max_message_bytes: usize,
+}
+
+impl Default for JsonMessageCodec {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_MESSAGE_BYTES)
+    }
+}
+
+impl JsonMessageCodec {
+    pub fn new(max_message_bytes: usize) -> Self {
+        Self { max_message_bytes }
+    }
+
+    fn oversized(&self) -> std::io::Error {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("message larger than {} bytes", self.max_message_bytes),
+        )
+    }
+
+    /// The line at the start of `src` as text, without its newline
+    fn take_line(src: &mut BytesMut, end: usize) -> StdoutMessage {
+        let line = src.split_to(end);
+        let line = String::from_utf8_lossy(&line).trim_end().to_string();
+        if src.first() == Some(&b'\n') {
+            src.advance(1);
+        }
+        StdoutMessage::Text(line)
+    }
+}
+
+impl Decoder for JsonMessageCodec {
+    type Item = StdoutMessage;
+    type Error = std::io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        // Blank lines and the whitespace between messages
+        let blank = src.iter().take_while(|b| b.is_ascii_whitespace()).count();
+        src.advance(blank);
+        if src.is_empty() {
+            return Ok(None);
+        }
+        let newline = src.iter().position(|&b| b == b'\n');
+
+        if matches!(src[0], b'{' | b'[') {
+            let mut values = serde_json::Deserializer::from_slice(&src[..]).into_iter::<Value>();
+            match values.next() {
+                Some(Ok(value)) => {
+                    let end = values.byte_offset();
+                    src.advance(end);
+                    return Ok(Some(StdoutMessage::Json(value)));
+                }
+                // Not all of it was read yet
+                Some(Err(e)) if e.is_eof() => {
+                    if src.len() > self.max_message_bytes {
+                        return Err(self.oversized());
+                    }
+                    return Ok(None);
+                }
+                // Starts like JSON but isn't, kept as a text line
+                _ => {}
+            }
+        }
+
+        match newline {
+            Some(end) => Ok(Some(Self::take_line(src, end))),
+            None if src.len() > self.max_message_bytes => Err(self.oversized()),
+            None => Ok(None),
+        }
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        match self.decode(src)? {
+            Some(message) => Ok(Some(message)),
+            // What is left can't be completed anymore
+            None if src.is_empty() => Ok(None),
+            None => {
+                let end = src.len();
+                Ok(Some(Self::take_line(src, end)))
+            }
+        }
+    }
+}
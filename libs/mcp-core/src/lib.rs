@@ -1,14 +1,101 @@
+//! Core of MCP Dockmaster: installs MCP servers, runs them and proxies their tools.
+//!
+//! Applications embed it by building an [`MCPCore`](prelude::MCPCore) over a database,
+//! then use the extension traits re-exported by the [`prelude`]:
+//!
+//! ```no_run
+//! use mcp_core::prelude::*;
+//!
+//! # async fn embed() -> Result<(), String> {
+//! let db = DBManager::with_path("dockmaster.db".into())?;
+//! let core = MCPCoreBuilder::new(db).app_name("my-app".to_string()).build();
+//! core.init().await.map_err(|e| format!("{:?}", e))?;
+//!
+//! let mut events = core.event_bus.subscribe();
+//! core.register_server(ServerRegistrationRequest {
+//!     server_id: "hello".to_string(),
+//!     server_name: "Hello".to_string(),
+//!     description: "Says hello".to_string(),
+//!     tools_type: "node".to_string(),
+//!     configuration: Some(ServerConfiguration {
+//!         command: Some("npx".to_string()),
+//!         args: Some(vec!["-y".to_string(), "hello-mcp".to_string()]),
+//!         ..Default::default()
+//!     }),
+//!     distribution: None,
+//!     provenance: None,
+//!     authentication: None,
+//!     enabled: None,
+//! })
+//! .await?;
+//! let response = core
+//!     .execute_proxy_tool(ToolExecutionRequest {
+//!         tool_id: "hello:say_hello".to_string(),
+//!         parameters: serde_json::json!({}),
+//!     })
+//!     .await?;
+//! println!("{:?} {:?}", response.result, events.try_recv());
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Optional parts are behind features, all on by default:
+//! - `http-server`: the JSON-RPC and SSE server started by `init`, and the MCP router
+//! - `registry`: fetching the server registry and importing servers from GitHub
+//! - `webhooks`: delivering webhook notifications, without it every delivery fails
+//!
+//! The `lock-debug` feature, off by default, records who holds the main locks of the
+//! core, see [`lock_debug`].
+//!
+//! Without `registry`, the registry is only available through an injected
+//! [`RegistryClient`](prelude::RegistryClient).
+
+pub mod approvals;
+pub mod arch;
+pub mod cancellation;
+pub mod capabilities;
+pub mod config_file;
+pub mod config_history;
 pub mod core;
 pub mod database;
+pub mod diagnose;
+pub mod diagnostics;
+pub mod events;
+pub mod footprint;
+pub mod health;
+pub mod hooks;
+#[cfg(feature = "http-server")]
 pub mod http_server;
+pub mod identity;
+pub mod instance_lock;
+pub mod json_message_codec;
+pub mod jsonrpc_frame_codec;
+pub mod lifecycle;
+pub mod lock_debug;
 pub mod mcp_installers;
+pub mod mcp_server;
 pub mod mcp_state;
+pub mod metrics;
 pub mod models;
+pub mod operations;
+pub mod prelude;
+pub mod rate_limits;
+pub mod read_only;
+pub mod recordings;
 pub mod registry;
+pub mod relocation;
+pub mod schedules;
 pub mod schema;
+pub mod sealed;
+pub mod selftest;
+pub mod server_info;
+pub mod server_logs;
+pub mod sessions;
 pub mod spawned_process;
-pub mod mcp_server;
-pub mod jsonrpc_frame_codec;
+pub mod tool_limits;
+pub mod tools_snapshot;
+pub mod undo;
+pub mod webhooks;
 
 // Re-export commonly used types and functions
 pub use error::{MCPError, MCPResult};
@@ -0,0 +1,234 @@
+//! When servers started, crashed, restarted and stopped, to triage the flaky ones.
+//!
+//! Every transition of a server is appended to its history by the registry, see
+//! [`ServerRegistry::record_lifecycle_event`]. The last [`LIFECYCLE_HISTORY_KEPT`] events of
+//! each server are kept in memory and answered by `server/history`, the last
+//! [`LIFECYCLE_EVENTS_PERSISTED`] are saved so that the history survives a restart of
+//! Dockmaster. `server/status`, the server list and the dashboard summarize it as a
+//! [`ServerUptime`].
+//!
+//! [`ServerRegistry::record_lifecycle_event`]:
+//! crate::registry::server_registry::ServerRegistry::record_lifecycle_event
+
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+/// Events kept in memory per server
+pub const LIFECYCLE_HISTORY_KEPT: usize = 100;
+
+/// Events saved in the database per server
+pub const LIFECYCLE_EVENTS_PERSISTED: i64 = 20;
+
+/// Events included in a [`ServerUptime`]
+pub const RECENT_LIFECYCLE_EVENTS: usize = 5;
+
+/// Restarts are counted over this window
+pub const RESTART_WINDOW: Duration = Duration::from_secs(60 * 60);
+
+/// What happened to a server
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LifecycleKind {
+    Started,
+    StartFailed,
+    /// The process exited on its own
+    Crashed,
+    /// Restarted after its connection died
+    RestartedBySupervisor,
+    /// Restarted by the user
+    Restarted,
+    /// Restarted to apply a configuration change
+    ConfigRestart,
+    StoppedByUser,
+}
+
+impl LifecycleKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LifecycleKind::Started => "started",
+            LifecycleKind::StartFailed => "start_failed",
+            LifecycleKind::Crashed => "crashed",
+            LifecycleKind::RestartedBySupervisor => "restarted_by_supervisor",
+            LifecycleKind::Restarted => "restarted",
+            LifecycleKind::ConfigRestart => "config_restart",
+            LifecycleKind::StoppedByUser => "stopped_by_user",
+        }
+    }
+
+    pub fn parse(kind: &str) -> Option<Self> {
+        [
+            LifecycleKind::Started,
+            LifecycleKind::StartFailed,
+            LifecycleKind::Crashed,
+            LifecycleKind::RestartedBySupervisor,
+            LifecycleKind::Restarted,
+            LifecycleKind::ConfigRestart,
+            LifecycleKind::StoppedByUser,
+        ]
+        .into_iter()
+        .find(|known| known.as_str() == kind)
+    }
+
+    /// Whether the event is a restart counted in [`ServerUptime::restarts_last_hour`]
+    pub fn is_restart(&self) -> bool {
+        matches!(
+            self,
+            LifecycleKind::RestartedBySupervisor
+                | LifecycleKind::Restarted
+                | LifecycleKind::ConfigRestart
+        )
+    }
+}
+
+/// A transition of a server
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LifecycleEvent {
+    pub server_id: String,
+    pub kind: LifecycleKind,
+    /// When it happened, in milliseconds since the epoch
+    pub at: u64,
+    /// Exit code of a crashed process, when it left one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    /// Why a start failed or a connection died
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl LifecycleEvent {
+    /// An event that happens now
+    pub fn now(server_id: &str, kind: LifecycleKind) -> Self {
+        Self {
+            server_id: server_id.to_string(),
+            kind,
+            at: now_millis(),
+            exit_code: None,
+            detail: None,
+        }
+    }
+
+    pub fn with_exit_code(mut self, exit_code: Option<i32>) -> Self {
+        self.exit_code = exit_code;
+        self
+    }
+
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+}
+
+/// How long a server has been up and how often it restarted lately
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServerUptime {
+    /// When the running process started, in milliseconds since the epoch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<u64>,
+    /// Seconds since the running process started
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uptime_secs: Option<u64>,
+    /// Restarts within [`RESTART_WINDOW`]
+    pub restarts_last_hour: usize,
+    /// Restarts in the [`LIFECYCLE_HISTORY_KEPT`] events kept
+    #[serde(default)]
+    pub restart_count: usize,
+    /// Exit code of the last crash, when it left one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_exit_code: Option<i32>,
+    /// Why the server last failed to start or crashed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    /// The last [`RECENT_LIFECYCLE_EVENTS`] events, oldest first
+    pub recent: Vec<LifecycleEvent>,
+}
+
+impl ServerUptime {
+    /// Summarize the history of a server, `started_at` being the start of its running
+    /// process if any
+    pub fn new(started_at: Option<u64>, history: &[LifecycleEvent], now: u64) -> Self {
+        let window_start = now.saturating_sub(RESTART_WINDOW.as_millis() as u64);
+        let last_failure = history.iter().rev().find(|event| {
+            matches!(
+                event.kind,
+                LifecycleKind::StartFailed | LifecycleKind::Crashed
+            )
+        });
+        Self {
+            started_at,
+            uptime_secs: started_at.map(|started_at| now.saturating_sub(started_at) / 1000),
+            restarts_last_hour: history
+                .iter()
+                .filter(|event| event.kind.is_restart() && event.at >= window_start)
+                .count(),
+            restart_count: history
+                .iter()
+                .filter(|event| event.kind.is_restart())
+                .count(),
+            last_exit_code: history
+                .iter()
+                .rev()
+                .find(|event| event.kind == LifecycleKind::Crashed)
+                .and_then(|event| event.exit_code),
+            last_error: last_failure.and_then(|event| event.detail.clone()),
+            recent: history
+                .iter()
+                .skip(history.len().saturating_sub(RECENT_LIFECYCLE_EVENTS))
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
+/// Every event of a server kept in memory, answered by `server/history`
+#[derive(Clone, Debug, Serialize)]
+pub struct ServerHistory {
+    pub server_id: String,
+    /// Oldest first
+    pub events: Vec<LifecycleEvent>,
+    pub uptime: ServerUptime,
+}
+
+/// Histories of every server, oldest event first
+#[derive(Default)]
+pub(crate) struct LifecycleHistory {
+    events: HashMap<String, VecDeque<LifecycleEvent>>,
+}
+
+impl LifecycleHistory {
+    /// Histories loaded from the database, oldest event first
+    pub fn from_events(events: Vec<LifecycleEvent>) -> Self {
+        let mut history = Self::default();
+        for event in events {
+            history.record(event);
+        }
+        history
+    }
+
+    pub fn record(&mut self, event: LifecycleEvent) {
+        let events = self.events.entry(event.server_id.clone()).or_default();
+        events.push_back(event);
+        while events.len() > LIFECYCLE_HISTORY_KEPT {
+            events.pop_front();
+        }
+    }
+
+    pub fn events(&self, server_id: &str) -> Vec<LifecycleEvent> {
+        self.events
+            .get(server_id)
+            .map(|events| events.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn remove(&mut self, server_id: &str) {
+        self.events.remove(server_id);
+    }
+}
+
+pub fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or_default()
+}
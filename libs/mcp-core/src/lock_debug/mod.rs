@@ -0,0 +1,313 @@
+//! Instrumented locks, to investigate freezes of the app.
+//!
+//! The main locks of the core ([`LOCK_MCP_STATE`], [`LOCK_TOOL_REGISTRY`] and the maps of
+//! the MCP state) are [`TrackedRwLock`]s. With the `lock-debug` feature, each guard taken
+//! records the name of the lock, where it was taken and when, until it is dropped. Those
+//! holds are listed by `system/dump_state`, and a watchdog logs a warning with a dump of
+//! the state when one of them lasts longer than [`LOCK_HOLD_WARNING`].
+//!
+//! Without the feature a [`TrackedRwLock`] is a plain tokio [`RwLock`], its guards are the
+//! tokio guards and no hold is ever recorded.
+
+use std::collections::{BTreeSet, HashSet};
+use std::future::Future;
+#[cfg(feature = "lock-debug")]
+use std::ops::{Deref, DerefMut};
+#[cfg(feature = "lock-debug")]
+use std::panic::Location;
+use std::time::Duration;
+
+use serde::Serialize;
+use tokio::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard, TryLockError};
+
+use crate::models::types::ServerStatus;
+
+/// Name of the lock of the MCP state in [`MCPCore`](crate::core::mcp_core::MCPCore)
+pub const LOCK_MCP_STATE: &str = "mcp_state";
+/// Name of the lock of the server registry
+pub const LOCK_TOOL_REGISTRY: &str = "tool_registry";
+/// Name of the lock of the running servers
+pub const LOCK_MCP_CLIENTS: &str = "mcp_clients";
+/// Name of the lock of the tools of the running servers
+pub const LOCK_SERVER_TOOLS: &str = "server_tools";
+
+/// Hold after which the watchdog started by `init` logs a warning
+pub const LOCK_HOLD_WARNING: Duration = Duration::from_secs(5);
+
+/// Shortest interval between two checks of the watchdog
+const WATCHDOG_MIN_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Whether locks record their holds, with the `lock-debug` feature
+pub const LOCK_TRACKING: bool = cfg!(feature = "lock-debug");
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LockMode {
+    Read,
+    Write,
+}
+
+/// A guard of a [`TrackedRwLock`] that is still alive
+#[derive(Clone, Debug, Serialize)]
+pub struct LockHold {
+    /// Unique among the holds since the start
+    pub id: u64,
+    pub lock: &'static str,
+    pub mode: LockMode,
+    /// Source location that took the guard, as `file:line`
+    pub holder: String,
+    /// Time since the guard was taken
+    #[serde(rename = "held_ms", serialize_with = "serialize_millis")]
+    pub held_for: Duration,
+}
+
+fn serialize_millis<S: serde::Serializer>(duration: &Duration, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_u64(duration.as_millis() as u64)
+}
+
+#[cfg(feature = "lock-debug")]
+mod holds {
+    use std::collections::BTreeMap;
+    use std::panic::Location;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Mutex;
+    use std::time::Instant;
+
+    use once_cell::sync::Lazy;
+
+    use super::{LockHold, LockMode};
+
+    struct Hold {
+        lock: &'static str,
+        mode: LockMode,
+        holder: &'static Location<'static>,
+        acquired_at: Instant,
+    }
+
+    static HOLDS: Lazy<Mutex<BTreeMap<u64, Hold>>> = Lazy::new(Default::default);
+    static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+    /// Removes its hold when dropped, with the guard it is kept beside
+    pub struct HoldToken(u64);
+
+    impl Drop for HoldToken {
+        fn drop(&mut self) {
+            HOLDS.lock().unwrap().remove(&self.0);
+        }
+    }
+
+    pub fn record(
+        lock: &'static str,
+        mode: LockMode,
+        holder: &'static Location<'static>,
+    ) -> HoldToken {
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        let hold = Hold {
+            lock,
+            mode,
+            holder,
+            acquired_at: Instant::now(),
+        };
+        HOLDS.lock().unwrap().insert(id, hold);
+        HoldToken(id)
+    }
+
+    pub fn snapshot() -> Vec<LockHold> {
+        HOLDS
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(id, hold)| LockHold {
+                id: *id,
+                lock: hold.lock,
+                mode: hold.mode,
+                holder: format!("{}:{}", hold.holder.file(), hold.holder.line()),
+                held_for: hold.acquired_at.elapsed(),
+            })
+            .collect()
+    }
+}
+
+/// Guard of a [`TrackedRwLock`] with the hold it records
+#[cfg(feature = "lock-debug")]
+pub struct Tracked<G> {
+    guard: G,
+    _hold: holds::HoldToken,
+}
+
+#[cfg(feature = "lock-debug")]
+impl<G: Deref> Deref for Tracked<G> {
+    type Target = G::Target;
+
+    fn deref(&self) -> &Self::Target {
+        &self.guard
+    }
+}
+
+#[cfg(feature = "lock-debug")]
+impl<G: DerefMut> DerefMut for Tracked<G> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.guard
+    }
+}
+
+#[cfg(feature = "lock-debug")]
+pub type ReadGuard<'a, T> = Tracked<RwLockReadGuard<'a, T>>;
+#[cfg(feature = "lock-debug")]
+pub type WriteGuard<'a, T> = Tracked<RwLockWriteGuard<'a, T>>;
+#[cfg(not(feature = "lock-debug"))]
+pub type ReadGuard<'a, T> = RwLockReadGuard<'a, T>;
+#[cfg(not(feature = "lock-debug"))]
+pub type WriteGuard<'a, T> = RwLockWriteGuard<'a, T>;
+
+/// A tokio [`RwLock`] whose holds are recorded with the `lock-debug` feature
+#[derive(Debug)]
+pub struct TrackedRwLock<T> {
+    inner: RwLock<T>,
+    #[cfg(feature = "lock-debug")]
+    name: &'static str,
+}
+
+impl<T> TrackedRwLock<T> {
+    /// A lock named `name` in the holds it records
+    #[allow(unused_variables)]
+    pub fn new(name: &'static str, value: T) -> Self {
+        Self {
+            inner: RwLock::new(value),
+            #[cfg(feature = "lock-debug")]
+            name,
+        }
+    }
+
+    #[cfg(feature = "lock-debug")]
+    #[track_caller]
+    pub fn read(&self) -> impl Future<Output = ReadGuard<'_, T>> {
+        let holder = Location::caller();
+        async move {
+            let guard = self.inner.read().await;
+            Tracked {
+                guard,
+                _hold: holds::record(self.name, LockMode::Read, holder),
+            }
+        }
+    }
+
+    #[cfg(not(feature = "lock-debug"))]
+    #[inline]
+    pub fn read(&self) -> impl Future<Output = ReadGuard<'_, T>> {
+        self.inner.read()
+    }
+
+    #[cfg(feature = "lock-debug")]
+    #[track_caller]
+    pub fn write(&self) -> impl Future<Output = WriteGuard<'_, T>> {
+        let holder = Location::caller();
+        async move {
+            let guard = self.inner.write().await;
+            Tracked {
+                guard,
+                _hold: holds::record(self.name, LockMode::Write, holder),
+            }
+        }
+    }
+
+    #[cfg(not(feature = "lock-debug"))]
+    #[inline]
+    pub fn write(&self) -> impl Future<Output = WriteGuard<'_, T>> {
+        self.inner.write()
+    }
+
+    /// Read guard if no writer holds the lock, without waiting
+    #[cfg_attr(feature = "lock-debug", track_caller)]
+    #[inline]
+    pub fn try_read(&self) -> Result<ReadGuard<'_, T>, TryLockError> {
+        let guard = self.inner.try_read()?;
+        #[cfg(feature = "lock-debug")]
+        let guard = Tracked {
+            guard,
+            _hold: holds::record(self.name, LockMode::Read, Location::caller()),
+        };
+        Ok(guard)
+    }
+
+    /// Read guard for code outside of the runtime, such as blocking tasks
+    #[cfg_attr(feature = "lock-debug", track_caller)]
+    #[inline]
+    pub fn blocking_read(&self) -> ReadGuard<'_, T> {
+        let guard = self.inner.blocking_read();
+        #[cfg(feature = "lock-debug")]
+        let guard = Tracked {
+            guard,
+            _hold: holds::record(self.name, LockMode::Read, Location::caller()),
+        };
+        guard
+    }
+}
+
+/// Guards alive now, always empty without the `lock-debug` feature
+#[cfg(feature = "lock-debug")]
+pub fn lock_holds() -> Vec<LockHold> {
+    holds::snapshot()
+}
+
+#[cfg(not(feature = "lock-debug"))]
+pub fn lock_holds() -> Vec<LockHold> {
+    Vec::new()
+}
+
+/// Guards alive for longer than `threshold`
+pub fn long_lock_holds(threshold: Duration) -> Vec<LockHold> {
+    lock_holds()
+        .into_iter()
+        .filter(|hold| hold.held_for > threshold)
+        .collect()
+}
+
+/// Check the holds every quarter of `threshold` and call `report` with those that just
+/// went past it, once per hold
+pub fn spawn_lock_watchdog<F>(threshold: Duration, report: F) -> tokio::task::JoinHandle<()>
+where
+    F: Fn(Vec<LockHold>) + Send + 'static,
+{
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval((threshold / 4).max(WATCHDOG_MIN_INTERVAL));
+        let mut reported = HashSet::new();
+        loop {
+            interval.tick().await;
+            let long_holds = long_lock_holds(threshold);
+            let current: HashSet<u64> = long_holds.iter().map(|hold| hold.id).collect();
+            reported.retain(|id| current.contains(id));
+            let new_holds: Vec<LockHold> = long_holds
+                .into_iter()
+                .filter(|hold| !reported.contains(&hold.id))
+                .collect();
+            if new_holds.is_empty() {
+                continue;
+            }
+            reported.extend(new_holds.iter().map(|hold| hold.id));
+            report(new_holds);
+        }
+    })
+}
+
+/// Running server as seen by `system/dump_state`
+#[derive(Clone, Debug, Serialize)]
+pub struct ServerStateDump {
+    pub server_id: String,
+    /// None when the server has no process or the lock of the running servers is taken
+    pub status: Option<ServerStatus>,
+    /// None when the server has no tools or their lock is taken
+    pub tool_count: Option<usize>,
+    /// Tool calls waiting on the server
+    pub in_flight: u64,
+}
+
+/// What the core is doing, taken without waiting on any lock
+#[derive(Clone, Debug, Serialize)]
+pub struct StateDump {
+    pub lock_tracking: bool,
+    pub lock_holds: Vec<LockHold>,
+    /// Locks held by a writer, whose content is left out of the dump
+    pub contended: BTreeSet<&'static str>,
+    pub servers: Vec<ServerStateDump>,
+}
@@ -2,7 +2,7 @@ use crate::utils::process::kill_process_by_name;
 
 use super::install_paths::get_claude_config_path;
 use super::{install_errors::ClaudeError, install_paths};
-use log::info;
+use log::{info, warn};
 use serde_json::{json, Value};
 use std::{fs, path::Path};
 
@@ -11,8 +11,8 @@ pub fn is_claude_installed(app_name: &str) -> Result<(), ClaudeError> {
 
     // Check if the file exists
     if !Path::new(&config_path).exists() {
-        println!("❌ MCP Dockmaster not installed in CLAUDE");
-        println!("Configuration file not found");
+        info!("❌ MCP Dockmaster not installed in CLAUDE");
+        info!("Configuration file not found");
         return Err(ClaudeError::ConfigNotFound(config_path));
     }
 
@@ -56,10 +56,10 @@ pub fn install_claude(app_name: &str, binary_path: &str) -> Result<(), ClaudeErr
 
     // Create backup if file exists
     if Path::new(&config_path).exists() {
-        println!("ℹ️ Creating backup of Claude config...");
+        info!("ℹ️ Creating backup of Claude config...");
         match install_paths::backup_file(&config_path) {
-            Ok(backup_path) => println!("✅ Backup created at: {}", backup_path),
-            Err(e) => println!("⚠️ Failed to create backup: {}", e),
+            Ok(backup_path) => info!("✅ Backup created at: {}", backup_path),
+            Err(e) => warn!("⚠️ Failed to create backup: {}", e),
         }
     }
 
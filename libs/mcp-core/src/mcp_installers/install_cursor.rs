@@ -1,7 +1,7 @@
 use std::{collections::HashMap, fs::File, path::PathBuf};
 
 use home::home_dir;
-use log::error;
+use log::{debug, error};
 use serde::{Deserialize, Serialize};
 
 use super::install_errors::CursorError;
@@ -46,9 +46,9 @@ pub fn is_cursor_installed(app_name: &str) -> Result<bool, CursorError> {
         );
         CursorError::ConfigNotFound(cursor_mcp_global_config_path.to_string_lossy().to_string())
     })?;
-    println!(
-        "cursor_mcp_global_config_file: {}",
-        cursor_mcp_global_config_as_str
+    debug!(
+        "Read the Cursor MCP global config ({} bytes)",
+        cursor_mcp_global_config_as_str.len()
     );
     let cursor_mcp_global_config: CursorMcpGlobalConfig =
         if cursor_mcp_global_config_as_str.is_empty() {
@@ -88,9 +88,9 @@ pub fn install_cursor(app_name: &str, binary_path: &str) -> Result<(), CursorErr
         })?
     };
 
-    println!(
-        "cursor_mcp_global_config_file: {}",
-        cursor_mcp_global_config_as_str
+    debug!(
+        "Read the Cursor MCP global config ({} bytes)",
+        cursor_mcp_global_config_as_str.len()
     );
     let mut cursor_mcp_global_config: CursorMcpGlobalConfig =
         if cursor_mcp_global_config_as_str.is_empty() {
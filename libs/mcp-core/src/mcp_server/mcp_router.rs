@@ -1,5 +1,6 @@
 use std::{future::Future, pin::Pin, sync::Arc};
 
+use log::{error, info, warn};
 use mcp_sdk_core::{
     handler::{PromptError, ResourceError},
     prompt::Prompt,
@@ -8,22 +9,36 @@ use mcp_sdk_core::{
 };
 use mcp_sdk_server::router::CapabilitiesBuilder;
 use serde_json::{json, Value};
-use log::{info, error};
+use tokio::sync::broadcast::error::RecvError;
+use uuid::Uuid;
 
 use crate::{
+    approvals::{ApprovalRequest, APPROVAL_WAIT},
+    cancellation::{call_id, CANCELLED_ERROR},
     core::mcp_core::MCPCore,
+    core::mcp_core_approvals_ext::McpCoreApprovalsExt,
     core::mcp_core_proxy_ext::McpCoreProxyExt,
-    models::types::{ToolExecutionRequest, ToolUninstallRequest},
-    registry::registry_search::{RegistrySearch, SearchError},
+    core::mcp_core_read_only_ext::McpCoreReadOnlyExt,
+    core::mcp_core_tools_ext::McpCoreToolsExt,
+    events::DockmasterEvent,
+    http_server::handlers::InstallOrigin,
     mcp_server::mcp_tools_service::MCPToolsService,
+    mcp_state::server_notifications::progress_topic,
+    models::server_id::{legacy_server_id_warning, request_server_id},
+    models::tool_id::proxy_tool_id,
+    models::types::{
+        ActionPolicy, AuditDecision, AuditEntry, GatedAction, ToolAnnotations,
+        ToolExecutionRequest, ToolUninstallRequest,
+    },
+    operations::{Operation, OperationState},
+    read_only::READ_ONLY_REASON,
+    registry::registry_search::{RegistrySearch, SearchError},
+    sessions::PostedToolCall,
 };
 
 use super::tools::{
-    TOOL_REGISTER_SERVER,
-    TOOL_SEARCH_SERVER,
-    TOOL_CONFIGURE_SERVER,
+    TOOL_CONFIGURE_SERVER, TOOL_LIST_INSTALLED_SERVERS, TOOL_REGISTER_SERVER, TOOL_SEARCH_SERVER,
     TOOL_UNINSTALL_SERVER,
-    TOOL_LIST_INSTALLED_SERVERS,
 };
 
 use super::notifications::broadcast_tools_list_changed;
@@ -33,8 +48,14 @@ use super::notifications::broadcast_tools_list_changed;
 #[derive(Clone)]
 pub struct MCPDockmasterRouter {
     mcp_core: MCPCore,
-    server_name: String,
     tools_service: Arc<MCPToolsService>,
+    /// Client session served by this router, if any
+    session_id: Option<String>,
+    /// Id the tool call run by this clone can be cancelled under, see `call_tool`
+    call_id: Option<String>,
+    /// Token the server reports the progress of the call run by this clone with, see
+    /// [`progress_topic`]
+    progress_token: Option<String>,
 }
 
 impl MCPDockmasterRouter {
@@ -43,11 +64,31 @@ impl MCPDockmasterRouter {
         let tools_service = MCPToolsService::initialize(mcp_core.clone()).await;
         Self {
             mcp_core,
-            server_name: "mcp-dockmaster-server".to_string(),
             tools_service,
+            session_id: None,
+            call_id: None,
+            progress_token: None,
+        }
+    }
+
+    /// Router serving a single client session
+    pub fn for_session(&self, session_id: &str) -> Self {
+        Self {
+            session_id: Some(session_id.to_string()),
+            ..self.clone()
         }
     }
 
+    /// Version advertised to MCP clients, the SDK's `Router` has no method for it
+    pub fn version(&self) -> String {
+        self.tools_service.identity().version
+    }
+
+    /// Cache of the tools listed by this router
+    pub fn tools_service(&self) -> Arc<MCPToolsService> {
+        self.tools_service.clone()
+    }
+
     /// Update the tools cache and broadcast a notification
     pub async fn update_tools_cache(&self, operation: &str) -> Result<(), String> {
         // Update the tools cache
@@ -57,44 +98,251 @@ impl MCPDockmasterRouter {
         }
 
         // Spawn the broadcast notification as a separate task
-        tokio::spawn(async {
+        let sessions = self.mcp_core.mcp_state.read().await.sessions.clone();
+        tokio::spawn(async move {
             tokio::time::sleep(std::time::Duration::from_secs(5)).await;
-            broadcast_tools_list_changed().await;
+            broadcast_tools_list_changed(&sessions).await;
         });
 
         Ok(())
     }
 
+    /// Tell every session that the tools changed whenever the core publishes a
+    /// [`DockmasterEvent::ToolsUpdated`], its publishers update the tools cache first
+    pub fn forward_tools_updates(&self) -> tokio::task::JoinHandle<()> {
+        let mut events = self.mcp_core.event_bus.subscribe();
+        let mcp_core = self.mcp_core.clone();
+        tokio::spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(envelope) => {
+                        if let DockmasterEvent::ToolsUpdated(update) = &envelope.event {
+                            info!(
+                                "Tools of {:?} changed, notifying sessions",
+                                update.server_ids
+                            );
+                            let sessions = mcp_core.mcp_state.read().await.sessions.clone();
+                            broadcast_tools_list_changed(&sessions).await;
+                        }
+                    }
+                    Err(RecvError::Lagged(missed)) => {
+                        // Some may have been tool updates
+                        warn!("Tools list notifications missed {} events", missed);
+                        let sessions = mcp_core.mcp_state.read().await.sessions.clone();
+                        broadcast_tools_list_changed(&sessions).await;
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+
+    /// Name of the MCP client of this router's session
+    async fn client_name(&self) -> Option<String> {
+        let session_id = self.session_id.as_ref()?;
+        let sessions = self.mcp_core.mcp_state.read().await.sessions.clone();
+        sessions.client_name(session_id).await
+    }
+
+    /// Run a management action requested by the model under the user's policy for it
+    ///
+    /// Under the `confirm` policy the action runs as an operation once the user approves
+    /// it. The call waits up to [`APPROVAL_WAIT`] for the outcome and otherwise returns a
+    /// `pending_approval` status with the operation id. Nothing runs in read-only mode.
+    async fn run_gated<F, Fut>(
+        &self,
+        action: GatedAction,
+        server_id: Option<String>,
+        summary: String,
+        run: F,
+    ) -> Result<Value, ToolError>
+    where
+        F: FnOnce(Self) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<Value, ToolError>> + Send + 'static,
+    {
+        let client = self.client_name().await;
+        if self.mcp_core.is_read_only().await {
+            info!(
+                "Refused {} request from {:?} in read-only mode",
+                action.as_str(),
+                client
+            );
+            self.mcp_core
+                .record_audit(
+                    AuditEntry::new(action, server_id, client, AuditDecision::Denied)
+                        .with_detail(format!("{} (read-only mode)", summary)),
+                )
+                .await;
+            return Ok(json!({
+                "success": false,
+                "status": READ_ONLY_REASON,
+                "message": format!(
+                    "MCP clients can't {} servers while MCP Dockmaster is in read-only mode.",
+                    action.as_str()
+                )
+            }));
+        }
+        match self.mcp_core.action_policy(action).await {
+            ActionPolicy::Allow => {
+                self.mcp_core
+                    .record_audit(
+                        AuditEntry::new(action, server_id, client, AuditDecision::Allowed)
+                            .with_detail(summary),
+                    )
+                    .await;
+                run(self.clone()).await
+            }
+            ActionPolicy::Deny => {
+                info!(
+                    "Denied {} request from {:?} by policy",
+                    action.as_str(),
+                    client
+                );
+                self.mcp_core
+                    .record_audit(
+                        AuditEntry::new(action, server_id, client, AuditDecision::Denied)
+                            .with_detail(summary),
+                    )
+                    .await;
+                Ok(json!({
+                    "success": false,
+                    "status": "denied",
+                    "message": format!(
+                        "The user does not allow MCP clients to {} servers. They can do it from the MCP Dockmaster app.",
+                        action.as_str()
+                    )
+                }))
+            }
+            ActionPolicy::Confirm => {
+                let id = Uuid::new_v4().to_string();
+                // Subscribe before the operation starts so that its outcome can't be missed
+                let mut events = self.mcp_core.event_bus.subscribe();
+                let decision = self.mcp_core.approvals.request(ApprovalRequest::new(
+                    id.clone(),
+                    action,
+                    server_id.clone(),
+                    client,
+                    summary,
+                ));
+                let this = self.clone();
+                self.mcp_core.operations.spawn_with_id(
+                    id.clone(),
+                    action.as_str(),
+                    server_id,
+                    true,
+                    move |handle| async move {
+                        handle.set_awaiting_approval(true);
+                        if !decision.await.unwrap_or(false) {
+                            return Err(REJECTED_BY_USER.to_string());
+                        }
+                        handle.set_awaiting_approval(false);
+                        run(this).await.map_err(|error| match error {
+                            ToolError::ExecutionError(message) => message,
+                            other => other.to_string(),
+                        })
+                    },
+                );
+
+                let outcome = tokio::time::timeout(APPROVAL_WAIT, async {
+                    loop {
+                        match events.recv().await {
+                            Ok(envelope) => {
+                                if let DockmasterEvent::OperationUpdate(operation) = envelope.event
+                                {
+                                    if operation.id == id && operation.state.is_finished() {
+                                        return Some(operation);
+                                    }
+                                }
+                            }
+                            Err(RecvError::Lagged(_)) => {
+                                if let Some(operation) = self.mcp_core.operations.get(&id) {
+                                    if operation.state.is_finished() {
+                                        return Some(operation);
+                                    }
+                                }
+                            }
+                            Err(RecvError::Closed) => return None,
+                        }
+                    }
+                })
+                .await
+                .ok()
+                .flatten();
+
+                match outcome {
+                    Some(operation) => approval_outcome(operation),
+                    None => {
+                        warn!("No decision on {} request {} yet", action.as_str(), id);
+                        Ok(json!({
+                            "success": false,
+                            "status": "pending_approval",
+                            "operation_id": id,
+                            "message": format!(
+                                "The request to {} is waiting for the user's approval in MCP Dockmaster. It will run once they approve it.",
+                                action.as_str()
+                            )
+                        }))
+                    }
+                }
+            }
+        }
+    }
+
     /// Handle register_server tool
     async fn handle_register_server(&self, args: Value) -> Result<Value, ToolError> {
         // Convert the args into the format expected by the HTTP handler
-        let registration_request = if let Some(tool_id) = args.get("tool_id").and_then(|v| v.as_str()) {
-            // If we receive a tool_id, treat it as a registry-based installation
-            serde_json::json!({
-                "tool_id": tool_id
-            })
-        } else {
-            // Otherwise, expect direct registration parameters
-            args
+        let registration_request =
+            if let Some(tool_id) = args.get("tool_id").and_then(|v| v.as_str()) {
+                // If we receive a tool_id, treat it as a registry-based installation
+                serde_json::json!({
+                    "tool_id": tool_id
+                })
+            } else {
+                // Otherwise, expect direct registration parameters
+                args
+            };
+
+        let server_id = registration_request
+            .get("tool_id")
+            .or_else(|| registration_request.get("server_id"))
+            .and_then(|v| v.as_str())
+            .map(|id| id.to_string());
+        let summary = match &server_id {
+            Some(server_id) => format!("Install the server '{}'", server_id),
+            None => "Install a new server".to_string(),
         };
+        self.run_gated(
+            GatedAction::Register,
+            server_id,
+            summary,
+            move |router| async move { router.register_server(registration_request).await },
+        )
+        .await
+    }
+
+    async fn register_server(&self, registration_request: Value) -> Result<Value, ToolError> {
+        let client = self.client_name().await;
 
         // Use the HTTP handler's logic through MCPCore
         match crate::http_server::handlers::handle_register_tool(
             self.mcp_core.clone(),
-            registration_request
-        ).await {
+            registration_request,
+            InstallOrigin::McpSession { client },
+        )
+        .await
+        {
             Ok(response) => {
                 // Update the tools cache after successful registration
                 if let Err(e) = self.update_tools_cache("registration").await {
                     error!("Failed to update tools cache after registration: {}", e);
                 }
-                
+
                 Ok(json!({
                     "success": true,
                     "message": response.message,
-                    "tool_id": response.tool_id
+                    "server_id": response.server_id
                 }))
-            },
+            }
             Err(error) => Err(ToolError::ExecutionError(error.message)),
         }
     }
@@ -102,14 +350,40 @@ impl MCPDockmasterRouter {
     /// Handle uninstall_server tool
     async fn handle_uninstall_server(&self, args: Value) -> Result<Value, ToolError> {
         // Convert the args into the format expected by the HTTP handler
-        let uninstall_request: ToolUninstallRequest = if let Some(server_id) = args.get("server_id").and_then(|v| v.as_str()) {
-            ToolUninstallRequest {
-                server_id: server_id.to_string()
-            }
-        } else {
-            // Otherwise, return error of missing server_id
-            return Err(ToolError::ExecutionError("Missing server_id parameter".to_string()));
-        };
+        let legacy_warning = legacy_server_id_warning(&args);
+        let uninstall_request: ToolUninstallRequest =
+            if let Some(server_id) = request_server_id(&args) {
+                ToolUninstallRequest {
+                    server_id: server_id.to_string(),
+                }
+            } else {
+                // Otherwise, return error of missing server_id
+                return Err(ToolError::ExecutionError(
+                    "Missing server_id parameter".to_string(),
+                ));
+            };
+
+        let server_id = uninstall_request.server_id.clone();
+        let summary = format!("Uninstall the server '{}'", server_id);
+        self.run_gated(
+            GatedAction::Uninstall,
+            Some(server_id),
+            summary,
+            move |router| async move {
+                router
+                    .uninstall_server(uninstall_request, legacy_warning)
+                    .await
+            },
+        )
+        .await
+    }
+
+    async fn uninstall_server(
+        &self,
+        uninstall_request: ToolUninstallRequest,
+        legacy_warning: Option<String>,
+    ) -> Result<Value, ToolError> {
+        let server_id = uninstall_request.server_id.clone();
         match self.mcp_core.uninstall_server(uninstall_request).await {
             Ok(response) => {
                 // Update the tools cache after successful uninstallation
@@ -118,31 +392,48 @@ impl MCPDockmasterRouter {
                 }
                 Ok(json!({
                     "success": true,
-                    "message": response.message
+                    "message": response.message,
+                    "server_id": server_id,
+                    "warnings": legacy_warning.into_iter().collect::<Vec<_>>()
                 }))
-            },
+            }
             Err(error) => Err(ToolError::ExecutionError(error)),
         }
     }
 
     /// Handle configure_server tool
     async fn handle_configure_server(&self, args: Value) -> Result<Value, ToolError> {
-        // Convert the args into the format expected by the HTTP handler
-        let configure_request = if let Some(server_id) = args.get("server_id").and_then(|v| v.as_str()) {
-            serde_json::json!({
-                "tool_id": server_id,
-                "config": args.get("config").unwrap_or(&Value::Null)
-            })
-        } else {
-            // Otherwise, expect direct registration parameters
-            args
-        };
+        // The HTTP handler takes the same arguments
+        let configure_request = args;
+        let server_id = request_server_id(&configure_request).map(|id| id.to_string());
+        // Only the keys are shown, values can be secrets
+        let keys = configure_request
+            .get("config")
+            .and_then(|config| config.as_object())
+            .map(|config| config.keys().cloned().collect::<Vec<_>>().join(", "))
+            .unwrap_or_default();
+        let summary = format!(
+            "Change the configuration of the server '{}' ({})",
+            server_id.as_deref().unwrap_or("unknown"),
+            keys
+        );
+        self.run_gated(
+            GatedAction::Configure,
+            server_id,
+            summary,
+            move |router| async move { router.configure_server(configure_request).await },
+        )
+        .await
+    }
 
+    async fn configure_server(&self, configure_request: Value) -> Result<Value, ToolError> {
         // Use the HTTP handler's logic through MCPCore
         match crate::http_server::handlers::handle_get_server_config(
             self.mcp_core.clone(),
-            configure_request
-        ).await {
+            configure_request,
+        )
+        .await
+        {
             Ok(response) => {
                 // Update the tools cache after successful configuration
                 if let Err(e) = self.update_tools_cache("configuration").await {
@@ -150,7 +441,13 @@ impl MCPDockmasterRouter {
                 }
                 Ok(response)
             }
-            Err(error) => Err(ToolError::ExecutionError(error.get("message").and_then(|m| m.as_str()).unwrap_or("Unknown error").to_string())),
+            Err(error) => Err(ToolError::ExecutionError(
+                error
+                    .get("message")
+                    .and_then(|m| m.as_str())
+                    .unwrap_or("Unknown error")
+                    .to_string(),
+            )),
         }
     }
 
@@ -159,25 +456,35 @@ impl MCPDockmasterRouter {
         // Extract the query parameter from the args
         let query = match args.get("query").and_then(|q| q.as_str()) {
             Some(q) => q,
-            None => return Err(ToolError::ExecutionError("Missing or invalid 'query' parameter".to_string())),
+            None => {
+                return Err(ToolError::ExecutionError(
+                    "Missing or invalid 'query' parameter".to_string(),
+                ))
+            }
         };
 
         // Create a new RegistrySearch instance
-        let mut registry_search = match RegistrySearch::new().await {
+        let mut registry_search = match RegistrySearch::new(&self.mcp_core.registry_cache).await {
             Ok(search) => search,
             Err(e) => match e {
                 SearchError::CacheError(msg) => {
                     error!("Cache error during registry search: {}", msg);
-                    return Err(ToolError::ExecutionError(format!("Registry cache error: {}", msg)));
-                },
+                    return Err(ToolError::ExecutionError(format!(
+                        "Registry cache error: {}",
+                        msg
+                    )));
+                }
                 SearchError::IndexError(msg) => {
                     error!("Index error during registry search: {}", msg);
-                    return Err(ToolError::ExecutionError(format!("Registry index error: {}", msg)));
-                },
+                    return Err(ToolError::ExecutionError(format!(
+                        "Registry index error: {}",
+                        msg
+                    )));
+                }
                 SearchError::QueryError(msg) => {
                     error!("Query error during registry search: {}", msg);
                     return Err(ToolError::ExecutionError(format!("Query error: {}", msg)));
-                },
+                }
             },
         };
 
@@ -187,34 +494,40 @@ impl MCPDockmasterRouter {
             Err(e) => match e {
                 SearchError::QueryError(msg) => {
                     return Err(ToolError::ExecutionError(format!("Invalid query: {}", msg)));
-                },
+                }
                 _ => {
-                    return Err(ToolError::ExecutionError(format!("Search execution error: {:?}", e)));
-                },
+                    return Err(ToolError::ExecutionError(format!(
+                        "Search execution error: {:?}",
+                        e
+                    )));
+                }
             },
         };
 
         // Limit results to top 10 for better UI display
         let top_results = search_results.into_iter().take(10).collect::<Vec<_>>();
-        
+
         // Transform results into a format suitable for JSON response
-        let formatted_results = top_results.into_iter().map(|(tool, score)| {
-            json!({
-                "id": tool.id,
-                "name": tool.name,
-                "description": tool.description,
-                "short_description": tool.short_description,
-                "publisher": tool.publisher,
-                "is_official": tool.is_official,
-                "source_url": tool.source_url,
-                "distribution": tool.distribution,
-                "license": tool.license,
-                "runtime": tool.runtime,
-                "categories": tool.categories,
-                "tags": tool.tags,
-                "score": score
+        let formatted_results = top_results
+            .into_iter()
+            .map(|(tool, score)| {
+                json!({
+                    "id": tool.id,
+                    "name": tool.name,
+                    "description": tool.description,
+                    "short_description": tool.short_description,
+                    "publisher": tool.publisher,
+                    "is_official": tool.is_official,
+                    "source_url": tool.source_url,
+                    "distribution": tool.distribution,
+                    "license": tool.license,
+                    "runtime": tool.runtime,
+                    "categories": tool.categories,
+                    "tags": tool.tags,
+                    "score": score
+                })
             })
-        }).collect::<Vec<_>>();
+            .collect::<Vec<_>>();
 
         // Return the results as JSON
         Ok(json!({
@@ -228,7 +541,7 @@ impl MCPDockmasterRouter {
     async fn handle_list_installed_servers(&self, _args: Value) -> Result<Value, ToolError> {
         // Get the installed servers from MCPCore
         let result = self.mcp_core.list_servers().await;
-        
+
         // Return the installed servers as JSON
         Ok(json!({
             "servers": result
@@ -245,73 +558,126 @@ impl MCPDockmasterRouter {
             TOOL_LIST_INSTALLED_SERVERS => self.handle_list_installed_servers(args).await,
             _ => {
                 // For non-built-in tools, find the appropriate server that has this tool
-                let mcp_state = self.mcp_core.mcp_state.read().await;
-                let server_tools = mcp_state.server_tools.read().await;
-
-                // Find which server has the requested tool
-                let mut server_id = None;
-
-                for (sid, tools) in &*server_tools {
-                    for tool in tools {
-                        if tool.id == tool_name {
-                            server_id = Some(sid.clone());
-                            break;
-                        }
-
-                        // Also check by name if id doesn't match
-                        if tool.name == tool_name {
-                            server_id = Some(sid.clone());
-                            break;
-                        }
-                    }
-
-                    if server_id.is_some() {
-                        break;
-                    }
+                let mcp_state = self.mcp_core.mcp_state.read().await.clone();
+
+                // Find which server has the requested tool, by name first
+                let found = mcp_state.find_callable_tool(tool_name).await;
+
+                let Some(mut tool) = found else {
+                    return Err(ToolError::NotFound(format!(
+                        "Tool '{}' not found",
+                        tool_name
+                    )));
+                };
+                tool.apply_annotation_overrides(&self.mcp_core.tool_annotation_overrides().await);
+                // The server is called with the name it listed the tool under
+                let server_id = tool.server_id.clone();
+                let tool_name = tool.name.clone();
+                if !tool
+                    .annotations
+                    .as_ref()
+                    .is_some_and(ToolAnnotations::is_destructive)
+                {
+                    return self.call_server_tool(server_id, tool_name, args).await;
                 }
 
-                // Drop the locks before proceeding
-                drop(server_tools);
-                drop(mcp_state);
-
-                match server_id {
-                    Some(server_id) => {
-                        let request = ToolExecutionRequest {
-                            tool_id: format!("{}:{}", server_id, tool_name),
-                            parameters: args,
-                        };
-
-                        match self.mcp_core.execute_proxy_tool(request).await {
-                            Ok(response) => {
-                                if response.success {
-                                    Ok(response.result.unwrap_or(json!(null)))
-                                } else {
-                                    Err(ToolError::ExecutionError(response.error.unwrap_or_else(|| "Unknown error".to_string())))
-                                }
-                            },
-                            Err(e) => Err(ToolError::ExecutionError(format!("Failed to execute tool: {}", e))),
-                        }
+                // Gated like management actions, the policy allows these calls by default
+                let summary = format!(
+                    "Call '{}' on '{}', a tool that says it may destroy data",
+                    tool_name, server_id
+                );
+                self.run_gated(
+                    GatedAction::DestructiveTool,
+                    Some(server_id.clone()),
+                    summary,
+                    move |router| async move {
+                        router.call_server_tool(server_id, tool_name, args).await
                     },
-                    None => Err(ToolError::NotFound(format!("Tool '{}' not found", tool_name))),
+                )
+                .await
+            }
+        }
+    }
+
+    /// Call a tool of a server through the proxy
+    async fn call_server_tool(
+        &self,
+        server_id: String,
+        tool_name: String,
+        args: Value,
+    ) -> Result<Value, ToolError> {
+        let request = ToolExecutionRequest {
+            tool_id: proxy_tool_id(&server_id, &tool_name),
+            parameters: args,
+        };
+
+        let call_id = self.call_id.clone();
+        let meta = self
+            .progress_token
+            .as_ref()
+            .map(|token| json!({ "progressToken": token }));
+        match self
+            .mcp_core
+            .execute_cancellable_proxy_tool(request, call_id, meta)
+            .await
+        {
+            Ok(response) => {
+                if response.success {
+                    Ok(response.result.unwrap_or(json!(null)))
+                } else if response.cancelled {
+                    Err(ToolError::ExecutionError(CANCELLED_ERROR.to_string()))
+                } else {
+                    Err(ToolError::ExecutionError(
+                        response
+                            .error
+                            .unwrap_or_else(|| "Unknown error".to_string()),
+                    ))
                 }
             }
+            Err(e) => Err(ToolError::ExecutionError(format!(
+                "Failed to execute tool: {}",
+                e
+            ))),
         }
     }
 }
 
+/// Error of an approval operation rejected by the user
+const REJECTED_BY_USER: &str = "Rejected by the user";
+
+/// Tool result of a finished approval operation
+fn approval_outcome(operation: Operation) -> Result<Value, ToolError> {
+    match operation.state {
+        OperationState::Succeeded => Ok(operation.result.unwrap_or(Value::Null)),
+        OperationState::Failed if operation.error.as_deref() != Some(REJECTED_BY_USER) => {
+            Err(ToolError::ExecutionError(
+                operation
+                    .error
+                    .unwrap_or_else(|| "Unknown error".to_string()),
+            ))
+        }
+        _ => Ok(json!({
+            "success": false,
+            "status": "denied",
+            "operation_id": operation.id,
+            "message": "The user did not approve this request."
+        })),
+    }
+}
+
 impl mcp_sdk_server::Router for MCPDockmasterRouter {
     fn name(&self) -> String {
-        self.server_name.clone()
+        self.tools_service.identity().name
     }
 
     fn instructions(&self) -> String {
-        "This server provides tools for managing Docker containers, images, and networks. You can use it to manage containers, build images, and interact with Docker registries. It also allows you to register new MCP servers.".to_string()
+        self.tools_service.identity().instructions
     }
 
     fn capabilities(&self) -> ServerCapabilities {
         // Build capabilities with tools support
         CapabilitiesBuilder::new()
-            .with_tools(false)
+            .with_tools(true)
             .with_resources(false, false)
             .with_prompts(false)
             .build()
@@ -326,15 +692,35 @@ impl mcp_sdk_server::Router for MCPDockmasterRouter {
         tool_name: &str,
         arguments: Value,
     ) -> Pin<Box<dyn Future<Output = Result<Vec<Content>, ToolError>> + Send + 'static>> {
-        let this = self.clone();
+        let mut this = self.clone();
         let tool_name = tool_name.to_string();
         info!("Calling tool: {}", tool_name);
         Box::pin(async move {
-            match this.execute_tool(&tool_name, arguments).await {
+            // Attached by the session the call was posted to, never passed to the tool
+            let mut arguments = arguments;
+            let posted = PostedToolCall::take(&mut arguments);
+            let mut progress = None;
+            if let (Some(session_id), Some(call)) = (this.session_id.clone(), posted) {
+                let sessions = this.mcp_core.mcp_state.read().await.sessions.clone();
+                this.call_id = Some(call_id(&session_id, &call.id));
+                this.progress_token = call
+                    .progress_token
+                    .map(|token| progress_topic(&session_id, &token));
+                progress = this
+                    .progress_token
+                    .clone()
+                    .map(|topic| (sessions, session_id, topic));
+            }
+            let result = this.execute_tool(&tool_name, arguments).await;
+            // No more progress comes for a finished call
+            if let Some((sessions, session_id, topic)) = progress {
+                sessions.unsubscribe(&session_id, &topic).await;
+            }
+            match result {
                 Ok(result) => {
                     let result_str = serde_json::to_string_pretty(&result).unwrap_or_default();
                     Ok(vec![Content::text(result_str)])
-                },
+                }
                 Err(e) => Err(e),
             }
         })
@@ -351,7 +737,10 @@ impl mcp_sdk_server::Router for MCPDockmasterRouter {
     ) -> Pin<Box<dyn Future<Output = Result<String, ResourceError>> + Send + 'static>> {
         let uri = uri.to_string();
         Box::pin(async move {
-            Err(ResourceError::NotFound(format!("Resource not found: {}", uri)))
+            Err(ResourceError::NotFound(format!(
+                "Resource not found: {}",
+                uri
+            )))
         })
     }
 
@@ -366,7 +755,10 @@ impl mcp_sdk_server::Router for MCPDockmasterRouter {
     ) -> Pin<Box<dyn Future<Output = Result<String, PromptError>> + Send + 'static>> {
         let prompt_name = prompt_name.to_string();
         Box::pin(async move {
-            Err(PromptError::NotFound(format!("Prompt not found: {}", prompt_name)))
+            Err(PromptError::NotFound(format!(
+                "Prompt not found: {}",
+                prompt_name
+            )))
         })
     }
 }
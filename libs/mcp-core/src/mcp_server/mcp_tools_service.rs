@@ -1,20 +1,19 @@
 use crate::core::mcp_core::MCPCore;
-use crate::core::mcp_core_proxy_ext::McpCoreProxyExt;
+use crate::core::mcp_core_footprint_ext::McpCoreFootprintExt;
+use crate::core::mcp_core_identity_ext::McpCoreIdentityExt;
+use crate::core::mcp_core_tools_snapshot_ext::McpCoreToolsSnapshotExt;
+use crate::identity::ServerIdentity;
+use crate::models::types::ToolAnnotations;
+use log::{error, info};
 use mcp_sdk_core::Tool;
-use serde_json::json;
+use serde_json::Value;
 use tokio::sync::RwLock;
-use log::{info, error};
 
-use super::tools::{
-    get_register_server_tool,
-    get_search_server_tool,
-    get_configure_server_tool,
-    get_uninstall_server_tool,
-    get_list_installed_servers_tool,
-};
+use super::tools::get_builtin_tools;
 
-use std::sync::Arc;
 use lazy_static::lazy_static;
+use std::collections::HashMap;
+use std::sync::Arc;
 
 lazy_static! {
     static ref INSTANCE: RwLock<Option<Arc<MCPToolsService>>> = RwLock::new(None);
@@ -23,26 +22,35 @@ lazy_static! {
 pub struct MCPToolsService {
     mcp_core: MCPCore,
     tools_cache: Arc<RwLock<Vec<Tool>>>,
+    /// Annotations of the cached tools by name, the SDK's `Tool` can't hold them
+    annotations: Arc<std::sync::RwLock<HashMap<String, ToolAnnotations>>>,
+    /// Identity advertised to MCP clients, refreshed with the tools
+    identity: Arc<std::sync::RwLock<ServerIdentity>>,
     are_tools_hidden: Arc<RwLock<bool>>,
 }
 
 impl MCPToolsService {
     pub fn new(mcp_core: MCPCore) -> Self {
+        let identity = mcp_core.server_identity.clone();
         Self {
             mcp_core,
             tools_cache: Arc::new(RwLock::new(Vec::new())),
+            annotations: Arc::new(std::sync::RwLock::new(HashMap::new())),
+            identity: Arc::new(std::sync::RwLock::new(identity)),
             are_tools_hidden: Arc::new(RwLock::new(false)),
         }
     }
-    
+
     pub async fn initialize(mcp_core: MCPCore) -> Arc<Self> {
         let mut instance = INSTANCE.write().await;
         if instance.is_none() {
-            *instance = Some(Arc::new(Self::new(mcp_core)));
+            let service = Arc::new(Self::new(mcp_core));
+            service.refresh_identity().await;
+            *instance = Some(service);
         }
         instance.as_ref().unwrap().clone()
     }
-    
+
     pub async fn get_instance() -> Option<Arc<Self>> {
         let instance = INSTANCE.read().await;
         instance.clone()
@@ -52,7 +60,10 @@ impl MCPToolsService {
     pub async fn set_tools_hidden(&self, hidden: bool) -> Result<(), String> {
         let mut are_tools_hidden = self.are_tools_hidden.write().await;
         *are_tools_hidden = hidden;
-        info!("Tools visibility set to: {}", if hidden { "hidden" } else { "visible" });
+        info!(
+            "Tools visibility set to: {}",
+            if hidden { "hidden" } else { "visible" }
+        );
         Ok(())
     }
 
@@ -74,10 +85,10 @@ impl MCPToolsService {
 
         // Start with an empty list of tools
         let mut tools = Vec::new();
-        
+
         // Get cached tools if available
         let cache_handle = self.tools_cache.clone();
-        
+
         // Try to read from the existing cache (non-blocking)
         let cache_found = if let Ok(cache) = cache_handle.try_read() {
             if !cache.is_empty() {
@@ -91,31 +102,31 @@ impl MCPToolsService {
         } else {
             false
         };
-        
+
         // If we didn't find any cached tools, add the built-in tools
         if !cache_found {
             info!("No tools in cache, adding built-in tools");
-            tools.push(get_register_server_tool());
-            tools.push(get_search_server_tool());
-            tools.push(get_configure_server_tool());
-            tools.push(get_uninstall_server_tool());
-            tools.push(get_list_installed_servers_tool());
+            tools.extend(get_builtin_tools());
         }
-        
+
         // Log what we're returning
         info!("Returning {} tools from list_tools", tools.len());
-        
+
         // Trigger an async task to update the cache for future calls
         let mcp_core = self.mcp_core.clone();
         let cache_clone = self.tools_cache.clone();
-        
+        let annotations = self.annotations.clone();
+        let identity = self.identity.clone();
+
         // Spawn a task to update the cache for future requests
         tokio::spawn(async move {
-            if let Err(e) = update_cache_internal(mcp_core, cache_clone).await {
+            if let Err(e) =
+                update_cache_internal(mcp_core, cache_clone, annotations, identity).await
+            {
                 error!("Failed to update tools cache: {}", e);
             }
         });
-        
+
         tools
     }
 
@@ -129,75 +140,127 @@ impl MCPToolsService {
 
         // Only update the tools cache if tools are visible
         if !*are_tools_hidden {
-            update_cache_internal(self.mcp_core.clone(), self.tools_cache.clone()).await
+            update_cache_internal(
+                self.mcp_core.clone(),
+                self.tools_cache.clone(),
+                self.annotations.clone(),
+                self.identity.clone(),
+            )
+            .await
         } else {
             // If tools are hidden, clear the cache
             let mut cache = self.tools_cache.write().await;
             cache.clear();
+            self.annotations.write().unwrap().clear();
+            self.refresh_identity().await;
             Ok(())
         }
     }
+
+    /// Identity advertised to MCP clients, see [`crate::identity`]
+    pub fn identity(&self) -> ServerIdentity {
+        self.identity.read().unwrap().clone()
+    }
+
+    /// Read the identity again, after the user changed it
+    pub async fn refresh_identity(&self) {
+        let identity = self.mcp_core.advertised_identity().await;
+        *self.identity.write().unwrap() = identity;
+    }
+
+    /// Put the advertised version in an `initialize` result of the router
+    ///
+    /// The SDK answers with its own version. Returns whether the message changed.
+    pub fn identify_server(&self, message: &mut Value) -> bool {
+        let Some(server_info) = message
+            .pointer_mut("/result/serverInfo")
+            .and_then(Value::as_object_mut)
+        else {
+            return false;
+        };
+        let version = Value::String(self.identity.read().unwrap().version.clone());
+        if server_info.get("version") == Some(&version) {
+            return false;
+        }
+        server_info.insert("version".to_string(), version);
+        true
+    }
+
+    /// Add the annotations of the cached tools to a `tools/list` result of the router
+    ///
+    /// The SDK's `Tool` has no annotations, so they are added to the message on its way
+    /// to the client. Returns whether the message changed.
+    pub fn annotate_tools_list(&self, message: &mut Value) -> bool {
+        let Some(tools) = message
+            .pointer_mut("/result/tools")
+            .and_then(Value::as_array_mut)
+        else {
+            return false;
+        };
+        let annotations = self.annotations.read().unwrap();
+        let mut changed = false;
+        for tool in tools.iter_mut().filter_map(Value::as_object_mut) {
+            let name = tool.get("name").and_then(Value::as_str).unwrap_or_default();
+            let Some(tool_annotations) = annotations.get(name) else {
+                continue;
+            };
+            if !tool.contains_key("annotations") {
+                let value = serde_json::to_value(tool_annotations).unwrap_or_default();
+                tool.insert("annotations".to_string(), value);
+                changed = true;
+            }
+        }
+        changed
+    }
 }
 
 /// Internal function to update the cache
-async fn update_cache_internal(mcp_core: MCPCore, cache: Arc<RwLock<Vec<Tool>>>) -> Result<(), String> {
-    // Get user-installed tools from MCPCore
-    match mcp_core.list_all_server_tools().await {
-        Ok(server_tools) => {
+async fn update_cache_internal(
+    mcp_core: MCPCore,
+    cache: Arc<RwLock<Vec<Tool>>>,
+    annotations: Arc<std::sync::RwLock<HashMap<String, ToolAnnotations>>>,
+    identity: Arc<std::sync::RwLock<ServerIdentity>>,
+) -> Result<(), String> {
+    // Get user-installed tools from MCPCore, those of the last run while the servers start
+    match mcp_core.served_server_tools().await {
+        Ok((server_tools, stale)) => {
+            *annotations.write().unwrap() = server_tools
+                .iter()
+                .filter_map(|tool| Some((tool.name.clone(), tool.annotations.clone()?)))
+                .collect();
+
             // Add built-in tools
-            let mut tools_vec = vec![
-                get_register_server_tool(),
-                get_search_server_tool(),
-                get_configure_server_tool(),
-                get_uninstall_server_tool(),
-                get_list_installed_servers_tool(),
-            ];
-
-            // Add user-installed tools
-            for tool_info in server_tools {
-                // Convert ServerToolInfo to Tool
-                if let Some(input_schema) = tool_info.input_schema {
-                    // Convert InputSchema to serde_json::Value
-                    let schema_value = json!({
-                        "type": input_schema.r#type,
-                        "properties": input_schema.properties,
-                        "required": input_schema.required,
-                    });
-                    
-                    let tool = Tool {
-                        name: tool_info.name,
-                        description: tool_info.description,
-                        input_schema: schema_value,
-                    };
-                    
-                    tools_vec.push(tool);
-                } else {
-                    // Create a tool with an empty schema
-                    let tool = Tool {
-                        name: tool_info.name,
-                        description: tool_info.description,
-                        input_schema: json!({
-                            "type": "object",
-                            "properties": {},
-                            "required": []
-                        }),
-                    };
-                    
-                    tools_vec.push(tool);
-                }
-            }
-            
+            let mut tools_vec = get_builtin_tools();
+
+            // Add user-installed tools, with their whole input schema
+            tools_vec.extend(server_tools.into_iter().map(|tool_info| Tool {
+                input_schema: tool_info.input_schema_value(),
+                name: tool_info.name,
+                description: tool_info.description,
+            }));
+
             // Update the cache
             let mut cache = cache.write().await;
             *cache = tools_vec;
             info!("Tools cache updated with {} tools", cache.len());
+            drop(cache);
+            // The summary of the instructions follows the exposed servers
+            let advertised = mcp_core.advertised_identity().await;
+            *identity.write().unwrap() = advertised;
+            mcp_core.check_tools_budget().await;
+            if !stale {
+                if let Err(e) = mcp_core.save_tools_snapshot().await {
+                    error!("Failed to save the tools snapshot: {}", e);
+                }
+            }
             Ok(())
-        },
+        }
         Err(e) => {
             error!("Failed to update tools cache: {}", e);
             // Clear the cache to force refresh on next request
             let mut cache = cache.write().await;
             cache.clear();
+            annotations.write().unwrap().clear();
             Err(e)
         }
     }
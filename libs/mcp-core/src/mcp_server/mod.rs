@@ -1,13 +1,13 @@
+#[cfg(feature = "http-server")]
 pub mod mcp_router;
-pub mod session_manager;
-pub mod tools;
-pub mod notifications;
 pub mod mcp_tools_service;
+#[cfg(feature = "http-server")]
+pub mod notifications;
+pub mod tools;
 
+#[cfg(feature = "http-server")]
 pub use self::mcp_router::MCPDockmasterRouter;
-pub use self::session_manager::SESSION_MANAGER;
 pub use self::tools::{
-    TOOL_REGISTER_SERVER, get_register_server_tool,
-    TOOL_CONFIGURE_SERVER, get_configure_server_tool,
-    TOOL_SEARCH_SERVER, get_search_server_tool,
-}; 
+    get_configure_server_tool, get_register_server_tool, get_search_server_tool,
+    TOOL_CONFIGURE_SERVER, TOOL_REGISTER_SERVER, TOOL_SEARCH_SERVER,
+};
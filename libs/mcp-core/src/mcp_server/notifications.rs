@@ -1,7 +1,8 @@
 use serde_json::json;
-use super::session_manager::SESSION_MANAGER;
 
-pub async fn broadcast_tools_list_changed() {
+use crate::sessions::SessionRegistry;
+
+pub async fn broadcast_tools_list_changed(sessions: &SessionRegistry) {
     let notification = json!({
         "jsonrpc": "2.0",
         "method": "notifications/tools/list_changed",
@@ -9,9 +10,12 @@ pub async fn broadcast_tools_list_changed() {
     });
 
     let message = serde_json::to_string(&notification).unwrap_or_default();
-    let failed = SESSION_MANAGER.broadcast_message(&message).await;
-    
+    let failed = sessions.broadcast(&message).await;
+
     if !failed.is_empty() {
-        log::warn!("Failed to send tools list changed notification to some sessions: {:?}", failed);
+        log::warn!(
+            "Failed to send tools list changed notification to some sessions: {:?}",
+            failed
+        );
     }
 }
@@ -1,5 +1,8 @@
+use std::collections::HashMap;
+
 use mcp_sdk_core::Tool;
-use serde_json::json;
+
+use crate::models::types::{InputSchema, InputSchemaProperty, ServerToolInfo};
 
 /// Constants for tool names
 pub const TOOL_REGISTER_SERVER: &str = "mcp_register_server";
@@ -8,89 +11,180 @@ pub const TOOL_CONFIGURE_SERVER: &str = "mcp_configure_server";
 pub const TOOL_UNINSTALL_SERVER: &str = "mcp_uninstall_server";
 pub const TOOL_LIST_INSTALLED_SERVERS: &str = "mcp_list_installed_servers";
 
+/// Server id of the built-in tools in tool listings
+pub const BUILTIN_SERVER_ID: &str = "builtin";
+
+/// Argument of a built-in tool
+#[derive(Clone, Copy, Debug)]
+pub struct BuiltinToolArg {
+    pub name: &'static str,
+    /// JSON Schema type of the argument
+    pub r#type: &'static str,
+    pub description: &'static str,
+    pub required: bool,
+}
+
+/// A tool Dockmaster provides itself, defined once for the MCP and the HTTP tool lists
+#[derive(Clone, Copy, Debug)]
+pub struct BuiltinTool {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub args: &'static [BuiltinToolArg],
+}
+
+impl BuiltinTool {
+    /// Input schema of the tool, built from its arguments
+    pub fn input_schema(&self) -> InputSchema {
+        let properties: HashMap<String, InputSchemaProperty> = self
+            .args
+            .iter()
+            .map(|arg| {
+                let property = InputSchemaProperty {
+                    description: arg.description.to_string(),
+                    r#type: Some(arg.r#type.into()),
+                    ..Default::default()
+                };
+                (arg.name.to_string(), property)
+            })
+            .collect();
+        InputSchema {
+            properties,
+            required: self
+                .args
+                .iter()
+                .filter(|arg| arg.required)
+                .map(|arg| arg.name.to_string())
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    /// The tool as listed to MCP clients
+    pub fn to_tool(&self) -> Tool {
+        Tool {
+            name: self.name.to_string(),
+            description: self.description.to_string(),
+            input_schema: self.to_tool_info().input_schema_value(),
+        }
+    }
+
+    /// The tool as listed with the tools of the servers
+    pub fn to_tool_info(&self) -> ServerToolInfo {
+        ServerToolInfo {
+            id: self.name.to_string(),
+            name: self.name.to_string(),
+            description: self.description.to_string(),
+            input_schema: Some(self.input_schema()),
+            server_id: BUILTIN_SERVER_ID.to_string(),
+            proxy_id: None,
+            is_active: true,
+            output_schema: None,
+            annotations: None,
+        }
+    }
+}
+
+pub const REGISTER_SERVER_TOOL: BuiltinTool = BuiltinTool {
+    name: TOOL_REGISTER_SERVER,
+    description: "Register a new server with MCP using its registry tool ID",
+    args: &[BuiltinToolArg {
+        name: "tool_id",
+        r#type: "string",
+        description: "ID of the tool in the registry to install",
+        required: true,
+    }],
+};
+
+pub const SEARCH_SERVER_TOOL: BuiltinTool = BuiltinTool {
+    name: TOOL_SEARCH_SERVER,
+    description: "Search for MCP Servers in the registry",
+    args: &[BuiltinToolArg {
+        name: "query",
+        r#type: "string",
+        description: "Search query",
+        required: true,
+    }],
+};
+
+pub const CONFIGURE_SERVER_TOOL: BuiltinTool = BuiltinTool {
+    name: TOOL_CONFIGURE_SERVER,
+    description: "Configure a server and its environment variables",
+    args: &[
+        BuiltinToolArg {
+            name: "server_id",
+            r#type: "string",
+            description: "ID of the server to configure",
+            required: true,
+        },
+        BuiltinToolArg {
+            name: "config",
+            r#type: "object",
+            description: "Configuration for the server, it's not neccesary to nest the \
+                          environment variables inside an env object, just pass the key and \
+                          value. Values can be strings, numbers or booleans",
+            required: true,
+        },
+    ],
+};
+
+pub const UNINSTALL_SERVER_TOOL: BuiltinTool = BuiltinTool {
+    name: TOOL_UNINSTALL_SERVER,
+    description: "Uninstall a server from MCP using its registry tool ID",
+    args: &[BuiltinToolArg {
+        name: "server_id",
+        r#type: "string",
+        description: "ID of the server to uninstall",
+        required: true,
+    }],
+};
+
+pub const LIST_INSTALLED_SERVERS_TOOL: BuiltinTool = BuiltinTool {
+    name: TOOL_LIST_INSTALLED_SERVERS,
+    description: "List all installed servers",
+    args: &[],
+};
+
+/// Every built-in tool, in the order they are listed
+pub const BUILTIN_TOOLS: [BuiltinTool; 5] = [
+    REGISTER_SERVER_TOOL,
+    SEARCH_SERVER_TOOL,
+    CONFIGURE_SERVER_TOOL,
+    UNINSTALL_SERVER_TOOL,
+    LIST_INSTALLED_SERVERS_TOOL,
+];
+
 /// Get the list installed servers tool definition
 pub fn get_list_installed_servers_tool() -> Tool {
-    Tool {
-        name: TOOL_LIST_INSTALLED_SERVERS.to_string(),
-        description: "List all installed servers".to_string(),
-        input_schema: json!({
-            "type": "object",
-            "properties": {},
-            "required": []
-        }),
-    }
+    LIST_INSTALLED_SERVERS_TOOL.to_tool()
 }
 
 /// Get the register_server tool definition
 pub fn get_register_server_tool() -> Tool {
-    Tool {
-        name: TOOL_REGISTER_SERVER.to_string(),
-        description: "Register a new server with MCP using its registry tool ID".to_string(),
-        input_schema: json!({
-            "type": "object",
-            "properties": {
-                "tool_id": {
-                    "type": "string",
-                    "description": "ID of the tool in the registry to install"
-                }
-            },
-            "required": ["tool_id"]
-        }),
-    }
+    REGISTER_SERVER_TOOL.to_tool()
 }
 
 pub fn get_search_server_tool() -> Tool {
-    Tool {
-        name: TOOL_SEARCH_SERVER.to_string(),
-        description: "Search for MCP Servers in the registry".to_string(),
-        input_schema: json!({
-            "type": "object",
-            "properties": {
-                "query": {
-                    "type": "string",
-                    "description": "Search query"
-                }
-            },
-            "required": ["query"]
-        }),
-    }
+    SEARCH_SERVER_TOOL.to_tool()
 }
 
 pub fn get_configure_server_tool() -> Tool {
-    Tool {
-        name: TOOL_CONFIGURE_SERVER.to_string(),
-        description: "Configure a server and its environment variables".to_string(),
-        input_schema: json!({
-            "type": "object",
-            "properties": {
-                "server_id": {
-                    "type": "string",
-                    "description": "ID of the server to configure"
-                },
-                "config": {
-                    "type": "object",
-                    "description": "Configuration for the server, it's not neccesary to nest the environment variables inside an env object, just pass the key and value"
-                }
-            },
-            "required": ["server_id", "config"]
-        }),
-    }
+    CONFIGURE_SERVER_TOOL.to_tool()
 }
 
 /// Get the uninstall_server tool definition
 pub fn get_uninstall_server_tool() -> Tool {
-    Tool {
-        name: TOOL_UNINSTALL_SERVER.to_string(),
-        description: "Uninstall a server from MCP using its registry tool ID".to_string(),
-        input_schema: json!({
-            "type": "object",
-            "properties": {
-                "server_id": {
-                    "type": "string",
-                    "description": "ID of the server to uninstall"
-                }
-            },
-            "required": ["server_id"]
-        }),
-    }
-}
\ No newline at end of file
+    UNINSTALL_SERVER_TOOL.to_tool()
+}
+
+/// Get every built-in tool, in the order they are listed
+pub fn get_builtin_tools() -> Vec<Tool> {
+    BUILTIN_TOOLS.iter().map(BuiltinTool::to_tool).collect()
+}
+
+/// Get the built-in tools as listed with the tools of the servers
+pub fn get_builtin_tool_infos() -> Vec<ServerToolInfo> {
+    BUILTIN_TOOLS
+        .iter()
+        .map(BuiltinTool::to_tool_info)
+        .collect()
+}
@@ -0,0 +1,355 @@
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use super::mcp_state::{docker_container_name, inject_container_name};
+use super::process_env::{parent_env, ProcessEnv};
+use super::sandbox::{inject_docker_network_none, wrap_command, SecurityMode};
+use crate::arch::{host_arch, select_docker_platform, Arch, LaunchArch};
+use crate::models::config_check::{resolve_command, CONFIGURATION_FIELD};
+use crate::models::types::{ProcessPriority, ServerDefinition};
+use crate::utils::command::server_path;
+
+/// Everything needed to spawn a server, resolved from its definition
+///
+/// The plan is built before spawning so it can also be previewed without starting
+/// anything. Environment values are never part of the serialized plan, only their keys.
+#[derive(Clone, Debug, Serialize)]
+pub struct LaunchPlan {
+    pub server_id: String,
+    pub tools_type: String,
+    pub command: String,
+    pub args: Vec<String>,
+    #[serde(skip)]
+    pub env: HashMap<String, String>,
+    /// Names of the environment variables handed to the process
+    pub env_keys: Vec<String>,
+    /// Whether the server inherits the whole Dockmaster environment
+    pub inherit_env: bool,
+    /// Environment the server starts with, values that may be secrets redacted
+    pub effective_env: BTreeMap<String, String>,
+    /// Dockmaster variables removed from the server environment
+    #[serde(skip)]
+    pub removed_env: Vec<String>,
+    /// Effective scheduling priority
+    pub priority: ProcessPriority,
+    /// Effective CPU core limit, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu_limit: Option<u32>,
+    /// Effective memory limit in megabytes, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_memory_mb: Option<u32>,
+    /// Directory the server is started in, the one of Dockmaster when None
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<PathBuf>,
+    /// Name given to the docker container, for docker servers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub container_name: Option<String>,
+    /// Restrictions the server runs under
+    pub security_mode: SecurityMode,
+    /// Architectures the server runs with, see [`crate::arch`]
+    pub arch: LaunchArch,
+    /// Whether the server is used even when it doesn't answer the initialize request
+    /// properly
+    pub lenient_initialize: bool,
+}
+
+impl LaunchPlan {
+    /// Resolve the command, arguments and environment of a server
+    pub fn build(server_id: &str, server: &ServerDefinition) -> Result<Self, String> {
+        Self::build_with_parent_env(server_id, server, parent_env())
+    }
+
+    /// Same as [`LaunchPlan::build`], with `parent` as the Dockmaster environment
+    pub fn build_with_parent_env(
+        server_id: &str,
+        server: &ServerDefinition,
+        parent: Vec<(String, String)>,
+    ) -> Result<Self, String> {
+        Self::build_for_host(server_id, server, parent, host_arch())
+    }
+
+    /// Same as [`LaunchPlan::build_with_parent_env`], on a machine of architecture `host`
+    pub fn build_for_host(
+        server_id: &str,
+        server: &ServerDefinition,
+        parent: Vec<(String, String)>,
+        host: Option<Arch>,
+    ) -> Result<Self, String> {
+        let (command, raw_args) = resolve_command(
+            server.configuration.as_ref(),
+            server.entry_point.as_deref(),
+            CONFIGURATION_FIELD,
+        )
+        .map_err(|errors| format!("Cannot start server {}: {}", server_id, errors))?;
+
+        // Convert typed env values into the strings handed to the process
+        let mut env: HashMap<String, String> = server
+            .configuration
+            .as_ref()
+            .and_then(|configuration| configuration.env.as_ref())
+            .map(|env_map| {
+                env_map
+                    .iter()
+                    .filter_map(|(k, tool_env)| tool_env.env_string().map(|v| (k.clone(), v)))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        // `$VAR` arguments are replaced by the value of VAR, which is then not passed
+        // as an environment variable
+        let mut args = Vec::new();
+        for arg in raw_args {
+            let value = if arg.starts_with("$") {
+                env.remove(&arg.replace("$", ""))
+            } else {
+                None
+            };
+            args.push(value.unwrap_or(arg));
+        }
+
+        let priority = server
+            .configuration
+            .as_ref()
+            .and_then(|configuration| configuration.priority)
+            .unwrap_or_default();
+        let cpu_limit = server
+            .configuration
+            .as_ref()
+            .and_then(|configuration| configuration.cpu_limit)
+            .filter(|limit| *limit > 0);
+        let max_memory_mb = server
+            .configuration
+            .as_ref()
+            .and_then(|configuration| configuration.max_memory_mb)
+            .filter(|limit| *limit > 0);
+        let cwd = server
+            .configuration
+            .as_ref()
+            .and_then(|configuration| configuration.cwd.as_deref())
+            .map(str::trim)
+            .filter(|cwd| !cwd.is_empty())
+            .map(PathBuf::from);
+
+        let security_mode = SecurityMode::resolve(
+            &server.tools_type,
+            server
+                .configuration
+                .as_ref()
+                .and_then(|configuration| configuration.sandbox.as_ref()),
+        )?;
+
+        let mut arch = LaunchArch {
+            host,
+            ..Default::default()
+        };
+        // PowerShell would run the `npx.ps1` installed next to `npx.cmd`, which the
+        // execution policy may refuse
+        let command = match cfg!(windows) {
+            true => windows_batch_shim(&command, server_path().as_deref().map(OsStr::new))
+                .unwrap_or(command),
+            false => command,
+        };
+        let container_name = if server.tools_type == "docker" {
+            let name = docker_container_name(server_id);
+            if let SecurityMode::Restricted { network: false, .. } = security_mode {
+                inject_docker_network_none(&mut args);
+            }
+            inject_docker_cpu_flags(&mut args, priority, cpu_limit);
+            inject_docker_memory_flag(&mut args, max_memory_mb);
+            let platforms = server
+                .configuration
+                .as_ref()
+                .and_then(|configuration| configuration.platforms.as_deref())
+                .unwrap_or_default();
+            arch.docker_platform = docker_platform(server_id, platforms, host, &args)?;
+            if let Some(platform) = &arch.docker_platform {
+                inject_docker_flag(&mut args, format!("--platform={}", platform));
+            }
+            inject_container_name(&mut args, &name).then_some(name)
+        } else {
+            let path = server_path();
+            arch = LaunchArch::for_command(host, &command, path.as_deref().map(OsStr::new));
+            None
+        };
+        let (command, args) = wrap_command(&security_mode, &command, &args);
+
+        let mut env_keys: Vec<String> = env.keys().cloned().collect();
+        env_keys.sort();
+
+        let process_env = ProcessEnv {
+            inherit: server
+                .configuration
+                .as_ref()
+                .and_then(|configuration| configuration.inherit_env)
+                .unwrap_or(false),
+            passthrough: server
+                .configuration
+                .as_ref()
+                .and_then(|configuration| configuration.env_passthrough.clone())
+                .unwrap_or_default(),
+        };
+        let effective_env = process_env.preview(parent.iter().cloned(), &env);
+        let removed_env = process_env.removed_vars(parent, &env);
+
+        Ok(Self {
+            server_id: server_id.to_string(),
+            tools_type: server.tools_type.clone(),
+            command,
+            args,
+            env,
+            env_keys,
+            inherit_env: process_env.inherit,
+            effective_env,
+            removed_env,
+            priority,
+            cpu_limit,
+            max_memory_mb,
+            cwd,
+            container_name,
+            security_mode,
+            arch,
+            lenient_initialize: server
+                .configuration
+                .as_ref()
+                .and_then(|configuration| configuration.lenient_initialize)
+                .unwrap_or(false),
+        })
+    }
+
+    /// Check that the working directory of the server exists, right before it is spawned
+    pub fn check_cwd(&self) -> Result<(), String> {
+        let Some(cwd) = &self.cwd else {
+            return Ok(());
+        };
+        if !cwd.is_absolute() {
+            return Err(format!(
+                "Working directory {} of server {} should be an absolute path",
+                cwd.display(),
+                self.server_id
+            ));
+        }
+        match std::fs::metadata(cwd) {
+            Ok(metadata) if metadata.is_dir() => Ok(()),
+            Ok(_) => Err(format!(
+                "Working directory {} of server {} is not a directory",
+                cwd.display(),
+                self.server_id
+            )),
+            Err(e) => Err(format!(
+                "Working directory {} of server {} can't be used: {}",
+                cwd.display(),
+                self.server_id,
+                e
+            )),
+        }
+    }
+
+    /// Whether priority, CPU and memory limits are applied by docker instead of on the
+    /// process
+    pub fn limits_applied_by_docker(&self) -> bool {
+        self.tools_type == "docker"
+    }
+}
+
+/// Platform of the docker image to run on `host`, out of the `declared` platforms it
+/// is built for
+///
+/// Nothing is chosen when no platform is declared or the arguments already choose one.
+fn docker_platform(
+    server_id: &str,
+    declared: &[String],
+    host: Option<Arch>,
+    args: &[String],
+) -> Result<Option<String>, String> {
+    let Some(host) = host else {
+        return Ok(None);
+    };
+    if declared.is_empty() || has_docker_flag(args, "--platform") {
+        return Ok(None);
+    }
+    match select_docker_platform(declared, host) {
+        Some(platform) => Ok(Some(platform.clone())),
+        None => Err(format!(
+            "Cannot start server {}: its docker image is only built for {}, not for {} of \
+             this machine",
+            server_id,
+            declared.join(", "),
+            host.docker_platform()
+        )),
+    }
+}
+
+/// Add a flag right after `run` in a `docker run` argument list
+fn inject_docker_flag(args: &mut Vec<String>, flag: String) {
+    if let Some(run_index) = args.iter().position(|arg| arg == "run") {
+        args.insert(run_index + 1, flag);
+    }
+}
+
+/// Add `--cpus` and `--cpu-shares` to a `docker run` argument list
+///
+/// Flags already present in the registry arguments are kept as they are. Normal
+/// priority without a CPU limit adds nothing.
+pub fn inject_docker_cpu_flags(
+    args: &mut Vec<String>,
+    priority: ProcessPriority,
+    cpu_limit: Option<u32>,
+) {
+    let Some(run_index) = args.iter().position(|arg| arg == "run") else {
+        return;
+    };
+    let mut flags = Vec::new();
+    if let Some(limit) = cpu_limit {
+        if !has_docker_flag(args, "--cpus") {
+            flags.push(format!("--cpus={}", limit));
+        }
+    }
+    if priority != ProcessPriority::Normal && !has_docker_flag(args, "--cpu-shares") {
+        flags.push(format!("--cpu-shares={}", priority.docker_cpu_shares()));
+    }
+    for (offset, flag) in flags.into_iter().enumerate() {
+        args.insert(run_index + 1 + offset, flag);
+    }
+}
+
+/// Add `--memory` to a `docker run` argument list, unless the registry arguments
+/// already limit the memory
+pub fn inject_docker_memory_flag(args: &mut Vec<String>, max_memory_mb: Option<u32>) {
+    let Some(limit) = max_memory_mb else {
+        return;
+    };
+    if has_docker_flag(args, "--memory") || has_docker_flag(args, "-m") {
+        return;
+    }
+    inject_docker_flag(args, format!("--memory={}m", limit));
+}
+
+/// File name of the batch shim `command` runs on Windows, like `npx.cmd` for `npx`,
+/// looked up in the directories of `path`
+///
+/// None when the command has an extension or the first match is an executable.
+pub fn windows_batch_shim(command: &str, path: Option<&OsStr>) -> Option<String> {
+    let program = Path::new(command);
+    if program.extension().is_some() || program.components().count() > 1 {
+        return None;
+    }
+    std::env::split_paths(path?).find_map(|dir| {
+        if dir.join(format!("{}.exe", command)).is_file() {
+            return Some(None);
+        }
+        [".cmd", ".bat"]
+            .iter()
+            .map(|ext| format!("{}{}", command, ext))
+            .find(|shim| dir.join(shim).is_file())
+            .map(Some)
+    })?
+}
+
+fn has_docker_flag(args: &[String], flag: &str) -> bool {
+    let prefix = format!("{}=", flag);
+    args.iter()
+        .any(|arg| arg == flag || arg.starts_with(&prefix))
+}
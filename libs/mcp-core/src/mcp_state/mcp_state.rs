@@ -1,26 +1,66 @@
-use crate::models::types::ServerToolInfo;
+use crate::arch::LaunchArch;
+use crate::diagnose::missing_required_env;
+use crate::events::{
+    DockmasterEvent, EventBus, InstallProgressReporter, InstallStage, ServerReconnect, ToolsUpdate,
+};
+use crate::health::{HealthCheckSettings, HealthMonitor, HEALTH_CHECK_TIMEOUT};
+use crate::lifecycle::{now_millis, LifecycleEvent, LifecycleKind, ServerUptime};
+use crate::lock_debug::TrackedRwLock;
+use crate::mcp_server::mcp_tools_service::MCPToolsService;
+use crate::mcp_state::launch_plan::LaunchPlan;
+use crate::mcp_state::process_launcher::{
+    ProcessLauncher, StdioProcessLauncher, DEFAULT_STARTUP_TIMEOUT_SECS, STARTUP_TIMEOUT_SETTING,
+};
+use crate::mcp_state::reconnect::{
+    DeadConnection, ReconnectBudget, RECONNECT_LIMIT, RECONNECT_WINDOW,
+};
+use crate::mcp_state::restart_gate::RestartGate;
+use crate::mcp_state::sandbox::SecurityMode;
+use crate::mcp_state::server_notifications::{parse_progress_topic, ServerNotification};
+use crate::mcp_state::shutdown::{
+    ShutdownOutcome, DEFAULT_SHUTDOWN_GRACE_SECS, PROCESS_EXIT_TIMEOUT, SHUTDOWN_GRACE_SETTING,
+    SHUTDOWN_POLL_INTERVAL,
+};
+use crate::mcp_state::start_failures::{
+    StartFailures, DEFAULT_MAX_FAILED_STARTS, MAX_FAILED_STARTS_SETTING,
+};
+use crate::mcp_state::startup_capture::StartupCapture;
+use crate::mcp_state::tool_stats::ToolStats;
+use crate::metrics::MetricsRegistry;
+use crate::models::tool_id::{find_tool, parse_proxy_tool_id};
+use crate::models::types::{
+    OrphanCleanupReport, ServerInitInfo, ServerResources, ServerToolInfo, SpawnedProcessRecord,
+};
 use crate::registry::server_registry::ServerRegistry;
+use crate::server_logs::ServerLogs;
+use crate::sessions::SessionRegistry;
+use crate::spawned_process::SpawnedProcess;
+use crate::tool_limits::{apply_tool_limits, ToolLimitReport};
 use crate::types::ServerStatus;
-use crate::utils::command::CommandWrappedInShellBuilder;
-use crate::MCPError;
-use log::{error, info};
-use mcp_sdk_client::transport::stdio::StdioTransport;
-use mcp_sdk_client::transport::stdio::StdioTransportHandle;
-use mcp_sdk_client::{
-    ClientCapabilities, ClientInfo, McpClient, McpClientTrait, McpService, Transport,
+use crate::utils::clock::{Clock, SystemClock};
+use crate::utils::process::{
+    is_process_alive, kill_process_tree, kill_spawned_processes, remove_docker_container,
+    spawn_memory_usage, spawn_resource_usage, spawned_process_tree, stop_docker_container,
+    ProcessInfo,
 };
+use crate::MCPError;
+use log::{debug, error, info, warn};
 use serde_json::{json, Value};
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::RwLock;
-use crate::mcp_server::mcp_tools_service::MCPToolsService;
+use uuid::Uuid;
+
+/// Connection to the process of a server, shared by its callers
+pub type McpClientType = Arc<SpawnedProcess>;
 
-/// Type alias for a transport that uses StdioTransportHandle
-pub type StdioTransportType = Arc<dyn Transport<Handle = StdioTransportHandle> + Send + Sync>;
+/// How often the processes of running servers are checked for an exit and their memory
+/// limit
+pub const PROCESS_CHECK_INTERVAL: Duration = Duration::from_secs(5);
 
-/// Type alias for McpClient trait objects
-pub type McpClientType = Arc<dyn McpClientTrait + Send + Sync>;
+/// Pages of `tools/list` read from a server at most, the tools past them are left out
+pub const MAX_TOOL_PAGES: usize = 50;
 
 /// MCPState: the main service layer
 ///
@@ -29,24 +69,80 @@ pub type McpClientType = Arc<dyn McpClientTrait + Send + Sync>;
 /// process management (ProcessManager), and JSON-RPC operations (mcp_proxy).
 #[derive(Clone)]
 pub struct MCPState {
-    pub tool_registry: Arc<RwLock<ServerRegistry>>,
-    pub server_tools: Arc<RwLock<HashMap<String, Vec<ServerToolInfo>>>>,
-    pub mcp_clients: Arc<RwLock<HashMap<String, MCPClient>>>,
+    pub tool_registry: Arc<TrackedRwLock<ServerRegistry>>,
+    pub server_tools: Arc<TrackedRwLock<HashMap<String, Vec<ServerToolInfo>>>>,
+    pub mcp_clients: Arc<TrackedRwLock<HashMap<String, MCPClient>>>,
     pub are_tools_hidden: Arc<RwLock<bool>>,
+    /// Keeps restarts of the same server from running concurrently
+    pub restart_gate: RestartGate,
+    /// Result of the last orphan cleanup, the one done at startup until asked again
+    pub orphan_cleanup_report: Arc<RwLock<OrphanCleanupReport>>,
+    /// Starts server processes
+    pub launcher: Arc<dyn ProcessLauncher>,
+    /// Clock used for startup timeouts
+    pub clock: Arc<dyn Clock>,
+    /// Usage counters of the proxied tools
+    pub tool_stats: ToolStats,
+    /// Prometheus metrics of the servers
+    pub metrics: MetricsRegistry,
+    /// Why the last start of a server failed, by server id, until it starts or stops
+    pub launch_errors: Arc<RwLock<HashMap<String, String>>>,
+    /// Connected MCP clients
+    pub sessions: SessionRegistry,
+    /// Automatic restarts of the servers whose connection died
+    pub reconnect_budget: ReconnectBudget,
+    /// Consecutive failed starts of the servers, see [`crate::mcp_state::start_failures`]
+    pub start_failures: StartFailures,
+    /// Bus the events of the servers are published on, shared with the core
+    pub event_bus: EventBus,
+    /// Tools of the last run served until the servers start, see [`crate::tools_snapshot`]
+    pub stale_tools: Arc<RwLock<Option<Vec<ServerToolInfo>>>>,
+    /// What discovery left out of the tools of each server, by server id, see
+    /// [`crate::tool_limits`]
+    pub tool_limits: Arc<RwLock<HashMap<String, ToolLimitReport>>>,
+    /// Required environment variables without a value of the servers not started for
+    /// lack of them, by server id
+    pub needs_configuration: Arc<RwLock<HashMap<String, Vec<String>>>>,
+    /// What the servers wrote to stderr, see [`crate::server_logs`]
+    pub server_logs: ServerLogs,
+    /// Whether the running servers answer, see [`crate::health`]
+    pub server_health: HealthMonitor,
+    /// Servers whose changed tools are being discovered, by server id, true when they
+    /// changed again meanwhile
+    pub changed_tools: Arc<std::sync::Mutex<HashMap<String, bool>>>,
 }
 
 #[derive(Clone)]
 pub struct MCPClient {
     pub client: McpClientType,
-    pub transport: StdioTransportType,
     pub server_status: ServerStatus,
+    /// Restrictions the process was started with
+    pub security_mode: SecurityMode,
+    /// Tag of the process tree, see [`SPAWN_ID_ENV`](crate::utils::process::SPAWN_ID_ENV)
+    pub spawn_id: String,
+    /// Topmost process of the server, if it could be found
+    pub process: Option<ProcessInfo>,
+    /// When the process started, in milliseconds since the epoch
+    pub started_at: u64,
+    /// Container of a docker server, stopped along with it
+    pub container_name: Option<String>,
+    /// Architectures the process runs with
+    pub arch: LaunchArch,
+    /// Milliseconds the server took to answer the initialize handshake
+    pub startup_ms: u64,
+    /// Memory limit in megabytes, enforced by docker for a docker server and by
+    /// [`MCPState::enforce_memory_limits`] otherwise
+    pub max_memory_mb: Option<u32>,
+    /// Whether its tools are listed, false when it declared no tool support in the
+    /// initialize handshake
+    pub lists_tools: bool,
 }
 
 impl MCPState {
     pub fn new(
-        tool_registry: Arc<RwLock<ServerRegistry>>,
-        server_tools: Arc<RwLock<HashMap<String, Vec<ServerToolInfo>>>>,
-        mcp_clients: Arc<RwLock<HashMap<String, MCPClient>>>,
+        tool_registry: Arc<TrackedRwLock<ServerRegistry>>,
+        server_tools: Arc<TrackedRwLock<HashMap<String, Vec<ServerToolInfo>>>>,
+        mcp_clients: Arc<TrackedRwLock<HashMap<String, MCPClient>>>,
     ) -> Self {
         // Initialize with default value
         let are_tools_hidden = Arc::new(RwLock::new(false));
@@ -56,6 +152,23 @@ impl MCPState {
             server_tools,
             mcp_clients,
             are_tools_hidden,
+            restart_gate: RestartGate::new(),
+            orphan_cleanup_report: Arc::new(RwLock::new(OrphanCleanupReport::default())),
+            launcher: Arc::new(StdioProcessLauncher),
+            clock: Arc::new(SystemClock),
+            tool_stats: ToolStats::new(),
+            metrics: MetricsRegistry::new(),
+            launch_errors: Arc::new(RwLock::new(HashMap::new())),
+            sessions: SessionRegistry::new(),
+            reconnect_budget: ReconnectBudget::new(),
+            start_failures: StartFailures::new(),
+            event_bus: EventBus::new(),
+            stale_tools: Arc::new(RwLock::new(None)),
+            tool_limits: Arc::new(RwLock::new(HashMap::new())),
+            needs_configuration: Arc::new(RwLock::new(HashMap::new())),
+            server_logs: ServerLogs::new(),
+            server_health: HealthMonitor::new(),
+            changed_tools: Arc::new(std::sync::Mutex::new(HashMap::new())),
         }
     }
 
@@ -79,26 +192,268 @@ impl MCPState {
         Ok(())
     }
 
-    /// Kill all running processes, attempting to kill each process even if some fail
-    pub async fn kill_all_processes(&self) -> Result<(), String> {
+    /// Stop all running processes, attempting to stop each process even if some fail
+    ///
+    /// Returns how each server stopped, by server id.
+    pub async fn kill_all_processes(&self) -> Result<HashMap<String, ShutdownOutcome>, String> {
         let server_ids: Vec<String> = self.mcp_clients.read().await.keys().cloned().collect();
+        let mut outcomes = HashMap::new();
         let mut errors = Vec::new();
 
         for server_id in server_ids {
-            if let Err(e) = self.kill_process(&server_id).await {
-                errors.push(format!("Failed to kill process {}: {}", server_id, e));
+            match self.kill_process(&server_id).await {
+                Ok(outcome) => {
+                    outcomes.insert(server_id, outcome);
+                }
+                Err(e) => errors.push(format!("Failed to kill process {}: {}", server_id, e)),
             }
         }
 
         if errors.is_empty() {
-            Ok(())
+            Ok(outcomes)
         } else {
             Err(errors.join("\n"))
         }
     }
 
-    /// Kill a process by its ID
-    pub async fn kill_process(&self, server_id: &str) -> Result<(), String> {
+    /// Time a server has to start and answer the initialize handshake
+    pub async fn startup_timeout(&self) -> Duration {
+        let secs = self
+            .tool_registry
+            .read()
+            .await
+            .get_setting(STARTUP_TIMEOUT_SETTING)
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_STARTUP_TIMEOUT_SECS);
+        Duration::from_secs(secs)
+    }
+
+    /// Time a server has to exit once asked to, see [`crate::mcp_state::shutdown`]
+    pub async fn shutdown_grace_period(&self) -> Duration {
+        let secs = self
+            .tool_registry
+            .read()
+            .await
+            .get_setting(SHUTDOWN_GRACE_SETTING)
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_SHUTDOWN_GRACE_SECS);
+        Duration::from_secs(secs)
+    }
+
+    /// Consecutive failed starts after which a server is faulted, see
+    /// [`crate::mcp_state::start_failures`]
+    pub async fn max_failed_starts(&self) -> u32 {
+        self.tool_registry
+            .read()
+            .await
+            .get_setting(MAX_FAILED_STARTS_SETTING)
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_MAX_FAILED_STARTS)
+    }
+
+    /// Status of a server: the status of its client while its process runs, `Faulted`
+    /// when it failed to start too many times in a row, or `Error` when it failed to
+    /// start or exited
+    pub async fn server_status(&self, server_id: &str) -> ServerStatus {
+        let mcp_client = self.mcp_clients.read().await.get(server_id).cloned();
+        if let Some(mcp_client) = mcp_client {
+            match self.exit_of(&mcp_client).await {
+                None => return mcp_client.server_status,
+                Some(exit_code) => {
+                    self.forget_exited_server(server_id, &mcp_client, exit_code, None)
+                        .await
+                }
+            }
+        }
+        if self
+            .needs_configuration
+            .read()
+            .await
+            .contains_key(server_id)
+        {
+            return ServerStatus::NeedsConfiguration;
+        }
+        let limit = self.max_failed_starts().await;
+        if let Some(failed) = self.start_failures.fault(server_id, limit) {
+            return ServerStatus::Faulted(failed.last_error);
+        }
+        match self.launch_errors.read().await.get(server_id) {
+            Some(error) => ServerStatus::Error(error.clone()),
+            None => ServerStatus::Stopped,
+        }
+    }
+
+    /// Remove the clients of the servers whose process exited, returning their ids
+    pub async fn reap_exited_servers(&self) -> Vec<String> {
+        let mcp_clients: Vec<(String, MCPClient)> = self
+            .mcp_clients
+            .read()
+            .await
+            .iter()
+            .map(|(server_id, mcp_client)| (server_id.clone(), mcp_client.clone()))
+            .collect();
+        let mut exited = Vec::new();
+        for (server_id, mcp_client) in mcp_clients {
+            if let Some(exit_code) = self.exit_of(&mcp_client).await {
+                self.metrics.record_health_check_failure(&server_id);
+                self.forget_exited_server(&server_id, &mcp_client, exit_code, None)
+                    .await;
+                exited.push(server_id);
+            }
+        }
+        exited
+    }
+
+    /// Kill the servers using more memory than their limit, returning their ids
+    ///
+    /// Docker servers are left to docker, which enforces their limit itself.
+    pub async fn enforce_memory_limits(&self) -> Vec<String> {
+        let limited: Vec<(String, MCPClient, u32)> = self
+            .mcp_clients
+            .read()
+            .await
+            .iter()
+            .filter(|(_, mcp_client)| mcp_client.container_name.is_none())
+            .filter_map(|(server_id, mcp_client)| {
+                let limit = mcp_client.max_memory_mb?;
+                Some((server_id.clone(), mcp_client.clone(), limit))
+            })
+            .collect();
+        if limited.is_empty() {
+            return Vec::new();
+        }
+
+        let spawn_ids: Vec<String> = limited
+            .iter()
+            .map(|(_, mcp_client, _)| mcp_client.spawn_id.clone())
+            .collect();
+        let usage = tokio::task::spawn_blocking(move || spawn_memory_usage(&spawn_ids))
+            .await
+            .unwrap_or_default();
+
+        let mut killed = Vec::new();
+        for (server_id, mcp_client, limit) in limited {
+            let Some(used) = usage.get(&mcp_client.spawn_id) else {
+                continue;
+            };
+            let used_mb = used / (1024 * 1024);
+            if used_mb <= u64::from(limit) {
+                continue;
+            }
+            let reason = format!(
+                "Server exceeded its memory limit of {} MB, using {} MB",
+                limit, used_mb
+            );
+            warn!("Killing server {}: {}", server_id, reason);
+            let spawn_id = mcp_client.spawn_id.clone();
+            let process = mcp_client.process.clone();
+            let _ = tokio::task::spawn_blocking(move || {
+                kill_spawned_processes(
+                    &spawn_id,
+                    process.as_ref().map(|process| process.pid),
+                    process.as_ref().map(|process| process.start_time),
+                    process.as_ref().map(|process| process.command.as_str()),
+                )
+            })
+            .await;
+            self.forget_exited_server(&server_id, &mcp_client, None, Some(reason))
+                .await;
+            killed.push(server_id);
+        }
+        killed
+    }
+
+    /// Exit code of the process of a client, None while it runs
+    ///
+    /// `Some(None)` when the process is gone without leaving its exit code.
+    async fn exit_of(&self, mcp_client: &MCPClient) -> Option<Option<i32>> {
+        if let Some(exit_code) = StartupCapture::open(&mcp_client.spawn_id).exit_code() {
+            return Some(exit_code);
+        }
+        // Killed along with its shell, before the exit code could be written
+        let process = mcp_client.process.clone()?;
+        let alive = tokio::task::spawn_blocking(move || is_process_alive(&process))
+            .await
+            .unwrap_or(true);
+        (!alive).then_some(None)
+    }
+
+    /// Drop the client of a server whose process exited, keeping why as its error
+    ///
+    /// `reason` replaces the exit code and stderr of the server in the error, when
+    /// Dockmaster knows better why it exited.
+    async fn forget_exited_server(
+        &self,
+        server_id: &str,
+        mcp_client: &MCPClient,
+        exit_code: Option<i32>,
+        reason: Option<String>,
+    ) {
+        {
+            let mut mcp_clients = self.mcp_clients.write().await;
+            // A restart may have replaced the client in the meantime
+            if mcp_clients
+                .get(server_id)
+                .is_none_or(|current| current.spawn_id != mcp_client.spawn_id)
+            {
+                return;
+            }
+            mcp_clients.remove(server_id);
+        }
+        self.metrics.record_exit(server_id, exit_code);
+        // Docker kills a container over its memory limit, which exits with 137
+        let reason = reason.or_else(|| {
+            let limit = mcp_client.max_memory_mb?;
+            (mcp_client.container_name.is_some() && exit_code == Some(137)).then(|| {
+                format!(
+                    "Server was killed with exit code 137, most likely for exceeding its \
+                     memory limit of {} MB",
+                    limit
+                )
+            })
+        });
+        let message = match reason {
+            Some(reason) => reason,
+            None => StartupCapture::open(&mcp_client.spawn_id).exit_message(exit_code),
+        };
+        warn!("Server {} is not running anymore: {}", server_id, message);
+        self.record_lifecycle(
+            LifecycleEvent::now(server_id, LifecycleKind::Crashed)
+                .with_exit_code(exit_code)
+                .with_detail(message.clone()),
+        )
+        .await;
+        self.launch_errors
+            .write()
+            .await
+            .insert(server_id.to_string(), message);
+        self.server_tools.write().await.remove(server_id);
+        self.tool_limits.write().await.remove(server_id);
+        self.server_health.remove(server_id);
+
+        mcp_client.client.close().await;
+        self.server_logs.stop(server_id);
+        StartupCapture::remove(&mcp_client.spawn_id);
+        if let Err(e) = self
+            .tool_registry
+            .read()
+            .await
+            .delete_server_process(server_id)
+        {
+            warn!("Failed to delete process record of {}: {}", server_id, e);
+        }
+    }
+
+    /// Stop the process of a server, gracefully when it exits within the
+    /// [`shutdown_grace_period`](Self::shutdown_grace_period)
+    pub async fn kill_process(&self, server_id: &str) -> Result<ShutdownOutcome, String> {
+        // A stopped server has no start failure to report anymore
+        self.launch_errors.write().await.remove(server_id);
+        self.needs_configuration.write().await.remove(server_id);
+
         // First check if the client exists
         let client_exists = {
             let mcp_clients = self.mcp_clients.read().await;
@@ -116,8 +471,9 @@ impl MCPState {
         };
 
         if let Some(mcp_client) = mcp_client_clone {
-            // Close the transport
-            let _ = mcp_client.transport.close().await;
+            let outcome = self.shut_down(server_id, &mcp_client).await;
+            self.server_logs.stop(server_id);
+            StartupCapture::remove(&mcp_client.spawn_id);
 
             // Update the server status to Stopped in the map and remove the client
             let mut mcp_clients = self.mcp_clients.write().await;
@@ -126,13 +482,143 @@ impl MCPState {
 
             // Remove the server tools
             let _ = self.server_tools.write().await.remove(server_id);
+            self.tool_limits.write().await.remove(server_id);
+            self.server_health.remove(server_id);
 
-            Ok(())
+            // The process is gone, it can't become an orphan anymore
+            if let Err(e) = self
+                .tool_registry
+                .read()
+                .await
+                .delete_server_process(server_id)
+            {
+                warn!("Failed to delete process record of {}: {}", server_id, e);
+            }
+
+            Ok(outcome)
         } else {
             Err(format!("No client found for server: {}", server_id))
         }
     }
 
+    /// Close the stdin of a server and stop its container, then wait for it to exit and
+    /// kill what is left of its process tree
+    async fn shut_down(&self, server_id: &str, mcp_client: &MCPClient) -> ShutdownOutcome {
+        let grace = self.shutdown_grace_period().await;
+        // The children of the server can't be found by parent once it exited
+        let spawn_id = mcp_client.spawn_id.clone();
+        let process = mcp_client.process.clone();
+        let tree = tokio::task::spawn_blocking(move || {
+            spawned_process_tree(
+                &spawn_id,
+                process.as_ref().map(|process| process.pid),
+                process.as_ref().map(|process| process.start_time),
+                process.as_ref().map(|process| process.command.as_str()),
+            )
+        })
+        .await
+        .unwrap_or_default();
+
+        // Many servers exit on EOF
+        mcp_client.client.close().await;
+        if let Some(container_name) = &mcp_client.container_name {
+            if let Err(e) = stop_docker_container(container_name, grace).await {
+                warn!("Failed to stop container {}: {}", container_name, e);
+            }
+        }
+
+        let deadline = self.clock.now() + grace;
+        let mut exited = false;
+        while self.clock.now() < deadline {
+            if self.exit_of(mcp_client).await.is_some() {
+                exited = true;
+                break;
+            }
+            self.clock.sleep(SHUTDOWN_POLL_INTERVAL).await;
+        }
+
+        let spawn_id = mcp_client.spawn_id.clone();
+        let process = mcp_client.process.clone();
+        let killed = tokio::task::spawn_blocking(move || {
+            kill_process_tree(
+                &spawn_id,
+                process.as_ref().map(|process| process.pid),
+                process.as_ref().map(|process| process.start_time),
+                process.as_ref().map(|process| process.command.as_str()),
+                &tree,
+            )
+        })
+        .await
+        .unwrap_or_default();
+        if exited {
+            if killed > 0 {
+                info!(
+                    "Server {} exited gracefully, killed {} processes it left behind",
+                    server_id, killed
+                );
+            } else {
+                info!("Server {} exited gracefully", server_id);
+            }
+            return ShutdownOutcome::Graceful;
+        }
+        if killed == 0 {
+            // Nothing was left to kill, it exited without telling
+            return ShutdownOutcome::Graceful;
+        }
+        warn!(
+            "Server {} didn't exit within {:?}, killed {} processes",
+            server_id, grace, killed
+        );
+        ShutdownOutcome::Forced
+    }
+
+    /// Wait for a killed process of a server to be gone, so that a restart doesn't leave
+    /// two of them running
+    async fn wait_for_process_exit(
+        &self,
+        server_id: &str,
+        process: ProcessInfo,
+    ) -> Result<(), String> {
+        let pid = process.pid;
+        let deadline = self.clock.now() + PROCESS_EXIT_TIMEOUT;
+        loop {
+            let check = process.clone();
+            let alive = tokio::task::spawn_blocking(move || is_process_alive(&check))
+                .await
+                .unwrap_or(false);
+            if !alive {
+                return Ok(());
+            }
+            if self.clock.now() >= deadline {
+                error!(
+                    "Process {} of server {} is still running after it was killed",
+                    pid, server_id
+                );
+                return Err(format!(
+                    "Process {} of server {} is still running after it was killed, not \
+                     starting another",
+                    pid, server_id
+                ));
+            }
+            self.clock.sleep(SHUTDOWN_POLL_INTERVAL).await;
+        }
+    }
+
+    /// Tool asked for by `key`, see [`find_tool`], or a tool left out by the
+    /// [`crate::tool_limits`] of its server when `key` is its proxy id
+    pub async fn find_callable_tool(&self, key: &str) -> Option<ServerToolInfo> {
+        if let Some(tool) = find_tool(&*self.server_tools.read().await, key) {
+            return Some(tool.clone());
+        }
+        let (server_id, tool_name) = parse_proxy_tool_id(key)?;
+        self.tool_limits
+            .read()
+            .await
+            .get(server_id)?
+            .left_out_tool(tool_name)
+            .cloned()
+    }
+
     /// Execute a tool on a server
     pub async fn execute_tool(
         &self,
@@ -148,15 +634,22 @@ impl MCPState {
             );
             let result = mcp_client
                 .client
-                .call_tool(tool_id, parameters)
+                .call_tool(tool_id, parameters, None)
                 .await
-                .map_err(|e| MCPError::ToolExecutionError(e.to_string()))?;
-
-            // Convert the result to a Value
-            let content_value = serde_json::to_value(result.content)
-                .map_err(|e| MCPError::SerializationError(e.to_string()))?;
+                .map_err(|e| {
+                    if mcp_client.security_mode.is_restricted() {
+                        // Sandbox violations usually show up as failed tool calls
+                        MCPError::ToolExecutionError(format!(
+                            "{} (server runs in {})",
+                            e,
+                            mcp_client.security_mode.describe()
+                        ))
+                    } else {
+                        MCPError::ToolExecutionError(e.to_string())
+                    }
+                })?;
 
-            Ok(content_value)
+            Ok(result.get("content").cloned().unwrap_or(Value::Null))
         } else {
             Err(MCPError::ServerNotFound(format!(
                 "No client found for server: {}",
@@ -167,6 +660,128 @@ impl MCPState {
 
     /// Restart a server by its ID
     pub async fn restart_server(&self, server_id: &str) -> Result<(), String> {
+        self.restart_server_with_progress(server_id, None).await
+    }
+
+    /// Start a server unless it runs, waiting for a start of it in progress, returning
+    /// whether it was started
+    pub async fn start_server_if_stopped(&self, server_id: &str) -> Result<bool, String> {
+        self.restart_gate
+            .exclusive(server_id, || async {
+                if self.mcp_clients.read().await.contains_key(server_id) {
+                    return Ok(false);
+                }
+                self.restart_server_locked(server_id, None).await?;
+                Ok(self.mcp_clients.read().await.contains_key(server_id))
+            })
+            .await
+    }
+
+    /// List the tools saved by the last run of a server that isn't running, returning
+    /// how many
+    ///
+    /// A lazy server is listed with them until its first tool call starts it.
+    pub async fn serve_saved_tools(&self, server_id: &str) -> usize {
+        let tools = self
+            .tool_registry
+            .read()
+            .await
+            .get_server_tools(server_id)
+            .unwrap_or_default();
+        let count = tools.len();
+        if count > 0 {
+            self.server_tools
+                .write()
+                .await
+                .insert(server_id.to_string(), tools);
+        }
+        count
+    }
+
+    /// Restart a server by its ID, reporting the start and discovery stages
+    ///
+    /// Concurrent restarts of the same server are coalesced, see [`RestartGate`].
+    pub async fn restart_server_with_progress(
+        &self,
+        server_id: &str,
+        progress: Option<&InstallProgressReporter>,
+    ) -> Result<(), String> {
+        self.restart_gate
+            .run(server_id, || {
+                self.restart_server_locked(server_id, progress)
+            })
+            .await
+    }
+
+    /// Restart a server, recording `cause` in its lifecycle history when it was running
+    ///
+    /// A configuration change gives a faulted server another chance.
+    pub async fn restart_server_because(
+        &self,
+        server_id: &str,
+        cause: LifecycleKind,
+    ) -> Result<(), String> {
+        if cause == LifecycleKind::ConfigRestart {
+            self.start_failures.reset(server_id);
+        }
+        if self.mcp_clients.read().await.contains_key(server_id) {
+            self.record_lifecycle(LifecycleEvent::now(server_id, cause))
+                .await;
+        }
+        self.restart_server(server_id).await
+    }
+
+    /// Stop a server, waiting for any restart of it in progress
+    pub async fn stop_server(&self, server_id: &str) -> Result<(), String> {
+        let outcome = self
+            .restart_gate
+            .exclusive(server_id, || self.kill_process(server_id))
+            .await?;
+        let mut stopped = LifecycleEvent::now(server_id, LifecycleKind::StoppedByUser);
+        if outcome == ShutdownOutcome::Forced {
+            stopped = stopped.with_detail("Killed after the shutdown grace period");
+        }
+        self.record_lifecycle(stopped).await;
+        Ok(())
+    }
+
+    /// Append an event to the lifecycle history of its server
+    async fn record_lifecycle(&self, event: LifecycleEvent) {
+        if let Err(e) = self
+            .tool_registry
+            .read()
+            .await
+            .record_lifecycle_event(&event)
+        {
+            warn!(
+                "Failed to record lifecycle event of {}: {}",
+                event.server_id, e
+            );
+        }
+    }
+
+    /// Uptime of a server and how often it restarted lately
+    pub async fn uptime(&self, server_id: &str) -> ServerUptime {
+        let started_at = self
+            .mcp_clients
+            .read()
+            .await
+            .get(server_id)
+            .map(|mcp_client| mcp_client.started_at);
+        let history = self
+            .tool_registry
+            .read()
+            .await
+            .get_lifecycle_history(server_id);
+        ServerUptime::new(started_at, &history, now_millis())
+    }
+
+    /// Kill and respawn a server, must be called with the server's restart lock held
+    async fn restart_server_locked(
+        &self,
+        server_id: &str,
+        progress: Option<&InstallProgressReporter>,
+    ) -> Result<(), String> {
         info!("Attempting to restart server: {}", server_id);
 
         // Get tool from database
@@ -188,11 +803,20 @@ impl MCPState {
         };
 
         if client_exists {
+            let process = self
+                .mcp_clients
+                .read()
+                .await
+                .get(server_id)
+                .and_then(|mcp_client| mcp_client.process.clone());
             // First kill the existing process
             if let Err(e) = self.kill_process(server_id).await {
                 error!("Failed to kill existing process during restart: {}", e);
                 return Err(format!("Failed to kill existing process: {}", e));
             }
+            if let Some(process) = process {
+                self.wait_for_process_exit(server_id, process).await?;
+            }
             info!(
                 "Successfully killed existing process for server: {}",
                 server_id
@@ -200,125 +824,187 @@ impl MCPState {
         }
 
         // Check if the tool is enabled
+        self.needs_configuration.write().await.remove(server_id);
         if !server_data.enabled {
             info!("Server {} is disabled, not restarting", server_id);
             return Ok(());
         }
 
-        // Extract environment variables from the tool configuration
-        let env_vars = if let Some(configuration) = &server_data.configuration {
-            if let Some(env_map) = &configuration.env {
-                info!(
-                    "Extracted {} environment variables for server {}",
-                    env_map.len(),
-                    server_id
-                );
-                // Convert ToolEnvironment -> just the defaults
-                let simple_env_map: HashMap<String, String> = env_map
-                    .iter()
-                    .filter_map(|(k, tool_env)| tool_env.default.clone().map(|v| (k.clone(), v)))
-                    .collect();
-                Some(simple_env_map)
-            } else {
-                info!("No environment variables found for server {}", server_id);
-                None
-            }
-        } else {
-            info!("No configuration found for server {}", server_id);
-            None
-        };
-
-        // Get the configuration from the tool data
-        let config_value = if let Some(configuration) = &server_data.configuration {
-            info!("Using configuration from server data for {}", server_id);
-            json!({
-                "command": configuration.command,
-                "args": configuration.args
-            })
-        } else if !server_data
-            .entry_point
-            .clone()
-            .unwrap_or_default()
-            .is_empty()
-        {
+        // It would only fail, the user sets the variables and the server starts then
+        let missing = missing_required_env(server_data.configuration.as_ref());
+        if !missing.is_empty() {
             info!(
-                "Creating simple configuration with entry_point for {}",
-                server_id
+                "Server {} needs configuration, no value for {}",
+                server_id,
+                missing.join(", ")
             );
-            json!({
-                "command": server_data.entry_point
-            })
-        } else {
-            error!("Missing configuration for server {}", server_id);
-            return Err(format!("Missing configuration for server {}", server_id));
-        };
-
-        let mut envs = env_vars.unwrap_or_default();
-        let mut sustituted_args = Vec::new();
-        for v in config_value["args"].as_array().unwrap_or(&vec![]) {
-            let args_key = v.as_str().unwrap();
-            let adapted_args_key = args_key.replace("$", "");
-
-            let args_value = if args_key.starts_with("$") && envs.contains_key(&adapted_args_key) {
-                let args_value_from_env = envs.get(&adapted_args_key).unwrap().clone();
-                envs.remove(&adapted_args_key);
-                args_value_from_env
-            } else {
-                args_key.to_string()
-            };
-            sustituted_args.push(args_value);
+            self.launch_errors.write().await.remove(server_id);
+            self.needs_configuration
+                .write()
+                .await
+                .insert(server_id.to_string(), missing);
+            return Ok(());
         }
-        let (adapted_program, adapted_args, adapted_envs) =
-            CommandWrappedInShellBuilder::wrap_in_shell_as_values(
-                config_value["command"].as_str().unwrap(),
-                Some(sustituted_args.iter().map(|s| s.as_str())),
-                Some(envs),
-            );
 
-        let transport = StdioTransport::new(adapted_program, adapted_args, adapted_envs);
+        // Given up on until the user looks at it
+        let limit = self.max_failed_starts().await;
+        if let Some(failed) = self.start_failures.fault(server_id, limit) {
+            let message = failed.describe(server_id);
+            warn!("{}", message);
+            return Err(message);
+        }
 
-        let transport_handle = match transport.start().await {
-            Ok(handle) => handle,
-            Err(e) => return Err(format!("Failed to start transport: {}", e)),
-        };
+        let plan = LaunchPlan::build(server_id, &server_data).inspect_err(|e| error!("{}", e))?;
+        info!(
+            "Launching server {} with {} environment variables",
+            server_id,
+            plan.env_keys.len()
+        );
+        if let Some(warning) = plan.arch.warning() {
+            warn!("Server {}: {}", server_id, warning);
+        }
 
-        // Create the service with a timeout of 300 seconds
-        let service = McpService::with_timeout(transport_handle, Duration::from_secs(300));
+        let spawn_id = Uuid::new_v4().to_string();
+        // Recorded before spawning so that the process is cleaned up if we crash while
+        // it starts, the pid is filled in once it is known
+        self.record_spawned_process(
+            server_id,
+            spawn_id.clone(),
+            plan.container_name.clone(),
+            None,
+        )
+        .await;
 
-        // Create and initialize the client
-        let mut client = McpClient::new(service);
+        self.server_logs
+            .follow(server_id, StartupCapture::open(&spawn_id).stderr_path());
+        if let Some(progress) = progress {
+            progress.report(InstallStage::Starting, None);
+        }
 
-        let client_info = ClientInfo {
-            name: "mcp-dockmaster".to_string(),
-            version: env!("CARGO_PKG_VERSION").to_string(),
+        // Ready once it answers the initialize handshake, its tools are listed right away
+        let startup_timeout = self.startup_timeout().await;
+        let launch_started = self.clock.now();
+        let launched = tokio::select! {
+            launched = self.launcher.launch(&plan, &spawn_id) => launched,
+            _ = self.clock.sleep(startup_timeout) => Err(format!(
+                "Server {} did not start within {} seconds",
+                server_id,
+                startup_timeout.as_secs()
+            )),
         };
-
-        if let Err(e) = client
-            .initialize(client_info, ClientCapabilities::default())
-            .await
-        {
-            return Err(format!("Failed to initialize client: {}", e));
+        let startup_ms = self.clock.now().duration_since(launch_started).as_millis() as u64;
+        let launched = match launched {
+            Ok(launched) => {
+                info!("Server {} answered after {} ms", server_id, startup_ms);
+                self.launch_errors.write().await.remove(server_id);
+                self.start_failures.reset(server_id);
+                launched
+            }
+            Err(e) => {
+                error!("Failed to start server {}: {}", server_id, e);
+                self.launch_errors
+                    .write()
+                    .await
+                    .insert(server_id.to_string(), e.clone());
+                self.server_logs.stop(server_id);
+                StartupCapture::remove(&spawn_id);
+                self.record_lifecycle(
+                    LifecycleEvent::now(server_id, LifecycleKind::StartFailed).with_detail(&e),
+                )
+                .await;
+                let failed = self.start_failures.record_failure(server_id, &e);
+                if failed == limit {
+                    warn!(
+                        "Server {} failed to start {} times in a row, not starting it again",
+                        server_id, failed
+                    );
+                }
+                return Err(e);
+            }
+        };
+        if launched.process.is_some() {
+            self.record_spawned_process(
+                server_id,
+                spawn_id.clone(),
+                plan.container_name.clone(),
+                launched.process.clone(),
+            )
+            .await;
         }
+        if let Some(info) = &launched.server_info {
+            if let Err(e) = self
+                .tool_registry
+                .read()
+                .await
+                .save_server_info(server_id, info)
+            {
+                warn!("Failed to save server info of {}: {}", server_id, e);
+            }
+        }
+        let lists_tools = plan.lenient_initialize
+            || launched
+                .server_info
+                .as_ref()
+                .is_none_or(ServerInitInfo::declares_tools);
 
-        self.mcp_clients.write().await.insert(
+        self.server_health.remove(server_id);
+        let started = LifecycleEvent::now(server_id, LifecycleKind::Started);
+        let started_at = started.at;
+        self.record_lifecycle(started).await;
+        self.follow_server_notifications(server_id, launched.client.clone());
+        let previous = self.mcp_clients.write().await.insert(
             server_id.to_string(),
             MCPClient {
-                client: Arc::new(client) as McpClientType,
-                transport: Arc::new(transport) as StdioTransportType,
+                client: launched.client,
                 server_status: ServerStatus::Running,
+                security_mode: plan.security_mode.clone(),
+                spawn_id,
+                process: launched.process,
+                started_at,
+                container_name: plan.container_name.clone(),
+                arch: plan.arch.clone(),
+                startup_ms,
+                max_memory_mb: plan.max_memory_mb,
+                lists_tools,
             },
         );
+        // Never leave two live children for one server id
+        if let Some(previous) = previous {
+            warn!(
+                "Server {} already had a running process, closing it",
+                server_id
+            );
+            previous.client.close().await;
+        }
+        self.metrics.record_start(server_id);
 
-        match self.discover_server_tools(server_id).await {
+        if let Some(progress) = progress {
+            progress.report(InstallStage::Discovering, None);
+        }
+
+        // Not reconnecting, the restart lock is held
+        match self.discover_server_tools_once(server_id).await {
             Ok(tools) => {
                 info!(
                     "Successfully discovered {} tools for {}",
                     tools.len(),
                     server_id
                 );
+                if let Some(progress) = progress {
+                    progress.report(
+                        InstallStage::Discovering,
+                        Some(format!("{} tools found", tools.len())),
+                    );
+                }
             }
             Err(e) => {
                 error!("Failed to discover tools for server: {}", e);
+                if let Some(progress) = progress {
+                    progress.report(
+                        InstallStage::Discovering,
+                        Some(format!("tool discovery failed: {}", e)),
+                    );
+                }
             }
         }
 
@@ -327,6 +1013,182 @@ impl MCPState {
         Ok(())
     }
 
+    /// Record the process spawned for a server so it can be cleaned up after a crash
+    async fn record_spawned_process(
+        &self,
+        server_id: &str,
+        spawn_id: String,
+        container_name: Option<String>,
+        process: Option<ProcessInfo>,
+    ) {
+        let record = SpawnedProcessRecord {
+            server_id: server_id.to_string(),
+            spawn_id,
+            pid: process.as_ref().map(|p| p.pid),
+            start_time: process.as_ref().map(|p| p.start_time),
+            command: process.map(|p| p.command),
+            container_name,
+        };
+        if let Err(e) = self.tool_registry.read().await.save_server_process(&record) {
+            warn!("Failed to record process of {}: {}", server_id, e);
+        }
+    }
+
+    /// Resolve how a server would be launched, without starting it
+    pub async fn launch_plan(&self, server_id: &str) -> Result<LaunchPlan, String> {
+        let server_data = self.tool_registry.read().await.get_server(server_id)?;
+        LaunchPlan::build(server_id, &server_data)
+    }
+
+    /// Architectures of a server, as started if it is running or as it would be started
+    pub async fn launch_arch(&self, server_id: &str) -> Result<LaunchArch, String> {
+        if let Some(client) = self.mcp_clients.read().await.get(server_id) {
+            return Ok(client.arch.clone());
+        }
+        Ok(self.launch_plan(server_id).await?.arch)
+    }
+
+    /// Restrictions of a server, as started if it is running or as it would be started
+    pub async fn security_mode(&self, server_id: &str) -> Result<SecurityMode, String> {
+        if let Some(client) = self.mcp_clients.read().await.get(server_id) {
+            return Ok(client.security_mode.clone());
+        }
+        let server_data = self.tool_registry.read().await.get_server(server_id)?;
+        SecurityMode::resolve(
+            &server_data.tools_type,
+            server_data
+                .configuration
+                .as_ref()
+                .and_then(|configuration| configuration.sandbox.as_ref()),
+        )
+    }
+
+    /// Effective priority and CPU limit of a server and the resources it currently uses
+    pub async fn server_resources(&self, server_id: &str) -> Result<ServerResources, String> {
+        let plan = self.launch_plan(server_id).await?;
+        let running = matches!(self.server_status(server_id).await, ServerStatus::Running);
+
+        let record = if running {
+            self.tool_registry
+                .read()
+                .await
+                .get_server_processes()?
+                .into_iter()
+                .find(|record| record.server_id == server_id)
+        } else {
+            None
+        };
+        let usage = match &record {
+            Some(record) => {
+                let spawn_id = record.spawn_id.clone();
+                tokio::task::spawn_blocking(move || spawn_resource_usage(&spawn_id))
+                    .await
+                    .ok()
+                    .flatten()
+            }
+            None => None,
+        }
+        .unwrap_or_default();
+
+        Ok(ServerResources {
+            server_id: server_id.to_string(),
+            priority: plan.priority,
+            cpu_limit: plan.cpu_limit,
+            max_memory_mb: plan.max_memory_mb,
+            applied_by_docker: plan.limits_applied_by_docker(),
+            running,
+            pid: record.and_then(|record| record.pid),
+            process_count: usage.processes,
+            cpu_percent: usage.cpu_percent,
+            memory_bytes: usage.memory_bytes,
+        })
+    }
+
+    /// Terminate the server processes and containers left behind by a previous run
+    ///
+    /// Every recorded process of a server without a running client is an orphan, so
+    /// this can also run while servers do. A process is only killed while its pid, start
+    /// time and command line still match the record, or it carries the spawn id of the
+    /// record. Orphans can't be adopted, their stdio went away with the Dockmaster that
+    /// started them.
+    pub async fn cleanup_orphaned_processes(&self) -> OrphanCleanupReport {
+        let records = match self.tool_registry.read().await.get_server_processes() {
+            Ok(records) => records,
+            Err(e) => {
+                error!("Failed to read recorded server processes: {}", e);
+                return OrphanCleanupReport::default();
+            }
+        };
+
+        let mut report = OrphanCleanupReport::default();
+        for record in records {
+            let server_id = record.server_id.clone();
+            // Keeps the server from starting while its record is cleaned up
+            let (killed, container_removed) = self
+                .restart_gate
+                .exclusive(&server_id, || self.cleanup_orphan(record))
+                .await;
+            report.processes_terminated += killed;
+            if container_removed {
+                report.containers_removed += 1;
+            }
+            if killed > 0 || container_removed {
+                report.servers.push(server_id);
+            }
+        }
+
+        *self.orphan_cleanup_report.write().await = report.clone();
+        report
+    }
+
+    /// Kill the processes and remove the container of a record, unless its server
+    /// runs, returning the number of processes killed and whether a container was
+    /// removed
+    async fn cleanup_orphan(&self, record: SpawnedProcessRecord) -> (usize, bool) {
+        if self
+            .mcp_clients
+            .read()
+            .await
+            .contains_key(&record.server_id)
+        {
+            return (0, false);
+        }
+
+        let lookup = record.clone();
+        let killed = tokio::task::spawn_blocking(move || {
+            kill_spawned_processes(
+                &lookup.spawn_id,
+                lookup.pid,
+                lookup.start_time,
+                lookup.command.as_deref(),
+            )
+        })
+        .await
+        .unwrap_or(0);
+
+        let mut container_removed = false;
+        if let Some(container_name) = &record.container_name {
+            match remove_docker_container(container_name).await {
+                Ok(removed) => container_removed = removed,
+                Err(e) => warn!("Failed to remove container {}: {}", container_name, e),
+            }
+        }
+
+        StartupCapture::remove(&record.spawn_id);
+        if let Err(e) = self
+            .tool_registry
+            .read()
+            .await
+            .delete_server_process(&record.server_id)
+        {
+            warn!(
+                "Failed to delete process record of {}: {}",
+                record.server_id, e
+            );
+        }
+        (killed, container_removed)
+    }
+
     /// Get the current tool visibility state
     pub async fn are_tools_hidden(&self) -> bool {
         let are_tools_hidden = self.are_tools_hidden.read().await;
@@ -355,9 +1217,309 @@ impl MCPState {
         registry.save_setting("tools_hidden", if hidden { "true" } else { "false" })
     }
 
+    /// Spawn id of the running process of a server
+    pub async fn spawn_id_of(&self, server_id: &str) -> Option<String> {
+        self.mcp_clients
+            .read()
+            .await
+            .get(server_id)
+            .map(|mcp_client| mcp_client.spawn_id.clone())
+    }
+
+    /// Check that each running server answers, see [`crate::health`], returning the ids
+    /// of the unhealthy servers restarted
+    ///
+    /// The servers are checked at the same time and no lock is held while they answer.
+    pub async fn check_server_health(&self, settings: &HealthCheckSettings) -> Vec<String> {
+        let mcp_clients: Vec<(String, MCPClient)> = self
+            .mcp_clients
+            .read()
+            .await
+            .iter()
+            .map(|(server_id, mcp_client)| (server_id.clone(), mcp_client.clone()))
+            .collect();
+        let checks = mcp_clients
+            .into_iter()
+            .map(|(server_id, mcp_client)| async move {
+                let answered = tokio::select! {
+                    listed = mcp_client.client.list_tools(None) => listed
+                        .map(|_| ())
+                        .map_err(|e| e.to_string()),
+                    _ = self.clock.sleep(HEALTH_CHECK_TIMEOUT) => Err(format!(
+                        "No answer to tools/list within {} seconds",
+                        HEALTH_CHECK_TIMEOUT.as_secs()
+                    )),
+                };
+                (server_id, mcp_client.spawn_id, answered)
+            });
+        let results = futures::future::join_all(checks).await;
+
+        let mut restarted = Vec::new();
+        for (server_id, spawn_id, answered) in results {
+            // Restarted or stopped while it was checked
+            if self.spawn_id_of(&server_id).await.as_deref() != Some(spawn_id.as_str()) {
+                continue;
+            }
+            let error = match answered {
+                Ok(()) => {
+                    self.server_health.record_success(&server_id, now_millis());
+                    continue;
+                }
+                Err(e) => e,
+            };
+            self.metrics.record_health_check_failure(&server_id);
+            let health = self.server_health.record_failure(
+                &server_id,
+                now_millis(),
+                error.clone(),
+                settings.unhealthy_after,
+            );
+            warn!(
+                "Health check of server {} failed, {} in a row: {}",
+                server_id, health.consecutive_failures, error
+            );
+            if health.healthy || !settings.restart_unhealthy {
+                continue;
+            }
+            if !self.reconnect_budget.try_take(&server_id, self.clock.now()) {
+                warn!(
+                    "Server {} is unhealthy but was restarted {} times in {} minutes already",
+                    server_id,
+                    RECONNECT_LIMIT,
+                    RECONNECT_WINDOW.as_secs() / 60
+                );
+                continue;
+            }
+            self.record_lifecycle(
+                LifecycleEvent::now(&server_id, LifecycleKind::RestartedBySupervisor)
+                    .with_detail(format!("Unhealthy: {}", error)),
+            )
+            .await;
+            match self.restart_server(&server_id).await {
+                Ok(()) => restarted.push(server_id),
+                Err(e) => error!("Failed to restart unhealthy server {}: {}", server_id, e),
+            }
+        }
+        restarted
+    }
+
+    /// Restart a server whose connection died, within its [`ReconnectBudget`]
+    ///
+    /// `spawn_id` is the process the failed request was sent to, nothing is done when it
+    /// was replaced since. `retry` tells whether the request is sent again afterwards.
+    pub async fn reconnect_server(
+        &self,
+        server_id: &str,
+        spawn_id: &str,
+        cause: &str,
+        retry: bool,
+    ) -> Result<(), String> {
+        {
+            let mut mcp_clients = self.mcp_clients.write().await;
+            match mcp_clients.get_mut(server_id) {
+                Some(mcp_client) if mcp_client.spawn_id == spawn_id => {
+                    mcp_client.server_status =
+                        ServerStatus::Error(format!("Connection lost: {}", cause));
+                }
+                // Restarted or stopped by someone else in the meantime
+                _ => return Ok(()),
+            }
+        }
+        if !self.reconnect_budget.try_take(server_id, self.clock.now()) {
+            return Err(format!(
+                "Server {} lost its connection {} times in {} minutes, restart it manually",
+                server_id,
+                RECONNECT_LIMIT,
+                RECONNECT_WINDOW.as_secs() / 60
+            ));
+        }
+
+        warn!(
+            "Connection to server {} is dead, restarting it: {}",
+            server_id, cause
+        );
+        self.record_lifecycle(
+            LifecycleEvent::now(server_id, LifecycleKind::RestartedBySupervisor).with_detail(cause),
+        )
+        .await;
+        self.restart_server(server_id).await?;
+        self.event_bus
+            .publish(DockmasterEvent::ServerReconnected(ServerReconnect {
+                server_id: server_id.to_string(),
+                cause: cause.to_string(),
+                retried: retry,
+            }));
+        Ok(())
+    }
+
+    /// List the tools of a running server and save them, restarting the server and
+    /// listing them again if its connection died
     pub async fn discover_server_tools(
         &self,
         server_id: &str,
+    ) -> Result<Vec<ServerToolInfo>, String> {
+        let spawn_id = self.spawn_id_of(server_id).await;
+        let error = match self.discover_server_tools_once(server_id).await {
+            Err(e) => e,
+            tools => return tools,
+        };
+        let (Some(spawn_id), Some(dead)) = (spawn_id, DeadConnection::of(&error)) else {
+            return Err(error);
+        };
+        self.reconnect_server(server_id, &spawn_id, &error, dead.may_retry(true))
+            .await
+            .map_err(|e| format!("{}. {}", error, e))?;
+        self.discover_server_tools_once(server_id).await
+    }
+
+    /// Handle a notification `server_id` sent on its own, returning what it was
+    ///
+    /// Called for each notification of a running server as it comes. Log messages go
+    /// to the log, progress goes to the sessions waiting for it and a changed tool list
+    /// is discovered again in the background and published.
+    pub async fn dispatch_server_notification(
+        &self,
+        server_id: &str,
+        message: &Value,
+    ) -> Option<ServerNotification> {
+        let notification = ServerNotification::parse(message)?;
+        match &notification {
+            ServerNotification::Message {
+                level,
+                logger,
+                data,
+            } => {
+                let source = match logger {
+                    Some(logger) => format!("{}/{}", server_id, logger),
+                    None => server_id.to_string(),
+                };
+                let level = match level.as_str() {
+                    "debug" => log::Level::Debug,
+                    "info" | "notice" => log::Level::Info,
+                    "warning" => log::Level::Warn,
+                    _ => log::Level::Error,
+                };
+                log::log!(level, "[{}] {}", source, data);
+            }
+            ServerNotification::Progress {
+                progress_token,
+                params,
+            } => {
+                let topic = progress_token.as_str().unwrap_or_default();
+                let Some((session_id, token)) = parse_progress_topic(topic) else {
+                    debug!("Progress of server {} for no call of ours", server_id);
+                    return Some(notification);
+                };
+                let subscribers = self.sessions.subscribers(topic).await;
+                if !subscribers
+                    .iter()
+                    .any(|subscriber| subscriber == session_id)
+                {
+                    debug!("No session waits for {} of server {}", topic, server_id);
+                    return Some(notification);
+                }
+                // With the token the client chose
+                let mut params = params.clone();
+                params["progressToken"] = token;
+                let forwarded = json!({
+                    "jsonrpc": "2.0",
+                    "method": "notifications/progress",
+                    "params": params
+                });
+                if let Err(e) = self.sessions.send(session_id, &forwarded.to_string()).await {
+                    warn!(
+                        "Failed to forward progress to session {}: {}",
+                        session_id, e
+                    );
+                }
+            }
+            ServerNotification::ToolsListChanged => self.rediscover_changed_tools(server_id),
+            ServerNotification::Other(method) => {
+                debug!("Ignoring notification {} from server {}", method, server_id);
+            }
+        }
+        Some(notification)
+    }
+
+    /// Refresh the tools served to MCP clients once and tell subscribers
+    pub async fn publish_tools_update(&self, server_ids: Vec<String>) {
+        if let Some(tools_service) = MCPToolsService::get_instance().await {
+            if let Err(e) = tools_service.update_cache().await {
+                error!("Failed to update tools cache: {}", e);
+            }
+        }
+        self.event_bus
+            .publish(DockmasterEvent::ToolsUpdated(ToolsUpdate { server_ids }));
+    }
+
+    /// Discover the tools of a server that said they changed, in the background
+    ///
+    /// Changes told while a discovery runs are collapsed into a single discovery once it
+    /// is done, the notifications of the server keep being handled meanwhile.
+    fn rediscover_changed_tools(&self, server_id: &str) {
+        {
+            let mut changed_tools = self.changed_tools.lock().unwrap();
+            if let Some(changed_again) = changed_tools.get_mut(server_id) {
+                *changed_again = true;
+                return;
+            }
+            changed_tools.insert(server_id.to_string(), false);
+        }
+        let mcp_state = self.clone();
+        let server_id = server_id.to_string();
+        tokio::spawn(async move {
+            loop {
+                info!(
+                    "Tools of server {} changed, discovering them again",
+                    server_id
+                );
+                match mcp_state.discover_server_tools(&server_id).await {
+                    Ok(_) => {
+                        mcp_state
+                            .publish_tools_update(vec![server_id.clone()])
+                            .await
+                    }
+                    Err(e) => warn!(
+                        "Failed to discover the changed tools of {}: {}",
+                        server_id, e
+                    ),
+                }
+                let changed_again = {
+                    let mut changed_tools = mcp_state.changed_tools.lock().unwrap();
+                    match changed_tools.get_mut(&server_id) {
+                        Some(changed_again) if *changed_again => {
+                            *changed_again = false;
+                            true
+                        }
+                        _ => {
+                            changed_tools.remove(&server_id);
+                            false
+                        }
+                    }
+                };
+                if !changed_again {
+                    break;
+                }
+            }
+        });
+    }
+
+    /// Handle the notifications of a server as they come, until its stdout closes
+    fn follow_server_notifications(&self, server_id: &str, client: McpClientType) {
+        let mcp_state = self.clone();
+        let server_id = server_id.to_string();
+        tokio::spawn(async move {
+            while let Some(message) = client.next_notification().await {
+                mcp_state
+                    .dispatch_server_notification(&server_id, &message)
+                    .await;
+            }
+        });
+    }
+
+    async fn discover_server_tools_once(
+        &self,
+        server_id: &str,
     ) -> Result<Vec<ServerToolInfo>, String> {
         info!(
             "[discover_tools] Starting discovery for server: {}",
@@ -372,51 +1534,101 @@ impl MCPState {
             );
 
             match mcp_client.server_status {
+                ServerStatus::Running if !mcp_client.lists_tools => {
+                    info!(
+                        "Server {} declared no tool support, not listing its tools",
+                        server_id
+                    );
+                    let mut server_tools = self.server_tools.write().await;
+                    server_tools.insert(server_id.to_string(), Vec::new());
+                    Ok(Vec::new())
+                }
                 ServerStatus::Running => {
                     info!("Server status is Running, about to call list_tools");
 
-                    let list_tools = match mcp_client.client.list_tools(None).await {
-                        Ok(result) => {
-                            info!("mcp_client: list_tools call succeeded");
-                            result
+                    // Follow the cursors of servers listing their tools over several pages
+                    let mut tools = Vec::new();
+                    let mut cursor = None;
+                    for page in 1..=MAX_TOOL_PAGES {
+                        let list_tools = match mcp_client.client.list_tools(cursor.take()).await {
+                            Ok(result) => {
+                                info!("mcp_client: list_tools call succeeded, page {}", page);
+                                result
+                            }
+                            Err(e) => {
+                                error!("mcp_client: list_tools call failed: {}", e);
+                                return Err(e.to_string());
+                            }
+                        };
+                        if let Some(listed) = list_tools.get("tools").and_then(Value::as_array) {
+                            tools.extend(listed.iter().cloned());
                         }
-                        Err(e) => {
-                            error!("mcp_client: list_tools call failed: {}", e);
-                            return Err(e.to_string());
+                        let next_cursor = list_tools.get("nextCursor").and_then(Value::as_str);
+                        match next_cursor {
+                            Some(next) if page < MAX_TOOL_PAGES => cursor = Some(next.to_string()),
+                            Some(_) => warn!(
+                                "Server {} lists more than {} pages of tools, the rest is left out",
+                                server_id, MAX_TOOL_PAGES
+                            ),
+                            None => break,
                         }
-                    };
+                    }
 
-                    let tools = list_tools.tools;
                     info!(
                         "Successfully discovered {} tools for {}",
                         tools.len(),
                         server_id
                     );
 
-                    // Save the tools to the database
                     let registry = self.tool_registry.read().await;
                     let mut tools_info = Vec::new();
 
-                    for tool in &tools {
-                        let tool_info = ServerToolInfo {
-                            id: tool.name.clone(),
-                            name: tool.name.clone(),
-                            description: tool.description.clone(),
-                            input_schema: if tool.input_schema.is_object() {
-                                serde_json::from_value(tool.input_schema.clone()).ok()
-                            } else {
-                                None
-                            },
-                            proxy_id: Some(server_id.to_string()),
-                            server_id: server_id.to_string(),
-                            is_active: true,
+                    for tool in tools {
+                        let name = tool["name"].as_str().unwrap_or_default().to_string();
+                        let tool_info = match ServerToolInfo::from_spec_value(server_id, tool) {
+                            Ok(tool_info) => tool_info,
+                            Err(e) => {
+                                warn!("Skipping tool {} of {}: {}", name, server_id, e);
+                                continue;
+                            }
                         };
-                        info!("Saving tool info to database: {:?}", tool_info);
-                        if let Err(e) = registry.save_server_tool(&tool_info) {
+                        tools_info.push(tool_info);
+                    }
+
+                    let filter = registry
+                        .get_server(server_id)
+                        .ok()
+                        .and_then(|server| server.configuration?.tool_filter);
+                    let (tools_info, report) = apply_tool_limits(filter.as_ref(), tools_info);
+                    if report.truncated > 0 {
+                        warn!(
+                            "Server {} listed {} tools, only the first {} are kept",
+                            server_id,
+                            report.listed - report.filtered,
+                            report.max_tools
+                        );
+                    }
+
+                    // Save the tools to the database, without the ones left out anymore
+                    for tool_info in &tools_info {
+                        debug!("Saving tool info to database: {:?}", tool_info);
+                        if let Err(e) = registry.save_server_tool(tool_info) {
                             error!("Failed to save server tool to database: {}", e);
                         }
-                        tools_info.push(tool_info);
                     }
+                    let saved = registry.get_server_tools(server_id).unwrap_or_default();
+                    for tool_info in saved
+                        .iter()
+                        .filter(|saved| report.left_out_tool(&saved.name).is_some())
+                    {
+                        if let Err(e) = registry.delete_server_tool(&tool_info.id, server_id) {
+                            warn!("Failed to delete left out tool {}: {}", tool_info.name, e);
+                        }
+                    }
+                    self.tool_limits
+                        .write()
+                        .await
+                        .insert(server_id.to_string(), report);
 
                     // Save the tools to the server_tools map
                     let mut server_tools = self.server_tools.write().await;
@@ -432,3 +1644,36 @@ impl MCPState {
         }
     }
 }
+
+/// Container name used for the docker server `server_id`
+pub fn docker_container_name(server_id: &str) -> String {
+    let sanitized: String = server_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '-' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    format!("mcp-dockmaster-{}", sanitized)
+}
+
+/// Add `--name <name>` to a `docker run` argument list that doesn't name its container
+///
+/// Returns whether the container ends up with `name`.
+pub fn inject_container_name(args: &mut Vec<String>, name: &str) -> bool {
+    let Some(run_index) = args.iter().position(|arg| arg == "run") else {
+        return false;
+    };
+    if args
+        .iter()
+        .any(|arg| arg == "--name" || arg.starts_with("--name="))
+    {
+        return false;
+    }
+    args.insert(run_index + 1, "--name".to_string());
+    args.insert(run_index + 2, name.to_string());
+    true
+}
@@ -1,3 +1,14 @@
 // fix this once we finish the refactor having a single entry point (McpCore)
 #![allow(clippy::module_inception)]
+pub mod launch_plan;
 pub mod mcp_state;
+pub mod process_env;
+pub mod process_launcher;
+pub mod reconnect;
+pub mod restart_gate;
+pub mod sandbox;
+pub mod server_notifications;
+pub mod shutdown;
+pub mod start_failures;
+pub mod startup_capture;
+pub mod tool_stats;
@@ -0,0 +1,205 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::diagnostics::REDACTED;
+
+/// Variables of the Dockmaster environment every server gets: what programs need to
+/// find binaries, the user's home and temp directories, the locale and the docker daemon
+#[cfg(not(windows))]
+const BASELINE_ENV_VARS: &[&str] = &[
+    "PATH",
+    "HOME",
+    "USER",
+    "LOGNAME",
+    "SHELL",
+    "TMPDIR",
+    "TMP",
+    "TEMP",
+    "TZ",
+    "TERM",
+    "LANG",
+    "LANGUAGE",
+    "XDG_RUNTIME_DIR",
+    "XDG_CONFIG_HOME",
+    "XDG_CACHE_HOME",
+    "XDG_DATA_HOME",
+    "SSL_CERT_FILE",
+    "SSL_CERT_DIR",
+    "DOCKER_HOST",
+    "DOCKER_CONTEXT",
+    "DOCKER_CONFIG",
+    "DOCKER_CERT_PATH",
+    "DOCKER_TLS_VERIFY",
+    "__CF_USER_TEXT_ENCODING",
+];
+
+#[cfg(windows)]
+const BASELINE_ENV_VARS: &[&str] = &[
+    "PATH",
+    "PATHEXT",
+    "SYSTEMROOT",
+    "SYSTEMDRIVE",
+    "WINDIR",
+    "COMSPEC",
+    "USERNAME",
+    "USERPROFILE",
+    "HOMEDRIVE",
+    "HOMEPATH",
+    "APPDATA",
+    "LOCALAPPDATA",
+    "PROGRAMDATA",
+    "PROGRAMFILES",
+    "PROGRAMFILES(X86)",
+    "PROGRAMW6432",
+    "COMMONPROGRAMFILES",
+    "COMMONPROGRAMFILES(X86)",
+    "TEMP",
+    "TMP",
+    "OS",
+    "NUMBER_OF_PROCESSORS",
+    "PROCESSOR_ARCHITECTURE",
+    "DOCKER_HOST",
+    "DOCKER_CONTEXT",
+    "DOCKER_CONFIG",
+    "DOCKER_CERT_PATH",
+    "DOCKER_TLS_VERIFY",
+];
+
+/// Locale variables (`LC_ALL`, `LC_CTYPE`, ...) are part of the baseline
+const LOCALE_PREFIX: &str = "LC_";
+
+/// Env var names are case-insensitive on Windows
+fn normalize(name: &str) -> String {
+    if cfg!(windows) {
+        name.to_uppercase()
+    } else {
+        name.to_string()
+    }
+}
+
+/// Whether a variable of the Dockmaster environment is passed to every server
+pub fn is_baseline_env_var(name: &str) -> bool {
+    let name = normalize(name);
+    name.starts_with(LOCALE_PREFIX) || BASELINE_ENV_VARS.contains(&name.as_str())
+}
+
+/// The Dockmaster environment, variables that aren't valid UTF-8 are skipped
+pub fn parent_env() -> Vec<(String, String)> {
+    std::env::vars_os()
+        .filter_map(|(name, value)| Some((name.into_string().ok()?, value.into_string().ok()?)))
+        .collect()
+}
+
+/// How a server process gets its environment
+#[derive(Clone, Debug)]
+pub struct ProcessEnv {
+    /// Inherit the whole Dockmaster environment, the behavior before isolation
+    pub inherit: bool,
+    /// Dockmaster variables passed to the server besides the baseline
+    pub passthrough: Vec<String>,
+}
+
+impl ProcessEnv {
+    fn keeps(&self, name: &str, configured: &HashSet<String>) -> bool {
+        let normalized = normalize(name);
+        self.inherit
+            || is_baseline_env_var(name)
+            || configured.contains(&normalized)
+            || self
+                .passthrough
+                .iter()
+                .any(|allowed| normalize(allowed) == normalized)
+    }
+
+    /// Variables of `parent` to remove from the server environment
+    ///
+    /// Configured variables are set on the process anyway, so they are never removed.
+    pub fn removed_vars<I>(&self, parent: I, configured: &HashMap<String, String>) -> Vec<String>
+    where
+        I: IntoIterator<Item = (String, String)>,
+    {
+        let configured: HashSet<String> = configured.keys().map(|key| normalize(key)).collect();
+        let mut removed: Vec<String> = parent
+            .into_iter()
+            .map(|(name, _)| name)
+            .filter(|name| !self.keeps(name, &configured))
+            .collect();
+        removed.sort();
+        removed
+    }
+
+    /// Environment the server sees, with every value that may be a secret redacted
+    ///
+    /// Configured and passed-through values are redacted, as well as everything
+    /// inherited outside of the baseline when the whole environment is inherited.
+    pub fn preview<I>(
+        &self,
+        parent: I,
+        configured: &HashMap<String, String>,
+    ) -> BTreeMap<String, String>
+    where
+        I: IntoIterator<Item = (String, String)>,
+    {
+        let configured_names: HashSet<String> =
+            configured.keys().map(|key| normalize(key)).collect();
+        let mut preview = BTreeMap::new();
+        for (name, value) in parent {
+            if !self.keeps(&name, &configured_names) {
+                continue;
+            }
+            let value = if is_baseline_env_var(&name) {
+                value
+            } else {
+                REDACTED.to_string()
+            };
+            preview.insert(name, value);
+        }
+        for name in configured.keys() {
+            preview.insert(name.clone(), REDACTED.to_string());
+        }
+        preview
+    }
+}
+
+/// Wrap a command so that it starts without the variables in `removed`
+///
+/// The stdio transport spawns servers with the environment of Dockmaster, so the
+/// variables are removed by the command itself: through `env -u` on Unix and a
+/// PowerShell prelude on Windows. Only names are passed on the command line, never
+/// values.
+pub fn isolate_command(
+    program: String,
+    args: Vec<String>,
+    removed: &[String],
+) -> (String, Vec<String>) {
+    if removed.is_empty() {
+        return (program, args);
+    }
+
+    if cfg!(windows) {
+        // The shell wrapper runs `powershell -c <command>`
+        let targets = removed
+            .iter()
+            .map(|name| format!("'Env:{}'", name.replace('\'', "''")))
+            .collect::<Vec<_>>()
+            .join(",");
+        let prelude = format!(
+            "Remove-Item -LiteralPath {} -ErrorAction SilentlyContinue; ",
+            targets
+        );
+        let mut args = args;
+        match args.last_mut() {
+            Some(command) => command.insert_str(0, &prelude),
+            None => args.push(prelude),
+        }
+        (program, args)
+    } else {
+        let mut isolated = Vec::with_capacity(removed.len() * 2 + args.len() + 1);
+        for name in removed {
+            isolated.push("-u".to_string());
+            isolated.push(name.clone());
+        }
+        isolated.push(program);
+        isolated.extend(args);
+        ("/usr/bin/env".to_string(), isolated)
+    }
+}
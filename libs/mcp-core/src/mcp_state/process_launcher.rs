@@ -0,0 +1,217 @@
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use log::warn;
+use serde_json::json;
+
+use super::launch_plan::LaunchPlan;
+use super::mcp_state::McpClientType;
+use super::process_env::isolate_command;
+use super::startup_capture::{StartupCapture, EXIT_CODE_GRACE};
+use crate::models::error::MCPError;
+use crate::models::types::{ServerId, ServerInitInfo};
+use crate::spawned_process::{SpawnedProcess, SERVER_RESPONSE_TIMEOUT};
+use crate::utils::command::{run_in_directory, CommandWrappedInShellBuilder};
+use crate::utils::process::{
+    apply_process_limits, find_process_by_spawn_id, remove_docker_container, ProcessInfo,
+    SPAWN_ID_ENV,
+};
+
+/// Setting holding the seconds a server has to start and answer the initialize
+/// handshake
+pub const STARTUP_TIMEOUT_SETTING: &str = "startup_timeout_secs";
+
+/// Seconds a server has to start and answer the initialize handshake, unless set with
+/// [`STARTUP_TIMEOUT_SETTING`]
+pub const DEFAULT_STARTUP_TIMEOUT_SECS: u64 = 30;
+
+/// Time a server started with `lenient_initialize` has to answer the initialize
+/// request before it is used without an answer
+pub const LENIENT_INITIALIZE_WAIT: Duration = Duration::from_secs(10);
+
+/// A started and initialized server
+pub struct LaunchedServer {
+    pub client: McpClientType,
+    /// Process found for the spawn id, if it could be found
+    pub process: Option<ProcessInfo>,
+    /// What the server answered to the initialize request
+    pub server_info: Option<ServerInitInfo>,
+}
+
+/// Starts server processes
+///
+/// [`StdioProcessLauncher`] spawns real child processes, tests inject their own
+/// implementation to simulate crashes and hangs.
+#[async_trait]
+pub trait ProcessLauncher: Send + Sync {
+    /// Start the server described by `plan`, tag its process tree with `spawn_id` and
+    /// run the MCP initialize handshake
+    async fn launch(&self, plan: &LaunchPlan, spawn_id: &str) -> Result<LaunchedServer, String>;
+}
+
+/// Launcher spawning servers as child processes talking MCP over stdio
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StdioProcessLauncher;
+
+#[async_trait]
+impl ProcessLauncher for StdioProcessLauncher {
+    async fn launch(&self, plan: &LaunchPlan, spawn_id: &str) -> Result<LaunchedServer, String> {
+        plan.check_cwd()?;
+
+        // Remove a container left over under the same name so `docker run` can reuse it
+        if let Some(name) = &plan.container_name {
+            if let Err(e) = remove_docker_container(name).await {
+                warn!("Failed to remove stale container {}: {}", name, e);
+            }
+        }
+
+        // Tag the process tree so it can be recognized if we crash
+        let mut envs = plan.env.clone();
+        envs.insert(SPAWN_ID_ENV.to_string(), spawn_id.to_string());
+
+        let (adapted_program, mut adapted_args, adapted_envs) =
+            CommandWrappedInShellBuilder::wrap_in_shell_as_values(
+                &plan.command,
+                Some(plan.args.iter().map(|s| s.as_str())),
+                Some(envs),
+            );
+        // The process is started without a directory of the process, the shell changes to it
+        if let (Some(cwd), Some(command)) = (&plan.cwd, adapted_args.last_mut()) {
+            *command = run_in_directory(&adapted_program, command, cwd);
+        }
+        // Capture how the server exits, to explain a server that won't start
+        let capture = match StartupCapture::create(spawn_id) {
+            Ok(capture) => {
+                if let Some(command) = adapted_args.last_mut() {
+                    *command = capture.wrap_command(&adapted_program, command);
+                }
+                Some(capture)
+            }
+            Err(e) => {
+                warn!(
+                    "Failed to capture the output of {}, startup failures won't be diagnosed: {}",
+                    plan.server_id, e
+                );
+                None
+            }
+        };
+        // Keep the Dockmaster environment, tokens included, out of the server
+        let (adapted_program, adapted_args) =
+            isolate_command(adapted_program, adapted_args, &plan.removed_env);
+
+        let client = match SpawnedProcess::spawn(
+            &ServerId::new(plan.server_id.clone()),
+            adapted_program,
+            adapted_args,
+            adapted_envs,
+            SERVER_RESPONSE_TIMEOUT,
+        )
+        .await
+        {
+            Ok(client) => client,
+            Err(e) => return Err(format!("Failed to start transport: {}", e)),
+        };
+
+        let lookup_id = spawn_id.to_string();
+        let process = tokio::task::spawn_blocking(move || find_process_by_spawn_id(&lookup_id))
+            .await
+            .ok()
+            .flatten();
+        if process.is_none() {
+            warn!("Could not find the process spawned for {}", plan.server_id);
+        }
+
+        if !plan.limits_applied_by_docker() {
+            let (priority, cpu_limit) = (plan.priority, plan.cpu_limit);
+            let limits_id = spawn_id.to_string();
+            let warnings = tokio::task::spawn_blocking(move || {
+                apply_process_limits(&limits_id, priority, cpu_limit)
+            })
+            .await
+            .unwrap_or_default();
+            for warning in warnings {
+                warn!("Server {}: {}", plan.server_id, warning);
+            }
+        }
+
+        let initialized = match &capture {
+            Some(capture) => tokio::select! {
+                initialized = initialize(&client, plan) => initialized,
+                exit_code = capture.wait_for_exit() => {
+                    Err(capture.failure(exit_code).to_string())
+                }
+            },
+            None => initialize(&client, plan).await,
+        };
+        let initialized = match initialized {
+            Ok(initialized) => initialized,
+            Err(e) => {
+                let mut error = format!("Failed to initialize client: {}", e);
+                // The handshake fails as soon as the pipes close, the exit code follows
+                if let Some(capture) = &capture {
+                    if let Ok(exit_code) =
+                        tokio::time::timeout(EXIT_CODE_GRACE, capture.wait_for_exit()).await
+                    {
+                        error = capture.failure(exit_code).to_string();
+                    }
+                }
+                if plan.security_mode.is_restricted() {
+                    // The server may have been denied something it needs at startup
+                    error = format!(
+                        "{} (server runs in {})",
+                        error,
+                        plan.security_mode.describe()
+                    );
+                }
+                return Err(error);
+            }
+        };
+        let initialized_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        let server_info = initialized.and_then(|initialized| {
+            ServerInitInfo::from_initialize_result(&initialized, initialized_at)
+        });
+
+        Ok(LaunchedServer {
+            client: Arc::new(client),
+            process,
+            server_info,
+        })
+    }
+}
+
+/// Run the initialize handshake, returning what the server answered
+///
+/// A server started with `lenient_initialize` that answers with an error, without a
+/// result or not at all within [`LENIENT_INITIALIZE_WAIT`] is still told it is
+/// initialized, and used without an answer.
+async fn initialize(
+    client: &SpawnedProcess,
+    plan: &LaunchPlan,
+) -> Result<Option<serde_json::Value>, String> {
+    if !plan.lenient_initialize {
+        return client
+            .initialize()
+            .await
+            .map(Some)
+            .map_err(|e| e.to_string());
+    }
+    let error = match tokio::time::timeout(LENIENT_INITIALIZE_WAIT, client.initialize()).await {
+        Ok(Ok(initialized)) => return Ok(Some(initialized)),
+        Ok(Err(e @ (MCPError::ToolExecutionError(_) | MCPError::NoResultField))) => e.to_string(),
+        Ok(Err(e)) => return Err(e.to_string()),
+        Err(_) => "no answer".to_string(),
+    };
+    warn!(
+        "Server {} did not answer initialize properly, using it anyway: {}",
+        plan.server_id, error
+    );
+    client
+        .notify("notifications/initialized", json!({}))
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(None)
+}
@@ -0,0 +1,87 @@
+//! Reconnecting servers whose process lives on with its stdin closed.
+//!
+//! Some servers close their stdin after an internal error and keep running, so every
+//! request written to them fails with a broken pipe. Such failures are told apart by
+//! [`DeadConnection::of`], the server is then restarted once, within the
+//! [`ReconnectBudget`], and the request is sent again to the new process when
+//! [`DeadConnection::may_retry`] allows it.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Automatic reconnects allowed per server within [`RECONNECT_WINDOW`]
+pub const RECONNECT_LIMIT: usize = 3;
+
+pub const RECONNECT_WINDOW: Duration = Duration::from_secs(10 * 60);
+
+/// How a request found the connection to its server dead
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeadConnection {
+    /// Writing the request failed, the server never got it
+    WriteFailed,
+    /// The connection closed, the server may have got the request
+    Closed,
+}
+
+impl DeadConnection {
+    /// How the connection died, if the error of a request says it did
+    pub fn of(error: &str) -> Option<Self> {
+        let error = error.to_lowercase();
+        let matches = |patterns: &[&str]| patterns.iter().any(|p| error.contains(p));
+        if matches(&[
+            "broken pipe",
+            "os error 32",
+            "failed to write to process stdin",
+        ]) {
+            Some(DeadConnection::WriteFailed)
+        } else if matches(&[
+            "channel closed",
+            "connection closed",
+            "closed connection",
+            "transport closed",
+        ]) {
+            Some(DeadConnection::Closed)
+        } else {
+            None
+        }
+    }
+
+    /// Whether a request that failed this way may be sent again to a new process
+    ///
+    /// Requests that change nothing, like listing tools, are always sent again. Tool
+    /// calls only are when the server never got them.
+    pub fn may_retry(&self, idempotent: bool) -> bool {
+        idempotent || *self == DeadConnection::WriteFailed
+    }
+}
+
+/// Counts the automatic reconnects of each server, so that a server whose connection
+/// keeps dying is left for the user to look at
+#[derive(Clone, Debug, Default)]
+pub struct ReconnectBudget {
+    reconnects: Arc<Mutex<HashMap<String, VecDeque<Instant>>>>,
+}
+
+impl ReconnectBudget {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count a reconnect of `server_id`, or return false when its budget is spent
+    pub fn try_take(&self, server_id: &str, now: Instant) -> bool {
+        let mut reconnects = self.reconnects.lock().unwrap();
+        let recent = reconnects.entry(server_id.to_string()).or_default();
+        while recent
+            .front()
+            .is_some_and(|at| now.saturating_duration_since(*at) >= RECONNECT_WINDOW)
+        {
+            recent.pop_front();
+        }
+        if recent.len() >= RECONNECT_LIMIT {
+            return false;
+        }
+        recent.push_back(now);
+        true
+    }
+}
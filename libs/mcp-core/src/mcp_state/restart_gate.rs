@@ -0,0 +1,94 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use log::info;
+use tokio::sync::Mutex as TokioMutex;
+
+/// Serializes and coalesces the restarts of each server
+///
+/// Restarts of the same server never run concurrently. A request that arrives while a
+/// restart is in progress waits for it, and all the requests that piled up meanwhile
+/// are served by a single follow-up restart, so a burst of config saves costs at most
+/// two restarts.
+#[derive(Clone, Default)]
+pub struct RestartGate {
+    servers: Arc<Mutex<HashMap<String, Arc<ServerGate>>>>,
+}
+
+#[derive(Default)]
+struct ServerGate {
+    /// Number of restarts requested so far
+    requested: AtomicU64,
+    state: TokioMutex<GateState>,
+}
+
+#[derive(Default)]
+struct GateState {
+    /// Requests up to this number are covered by a restart that already started
+    covered: u64,
+    last_result: Option<Result<(), String>>,
+}
+
+impl RestartGate {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn server_gate(&self, server_id: &str) -> Arc<ServerGate> {
+        self.servers
+            .lock()
+            .unwrap()
+            .entry(server_id.to_string())
+            .or_default()
+            .clone()
+    }
+
+    /// Run `restart` for `server_id` unless a restart that started after this request
+    /// already completed, in which case its result is returned instead
+    ///
+    /// `restart` runs while holding the per-server lock, so it can kill the previous
+    /// process and spawn the new one without another restart interleaving.
+    pub async fn run<F, Fut>(&self, server_id: &str, restart: F) -> Result<(), String>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<(), String>>,
+    {
+        let gate = self.server_gate(server_id);
+        let ticket = gate.requested.fetch_add(1, Ordering::SeqCst) + 1;
+
+        let mut state = gate.state.lock().await;
+        if state.covered >= ticket {
+            if let Some(result) = &state.last_result {
+                info!(
+                    "Restart of server {} coalesced with a concurrent restart",
+                    server_id
+                );
+                return result.clone();
+            }
+        }
+
+        // This restart covers every request made until now
+        state.covered = gate.requested.load(Ordering::SeqCst);
+        let result = restart().await;
+        state.last_result = Some(result.clone());
+        result
+    }
+
+    /// Run `action` while holding the lock of `server_id`
+    ///
+    /// Restarts waiting on the lock are not coalesced with one that finished before
+    /// `action`, since `action` may have changed what is running (e.g. stopped it).
+    pub async fn exclusive<F, Fut, T>(&self, server_id: &str, action: F) -> T
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = T>,
+    {
+        let gate = self.server_gate(server_id);
+        let mut state = gate.state.lock().await;
+        let result = action().await;
+        state.last_result = None;
+        result
+    }
+}
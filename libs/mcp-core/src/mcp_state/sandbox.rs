@@ -0,0 +1,214 @@
+//! Restricted launch mode for servers that are not trusted.
+//!
+//! A server with `sandbox` options in its configuration is started inside a sandbox:
+//! `sandbox-exec` on macOS and bubblewrap on Linux. Both keep the filesystem readable
+//! but only let the server write to its declared paths and to temporary directories,
+//! and cut network access unless it is declared. Docker servers are already isolated
+//! from the host filesystem and only get `--network none`.
+//!
+//! A restricted server is never started unrestricted: when no sandbox is available the
+//! launch fails.
+
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::models::types::SandboxOptions;
+
+/// Program used to enforce the restrictions
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SandboxMechanism {
+    SandboxExec,
+    Bubblewrap,
+    Docker,
+}
+
+/// How a server is isolated from the host
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "mode", rename_all = "lowercase")]
+pub enum SecurityMode {
+    Unrestricted,
+    Restricted {
+        mechanism: SandboxMechanism,
+        /// Paths the server may write to
+        allowed_paths: Vec<String>,
+        network: bool,
+    },
+}
+
+impl SecurityMode {
+    /// Resolve the security mode of a server and check that it can be enforced here
+    pub fn resolve(tools_type: &str, sandbox: Option<&SandboxOptions>) -> Result<Self, String> {
+        let Some(options) = sandbox else {
+            return Ok(SecurityMode::Unrestricted);
+        };
+        validate_allowed_paths(&options.allowed_paths)?;
+
+        let mechanism = if tools_type == "docker" {
+            SandboxMechanism::Docker
+        } else {
+            detect_mechanism().ok_or_else(|| {
+                if cfg!(target_os = "linux") {
+                    "Restricted mode requires bubblewrap (bwrap) to be installed".to_string()
+                } else {
+                    "Restricted mode is not supported on this platform".to_string()
+                }
+            })?
+        };
+
+        Ok(SecurityMode::Restricted {
+            mechanism,
+            allowed_paths: options.allowed_paths.clone(),
+            network: options.network,
+        })
+    }
+
+    pub fn is_restricted(&self) -> bool {
+        matches!(self, SecurityMode::Restricted { .. })
+    }
+
+    /// Short description used in error messages
+    pub fn describe(&self) -> String {
+        match self {
+            SecurityMode::Unrestricted => "unrestricted".to_string(),
+            SecurityMode::Restricted {
+                allowed_paths,
+                network,
+                ..
+            } => format!(
+                "restricted mode, writable paths: [{}], network {}",
+                allowed_paths.join(", "),
+                if *network { "allowed" } else { "denied" }
+            ),
+        }
+    }
+}
+
+/// Sandbox program available on this machine for local processes
+pub fn detect_mechanism() -> Option<SandboxMechanism> {
+    if cfg!(target_os = "macos") && Path::new("/usr/bin/sandbox-exec").exists() {
+        Some(SandboxMechanism::SandboxExec)
+    } else if cfg!(target_os = "linux") && find_in_path("bwrap") {
+        Some(SandboxMechanism::Bubblewrap)
+    } else {
+        None
+    }
+}
+
+fn find_in_path(program: &str) -> bool {
+    std::env::var_os("PATH")
+        .map(|paths| std::env::split_paths(&paths).any(|dir| dir.join(program).is_file()))
+        .unwrap_or(false)
+}
+
+fn validate_allowed_paths(paths: &[String]) -> Result<(), String> {
+    for path in paths {
+        if !Path::new(path).is_absolute() {
+            return Err(format!("Sandbox path '{}' must be absolute", path));
+        }
+        if !Path::new(path).exists() {
+            return Err(format!("Sandbox path '{}' does not exist", path));
+        }
+    }
+    Ok(())
+}
+
+/// Wrap a command so that it runs under the restrictions of `mode`
+///
+/// Arguments added here are quoted, since the command line is later run by a shell.
+/// Docker servers are restricted through their own flags, see
+/// [`inject_docker_network_none`].
+pub fn wrap_command(mode: &SecurityMode, command: &str, args: &[String]) -> (String, Vec<String>) {
+    let SecurityMode::Restricted {
+        mechanism,
+        allowed_paths,
+        network,
+    } = mode
+    else {
+        return (command.to_string(), args.to_vec());
+    };
+
+    let mut wrapped = match mechanism {
+        SandboxMechanism::Docker => return (command.to_string(), args.to_vec()),
+        SandboxMechanism::SandboxExec => vec![
+            "-p".to_string(),
+            shell_quote(&sandbox_exec_profile(allowed_paths, *network)),
+        ],
+        SandboxMechanism::Bubblewrap => bubblewrap_args(allowed_paths, *network),
+    };
+    wrapped.push(command.to_string());
+    wrapped.extend(args.iter().cloned());
+
+    let program = match mechanism {
+        SandboxMechanism::SandboxExec => "sandbox-exec",
+        _ => "bwrap",
+    };
+    (program.to_string(), wrapped)
+}
+
+/// Seatbelt profile allowing writes to `allowed_paths` and temporary directories only
+pub fn sandbox_exec_profile(allowed_paths: &[String], network: bool) -> String {
+    let mut profile = String::from("(version 1)(allow default)(deny file-write*)");
+    profile.push_str(
+        "(allow file-write* (regex #\"^/dev/\") (subpath \"/private/tmp\") \
+         (subpath \"/private/var/folders\")",
+    );
+    for path in allowed_paths {
+        profile.push_str(&format!(
+            " (subpath \"{}\")",
+            path.replace('\\', "\\\\").replace('"', "\\\"")
+        ));
+    }
+    profile.push(')');
+    if !network {
+        profile.push_str("(deny network*)");
+    }
+    profile
+}
+
+fn bubblewrap_args(allowed_paths: &[String], network: bool) -> Vec<String> {
+    let mut args: Vec<String> = [
+        "--ro-bind",
+        "/",
+        "/",
+        "--dev",
+        "/dev",
+        "--proc",
+        "/proc",
+        "--tmpfs",
+        "/tmp",
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect();
+    for path in allowed_paths {
+        let quoted = shell_quote(path);
+        args.extend(["--bind".to_string(), quoted.clone(), quoted]);
+    }
+    if !network {
+        args.push("--unshare-net".to_string());
+    }
+    args.extend(["--die-with-parent".to_string(), "--".to_string()]);
+    args
+}
+
+/// Add `--network none` to a `docker run` argument list that doesn't set a network
+pub fn inject_docker_network_none(args: &mut Vec<String>) {
+    let Some(run_index) = args.iter().position(|arg| arg == "run") else {
+        return;
+    };
+    if args.iter().any(|arg| {
+        arg == "--network"
+            || arg == "--net"
+            || arg.starts_with("--network=")
+            || arg.starts_with("--net=")
+    }) {
+        return;
+    }
+    args.insert(run_index + 1, "--network=none".to_string());
+}
+
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
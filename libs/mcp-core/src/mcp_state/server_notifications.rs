@@ -0,0 +1,88 @@
+//! Notifications servers send on their own, between the responses to our requests.
+//!
+//! A server reading its configuration may log through `notifications/message`, report
+//! the progress of a long tool call through `notifications/progress`, or tell that its
+//! tools changed through `notifications/tools/list_changed`. Each is told apart by
+//! [`ServerNotification::parse`] and handled by `MCPState::dispatch_server_notification`
+//! as soon as the server sends it: messages are logged, progress goes to the session
+//! that asked for it, see [`progress_topic`], and a changed tool list is discovered
+//! again.
+
+use serde_json::Value;
+
+/// A notification sent by a server
+#[derive(Clone, Debug, PartialEq)]
+pub enum ServerNotification {
+    /// A log message, `notifications/message`
+    Message {
+        level: String,
+        logger: Option<String>,
+        data: Value,
+    },
+    /// Progress of a request, `notifications/progress`
+    Progress {
+        progress_token: Value,
+        params: Value,
+    },
+    /// `notifications/tools/list_changed`
+    ToolsListChanged,
+    /// Any other notification, named by its method
+    Other(String),
+}
+
+impl ServerNotification {
+    /// The notification `message` is, None for responses and requests
+    pub fn parse(message: &Value) -> Option<Self> {
+        if message.get("id").is_some_and(|id| !id.is_null()) {
+            return None;
+        }
+        let method = message.get("method")?.as_str()?;
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+        Some(match method {
+            "notifications/message" => ServerNotification::Message {
+                level: params
+                    .get("level")
+                    .and_then(Value::as_str)
+                    .unwrap_or("info")
+                    .to_string(),
+                logger: params
+                    .get("logger")
+                    .and_then(Value::as_str)
+                    .map(str::to_string),
+                data: params.get("data").cloned().unwrap_or(Value::Null),
+            },
+            "notifications/progress" => ServerNotification::Progress {
+                progress_token: params.get("progressToken")?.clone(),
+                params,
+            },
+            "notifications/tools/list_changed" => ServerNotification::ToolsListChanged,
+            other => ServerNotification::Other(other.to_string()),
+        })
+    }
+}
+
+/// Topic a session is subscribed to while it waits for the progress of `token`
+///
+/// Clients choose their tokens, so two sessions may use the same one. The topic is
+/// sent to the server as the progress token of the call instead, and
+/// [`parse_progress_topic`] tells which session and token its progress is for.
+pub fn progress_topic(session_id: &str, token: &Value) -> String {
+    format!("progress/{}/{}", session_id, token)
+}
+
+/// Session and client token of a [`progress_topic`]
+pub fn parse_progress_topic(topic: &str) -> Option<(&str, Value)> {
+    let (session_id, token) = topic.strip_prefix("progress/")?.split_once('/')?;
+    let token = serde_json::from_str(token).ok()?;
+    Some((session_id, token))
+}
+
+/// Progress token a client asked for in the `_meta` of its request, if any
+pub fn requested_progress_token(message: &Value) -> Option<&Value> {
+    message.get("id")?;
+    message
+        .get("params")?
+        .get("_meta")?
+        .get("progressToken")
+        .filter(|token| token.is_string() || token.is_number())
+}
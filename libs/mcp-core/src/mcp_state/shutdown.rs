@@ -0,0 +1,33 @@
+//! Stopping a server without leaving its container or temporary files behind.
+//!
+//! The stdin of the server is closed first, most MCP servers exit on EOF, and the
+//! container of a docker server is stopped. The processes of the server are only
+//! killed when they are still running after the grace period, saved under
+//! [`SHUTDOWN_GRACE_SETTING`].
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// Setting holding the seconds a server has to exit once asked to
+pub const SHUTDOWN_GRACE_SETTING: &str = "shutdown_grace_secs";
+
+/// Seconds a server has to exit once asked to, unless set with [`SHUTDOWN_GRACE_SETTING`]
+pub const DEFAULT_SHUTDOWN_GRACE_SECS: u64 = 5;
+
+/// How often a stopping server is checked for having exited
+pub const SHUTDOWN_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How long a restart waits for the killed process of a server to be gone before it
+/// gives up instead of starting a second one
+pub const PROCESS_EXIT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How a server stopped
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShutdownOutcome {
+    /// It exited within the grace period
+    Graceful,
+    /// It was killed after the grace period
+    Forced,
+}
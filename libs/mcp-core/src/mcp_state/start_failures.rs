@@ -0,0 +1,78 @@
+//! Giving up on servers that keep failing to start.
+//!
+//! A server whose command is broken fails every start, and each attempt, be it from
+//! enabling it, a reconnect or a restart, costs a process and its startup timeout. The
+//! consecutive failed starts of each server are counted by [`StartFailures`], once
+//! they reach the limit saved under [`MAX_FAILED_STARTS_SETTING`] the server is
+//! faulted and isn't started again until its configuration changes or the user forces
+//! a restart.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Setting holding the consecutive failed starts after which a server is faulted
+pub const MAX_FAILED_STARTS_SETTING: &str = "max_failed_starts";
+
+/// Consecutive failed starts after which a server is faulted, unless set with
+/// [`MAX_FAILED_STARTS_SETTING`]
+pub const DEFAULT_MAX_FAILED_STARTS: u32 = 5;
+
+/// Consecutive failed starts of a server
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FailedStarts {
+    pub count: u32,
+    /// Why the last start failed
+    pub last_error: String,
+}
+
+impl FailedStarts {
+    /// Why a faulted server isn't started
+    pub fn describe(&self, server_id: &str) -> String {
+        format!(
+            "Server {} failed to start {} times in a row, it won't be started again until \
+             its configuration changes or it is restarted with force. Last error: {}",
+            server_id, self.count, self.last_error
+        )
+    }
+}
+
+/// Counts the consecutive failed starts of each server
+#[derive(Clone, Debug, Default)]
+pub struct StartFailures {
+    failures: Arc<Mutex<HashMap<String, FailedStarts>>>,
+}
+
+impl StartFailures {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count a failed start of `server_id`, returning how many in a row it failed
+    pub fn record_failure(&self, server_id: &str, error: &str) -> u32 {
+        let mut failures = self.failures.lock().unwrap();
+        let failed = failures
+            .entry(server_id.to_string())
+            .or_insert_with(|| FailedStarts {
+                count: 0,
+                last_error: String::new(),
+            });
+        failed.count += 1;
+        failed.last_error = error.to_string();
+        failed.count
+    }
+
+    /// Forget the failed starts of `server_id`, once it started or may start again
+    pub fn reset(&self, server_id: &str) {
+        self.failures.lock().unwrap().remove(server_id);
+    }
+
+    /// Failed starts of `server_id` when they reached `limit`, faulting it
+    pub fn fault(&self, server_id: &str, limit: u32) -> Option<FailedStarts> {
+        self.failures
+            .lock()
+            .unwrap()
+            .get(server_id)
+            .filter(|failed| failed.count >= limit)
+            .cloned()
+    }
+}
@@ -0,0 +1,191 @@
+//! Diagnosis of servers that exit before answering the initialize handshake.
+//!
+//! The transport owns the child process, so the shell command of a server is wrapped
+//! to append its stderr to a file and write its exit code to another once it exits.
+//! While the handshake runs, the launcher watches the exit code file: a server that
+//! exits is reported right away with its exit code and the end of its stderr, instead
+//! of waiting for the startup timeout. Once the server runs, the same file tells that
+//! it exited.
+
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::utils::command::{quote_fish, quote_posix, quote_powershell, shell_name};
+
+/// Only the end of the stderr of a server is reported
+pub const STARTUP_OUTPUT_TAIL_BYTES: u64 = 4 * 1024;
+
+/// How often the exit code file is checked while a server starts
+pub const EXIT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How long a failed handshake waits for the exit code of the server
+pub const EXIT_CODE_GRACE: Duration = Duration::from_secs(1);
+
+const STDERR_FILE: &str = "stderr.log";
+const EXIT_CODE_FILE: &str = "exit_code";
+
+/// Where the output of the servers started by this machine is captured
+pub fn server_output_dir() -> PathBuf {
+    std::env::temp_dir().join("mcp-dockmaster-servers")
+}
+
+/// A server that exited while it was starting
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StartupFailure {
+    /// None when the shell couldn't report it
+    pub exit_code: Option<i32>,
+    /// End of what the server wrote to stderr
+    pub stderr_tail: String,
+}
+
+impl fmt::Display for StartupFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.exit_code {
+            Some(code) => write!(f, "Server exited during startup with exit code {}", code)?,
+            None => write!(f, "Server exited during startup")?,
+        }
+        if self.stderr_tail.is_empty() {
+            write!(f, " without writing to stderr")
+        } else {
+            write!(f, ", stderr:\n{}", self.stderr_tail)
+        }
+    }
+}
+
+/// Output files of one spawn of a server
+pub struct StartupCapture {
+    dir: PathBuf,
+}
+
+impl StartupCapture {
+    /// Create the output directory of the spawn `spawn_id`
+    pub fn create(spawn_id: &str) -> io::Result<Self> {
+        Self::create_in(&server_output_dir(), spawn_id)
+    }
+
+    /// Output files of a spawn whose directory was already created
+    pub fn open(spawn_id: &str) -> Self {
+        Self {
+            dir: server_output_dir().join(spawn_id),
+        }
+    }
+
+    /// Create the output directory of a spawn under `root`
+    pub fn create_in(root: &Path, spawn_id: &str) -> io::Result<Self> {
+        let dir = root.join(spawn_id);
+        fs::create_dir_all(&dir)?;
+        // Left over by a spawn that reused the id
+        let _ = fs::remove_file(dir.join(EXIT_CODE_FILE));
+        Ok(Self { dir })
+    }
+
+    pub fn stderr_path(&self) -> PathBuf {
+        self.dir.join(STDERR_FILE)
+    }
+
+    pub fn exit_code_path(&self) -> PathBuf {
+        self.dir.join(EXIT_CODE_FILE)
+    }
+
+    /// Wrap `command`, run with `shell -c`, so that its stderr and exit code are
+    /// captured
+    pub fn wrap_command(&self, shell: &str, command: &str) -> String {
+        let stderr = self.stderr_path();
+        let exit_code = self.exit_code_path();
+        match shell_name(shell).as_str() {
+            "powershell" | "pwsh" => format!(
+                "& {{ {} }} 2>> {}; $LASTEXITCODE | Out-File -Encoding ascii {}",
+                command,
+                quote_powershell(&stderr),
+                quote_powershell(&exit_code)
+            ),
+            "fish" => format!(
+                "begin; {}\nend 2>> {}; echo $status > {}",
+                command,
+                quote_fish(&stderr),
+                quote_fish(&exit_code)
+            ),
+            _ => format!(
+                "{{ {}\n}} 2>> {}; echo $? > {}",
+                command,
+                quote_posix(&stderr),
+                quote_posix(&exit_code)
+            ),
+        }
+    }
+
+    /// Exit code written by the shell, None while the server runs
+    ///
+    /// `Some(None)` when the server exited but its exit code couldn't be read.
+    pub fn exit_code(&self) -> Option<Option<i32>> {
+        let text = fs::read_to_string(self.exit_code_path()).ok()?;
+        // The shell may still be writing it
+        if !text.ends_with('\n') {
+            return None;
+        }
+        Some(text.trim().parse().ok())
+    }
+
+    /// Wait until the server exits, returning its exit code
+    pub async fn wait_for_exit(&self) -> Option<i32> {
+        loop {
+            if let Some(code) = self.exit_code() {
+                return code;
+            }
+            tokio::time::sleep(EXIT_POLL_INTERVAL).await;
+        }
+    }
+
+    /// The failure of a server that exited with `exit_code`
+    pub fn failure(&self, exit_code: Option<i32>) -> StartupFailure {
+        StartupFailure {
+            exit_code,
+            stderr_tail: self.stderr_tail(),
+        }
+    }
+
+    /// Why a running server stopped, once it exited with `exit_code`
+    pub fn exit_message(&self, exit_code: Option<i32>) -> String {
+        let mut message = match exit_code {
+            Some(code) => format!("Server exited with exit code {}", code),
+            None => "Server exited".to_string(),
+        };
+        let stderr_tail = self.stderr_tail();
+        if !stderr_tail.is_empty() {
+            message.push_str(", stderr:\n");
+            message.push_str(&stderr_tail);
+        }
+        message
+    }
+
+    /// End of what the server wrote to stderr so far
+    pub fn stderr_tail(&self) -> String {
+        read_tail(&self.stderr_path(), STARTUP_OUTPUT_TAIL_BYTES)
+            .map(|tail| tail.trim().to_string())
+            .unwrap_or_default()
+    }
+
+    /// Remove the output files of the spawn `spawn_id`
+    pub fn remove(spawn_id: &str) {
+        let _ = fs::remove_dir_all(server_output_dir().join(spawn_id));
+    }
+}
+
+/// Last `max` bytes of a file, starting at a line when it was cut
+fn read_tail(path: &Path, max: u64) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    file.seek(SeekFrom::Start(len.saturating_sub(max)))?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes)?;
+    let text = String::from_utf8_lossy(&bytes).into_owned();
+    if len > max {
+        if let Some((_, rest)) = text.split_once('\n') {
+            return Ok(rest.to_string());
+        }
+    }
+    Ok(text)
+}
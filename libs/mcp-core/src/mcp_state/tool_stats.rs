@@ -0,0 +1,100 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::models::types::ToolUsage;
+
+/// How long calls are remembered for [`ToolStats::recent_calls`]
+pub const RECENT_CALLS_WINDOW: Duration = Duration::from_secs(60 * 60);
+
+/// Usage counters of the proxied tools, by server and tool name
+#[derive(Clone, Debug, Default)]
+pub struct ToolStats {
+    usage: Arc<Mutex<HashMap<(String, String), ToolUsage>>>,
+    /// When the calls of the last [`RECENT_CALLS_WINDOW`] ended, oldest first
+    recent: Arc<Mutex<VecDeque<Instant>>>,
+}
+
+impl ToolStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count a call of `tool_name` on `server_id`
+    pub fn record(&self, server_id: &str, tool_name: &str, duration: Duration, success: bool) {
+        let mut usage = self.usage.lock().unwrap();
+        let entry = usage
+            .entry((server_id.to_string(), tool_name.to_string()))
+            .or_default();
+        entry.calls += 1;
+        if !success {
+            entry.errors += 1;
+        }
+        entry.total_duration_ms += duration.as_millis() as u64;
+        entry.last_called_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .ok()
+            .map(|d| d.as_secs());
+        drop(usage);
+
+        let now = Instant::now();
+        let mut recent = self.recent.lock().unwrap();
+        forget_old_calls(&mut recent, now);
+        recent.push_back(now);
+    }
+
+    /// Count a call of `tool_name` on `server_id` refused by its rate limit
+    pub fn record_rate_limited(&self, server_id: &str, tool_name: &str) {
+        let mut usage = self.usage.lock().unwrap();
+        usage
+            .entry((server_id.to_string(), tool_name.to_string()))
+            .or_default()
+            .rate_limited += 1;
+    }
+
+    /// Usage of a tool, zero when it was never called
+    pub fn get(&self, server_id: &str, tool_name: &str) -> ToolUsage {
+        self.usage
+            .lock()
+            .unwrap()
+            .get(&(server_id.to_string(), tool_name.to_string()))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Calls made in the last [`RECENT_CALLS_WINDOW`]
+    pub fn recent_calls(&self) -> usize {
+        let mut recent = self.recent.lock().unwrap();
+        forget_old_calls(&mut recent, Instant::now());
+        recent.len()
+    }
+
+    /// The `limit` most called tools as (server id, tool name, usage), most called first
+    pub fn most_used(&self, limit: usize) -> Vec<(String, String, ToolUsage)> {
+        let usage = self.usage.lock().unwrap();
+        let mut tools: Vec<(String, String, ToolUsage)> = usage
+            .iter()
+            .filter(|(_, usage)| usage.calls > 0)
+            .map(|((server_id, tool_name), usage)| {
+                (server_id.clone(), tool_name.clone(), usage.clone())
+            })
+            .collect();
+        tools.sort_by(|a, b| {
+            b.2.calls
+                .cmp(&a.2.calls)
+                .then_with(|| (&a.0, &a.1).cmp(&(&b.0, &b.1)))
+        });
+        tools.truncate(limit);
+        tools
+    }
+}
+
+/// Drop the calls older than [`RECENT_CALLS_WINDOW`]
+fn forget_old_calls(recent: &mut VecDeque<Instant>, now: Instant) {
+    while recent
+        .front()
+        .is_some_and(|call| now.duration_since(*call) > RECENT_CALLS_WINDOW)
+    {
+        recent.pop_front();
+    }
+}
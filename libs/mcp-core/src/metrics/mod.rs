@@ -0,0 +1,543 @@
+//! Prometheus metrics of the downstream servers, served on `/metrics`.
+//!
+//! Series are labeled by server id and name and fed by the process supervisor (restarts,
+//! crashes, exit codes), the process monitor (failed liveness checks) and the proxy
+//! (calls, their latency and the calls waiting on each server). Series updated on every
+//! call are labeled by server only so that their number stays bounded, the latency
+//! histogram by tool is only kept when `tool_latency_metrics` is set in the
+//! configuration file. The registry cache counts its lookups, unlabeled. The Grafana
+//! dashboard in `dashboards/grafana-servers.json` is generated from
+//! [`METRICS`] by [`grafana_dashboard`], so that it uses the names and labels served.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde_json::{json, Value};
+
+/// Content type of the `/metrics` response
+pub const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+pub const SERVER_ID_LABEL: &str = "server_id";
+pub const SERVER_NAME_LABEL: &str = "server_name";
+pub const TOOL_NAME_LABEL: &str = "tool_name";
+
+pub const SERVERS_INSTALLED: &str = "dockmaster_servers_installed";
+pub const SERVERS_RUNNING: &str = "dockmaster_servers_running";
+pub const SERVER_RESTARTS: &str = "dockmaster_server_restarts_total";
+pub const SERVER_CRASHES: &str = "dockmaster_server_crashes_total";
+pub const SERVER_HEALTH_CHECK_FAILURES: &str = "dockmaster_server_health_check_failures_total";
+pub const SERVER_CALL_ERRORS: &str = "dockmaster_server_call_errors_total";
+pub const SERVER_CALL_DURATION: &str = "dockmaster_server_call_duration_seconds";
+pub const SERVER_CALLS_IN_FLIGHT: &str = "dockmaster_server_calls_in_flight";
+pub const SERVER_LAST_EXIT_CODE: &str = "dockmaster_server_last_exit_code";
+pub const TOOL_CALL_DURATION: &str = "dockmaster_tool_call_duration_seconds";
+pub const REGISTRY_CACHE_HITS: &str = "dockmaster_registry_cache_hits_total";
+pub const REGISTRY_CACHE_MISSES: &str = "dockmaster_registry_cache_misses_total";
+pub const REGISTRY_CACHE_PARSE_FAILURES: &str = "dockmaster_registry_cache_parse_failures_total";
+
+/// Upper bounds of the buckets of the latency histogram by tool, in seconds
+pub const TOOL_LATENCY_BUCKETS: [f64; 11] =
+    [0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+
+const SERVER_LABELS: &[&str] = &[SERVER_ID_LABEL, SERVER_NAME_LABEL];
+const TOOL_LABELS: &[&str] = &[SERVER_ID_LABEL, SERVER_NAME_LABEL, TOOL_NAME_LABEL];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MetricKind {
+    Counter,
+    Gauge,
+    /// Only `_sum` and `_count`, averaged over time
+    Summary,
+    Histogram,
+}
+
+impl MetricKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MetricKind::Counter => "counter",
+            MetricKind::Gauge => "gauge",
+            MetricKind::Summary => "summary",
+            MetricKind::Histogram => "histogram",
+        }
+    }
+}
+
+/// A metric family served on `/metrics`
+#[derive(Clone, Copy, Debug)]
+pub struct MetricDef {
+    pub name: &'static str,
+    pub help: &'static str,
+    pub kind: MetricKind,
+    pub labels: &'static [&'static str],
+}
+
+/// Every metric family served, in the order they are served
+pub const METRICS: [MetricDef; 13] = [
+    MetricDef {
+        name: SERVERS_INSTALLED,
+        help: "Servers installed",
+        kind: MetricKind::Gauge,
+        labels: &[],
+    },
+    MetricDef {
+        name: SERVERS_RUNNING,
+        help: "Servers whose process is running",
+        kind: MetricKind::Gauge,
+        labels: &[],
+    },
+    MetricDef {
+        name: SERVER_RESTARTS,
+        help: "Starts of a server after its first one",
+        kind: MetricKind::Counter,
+        labels: SERVER_LABELS,
+    },
+    MetricDef {
+        name: SERVER_CRASHES,
+        help: "Exits of a server process without a zero exit code",
+        kind: MetricKind::Counter,
+        labels: SERVER_LABELS,
+    },
+    MetricDef {
+        name: SERVER_HEALTH_CHECK_FAILURES,
+        help: "Checks of the process monitor that found a server process gone",
+        kind: MetricKind::Counter,
+        labels: SERVER_LABELS,
+    },
+    MetricDef {
+        name: SERVER_CALL_ERRORS,
+        help: "Tool calls that failed",
+        kind: MetricKind::Counter,
+        labels: SERVER_LABELS,
+    },
+    MetricDef {
+        name: SERVER_CALL_DURATION,
+        help: "Duration of the tool calls",
+        kind: MetricKind::Summary,
+        labels: SERVER_LABELS,
+    },
+    MetricDef {
+        name: SERVER_CALLS_IN_FLIGHT,
+        help: "Tool calls waiting on a server",
+        kind: MetricKind::Gauge,
+        labels: SERVER_LABELS,
+    },
+    MetricDef {
+        name: SERVER_LAST_EXIT_CODE,
+        help: "Exit code of the last process of a server",
+        kind: MetricKind::Gauge,
+        labels: SERVER_LABELS,
+    },
+    MetricDef {
+        name: TOOL_CALL_DURATION,
+        help: "Duration of the tool calls by tool",
+        kind: MetricKind::Histogram,
+        labels: TOOL_LABELS,
+    },
+    MetricDef {
+        name: REGISTRY_CACHE_HITS,
+        help: "Lookups of the registry served from the cache",
+        kind: MetricKind::Counter,
+        labels: &[],
+    },
+    MetricDef {
+        name: REGISTRY_CACHE_MISSES,
+        help: "Lookups of the registry that fetched it",
+        kind: MetricKind::Counter,
+        labels: &[],
+    },
+    MetricDef {
+        name: REGISTRY_CACHE_PARSE_FAILURES,
+        help: "Fetches of the registry that could not be parsed",
+        kind: MetricKind::Counter,
+        labels: &[],
+    },
+];
+
+/// Outcome of a lookup of the registry cache
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RegistryCacheLookup {
+    Hit,
+    Miss,
+    /// The registry was fetched but could not be parsed
+    ParseFailure,
+}
+
+#[derive(Clone, Debug, Default)]
+struct ServerSeries {
+    started: bool,
+    restarts: u64,
+    crashes: u64,
+    health_check_failures: u64,
+    calls: u64,
+    call_errors: u64,
+    call_seconds: f64,
+    in_flight: u64,
+    last_exit_code: Option<i32>,
+}
+
+#[derive(Clone, Debug, Default)]
+struct Histogram {
+    buckets: [u64; TOOL_LATENCY_BUCKETS.len()],
+    count: u64,
+    sum: f64,
+}
+
+#[derive(Debug, Default)]
+struct Series {
+    servers: HashMap<String, ServerSeries>,
+    tools: HashMap<(String, String), Histogram>,
+    registry_cache_hits: u64,
+    registry_cache_misses: u64,
+    registry_cache_parse_failures: u64,
+}
+
+/// Metrics of the servers since Dockmaster started
+#[derive(Clone, Debug, Default)]
+pub struct MetricsRegistry {
+    series: Arc<Mutex<Series>>,
+    tool_latency: Arc<AtomicBool>,
+}
+
+impl MetricsRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep the latency histogram by tool, forgetting it when turned off
+    pub fn set_tool_latency(&self, enabled: bool) {
+        self.tool_latency.store(enabled, Ordering::Relaxed);
+        if !enabled {
+            self.series.lock().unwrap().tools.clear();
+        }
+    }
+
+    pub fn tool_latency(&self) -> bool {
+        self.tool_latency.load(Ordering::Relaxed)
+    }
+
+    fn update(&self, server_id: &str, update: impl FnOnce(&mut ServerSeries)) {
+        let mut series = self.series.lock().unwrap();
+        update(series.servers.entry(server_id.to_string()).or_default());
+    }
+
+    /// Count a start of a server, every start but the first is a restart
+    pub fn record_start(&self, server_id: &str) {
+        self.update(server_id, |server| {
+            if server.started {
+                server.restarts += 1;
+            }
+            server.started = true;
+        });
+    }
+
+    /// Count the exit of a server process, `exit_code` is None when it is unknown
+    pub fn record_exit(&self, server_id: &str, exit_code: Option<i32>) {
+        self.update(server_id, |server| {
+            if exit_code != Some(0) {
+                server.crashes += 1;
+            }
+            if exit_code.is_some() {
+                server.last_exit_code = exit_code;
+            }
+        });
+    }
+
+    /// Count a check that found the process of a server gone
+    pub fn record_health_check_failure(&self, server_id: &str) {
+        self.update(server_id, |server| server.health_check_failures += 1);
+    }
+
+    /// Count a lookup of the registry cache
+    pub fn record_registry_cache(&self, lookup: RegistryCacheLookup) {
+        let mut series = self.series.lock().unwrap();
+        match lookup {
+            RegistryCacheLookup::Hit => series.registry_cache_hits += 1,
+            RegistryCacheLookup::Miss => series.registry_cache_misses += 1,
+            RegistryCacheLookup::ParseFailure => series.registry_cache_parse_failures += 1,
+        }
+    }
+
+    /// Count a call waiting on a server until the returned guard is dropped
+    pub fn start_call(&self, server_id: &str) -> InFlightCall {
+        self.update(server_id, |server| server.in_flight += 1);
+        InFlightCall {
+            metrics: self.clone(),
+            server_id: server_id.to_string(),
+        }
+    }
+
+    /// Calls waiting on each server that has any
+    pub fn in_flight(&self) -> BTreeMap<String, u64> {
+        let series = self.series.lock().unwrap();
+        series
+            .servers
+            .iter()
+            .filter(|(_, server)| server.in_flight > 0)
+            .map(|(server_id, server)| (server_id.clone(), server.in_flight))
+            .collect()
+    }
+
+    /// Count a finished call of `tool_name` on `server_id`
+    pub fn record_call(&self, server_id: &str, tool_name: &str, duration: Duration, success: bool) {
+        let seconds = duration.as_secs_f64();
+        let mut series = self.series.lock().unwrap();
+        let server = series.servers.entry(server_id.to_string()).or_default();
+        server.calls += 1;
+        server.call_seconds += seconds;
+        if !success {
+            server.call_errors += 1;
+        }
+        if !self.tool_latency() {
+            return;
+        }
+        let histogram = series
+            .tools
+            .entry((server_id.to_string(), tool_name.to_string()))
+            .or_default();
+        histogram.count += 1;
+        histogram.sum += seconds;
+        for (bucket, bound) in histogram.buckets.iter_mut().zip(TOOL_LATENCY_BUCKETS) {
+            if seconds <= bound {
+                *bucket += 1;
+            }
+        }
+    }
+
+    /// The metrics in the Prometheus text format
+    ///
+    /// `servers` maps the ids of the installed servers to their name, servers removed
+    /// since they were counted are labeled with an empty name.
+    pub fn render(&self, servers: &HashMap<String, String>, running: usize) -> String {
+        let series = self.series.lock().unwrap();
+        let mut by_server: BTreeMap<&str, ServerSeries> = servers
+            .keys()
+            .map(|server_id| (server_id.as_str(), ServerSeries::default()))
+            .collect();
+        for (server_id, server) in &series.servers {
+            by_server.insert(server_id, server.clone());
+        }
+        let name_of = |server_id: &str| servers.get(server_id).map_or("", |name| name.as_str());
+        let mut tools: Vec<_> = series.tools.iter().collect();
+        tools.sort_by(|a, b| a.0.cmp(b.0));
+        let bounds: Vec<String> = TOOL_LATENCY_BUCKETS.iter().map(|b| b.to_string()).collect();
+
+        let mut out = String::new();
+        for def in METRICS.iter() {
+            let _ = writeln!(out, "# HELP {} {}", def.name, def.help);
+            let _ = writeln!(out, "# TYPE {} {}", def.name, def.kind.as_str());
+            let server_value = |server: &ServerSeries| -> Option<f64> {
+                match def.name {
+                    SERVER_RESTARTS => Some(server.restarts as f64),
+                    SERVER_CRASHES => Some(server.crashes as f64),
+                    SERVER_HEALTH_CHECK_FAILURES => Some(server.health_check_failures as f64),
+                    SERVER_CALL_ERRORS => Some(server.call_errors as f64),
+                    SERVER_CALLS_IN_FLIGHT => Some(server.in_flight as f64),
+                    SERVER_LAST_EXIT_CODE => server.last_exit_code.map(f64::from),
+                    _ => None,
+                }
+            };
+            match def.name {
+                SERVERS_INSTALLED => sample(&mut out, def.name, &[], servers.len() as f64),
+                SERVERS_RUNNING => sample(&mut out, def.name, &[], running as f64),
+                REGISTRY_CACHE_HITS => {
+                    sample(&mut out, def.name, &[], series.registry_cache_hits as f64)
+                }
+                REGISTRY_CACHE_MISSES => {
+                    sample(&mut out, def.name, &[], series.registry_cache_misses as f64)
+                }
+                REGISTRY_CACHE_PARSE_FAILURES => sample(
+                    &mut out,
+                    def.name,
+                    &[],
+                    series.registry_cache_parse_failures as f64,
+                ),
+                SERVER_CALL_DURATION => {
+                    for (server_id, server) in &by_server {
+                        let labels = [
+                            (SERVER_ID_LABEL, *server_id),
+                            (SERVER_NAME_LABEL, name_of(server_id)),
+                        ];
+                        sample(
+                            &mut out,
+                            &format!("{}_sum", def.name),
+                            &labels,
+                            server.call_seconds,
+                        );
+                        sample(
+                            &mut out,
+                            &format!("{}_count", def.name),
+                            &labels,
+                            server.calls as f64,
+                        );
+                    }
+                }
+                TOOL_CALL_DURATION => {
+                    for ((server_id, tool_name), histogram) in &tools {
+                        let mut labels = vec![
+                            (SERVER_ID_LABEL, server_id.as_str()),
+                            (SERVER_NAME_LABEL, name_of(server_id)),
+                            (TOOL_NAME_LABEL, tool_name.as_str()),
+                        ];
+                        let bucket = format!("{}_bucket", def.name);
+                        for (count, bound) in histogram.buckets.iter().zip(&bounds) {
+                            labels.push(("le", bound));
+                            sample(&mut out, &bucket, &labels, *count as f64);
+                            labels.pop();
+                        }
+                        labels.push(("le", "+Inf"));
+                        sample(&mut out, &bucket, &labels, histogram.count as f64);
+                        labels.pop();
+                        sample(
+                            &mut out,
+                            &format!("{}_sum", def.name),
+                            &labels,
+                            histogram.sum,
+                        );
+                        sample(
+                            &mut out,
+                            &format!("{}_count", def.name),
+                            &labels,
+                            histogram.count as f64,
+                        );
+                    }
+                }
+                _ => {
+                    for (server_id, server) in &by_server {
+                        if let Some(value) = server_value(server) {
+                            let labels = [
+                                (SERVER_ID_LABEL, *server_id),
+                                (SERVER_NAME_LABEL, name_of(server_id)),
+                            ];
+                            sample(&mut out, def.name, &labels, value);
+                        }
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+/// A call counted as waiting on its server until dropped
+#[derive(Debug)]
+pub struct InFlightCall {
+    metrics: MetricsRegistry,
+    server_id: String,
+}
+
+impl Drop for InFlightCall {
+    fn drop(&mut self) {
+        self.metrics.update(&self.server_id, |server| {
+            server.in_flight = server.in_flight.saturating_sub(1)
+        });
+    }
+}
+
+fn sample(out: &mut String, name: &str, labels: &[(&str, &str)], value: f64) {
+    let _ = write!(out, "{}", name);
+    if !labels.is_empty() {
+        let labels: Vec<String> = labels
+            .iter()
+            .map(|(label, value)| format!("{}=\"{}\"", label, escape_label_value(value)))
+            .collect();
+        let _ = write!(out, "{{{}}}", labels.join(","));
+    }
+    let _ = writeln!(out, " {}", value);
+}
+
+/// Escape a label value of the Prometheus text format
+pub fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// PromQL query charted for a metric family, for the servers picked on the dashboard
+fn panel_query(def: &MetricDef) -> String {
+    let selector = format!("{{{}=~\"$server\"}}", SERVER_ID_LABEL);
+    let by = def.labels.join(", ");
+    match def.kind {
+        MetricKind::Counter => format!(
+            "sum by ({}) (increase({}{}[$__rate_interval]))",
+            by, def.name, selector
+        ),
+        MetricKind::Gauge => format!("{}{}", def.name, selector),
+        MetricKind::Summary => format!(
+            "rate({0}_sum{1}[$__rate_interval]) / rate({0}_count{1}[$__rate_interval])",
+            def.name, selector
+        ),
+        MetricKind::Histogram => format!(
+            "histogram_quantile(0.95, sum by (le, {}) (rate({}_bucket{}[$__rate_interval])))",
+            by, def.name, selector
+        ),
+    }
+}
+
+/// Grafana dashboard charting the metrics of each server, saved in `dashboards/`
+pub fn grafana_dashboard() -> Value {
+    let datasource = json!({ "type": "prometheus", "uid": "${datasource}" });
+    let panels: Vec<Value> = METRICS
+        .iter()
+        .filter(|def| !def.labels.is_empty())
+        .enumerate()
+        .map(|(i, def)| {
+            let unit = match def.kind {
+                MetricKind::Summary | MetricKind::Histogram => "s",
+                MetricKind::Counter | MetricKind::Gauge => "short",
+            };
+            let legend: Vec<String> = def
+                .labels
+                .iter()
+                .map(|label| format!("{{{{{}}}}}", label))
+                .collect();
+            json!({
+                "id": i + 1,
+                "type": "timeseries",
+                "title": def.help,
+                "description": def.name,
+                "datasource": datasource,
+                "gridPos": { "h": 8, "w": 12, "x": (i % 2) * 12, "y": (i / 2) * 8 },
+                "fieldConfig": { "defaults": { "unit": unit }, "overrides": [] },
+                "targets": [{
+                    "refId": "A",
+                    "datasource": datasource,
+                    "expr": panel_query(def),
+                    "legendFormat": legend.join(" "),
+                }],
+            })
+        })
+        .collect();
+    json!({
+        "uid": "mcp-dockmaster-servers",
+        "title": "MCP Dockmaster servers",
+        "schemaVersion": 39,
+        "refresh": "30s",
+        "time": { "from": "now-6h", "to": "now" },
+        "templating": {
+            "list": [
+                {
+                    "name": "datasource",
+                    "label": "Data source",
+                    "type": "datasource",
+                    "query": "prometheus",
+                },
+                {
+                    "name": "server",
+                    "label": "Server",
+                    "type": "query",
+                    "datasource": datasource,
+                    "query": format!("label_values({}, {})", SERVER_RESTARTS, SERVER_ID_LABEL),
+                    "includeAll": true,
+                    "multi": true,
+                    "allValue": ".*",
+                    "refresh": 2,
+                },
+            ]
+        },
+        "panels": panels,
+    })
+}
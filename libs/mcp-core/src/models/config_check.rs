@@ -0,0 +1,155 @@
+//! Resolution of the command a server configuration runs.
+//!
+//! Registration, restart and the launch plan preview all resolve the command through
+//! [`resolve_command`], so a configuration without a usable command is reported the
+//! same way on every path, naming the field at fault.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::types::ServerConfiguration;
+
+/// Name of the configuration in registration requests and installed servers
+pub const CONFIGURATION_FIELD: &str = "configuration";
+
+/// Name of the configuration in registry entries
+pub const REGISTRY_CONFIG_FIELD: &str = "config";
+
+/// A problem with one field of a server configuration
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigFieldError {
+    /// Dotted path of the field, e.g. `configuration.command` or `config.args[1]`
+    pub field: String,
+    pub message: String,
+}
+
+impl ConfigFieldError {
+    fn new(field: impl Into<String>, message: &str) -> Self {
+        Self {
+            field: field.into(),
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ConfigFieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.field, self.message)
+    }
+}
+
+/// Every problem found in a server configuration
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConfigErrors(pub Vec<ConfigFieldError>);
+
+impl ConfigErrors {
+    fn single(field: impl Into<String>, message: &str) -> Self {
+        Self(vec![ConfigFieldError::new(field, message)])
+    }
+
+    pub fn fields(&self) -> &[ConfigFieldError] {
+        &self.0
+    }
+
+    /// JSON-RPC error listing the field errors in its `data`
+    pub fn to_rpc_error(&self) -> Value {
+        json!({
+            "code": -32602,
+            "message": self.to_string(),
+            "data": { "fields": self.0 }
+        })
+    }
+}
+
+impl fmt::Display for ConfigErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let fields: Vec<String> = self.0.iter().map(|error| error.to_string()).collect();
+        write!(f, "Invalid server configuration: {}", fields.join("; "))
+    }
+}
+
+/// Check the JSON of a configuration before it is deserialized
+///
+/// Deserialization would fail on the first type mismatch without saying which field
+/// it was in, so every field is checked here and all problems are reported at once.
+pub fn check_configuration_value(value: Option<&Value>, prefix: &str) -> Result<(), ConfigErrors> {
+    let configuration = match value {
+        None | Some(Value::Null) => return Err(ConfigErrors::single(prefix, "is missing")),
+        Some(Value::Object(configuration)) => configuration,
+        Some(_) => return Err(ConfigErrors::single(prefix, "must be an object")),
+    };
+
+    let mut errors = Vec::new();
+    let field = |name: &str| format!("{}.{}", prefix, name);
+
+    match configuration.get("command") {
+        None | Some(Value::Null) => {
+            errors.push(ConfigFieldError::new(field("command"), "is missing"))
+        }
+        Some(Value::String(command)) if command.trim().is_empty() => {
+            errors.push(ConfigFieldError::new(field("command"), "is empty"))
+        }
+        Some(Value::String(_)) => {}
+        Some(_) => errors.push(ConfigFieldError::new(field("command"), "must be a string")),
+    }
+
+    match configuration.get("args") {
+        None | Some(Value::Null) => {}
+        Some(Value::Array(args)) => {
+            for (index, arg) in args.iter().enumerate() {
+                if !arg.is_string() {
+                    errors.push(ConfigFieldError::new(
+                        format!("{}.args[{}]", prefix, index),
+                        "must be a string",
+                    ));
+                }
+            }
+        }
+        Some(_) => errors.push(ConfigFieldError::new(
+            field("args"),
+            "must be an array of strings",
+        )),
+    }
+
+    match configuration.get("env") {
+        None | Some(Value::Null) | Some(Value::Object(_)) => {}
+        Some(_) => errors.push(ConfigFieldError::new(field("env"), "must be an object")),
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(ConfigErrors(errors))
+    }
+}
+
+/// Command and arguments a server runs
+///
+/// Servers stored without a configuration fall back to their entry point. `prefix`
+/// names the configuration in the errors, see [`CONFIGURATION_FIELD`] and
+/// [`REGISTRY_CONFIG_FIELD`].
+pub fn resolve_command(
+    configuration: Option<&ServerConfiguration>,
+    entry_point: Option<&str>,
+    prefix: &str,
+) -> Result<(String, Vec<String>), ConfigErrors> {
+    let field = format!("{}.command", prefix);
+    match configuration {
+        Some(configuration) => match configuration.command.as_deref() {
+            None => Err(ConfigErrors::single(field, "is missing")),
+            Some(command) if command.trim().is_empty() => {
+                Err(ConfigErrors::single(field, "is empty"))
+            }
+            Some(command) => Ok((
+                command.to_string(),
+                configuration.args.clone().unwrap_or_default(),
+            )),
+        },
+        None => match entry_point.filter(|entry_point| !entry_point.trim().is_empty()) {
+            Some(entry_point) => Ok((entry_point.to_string(), Vec::new())),
+            None => Err(ConfigErrors::single(prefix, "is missing")),
+        },
+    }
+}
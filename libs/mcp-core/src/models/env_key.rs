@@ -0,0 +1,54 @@
+//! Rules for the names of server environment variables.
+//!
+//! SQLite compares keys case-sensitively, so a server could end up with both `API_KEY`
+//! and `api_key`, the one passed to the process depending on map order. Keys are made
+//! upper case without blanks around them by [`canonical_env_key`] wherever servers are
+//! registered, imported or configured, and keys that would collide are refused.
+
+use std::collections::{BTreeMap, HashMap};
+
+use super::types::ServerConfiguration;
+
+/// The name an environment variable is saved and passed to the server under
+pub fn canonical_env_key(key: &str) -> String {
+    key.trim().to_uppercase()
+}
+
+/// Key entries by their canonical name, refusing keys that differ only by case
+pub fn canonicalize_env<V>(
+    env: impl IntoIterator<Item = (String, V)>,
+) -> Result<HashMap<String, V>, String> {
+    let mut canonical = HashMap::new();
+    let mut names: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for (key, value) in env {
+        let canonical_key = canonical_env_key(&key);
+        names.entry(canonical_key.clone()).or_default().push(key);
+        canonical.insert(canonical_key, value);
+    }
+
+    let collisions: Vec<String> = names
+        .into_values()
+        .filter(|keys| keys.len() > 1)
+        .map(|mut keys| {
+            keys.sort();
+            keys.join(", ")
+        })
+        .collect();
+    if !collisions.is_empty() {
+        return Err(format!(
+            "Environment variable names must differ by more than case: {}",
+            collisions.join("; ")
+        ));
+    }
+    Ok(canonical)
+}
+
+impl ServerConfiguration {
+    /// Make the keys of the environment canonical, see [`canonicalize_env`]
+    pub fn canonicalize_env(&mut self) -> Result<(), String> {
+        if let Some(env) = self.env.take() {
+            self.env = Some(canonicalize_env(env)?);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,106 @@
+//! Environment variables a server declares, as shown before it is installed or when it
+//! is configured.
+//!
+//! Registry entries describe their variables in `config.env` with a description, whether
+//! they are required and sometimes a default. They are parsed into
+//! [`ServerEnvironment`] entries with the configuration and saved as such, a server
+//! whose required variables have no value is not started and has the
+//! [`ServerStatus::NeedsConfiguration`](super::types::ServerStatus::NeedsConfiguration)
+//! status until they are set.
+
+use serde::Serialize;
+
+use super::types::{EnvValue, RegistryTool, ServerConfiguration, ServerEnvironment};
+use crate::diagnose::missing_required_env;
+
+/// A variable of a server, values set by the user are never included
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct EnvTemplate {
+    pub name: String,
+    pub description: String,
+    pub required: bool,
+    /// Value the registry entry gives, only in install previews
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<EnvValue>,
+    /// Whether the variable has a value
+    pub is_set: bool,
+}
+
+impl EnvTemplate {
+    fn new(name: &str, env: &ServerEnvironment, with_default: bool) -> Self {
+        Self {
+            name: name.to_string(),
+            description: env.description.clone(),
+            required: env.required,
+            default: env.default.clone().filter(|_| with_default),
+            is_set: env
+                .env_string()
+                .is_some_and(|value| !value.trim().is_empty()),
+        }
+    }
+}
+
+/// Variables of a configuration sorted by name, with their defaults when `with_default`
+pub fn env_templates(
+    configuration: Option<&ServerConfiguration>,
+    with_default: bool,
+) -> Vec<EnvTemplate> {
+    let mut templates: Vec<EnvTemplate> = configuration
+        .and_then(|configuration| configuration.env.as_ref())
+        .map(|env| {
+            env.iter()
+                .map(|(name, env)| EnvTemplate::new(name, env, with_default))
+                .collect()
+        })
+        .unwrap_or_default();
+    templates.sort_by(|a, b| a.name.cmp(&b.name));
+    templates
+}
+
+/// What installing a registry entry would set up, answered by `registry/preview_install`
+#[derive(Clone, Debug, Serialize)]
+pub struct InstallPreview {
+    pub tool_id: String,
+    pub name: String,
+    pub description: String,
+    pub runtime: String,
+    pub command: Option<String>,
+    pub args: Vec<String>,
+    pub env: Vec<EnvTemplate>,
+    /// Required variables without a default, the server won't start until they are set
+    pub needs_configuration: Vec<String>,
+}
+
+impl InstallPreview {
+    pub fn from_registry_tool(tool: &RegistryTool) -> Self {
+        Self {
+            tool_id: tool.id.clone(),
+            name: tool.name.clone(),
+            description: tool.description.clone(),
+            runtime: tool.runtime.clone(),
+            command: tool.config.command.clone(),
+            args: tool.config.args.clone().unwrap_or_default(),
+            env: env_templates(Some(&tool.config), true),
+            needs_configuration: missing_required_env(Some(&tool.config)),
+        }
+    }
+}
+
+/// Variables of an installed server, answered by `server/config_schema`
+#[derive(Clone, Debug, Serialize)]
+pub struct ConfigSchema {
+    pub server_id: String,
+    pub env: Vec<EnvTemplate>,
+    /// Required variables without a value
+    pub missing: Vec<String>,
+}
+
+impl ConfigSchema {
+    pub fn new(server_id: &str, configuration: Option<&ServerConfiguration>) -> Self {
+        Self {
+            server_id: server_id.to_string(),
+            env: env_templates(configuration, false),
+            missing: missing_required_env(configuration),
+        }
+    }
+}
@@ -1,3 +1,10 @@
+pub mod config_check;
+pub mod env_key;
+pub mod env_template;
 pub mod error;
-pub mod types;
+pub mod registration;
+pub mod server_id;
 pub mod tool_db;
+pub mod tool_id;
+pub mod types;
+pub mod unknown_fields;
@@ -0,0 +1,88 @@
+//! Registration of a server, whichever way it was asked for.
+//!
+//! The app sends a [`ServerRegistrationRequest`], JSON-RPC clients and models register
+//! by name or by registry id, imports and the config file build their own request.
+//! Every one of them is turned into a [`ResolvedRegistration`], which is all
+//! `MCPCore` registers, so the checks and the env handling are the same on every path.
+
+use serde_json::Value;
+
+use super::config_check::{check_configuration_value, resolve_command, CONFIGURATION_FIELD};
+use super::types::{
+    Distribution, ProvenanceSource, ServerConfiguration, ServerDefinition, ServerProvenance,
+    ServerRegistrationRequest,
+};
+use super::unknown_fields::{parse_with_unknown_fields, unknown_field_warnings};
+
+/// A registration request once checked, with its env in `configuration.env`
+#[derive(Clone, Debug)]
+pub struct ResolvedRegistration {
+    /// Id asked for, the registry may assign a different one
+    pub server_id: String,
+    pub server_name: String,
+    pub description: String,
+    pub tools_type: String,
+    pub configuration: ServerConfiguration,
+    pub distribution: Option<Distribution>,
+    /// Stamped with the install time and the app version
+    pub provenance: ServerProvenance,
+    /// Start the server once registered
+    pub enabled: bool,
+    /// Fields of the request that were ignored
+    pub warnings: Vec<String>,
+}
+
+impl ResolvedRegistration {
+    /// Check a request before anything is saved
+    ///
+    /// A configuration without a usable command is refused, the env declared in
+    /// `authentication` is moved into `configuration.env` and the provenance defaults to
+    /// a manual install.
+    pub fn resolve(mut request: ServerRegistrationRequest) -> Result<Self, String> {
+        resolve_command(request.configuration.as_ref(), None, CONFIGURATION_FIELD)
+            .map_err(|errors| errors.to_string())?;
+        let warnings = request.resolve_env()?;
+        let provenance = request
+            .provenance
+            .unwrap_or_else(|| ServerProvenance::new(ProvenanceSource::Manual, None))
+            .stamped();
+        Ok(Self {
+            server_id: request.server_id,
+            server_name: request.server_name,
+            description: request.description,
+            tools_type: request.tools_type,
+            configuration: request.configuration.unwrap_or_default(),
+            distribution: request.distribution,
+            provenance,
+            enabled: request.enabled.unwrap_or(true),
+            warnings,
+        })
+    }
+
+    /// Definition saved in the registry
+    pub fn definition(&self) -> ServerDefinition {
+        ServerDefinition {
+            name: self.server_name.clone(),
+            description: self.description.clone(),
+            enabled: self.enabled,
+            tools_type: self.tools_type.clone(),
+            entry_point: None,
+            configuration: Some(self.configuration.clone()),
+            distribution: self.distribution.clone(),
+        }
+    }
+}
+
+/// Parse the JSON of a [`ServerRegistrationRequest`], as the app sends it
+///
+/// The configuration is checked field by field first so every problem is reported at
+/// once. Returns the request and a warning for each unknown field.
+pub fn parse_registration_request(
+    value: Value,
+) -> Result<(ServerRegistrationRequest, Vec<String>), String> {
+    check_configuration_value(value.get(CONFIGURATION_FIELD), CONFIGURATION_FIELD)
+        .map_err(|errors| errors.to_string())?;
+    let (request, unknown) = parse_with_unknown_fields::<ServerRegistrationRequest>(value)
+        .map_err(|e| format!("Invalid registration request: {}", e))?;
+    Ok((request, unknown_field_warnings(&unknown)))
+}
@@ -0,0 +1,130 @@
+//! Rules for server ids.
+//!
+//! Server ids end up in proxy ids (`server:tool`), file paths, docker container names
+//! and URLs, so every registration goes through [`normalize_server_id`]: ids are
+//! lowercase slugs of `a-z`, `0-9`, `-` and `_`, starting with a letter or a digit.
+//!
+//! Requests name the id of a server `server_id`. Older requests named it `tool_id`,
+//! which is still accepted with a deprecation warning, see
+//! [`legacy_server_id_warning`].
+
+use serde_json::Value;
+
+/// Longest server id accepted
+pub const MAX_SERVER_ID_LEN: usize = 64;
+
+/// Ids with a meaning of their own in requests, never given to a server
+pub const RESERVED_SERVER_IDS: &[&str] = &["builtin", "auto", "all"];
+
+/// Name the server id had in older requests
+pub const LEGACY_SERVER_ID_FIELD: &str = "tool_id";
+
+/// Id used when nothing usable is left of a name
+const FALLBACK_SERVER_ID: &str = "server";
+
+/// Check that `id` can be used as a server id as is
+pub fn validate_server_id(id: &str) -> Result<(), String> {
+    if id.is_empty() {
+        return Err("Server id is empty".to_string());
+    }
+    if id.len() > MAX_SERVER_ID_LEN {
+        return Err(format!(
+            "Server id '{}' is longer than {} characters",
+            id, MAX_SERVER_ID_LEN
+        ));
+    }
+    if RESERVED_SERVER_IDS.contains(&id) {
+        return Err(format!("Server id '{}' is reserved", id));
+    }
+    if !id.starts_with(|c: char| c.is_ascii_lowercase() || c.is_ascii_digit()) {
+        return Err(format!(
+            "Server id '{}' should start with a lowercase letter or a digit",
+            id
+        ));
+    }
+    if let Some(c) = id.chars().find(|&c| !is_id_char(c)) {
+        return Err(format!(
+            "Server id '{}' contains '{}', only lowercase letters, digits, '-' and '_' are allowed",
+            id, c
+        ));
+    }
+    Ok(())
+}
+
+/// Turn a display name, or an id that isn't valid, into a valid server id
+///
+/// Deterministic: characters that aren't allowed become `-`, runs of them are
+/// collapsed and trimmed, and reserved ids get a `-server` suffix.
+pub fn slugify_server_id(name: &str) -> String {
+    let mut slug = String::with_capacity(name.len());
+    for c in name.to_lowercase().chars() {
+        if is_id_char(c) {
+            slug.push(c);
+        } else if !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = truncate(
+        slug.trim_matches(|c| c == '-' || c == '_'),
+        MAX_SERVER_ID_LEN,
+    );
+    if slug.is_empty() {
+        FALLBACK_SERVER_ID.to_string()
+    } else if RESERVED_SERVER_IDS.contains(&slug) {
+        format!("{}-{}", slug, FALLBACK_SERVER_ID)
+    } else {
+        slug.to_string()
+    }
+}
+
+/// The id to register a server under: `id` when it is valid, its slug otherwise
+pub fn normalize_server_id(id: &str) -> String {
+    match validate_server_id(id) {
+        Ok(()) => id.to_string(),
+        Err(_) => slugify_server_id(id),
+    }
+}
+
+/// `base`, or `base` with the first `-2`, `-3`, ... suffix for which `taken` is false
+pub fn unique_server_id(base: &str, taken: impl Fn(&str) -> bool) -> String {
+    if !taken(base) {
+        return base.to_string();
+    }
+    (2..)
+        .map(|n| {
+            let suffix = format!("-{}", n);
+            format!(
+                "{}{}",
+                truncate(base, MAX_SERVER_ID_LEN - suffix.len()),
+                suffix
+            )
+        })
+        .find(|id| !taken(id))
+        .unwrap()
+}
+
+/// Warning for a request that named the server id `tool_id`, None when it didn't
+pub fn legacy_server_id_warning(params: &Value) -> Option<String> {
+    params.get(LEGACY_SERVER_ID_FIELD)?;
+    Some(format!(
+        "'{}' is deprecated, send the id of the server as 'server_id'",
+        LEGACY_SERVER_ID_FIELD
+    ))
+}
+
+/// Server id of a request, named `server_id` or, in older requests, `tool_id`
+pub fn request_server_id(params: &Value) -> Option<&str> {
+    params
+        .get("server_id")
+        .or_else(|| params.get(LEGACY_SERVER_ID_FIELD))
+        .and_then(|v| v.as_str())
+}
+
+fn is_id_char(c: char) -> bool {
+    c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_'
+}
+
+/// At most `len` bytes of a slug, which is ASCII
+fn truncate(slug: &str, len: usize) -> &str {
+    slug[..slug.len().min(len)].trim_end_matches(|c| c == '-' || c == '_')
+}
@@ -1,4 +1,9 @@
-use crate::schema::{app_settings, server_env, server_tools, servers};
+use crate::schema::{
+    app_settings, audit_log, registry_sources, schedule_runs, schedules, server_call_modes,
+    server_config_history, server_env, server_id_aliases, server_info, server_lifecycle_events,
+    server_processes, server_provenance, server_run_states, server_tools, servers, undo_journal,
+    webhook_dead_letters, webhooks,
+};
 use diesel::prelude::*;
 
 /// This struct corresponds to a row in the `tools` table.
@@ -15,6 +20,20 @@ pub struct DBServer {
     pub args: Option<String>,
     pub distribution_type: Option<String>,
     pub distribution_package: Option<String>,
+    pub priority: Option<String>,
+    pub cpu_limit: Option<i32>,
+    pub max_memory_mb: Option<i32>,
+    pub sandbox: Option<String>,
+    pub inherit_env: Option<bool>,
+    pub env_passthrough: Option<String>,
+    pub truncate_descriptions: Option<bool>,
+    pub strict_output_schema: Option<bool>,
+    pub share_instructions: Option<bool>,
+    pub tool_filter: Option<String>,
+    pub platforms: Option<String>,
+    pub start_mode: Option<String>,
+    pub cwd: Option<String>,
+    pub lenient_initialize: Option<bool>,
 }
 
 /// For inserting a new row into the `tools` table
@@ -31,6 +50,20 @@ pub struct NewServer<'a> {
     pub args: Option<&'a str>,
     pub distribution_type: Option<&'a str>,
     pub distribution_package: Option<&'a str>,
+    pub priority: Option<&'a str>,
+    pub cpu_limit: Option<i32>,
+    pub max_memory_mb: Option<i32>,
+    pub sandbox: Option<&'a str>,
+    pub inherit_env: Option<bool>,
+    pub env_passthrough: Option<&'a str>,
+    pub truncate_descriptions: Option<bool>,
+    pub strict_output_schema: Option<bool>,
+    pub share_instructions: Option<bool>,
+    pub tool_filter: Option<&'a str>,
+    pub platforms: Option<&'a str>,
+    pub start_mode: Option<&'a str>,
+    pub cwd: Option<&'a str>,
+    pub lenient_initialize: Option<bool>,
 }
 
 /// For updating an existing row in the `tools` table
@@ -46,6 +79,20 @@ pub struct UpdateServer<'a> {
     pub args: Option<Option<&'a str>>,
     pub distribution_type: Option<Option<&'a str>>,
     pub distribution_package: Option<Option<&'a str>>,
+    pub priority: Option<Option<&'a str>>,
+    pub cpu_limit: Option<Option<i32>>,
+    pub max_memory_mb: Option<Option<i32>>,
+    pub sandbox: Option<Option<&'a str>>,
+    pub inherit_env: Option<Option<bool>>,
+    pub env_passthrough: Option<Option<&'a str>>,
+    pub truncate_descriptions: Option<Option<bool>>,
+    pub strict_output_schema: Option<Option<bool>>,
+    pub share_instructions: Option<Option<bool>>,
+    pub tool_filter: Option<Option<&'a str>>,
+    pub platforms: Option<Option<&'a str>>,
+    pub start_mode: Option<Option<&'a str>>,
+    pub cwd: Option<Option<&'a str>>,
+    pub lenient_initialize: Option<Option<bool>>,
 }
 
 /// This struct corresponds to a row in the `server_env` table.
@@ -57,6 +104,8 @@ pub struct DBServerEnv {
     pub env_value: String,
     pub env_description: String,
     pub env_required: bool,
+    pub env_type: String,
+    pub env_bool_format: String,
 }
 
 /// For inserting a new row into the `server_env` table
@@ -68,6 +117,8 @@ pub struct NewServerEnv {
     pub env_value: String,
     pub env_description: String,
     pub env_required: bool,
+    pub env_type: String,
+    pub env_bool_format: String,
 }
 
 /// For updating an existing row in the `server_env` table
@@ -78,6 +129,8 @@ pub struct UpdateServerEnv<'a> {
     pub env_value: Option<&'a str>,
     pub env_description: Option<&'a str>,
     pub env_required: Option<bool>,
+    pub env_type: Option<&'a str>,
+    pub env_bool_format: Option<&'a str>,
 }
 
 /// This struct corresponds to a row in the `server_tools` table.
@@ -91,6 +144,8 @@ pub struct DBServerTool {
     pub server_id: String,
     pub proxy_id: Option<String>,
     pub is_active: bool,
+    pub output_schema: Option<String>,
+    pub annotations: Option<String>,
 }
 
 /// For inserting a new row into the `server_tools` table
@@ -104,6 +159,8 @@ pub struct NewServerTool {
     pub server_id: String,
     pub proxy_id: Option<String>,
     pub is_active: bool,
+    pub output_schema: Option<String>,
+    pub annotations: Option<String>,
 }
 
 /// For updating an existing row in the `server_tools` table
@@ -116,6 +173,8 @@ pub struct UpdateServerTool {
     pub input_schema: Option<Option<String>>,
     pub proxy_id: Option<Option<String>>,
     pub is_active: Option<bool>,
+    pub output_schema: Option<Option<String>>,
+    pub annotations: Option<Option<String>>,
 }
 
 /// This struct corresponds to a row in the `app_settings` table.
@@ -133,3 +192,278 @@ pub struct NewAppSetting<'a> {
     pub key: &'a str,
     pub value: &'a str,
 }
+
+/// This struct corresponds to a row in the `server_processes` table.
+#[derive(Debug, Queryable, Selectable, Insertable)]
+#[diesel(table_name = server_processes)]
+pub struct DBServerProcess {
+    pub server_id: String,
+    pub spawn_id: String,
+    pub pid: Option<i64>,
+    pub start_time: Option<i64>,
+    pub command: Option<String>,
+    pub container_name: Option<String>,
+}
+
+/// This struct corresponds to a row in the `server_provenance` table.
+#[derive(Debug, Queryable, Selectable, Insertable)]
+#[diesel(table_name = server_provenance)]
+pub struct DBServerProvenance {
+    pub server_id: String,
+    pub source: String,
+    pub source_id: Option<String>,
+    pub client: Option<String>,
+    pub installed_at: i64,
+    pub app_version: String,
+    pub registry_source: Option<String>,
+}
+
+/// This struct corresponds to a row in the `server_id_aliases` table.
+#[derive(Debug, Queryable, Selectable, Insertable)]
+#[diesel(table_name = server_id_aliases)]
+pub struct DBServerIdAlias {
+    pub alias: String,
+    pub server_id: String,
+}
+
+/// This struct corresponds to a row in the `server_run_states` table.
+#[derive(Debug, Queryable, Selectable, Insertable)]
+#[diesel(table_name = server_run_states)]
+pub struct DBServerRunState {
+    pub server_id: String,
+    pub desired_running: bool,
+}
+
+/// This struct corresponds to a row in the `server_call_modes` table.
+#[derive(Debug, Queryable, Selectable, Insertable)]
+#[diesel(table_name = server_call_modes)]
+pub struct DBServerCallMode {
+    pub server_id: String,
+    pub mode: String,
+    pub on_miss: String,
+}
+
+/// This struct corresponds to a row in the `server_info` table.
+#[derive(Debug, Queryable, Selectable, Insertable)]
+#[diesel(table_name = server_info)]
+pub struct DBServerInfo {
+    pub server_id: String,
+    pub name: String,
+    pub version: String,
+    pub protocol_version: Option<String>,
+    pub instructions: Option<String>,
+    pub initialized_at: i64,
+    pub capabilities: Option<String>,
+}
+
+/// This struct corresponds to a row in the `audit_log` table.
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = audit_log)]
+pub struct DBAuditLogEntry {
+    pub id: i32,
+    pub timestamp: i64,
+    pub action: String,
+    pub server_id: Option<String>,
+    pub client: Option<String>,
+    pub decision: String,
+    pub detail: Option<String>,
+}
+
+/// This struct is used for appending to the `audit_log` table.
+#[derive(Debug, Insertable)]
+#[diesel(table_name = audit_log)]
+pub struct NewAuditLogEntry<'a> {
+    pub timestamp: i64,
+    pub action: &'a str,
+    pub server_id: Option<&'a str>,
+    pub client: Option<&'a str>,
+    pub decision: &'a str,
+    pub detail: Option<&'a str>,
+}
+
+/// This struct corresponds to a row in the `schedules` table.
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = schedules)]
+pub struct DBSchedule {
+    pub id: String,
+    pub cron: String,
+    pub tool_id: String,
+    pub arguments: String,
+    pub enabled: bool,
+    pub misfire: String,
+    pub last_run_at: Option<i64>,
+    pub last_success: Option<bool>,
+    pub last_error: Option<String>,
+    pub consecutive_failures: i32,
+}
+
+/// This struct is used for saving the definition of a job to the `schedules` table,
+/// its status is left as is.
+#[derive(Debug, Insertable, AsChangeset)]
+#[diesel(table_name = schedules)]
+pub struct NewSchedule<'a> {
+    pub id: &'a str,
+    pub cron: &'a str,
+    pub tool_id: &'a str,
+    pub arguments: String,
+    pub enabled: bool,
+    pub misfire: &'a str,
+}
+
+/// This struct corresponds to a row in the `schedule_runs` table.
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = schedule_runs)]
+pub struct DBScheduleRun {
+    pub id: i32,
+    pub schedule_id: String,
+    pub started_at: i64,
+    pub duration_ms: i64,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+/// This struct is used for appending to the `schedule_runs` table.
+#[derive(Debug, Insertable)]
+#[diesel(table_name = schedule_runs)]
+pub struct NewScheduleRun<'a> {
+    pub schedule_id: &'a str,
+    pub started_at: i64,
+    pub duration_ms: i64,
+    pub success: bool,
+    pub error: Option<&'a str>,
+}
+
+/// This struct corresponds to a row in the `webhooks` table.
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = webhooks)]
+pub struct DBWebhook {
+    pub id: String,
+    pub url: String,
+    pub events: String,
+    pub enabled: bool,
+    pub secret: String,
+}
+
+/// This struct is used for inserting and updating rows of the `webhooks` table.
+#[derive(Debug, Insertable, AsChangeset)]
+#[diesel(table_name = webhooks)]
+pub struct NewWebhook<'a> {
+    pub id: &'a str,
+    pub url: &'a str,
+    pub events: String,
+    pub enabled: bool,
+    pub secret: &'a str,
+}
+
+/// This struct corresponds to a row in the `webhook_dead_letters` table.
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = webhook_dead_letters)]
+pub struct DBWebhookDeadLetter {
+    pub id: i32,
+    pub webhook_id: String,
+    pub payload: String,
+    pub error: String,
+    pub attempts: i32,
+    pub failed_at: i64,
+}
+
+/// This struct is used for appending to the `webhook_dead_letters` table.
+#[derive(Debug, Insertable)]
+#[diesel(table_name = webhook_dead_letters)]
+pub struct NewWebhookDeadLetter<'a> {
+    pub webhook_id: &'a str,
+    pub payload: String,
+    pub error: &'a str,
+    pub attempts: i32,
+    pub failed_at: i64,
+}
+
+/// This struct corresponds to a row in the `registry_sources` table.
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = registry_sources)]
+pub struct DBRegistrySource {
+    pub id: String,
+    pub url: String,
+    pub kind: String,
+    pub headers: String,
+    pub credentials: Option<String>,
+}
+
+/// This struct is used for inserting and updating rows of the `registry_sources` table.
+#[derive(Debug, Insertable, AsChangeset)]
+#[diesel(table_name = registry_sources)]
+#[diesel(treat_none_as_null = true)]
+pub struct NewRegistrySource<'a> {
+    pub id: &'a str,
+    pub url: &'a str,
+    pub kind: &'a str,
+    pub headers: String,
+    pub credentials: Option<String>,
+}
+
+/// This struct corresponds to a row in the `server_config_history` table.
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = server_config_history)]
+pub struct DBConfigSnapshot {
+    pub id: i32,
+    pub server_id: String,
+    pub created_at: i64,
+    pub configuration: String,
+}
+
+/// This struct is used for appending to the `server_config_history` table.
+#[derive(Debug, Insertable)]
+#[diesel(table_name = server_config_history)]
+pub struct NewConfigSnapshot<'a> {
+    pub server_id: &'a str,
+    pub created_at: i64,
+    pub configuration: String,
+}
+
+/// This struct corresponds to a row in the `server_lifecycle_events` table.
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = server_lifecycle_events)]
+pub struct DBLifecycleEvent {
+    pub id: i32,
+    pub server_id: String,
+    pub kind: String,
+    pub at: i64,
+    pub exit_code: Option<i32>,
+    pub detail: Option<String>,
+}
+
+/// This struct is used for appending to the `server_lifecycle_events` table.
+#[derive(Debug, Insertable)]
+#[diesel(table_name = server_lifecycle_events)]
+pub struct NewLifecycleEvent<'a> {
+    pub server_id: &'a str,
+    pub kind: &'a str,
+    pub at: i64,
+    pub exit_code: Option<i32>,
+    pub detail: Option<&'a str>,
+}
+
+/// This struct corresponds to a row in the `undo_journal` table.
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = undo_journal)]
+pub struct DBUndoEntry {
+    pub id: i32,
+    pub action: String,
+    pub server_id: String,
+    pub summary: String,
+    pub created_at: i64,
+    pub expires_at: i64,
+    pub before_image: String,
+}
+
+/// This struct is used for appending to the `undo_journal` table.
+#[derive(Debug, Insertable)]
+#[diesel(table_name = undo_journal)]
+pub struct NewUndoEntry<'a> {
+    pub action: &'a str,
+    pub server_id: &'a str,
+    pub summary: &'a str,
+    pub created_at: i64,
+    pub expires_at: i64,
+    pub before_image: &'a str,
+}
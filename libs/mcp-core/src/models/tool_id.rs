@@ -0,0 +1,47 @@
+//! Keys tools are routed by.
+//!
+//! The MCP specification identifies a tool by its `name`, servers may add an `id` of
+//! their own or leave it out. Dockmaster keys tools by their name: the proxy id clients
+//! call a tool with is `server_id:tool_name`, and a tool asked for by a bare key is
+//! looked up by name before ids are tried, so a name never loses to the id of a tool of
+//! another server.
+
+use std::collections::HashMap;
+
+use crate::models::types::ServerToolInfo;
+
+/// Proxy id of the tool `tool_name` of `server_id`
+pub fn proxy_tool_id(server_id: &str, tool_name: &str) -> String {
+    format!("{}:{}", server_id, tool_name)
+}
+
+/// Server id and tool name of a proxy id, `None` when either is missing
+pub fn parse_proxy_tool_id(proxy_id: &str) -> Option<(&str, &str)> {
+    let (server_id, tool_name) = proxy_id.split_once(':')?;
+    (!server_id.is_empty() && !tool_name.is_empty()).then_some((server_id, tool_name))
+}
+
+/// Tool of the servers asked for by `key`
+///
+/// `key` is a tool name, a proxy id or, as older clients sent, a tool id. Servers are
+/// searched in the order of their ids, so the same key always finds the same tool when
+/// several servers have a tool of that name.
+pub fn find_tool<'a>(
+    server_tools: &'a HashMap<String, Vec<ServerToolInfo>>,
+    key: &str,
+) -> Option<&'a ServerToolInfo> {
+    let mut servers: Vec<(&String, &Vec<ServerToolInfo>)> = server_tools.iter().collect();
+    servers.sort_by(|a, b| a.0.cmp(b.0));
+    let tools = || servers.iter().flat_map(|(_, tools)| tools.iter());
+
+    tools()
+        .find(|tool| tool.name == key)
+        .or_else(|| {
+            let (server_id, tool_name) = parse_proxy_tool_id(key)?;
+            server_tools
+                .get(server_id)?
+                .iter()
+                .find(|tool| tool.name == tool_name)
+        })
+        .or_else(|| tools().find(|tool| !tool.id.is_empty() && tool.id == key))
+}
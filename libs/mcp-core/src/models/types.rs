@@ -1,8 +1,18 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 use std::hash::{Hash, Hasher};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::health::ServerHealth;
+use crate::hooks::PolicyError;
+use crate::lifecycle::ServerUptime;
+use crate::mcp_server::tools::BUILTIN_SERVER_ID;
+use crate::models::env_key::canonicalize_env;
+use crate::models::tool_id::proxy_tool_id;
+use crate::rate_limits::{RateLimit, RateLimited};
+use crate::tool_limits::ToolFilter;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServerId(String);
@@ -43,7 +53,9 @@ impl fmt::Display for ServerStatus {
             ServerStatus::Running => write!(f, "Running"),
             ServerStatus::Stopped => write!(f, "Stopped"),
             ServerStatus::Starting => write!(f, "Starting"),
+            ServerStatus::NeedsConfiguration => write!(f, "Needs configuration"),
             ServerStatus::Error(msg) => write!(f, "Error: {}", msg),
+            ServerStatus::Faulted(msg) => write!(f, "Faulted: {}", msg),
         }
     }
 }
@@ -63,11 +75,17 @@ pub enum ServerStatus {
     Running,
     Stopped,
     Starting,
+    /// Not started because required environment variables have no value
+    #[serde(rename = "needs_configuration")]
+    NeedsConfiguration,
     #[serde(
         serialize_with = "serialize_error",
         deserialize_with = "deserialize_error"
     )]
     Error(String),
+    /// Failed to start too many times in a row, with the last error, see
+    /// [`crate::mcp_state::start_failures`]
+    Faulted(String),
 }
 
 #[derive(Debug, Serialize)]
@@ -77,7 +95,7 @@ pub struct ToolConfigUpdateResponse {
 }
 
 /// Tool configuration for command and arguments
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct ServerConfiguration {
     #[serde(default)]
     pub command: Option<String>,
@@ -85,16 +103,421 @@ pub struct ServerConfiguration {
     pub args: Option<Vec<String>>,
     #[serde(default)]
     pub env: Option<HashMap<String, ServerEnvironment>>,
+    /// Scheduling priority of the server process
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<ProcessPriority>,
+    /// Maximum number of CPU cores the server may use
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_limit: Option<u32>,
+    /// Memory the server may use, in megabytes. Docker enforces it for docker
+    /// servers, other servers are killed when they use more
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_memory_mb: Option<u32>,
+    /// Restrictions applied when the server runs in restricted mode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sandbox: Option<SandboxOptions>,
+    /// Inherit the whole Dockmaster environment instead of a minimal baseline, for
+    /// servers that break without it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inherit_env: Option<bool>,
+    /// Dockmaster environment variables passed to the server besides the baseline
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env_passthrough: Option<Vec<String>>,
+    /// Truncate long tool descriptions in the aggregated tool list, true unless set
+    /// to false for servers whose long descriptions are essential
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub truncate_descriptions: Option<bool>,
+    /// Fail tool calls whose result does not match the output schema of the tool,
+    /// instead of only attaching a warning
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strict_output_schema: Option<bool>,
+    /// Pass the instructions the server gives at initialization on to MCP clients, in
+    /// the instructions of Dockmaster
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub share_instructions: Option<bool>,
+    /// Which tools of the server are listed and how many at most, see
+    /// [`crate::tool_limits`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_filter: Option<ToolFilter>,
+    /// Docker platforms the image is built for, like `linux/amd64`, see [`crate::arch`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub platforms: Option<Vec<String>>,
+    /// Whether the server starts with Dockmaster or on the first call to one of its
+    /// tools
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_mode: Option<StartMode>,
+    /// Directory the server is started in, relative paths it is given resolve against it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+    /// Use the server even when it doesn't answer the initialize request properly, and
+    /// list its tools even when its answer declares no tool support
+    ///
+    /// An answer with an error, without a result or not coming within
+    /// [`crate::mcp_state::process_launcher::LENIENT_INITIALIZE_WAIT`] doesn't stop the
+    /// server from starting then.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lenient_initialize: Option<bool>,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Environment variable entry of a server configuration.
+///
+/// Accepts either a template object (`{"description": ..., "default": ...}`) or a
+/// bare string/number/boolean value, see [`ServerEnvironment::from_value`].
+#[derive(Clone, Debug, Serialize)]
 pub struct ServerEnvironment {
-    #[serde(default)]
     pub description: String,
+    pub default: Option<EnvValue>,
+    pub required: bool,
+    /// How a boolean value is rendered when the server is spawned
+    pub bool_format: EnvBoolFormat,
+}
+
+impl ServerEnvironment {
+    /// Whether a JSON value is an env template object rather than a plain value
+    pub fn is_template(value: &Value) -> bool {
+        value.as_object().is_some_and(|obj| {
+            obj.contains_key("description")
+                || obj.contains_key("default")
+                || obj.contains_key("required")
+        })
+    }
+
+    /// Build an environment entry from a JSON value.
+    ///
+    /// Strings, numbers and booleans are taken as the value itself, `null` as no value
+    /// and template objects are parsed field by field. Any other object or array is
+    /// rejected instead of being stored as raw JSON.
+    pub fn from_value(value: &Value) -> Result<Self, String> {
+        if !Self::is_template(value) {
+            return Ok(Self {
+                description: String::new(),
+                default: EnvValue::from_json(value)?,
+                required: false,
+                bool_format: EnvBoolFormat::default(),
+            });
+        }
+
+        let obj = value.as_object().expect("templates are objects");
+        let description = match obj.get("description") {
+            None | Some(Value::Null) => String::new(),
+            Some(Value::String(s)) => s.clone(),
+            Some(other) => return Err(format!("env description must be a string, got {}", other)),
+        };
+        let default = match obj.get("default") {
+            Some(v) => EnvValue::from_json(v)?,
+            None => None,
+        };
+        let required = match obj.get("required") {
+            None | Some(Value::Null) => false,
+            Some(Value::Bool(b)) => *b,
+            Some(other) => return Err(format!("env required must be a boolean, got {}", other)),
+        };
+        let bool_format = match obj.get("bool_format") {
+            None | Some(Value::Null) => EnvBoolFormat::default(),
+            Some(v) => serde_json::from_value(v.clone())
+                .map_err(|e| format!("invalid env bool_format: {}", e))?,
+        };
+
+        Ok(Self {
+            description,
+            default,
+            required,
+            bool_format,
+        })
+    }
+
+    /// The value passed to the server process, if any
+    pub fn env_string(&self) -> Option<String> {
+        self.default
+            .as_ref()
+            .map(|v| v.to_env_string(self.bool_format))
+    }
+}
+
+impl<'de> Deserialize<'de> for ServerEnvironment {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value = Value::deserialize(deserializer)?;
+        ServerEnvironment::from_value(&value).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Typed environment variable value.
+///
+/// Values keep their JSON type in the configuration and the database and are only
+/// turned into strings when the server process is spawned: strings are passed as is,
+/// numbers use their JSON representation and booleans follow the entry's
+/// [`EnvBoolFormat`] (`"true"`/`"false"` or `"1"`/`"0"`).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum EnvValue {
+    Bool(bool),
+    Number(serde_json::Number),
+    String(String),
+}
+
+impl EnvValue {
+    /// Convert a scalar JSON value, `null` means no value
+    pub fn from_json(value: &Value) -> Result<Option<Self>, String> {
+        match value {
+            Value::Null => Ok(None),
+            Value::Bool(b) => Ok(Some(EnvValue::Bool(*b))),
+            Value::Number(n) => Ok(Some(EnvValue::Number(n.clone()))),
+            Value::String(s) => Ok(Some(EnvValue::String(s.clone()))),
+            Value::Array(_) | Value::Object(_) => Err(format!(
+                "unsupported env value {}: expected a string, number, boolean or a template object with a description",
+                value
+            )),
+        }
+    }
+
+    /// Restore a value from its stored text and type name
+    pub fn from_stored(value: &str, value_type: &str) -> Self {
+        match value_type {
+            "bool" => match value {
+                "true" => EnvValue::Bool(true),
+                "false" => EnvValue::Bool(false),
+                _ => EnvValue::String(value.to_string()),
+            },
+            "number" => value
+                .parse::<serde_json::Number>()
+                .map(EnvValue::Number)
+                .unwrap_or_else(|_| EnvValue::String(value.to_string())),
+            _ => EnvValue::String(value.to_string()),
+        }
+    }
+
+    /// Type name stored next to the value in the database
+    pub fn value_type(&self) -> &'static str {
+        match self {
+            EnvValue::Bool(_) => "bool",
+            EnvValue::Number(_) => "number",
+            EnvValue::String(_) => "string",
+        }
+    }
+
+    /// Render the value for the process environment
+    pub fn to_env_string(&self, bool_format: EnvBoolFormat) -> String {
+        match (self, bool_format) {
+            (EnvValue::Bool(b), EnvBoolFormat::Numeric) => if *b { "1" } else { "0" }.to_string(),
+            _ => self.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for EnvValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EnvValue::Bool(b) => write!(f, "{}", b),
+            EnvValue::Number(n) => write!(f, "{}", n),
+            EnvValue::String(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+impl From<String> for EnvValue {
+    fn from(value: String) -> Self {
+        EnvValue::String(value)
+    }
+}
+
+impl From<&str> for EnvValue {
+    fn from(value: &str) -> Self {
+        EnvValue::String(value.to_string())
+    }
+}
+
+/// Serialization of boolean env values expected by a server
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EnvBoolFormat {
+    /// `"true"` / `"false"`
+    #[default]
+    Literal,
+    /// `"1"` / `"0"`
+    Numeric,
+}
+
+impl EnvBoolFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EnvBoolFormat::Literal => "literal",
+            EnvBoolFormat::Numeric => "numeric",
+        }
+    }
+
+    pub fn from_stored(value: &str) -> Self {
+        match value {
+            "numeric" => EnvBoolFormat::Numeric,
+            _ => EnvBoolFormat::Literal,
+        }
+    }
+}
+
+/// Whether the tool calls of a server reach it, see [`crate::recordings`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CallMode {
+    /// Calls go to the server
+    #[default]
+    Live,
+    /// Calls go to the server and their results are recorded
+    Record,
+    /// Calls are answered from the recordings, the server is not called
+    Replay,
+}
+
+impl CallMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CallMode::Live => "live",
+            CallMode::Record => "record",
+            CallMode::Replay => "replay",
+        }
+    }
+
+    pub fn from_stored(value: &str) -> Option<Self> {
+        match value {
+            "live" => Some(CallMode::Live),
+            "record" => Some(CallMode::Record),
+            "replay" => Some(CallMode::Replay),
+            _ => None,
+        }
+    }
+}
+
+/// What a replayed call without a recording does
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplayMiss {
+    /// Fail the call
+    #[default]
+    Error,
+    /// Call the server
+    PassThrough,
+}
+
+impl ReplayMiss {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReplayMiss::Error => "error",
+            ReplayMiss::PassThrough => "pass_through",
+        }
+    }
+
+    pub fn from_stored(value: &str) -> Option<Self> {
+        match value {
+            "error" => Some(ReplayMiss::Error),
+            "pass_through" => Some(ReplayMiss::PassThrough),
+            _ => None,
+        }
+    }
+}
+
+/// Call mode of a server
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServerCallMode {
+    pub mode: CallMode,
     #[serde(default)]
-    pub default: Option<String>,
+    pub on_miss: ReplayMiss,
+}
+
+/// Scheduling priority of a server process
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProcessPriority {
+    Low,
+    #[default]
+    Normal,
+    High,
+}
+
+impl ProcessPriority {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProcessPriority::Low => "low",
+            ProcessPriority::Normal => "normal",
+            ProcessPriority::High => "high",
+        }
+    }
+
+    pub fn from_stored(value: &str) -> Option<Self> {
+        match value {
+            "low" => Some(ProcessPriority::Low),
+            "normal" => Some(ProcessPriority::Normal),
+            "high" => Some(ProcessPriority::High),
+            _ => None,
+        }
+    }
+
+    /// Nice value used on Unix, raising the priority usually requires privileges
+    pub fn nice_value(&self) -> i32 {
+        match self {
+            ProcessPriority::Low => 10,
+            ProcessPriority::Normal => 0,
+            ProcessPriority::High => -5,
+        }
+    }
+
+    /// Priority class used on Windows
+    pub fn windows_priority_class(&self) -> &'static str {
+        match self {
+            ProcessPriority::Low => "BelowNormal",
+            ProcessPriority::Normal => "Normal",
+            ProcessPriority::High => "AboveNormal",
+        }
+    }
+
+    /// Relative CPU weight given to docker containers (`--cpu-shares`)
+    pub fn docker_cpu_shares(&self) -> u32 {
+        match self {
+            ProcessPriority::Low => 512,
+            ProcessPriority::Normal => 1024,
+            ProcessPriority::High => 2048,
+        }
+    }
+}
+
+/// When a server is started
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StartMode {
+    /// With Dockmaster
+    #[default]
+    Eager,
+    /// On the first call to one of its tools, its tools saved by the last run are
+    /// listed until then
+    Lazy,
+}
+
+impl StartMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StartMode::Eager => "eager",
+            StartMode::Lazy => "lazy",
+        }
+    }
+
+    pub fn from_stored(value: &str) -> Option<Self> {
+        match value {
+            "eager" => Some(StartMode::Eager),
+            "lazy" => Some(StartMode::Lazy),
+            _ => None,
+        }
+    }
+}
+
+/// Sandbox options of a server, their presence enables restricted mode
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SandboxOptions {
+    /// Absolute paths the server may write to, besides temporary directories
     #[serde(default)]
-    pub required: bool,
+    pub allowed_paths: Vec<String>,
+    /// Whether the server may access the network
+    #[serde(default)]
+    pub network: bool,
 }
 
 // ToolConfig struct has been removed and merged into ToolConfiguration
@@ -114,13 +537,471 @@ pub struct ServerDefinition {
     pub distribution: Option<Distribution>,
 }
 
+/// An installed server and what its process is doing
+///
+/// `enabled` says whether the server may run at all and `desired_running` whether the
+/// user wants it running, which survives restarts of the app:
+///
+/// | enabled | desired_running | status                                          |
+/// |---------|-----------------|-------------------------------------------------|
+/// | false   | any             | `stopped`, the server is never started          |
+/// | true    | false           | `stopped`, stopped by the user                  |
+/// | true    | true            | `starting`, `running`, or `error` if it failed  |
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct RuntimeServer {
     #[serde(flatten)]
     pub definition: ServerDefinition,
     pub id: ServerId,
     pub status: ServerStatus,
+    /// Whether the user wants the server running, restored on the next launch
+    #[serde(default = "default_desired_running")]
+    pub desired_running: bool,
     pub tool_count: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<ServerProvenance>,
+    /// Whether a model installed the server through the built-in tools
+    #[serde(default)]
+    pub llm_initiated: bool,
+    /// Whether its tool calls are recorded or replayed
+    #[serde(default)]
+    pub call_mode: ServerCallMode,
+    /// What the server said about itself when it last started
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_info: Option<ServerInitInfo>,
+    /// How long it has been up and how often it restarted lately
+    #[serde(default)]
+    pub uptime: ServerUptime,
+    /// Outcome of its health checks while it runs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub health: Option<ServerHealth>,
+}
+
+fn default_desired_running() -> bool {
+    true
+}
+
+/// Name, version and instructions a server gave in its last initialize handshake
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServerInitInfo {
+    pub name: String,
+    pub version: String,
+    /// MCP protocol version the server answered with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub protocol_version: Option<String>,
+    /// How to use the server, meant for the models
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instructions: Option<String>,
+    /// When the handshake happened, in milliseconds since the Unix epoch
+    pub initialized_at: u64,
+    /// Capabilities the server declared, like `tools` or `resources`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capabilities: Option<Value>,
+}
+
+impl ServerInitInfo {
+    /// Read the result of an initialize request, `None` when it has no `serverInfo`
+    pub fn from_initialize_result(result: &Value, initialized_at: u64) -> Option<Self> {
+        let server_info = result.get("serverInfo")?;
+        let text = |value: Option<&Value>| {
+            value
+                .and_then(Value::as_str)
+                .map(str::trim)
+                .filter(|text| !text.is_empty())
+                .map(str::to_string)
+        };
+        Some(Self {
+            name: text(server_info.get("name")).unwrap_or_default(),
+            version: text(server_info.get("version")).unwrap_or_default(),
+            protocol_version: text(result.get("protocolVersion")),
+            instructions: text(result.get("instructions")),
+            initialized_at,
+            capabilities: result
+                .get("capabilities")
+                .filter(|c| c.is_object())
+                .cloned(),
+        })
+    }
+
+    /// Whether the server declared tool support, true when it declared no
+    /// capabilities at all
+    pub fn declares_tools(&self) -> bool {
+        self.capabilities
+            .as_ref()
+            .is_none_or(|capabilities| capabilities.get("tools").is_some())
+    }
+}
+
+/// Process spawned for a server, recorded to detect orphans after a crash
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SpawnedProcessRecord {
+    pub server_id: String,
+    /// Value of the spawn id env var set on the process and inherited by its children
+    pub spawn_id: String,
+    pub pid: Option<u32>,
+    /// Start time of the process, in seconds since the Unix epoch
+    pub start_time: Option<u64>,
+    pub command: Option<String>,
+    /// Name given to the container of docker servers
+    pub container_name: Option<String>,
+}
+
+/// Location, size and schema version of the database
+#[derive(Clone, Debug, Serialize)]
+#[non_exhaustive]
+pub struct DatabaseInfo {
+    pub path: String,
+    pub size_bytes: u64,
+    /// Most recent applied migration
+    pub schema_version: Option<String>,
+    pub server_count: i64,
+    pub tool_count: i64,
+}
+
+/// Orphaned server processes cleaned up at startup
+#[derive(Clone, Debug, Default, Serialize)]
+#[non_exhaustive]
+pub struct OrphanCleanupReport {
+    pub processes_terminated: usize,
+    pub containers_removed: usize,
+    /// Servers that had orphans
+    pub servers: Vec<String>,
+}
+
+impl OrphanCleanupReport {
+    pub fn is_empty(&self) -> bool {
+        self.processes_terminated == 0 && self.containers_removed == 0
+    }
+}
+
+/// Effective process settings and current resource usage of a server
+#[derive(Clone, Debug, Serialize)]
+#[non_exhaustive]
+pub struct ServerResources {
+    pub server_id: String,
+    pub priority: ProcessPriority,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu_limit: Option<u32>,
+    /// Memory limit in megabytes, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_memory_mb: Option<u32>,
+    /// Whether the limits are enforced by docker rather than on the local process
+    pub applied_by_docker: bool,
+    pub running: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pid: Option<u32>,
+    /// Number of local processes of the server, the docker client for docker servers
+    pub process_count: usize,
+    /// CPU usage summed over the processes, 100 is one full core
+    pub cpu_percent: f32,
+    pub memory_bytes: u64,
+}
+
+/// How a server got installed
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProvenanceSource {
+    /// Installed from the registry
+    Registry,
+    /// Imported from a repository URL
+    Url,
+    /// Registered with a hand-written configuration
+    Manual,
+    /// Registered by a model through the built-in tools
+    Llm,
+    /// Imported from another MCP client's configuration
+    Import,
+    /// Declared in a configuration file, see [`crate::config_file`]
+    #[serde(rename = "config_file")]
+    ConfigFile,
+}
+
+impl ProvenanceSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProvenanceSource::Registry => "registry",
+            ProvenanceSource::Url => "url",
+            ProvenanceSource::Manual => "manual",
+            ProvenanceSource::Llm => "llm",
+            ProvenanceSource::Import => "import",
+            ProvenanceSource::ConfigFile => "config_file",
+        }
+    }
+
+    pub fn from_stored(value: &str) -> Option<Self> {
+        match value {
+            "registry" => Some(ProvenanceSource::Registry),
+            "url" => Some(ProvenanceSource::Url),
+            "manual" => Some(ProvenanceSource::Manual),
+            "llm" => Some(ProvenanceSource::Llm),
+            "import" => Some(ProvenanceSource::Import),
+            "config_file" => Some(ProvenanceSource::ConfigFile),
+            _ => None,
+        }
+    }
+}
+
+/// Where an installed server came from
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServerProvenance {
+    pub source: ProvenanceSource,
+    /// Registry id or URL the server was installed from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_id: Option<String>,
+    /// Name of the MCP client whose session asked for the install
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client: Option<String>,
+    /// Install time, in milliseconds since the Unix epoch
+    #[serde(default)]
+    pub installed_at: u64,
+    /// Version of the app that performed the install
+    #[serde(default)]
+    pub app_version: String,
+    /// Registry source other than the default registry the server was installed from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub registry_source: Option<String>,
+}
+
+impl ServerProvenance {
+    pub fn new(source: ProvenanceSource, source_id: Option<String>) -> Self {
+        Self {
+            source,
+            source_id,
+            client: None,
+            installed_at: 0,
+            app_version: String::new(),
+            registry_source: None,
+        }
+    }
+
+    /// Provenance of a registration a model asked for from an MCP session
+    pub fn llm(client: Option<String>, source_id: Option<String>) -> Self {
+        Self {
+            client,
+            ..Self::new(ProvenanceSource::Llm, source_id)
+        }
+    }
+
+    /// Set the install time and app version to the current ones
+    pub fn stamped(mut self) -> Self {
+        self.installed_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        self.app_version = env!("CARGO_PKG_VERSION").to_string();
+        self
+    }
+
+    pub fn is_llm_initiated(&self) -> bool {
+        self.source == ProvenanceSource::Llm
+    }
+}
+
+/// Management action a model can request through the built-in tools
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GatedAction {
+    Register,
+    Uninstall,
+    Configure,
+    /// A call of a server tool whose annotations say it may destroy data
+    #[serde(rename = "destructive_tool")]
+    DestructiveTool,
+}
+
+impl GatedAction {
+    pub const ALL: [GatedAction; 4] = [
+        GatedAction::Register,
+        GatedAction::Uninstall,
+        GatedAction::Configure,
+        GatedAction::DestructiveTool,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GatedAction::Register => "register",
+            GatedAction::Uninstall => "uninstall",
+            GatedAction::Configure => "configure",
+            GatedAction::DestructiveTool => "destructive_tool",
+        }
+    }
+
+    pub fn from_stored(value: &str) -> Option<Self> {
+        match value {
+            "register" => Some(GatedAction::Register),
+            "uninstall" => Some(GatedAction::Uninstall),
+            "configure" => Some(GatedAction::Configure),
+            "destructive_tool" => Some(GatedAction::DestructiveTool),
+            _ => None,
+        }
+    }
+
+    /// Policy applied when the user hasn't chosen one
+    ///
+    /// Installing and calling tools are allowed, while removing or reconfiguring a
+    /// server needs the user's confirmation.
+    pub fn default_policy(&self) -> ActionPolicy {
+        match self {
+            GatedAction::Register | GatedAction::DestructiveTool => ActionPolicy::Allow,
+            GatedAction::Uninstall | GatedAction::Configure => ActionPolicy::Confirm,
+        }
+    }
+}
+
+/// Action recorded in the audit log
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    Register,
+    Uninstall,
+    Configure,
+    /// A tool call seen by a hook, see [`crate::hooks`], or gated as destructive
+    ToolCall,
+}
+
+impl AuditAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::Register => "register",
+            AuditAction::Uninstall => "uninstall",
+            AuditAction::Configure => "configure",
+            AuditAction::ToolCall => "tool_call",
+        }
+    }
+
+    pub fn from_stored(value: &str) -> Option<Self> {
+        match value {
+            "tool_call" => Some(AuditAction::ToolCall),
+            _ => GatedAction::from_stored(value).map(AuditAction::from),
+        }
+    }
+}
+
+impl From<GatedAction> for AuditAction {
+    fn from(action: GatedAction) -> Self {
+        match action {
+            GatedAction::Register => AuditAction::Register,
+            GatedAction::Uninstall => AuditAction::Uninstall,
+            GatedAction::Configure => AuditAction::Configure,
+            GatedAction::DestructiveTool => AuditAction::ToolCall,
+        }
+    }
+}
+
+impl PartialEq<GatedAction> for AuditAction {
+    fn eq(&self, other: &GatedAction) -> bool {
+        *self == AuditAction::from(*other)
+    }
+}
+
+/// What happens when a model requests a management action
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ActionPolicy {
+    Allow,
+    Deny,
+    /// Wait for the user to approve the action
+    Confirm,
+}
+
+impl ActionPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ActionPolicy::Allow => "allow",
+            ActionPolicy::Deny => "deny",
+            ActionPolicy::Confirm => "confirm",
+        }
+    }
+
+    /// Parse a policy as stored in the settings
+    pub fn from_stored(value: &str) -> Option<Self> {
+        match value {
+            "allow" => Some(ActionPolicy::Allow),
+            "deny" => Some(ActionPolicy::Deny),
+            "confirm" => Some(ActionPolicy::Confirm),
+            _ => None,
+        }
+    }
+}
+
+/// Decision taken on a management action requested by a model, or by a hook on a tool call
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditDecision {
+    /// Allowed by the action policy
+    Allowed,
+    /// Denied by the action policy
+    Denied,
+    /// Approved by the user
+    Approved,
+    /// Rejected by the user
+    Rejected,
+    /// Changed by a hook
+    Modified,
+}
+
+impl AuditDecision {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditDecision::Allowed => "allowed",
+            AuditDecision::Denied => "denied",
+            AuditDecision::Approved => "approved",
+            AuditDecision::Rejected => "rejected",
+            AuditDecision::Modified => "modified",
+        }
+    }
+
+    pub fn from_stored(value: &str) -> Option<Self> {
+        match value {
+            "allowed" => Some(AuditDecision::Allowed),
+            "denied" => Some(AuditDecision::Denied),
+            "approved" => Some(AuditDecision::Approved),
+            "rejected" => Some(AuditDecision::Rejected),
+            "modified" => Some(AuditDecision::Modified),
+            _ => None,
+        }
+    }
+}
+
+/// Entry of the audit log of management actions requested by models and of hook decisions
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// Assigned by the database, 0 for entries not saved yet
+    pub id: i64,
+    /// Time of the decision, in milliseconds since the Unix epoch
+    pub timestamp: u64,
+    pub action: AuditAction,
+    pub server_id: Option<String>,
+    /// MCP client that requested the action, as announced in `initialize`
+    pub client: Option<String>,
+    pub decision: AuditDecision,
+    pub detail: Option<String>,
+}
+
+impl AuditEntry {
+    pub fn new(
+        action: impl Into<AuditAction>,
+        server_id: Option<String>,
+        client: Option<String>,
+        decision: AuditDecision,
+    ) -> Self {
+        Self {
+            id: 0,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0),
+            action: action.into(),
+            server_id,
+            client,
+            decision,
+            detail: None,
+        }
+    }
+
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
 }
 
 /// MCP server registration request
@@ -132,6 +1013,75 @@ pub struct ServerRegistrationRequest {
     pub tools_type: String, // "node", "python", "docker"
     pub configuration: Option<ServerConfiguration>,
     pub distribution: Option<Distribution>,
+    /// Where the registration comes from, manual when not set
+    #[serde(default)]
+    pub provenance: Option<ServerProvenance>,
+    /// Env templates under `env`, as older payloads declared them
+    #[serde(default)]
+    pub authentication: Option<Value>,
+    /// Start the server once registered, true unless set to false to configure it first
+    #[serde(default, alias = "auto_start")]
+    pub enabled: Option<bool>,
+}
+
+/// Import of a server from its GitHub repository
+#[derive(Debug, Deserialize)]
+pub struct ServerImportRequest {
+    pub url: String,
+    /// Start the server once imported, true unless set to false to configure it first
+    #[serde(default, alias = "auto_start")]
+    pub enabled: Option<bool>,
+}
+
+impl ServerRegistrationRequest {
+    /// Make `configuration.env` the only env of the request
+    ///
+    /// Keys are made canonical and the env declared in `authentication` is moved into
+    /// `configuration.env`, whose entries win. Returns warnings for the other fields of
+    /// `authentication`, which are dropped.
+    pub fn resolve_env(&mut self) -> Result<Vec<String>, String> {
+        if let Some(configuration) = &mut self.configuration {
+            configuration.canonicalize_env()?;
+        }
+        let Some(authentication) = self.authentication.take() else {
+            return Ok(Vec::new());
+        };
+        let Value::Object(mut authentication) = authentication else {
+            return match authentication {
+                Value::Null => Ok(Vec::new()),
+                _ => Err("authentication must be an object".to_string()),
+            };
+        };
+
+        let env = match authentication.remove("env") {
+            None | Some(Value::Null) => HashMap::new(),
+            Some(Value::Object(env)) => {
+                let mut parsed = HashMap::new();
+                for (key, value) in env {
+                    let environment = ServerEnvironment::from_value(&value).map_err(|e| {
+                        format!("Invalid env variable {} in authentication: {}", key, e)
+                    })?;
+                    parsed.insert(key, environment);
+                }
+                canonicalize_env(parsed)?
+            }
+            Some(_) => return Err("authentication.env must be an object".to_string()),
+        };
+        if !env.is_empty() {
+            let configuration = self.configuration.get_or_insert_with(Default::default);
+            let configured = configuration.env.get_or_insert_with(HashMap::new);
+            for (key, environment) in env {
+                configured.entry(key).or_insert(environment);
+            }
+        }
+
+        let mut ignored: Vec<String> = authentication.into_iter().map(|(key, _)| key).collect();
+        ignored.sort();
+        Ok(ignored
+            .into_iter()
+            .map(|key| format!("Field 'authentication.{}' was ignored", key))
+            .collect())
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -144,7 +1094,22 @@ pub struct IsProcessRunningRequest {
 pub struct ServerRegistrationResponse {
     pub success: bool,
     pub message: String,
-    pub tool_id: Option<String>,
+    pub server_id: Option<String>,
+    /// Status of the server once registered, stopped when it was registered disabled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<ServerStatus>,
+    /// Tools the server listed once started
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_count: Option<usize>,
+    /// Milliseconds the server took to start and answer the initialize handshake
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub startup_ms: Option<u64>,
+    /// Non-fatal problems with the request, such as unrecognized fields
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+    /// Id of the install operation whose progress is published as events
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operation_id: Option<String>,
 }
 
 /// MCP tool execution request
@@ -154,17 +1119,34 @@ pub struct ToolExecutionRequest {
     pub parameters: Value,
 }
 
+/// Key of the `_meta` entry listing the warnings of a tool call in its MCP result
+pub const TOOL_WARNINGS_META_KEY: &str = "dockmaster/warnings";
+
 /// MCP tool execution response
 #[derive(Serialize, Debug)]
 pub struct ToolExecutionResponse {
     pub success: bool,
     pub result: Option<Value>,
     pub error: Option<String>,
+    /// Problems with the result that didn't fail the call, such as a result not
+    /// matching the output schema of the tool
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+    /// Set when a hook rejected the call
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub policy_error: Option<PolicyError>,
+    /// Set when the call was refused by the rate limit of the tool
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rate_limited: Option<RateLimited>,
+    /// Set when the client cancelled the call before the server answered
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub cancelled: bool,
 }
 
 /// MCP tool update request
 #[derive(Deserialize)]
 pub struct ServerUpdateRequest {
+    #[serde(alias = "tool_id")]
     pub server_id: String,
     pub enabled: bool,
 }
@@ -176,16 +1158,48 @@ pub struct ToolUpdateResponse {
     pub message: String,
 }
 
+/// Outcome of a bulk operation for one server
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct BulkServerResult {
+    pub server_id: String,
+    pub success: bool,
+    pub message: String,
+}
+
+/// Response of a bulk operation, one result per requested server
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct BulkServerResponse {
+    pub results: Vec<BulkServerResult>,
+    /// Whether some servers didn't finish in time, see [`crate::utils::fan_out`]
+    #[serde(default)]
+    pub partial: bool,
+    /// Servers that didn't finish in time, still being started or stopped
+    #[serde(default)]
+    pub timed_out: Vec<String>,
+}
+
 /// MCP tool config update request
+///
+/// Values may be plain strings, numbers or booleans, or env template objects.
 #[derive(Deserialize)]
 pub struct ServerConfigUpdateRequest {
+    #[serde(alias = "tool_id")]
     pub server_id: String,
-    pub config: HashMap<String, String>,
+    pub config: HashMap<String, Value>,
+    /// Whether the server inherits the environment of Dockmaster, unchanged when None
+    #[serde(default)]
+    pub inherit_env: Option<bool>,
+    /// Directory the server is started in, unchanged when None and removed when empty
+    #[serde(default)]
+    pub cwd: Option<String>,
 }
 
 /// MCP tool uninstall request
 #[derive(Deserialize)]
 pub struct ToolUninstallRequest {
+    #[serde(alias = "tool_id")]
     pub server_id: String,
 }
 
@@ -194,6 +1208,8 @@ pub struct ToolUninstallRequest {
 pub struct ServerUninstallResponse {
     pub success: bool,
     pub message: String,
+    /// Tools of the server that are no longer listed
+    pub tools_removed: usize,
 }
 
 /// MCP server discovery request
@@ -209,6 +1225,10 @@ pub struct Distribution {
     pub package: String,
 }
 
+/// Schema of one argument of a tool
+///
+/// Keywords without a field of their own, like `items`, `enum`, `oneOf` or `$ref`, are
+/// kept in `additional_fields`, so the schema reaches clients as the server wrote it.
 #[derive(Clone, Debug, Serialize, Deserialize, Default)]
 pub struct InputSchemaProperty {
     #[serde(default)]
@@ -229,32 +1249,36 @@ pub struct InputSchemaProperty {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub format: Option<String>,
 
-    #[serde(default)]
+    // A `null` default is kept as `Some(Value::Null)`
+    #[serde(default, deserialize_with = "present_value")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub default: Option<Value>,
 
+    // Bounds keep their JSON number, `1` isn't turned into `1.0`
     #[serde(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub minimum: Option<f64>,
+    pub minimum: Option<Value>,
 
     #[serde(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub maximum: Option<f64>,
+    pub maximum: Option<Value>,
 
+    // A number, or a boolean in draft 4 schemas
     #[serde(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(rename = "exclusiveMinimum")]
-    pub exclusive_minimum: Option<f64>,
+    pub exclusive_minimum: Option<Value>,
 
     #[serde(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(rename = "exclusiveMaximum")]
-    pub exclusive_maximum: Option<f64>,
+    pub exclusive_maximum: Option<Value>,
 
     #[serde(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
+    // A boolean, or the schema of the properties not listed
     #[serde(rename = "additionalProperties")]
-    pub additional_properties: Option<bool>,
+    pub additional_properties: Option<Value>,
 
     // Support for allOf arrays
     #[serde(default)]
@@ -269,6 +1293,8 @@ pub struct InputSchemaProperty {
     pub additional_fields: HashMap<String, Value>,
 }
 
+/// Input schema of a tool, `$defs` and the other keywords without a field of their own
+/// are kept in `additional_fields`
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct InputSchema {
     #[serde(default)]
@@ -279,7 +1305,9 @@ pub struct InputSchema {
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub required: Vec<String>,
 
+    // Left out when the server didn't set it, like schemas made only of `anyOf`
     #[serde(default)]
+    #[serde(skip_serializing_if = "String::is_empty")]
     pub r#type: String,
 
     // Additional fields from the JSON example - all made optional with default values
@@ -293,8 +1321,9 @@ pub struct InputSchema {
 
     #[serde(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
+    // A boolean, or the schema of the properties not listed
     #[serde(rename = "additionalProperties")]
-    pub additional_properties: Option<bool>,
+    pub additional_properties: Option<Value>,
 
     #[serde(rename = "$schema")]
     #[serde(default)]
@@ -323,25 +1352,213 @@ impl Default for InputSchema {
     }
 }
 
+/// Deserialize a field that is `Some` whenever it is present, `null` included
+fn present_value<'de, D>(deserializer: D) -> Result<Option<Value>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Value::deserialize(deserializer).map(Some)
+}
+
+/// A tool as listed by `tools/list`
+///
+/// Types returned by the MCP methods (`initialize`, `tools/*`) use the camelCase names
+/// of the MCP specification; the snake_case names are still accepted when reading.
+/// Management methods and the database keep snake_case.
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ServerToolInfo {
     pub id: String,
     pub name: String,
     pub description: String,
-    #[serde(default)]
-    #[serde(rename = "inputSchema")]
+    #[serde(default, alias = "input_schema")]
     pub input_schema: Option<InputSchema>,
+    #[serde(alias = "server_id")]
     pub server_id: String,
-    #[serde(default)]
+    #[serde(default, alias = "proxy_id")]
     pub proxy_id: Option<String>,
-    #[serde(default = "default_is_active")]
+    #[serde(default = "default_is_active", alias = "is_active")]
     pub is_active: bool,
+    /// JSON Schema of the `structuredContent` the tool returns, when it declares one
+    #[serde(
+        default,
+        alias = "output_schema",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub output_schema: Option<Value>,
+    /// Hints the server gives about the tool's behavior, with the user's overrides
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<ToolAnnotations>,
 }
 
 fn default_is_active() -> bool {
     true
 }
 
+/// Hints about the behavior of a tool, as defined by the MCP specification
+///
+/// Clients use them to decide when to ask the user before a call. They are hints given
+/// by the server, which the user can add or correct with a [`ToolAnnotationOverride`].
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolAnnotations {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub read_only_hint: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub destructive_hint: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idempotent_hint: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub open_world_hint: Option<bool>,
+    /// Hints this version doesn't know, passed on as they are
+    #[serde(flatten)]
+    pub other: BTreeMap<String, Value>,
+}
+
+impl ToolAnnotations {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// These annotations with the hints set in `overrides` replacing theirs
+    pub fn merged(&self, overrides: &ToolAnnotations) -> ToolAnnotations {
+        let mut other = self.other.clone();
+        other.extend(overrides.other.clone());
+        ToolAnnotations {
+            title: overrides.title.clone().or_else(|| self.title.clone()),
+            read_only_hint: overrides.read_only_hint.or(self.read_only_hint),
+            destructive_hint: overrides.destructive_hint.or(self.destructive_hint),
+            idempotent_hint: overrides.idempotent_hint.or(self.idempotent_hint),
+            open_world_hint: overrides.open_world_hint.or(self.open_world_hint),
+            other,
+        }
+    }
+
+    /// Whether the tool says it may destroy data
+    ///
+    /// Only an explicit `destructiveHint` counts: the specification's default of true
+    /// would flag every tool that has no annotations.
+    pub fn is_destructive(&self) -> bool {
+        self.destructive_hint == Some(true) && self.read_only_hint != Some(true)
+    }
+}
+
+/// Annotations set by the user on a tool, replacing the hints of its server
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ToolAnnotationOverride {
+    pub server_id: String,
+    pub tool_name: String,
+    pub annotations: ToolAnnotations,
+}
+
+/// How often a tool was called through the proxy since Dockmaster started
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolUsage {
+    pub calls: u64,
+    /// Calls that failed before the server returned a result
+    pub errors: u64,
+    /// Sum of the call durations, in milliseconds
+    pub total_duration_ms: u64,
+    /// Unix time of the last call, in seconds
+    pub last_called_at: Option<u64>,
+    /// Calls refused by the rate limit of the tool, not counted in `calls`
+    #[serde(default)]
+    pub rate_limited: u64,
+}
+
+/// Counts shown on the home screen, see [`McpCoreDashboardExt`]
+///
+/// [`McpCoreDashboardExt`]: crate::core::mcp_core_dashboard_ext::McpCoreDashboardExt
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct DashboardSummary {
+    pub servers: usize,
+    pub running: usize,
+    pub starting: usize,
+    pub stopped: usize,
+    pub errored: usize,
+    /// Servers waiting for required environment variables
+    #[serde(default)]
+    pub needs_configuration: usize,
+    /// Tools of every server
+    pub tools: usize,
+    /// Tools hidden from clients by the visibility switch
+    pub tools_hidden: usize,
+    /// Tools that are inactive or belong to a disabled server
+    pub tools_disabled: usize,
+    /// Tool calls in the last hour
+    pub calls_last_hour: usize,
+    /// The most called tools since Dockmaster started
+    pub top_tools: Vec<ToolCallCount>,
+    /// Servers in error, with the start of their last error
+    pub servers_in_error: Vec<ServerErrorSummary>,
+    /// Estimated tokens of the tool list served to clients
+    #[serde(default)]
+    pub tools_tokens: usize,
+    /// Servers to hide to bring the tool list under its budget, largest first
+    #[serde(default)]
+    pub servers_over_budget: Vec<String>,
+    /// Restarts of every server within the last hour
+    #[serde(default)]
+    pub restarts_last_hour: usize,
+    /// Servers restarted within the last hour, most restarted first
+    #[serde(default)]
+    pub flapping_servers: Vec<ServerRestartCount>,
+}
+
+/// How often a server restarted within the last hour
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServerRestartCount {
+    pub server_id: String,
+    pub restarts: usize,
+    /// Seconds since its running process started
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uptime_secs: Option<u64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolCallCount {
+    pub server_id: String,
+    pub tool_name: String,
+    pub calls: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServerErrorSummary {
+    pub server_id: String,
+    pub name: String,
+    pub error: String,
+}
+
+/// Everything known about one tool, returned by `tools/describe`
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolDescription {
+    /// Id clients call the tool with, `server_id:tool_name`
+    pub proxy_id: String,
+    pub server_id: String,
+    pub id: String,
+    pub name: String,
+    /// Description as the server reported it, never truncated
+    pub description: String,
+    pub input_schema: Option<InputSchema>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_schema: Option<Value>,
+    /// Default values the input schema declares, by argument
+    pub defaults: BTreeMap<String, Value>,
+    pub is_active: bool,
+    /// Status of the server providing the tool
+    pub server_status: ServerStatus,
+    pub usage: ToolUsage,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<RateLimit>,
+    /// Hints about the tool's behavior, with the user's overrides
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<ToolAnnotations>,
+}
+
 // Custom serializer for the Error variant to format as "Error: message"
 fn serialize_error<S>(error_message: &String, serializer: S) -> Result<S::Ok, S::Error>
 where
@@ -393,10 +1610,13 @@ pub struct RegistryTool {
     pub config: ServerConfiguration,
     pub categories: Vec<String>,
     pub tags: Vec<String>,
+    /// Registry source the tool was listed by, see [`crate::registry::registry_sources`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
 }
 
 /// Response for registry tools listing
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct RegistryToolsResponse {
     pub count: u32,
     pub version: u64,
@@ -405,16 +1625,25 @@ pub struct RegistryToolsResponse {
     pub tools: Vec<RegistryTool>,
 }
 
+/// Request of `server/config`, the server id may be sent as `tool_id` by older clients
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ConfigUpdateRequest {
-    pub tool_id: String,
-    pub config: HashMap<String, String>,
+    #[serde(alias = "tool_id")]
+    pub server_id: String,
+    pub config: HashMap<String, Value>,
+    #[serde(default)]
+    pub inherit_env: Option<bool>,
+    #[serde(default)]
+    pub cwd: Option<String>,
 }
 
 /// Response for server tools listing
 #[derive(Debug, Serialize)]
 pub struct ServerToolsResponse {
     pub tools: Vec<ServerToolInfo>,
+    /// Whether the tools are those of the last run, served while the servers start
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub stale: bool,
 }
 
 /// Common error response structure
@@ -424,32 +1653,110 @@ pub struct ErrorResponse {
     pub message: String,
 }
 
+/// A tool as the MCP specification describes it, in `tools/list` results
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SpecTool {
+    name: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default, alias = "input_schema")]
+    input_schema: Option<Value>,
+    #[serde(default, alias = "output_schema")]
+    output_schema: Option<Value>,
+    #[serde(default)]
+    annotations: Option<Value>,
+}
+
 impl ServerToolInfo {
-    /// Create a new ServerToolInfo from a JSON value
-    pub fn from_value(value: Value, server_id: String) -> Result<ServerToolInfo, String> {
-        // Generate id from name before deserializing
-        let name = value
-            .get("name")
-            .and_then(|v| v.as_str())
-            .ok_or("missing name field")?;
+    /// Build a tool of `server_id` from a tool as listed by the MCP `tools/list` method
+    ///
+    /// The tool is keyed by its name, see [`crate::models::tool_id`]. A missing
+    /// description is left empty, anything else that doesn't fit is an error naming the
+    /// tool and the field.
+    pub fn from_spec_value(server_id: &str, value: Value) -> Result<ServerToolInfo, String> {
+        let tool: SpecTool = serde_json::from_value(value)
+            .map_err(|e| format!("Invalid tool of server '{}': {}", server_id, e))?;
+        if tool.name.trim().is_empty() {
+            return Err(format!(
+                "Invalid tool of server '{}': empty name",
+                server_id
+            ));
+        }
+        let invalid = |field: &str, reason: String| {
+            format!(
+                "Invalid {} of tool '{}' of server '{}': {}",
+                field, tool.name, server_id, reason
+            )
+        };
+
+        let object = |schema: Option<Value>, field: &str| match schema {
+            None | Some(Value::Null) => Ok(None),
+            Some(schema @ Value::Object(_)) => Ok(Some(schema)),
+            Some(other) => Err(invalid(field, format!("expected an object, got {}", other))),
+        };
+        let input_schema = object(tool.input_schema, "inputSchema")?
+            .map(serde_json::from_value::<InputSchema>)
+            .transpose()
+            .map_err(|e| invalid("inputSchema", e.to_string()))?;
+        let output_schema = object(tool.output_schema, "outputSchema")?;
+        let annotations = object(tool.annotations, "annotations")?
+            .map(serde_json::from_value::<ToolAnnotations>)
+            .transpose()
+            .map_err(|e| invalid("annotations", e.to_string()))?;
+
+        Ok(ServerToolInfo {
+            id: tool.name.clone(),
+            name: tool.name,
+            description: tool.description.unwrap_or_default(),
+            input_schema,
+            proxy_id: Some(proxy_tool_id(server_id, &tool.name)),
+            server_id: server_id.to_string(),
+            is_active: true,
+            output_schema,
+            annotations,
+        })
+    }
 
-        let id = name
+    /// Create a new ServerToolInfo from a JSON value, with an id made from its name
+    pub fn from_value(value: Value, server_id: String) -> Result<ServerToolInfo, String> {
+        let mut tool = Self::from_spec_value(&server_id, value)?;
+        tool.id = tool
+            .name
             .to_lowercase()
             .chars()
             .map(|c| if c.is_alphanumeric() { c } else { '_' })
             .collect();
+        Ok(tool)
+    }
 
-        // Create mutable copy and insert the generated fields
-        let mut obj = value;
+    /// Key a tool saved by an older version by its name, see [`crate::models::tool_id`]
+    ///
+    /// An empty id is replaced by the name and the proxy id is made from the name,
+    /// built-in tools have none.
+    pub fn normalize_ids(&mut self) {
+        if self.id.trim().is_empty() {
+            self.id = self.name.clone();
+        }
+        self.proxy_id = (self.server_id != BUILTIN_SERVER_ID)
+            .then(|| proxy_tool_id(&self.server_id, &self.name));
+    }
 
-        obj.as_object_mut()
-            .ok_or("value must be an object")?
-            .insert("id".to_string(), Value::String(id));
-        obj.as_object_mut()
-            .unwrap()
-            .insert("server_id".to_string(), Value::String(server_id));
+    /// Apply the user's annotations of this tool over its server's
+    pub fn apply_annotation_overrides(&mut self, overrides: &[ToolAnnotationOverride]) {
+        let Some(tool_override) = overrides
+            .iter()
+            .find(|o| o.server_id == self.server_id && o.tool_name == self.name)
+        else {
+            return;
+        };
+        let annotations = self.annotations.take().unwrap_or_default();
+        self.annotations = Some(annotations.merged(&tool_override.annotations));
+    }
 
-        // Now deserialize the complete object
-        serde_json::from_value(obj).map_err(|e| e.to_string())
+    /// The input schema as sent to MCP clients, an empty object schema when unknown
+    pub fn input_schema_value(&self) -> Value {
+        let schema = self.input_schema.clone().unwrap_or_default();
+        serde_json::to_value(schema).unwrap_or_else(|_| serde_json::json!({ "type": "object" }))
     }
 }
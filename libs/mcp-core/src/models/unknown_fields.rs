@@ -0,0 +1,253 @@
+//! Detection of unrecognized keys in incoming JSON payloads.
+//!
+//! Request types keep serde's default behaviour of ignoring unknown fields, because
+//! older clients and newer registry payloads must keep working. Instead, the keys that
+//! would be dropped are collected so typos like `"comand"` or `"evn"` can be reported
+//! back to the caller as warnings.
+
+use std::collections::BTreeSet;
+
+use log::warn;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use super::types::{
+    ConfigUpdateRequest, Distribution, RegistryTool, RegistryToolsResponse, SandboxOptions,
+    ServerConfiguration, ServerEnvironment, ServerImportRequest, ServerRegistrationRequest,
+};
+use crate::tool_limits::ToolFilter;
+
+/// Types that can report the keys of a JSON payload they don't understand
+pub trait KnownFields {
+    /// Field names accepted at the top level of the type
+    const FIELDS: &'static [&'static str];
+
+    /// Unknown keys of `value` as dotted paths below `prefix`, nested known types included
+    fn unknown_fields_at(value: &Value, prefix: &str) -> Vec<String> {
+        unknown_keys(value, Self::FIELDS, prefix)
+    }
+
+    /// Unknown keys of `value` as dotted paths
+    fn unknown_fields(value: &Value) -> Vec<String> {
+        Self::unknown_fields_at(value, "")
+    }
+}
+
+/// Keys of a JSON object that are not in `known`
+pub fn unknown_keys(value: &Value, known: &[&str], prefix: &str) -> Vec<String> {
+    match value.as_object() {
+        Some(obj) => obj
+            .keys()
+            .filter(|key| !known.contains(&key.as_str()))
+            .map(|key| join_path(prefix, key))
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+/// Deserialize `value` and return the unrecognized keys alongside the result
+pub fn parse_with_unknown_fields<T>(value: Value) -> Result<(T, Vec<String>), serde_json::Error>
+where
+    T: KnownFields + DeserializeOwned,
+{
+    let unknown = T::unknown_fields(&value);
+    let parsed = serde_json::from_value(value)?;
+    Ok((parsed, unknown))
+}
+
+/// Human readable warnings for a list of unknown keys
+pub fn unknown_field_warnings(unknown: &[String]) -> Vec<String> {
+    unknown
+        .iter()
+        .map(|key| format!("Unknown field '{}' was ignored", key))
+        .collect()
+}
+
+/// Log fields of a registry payload we don't understand, once per fetch
+///
+/// Registry parsing stays lenient so newer registries keep working with older apps.
+pub fn log_unknown_registry_fields(raw: &Value) {
+    let unknown = RegistryToolsResponse::unknown_fields(raw);
+    if !unknown.is_empty() {
+        warn!(
+            "Registry payload contains {} unknown field(s): {}",
+            unknown.len(),
+            unknown.join(", ")
+        );
+    }
+}
+
+fn join_path(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", prefix, key)
+    }
+}
+
+fn nested<T: KnownFields>(value: &Value, key: &str, prefix: &str) -> Vec<String> {
+    match value.get(key) {
+        Some(inner) => T::unknown_fields_at(inner, &join_path(prefix, key)),
+        None => Vec::new(),
+    }
+}
+
+/// Unknown keys of every env template object in an env map
+fn env_templates(value: Option<&Value>, prefix: &str) -> Vec<String> {
+    let Some(obj) = value.and_then(|v| v.as_object()) else {
+        return Vec::new();
+    };
+    obj.iter()
+        .filter(|(_, entry)| ServerEnvironment::is_template(entry))
+        .flat_map(|(key, entry)| {
+            ServerEnvironment::unknown_fields_at(entry, &join_path(prefix, key))
+        })
+        .collect()
+}
+
+impl KnownFields for ServerRegistrationRequest {
+    const FIELDS: &'static [&'static str] = &[
+        "server_id",
+        "server_name",
+        "description",
+        "tools_type",
+        "configuration",
+        "distribution",
+        // Env templates of older payloads, see `ServerRegistrationRequest::resolve_env`
+        "authentication",
+        "provenance",
+        "enabled",
+        "auto_start",
+    ];
+
+    fn unknown_fields_at(value: &Value, prefix: &str) -> Vec<String> {
+        registration_fields_at(value, Self::FIELDS, prefix)
+    }
+}
+
+/// Unknown keys of a registration payload whose top level accepts `fields`
+///
+/// Registrations by name over JSON-RPC name their top level fields differently but
+/// nest the same configuration, distribution and env templates.
+pub fn registration_fields_at(value: &Value, fields: &[&str], prefix: &str) -> Vec<String> {
+    let mut unknown = unknown_keys(value, fields, prefix);
+    unknown.extend(nested::<ServerConfiguration>(
+        value,
+        "configuration",
+        prefix,
+    ));
+    unknown.extend(nested::<Distribution>(value, "distribution", prefix));
+    unknown.extend(env_templates(
+        value.pointer("/authentication/env"),
+        &join_path(prefix, "authentication.env"),
+    ));
+    unknown
+}
+
+impl KnownFields for ServerImportRequest {
+    const FIELDS: &'static [&'static str] = &["url", "enabled", "auto_start"];
+}
+
+impl KnownFields for ServerConfiguration {
+    const FIELDS: &'static [&'static str] = &[
+        "command",
+        "args",
+        "env",
+        "priority",
+        "cpu_limit",
+        "max_memory_mb",
+        "sandbox",
+        "inherit_env",
+        "env_passthrough",
+        "truncate_descriptions",
+        "strict_output_schema",
+        "share_instructions",
+        "tool_filter",
+        "platforms",
+        "start_mode",
+        "cwd",
+        "lenient_initialize",
+    ];
+
+    fn unknown_fields_at(value: &Value, prefix: &str) -> Vec<String> {
+        let mut unknown = unknown_keys(value, Self::FIELDS, prefix);
+        unknown.extend(env_templates(value.get("env"), &join_path(prefix, "env")));
+        unknown.extend(nested::<SandboxOptions>(value, "sandbox", prefix));
+        unknown.extend(nested::<ToolFilter>(value, "tool_filter", prefix));
+        unknown
+    }
+}
+
+impl KnownFields for ToolFilter {
+    const FIELDS: &'static [&'static str] = &["include", "exclude", "max_tools"];
+}
+
+impl KnownFields for SandboxOptions {
+    const FIELDS: &'static [&'static str] = &["allowed_paths", "network"];
+}
+
+impl KnownFields for ServerEnvironment {
+    const FIELDS: &'static [&'static str] = &["description", "default", "required", "bool_format"];
+}
+
+impl KnownFields for Distribution {
+    const FIELDS: &'static [&'static str] = &["type", "package"];
+}
+
+impl KnownFields for ConfigUpdateRequest {
+    const FIELDS: &'static [&'static str] =
+        &["server_id", "tool_id", "config", "inherit_env", "cwd"];
+
+    fn unknown_fields_at(value: &Value, prefix: &str) -> Vec<String> {
+        let mut unknown = unknown_keys(value, Self::FIELDS, prefix);
+        unknown.extend(env_templates(
+            value.get("config"),
+            &join_path(prefix, "config"),
+        ));
+        unknown
+    }
+}
+
+impl KnownFields for RegistryTool {
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "name",
+        "description",
+        "short_description",
+        "publisher",
+        "is_official",
+        "source_url",
+        "distribution",
+        "license",
+        "runtime",
+        "config",
+        "categories",
+        "tags",
+        "source",
+    ];
+
+    fn unknown_fields_at(value: &Value, prefix: &str) -> Vec<String> {
+        let mut unknown = unknown_keys(value, Self::FIELDS, prefix);
+        unknown.extend(nested::<ServerConfiguration>(value, "config", prefix));
+        unknown.extend(nested::<Distribution>(value, "distribution", prefix));
+        unknown
+    }
+}
+
+impl KnownFields for RegistryToolsResponse {
+    const FIELDS: &'static [&'static str] = &["count", "version", "categories", "tags", "tools"];
+
+    /// Reports tool fields as `tools[].<field>` once, whatever the number of tools
+    fn unknown_fields_at(value: &Value, prefix: &str) -> Vec<String> {
+        let mut unknown: BTreeSet<String> = unknown_keys(value, Self::FIELDS, prefix)
+            .into_iter()
+            .collect();
+        if let Some(tools) = value.get("tools").and_then(|v| v.as_array()) {
+            let tools_prefix = join_path(prefix, "tools[]");
+            for tool in tools {
+                unknown.extend(RegistryTool::unknown_fields_at(tool, &tools_prefix));
+            }
+        }
+        unknown.into_iter().collect()
+    }
+}
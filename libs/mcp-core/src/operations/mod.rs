@@ -0,0 +1,331 @@
+//! Tracked long-running management operations.
+//!
+//! Installs, imports and restarts can run in the background as operations. Each one has
+//! an id, a state and an optional progress percentage, and its state changes are
+//! published on the [`EventBus`] so that clients can follow them without polling.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use serde_json::Value;
+use tokio::task::AbortHandle;
+use uuid::Uuid;
+
+use crate::events::{DockmasterEvent, EventBus};
+
+/// Number of finished operations kept for `operations/get` and `operations/list`
+const FINISHED_RETENTION: usize = 100;
+
+/// Lifecycle of an operation
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OperationState {
+    Queued,
+    Running,
+    /// Waiting for the user to approve the action, see [`crate::approvals`]
+    #[serde(rename = "awaiting_approval")]
+    AwaitingApproval,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+impl OperationState {
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            OperationState::Succeeded | OperationState::Failed | OperationState::Cancelled
+        )
+    }
+}
+
+/// Snapshot of an operation
+#[derive(Clone, Debug, Serialize)]
+pub struct Operation {
+    pub id: String,
+    /// Kind of management action, e.g. `install` or `restart`
+    pub kind: String,
+    /// Server the operation applies to, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub server_id: Option<String>,
+    pub state: OperationState,
+    /// Completion percentage, when the operation reports one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub progress: Option<u8>,
+    /// Result payload of a succeeded operation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    /// Error message of a failed operation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub cancellable: bool,
+    /// Creation time, in milliseconds since the Unix epoch
+    pub created_at: u64,
+    /// Last state change, in milliseconds since the Unix epoch
+    pub updated_at: u64,
+}
+
+struct OperationEntry {
+    operation: Operation,
+    abort_handle: Option<AbortHandle>,
+}
+
+/// Registry of the operations started on an `MCPCore`
+#[derive(Clone)]
+pub struct OperationRegistry {
+    operations: Arc<Mutex<HashMap<String, OperationEntry>>>,
+    event_bus: EventBus,
+}
+
+/// Handle given to a running operation to report its progress
+#[derive(Clone)]
+pub struct OperationHandle {
+    id: String,
+    registry: OperationRegistry,
+}
+
+impl OperationHandle {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Update the completion percentage, capped at 100
+    pub fn set_progress(&self, percent: u8) {
+        self.registry.update(&self.id, |operation| {
+            operation.progress = Some(percent.min(100));
+        });
+    }
+
+    /// Mark the operation as waiting for the user's approval, or as running again
+    pub fn set_awaiting_approval(&self, awaiting: bool) {
+        self.registry.update(&self.id, |operation| {
+            if operation.state.is_finished() {
+                return;
+            }
+            operation.state = if awaiting {
+                OperationState::AwaitingApproval
+            } else {
+                OperationState::Running
+            };
+        });
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+impl OperationRegistry {
+    pub fn new(event_bus: EventBus) -> Self {
+        Self {
+            operations: Arc::new(Mutex::new(HashMap::new())),
+            event_bus,
+        }
+    }
+
+    /// Start `task` in the background as a tracked operation with a new id
+    pub fn spawn<F, Fut>(
+        &self,
+        kind: &str,
+        server_id: Option<String>,
+        cancellable: bool,
+        task: F,
+    ) -> String
+    where
+        F: FnOnce(OperationHandle) -> Fut,
+        Fut: Future<Output = Result<Value, String>> + Send + 'static,
+    {
+        self.spawn_with_id(
+            Uuid::new_v4().to_string(),
+            kind,
+            server_id,
+            cancellable,
+            task,
+        )
+    }
+
+    /// Start `task` in the background as a tracked operation with the given id
+    pub fn spawn_with_id<F, Fut>(
+        &self,
+        id: String,
+        kind: &str,
+        server_id: Option<String>,
+        cancellable: bool,
+        task: F,
+    ) -> String
+    where
+        F: FnOnce(OperationHandle) -> Fut,
+        Fut: Future<Output = Result<Value, String>> + Send + 'static,
+    {
+        let now = now_millis();
+        let operation = Operation {
+            id: id.clone(),
+            kind: kind.to_string(),
+            server_id,
+            state: OperationState::Queued,
+            progress: None,
+            result: None,
+            error: None,
+            cancellable,
+            created_at: now,
+            updated_at: now,
+        };
+        self.operations.lock().unwrap().insert(
+            id.clone(),
+            OperationEntry {
+                operation: operation.clone(),
+                abort_handle: None,
+            },
+        );
+        self.publish(operation);
+
+        let handle = OperationHandle {
+            id: id.clone(),
+            registry: self.clone(),
+        };
+        let future = task(handle);
+        let registry = self.clone();
+        let task_id = id.clone();
+        let join_handle = tokio::spawn(async move {
+            registry.update(&task_id, |operation| {
+                operation.state = OperationState::Running;
+            });
+            let outcome = future.await;
+            registry.update(&task_id, |operation| {
+                // A cancelled operation keeps its state even if the task got to finish
+                if operation.state.is_finished() {
+                    return;
+                }
+                match outcome {
+                    Ok(result) => {
+                        operation.state = OperationState::Succeeded;
+                        operation.progress = Some(100);
+                        operation.result = Some(result);
+                    }
+                    Err(error) => {
+                        operation.state = OperationState::Failed;
+                        operation.error = Some(error);
+                    }
+                }
+            });
+            registry.prune();
+        });
+
+        if let Some(entry) = self.operations.lock().unwrap().get_mut(&id) {
+            entry.abort_handle = Some(join_handle.abort_handle());
+        }
+        id
+    }
+
+    /// Get an operation by id
+    pub fn get(&self, id: &str) -> Option<Operation> {
+        self.operations
+            .lock()
+            .unwrap()
+            .get(id)
+            .map(|entry| entry.operation.clone())
+    }
+
+    /// List known operations, most recent first
+    pub fn list(&self) -> Vec<Operation> {
+        let mut operations: Vec<Operation> = self
+            .operations
+            .lock()
+            .unwrap()
+            .values()
+            .map(|entry| entry.operation.clone())
+            .collect();
+        operations.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        operations
+    }
+
+    /// Cancel a queued or running operation
+    pub fn cancel(&self, id: &str) -> Result<Operation, String> {
+        let operation = {
+            let mut operations = self.operations.lock().unwrap();
+            let entry = operations
+                .get_mut(id)
+                .ok_or_else(|| format!("Operation '{}' not found", id))?;
+            if entry.operation.state.is_finished() {
+                return Err(format!("Operation '{}' has already finished", id));
+            }
+            if !entry.operation.cancellable {
+                return Err(format!("Operation '{}' cannot be cancelled", id));
+            }
+            if let Some(abort_handle) = entry.abort_handle.take() {
+                abort_handle.abort();
+            }
+            entry.operation.state = OperationState::Cancelled;
+            entry.operation.updated_at = now_millis();
+            entry.operation.clone()
+        };
+        self.publish(operation.clone());
+        self.prune();
+        Ok(operation)
+    }
+
+    /// Whether `event` is the last one a follower of its operation should wait for
+    ///
+    /// Tracked operations end with their final state change, which comes after the
+    /// terminal install progress event of an install running as an operation.
+    pub fn is_final_event(&self, event: &DockmasterEvent) -> bool {
+        match event {
+            DockmasterEvent::OperationUpdate(operation) => operation.state.is_finished(),
+            _ => {
+                event.is_terminal()
+                    && event
+                        .operation_id()
+                        .is_none_or(|id| !self.operations.lock().unwrap().contains_key(id))
+            }
+        }
+    }
+
+    fn update<F>(&self, id: &str, change: F)
+    where
+        F: FnOnce(&mut Operation),
+    {
+        let operation = {
+            let mut operations = self.operations.lock().unwrap();
+            let Some(entry) = operations.get_mut(id) else {
+                return;
+            };
+            let before = (entry.operation.state, entry.operation.progress);
+            change(&mut entry.operation);
+            if before == (entry.operation.state, entry.operation.progress) {
+                return;
+            }
+            entry.operation.updated_at = now_millis();
+            entry.operation.clone()
+        };
+        self.publish(operation);
+    }
+
+    fn publish(&self, operation: Operation) {
+        self.event_bus
+            .publish(DockmasterEvent::OperationUpdate(operation));
+    }
+
+    /// Drop the oldest finished operations beyond the retention limit
+    fn prune(&self) {
+        let mut operations = self.operations.lock().unwrap();
+        let mut finished: Vec<(u64, String)> = operations
+            .values()
+            .filter(|entry| entry.operation.state.is_finished())
+            .map(|entry| (entry.operation.updated_at, entry.operation.id.clone()))
+            .collect();
+        if finished.len() <= FINISHED_RETENTION {
+            return;
+        }
+        finished.sort();
+        let excess = finished.len() - FINISHED_RETENTION;
+        for (_, id) in finished.into_iter().take(excess) {
+            operations.remove(&id);
+        }
+    }
+}
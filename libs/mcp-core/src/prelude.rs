@@ -0,0 +1,36 @@
+//! What an application embedding the core needs, in one import.
+
+pub use crate::core::mcp_core::{InitError, MCPCore};
+pub use crate::core::mcp_core_approvals_ext::McpCoreApprovalsExt;
+pub use crate::core::mcp_core_builder::MCPCoreBuilder;
+pub use crate::core::mcp_core_bulk_ext::McpCoreBulkExt;
+pub use crate::core::mcp_core_config_file_ext::McpCoreConfigFileExt;
+pub use crate::core::mcp_core_database_ext::McpCoreDatabaseExt;
+pub use crate::core::mcp_core_diagnostics_ext::McpCoreDiagnosticsExt;
+pub use crate::core::mcp_core_hooks_ext::McpCoreHooksExt;
+pub use crate::core::mcp_core_installers_ext::McpCoreInstallersExt;
+pub use crate::core::mcp_core_metrics_ext::McpCoreMetricsExt;
+pub use crate::core::mcp_core_proxy_ext::McpCoreProxyExt;
+pub use crate::core::mcp_core_rate_limits_ext::McpCoreRateLimitsExt;
+pub use crate::core::mcp_core_read_only_ext::McpCoreReadOnlyExt;
+pub use crate::core::mcp_core_recordings_ext::McpCoreRecordingsExt;
+pub use crate::core::mcp_core_registry_sources_ext::McpCoreRegistrySourcesExt;
+pub use crate::core::mcp_core_runtimes_ext::McpCoreRuntimesExt;
+pub use crate::core::mcp_core_schedules_ext::McpCoreSchedulesExt;
+pub use crate::core::mcp_core_selftest_ext::McpCoreSelftestExt;
+pub use crate::core::mcp_core_tools_ext::McpCoreToolsExt;
+pub use crate::core::mcp_core_webhooks_ext::McpCoreWebhooksExt;
+pub use crate::database::db_manager::DBManager;
+pub use crate::events::{DockmasterEvent, EventBus, EventEnvelope};
+pub use crate::mcp_state::process_launcher::{ProcessLauncher, StdioProcessLauncher};
+pub use crate::models::types::{
+    ErrorResponse, RuntimeServer, ServerConfiguration, ServerRegistrationRequest,
+    ServerRegistrationResponse, ServerToolInfo, ToolExecutionRequest, ToolExecutionResponse,
+};
+#[cfg(feature = "registry")]
+pub use crate::registry::registry_client::HttpRegistryClient;
+pub use crate::registry::registry_client::{NoRegistryClient, RegistryClient};
+pub use crate::utils::clock::{Clock, SystemClock};
+#[cfg(feature = "webhooks")]
+pub use crate::webhooks::HttpWebhookSender;
+pub use crate::webhooks::{NoWebhookSender, WebhookSender};
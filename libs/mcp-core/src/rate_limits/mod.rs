@@ -0,0 +1,232 @@
+//! Rate limits of tool calls, for tools that call paid or quota limited APIs.
+//!
+//! A limit applies to one tool of one server and caps its calls per minute, per hour
+//! and running at once. Calls per minute and per hour are counted with token buckets
+//! kept in memory: a burst up to the limit goes through, then calls are let through as
+//! the bucket refills. A call over a limit fails with a JSON-RPC error whose
+//! `data.reason` is [`RATE_LIMITED_REASON`] and which tells when to retry. Limits are
+//! saved in the [`RATE_LIMITS_SETTING`] setting.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+/// Settings key of the configured limits
+pub const RATE_LIMITS_SETTING: &str = "tool_rate_limits";
+
+/// `reason` in the data of the JSON-RPC error of a call over a limit
+pub const RATE_LIMITED_REASON: &str = "rate_limited";
+
+const MINUTE: Duration = Duration::from_secs(60);
+const HOUR: Duration = Duration::from_secs(3600);
+
+/// Limits of a tool, unset limits don't apply
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimit {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub per_minute: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub per_hour: Option<u32>,
+    /// Calls running at once
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_concurrent: Option<u32>,
+}
+
+impl RateLimit {
+    /// Whether no limit is set
+    pub fn is_unlimited(&self) -> bool {
+        self.per_minute.is_none() && self.per_hour.is_none() && self.max_concurrent.is_none()
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if [self.per_minute, self.per_hour, self.max_concurrent].contains(&Some(0)) {
+            return Err("Rate limits must be above 0, leave a limit out to remove it".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// A limit with the tool it applies to, as saved
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolRateLimit {
+    pub server_id: String,
+    pub tool_name: String,
+    #[serde(flatten)]
+    pub limit: RateLimit,
+}
+
+/// Which limit a call reached
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RateLimitKind {
+    PerMinute,
+    PerHour,
+    MaxConcurrent,
+}
+
+/// A call refused because its tool reached a limit
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct RateLimited {
+    pub server_id: String,
+    pub tool_name: String,
+    pub limit: RateLimitKind,
+    /// Time until a call is let through again, None when waiting for a running call
+    pub retry_after_ms: Option<u64>,
+    /// When a call is let through again, in milliseconds since the Unix epoch
+    pub reset_at: Option<u64>,
+}
+
+impl std::fmt::Display for RateLimited {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let limit = match self.limit {
+            RateLimitKind::PerMinute => "calls per minute",
+            RateLimitKind::PerHour => "calls per hour",
+            RateLimitKind::MaxConcurrent => "concurrent calls",
+        };
+        write!(
+            f,
+            "Tool '{}' of server '{}' reached its limit of {}",
+            self.tool_name, self.server_id, limit
+        )?;
+        match self.retry_after_ms {
+            Some(ms) => write!(f, ", retry in {} s", ms.div_ceil(1000)),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    /// Refill for the time elapsed and return the time until a token is available
+    fn wait(&mut self, capacity: u32, window: Duration, now: Instant) -> Option<Duration> {
+        let capacity = capacity as f64;
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed / window.as_secs_f64() * capacity).min(capacity);
+        self.updated = now;
+        if self.tokens >= 1.0 {
+            return None;
+        }
+        Some(Duration::from_secs_f64(
+            (1.0 - self.tokens) * window.as_secs_f64() / capacity,
+        ))
+    }
+}
+
+#[derive(Debug, Default)]
+struct ToolState {
+    minute: Option<Bucket>,
+    hour: Option<Bucket>,
+    running: u32,
+}
+
+/// Counts the calls of the tools with a limit
+#[derive(Clone, Debug, Default)]
+pub struct RateLimiter {
+    tools: Arc<Mutex<HashMap<(String, String), ToolState>>>,
+}
+
+impl RateLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Let a call of `tool_name` on `server_id` through, or tell which limit it reached
+    ///
+    /// The call counts as running until the permit is dropped.
+    pub fn acquire(
+        &self,
+        server_id: &str,
+        tool_name: &str,
+        limit: &RateLimit,
+        now: Instant,
+    ) -> Result<RatePermit, RateLimited> {
+        let key = (server_id.to_string(), tool_name.to_string());
+        let mut tools = self.tools.lock().unwrap();
+        let state = tools.entry(key.clone()).or_default();
+        let refused = |limit, wait: Option<Duration>| {
+            let retry_after_ms = wait.map(|wait| wait.as_millis() as u64 + 1);
+            let now_ms = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0);
+            RateLimited {
+                server_id: server_id.to_string(),
+                tool_name: tool_name.to_string(),
+                limit,
+                retry_after_ms,
+                reset_at: retry_after_ms.map(|ms| now_ms + ms),
+            }
+        };
+
+        if limit.max_concurrent.is_some_and(|max| state.running >= max) {
+            return Err(refused(RateLimitKind::MaxConcurrent, None));
+        }
+        let buckets = [
+            (
+                &mut state.minute,
+                limit.per_minute,
+                MINUTE,
+                RateLimitKind::PerMinute,
+            ),
+            (
+                &mut state.hour,
+                limit.per_hour,
+                HOUR,
+                RateLimitKind::PerHour,
+            ),
+        ];
+        for (bucket, capacity, window, kind) in buckets {
+            let Some(capacity) = capacity else {
+                *bucket = None;
+                continue;
+            };
+            let bucket = bucket.get_or_insert(Bucket {
+                tokens: capacity as f64,
+                updated: now,
+            });
+            if let Some(wait) = bucket.wait(capacity, window, now) {
+                return Err(refused(kind, Some(wait)));
+            }
+        }
+
+        for bucket in [&mut state.minute, &mut state.hour].into_iter().flatten() {
+            bucket.tokens -= 1.0;
+        }
+        state.running += 1;
+        Ok(RatePermit {
+            limiter: self.clone(),
+            key,
+        })
+    }
+
+    /// Forget the calls counted for a tool, when its limit changes
+    pub fn reset(&self, server_id: &str, tool_name: &str) {
+        let key = (server_id.to_string(), tool_name.to_string());
+        if let Some(state) = self.tools.lock().unwrap().get_mut(&key) {
+            state.minute = None;
+            state.hour = None;
+        }
+    }
+}
+
+/// A call let through by the [`RateLimiter`], running until dropped
+#[derive(Debug)]
+pub struct RatePermit {
+    limiter: RateLimiter,
+    key: (String, String),
+}
+
+impl Drop for RatePermit {
+    fn drop(&mut self) {
+        if let Some(state) = self.limiter.tools.lock().unwrap().get_mut(&self.key) {
+            state.running = state.running.saturating_sub(1);
+        }
+    }
+}
@@ -0,0 +1,88 @@
+//! Read-only mode, for instances shared with others to try tools.
+//!
+//! In read-only mode the JSON-RPC server only answers the methods listed in
+//! [`READ_ONLY_METHODS`], and the built-in register, configure and uninstall tools
+//! refuse to run. Everything else fails with a policy error whose `data.reason` is
+//! [`READ_ONLY_REASON`]. The mode is turned on and off from the app, or over JSON-RPC
+//! with the admin token set in [`ADMIN_TOKEN_ENV`].
+
+use serde_json::{json, Value};
+
+/// Settings key of the mode, "true" when read-only
+pub const READ_ONLY_SETTING: &str = "read_only";
+
+/// Reason given in the data of the errors returned in read-only mode
+pub const READ_ONLY_REASON: &str = "read_only_mode";
+
+/// Environment variable holding the token that allows changing the mode over JSON-RPC
+pub const ADMIN_TOKEN_ENV: &str = "DOCKMASTER_ADMIN_TOKEN";
+
+/// JSON-RPC methods answered in read-only mode, all the others are refused
+pub const READ_ONLY_METHODS: [&str; 35] = [
+    "initialize",
+    "tools/list",
+    "tools/hidden",
+    "tools/footprint",
+    "tools/describe",
+    "tools/call",
+    "notifications/initialized",
+    "notifications/cancelled",
+    "prompts/list",
+    "prompts/get",
+    "resources/list",
+    "resources/read",
+    "registry/list",
+    "registry/preview_install",
+    "registry/sources",
+    "server/status",
+    "server/config_schema",
+    "server/info",
+    "server/launch_plan",
+    "server/resources",
+    "server/history",
+    "dashboard/summary",
+    "operations/list",
+    "operations/get",
+    "hooks/list",
+    "schedules/list",
+    "schedules/get",
+    "schedules/runs",
+    "webhooks/list",
+    "webhooks/dead_letters",
+    "system/capabilities",
+    "system/migration_report",
+    "database/state",
+    // Check the admin token themselves
+    "read_only/set",
+    "system/dump_state",
+];
+
+/// Whether a JSON-RPC method is answered in read-only mode
+pub fn allowed_in_read_only(method: &str) -> bool {
+    READ_ONLY_METHODS.contains(&method)
+}
+
+/// JSON-RPC error returned for a method refused in read-only mode
+pub fn read_only_error(method: &str) -> Value {
+    json!({
+        "code": -32000,
+        "message": format!("'{}' is not allowed, MCP Dockmaster is in read-only mode", method),
+        "data": { "reason": READ_ONLY_REASON }
+    })
+}
+
+/// Whether a token is the admin token, never true when no admin token is set
+pub fn is_admin_token(token: &str) -> bool {
+    match std::env::var(ADMIN_TOKEN_ENV) {
+        Ok(expected) if !expected.is_empty() => constant_time_eq(token, &expected),
+        _ => false,
+    }
+}
+
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0, |diff, (x, y)| diff | (x ^ y))
+            == 0
+}
@@ -0,0 +1,137 @@
+//! Recorded tool calls, replayed instead of calling flaky or rate-limited servers.
+//!
+//! A server in `record` mode appends the result of each successful call to its store,
+//! a JSON Lines file under `recordings` in the data directory. A server in `replay`
+//! mode answers calls from that store without reaching the server, see
+//! [`CallMode`](crate::models::types::CallMode). Calls are matched on the tool name and
+//! a hash of the arguments with their object keys sorted, so that the order in which a
+//! client sends the arguments doesn't matter.
+
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Directory of the stores, under the data directory
+pub const RECORDINGS_DIR: &str = "recordings";
+
+/// A recorded tool call
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RecordedCall {
+    /// Hash of the tool name and the canonical arguments, see [`call_key`]
+    pub key: String,
+    pub tool: String,
+    pub arguments: Value,
+    /// Result of the call, as returned by the server before any hook ran
+    pub result: Value,
+    /// Recording time, in milliseconds since the Unix epoch
+    pub recorded_at: u64,
+}
+
+impl RecordedCall {
+    pub fn new(tool: &str, arguments: &Value, result: Value) -> Self {
+        let arguments = canonicalize(arguments);
+        Self {
+            key: call_key(tool, &arguments),
+            tool: tool.to_string(),
+            arguments,
+            result,
+            recorded_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0),
+        }
+    }
+}
+
+/// `value` with the keys of its objects sorted
+pub fn canonicalize(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            let mut sorted = Map::new();
+            for key in keys {
+                sorted.insert(key.clone(), canonicalize(&map[key]));
+            }
+            Value::Object(sorted)
+        }
+        Value::Array(items) => Value::Array(items.iter().map(canonicalize).collect()),
+        other => other.clone(),
+    }
+}
+
+/// Key a call is recorded under
+///
+/// FNV-1a is used rather than the hasher of the standard library, whose output may
+/// change between Rust releases, since stores outlive builds.
+pub fn call_key(tool: &str, arguments: &Value) -> String {
+    let canonical = canonicalize(arguments).to_string();
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in tool.bytes().chain([b'\n']).chain(canonical.bytes()) {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:016x}", hash)
+}
+
+/// Recorded calls of one server
+pub struct RecordingStore {
+    path: PathBuf,
+}
+
+impl RecordingStore {
+    /// Store of `server_id` under the data directory `data_dir`
+    pub fn new(data_dir: &Path, server_id: &str) -> Self {
+        Self {
+            path: data_dir
+                .join(RECORDINGS_DIR)
+                .join(format!("{}.jsonl", server_id)),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append a call, the latest recording of a call is the one replayed
+    pub fn append(&self, call: &RecordedCall) -> Result<(), String> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        let mut line = serde_json::to_string(call).map_err(|e| e.to_string())?;
+        line.push('\n');
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .map_err(|e| format!("Failed to write {}: {}", self.path.display(), e))
+    }
+
+    /// Result of the latest recording of a call, None when it was never recorded
+    pub fn find(&self, tool: &str, arguments: &Value) -> Option<Value> {
+        let key = call_key(tool, arguments);
+        self.calls()
+            .into_iter()
+            .rev()
+            .find(|call| call.key == key && call.tool == tool)
+            .map(|call| call.result)
+    }
+
+    /// Every recorded call, oldest first, skipping lines that can't be parsed
+    pub fn calls(&self) -> Vec<RecordedCall> {
+        let Ok(file) = fs::File::open(&self.path) else {
+            return Vec::new();
+        };
+        BufReader::new(file)
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|line| serde_json::from_str(&line).ok())
+            .collect()
+    }
+}
@@ -1,4 +1,7 @@
-pub mod server_registry;
 pub mod registry_cache;
-pub mod registry_service;
+pub mod registry_client;
 pub mod registry_search;
+pub mod registry_service;
+pub mod registry_sources;
+pub mod server_registry;
+pub mod storage;
@@ -1,20 +1,27 @@
-use std::time::{Duration, Instant};
-use lazy_static::lazy_static;
 use log::{info, warn};
-use reqwest;
-use serde_json::Value;
 use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
 
-use crate::models::types::{ErrorResponse, RegistryToolsResponse};
+use crate::metrics::{MetricsRegistry, RegistryCacheLookup};
+use crate::models::types::{ErrorResponse, RegistryTool, RegistryToolsResponse};
+use crate::models::unknown_fields::log_unknown_registry_fields;
+use crate::registry::registry_client::RegistryClient;
+use crate::registry::registry_sources::{fetch_source_tools, RegistrySource};
+use crate::utils::clock::Clock;
 
 // Cache duration constant (10 minute)
 const CACHE_DURATION: Duration = Duration::from_secs(600);
 
-// Cache structure to store registry data and timestamp
+/// Time a stale registry is served after a failed refresh before fetching again
+pub const REFRESH_RETRY_INTERVAL: Duration = Duration::from_secs(30);
+
+// Cache structure to store the parsed registry and timestamp
 struct RegistryCacheEntry {
-    data: Option<Value>,
+    data: Option<Arc<RegistryToolsResponse>>,
     timestamp: Option<Instant>,
+    /// Last refresh that failed while `data` was kept
+    failed_at: Option<Instant>,
 }
 
 impl RegistryCacheEntry {
@@ -22,66 +29,85 @@ impl RegistryCacheEntry {
         Self {
             data: None,
             timestamp: None,
+            failed_at: None,
         }
     }
 
-    fn is_valid(&self) -> bool {
-        if let (Some(data), Some(timestamp)) = (&self.data, self.timestamp) {
-            // Check that the timestamp is recent and data is not empty/null
-            if timestamp.elapsed() < CACHE_DURATION && !data.is_null() {
-                // Verify that the data appears to have the expected structure
-                if let Some(tools) = data.get("tools") {
-                    if tools.is_array() {
-                        return true;
-                    }
-                }
-            }
+    /// The registry, when it is recent enough to be served without fetching
+    fn fresh(&self, now: Instant) -> Option<Arc<RegistryToolsResponse>> {
+        let data = self.data.as_ref()?;
+        let recent = |at: Option<Instant>, duration: Duration| {
+            at.is_some_and(|at| now.saturating_duration_since(at) < duration)
+        };
+        if recent(self.timestamp, CACHE_DURATION) || recent(self.failed_at, REFRESH_RETRY_INTERVAL)
+        {
+            return Some(data.clone());
         }
-        false
+        None
     }
 }
 
-// Cache structure that provides both sync and async access
+/// Cache of the server registry, owned by an `MCPCore`
+///
+/// The registry is parsed once when fetched and shared with the callers. A refresh that
+/// fails keeps the registry fetched before, which is served for
+/// [`REFRESH_RETRY_INTERVAL`] before trying again.
 pub struct RegistryCache {
     // Use RwLock for sync access
     sync_cache: Arc<RwLock<RegistryCacheEntry>>,
     // Use Mutex for async access
     async_cache: Mutex<()>,
-}
-
-lazy_static! {
-    static ref REGISTRY_CACHE_INSTANCE: RegistryCache = RegistryCache::new();
+    client: Arc<dyn RegistryClient>,
+    clock: Arc<dyn Clock>,
+    metrics: MetricsRegistry,
 }
 
 impl RegistryCache {
-    fn new() -> Self {
+    /// Create a cache fetching from `client`
+    pub fn new(client: Arc<dyn RegistryClient>, clock: Arc<dyn Clock>) -> Self {
         Self {
             sync_cache: Arc::new(RwLock::new(RegistryCacheEntry::new())),
             async_cache: Mutex::new(()),
+            client,
+            clock,
+            metrics: MetricsRegistry::new(),
         }
     }
 
-    // Get instance of the cache singleton
-    pub fn instance() -> &'static Self {
-        &REGISTRY_CACHE_INSTANCE
+    /// Count the lookups of the cache in `metrics`
+    pub fn with_metrics(mut self, metrics: MetricsRegistry) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Time since the registry was last fetched, `None` if it never was
+    pub fn age(&self) -> Option<Duration> {
+        let cache_read = self.sync_cache.read().unwrap();
+        cache_read
+            .timestamp
+            .map(|timestamp| self.clock.now().saturating_duration_since(timestamp))
+    }
+
+    /// Fetch the tools of a registry source with the client of the cache, uncached
+    pub async fn fetch_source(
+        &self,
+        source: &RegistrySource,
+    ) -> Result<Vec<RegistryTool>, ErrorResponse> {
+        fetch_source_tools(self.client.as_ref(), source).await
+    }
+
+    fn cached(&self) -> Option<Arc<RegistryToolsResponse>> {
+        self.sync_cache.read().unwrap().fresh(self.clock.now())
     }
 
     // Get registry tools from cache or fetch if needed (sync version)
-    pub fn get_registry_tools_sync(&self) -> Result<RegistryToolsResponse, String> {
-        // Check if we have a valid cache
-        {
-            let cache_read = self.sync_cache.read().unwrap();
-            if cache_read.is_valid() {
-                if let Some(data) = &cache_read.data {
-                    match serde_json::from_value::<RegistryToolsResponse>(data.clone()) {
-                        Ok(response) => return Ok(response),
-                        Err(e) => warn!("Failed to parse cached registry data: {}", e),
-                    }
-                }
-            }
+    pub fn get_registry_tools_sync(&self) -> Result<Arc<RegistryToolsResponse>, String> {
+        if let Some(registry) = self.cached() {
+            self.metrics.record_registry_cache(RegistryCacheLookup::Hit);
+            return Ok(registry);
         }
 
-        // Cache is invalid or corrupted, need to fetch fresh data
+        // Cache is invalid, need to fetch fresh data
         // This is a blocking call in a synchronous context - not ideal but necessary
         let rt = match tokio::runtime::Runtime::new() {
             Ok(rt) => rt,
@@ -100,85 +126,68 @@ impl RegistryCache {
     }
 
     // Get registry tools from cache or fetch if needed (async version)
-    pub async fn get_registry_tools(&self) -> Result<RegistryToolsResponse, ErrorResponse> {
-        // Check if we have a valid cache first (using read lock)
-        {
-            let cache_read = self.sync_cache.read().unwrap();
-            if cache_read.is_valid() {
-                if let Some(data) = &cache_read.data {
-                    match serde_json::from_value::<RegistryToolsResponse>(data.clone()) {
-                        Ok(response) => return Ok(response),
-                        Err(_) => {
-                            // Cache data is corrupted, we'll refresh it
-                        }
-                    }
-                }
-            }
+    pub async fn get_registry_tools(&self) -> Result<Arc<RegistryToolsResponse>, ErrorResponse> {
+        if let Some(registry) = self.cached() {
+            self.metrics.record_registry_cache(RegistryCacheLookup::Hit);
+            return Ok(registry);
         }
 
         // Cache is invalid or doesn't exist, fetch fresh data
         self.update_registry_cache().await
     }
 
-    // Update the registry cache with fresh data
-    pub async fn update_registry_cache(&self) -> Result<RegistryToolsResponse, ErrorResponse> {
+    /// Fetch the registry unless it was fetched while waiting for another fetch
+    ///
+    /// When the fetch fails, the registry fetched before is returned if there is one.
+    pub async fn update_registry_cache(&self) -> Result<Arc<RegistryToolsResponse>, ErrorResponse> {
         // Lock to prevent multiple simultaneous fetches
         let _guard = self.async_cache.lock().await;
-        
+
         // Double-check if cache was updated while we were waiting for the lock
-        {
-            let cache_read = self.sync_cache.read().unwrap();
-            if cache_read.is_valid() {
-                if let Some(data) = &cache_read.data {
-                    match serde_json::from_value::<RegistryToolsResponse>(data.clone()) {
-                        Ok(response) => return Ok(response),
-                        Err(_) => {
-                            // Cache data is corrupted, will refresh it
-                        }
+        if let Some(registry) = self.cached() {
+            self.metrics.record_registry_cache(RegistryCacheLookup::Hit);
+            return Ok(registry);
+        }
+        self.metrics
+            .record_registry_cache(RegistryCacheLookup::Miss);
+
+        let fetched = match self.client.fetch_registry().await {
+            Ok(raw) => {
+                log_unknown_registry_fields(&raw);
+                serde_json::from_value::<RegistryToolsResponse>(raw).map_err(|e| {
+                    self.metrics
+                        .record_registry_cache(RegistryCacheLookup::ParseFailure);
+                    ErrorResponse {
+                        code: -32000,
+                        message: format!("Failed to parse tools from registry: {}", e),
                     }
-                }
+                })
             }
-        }
+            Err(e) => Err(e),
+        };
 
-        // Fetch tools from remote URL
-        // All Tools: Stable & Unstable
-        let tools_url =
-            "https://pub-5e2d77d67aac45ef811998185d312005.r2.dev/registry/registry.all.json";
-        
-        info!("Fetching registry data from {}", tools_url);
-        let client = reqwest::Client::builder().build().unwrap_or_default();
-
-        let response = client
-            .get(tools_url)
-            .header("Accept-Encoding", "gzip")
-            .header("User-Agent", "MCP-Core/1.0")
-            .send()
-            .await
-            .map_err(|e| ErrorResponse {
-                code: -32000,
-                message: format!("Failed to fetch tools from registry: {}", e),
-            })?;
-
-        let raw = response.json().await.map_err(|e| ErrorResponse {
-            code: -32000,
-            message: format!("Failed to parse tools from registry: {}", e),
-        })?;
-
-        let tool_wrapper: RegistryToolsResponse =
-            serde_json::from_value(raw).map_err(|e| ErrorResponse {
-                code: -32000,
-                message: format!("Failed to parse tools from registry: {}", e),
-            })?;
-
-        info!("Fetched {} tools from registry", tool_wrapper.tools.len());
-
-        // Update the cache with new data
-        {
-            let mut cache_write = self.sync_cache.write().unwrap();
-            cache_write.data = Some(serde_json::to_value(&tool_wrapper).unwrap_or_default());
-            cache_write.timestamp = Some(Instant::now());
+        let mut cache_write = self.sync_cache.write().unwrap();
+        match fetched {
+            Ok(tool_wrapper) => {
+                info!("Fetched {} tools from registry", tool_wrapper.tools.len());
+                let registry = Arc::new(tool_wrapper);
+                cache_write.data = Some(registry.clone());
+                cache_write.timestamp = Some(self.clock.now());
+                cache_write.failed_at = None;
+                Ok(registry)
+            }
+            // A failed refresh never replaces the registry fetched before
+            Err(e) => match cache_write.data.clone() {
+                Some(registry) => {
+                    warn!(
+                        "Failed to refresh the registry, serving the one fetched before: {}",
+                        e.message
+                    );
+                    cache_write.failed_at = Some(self.clock.now());
+                    Ok(registry)
+                }
+                None => Err(e),
+            },
         }
-
-        Ok(tool_wrapper)
     }
-} 
\ No newline at end of file
+}
@@ -0,0 +1,148 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+#[cfg(feature = "registry")]
+use log::info;
+use serde_json::Value;
+
+use crate::models::types::ErrorResponse;
+#[cfg(feature = "registry")]
+use crate::registry::registry_sources::RegistryCredentials;
+use crate::registry::registry_sources::RegistrySource;
+
+/// All Tools: Stable & Unstable
+pub const REGISTRY_URL: &str =
+    "https://pub-5e2d77d67aac45ef811998185d312005.r2.dev/registry/registry.all.json";
+
+/// Fetches the raw server registry
+///
+/// `HttpRegistryClient` downloads it, tests and embedders inject their own implementation.
+#[async_trait]
+pub trait RegistryClient: Send + Sync {
+    async fn fetch_registry(&self) -> Result<Value, ErrorResponse>;
+
+    /// Fetch a JSON document of a registry source, with its headers and credentials
+    async fn fetch_source_json(
+        &self,
+        source: &RegistrySource,
+        _url: &str,
+    ) -> Result<Value, ErrorResponse> {
+        Err(ErrorResponse {
+            code: -32000,
+            message: format!(
+                "Registry source {} is not available in this build",
+                source.id
+            ),
+        })
+    }
+}
+
+/// Client of the registry cache unless the core is built with another
+///
+/// Without the `registry` feature nothing is downloaded and the registry is unavailable.
+pub fn default_registry_client() -> Arc<dyn RegistryClient> {
+    #[cfg(feature = "registry")]
+    return Arc::new(HttpRegistryClient::default());
+    #[cfg(not(feature = "registry"))]
+    return Arc::new(NoRegistryClient);
+}
+
+/// Registry client for embedders that don't use the registry, every fetch fails
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoRegistryClient;
+
+#[async_trait]
+impl RegistryClient for NoRegistryClient {
+    async fn fetch_registry(&self) -> Result<Value, ErrorResponse> {
+        Err(ErrorResponse {
+            code: -32000,
+            message: "The registry is not available in this build".to_string(),
+        })
+    }
+}
+
+/// Registry client downloading the registry over HTTP
+#[cfg(feature = "registry")]
+#[derive(Clone, Debug)]
+pub struct HttpRegistryClient {
+    url: String,
+}
+
+#[cfg(feature = "registry")]
+impl Default for HttpRegistryClient {
+    fn default() -> Self {
+        Self::new(REGISTRY_URL)
+    }
+}
+
+#[cfg(feature = "registry")]
+impl HttpRegistryClient {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+        }
+    }
+}
+
+#[cfg(feature = "registry")]
+#[async_trait]
+impl RegistryClient for HttpRegistryClient {
+    async fn fetch_registry(&self) -> Result<Value, ErrorResponse> {
+        info!("Fetching registry data from {}", self.url);
+        let client = reqwest::Client::builder().build().unwrap_or_default();
+
+        let response = client
+            .get(&self.url)
+            .header("Accept-Encoding", "gzip")
+            .header("User-Agent", "MCP-Core/1.0")
+            .send()
+            .await
+            .map_err(|e| ErrorResponse {
+                code: -32000,
+                message: format!("Failed to fetch tools from registry: {}", e),
+            })?;
+
+        response.json().await.map_err(|e| ErrorResponse {
+            code: -32000,
+            message: format!("Failed to parse tools from registry: {}", e),
+        })
+    }
+
+    async fn fetch_source_json(
+        &self,
+        source: &RegistrySource,
+        url: &str,
+    ) -> Result<Value, ErrorResponse> {
+        info!("Fetching {} of registry source {}", url, source.id);
+        let client = reqwest::Client::builder().build().unwrap_or_default();
+
+        let mut request = client
+            .get(url)
+            .header("Accept-Encoding", "gzip")
+            .header("User-Agent", "MCP-Core/1.0");
+        for (name, value) in &source.headers {
+            request = request.header(name.as_str(), value.as_str());
+        }
+        request = match &source.credentials {
+            Some(RegistryCredentials::Basic { username, password }) => {
+                request.basic_auth(username, Some(password))
+            }
+            Some(RegistryCredentials::Bearer { token }) => request.bearer_auth(token),
+            None => request,
+        };
+
+        let fetch_error = |e: String| ErrorResponse {
+            code: -32000,
+            message: format!("Failed to fetch registry source {}: {}", source.id, e),
+        };
+        let response = request
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| fetch_error(e.to_string()))?;
+        response
+            .json()
+            .await
+            .map_err(|e| fetch_error(e.to_string()))
+    }
+}
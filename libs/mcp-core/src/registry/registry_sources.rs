@@ -0,0 +1,217 @@
+//! Registries listed next to the default one, such as a company's internal registry.
+//!
+//! A source is either a registry JSON shaped like the default registry, or an index
+//! whose `tools` list the URLs of one JSON file per tool, relative to the index. The
+//! headers and credentials of a source are sent with each of its requests, but never
+//! sent back: header values are listed as [`REDACTED_HEADER_VALUE`].
+//!
+//! Tools of the merged registry carry the id of their source. When several sources
+//! have a tool with the same id, the default registry wins, then the first source by id.
+
+use std::collections::{BTreeMap, HashSet};
+
+use log::warn;
+use serde::{Deserialize, Serialize, Serializer};
+use serde_json::Value;
+
+use crate::models::types::{ErrorResponse, RegistryTool, RegistryToolsResponse};
+use crate::registry::registry_client::RegistryClient;
+
+/// Source id of the tools of the default registry
+pub const DEFAULT_SOURCE_ID: &str = "default";
+
+/// Listed in place of the value of each header, saving it back keeps the saved value
+pub const REDACTED_HEADER_VALUE: &str = "********";
+
+/// Format of a registry source
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RegistrySourceKind {
+    /// A registry JSON with every tool, like the default registry
+    #[default]
+    Registry,
+    /// An index listing one JSON file per tool
+    Index,
+}
+
+impl RegistrySourceKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RegistrySourceKind::Registry => "registry",
+            RegistrySourceKind::Index => "index",
+        }
+    }
+
+    pub fn from_stored(value: &str) -> Option<Self> {
+        match value {
+            "registry" => Some(RegistrySourceKind::Registry),
+            "index" => Some(RegistrySourceKind::Index),
+            _ => None,
+        }
+    }
+}
+
+/// Credentials sent to a registry source
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RegistryCredentials {
+    Basic { username: String, password: String },
+    Bearer { token: String },
+}
+
+/// A registry source
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegistrySource {
+    pub id: String,
+    pub url: String,
+    #[serde(default)]
+    pub kind: RegistrySourceKind,
+    /// Headers added to each request, e.g. an API key. Sent back redacted
+    #[serde(default, serialize_with = "serialize_redacted_headers")]
+    pub headers: BTreeMap<String, String>,
+    /// Accepted but never sent back, like the secrets of webhooks
+    #[serde(default, skip_serializing)]
+    pub credentials: Option<RegistryCredentials>,
+}
+
+impl RegistrySource {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.id.is_empty()
+            || !self
+                .id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(format!(
+                "Invalid registry source id '{}': use letters, digits, '-' and '_'",
+                self.id
+            ));
+        }
+        if self.id == DEFAULT_SOURCE_ID {
+            return Err(format!("'{}' is the id of the default registry", self.id));
+        }
+        if !self.url.starts_with("http://") && !self.url.starts_with("https://") {
+            return Err(format!("Invalid registry source URL '{}'", self.url));
+        }
+        for name in self.headers.keys() {
+            if name.is_empty() || !name.chars().all(|c| c.is_ascii_graphic() && c != ':') {
+                return Err(format!("Invalid header name '{}'", name));
+            }
+        }
+        if let Some((name, _)) = self
+            .headers
+            .iter()
+            .find(|(_, value)| value.contains(['\r', '\n']))
+        {
+            return Err(format!("Invalid value for header '{}'", name));
+        }
+        Ok(())
+    }
+}
+
+fn serialize_redacted_headers<S: Serializer>(
+    headers: &BTreeMap<String, String>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_map(headers.keys().map(|name| (name, REDACTED_HEADER_VALUE)))
+}
+
+/// URLs of the tool files listed by an index, resolved against the URL of the index
+pub fn index_entry_urls(index_url: &str, index: &Value) -> Result<Vec<String>, String> {
+    let Some(entries) = index.get("tools").and_then(|v| v.as_array()) else {
+        return Err("The index has no tools list".to_string());
+    };
+    entries
+        .iter()
+        .map(|entry| match entry.as_str() {
+            Some(entry) => Ok(resolve_url(index_url, entry)),
+            None => Err(format!("Invalid index entry {}", entry)),
+        })
+        .collect()
+}
+
+fn resolve_url(base: &str, reference: &str) -> String {
+    if reference.starts_with("http://") || reference.starts_with("https://") {
+        return reference.to_string();
+    }
+    let after_scheme = base.find("://").map(|i| i + 3).unwrap_or(0);
+    if let Some(path) = reference.strip_prefix('/') {
+        let origin_end = base[after_scheme..]
+            .find('/')
+            .map(|i| after_scheme + i)
+            .unwrap_or(base.len());
+        return format!("{}/{}", &base[..origin_end], path);
+    }
+    let base = base.split(['?', '#']).next().unwrap_or(base);
+    match base[after_scheme..].rfind('/') {
+        Some(i) => format!("{}/{}", &base[..after_scheme + i], reference),
+        None => format!("{}/{}", base, reference),
+    }
+}
+
+/// Fetch the tools of a source
+///
+/// Tool files of an index that can't be fetched or parsed are skipped with a warning.
+pub async fn fetch_source_tools(
+    client: &dyn RegistryClient,
+    source: &RegistrySource,
+) -> Result<Vec<RegistryTool>, ErrorResponse> {
+    let invalid = |e: String| ErrorResponse {
+        code: -32000,
+        message: format!("Invalid registry source {}: {}", source.id, e),
+    };
+    let document = client.fetch_source_json(source, &source.url).await?;
+    match source.kind {
+        RegistrySourceKind::Registry => {
+            let registry: RegistryToolsResponse =
+                serde_json::from_value(document).map_err(|e| invalid(e.to_string()))?;
+            Ok(registry.tools)
+        }
+        RegistrySourceKind::Index => {
+            let mut tools = Vec::new();
+            for url in index_entry_urls(&source.url, &document).map_err(invalid)? {
+                let tool = match client.fetch_source_json(source, &url).await {
+                    Ok(tool) => serde_json::from_value(tool).map_err(|e| e.to_string()),
+                    Err(e) => Err(e.message),
+                };
+                match tool {
+                    Ok(tool) => tools.push(tool),
+                    Err(e) => warn!("Skipping {} of registry source {}: {}", url, source.id, e),
+                }
+            }
+            Ok(tools)
+        }
+    }
+}
+
+/// Merge the tools of the sources into the default registry, tagging each with its source
+pub fn merge_registries(
+    mut registry: RegistryToolsResponse,
+    sources: Vec<(String, Vec<RegistryTool>)>,
+) -> RegistryToolsResponse {
+    for tool in &mut registry.tools {
+        tool.source = Some(DEFAULT_SOURCE_ID.to_string());
+    }
+    let mut ids: HashSet<String> = registry.tools.iter().map(|t| t.id.clone()).collect();
+    for (source_id, tools) in sources {
+        for mut tool in tools {
+            if !ids.insert(tool.id.clone()) {
+                warn!(
+                    "Ignoring tool {} of registry source {}, another source has it",
+                    tool.id, source_id
+                );
+                continue;
+            }
+            for category in &tool.categories {
+                *registry.categories.entry(category.clone()).or_default() += 1;
+            }
+            for tag in &tool.tags {
+                *registry.tags.entry(tag.clone()).or_default() += 1;
+            }
+            tool.source = Some(source_id.clone());
+            registry.tools.push(tool);
+        }
+    }
+    registry.count = registry.tools.len() as u32;
+    registry
+}
@@ -1,73 +1,666 @@
 use std::collections::HashMap;
+use std::sync::Mutex;
 
-use crate::{database::db_manager::DBManager, models::types::{ServerDefinition, ServerToolInfo}};
+use log::warn;
+
+use crate::{
+    config_history::ConfigSnapshot,
+    database::db_manager::DBManager,
+    lifecycle::{LifecycleEvent, LifecycleHistory},
+    models::server_id::{
+        normalize_server_id, slugify_server_id, unique_server_id, validate_server_id,
+    },
+    models::types::{
+        AuditEntry, ServerCallMode, ServerConfiguration, ServerDefinition, ServerInitInfo,
+        ServerProvenance, ServerToolInfo, SpawnedProcessRecord,
+    },
+    registry::registry_sources::RegistrySource,
+    registry::storage::{
+        is_storage_error, PendingWrite, StorageCache, StorageHealth, StorageUnavailable,
+    },
+    schedules::{Schedule, ScheduleDefinition, ScheduleRun},
+    undo::UndoEntry,
+    webhooks::{DeadLetter, Webhook},
+};
 
 /// ServerRegistry: database logic only
 ///
 /// This module is responsible for reading/writing Server objects in the database.
 /// It has no knowledge about processes or server tools in memory.
+///
+/// While the database is unavailable, reads are served from what the registry last
+/// read or wrote and writes are queued, see [`StorageHealth`]. Servers themselves are
+/// only saved or deleted durably: those calls fail with [`StorageUnavailable`].
 pub struct ServerRegistry {
     db_manager: DBManager,
+    storage: StorageHealth,
+    cache: Mutex<StorageCache>,
 }
 
 impl ServerRegistry {
     pub fn new() -> Result<Self, String> {
         let db = DBManager::new()?;
-        Ok(Self { db_manager: db })
+        Ok(Self::with_db_manager(db))
     }
 
     /// Create a new ServerRegistry with a custom DBManager
     pub fn with_db_manager(db_manager: DBManager) -> Self {
-        Self { db_manager }
+        Self {
+            db_manager,
+            storage: StorageHealth::new(),
+            cache: Mutex::new(StorageCache::default()),
+        }
+    }
+
+    /// Availability of the database
+    pub fn storage(&self) -> StorageHealth {
+        self.storage.clone()
     }
 
     /// Get a server by ID
     pub fn get_server(&self, tool_id: &str) -> Result<ServerDefinition, String> {
-        self.db_manager.get_server(tool_id)
+        self.read(
+            |db| db.get_server(tool_id),
+            |cache, server| {
+                cache.servers.insert(tool_id.to_string(), server.clone());
+            },
+            |cache| cache.servers.get(tool_id).cloned(),
+        )
     }
 
     /// Get all servers
     pub fn get_all_servers(&self) -> Result<HashMap<String, ServerDefinition>, String> {
-        self.db_manager.get_all_servers()
+        self.read(
+            |db| db.get_all_servers(),
+            |cache, servers| {
+                cache.servers = servers.clone();
+                cache.servers_loaded = true;
+            },
+            |cache| cache.servers_loaded.then(|| cache.servers.clone()),
+        )
+    }
+
+    /// Every server as last read or written, without going to the database
+    ///
+    /// None until every server was loaded once, see [`Self::get_all_servers`].
+    pub fn cached_servers(&self) -> Option<HashMap<String, ServerDefinition>> {
+        let cache = self.cache.lock().unwrap();
+        cache.servers_loaded.then(|| cache.servers.clone())
     }
 
     /// Save or update a server
     pub fn save_server(&self, tool_id: &str, tool: &ServerDefinition) -> Result<(), String> {
-        self.db_manager.save_server(tool_id, tool)
+        self.write_durably(|db| db.save_server(tool_id, tool))?;
+        let mut cache = self.cache.lock().unwrap();
+        cache.servers.insert(tool_id.to_string(), tool.clone());
+        Ok(())
     }
 
     /// Delete a server
     pub fn delete_server(&self, tool_id: &str) -> Result<(), String> {
-        self.db_manager.delete_server(tool_id)
+        self.write_durably(|db| db.delete_server(tool_id))?;
+        let mut cache = self.cache.lock().unwrap();
+        cache.servers.remove(tool_id);
+        cache.server_tools.remove(tool_id);
+        cache.provenances.remove(tool_id);
+        cache.run_states.remove(tool_id);
+        cache.lifecycle.remove(tool_id);
+        Ok(())
+    }
+
+    /// The id a server registered as `requested` gets, see [`normalize_server_id`]
+    ///
+    /// Valid ids are kept, so registering one again replaces the server. Other ids get
+    /// a slug of their own, or of `name` when empty, that no installed server uses, and
+    /// are kept as an alias of it.
+    pub fn assign_server_id(&self, requested: &str, name: &str) -> Result<String, String> {
+        if validate_server_id(requested).is_ok() {
+            return Ok(requested.to_string());
+        }
+        if requested.trim().is_empty() {
+            let servers = self.get_all_servers()?;
+            return Ok(unique_server_id(&slugify_server_id(name), |id| {
+                servers.contains_key(id)
+            }));
+        }
+        // Registered before under the same id
+        if let Some(server_id) =
+            self.read(|db| db.get_server_id_alias(requested), |_, _| {}, |_| None)?
+        {
+            return Ok(server_id);
+        }
+        let servers = self.get_all_servers()?;
+        let server_id = unique_server_id(&normalize_server_id(requested), |id| {
+            servers.contains_key(id)
+        });
+        self.write_durably(|db| db.save_server_id_alias(requested, &server_id))?;
+        Ok(server_id)
+    }
+
+    /// The id of the server `id` refers to, following the alias of a former id
+    pub fn resolve_server_id(&self, id: &str) -> String {
+        // Only ids that aren't valid are aliases
+        if validate_server_id(id).is_ok() {
+            return id.to_string();
+        }
+        match self.read(|db| db.get_server_id_alias(id), |_, _| {}, |_| None) {
+            Ok(Some(server_id)) => server_id,
+            _ => id.to_string(),
+        }
     }
 
     /// Save a server tool
     pub fn save_server_tool(&self, tool: &ServerToolInfo) -> Result<(), String> {
-        self.db_manager.save_server_tool(tool)
+        self.write(PendingWrite::SaveServerTool(tool.clone()))
     }
 
     /// Get a server tool by ID and server_id
-    pub fn get_server_tool(&self, tool_id: &str, server_id: &str) -> Result<ServerToolInfo, String> {
-        self.db_manager.get_server_tool(tool_id, server_id)
+    pub fn get_server_tool(
+        &self,
+        tool_id: &str,
+        server_id: &str,
+    ) -> Result<ServerToolInfo, String> {
+        self.read(
+            |db| db.get_server_tool(tool_id, server_id),
+            |_, _| {},
+            |cache| {
+                cache
+                    .server_tools
+                    .get(server_id)?
+                    .iter()
+                    .find(|tool| tool.id == tool_id)
+                    .cloned()
+            },
+        )
     }
 
     /// Get all server tools for a server
     pub fn get_server_tools(&self, server_id: &str) -> Result<Vec<ServerToolInfo>, String> {
-        self.db_manager.get_server_tools(server_id)
+        self.read(
+            |db| db.get_server_tools(server_id),
+            |cache, tools| {
+                cache
+                    .server_tools
+                    .insert(server_id.to_string(), tools.clone());
+            },
+            |cache| cache.server_tools.get(server_id).cloned(),
+        )
     }
 
     /// Delete a server tool
     pub fn delete_server_tool(&self, tool_id: &str, server_id: &str) -> Result<(), String> {
-        self.db_manager.delete_server_tool(tool_id, server_id)
+        self.write(PendingWrite::DeleteServerTool {
+            tool_id: tool_id.to_string(),
+            server_id: server_id.to_string(),
+        })
     }
 
     /// Get a setting by key
     pub fn get_setting(&self, key: &str) -> Result<String, String> {
-        self.db_manager.get_setting(key)
+        self.read(
+            |db| db.get_setting(key),
+            |cache, value| {
+                cache.settings.insert(key.to_string(), value.clone());
+            },
+            |cache| cache.settings.get(key).cloned(),
+        )
     }
 
     /// Save or update a setting
     pub fn save_setting(&self, key: &str, value: &str) -> Result<(), String> {
-        self.db_manager.save_setting(key, value)
+        self.write(PendingWrite::SaveSetting {
+            key: key.to_string(),
+            value: value.to_string(),
+        })
+    }
+
+    /// Save or replace the process record of a server
+    pub fn save_server_process(&self, record: &SpawnedProcessRecord) -> Result<(), String> {
+        self.write(PendingWrite::SaveServerProcess(record.clone()))
+    }
+
+    /// Get all recorded server processes
+    pub fn get_server_processes(&self) -> Result<Vec<SpawnedProcessRecord>, String> {
+        self.read(|db| db.get_server_processes(), |_, _| {}, |_| None)
+    }
+
+    /// Delete the process record of a server
+    pub fn delete_server_process(&self, server_id: &str) -> Result<(), String> {
+        self.write(PendingWrite::DeleteServerProcess(server_id.to_string()))
+    }
+
+    /// Save or replace the provenance of a server
+    pub fn save_server_provenance(
+        &self,
+        server_id: &str,
+        provenance: &ServerProvenance,
+    ) -> Result<(), String> {
+        self.write(PendingWrite::SaveServerProvenance(
+            server_id.to_string(),
+            provenance.clone(),
+        ))
+    }
+
+    /// Get the provenance of every server that has one, by server id
+    pub fn get_server_provenances(&self) -> Result<HashMap<String, ServerProvenance>, String> {
+        self.read(
+            |db| db.get_server_provenances(),
+            |cache, provenances| {
+                cache.provenances = provenances.clone();
+                cache.provenances_loaded = true;
+            },
+            |cache| cache.provenances_loaded.then(|| cache.provenances.clone()),
+        )
+    }
+
+    /// Save what a server said about itself in its initialize handshake
+    pub fn save_server_info(&self, server_id: &str, info: &ServerInitInfo) -> Result<(), String> {
+        self.write(PendingWrite::SaveServerInfo(
+            server_id.to_string(),
+            info.clone(),
+        ))
+    }
+
+    /// Get what every server that started once said about itself, by server id
+    pub fn get_server_infos(&self) -> Result<HashMap<String, ServerInitInfo>, String> {
+        self.read(|db| db.get_server_infos(), |_, _| {}, |_| None)
+    }
+
+    /// Save whether the tool calls of a server are recorded or replayed
+    pub fn save_server_call_mode(
+        &self,
+        server_id: &str,
+        call_mode: &ServerCallMode,
+    ) -> Result<(), String> {
+        self.write_durably(|db| db.save_server_call_mode(server_id, call_mode))
+    }
+
+    /// Get the call mode of every server that has one, by server id
+    pub fn get_server_call_modes(&self) -> Result<HashMap<String, ServerCallMode>, String> {
+        self.read(|db| db.get_server_call_modes(), |_, _| {}, |_| None)
+    }
+
+    /// Call mode of `server_id`, live unless one was saved
+    pub fn server_call_mode(&self, server_id: &str) -> ServerCallMode {
+        self.get_server_call_modes()
+            .ok()
+            .and_then(|call_modes| call_modes.get(server_id).copied())
+            .unwrap_or_default()
+    }
+
+    /// Save whether the user wants a server running, see [`ServerRegistry::desired_running`]
+    pub fn save_server_run_state(
+        &self,
+        server_id: &str,
+        desired_running: bool,
+    ) -> Result<(), String> {
+        self.write(PendingWrite::SaveServerRunState {
+            server_id: server_id.to_string(),
+            desired_running,
+        })
+    }
+
+    /// Get whether the user wants each server running, for the servers with a saved
+    /// run state
+    pub fn get_server_run_states(&self) -> Result<HashMap<String, bool>, String> {
+        self.read(
+            |db| db.get_server_run_states(),
+            |cache, run_states| {
+                cache.run_states = run_states.clone();
+                cache.run_states_loaded = true;
+            },
+            |cache| cache.run_states_loaded.then(|| cache.run_states.clone()),
+        )
+    }
+
+    /// Save whether the user wants each of `server_ids` running, all at once
+    pub fn save_server_run_states(
+        &self,
+        server_ids: &[String],
+        desired_running: bool,
+    ) -> Result<(), String> {
+        self.write_durably(|db| db.save_server_run_states(server_ids, desired_running))?;
+        let mut cache = self.cache.lock().unwrap();
+        for server_id in server_ids {
+            cache.run_states.insert(server_id.clone(), desired_running);
+        }
+        Ok(())
+    }
+
+    /// Enable or disable each of `server_ids` and save whether the user wants them
+    /// running, all at once
+    pub fn set_servers_enabled(&self, server_ids: &[String], enabled: bool) -> Result<(), String> {
+        self.write_durably(|db| db.set_servers_enabled(server_ids, enabled))?;
+        let mut cache = self.cache.lock().unwrap();
+        for server_id in server_ids {
+            if let Some(server) = cache.servers.get_mut(server_id) {
+                server.enabled = enabled;
+            }
+            cache.run_states.insert(server_id.clone(), enabled);
+        }
+        Ok(())
+    }
+
+    /// Whether the user wants `server_id` running
+    ///
+    /// Servers without a saved run state, installed before it was recorded, are
+    /// wanted running as long as they are enabled.
+    pub fn desired_running(&self, server_id: &str) -> bool {
+        self.get_server_run_states()
+            .ok()
+            .and_then(|run_states| run_states.get(server_id).copied())
+            .unwrap_or(true)
+    }
+
+    /// Append an entry to the audit log
+    pub fn append_audit_entry(&self, entry: &AuditEntry) -> Result<(), String> {
+        self.write(PendingWrite::AppendAuditEntry(entry.clone()))
+    }
+
+    /// Get the most recent audit log entries, newest first
+    pub fn get_audit_log(&self, limit: i64) -> Result<Vec<AuditEntry>, String> {
+        self.read(|db| db.get_audit_log(limit), |_, _| {}, |_| None)
+    }
+
+    /// Append a lifecycle event to the history of its server
+    ///
+    /// The event is kept in memory even when it can't be saved, so that the history of
+    /// this run stays complete.
+    pub fn record_lifecycle_event(&self, event: &LifecycleEvent) -> Result<(), String> {
+        self.load_lifecycle_history();
+        self.cache.lock().unwrap().lifecycle.record(event.clone());
+        self.write(PendingWrite::AppendLifecycleEvent(event.clone()))
+    }
+
+    /// Get the lifecycle events of a server kept in memory, oldest first
+    pub fn get_lifecycle_history(&self, server_id: &str) -> Vec<LifecycleEvent> {
+        self.load_lifecycle_history();
+        self.cache.lock().unwrap().lifecycle.events(server_id)
+    }
+
+    /// Seed the lifecycle history with the saved events, once
+    ///
+    /// Without the database the history starts empty.
+    fn load_lifecycle_history(&self) {
+        if self.cache.lock().unwrap().lifecycle_loaded {
+            return;
+        }
+        let events = match self.read(|db| db.get_lifecycle_events(), |_, _| {}, |_| None) {
+            Ok(events) => events,
+            Err(e) => {
+                warn!("Failed to load the lifecycle history: {}", e);
+                Vec::new()
+            }
+        };
+        // Events are only recorded once loaded, another load may have won the race
+        let mut cache = self.cache.lock().unwrap();
+        if !cache.lifecycle_loaded {
+            cache.lifecycle = LifecycleHistory::from_events(events);
+            cache.lifecycle_loaded = true;
+        }
+    }
+
+    /// Create a scheduled job or update its definition
+    pub fn save_schedule(&self, schedule: &ScheduleDefinition) -> Result<(), String> {
+        self.write_durably(|db| db.save_schedule(schedule))
+    }
+
+    /// Get every scheduled job, by id
+    pub fn get_schedules(&self) -> Result<Vec<Schedule>, String> {
+        self.read(|db| db.get_schedules(), |_, _| {}, |_| None)
+    }
+
+    /// Delete a scheduled job with its run history
+    pub fn delete_schedule(&self, schedule_id: &str) -> Result<(), String> {
+        self.write_durably(|db| db.delete_schedule(schedule_id))
+    }
+
+    /// Record the outcome of a run of a scheduled job
+    pub fn record_schedule_run(&self, run: &ScheduleRun) -> Result<(), String> {
+        self.write_durably(|db| db.record_schedule_run(run))
+    }
+
+    /// Get the most recent runs of a scheduled job, newest first
+    pub fn get_schedule_runs(
+        &self,
+        schedule_id: &str,
+        limit: i64,
+    ) -> Result<Vec<ScheduleRun>, String> {
+        self.read(
+            |db| db.get_schedule_runs(schedule_id, limit),
+            |_, _| {},
+            |_| None,
+        )
+    }
+
+    /// Record the configuration a server had before a change, returning the snapshot id
+    pub fn record_config_snapshot(
+        &self,
+        server_id: &str,
+        created_at: u64,
+        configuration: &ServerConfiguration,
+    ) -> Result<i64, String> {
+        let mut snapshot_id = 0;
+        self.write_durably(|db| {
+            snapshot_id = db.record_config_snapshot(server_id, created_at, configuration)?;
+            Ok(())
+        })?;
+        Ok(snapshot_id)
+    }
+
+    /// Get the most recent configuration snapshots of a server, newest first
+    pub fn get_config_history(
+        &self,
+        server_id: &str,
+        limit: i64,
+    ) -> Result<Vec<ConfigSnapshot>, String> {
+        self.read(
+            |db| db.get_config_history(server_id, limit),
+            |_, _| {},
+            |_| None,
+        )
+    }
+
+    /// Get a configuration snapshot by its id
+    pub fn get_config_snapshot(&self, snapshot_id: i64) -> Result<ConfigSnapshot, String> {
+        self.read(
+            |db| db.get_config_snapshot(snapshot_id),
+            |_, _| {},
+            |_| None,
+        )
+    }
+
+    /// Record a change in the undo journal with its sealed before-image, returning the
+    /// entry id
+    pub fn record_undo_entry(&self, entry: &UndoEntry, before_image: &str) -> Result<i64, String> {
+        let mut entry_id = 0;
+        self.write_durably(|db| {
+            entry_id = db.record_undo_entry(entry, before_image)?;
+            Ok(())
+        })?;
+        Ok(entry_id)
+    }
+
+    /// Get the entries of the undo journal that haven't expired at `now`, newest first
+    pub fn get_undo_entries(&self, now: u64) -> Result<Vec<UndoEntry>, String> {
+        self.read(|db| db.get_undo_entries(now), |_, _| {}, |_| None)
+    }
+
+    /// Get an entry of the undo journal with its sealed before-image
+    pub fn get_undo_entry(&self, entry_id: i64) -> Result<(UndoEntry, String), String> {
+        self.read(|db| db.get_undo_entry(entry_id), |_, _| {}, |_| None)
+    }
+
+    pub fn delete_undo_entry(&self, entry_id: i64) -> Result<(), String> {
+        self.write_durably(|db| db.delete_undo_entry(entry_id))
+    }
+
+    /// Create a webhook or replace it
+    pub fn save_webhook(&self, webhook: &Webhook) -> Result<(), String> {
+        self.write_durably(|db| db.save_webhook(webhook))
+    }
+
+    /// Get every webhook with its secret, by id
+    pub fn get_webhooks(&self) -> Result<Vec<Webhook>, String> {
+        self.read(|db| db.get_webhooks(), |_, _| {}, |_| None)
+    }
+
+    pub fn delete_webhook(&self, webhook_id: &str) -> Result<(), String> {
+        self.write_durably(|db| db.delete_webhook(webhook_id))
+    }
+
+    /// Keep a payload that could not be delivered
+    pub fn record_webhook_dead_letter(&self, dead_letter: &DeadLetter) -> Result<(), String> {
+        self.write_durably(|db| db.record_webhook_dead_letter(dead_letter))
+    }
+
+    /// Get the most recent dead letters, newest first
+    pub fn get_webhook_dead_letters(&self, limit: i64) -> Result<Vec<DeadLetter>, String> {
+        self.read(|db| db.get_webhook_dead_letters(limit), |_, _| {}, |_| None)
+    }
+
+    /// Create a registry source or replace it
+    pub fn save_registry_source(&self, source: &RegistrySource) -> Result<(), String> {
+        self.write_durably(|db| db.save_registry_source(source))?;
+        if let Some(sources) = &mut self.cache.lock().unwrap().registry_sources {
+            sources.retain(|known| known.id != source.id);
+            sources.push(source.clone());
+            sources.sort_by(|a, b| a.id.cmp(&b.id));
+        }
+        Ok(())
+    }
+
+    /// Get every registry source with its credentials, by id
+    pub fn get_registry_sources(&self) -> Result<Vec<RegistrySource>, String> {
+        self.read(
+            |db| db.get_registry_sources(),
+            |cache, sources| cache.registry_sources = Some(sources.clone()),
+            |cache| cache.registry_sources.clone(),
+        )
+    }
+
+    /// Registry sources as last read, without going to the database
+    pub fn cached_registry_sources(&self) -> Option<Vec<RegistrySource>> {
+        self.cache.lock().unwrap().registry_sources.clone()
+    }
+
+    pub fn delete_registry_source(&self, source_id: &str) -> Result<(), String> {
+        self.write_durably(|db| db.delete_registry_source(source_id))?;
+        if let Some(sources) = &mut self.cache.lock().unwrap().registry_sources {
+            sources.retain(|source| source.id != source_id);
+        }
+        Ok(())
+    }
+
+    /// Apply the writes queued while the database was unavailable, and leave the
+    /// degraded mode once the database answers again
+    ///
+    /// Called periodically in the background, returns the number of writes applied.
+    pub fn retry_storage(&self) -> usize {
+        if !self.storage.is_degraded() {
+            return 0;
+        }
+        let applied = self
+            .storage
+            .flush(|write| apply_write(&self.db_manager, write));
+        if self.storage.status().pending_writes == 0 {
+            // Nothing left to write, check the database answers reads too
+            match self.db_manager.get_all_servers() {
+                Ok(servers) => {
+                    let mut cache = self.cache.lock().unwrap();
+                    cache.servers = servers;
+                    cache.servers_loaded = true;
+                    drop(cache);
+                    self.storage.mark_available();
+                }
+                Err(e) if is_storage_error(&e) => self.storage.mark_unavailable(&e),
+                Err(_) => self.storage.mark_available(),
+            }
+        }
+        applied
+    }
+
+    /// Read from the database, or from memory while it is unavailable
+    ///
+    /// `remember` stores a successful read in memory and `cached` looks it up.
+    fn read<T>(
+        &self,
+        read: impl FnOnce(&DBManager) -> Result<T, String>,
+        remember: impl FnOnce(&mut StorageCache, &T),
+        cached: impl Fn(&StorageCache) -> Option<T>,
+    ) -> Result<T, String> {
+        // Memory has the writes that are still queued
+        if self.storage.is_degraded() {
+            if let Some(value) = cached(&self.cache.lock().unwrap()) {
+                return Ok(value);
+            }
+        }
+        match read(&self.db_manager) {
+            Ok(value) => {
+                remember(&mut self.cache.lock().unwrap(), &value);
+                Ok(value)
+            }
+            Err(e) if is_storage_error(&e) => {
+                self.storage.mark_unavailable(&e);
+                cached(&self.cache.lock().unwrap()).ok_or_else(|| StorageUnavailable(e).to_string())
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Write to the database, or queue the write while it is unavailable
+    fn write(&self, write: PendingWrite) -> Result<(), String> {
+        if !self.storage.is_degraded() {
+            match apply_write(&self.db_manager, &write) {
+                Err(e) if is_storage_error(&e) => self.storage.mark_unavailable(&e),
+                Err(e) => return Err(e),
+                Ok(()) => {
+                    self.cache.lock().unwrap().remember(&write);
+                    return Ok(());
+                }
+            }
+        }
+        self.storage
+            .enqueue(write.clone())
+            .map_err(|e| e.to_string())?;
+        self.cache.lock().unwrap().remember(&write);
+        Ok(())
+    }
+
+    /// Write to the database, failing with [`StorageUnavailable`] when it can't be
+    /// reached
+    fn write_durably(
+        &self,
+        write: impl FnOnce(&DBManager) -> Result<(), String>,
+    ) -> Result<(), String> {
+        match write(&self.db_manager) {
+            Err(e) if is_storage_error(&e) => {
+                self.storage.mark_unavailable(&e);
+                Err(StorageUnavailable(e).to_string())
+            }
+            result => result,
+        }
+    }
+}
+
+fn apply_write(db: &DBManager, write: &PendingWrite) -> Result<(), String> {
+    match write {
+        PendingWrite::SaveServerTool(tool) => db.save_server_tool(tool),
+        PendingWrite::DeleteServerTool { tool_id, server_id } => {
+            db.delete_server_tool(tool_id, server_id)
+        }
+        PendingWrite::SaveSetting { key, value } => db.save_setting(key, value),
+        PendingWrite::SaveServerProcess(record) => db.save_server_process(record),
+        PendingWrite::DeleteServerProcess(server_id) => db.delete_server_process(server_id),
+        PendingWrite::SaveServerProvenance(server_id, provenance) => {
+            db.save_server_provenance(server_id, provenance)
+        }
+        PendingWrite::SaveServerInfo(server_id, info) => db.save_server_info(server_id, info),
+        PendingWrite::SaveServerRunState {
+            server_id,
+            desired_running,
+        } => db.save_server_run_state(server_id, *desired_running),
+        PendingWrite::AppendAuditEntry(entry) => db.append_audit_entry(entry),
+        PendingWrite::AppendLifecycleEvent(event) => db.append_lifecycle_event(event),
     }
 }
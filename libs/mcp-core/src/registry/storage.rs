@@ -0,0 +1,247 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use log::{info, warn};
+use serde::Serialize;
+
+use crate::lifecycle::{LifecycleEvent, LifecycleHistory};
+use crate::models::types::{
+    AuditEntry, ServerDefinition, ServerInitInfo, ServerProvenance, ServerToolInfo,
+    SpawnedProcessRecord,
+};
+use crate::registry::registry_sources::RegistrySource;
+
+/// Writes kept in memory while the database is unavailable, newer ones are refused
+pub const MAX_PENDING_WRITES: usize = 256;
+
+/// How often pending writes are retried while the database is unavailable
+pub const STORAGE_RETRY_INTERVAL: Duration = Duration::from_secs(10);
+
+/// How long request handlers wait for the database before answering from memory
+pub const REGISTRY_LOOKUP_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Machine readable reason of [`StorageUnavailable`] errors in JSON-RPC error data
+pub const STORAGE_UNAVAILABLE_REASON: &str = "storage_unavailable";
+
+const STORAGE_UNAVAILABLE_PREFIX: &str = "Storage unavailable";
+
+/// Error of an operation that needs the database while it can't be reached
+///
+/// Registry methods return errors as strings, [`StorageUnavailable::is`] recognizes the
+/// message of this error, even when it was wrapped in another message.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+#[error("Storage unavailable: {0}")]
+pub struct StorageUnavailable(pub String);
+
+impl StorageUnavailable {
+    pub fn is(error: &str) -> bool {
+        error.contains(STORAGE_UNAVAILABLE_PREFIX)
+    }
+}
+
+/// Whether a database error means the database can't be reached or written right
+/// now, as opposed to an error with the data itself
+pub fn is_storage_error(error: &str) -> bool {
+    let error = error.to_lowercase();
+    [
+        "failed to get database connection",
+        "unable to open database",
+        "database or disk is full",
+        "disk i/o error",
+        "database is locked",
+        "readonly database",
+        "no such file or directory",
+    ]
+    .iter()
+    .any(|marker| error.contains(marker))
+}
+
+/// A write that couldn't reach the database, applied once it is back
+#[derive(Clone, Debug)]
+pub enum PendingWrite {
+    SaveServerTool(ServerToolInfo),
+    DeleteServerTool {
+        tool_id: String,
+        server_id: String,
+    },
+    SaveSetting {
+        key: String,
+        value: String,
+    },
+    SaveServerProcess(SpawnedProcessRecord),
+    DeleteServerProcess(String),
+    SaveServerProvenance(String, ServerProvenance),
+    SaveServerInfo(String, ServerInitInfo),
+    SaveServerRunState {
+        server_id: String,
+        desired_running: bool,
+    },
+    AppendAuditEntry(AuditEntry),
+    AppendLifecycleEvent(LifecycleEvent),
+}
+
+/// Whether the database can be used, reported by `/health` and `server/info`
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct StorageStatus {
+    /// True while the database is unavailable and reads are served from memory
+    pub degraded: bool,
+    /// Unix time, in seconds, the database became unavailable
+    pub since: Option<u64>,
+    pub last_error: Option<String>,
+    /// Writes waiting for the database
+    pub pending_writes: usize,
+    /// Writes refused because too many were pending
+    pub dropped_writes: u64,
+}
+
+/// What is known in memory of the database, kept up to date by every read and write
+/// that goes through the registry
+#[derive(Default)]
+pub(crate) struct StorageCache {
+    /// Servers by id, complete once every server was loaded
+    pub servers: HashMap<String, ServerDefinition>,
+    pub servers_loaded: bool,
+    pub settings: HashMap<String, String>,
+    pub server_tools: HashMap<String, Vec<ServerToolInfo>>,
+    pub provenances: HashMap<String, ServerProvenance>,
+    pub provenances_loaded: bool,
+    pub run_states: HashMap<String, bool>,
+    pub run_states_loaded: bool,
+    /// Lifecycle events of every server, including those older than the saved ones
+    pub lifecycle: LifecycleHistory,
+    pub lifecycle_loaded: bool,
+    /// Registry sources with their credentials, once read
+    pub registry_sources: Option<Vec<RegistrySource>>,
+}
+
+impl StorageCache {
+    /// Apply a write to what is known in memory
+    pub fn remember(&mut self, write: &PendingWrite) {
+        match write {
+            PendingWrite::SaveServerTool(tool) => {
+                let tools = self.server_tools.entry(tool.server_id.clone()).or_default();
+                tools.retain(|known| known.id != tool.id);
+                tools.push(tool.clone());
+            }
+            PendingWrite::DeleteServerTool { tool_id, server_id } => {
+                if let Some(tools) = self.server_tools.get_mut(server_id) {
+                    tools.retain(|known| &known.id != tool_id);
+                }
+            }
+            PendingWrite::SaveSetting { key, value } => {
+                self.settings.insert(key.clone(), value.clone());
+            }
+            PendingWrite::SaveServerProvenance(server_id, provenance) => {
+                self.provenances
+                    .insert(server_id.clone(), provenance.clone());
+            }
+            PendingWrite::SaveServerRunState {
+                server_id,
+                desired_running,
+            } => {
+                self.run_states.insert(server_id.clone(), *desired_running);
+            }
+            PendingWrite::SaveServerProcess(_)
+            | PendingWrite::DeleteServerProcess(_)
+            | PendingWrite::SaveServerInfo(_, _)
+            | PendingWrite::AppendAuditEntry(_) => {}
+            // Kept in memory even when it can't be saved, see `record_lifecycle_event`
+            PendingWrite::AppendLifecycleEvent(_) => {}
+        }
+    }
+}
+
+#[derive(Default)]
+struct StorageInner {
+    status: StorageStatus,
+    pending: VecDeque<PendingWrite>,
+}
+
+/// Availability of the database and the writes waiting for it
+#[derive(Clone, Default)]
+pub struct StorageHealth {
+    inner: Arc<Mutex<StorageInner>>,
+}
+
+impl StorageHealth {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn status(&self) -> StorageStatus {
+        let inner = self.inner.lock().unwrap();
+        StorageStatus {
+            pending_writes: inner.pending.len(),
+            ..inner.status.clone()
+        }
+    }
+
+    pub fn is_degraded(&self) -> bool {
+        self.inner.lock().unwrap().status.degraded
+    }
+
+    /// Record that the database failed with `error`
+    pub fn mark_unavailable(&self, error: &str) {
+        let mut inner = self.inner.lock().unwrap();
+        if !inner.status.degraded {
+            warn!("Database unavailable, serving reads from memory: {}", error);
+            inner.status.degraded = true;
+            inner.status.since = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .ok()
+                .map(|d| d.as_secs());
+        }
+        inner.status.last_error = Some(error.to_string());
+    }
+
+    /// Record that the database answered, leaving the degraded mode once no write is
+    /// pending
+    pub fn mark_available(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.status.degraded && inner.pending.is_empty() {
+            info!("Database available again");
+            inner.status.degraded = false;
+            inner.status.since = None;
+            inner.status.last_error = None;
+        }
+    }
+
+    /// Queue a write for when the database is back
+    pub fn enqueue(&self, write: PendingWrite) -> Result<(), StorageUnavailable> {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.pending.len() >= MAX_PENDING_WRITES {
+            inner.status.dropped_writes += 1;
+            return Err(StorageUnavailable(format!(
+                "{} writes are already waiting for the database",
+                MAX_PENDING_WRITES
+            )));
+        }
+        inner.pending.push_back(write);
+        Ok(())
+    }
+
+    /// Apply the pending writes in order with `apply`, stopping at the first one that
+    /// fails because the database is still unavailable
+    ///
+    /// Writes failing for another reason, such as a tool of a server deleted since,
+    /// are logged and dropped. Returns the number of writes applied.
+    pub fn flush(&self, mut apply: impl FnMut(&PendingWrite) -> Result<(), String>) -> usize {
+        let mut applied = 0;
+        loop {
+            let Some(write) = self.inner.lock().unwrap().pending.front().cloned() else {
+                break;
+            };
+            match apply(&write) {
+                Err(e) if is_storage_error(&e) => {
+                    self.mark_unavailable(&e);
+                    return applied;
+                }
+                Err(e) => warn!("Dropping pending write {:?}: {}", write, e),
+                Ok(()) => applied += 1,
+            }
+            self.inner.lock().unwrap().pending.pop_front();
+        }
+        applied
+    }
+}
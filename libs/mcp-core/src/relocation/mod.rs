@@ -0,0 +1,212 @@
+//! Servers left with broken paths when the data directory moved to another machine.
+//!
+//! Restoring a backup of the data directory on a new machine, or under another user,
+//! keeps the absolute paths of the servers (commands, arguments, env values), which
+//! then point at nothing. At startup the [`EnvironmentFingerprint`] of the machine is
+//! compared with the one saved under [`ENVIRONMENT_FINGERPRINT_SETTING`]. When it
+//! changed, the absolute paths of every server are checked and the missing ones listed
+//! in a [`MigrationReport`], kept under [`MIGRATION_REPORT_SETTING`]. Missing paths under
+//! the previous home directory can be rewritten to the same place under the new one.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config_history::ConfigApplyResult;
+use crate::models::types::{EnvValue, ServerDefinition};
+
+/// Setting holding the fingerprint of the machine of the last run
+pub const ENVIRONMENT_FINGERPRINT_SETTING: &str = "environment_fingerprint";
+
+/// Setting holding the report of the last change of machine
+pub const MIGRATION_REPORT_SETTING: &str = "migration_report";
+
+/// Result of moving the paths of each server, by server id
+pub type PathMigrationResults = BTreeMap<String, ConfigApplyResult>;
+
+/// What identifies the machine and the user the data directory is used by
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnvironmentFingerprint {
+    pub hostname: String,
+    pub home_dir: String,
+    pub os: String,
+}
+
+impl EnvironmentFingerprint {
+    /// Fingerprint of the machine running Dockmaster
+    pub fn current() -> Self {
+        Self {
+            hostname: sysinfo::System::host_name().unwrap_or_default(),
+            home_dir: home::home_dir()
+                .map(|home| home.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            os: std::env::consts::OS.to_string(),
+        }
+    }
+}
+
+/// An absolute path of a server that doesn't exist on this machine
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MissingPath {
+    pub server_id: String,
+    /// Where the path is in the server, like `command`, `args[1]` or `env.CONFIG`
+    pub field: String,
+    pub path: String,
+    /// Same path under the new home directory, when it was under the previous one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rewrite: Option<String>,
+    /// Whether the rewritten path exists
+    #[serde(default)]
+    pub rewrite_exists: bool,
+}
+
+/// Servers whose paths are missing since the data directory changed machine
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MigrationReport {
+    /// When the change was found, in milliseconds since the epoch
+    pub created_at: u64,
+    pub previous: EnvironmentFingerprint,
+    pub current: EnvironmentFingerprint,
+    pub missing_paths: Vec<MissingPath>,
+}
+
+impl MigrationReport {
+    /// Servers with missing paths, sorted
+    pub fn server_ids(&self) -> Vec<String> {
+        self.missing_paths
+            .iter()
+            .map(|missing| missing.server_id.clone())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
+    /// Missing paths that can be rewritten under the new home directory
+    pub fn rewritable(&self) -> usize {
+        self.missing_paths
+            .iter()
+            .filter(|missing| missing.rewrite.is_some())
+            .count()
+    }
+}
+
+/// Whether a value is an absolute path, on this machine or the usual other ones
+pub fn is_absolute_path(value: &str) -> bool {
+    let bytes = value.as_bytes();
+    let windows_drive = bytes.len() > 2
+        && bytes[0].is_ascii_alphabetic()
+        && bytes[1] == b':'
+        && matches!(bytes[2], b'\\' | b'/');
+    value.starts_with('/') || windows_drive || Path::new(value).is_absolute()
+}
+
+/// `path` moved from under `old_home` to under `new_home`, `None` when it isn't there
+pub fn rebase_home(path: &str, old_home: &str, new_home: &str) -> Option<String> {
+    let old_home = old_home.trim_end_matches(['/', '\\']);
+    if old_home.is_empty() || new_home.is_empty() {
+        return None;
+    }
+    let rest = path.strip_prefix(old_home)?;
+    if !(rest.is_empty() || rest.starts_with(['/', '\\'])) {
+        return None;
+    }
+    Some(format!(
+        "{}{}",
+        new_home.trim_end_matches(['/', '\\']),
+        rest
+    ))
+}
+
+/// Absolute paths a server keeps, with where they are in it
+pub fn server_paths(server: &ServerDefinition) -> Vec<(String, String)> {
+    let mut paths = Vec::new();
+    if let Some(entry_point) = &server.entry_point {
+        paths.push(("entry_point".to_string(), entry_point.clone()));
+    }
+    if let Some(configuration) = &server.configuration {
+        if let Some(command) = &configuration.command {
+            paths.push(("command".to_string(), command.clone()));
+        }
+        for (i, arg) in configuration.args.iter().flatten().enumerate() {
+            paths.push((format!("args[{}]", i), arg.clone()));
+        }
+        let mut env: Vec<_> = configuration.env.iter().flatten().collect();
+        env.sort_by(|a, b| a.0.cmp(b.0));
+        for (key, env) in env {
+            if let Some(EnvValue::String(value)) = &env.default {
+                paths.push((format!("env.{}", key), value.clone()));
+            }
+        }
+    }
+    paths.retain(|(_, path)| is_absolute_path(path));
+    paths
+}
+
+/// Compare the paths of `servers` with what `exists` finds on this machine
+pub fn find_missing_paths(
+    servers: &HashMap<String, ServerDefinition>,
+    previous: &EnvironmentFingerprint,
+    current: &EnvironmentFingerprint,
+    exists: impl Fn(&Path) -> bool,
+) -> Vec<MissingPath> {
+    let mut server_ids: Vec<&String> = servers.keys().collect();
+    server_ids.sort();
+    let mut missing = Vec::new();
+    for server_id in server_ids {
+        for (field, path) in server_paths(&servers[server_id]) {
+            if exists(Path::new(&path)) {
+                continue;
+            }
+            let rewrite = rebase_home(&path, &previous.home_dir, &current.home_dir)
+                .filter(|rewrite| *rewrite != path);
+            missing.push(MissingPath {
+                server_id: server_id.clone(),
+                field,
+                rewrite_exists: rewrite
+                    .as_ref()
+                    .is_some_and(|rewrite| exists(Path::new(rewrite))),
+                path,
+                rewrite,
+            });
+        }
+    }
+    missing
+}
+
+/// Replace the paths of a server listed in `missing` by their rewrite, returns how many
+pub fn rewrite_server_paths(server: &mut ServerDefinition, missing: &[MissingPath]) -> usize {
+    let rewrite = |value: &mut String| {
+        let Some(rewrite) = missing
+            .iter()
+            .find(|missing| missing.path == *value)
+            .and_then(|missing| missing.rewrite.clone())
+        else {
+            return 0;
+        };
+        *value = rewrite;
+        1
+    };
+    let mut rewritten = 0;
+    if let Some(entry_point) = &mut server.entry_point {
+        rewritten += rewrite(entry_point);
+    }
+    if let Some(configuration) = &mut server.configuration {
+        if let Some(command) = &mut configuration.command {
+            rewritten += rewrite(command);
+        }
+        for arg in configuration.args.iter_mut().flatten() {
+            rewritten += rewrite(arg);
+        }
+        for env in configuration
+            .env
+            .iter_mut()
+            .flat_map(|env| env.values_mut())
+        {
+            if let Some(EnvValue::String(value)) = &mut env.default {
+                rewritten += rewrite(value);
+            }
+        }
+    }
+    rewritten
+}
@@ -0,0 +1,206 @@
+//! Five-field cron expressions, evaluated in UTC.
+//!
+//! Fields are minute, hour, day of month, month and day of week, each one `*`, a
+//! value, a range `a-b`, a step `*/n` or `a-b/n`, or a comma separated list of these.
+//! Months and days of the week also take their English three letter names, and
+//! Sunday is both 0 and 7. As in most crons, a job runs on the days matching either
+//! the day of month or the day of week when both are restricted. The `@hourly`,
+//! `@daily`, `@weekly`, `@monthly` and `@yearly` shortcuts are understood too.
+
+use std::fmt;
+use std::str::FromStr;
+
+const MINUTE_MS: u64 = 60_000;
+const DAY_MINUTES: u64 = 24 * 60;
+
+/// Years searched for the next run before giving up, e.g. on `0 0 30 2 *`
+const SEARCH_YEARS: i64 = 5;
+
+const MONTHS: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+const WEEKDAYS: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+/// A parsed cron expression
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CronExpr {
+    source: String,
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    any_day: bool,
+    any_weekday: bool,
+}
+
+impl FromStr for CronExpr {
+    type Err = String;
+
+    fn from_str(expr: &str) -> Result<Self, Self::Err> {
+        let expanded = match expr.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            other => other,
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(format!(
+                "Invalid cron expression '{}': expected 5 fields, found {}",
+                expr,
+                fields.len()
+            ));
+        };
+        let invalid = |e: String| format!("Invalid cron expression '{}': {}", expr, e);
+
+        let mut weekdays = parse_field(weekday, 0, 7, &WEEKDAYS).map_err(invalid)?;
+        // Sunday is both 0 and 7
+        if weekdays & (1 << 7) != 0 {
+            weekdays = (weekdays & !(1 << 7)) | 1;
+        }
+        Ok(Self {
+            source: expr.trim().to_string(),
+            minutes: parse_field(minute, 0, 59, &[]).map_err(invalid)?,
+            hours: parse_field(hour, 0, 23, &[]).map_err(invalid)?,
+            days: parse_field(day, 1, 31, &[]).map_err(invalid)?,
+            months: parse_field(month, 1, 12, &MONTHS).map_err(invalid)?,
+            weekdays,
+            any_day: day == "*",
+            any_weekday: weekday == "*",
+        })
+    }
+}
+
+impl fmt::Display for CronExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl CronExpr {
+    /// First time the expression matches strictly after `after_ms`, in milliseconds
+    /// since the Unix epoch, None when it never matches in the next few years
+    pub fn next_after(&self, after_ms: u64) -> Option<u64> {
+        let mut minute = after_ms / MINUTE_MS + 1;
+        let (last_year, _, _) = civil_from_days((minute / DAY_MINUTES) as i64);
+        let last_year = last_year + SEARCH_YEARS;
+        loop {
+            let days = (minute / DAY_MINUTES) as i64;
+            let (year, month, day) = civil_from_days(days);
+            if year > last_year {
+                return None;
+            }
+            if !bit(self.months, month) {
+                let (year, month) = if month == 12 {
+                    (year + 1, 1)
+                } else {
+                    (year, month + 1)
+                };
+                minute = days_from_civil(year, month, 1) as u64 * DAY_MINUTES;
+                continue;
+            }
+            if !self.matches_day(day, weekday(days)) {
+                minute = (days as u64 + 1) * DAY_MINUTES;
+                continue;
+            }
+            let of_day = minute % DAY_MINUTES;
+            if !bit(self.hours, of_day / 60) {
+                minute = (minute / 60 + 1) * 60;
+                continue;
+            }
+            if !bit(self.minutes, of_day % 60) {
+                minute += 1;
+                continue;
+            }
+            return Some(minute * MINUTE_MS);
+        }
+    }
+
+    fn matches_day(&self, day: u64, weekday: u64) -> bool {
+        match (self.any_day, self.any_weekday) {
+            (true, true) => true,
+            (false, true) => bit(self.days, day),
+            (true, false) => bit(self.weekdays, weekday),
+            (false, false) => bit(self.days, day) || bit(self.weekdays, weekday),
+        }
+    }
+}
+
+fn bit(set: u64, value: u64) -> bool {
+    set & (1 << value) != 0
+}
+
+/// Values of one field as a bit set
+fn parse_field(field: &str, min: u64, max: u64, names: &[&str]) -> Result<u64, String> {
+    let value = |text: &str| -> Result<u64, String> {
+        if let Some(index) = names
+            .iter()
+            .position(|name| name.eq_ignore_ascii_case(text))
+        {
+            return Ok(index as u64 + min);
+        }
+        match text.parse::<u64>() {
+            Ok(value) if (min..=max).contains(&value) => Ok(value),
+            _ => Err(format!("'{}' is not between {} and {}", text, min, max)),
+        }
+    };
+
+    let mut set = 0;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => match step.parse::<u64>() {
+                Ok(step) if step > 0 => (range, Some(step)),
+                _ => return Err(format!("invalid step in '{}'", part)),
+            },
+            None => (part, None),
+        };
+        let (start, end) = match range.split_once('-') {
+            _ if range == "*" => (min, max),
+            Some((start, end)) => (value(start)?, value(end)?),
+            // `5/15` runs from 5 to the end of the range
+            None if step.is_some() => (value(range)?, max),
+            None => (value(range)?, value(range)?),
+        };
+        if start > end {
+            return Err(format!("empty range '{}'", range));
+        }
+        for value in (start..=end).step_by(step.unwrap_or(1) as usize) {
+            set |= 1 << value;
+        }
+    }
+    Ok(set)
+}
+
+/// Day of the week of a day since the Unix epoch, 0 being Sunday
+fn weekday(days: i64) -> u64 {
+    // 1970-01-01 was a Thursday
+    (days + 4).rem_euclid(7) as u64
+}
+
+/// Year, month and day of a day since the Unix epoch
+fn civil_from_days(days: i64) -> (i64, u64, u64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u64;
+    let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u64;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Day since the Unix epoch of a date
+fn days_from_civil(year: i64, month: u64, day: u64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let month = month as i64;
+    let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
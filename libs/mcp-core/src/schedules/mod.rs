@@ -0,0 +1,179 @@
+//! Tool calls run on a schedule, for maintenance tools no model would think to call.
+//!
+//! A [`ScheduleDefinition`] calls a proxied tool with fixed arguments each time its
+//! [`cron`] expression matches. Runs go through the same path as the calls of
+//! clients, hooks and call modes included, and their outcome is kept in the run history
+//! of the job. Runs missed while the app was closed are dropped or made up for once at
+//! startup, following the [`MisfirePolicy`] of the job.
+
+pub mod cron;
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use self::cron::CronExpr;
+
+/// How often the scheduler looks for due jobs
+pub const SCHEDULER_TICK: Duration = Duration::from_secs(5);
+
+/// Runs kept in the history of each job
+pub const SCHEDULE_RUNS_KEPT: i64 = 100;
+
+/// What happens to the runs of a job missed while the app was closed
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MisfirePolicy {
+    /// Wait for the next run
+    #[default]
+    Skip,
+    /// Run once at startup, however many runs were missed
+    RunOnce,
+}
+
+impl MisfirePolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MisfirePolicy::Skip => "skip",
+            MisfirePolicy::RunOnce => "run_once",
+        }
+    }
+
+    pub fn from_stored(value: &str) -> Option<Self> {
+        match value {
+            "skip" => Some(MisfirePolicy::Skip),
+            "run_once" => Some(MisfirePolicy::RunOnce),
+            _ => None,
+        }
+    }
+}
+
+/// A job, as created and updated by the user
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScheduleDefinition {
+    pub id: String,
+    /// When the job runs, see [`cron`]
+    pub cron: String,
+    /// Proxied tool to call, as `server_id:tool_name`
+    pub tool_id: String,
+    #[serde(default = "empty_arguments")]
+    pub arguments: Value,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub misfire: MisfirePolicy,
+}
+
+fn empty_arguments() -> Value {
+    Value::Object(Default::default())
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl ScheduleDefinition {
+    /// Check the job before saving it
+    pub fn validate(&self) -> Result<CronExpr, String> {
+        if self.id.trim().is_empty() {
+            return Err("A schedule needs an id".to_string());
+        }
+        match self.tool_id.split_once(':') {
+            Some((server, tool)) if !server.is_empty() && !tool.is_empty() => {}
+            _ => {
+                return Err(format!(
+                    "Invalid tool_id '{}'. Expected 'server_id:tool_id'",
+                    self.tool_id
+                ))
+            }
+        }
+        if !self.arguments.is_object() {
+            return Err("The arguments of a schedule must be an object".to_string());
+        }
+        self.cron.parse()
+    }
+
+    /// Whether the job is enabled and has a run after `from_ms` and until `to_ms`
+    pub fn runs_between(&self, from_ms: u64, to_ms: u64) -> bool {
+        self.enabled
+            && self
+                .cron
+                .parse::<CronExpr>()
+                .ok()
+                .and_then(|cron| cron.next_after(from_ms))
+                .is_some_and(|next| next <= to_ms)
+    }
+}
+
+/// Outcome of the past runs of a job
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScheduleStatus {
+    /// Start of the last run, in milliseconds since the Unix epoch
+    pub last_run_at: Option<u64>,
+    pub last_success: Option<bool>,
+    pub last_error: Option<String>,
+    /// Failed runs since the last successful one
+    pub consecutive_failures: u32,
+    /// Next run, in milliseconds since the Unix epoch, None for disabled jobs
+    pub next_run_at: Option<u64>,
+}
+
+/// A job with its status
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Schedule {
+    #[serde(flatten)]
+    pub definition: ScheduleDefinition,
+    #[serde(flatten)]
+    pub status: ScheduleStatus,
+}
+
+impl Schedule {
+    /// Fill in the next run of the job after `now_ms`
+    pub fn with_next_run(mut self, now_ms: u64) -> Self {
+        self.status.next_run_at = match self.definition.enabled {
+            true => self.definition.cron.parse::<CronExpr>().ok(),
+            false => None,
+        }
+        .and_then(|cron| cron.next_after(now_ms));
+        self
+    }
+}
+
+/// One run of a job
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ScheduleRun {
+    /// Assigned by the database, 0 for runs not saved yet
+    pub id: i64,
+    pub schedule_id: String,
+    /// In milliseconds since the Unix epoch
+    pub started_at: u64,
+    pub duration_ms: u64,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+/// Jobs with a run after `from_ms` and until `to_ms`
+pub fn due_schedules(schedules: &[Schedule], from_ms: u64, to_ms: u64) -> Vec<&Schedule> {
+    schedules
+        .iter()
+        .filter(|schedule| schedule.definition.runs_between(from_ms, to_ms))
+        .collect()
+}
+
+/// Jobs that missed a run before `now_ms` and make up for it at startup
+///
+/// Jobs that never ran have nothing to make up for.
+pub fn misfired_schedules(schedules: &[Schedule], now_ms: u64) -> Vec<&Schedule> {
+    schedules
+        .iter()
+        .filter(|schedule| schedule.definition.misfire == MisfirePolicy::RunOnce)
+        .filter(|schedule| {
+            schedule
+                .status
+                .last_run_at
+                .is_some_and(|last_run| schedule.definition.runs_between(last_run, now_ms))
+        })
+        .collect()
+}
@@ -9,6 +9,8 @@ diesel::table! {
         server_id -> Text,
         proxy_id -> Nullable<Text>,
         is_active -> Bool,
+        output_schema -> Nullable<Text>,
+        annotations -> Nullable<Text>,
     }
 }
 
@@ -19,6 +21,8 @@ diesel::table! {
         env_value -> Text,
         env_description -> Text,
         env_required -> Bool,
+        env_type -> Text,
+        env_bool_format -> Text,
     }
 }
 
@@ -34,6 +38,20 @@ diesel::table! {
         args -> Nullable<Text>,
         distribution_type -> Nullable<Text>,
         distribution_package -> Nullable<Text>,
+        priority -> Nullable<Text>,
+        cpu_limit -> Nullable<Integer>,
+        max_memory_mb -> Nullable<Integer>,
+        sandbox -> Nullable<Text>,
+        inherit_env -> Nullable<Bool>,
+        env_passthrough -> Nullable<Text>,
+        truncate_descriptions -> Nullable<Bool>,
+        strict_output_schema -> Nullable<Bool>,
+        share_instructions -> Nullable<Bool>,
+        tool_filter -> Nullable<Text>,
+        platforms -> Nullable<Text>,
+        start_mode -> Nullable<Text>,
+        cwd -> Nullable<Text>,
+        lenient_initialize -> Nullable<Bool>,
     }
 }
 
@@ -44,6 +62,190 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    server_processes (server_id) {
+        server_id -> Text,
+        spawn_id -> Text,
+        pid -> Nullable<BigInt>,
+        start_time -> Nullable<BigInt>,
+        command -> Nullable<Text>,
+        container_name -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    server_provenance (server_id) {
+        server_id -> Text,
+        source -> Text,
+        source_id -> Nullable<Text>,
+        client -> Nullable<Text>,
+        installed_at -> BigInt,
+        app_version -> Text,
+        registry_source -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    audit_log (id) {
+        id -> Integer,
+        timestamp -> BigInt,
+        action -> Text,
+        server_id -> Nullable<Text>,
+        client -> Nullable<Text>,
+        decision -> Text,
+        detail -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    server_id_aliases (alias) {
+        alias -> Text,
+        server_id -> Text,
+    }
+}
+
+diesel::table! {
+    server_run_states (server_id) {
+        server_id -> Text,
+        desired_running -> Bool,
+    }
+}
+
+diesel::table! {
+    server_call_modes (server_id) {
+        server_id -> Text,
+        mode -> Text,
+        on_miss -> Text,
+    }
+}
+
+diesel::table! {
+    schedules (id) {
+        id -> Text,
+        cron -> Text,
+        tool_id -> Text,
+        arguments -> Text,
+        enabled -> Bool,
+        misfire -> Text,
+        last_run_at -> Nullable<BigInt>,
+        last_success -> Nullable<Bool>,
+        last_error -> Nullable<Text>,
+        consecutive_failures -> Integer,
+    }
+}
+
+diesel::table! {
+    schedule_runs (id) {
+        id -> Integer,
+        schedule_id -> Text,
+        started_at -> BigInt,
+        duration_ms -> BigInt,
+        success -> Bool,
+        error -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    webhooks (id) {
+        id -> Text,
+        url -> Text,
+        events -> Text,
+        enabled -> Bool,
+        secret -> Text,
+    }
+}
+
+diesel::table! {
+    webhook_dead_letters (id) {
+        id -> Integer,
+        webhook_id -> Text,
+        payload -> Text,
+        error -> Text,
+        attempts -> Integer,
+        failed_at -> BigInt,
+    }
+}
+
+diesel::table! {
+    registry_sources (id) {
+        id -> Text,
+        url -> Text,
+        kind -> Text,
+        headers -> Text,
+        credentials -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    server_config_history (id) {
+        id -> Integer,
+        server_id -> Text,
+        created_at -> BigInt,
+        configuration -> Text,
+    }
+}
+
+diesel::table! {
+    server_info (server_id) {
+        server_id -> Text,
+        name -> Text,
+        version -> Text,
+        protocol_version -> Nullable<Text>,
+        instructions -> Nullable<Text>,
+        initialized_at -> BigInt,
+        capabilities -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    server_lifecycle_events (id) {
+        id -> Integer,
+        server_id -> Text,
+        kind -> Text,
+        at -> BigInt,
+        exit_code -> Nullable<Integer>,
+        detail -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    undo_journal (id) {
+        id -> Integer,
+        action -> Text,
+        server_id -> Text,
+        summary -> Text,
+        created_at -> BigInt,
+        expires_at -> BigInt,
+        before_image -> Text,
+    }
+}
+
 diesel::joinable!(server_env -> servers (server_id));
+diesel::joinable!(server_provenance -> servers (server_id));
+diesel::joinable!(server_run_states -> servers (server_id));
+diesel::joinable!(server_call_modes -> servers (server_id));
+diesel::joinable!(server_config_history -> servers (server_id));
+diesel::joinable!(server_info -> servers (server_id));
+diesel::joinable!(server_lifecycle_events -> servers (server_id));
 
-diesel::allow_tables_to_appear_in_same_query!(server_tools, server_env, servers, app_settings,);
+diesel::allow_tables_to_appear_in_same_query!(
+    server_tools,
+    server_env,
+    servers,
+    app_settings,
+    server_processes,
+    server_provenance,
+    audit_log,
+    server_id_aliases,
+    server_run_states,
+    server_call_modes,
+    schedules,
+    schedule_runs,
+    webhooks,
+    webhook_dead_letters,
+    registry_sources,
+    server_config_history,
+    server_info,
+    server_lifecycle_events,
+    undo_journal,
+);
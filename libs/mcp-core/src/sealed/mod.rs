@@ -0,0 +1,171 @@
+//! Values kept encrypted in the database, such as server configurations with secrets.
+//!
+//! A [`SealingKey`] is created once per data directory, in [`SEALING_KEY_FILE`], and
+//! never stored in the database: a copy of the database alone doesn't reveal what was
+//! sealed with it. Values are encrypted with a keystream of HMAC-SHA256 blocks over a
+//! random nonce, then authenticated with HMAC-SHA256, so a sealed value that was
+//! changed or sealed with another key is refused rather than read back wrong.
+
+use std::fmt::Write;
+use std::fs;
+use std::io::Write as _;
+use std::path::Path;
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use uuid::Uuid;
+
+/// File holding the key, in the data directory
+pub const SEALING_KEY_FILE: &str = "dockmaster.key";
+
+/// Prefix of sealed values, followed by the hex of the nonce, ciphertext and tag
+const SEALED_PREFIX: &str = "sealed:v1:";
+const KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 16;
+const TAG_LEN: usize = 32;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Key values are sealed and opened with
+#[derive(Clone)]
+pub struct SealingKey {
+    key: [u8; KEY_LEN],
+}
+
+impl std::fmt::Debug for SealingKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SealingKey(..)")
+    }
+}
+
+impl SealingKey {
+    pub fn new(key: [u8; KEY_LEN]) -> Self {
+        Self { key }
+    }
+
+    /// Key of a data directory, created the first time it is needed
+    pub fn load_or_create(data_dir: &Path) -> Result<Self, String> {
+        let path = data_dir.join(SEALING_KEY_FILE);
+        match fs::read_to_string(&path) {
+            Ok(text) => {
+                let bytes = from_hex(text.trim())
+                    .filter(|bytes| bytes.len() == KEY_LEN)
+                    .ok_or_else(|| format!("{} is not a valid key", path.display()))?;
+                let mut key = [0; KEY_LEN];
+                key.copy_from_slice(&bytes);
+                return Ok(Self::new(key));
+            }
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                return Err(format!("Failed to read {}: {}", path.display(), e));
+            }
+            Err(_) => {}
+        }
+
+        let mut key = [0; KEY_LEN];
+        key[..16].copy_from_slice(Uuid::new_v4().as_bytes());
+        key[16..].copy_from_slice(Uuid::new_v4().as_bytes());
+        fs::create_dir_all(data_dir)
+            .map_err(|e| format!("Failed to create {}: {}", data_dir.display(), e))?;
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        match options.open(&path) {
+            Ok(mut file) => file
+                .write_all(to_hex(&key).as_bytes())
+                .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?,
+            // Created by another instance in the meantime
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                return Self::load_or_create(data_dir);
+            }
+            Err(e) => return Err(format!("Failed to create {}: {}", path.display(), e)),
+        }
+        Ok(Self::new(key))
+    }
+
+    /// Encrypt a value, sealing the same value twice gives different results
+    pub fn seal(&self, plaintext: &str) -> String {
+        let nonce = *Uuid::new_v4().as_bytes();
+        let mut sealed = nonce.to_vec();
+        sealed.extend(self.apply_keystream(&nonce, plaintext.as_bytes()));
+        let tag = self.tag(&sealed);
+        sealed.extend(tag);
+        format!("{}{}", SEALED_PREFIX, to_hex(&sealed))
+    }
+
+    /// Decrypt a sealed value, refusing values that were changed or sealed with another key
+    pub fn open(&self, sealed: &str) -> Result<String, String> {
+        let bytes = sealed
+            .strip_prefix(SEALED_PREFIX)
+            .and_then(from_hex)
+            .filter(|bytes| bytes.len() >= NONCE_LEN + TAG_LEN)
+            .ok_or_else(|| "Not a sealed value".to_string())?;
+        let (authenticated, tag) = bytes.split_at(bytes.len() - TAG_LEN);
+        let mut mac = self.mac(b"authenticate");
+        mac.update(authenticated);
+        mac.verify_slice(tag)
+            .map_err(|_| "The sealed value was changed or sealed with another key".to_string())?;
+        let (nonce, ciphertext) = authenticated.split_at(NONCE_LEN);
+        String::from_utf8(self.apply_keystream(nonce, ciphertext))
+            .map_err(|e| format!("The sealed value is not text: {}", e))
+    }
+
+    fn mac(&self, purpose: &[u8]) -> HmacSha256 {
+        let mut derive =
+            HmacSha256::new_from_slice(&self.key).expect("HMAC takes keys of any size");
+        derive.update(purpose);
+        HmacSha256::new_from_slice(&derive.finalize().into_bytes())
+            .expect("HMAC takes keys of any size")
+    }
+
+    fn tag(&self, authenticated: &[u8]) -> Vec<u8> {
+        let mut mac = self.mac(b"authenticate");
+        mac.update(authenticated);
+        mac.finalize().into_bytes().to_vec()
+    }
+
+    /// XOR `data` with the blocks HMAC(key, nonce || counter)
+    fn apply_keystream(&self, nonce: &[u8], data: &[u8]) -> Vec<u8> {
+        let encrypt = self.mac(b"encrypt");
+        data.chunks(32)
+            .enumerate()
+            .flat_map(|(counter, chunk)| {
+                let mut block = encrypt.clone();
+                block.update(nonce);
+                block.update(&(counter as u64).to_be_bytes());
+                let block = block.finalize().into_bytes();
+                chunk
+                    .iter()
+                    .zip(block)
+                    .map(|(byte, key)| byte ^ key)
+                    .collect::<Vec<u8>>()
+            })
+            .collect()
+    }
+}
+
+/// Whether a stored value was sealed, rather than saved as is
+pub fn is_sealed(value: &str) -> bool {
+    value.starts_with(SEALED_PREFIX)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    let mut hex = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        let _ = write!(hex, "{:02x}", byte);
+    }
+    hex
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 || !hex.is_ascii() {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
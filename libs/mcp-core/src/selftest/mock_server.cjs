@@ -0,0 +1,61 @@
+// MCP server run by the Dockmaster self-test, see src/selftest/mod.rs.
+//
+// Answers newline-delimited JSON-RPC on stdio with nothing but Node.js: the handshake,
+// the list of its only tool and calls of that tool, which return their message.
+
+const readline = require("readline");
+
+const TOOL = {
+  name: "dockmaster_selftest_echo",
+  description: "Returns the message it is given",
+  inputSchema: {
+    type: "object",
+    properties: { message: { type: "string" } },
+    required: ["message"],
+  },
+};
+
+function send(message) {
+  process.stdout.write(JSON.stringify({ jsonrpc: "2.0", ...message }) + "\n");
+}
+
+function answer(request) {
+  switch (request.method) {
+    case "initialize":
+      return {
+        result: {
+          protocolVersion: (request.params && request.params.protocolVersion) || "2024-11-05",
+          capabilities: { tools: {} },
+          serverInfo: { name: "dockmaster-selftest", version: "1.0.0" },
+        },
+      };
+    case "ping":
+      return { result: {} };
+    case "tools/list":
+      return { result: { tools: [TOOL] } };
+    case "tools/call": {
+      const params = request.params || {};
+      if (params.name !== TOOL.name) {
+        return { error: { code: -32602, message: `Unknown tool: ${params.name}` } };
+      }
+      const message = String((params.arguments || {}).message || "");
+      return { result: { content: [{ type: "text", text: message }] } };
+    }
+    default:
+      return { error: { code: -32601, message: `Method not found: ${request.method}` } };
+  }
+}
+
+readline.createInterface({ input: process.stdin }).on("line", (line) => {
+  let request;
+  try {
+    request = JSON.parse(line);
+  } catch (e) {
+    return;
+  }
+  // Notifications get no answer
+  if (request.id === undefined || request.id === null) {
+    return;
+  }
+  send({ id: request.id, ...answer(request) });
+});
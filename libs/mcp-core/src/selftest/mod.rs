@@ -0,0 +1,99 @@
+//! End-to-end check of the app, run by `system/selftest` and `dockmaster selftest`.
+//!
+//! A mock MCP server shipped with the core, [`MOCK_SERVER_SCRIPT`], is registered as a
+//! temporary server. The self-test checks that it starts and answers the handshake,
+//! that its tool is discovered and answers a call through the proxy, then lists and
+//! calls the tool over the local HTTP endpoint, as MCP clients do. The server is removed
+//! at the end, whatever the outcome. Each step is timed and the [`SelftestReport`] can be
+//! pasted into a bug report with [`SelftestReport::to_text`].
+
+use std::fmt::Write as _;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::diagnose::{DiagnosisStatus, DiagnosisStep};
+
+/// Id the mock server is registered under while the self-test runs
+pub const SELFTEST_SERVER_ID: &str = "dockmaster_selftest";
+
+/// Name the mock server gives in its answer to the handshake
+pub const SELFTEST_SERVER_NAME: &str = "dockmaster-selftest";
+
+/// Only tool of the mock server, which returns its `message` argument
+pub const SELFTEST_TOOL: &str = "dockmaster_selftest_echo";
+
+/// Time each step is given, starting the mock server included
+pub const SELFTEST_STEP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Mock MCP server run with Node.js, which has no dependency to install
+pub const MOCK_SERVER_SCRIPT: &str = include_str!("mock_server.cjs");
+
+/// File name the mock server is written to in the `selftest` folder of the data directory
+pub const MOCK_SERVER_FILE: &str = "mock_server.cjs";
+
+/// Result of a step of the self-test, with the time it took
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SelftestStep {
+    #[serde(flatten)]
+    pub step: DiagnosisStep,
+    pub duration_ms: u64,
+}
+
+/// Report of a run of the self-test
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SelftestReport {
+    /// When the self-test ran, in milliseconds since the epoch
+    pub created_at: u64,
+    /// Whether no step failed
+    pub passed: bool,
+    pub app: String,
+    pub core_version: String,
+    pub os: String,
+    pub arch: String,
+    pub steps: Vec<SelftestStep>,
+}
+
+impl SelftestReport {
+    pub fn new(app: &str, created_at: u64, steps: Vec<SelftestStep>) -> Self {
+        Self {
+            created_at,
+            passed: steps
+                .iter()
+                .all(|step| step.step.status != DiagnosisStatus::Fail),
+            app: app.to_string(),
+            core_version: env!("CARGO_PKG_VERSION").to_string(),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            steps,
+        }
+    }
+
+    /// Plain text report, one line per step and a hint under each failed step
+    pub fn to_text(&self) -> String {
+        let mut text = format!(
+            "Dockmaster self-test: {}\n{} (core {}) on {} {}\n",
+            if self.passed { "passed" } else { "FAILED" },
+            self.app,
+            self.core_version,
+            self.os,
+            self.arch
+        );
+        for SelftestStep { step, duration_ms } in &self.steps {
+            let status = match step.status {
+                DiagnosisStatus::Pass => "pass",
+                DiagnosisStatus::Fail => "FAIL",
+                DiagnosisStatus::Skipped => "skip",
+            };
+            let _ = writeln!(
+                text,
+                "[{}] {} ({} ms): {}",
+                status, step.title, duration_ms, step.details
+            );
+            if let Some(hint) = &step.hint {
+                let _ = writeln!(text, "       hint: {}", hint);
+            }
+        }
+        text
+    }
+}
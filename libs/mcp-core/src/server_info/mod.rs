@@ -0,0 +1,41 @@
+//! What servers say about themselves when they start.
+//!
+//! Every server answers the initialize handshake with its name and version, and some
+//! with instructions on how to use their tools. Dockmaster keeps the last answer of
+//! each server and, for the servers that opt in with `share_instructions`, passes the
+//! instructions on to MCP clients after its own. Instructions are capped per server
+//! and in total, a server can't flood the context of the models.
+
+use crate::models::types::ServerInitInfo;
+use crate::utils::description::truncate_chars;
+
+/// Characters of instructions kept per server
+pub const MAX_SERVER_INSTRUCTIONS: usize = 2000;
+
+/// Characters of instructions shared by all servers together
+pub const MAX_SHARED_INSTRUCTIONS: usize = 8000;
+
+/// Section appended to the instructions of Dockmaster, empty without instructions
+///
+/// Servers are listed as given, each under a heading with its id, until the total cap
+/// is reached.
+pub fn shared_instructions(servers: &[(String, ServerInitInfo)]) -> String {
+    let mut shared = String::new();
+    let mut remaining = MAX_SHARED_INSTRUCTIONS;
+    for (server_id, info) in servers {
+        let Some(instructions) = info.instructions.as_deref() else {
+            continue;
+        };
+        let max_length = MAX_SERVER_INSTRUCTIONS.min(remaining);
+        if max_length == 0 {
+            break;
+        }
+        let instructions = truncate_chars(instructions.trim().to_string(), max_length);
+        remaining -= instructions.chars().count();
+        shared.push_str(&format!("\n\n## {}\n{}", server_id, instructions));
+    }
+    if shared.is_empty() {
+        return shared;
+    }
+    format!("\n\n# Instructions of the installed servers{}", shared)
+}
@@ -0,0 +1,232 @@
+//! What the servers write to stderr, kept in memory for `server/logs`.
+//!
+//! The stderr of each spawn is appended to a file by its shell, see
+//! [`StartupCapture`](crate::mcp_state::startup_capture::StartupCapture). While a server
+//! runs, a task follows that file and appends each new line to the buffer of the server,
+//! timestamped when it was read. A buffer keeps the last [`SERVER_LOG_MAX_BYTES`] of a
+//! server across its restarts, so the output of a crashed spawn is still there once the
+//! next one starts, and is dropped when the server is uninstalled.
+
+use std::collections::{HashMap, VecDeque};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+/// Stderr kept per server, older lines are dropped first
+pub const SERVER_LOG_MAX_BYTES: usize = 64 * 1024;
+
+/// Longer lines are cut
+pub const SERVER_LOG_MAX_LINE_BYTES: usize = 4 * 1024;
+
+/// How often the stderr of a running server is read
+pub const SERVER_LOG_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// A line a server wrote to stderr
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct LogLine {
+    /// Unix time in milliseconds when the line was read
+    pub timestamp: u64,
+    pub line: String,
+}
+
+/// The buffered stderr of a server, answered by `server/logs`
+#[derive(Clone, Debug, Serialize)]
+pub struct ServerLogSnapshot {
+    pub server_id: String,
+    /// Oldest first
+    pub lines: Vec<LogLine>,
+    /// Lines dropped to stay under [`SERVER_LOG_MAX_BYTES`]
+    pub dropped_lines: u64,
+    /// Whether the stderr of a running process is followed
+    pub following: bool,
+}
+
+#[derive(Default)]
+struct LogBuffer {
+    lines: VecDeque<LogLine>,
+    bytes: usize,
+    dropped: u64,
+}
+
+impl LogBuffer {
+    fn push(&mut self, line: LogLine) {
+        self.bytes += line.line.len();
+        self.lines.push_back(line);
+        while self.bytes > SERVER_LOG_MAX_BYTES {
+            let Some(oldest) = self.lines.pop_front() else {
+                break;
+            };
+            self.bytes -= oldest.line.len();
+            self.dropped += 1;
+        }
+    }
+}
+
+/// Reads the lines appended to a stderr file since the last read
+struct StderrReader {
+    path: PathBuf,
+    offset: u64,
+    /// End of the file after its last newline
+    partial: Vec<u8>,
+}
+
+impl StderrReader {
+    /// New complete lines, and the partial last one when `flush`
+    fn read_lines(&mut self, flush: bool) -> Vec<String> {
+        let mut lines = Vec::new();
+        // Not there until the shell starts
+        if let Ok(mut file) = File::open(&self.path) {
+            let mut bytes = Vec::new();
+            if file.seek(SeekFrom::Start(self.offset)).is_ok()
+                && file.read_to_end(&mut bytes).is_ok()
+            {
+                self.offset += bytes.len() as u64;
+                for byte in bytes {
+                    if byte == b'\n' {
+                        lines.push(take_line(&mut self.partial));
+                    } else if self.partial.len() < SERVER_LOG_MAX_LINE_BYTES {
+                        self.partial.push(byte);
+                    }
+                }
+            }
+        }
+        if flush && !self.partial.is_empty() {
+            lines.push(take_line(&mut self.partial));
+        }
+        lines
+    }
+}
+
+fn take_line(partial: &mut Vec<u8>) -> String {
+    let line = String::from_utf8_lossy(partial)
+        .trim_end_matches('\r')
+        .to_string();
+    partial.clear();
+    line
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+struct Follower {
+    reader: Arc<Mutex<StderrReader>>,
+    task: tokio::task::JoinHandle<()>,
+}
+
+#[derive(Default)]
+struct ServerLog {
+    buffer: LogBuffer,
+    follower: Option<Follower>,
+}
+
+/// Buffered stderr of every server, by server id
+#[derive(Clone, Default)]
+pub struct ServerLogs {
+    logs: Arc<Mutex<HashMap<String, ServerLog>>>,
+}
+
+impl ServerLogs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a line to the buffer of a server
+    pub fn push(&self, server_id: &str, line: &str) {
+        self.push_lines(server_id, vec![line.to_string()]);
+    }
+
+    fn push_lines(&self, server_id: &str, lines: Vec<String>) {
+        if lines.is_empty() {
+            return;
+        }
+        let timestamp = now_millis();
+        let mut logs = self.logs.lock().unwrap();
+        let buffer = &mut logs.entry(server_id.to_string()).or_default().buffer;
+        for mut line in lines {
+            if line.len() > SERVER_LOG_MAX_LINE_BYTES {
+                let mut end = SERVER_LOG_MAX_LINE_BYTES;
+                while !line.is_char_boundary(end) {
+                    end -= 1;
+                }
+                line.truncate(end);
+            }
+            buffer.push(LogLine { timestamp, line });
+        }
+    }
+
+    /// Follow the stderr file of a new spawn of a server, instead of the previous one
+    pub fn follow(&self, server_id: &str, stderr_path: PathBuf) {
+        self.stop(server_id);
+        let reader = Arc::new(Mutex::new(StderrReader {
+            path: stderr_path,
+            offset: 0,
+            partial: Vec::new(),
+        }));
+        let task = tokio::spawn({
+            let logs = self.clone();
+            let reader = reader.clone();
+            let server_id = server_id.to_string();
+            async move {
+                loop {
+                    tokio::time::sleep(SERVER_LOG_POLL_INTERVAL).await;
+                    let lines = reader.lock().unwrap().read_lines(false);
+                    logs.push_lines(&server_id, lines);
+                }
+            }
+        });
+        let mut logs = self.logs.lock().unwrap();
+        logs.entry(server_id.to_string()).or_default().follower = Some(Follower { reader, task });
+    }
+
+    /// Stop following the stderr of a server, after reading what is left of it
+    ///
+    /// Called before the output files of the spawn are removed.
+    pub fn stop(&self, server_id: &str) {
+        let follower = self
+            .logs
+            .lock()
+            .unwrap()
+            .get_mut(server_id)
+            .and_then(|log| log.follower.take());
+        if let Some(follower) = follower {
+            follower.task.abort();
+            let lines = follower.reader.lock().unwrap().read_lines(true);
+            self.push_lines(server_id, lines);
+        }
+    }
+
+    /// Forget the stderr of a server, once it is uninstalled
+    pub fn remove(&self, server_id: &str) {
+        self.stop(server_id);
+        self.logs.lock().unwrap().remove(server_id);
+    }
+
+    /// Buffered lines of a server read at or after `since`, in Unix milliseconds
+    pub fn snapshot(&self, server_id: &str, since: Option<u64>) -> ServerLogSnapshot {
+        let logs = self.logs.lock().unwrap();
+        let log = logs.get(server_id);
+        ServerLogSnapshot {
+            server_id: server_id.to_string(),
+            lines: log
+                .map(|log| {
+                    log.buffer
+                        .lines
+                        .iter()
+                        .filter(|line| since.is_none_or(|since| line.timestamp >= since))
+                        .cloned()
+                        .collect()
+                })
+                .unwrap_or_default(),
+            dropped_lines: log.map(|log| log.buffer.dropped).unwrap_or_default(),
+            following: log.is_some_and(|log| log.follower.is_some()),
+        }
+    }
+}
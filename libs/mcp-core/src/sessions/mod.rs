@@ -0,0 +1,266 @@
+//! MCP client sessions, whatever transport they arrived on.
+//!
+//! A [`ClientSession`] pairs the transport of a connected client with what the client
+//! told about itself during the initialize handshake. Sessions live in the
+//! [`SessionRegistry`] owned by `MCPState`, so that per-session features only deal
+//! with session ids and never with a particular transport.
+
+pub mod sse;
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use log::error;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::io::{self, AsyncWriteExt};
+use tokio::sync::{Mutex as TokioMutex, RwLock};
+
+/// Write half of an in-memory pipe feeding a session
+pub type SessionWriter = Arc<TokioMutex<io::WriteHalf<io::SimplexStream>>>;
+
+/// How messages reach the client of a session
+#[async_trait]
+pub trait SessionTransport: Send + Sync {
+    /// Short name of the transport, as reported in session listings
+    fn kind(&self) -> &'static str;
+
+    /// Send a message initiated by the server, such as a notification
+    async fn send(&self, message: &str) -> Result<(), String>;
+
+    /// Where the messages posted by the client are written, for transports whose
+    /// inbound messages arrive on a separate request
+    fn inbound(&self) -> Option<SessionWriter> {
+        None
+    }
+}
+
+/// Name and version a client sent in its `initialize` request
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ClientIdentity {
+    pub name: String,
+    pub version: Option<String>,
+}
+
+impl ClientIdentity {
+    /// Identity and capabilities sent in an `initialize` request, None for any other
+    /// message
+    pub fn from_initialize(message: &Value) -> Option<(Self, Value)> {
+        if message.get("method")?.as_str()? != "initialize" {
+            return None;
+        }
+        let params = message.get("params")?;
+        let client_info = params.get("clientInfo")?;
+        let identity = Self {
+            name: client_info.get("name")?.as_str()?.to_string(),
+            version: client_info
+                .get("version")
+                .and_then(|v| v.as_str())
+                .map(str::to_string),
+        };
+        let capabilities = params.get("capabilities").cloned().unwrap_or(Value::Null);
+        Some((identity, capabilities))
+    }
+
+    /// Name followed by the version, as recorded in the audit log
+    pub fn display_name(&self) -> String {
+        match &self.version {
+            Some(version) => format!("{} {}", self.name, version),
+            None => self.name.clone(),
+        }
+    }
+}
+
+/// Argument carrying the [`PostedToolCall`] of a call posted to a session, from the
+/// POST handler to the router running the call
+pub const POSTED_CALL_ARGUMENT: &str = "_dockmaster_call";
+
+/// A `tools/call` request posted to a session, as the router running it needs it
+///
+/// The router only gets the name and arguments of a call, so the call travels in its
+/// arguments under [`POSTED_CALL_ARGUMENT`] and is taken out before the call runs.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PostedToolCall {
+    /// JSON-RPC id of the request
+    pub id: Value,
+    /// Token the client asked progress for, the session is subscribed to it until the
+    /// call returns
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub progress_token: Option<Value>,
+}
+
+impl PostedToolCall {
+    /// Attach the call to the arguments of the `tools/call` request `message`,
+    /// returning false when its arguments aren't an object
+    pub fn attach(&self, message: &mut Value) -> bool {
+        let Some(params) = message.get_mut("params").and_then(Value::as_object_mut) else {
+            return false;
+        };
+        let arguments = params
+            .entry("arguments")
+            .or_insert_with(|| Value::Object(Default::default()));
+        match (arguments.as_object_mut(), serde_json::to_value(self)) {
+            (Some(arguments), Ok(call)) => {
+                arguments.insert(POSTED_CALL_ARGUMENT.to_string(), call);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Take the call attached to `arguments` out of them, if any
+    pub fn take(arguments: &mut Value) -> Option<Self> {
+        let call = arguments.as_object_mut()?.remove(POSTED_CALL_ARGUMENT)?;
+        serde_json::from_value(call).ok()
+    }
+}
+
+/// A connected MCP client
+#[derive(Clone)]
+pub struct ClientSession {
+    pub id: String,
+    pub transport: Arc<dyn SessionTransport>,
+    /// Set once the client sent its `initialize` request
+    pub client: Option<ClientIdentity>,
+    /// Capabilities the client declared in its `initialize` request
+    pub capabilities: Option<Value>,
+    /// Set once the client sent `notifications/initialized`
+    pub initialized: bool,
+    /// What the client subscribed to, such as resource URIs
+    pub subscriptions: HashSet<String>,
+}
+
+impl ClientSession {
+    pub fn new(id: String, transport: Arc<dyn SessionTransport>) -> Self {
+        Self {
+            id,
+            transport,
+            client: None,
+            capabilities: None,
+            initialized: false,
+            subscriptions: HashSet::new(),
+        }
+    }
+}
+
+/// The sessions of the connected clients, by session id
+#[derive(Clone, Default)]
+pub struct SessionRegistry {
+    sessions: Arc<RwLock<HashMap<String, ClientSession>>>,
+}
+
+impl SessionRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub async fn register(&self, session: ClientSession) {
+        self.sessions
+            .write()
+            .await
+            .insert(session.id.clone(), session);
+    }
+
+    pub async fn remove(&self, session_id: &str) {
+        self.sessions.write().await.remove(session_id);
+    }
+
+    pub async fn get(&self, session_id: &str) -> Option<ClientSession> {
+        self.sessions.read().await.get(session_id).cloned()
+    }
+
+    /// Ids of the open sessions
+    pub async fn ids(&self) -> Vec<String> {
+        self.sessions.read().await.keys().cloned().collect()
+    }
+
+    /// Remember what a session's client sent in its `initialize` request
+    pub async fn set_client(&self, session_id: &str, client: ClientIdentity, capabilities: Value) {
+        if let Some(session) = self.sessions.write().await.get_mut(session_id) {
+            session.client = Some(client);
+            session.capabilities = Some(capabilities);
+        }
+    }
+
+    /// Remember that a session's client finished the initialize handshake
+    pub async fn mark_initialized(&self, session_id: &str) {
+        if let Some(session) = self.sessions.write().await.get_mut(session_id) {
+            session.initialized = true;
+        }
+    }
+
+    /// Name and version of the client of a session, if it sent them
+    pub async fn client_name(&self, session_id: &str) -> Option<String> {
+        let sessions = self.sessions.read().await;
+        let client = sessions.get(session_id)?.client.as_ref()?;
+        Some(client.display_name())
+    }
+
+    /// Subscribe a session to `topic`, returning false when the session is gone
+    pub async fn subscribe(&self, session_id: &str, topic: &str) -> bool {
+        match self.sessions.write().await.get_mut(session_id) {
+            Some(session) => {
+                session.subscriptions.insert(topic.to_string());
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub async fn unsubscribe(&self, session_id: &str, topic: &str) {
+        if let Some(session) = self.sessions.write().await.get_mut(session_id) {
+            session.subscriptions.remove(topic);
+        }
+    }
+
+    /// Ids of the sessions subscribed to `topic`
+    pub async fn subscribers(&self, topic: &str) -> Vec<String> {
+        self.sessions
+            .read()
+            .await
+            .values()
+            .filter(|session| session.subscriptions.contains(topic))
+            .map(|session| session.id.clone())
+            .collect()
+    }
+
+    /// Send a server-initiated message to one session
+    pub async fn send(&self, session_id: &str, message: &str) -> Result<(), String> {
+        // Not holding the lock while writing, a slow client mustn't block the others
+        let transport = match self.sessions.read().await.get(session_id) {
+            Some(session) => session.transport.clone(),
+            None => return Err(format!("Session {} not found", session_id)),
+        };
+        transport.send(message).await
+    }
+
+    /// Send a message to every session, returning the ids of those that failed
+    pub async fn broadcast(&self, message: &str) -> Vec<String> {
+        let transports: Vec<(String, Arc<dyn SessionTransport>)> = self
+            .sessions
+            .read()
+            .await
+            .values()
+            .map(|session| (session.id.clone(), session.transport.clone()))
+            .collect();
+
+        let mut failed_sessions = Vec::new();
+        for (session_id, transport) in transports {
+            if let Err(e) = transport.send(message).await {
+                error!("Failed to broadcast to session {}: {}", session_id, e);
+                failed_sessions.push(session_id);
+            }
+        }
+        failed_sessions
+    }
+}
+
+/// Write one newline-delimited message to a pipe
+pub(crate) async fn write_line(writer: &SessionWriter, message: &str) -> io::Result<()> {
+    let mut writer = writer.lock().await;
+    writer.write_all(message.as_bytes()).await?;
+    writer.write_u8(b'\n').await?;
+    writer.flush().await
+}
@@ -0,0 +1,41 @@
+//! Sessions of clients connected over SSE.
+//!
+//! The client reads the event stream of `GET /mcp/sse` and posts its messages to
+//! `POST /mcp/sse?sessionId=...`. Both directions go through in-memory pipes: posted
+//! messages are written to the command pipe read by the MCP server of the session,
+//! and notifications to a pipe merged into the event stream.
+
+use async_trait::async_trait;
+
+use super::{write_line, SessionTransport, SessionWriter};
+
+pub struct SseTransport {
+    command: SessionWriter,
+    notification: SessionWriter,
+}
+
+impl SseTransport {
+    pub fn new(command: SessionWriter, notification: SessionWriter) -> Self {
+        Self {
+            command,
+            notification,
+        }
+    }
+}
+
+#[async_trait]
+impl SessionTransport for SseTransport {
+    fn kind(&self) -> &'static str {
+        "sse"
+    }
+
+    async fn send(&self, message: &str) -> Result<(), String> {
+        write_line(&self.notification, message)
+            .await
+            .map_err(|e| format!("Failed to send message over SSE: {}", e))
+    }
+
+    fn inbound(&self) -> Option<SessionWriter> {
+        Some(self.command.clone())
+    }
+}
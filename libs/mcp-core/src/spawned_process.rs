@@ -1,183 +1,95 @@
 use crate::{
     error::{MCPError, MCPResult},
-    models::types::{ServerConfiguration, ServerId, ToolType},
-    utils::command::CommandWrappedInShellBuilder,
+    json_message_codec::{JsonMessageCodec, StdoutMessage},
+    models::types::ServerId,
 };
-use log::{error, info};
+use futures::StreamExt;
+use log::{error, info, warn};
 use serde_json::json;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use tokio::{
     io::{AsyncBufReadExt, AsyncWriteExt},
     process::{Child, ChildStdin, ChildStdout, Command},
+    sync::{mpsc, oneshot},
+    task::JoinHandle,
     time::Duration,
 };
+use tokio_util::codec::FramedRead;
 
+/// How long a request to a server started with [`SpawnedProcess::spawn`] waits for its
+/// response
+pub const SERVER_RESPONSE_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// MCP protocol version asked for in the initialize handshake
+pub const PROTOCOL_VERSION: &str = "2024-11-05";
+
+/// Notifications kept until read, later ones are dropped
+const NOTIFICATION_CAPACITY: usize = 256;
+
+/// Requests waiting for their response, by id
+type PendingRequests = Arc<Mutex<HashMap<u64, oneshot::Sender<serde_json::Value>>>>;
+
+/// Connection to a server process talking JSON-RPC over its stdin and stdout
+///
+/// Servers are started with [`SpawnedProcess::spawn`] and spoken to with
+/// [`SpawnedProcess::initialize`], [`SpawnedProcess::list_tools`] and
+/// [`SpawnedProcess::call_tool`].
 pub struct SpawnedProcess {
-    pub stdin: ChildStdin,
-    pub stdout: ChildStdout,
-    pub child: Child,
+    /// None once closed with [`SpawnedProcess::close`]
+    pub stdin: SharedStdin,
+    pub child: tokio::sync::Mutex<Child>,
+    server_id: ServerId,
+    timeout: Duration,
+    next_id: AtomicU64,
+    pending: PendingRequests,
+    notifications: tokio::sync::Mutex<mpsc::Receiver<serde_json::Value>>,
+    reader: JoinHandle<()>,
 }
 
 impl SpawnedProcess {
-    pub async fn new(
-        tool_id: &ServerId,
-        tools_type: &ToolType,
-        config: &ServerConfiguration,
-        env_vars: Option<&HashMap<String, String>>,
+    /// Start `program` with `args` and `envs` as the process of a server
+    ///
+    /// The process is killed when the connection is dropped.
+    pub async fn spawn(
+        server_id: &ServerId,
+        program: String,
+        args: Vec<String>,
+        envs: HashMap<String, String>,
+        timeout: Duration,
     ) -> Result<Self, String> {
-        match tools_type {
-            ToolType::Node => Self::spawn_nodejs_process(tool_id, config, env_vars).await,
-            ToolType::Python => Self::spawn_python_process(tool_id, config, env_vars).await,
-            ToolType::Docker => Self::spawn_docker_process(tool_id, config, env_vars).await,
-        }
+        let mut cmd = Command::new(program);
+        cmd.args(args).envs(envs).kill_on_drop(true);
+        Self::start(cmd, server_id, timeout).await
     }
 
-    pub async fn kill(&mut self) -> Result<(), String> {
+    /// Kill the process, requests waiting for their response fail right away
+    pub async fn kill(&self) -> Result<(), String> {
+        self.reader.abort();
+        self.pending.lock().unwrap().clear();
         self.child
+            .lock()
+            .await
             .kill()
             .await
             .map_err(|e| format!("Failed to kill process: {}", e))
     }
 
-    async fn spawn_nodejs_process(
-        tool_id: &ServerId,
-        config: &ServerConfiguration,
-        env_vars: Option<&HashMap<String, String>>,
-    ) -> Result<Self, String> {
-        info!("Spawning Node.js process for tool ID: {}", tool_id);
-
-        let command = config
-            .command
-            .as_ref()
-            .ok_or_else(|| format!("Command is required for Node.js tool {}", tool_id))?;
-
-        if !command.contains("npx") && !command.contains("node") {
-            error!("Entry point doesn't exist and doesn't look like an npm package or node command for tool {}: {}", tool_id, command);
-            return Err(format!("Entry point file '{}' does not exist", command));
-        }
-
-        info!(
-            "Using command to start process for tool {}: {}",
-            tool_id, command
-        );
-
-        let mut cmd_builder = CommandWrappedInShellBuilder::new(command);
-
-        if let Some(args) = &config.args {
-            info!(
-                "Adding {} arguments to command for tool {}",
-                args.len(),
-                tool_id
-            );
-            for (i, arg) in args.iter().enumerate() {
-                info!("Arg {}: {}", i, arg);
-                cmd_builder.arg(arg);
-            }
-        } else {
-            info!("No arguments found in configuration for tool {}", tool_id);
-        }
-
-        let cmd = cmd_builder.build();
-        Self::setup_process(cmd, tool_id, env_vars).await
+    /// Close the stdin of the server, many servers exit on EOF
+    ///
+    /// Requests sent afterwards fail without being written.
+    pub async fn close(&self) {
+        self.stdin.lock().await.take();
     }
 
-    async fn spawn_python_process(
-        tool_id: &ServerId,
-        config: &ServerConfiguration,
-        env_vars: Option<&HashMap<String, String>>,
-    ) -> Result<Self, String> {
-        info!("Spawning Python process for tool ID: {}", tool_id);
-
-        let command = config
-            .command
-            .as_ref()
-            .ok_or_else(|| format!("Command is required for Python tool {}", tool_id))?;
-
-        info!("Using Python command: {}", command);
-
-        let mut cmd_builder = CommandWrappedInShellBuilder::new(command);
-
-        if let Some(args) = &config.args {
-            info!("Args: {:?}", args);
-            for arg in args {
-                cmd_builder.arg(arg);
-            }
-        }
-
-        let cmd = cmd_builder.build();
-        Self::setup_process(cmd, tool_id, env_vars).await
-    }
-
-    async fn spawn_docker_process(
-        tool_id: &ServerId,
-        config: &ServerConfiguration,
-        env_vars: Option<&HashMap<String, String>>,
-    ) -> Result<Self, String> {
-        info!("Spawning Docker process for tool ID: {}", tool_id);
-
-        let command = config
-            .command
-            .as_ref()
-            .ok_or_else(|| format!("Command is required for Docker tool {}", tool_id))?;
-
-        if command != "docker" {
-            return Err(format!(
-                "Expected 'docker' command for Docker runtime, got '{}'",
-                command
-            ));
-        }
-
-        info!("Using Docker command");
-        let mut cmd_builder = CommandWrappedInShellBuilder::new(command);
-
-        cmd_builder
-            .arg("run")
-            .arg("-i")
-            .arg("--name")
-            .arg("-a")
-            .arg("stdout")
-            .arg("-a")
-            .arg("stderr")
-            .arg("-a")
-            .arg("stdin")
-            .arg("--rm");
-
-        if let Some(args) = &config.args {
-            info!("Args: {:?}", args);
-            for arg in args {
-                cmd_builder.arg(arg);
-            }
-        }
-
-        let cmd = cmd_builder.build();
-
-        Self::setup_process(cmd, tool_id, env_vars).await
-    }
-
-    async fn setup_process(
+    async fn start(
         mut cmd: Command,
         tool_id: &ServerId,
-        env_vars: Option<&HashMap<String, String>>,
+        timeout: Duration,
     ) -> Result<Self, String> {
         use std::process::Stdio;
 
-        if let Some(env_map) = env_vars {
-            info!(
-                "Setting {} environment variables for tool {}",
-                env_map.len(),
-                tool_id
-            );
-            for (key, value) in env_map {
-                info!(
-                    "Setting environment variable for tool {}: {}={}",
-                    tool_id, key, value
-                );
-                cmd.env(key, value);
-            }
-        } else {
-            info!("No environment variables provided for tool {}", tool_id);
-        }
-
         cmd.stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped());
@@ -220,63 +132,267 @@ impl SpawnedProcess {
         })?;
 
         info!("Process spawned successfully with stdin and stdout pipes");
-        Ok(Self {
-            stdin,
+        let pending = PendingRequests::default();
+        let (notifications_sender, notifications) = mpsc::channel(NOTIFICATION_CAPACITY);
+        let reader = spawn_stdout_reader(
+            tool_id.clone(),
             stdout,
-            child,
+            pending.clone(),
+            notifications_sender,
+        );
+        Ok(Self {
+            stdin: Arc::new(tokio::sync::Mutex::new(Some(stdin))),
+            child: tokio::sync::Mutex::new(child),
+            server_id: tool_id.clone(),
+            timeout,
+            next_id: AtomicU64::new(1),
+            pending,
+            notifications: tokio::sync::Mutex::new(notifications),
+            reader,
         })
     }
 
+    /// Send a request and wait for the response with its id
+    ///
+    /// Requests may be sent concurrently, each gets its own id.
     pub async fn send_command(
-        &mut self,
+        &self,
         method: &str,
         params: serde_json::Value,
     ) -> MCPResult<serde_json::Value> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
         let command = json!({
             "jsonrpc": "2.0",
-            "id": 1,
+            "id": id,
             "method": method,
             "params": params
         });
 
-        let cmd_str = serde_json::to_string(&command)
-            .map_err(|e| MCPError::SerializationError(e.to_string()))?
-            + "\n";
+        // Registered before writing, the response may come before the write returns
+        let (sender, receiver) = oneshot::channel();
+        self.pending.lock().unwrap().insert(id, sender);
 
-        self.stdin
-            .write_all(cmd_str.as_bytes())
-            .await
-            .map_err(|e| MCPError::StdinWriteError(e.to_string()))?;
+        if let Err(e) = write_line(&self.stdin, &command).await {
+            self.pending.lock().unwrap().remove(&id);
+            return Err(e);
+        }
 
-        self.stdin
-            .flush()
-            .await
-            .map_err(|e| MCPError::StdinFlushError(e.to_string()))?;
-
-        let mut reader = tokio::io::BufReader::new(&mut self.stdout);
-        let mut response_line = String::new();
-
-        let read_result =
-            tokio::time::timeout(Duration::from_secs(3), reader.read_line(&mut response_line))
-                .await;
-
-        match read_result {
-            Ok(Ok(0)) => Err(MCPError::ServerClosedConnection),
-            Ok(Ok(_)) => {
-                let response: serde_json::Value = serde_json::from_str(&response_line)
-                    .map_err(|e| MCPError::JsonParseError(e.to_string()))?;
-
-                if let Some(error) = response.get("error") {
-                    Err(MCPError::ToolExecutionError(error.to_string()))
-                } else {
-                    response
-                        .get("result")
-                        .cloned()
-                        .ok_or(MCPError::NoResultField)
+        // Cancelled on the server if given up on before the response comes
+        let mut awaited = AwaitedRequest {
+            process: self,
+            id,
+            reason: (method != "initialize").then_some(CANCELLED_REASON),
+        };
+        let response = match tokio::time::timeout(self.timeout, receiver).await {
+            Ok(Ok(response)) => response,
+            // The reader dropped the request, stdout closed
+            Ok(Err(_)) => return Err(MCPError::ServerClosedConnection),
+            Err(_) => {
+                if awaited.reason.is_some() {
+                    awaited.reason = Some(TIMED_OUT_REASON);
                 }
+                return Err(MCPError::TimeoutError(self.server_id.to_string()));
             }
-            Ok(Err(e)) => Err(MCPError::StdoutReadError(e.to_string())),
-            Err(_) => Err(MCPError::TimeoutError("Response timeout".to_string())),
+        };
+
+        if let Some(error) = response.get("error") {
+            Err(MCPError::ToolExecutionError(error.to_string()))
+        } else {
+            response
+                .get("result")
+                .cloned()
+                .ok_or(MCPError::NoResultField)
+        }
+    }
+
+    /// Send a notification, which gets no response
+    pub async fn notify(&self, method: &str, params: serde_json::Value) -> MCPResult<()> {
+        write_line(&self.stdin, &notification(method, params)).await
+    }
+
+    /// Run the initialize handshake, returning what the server answered
+    pub async fn initialize(&self) -> MCPResult<serde_json::Value> {
+        let result = self
+            .send_command(
+                "initialize",
+                json!({
+                    "protocolVersion": PROTOCOL_VERSION,
+                    "capabilities": {},
+                    "clientInfo": {
+                        "name": "mcp-dockmaster",
+                        "version": env!("CARGO_PKG_VERSION")
+                    }
+                }),
+            )
+            .await?;
+        self.notify("notifications/initialized", json!({})).await?;
+        Ok(result)
+    }
+
+    /// List a page of the tools of the server, the first one without a cursor
+    ///
+    /// The result holds the `tools` and the `nextCursor` of the next page, if any.
+    pub async fn list_tools(&self, cursor: Option<String>) -> MCPResult<serde_json::Value> {
+        let params = match cursor {
+            Some(cursor) => json!({ "cursor": cursor }),
+            None => json!({}),
+        };
+        self.send_command("tools/list", params).await
+    }
+
+    /// Call a tool of the server, returning its result as sent
+    ///
+    /// `meta` is sent as the `_meta` of the request, such as the token the server
+    /// reports the progress of the call with.
+    pub async fn call_tool(
+        &self,
+        name: &str,
+        arguments: serde_json::Value,
+        meta: Option<serde_json::Value>,
+    ) -> MCPResult<serde_json::Value> {
+        let mut params = json!({ "name": name, "arguments": arguments });
+        if let Some(meta) = meta {
+            params["_meta"] = meta;
+        }
+        self.send_command("tools/call", params).await
+    }
+
+    /// Next message the server sent without being asked, in the order it sent them
+    ///
+    /// None once the server closed its stdout.
+    pub async fn next_notification(&self) -> Option<serde_json::Value> {
+        self.notifications.lock().await.recv().await
+    }
+}
+
+/// Stdin of a server, shared with the tasks cancelling its requests
+type SharedStdin = Arc<tokio::sync::Mutex<Option<ChildStdin>>>;
+
+/// Reason sent with the `notifications/cancelled` of a request given up on
+const CANCELLED_REASON: &str = "The client cancelled the request";
+
+/// Reason sent with the `notifications/cancelled` of a request that timed out
+const TIMED_OUT_REASON: &str = "The request timed out";
+
+/// A request waiting for its response
+///
+/// Dropped before the response came, because it timed out or the future waiting for
+/// it was dropped, it stops waiting and the server is sent a `notifications/cancelled`.
+struct AwaitedRequest<'a> {
+    process: &'a SpawnedProcess,
+    id: u64,
+    /// None for requests that can't be cancelled
+    reason: Option<&'static str>,
+}
+
+impl Drop for AwaitedRequest<'_> {
+    fn drop(&mut self) {
+        // The response is dropped if it comes anyway
+        let pending = self.process.pending.lock().unwrap().remove(&self.id);
+        if pending.is_none() {
+            return;
         }
+        let (Some(reason), Ok(runtime)) = (self.reason, tokio::runtime::Handle::try_current())
+        else {
+            return;
+        };
+        let stdin = self.process.stdin.clone();
+        let cancelled = notification(
+            "notifications/cancelled",
+            json!({ "requestId": self.id, "reason": reason }),
+        );
+        let server_id = self.process.server_id.clone();
+        runtime.spawn(async move {
+            if let Err(e) = write_line(&stdin, &cancelled).await {
+                warn!("Failed to cancel a request to {}: {}", server_id, e);
+            }
+        });
     }
 }
+
+fn notification(method: &str, params: serde_json::Value) -> serde_json::Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": method,
+        "params": params
+    })
+}
+
+/// Write a message on its own line to the stdin of a server
+async fn write_line(stdin: &SharedStdin, message: &serde_json::Value) -> MCPResult<()> {
+    let line = serde_json::to_string(message)
+        .map_err(|e| MCPError::SerializationError(e.to_string()))?
+        + "\n";
+    let mut stdin = stdin.lock().await;
+    let stdin = stdin
+        .as_mut()
+        .ok_or_else(|| MCPError::StdinWriteError("stdin closed".to_string()))?;
+    stdin
+        .write_all(line.as_bytes())
+        .await
+        .map_err(|e| MCPError::StdinWriteError(e.to_string()))?;
+    stdin
+        .flush()
+        .await
+        .map_err(|e| MCPError::StdinFlushError(e.to_string()))
+}
+
+/// Read every message the server writes to stdout, routing responses to the request
+/// with their id and queueing the other messages as notifications
+///
+/// Messages may span several lines or reads, see [`JsonMessageCodec`]. Lines that
+/// aren't JSON are logged, some servers print to stdout. Requests still waiting when
+/// stdout closes fail with [`MCPError::ServerClosedConnection`], those waiting when the
+/// server sends a message over the size limit fail with an error saying so.
+fn spawn_stdout_reader(
+    tool_id: ServerId,
+    stdout: ChildStdout,
+    pending: PendingRequests,
+    notifications: mpsc::Sender<serde_json::Value>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut messages = FramedRead::new(stdout, JsonMessageCodec::default());
+        loop {
+            let message = match messages.next().await {
+                Some(Ok(StdoutMessage::Json(message))) => message,
+                Some(Ok(StdoutMessage::Text(line))) => {
+                    info!("[{} stdout]: {}", tool_id, line);
+                    continue;
+                }
+                Some(Err(e)) if e.kind() == std::io::ErrorKind::InvalidData => {
+                    let error = format!("Server {} sent a {}", tool_id, e);
+                    error!("{}, not reading its stdout anymore", error);
+                    for (_, sender) in pending.lock().unwrap().drain() {
+                        let _ = sender.send(json!({
+                            "jsonrpc": "2.0",
+                            "error": { "code": -32700, "message": error }
+                        }));
+                    }
+                    break;
+                }
+                Some(Err(e)) => {
+                    warn!("Failed to read stdout of {}: {}", tool_id, e);
+                    break;
+                }
+                None => break,
+            };
+
+            let is_response = message.get("result").is_some() || message.get("error").is_some();
+            let id = message.get("id").and_then(|id| id.as_u64());
+            match id.filter(|_| is_response) {
+                Some(id) => match pending.lock().unwrap().remove(&id) {
+                    Some(sender) => {
+                        let _ = sender.send(message);
+                    }
+                    None => warn!("{} answered request {} which isn't waiting", tool_id, id),
+                },
+                None => {
+                    if notifications.try_send(message).is_err() {
+                        warn!("Dropped a notification of {}, none are being read", tool_id);
+                    }
+                }
+            }
+        }
+        pending.lock().unwrap().clear();
+    })
+}
@@ -0,0 +1,170 @@
+//! Bounds on the tools of servers that list a lot of them.
+//!
+//! Some servers generate a tool per endpoint of an API and list hundreds of them, which
+//! then fill the tools of every client. The [`ToolFilter`] of a server, saved with its
+//! configuration, keeps the tools whose name matches its include patterns and none of
+//! its exclude patterns, then at most `max_tools` of them ([`DEFAULT_MAX_TOOLS`] unless
+//! set), the first ones by name. It is applied when the tools are discovered, what was
+//! left out is described by a [`ToolLimitReport`] in the status of the server. Tools
+//! left out are not listed but can still be called by their proxy id.
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::types::ServerToolInfo;
+
+/// Tools kept of a server without a maximum of its own
+pub const DEFAULT_MAX_TOOLS: usize = 500;
+
+/// Code of the warning given in the server status when tools were left out
+pub const TOOLS_TRUNCATED_WARNING: &str = "tools_truncated";
+
+/// Which tools of a server are listed, by name, and how many at most
+///
+/// Patterns are globs where `*` matches any text and `?` any single character.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolFilter {
+    /// Patterns of the tools kept, every tool when empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<String>,
+    /// Patterns of the tools left out, even when included
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
+    /// Tools kept at most, [`DEFAULT_MAX_TOOLS`] unless set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tools: Option<usize>,
+}
+
+impl ToolFilter {
+    /// Refuse a maximum of no tools and empty patterns
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_tools == Some(0) {
+            return Err("max_tools must be at least 1".to_string());
+        }
+        if let Some(pattern) = self
+            .include
+            .iter()
+            .chain(&self.exclude)
+            .find(|pattern| pattern.trim().is_empty())
+        {
+            return Err(format!("Invalid tool pattern '{}'", pattern));
+        }
+        Ok(())
+    }
+
+    /// Whether the tool named `name` passes the patterns
+    pub fn allows(&self, name: &str) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|p| glob_match(p, name)))
+            && !self.exclude.iter().any(|p| glob_match(p, name))
+    }
+
+    /// Tools kept at most
+    pub fn max_tools(&self) -> usize {
+        self.max_tools.unwrap_or(DEFAULT_MAX_TOOLS)
+    }
+}
+
+/// What discovery left out of the tools of a server
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct ToolLimitReport {
+    /// Tools the server listed
+    pub listed: usize,
+    /// Tools left out by the patterns of the filter
+    pub filtered: usize,
+    /// Tools left out beyond the maximum
+    pub truncated: usize,
+    pub max_tools: usize,
+    /// Tools left out, without description nor schemas, still callable by proxy id
+    #[serde(skip)]
+    pub left_out: Vec<ServerToolInfo>,
+}
+
+impl ToolLimitReport {
+    /// Warnings of the server status, when tools were left out beyond the maximum
+    pub fn warnings(&self) -> Vec<serde_json::Value> {
+        if self.truncated == 0 {
+            return Vec::new();
+        }
+        vec![serde_json::json!({
+            "code": TOOLS_TRUNCATED_WARNING,
+            "message": format!(
+                "Only {} of the {} tools of the server are listed, raise max_tools or \
+                 filter them with server/set_tool_filter",
+                self.max_tools,
+                self.listed - self.filtered
+            ),
+        })]
+    }
+
+    /// Tool left out named `name`
+    pub fn left_out_tool(&self, name: &str) -> Option<&ServerToolInfo> {
+        self.left_out.iter().find(|tool| tool.name == name)
+    }
+}
+
+/// Keep the tools of a server allowed by its filter, the first ones by name beyond the
+/// maximum, with what was left out
+pub fn apply_tool_limits(
+    filter: Option<&ToolFilter>,
+    tools: Vec<ServerToolInfo>,
+) -> (Vec<ServerToolInfo>, ToolLimitReport) {
+    let default_filter = ToolFilter::default();
+    let filter = filter.unwrap_or(&default_filter);
+    let mut report = ToolLimitReport {
+        listed: tools.len(),
+        max_tools: filter.max_tools(),
+        ..Default::default()
+    };
+
+    let (mut kept, filtered): (Vec<_>, Vec<_>) = tools
+        .into_iter()
+        .partition(|tool| filter.allows(&tool.name));
+    report.filtered = filtered.len();
+    let mut left_out = filtered;
+    if kept.len() > report.max_tools {
+        // By name, so that the same tools are kept whatever order the server lists them in
+        kept.sort_by(|a, b| a.name.cmp(&b.name));
+        left_out.extend(kept.split_off(report.max_tools));
+        report.truncated = left_out.len() - report.filtered;
+    }
+    report.left_out = left_out
+        .into_iter()
+        .map(|tool| ServerToolInfo {
+            description: String::new(),
+            input_schema: None,
+            output_schema: None,
+            ..tool
+        })
+        .collect();
+    (kept, report)
+}
+
+/// Whether `name` matches the glob `pattern`, where `*` matches any text and `?` any
+/// single character
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // Where to resume after the last `*`, in the pattern and in the name
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p + 1, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((star_p, star_n)) => {
+                    p = star_p;
+                    n = star_n + 1;
+                    star = Some((star_p, star_n + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
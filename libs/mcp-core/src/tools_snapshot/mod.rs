@@ -0,0 +1,101 @@
+//! Tool list of the last run, served while the servers start.
+//!
+//! Every time the tool list changes, the tools of the servers as clients see them,
+//! namespaced and with the user's overrides, are written to [`TOOLS_SNAPSHOT_FILE`] next
+//! to the database. At startup the file is read before the HTTP server accepts requests,
+//! and `tools/list` answers with it, flagged `stale`, until the servers have started and
+//! the live list replaces it. A file that can't be read, or was written by another
+//! [`TOOLS_SNAPSHOT_VERSION`], is ignored. Setting [`TOOLS_SNAPSHOT_SETTING`] to "false"
+//! stops writing the file and removes it.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::models::types::ServerToolInfo;
+
+/// Version of the file format, files of other versions are ignored
+pub const TOOLS_SNAPSHOT_VERSION: u32 = 1;
+
+/// Settings key of the snapshot, "false" when it is disabled
+pub const TOOLS_SNAPSHOT_SETTING: &str = "tools_snapshot";
+
+/// Name of the snapshot file, in the directory of the database
+pub const TOOLS_SNAPSHOT_FILE: &str = "tools_snapshot.json";
+
+#[derive(Serialize, Deserialize)]
+struct ToolsSnapshot {
+    version: u32,
+    /// When the snapshot was written, in milliseconds since the epoch
+    saved_at: u64,
+    tools: Vec<ServerToolInfo>,
+}
+
+/// Path of the snapshot of the core storing its state in `database_path`
+pub fn snapshot_path(database_path: &Path) -> PathBuf {
+    database_path.with_file_name(TOOLS_SNAPSHOT_FILE)
+}
+
+/// Tools of the snapshot at `path`, None when there is none or it can't be used
+pub fn load_snapshot(path: &Path) -> Option<Vec<ServerToolInfo>> {
+    let content = fs::read(path).ok()?;
+    let snapshot = match serde_json::from_slice::<ToolsSnapshot>(&content) {
+        Ok(snapshot) => snapshot,
+        Err(e) => {
+            warn!("Ignoring unreadable tools snapshot {:?}: {}", path, e);
+            return None;
+        }
+    };
+    if snapshot.version != TOOLS_SNAPSHOT_VERSION {
+        warn!(
+            "Ignoring tools snapshot {:?} of version {}, expected {}",
+            path, snapshot.version, TOOLS_SNAPSHOT_VERSION
+        );
+        return None;
+    }
+    // Snapshots of older versions may have tools without ids
+    let mut tools = snapshot.tools;
+    tools.iter_mut().for_each(ServerToolInfo::normalize_ids);
+    Some(tools)
+}
+
+/// Write the snapshot of `tools` at `path`, returns false when it already held them
+pub fn save_snapshot(path: &Path, tools: &[ServerToolInfo], saved_at: u64) -> Result<bool, String> {
+    let tools_value = serde_json::to_value(tools)
+        .map_err(|e| format!("Failed to serialize tools snapshot: {}", e))?;
+    let unchanged = fs::read(path)
+        .ok()
+        .and_then(|content| serde_json::from_slice::<Value>(&content).ok())
+        .is_some_and(|saved| {
+            saved["version"] == TOOLS_SNAPSHOT_VERSION && saved["tools"] == tools_value
+        });
+    if unchanged {
+        return Ok(false);
+    }
+
+    let content = serde_json::to_vec(&ToolsSnapshot {
+        version: TOOLS_SNAPSHOT_VERSION,
+        saved_at,
+        tools: tools.to_vec(),
+    })
+    .map_err(|e| format!("Failed to serialize tools snapshot: {}", e))?;
+    // Written aside then renamed, so a crash never leaves half a file
+    let partial = path.with_extension("json.partial");
+    fs::write(&partial, content)
+        .map_err(|e| format!("Failed to write tools snapshot {:?}: {}", partial, e))?;
+    fs::rename(&partial, path)
+        .map_err(|e| format!("Failed to write tools snapshot {:?}: {}", path, e))?;
+    Ok(true)
+}
+
+/// Remove the snapshot at `path`, if there is one
+pub fn remove_snapshot(path: &Path) -> Result<(), String> {
+    match fs::remove_file(path) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(format!("Failed to remove tools snapshot {:?}: {}", path, e)),
+    }
+}
@@ -0,0 +1,128 @@
+//! Journal of the changes to servers that can be undone from the app.
+//!
+//! Uninstalling, disabling or reconfiguring a server and changing the annotations of
+//! a tool record an [`UndoEntry`] with what the change replaced, its [`BeforeImage`].
+//! Undoing an entry goes through the same code paths as the user would: an uninstalled
+//! server is registered again, a disabled one enabled and a configuration saved and the
+//! server restarted, so processes and caches follow. Before-images hold env values and
+//! are sealed with the key of the data directory, see [`crate::sealed`].
+//!
+//! Entries expire after [`DEFAULT_UNDO_RETENTION_HOURS`] unless set otherwise with
+//! [`UNDO_RETENTION_SETTING`], and only the last [`UNDO_JOURNAL_KEPT`] are kept.
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::types::{
+    ServerConfiguration, ServerDefinition, ServerProvenance, ToolAnnotations,
+};
+
+/// Entries kept in the journal
+pub const UNDO_JOURNAL_KEPT: i64 = 100;
+
+/// Hours an entry can be undone for, unless set with [`UNDO_RETENTION_SETTING`]
+pub const DEFAULT_UNDO_RETENTION_HOURS: u64 = 24;
+
+/// Setting holding the hours entries can be undone for
+pub const UNDO_RETENTION_SETTING: &str = "undo_retention_hours";
+
+/// Change recorded in the journal
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UndoAction {
+    Uninstall,
+    Disable,
+    Configure,
+    /// Annotations of a tool set by the user
+    Annotations,
+}
+
+impl UndoAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UndoAction::Uninstall => "uninstall",
+            UndoAction::Disable => "disable",
+            UndoAction::Configure => "configure",
+            UndoAction::Annotations => "annotations",
+        }
+    }
+
+    pub fn from_stored(value: &str) -> Option<Self> {
+        match value {
+            "uninstall" => Some(UndoAction::Uninstall),
+            "disable" => Some(UndoAction::Disable),
+            "configure" => Some(UndoAction::Configure),
+            "annotations" => Some(UndoAction::Annotations),
+            _ => None,
+        }
+    }
+}
+
+/// A change that can be undone, as listed to the app
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct UndoEntry {
+    /// Assigned by the database
+    pub id: i64,
+    pub action: UndoAction,
+    pub server_id: String,
+    /// One line description of the change
+    pub summary: String,
+    /// In milliseconds since the Unix epoch
+    pub created_at: u64,
+    /// When the change can no longer be undone, in milliseconds since the Unix epoch
+    pub expires_at: u64,
+}
+
+/// What a change replaced, enough to put it back
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum BeforeImage {
+    /// An uninstalled server
+    Server {
+        definition: ServerDefinition,
+        provenance: Option<ServerProvenance>,
+    },
+    /// A server that was enabled before being disabled
+    Enabled,
+    /// The configuration a change replaced
+    Configuration { configuration: ServerConfiguration },
+    /// Annotations of a tool before they were set, empty when it had none
+    Annotations {
+        tool_name: String,
+        annotations: ToolAnnotations,
+    },
+}
+
+impl BeforeImage {
+    pub fn action(&self) -> UndoAction {
+        match self {
+            BeforeImage::Server { .. } => UndoAction::Uninstall,
+            BeforeImage::Enabled => UndoAction::Disable,
+            BeforeImage::Configuration { .. } => UndoAction::Configure,
+            BeforeImage::Annotations { .. } => UndoAction::Annotations,
+        }
+    }
+
+    /// Description of the change this image undoes
+    pub fn summary(&self, server_id: &str) -> String {
+        match self {
+            BeforeImage::Server { definition, .. } => {
+                format!("Uninstalled {} ({})", definition.name, server_id)
+            }
+            BeforeImage::Enabled => format!("Disabled {}", server_id),
+            BeforeImage::Configuration { .. } => {
+                format!("Changed the configuration of {}", server_id)
+            }
+            BeforeImage::Annotations { tool_name, .. } => {
+                format!("Changed the annotations of {}:{}", server_id, tool_name)
+            }
+        }
+    }
+}
+
+/// Outcome of undoing an entry
+#[derive(Clone, Debug, Serialize)]
+pub struct UndoResult {
+    pub success: bool,
+    pub message: String,
+    pub entry: UndoEntry,
+}
@@ -1,5 +1,5 @@
 use log::{error, info};
-use std::{collections::HashMap, ffi::OsStr};
+use std::{collections::HashMap, ffi::OsStr, path::Path};
 use tokio::process::Command;
 
 use once_cell::sync::Lazy;
@@ -69,6 +69,15 @@ pub fn get_envs() -> HashMap<String, String> {
     }
 }
 
+/// PATH the servers are started with
+pub fn server_path() -> Option<String> {
+    #[cfg(not(windows))]
+    if let Some(path) = ENVS.get("PATH") {
+        return Some(path.clone());
+    }
+    std::env::var("PATH").ok()
+}
+
 static BASE_OS_ENVS: Lazy<Option<HashMap<String, String>>> = Lazy::new(get_base_os_envs);
 fn get_base_os_envs() -> Option<HashMap<String, String>> {
     let env_location = "/usr/bin/env";
@@ -249,3 +258,43 @@ impl CommandWrappedInShellBuilder {
         Self::wrapped_in_shell(command_with_args)
     }
 }
+
+/// Name of a shell, like `bash` or `powershell`, from its path
+pub(crate) fn shell_name(shell: &str) -> String {
+    Path::new(shell)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+/// Prefix `command`, run with `shell -c`, so that it runs in `dir`
+///
+/// The command isn't run when the shell can't change to the directory.
+pub fn run_in_directory(shell: &str, command: &str, dir: &Path) -> String {
+    match shell_name(shell).as_str() {
+        "powershell" | "pwsh" => format!(
+            "Set-Location -LiteralPath {} -ErrorAction Stop; {}",
+            quote_powershell(dir),
+            command
+        ),
+        "fish" => format!("cd {}; and {}", quote_fish(dir), command),
+        _ => format!("cd {} && {}", quote_posix(dir), command),
+    }
+}
+
+pub(crate) fn quote_posix(path: &Path) -> String {
+    format!("'{}'", path.to_string_lossy().replace('\'', "'\\''"))
+}
+
+pub(crate) fn quote_fish(path: &Path) -> String {
+    format!(
+        "'{}'",
+        path.to_string_lossy()
+            .replace('\\', "\\\\")
+            .replace('\'', "\\'")
+    )
+}
+
+pub(crate) fn quote_powershell(path: &Path) -> String {
+    format!("'{}'", path.to_string_lossy().replace('\'', "''"))
+}
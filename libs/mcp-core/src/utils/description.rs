@@ -0,0 +1,136 @@
+use serde::{Deserialize, Serialize};
+
+/// Length tool descriptions are truncated to when the user hasn't chosen one
+pub const DEFAULT_DESCRIPTION_MAX_LENGTH: usize = 1024;
+
+/// Settings key of the maximum description length
+pub const DESCRIPTION_MAX_LENGTH_SETTING: &str = "tool_description_max_length";
+
+/// Settings key of whether markdown is removed from descriptions
+pub const DESCRIPTION_STRIP_MARKDOWN_SETTING: &str = "tool_description_strip_markdown";
+
+const ELLIPSIS: char = '…';
+
+/// How tool descriptions are shortened in the aggregated tool list
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DescriptionOptions {
+    /// Descriptions longer than this many characters are truncated, 0 keeps them whole
+    pub max_length: usize,
+    /// Remove markdown markup (headings, emphasis, code and link syntax)
+    pub strip_markdown: bool,
+}
+
+impl Default for DescriptionOptions {
+    fn default() -> Self {
+        Self {
+            max_length: DEFAULT_DESCRIPTION_MAX_LENGTH,
+            strip_markdown: false,
+        }
+    }
+}
+
+/// Description of a tool as forwarded to clients
+///
+/// Whitespace is always collapsed; markdown is stripped when enabled and the text is
+/// truncated unless `truncate` is false, for servers that opted out.
+pub fn normalize_description(
+    description: &str,
+    options: &DescriptionOptions,
+    truncate: bool,
+) -> String {
+    let text = if options.strip_markdown {
+        collapse_whitespace(&strip_markdown(description))
+    } else {
+        collapse_whitespace(description)
+    };
+    if truncate && options.max_length > 0 {
+        truncate_chars(text, options.max_length)
+    } else {
+        text
+    }
+}
+
+/// Collapse runs of spaces on each line and runs of blank lines into one
+pub fn collapse_whitespace(text: &str) -> String {
+    let mut collapsed = String::with_capacity(text.len());
+    let mut paragraph_break = false;
+    for line in text.lines() {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        if words.is_empty() {
+            paragraph_break = !collapsed.is_empty();
+            continue;
+        }
+        if !collapsed.is_empty() {
+            collapsed.push_str(if paragraph_break { "\n\n" } else { "\n" });
+        }
+        paragraph_break = false;
+        collapsed.push_str(&words.join(" "));
+    }
+    collapsed
+}
+
+/// Remove markdown markup, keeping the text it decorates
+///
+/// Single `*` and `_` are kept since they are common in identifiers and globs.
+pub fn strip_markdown(text: &str) -> String {
+    text.lines()
+        .filter(|line| {
+            let line = line.trim_start();
+            !(line.starts_with("```") || line.starts_with("~~~"))
+        })
+        .map(|line| {
+            let line = line.trim_start();
+            let line = line.trim_start_matches('>').trim_start();
+            let line = match line.trim_start_matches('#') {
+                heading if heading.len() < line.len() && heading.starts_with(' ') => {
+                    heading.trim_start()
+                }
+                _ => line,
+            };
+            strip_links(line)
+                .replace("**", "")
+                .replace("__", "")
+                .replace('`', "")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Replace `[text](url)` and `![alt](url)` by their text
+fn strip_links(line: &str) -> String {
+    let mut stripped = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(open) = rest.find('[') {
+        let after = &rest[open + 1..];
+        let link = after.find("](").and_then(|close| {
+            let label = &after[..close];
+            let end = after[close..].find(')')?;
+            (!label.contains('[')).then_some((label, close + end + 1))
+        });
+        match link {
+            Some((label, consumed)) => {
+                let before = &rest[..open];
+                stripped.push_str(before.strip_suffix('!').unwrap_or(before));
+                stripped.push_str(label);
+                rest = &after[consumed..];
+            }
+            None => {
+                stripped.push_str(&rest[..=open]);
+                rest = after;
+            }
+        }
+    }
+    stripped.push_str(rest);
+    stripped
+}
+
+/// Cut `text` to at most `max_length` characters, ending with an ellipsis when cut
+pub(crate) fn truncate_chars(text: String, max_length: usize) -> String {
+    if text.chars().count() <= max_length {
+        return text;
+    }
+    let mut truncated: String = text.chars().take(max_length - 1).collect();
+    truncated.truncate(truncated.trim_end().len());
+    truncated.push(ELLIPSIS);
+    truncated
+}
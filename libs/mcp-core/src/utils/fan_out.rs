@@ -0,0 +1,135 @@
+//! Running an operation on several servers without waiting on the slowest one.
+//!
+//! Each member of a fan-out runs in its own task with a timeout, and the fan-out as a
+//! whole has a deadline. Members that miss either are reported in
+//! [`FanOut::timed_out`] and the results collected so far are returned as partial.
+//! They keep running in the background: when one finishes, its result goes to the
+//! `on_late` callback, which updates the caches the caller already returned from.
+
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Serialize;
+use tokio::sync::{mpsc, Semaphore};
+
+use super::clock::Clock;
+
+/// Time limits of the fan-outs of a core
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FanOutLimits {
+    /// Time a member may run once it started
+    pub member_timeout: Duration,
+    /// Time after which the fan-out returns, whatever is still running
+    pub deadline: Duration,
+}
+
+impl Default for FanOutLimits {
+    fn default() -> Self {
+        Self {
+            member_timeout: Duration::from_secs(30),
+            deadline: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Results of a fan-out, in the order of its members
+#[derive(Clone, Debug, Serialize)]
+pub struct FanOut<T> {
+    pub results: Vec<(String, T)>,
+    /// Whether some members didn't finish in time
+    pub partial: bool,
+    /// Members that didn't finish in time, still running in the background
+    pub timed_out: Vec<String>,
+}
+
+enum Outcome<T> {
+    Done(String, T),
+    TimedOut(String),
+}
+
+/// Run `members`, `concurrency` at a time, within `limits`
+pub async fn fan_out<T, F, L>(
+    clock: Arc<dyn Clock>,
+    members: Vec<(String, F)>,
+    limits: FanOutLimits,
+    concurrency: usize,
+    mut on_late: L,
+) -> FanOut<T>
+where
+    T: Send + 'static,
+    F: Future<Output = T> + Send + 'static,
+    L: FnMut(String, T) + Send + 'static,
+{
+    let ids: Vec<String> = members.iter().map(|(id, _)| id.clone()).collect();
+    let semaphore = Arc::new(Semaphore::new(concurrency.max(1)));
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+    for (id, member) in members {
+        let clock = clock.clone();
+        let semaphore = semaphore.clone();
+        let sender = sender.clone();
+        tokio::spawn(async move {
+            let permit = semaphore.acquire_owned().await;
+            let mut member = Box::pin(member);
+            let finished = tokio::select! {
+                biased;
+                result = &mut member => Some(result),
+                _ = clock.sleep(limits.member_timeout) => None,
+            };
+            let result = match finished {
+                Some(result) => result,
+                None => {
+                    let _ = sender.send(Outcome::TimedOut(id.clone()));
+                    // Let the next member start while this one finishes
+                    drop(permit);
+                    member.await
+                }
+            };
+            let _ = sender.send(Outcome::Done(id, result));
+        });
+    }
+    drop(sender);
+
+    let mut done: Vec<(String, T)> = Vec::new();
+    let mut timed_out: Vec<String> = Vec::new();
+    let deadline = clock.sleep(limits.deadline);
+    tokio::pin!(deadline);
+    while done.len() + timed_out.len() < ids.len() {
+        tokio::select! {
+            biased;
+            outcome = receiver.recv() => match outcome {
+                Some(Outcome::Done(id, result)) if !timed_out.contains(&id) => {
+                    done.push((id, result))
+                }
+                // Finished after its timeout, the caller hears of it through on_late
+                Some(Outcome::Done(id, result)) => on_late(id, result),
+                Some(Outcome::TimedOut(id)) => timed_out.push(id),
+                None => break,
+            },
+            _ = &mut deadline => break,
+        }
+    }
+
+    let mut results = Vec::new();
+    let mut late = Vec::new();
+    for id in &ids {
+        match done.iter().position(|(done_id, _)| done_id == id) {
+            Some(i) => results.push(done.swap_remove(i)),
+            None => late.push(id.clone()),
+        }
+    }
+    if !late.is_empty() {
+        tokio::spawn(async move {
+            while let Some(outcome) = receiver.recv().await {
+                if let Outcome::Done(id, result) = outcome {
+                    on_late(id, result);
+                }
+            }
+        });
+    }
+    FanOut {
+        partial: !late.is_empty(),
+        results,
+        timed_out: late,
+    }
+}
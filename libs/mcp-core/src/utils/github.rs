@@ -1,5 +1,6 @@
 use log::info;
 use regex::Regex;
+#[cfg(feature = "registry")]
 use reqwest::Client;
 use std::collections::HashSet;
 
@@ -9,6 +10,13 @@ pub struct GitHubRepo {
     pub repo: String,
 }
 
+impl GitHubRepo {
+    /// Canonical URL of the repository
+    pub fn url(&self) -> String {
+        format!("https://github.com/{}/{}", self.owner, self.repo)
+    }
+}
+
 /// Parse a GitHub URL to extract owner and repository name
 pub fn parse_github_url(url: &str) -> Result<GitHubRepo, String> {
     // Handle different GitHub URL formats
@@ -29,6 +37,7 @@ pub fn parse_github_url(url: &str) -> Result<GitHubRepo, String> {
 }
 
 /// Fetch a file from a GitHub repository
+#[cfg(feature = "registry")]
 pub async fn fetch_github_file(
     client: &Client,
     owner: &str,
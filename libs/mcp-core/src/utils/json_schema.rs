@@ -0,0 +1,215 @@
+use serde::Serialize;
+use serde_json::Value;
+
+/// Output schema a discovered tool declares
+///
+/// Read from the serialized tool, so the schema is kept whatever the SDK type of the
+/// tool looks like.
+pub fn declared_output_schema<T: Serialize>(tool: &T) -> Option<Value> {
+    serde_json::to_value(tool)
+        .ok()?
+        .get("outputSchema")
+        .filter(|schema| schema.is_object())
+        .cloned()
+}
+
+/// Problems with the `structuredContent` of a tool result, empty when it conforms to
+/// `output_schema`
+///
+/// Error results are not checked, servers are not expected to return structured
+/// content for them.
+pub fn check_structured_content(result: &Value, output_schema: &Value) -> Vec<String> {
+    if result.get("isError").and_then(Value::as_bool) == Some(true) {
+        return Vec::new();
+    }
+    match result.get("structuredContent") {
+        Some(content) => validate(content, output_schema, "structuredContent"),
+        None => vec!["structuredContent is missing".to_string()],
+    }
+}
+
+/// Check `value` against a JSON Schema, returning one message per violation
+///
+/// Covers the keywords tool schemas use in practice: `type`, `enum`, `const`,
+/// `properties`, `required`, `additionalProperties`, `items`, the length and range
+/// bounds, and `allOf`/`anyOf`/`oneOf`. Other keywords, `$ref` included, are ignored.
+pub fn validate(value: &Value, schema: &Value, path: &str) -> Vec<String> {
+    let mut errors = Vec::new();
+    check(value, schema, path, &mut errors);
+    errors
+}
+
+fn check(value: &Value, schema: &Value, path: &str, errors: &mut Vec<String>) {
+    let Some(schema) = schema.as_object() else {
+        if schema == &Value::Bool(false) {
+            errors.push(format!("{} is not allowed", path));
+        }
+        return;
+    };
+
+    if let Some(expected) = schema.get("type") {
+        let types: Vec<&str> = match expected {
+            Value::String(name) => vec![name.as_str()],
+            Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !types.is_empty() && !types.iter().any(|name| has_type(value, name)) {
+            errors.push(format!(
+                "{} should be {}, got {}",
+                path,
+                types.join(" or "),
+                type_name(value)
+            ));
+            return;
+        }
+    }
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+        if !allowed.contains(value) {
+            errors.push(format!("{} is not one of the allowed values", path));
+        }
+    }
+    if let Some(constant) = schema.get("const") {
+        if constant != value {
+            errors.push(format!("{} should be {}", path, constant));
+        }
+    }
+
+    match value {
+        Value::Object(map) => {
+            let properties = schema.get("properties").and_then(Value::as_object);
+            for name in schema
+                .get("required")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+            {
+                if !map.contains_key(name) {
+                    errors.push(format!("{}.{} is missing", path, name));
+                }
+            }
+            for (name, property) in map {
+                let property_path = format!("{}.{}", path, name);
+                match properties.and_then(|properties| properties.get(name)) {
+                    Some(property_schema) => {
+                        check(property, property_schema, &property_path, errors)
+                    }
+                    None => match schema.get("additionalProperties") {
+                        Some(Value::Bool(false)) => {
+                            errors.push(format!("{} is not allowed", property_path))
+                        }
+                        Some(additional @ Value::Object(_)) => {
+                            check(property, additional, &property_path, errors)
+                        }
+                        _ => {}
+                    },
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(item_schema) = schema.get("items") {
+                for (index, item) in items.iter().enumerate() {
+                    check(item, item_schema, &format!("{}[{}]", path, index), errors);
+                }
+            }
+            check_bound(
+                schema,
+                "minItems",
+                "maxItems",
+                items.len(),
+                "items",
+                path,
+                errors,
+            );
+        }
+        Value::String(text) => {
+            let length = text.chars().count();
+            check_bound(
+                schema,
+                "minLength",
+                "maxLength",
+                length,
+                "characters",
+                path,
+                errors,
+            );
+        }
+        Value::Number(number) => {
+            let number = number.as_f64().unwrap_or_default();
+            if let Some(minimum) = schema.get("minimum").and_then(Value::as_f64) {
+                if number < minimum {
+                    errors.push(format!("{} should be at least {}", path, minimum));
+                }
+            }
+            if let Some(maximum) = schema.get("maximum").and_then(Value::as_f64) {
+                if number > maximum {
+                    errors.push(format!("{} should be at most {}", path, maximum));
+                }
+            }
+        }
+        _ => {}
+    }
+
+    if let Some(all) = schema.get("allOf").and_then(Value::as_array) {
+        for sub_schema in all {
+            check(value, sub_schema, path, errors);
+        }
+    }
+    if let Some(any) = schema.get("anyOf").and_then(Value::as_array) {
+        if !any.iter().any(|s| validate(value, s, path).is_empty()) {
+            errors.push(format!("{} doesn't match any of the allowed schemas", path));
+        }
+    }
+    if let Some(one) = schema.get("oneOf").and_then(Value::as_array) {
+        let matching = one
+            .iter()
+            .filter(|s| validate(value, s, path).is_empty())
+            .count();
+        if matching != 1 {
+            errors.push(format!(
+                "{} should match exactly one schema, matches {}",
+                path, matching
+            ));
+        }
+    }
+}
+
+fn check_bound(
+    schema: &serde_json::Map<String, Value>,
+    min_keyword: &str,
+    max_keyword: &str,
+    count: usize,
+    unit: &str,
+    path: &str,
+    errors: &mut Vec<String>,
+) {
+    if let Some(min) = schema.get(min_keyword).and_then(Value::as_u64) {
+        if (count as u64) < min {
+            errors.push(format!("{} should have at least {} {}", path, min, unit));
+        }
+    }
+    if let Some(max) = schema.get(max_keyword).and_then(Value::as_u64) {
+        if (count as u64) > max {
+            errors.push(format!("{} should have at most {} {}", path, max, unit));
+        }
+    }
+}
+
+fn has_type(value: &Value, name: &str) -> bool {
+    match name {
+        "integer" => value.as_f64().is_some_and(|n| n.fract() == 0.0),
+        "number" => value.is_number(),
+        name => type_name(value) == name,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
@@ -3,9 +3,15 @@ use std::{fs, path::PathBuf};
 use directories::ProjectDirs;
 use log::info;
 
+pub mod clock;
 pub mod command;
+pub mod description;
+pub mod fan_out;
 pub mod github;
+pub mod json_schema;
+pub mod payload;
 pub mod process;
+pub mod zip;
 
 /// Get the default storage path
 pub fn default_storage_path() -> Result<PathBuf, String> {
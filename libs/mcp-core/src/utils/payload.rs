@@ -0,0 +1,69 @@
+use log::{debug, info};
+use serde_json::Value;
+
+use crate::diagnostics::{is_secret_name, REDACTED};
+
+/// Environment variable enabling the wire log, `1` or `true` to log full payloads
+///
+/// Payloads are logged at debug level with the values of secret-named keys redacted.
+/// Without it, only the key names and sizes of payloads are logged.
+pub const WIRE_LOG_ENV: &str = "DOCKMASTER_WIRE_LOG";
+
+/// Whether full payloads are logged, see [`WIRE_LOG_ENV`]
+pub fn wire_log_enabled() -> bool {
+    std::env::var(WIRE_LOG_ENV)
+        .map(|value| matches!(value.trim().to_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false)
+}
+
+/// Key names and size of a payload, safe to log: `{path, encoding} (42 bytes)`
+pub fn payload_summary(payload: &Value) -> String {
+    let size = serde_json::to_vec(payload)
+        .map(|bytes| bytes.len())
+        .unwrap_or(0);
+    let shape = match payload {
+        Value::Object(map) => format!(
+            "{{{}}}",
+            map.keys()
+                .map(String::as_str)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        Value::Array(items) => format!("array of {}", items.len()),
+        Value::String(_) => "string".to_string(),
+        Value::Number(_) => "number".to_string(),
+        Value::Bool(_) => "bool".to_string(),
+        Value::Null => "null".to_string(),
+    };
+    format!("{} ({} bytes)", shape, size)
+}
+
+/// Copy of a payload where the values of secret-named keys (`apiKey`, `token`, ...)
+/// are replaced, at any depth
+pub fn redact_payload(payload: &Value) -> Value {
+    match payload {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| {
+                    let value = if is_secret_name(key) && !value.is_null() {
+                        Value::String(REDACTED.to_string())
+                    } else {
+                        redact_payload(value)
+                    };
+                    (key.clone(), value)
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(redact_payload).collect()),
+        other => other.clone(),
+    }
+}
+
+/// Log the summary of a payload at info, and the redacted payload at debug when the
+/// wire log is enabled
+pub fn log_payload(label: &str, payload: &Value) {
+    info!("{}: {}", label, payload_summary(payload));
+    if wire_log_enabled() {
+        debug!("{} (wire): {}", label, redact_payload(payload));
+    }
+}
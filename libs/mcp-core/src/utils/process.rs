@@ -1,9 +1,14 @@
+use std::collections::{HashMap, HashSet};
 use std::process::Command;
+use std::time::Duration;
 
-use log::info;
-use sysinfo::System;
+use log::{debug, info, warn};
+use sysinfo::{
+    Pid, Process, ProcessStatus, ProcessesToUpdate, System, MINIMUM_CPU_UPDATE_INTERVAL,
+};
 
 use super::command::CommandWrappedInShellBuilder;
+use crate::models::types::ProcessPriority;
 
 fn adapted_process_name(process_name: &str) -> String {
     let name = if cfg!(target_os = "windows") {
@@ -29,7 +34,7 @@ pub fn restart_process(process_name: &str) -> Result<bool, String> {
             Ok(true)
         }
         Err(e) => {
-            println!("Process not found {}", process_name);
+            warn!("Process not found {}", process_name);
             Err(e)
         }
     }
@@ -44,7 +49,7 @@ pub fn find_process_by_name(process_name: &str) -> Result<String, String> {
     for (pid, process) in system.processes() {
         let n = process.name().to_str().unwrap().to_string();
         if n.to_lowercase().eq(&target_name.to_lowercase()) {
-            println!(
+            debug!(
                 "Found process '{:?}' (PID: {}) -- terminating...",
                 process, pid
             );
@@ -56,7 +61,7 @@ pub fn find_process_by_name(process_name: &str) -> Result<String, String> {
             return Ok(command.first().unwrap_or(&"").to_string());
         }
     }
-    print!("Process not found {}", process_name);
+    debug!("Process not found {}", process_name);
     Err("Process not found".to_string())
 }
 
@@ -175,3 +180,469 @@ pub async fn kill_all_processes_by_name(process_name: &str) {
 
     futures::future::join_all(futures).await;
 }
+
+/// Env var set on every spawned server, inherited by the processes it starts
+pub const SPAWN_ID_ENV: &str = "MCP_DOCKMASTER_SPAWN_ID";
+
+/// A running process identified well enough to recognize it after a restart
+#[derive(Debug, Clone)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    /// Start time, in seconds since the Unix epoch
+    pub start_time: u64,
+    pub command: String,
+}
+
+fn command_line(process: &Process) -> String {
+    process
+        .cmd()
+        .iter()
+        .map(|arg| arg.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn has_spawn_id(process: &Process, spawn_id: &str) -> bool {
+    let marker = format!("{}={}", SPAWN_ID_ENV, spawn_id);
+    process
+        .environ()
+        .iter()
+        .any(|var| var.to_string_lossy() == marker)
+}
+
+/// Find the topmost process started with `spawn_id` in its environment
+pub fn find_process_by_spawn_id(spawn_id: &str) -> Option<ProcessInfo> {
+    let system = System::new_all();
+    let tagged: Vec<(&Pid, &Process)> = system
+        .processes()
+        .iter()
+        .filter(|(_, process)| has_spawn_id(process, spawn_id))
+        .collect();
+
+    tagged
+        .iter()
+        .find(|(_, process)| {
+            process
+                .parent()
+                .is_none_or(|parent| !tagged.iter().any(|(pid, _)| **pid == parent))
+        })
+        .map(|(pid, process)| ProcessInfo {
+            pid: pid.as_u32(),
+            start_time: process.start_time(),
+            command: command_line(process),
+        })
+}
+
+/// Whether `process` still runs, a recycled pid doesn't count
+pub fn is_process_alive(process: &ProcessInfo) -> bool {
+    let pid = Pid::from_u32(process.pid);
+    let mut system = System::new();
+    system.refresh_processes(ProcessesToUpdate::Some(&[pid]), true);
+    system.process(pid).is_some_and(|found| {
+        found.start_time() == process.start_time && found.status() != ProcessStatus::Zombie
+    })
+}
+
+/// This process, None if the system doesn't report it
+pub fn current_process() -> Option<ProcessInfo> {
+    let pid = Pid::from_u32(std::process::id());
+    let mut system = System::new();
+    system.refresh_processes(ProcessesToUpdate::Some(&[pid]), true);
+    system.process(pid).map(|process| ProcessInfo {
+        pid: pid.as_u32(),
+        start_time: process.start_time(),
+        command: command_line(process),
+    })
+}
+
+/// Resources used by the processes of a spawn
+#[derive(Debug, Clone, Default)]
+pub struct ProcessUsage {
+    pub processes: usize,
+    /// CPU usage summed over the processes, 100 is one full core
+    pub cpu_percent: f32,
+    pub memory_bytes: u64,
+}
+
+/// Measure the processes started with `spawn_id` in their environment
+///
+/// Blocks for the minimum CPU sampling interval. Returns `None` when no process of the
+/// spawn is running.
+pub fn spawn_resource_usage(spawn_id: &str) -> Option<ProcessUsage> {
+    let mut system = System::new_all();
+    std::thread::sleep(MINIMUM_CPU_UPDATE_INTERVAL);
+    system.refresh_processes(ProcessesToUpdate::All, true);
+
+    let mut usage = ProcessUsage::default();
+    for process in system.processes().values() {
+        if has_spawn_id(process, spawn_id) {
+            usage.processes += 1;
+            usage.cpu_percent += process.cpu_usage();
+            usage.memory_bytes += process.memory();
+        }
+    }
+    (usage.processes > 0).then_some(usage)
+}
+
+/// Memory used by the processes of each of `spawn_ids`, in bytes
+///
+/// Unlike [`spawn_resource_usage`] this doesn't block to sample the CPU. Spawns without
+/// a running process are left out.
+pub fn spawn_memory_usage(spawn_ids: &[String]) -> HashMap<String, u64> {
+    let system = System::new_all();
+    let mut memory = HashMap::new();
+    for process in system.processes().values() {
+        if let Some(spawn_id) = spawn_ids
+            .iter()
+            .find(|spawn_id| has_spawn_id(process, spawn_id))
+        {
+            *memory.entry(spawn_id.clone()).or_insert(0) += process.memory();
+        }
+    }
+    memory
+}
+
+/// Kill the whole process tree of a spawn: the recorded process if it is still the
+/// same program, every process that inherited the spawn id, and their descendants
+///
+/// Servers are spawned by the stdio transport, which doesn't start them in a process
+/// group or job object of their own, so the tree is found from the processes instead.
+/// Descendants are looked up by parent for grandchildren that were started with a
+/// clean environment, like some `npx` and `uvx` children. On Windows the roots of the
+/// tree are also killed with `taskkill /T`, which follows the console processes of a
+/// `.cmd` shim.
+///
+/// Returns the number of processes killed.
+pub fn kill_spawned_processes(
+    spawn_id: &str,
+    pid: Option<u32>,
+    start_time: Option<u64>,
+    command: Option<&str>,
+) -> usize {
+    kill_process_tree(spawn_id, pid, start_time, command, &[])
+}
+
+/// Same as [`kill_spawned_processes`], also killing the processes of `known` that
+/// still run
+///
+/// A descendant is orphaned when its parent exits and can't be found by parent
+/// anymore, `known` is the tree found with [`spawned_process_tree`] before.
+pub fn kill_process_tree(
+    spawn_id: &str,
+    pid: Option<u32>,
+    start_time: Option<u64>,
+    command: Option<&str>,
+    known: &[ProcessInfo],
+) -> usize {
+    let system = System::new_all();
+    let mut tree = spawn_tree(&system, spawn_id, pid, start_time, command);
+    for process in known {
+        let pid = Pid::from_u32(process.pid);
+        // A recycled pid has another start time
+        if system
+            .process(pid)
+            .is_some_and(|found| found.start_time() == process.start_time)
+        {
+            tree.insert(pid);
+        }
+    }
+
+    #[cfg(windows)]
+    let taskkilled = taskkill_roots(&system, &tree);
+    #[cfg(not(windows))]
+    let taskkilled = false;
+    // What taskkill left, the processes of the tree that are gone were killed by it
+    let (system, mut killed) = if taskkilled {
+        let after = System::new_all();
+        let gone = tree
+            .iter()
+            .filter(|pid| {
+                system.process(**pid).is_some_and(|before| {
+                    before.status() != ProcessStatus::Zombie
+                        && after
+                            .process(**pid)
+                            .is_none_or(|found| found.start_time() != before.start_time())
+                })
+            })
+            .count();
+        (after, gone)
+    } else {
+        (system, 0)
+    };
+
+    for (process_pid, process) in system.processes() {
+        if !tree.contains(process_pid) || process.status() == ProcessStatus::Zombie {
+            continue;
+        }
+
+        info!(
+            "terminating orphaned process id:{} command:{}",
+            process_pid.as_u32(),
+            command_line(process)
+        );
+        if process.kill() {
+            killed += 1;
+        } else {
+            warn!("failed to terminate process id:{}", process_pid.as_u32());
+        }
+    }
+
+    killed
+}
+
+/// Kill the processes of `tree` whose parent isn't part of it with their descendants,
+/// returning whether taskkill succeeded for any of them
+///
+/// The output of taskkill is localized, so only its exit status is looked at.
+#[cfg(windows)]
+fn taskkill_roots(system: &System, tree: &HashSet<Pid>) -> bool {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    let roots: Vec<Pid> = tree
+        .iter()
+        .filter(|pid| {
+            system.process(**pid).is_some_and(|process| {
+                process
+                    .parent()
+                    .is_none_or(|parent| !tree.contains(&parent))
+            })
+        })
+        .copied()
+        .collect();
+    let mut killed = false;
+    for root in roots {
+        let pid = root.as_u32().to_string();
+        let output = Command::new("taskkill")
+            .args(["/PID", &pid, "/T", "/F"])
+            .creation_flags(CREATE_NO_WINDOW)
+            .output();
+        match output {
+            Ok(output) if output.status.success() => killed = true,
+            Ok(output) => debug!(
+                "taskkill of process id:{} failed: {}",
+                pid,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+            Err(e) => warn!("failed to run taskkill for process id:{}: {}", pid, e),
+        }
+    }
+    killed
+}
+
+/// Processes of the tree of a spawn, see [`kill_spawned_processes`]
+pub fn spawned_process_tree(
+    spawn_id: &str,
+    pid: Option<u32>,
+    start_time: Option<u64>,
+    command: Option<&str>,
+) -> Vec<ProcessInfo> {
+    let system = System::new_all();
+    spawn_tree(&system, spawn_id, pid, start_time, command)
+        .into_iter()
+        .filter_map(|pid| {
+            system.process(pid).map(|process| ProcessInfo {
+                pid: pid.as_u32(),
+                start_time: process.start_time(),
+                command: command_line(process),
+            })
+        })
+        .collect()
+}
+
+fn spawn_tree(
+    system: &System,
+    spawn_id: &str,
+    pid: Option<u32>,
+    start_time: Option<u64>,
+    command: Option<&str>,
+) -> HashSet<Pid> {
+    let mut tree: HashSet<Pid> = system
+        .processes()
+        .iter()
+        .filter(|(process_pid, process)| {
+            // A recycled pid fails the start time or command check and is left alone
+            let is_recorded = pid == Some(process_pid.as_u32())
+                && start_time == Some(process.start_time())
+                && command.is_some_and(|command| command == command_line(process));
+            is_recorded || has_spawn_id(process, spawn_id)
+        })
+        .map(|(process_pid, _)| *process_pid)
+        .collect();
+    add_descendants(system, &mut tree);
+    tree
+}
+
+/// Add the descendants of the processes of `tree` to it
+fn add_descendants(system: &System, tree: &mut HashSet<Pid>) {
+    loop {
+        let children: Vec<Pid> = system
+            .processes()
+            .iter()
+            .filter(|(process_pid, process)| {
+                !tree.contains(*process_pid)
+                    && process
+                        .parent()
+                        .is_some_and(|parent| tree.contains(&parent))
+            })
+            .map(|(process_pid, _)| *process_pid)
+            .collect();
+        if children.is_empty() {
+            return;
+        }
+        tree.extend(children);
+    }
+}
+
+/// Remove a docker container by name, returns whether it existed
+pub async fn remove_docker_container(container_name: &str) -> Result<bool, String> {
+    let mut command = CommandWrappedInShellBuilder::new("docker")
+        .args(["rm", "-f", container_name])
+        .clone()
+        .build();
+    let output = command
+        .output()
+        .await
+        .map_err(|e| format!("failed to run docker: {}", e))?;
+
+    if output.status.success() {
+        info!("removed docker container {}", container_name);
+        return Ok(true);
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if stderr.contains("No such container") {
+        Ok(false)
+    } else {
+        Err(stderr.trim().to_string())
+    }
+}
+
+/// Stop a docker container by name, letting it exit for `timeout` before docker kills
+/// it, returns whether it existed
+pub async fn stop_docker_container(
+    container_name: &str,
+    timeout: Duration,
+) -> Result<bool, String> {
+    let timeout_secs = timeout.as_secs().to_string();
+    let mut command = CommandWrappedInShellBuilder::new("docker")
+        .args(["stop", "-t", &timeout_secs, container_name])
+        .clone()
+        .build();
+    let output = command
+        .output()
+        .await
+        .map_err(|e| format!("failed to run docker: {}", e))?;
+
+    if output.status.success() {
+        info!("stopped docker container {}", container_name);
+        return Ok(true);
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if stderr.contains("No such container") {
+        Ok(false)
+    } else {
+        Err(stderr.trim().to_string())
+    }
+}
+
+/// Apply a priority and a CPU core limit to every process of a spawn
+///
+/// Processes forked later inherit the settings. Returns the settings that could not be
+/// applied, failing to apply them does not prevent the server from running.
+pub fn apply_process_limits(
+    spawn_id: &str,
+    priority: ProcessPriority,
+    cpu_limit: Option<u32>,
+) -> Vec<String> {
+    if priority == ProcessPriority::Normal && cpu_limit.is_none() {
+        return Vec::new();
+    }
+
+    let system = System::new_all();
+    let pids: Vec<u32> = system
+        .processes()
+        .iter()
+        .filter(|(_, process)| has_spawn_id(process, spawn_id))
+        .map(|(pid, _)| pid.as_u32())
+        .collect();
+    if pids.is_empty() {
+        return vec!["no process found to apply the priority and CPU limit to".to_string()];
+    }
+
+    let mut warnings = Vec::new();
+    for pid in pids {
+        if let Err(e) = set_process_limits(pid, priority, cpu_limit) {
+            warnings.push(format!("process id:{}: {}", pid, e));
+        }
+    }
+    warnings
+}
+
+fn run_limit_command(command: &mut Command) -> Result<(), String> {
+    let output = command
+        .output()
+        .map_err(|e| format!("failed to run {:?}: {}", command.get_program(), e))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+#[cfg(unix)]
+fn set_process_limits(
+    pid: u32,
+    priority: ProcessPriority,
+    cpu_limit: Option<u32>,
+) -> Result<(), String> {
+    if priority != ProcessPriority::Normal {
+        run_limit_command(Command::new("renice").args([
+            "-n",
+            &priority.nice_value().to_string(),
+            "-p",
+            &pid.to_string(),
+        ]))
+        .map_err(|e| format!("failed to set priority {}: {}", priority.as_str(), e))?;
+    }
+
+    if let Some(limit) = cpu_limit {
+        if cfg!(target_os = "linux") {
+            let cores = format!("0-{}", limit.saturating_sub(1));
+            run_limit_command(Command::new("taskset").args([
+                "-a",
+                "-p",
+                "-c",
+                &cores,
+                &pid.to_string(),
+            ]))
+            .map_err(|e| format!("failed to limit to {} CPU cores: {}", limit, e))?;
+        } else {
+            return Err("CPU core limits are not supported on this platform".to_string());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+fn set_process_limits(
+    pid: u32,
+    priority: ProcessPriority,
+    cpu_limit: Option<u32>,
+) -> Result<(), String> {
+    let mut script = format!("$p = Get-Process -Id {};", pid);
+    if priority != ProcessPriority::Normal {
+        script.push_str(&format!(
+            " $p.PriorityClass = '{}';",
+            priority.windows_priority_class()
+        ));
+    }
+    if let Some(limit) = cpu_limit {
+        let mask: u64 = if limit >= 64 {
+            u64::MAX
+        } else {
+            (1u64 << limit) - 1
+        };
+        script.push_str(&format!(" $p.ProcessorAffinity = {};", mask));
+    }
+    run_limit_command(Command::new("powershell").args(["-NoProfile", "-Command", &script]))
+}
@@ -0,0 +1,383 @@
+//! Webhook notifications, for teams that want a chat message when a server breaks.
+//!
+//! Each [`Webhook`] is a URL with the [`WebhookEventKind`]s it wants. Payloads are JSON
+//! signed with an HMAC-SHA256 of the body keyed by the secret of the webhook, sent in
+//! the [`SIGNATURE_HEADER`] header as `sha256=<hex>`. Failed deliveries are retried
+//! after each of [`WEBHOOK_RETRY_DELAYS`], then kept in a dead-letter log.
+//!
+//! Installs and app updates come from the event bus. Status changes and crashes are
+//! found by comparing the statuses of the servers every [`WEBHOOK_STATUS_INTERVAL`],
+//! as statuses change in too many places to publish each change.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use uuid::Uuid;
+
+use crate::events::{DockmasterEvent, InstallStage};
+use crate::models::types::ServerStatus;
+use crate::utils::clock::Clock;
+
+/// Waits before each retry of a failed delivery
+pub const WEBHOOK_RETRY_DELAYS: [Duration; 3] = [
+    Duration::from_secs(5),
+    Duration::from_secs(30),
+    Duration::from_secs(300),
+];
+
+/// How long a webhook has to answer
+pub const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How often the statuses of the servers are compared
+pub const WEBHOOK_STATUS_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Failed deliveries kept in the dead-letter log
+pub const WEBHOOK_DEAD_LETTERS_KEPT: i64 = 500;
+
+pub const SIGNATURE_HEADER: &str = "X-Dockmaster-Signature";
+pub const EVENT_HEADER: &str = "X-Dockmaster-Event";
+pub const DELIVERY_HEADER: &str = "X-Dockmaster-Delivery";
+
+/// Events a webhook can be sent
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEventKind {
+    /// A server started, stopped or failed
+    StatusChange,
+    /// A running server exited on its own
+    Crash,
+    /// A server installation finished, successfully or not
+    Install,
+    /// A new version of the app can be installed
+    UpdateAvailable,
+    /// Sent by `webhooks/test`, whatever the events of the webhook
+    Test,
+}
+
+impl WebhookEventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEventKind::StatusChange => "status_change",
+            WebhookEventKind::Crash => "crash",
+            WebhookEventKind::Install => "install",
+            WebhookEventKind::UpdateAvailable => "update_available",
+            WebhookEventKind::Test => "test",
+        }
+    }
+}
+
+/// A webhook, as created and updated by the user
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Webhook {
+    pub id: String,
+    pub url: String,
+    /// Events sent to the webhook, every event when empty
+    #[serde(default)]
+    pub events: Vec<WebhookEventKind>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Key of the payload signatures, never sent back to clients
+    ///
+    /// Generated when a webhook is created without one, kept when a webhook is
+    /// updated without one.
+    #[serde(default, skip_serializing)]
+    pub secret: Option<String>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl Webhook {
+    /// Check the webhook before saving it
+    pub fn validate(&self) -> Result<(), String> {
+        if self.id.trim().is_empty() {
+            return Err("A webhook needs an id".to_string());
+        }
+        if !self.url.starts_with("https://") && !self.url.starts_with("http://") {
+            return Err(format!(
+                "Invalid webhook URL '{}'. Expected an http or https URL",
+                self.url
+            ));
+        }
+        if self.secret.as_deref() == Some("") {
+            return Err("The secret of a webhook can't be empty".to_string());
+        }
+        Ok(())
+    }
+
+    /// Whether the webhook is enabled and wants `event`
+    pub fn wants(&self, event: WebhookEventKind) -> bool {
+        self.enabled && (self.events.is_empty() || self.events.contains(&event))
+    }
+}
+
+/// A created webhook, with its secret shown this one time
+#[derive(Clone, Debug, Serialize)]
+pub struct CreatedWebhook {
+    pub webhook: Webhook,
+    pub secret: String,
+}
+
+/// Secret for a webhook created without one
+pub fn generate_secret() -> String {
+    format!("whsec_{}", Uuid::new_v4().simple())
+}
+
+/// Body of a webhook request
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WebhookPayload {
+    /// Unique per delivery, the same across retries
+    pub id: String,
+    pub event: WebhookEventKind,
+    /// In milliseconds since the Unix epoch
+    pub timestamp: u64,
+    /// One line summary, shown as is by Slack incoming webhooks
+    pub text: String,
+    pub data: Value,
+}
+
+impl WebhookPayload {
+    pub fn new(event: WebhookEventKind, data: Value) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            event,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0),
+            text: summary(event, &data),
+            data,
+        }
+    }
+}
+
+fn summary(event: WebhookEventKind, data: &Value) -> String {
+    let field = |name: &str| data.get(name).and_then(Value::as_str).unwrap_or("?");
+    match event {
+        WebhookEventKind::StatusChange => {
+            format!("Server {} is now {}", field("server_id"), field("to"))
+        }
+        WebhookEventKind::Crash => {
+            format!("Server {} crashed: {}", field("server_id"), field("error"))
+        }
+        WebhookEventKind::Install => match data.get("success").and_then(Value::as_bool) {
+            Some(true) => format!("Installed server {}", field("server_id")),
+            _ => format!(
+                "Failed to install server {}: {}",
+                field("server_id"),
+                field("detail")
+            ),
+        },
+        WebhookEventKind::UpdateAvailable => {
+            format!("MCP Dockmaster {} is available", field("version"))
+        }
+        WebhookEventKind::Test => "Test notification from MCP Dockmaster".to_string(),
+    }
+}
+
+/// Signature of a body, as sent in the [`SIGNATURE_HEADER`] header
+pub fn sign(secret: &str, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC takes keys of any size");
+    mac.update(body.as_bytes());
+    let mut signature = "sha256=".to_string();
+    for byte in mac.finalize().into_bytes() {
+        let _ = write!(signature, "{:02x}", byte);
+    }
+    signature
+}
+
+/// Webhook event of a bus event, if webhooks are sent for it
+pub fn webhook_event(event: &DockmasterEvent) -> Option<(WebhookEventKind, Value)> {
+    match event {
+        DockmasterEvent::InstallProgress(progress) if progress.stage.is_terminal() => Some((
+            WebhookEventKind::Install,
+            json!({
+                "server_id": progress.server_id,
+                "operation_id": progress.operation_id,
+                "success": progress.stage == InstallStage::Done,
+                "detail": progress.detail,
+            }),
+        )),
+        DockmasterEvent::UpdateAvailable(update) => Some((
+            WebhookEventKind::UpdateAvailable,
+            json!({ "version": update.version }),
+        )),
+        _ => None,
+    }
+}
+
+/// Webhook events for the servers whose status changed, by server id
+///
+/// A server going from running to an error crashed. Servers that appeared or
+/// disappeared in the meantime were installed or uninstalled, not changed.
+pub fn status_changes(
+    previous: &HashMap<String, ServerStatus>,
+    current: &HashMap<String, ServerStatus>,
+) -> Vec<(WebhookEventKind, Value)> {
+    let mut server_ids: Vec<&String> = current.keys().collect();
+    server_ids.sort();
+    let mut events = Vec::new();
+    for server_id in server_ids {
+        let (Some(before), Some(after)) = (previous.get(server_id), current.get(server_id)) else {
+            continue;
+        };
+        if before.to_string() == after.to_string() {
+            continue;
+        }
+        events.push((
+            WebhookEventKind::StatusChange,
+            json!({
+                "server_id": server_id,
+                "from": before.to_string(),
+                "to": after.to_string(),
+            }),
+        ));
+        if let (ServerStatus::Running, ServerStatus::Error(error)) = (before, after) {
+            events.push((
+                WebhookEventKind::Crash,
+                json!({ "server_id": server_id, "error": error }),
+            ));
+        }
+    }
+    events
+}
+
+/// Outcome of delivering a payload to a webhook
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct WebhookDelivery {
+    pub webhook_id: String,
+    pub delivery_id: String,
+    pub event: WebhookEventKind,
+    pub success: bool,
+    pub attempts: u32,
+    /// Error of the last attempt, when every attempt failed
+    pub error: Option<String>,
+}
+
+/// A payload that could not be delivered
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DeadLetter {
+    /// Assigned by the database, 0 for entries not saved yet
+    pub id: i64,
+    pub webhook_id: String,
+    pub payload: WebhookPayload,
+    pub error: String,
+    pub attempts: u32,
+    /// In milliseconds since the Unix epoch
+    pub failed_at: u64,
+}
+
+/// Sends webhook requests
+///
+/// `HttpWebhookSender` posts them, tests inject their own implementation.
+#[async_trait]
+pub trait WebhookSender: Send + Sync {
+    /// POST `body` to `url`, failing on anything but a 2xx answer
+    async fn send(&self, url: &str, headers: &[(&str, String)], body: &str) -> Result<(), String>;
+}
+
+/// Sender of the webhooks of the core
+///
+/// Without the `webhooks` feature nothing is sent and every delivery fails.
+pub fn default_webhook_sender() -> Arc<dyn WebhookSender> {
+    #[cfg(feature = "webhooks")]
+    return Arc::new(HttpWebhookSender);
+    #[cfg(not(feature = "webhooks"))]
+    return Arc::new(NoWebhookSender);
+}
+
+/// Webhook sender for embedders that don't use webhooks, every delivery fails
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoWebhookSender;
+
+#[async_trait]
+impl WebhookSender for NoWebhookSender {
+    async fn send(
+        &self,
+        _url: &str,
+        _headers: &[(&str, String)],
+        _body: &str,
+    ) -> Result<(), String> {
+        Err("Webhooks are not available in this build".to_string())
+    }
+}
+
+/// Webhook sender posting over HTTP
+#[cfg(feature = "webhooks")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HttpWebhookSender;
+
+#[cfg(feature = "webhooks")]
+#[async_trait]
+impl WebhookSender for HttpWebhookSender {
+    async fn send(&self, url: &str, headers: &[(&str, String)], body: &str) -> Result<(), String> {
+        let client = reqwest::Client::builder()
+            .timeout(WEBHOOK_TIMEOUT)
+            .build()
+            .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+        let mut request = client
+            .post(url)
+            .header("Content-Type", "application/json")
+            .header("User-Agent", "MCP-Dockmaster")
+            .body(body.to_string());
+        for (name, value) in headers {
+            request = request.header(*name, value);
+        }
+
+        let response = request.send().await.map_err(|e| e.to_string())?;
+        if !response.status().is_success() {
+            return Err(format!("The webhook answered {}", response.status()));
+        }
+        Ok(())
+    }
+}
+
+/// Deliver a payload, waiting on `clock` for each of `retry_delays` between attempts
+pub async fn deliver(
+    sender: &dyn WebhookSender,
+    clock: &dyn Clock,
+    webhook: &Webhook,
+    payload: &WebhookPayload,
+    retry_delays: &[Duration],
+) -> WebhookDelivery {
+    let body = serde_json::to_string(payload).unwrap_or_default();
+    let mut headers = vec![
+        (EVENT_HEADER, payload.event.as_str().to_string()),
+        (DELIVERY_HEADER, payload.id.clone()),
+    ];
+    if let Some(secret) = &webhook.secret {
+        headers.push((SIGNATURE_HEADER, sign(secret, &body)));
+    }
+
+    let mut attempts = 0;
+    let mut delays = retry_delays.iter();
+    let error = loop {
+        attempts += 1;
+        let error = match sender.send(&webhook.url, &headers, &body).await {
+            Ok(()) => break None,
+            Err(e) => e,
+        };
+        match delays.next() {
+            Some(delay) => clock.sleep(*delay).await,
+            None => break Some(error),
+        }
+    };
+    WebhookDelivery {
+        webhook_id: webhook.id.clone(),
+        delivery_id: payload.id.clone(),
+        event: payload.event,
+        success: error.is_none(),
+        attempts,
+        error,
+    }
+}
@@ -0,0 +1,76 @@
+//! Fixtures shared by the unit tests.
+//!
+//! [`db`] gives a migrated throwaway database and [`FailingLauncher`] stands in for
+//! the process launcher of cores that must never start a real server.
+
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use mcp_core::{
+    database::db_manager::DBManager,
+    mcp_state::{
+        launch_plan::LaunchPlan,
+        process_launcher::{LaunchedServer, ProcessLauncher},
+    },
+};
+use tempfile::TempDir;
+
+/// Error of the launches of a [`FailingLauncher`] built with `new`
+pub const NOT_STARTED: &str = "not started in tests";
+
+/// Error of a server that exits while it starts
+pub const EXITED_AT_STARTUP: &str = "Server exited during startup with exit code 1";
+
+/// Database in `dir` with the migrations applied
+pub fn db(dir: &TempDir) -> DBManager {
+    let db = DBManager::with_path(dir.path().join("mcp-dockmaster.db")).unwrap();
+    db.apply_migrations().unwrap();
+    db
+}
+
+/// Launcher recording the plan of every launch, all of them fail
+pub struct FailingLauncher {
+    error: String,
+    plans: Mutex<Vec<LaunchPlan>>,
+}
+
+impl Default for FailingLauncher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FailingLauncher {
+    pub fn new() -> Self {
+        Self::with_error(NOT_STARTED)
+    }
+
+    /// Launcher whose launches fail with `error`
+    pub fn with_error(error: &str) -> Self {
+        Self {
+            error: error.to_string(),
+            plans: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Plans of the launches, oldest first
+    pub fn plans(&self) -> Vec<LaunchPlan> {
+        self.plans.lock().unwrap().clone()
+    }
+
+    /// Ids of the servers launched, oldest first
+    pub fn launched(&self) -> Vec<String> {
+        self.plans()
+            .into_iter()
+            .map(|plan| plan.server_id)
+            .collect()
+    }
+}
+
+#[async_trait]
+impl ProcessLauncher for FailingLauncher {
+    async fn launch(&self, plan: &LaunchPlan, _: &str) -> Result<LaunchedServer, String> {
+        self.plans.lock().unwrap().push(plan.clone());
+        Err(self.error.clone())
+    }
+}
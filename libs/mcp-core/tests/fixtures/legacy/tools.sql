@@ -0,0 +1,25 @@
+-- The tools table of the DatabaseManager of builds before the Diesel schema
+CREATE TABLE tools (
+    id TEXT PRIMARY KEY,
+    data TEXT NOT NULL
+);
+
+INSERT INTO tools (id, data) VALUES (
+    'filesystem',
+    '{"name":"Filesystem","description":"Read and write files","enabled":true,"tool_type":"node","entry_point":null,"configuration":{"command":"npx","args":["-y","@modelcontextprotocol/server-filesystem"]},"distribution":{"type":"npm","package":"@modelcontextprotocol/server-filesystem"},"env":{"ROOT":{"value":"/home/user","description":"Shared directory","required":true}}}'
+);
+
+INSERT INTO tools (id, data) VALUES (
+    'My Weather',
+    '{"name":"My Weather","description":"","enabled":false,"tools_type":"python","configuration":{"command":"uvx","args":["weather-mcp"]},"config":{"env":{"API_KEY":"secret"}}}'
+);
+
+INSERT INTO tools (id, data) VALUES (
+    'broken',
+    '{"description":"Saved without a name"}'
+);
+
+INSERT INTO tools (id, data) VALUES (
+    'github',
+    '{"name":"GitHub","description":"","enabled":true,"tools_type":"docker","configuration":{"command":"docker","args":["run","-i","github-mcp"]}}'
+);
@@ -0,0 +1,378 @@
+//! Harness running the HTTP JSON-RPC server of a core for end-to-end tests.
+//!
+//! [`TestServer`] boots the axum routes on a free port, with a throwaway database and
+//! a stubbed registry. Responses are compared to fixtures in `tests/fixtures/http`
+//! with [`assert_fixture`], where the string `"<any>"` matches any value.
+
+// Helpers are shared with the tests of features, not all are used everywhere
+#![allow(dead_code)]
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use mcp_core::{
+    core::{mcp_core::MCPCore, mcp_core_builder::MCPCoreBuilder},
+    database::db_manager::DBManager,
+    http_server::serve_http,
+    models::types::ErrorResponse,
+    registry::registry_client::RegistryClient,
+};
+use serde_json::{json, Value};
+use tempfile::{tempdir, TempDir};
+use tokio::net::TcpListener;
+
+/// Fixture value matching anything
+pub const ANY: &str = "<any>";
+
+/// How long to wait for an SSE event before failing the test
+const EVENT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Load `tests/fixtures/http/<name>.json`
+pub fn fixture(name: &str) -> Value {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/http")
+        .join(format!("{}.json", name));
+    let text = std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("Failed to read fixture {}: {}", path.display(), e));
+    serde_json::from_str(&text).expect("fixture should be valid JSON")
+}
+
+/// Assert that `actual` has exactly the shape of the fixture `name`
+pub fn assert_fixture(actual: &Value, name: &str) {
+    if let Some(mismatch) = mismatch(&fixture(name), actual, "$") {
+        panic!(
+            "response doesn't match fixture {}: {}\nresponse: {}",
+            name,
+            mismatch,
+            serde_json::to_string_pretty(actual).unwrap()
+        );
+    }
+}
+
+/// First difference between a fixture and a value, objects must have the same keys
+fn mismatch(expected: &Value, actual: &Value, path: &str) -> Option<String> {
+    match (expected, actual) {
+        (Value::String(any), _) if any == ANY => None,
+        (Value::Object(expected), Value::Object(actual)) => {
+            for key in expected.keys().chain(actual.keys()) {
+                let path = format!("{}.{}", path, key);
+                match (expected.get(key), actual.get(key)) {
+                    (Some(expected), Some(actual)) => {
+                        if let Some(mismatch) = self::mismatch(expected, actual, &path) {
+                            return Some(mismatch);
+                        }
+                    }
+                    (Some(_), None) => return Some(format!("{} is missing", path)),
+                    (None, _) => return Some(format!("{} is not expected", path)),
+                }
+            }
+            None
+        }
+        (Value::Array(expected), Value::Array(actual)) => {
+            if expected.len() != actual.len() {
+                return Some(format!(
+                    "{} has {} items, expected {}",
+                    path,
+                    actual.len(),
+                    expected.len()
+                ));
+            }
+            expected
+                .iter()
+                .zip(actual)
+                .enumerate()
+                .find_map(|(index, (expected, actual))| {
+                    mismatch(expected, actual, &format!("{}[{}]", path, index))
+                })
+        }
+        _ if expected == actual => None,
+        _ => Some(format!("{} is {}, expected {}", path, actual, expected)),
+    }
+}
+
+/// Registry client serving a fixed registry
+pub struct StubRegistry(pub Value);
+
+#[async_trait]
+impl RegistryClient for StubRegistry {
+    async fn fetch_registry(&self) -> Result<Value, ErrorResponse> {
+        Ok(self.0.clone())
+    }
+}
+
+/// HTTP server of a core, listening on a free local port
+pub struct TestServer {
+    pub core: MCPCore,
+    pub base_url: String,
+    client: reqwest::Client,
+    next_id: AtomicU64,
+    _dir: TempDir,
+}
+
+impl TestServer {
+    /// Start a server whose registry is the `registry` fixture
+    pub async fn start() -> Self {
+        Self::start_with(|builder| builder).await
+    }
+
+    /// Start a server, letting the test replace dependencies of the core
+    pub async fn start_with(customize: impl FnOnce(MCPCoreBuilder) -> MCPCoreBuilder) -> Self {
+        let dir = tempdir().expect("Failed to create temp directory");
+        let db = DBManager::with_path(dir.path().join("mcp-dockmaster.db"))
+            .expect("Failed to create database");
+        db.apply_migrations().expect("Failed to apply migrations");
+        let builder =
+            MCPCoreBuilder::new(db).registry_client(Arc::new(StubRegistry(fixture("registry"))));
+        let core = customize(builder).build();
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Failed to bind a local port");
+        let addr = serve_http(core.clone(), listener)
+            .await
+            .expect("Failed to start the HTTP server");
+
+        Self {
+            core,
+            base_url: format!("http://{}", addr),
+            client: reqwest::Client::new(),
+            next_id: AtomicU64::new(1),
+            _dir: dir,
+        }
+    }
+
+    /// Send a JSON-RPC request to `/mcp` and return the whole response
+    pub async fn rpc(&self, method: &str, params: Option<Value>) -> Value {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let mut request = json!({ "jsonrpc": "2.0", "id": id, "method": method });
+        if let Some(params) = params {
+            request["params"] = params;
+        }
+        let response: Value = self
+            .client
+            .post(format!("{}/mcp", self.base_url))
+            .json(&request)
+            .send()
+            .await
+            .expect("Failed to send the request")
+            .json()
+            .await
+            .expect("Response should be JSON");
+        assert_eq!(
+            response["id"],
+            json!(id),
+            "response id should echo the request"
+        );
+        response
+    }
+
+    /// POST any body to `/mcp`, returning the status and the body, empty for none
+    pub async fn post_mcp(&self, body: Value) -> (u16, String) {
+        let response = self
+            .client
+            .post(format!("{}/mcp", self.base_url))
+            .json(&body)
+            .send()
+            .await
+            .expect("Failed to send the request");
+        let status = response.status().as_u16();
+        (
+            status,
+            response.text().await.expect("Response should be text"),
+        )
+    }
+
+    /// GET a JSON endpoint, returning the status code and the body
+    pub async fn get_json(&self, path: &str) -> (u16, Value) {
+        let response = self
+            .client
+            .get(format!("{}{}", self.base_url, path))
+            .send()
+            .await
+            .expect("Failed to send the request");
+        let status = response.status().as_u16();
+        (
+            status,
+            response.json().await.expect("Response should be JSON"),
+        )
+    }
+
+    /// GET `path`, returning the status and the body
+    pub async fn get_text(&self, path: &str) -> (u16, String) {
+        let response = self
+            .client
+            .get(format!("{}{}", self.base_url, path))
+            .send()
+            .await
+            .expect("Failed to send the request");
+        let status = response.status().as_u16();
+        (
+            status,
+            response.text().await.expect("Response should be text"),
+        )
+    }
+
+    /// Open an SSE stream on `path`
+    pub async fn sse(&self, path: &str) -> SseStream {
+        let response = self
+            .client
+            .get(format!("{}{}", self.base_url, path))
+            .header("Accept", "text/event-stream")
+            .send()
+            .await
+            .expect("Failed to open the event stream");
+        assert!(response.status().is_success());
+        SseStream {
+            response,
+            buffer: String::new(),
+        }
+    }
+
+    /// Stream of the events of an operation, ending after its final event
+    pub async fn operation_events(&self, operation_id: &str) -> SseStream {
+        self.sse(&format!("/mcp/events?operationId={}", operation_id))
+            .await
+    }
+
+    /// Open an MCP session over SSE
+    pub async fn open_session(&self) -> Session {
+        let mut events = self.sse("/mcp/sse").await;
+        let endpoint = events.next_event().await.expect("Session should start");
+        assert_eq!(endpoint.event.as_deref(), Some("endpoint"));
+        let id = endpoint
+            .data
+            .strip_prefix("?sessionId=")
+            .expect("endpoint event should carry the session id")
+            .to_string();
+        Session {
+            id,
+            events,
+            post_url: format!("{}/mcp/sse", self.base_url),
+            client: self.client.clone(),
+        }
+    }
+}
+
+/// One server-sent event
+#[derive(Debug)]
+pub struct SseEvent {
+    pub event: Option<String>,
+    pub data: String,
+}
+
+impl SseEvent {
+    pub fn json(&self) -> Value {
+        serde_json::from_str(&self.data).expect("event data should be JSON")
+    }
+}
+
+/// Server-sent events read from an HTTP response
+pub struct SseStream {
+    response: reqwest::Response,
+    buffer: String,
+}
+
+impl SseStream {
+    /// Next event, None once the stream ended
+    ///
+    /// Panics when no event arrives within ten seconds.
+    pub async fn next_event(&mut self) -> Option<SseEvent> {
+        loop {
+            if let Some(end) = self.buffer.find("\n\n") {
+                let block: String = self.buffer.drain(..end + 2).collect();
+                if let Some(event) = parse_event(&block) {
+                    return Some(event);
+                }
+                continue;
+            }
+            let chunk = tokio::time::timeout(EVENT_TIMEOUT, self.response.chunk())
+                .await
+                .expect("Timed out waiting for an event")
+                .expect("Failed to read the event stream")?;
+            self.buffer
+                .push_str(&String::from_utf8_lossy(&chunk).replace("\r\n", "\n"));
+        }
+    }
+
+    /// Every remaining event, for streams that end
+    pub async fn collect(mut self) -> Vec<SseEvent> {
+        let mut events = Vec::new();
+        while let Some(event) = self.next_event().await {
+            events.push(event);
+        }
+        events
+    }
+}
+
+/// Parse one event block, None for comments and keep-alives
+fn parse_event(block: &str) -> Option<SseEvent> {
+    let mut event = None;
+    let mut data = Vec::new();
+    for line in block.lines() {
+        if let Some(value) = line.strip_prefix("event:") {
+            event = Some(value.trim_start().to_string());
+        } else if let Some(value) = line.strip_prefix("data:") {
+            data.push(value.strip_prefix(' ').unwrap_or(value));
+        }
+    }
+    if event.is_none() && data.is_empty() {
+        return None;
+    }
+    Some(SseEvent {
+        event,
+        data: data.join("\n"),
+    })
+}
+
+/// MCP session over SSE: requests are posted, responses arrive on the stream
+pub struct Session {
+    pub id: String,
+    pub events: SseStream,
+    post_url: String,
+    client: reqwest::Client,
+}
+
+impl Session {
+    /// Post a JSON-RPC message to the session, returning the status code
+    pub async fn send(&self, message: Value) -> u16 {
+        self.client
+            .post(&self.post_url)
+            .query(&[("sessionId", &self.id)])
+            .json(&message)
+            .send()
+            .await
+            .expect("Failed to post to the session")
+            .status()
+            .as_u16()
+    }
+
+    /// Next JSON-RPC message the server sent on the session
+    pub async fn next_message(&mut self) -> Value {
+        loop {
+            let event = self
+                .events
+                .next_event()
+                .await
+                .expect("Session stream ended");
+            if event.event.as_deref() == Some("message") {
+                return event.json();
+            }
+        }
+    }
+
+    /// Send a request and wait for the response with the same id
+    pub async fn request(&mut self, id: u64, method: &str, params: Value) -> Value {
+        let status = self
+            .send(json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }))
+            .await;
+        assert!((200..300).contains(&status), "post failed with {}", status);
+        loop {
+            let message = self.next_message().await;
+            if message["id"] == json!(id) {
+                return message;
+            }
+        }
+    }
+}
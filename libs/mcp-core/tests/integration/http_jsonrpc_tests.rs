@@ -0,0 +1,656 @@
+#[cfg(test)]
+mod tests {
+    use crate::integration::http_harness::{assert_fixture, TestServer};
+    use std::time::Duration;
+
+    use mcp_core::{
+        core::{mcp_core_proxy_ext::McpCoreProxyExt, mcp_core_read_only_ext::McpCoreReadOnlyExt},
+        init_logging,
+        mcp_state::mcp_state::PROCESS_CHECK_INTERVAL,
+        models::types::{ServerConfiguration, ServerRegistrationRequest, ToolUninstallRequest},
+        read_only::READ_ONLY_REASON,
+        utils::process::kill_spawned_processes,
+    };
+    use serde_json::{json, Value};
+
+    /// Registration of the hello world server built in `dist`
+    fn hello_world_registration() -> Value {
+        let script_path = std::env::current_dir()
+            .unwrap()
+            .join("../../dist/apps/mcp-server-hello-world/index.js");
+        json!({
+            "id": "hello_world",
+            "name": "Hello World",
+            "description": "A simple hello world tool",
+            "type": "node",
+            "configuration": {
+                "command": "node",
+                "args": [
+                    "--experimental-modules",
+                    "--no-warnings",
+                    script_path.to_string_lossy()
+                ]
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn test_health_and_server_info() {
+        let server = TestServer::start().await;
+
+        let (status, health) = server.get_json("/health").await;
+        assert_eq!(status, 200);
+        assert_fixture(&health, "health");
+
+        assert_fixture(&server.rpc("server/info", None).await, "server_info");
+        assert_fixture(&server.rpc("nope/nope", None).await, "unknown_method");
+    }
+
+    #[tokio::test]
+    async fn test_metrics() {
+        let server = TestServer::start().await;
+
+        let (status, metrics) = server.get_text("/metrics").await;
+        assert_eq!(status, 200);
+        assert!(metrics.contains("# TYPE dockmaster_server_restarts_total counter"));
+        assert!(metrics.contains("\ndockmaster_servers_running 0\n"));
+    }
+
+    #[tokio::test]
+    async fn test_tools_list_and_hidden() {
+        let server = TestServer::start().await;
+
+        assert_fixture(&server.rpc("tools/list", None).await, "tools_list");
+        assert_fixture(&server.rpc("tools/hidden", None).await, "tools_hidden");
+
+        let mcp_state = server.core.mcp_state.read().await;
+        mcp_state.set_tools_hidden(true).await.unwrap();
+        drop(mcp_state);
+        assert_eq!(
+            server.rpc("tools/hidden", None).await["result"],
+            json!({ "hidden": true })
+        );
+        assert_fixture(&server.rpc("tools/list", None).await, "tools_list_hidden");
+    }
+
+    #[tokio::test]
+    async fn test_tools_call_errors() {
+        let server = TestServer::start().await;
+
+        assert_fixture(
+            &server.rpc("tools/call", None).await,
+            "tools_call_missing_params",
+        );
+        assert_fixture(
+            &server.rpc("tools/call", Some(json!({}))).await,
+            "tools_call_missing_name",
+        );
+        assert_fixture(
+            &server
+                .rpc(
+                    "tools/call",
+                    Some(json!({ "name": "nope", "arguments": {} })),
+                )
+                .await,
+            "tools_call_unknown_tool",
+        );
+    }
+
+    #[tokio::test]
+    async fn test_batch_requests() {
+        let server = TestServer::start().await;
+        let notification = json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/cancelled",
+            "params": { "requestId": 9 }
+        });
+
+        let (status, body) = server
+            .post_mcp(json!([
+                {
+                    "jsonrpc": "2.0",
+                    "id": "init",
+                    "method": "initialize",
+                    "params": {
+                        "protocolVersion": "2024-11-05",
+                        "capabilities": {},
+                        "clientInfo": { "name": "http-tests", "version": "1.0.0" }
+                    }
+                },
+                notification,
+                { "jsonrpc": "2.0", "id": 2, "method": "tools/list" },
+                { "jsonrpc": "2.0", "id": 3, "method": "nope/nope" },
+                { "id": 4 }
+            ]))
+            .await;
+        assert_eq!(status, 200);
+        let responses: Value = serde_json::from_str(&body).unwrap();
+        let responses = responses.as_array().unwrap();
+        // In order, without an entry for the notification
+        assert_eq!(responses.len(), 4);
+        assert_eq!(responses[0]["id"], "init");
+        assert!(responses[0]["result"]["serverInfo"].is_object());
+        assert_eq!(responses[1]["id"], 2);
+        assert!(responses[1]["result"]["tools"].is_array());
+        assert_eq!(responses[2]["id"], 3);
+        assert_eq!(responses[2]["error"]["code"], -32601);
+        assert_eq!(responses[3]["id"], Value::Null);
+        assert_eq!(responses[3]["error"]["code"], -32600);
+
+        let (status, body) = server.post_mcp(json!([notification])).await;
+        assert_eq!(status, 202);
+        assert!(body.is_empty());
+
+        let (status, body) = server.post_mcp(json!([])).await;
+        assert_eq!(status, 200);
+        let response: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(response["error"]["code"], -32600);
+    }
+
+    #[tokio::test]
+    async fn test_registry_list() {
+        let server = TestServer::start().await;
+        assert_fixture(&server.rpc("registry/list", None).await, "registry_list");
+    }
+
+    #[tokio::test]
+    async fn test_registry_install_failures() {
+        let server = TestServer::start().await;
+
+        assert_fixture(
+            &server.rpc("registry/install", None).await,
+            "registry_install_missing_params",
+        );
+        assert_fixture(
+            &server
+                .rpc("registry/install", Some(json!({ "tool_id": "missing" })))
+                .await,
+            "registry_install_not_found",
+        );
+        assert_fixture(
+            &server
+                .rpc("registry/install", Some(json!({ "tool_id": "broken" })))
+                .await,
+            "registry_install_without_command",
+        );
+        let mut registration = hello_world_registration();
+        registration["configuration"] = json!({});
+        assert_fixture(
+            &server.rpc("registry/install", Some(registration)).await,
+            "registry_install_invalid_configuration",
+        );
+
+        // Nothing was installed
+        let servers = server.core.tool_registry.read().await.get_all_servers();
+        assert!(servers.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_read_only_mode() {
+        let server = TestServer::start().await;
+        server.core.set_read_only(true).await.unwrap();
+        assert_eq!(
+            server.rpc("server/info", None).await["result"]["read_only"],
+            json!(true)
+        );
+
+        for method in ["tools/list", "tools/call", "registry/list", "server/status"] {
+            let response = server.rpc(method, None).await;
+            assert_ne!(
+                response["error"]["data"]["reason"], READ_ONLY_REASON,
+                "{} should be allowed",
+                method
+            );
+        }
+        assert_fixture(
+            &server.rpc("registry/install", None).await,
+            "read_only_refused",
+        );
+        for method in [
+            "registry/import",
+            "server/config",
+            "server/restart",
+            "server/set_mode",
+            "servers/bulk_update",
+            "hooks/set",
+            "schedules/create",
+            "webhooks/delete",
+            "database/migrate_legacy",
+        ] {
+            let response = server.rpc(method, Some(json!({}))).await;
+            assert_eq!(
+                response["error"]["data"]["reason"], READ_ONLY_REASON,
+                "{} should be refused",
+                method
+            );
+        }
+
+        // Only with the admin token, which isn't set here
+        let response = server
+            .rpc(
+                "read_only/set",
+                Some(json!({ "read_only": false, "admin_token": "guess" })),
+            )
+            .await;
+        assert_eq!(response["error"]["code"], json!(-32000));
+        assert!(server.core.is_read_only().await);
+    }
+
+    #[tokio::test]
+    async fn test_registry_sources_hide_their_header_values() {
+        let server = TestServer::start().await;
+        let source = json!({
+            "id": "acme",
+            "url": "https://registry.acme.internal/registry.json",
+            "headers": { "X-Api-Key": "k3y" }
+        });
+
+        let response = server.rpc("registry/save_source", Some(source)).await;
+        assert_eq!(
+            response["result"]["headers"],
+            json!({ "X-Api-Key": "********" })
+        );
+        let response = server.rpc("registry/sources", None).await;
+        let sources = &response["result"]["sources"];
+        assert_eq!(sources[0]["headers"], json!({ "X-Api-Key": "********" }));
+        assert!(!response.to_string().contains("k3y"));
+    }
+
+    #[tokio::test]
+    async fn test_setting_hooks_requires_the_admin_token() {
+        let server = TestServer::start().await;
+        let hooks = json!({
+            "hooks": [{
+                "id": "audit",
+                "stage": "pre",
+                "command": "sh",
+                "args": ["-c", "cat"]
+            }]
+        });
+
+        let response = server.rpc("hooks/set", Some(hooks.clone())).await;
+        assert_eq!(response["error"]["code"], json!(-32000));
+        let mut guessed = hooks;
+        guessed["admin_token"] = json!("guess");
+        let response = server.rpc("hooks/set", Some(guessed)).await;
+        assert_eq!(response["error"]["code"], json!(-32000));
+
+        let response = server.rpc("hooks/list", None).await;
+        assert_eq!(response["result"], json!({ "hooks": [] }));
+    }
+
+    #[tokio::test]
+    async fn test_async_install_streams_its_events() {
+        let server = TestServer::start().await;
+
+        let response = server
+            .rpc(
+                "registry/install",
+                Some(json!({ "tool_id": "missing", "async": true })),
+            )
+            .await;
+        let operation_id = response["result"]["operation_id"].as_str().unwrap();
+        assert_eq!(response["result"]["status"], "queued");
+
+        let events = server.operation_events(operation_id).await.collect().await;
+        let last = events
+            .last()
+            .expect("operation should publish events")
+            .json();
+        assert_eq!(last["type"], "operation_update");
+        assert_eq!(last["state"], "failed");
+        assert_eq!(last["error"], "Tool missing not found");
+    }
+
+    #[tokio::test]
+    async fn test_server_config_errors() {
+        let server = TestServer::start().await;
+
+        assert_fixture(
+            &server
+                .rpc("server/config", Some(json!({ "config": {} })))
+                .await,
+            "server_config_invalid_params",
+        );
+        // The server id may be sent under its old name
+        for field in ["server_id", "tool_id"] {
+            assert_fixture(
+                &server
+                    .rpc(
+                        "server/config",
+                        Some(json!({ field: "nope", "config": {} })),
+                    )
+                    .await,
+                "server_config_unknown_server",
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_session_initialize() {
+        let server = TestServer::start().await;
+        let mut session = server.open_session().await;
+
+        let response = session
+            .request(
+                1,
+                "initialize",
+                json!({
+                    "protocolVersion": "2024-11-05",
+                    "capabilities": {},
+                    "clientInfo": { "name": "http-tests", "version": "1.0.0" }
+                }),
+            )
+            .await;
+        assert_eq!(response["jsonrpc"], "2.0");
+        assert!(response["error"].is_null(), "{}", response);
+        assert!(response["result"]["serverInfo"]["name"].is_string());
+
+        let sessions = server.core.mcp_state.read().await.sessions.clone();
+        assert_eq!(
+            sessions.client_name(&session.id).await.as_deref(),
+            Some("http-tests 1.0.0")
+        );
+        assert_eq!(
+            sessions.get(&session.id).await.unwrap().transport.kind(),
+            "sse"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_notifications_get_no_response() {
+        let server = TestServer::start().await;
+
+        for method in ["notifications/initialized", "nope/nope", "tools/call"] {
+            let (status, body) = server
+                .post_mcp(json!({ "jsonrpc": "2.0", "method": method }))
+                .await;
+            assert_eq!(status, 202, "{}", method);
+            assert!(body.is_empty(), "{} was answered with {}", method, body);
+        }
+
+        // A null id is still an id, only a missing one makes a notification
+        let (status, body) = server
+            .post_mcp(json!({ "jsonrpc": "2.0", "id": null, "method": "tools/list" }))
+            .await;
+        assert_eq!(status, 200);
+        let response: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(response.get("id"), Some(&Value::Null));
+        assert!(response["result"]["tools"].is_array(), "{}", response);
+
+        let mut session = server.open_session().await;
+        session
+            .request(
+                1,
+                "initialize",
+                json!({
+                    "protocolVersion": "2024-11-05",
+                    "capabilities": {},
+                    "clientInfo": { "name": "http-tests", "version": "1.0.0" }
+                }),
+            )
+            .await;
+        let status = session
+            .send(json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }))
+            .await;
+        assert_eq!(status, 202);
+        let sessions = server.core.mcp_state.read().await.sessions.clone();
+        assert!(sessions.get(&session.id).await.unwrap().initialized);
+
+        // The next message answers the next request, nothing answered the notification
+        assert_eq!(
+            session
+                .send(json!({ "jsonrpc": "2.0", "id": 2, "method": "tools/list" }))
+                .await,
+            202
+        );
+        let message = session.next_message().await;
+        assert_eq!(message["id"], 2, "{}", message);
+        assert!(message["result"]["tools"].is_array());
+    }
+
+    #[tokio::test]
+    async fn test_sessions_hear_about_new_tools() {
+        let server = TestServer::start().await;
+        let mut session = server.open_session().await;
+        let response = session
+            .request(
+                1,
+                "initialize",
+                json!({
+                    "protocolVersion": "2024-11-05",
+                    "capabilities": {},
+                    "clientInfo": { "name": "http-tests", "version": "1.0.0" }
+                }),
+            )
+            .await;
+        assert_eq!(
+            response["result"]["capabilities"]["tools"]["listChanged"],
+            json!(true)
+        );
+
+        // Registered even though it can't start, its tools changed from nothing
+        server
+            .core
+            .register_server(ServerRegistrationRequest {
+                server_id: "broken".to_string(),
+                server_name: "Broken".to_string(),
+                description: String::new(),
+                tools_type: "node".to_string(),
+                configuration: Some(ServerConfiguration {
+                    command: Some("node".to_string()),
+                    args: Some(vec!["missing.js".to_string()]),
+                    ..Default::default()
+                }),
+                distribution: None,
+                provenance: None,
+                authentication: None,
+                enabled: None,
+            })
+            .await
+            .unwrap();
+        loop {
+            let message = session.next_message().await;
+            if message["method"] == "notifications/tools/list_changed" {
+                break;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tools_call_through_installed_server() {
+        init_logging();
+        let server = TestServer::start().await;
+
+        let response = server
+            .rpc("registry/install", Some(hello_world_registration()))
+            .await;
+        assert!(response["error"].is_null(), "{}", response);
+        assert_eq!(response["result"]["server_id"], "hello_world");
+
+        let tools = server.rpc("tools/list", None).await;
+        let ids: Vec<&str> = tools["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|tool| tool["serverId"] == "hello_world")
+            .map(|tool| tool["id"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            ids,
+            vec![
+                "hello_world",
+                "hello_world_with_input",
+                "hello_world_with_config"
+            ]
+        );
+
+        let response = server
+            .rpc(
+                "tools/call",
+                Some(json!({
+                    "name": "hello_world_with_input",
+                    "arguments": { "message": "from http" }
+                })),
+            )
+            .await;
+        assert!(response["error"].is_null(), "{}", response);
+        assert_eq!(
+            response["result"]["content"][0]["text"],
+            "hello world from http"
+        );
+
+        server.core.kill_all_processes().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_status_follows_a_killed_server() {
+        init_logging();
+        let server = TestServer::start().await;
+        let response = server
+            .rpc("registry/install", Some(hello_world_registration()))
+            .await;
+        assert!(response["error"].is_null(), "{}", response);
+        let status = server
+            .rpc("server/status", Some(json!({ "server_id": "hello_world" })))
+            .await;
+        assert_eq!(status["result"]["status"], "running");
+
+        // Killed behind our back, as by the OOM killer
+        let record = server
+            .core
+            .tool_registry
+            .read()
+            .await
+            .get_server_processes()
+            .unwrap()
+            .into_iter()
+            .find(|record| record.server_id == "hello_world")
+            .expect("the process should be recorded");
+        kill_spawned_processes(&record.spawn_id, None, None, None);
+
+        let deadline = tokio::time::Instant::now() + PROCESS_CHECK_INTERVAL;
+        let status = loop {
+            let status = server
+                .rpc("server/status", Some(json!({ "server_id": "hello_world" })))
+                .await;
+            if status["result"]["status"] != "running" {
+                break status;
+            }
+            assert!(
+                tokio::time::Instant::now() < deadline,
+                "server still reported running"
+            );
+            tokio::time::sleep(Duration::from_millis(200)).await;
+        };
+        assert!(status["result"]["status"]["error"]
+            .as_str()
+            .unwrap()
+            .starts_with("Error: Server exited"));
+        let tools = server.rpc("tools/list", None).await;
+        assert!(!tools["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|tool| tool["serverId"] == "hello_world"));
+    }
+
+    /// Whether a process runs, zombies waiting to be reaped don't
+    #[cfg(target_os = "linux")]
+    fn is_running(pid: u32) -> bool {
+        std::fs::read_to_string(format!("/proc/{}/stat", pid)).is_ok_and(|stat| {
+            !stat
+                .rsplit(')')
+                .next()
+                .is_some_and(|state| state.trim_start().starts_with('Z'))
+        })
+    }
+
+    #[tokio::test]
+    async fn test_server_delete() {
+        init_logging();
+        let server = TestServer::start().await;
+        let response = server
+            .rpc("registry/install", Some(hello_world_registration()))
+            .await;
+        assert!(response["error"].is_null(), "{}", response);
+
+        let params = json!({ "server_id": "hello_world" });
+        let response = server.rpc("server/delete", Some(params.clone())).await;
+        assert!(response["error"].is_null(), "{}", response);
+        assert_eq!(response["result"]["tools_removed"], 3);
+
+        let tools = server.rpc("tools/list", None).await;
+        assert!(!tools["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|tool| tool["serverId"] == "hello_world"));
+        assert!(server.core.list_servers().await.unwrap().is_empty());
+
+        let response = server.rpc("server/delete", Some(params)).await;
+        assert_eq!(response["error"]["code"], -32000);
+        assert!(response["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("hello_world"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_uninstall_kills_the_process_tree() {
+        init_logging();
+        let server = TestServer::start().await;
+        let dir = tempfile::tempdir().unwrap();
+        let pid_file = dir.path().join("child.pid");
+
+        // The shell forks a child with a clean environment, so without the spawn id,
+        // before running the server
+        let mut registration = hello_world_registration();
+        let mut args = vec![
+            json!("-i"),
+            json!("sleep"),
+            json!("300"),
+            json!("&"),
+            json!("echo"),
+            json!("$!"),
+            json!(">"),
+            json!(pid_file.to_string_lossy()),
+            json!(";"),
+            json!("node"),
+        ];
+        args.extend(
+            registration["configuration"]["args"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .cloned(),
+        );
+        registration["configuration"] = json!({ "command": "env", "args": args });
+        let response = server.rpc("registry/install", Some(registration)).await;
+        assert!(response["error"].is_null(), "{}", response);
+        let child: u32 = std::fs::read_to_string(&pid_file)
+            .expect("the child should have been started")
+            .trim()
+            .parse()
+            .unwrap();
+        assert!(is_running(child));
+
+        server
+            .core
+            .uninstall_server(ToolUninstallRequest {
+                server_id: "hello_world".to_string(),
+            })
+            .await
+            .unwrap();
+
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(2);
+        while is_running(child) {
+            assert!(
+                tokio::time::Instant::now() < deadline,
+                "the child of the server survived its uninstall"
+            );
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    }
+}
@@ -56,8 +56,12 @@ mod tests {
                     script_path.clone(),
                 ]),
                 env: None,
+                ..Default::default()
             }),
             distribution: None,
+            provenance: None,
+            authentication: None,
+            enabled: None,
         };
 
         eprintln!(
@@ -67,7 +71,7 @@ mod tests {
 
         // Register tool
         let response = mcp_core.register_server(registration_request).await?;
-        let tool_id = response.tool_id.ok_or("No tool ID returned")?;
+        let tool_id = response.server_id.ok_or("No tool ID returned")?;
 
         eprintln!("Received tool_id from registration: {}", tool_id);
 
@@ -258,8 +262,12 @@ mod tests {
                     script_path.clone(),
                 ]),
                 env: None,
+                ..Default::default()
             }),
             distribution: None,
+            provenance: None,
+            authentication: None,
+            enabled: None,
         };
 
         eprintln!(
@@ -269,7 +277,7 @@ mod tests {
 
         // Register tool
         let response = mcp_core.register_server(registration_request).await?;
-        let tool_id = response.tool_id.ok_or("No tool ID returned")?;
+        let tool_id = response.server_id.ok_or("No tool ID returned")?;
 
         eprintln!("Received tool_id from registration: {}", tool_id);
 
@@ -466,13 +474,17 @@ mod tests {
                     script_path.clone(),
                 ]),
                 env: None,
+                ..Default::default()
             }),
             distribution: None,
+            provenance: None,
+            authentication: None,
+            enabled: None,
         };
 
         // Register the server
         let response = mcp_core.register_server(registration_request).await?;
-        let server_id = response.tool_id.ok_or("No server ID returned")?;
+        let server_id = response.server_id.ok_or("No server ID returned")?;
 
         // Verify server is running by executing a command
         let request = ToolExecutionRequest {
@@ -574,4 +586,117 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_concurrent_restarts_leave_single_process() -> Result<(), String> {
+        use tempfile::tempdir;
+
+        init_logging();
+        let temp_dir = tempdir().map_err(|e| format!("Failed to create temp dir: {}", e))?;
+        let db_path = temp_dir.path().join("test_concurrent_restarts.db");
+
+        let mcp_core = MCPCore::new_with_port(
+            db_path,
+            path::absolute("mcp-proxy-server").unwrap(),
+            3004,
+            "mcp-core-test".to_string(),
+        );
+        mcp_core.init().await.map_err(|e| {
+            let message = format!("error initializing mcp core: {:?}", e);
+            eprintln!("{}", message);
+            message
+        })?;
+
+        let current_dir = std::env::current_dir().map_err(|e| e.to_string())?;
+        let script_path = current_dir
+            .join("../../dist/apps/mcp-server-hello-world/index.js")
+            .to_string_lossy()
+            .into_owned();
+        // Extra argument ignored by the server, used to find its processes
+        let marker = "restart-stress-marker";
+
+        let registration_request = ServerRegistrationRequest {
+            server_id: "restart_stress".to_string(),
+            server_name: "Restart Stress".to_string(),
+            description: "A server restarted concurrently".to_string(),
+            tools_type: "node".to_string(),
+            configuration: Some(ServerConfiguration {
+                command: Some("node".to_string()),
+                args: Some(vec![
+                    "--experimental-modules".to_string(),
+                    "--no-warnings".to_string(),
+                    script_path.clone(),
+                    marker.to_string(),
+                ]),
+                env: None,
+                ..Default::default()
+            }),
+            distribution: None,
+            provenance: None,
+            authentication: None,
+            enabled: None,
+        };
+        let response = mcp_core.register_server(registration_request).await?;
+        let server_id = response.server_id.ok_or("No server ID returned")?;
+
+        // Fire 10 restarts at once
+        let restarts = (0..10).map(|_| mcp_core.restart_server_command(server_id.clone(), false));
+        for result in futures::future::join_all(restarts).await {
+            let response = result?;
+            assert!(response.success, "Restart failed: {}", response.message);
+        }
+
+        // Exactly one client is registered and it answers
+        {
+            let mcp_state = mcp_core.mcp_state.read().await;
+            let clients = mcp_state.mcp_clients.read().await;
+            assert_eq!(
+                clients.keys().filter(|id| **id == server_id).count(),
+                1,
+                "Expected a single client for {}",
+                server_id
+            );
+            let tools = mcp_state.server_tools.read().await;
+            assert!(
+                tools.get(&server_id).is_some_and(|tools| !tools.is_empty()),
+                "Tools of {} should be discovered",
+                server_id
+            );
+        }
+        let request = ToolExecutionRequest {
+            tool_id: format!("{}:{}", server_id, "hello_world"),
+            parameters: json!({}),
+        };
+        let result = mcp_core.execute_proxy_tool(request).await?;
+        assert!(result.success, "Server did not answer after the restarts");
+
+        // Closed processes may take a moment to exit
+        let count_processes = || {
+            let mut system = sysinfo::System::new_all();
+            system.refresh_all();
+            system
+                .processes()
+                .values()
+                .filter(|process| process.name().to_string_lossy().starts_with("node"))
+                .filter(|process| {
+                    process
+                        .cmd()
+                        .iter()
+                        .any(|arg| arg.to_string_lossy().contains(marker))
+                })
+                .count()
+        };
+        let start_time = std::time::Instant::now();
+        let mut processes = count_processes();
+        while processes != 1 && start_time.elapsed() < std::time::Duration::from_secs(10) {
+            tokio::time::sleep(std::time::Duration::from_millis(250)).await;
+            processes = count_processes();
+        }
+        assert_eq!(processes, 1, "Expected exactly one server process");
+
+        // Cleanup
+        let _ = mcp_core.kill_all_processes().await;
+
+        Ok(())
+    }
 }
@@ -1,11 +1,96 @@
+mod common;
+
 mod integration {
+    #[cfg(feature = "http-server")]
+    mod http_harness;
+    #[cfg(feature = "http-server")]
+    mod http_jsonrpc_tests;
     mod mcp_server_mockup_test;
 }
 
 mod unit {
+    mod approvals_tests;
+    mod arch_tests;
+    mod authentication_env_tests;
+    mod bulk_ops_tests;
+    mod cancellation_tests;
+    mod capabilities_tests;
+    mod config_check_tests;
+    mod config_file_tests;
+    mod config_history_tests;
+    mod dashboard_tests;
+    mod db_gc_tests;
     mod db_manager_tests;
+    mod db_state_tests;
+    mod describe_tool_tests;
+    mod description_tests;
+    mod diagnose_tests;
+    mod diagnostics_tests;
+    mod env_key_tests;
+    mod env_template_tests;
+    mod env_value_tests;
+    mod event_bus_tests;
+    mod facade_tests;
+    mod fan_out_tests;
+    mod footprint_tests;
     mod github_tests;
+    mod health_tests;
+    mod hooks_tests;
+    mod identity_tests;
+    mod input_schema_tests;
+    mod instance_lock_tests;
+    mod json_message_codec_tests;
+    mod launch_plan_tests;
+    mod lazy_start_tests;
+    mod legacy_migration_tests;
+    #[cfg(feature = "http-server")]
+    mod legacy_server_id_tests;
+    mod lifecycle_tests;
+    mod lock_debug_tests;
+    #[cfg(feature = "http-server")]
+    mod mcp_core_builder_tests;
     mod mcp_core_runtimes_ext;
+    mod metrics_tests;
+    mod operations_tests;
+    mod orphan_process_tests;
+    mod output_schema_tests;
+    mod payload_tests;
+    mod process_env_tests;
+    mod provenance_tests;
+    mod rate_limits_tests;
+    mod read_only_tests;
+    mod reconnect_tests;
+    mod recordings_tests;
+    mod registration_tests;
+    mod registry_cache_tests;
+    mod registry_lookup_tests;
+    mod registry_sources_tests;
+    mod relocation_tests;
+    mod restart_gate_tests;
+    mod run_state_tests;
+    mod sandbox_tests;
+    mod schedules_tests;
+    mod selftest_tests;
+    mod server_id_tests;
+    mod server_info_tests;
+    mod server_logs_tests;
+    mod server_notifications_tests;
     mod server_tool_info_db_tests;
     mod server_tool_info_deserialization_tests;
+    mod server_tool_info_spec_tests;
+    mod sessions_tests;
+    mod shutdown_tests;
+    mod spawned_process_tests;
+    mod start_failures_tests;
+    mod startup_capture_tests;
+    mod storage_tests;
+    mod tool_annotations_tests;
+    mod tool_id_tests;
+    mod tool_limits_tests;
+    mod tools_pagination_tests;
+    mod tools_snapshot_tests;
+    mod undo_tests;
+    mod unknown_fields_tests;
+    mod webhooks_tests;
+    mod wire_format_tests;
 }
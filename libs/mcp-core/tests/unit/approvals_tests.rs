@@ -0,0 +1,159 @@
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use mcp_core::{
+        approvals::{ApprovalRegistry, ApprovalRequest},
+        database::db_manager::DBManager,
+        events::{DockmasterEvent, EventBus},
+        models::types::{ActionPolicy, AuditDecision, AuditEntry, GatedAction},
+        operations::{OperationRegistry, OperationState},
+    };
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    fn request(id: &str) -> ApprovalRequest {
+        ApprovalRequest::new(
+            id.to_string(),
+            GatedAction::Uninstall,
+            Some("fetch".to_string()),
+            Some("claude-ai 0.1.0".to_string()),
+            "Uninstall the server 'fetch'".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_policies() {
+        assert_eq!(GatedAction::Register.default_policy(), ActionPolicy::Allow);
+        assert_eq!(
+            GatedAction::Uninstall.default_policy(),
+            ActionPolicy::Confirm
+        );
+        assert_eq!(ActionPolicy::from_stored("deny"), Some(ActionPolicy::Deny));
+        assert_eq!(ActionPolicy::from_stored("ask"), None);
+
+        let policy: ActionPolicy = serde_json::from_value(json!("confirm")).unwrap();
+        assert_eq!(policy, ActionPolicy::Confirm);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_approval() {
+        let bus = EventBus::new();
+        let mut events = bus.subscribe();
+        let approvals = ApprovalRegistry::new(bus);
+
+        let decision = approvals.request(request("op-1"));
+        assert_eq!(approvals.pending().len(), 1);
+        assert!(matches!(
+            events.recv().await.unwrap().event,
+            DockmasterEvent::ApprovalRequested(_)
+        ));
+
+        let resolved = approvals.resolve("op-1", true).unwrap();
+        assert_eq!(resolved.server_id.as_deref(), Some("fetch"));
+        assert!(decision.await.unwrap());
+        assert!(approvals.pending().is_empty());
+        assert!(matches!(
+            events.recv().await.unwrap().event,
+            DockmasterEvent::ApprovalResolved(ref resolution) if resolution.approved
+        ));
+
+        // Answered requests can't be answered again
+        assert!(approvals.resolve("op-1", false).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_withdrawn_approval() {
+        let approvals = ApprovalRegistry::new(EventBus::new());
+        let decision = approvals.request(request("op-2"));
+        drop(decision);
+
+        assert!(approvals.pending().is_empty());
+        assert!(approvals.resolve("op-2", true).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_operation_awaits_approval() {
+        let bus = EventBus::new();
+        let approvals = ApprovalRegistry::new(bus.clone());
+        let operations = OperationRegistry::new(bus);
+
+        let decision = approvals.request(request("op-3"));
+        operations.spawn_with_id(
+            "op-3".to_string(),
+            "uninstall",
+            Some("fetch".to_string()),
+            true,
+            |handle| async move {
+                handle.set_awaiting_approval(true);
+                if !decision.await.unwrap_or(false) {
+                    return Err("Rejected by the user".to_string());
+                }
+                handle.set_awaiting_approval(false);
+                Ok(json!({ "success": true }))
+            },
+        );
+
+        for _ in 0..100 {
+            if operations.get("op-3").unwrap().state == OperationState::AwaitingApproval {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let operation = operations.get("op-3").unwrap();
+        assert_eq!(operation.state, OperationState::AwaitingApproval);
+        assert_eq!(
+            serde_json::to_value(&operation).unwrap()["state"],
+            json!("awaiting_approval")
+        );
+
+        approvals.resolve("op-3", false).unwrap();
+        for _ in 0..100 {
+            if operations.get("op-3").unwrap().state.is_finished() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let operation = operations.get("op-3").unwrap();
+        assert_eq!(operation.state, OperationState::Failed);
+        assert_eq!(operation.error.as_deref(), Some("Rejected by the user"));
+    }
+
+    #[test]
+    fn test_audit_log_round_trip() {
+        let temp_dir = tempdir().expect("Failed to create temp directory");
+        let mut db = DBManager::with_path(temp_dir.path().join("mcp-dockmaster.db"))
+            .expect("Failed to create database");
+        db.apply_migrations().expect("Failed to apply migrations");
+
+        db.append_audit_entry(&AuditEntry::new(
+            GatedAction::Register,
+            Some("fetch".to_string()),
+            None,
+            AuditDecision::Allowed,
+        ))
+        .unwrap();
+        db.append_audit_entry(
+            &AuditEntry::new(
+                GatedAction::Uninstall,
+                Some("fetch".to_string()),
+                Some("cursor".to_string()),
+                AuditDecision::Rejected,
+            )
+            .with_detail("Uninstall the server 'fetch'"),
+        )
+        .unwrap();
+
+        let log = db.get_audit_log(10).unwrap();
+        assert_eq!(log.len(), 2);
+        assert_eq!(log[0].decision, AuditDecision::Rejected);
+        assert_eq!(log[0].client.as_deref(), Some("cursor"));
+        assert_eq!(log[1].action, GatedAction::Register);
+        assert!(log[0].id > log[1].id);
+        assert_eq!(db.get_audit_log(1).unwrap().len(), 1);
+
+        // Resetting the servers keeps the record of past decisions
+        db.clear_database().unwrap();
+        assert_eq!(db.get_audit_log(10).unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,154 @@
+#[cfg(test)]
+mod tests {
+    use std::ffi::OsStr;
+
+    use mcp_core::{
+        arch::{
+            executable_archs, resolve_runtime, runs_translated, select_docker_platform, Arch,
+            LaunchArch,
+        },
+        mcp_state::launch_plan::LaunchPlan,
+        models::types::ServerDefinition,
+    };
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    fn elf(machine: u16) -> Vec<u8> {
+        let mut header = vec![0x7f, b'E', b'L', b'F', 2, 1];
+        header.resize(18, 0);
+        header.extend(machine.to_le_bytes());
+        header
+    }
+
+    fn docker_server(args: serde_json::Value, platforms: serde_json::Value) -> ServerDefinition {
+        serde_json::from_value(json!({
+            "name": "Fetch",
+            "description": "",
+            "enabled": true,
+            "tools_type": "docker",
+            "configuration": {
+                "command": "docker",
+                "args": args,
+                "platforms": platforms
+            },
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_executable_formats() {
+        assert_eq!(executable_archs(&elf(62)), vec![Arch::X86_64]);
+        assert_eq!(executable_archs(&elf(183)), vec![Arch::Arm64]);
+
+        let mach_o = [0xcf, 0xfa, 0xed, 0xfe, 0x0c, 0x00, 0x00, 0x01];
+        assert_eq!(executable_archs(&mach_o), vec![Arch::Arm64]);
+
+        // Universal binary with both architectures
+        let mut fat = vec![0xca, 0xfe, 0xba, 0xbe, 0, 0, 0, 2];
+        for cpu_type in [0x0100_0007u32, 0x0100_000c] {
+            let mut entry = cpu_type.to_be_bytes().to_vec();
+            entry.resize(20, 0);
+            fat.extend(entry);
+        }
+        assert_eq!(executable_archs(&fat), vec![Arch::X86_64, Arch::Arm64]);
+
+        let mut pe = vec![b'M', b'Z'];
+        pe.resize(0x3c, 0);
+        pe.extend(0x40u32.to_le_bytes());
+        pe.extend(b"PE\0\0");
+        pe.extend(0xaa64u16.to_le_bytes());
+        assert_eq!(executable_archs(&pe), vec![Arch::Arm64]);
+
+        // A Java class file shares the magic of universal binaries
+        assert!(executable_archs(&[0xca, 0xfe, 0xba, 0xbe, 0, 0, 0, 52]).is_empty());
+        assert!(executable_archs(b"#!/bin/sh").is_empty());
+    }
+
+    #[test]
+    fn test_scripts_resolve_to_their_interpreter() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("node"), elf(62)).unwrap();
+        std::fs::write(
+            dir.path().join("npx"),
+            "#!/usr/bin/env -S NODE_OPTIONS=--no-warnings node\nrequire('npx')\n",
+        )
+        .unwrap();
+        let path = dir.path().as_os_str();
+
+        let (runtime, archs) = resolve_runtime("npx", Some(path)).unwrap();
+        assert_eq!(runtime, dir.path().join("node"));
+        assert_eq!(archs, vec![Arch::X86_64]);
+        assert!(resolve_runtime("uvx", Some(path)).is_none());
+
+        let arch = LaunchArch::for_command(Some(Arch::Arm64), "npx", Some(path));
+        assert_eq!(arch.runtime_archs, vec![Arch::X86_64]);
+        assert_eq!(arch.translated, cfg!(target_os = "macos"));
+        assert_eq!(arch.warning().is_some(), arch.translated);
+        let native = LaunchArch::for_command(Some(Arch::X86_64), "npx", Some(OsStr::new("")));
+        assert!(native.runtime.is_none() && !native.translated);
+    }
+
+    #[test]
+    fn test_translation() {
+        assert!(runs_translated(Some(Arch::Arm64), &[Arch::X86_64]));
+        assert!(!runs_translated(
+            Some(Arch::Arm64),
+            &[Arch::X86_64, Arch::Arm64]
+        ));
+        assert!(!runs_translated(Some(Arch::Arm64), &[]));
+        assert!(!runs_translated(Some(Arch::X86_64), &[Arch::Arm64]));
+    }
+
+    #[test]
+    fn test_docker_platform_is_chosen() {
+        let declared = vec!["linux/amd64".to_string(), "linux/arm64/v8".to_string()];
+        assert_eq!(
+            select_docker_platform(&declared, Arch::Arm64).map(String::as_str),
+            Some("linux/arm64/v8")
+        );
+
+        let server = docker_server(json!(["run", "-i", "--rm", "mcp/fetch"]), json!(declared));
+        let plan =
+            LaunchPlan::build_for_host("fetch", &server, Vec::new(), Some(Arch::X86_64)).unwrap();
+        assert_eq!(plan.arch.docker_platform.as_deref(), Some("linux/amd64"));
+        assert_eq!(
+            plan.args,
+            vec![
+                "run",
+                "--name",
+                "mcp-dockmaster-fetch",
+                "--platform=linux/amd64",
+                "-i",
+                "--rm",
+                "mcp/fetch"
+            ]
+        );
+        let preview = serde_json::to_value(&plan).unwrap();
+        assert_eq!(preview["arch"]["host"], "x86_64");
+        assert_eq!(preview["arch"]["docker_platform"], "linux/amd64");
+    }
+
+    #[test]
+    fn test_docker_platform_mismatch_fails_early() {
+        let server = docker_server(json!(["run", "-i", "mcp/fetch"]), json!(["linux/amd64"]));
+        let error = LaunchPlan::build_for_host("fetch", &server, Vec::new(), Some(Arch::Arm64))
+            .unwrap_err();
+        assert!(error.contains("only built for linux/amd64"));
+        assert!(error.contains("linux/arm64"));
+
+        // A platform chosen in the arguments is left alone
+        let server = docker_server(
+            json!(["run", "--platform", "linux/amd64", "mcp/fetch"]),
+            json!(["linux/amd64"]),
+        );
+        let plan =
+            LaunchPlan::build_for_host("fetch", &server, Vec::new(), Some(Arch::Arm64)).unwrap();
+        assert_eq!(plan.arch.docker_platform, None);
+
+        // Nothing declared, nothing passed
+        let server = docker_server(json!(["run", "mcp/fetch"]), json!(null));
+        let plan =
+            LaunchPlan::build_for_host("fetch", &server, Vec::new(), Some(Arch::Arm64)).unwrap();
+        assert!(!plan.args.iter().any(|arg| arg.starts_with("--platform")));
+    }
+}
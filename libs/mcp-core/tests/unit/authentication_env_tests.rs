@@ -0,0 +1,123 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use mcp_core::{
+        core::{mcp_core_builder::MCPCoreBuilder, mcp_core_proxy_ext::McpCoreProxyExt},
+        models::{
+            types::{EnvValue, ServerRegistrationRequest},
+            unknown_fields::KnownFields,
+        },
+    };
+    use serde_json::{json, Value};
+    use tempfile::tempdir;
+
+    use crate::common::{db, FailingLauncher};
+
+    /// A registration as older builds of the app sent it
+    fn old_format(authentication: Value) -> Value {
+        json!({
+            "server_id": "weather",
+            "server_name": "Weather",
+            "description": "",
+            "tools_type": "node",
+            "configuration": {
+                "command": "npx",
+                "args": ["-y", "weather-mcp"],
+                "env": { "DEBUG": "1" }
+            },
+            "authentication": authentication
+        })
+    }
+
+    fn request(payload: Value) -> ServerRegistrationRequest {
+        serde_json::from_value(payload).unwrap()
+    }
+
+    #[test]
+    fn test_authentication_env_moves_to_configuration() {
+        let mut request = request(old_format(json!({
+            "env": {
+                "api_key": { "description": "Weather API key", "default": "k3y", "required": true },
+                "DEBUG": "0"
+            },
+            "token_url": "https://example.com/token"
+        })));
+        let warnings = request.resolve_env().unwrap();
+        assert_eq!(
+            warnings,
+            vec!["Field 'authentication.token_url' was ignored"]
+        );
+        assert!(request.authentication.is_none());
+
+        let env = request.configuration.unwrap().env.unwrap();
+        assert_eq!(env.len(), 2);
+        assert!(env["API_KEY"].required);
+        assert_eq!(env["API_KEY"].default, Some(EnvValue::from("k3y")));
+        // The configuration wins over the authentication
+        assert_eq!(env["DEBUG"].default, Some(EnvValue::from("1")));
+    }
+
+    #[test]
+    fn test_invalid_authentication_is_refused() {
+        assert!(request(old_format(Value::Null))
+            .resolve_env()
+            .unwrap()
+            .is_empty());
+        assert!(request(old_format(json!("token"))).resolve_env().is_err());
+        assert!(request(old_format(json!({ "env": ["API_KEY"] })))
+            .resolve_env()
+            .is_err());
+
+        let error = request(old_format(
+            json!({ "env": { "api_key": "a", "Api_Key": "b" } }),
+        ))
+        .resolve_env()
+        .unwrap_err();
+        assert!(error.contains("Api_Key, api_key"), "{}", error);
+    }
+
+    #[test]
+    fn test_authentication_env_templates_are_checked() {
+        let payload = old_format(json!({
+            "env": { "API_KEY": { "descripton": "Weather API key", "default": "" } }
+        }));
+        assert_eq!(
+            ServerRegistrationRequest::unknown_fields(&payload),
+            vec!["authentication.env.API_KEY.descripton"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_old_format_registration_keeps_its_env() {
+        let dir = tempdir().unwrap();
+        let db = db(&dir);
+        let core = MCPCoreBuilder::new(db)
+            .process_launcher(Arc::new(FailingLauncher::new()))
+            .build();
+
+        let response = core
+            .register_server(request(old_format(json!({
+                "env": { "api_key": { "description": "Weather API key", "default": "k3y" } },
+                "scopes": ["read"]
+            }))))
+            .await
+            .unwrap();
+        assert!(response.success);
+        assert!(response
+            .warnings
+            .contains(&"Field 'authentication.scopes' was ignored".to_string()));
+
+        let saved = core
+            .tool_registry
+            .read()
+            .await
+            .get_server("weather")
+            .unwrap();
+        let env = saved.configuration.unwrap().env.unwrap();
+        let mut keys: Vec<&String> = env.keys().collect();
+        keys.sort();
+        assert_eq!(keys, vec!["API_KEY", "DEBUG"]);
+        assert_eq!(env["API_KEY"].default, Some(EnvValue::from("k3y")));
+    }
+}
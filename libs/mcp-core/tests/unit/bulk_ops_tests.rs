@@ -0,0 +1,238 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use mcp_core::{
+        core::{
+            mcp_core_builder::MCPCoreBuilder, mcp_core_bulk_ext::McpCoreBulkExt,
+            mcp_core_proxy_ext::McpCoreProxyExt,
+        },
+        events::DockmasterEvent,
+        models::types::{ServerDefinition, ServerStatus},
+    };
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    use crate::common::{db, FailingLauncher};
+
+    /// MCP server answering the handshake, without tools
+    #[cfg(not(windows))]
+    const EMPTY_SERVER: &str = r#"
+while read -r line; do
+  id=$(echo "$line" | sed -n 's/.*"id":\([0-9]*\).*/\1/p')
+  [ -z "$id" ] && continue
+  case "$line" in
+    *'"initialize"'*)
+      result='{"protocolVersion":"2024-11-05","capabilities":{"tools":{}},"serverInfo":{"name":"empty","version":"1.0.0"}}' ;;
+    *'"tools/list"'*) result='{"tools":[]}' ;;
+    *) result='{}' ;;
+  esac
+  echo "{\"jsonrpc\":\"2.0\",\"id\":$id,\"result\":$result}"
+done
+"#;
+
+    fn server(enabled: bool) -> ServerDefinition {
+        serde_json::from_value(json!({
+            "name": "Echo",
+            "description": "",
+            "enabled": enabled,
+            "tools_type": "node",
+            "configuration": { "command": "node", "args": ["echo.js"] }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_set_servers_enabled_in_one_transaction() {
+        let dir = tempdir().unwrap();
+        let db = db(&dir);
+        for id in ["a", "b", "c"] {
+            db.save_server(id, &server(true)).unwrap();
+        }
+
+        db.set_servers_enabled(&["a".to_string(), "b".to_string()], false)
+            .unwrap();
+
+        assert!(!db.get_server("a").unwrap().enabled);
+        assert!(!db.get_server("b").unwrap().enabled);
+        assert!(db.get_server("c").unwrap().enabled);
+        let run_states = db.get_server_run_states().unwrap();
+        assert_eq!(run_states.len(), 2);
+        assert!(!run_states["a"] && !run_states["b"]);
+    }
+
+    #[tokio::test]
+    async fn test_bulk_disable_persists_every_server() {
+        let dir = tempdir().unwrap();
+        let db = db(&dir);
+        for id in ["a", "b"] {
+            db.save_server(id, &server(true)).unwrap();
+        }
+        let core = MCPCoreBuilder::new(db)
+            .process_launcher(Arc::new(FailingLauncher::new()))
+            .build();
+        let (_, mut events) = core.event_bus.subscribe_with_history(|_| false);
+
+        let ids = vec!["a".to_string(), "missing".to_string(), "b".to_string()];
+        let response = core.set_servers_enabled(ids, false).await.unwrap();
+
+        let results: Vec<(&str, bool)> = response
+            .results
+            .iter()
+            .map(|result| (result.server_id.as_str(), result.success))
+            .collect();
+        assert_eq!(results, vec![("a", true), ("b", true), ("missing", false)]);
+        assert_eq!(response.results[2].message, "Server 'missing' not found");
+
+        let registry = core.tool_registry.read().await;
+        for id in ["a", "b"] {
+            assert!(!registry.get_server(id).unwrap().enabled);
+            assert!(!registry.desired_running(id));
+        }
+        drop(registry);
+
+        let mut updates = Vec::new();
+        while let Ok(envelope) = events.try_recv() {
+            if let DockmasterEvent::ToolsUpdated(update) = envelope.event {
+                updates.push(update.server_ids);
+            }
+        }
+        assert_eq!(updates, vec![vec!["a".to_string(), "b".to_string()]]);
+    }
+
+    #[tokio::test]
+    async fn test_bulk_enable_starts_every_server() {
+        let dir = tempdir().unwrap();
+        let db = db(&dir);
+        for id in ["a", "b"] {
+            db.save_server(id, &server(false)).unwrap();
+            db.save_server_run_state(id, false).unwrap();
+        }
+        let launcher = Arc::new(FailingLauncher::new());
+        let core = MCPCoreBuilder::new(db)
+            .process_launcher(launcher.clone())
+            .build();
+
+        let ids = vec!["a".to_string(), "b".to_string()];
+        let response = core.set_servers_enabled(ids, true).await.unwrap();
+
+        // Saved even though the processes failed to start
+        assert!(response.results.iter().all(|result| !result.success));
+        let registry = core.tool_registry.read().await;
+        for id in ["a", "b"] {
+            assert!(registry.get_server(id).unwrap().enabled);
+            assert!(registry.desired_running(id));
+        }
+        let mut launched = launcher.launched();
+        launched.sort();
+        assert_eq!(launched, vec!["a", "b"]);
+    }
+
+    #[tokio::test]
+    async fn test_restart_all_skips_stopped_and_disabled_servers() {
+        let dir = tempdir().unwrap();
+        let db = db(&dir);
+        for (id, enabled, desired_running) in [
+            ("running", true, true),
+            ("other", true, true),
+            ("stopped", true, false),
+            ("disabled", false, true),
+        ] {
+            db.save_server(id, &server(enabled)).unwrap();
+            db.save_server_run_state(id, desired_running).unwrap();
+        }
+        let launcher = Arc::new(FailingLauncher::new());
+        let core = MCPCoreBuilder::new(db)
+            .process_launcher(launcher.clone())
+            .build();
+
+        let response = core.restart_servers(None).await.unwrap();
+
+        let ids: Vec<&str> = response
+            .results
+            .iter()
+            .map(|result| result.server_id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["other", "running"]);
+        let mut launched = launcher.launched();
+        launched.sort();
+        assert_eq!(launched, vec!["other", "running"]);
+    }
+
+    #[tokio::test]
+    async fn test_restart_all_servers() {
+        let dir = tempdir().unwrap();
+        let db = db(&dir);
+        for (id, enabled) in [("running", true), ("disabled", false)] {
+            db.save_server(id, &server(enabled)).unwrap();
+        }
+        let launcher = Arc::new(FailingLauncher::new());
+        let core = MCPCoreBuilder::new(db)
+            .process_launcher(launcher.clone())
+            .build();
+
+        let response = core.restart_all_servers().await.unwrap();
+
+        assert_eq!(response.results.len(), 1);
+        assert_eq!(response.results[0].server_id, "running");
+        assert_eq!(launcher.launched(), vec!["running"]);
+    }
+
+    #[tokio::test]
+    async fn test_restart_rejects_disabled_servers() {
+        let dir = tempdir().unwrap();
+        let db = db(&dir);
+        db.save_server("disabled", &server(false)).unwrap();
+        let launcher = Arc::new(FailingLauncher::new());
+        let core = MCPCoreBuilder::new(db)
+            .process_launcher(launcher.clone())
+            .build();
+
+        let response = core
+            .restart_servers(Some(vec!["disabled".to_string()]))
+            .await
+            .unwrap();
+
+        assert!(!response.results[0].success);
+        assert_eq!(response.results[0].message, "Server 'disabled' is disabled");
+        assert!(launcher.launched().is_empty());
+    }
+
+    #[cfg(not(windows))]
+    #[tokio::test]
+    async fn test_restart_all_restarts_the_servers_that_start() {
+        let dir = tempdir().unwrap();
+        let script = dir.path().join("server.sh");
+        std::fs::write(&script, EMPTY_SERVER).unwrap();
+        let db = db(&dir);
+        for (id, args) in [
+            ("broken", json!(["-c", "exit 1"])),
+            ("live", json!([script.display().to_string()])),
+        ] {
+            let server: ServerDefinition = serde_json::from_value(json!({
+                "name": id,
+                "description": "",
+                "enabled": true,
+                "tools_type": "node",
+                "configuration": { "command": "sh", "args": args }
+            }))
+            .unwrap();
+            db.save_server(id, &server).unwrap();
+            db.save_server_run_state(id, true).unwrap();
+        }
+        let core = MCPCoreBuilder::new(db).build();
+
+        let response = core.restart_servers(None).await.unwrap();
+
+        let results: Vec<(&str, bool)> = response
+            .results
+            .iter()
+            .map(|result| (result.server_id.as_str(), result.success))
+            .collect();
+        assert_eq!(results, vec![("broken", false), ("live", true)]);
+        let status = core.mcp_state.read().await.server_status("live").await;
+        assert!(matches!(status, ServerStatus::Running), "{status:?}");
+
+        core.kill_all_processes().await.unwrap();
+    }
+}
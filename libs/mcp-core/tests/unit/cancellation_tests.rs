@@ -0,0 +1,164 @@
+#[cfg(test)]
+mod tests {
+    use mcp_core::cancellation::{call_id, cancelled_request_id, PendingCalls};
+    use serde_json::json;
+
+    #[test]
+    fn test_call_ids() {
+        assert_eq!(call_id("session", &json!(7)), "session/7");
+        assert_eq!(call_id("session", &json!("seven")), "session/\"seven\"");
+
+        assert_eq!(
+            cancelled_request_id(&json!({ "requestId": 7 })),
+            Some(&json!(7))
+        );
+        assert_eq!(cancelled_request_id(&json!({ "requestId": null })), None);
+        assert_eq!(cancelled_request_id(&json!({})), None);
+    }
+
+    #[tokio::test]
+    async fn test_pending_call_is_cancelled() {
+        let calls = PendingCalls::new();
+        let mut pending = calls.register("1").unwrap();
+        assert!(calls.is_pending("1"));
+        assert!(!calls.cancel("2"));
+
+        assert!(calls.cancel("1"));
+        pending.cancelled().await;
+        assert!(!calls.is_pending("1"));
+        // Only once
+        assert!(!calls.cancel("1"));
+    }
+
+    #[test]
+    fn test_finished_call_is_not_cancelled() {
+        let calls = PendingCalls::new();
+        drop(calls.register("1").unwrap());
+        assert!(!calls.is_pending("1"));
+        assert!(!calls.cancel("1"));
+    }
+
+    #[test]
+    fn test_running_call_id_is_not_reused() {
+        let calls = PendingCalls::new();
+        let first = calls.register("1").unwrap();
+        assert!(calls.register("1").is_err());
+
+        // Once cancelled, the id is free, and the first call doesn't untrack the next
+        assert!(calls.cancel("1"));
+        let second = calls.register("1").unwrap();
+        drop(first);
+        assert!(calls.is_pending("1"));
+        drop(second);
+        assert!(!calls.is_pending("1"));
+    }
+
+    #[cfg(not(windows))]
+    mod hanging_server {
+        use std::time::Duration;
+
+        use mcp_core::{
+            cancellation::CANCELLED_ERROR,
+            core::{mcp_core_builder::MCPCoreBuilder, mcp_core_proxy_ext::McpCoreProxyExt},
+            database::db_manager::DBManager,
+            models::types::{ServerConfiguration, ServerRegistrationRequest, ToolExecutionRequest},
+        };
+        use serde_json::{json, Value};
+        use tempfile::tempdir;
+
+        /// MCP server with one tool that never answers its calls, appending the
+        /// cancellations it gets to the file named by its first argument
+        const HANGING_SERVER: &str = r#"
+while read -r line; do
+  case "$line" in
+    *'"notifications/cancelled"'*) echo "$line" >> "$1" ;;
+  esac
+  id=$(echo "$line" | sed -n 's/.*"id":\([0-9]*\).*/\1/p')
+  [ -z "$id" ] && continue
+  case "$line" in
+    *'"initialize"'*)
+      result='{"protocolVersion":"2024-11-05","capabilities":{"tools":{}},"serverInfo":{"name":"hanging","version":"1.0.0"}}' ;;
+    *'"tools/list"'*)
+      result='{"tools":[{"name":"wait","description":"Waits forever","inputSchema":{"type":"object"}}]}' ;;
+    *'"tools/call"'*) continue ;;
+    *) result='{}' ;;
+  esac
+  echo "{\"jsonrpc\":\"2.0\",\"id\":$id,\"result\":$result}"
+done
+"#;
+
+        #[tokio::test]
+        async fn test_running_call_is_cancelled() {
+            let dir = tempdir().unwrap();
+            let script = dir.path().join("server.sh");
+            std::fs::write(&script, HANGING_SERVER).unwrap();
+            let cancellations = dir.path().join("cancellations");
+            let db = DBManager::with_path(dir.path().join("mcp-dockmaster.db")).unwrap();
+            db.apply_migrations().unwrap();
+            let core = MCPCoreBuilder::new(db).build();
+            let response = core
+                .register_server(ServerRegistrationRequest {
+                    server_id: "hanging".to_string(),
+                    server_name: "Hanging".to_string(),
+                    description: String::new(),
+                    tools_type: "node".to_string(),
+                    configuration: Some(ServerConfiguration {
+                        command: Some("sh".to_string()),
+                        args: Some(vec![
+                            script.display().to_string(),
+                            cancellations.display().to_string(),
+                        ]),
+                        ..Default::default()
+                    }),
+                    distribution: None,
+                    provenance: None,
+                    authentication: None,
+                    enabled: None,
+                })
+                .await
+                .unwrap();
+            assert!(response.success, "{}", response.message);
+
+            let call = {
+                let core = core.clone();
+                tokio::spawn(async move {
+                    let request = ToolExecutionRequest {
+                        tool_id: "hanging:wait".to_string(),
+                        parameters: json!({}),
+                    };
+                    core.execute_cancellable_proxy_tool(request, Some("call".to_string()))
+                        .await
+                })
+            };
+            while !core.pending_calls.is_pending("call") {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+            assert!(core.cancel_tool_call("call"));
+
+            let response = tokio::time::timeout(Duration::from_secs(5), call)
+                .await
+                .unwrap()
+                .unwrap()
+                .unwrap();
+            assert!(response.cancelled);
+            assert!(!response.success);
+            assert_eq!(response.error.as_deref(), Some(CANCELLED_ERROR));
+            assert!(!core.pending_calls.is_pending("call"));
+
+            // The server is told to stop working on the call
+            let mut cancelled = String::new();
+            for _ in 0..50 {
+                cancelled = std::fs::read_to_string(&cancellations).unwrap_or_default();
+                if !cancelled.is_empty() {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+            let cancelled: Value = serde_json::from_str(cancelled.trim()).unwrap();
+            assert_eq!(cancelled["method"], "notifications/cancelled");
+            assert!(cancelled["params"]["requestId"].is_u64());
+
+            core.kill_all_processes().await.unwrap();
+        }
+    }
+}
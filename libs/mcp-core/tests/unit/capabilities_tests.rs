@@ -0,0 +1,73 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use mcp_core::{
+        capabilities::{capabilities, is_supported_method, CORE_VERSION, METHODS},
+        read_only::READ_ONLY_METHODS,
+    };
+
+    const HANDLERS: &str = include_str!("../../src/http_server/handlers.rs");
+
+    /// Methods with an arm in the dispatch of `dispatch_mcp_request`
+    fn dispatched_methods() -> Vec<&'static str> {
+        let start = HANDLERS.find("async fn dispatch_mcp_request").unwrap();
+        let end = start + HANDLERS[start..].find("    match result {").unwrap();
+        HANDLERS[start..end]
+            .lines()
+            .filter_map(|line| line.strip_prefix("        \""))
+            .filter_map(|line| line.split_once("\" =>").map(|(method, _)| method))
+            .collect()
+    }
+
+    #[test]
+    fn test_every_handler_is_a_capability() {
+        let dispatched = dispatched_methods();
+        assert!(dispatched.contains(&"initialize"));
+        for method in &dispatched {
+            assert!(
+                is_supported_method(method),
+                "'{}' has a handler but is missing from capabilities::METHODS",
+                method
+            );
+        }
+        for method in METHODS {
+            assert!(
+                dispatched.contains(&method.name),
+                "'{}' is in capabilities::METHODS but has no handler",
+                method.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_methods() {
+        let mut names = HashSet::new();
+        for method in METHODS {
+            assert!(
+                names.insert(method.name),
+                "'{}' is listed twice",
+                method.name
+            );
+            assert!(method.schema_version > 0);
+        }
+        for method in READ_ONLY_METHODS {
+            assert!(
+                is_supported_method(method),
+                "'{}' is not dispatched",
+                method
+            );
+        }
+        assert!(!is_supported_method("tools/unknown"));
+    }
+
+    #[test]
+    fn test_capabilities_format() {
+        let value = serde_json::to_value(capabilities()).unwrap();
+        assert_eq!(value["version"], CORE_VERSION);
+        assert_eq!(value["methods"].as_array().unwrap().len(), METHODS.len());
+        assert_eq!(value["methods"][0]["name"], "initialize");
+        assert_eq!(value["methods"][0]["schema_version"], 1);
+        assert_eq!(value["features"]["registry"], cfg!(feature = "registry"));
+    }
+}
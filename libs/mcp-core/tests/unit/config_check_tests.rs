@@ -0,0 +1,211 @@
+#[cfg(test)]
+mod tests {
+    use mcp_core::{
+        mcp_state::launch_plan::LaunchPlan,
+        models::{
+            config_check::{
+                check_configuration_value, resolve_command, CONFIGURATION_FIELD,
+                REGISTRY_CONFIG_FIELD,
+            },
+            types::{ServerConfiguration, ServerDefinition},
+        },
+    };
+    use serde_json::{json, Value};
+
+    /// Field errors of a raw configuration, as `field message` strings
+    fn raw_errors(configuration: Option<Value>) -> Vec<String> {
+        match check_configuration_value(configuration.as_ref(), CONFIGURATION_FIELD) {
+            Ok(()) => Vec::new(),
+            Err(errors) => errors.fields().iter().map(|e| e.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_malformed_raw_configurations() {
+        let cases: Vec<(Option<Value>, Vec<&str>)> = vec![
+            (None, vec!["configuration is missing"]),
+            (Some(Value::Null), vec!["configuration is missing"]),
+            (
+                Some(json!("npx -y server")),
+                vec!["configuration must be an object"],
+            ),
+            (Some(json!({})), vec!["configuration.command is missing"]),
+            (
+                Some(json!({ "command": null, "args": ["-y", "server"] })),
+                vec!["configuration.command is missing"],
+            ),
+            (
+                Some(json!({ "args": ["-y", "server"] })),
+                vec!["configuration.command is missing"],
+            ),
+            (
+                Some(json!({ "command": "" })),
+                vec!["configuration.command is empty"],
+            ),
+            (
+                Some(json!({ "command": "   " })),
+                vec!["configuration.command is empty"],
+            ),
+            (
+                Some(json!({ "command": ["npx", "-y"] })),
+                vec!["configuration.command must be a string"],
+            ),
+            (
+                Some(json!({ "command": "npx", "args": "-y server" })),
+                vec!["configuration.args must be an array of strings"],
+            ),
+            (
+                Some(json!({ "command": "npx", "args": ["-y", 1, true] })),
+                vec![
+                    "configuration.args[1] must be a string",
+                    "configuration.args[2] must be a string",
+                ],
+            ),
+            (
+                Some(json!({ "command": "", "args": { "0": "-y" }, "env": ["TOKEN"] })),
+                vec![
+                    "configuration.command is empty",
+                    "configuration.args must be an array of strings",
+                    "configuration.env must be an object",
+                ],
+            ),
+            (Some(json!({ "command": "npx" })), vec![]),
+            (
+                Some(json!({ "command": "npx", "args": null, "env": { "TOKEN": "x" } })),
+                vec![],
+            ),
+        ];
+
+        for (configuration, expected) in cases {
+            assert_eq!(
+                raw_errors(configuration.clone()),
+                expected,
+                "{:?}",
+                configuration
+            );
+        }
+    }
+
+    #[test]
+    fn test_resolve_command() {
+        let configuration = |command: Option<&str>| -> ServerConfiguration {
+            serde_json::from_value(json!({ "command": command, "args": ["-y", "server"] })).unwrap()
+        };
+        let cases: Vec<(
+            Option<ServerConfiguration>,
+            Option<&str>,
+            &str,
+            Result<&str, &str>,
+        )> = vec![
+            (
+                Some(configuration(Some("npx"))),
+                None,
+                CONFIGURATION_FIELD,
+                Ok("npx"),
+            ),
+            (
+                Some(configuration(None)),
+                None,
+                CONFIGURATION_FIELD,
+                Err("configuration.command is missing"),
+            ),
+            (
+                Some(configuration(None)),
+                None,
+                REGISTRY_CONFIG_FIELD,
+                Err("config.command is missing"),
+            ),
+            (
+                Some(configuration(Some(" "))),
+                None,
+                REGISTRY_CONFIG_FIELD,
+                Err("config.command is empty"),
+            ),
+            // The entry point is only a fallback when there is no configuration
+            (
+                Some(configuration(None)),
+                Some("server.js"),
+                CONFIGURATION_FIELD,
+                Err("configuration.command is missing"),
+            ),
+            (
+                None,
+                Some("server.js"),
+                CONFIGURATION_FIELD,
+                Ok("server.js"),
+            ),
+            (
+                None,
+                Some(""),
+                CONFIGURATION_FIELD,
+                Err("configuration is missing"),
+            ),
+            (
+                None,
+                None,
+                CONFIGURATION_FIELD,
+                Err("configuration is missing"),
+            ),
+        ];
+
+        for (configuration, entry_point, prefix, expected) in cases {
+            let result = resolve_command(configuration.as_ref(), entry_point, prefix)
+                .map(|(command, _)| command)
+                .map_err(|errors| {
+                    errors
+                        .fields()
+                        .iter()
+                        .map(|e| e.to_string())
+                        .collect::<Vec<_>>()
+                        .join("; ")
+                });
+            assert_eq!(
+                result.as_deref(),
+                expected,
+                "{:?} {:?}",
+                configuration,
+                entry_point
+            );
+        }
+    }
+
+    #[test]
+    fn test_rpc_error_lists_fields() {
+        let errors = check_configuration_value(
+            Some(&json!({ "command": "", "args": "x" })),
+            CONFIGURATION_FIELD,
+        )
+        .unwrap_err();
+        let error = errors.to_rpc_error();
+
+        assert_eq!(error["code"], -32602);
+        assert_eq!(
+            error["message"],
+            "Invalid server configuration: configuration.command is empty; \
+             configuration.args must be an array of strings"
+        );
+        assert_eq!(
+            error["data"]["fields"][0],
+            json!({ "field": "configuration.command", "message": "is empty" })
+        );
+    }
+
+    #[test]
+    fn test_launch_plan_reports_the_field() {
+        let server: ServerDefinition = serde_json::from_value(json!({
+            "name": "Test server",
+            "description": "",
+            "enabled": true,
+            "tools_type": "node",
+            "configuration": { "args": ["server"] }
+        }))
+        .unwrap();
+
+        let error = LaunchPlan::build("test", &server).unwrap_err();
+        assert_eq!(
+            error,
+            "Cannot start server test: Invalid server configuration: \
+             configuration.command is missing"
+        );
+    }
+}
@@ -0,0 +1,209 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, HashMap};
+    use std::sync::Arc;
+
+    use mcp_core::{
+        config_file::{parse_config, plan_reconcile, resolve_env_references, ConflictPolicy},
+        core::{mcp_core_builder::MCPCoreBuilder, mcp_core_config_file_ext::McpCoreConfigFileExt},
+        models::types::ProvenanceSource,
+    };
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    use crate::common::{db, FailingLauncher};
+
+    const TOML: &str = r#"
+[settings]
+conflict_policy = "file_wins"
+
+[servers.weather]
+type = "node"
+command = "npx"
+args = ["-y", "weather-mcp"]
+env = { API_KEY = "secret", DEBUG = true }
+
+[servers.files]
+name = "Files"
+type = "node"
+command = "npx"
+enabled = false
+"#;
+
+    #[test]
+    fn test_parse_toml_yaml_and_json() {
+        let config = parse_config(TOML, "toml").unwrap();
+        assert_eq!(
+            config.settings.conflict_policy,
+            Some(ConflictPolicy::FileWins)
+        );
+        let weather = config.servers["weather"].to_definition("weather");
+        assert_eq!(weather.name, "weather");
+        assert!(weather.enabled);
+        let env = weather.configuration.unwrap().env.unwrap();
+        assert_eq!(env["API_KEY"].env_string().as_deref(), Some("secret"));
+        assert_eq!(env["DEBUG"].env_string().as_deref(), Some("true"));
+        assert!(!config.servers["files"].enabled);
+
+        let yaml = "servers:\n  files:\n    type: node\n    command: npx\n    args: [a]\n";
+        let config = parse_config(yaml, "yaml").unwrap();
+        assert_eq!(config.servers["files"].args, vec!["a"]);
+
+        let json = r#"{ "servers": { "files": { "type": "node", "command": "npx" } } }"#;
+        assert!(parse_config(json, "json")
+            .unwrap()
+            .servers
+            .contains_key("files"));
+    }
+
+    #[test]
+    fn test_parse_rejects_mistakes() {
+        let unknown_field = "[servers.files]\ntype = \"node\"\ncommand = \"npx\"\nargz = []\n";
+        assert!(parse_config(unknown_field, "toml")
+            .unwrap_err()
+            .contains("argz"));
+        let bad_id = "[servers.\"My Files\"]\ntype = \"node\"\ncommand = \"npx\"\n";
+        assert!(parse_config(bad_id, "toml")
+            .unwrap_err()
+            .starts_with("Invalid server id 'My Files'"));
+        assert!(parse_config("", "ini")
+            .unwrap_err()
+            .starts_with("Unsupported configuration format"));
+    }
+
+    #[test]
+    fn test_env_references() {
+        let lookup = |name: &str| (name == "TOKEN").then(|| "abc".to_string());
+        let mut value =
+            json!({ "env": { "KEY": "Bearer ${env:TOKEN}!" }, "args": ["${env:TOKEN}"] });
+        resolve_env_references(&mut value, &lookup).unwrap();
+        assert_eq!(
+            value,
+            json!({ "env": { "KEY": "Bearer abc!" }, "args": ["abc"] })
+        );
+
+        let mut missing = json!("${env:MISSING}");
+        assert_eq!(
+            resolve_env_references(&mut missing, &lookup).unwrap_err(),
+            "Environment variable MISSING is not set"
+        );
+        let mut unterminated = json!("${env:TOKEN");
+        assert!(resolve_env_references(&mut unterminated, &lookup).is_err());
+    }
+
+    #[test]
+    fn test_plan_adds_updates_and_removes() {
+        let v1 = parse_config(TOML, "toml").unwrap().definitions();
+        let mut v2 = v1.clone();
+        v2.remove("files");
+        v2.get_mut("weather").unwrap().description = "Forecasts".to_string();
+        let installed: HashMap<_, _> = v1.clone().into_iter().collect();
+
+        let plan = plan_reconcile(&v1, &BTreeMap::new(), &HashMap::new(), ConflictPolicy::Warn);
+        assert_eq!(plan.add, vec!["files", "weather"]);
+
+        let plan = plan_reconcile(&v1, &v1, &installed, ConflictPolicy::Warn);
+        assert_eq!(plan.unchanged, vec!["files", "weather"]);
+        assert!(plan.add.is_empty() && plan.update.is_empty() && plan.remove.is_empty());
+
+        let plan = plan_reconcile(&v2, &v1, &installed, ConflictPolicy::Warn);
+        assert_eq!(plan.update, vec!["weather"]);
+        assert_eq!(plan.remove, vec!["files"]);
+        assert!(plan.conflicts.is_empty());
+    }
+
+    #[test]
+    fn test_plan_conflicts_follow_the_policy() {
+        let v1 = parse_config(TOML, "toml").unwrap().definitions();
+        let mut v2 = v1.clone();
+        v2.remove("files");
+        v2.get_mut("weather").unwrap().description = "Forecasts".to_string();
+        // Both servers were changed in the app since v1 was applied
+        let mut installed: HashMap<_, _> = v1.clone().into_iter().collect();
+        for server in installed.values_mut() {
+            server.name = "Renamed".to_string();
+        }
+
+        for policy in [ConflictPolicy::Warn, ConflictPolicy::DbWins] {
+            let plan = plan_reconcile(&v2, &v1, &installed, policy);
+            assert!(plan.update.is_empty() && plan.remove.is_empty());
+            let conflicts: Vec<(&str, &str, bool)> = plan
+                .conflicts
+                .iter()
+                .map(|c| (c.server_id.as_str(), c.reason.as_str(), c.file_applied))
+                .collect();
+            assert_eq!(
+                conflicts,
+                vec![
+                    ("weather", "Changed in the app", false),
+                    ("files", "Changed in the app", false)
+                ]
+            );
+        }
+
+        let plan = plan_reconcile(&v2, &v1, &installed, ConflictPolicy::FileWins);
+        assert_eq!(plan.update, vec!["weather"]);
+        assert_eq!(plan.remove, vec!["files"]);
+        assert!(plan.conflicts.iter().all(|c| c.file_applied));
+
+        // Uninstalled in the app, the file only reinstalls it when it wins
+        let plan = plan_reconcile(&v1, &v1, &HashMap::new(), ConflictPolicy::Warn);
+        assert!(plan.add.is_empty());
+        assert_eq!(plan.conflicts[0].reason, "Uninstalled in the app");
+        let plan = plan_reconcile(&v1, &v1, &HashMap::new(), ConflictPolicy::FileWins);
+        assert_eq!(plan.add, vec!["files", "weather"]);
+    }
+
+    #[tokio::test]
+    async fn test_apply_config_file() {
+        let dir = tempdir().unwrap();
+        let db = db(&dir);
+        let core = MCPCoreBuilder::new(db)
+            .process_launcher(Arc::new(FailingLauncher::new()))
+            .build();
+        let path = dir.path().join("dockmaster.toml");
+
+        std::fs::write(&path, TOML).unwrap();
+        let report = core.apply_config_file(&path, None).await.unwrap();
+        assert_eq!(report.added, vec!["files", "weather"]);
+        assert!(report.failed.is_empty(), "{:?}", report.failed);
+        let registry = core.tool_registry.read().await;
+        assert!(!registry.get_server("files").unwrap().enabled);
+        assert_eq!(registry.get_server("files").unwrap().name, "Files");
+        let provenance = registry.get_server_provenances().unwrap();
+        assert_eq!(provenance["weather"].source, ProvenanceSource::ConfigFile);
+        assert_eq!(
+            provenance["weather"].source_id.as_deref(),
+            Some(path.display().to_string().as_str())
+        );
+        drop(registry);
+
+        let report = core.apply_config_file(&path, None).await.unwrap();
+        assert_eq!(report.unchanged, vec!["files", "weather"]);
+
+        // Renamed in the app, then dropped from the file
+        let registry = core.tool_registry.read().await;
+        let mut files = registry.get_server("files").unwrap();
+        files.name = "My files".to_string();
+        registry.save_server("files", &files).unwrap();
+        drop(registry);
+        let without_files = TOML.split("[servers.files]").next().unwrap();
+        std::fs::write(&path, without_files).unwrap();
+
+        let report = core
+            .apply_config_file(&path, Some(ConflictPolicy::Warn))
+            .await
+            .unwrap();
+        assert!(report.removed.is_empty());
+        assert_eq!(report.conflicts[0].server_id, "files");
+        assert_eq!(
+            core.tool_registry
+                .read()
+                .await
+                .get_server("files")
+                .unwrap()
+                .name,
+            "My files"
+        );
+    }
+}
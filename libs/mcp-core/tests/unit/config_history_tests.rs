@@ -0,0 +1,144 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    use mcp_core::{
+        config_history::CONFIG_HISTORY_KEPT,
+        core::{
+            mcp_core::MCPCore, mcp_core_builder::MCPCoreBuilder,
+            mcp_core_config_history_ext::McpCoreConfigHistoryExt,
+        },
+        models::types::{EnvValue, ServerConfigUpdateRequest, ServerDefinition},
+    };
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    use crate::common::{db, FailingLauncher};
+
+    fn server(enabled: bool) -> ServerDefinition {
+        serde_json::from_value(json!({
+            "name": "Weather",
+            "description": "",
+            "enabled": enabled,
+            "tools_type": "node",
+            "configuration": { "command": "npx", "env": { "API_KEY": "old" } }
+        }))
+        .unwrap()
+    }
+
+    fn update(api_key: &str) -> ServerConfigUpdateRequest {
+        ServerConfigUpdateRequest {
+            server_id: "weather".to_string(),
+            config: HashMap::from([("API_KEY".to_string(), json!(api_key))]),
+            inherit_env: None,
+            cwd: None,
+        }
+    }
+
+    async fn api_key(core: &MCPCore) -> Option<EnvValue> {
+        let server = core
+            .tool_registry
+            .read()
+            .await
+            .get_server("weather")
+            .unwrap();
+        server.configuration.unwrap().env.unwrap()["API_KEY"]
+            .default
+            .clone()
+    }
+
+    #[test]
+    fn test_history_is_pruned_and_follows_the_server() {
+        let dir = tempdir().unwrap();
+        let db = db(&dir);
+        db.save_server("weather", &server(true)).unwrap();
+        let configuration = server(true).configuration.unwrap();
+
+        for created_at in 0..CONFIG_HISTORY_KEPT + 5 {
+            db.record_config_snapshot("weather", created_at as u64, &configuration)
+                .unwrap();
+        }
+        let history = db.get_config_history("weather", 100).unwrap();
+        assert_eq!(history.len() as i64, CONFIG_HISTORY_KEPT);
+        assert_eq!(history[0].created_at, CONFIG_HISTORY_KEPT as u64 + 4);
+        assert_eq!(db.get_config_history("weather", 3).unwrap().len(), 3);
+
+        db.rename_server("weather", "forecast").unwrap();
+        let snapshot = db.get_config_snapshot(history[0].id).unwrap();
+        assert_eq!(snapshot.server_id, "forecast");
+
+        db.delete_server("forecast").unwrap();
+        assert!(db.get_config_history("forecast", 100).unwrap().is_empty());
+        assert!(db.get_config_snapshot(history[0].id).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_failed_restart_rolls_back() {
+        let dir = tempdir().unwrap();
+        let db = db(&dir);
+        db.save_server("weather", &server(true)).unwrap();
+        let launcher = Arc::new(FailingLauncher::new());
+        let core = MCPCoreBuilder::new(db)
+            .process_launcher(launcher.clone())
+            .build();
+
+        let result = core.apply_server_config(update("new")).await.unwrap();
+        assert!(!result.success);
+        assert!(result.rolled_back);
+        assert!(result.error.unwrap().contains("not started in tests"));
+
+        // Started with the new configuration, then with the previous one again
+        let api_keys: Vec<String> = launcher
+            .plans()
+            .into_iter()
+            .map(|plan| plan.env["API_KEY"].clone())
+            .collect();
+        assert_eq!(api_keys, vec!["new", "old"]);
+        assert_eq!(api_key(&core).await, Some(EnvValue::from("old")));
+
+        let history = core.config_history("weather", 10).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(Some(history[0].id), result.snapshot_id);
+        let env = history[0].configuration.env.as_ref().unwrap();
+        assert_eq!(env["API_KEY"].default, Some(EnvValue::from("old")));
+    }
+
+    #[tokio::test]
+    async fn test_manual_rollback() {
+        let dir = tempdir().unwrap();
+        let db = db(&dir);
+        // Disabled servers are not started, so every change is kept
+        db.save_server("weather", &server(false)).unwrap();
+        let core = MCPCoreBuilder::new(db)
+            .process_launcher(Arc::new(FailingLauncher::new()))
+            .build();
+
+        let first = core.apply_server_config(update("new")).await.unwrap();
+        assert!(first.success && !first.rolled_back);
+        core.apply_server_config(update("newer")).await.unwrap();
+        assert_eq!(api_key(&core).await, Some(EnvValue::from("newer")));
+
+        let result = core
+            .rollback_server_config("weather", first.snapshot_id.unwrap())
+            .await
+            .unwrap();
+        assert!(result.success);
+        assert_eq!(api_key(&core).await, Some(EnvValue::from("old")));
+
+        // The rollback can be undone in turn
+        let history = core.config_history("weather", 10).await.unwrap();
+        assert_eq!(history.len(), 3);
+        assert_eq!(Some(history[0].id), result.snapshot_id);
+        let env = history[0].configuration.env.as_ref().unwrap();
+        assert_eq!(env["API_KEY"].default, Some(EnvValue::from("newer")));
+
+        let other = core.rollback_server_config("other", history[0].id).await;
+        assert!(!other.unwrap().success);
+        assert!(core.rollback_server_config("weather", 999).await.is_err());
+        let mut request = update("x");
+        request.server_id = "other".to_string();
+        let unknown = core.apply_server_config(request).await.unwrap();
+        assert!(!unknown.success && unknown.snapshot_id.is_none());
+    }
+}
@@ -0,0 +1,136 @@
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use mcp_core::{
+        core::{
+            mcp_core_builder::MCPCoreBuilder,
+            mcp_core_dashboard_ext::{McpCoreDashboardExt, DASHBOARD_ERROR_SNIPPET},
+        },
+        database::db_manager::DBManager,
+        mcp_state::tool_stats::ToolStats,
+        models::types::{ServerDefinition, ServerToolInfo, ToolCallCount},
+    };
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    fn server(name: &str, enabled: bool) -> ServerDefinition {
+        serde_json::from_value(json!({
+            "name": name,
+            "description": "",
+            "enabled": enabled,
+            "tools_type": "node",
+            "configuration": { "command": "node" }
+        }))
+        .unwrap()
+    }
+
+    fn tool(server_id: &str, name: &str) -> ServerToolInfo {
+        ServerToolInfo::from_spec_value(server_id, json!({ "name": name })).unwrap()
+    }
+
+    #[test]
+    fn test_most_used_tools() {
+        let stats = ToolStats::new();
+        assert!(stats.most_used(5).is_empty());
+        assert_eq!(stats.recent_calls(), 0);
+
+        for (tool_name, calls) in [("read", 3), ("write", 1), ("list", 3)] {
+            for _ in 0..calls {
+                stats.record("files", tool_name, Duration::from_millis(5), true);
+            }
+        }
+        stats.record_rate_limited("files", "delete");
+
+        let most_used: Vec<(String, u64)> = stats
+            .most_used(2)
+            .into_iter()
+            .map(|(_, tool_name, usage)| (tool_name, usage.calls))
+            .collect();
+        // Ties are ordered by name, tools never called are left out
+        assert_eq!(
+            most_used,
+            vec![("list".to_string(), 3), ("read".to_string(), 3)]
+        );
+        assert_eq!(stats.most_used(10).len(), 3);
+        assert_eq!(stats.recent_calls(), 7);
+    }
+
+    #[tokio::test]
+    async fn test_dashboard_summary() {
+        let dir = tempdir().unwrap();
+        let db = DBManager::with_path(dir.path().join("mcp-dockmaster.db")).unwrap();
+        db.apply_migrations().unwrap();
+        db.save_server("files", &server("Files", true)).unwrap();
+        db.save_server("weather", &server("Weather", true)).unwrap();
+        db.save_server("notes", &server("Notes", false)).unwrap();
+        let core = MCPCoreBuilder::new(db).build();
+
+        {
+            let mcp_state = core.mcp_state.read().await;
+            let mut inactive = tool("files", "delete");
+            inactive.is_active = false;
+            let mut server_tools = mcp_state.server_tools.write().await;
+            server_tools.insert(
+                "files".to_string(),
+                vec![tool("files", "read"), tool("files", "write"), inactive],
+            );
+            server_tools.insert("notes".to_string(), vec![tool("notes", "add")]);
+            drop(server_tools);
+
+            mcp_state
+                .launch_errors
+                .write()
+                .await
+                .insert("weather".to_string(), "x".repeat(1000));
+            for _ in 0..2 {
+                mcp_state
+                    .tool_stats
+                    .record("files", "read", Duration::from_millis(5), true);
+            }
+            mcp_state
+                .tool_stats
+                .record("files", "write", Duration::from_millis(5), false);
+        }
+
+        let summary = core.dashboard_summary().await.unwrap();
+        assert_eq!(summary.servers, 3);
+        assert_eq!(summary.errored, 1);
+        assert_eq!(summary.stopped, 2);
+        assert_eq!(summary.running + summary.starting, 0);
+        assert_eq!(summary.tools, 4);
+        // The inactive tool and the tool of the disabled server
+        assert_eq!(summary.tools_disabled, 2);
+        assert_eq!(summary.tools_hidden, 0);
+        assert_eq!(summary.calls_last_hour, 3);
+        assert_eq!(
+            summary.top_tools[0],
+            ToolCallCount {
+                server_id: "files".to_string(),
+                tool_name: "read".to_string(),
+                calls: 2,
+            }
+        );
+        assert_eq!(summary.servers_in_error[0].name, "Weather");
+        assert_eq!(
+            summary.servers_in_error[0].error.len(),
+            DASHBOARD_ERROR_SNIPPET
+        );
+
+        // Servers saved since are counted without reloading them
+        core.tool_registry
+            .read()
+            .await
+            .save_server("search", &server("Search", true))
+            .unwrap();
+        core.mcp_state
+            .read()
+            .await
+            .set_tools_hidden(true)
+            .await
+            .unwrap();
+        let summary = core.dashboard_summary().await.unwrap();
+        assert_eq!(summary.servers, 4);
+        assert_eq!(summary.tools_hidden, 4);
+    }
+}
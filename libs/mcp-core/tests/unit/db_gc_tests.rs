@@ -0,0 +1,120 @@
+#[cfg(test)]
+mod tests {
+    use diesel::connection::SimpleConnection;
+    use diesel::{Connection, SqliteConnection};
+    use mcp_core::{
+        database::{
+            db_manager::DBManager,
+            gc::{duplicate_env_keys, invalid_server_reason},
+        },
+        models::types::ServerDefinition,
+    };
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    fn server(tools_type: &str, command: Option<&str>) -> ServerDefinition {
+        serde_json::from_value(json!({
+            "name": "Weather",
+            "description": "",
+            "enabled": true,
+            "tools_type": tools_type,
+            "configuration": {
+                "command": command,
+                "env": { "API_KEY": { "description": "", "default": "k3y" } }
+            }
+        }))
+        .unwrap()
+    }
+
+    fn keys(keys: &[&str]) -> Vec<String> {
+        keys.iter().map(|k| k.to_string()).collect()
+    }
+
+    #[test]
+    fn test_duplicate_env_keys() {
+        assert!(duplicate_env_keys(&keys(&["A", "B"])).is_empty());
+        assert_eq!(duplicate_env_keys(&keys(&[" A", "A", "B"])), keys(&[" A"]));
+        assert_eq!(duplicate_env_keys(&keys(&["A ", " A"])), keys(&[" A"]));
+    }
+
+    #[test]
+    fn test_invalid_server_reason() {
+        assert_eq!(invalid_server_reason("node", Some("npx"), None), None);
+        assert_eq!(invalid_server_reason("node", None, Some("index.js")), None);
+        assert!(invalid_server_reason("", Some("npx"), None).is_some());
+        assert!(invalid_server_reason("docker", Some(" "), None).is_some());
+    }
+
+    #[test]
+    fn test_collect_garbage() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("mcp-dockmaster.db");
+        let db = DBManager::with_path(path.clone()).unwrap();
+        db.apply_migrations().unwrap();
+        db.save_server("weather", &server("node", Some("npx")))
+            .unwrap();
+        db.save_server("broken", &server("", Some("npx"))).unwrap();
+        db.save_server_run_state("broken", false).unwrap();
+
+        // Rows written by builds that didn't enforce the foreign keys
+        let mut conn = SqliteConnection::establish(&path.to_string_lossy()).unwrap();
+        conn.batch_execute(
+            "PRAGMA foreign_keys = OFF;
+            INSERT INTO server_env (server_id, env_key, env_value, env_description)
+                VALUES ('gone', 'TOKEN', 'secret', ''), ('weather', ' API_KEY', 'old', '');
+            INSERT INTO server_tools (id, name, description, server_id)
+                VALUES ('search', 'Search', '', 'gone');
+            INSERT INTO server_run_states (server_id, desired_running) VALUES ('gone', 1);",
+        )
+        .unwrap();
+
+        let report = db.collect_garbage(true).unwrap();
+        assert!(report.dry_run);
+        assert_eq!(
+            report.orphaned_env,
+            vec![("gone".to_string(), "TOKEN".to_string())]
+        );
+        assert_eq!(
+            report.orphaned_tools,
+            vec![("gone".to_string(), "search".to_string())]
+        );
+        assert_eq!(
+            report.orphaned_state,
+            vec![("server_run_states".to_string(), "gone".to_string())]
+        );
+        assert_eq!(
+            report.duplicate_env,
+            vec![("weather".to_string(), " API_KEY".to_string())]
+        );
+        assert_eq!(report.invalid_servers.len(), 1);
+        assert_eq!(report.invalid_servers[0].server_id, "broken");
+        assert_eq!(report.total(), 5);
+        assert!(db.get_server("broken").is_ok());
+
+        let removed = db.collect_garbage(false).unwrap();
+        assert_eq!(removed.total(), 5);
+        assert!(db.collect_garbage(true).unwrap().is_empty());
+        assert!(db.get_server("broken").is_err());
+        assert!(db.get_server_run_states().unwrap().is_empty());
+        let weather = db.get_server("weather").unwrap();
+        let env = weather.configuration.unwrap().env.unwrap();
+        assert_eq!(env.keys().collect::<Vec<_>>(), vec!["API_KEY"]);
+    }
+
+    #[test]
+    fn test_server_state_is_deleted_with_the_server() {
+        let dir = tempdir().unwrap();
+        let db = DBManager::with_path(dir.path().join("mcp-dockmaster.db")).unwrap();
+        db.apply_migrations().unwrap();
+
+        // Each connection of the pool enforces the foreign keys
+        for _ in 0..10 {
+            assert!(db.save_server_run_state("gone", true).is_err());
+        }
+        db.save_server("weather", &server("node", Some("npx")))
+            .unwrap();
+        db.save_server_run_state("weather", false).unwrap();
+        db.delete_server("weather").unwrap();
+        assert!(db.collect_garbage(true).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,107 @@
+#[cfg(test)]
+mod tests {
+    use diesel::connection::SimpleConnection;
+    use diesel::{Connection, SqliteConnection};
+    use mcp_core::{
+        database::{
+            db_manager::DBManager,
+            legacy::LEGACY_MIGRATION_SETTING,
+            state::{DatabaseState, DatabaseStatus},
+        },
+        models::types::ServerDefinition,
+    };
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    fn server() -> ServerDefinition {
+        serde_json::from_value(json!({
+            "name": "Fetch",
+            "description": "",
+            "enabled": true,
+            "tools_type": "node",
+            "configuration": { "command": "npx", "args": ["-y", "fetch-mcp"] }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_fresh_empty_and_ready() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("mcp_dockmaster.db");
+        let db = DBManager::with_path(path.clone()).unwrap();
+
+        let state = db.state();
+        assert_eq!(state.status, DatabaseStatus::NeedsMigration);
+        assert_eq!(state.schema_version, None);
+        assert!(state.pending_migrations > 0);
+
+        db.apply_migrations().unwrap();
+        let state = db.state();
+        assert_eq!(state.status, DatabaseStatus::Fresh);
+        assert!(state.schema_version.is_some());
+        assert_eq!(state.pending_migrations, 0);
+        assert_eq!(state.server_count, 0);
+
+        // Opened again by the next run
+        let db = DBManager::with_path(path).unwrap();
+        assert_eq!(db.state().status, DatabaseStatus::Empty);
+
+        db.save_server("fetch", &server()).unwrap();
+        let state = db.state();
+        assert_eq!(state.status, DatabaseStatus::Ready);
+        assert_eq!(state.server_count, 1);
+        assert_eq!(state.error, None);
+    }
+
+    #[test]
+    fn test_legacy_servers_need_a_migration() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("mcp_dockmaster.db");
+        let db = DBManager::with_path(path.clone()).unwrap();
+        db.apply_migrations().unwrap();
+        let fixture = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/legacy/tools.sql");
+        let mut conn = SqliteConnection::establish(&path.to_string_lossy()).unwrap();
+        conn.batch_execute(&std::fs::read_to_string(fixture).unwrap())
+            .unwrap();
+
+        let state = db.state();
+        assert_eq!(state.status, DatabaseStatus::NeedsMigration);
+        assert_eq!(state.pending_migrations, 0);
+        assert_eq!(state.legacy_server_count, 4);
+
+        db.migrate_legacy_tools().unwrap();
+        db.save_setting(LEGACY_MIGRATION_SETTING, "true").unwrap();
+        let state = db.state();
+        assert_eq!(state.status, DatabaseStatus::Ready);
+        assert_eq!(state.legacy_server_count, 0);
+        assert_eq!(state.server_count, 3);
+    }
+
+    #[test]
+    fn test_unreadable_database() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("mcp_dockmaster.db");
+        std::fs::write(&path, "not a database, but long enough to have a header").unwrap();
+        let db = DBManager::with_path(path).unwrap();
+
+        let state = db.state();
+        assert_eq!(state.status, DatabaseStatus::Unreadable);
+        assert!(state.error.is_some());
+        let value = serde_json::to_value(&state).unwrap();
+        assert_eq!(value["status"], "unreadable");
+        assert!(value["error"]["kind"].is_string());
+    }
+
+    #[test]
+    fn test_status_from_counts() {
+        let status = |created, pending, servers, legacy| {
+            DatabaseState::from_counts(created, None, pending, servers, 0, legacy).status
+        };
+        assert_eq!(status(true, 0, 0, 0), DatabaseStatus::Fresh);
+        assert_eq!(status(false, 0, 0, 0), DatabaseStatus::Empty);
+        assert_eq!(status(true, 0, 3, 0), DatabaseStatus::Ready);
+        assert_eq!(status(false, 2, 3, 0), DatabaseStatus::NeedsMigration);
+        assert_eq!(status(false, 0, 3, 1), DatabaseStatus::NeedsMigration);
+    }
+}
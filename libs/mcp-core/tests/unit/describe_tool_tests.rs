@@ -0,0 +1,106 @@
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use mcp_core::{
+        core::{
+            mcp_core::MCPCore,
+            mcp_core_builder::MCPCoreBuilder,
+            mcp_core_tools_ext::{DescribeToolError, McpCoreToolsExt},
+        },
+        database::db_manager::DBManager,
+        models::types::{ServerDefinition, ServerStatus, ServerToolInfo},
+    };
+    use serde_json::json;
+    use tempfile::{tempdir, TempDir};
+
+    async fn core() -> (MCPCore, TempDir) {
+        let temp_dir = tempdir().expect("Failed to create temp directory");
+        let db = DBManager::with_path(temp_dir.path().join("mcp-dockmaster.db"))
+            .expect("Failed to create database");
+        db.apply_migrations().expect("Failed to apply migrations");
+        let core = MCPCoreBuilder::new(db).build();
+
+        let server: ServerDefinition = serde_json::from_value(json!({
+            "name": "Files",
+            "description": "",
+            "enabled": true,
+            "tools_type": "node",
+            "configuration": { "command": "node" }
+        }))
+        .unwrap();
+        core.tool_registry
+            .read()
+            .await
+            .save_server("files", &server)
+            .unwrap();
+
+        let tool = ServerToolInfo::from_value(
+            json!({
+                "name": "read_file",
+                "description": "Read a file",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "path": { "type": "string" },
+                        "encoding": { "type": "string", "default": "utf-8" }
+                    },
+                    "required": ["path"]
+                }
+            }),
+            "files".to_string(),
+        )
+        .unwrap();
+        {
+            let mcp_state = core.mcp_state.read().await;
+            mcp_state
+                .server_tools
+                .write()
+                .await
+                .insert("files".to_string(), vec![tool]);
+        }
+        (core, temp_dir)
+    }
+
+    #[tokio::test]
+    async fn test_describe_tool() {
+        let (core, _dir) = core().await;
+        {
+            let mcp_state = core.mcp_state.read().await;
+            let stats = &mcp_state.tool_stats;
+            stats.record("files", "read_file", Duration::from_millis(30), true);
+            stats.record("files", "read_file", Duration::from_millis(10), false);
+        }
+
+        let tool = core.describe_tool("files", "read_file").await.unwrap();
+        assert_eq!(tool.proxy_id, "files:read_file");
+        assert_eq!(tool.description, "Read a file");
+        assert_eq!(tool.input_schema.unwrap().required, vec!["path"]);
+        assert_eq!(tool.defaults.len(), 1);
+        assert_eq!(tool.defaults["encoding"], json!("utf-8"));
+        assert!(matches!(tool.server_status, ServerStatus::Stopped));
+        assert_eq!(tool.usage.calls, 2);
+        assert_eq!(tool.usage.errors, 1);
+        assert_eq!(tool.usage.total_duration_ms, 40);
+        assert!(tool.usage.last_called_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_describe_unknown_tool() {
+        let (core, _dir) = core().await;
+
+        let error = core.describe_tool("files", "write_file").await.unwrap_err();
+        assert_eq!(
+            error,
+            DescribeToolError::UnknownTool {
+                server_id: "files".to_string(),
+                tool_name: "write_file".to_string(),
+            }
+        );
+        assert_eq!(error.reason(), "unknown_tool");
+
+        let error = core.describe_tool("fetch", "read_file").await.unwrap_err();
+        assert_eq!(error, DescribeToolError::UnknownServer("fetch".to_string()));
+        assert_eq!(error.reason(), "unknown_server");
+    }
+}
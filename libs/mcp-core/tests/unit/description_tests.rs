@@ -0,0 +1,143 @@
+#[cfg(test)]
+mod tests {
+    use mcp_core::{
+        core::{
+            mcp_core::MCPCore, mcp_core_builder::MCPCoreBuilder,
+            mcp_core_proxy_ext::McpCoreProxyExt, mcp_core_tools_ext::McpCoreToolsExt,
+        },
+        database::db_manager::DBManager,
+        models::types::{ServerDefinition, ServerToolInfo},
+        utils::description::{
+            collapse_whitespace, normalize_description, strip_markdown, DescriptionOptions,
+        },
+    };
+    use serde_json::json;
+    use tempfile::{tempdir, TempDir};
+
+    fn options(max_length: usize, strip_markdown: bool) -> DescriptionOptions {
+        DescriptionOptions {
+            max_length,
+            strip_markdown,
+        }
+    }
+
+    #[test]
+    fn test_collapse_whitespace() {
+        assert_eq!(
+            collapse_whitespace("  Read   a file.\n\n\n\n  Returns\tits  content.  \n"),
+            "Read a file.\n\nReturns its content."
+        );
+        assert_eq!(collapse_whitespace("a\n  b"), "a\nb");
+        assert_eq!(collapse_whitespace(" \n \n"), "");
+    }
+
+    #[test]
+    fn test_strip_markdown() {
+        let markdown = "## Usage\n\
+                        ```json\n\
+                        {\"path\": \"/tmp\"}\n\
+                        ```\n\
+                        > **Note:** see [the docs](https://example.com) ![logo](logo.png)\n\
+                        Use `read_file` with *.md globs, [optional] and __bold__";
+        assert_eq!(
+            strip_markdown(markdown),
+            "Usage\n\
+             {\"path\": \"/tmp\"}\n\
+             Note: see the docs logo\n\
+             Use read_file with *.md globs, [optional] and bold"
+        );
+        // Hashes that aren't a heading are kept
+        assert_eq!(strip_markdown("#hashtag"), "#hashtag");
+    }
+
+    #[test]
+    fn test_truncation() {
+        let long = "word ".repeat(300);
+
+        let truncated = normalize_description(&long, &options(1024, false), true);
+        assert_eq!(truncated.chars().count(), 1024);
+        assert!(truncated.ends_with('…'));
+
+        // Opted-out servers and a zero length keep the whole text
+        let whole = normalize_description(&long, &options(1024, false), false);
+        assert_eq!(whole, long.trim_end());
+        let whole = normalize_description(&long, &options(0, false), true);
+        assert_eq!(whole, long.trim_end());
+
+        // Truncation counts characters, not bytes
+        let accents = normalize_description("éééééé", &options(4, false), true);
+        assert_eq!(accents, "ééé…");
+        assert_eq!(
+            normalize_description("short", &DescriptionOptions::default(), true),
+            "short"
+        );
+    }
+
+    fn core() -> (MCPCore, TempDir) {
+        let temp_dir = tempdir().expect("Failed to create temp directory");
+        let db = DBManager::with_path(temp_dir.path().join("mcp-dockmaster.db"))
+            .expect("Failed to create database");
+        db.apply_migrations().expect("Failed to apply migrations");
+        (MCPCoreBuilder::new(db).build(), temp_dir)
+    }
+
+    fn server(truncate_descriptions: Option<bool>) -> ServerDefinition {
+        serde_json::from_value(json!({
+            "name": "Docs",
+            "description": "",
+            "enabled": true,
+            "tools_type": "node",
+            "configuration": {
+                "command": "node",
+                "truncate_descriptions": truncate_descriptions
+            }
+        }))
+        .unwrap()
+    }
+
+    fn tool(server_id: &str, description: &str) -> ServerToolInfo {
+        ServerToolInfo::from_value(
+            json!({ "name": "search", "description": description }),
+            server_id.to_string(),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_tool_list_descriptions() {
+        let (core, _dir) = core();
+        let long = format!("# Search\n\n{}", "x".repeat(2000));
+        {
+            let registry = core.tool_registry.read().await;
+            registry.save_server("short", &server(None)).unwrap();
+            registry.save_server("full", &server(Some(false))).unwrap();
+        }
+        {
+            let mcp_state = core.mcp_state.read().await;
+            let mut server_tools = mcp_state.server_tools.write().await;
+            server_tools.insert("short".to_string(), vec![tool("short", &long)]);
+            server_tools.insert("full".to_string(), vec![tool("full", &long)]);
+        }
+        core.set_description_options(options(100, true))
+            .await
+            .unwrap();
+        assert_eq!(core.description_options().await, options(100, true));
+
+        let tools = core.list_all_server_tools().await.unwrap();
+        let description = |server_id: &str| {
+            tools
+                .iter()
+                .find(|tool| tool.server_id == server_id)
+                .map(|tool| tool.description.clone())
+                .unwrap()
+        };
+        assert_eq!(description("short").chars().count(), 100);
+        assert!(description("short").starts_with("Search\n\nxxx"));
+        assert_eq!(description("full").chars().count(), 2008);
+
+        // The full text stays available
+        let described = core.describe_tool("short", "search").await.unwrap();
+        assert_eq!(described.description, long);
+        assert!(core.describe_tool("short", "fetch").await.is_err());
+    }
+}
@@ -0,0 +1,148 @@
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use mcp_core::{
+        core::{mcp_core_builder::MCPCoreBuilder, mcp_core_diagnose_ext::McpCoreDiagnoseExt},
+        database::db_manager::DBManager,
+        diagnose::{
+            fatal_stderr_lines, find_program, missing_required_env, node_major, DiagnosisStatus,
+            ServerDiagnosis,
+        },
+        models::types::ServerDefinition,
+    };
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    fn server(enabled: bool) -> ServerDefinition {
+        serde_json::from_value(json!({
+            "name": "Weather",
+            "description": "",
+            "enabled": enabled,
+            "tools_type": "custom",
+            "configuration": {
+                "command": "dockmaster-missing-command",
+                "env": {
+                    "API_KEY": { "description": "", "required": true },
+                    "UNITS": { "description": "", "default": "metric", "required": true },
+                    "DEBUG": { "description": "" }
+                }
+            }
+        }))
+        .unwrap()
+    }
+
+    fn statuses(diagnosis: &ServerDiagnosis) -> Vec<(&str, DiagnosisStatus)> {
+        diagnosis
+            .steps
+            .iter()
+            .map(|step| (step.id.as_str(), step.status))
+            .collect()
+    }
+
+    #[test]
+    fn test_checks() {
+        assert_eq!(node_major("v20.11.1\n"), Some(20));
+        assert_eq!(node_major("18"), Some(18));
+        assert_eq!(node_major("unknown"), None);
+
+        let stderr = "Starting\n\
+                      Error: Cannot find module 'express'\n\
+                      thread 'main' panicked at src/main.rs";
+        assert_eq!(fatal_stderr_lines(stderr).len(), 2);
+        assert!(fatal_stderr_lines("Listening on stdio").is_empty());
+
+        let dir = tempdir().unwrap();
+        let program = dir.path().join("weather-server");
+        fs::write(&program, "").unwrap();
+        assert_eq!(
+            find_program("weather-server", Some(dir.path().as_os_str())),
+            Some(program.clone())
+        );
+        assert_eq!(
+            find_program(program.to_str().unwrap(), None),
+            Some(program.clone())
+        );
+        assert_eq!(
+            find_program("other-server", Some(dir.path().as_os_str())),
+            None
+        );
+        assert_eq!(find_program("weather-server", None), None);
+
+        let configuration = server(true).configuration;
+        assert_eq!(
+            missing_required_env(configuration.as_ref()),
+            vec!["API_KEY"]
+        );
+        assert!(missing_required_env(None).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_diagnose_server() {
+        let dir = tempdir().unwrap();
+        let db = DBManager::with_path(dir.path().join("mcp-dockmaster.db")).unwrap();
+        db.apply_migrations().unwrap();
+        db.save_server("weather", &server(false)).unwrap();
+        db.save_server("forecast", &server(true)).unwrap();
+        let core = MCPCoreBuilder::new(db).build();
+
+        let diagnosis = core.diagnose_server("weather").await.unwrap();
+        assert!(!diagnosis.healthy);
+        assert_eq!(diagnosis.server_name, "Weather");
+        assert_eq!(
+            statuses(&diagnosis),
+            vec![
+                ("command", DiagnosisStatus::Fail),
+                ("runtime", DiagnosisStatus::Skipped),
+                ("env", DiagnosisStatus::Fail),
+                ("process", DiagnosisStatus::Fail),
+                ("initialize", DiagnosisStatus::Skipped),
+                ("tools_list", DiagnosisStatus::Skipped),
+                ("round_trip", DiagnosisStatus::Skipped),
+                ("stderr", DiagnosisStatus::Pass),
+            ]
+        );
+        assert!(diagnosis.steps[2].details.contains("API_KEY"));
+        assert!(!diagnosis.steps[2].details.contains("UNITS"));
+        // Every failed step says what to do
+        assert!(diagnosis
+            .steps
+            .iter()
+            .all(|step| step.hint.is_some() == (step.status == DiagnosisStatus::Fail)));
+
+        // A server that failed to start is diagnosed from its error
+        core.mcp_state
+            .read()
+            .await
+            .launch_errors
+            .write()
+            .await
+            .insert(
+                "forecast".to_string(),
+                "Server exited during startup with exit code 1, stderr:\n\
+                 Error: Cannot find module 'express'"
+                    .to_string(),
+            );
+        let diagnosis = core.diagnose_server("forecast").await.unwrap();
+        let failed: Vec<&str> = statuses(&diagnosis)
+            .into_iter()
+            .filter(|(_, status)| *status == DiagnosisStatus::Fail)
+            .map(|(id, _)| id)
+            .collect();
+        assert_eq!(
+            failed,
+            vec!["command", "env", "process", "initialize", "stderr"]
+        );
+        assert_eq!(
+            diagnosis.steps[7].details,
+            "Error: Cannot find module 'express'"
+        );
+
+        let export = core.export_server_diagnosis(&diagnosis).await.unwrap();
+        assert!(export.path.exists());
+        let files: Vec<&str> = export.files.iter().map(|file| file.path.as_str()).collect();
+        assert_eq!(files, vec!["server.json", "diagnosis.json", "system.json"]);
+
+        assert!(core.diagnose_server("unknown").await.is_err());
+    }
+}
@@ -0,0 +1,128 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::fs;
+
+    use mcp_core::{
+        database::db_manager::DBManager,
+        diagnostics::{redact_server_definition, DiagnosticsBundle, REDACTED},
+        models::types::{EnvValue, ServerDefinition},
+        utils::zip::crc32,
+    };
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    fn server() -> ServerDefinition {
+        serde_json::from_value(json!({
+            "name": "GitHub",
+            "description": "",
+            "enabled": true,
+            "tools_type": "docker",
+            "configuration": {
+                "command": "docker",
+                "args": [
+                    "run", "-e", "GITHUB_TOKEN=ghp_env_arg", "--api-key", "sk-next-arg",
+                    "--token=tok-inline", "--verbose", "image"
+                ],
+                "env": { "GITHUB_TOKEN": "ghp_env_value", "DEBUG": "1" }
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_redact_server_definition() {
+        let (redacted, secrets) = redact_server_definition(&server());
+        let configuration = redacted.configuration.unwrap();
+
+        let env = configuration.env.unwrap();
+        assert_eq!(
+            env["GITHUB_TOKEN"].default,
+            Some(EnvValue::String(REDACTED.to_string()))
+        );
+        assert_eq!(
+            configuration.args.unwrap(),
+            vec![
+                "run",
+                "-e",
+                "GITHUB_TOKEN=<redacted>",
+                "--api-key",
+                "<redacted>",
+                "--token=<redacted>",
+                "--verbose",
+                "image"
+            ]
+        );
+        for secret in [
+            "ghp_env_value",
+            "1",
+            "ghp_env_arg",
+            "sk-next-arg",
+            "tok-inline",
+        ] {
+            assert!(secrets.contains(&secret.to_string()), "{}", secret);
+        }
+    }
+
+    #[test]
+    fn test_secrets_are_scrubbed_from_logs() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("app.log"),
+            "starting github with ghp_env_value\nDEBUG=1\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("notes.txt"), "not a log").unwrap();
+
+        let mut servers = HashMap::new();
+        servers.insert("github".to_string(), server());
+        let mut bundle = DiagnosticsBundle::new();
+        bundle.add_server_definitions("servers/config.json", &servers);
+        bundle.add_log_tails("logs", dir.path());
+        let bundle = bundle.redact();
+
+        let preview = bundle.preview();
+        let paths: Vec<_> = preview.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["servers/config.json", "logs/app.log"]);
+        assert_eq!(preview.redacted_values, 5);
+
+        let log = String::from_utf8(bundle.file("logs/app.log").unwrap().to_vec()).unwrap();
+        // Short values are only redacted from the configuration
+        assert_eq!(log, "starting github with <redacted>\nDEBUG=1\n");
+        let config = String::from_utf8(bundle.file("servers/config.json").unwrap().to_vec());
+        assert!(!config.unwrap().contains("ghp_"));
+    }
+
+    #[test]
+    fn test_write_zip() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+
+        let dir = tempdir().unwrap();
+        let mut bundle = DiagnosticsBundle::new();
+        bundle.add_text("system.txt", "linux".to_string());
+        bundle.add_json("database.json", &json!({ "server_count": 0 }));
+        let path = dir.path().join("diagnostics").join("bundle.zip");
+        let export = bundle.redact().write(&path).unwrap();
+
+        assert_eq!(export.files.len(), 2);
+        let bytes = fs::read(&path).unwrap();
+        assert_eq!(&bytes[..4], b"PK\x03\x04");
+        // End of central directory record, listing both entries
+        let end = &bytes[bytes.len() - 22..];
+        assert_eq!(&end[..4], b"PK\x05\x06");
+        assert_eq!(u16::from_le_bytes([end[10], end[11]]), 2);
+    }
+
+    #[test]
+    fn test_database_info() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("mcp-dockmaster.db");
+        let db = DBManager::with_path(path.clone()).unwrap();
+        db.apply_migrations().unwrap();
+
+        let info = db.info().unwrap();
+        assert_eq!(db.path(), path.as_path());
+        assert_eq!(info.server_count, 0);
+        assert!(info.schema_version.is_some());
+    }
+}
@@ -0,0 +1,156 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use diesel::connection::SimpleConnection;
+    use diesel::{Connection, SqliteConnection};
+    use mcp_core::{
+        config_file::parse_config,
+        core::{mcp_core_builder::MCPCoreBuilder, mcp_core_proxy_ext::McpCoreProxyExt},
+        database::db_manager::DBManager,
+        models::{
+            env_key::{canonical_env_key, canonicalize_env},
+            types::{EnvValue, ServerConfigUpdateRequest, ServerDefinition},
+        },
+    };
+    use serde_json::{json, Value};
+    use tempfile::tempdir;
+
+    fn server(env: Value) -> ServerDefinition {
+        serde_json::from_value(json!({
+            "name": "Weather",
+            "description": "",
+            "enabled": true,
+            "tools_type": "node",
+            "configuration": { "command": "npx", "env": env }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_canonical_env_keys() {
+        assert_eq!(canonical_env_key(" api_key "), "API_KEY");
+        assert_eq!(canonical_env_key("DEBUG"), "DEBUG");
+
+        let env = canonicalize_env([("api_key".to_string(), 1), ("Debug".to_string(), 2)]);
+        assert_eq!(
+            env.unwrap(),
+            HashMap::from([("API_KEY".to_string(), 1), ("DEBUG".to_string(), 2)])
+        );
+
+        let error = canonicalize_env([
+            ("api_key".to_string(), 1),
+            ("API_KEY".to_string(), 2),
+            ("Token".to_string(), 3),
+            ("TOKEN".to_string(), 4),
+            ("DEBUG".to_string(), 5),
+        ])
+        .unwrap_err();
+        assert!(
+            error.contains("API_KEY, api_key; TOKEN, Token"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn test_saved_env_keys_are_canonical() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("mcp-dockmaster.db");
+        let db = DBManager::with_path(path.clone()).unwrap();
+        db.apply_migrations().unwrap();
+
+        db.save_server("weather", &server(json!({ "api_key": "k3y" })))
+            .unwrap();
+        let env = db
+            .get_server("weather")
+            .unwrap()
+            .configuration
+            .unwrap()
+            .env
+            .unwrap();
+        assert_eq!(env.keys().collect::<Vec<_>>(), vec!["API_KEY"]);
+
+        // A collision is refused before the saved env is touched
+        let error = db
+            .save_server(
+                "weather",
+                &server(json!({ "API_KEY": "a", "Api_Key": "b" })),
+            )
+            .unwrap_err();
+        assert!(error.contains("API_KEY, Api_Key"), "{}", error);
+        let env = db
+            .get_server("weather")
+            .unwrap()
+            .configuration
+            .unwrap()
+            .env
+            .unwrap();
+        assert_eq!(env["API_KEY"].default, Some(EnvValue::from("k3y")));
+
+        // Rows written around the checks still can't differ only by case
+        let mut conn = SqliteConnection::establish(&path.to_string_lossy()).unwrap();
+        let inserted = conn.batch_execute(
+            "INSERT INTO server_env (server_id, env_key, env_value, env_description)
+                VALUES ('weather', 'api_key', 'other', '');",
+        );
+        assert!(inserted.is_err());
+    }
+
+    #[test]
+    fn test_config_file_env_keys_are_canonical() {
+        let config = parse_config(
+            r#"{ "servers": { "weather": { "type": "node", "command": "npx",
+                "env": { "api_key": "secret" } } } }"#,
+            "json",
+        )
+        .unwrap();
+        assert!(config.servers["weather"].env.contains_key("API_KEY"));
+
+        let error = parse_config(
+            r#"{ "servers": { "weather": { "type": "node", "command": "npx",
+                "env": { "api_key": "a", "API_KEY": "b" } } } }"#,
+            "json",
+        )
+        .unwrap_err();
+        assert!(error.contains("weather"), "{}", error);
+        assert!(error.contains("API_KEY, api_key"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn test_config_updates_keep_one_key() {
+        let dir = tempdir().unwrap();
+        let db = DBManager::with_path(dir.path().join("mcp-dockmaster.db")).unwrap();
+        db.apply_migrations().unwrap();
+        db.save_server("weather", &server(json!({ "API_KEY": "old" })))
+            .unwrap();
+        let core = MCPCoreBuilder::new(db).build();
+
+        let update = |config: Value| ServerConfigUpdateRequest {
+            server_id: "weather".to_string(),
+            config: serde_json::from_value(config).unwrap(),
+            inherit_env: None,
+            cwd: None,
+        };
+        let response = core
+            .update_server_config(update(json!({ "api_key": "new" })))
+            .await
+            .unwrap();
+        assert!(response.success);
+        let saved = core
+            .tool_registry
+            .read()
+            .await
+            .get_server("weather")
+            .unwrap();
+        let env = saved.configuration.unwrap().env.unwrap();
+        assert_eq!(env.len(), 1);
+        assert_eq!(env["API_KEY"].default, Some(EnvValue::from("new")));
+
+        let error = core
+            .update_server_config(update(json!({ "api_key": "a", "Api_Key": "b" })))
+            .await
+            .unwrap_err();
+        assert!(error.contains("Api_Key, api_key"), "{}", error);
+    }
+}
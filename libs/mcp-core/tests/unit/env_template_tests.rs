@@ -0,0 +1,188 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use mcp_core::{
+        core::{
+            mcp_core::MCPCore, mcp_core_builder::MCPCoreBuilder,
+            mcp_core_proxy_ext::McpCoreProxyExt,
+        },
+        models::{
+            env_template::{ConfigSchema, InstallPreview},
+            types::{EnvValue, RegistryTool, ServerRegistrationRequest, ServerStatus},
+            unknown_fields::KnownFields,
+        },
+    };
+    use serde_json::{json, Value};
+    use tempfile::{tempdir, TempDir};
+
+    use crate::common::{db, FailingLauncher};
+
+    const GITHUB: &str = include_str!("../fixtures/registry/github.json");
+    const SLACK: &str = include_str!("../fixtures/registry/slack.json");
+    const POSTGRES: &str = include_str!("../fixtures/registry/postgres.json");
+
+    fn registry_tool(fixture: &str) -> RegistryTool {
+        let value: Value = serde_json::from_str(fixture).unwrap();
+        assert!(RegistryTool::unknown_fields(&value).is_empty());
+        serde_json::from_value(value).unwrap()
+    }
+
+    /// The registration the install by id builds from a registry entry
+    fn registration(tool: &RegistryTool) -> ServerRegistrationRequest {
+        ServerRegistrationRequest {
+            server_id: tool.id.clone(),
+            server_name: tool.name.clone(),
+            description: tool.description.clone(),
+            tools_type: tool.runtime.clone(),
+            configuration: Some(tool.config.clone()),
+            distribution: Some(tool.distribution.clone()),
+            provenance: None,
+            authentication: None,
+            enabled: None,
+        }
+    }
+
+    fn core() -> (TempDir, MCPCore) {
+        let dir = tempdir().unwrap();
+        let db = db(&dir);
+        let core = MCPCoreBuilder::new(db)
+            .process_launcher(Arc::new(FailingLauncher::new()))
+            .build();
+        (dir, core)
+    }
+
+    #[test]
+    fn test_registry_templates_are_parsed() {
+        let github = registry_tool(GITHUB);
+        let env = github.config.env.as_ref().unwrap();
+        let token = &env["GITHUB_PERSONAL_ACCESS_TOKEN"];
+        assert_eq!(
+            token.description,
+            "Personal access token with the repo scope"
+        );
+        assert!(token.required);
+        assert!(token.default.is_none());
+
+        let slack = registry_tool(SLACK);
+        let env = slack.config.env.as_ref().unwrap();
+        assert_eq!(env.len(), 3);
+        assert!(env["SLACK_TEAM_ID"].required);
+        assert!(!env["SLACK_CHANNEL_IDS"].required);
+
+        let postgres = registry_tool(POSTGRES);
+        let env = postgres.config.env.as_ref().unwrap();
+        assert_eq!(
+            env["POSTGRES_URL"].default,
+            Some(EnvValue::from("postgresql://localhost/postgres"))
+        );
+        assert!(!env["PGSSLMODE"].required);
+    }
+
+    #[test]
+    fn test_install_preview() {
+        let preview = InstallPreview::from_registry_tool(&registry_tool(SLACK));
+        assert_eq!(preview.command.as_deref(), Some("npx"));
+        let names: Vec<&str> = preview.env.iter().map(|env| env.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["SLACK_BOT_TOKEN", "SLACK_CHANNEL_IDS", "SLACK_TEAM_ID"]
+        );
+        assert_eq!(
+            preview.needs_configuration,
+            vec!["SLACK_BOT_TOKEN", "SLACK_TEAM_ID"]
+        );
+
+        let preview = InstallPreview::from_registry_tool(&registry_tool(POSTGRES));
+        assert!(preview.needs_configuration.is_empty());
+        let value = serde_json::to_value(&preview).unwrap();
+        assert_eq!(
+            value["env"][1],
+            json!({
+                "name": "POSTGRES_URL",
+                "description": "Connection string of the database",
+                "required": true,
+                "default": "postgresql://localhost/postgres",
+                "is_set": true
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_install_without_required_values_needs_configuration() {
+        let (_dir, core) = core();
+        let github = registry_tool(GITHUB);
+
+        let response = core.register_server(registration(&github)).await.unwrap();
+        assert!(response.success);
+        assert!(matches!(
+            response.status,
+            Some(ServerStatus::NeedsConfiguration)
+        ));
+        assert!(response
+            .warnings
+            .iter()
+            .any(|warning| warning.contains("GITHUB_PERSONAL_ACCESS_TOKEN")));
+        assert_eq!(
+            serde_json::to_value(ServerStatus::NeedsConfiguration).unwrap(),
+            "needs_configuration"
+        );
+
+        // The template is saved with its description, values are never shown
+        let saved = core
+            .tool_registry
+            .read()
+            .await
+            .get_server("github")
+            .unwrap();
+        let schema = ConfigSchema::new("github", saved.configuration.as_ref());
+        assert_eq!(schema.missing, vec!["GITHUB_PERSONAL_ACCESS_TOKEN"]);
+        assert_eq!(
+            schema.env[0].description,
+            "Personal access token with the repo scope"
+        );
+        assert!(schema.env[0].required);
+        assert!(!schema.env[0].is_set);
+
+        // Once the token is set the server is started
+        let mut server = saved;
+        server
+            .configuration
+            .as_mut()
+            .unwrap()
+            .env
+            .as_mut()
+            .unwrap()
+            .get_mut("GITHUB_PERSONAL_ACCESS_TOKEN")
+            .unwrap()
+            .default = Some(EnvValue::from("ghp_secret"));
+        core.tool_registry
+            .read()
+            .await
+            .save_server("github", &server)
+            .unwrap();
+        let schema = ConfigSchema::new("github", server.configuration.as_ref());
+        assert!(schema.missing.is_empty());
+        assert!(schema.env[0].default.is_none());
+
+        let mcp_state = core.mcp_state.read().await.clone();
+        assert!(mcp_state
+            .restart_server_with_progress("github", None)
+            .await
+            .is_err());
+        assert!(matches!(
+            mcp_state.server_status("github").await,
+            ServerStatus::Error(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_install_with_defaults_is_started() {
+        let (_dir, core) = core();
+        let response = core
+            .register_server(registration(&registry_tool(POSTGRES)))
+            .await
+            .unwrap();
+        assert!(matches!(response.status, Some(ServerStatus::Error(_))));
+    }
+}
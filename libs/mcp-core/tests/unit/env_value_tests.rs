@@ -0,0 +1,149 @@
+#[cfg(test)]
+mod tests {
+    use mcp_core::{
+        database::db_manager::DBManager,
+        models::types::{
+            EnvBoolFormat, EnvValue, ServerConfiguration, ServerDefinition, ServerEnvironment,
+        },
+    };
+    use serde_json::json;
+    use serial_test::serial;
+    use std::collections::HashMap;
+    use tempfile::tempdir;
+
+    // Helper function to set up a temporary database for testing
+    fn setup_temp_db() -> (DBManager, tempfile::TempDir) {
+        let temp_dir = tempdir().expect("Failed to create temp directory");
+
+        let db = DBManager::with_path(temp_dir.path().join("mcp-dockmaster.db"))
+            .expect("Failed to create database");
+        db.apply_migrations().expect("Failed to apply migrations");
+        (db, temp_dir)
+    }
+
+    #[test]
+    fn test_plain_values_keep_their_type() {
+        let env: HashMap<String, ServerEnvironment> = serde_json::from_value(json!({
+            "API_KEY": "secret",
+            "PORT": 8080,
+            "DEBUG": true,
+            "UNSET": null
+        }))
+        .unwrap();
+
+        assert_eq!(env["API_KEY"].default, Some(EnvValue::from("secret")));
+        assert_eq!(env["PORT"].default, Some(EnvValue::Number(8080.into())));
+        assert_eq!(env["DEBUG"].default, Some(EnvValue::Bool(true)));
+        assert_eq!(env["UNSET"].default, None);
+        assert_eq!(env["PORT"].env_string().as_deref(), Some("8080"));
+        assert_eq!(env["DEBUG"].env_string().as_deref(), Some("true"));
+    }
+
+    #[test]
+    fn test_template_objects_are_recognized() {
+        let env = ServerEnvironment::from_value(&json!({
+            "description": "Enable verbose output",
+            "default": false,
+            "required": true,
+            "bool_format": "numeric"
+        }))
+        .unwrap();
+
+        assert_eq!(env.description, "Enable verbose output");
+        assert_eq!(env.default, Some(EnvValue::Bool(false)));
+        assert!(env.required);
+        assert_eq!(env.bool_format, EnvBoolFormat::Numeric);
+        assert_eq!(env.env_string().as_deref(), Some("0"));
+
+        // A template without a value is still a template, not a raw JSON string
+        let env = ServerEnvironment::from_value(&json!({ "description": "Token" })).unwrap();
+        assert_eq!(env.default, None);
+        assert_eq!(env.env_string(), None);
+    }
+
+    #[test]
+    fn test_nested_objects_are_rejected() {
+        assert!(ServerEnvironment::from_value(&json!({ "nested": { "a": 1 } })).is_err());
+        assert!(ServerEnvironment::from_value(&json!([1, 2])).is_err());
+        assert!(ServerEnvironment::from_value(&json!({
+            "description": "bad default",
+            "default": { "a": 1 }
+        }))
+        .is_err());
+    }
+
+    #[test]
+    fn test_bool_formats() {
+        assert_eq!(
+            EnvValue::Bool(true).to_env_string(EnvBoolFormat::Literal),
+            "true"
+        );
+        assert_eq!(
+            EnvValue::Bool(true).to_env_string(EnvBoolFormat::Numeric),
+            "1"
+        );
+        assert_eq!(
+            EnvValue::Bool(false).to_env_string(EnvBoolFormat::Numeric),
+            "0"
+        );
+        // The format only applies to booleans
+        assert_eq!(
+            EnvValue::from("true").to_env_string(EnvBoolFormat::Numeric),
+            "true"
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_typed_env_values_round_trip_through_database() {
+        let (db, _temp) = setup_temp_db();
+
+        let mut env = HashMap::new();
+        env.insert(
+            "FLAG".to_string(),
+            ServerEnvironment {
+                description: "A flag".to_string(),
+                default: Some(EnvValue::Bool(true)),
+                required: false,
+                bool_format: EnvBoolFormat::Numeric,
+            },
+        );
+        env.insert(
+            "RATIO".to_string(),
+            ServerEnvironment::from_value(&json!(0.5)).unwrap(),
+        );
+        env.insert(
+            "NAME".to_string(),
+            ServerEnvironment::from_value(&json!("42")).unwrap(),
+        );
+
+        let server = ServerDefinition {
+            name: "Env Server".to_string(),
+            description: "Server with typed env".to_string(),
+            enabled: true,
+            tools_type: "node".to_string(),
+            entry_point: None,
+            configuration: Some(ServerConfiguration {
+                command: Some("node".to_string()),
+                args: None,
+                env: Some(env),
+                ..Default::default()
+            }),
+            distribution: None,
+        };
+        db.save_server("env_server", &server).unwrap();
+
+        let retrieved = db.get_server("env_server").unwrap();
+        let env = retrieved.configuration.unwrap().env.unwrap();
+
+        assert_eq!(env["FLAG"].default, Some(EnvValue::Bool(true)));
+        assert_eq!(env["FLAG"].bool_format, EnvBoolFormat::Numeric);
+        assert_eq!(env["FLAG"].env_string().as_deref(), Some("1"));
+        assert_eq!(
+            env["RATIO"].default,
+            Some(EnvValue::Number(serde_json::Number::from_f64(0.5).unwrap()))
+        );
+        // A numeric-looking string stays a string
+        assert_eq!(env["NAME"].default, Some(EnvValue::from("42")));
+    }
+}
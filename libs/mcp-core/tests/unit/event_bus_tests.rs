@@ -0,0 +1,54 @@
+#[cfg(test)]
+mod tests {
+    use mcp_core::events::{DockmasterEvent, EventBus, InstallProgressReporter, InstallStage};
+    use serde_json::json;
+
+    #[test]
+    fn test_install_progress_serialization() {
+        let bus = EventBus::new();
+        let (_, mut receiver) = bus.subscribe_with_history(|_| true);
+        let reporter = InstallProgressReporter::new(bus, "op-1".into(), "fetch".into());
+        reporter.report(InstallStage::Pulling, Some("npm fetch".into()));
+
+        let envelope = receiver.try_recv().unwrap();
+        assert_eq!(
+            serde_json::to_value(&envelope).unwrap(),
+            json!({
+                "seq": 1,
+                "event": {
+                    "type": "install_progress",
+                    "operation_id": "op-1",
+                    "server_id": "fetch",
+                    "stage": "pulling",
+                    "detail": "npm fetch"
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_late_subscriber_gets_operation_history() {
+        let bus = EventBus::new();
+        let first = InstallProgressReporter::new(bus.clone(), "op-1".into(), "a".into());
+        let second = InstallProgressReporter::new(bus.clone(), "op-2".into(), "b".into());
+        first.report(InstallStage::Resolving, None);
+        second.report(InstallStage::Resolving, None);
+        first.report(InstallStage::Starting, None);
+
+        let (history, mut receiver) =
+            bus.subscribe_with_history(|event| event.operation_id() == Some("op-1"));
+        assert_eq!(
+            history.iter().map(|e| e.seq).collect::<Vec<_>>(),
+            vec![1, 3]
+        );
+
+        first.report(InstallStage::Done, None);
+        let live = receiver.try_recv().unwrap();
+        assert_eq!(live.seq, 4);
+        assert!(live.event.is_terminal());
+        assert!(matches!(
+            live.event,
+            DockmasterEvent::InstallProgress(ref p) if p.stage == InstallStage::Done
+        ));
+    }
+}
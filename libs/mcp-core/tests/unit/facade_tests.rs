@@ -0,0 +1,38 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use mcp_core::events::ToolsUpdate;
+    use mcp_core::prelude::*;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_embed_with_the_prelude() {
+        let dir = tempdir().unwrap();
+        let db = DBManager::with_path(dir.path().join("mcp-dockmaster.db")).unwrap();
+        db.apply_migrations().unwrap();
+        let core = MCPCoreBuilder::new(db)
+            .app_name("embedder".to_string())
+            .registry_client(Arc::new(NoRegistryClient))
+            .build();
+
+        assert!(core.list_servers().await.unwrap().is_empty());
+        assert!(core.list_all_server_tools().await.unwrap().is_empty());
+        let error = core
+            .registry_cache
+            .update_registry_cache()
+            .await
+            .unwrap_err();
+        assert_eq!(error.message, "The registry is not available in this build");
+
+        let mut events = core.event_bus.subscribe();
+        core.event_bus
+            .publish(DockmasterEvent::ToolsUpdated(ToolsUpdate {
+                server_ids: vec![],
+            }));
+        assert!(matches!(
+            events.recv().await.unwrap().event,
+            DockmasterEvent::ToolsUpdated(_)
+        ));
+    }
+}
@@ -0,0 +1,178 @@
+#[cfg(test)]
+mod tests {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use async_trait::async_trait;
+    use mcp_core::{
+        core::{mcp_core_builder::MCPCoreBuilder, mcp_core_bulk_ext::McpCoreBulkExt},
+        mcp_state::{
+            launch_plan::LaunchPlan,
+            process_launcher::{LaunchedServer, ProcessLauncher},
+        },
+        models::types::ServerDefinition,
+        utils::{
+            clock::SystemClock,
+            fan_out::{fan_out, FanOutLimits},
+        },
+    };
+    use serde_json::json;
+    use tempfile::tempdir;
+    use tokio::sync::mpsc;
+
+    use crate::common::db;
+
+    type Member = Pin<Box<dyn Future<Output = u32> + Send>>;
+
+    fn after(millis: u64, value: u32) -> Member {
+        Box::pin(async move {
+            tokio::time::sleep(Duration::from_millis(millis)).await;
+            value
+        })
+    }
+
+    fn limits(member_timeout: u64, deadline: u64) -> FanOutLimits {
+        FanOutLimits {
+            member_timeout: Duration::from_millis(member_timeout),
+            deadline: Duration::from_millis(deadline),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hanging_member_times_out() {
+        let members = vec![
+            (
+                "slow".to_string(),
+                Box::pin(std::future::pending::<u32>()) as Member,
+            ),
+            ("fast".to_string(), after(0, 1)),
+            ("medium".to_string(), after(20, 2)),
+        ];
+        let fan_out = fan_out(
+            Arc::new(SystemClock),
+            members,
+            limits(200, 5000),
+            3,
+            |_, _| {},
+        )
+        .await;
+
+        assert!(fan_out.partial);
+        assert_eq!(fan_out.timed_out, vec!["slow"]);
+        assert_eq!(
+            fan_out.results,
+            vec![("fast".to_string(), 1), ("medium".to_string(), 2)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_deadline_returns_what_finished() {
+        // One at a time, the last ones don't get to start before the deadline
+        let members = (0..5)
+            .map(|i| (format!("server-{}", i), after(100, i)))
+            .collect();
+        let fan_out = fan_out(
+            Arc::new(SystemClock),
+            members,
+            limits(5000, 250),
+            1,
+            |_, _| {},
+        )
+        .await;
+
+        assert!(fan_out.partial);
+        assert_eq!(
+            fan_out.results,
+            vec![("server-0".to_string(), 0), ("server-1".to_string(), 1)]
+        );
+        assert_eq!(fan_out.timed_out, vec!["server-2", "server-3", "server-4"]);
+    }
+
+    #[tokio::test]
+    async fn test_stragglers_finish_in_the_background() {
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let members = vec![
+            ("fast".to_string(), after(0, 1)),
+            ("late".to_string(), after(300, 2)),
+        ];
+        let fan_out = fan_out(
+            Arc::new(SystemClock),
+            members,
+            limits(50, 5000),
+            2,
+            move |id, value| {
+                let _ = sender.send((id, value));
+            },
+        )
+        .await;
+        assert_eq!(fan_out.timed_out, vec!["late"]);
+
+        let late = tokio::time::timeout(Duration::from_secs(5), receiver.recv()).await;
+        assert_eq!(late.unwrap(), Some(("late".to_string(), 2)));
+    }
+
+    #[tokio::test]
+    async fn test_complete_fan_out() {
+        let members = vec![
+            ("a".to_string(), after(0, 1)),
+            ("b".to_string(), after(10, 2)),
+        ];
+        let fan_out = fan_out(
+            Arc::new(SystemClock),
+            members,
+            FanOutLimits::default(),
+            1,
+            |_, _| {},
+        )
+        .await;
+        assert!(!fan_out.partial);
+        assert!(fan_out.timed_out.is_empty());
+        assert_eq!(fan_out.results.len(), 2);
+    }
+
+    /// Launcher that never starts "slow" and fails to start the others
+    struct OneHangingLauncher;
+
+    #[async_trait]
+    impl ProcessLauncher for OneHangingLauncher {
+        async fn launch(&self, plan: &LaunchPlan, _: &str) -> Result<LaunchedServer, String> {
+            if plan.server_id == "slow" {
+                std::future::pending().await
+            }
+            Err("not started in tests".to_string())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_bulk_restart_with_a_hanging_server() {
+        let dir = tempdir().unwrap();
+        let db = db(&dir);
+        let server: ServerDefinition = serde_json::from_value(json!({
+            "name": "Echo",
+            "description": "",
+            "enabled": true,
+            "tools_type": "node",
+            "configuration": { "command": "node", "args": ["echo.js"] }
+        }))
+        .unwrap();
+        for id in ["fast", "slow"] {
+            db.save_server(id, &server).unwrap();
+        }
+        let core = MCPCoreBuilder::new(db)
+            .process_launcher(Arc::new(OneHangingLauncher))
+            .fan_out_limits(limits(200, 2000))
+            .build();
+
+        let response = core
+            .restart_servers(Some(vec!["slow".to_string(), "fast".to_string()]))
+            .await
+            .unwrap();
+        assert!(response.partial);
+        assert_eq!(response.timed_out, vec!["slow"]);
+        assert_eq!(response.results.len(), 1);
+        assert_eq!(response.results[0].server_id, "fast");
+        assert!(!response.results[0].success);
+    }
+}
@@ -0,0 +1,185 @@
+#[cfg(test)]
+mod tests {
+    use mcp_core::{
+        core::{
+            mcp_core_builder::MCPCoreBuilder, mcp_core_dashboard_ext::McpCoreDashboardExt,
+            mcp_core_footprint_ext::McpCoreFootprintExt,
+        },
+        database::db_manager::DBManager,
+        events::DockmasterEvent,
+        footprint::{advertised_tool, measure, CharsPerToken, TokenEstimator},
+        mcp_server::tools::BUILTIN_SERVER_ID,
+        models::types::ServerToolInfo,
+    };
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    /// Counts every byte as a token, to check sizes by hand
+    struct BytesPerToken;
+
+    impl TokenEstimator for BytesPerToken {
+        fn estimate(&self, text: &str) -> usize {
+            text.len()
+        }
+    }
+
+    /// A tool whose description makes it `size` bytes larger
+    fn tool(server_id: &str, name: &str, size: usize) -> ServerToolInfo {
+        ServerToolInfo::from_spec_value(
+            server_id,
+            json!({ "name": name, "description": "x".repeat(size) }),
+        )
+        .unwrap()
+    }
+
+    fn bytes(tool: &ServerToolInfo) -> usize {
+        advertised_tool(tool).to_string().len()
+    }
+
+    #[test]
+    fn test_chars_per_token() {
+        assert_eq!(CharsPerToken.estimate(""), 0);
+        assert_eq!(CharsPerToken.estimate("abcd"), 1);
+        assert_eq!(CharsPerToken.estimate("abcde"), 2);
+        // Characters rather than bytes
+        assert_eq!(CharsPerToken.estimate("éééé"), 1);
+    }
+
+    #[test]
+    fn test_advertised_tool() {
+        let mut tool = tool("files", "read", 4);
+        assert_eq!(
+            advertised_tool(&tool),
+            json!({ "name": "read", "description": "xxxx", "inputSchema": { "type": "object" } })
+        );
+        tool.annotations = serde_json::from_value(json!({ "readOnlyHint": true })).unwrap();
+        assert_eq!(advertised_tool(&tool)["annotations"]["readOnlyHint"], true);
+    }
+
+    #[test]
+    fn test_measure_per_server() {
+        let tools = vec![
+            tool("files", "read", 100),
+            tool("files", "write", 300),
+            tool("weather", "forecast", 50),
+        ];
+        let footprint = measure(&tools, &BytesPerToken, None, 2);
+
+        let total: usize = tools.iter().map(bytes).sum();
+        assert_eq!(footprint.bytes, total);
+        assert_eq!(footprint.tokens, total);
+        assert!(!footprint.over_budget);
+        assert!(footprint.servers_over_budget().is_empty());
+
+        let servers: Vec<(&str, usize, usize)> = footprint
+            .servers
+            .iter()
+            .map(|server| (server.server_id.as_str(), server.tools, server.bytes))
+            .collect();
+        assert_eq!(
+            servers,
+            vec![
+                ("files", 2, bytes(&tools[0]) + bytes(&tools[1])),
+                ("weather", 1, bytes(&tools[2])),
+            ]
+        );
+
+        let top: Vec<&str> = footprint
+            .top_tools
+            .iter()
+            .map(|tool| tool.tool_name.as_str())
+            .collect();
+        assert_eq!(top, vec!["write", "read"]);
+        assert_eq!(footprint.top_tools[0].bytes, bytes(&tools[1]));
+    }
+
+    #[test]
+    fn test_measure_over_budget() {
+        let tools = vec![
+            tool(BUILTIN_SERVER_ID, "register_server", 2000),
+            tool("github", "create_issue", 1000),
+            tool("files", "read", 500),
+            tool("weather", "forecast", 100),
+        ];
+        let total: usize = tools.iter().map(bytes).sum();
+
+        // Hiding github is enough, the built-in tools are never flagged
+        let footprint = measure(&tools, &BytesPerToken, Some(total - 200), 10);
+        assert!(footprint.over_budget);
+        assert_eq!(footprint.budget, Some(total - 200));
+        assert_eq!(footprint.servers_over_budget(), vec!["github"]);
+
+        // Then the next largest until the excess is covered
+        let budget = total - bytes(&tools[1]) - 200;
+        let footprint = measure(&tools, &BytesPerToken, Some(budget), 10);
+        assert_eq!(footprint.servers_over_budget(), vec!["github", "files"]);
+
+        // Exactly at the budget is not over it
+        let footprint = measure(&tools, &BytesPerToken, Some(total), 10);
+        assert!(!footprint.over_budget);
+        assert!(footprint.servers_over_budget().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_tools_budget() {
+        let dir = tempdir().unwrap();
+        let db = DBManager::with_path(dir.path().join("mcp-dockmaster.db")).unwrap();
+        db.apply_migrations().unwrap();
+        let core = MCPCoreBuilder::new(db).build();
+        {
+            let mcp_state = core.mcp_state.read().await;
+            let mut server_tools = mcp_state.server_tools.write().await;
+            // Descriptions stay under the length they are cut to
+            let github = (0..10)
+                .map(|i| tool("github", &format!("tool_{}", i), 1000))
+                .collect();
+            server_tools.insert("github".to_string(), github);
+            server_tools.insert("weather".to_string(), vec![tool("weather", "forecast", 40)]);
+        }
+        let mut events = core.event_bus.subscribe();
+
+        assert_eq!(core.tools_budget().await, None);
+        let footprint = core.tools_footprint(3).await.unwrap();
+        assert_eq!(footprint.top_tools.len(), 3);
+        assert_eq!(footprint.servers[0].server_id, "github");
+        assert!(footprint
+            .servers
+            .iter()
+            .any(|server| server.server_id == BUILTIN_SERVER_ID));
+
+        // Without a budget nothing is flagged, but the dashboard has the size
+        core.check_tools_budget().await;
+        let summary = core.dashboard_summary().await.unwrap();
+        assert_eq!(summary.tools_tokens, footprint.tokens);
+        assert!(summary.servers_over_budget.is_empty());
+        assert!(events.try_recv().is_err());
+
+        core.set_tools_budget(Some(footprint.tokens - 100))
+            .await
+            .unwrap();
+        assert_eq!(core.tools_budget().await, Some(footprint.tokens - 100));
+        match events.try_recv().unwrap().event {
+            DockmasterEvent::ToolsBudgetExceeded(warning) => {
+                assert_eq!(warning.budget, footprint.tokens - 100);
+                assert_eq!(warning.tokens, footprint.tokens);
+                assert_eq!(warning.server_ids, vec!["github"]);
+            }
+            event => panic!("unexpected event {:?}", event),
+        }
+        let summary = core.dashboard_summary().await.unwrap();
+        assert_eq!(summary.servers_over_budget, vec!["github"]);
+
+        // Warned once, until the servers over the budget change
+        core.check_tools_budget().await;
+        assert!(events.try_recv().is_err());
+
+        core.set_tools_budget(None).await.unwrap();
+        assert_eq!(core.tools_budget().await, None);
+        assert!(core
+            .dashboard_summary()
+            .await
+            .unwrap()
+            .servers_over_budget
+            .is_empty());
+    }
+}
@@ -0,0 +1,114 @@
+#[cfg(test)]
+mod tests {
+    use mcp_core::{
+        core::{mcp_core_builder::MCPCoreBuilder, mcp_core_health_ext::McpCoreHealthExt},
+        database::db_manager::DBManager,
+        health::{
+            HealthCheckSettings, HealthMonitor, DEFAULT_HEALTH_CHECK_INTERVAL_SECS,
+            DEFAULT_UNHEALTHY_AFTER, HEALTH_CHECK_SETTING,
+        },
+    };
+    use serde_json::json;
+    use std::time::Duration;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_failures_in_a_row_make_a_server_unhealthy() {
+        let monitor = HealthMonitor::new();
+        assert!(monitor.get("slow").is_none());
+
+        let health = monitor.record_failure("slow", 1, "timed out".to_string(), 2);
+        assert!(health.healthy);
+        assert_eq!(health.consecutive_failures, 1);
+        let health = monitor.record_failure("slow", 2, "timed out".to_string(), 2);
+        assert!(!health.healthy);
+        assert_eq!(health.consecutive_failures, 2);
+        assert_eq!(monitor.get("slow"), Some(health));
+
+        // An answer resets the count
+        monitor.record_success("slow", 3);
+        let health = monitor.get("slow").unwrap();
+        assert!(health.healthy);
+        assert_eq!(health.consecutive_failures, 0);
+        assert_eq!(health.last_health_check, 3);
+        assert_eq!(health.last_error, None);
+
+        monitor.remove("slow");
+        assert!(monitor.get("slow").is_none());
+    }
+
+    #[test]
+    fn test_settings_defaults() {
+        let settings: HealthCheckSettings =
+            serde_json::from_value(json!({ "restart_unhealthy": true })).unwrap();
+        assert_eq!(settings.interval_secs, DEFAULT_HEALTH_CHECK_INTERVAL_SECS);
+        assert_eq!(settings.unhealthy_after, DEFAULT_UNHEALTHY_AFTER);
+        assert!(settings.restart_unhealthy);
+        assert_eq!(
+            settings.interval(),
+            Some(Duration::from_secs(DEFAULT_HEALTH_CHECK_INTERVAL_SECS))
+        );
+
+        let off = HealthCheckSettings {
+            interval_secs: 0,
+            ..Default::default()
+        };
+        assert_eq!(off.interval(), None);
+    }
+
+    #[tokio::test]
+    async fn test_settings_are_saved() {
+        let dir = tempdir().unwrap();
+        let db = DBManager::with_path(dir.path().join("mcp-dockmaster.db")).unwrap();
+        db.apply_migrations().unwrap();
+        let core = MCPCoreBuilder::new(db).build();
+        assert_eq!(
+            core.health_check_settings().await,
+            HealthCheckSettings::default()
+        );
+
+        let settings = HealthCheckSettings {
+            interval_secs: 5,
+            unhealthy_after: 2,
+            restart_unhealthy: true,
+        };
+        core.set_health_check_settings(settings.clone())
+            .await
+            .unwrap();
+        assert_eq!(core.health_check_settings().await, settings);
+
+        let never_unhealthy = HealthCheckSettings {
+            unhealthy_after: 0,
+            ..Default::default()
+        };
+        assert!(core
+            .set_health_check_settings(never_unhealthy)
+            .await
+            .is_err());
+
+        // Unreadable settings fall back to the defaults
+        core.tool_registry
+            .read()
+            .await
+            .save_setting(HEALTH_CHECK_SETTING, "every minute")
+            .unwrap();
+        assert_eq!(
+            core.health_check_settings().await,
+            HealthCheckSettings::default()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_no_running_server_to_check() {
+        let dir = tempdir().unwrap();
+        let db = DBManager::with_path(dir.path().join("mcp-dockmaster.db")).unwrap();
+        db.apply_migrations().unwrap();
+        let core = MCPCoreBuilder::new(db).build();
+        let mcp_state = core.mcp_state.read().await.clone();
+        let settings = HealthCheckSettings {
+            restart_unhealthy: true,
+            ..Default::default()
+        };
+        assert!(mcp_state.check_server_health(&settings).await.is_empty());
+    }
+}
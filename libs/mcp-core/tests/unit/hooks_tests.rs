@@ -0,0 +1,256 @@
+#[cfg(test)]
+mod tests {
+    use mcp_core::{
+        core::{
+            mcp_core_approvals_ext::McpCoreApprovalsExt, mcp_core_builder::MCPCoreBuilder,
+            mcp_core_hooks_ext::McpCoreHooksExt, mcp_core_proxy_ext::McpCoreProxyExt,
+            mcp_core_recordings_ext::McpCoreRecordingsExt,
+        },
+        database::db_manager::DBManager,
+        hooks::{
+            parse_hook_reply, HookFailurePolicy, HookOutcome, HookStage, HooksConfig, ToolHook,
+            DEFAULT_HOOK_TIMEOUT_MS,
+        },
+        models::types::{
+            AuditAction, AuditDecision, CallMode, ReplayMiss, ServerCallMode, ServerDefinition,
+            ToolExecutionRequest,
+        },
+        recordings::RecordedCall,
+    };
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    /// Hook running a shell script
+    fn hook(id: &str, stage: HookStage, script: &str) -> ToolHook {
+        ToolHook {
+            id: id.to_string(),
+            stage,
+            server_id: None,
+            command: "sh".to_string(),
+            args: vec!["-c".to_string(), script.to_string()],
+            timeout_ms: DEFAULT_HOOK_TIMEOUT_MS,
+            on_failure: HookFailurePolicy::FailClosed,
+            enabled: true,
+        }
+    }
+
+    fn server() -> ServerDefinition {
+        serde_json::from_value(json!({
+            "name": "Weather",
+            "description": "",
+            "enabled": false,
+            "tools_type": "node",
+            "configuration": { "command": "node", "args": ["weather.js"] }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_hook_reply() {
+        assert_eq!(parse_hook_reply("").unwrap(), HookOutcome::Pass);
+        assert_eq!(
+            parse_hook_reply(r#"{"decision":"pass"}"#).unwrap(),
+            HookOutcome::Pass
+        );
+        assert_eq!(
+            parse_hook_reply(r#"{"decision":"modify","value":{"city":"***"}}"#).unwrap(),
+            HookOutcome::Modify(json!({ "city": "***" }))
+        );
+        assert_eq!(
+            parse_hook_reply(r#"{"decision":"reject","reason":"PII"}"#).unwrap(),
+            HookOutcome::Reject("PII".to_string())
+        );
+        assert!(parse_hook_reply("ok").is_err());
+        assert!(parse_hook_reply(r#"{"decision":"maybe"}"#).is_err());
+    }
+
+    #[test]
+    fn test_hooks_config() {
+        let config: HooksConfig = serde_json::from_value(json!({
+            "hooks": [{ "id": "pii", "stage": "pre", "command": "strip-pii" }]
+        }))
+        .unwrap();
+        let pii = &config.hooks[0];
+        assert_eq!(pii.timeout_ms, DEFAULT_HOOK_TIMEOUT_MS);
+        assert_eq!(pii.on_failure, HookFailurePolicy::FailClosed);
+        assert!(pii.applies_to(HookStage::Pre, "weather"));
+        assert!(!pii.applies_to(HookStage::Post, "weather"));
+        assert!(config.validate().is_ok());
+
+        let mut scoped = pii.clone();
+        scoped.server_id = Some("files".to_string());
+        assert!(!scoped.applies_to(HookStage::Pre, "weather"));
+
+        let duplicate = HooksConfig {
+            hooks: vec![pii.clone(), scoped],
+        };
+        assert_eq!(duplicate.validate().unwrap_err(), "Duplicate hook id 'pii'");
+        assert!(serde_json::from_value::<HooksConfig>(json!({
+            "hooks": [{ "id": "pii", "stage": "pre", "command": "x", "timeout": 1 }]
+        }))
+        .is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_pre_hooks_change_or_reject_calls() {
+        let dir = tempdir().unwrap();
+        let db = DBManager::with_path(dir.path().join("mcp-dockmaster.db")).unwrap();
+        db.apply_migrations().unwrap();
+        let core = MCPCoreBuilder::new(db).build();
+        let arguments = json!({ "email": "me@example.com" });
+
+        let strip = hook(
+            "strip",
+            HookStage::Pre,
+            r#"cat > /dev/null; echo '{"decision":"modify","value":{"email":"***"}}'"#,
+        );
+        let mut block = hook(
+            "block",
+            HookStage::Pre,
+            r#"echo '{"decision":"reject","reason":"No emails"}'"#,
+        );
+        block.server_id = Some("files".to_string());
+        core.set_tool_hooks(HooksConfig {
+            hooks: vec![strip, block],
+        })
+        .await
+        .unwrap();
+
+        let changed = core
+            .run_tool_hooks(HookStage::Pre, "weather", "forecast", &arguments, None)
+            .await
+            .unwrap();
+        assert_eq!(changed, json!({ "email": "***" }));
+
+        let error = core
+            .run_tool_hooks(HookStage::Pre, "files", "read", &arguments, None)
+            .await
+            .unwrap_err();
+        assert_eq!(error.hook, "block");
+        assert_eq!(error.reason, "No emails");
+
+        // The rejection reaches the client before the call reaches any server
+        let response = core
+            .execute_proxy_tool(ToolExecutionRequest {
+                tool_id: "files:read".to_string(),
+                parameters: arguments.clone(),
+            })
+            .await
+            .unwrap();
+        assert!(!response.success);
+        assert_eq!(
+            response.error.as_deref(),
+            Some("Rejected by hook 'block': No emails")
+        );
+        assert_eq!(response.policy_error.unwrap().stage, HookStage::Pre);
+
+        let log = core.audit_log(10).await.unwrap();
+        assert!(log
+            .iter()
+            .all(|entry| entry.action == AuditAction::ToolCall));
+        let decisions: Vec<AuditDecision> = log.iter().map(|entry| entry.decision).collect();
+        assert_eq!(
+            decisions,
+            vec![
+                AuditDecision::Denied,
+                AuditDecision::Modified,
+                AuditDecision::Denied,
+                AuditDecision::Modified
+            ]
+        );
+        assert_eq!(
+            log[0].detail.as_deref(),
+            Some("Hook 'block' (pre) on read: No emails")
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_failing_hooks_follow_their_policy() {
+        let dir = tempdir().unwrap();
+        let db = DBManager::with_path(dir.path().join("mcp-dockmaster.db")).unwrap();
+        db.apply_migrations().unwrap();
+        let core = MCPCoreBuilder::new(db).build();
+        let arguments = json!({});
+
+        let mut slow = hook("slow", HookStage::Pre, "sleep 5");
+        slow.timeout_ms = 100;
+        slow.on_failure = HookFailurePolicy::FailOpen;
+        core.set_tool_hooks(HooksConfig { hooks: vec![slow] })
+            .await
+            .unwrap();
+        let started = std::time::Instant::now();
+        assert_eq!(
+            core.run_tool_hooks(HookStage::Pre, "weather", "forecast", &arguments, None)
+                .await
+                .unwrap(),
+            arguments
+        );
+        assert!(started.elapsed() < std::time::Duration::from_secs(4));
+
+        let crash = hook("crash", HookStage::Pre, "echo broken >&2; exit 3");
+        core.set_tool_hooks(HooksConfig { hooks: vec![crash] })
+            .await
+            .unwrap();
+        let error = core
+            .run_tool_hooks(HookStage::Pre, "weather", "forecast", &arguments, None)
+            .await
+            .unwrap_err();
+        assert!(
+            error.reason.starts_with("The hook failed"),
+            "{}",
+            error.reason
+        );
+        assert!(error.reason.contains("broken"));
+
+        let log = core.audit_log(10).await.unwrap();
+        assert_eq!(log[0].decision, AuditDecision::Denied);
+        assert_eq!(log[1].decision, AuditDecision::Allowed);
+        assert!(log[1]
+            .detail
+            .as_deref()
+            .unwrap()
+            .contains("passing the call"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_post_hooks_change_replayed_results() {
+        let dir = tempdir().unwrap();
+        let db = DBManager::with_path(dir.path().join("mcp-dockmaster.db")).unwrap();
+        db.apply_migrations().unwrap();
+        db.save_server("weather", &server()).unwrap();
+        let core = MCPCoreBuilder::new(db).build();
+
+        let replay = ServerCallMode {
+            mode: CallMode::Replay,
+            on_miss: ReplayMiss::Error,
+        };
+        core.set_server_call_mode("weather", replay).await.unwrap();
+        core.recording_store("weather")
+            .await
+            .append(&RecordedCall::new("forecast", &json!({}), json!("rain")))
+            .unwrap();
+        let watermark = hook(
+            "watermark",
+            HookStage::Post,
+            r#"echo '{"decision":"modify","value":"rain (via dockmaster)"}'"#,
+        );
+        core.set_tool_hooks(HooksConfig {
+            hooks: vec![watermark],
+        })
+        .await
+        .unwrap();
+
+        let response = core
+            .execute_proxy_tool(ToolExecutionRequest {
+                tool_id: "weather:forecast".to_string(),
+                parameters: json!({}),
+            })
+            .await
+            .unwrap();
+        assert_eq!(response.result, Some(json!("rain (via dockmaster)")));
+        assert!(response.policy_error.is_none());
+    }
+}
@@ -0,0 +1,172 @@
+#[cfg(test)]
+mod tests {
+    use mcp_core::{
+        core::{
+            mcp_core::MCPCore, mcp_core_builder::MCPCoreBuilder,
+            mcp_core_identity_ext::McpCoreIdentityExt,
+        },
+        database::db_manager::DBManager,
+        identity::{
+            servers_summary, ServerIdentity, ServerIdentityOverrides, DEFAULT_SERVER_NAME,
+            SUMMARY_MAX_SERVERS,
+        },
+        mcp_server::mcp_tools_service::MCPToolsService,
+        models::types::ServerToolInfo,
+    };
+    use serde_json::json;
+    use tempfile::{tempdir, TempDir};
+
+    fn workspace_identity() -> ServerIdentity {
+        ServerIdentity {
+            name: "acme-workspace".to_string(),
+            version: "2.1.0".to_string(),
+            instructions: "Tools of the Acme workspace.".to_string(),
+            summarize_servers: true,
+        }
+    }
+
+    fn core(identity: ServerIdentity) -> (MCPCore, TempDir) {
+        let dir = tempdir().unwrap();
+        let db = DBManager::with_path(dir.path().join("mcp-dockmaster.db")).unwrap();
+        db.apply_migrations().unwrap();
+        let core = MCPCoreBuilder::new(db).server_identity(identity).build();
+        (core, dir)
+    }
+
+    fn tools(server_id: &str, count: usize) -> Vec<ServerToolInfo> {
+        (0..count)
+            .map(|i| {
+                ServerToolInfo::from_spec_value(server_id, json!({ "name": format!("tool_{}", i) }))
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_servers_summary() {
+        assert_eq!(servers_summary(&[]), "");
+        assert_eq!(
+            servers_summary(&[("filesystem".to_string(), 1), ("github".to_string(), 14)]),
+            "Aggregates: filesystem (1 tool), github (14 tools)."
+        );
+
+        let servers: Vec<(String, usize)> = (0..SUMMARY_MAX_SERVERS + 2)
+            .map(|i| (format!("server-{}", i), 2))
+            .collect();
+        let summary = servers_summary(&servers);
+        assert!(summary.ends_with(" and 2 more servers."), "{}", summary);
+        assert!(!summary.contains(&format!("server-{} ", SUMMARY_MAX_SERVERS)));
+    }
+
+    #[test]
+    fn test_overrides() {
+        let identity = ServerIdentity::default();
+        assert_eq!(identity.name, DEFAULT_SERVER_NAME);
+        assert_eq!(identity.version, env!("CARGO_PKG_VERSION"));
+
+        let overrides = ServerIdentityOverrides {
+            name: Some(" personal ".to_string()),
+            summarize_servers: Some(false),
+            ..Default::default()
+        };
+        let identity = workspace_identity().with_overrides(&overrides);
+        assert_eq!(identity.name, "personal");
+        assert_eq!(identity.version, "2.1.0");
+        assert!(!identity.summarize_servers);
+        assert_eq!(
+            identity.advertised_instructions(&[("github".to_string(), 3)]),
+            "Tools of the Acme workspace."
+        );
+
+        let identity = workspace_identity();
+        assert_eq!(
+            identity.advertised_instructions(&[("github".to_string(), 3)]),
+            "Tools of the Acme workspace.\n\nAggregates: github (3 tools)."
+        );
+        assert_eq!(
+            identity.advertised_instructions(&[]),
+            "Tools of the Acme workspace."
+        );
+
+        let blank = ServerIdentityOverrides {
+            version: Some(" ".to_string()),
+            ..Default::default()
+        };
+        assert!(blank.validate().is_err());
+        assert!(overrides.validate().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_profiles_present_their_own_identity() {
+        let (work, _work_dir) = core(workspace_identity());
+        let (personal, _personal_dir) = core(ServerIdentity::default());
+        assert_eq!(work.server_identity().await, workspace_identity());
+        assert_eq!(personal.server_identity().await, ServerIdentity::default());
+
+        // The user's changes are saved with the profile
+        let identity = work
+            .set_server_identity_overrides(ServerIdentityOverrides {
+                version: Some("3.0.0".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(identity.name, "acme-workspace");
+        assert_eq!(identity.version, "3.0.0");
+        assert_eq!(work.server_identity().await.version, "3.0.0");
+        assert_eq!(
+            personal.server_identity().await.version,
+            env!("CARGO_PKG_VERSION")
+        );
+
+        let blank = ServerIdentityOverrides {
+            name: Some(String::new()),
+            ..Default::default()
+        };
+        assert!(work.set_server_identity_overrides(blank).await.is_err());
+        assert_eq!(work.server_identity().await.name, "acme-workspace");
+    }
+
+    #[tokio::test]
+    async fn test_advertised_identity_follows_the_tools() {
+        let (core, _dir) = core(workspace_identity());
+        let service = MCPToolsService::new(core.clone());
+        assert_eq!(service.identity(), workspace_identity());
+
+        {
+            let mcp_state = core.mcp_state.read().await;
+            let mut server_tools = mcp_state.server_tools.write().await;
+            server_tools.insert("github".to_string(), tools("github", 14));
+            server_tools.insert("filesystem".to_string(), tools("filesystem", 1));
+            server_tools.insert("idle".to_string(), Vec::new());
+        }
+        service.update_cache().await.unwrap();
+        assert_eq!(
+            service.identity().instructions,
+            "Tools of the Acme workspace.\n\nAggregates: filesystem (1 tool), github (14 tools)."
+        );
+
+        // The SDK's version is replaced in `initialize` results
+        let mut message = json!({
+            "jsonrpc": "2.0",
+            "id": 0,
+            "result": { "serverInfo": { "name": "acme-workspace", "version": "0.1.0" } }
+        });
+        assert!(service.identify_server(&mut message));
+        assert_eq!(message["result"]["serverInfo"]["version"], "2.1.0");
+        assert!(!service.identify_server(&mut message));
+        assert!(!service.identify_server(&mut json!({ "result": { "tools": [] } })));
+
+        core.mcp_state
+            .read()
+            .await
+            .set_tools_hidden(true)
+            .await
+            .unwrap();
+        service.update_cache().await.unwrap();
+        assert_eq!(
+            service.identity().instructions,
+            "Tools of the Acme workspace."
+        );
+    }
+}
@@ -0,0 +1,102 @@
+#[cfg(test)]
+mod tests {
+    use mcp_core::{
+        database::db_manager::DBManager,
+        models::types::{InputSchema, ServerDefinition, ServerToolInfo},
+    };
+    use serde_json::{json, Value};
+    use tempfile::tempdir;
+
+    /// Tools as listed by popular servers
+    const SERVERS: [(&str, &str); 4] = [
+        (
+            "filesystem",
+            include_str!("../fixtures/schemas/filesystem.json"),
+        ),
+        ("github", include_str!("../fixtures/schemas/github.json")),
+        (
+            "puppeteer",
+            include_str!("../fixtures/schemas/puppeteer.json"),
+        ),
+        ("fetch", include_str!("../fixtures/schemas/fetch.json")),
+    ];
+
+    fn spec_tools(text: &str) -> Vec<Value> {
+        serde_json::from_str(text).expect("fixture should be a list of tools")
+    }
+
+    #[test]
+    fn test_schemas_reach_clients_unchanged() {
+        for (server_id, text) in SERVERS {
+            for spec in spec_tools(text) {
+                let tool = ServerToolInfo::from_spec_value(server_id, spec.clone()).unwrap();
+                let schema = &spec["inputSchema"];
+                // `tools/list` of the HTTP server and of MCP sessions
+                assert_eq!(&serde_json::to_value(&tool).unwrap()["inputSchema"], schema);
+                assert_eq!(&tool.input_schema_value(), schema, "{}", tool.name);
+
+                // The tools cache and snapshot read their tools back
+                let cached: ServerToolInfo =
+                    serde_json::from_value(serde_json::to_value(&tool).unwrap()).unwrap();
+                assert_eq!(&cached.input_schema_value(), schema, "{}", tool.name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_keywords_keep_their_json() {
+        let schema: InputSchema = serde_json::from_value(json!({
+            "anyOf": [{ "required": ["path"] }, { "required": ["url"] }],
+            "properties": {
+                "limit": { "type": "integer", "minimum": 1, "exclusiveMaximum": true },
+                "cursor": { "type": ["string", "null"], "default": null }
+            }
+        }))
+        .unwrap();
+        let limit = &schema.properties["limit"];
+        assert_eq!(limit.minimum, Some(json!(1)));
+        assert_eq!(limit.exclusive_maximum, Some(json!(true)));
+        assert_eq!(schema.properties["cursor"].default, Some(Value::Null));
+
+        // No type is made up for schemas that don't set one
+        let value = serde_json::to_value(&schema).unwrap();
+        assert!(value.get("type").is_none());
+        assert_eq!(value["properties"]["limit"]["minimum"], json!(1));
+        assert_eq!(value["properties"]["cursor"]["default"], Value::Null);
+        assert_eq!(
+            serde_json::to_value(InputSchema::default()).unwrap(),
+            json!({ "type": "object" })
+        );
+    }
+
+    #[test]
+    fn test_saved_schemas_read_back_unchanged() {
+        let dir = tempdir().unwrap();
+        let db = DBManager::with_path(dir.path().join("mcp-dockmaster.db")).unwrap();
+        db.apply_migrations().unwrap();
+
+        for (server_id, text) in SERVERS {
+            let server: ServerDefinition = serde_json::from_value(json!({
+                "name": server_id,
+                "description": "",
+                "enabled": true,
+                "tools_type": "node",
+                "configuration": { "command": "node" }
+            }))
+            .unwrap();
+            db.save_server(server_id, &server).unwrap();
+
+            for spec in spec_tools(text) {
+                let tool = ServerToolInfo::from_spec_value(server_id, spec.clone()).unwrap();
+                db.save_server_tool(&tool).unwrap();
+                let saved = db.get_server_tool(&tool.id, server_id).unwrap();
+                assert_eq!(
+                    saved.input_schema_value(),
+                    spec["inputSchema"],
+                    "{}",
+                    tool.name
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,109 @@
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use mcp_core::{
+        core::mcp_core_builder::MCPCoreBuilder,
+        database::db_manager::DBManager,
+        instance_lock::{
+            read_lock, InstanceLock, InstanceLockError, InstanceLockInfo, INSTANCE_LOCK_FILE,
+        },
+    };
+    use tempfile::tempdir;
+
+    /// Lock of an instance that crashed, its pid is not running
+    fn crashed_instance() -> InstanceLockInfo {
+        InstanceLockInfo {
+            pid: u32::MAX - 1,
+            process_start_time: 1,
+            port: 11012,
+            app_name: "mcp-dockmaster-cli".to_string(),
+            locked_at: 1_700_000_000_000,
+        }
+    }
+
+    #[test]
+    fn test_second_instance_is_refused() {
+        let dir = tempdir().unwrap();
+        let lock = InstanceLock::acquire(dir.path(), 11011, "mcp-dockmaster").unwrap();
+        assert_eq!(lock.info().pid, std::process::id());
+        assert_eq!(read_lock(lock.path()).as_ref(), Some(lock.info()));
+
+        let error = InstanceLock::acquire(dir.path(), 11012, "mcp-dockmaster-cli").unwrap_err();
+        let InstanceLockError::Held(holder) = &error else {
+            panic!("{:?}", error);
+        };
+        assert_eq!(holder, lock.info());
+        let message = error.to_string();
+        assert!(
+            message.contains("http://127.0.0.1:11011/mcp-proxy"),
+            "{}",
+            message
+        );
+
+        // Released when dropped
+        drop(lock);
+        assert!(!dir.path().join(INSTANCE_LOCK_FILE).exists());
+        InstanceLock::acquire(dir.path(), 11012, "mcp-dockmaster-cli").unwrap();
+    }
+
+    #[test]
+    fn test_stale_lock_is_replaced() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(INSTANCE_LOCK_FILE);
+        fs::write(&path, serde_json::to_string(&crashed_instance()).unwrap()).unwrap();
+        assert!(!crashed_instance().is_alive());
+
+        let lock = InstanceLock::acquire(dir.path(), 11011, "mcp-dockmaster").unwrap();
+        assert_eq!(read_lock(&path).unwrap().pid, std::process::id());
+        drop(lock);
+
+        // A lock file cut short by a crash is replaced too
+        fs::write(&path, "{\"pid\": 12").unwrap();
+        let lock = InstanceLock::acquire(dir.path(), 11011, "mcp-dockmaster").unwrap();
+        assert_eq!(read_lock(&path).as_ref(), Some(lock.info()));
+    }
+
+    #[test]
+    fn test_liveness_decides() {
+        let dir = tempdir().unwrap();
+        let other = InstanceLock::acquire_with(dir.path(), crashed_instance(), |_| true).unwrap();
+
+        let mine = InstanceLockInfo::current(11011, "mcp-dockmaster");
+        let error = InstanceLock::acquire_with(dir.path(), mine.clone(), |_| true).unwrap_err();
+        assert_eq!(error, InstanceLockError::Held(crashed_instance()));
+
+        let lock = InstanceLock::acquire_with(dir.path(), mine.clone(), |_| false).unwrap();
+        assert_eq!(lock.info(), &mine);
+
+        // The lock that was replaced doesn't remove the new one
+        drop(other);
+        assert_eq!(read_lock(lock.path()), Some(mine));
+    }
+
+    #[tokio::test]
+    async fn test_cores_on_the_same_data_dir() {
+        let dir = tempdir().unwrap();
+        let core = |port| {
+            let db = DBManager::with_path(dir.path().join("mcp-dockmaster.db")).unwrap();
+            MCPCoreBuilder::new(db).port(port).build()
+        };
+        let app = core(11011);
+        let cli = core(11012);
+
+        app.acquire_instance_lock().await.unwrap();
+        // Taking it again is a no-op
+        app.acquire_instance_lock().await.unwrap();
+        let error = cli.acquire_instance_lock().await.unwrap_err();
+        assert!(matches!(&error, InstanceLockError::Held(holder) if holder.port == 11011));
+
+        app.release_instance_lock();
+        cli.acquire_instance_lock().await.unwrap();
+        assert_eq!(
+            read_lock(&dir.path().join(INSTANCE_LOCK_FILE))
+                .unwrap()
+                .port,
+            11012
+        );
+    }
+}
@@ -0,0 +1,148 @@
+#[cfg(test)]
+mod tests {
+    use bytes::BytesMut;
+    use mcp_core::json_message_codec::{JsonMessageCodec, StdoutMessage};
+    use serde_json::json;
+    use tokio_util::codec::Decoder;
+
+    fn decode_all(codec: &mut JsonMessageCodec, src: &mut BytesMut) -> Vec<StdoutMessage> {
+        let mut messages = Vec::new();
+        while let Some(message) = codec.decode(src).unwrap() {
+            messages.push(message);
+        }
+        messages
+    }
+
+    #[test]
+    fn test_split_writes() {
+        let mut codec = JsonMessageCodec::default();
+        let mut src = BytesMut::from(&b"{\"jsonrpc\":\"2.0\",\"id\":1,\"res"[..]);
+        assert!(decode_all(&mut codec, &mut src).is_empty());
+
+        src.extend_from_slice(b"ult\":{\"tools\":[]}}\n");
+        assert_eq!(
+            decode_all(&mut codec, &mut src),
+            [StdoutMessage::Json(
+                json!({ "jsonrpc": "2.0", "id": 1, "result": { "tools": [] } })
+            )]
+        );
+        assert!(src.is_empty());
+    }
+
+    #[test]
+    fn test_several_messages_per_read() {
+        let mut codec = JsonMessageCodec::default();
+        let pretty = serde_json::to_string_pretty(&json!({ "id": 3, "result": {} })).unwrap();
+        let mut src = BytesMut::from(
+            format!(
+                "{{\"id\":1,\"result\":{{}}}}{{\"id\":2,\"result\":{{}}}}\n\
+                 Server listening on stdio\n\n{}\n",
+                pretty
+            )
+            .as_bytes(),
+        );
+        assert_eq!(
+            decode_all(&mut codec, &mut src),
+            [
+                StdoutMessage::Json(json!({ "id": 1, "result": {} })),
+                StdoutMessage::Json(json!({ "id": 2, "result": {} })),
+                StdoutMessage::Text("Server listening on stdio".to_string()),
+                StdoutMessage::Json(json!({ "id": 3, "result": {} })),
+            ]
+        );
+
+        // Text starting like JSON is still text, an unfinished line waits for the rest
+        let mut src = BytesMut::from(&b"[INFO] ready\n[WARN] slow"[..]);
+        assert_eq!(
+            decode_all(&mut codec, &mut src),
+            [StdoutMessage::Text("[INFO] ready".to_string())]
+        );
+        assert_eq!(
+            codec.decode_eof(&mut src).unwrap(),
+            Some(StdoutMessage::Text("[WARN] slow".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_oversized_messages() {
+        let mut codec = JsonMessageCodec::new(64);
+        let mut src = BytesMut::from(format!("{{\"result\":\"{}", "x".repeat(32)).as_bytes());
+        assert_eq!(codec.decode(&mut src).unwrap(), None);
+
+        src.extend_from_slice("x".repeat(64).as_bytes());
+        let error = codec.decode(&mut src).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        assert!(error.to_string().contains("larger than 64 bytes"));
+
+        // Endless text without a newline is capped too
+        let mut src = BytesMut::from("y".repeat(65).as_bytes());
+        assert!(codec.decode(&mut src).is_err());
+    }
+
+    #[cfg(not(windows))]
+    mod live_server {
+        use mcp_core::{
+            core::{mcp_core_builder::MCPCoreBuilder, mcp_core_proxy_ext::McpCoreProxyExt},
+            models::types::{ServerConfiguration, ServerRegistrationRequest},
+        };
+        use tempfile::tempdir;
+
+        use crate::common::db;
+
+        /// MCP server logging to stdout and pretty-printing its responses
+        const PRETTY_SERVER: &str = r#"
+echo "pretty server starting"
+while read -r line; do
+  id=$(echo "$line" | sed -n 's/.*"id":\([0-9]*\).*/\1/p')
+  [ -z "$id" ] && continue
+  echo "handling request $id"
+  case "$line" in
+    *'"initialize"'*)
+      result='{
+    "capabilities": { "tools": {} },
+    "serverInfo": { "name": "pretty", "version": "1.0.0" }
+  }' ;;
+    *'"tools/list"'*)
+      result='{
+    "tools": [
+      { "name": "format", "inputSchema": { "type": "object" } }
+    ]
+  }' ;;
+    *) result='{}' ;;
+  esac
+  printf '{\n  "jsonrpc": "2.0",\n  "id": %s,\n  "result": %s\n}\n' "$id" "$result"
+done
+"#;
+
+        #[tokio::test]
+        async fn test_pretty_printed_server_is_discovered() {
+            let dir = tempdir().unwrap();
+            let script = dir.path().join("server.sh");
+            std::fs::write(&script, PRETTY_SERVER).unwrap();
+            let core = MCPCoreBuilder::new(db(&dir)).build();
+
+            let response = core
+                .register_server(ServerRegistrationRequest {
+                    server_id: "pretty".to_string(),
+                    server_name: "Pretty".to_string(),
+                    description: String::new(),
+                    tools_type: "node".to_string(),
+                    configuration: Some(ServerConfiguration {
+                        command: Some("sh".to_string()),
+                        args: Some(vec![script.display().to_string()]),
+                        ..Default::default()
+                    }),
+                    distribution: None,
+                    provenance: None,
+                    authentication: None,
+                    enabled: None,
+                })
+                .await
+                .unwrap();
+            assert!(response.success, "{}", response.message);
+            assert_eq!(response.tool_count, Some(1));
+
+            core.kill_all_processes().await.unwrap();
+        }
+    }
+}
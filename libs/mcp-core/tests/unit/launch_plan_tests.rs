@@ -0,0 +1,255 @@
+#[cfg(test)]
+mod tests {
+    use mcp_core::{
+        mcp_state::{
+            launch_plan::{
+                inject_docker_cpu_flags, inject_docker_memory_flag, windows_batch_shim, LaunchPlan,
+            },
+            process_launcher::{ProcessLauncher, StdioProcessLauncher},
+        },
+        models::types::{ProcessPriority, ServerDefinition},
+        utils::command::run_in_directory,
+    };
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    fn definition(tools_type: &str, configuration: serde_json::Value) -> ServerDefinition {
+        serde_json::from_value(json!({
+            "name": "Test server",
+            "description": "",
+            "enabled": true,
+            "tools_type": tools_type,
+            "configuration": configuration,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_plan_substitutes_env_args() {
+        let server = definition(
+            "node",
+            json!({
+                "command": "npx",
+                "args": ["server", "$ROOT_DIR"],
+                "env": { "ROOT_DIR": "/tmp", "API_KEY": "secret" }
+            }),
+        );
+        let plan = LaunchPlan::build("test", &server).unwrap();
+
+        assert_eq!(plan.command, "npx");
+        assert_eq!(plan.args, vec!["server", "/tmp"]);
+        assert_eq!(plan.env_keys, vec!["API_KEY"]);
+        assert_eq!(plan.priority, ProcessPriority::Normal);
+        assert_eq!(plan.cpu_limit, None);
+        assert_eq!(plan.max_memory_mb, None);
+        assert!(!plan.lenient_initialize);
+
+        // Env values are never part of the preview
+        let preview = serde_json::to_value(&plan).unwrap();
+        assert!(preview.get("env").is_none());
+        assert!(!preview.to_string().contains("secret"));
+    }
+
+    #[test]
+    fn test_plan_requires_command() {
+        let server = definition("node", json!({ "args": ["server"] }));
+        assert!(LaunchPlan::build("test", &server).is_err());
+    }
+
+    #[test]
+    fn test_docker_plan_maps_limits_to_flags() {
+        let server = definition(
+            "docker",
+            json!({
+                "command": "docker",
+                "args": ["run", "-i", "--rm", "mcp/fetch"],
+                "priority": "low",
+                "cpu_limit": 2
+            }),
+        );
+        let plan = LaunchPlan::build("fetch", &server).unwrap();
+
+        assert!(plan.limits_applied_by_docker());
+        assert_eq!(plan.container_name.as_deref(), Some("mcp-dockmaster-fetch"));
+        assert_eq!(
+            plan.args,
+            vec![
+                "run",
+                "--name",
+                "mcp-dockmaster-fetch",
+                "--cpus=2",
+                "--cpu-shares=512",
+                "-i",
+                "--rm",
+                "mcp/fetch"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_docker_cpu_flags_keep_explicit_values() {
+        let mut args: Vec<String> = ["run", "--cpus", "4", "mcp/fetch"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        inject_docker_cpu_flags(&mut args, ProcessPriority::High, Some(1));
+        assert_eq!(
+            args,
+            vec!["run", "--cpu-shares=2048", "--cpus", "4", "mcp/fetch"]
+        );
+
+        let mut normal: Vec<String> = vec!["run".to_string(), "mcp/fetch".to_string()];
+        inject_docker_cpu_flags(&mut normal, ProcessPriority::Normal, None);
+        assert_eq!(normal, vec!["run", "mcp/fetch"]);
+    }
+
+    #[test]
+    fn test_memory_limit() {
+        let server = definition(
+            "docker",
+            json!({
+                "command": "docker",
+                "args": ["run", "-i", "mcp/fetch"],
+                "cpu_limit": 1,
+                "max_memory_mb": 512
+            }),
+        );
+        let plan = LaunchPlan::build("fetch", &server).unwrap();
+        assert_eq!(plan.max_memory_mb, Some(512));
+        assert_eq!(
+            plan.args,
+            vec![
+                "run",
+                "--name",
+                "mcp-dockmaster-fetch",
+                "--memory=512m",
+                "--cpus=1",
+                "-i",
+                "mcp/fetch"
+            ]
+        );
+        let preview = serde_json::to_value(&plan).unwrap();
+        assert_eq!(preview["max_memory_mb"], json!(512));
+
+        // A limit in the registry arguments wins
+        let mut args: Vec<String> = ["run", "-m", "1g", "mcp/fetch"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        inject_docker_memory_flag(&mut args, Some(512));
+        assert_eq!(args, vec!["run", "-m", "1g", "mcp/fetch"]);
+
+        // Node servers are watched by Dockmaster instead, 0 means no limit
+        let node = definition(
+            "node",
+            json!({ "command": "node", "args": ["index.js"], "max_memory_mb": 256 }),
+        );
+        let plan = LaunchPlan::build("node", &node).unwrap();
+        assert_eq!(plan.max_memory_mb, Some(256));
+        assert!(!plan.args.iter().any(|arg| arg.starts_with("--memory")));
+        let unlimited = definition(
+            "node",
+            json!({ "command": "node", "args": ["index.js"], "max_memory_mb": 0 }),
+        );
+        assert_eq!(
+            LaunchPlan::build("node", &unlimited).unwrap().max_memory_mb,
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_working_directory() {
+        let dir = tempdir().unwrap();
+        let cwd = dir.path().to_string_lossy().to_string();
+        let server = definition("node", json!({ "command": "node", "cwd": cwd }));
+        let plan = LaunchPlan::build("node", &server).unwrap();
+        assert_eq!(plan.cwd.as_deref(), Some(dir.path()));
+        assert!(plan.check_cwd().is_ok());
+        assert_eq!(serde_json::to_value(&plan).unwrap()["cwd"], json!(cwd));
+
+        let missing = dir.path().join("missing").to_string_lossy().to_string();
+        let server = definition("node", json!({ "command": "node", "cwd": missing }));
+        let plan = LaunchPlan::build("node", &server).unwrap();
+        // Refused before anything is spawned
+        let error = match StdioProcessLauncher.launch(&plan, "test-spawn").await {
+            Ok(_) => panic!("the server should not start"),
+            Err(error) => error,
+        };
+        assert!(error.contains(&missing), "{}", error);
+
+        let server = definition("node", json!({ "command": "node", "cwd": "data" }));
+        let plan = LaunchPlan::build("node", &server).unwrap();
+        assert!(plan.check_cwd().unwrap_err().contains("absolute"));
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn test_command_runs_in_directory() {
+        let dir = tempdir().unwrap();
+        let dir = dir.path().canonicalize().unwrap();
+        let command = run_in_directory("/bin/sh", "pwd", &dir);
+        let output = std::process::Command::new("/bin/sh")
+            .args(["-c", &command])
+            .output()
+            .unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout).trim(),
+            dir.to_string_lossy()
+        );
+
+        let command = run_in_directory("/bin/sh", "echo ran", &dir.join("missing"));
+        let output = std::process::Command::new("/bin/sh")
+            .args(["-c", &command])
+            .output()
+            .unwrap();
+        assert!(!output.status.success());
+        assert!(output.stdout.is_empty());
+    }
+
+    #[test]
+    fn test_windows_batch_shim() {
+        let npm = tempdir().unwrap();
+        let system = tempdir().unwrap();
+        for (dir, file) in [
+            (&npm, "npx.cmd"),
+            (&npm, "npx.ps1"),
+            (&npm, "tool.bat"),
+            (&system, "node.exe"),
+            (&npm, "node.cmd"),
+        ] {
+            std::fs::write(dir.path().join(file), "").unwrap();
+        }
+        let path = std::env::join_paths([system.path(), npm.path()]).unwrap();
+        let path = Some(path.as_os_str());
+
+        assert_eq!(windows_batch_shim("npx", path).as_deref(), Some("npx.cmd"));
+        assert_eq!(
+            windows_batch_shim("tool", path).as_deref(),
+            Some("tool.bat")
+        );
+        // The executable comes first on the PATH
+        assert_eq!(windows_batch_shim("node", path), None);
+        assert_eq!(windows_batch_shim("npx.cmd", path), None);
+        assert_eq!(windows_batch_shim("uvx", path), None);
+    }
+
+    #[test]
+    fn test_priority_mapping() {
+        assert_eq!(ProcessPriority::Low.nice_value(), 10);
+        assert_eq!(ProcessPriority::Normal.nice_value(), 0);
+        assert_eq!(ProcessPriority::High.nice_value(), -5);
+        assert_eq!(ProcessPriority::Low.windows_priority_class(), "BelowNormal");
+        assert_eq!(
+            ProcessPriority::High.windows_priority_class(),
+            "AboveNormal"
+        );
+
+        let priority: ProcessPriority = serde_json::from_value(json!("high")).unwrap();
+        assert_eq!(priority, ProcessPriority::High);
+        assert!(serde_json::from_value::<ProcessPriority>(json!("realtime")).is_err());
+        assert_eq!(
+            ProcessPriority::from_stored("low"),
+            Some(ProcessPriority::Low)
+        );
+    }
+}
@@ -0,0 +1,86 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use mcp_core::{
+        core::{mcp_core_builder::MCPCoreBuilder, mcp_core_proxy_ext::McpCoreProxyExt},
+        models::types::{ServerDefinition, ServerToolInfo, StartMode, ToolExecutionRequest},
+    };
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    use crate::common::{db, FailingLauncher};
+
+    fn server(start_mode: &str) -> ServerDefinition {
+        serde_json::from_value(json!({
+            "name": "Echo",
+            "description": "",
+            "enabled": true,
+            "tools_type": "node",
+            "configuration": { "command": "node", "args": ["echo.js"], "start_mode": start_mode }
+        }))
+        .unwrap()
+    }
+
+    fn call(tool_id: &str) -> ToolExecutionRequest {
+        ToolExecutionRequest {
+            tool_id: tool_id.to_string(),
+            parameters: json!({}),
+        }
+    }
+
+    #[test]
+    fn test_start_mode_is_stored() {
+        let dir = tempdir().unwrap();
+        let db = db(&dir);
+        db.save_server("lazy", &server("lazy")).unwrap();
+        db.save_server("eager", &server("eager")).unwrap();
+
+        let start_mode = |id: &str| {
+            db.get_server(id)
+                .unwrap()
+                .configuration
+                .and_then(|configuration| configuration.start_mode)
+        };
+        assert_eq!(start_mode("lazy"), Some(StartMode::Lazy));
+        assert_eq!(start_mode("eager"), Some(StartMode::Eager));
+        assert_eq!(StartMode::from_stored("sometime"), None);
+    }
+
+    #[tokio::test]
+    async fn test_lazy_server_starts_on_its_first_call() {
+        let dir = tempdir().unwrap();
+        let db = db(&dir);
+        db.save_server("lazy", &server("lazy")).unwrap();
+        db.save_server("eager", &server("eager")).unwrap();
+        db.save_server("stopped", &server("lazy")).unwrap();
+        db.save_server_run_state("stopped", false).unwrap();
+        let tool = ServerToolInfo::from_value(
+            json!({ "name": "echo", "description": "Echo the input" }),
+            "lazy".to_string(),
+        )
+        .unwrap();
+        db.save_server_tool(&tool).unwrap();
+        let launcher = Arc::new(FailingLauncher::new());
+        let core = MCPCoreBuilder::new(db)
+            .process_launcher(launcher.clone())
+            .build();
+
+        core.init_mcp_server().await.unwrap();
+        assert_eq!(launcher.launched(), vec!["eager"]);
+        // Its tools are listed before it runs
+        let tools = core.list_all_server_tools().await.unwrap();
+        assert!(tools.iter().any(|tool| tool.server_id == "lazy"));
+
+        let error = core
+            .execute_proxy_tool(call("lazy:echo"))
+            .await
+            .unwrap_err();
+        assert_eq!(error, "not started in tests");
+        assert_eq!(launcher.launched(), vec!["eager", "lazy"]);
+
+        // A server stopped by the user stays stopped
+        assert!(core.execute_proxy_tool(call("stopped:echo")).await.is_err());
+        assert_eq!(launcher.launched().len(), 2);
+    }
+}
@@ -0,0 +1,121 @@
+#[cfg(test)]
+mod tests {
+    use diesel::connection::SimpleConnection;
+    use diesel::{Connection, SqliteConnection};
+    use mcp_core::{
+        database::{db_manager::DBManager, legacy::parse_legacy_tool},
+        models::types::{EnvValue, ServerDefinition},
+    };
+    use serde_json::json;
+    use tempfile::{tempdir, TempDir};
+
+    /// Database with the current schema, the legacy table of the fixture and a GitHub
+    /// server installed since the update
+    fn legacy_db(dir: &TempDir) -> DBManager {
+        let path = dir.path().join("mcp_dockmaster.db");
+        let db = DBManager::with_path(path.clone()).unwrap();
+        db.apply_migrations().unwrap();
+
+        let fixture = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/legacy/tools.sql");
+        let mut conn = SqliteConnection::establish(&path.to_string_lossy()).unwrap();
+        conn.batch_execute(&std::fs::read_to_string(fixture).unwrap())
+            .unwrap();
+
+        let github: ServerDefinition = serde_json::from_value(json!({
+            "name": "GitHub",
+            "description": "Installed after the update",
+            "enabled": true,
+            "tools_type": "docker",
+            "configuration": { "command": "docker", "args": ["run", "-i", "github-mcp"] }
+        }))
+        .unwrap();
+        db.save_server("github", &github).unwrap();
+        db
+    }
+
+    #[test]
+    fn test_migrates_the_legacy_table() {
+        let dir = tempdir().unwrap();
+        let db = legacy_db(&dir);
+
+        let report = db.migrate_legacy_tools().unwrap();
+
+        assert!(report.found);
+        assert_eq!(report.migrated, vec!["my-weather", "filesystem"]);
+        assert_eq!(
+            report.renamed,
+            vec![("My Weather".to_string(), "my-weather".to_string())]
+        );
+        let skipped: Vec<&str> = report.skipped.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(skipped, vec!["broken", "github"]);
+        assert_eq!(report.skipped[0].reason, "Missing name");
+
+        let filesystem = db.get_server("filesystem").unwrap();
+        assert!(filesystem.enabled);
+        assert_eq!(filesystem.tools_type, "node");
+        let configuration = filesystem.configuration.unwrap();
+        assert_eq!(configuration.command.as_deref(), Some("npx"));
+        let root = &configuration.env.unwrap()["ROOT"];
+        assert_eq!(root.default, Some(EnvValue::from("/home/user")));
+        assert!(root.required);
+        assert_eq!(filesystem.distribution.unwrap().r#type, "npm");
+
+        let weather = db.get_server("my-weather").unwrap();
+        assert!(!weather.enabled);
+        let api_key = &weather.configuration.unwrap().env.unwrap()["API_KEY"];
+        assert_eq!(api_key.default, Some(EnvValue::from("secret")));
+        assert_eq!(
+            db.get_server_id_alias("My Weather").unwrap().as_deref(),
+            Some("my-weather")
+        );
+
+        // The server installed since the update is left alone
+        let github = db.get_server("github").unwrap();
+        assert_eq!(github.description, "Installed after the update");
+    }
+
+    #[test]
+    fn test_backs_up_the_legacy_table_and_can_run_again() {
+        let dir = tempdir().unwrap();
+        let db = legacy_db(&dir);
+
+        let report = db.migrate_legacy_tools().unwrap();
+        let backup = std::fs::read_to_string(report.backup_path.unwrap()).unwrap();
+        let rows: serde_json::Value = serde_json::from_str(&backup).unwrap();
+        assert_eq!(rows.as_array().unwrap().len(), 4);
+        assert_eq!(rows[0]["id"], "My Weather");
+
+        // Nothing left to migrate, the legacy table is still there
+        let report = db.migrate_legacy_tools().unwrap();
+        assert!(report.found);
+        assert!(report.migrated.is_empty());
+        assert_eq!(report.skipped.len(), 4);
+    }
+
+    #[test]
+    fn test_current_databases_have_nothing_to_migrate() {
+        let dir = tempdir().unwrap();
+        let db = DBManager::with_path(dir.path().join("mcp_dockmaster.db")).unwrap();
+        db.apply_migrations().unwrap();
+
+        let report = db.migrate_legacy_tools().unwrap();
+
+        assert!(!report.found);
+        assert!(report.backup_path.is_none());
+    }
+
+    #[test]
+    fn test_parse_legacy_tool_errors() {
+        assert!(parse_legacy_tool("not json")
+            .unwrap_err()
+            .starts_with("Invalid JSON document"));
+        assert_eq!(
+            parse_legacy_tool(r#"{"name": "Echo"}"#).unwrap_err(),
+            "Missing tool type"
+        );
+        let server = parse_legacy_tool(r#"{"name": "Echo", "type": "node"}"#).unwrap();
+        assert!(server.enabled);
+        assert!(server.configuration.is_none());
+    }
+}
@@ -0,0 +1,111 @@
+#[cfg(test)]
+mod tests {
+    use mcp_core::{
+        core::mcp_core_builder::MCPCoreBuilder,
+        database::db_manager::DBManager,
+        http_server::handlers::handle_get_server_config,
+        models::{
+            server_id::{legacy_server_id_warning, request_server_id},
+            types::{
+                ConfigUpdateRequest, ServerConfigUpdateRequest, ServerDefinition,
+                ServerRegistrationResponse, ServerUpdateRequest, ToolUninstallRequest,
+            },
+            unknown_fields::KnownFields,
+        },
+    };
+    use serde_json::{json, Value};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_requests_accept_both_names() {
+        for field in ["server_id", "tool_id"] {
+            let update: ServerUpdateRequest =
+                serde_json::from_value(json!({ field: "fetch", "enabled": true })).unwrap();
+            assert_eq!(update.server_id, "fetch");
+
+            let uninstall: ToolUninstallRequest =
+                serde_json::from_value(json!({ field: "fetch" })).unwrap();
+            assert_eq!(uninstall.server_id, "fetch");
+
+            let params = json!({ field: "fetch", "config": { "API_KEY": "key" } });
+            let config: ServerConfigUpdateRequest = serde_json::from_value(params.clone()).unwrap();
+            assert_eq!(config.server_id, "fetch");
+            let config: ConfigUpdateRequest = serde_json::from_value(params.clone()).unwrap();
+            assert_eq!(config.server_id, "fetch");
+            assert!(ConfigUpdateRequest::unknown_fields(&params).is_empty());
+
+            assert_eq!(request_server_id(&params), Some("fetch"));
+            assert_eq!(
+                legacy_server_id_warning(&params).is_some(),
+                field == "tool_id"
+            );
+        }
+
+        // The canonical name in the error of a request without either
+        let error = serde_json::from_value::<ConfigUpdateRequest>(json!({ "config": {} }))
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("`server_id`"));
+    }
+
+    #[test]
+    fn test_responses_use_server_id() {
+        let config: ConfigUpdateRequest =
+            serde_json::from_value(json!({ "tool_id": "fetch", "config": {} })).unwrap();
+        let value = serde_json::to_value(config).unwrap();
+        assert_eq!(value["server_id"], "fetch");
+        assert!(value.get("tool_id").is_none());
+
+        let response = ServerRegistrationResponse {
+            success: true,
+            message: String::new(),
+            server_id: Some("fetch".to_string()),
+            status: None,
+            tool_count: None,
+            startup_ms: None,
+            warnings: Vec::new(),
+            operation_id: None,
+        };
+        let value = serde_json::to_value(response).unwrap();
+        assert_eq!(value["server_id"], "fetch");
+        assert!(value.get("tool_id").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_server_config_with_both_names() {
+        let dir = tempdir().unwrap();
+        let db = DBManager::with_path(dir.path().join("mcp-dockmaster.db")).unwrap();
+        db.apply_migrations().unwrap();
+        let server: ServerDefinition = serde_json::from_value(json!({
+            "name": "Weather",
+            "description": "",
+            "enabled": false,
+            "tools_type": "node",
+            "configuration": { "command": "npx" }
+        }))
+        .unwrap();
+        db.save_server("weather", &server).unwrap();
+        let core = MCPCoreBuilder::new(db).build();
+
+        let warnings = |response: &Value| -> Vec<String> {
+            serde_json::from_value(response["warnings"].clone()).unwrap()
+        };
+
+        let params = json!({ "server_id": "weather", "config": { "API_KEY": "new" } });
+        let response = handle_get_server_config(core.clone(), params)
+            .await
+            .unwrap();
+        assert_eq!(response["success"], true);
+        assert_eq!(response["server_id"], "weather");
+        assert!(warnings(&response).is_empty());
+
+        let params = json!({ "tool_id": "weather", "config": { "API_KEY": "newer" } });
+        let response = handle_get_server_config(core, params).await.unwrap();
+        assert_eq!(response["success"], true);
+        assert_eq!(response["server_id"], "weather");
+        assert_eq!(
+            warnings(&response),
+            vec!["'tool_id' is deprecated, send the id of the server as 'server_id'"]
+        );
+    }
+}
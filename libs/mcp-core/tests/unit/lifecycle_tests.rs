@@ -0,0 +1,195 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use mcp_core::{
+        core::{
+            mcp_core::MCPCore, mcp_core_builder::MCPCoreBuilder,
+            mcp_core_dashboard_ext::McpCoreDashboardExt, mcp_core_proxy_ext::McpCoreProxyExt,
+        },
+        database::db_manager::DBManager,
+        lifecycle::{
+            LifecycleEvent, LifecycleKind, ServerUptime, LIFECYCLE_EVENTS_PERSISTED,
+            LIFECYCLE_HISTORY_KEPT, RECENT_LIFECYCLE_EVENTS, RESTART_WINDOW,
+        },
+        models::types::{ServerConfiguration, ServerDefinition, ServerRegistrationRequest},
+        registry::server_registry::ServerRegistry,
+    };
+    use serde_json::json;
+    use tempfile::{tempdir, TempDir};
+
+    use crate::common::{db, FailingLauncher, EXITED_AT_STARTUP};
+
+    fn event(kind: LifecycleKind, at: u64) -> LifecycleEvent {
+        LifecycleEvent {
+            server_id: "flaky".to_string(),
+            kind,
+            at,
+            exit_code: None,
+            detail: None,
+        }
+    }
+
+    fn save_server(db: &DBManager, server_id: &str) {
+        let server: ServerDefinition = serde_json::from_value(json!({
+            "name": server_id,
+            "description": "",
+            "enabled": false,
+            "tools_type": "node",
+            "configuration": { "command": "npx" }
+        }))
+        .unwrap();
+        db.save_server(server_id, &server).unwrap();
+    }
+
+    #[test]
+    fn test_uptime_counts_recent_restarts() {
+        let hour = RESTART_WINDOW.as_millis() as u64;
+        let now = 10 * hour;
+        let history = vec![
+            event(LifecycleKind::Started, now - 2 * hour),
+            event(LifecycleKind::Restarted, now - 2 * hour),
+            event(LifecycleKind::Crashed, now - 40_000)
+                .with_exit_code(Some(137))
+                .with_detail("Server exited with exit code 137"),
+            event(LifecycleKind::RestartedBySupervisor, now - 40_000),
+            event(LifecycleKind::Started, now - 40_000),
+            event(LifecycleKind::ConfigRestart, now - 30_000),
+            event(LifecycleKind::Started, now - 30_000),
+        ];
+
+        let uptime = ServerUptime::new(Some(now - 30_000), &history, now);
+        assert_eq!(uptime.started_at, Some(now - 30_000));
+        assert_eq!(uptime.uptime_secs, Some(30));
+        // The restart before the window isn't counted
+        assert_eq!(uptime.restarts_last_hour, 2);
+        assert_eq!(uptime.restart_count, 3);
+        assert_eq!(uptime.last_exit_code, Some(137));
+        assert_eq!(
+            uptime.last_error.as_deref(),
+            Some("Server exited with exit code 137")
+        );
+        assert_eq!(uptime.recent.len(), RECENT_LIFECYCLE_EVENTS);
+        assert_eq!(uptime.recent.last(), history.last());
+
+        let stopped = ServerUptime::new(None, &history, now);
+        assert_eq!(stopped.uptime_secs, None);
+        assert_eq!(stopped.restarts_last_hour, 2);
+    }
+
+    #[test]
+    fn test_kinds_round_trip() {
+        for kind in [
+            LifecycleKind::Started,
+            LifecycleKind::StartFailed,
+            LifecycleKind::Crashed,
+            LifecycleKind::RestartedBySupervisor,
+            LifecycleKind::Restarted,
+            LifecycleKind::ConfigRestart,
+            LifecycleKind::StoppedByUser,
+        ] {
+            assert_eq!(LifecycleKind::parse(kind.as_str()), Some(kind));
+            assert_eq!(serde_json::to_value(kind).unwrap(), json!(kind.as_str()));
+        }
+        assert_eq!(LifecycleKind::parse("exploded"), None);
+    }
+
+    #[test]
+    fn test_history_is_persisted_and_bounded() {
+        let dir = tempdir().unwrap();
+        let db = db(&dir);
+        save_server(&db, "flaky");
+        let registry = ServerRegistry::with_db_manager(db.clone());
+
+        let count = LIFECYCLE_HISTORY_KEPT as u64 + 5;
+        for at in 0..count {
+            let kind = if at % 2 == 0 {
+                LifecycleKind::Started
+            } else {
+                LifecycleKind::Crashed
+            };
+            let event = event(kind, at).with_exit_code((at % 2 == 1).then_some(1));
+            registry.record_lifecycle_event(&event).unwrap();
+        }
+
+        // Memory keeps more than the database
+        let history = registry.get_lifecycle_history("flaky");
+        assert_eq!(history.len(), LIFECYCLE_HISTORY_KEPT);
+        assert_eq!(history.first().unwrap().at, 5);
+        assert_eq!(history.last().unwrap().at, count - 1);
+        assert_eq!(history.last().unwrap().exit_code, Some(1));
+
+        let saved = db.get_lifecycle_events().unwrap();
+        assert_eq!(saved.len(), LIFECYCLE_EVENTS_PERSISTED as usize);
+        assert_eq!(saved.last(), history.last());
+
+        // A new run starts from what was saved
+        let registry = ServerRegistry::with_db_manager(db.clone());
+        assert_eq!(registry.get_lifecycle_history("flaky"), saved);
+        assert!(registry.get_lifecycle_history("other").is_empty());
+
+        registry.delete_server("flaky").unwrap();
+        assert!(registry.get_lifecycle_history("flaky").is_empty());
+        assert!(db.get_lifecycle_events().unwrap().is_empty());
+    }
+
+    async fn core_with_server(dir: &TempDir) -> MCPCore {
+        let core = MCPCoreBuilder::new(db(dir))
+            .process_launcher(Arc::new(FailingLauncher::with_error(EXITED_AT_STARTUP)))
+            .build();
+        let request = ServerRegistrationRequest {
+            server_id: "flaky".to_string(),
+            server_name: "Flaky".to_string(),
+            description: String::new(),
+            tools_type: "node".to_string(),
+            configuration: Some(ServerConfiguration {
+                command: Some("node".to_string()),
+                args: Some(vec!["index.js".to_string()]),
+                ..Default::default()
+            }),
+            distribution: None,
+            provenance: None,
+            authentication: None,
+            enabled: None,
+        };
+        core.register_server(request).await.unwrap();
+        core
+    }
+
+    #[tokio::test]
+    async fn test_failed_starts_are_recorded() {
+        let dir = tempdir().unwrap();
+        let core = core_with_server(&dir).await;
+        core.restart_server_command("flaky".to_string(), false)
+            .await
+            .unwrap();
+
+        let history = core.get_server_history("flaky".to_string()).await.unwrap();
+        assert_eq!(history.server_id, "flaky");
+        let kinds: Vec<LifecycleKind> = history.events.iter().map(|event| event.kind).collect();
+        // Not running, so not a restart
+        assert_eq!(
+            kinds,
+            vec![LifecycleKind::StartFailed, LifecycleKind::StartFailed]
+        );
+        assert!(history.events[0]
+            .detail
+            .as_deref()
+            .is_some_and(|detail| detail.contains("exit code 1")));
+        assert_eq!(history.uptime.uptime_secs, None);
+        assert_eq!(history.uptime.restarts_last_hour, 0);
+        assert_eq!(history.uptime.restart_count, 0);
+        assert_eq!(history.uptime.last_error, history.events[1].detail);
+
+        let servers = core.list_servers().await.unwrap();
+        assert_eq!(servers[0].uptime.recent, history.events);
+        let summary = core.dashboard_summary().await.unwrap();
+        assert_eq!(summary.restarts_last_hour, 0);
+        assert!(summary.flapping_servers.is_empty());
+
+        assert!(core
+            .get_server_history("unknown".to_string())
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,130 @@
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use mcp_core::{
+        core::{
+            mcp_core::MCPCore, mcp_core_builder::MCPCoreBuilder,
+            mcp_core_lock_debug_ext::McpCoreLockDebugExt,
+        },
+        database::db_manager::DBManager,
+        lock_debug::{lock_holds, TrackedRwLock, LOCK_MCP_CLIENTS, LOCK_TRACKING},
+    };
+    use tempfile::{tempdir, TempDir};
+
+    fn core() -> (TempDir, MCPCore) {
+        let dir = tempdir().unwrap();
+        let db = DBManager::with_path(dir.path().join("mcp-dockmaster.db")).unwrap();
+        db.apply_migrations().unwrap();
+        (dir, MCPCoreBuilder::new(db).build())
+    }
+
+    #[tokio::test]
+    async fn test_tracked_lock_guards() {
+        let lock = TrackedRwLock::new("guards_test", vec![1]);
+        lock.write().await.push(2);
+        assert_eq!(*lock.read().await, vec![1, 2]);
+
+        let reader = lock.read().await;
+        assert!(lock.try_read().is_ok());
+        drop(reader);
+        let writer = lock.write().await;
+        assert!(lock.try_read().is_err());
+        assert_eq!(
+            lock_holds()
+                .iter()
+                .filter(|hold| hold.lock == "guards_test")
+                .count(),
+            usize::from(LOCK_TRACKING)
+        );
+        drop(writer);
+        assert!(lock_holds().iter().all(|hold| hold.lock != "guards_test"));
+    }
+
+    #[tokio::test]
+    async fn test_dump_state_does_not_wait_on_a_writer() {
+        let (_dir, core) = core();
+        let mcp_state = core.mcp_state.read().await.clone();
+        let _call = mcp_state.metrics.start_call("fetch");
+        mcp_state
+            .server_tools
+            .write()
+            .await
+            .insert("fetch".to_string(), Vec::new());
+
+        let clients = mcp_state.mcp_clients.write().await;
+        let dump = core.dump_state();
+        assert_eq!(dump.lock_tracking, LOCK_TRACKING);
+        assert!(dump.contended.contains(LOCK_MCP_CLIENTS));
+        assert_eq!(dump.servers.len(), 1);
+        assert_eq!(dump.servers[0].server_id, "fetch");
+        assert_eq!(dump.servers[0].in_flight, 1);
+        assert_eq!(dump.servers[0].tool_count, Some(0));
+        assert!(dump.servers[0].status.is_none());
+        if LOCK_TRACKING {
+            let hold = dump
+                .lock_holds
+                .iter()
+                .find(|hold| hold.lock == LOCK_MCP_CLIENTS)
+                .unwrap();
+            assert!(hold.holder.contains("lock_debug_tests.rs"));
+        }
+        drop(clients);
+
+        let value = serde_json::to_value(core.dump_state()).unwrap();
+        assert_eq!(value["contended"], serde_json::json!([]));
+        assert_eq!(value["servers"][0]["in_flight"], 1);
+    }
+
+    #[cfg(feature = "lock-debug")]
+    #[tokio::test]
+    async fn test_watchdog_reports_a_long_hold() {
+        use mcp_core::lock_debug::{spawn_lock_watchdog, LockMode};
+
+        let threshold = Duration::from_millis(100);
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let watchdog = spawn_lock_watchdog(threshold, move |holds| {
+            let _ = sender.send(holds);
+        });
+
+        let lock = TrackedRwLock::new("watchdog_test", 0);
+        let guard = lock.write().await;
+        let hold = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let holds = receiver.recv().await.unwrap();
+                if let Some(hold) = holds.into_iter().find(|hold| hold.lock == "watchdog_test") {
+                    return hold;
+                }
+            }
+        })
+        .await
+        .expect("the watchdog didn't report the hold");
+        assert_eq!(hold.mode, LockMode::Write);
+        assert!(hold.holder.contains("lock_debug_tests.rs"));
+        assert!(hold.held_for > threshold);
+
+        // Reported once
+        tokio::time::sleep(threshold).await;
+        while let Ok(holds) = receiver.try_recv() {
+            assert!(holds.iter().all(|hold| hold.lock != "watchdog_test"));
+        }
+        drop(guard);
+        watchdog.abort();
+    }
+
+    #[cfg(not(feature = "lock-debug"))]
+    #[tokio::test]
+    async fn test_watchdog_is_silent_without_lock_tracking() {
+        use mcp_core::lock_debug::spawn_lock_watchdog;
+
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let watchdog = spawn_lock_watchdog(Duration::from_millis(20), move |holds| {
+            let _ = sender.send(holds);
+        });
+        let lock = TrackedRwLock::new("silent_test", 0);
+        let _guard = lock.write().await;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(receiver.try_recv().is_err());
+        watchdog.abort();
+    }
+}
@@ -0,0 +1,219 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use async_trait::async_trait;
+    use mcp_core::{
+        core::{
+            mcp_core::MCPCore, mcp_core_builder::MCPCoreBuilder,
+            mcp_core_proxy_ext::McpCoreProxyExt,
+        },
+        events::{DockmasterEvent, InstallProgressReporter, InstallStage},
+        http_server::handlers::{handle_register_tool, InstallOrigin},
+        mcp_state::{
+            launch_plan::LaunchPlan,
+            process_launcher::{LaunchedServer, ProcessLauncher},
+        },
+        models::types::{ErrorResponse, ServerRegistrationRequest, ServerStatus},
+        registry::{registry_cache::RegistryCache, registry_client::RegistryClient},
+        utils::clock::ManualClock,
+    };
+    use serde_json::{json, Value};
+    use tempfile::{tempdir, TempDir};
+
+    use crate::common::db;
+
+    /// Registry serving a fixed response and counting the fetches
+    struct FakeRegistry {
+        response: Result<Value, String>,
+        fetches: AtomicUsize,
+    }
+
+    impl FakeRegistry {
+        fn new(response: Result<Value, String>) -> Arc<Self> {
+            Arc::new(Self {
+                response,
+                fetches: AtomicUsize::new(0),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl RegistryClient for FakeRegistry {
+        async fn fetch_registry(&self) -> Result<Value, ErrorResponse> {
+            self.fetches.fetch_add(1, Ordering::SeqCst);
+            self.response.clone().map_err(|message| ErrorResponse {
+                code: -32000,
+                message,
+            })
+        }
+    }
+
+    /// Launcher whose processes exit right away
+    #[derive(Default)]
+    struct CrashingLauncher {
+        launches: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl ProcessLauncher for CrashingLauncher {
+        async fn launch(&self, plan: &LaunchPlan, _: &str) -> Result<LaunchedServer, String> {
+            self.launches.fetch_add(1, Ordering::SeqCst);
+            Err(format!("{} exited with status 1", plan.command))
+        }
+    }
+
+    /// Launcher whose processes never answer the initialize handshake
+    struct HangingLauncher;
+
+    #[async_trait]
+    impl ProcessLauncher for HangingLauncher {
+        async fn launch(&self, _: &LaunchPlan, _: &str) -> Result<LaunchedServer, String> {
+            std::future::pending().await
+        }
+    }
+
+    fn empty_registry() -> Value {
+        json!({ "count": 0, "version": 1, "categories": {}, "tags": {}, "tools": [] })
+    }
+
+    fn core(builder: impl FnOnce(MCPCoreBuilder) -> MCPCoreBuilder) -> (MCPCore, TempDir) {
+        let temp_dir = tempdir().expect("Failed to create temp directory");
+        let core = builder(
+            MCPCoreBuilder::new(db(&temp_dir))
+                .registry_client(FakeRegistry::new(Ok(empty_registry()))),
+        )
+        .build();
+        (core, temp_dir)
+    }
+
+    fn echo_server() -> ServerRegistrationRequest {
+        serde_json::from_value(json!({
+            "server_id": "echo",
+            "server_name": "Echo",
+            "description": "",
+            "tools_type": "node",
+            "configuration": { "command": "node", "args": ["echo.js"] }
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_registry_cache_expires_on_clock() {
+        let registry = FakeRegistry::new(Ok(empty_registry()));
+        let clock = Arc::new(ManualClock::new());
+        let cache = RegistryCache::new(registry.clone(), clock.clone());
+
+        cache.get_registry_tools().await.unwrap();
+        cache.get_registry_tools().await.unwrap();
+        assert_eq!(registry.fetches.load(Ordering::SeqCst), 1);
+
+        clock.advance(Duration::from_secs(601));
+        cache.get_registry_tools().await.unwrap();
+        assert_eq!(registry.fetches.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_registry_failure_fails_install() {
+        let registry = FakeRegistry::new(Err("registry unreachable".to_string()));
+        let (core, _dir) = core(|builder| builder.registry_client(registry.clone()));
+
+        let error = handle_register_tool(core, json!({ "tool_id": "fetch" }), InstallOrigin::Api)
+            .await
+            .unwrap_err();
+        assert_eq!(error.message, "registry unreachable");
+        assert_eq!(registry.fetches.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_crashing_server_fails_install() {
+        let launcher = Arc::new(CrashingLauncher::default());
+        let (core, _dir) = core(|builder| builder.process_launcher(launcher.clone()));
+        let (_, mut events) = core.event_bus.subscribe_with_history(|_| false);
+
+        let progress =
+            InstallProgressReporter::new(core.event_bus.clone(), "op".into(), "echo".into());
+        let response = core
+            .register_server_with_progress(echo_server(), Some(progress))
+            .await
+            .unwrap();
+        assert_eq!(launcher.launches.load(Ordering::SeqCst), 1);
+        assert_eq!(
+            response.message,
+            "Tool 'Echo' registered but failed to start"
+        );
+        assert_eq!(
+            response.warnings,
+            vec!["Server failed to start: node exited with status 1"]
+        );
+
+        let mut failure = None;
+        while let Ok(envelope) = events.try_recv() {
+            if let DockmasterEvent::InstallProgress(progress) = envelope.event {
+                if progress.stage == InstallStage::Failed {
+                    failure = progress.detail;
+                }
+            }
+        }
+        assert_eq!(failure.as_deref(), Some("node exited with status 1"));
+
+        let servers = core.list_servers().await.unwrap();
+        assert!(matches!(
+            &servers[0].status,
+            ServerStatus::Error(e) if e == "node exited with status 1"
+        ));
+
+        let mcp_state = core.mcp_state.read().await;
+        assert!(mcp_state.mcp_clients.read().await.is_empty());
+        // The spawn stays recorded so that a leftover process is cleaned up later
+        let records = mcp_state
+            .tool_registry
+            .read()
+            .await
+            .get_server_processes()
+            .unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].pid, None);
+    }
+
+    #[tokio::test]
+    async fn test_hanging_server_times_out() {
+        let clock = Arc::new(ManualClock::new());
+        let (core, _dir) = core(|builder| {
+            builder
+                .process_launcher(Arc::new(HangingLauncher))
+                .clock(clock.clone())
+        });
+        core.register_server(echo_server()).await.unwrap();
+
+        let mcp_state = core.mcp_state.read().await;
+        let error = mcp_state.restart_server("echo").await.unwrap_err();
+        assert!(error.contains("did not start within 30 seconds"));
+        assert!(mcp_state.mcp_clients.read().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_startup_timeout_is_configurable() {
+        let clock = Arc::new(ManualClock::new());
+        let (core, _dir) = core(|builder| {
+            builder
+                .process_launcher(Arc::new(HangingLauncher))
+                .clock(clock.clone())
+        });
+        assert!(core.set_startup_timeout_secs(0).await.is_err());
+        core.set_startup_timeout_secs(120).await.unwrap();
+        assert_eq!(core.startup_timeout_secs().await, 120);
+
+        let response = core.register_server(echo_server()).await.unwrap();
+        // The server stays registered without tools
+        assert!(response.success);
+        assert_eq!(response.tool_count, Some(0));
+        assert_eq!(response.startup_ms, None);
+        assert_eq!(
+            response.warnings,
+            vec!["Server failed to start: Server echo did not start within 120 seconds"]
+        );
+    }
+}
@@ -0,0 +1,167 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::path::Path;
+    use std::time::Duration;
+
+    use mcp_core::metrics::{
+        escape_label_value, grafana_dashboard, MetricsRegistry, METRICS, SERVER_ID_LABEL,
+        SERVER_NAME_LABEL, TOOL_NAME_LABEL,
+    };
+    use serde_json::Value;
+
+    fn names() -> HashMap<String, String> {
+        HashMap::from([
+            ("search".to_string(), "Search".to_string()),
+            ("idle".to_string(), "Idle".to_string()),
+        ])
+    }
+
+    #[test]
+    fn test_server_series() {
+        let metrics = MetricsRegistry::new();
+        metrics.record_start("search");
+        metrics.record_exit("search", Some(137));
+        metrics.record_health_check_failure("search");
+        metrics.record_start("search");
+        metrics.record_exit("search", Some(0));
+        metrics.record_start("search");
+        metrics.record_exit("search", None);
+        metrics.record_call("search", "query", Duration::from_millis(500), true);
+        metrics.record_call("search", "query", Duration::from_millis(1500), false);
+        metrics.record_call("gone", "query", Duration::from_millis(10), true);
+
+        let text = metrics.render(&names(), 1);
+        let labels = r#"{server_id="search",server_name="Search"}"#;
+        for line in [
+            "dockmaster_servers_installed 2".to_string(),
+            "dockmaster_servers_running 1".to_string(),
+            format!("dockmaster_server_restarts_total{} 2", labels),
+            format!("dockmaster_server_crashes_total{} 2", labels),
+            format!("dockmaster_server_health_check_failures_total{} 1", labels),
+            format!("dockmaster_server_call_errors_total{} 1", labels),
+            format!("dockmaster_server_call_duration_seconds_sum{} 2", labels),
+            format!("dockmaster_server_call_duration_seconds_count{} 2", labels),
+            // The exit code is kept from the last exit that had one
+            format!("dockmaster_server_last_exit_code{} 0", labels),
+            r#"dockmaster_server_restarts_total{server_id="idle",server_name="Idle"} 0"#
+                .to_string(),
+            // Removed servers are kept, without a name
+            r#"dockmaster_server_call_errors_total{server_id="gone",server_name=""} 0"#.to_string(),
+        ] {
+            assert!(
+                text.lines().any(|l| l == line),
+                "{} missing in\n{}",
+                line,
+                text
+            );
+        }
+        assert!(!text.contains("dockmaster_tool_call_duration_seconds_bucket"));
+    }
+
+    #[test]
+    fn test_calls_in_flight() {
+        let metrics = MetricsRegistry::new();
+        let line = r#"dockmaster_server_calls_in_flight{server_id="search",server_name="Search"}"#;
+        let call = metrics.start_call("search");
+        let other = metrics.start_call("search");
+        assert!(metrics
+            .render(&names(), 1)
+            .contains(&format!("{} 2\n", line)));
+        drop(call);
+        drop(other);
+        assert!(metrics
+            .render(&names(), 1)
+            .contains(&format!("{} 0\n", line)));
+    }
+
+    #[test]
+    fn test_tool_latency_histogram() {
+        let metrics = MetricsRegistry::new();
+        metrics.set_tool_latency(true);
+        metrics.record_call("search", "query", Duration::from_millis(200), true);
+        metrics.record_call("search", "query", Duration::from_secs(60), true);
+
+        let text = metrics.render(&names(), 1);
+        let labels = r#"server_id="search",server_name="Search",tool_name="query""#;
+        for line in [
+            format!(
+                r#"dockmaster_tool_call_duration_seconds_bucket{{{},le="0.1"}} 0"#,
+                labels
+            ),
+            format!(
+                r#"dockmaster_tool_call_duration_seconds_bucket{{{},le="0.25"}} 1"#,
+                labels
+            ),
+            format!(
+                r#"dockmaster_tool_call_duration_seconds_bucket{{{},le="30"}} 1"#,
+                labels
+            ),
+            format!(
+                r#"dockmaster_tool_call_duration_seconds_bucket{{{},le="+Inf"}} 2"#,
+                labels
+            ),
+            format!(
+                "dockmaster_tool_call_duration_seconds_count{{{}}} 2",
+                labels
+            ),
+        ] {
+            assert!(
+                text.lines().any(|l| l == line),
+                "{} missing in\n{}",
+                line,
+                text
+            );
+        }
+
+        metrics.set_tool_latency(false);
+        assert!(!metrics
+            .render(&names(), 1)
+            .contains("dockmaster_tool_call_duration_seconds_count"));
+    }
+
+    #[test]
+    fn test_escape_label_value() {
+        assert_eq!(escape_label_value("plain"), "plain");
+        assert_eq!(escape_label_value("a \"b\"\\\n"), "a \\\"b\\\"\\\\\\n");
+    }
+
+    /// Set `DOCKMASTER_UPDATE_DASHBOARD=1` to write the dashboard after changing the metrics
+    #[test]
+    fn test_grafana_dashboard_is_up_to_date() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("dashboards/grafana-servers.json");
+        let generated = grafana_dashboard();
+        if std::env::var("DOCKMASTER_UPDATE_DASHBOARD").is_ok() {
+            let text = serde_json::to_string_pretty(&generated).unwrap() + "\n";
+            std::fs::write(&path, text).unwrap();
+        }
+        let saved: Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(
+            saved, generated,
+            "the dashboard is out of date, run the tests with DOCKMASTER_UPDATE_DASHBOARD=1"
+        );
+    }
+
+    #[test]
+    fn test_grafana_dashboard_uses_served_series() {
+        let dashboard = grafana_dashboard();
+        let panels = dashboard["panels"].as_array().unwrap();
+        let per_server: Vec<_> = METRICS.iter().filter(|m| !m.labels.is_empty()).collect();
+        assert_eq!(panels.len(), per_server.len());
+        for (panel, metric) in panels.iter().zip(per_server) {
+            let expr = panel["targets"][0]["expr"].as_str().unwrap();
+            assert!(expr.contains(metric.name), "{}", expr);
+            assert!(metric.labels.contains(&SERVER_ID_LABEL));
+            assert!(metric.labels.contains(&SERVER_NAME_LABEL));
+            let legend = panel["targets"][0]["legendFormat"].as_str().unwrap();
+            for label in metric.labels {
+                assert!(legend.contains(&format!("{{{{{}}}}}", label)));
+            }
+        }
+        // Only the histogram is labeled by tool
+        let by_tool = METRICS
+            .iter()
+            .filter(|m| m.labels.contains(&TOOL_NAME_LABEL));
+        assert_eq!(by_tool.count(), 1);
+    }
+}
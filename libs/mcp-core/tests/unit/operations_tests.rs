@@ -0,0 +1,116 @@
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use mcp_core::events::{DockmasterEvent, EventBus, InstallProgressReporter, InstallStage};
+    use mcp_core::operations::{OperationRegistry, OperationState};
+    use serde_json::json;
+
+    async fn wait_until_finished(registry: &OperationRegistry, id: &str) -> OperationState {
+        for _ in 0..100 {
+            let state = registry.get(id).unwrap().state;
+            if state.is_finished() {
+                return state;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("operation {} did not finish", id);
+    }
+
+    #[tokio::test]
+    async fn test_operation_succeeds_with_result() {
+        let bus = EventBus::new();
+        let mut receiver = bus.subscribe();
+        let registry = OperationRegistry::new(bus);
+
+        let id = registry.spawn(
+            "restart",
+            Some("fetch".into()),
+            false,
+            |handle| async move {
+                handle.set_progress(50);
+                Ok(json!({ "message": "restarted" }))
+            },
+        );
+
+        assert_eq!(
+            wait_until_finished(&registry, &id).await,
+            OperationState::Succeeded
+        );
+        let operation = registry.get(&id).unwrap();
+        assert_eq!(operation.progress, Some(100));
+        assert_eq!(operation.result, Some(json!({ "message": "restarted" })));
+
+        let mut states = Vec::new();
+        while let Ok(envelope) = receiver.try_recv() {
+            if let DockmasterEvent::OperationUpdate(operation) = envelope.event {
+                states.push(operation.state);
+            }
+        }
+        assert_eq!(states.first(), Some(&OperationState::Queued));
+        assert_eq!(states.last(), Some(&OperationState::Succeeded));
+    }
+
+    #[tokio::test]
+    async fn test_failed_operation_keeps_error() {
+        let registry = OperationRegistry::new(EventBus::new());
+        let id = registry.spawn("import", None, true, |_| async move {
+            Err("repository not found".to_string())
+        });
+
+        assert_eq!(
+            wait_until_finished(&registry, &id).await,
+            OperationState::Failed
+        );
+        assert_eq!(
+            registry.get(&id).unwrap().error.as_deref(),
+            Some("repository not found")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cancel_operation() {
+        let registry = OperationRegistry::new(EventBus::new());
+        let cancellable = registry.spawn("install", None, true, |_| async move {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            Ok(json!(null))
+        });
+        let not_cancellable = registry.spawn("restart", None, false, |_| async move {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            Ok(json!(null))
+        });
+
+        let cancelled = registry.cancel(&cancellable).unwrap();
+        assert_eq!(cancelled.state, OperationState::Cancelled);
+        assert!(registry.cancel(&cancellable).is_err());
+        assert!(registry.cancel(&not_cancellable).is_err());
+        assert!(registry.cancel("missing").is_err());
+
+        let listed: Vec<String> = registry.list().into_iter().map(|op| op.id).collect();
+        assert_eq!(listed.len(), 2);
+        assert!(listed.contains(&cancellable));
+    }
+
+    #[tokio::test]
+    async fn test_final_event_of_tracked_install_is_operation_update() {
+        let bus = EventBus::new();
+        let mut receiver = bus.subscribe();
+        let registry = OperationRegistry::new(bus.clone());
+        let id = registry.spawn("install", None, true, |_| async move {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            Ok(json!(null))
+        });
+        let reporter = InstallProgressReporter::new(bus, id.clone(), "a".into());
+        reporter.report(InstallStage::Done, None);
+        registry.cancel(&id).unwrap();
+
+        let mut finals = Vec::new();
+        while let Ok(envelope) = receiver.try_recv() {
+            if registry.is_final_event(&envelope.event) {
+                finals.push(envelope.event);
+            }
+        }
+        assert_eq!(finals.len(), 1);
+        assert!(matches!(finals[0], DockmasterEvent::OperationUpdate(_)));
+    }
+}
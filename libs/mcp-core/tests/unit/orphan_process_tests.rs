@@ -0,0 +1,194 @@
+#[cfg(test)]
+mod tests {
+    use mcp_core::{
+        core::{mcp_core_builder::MCPCoreBuilder, mcp_core_proxy_ext::McpCoreProxyExt},
+        database::db_manager::DBManager,
+        mcp_state::mcp_state::{docker_container_name, inject_container_name},
+        models::types::SpawnedProcessRecord,
+        utils::process::{
+            find_process_by_spawn_id, kill_spawned_processes, spawned_process_tree, SPAWN_ID_ENV,
+        },
+    };
+    use tempfile::tempdir;
+
+    fn record(server_id: &str, pid: Option<u32>) -> SpawnedProcessRecord {
+        SpawnedProcessRecord {
+            server_id: server_id.to_string(),
+            spawn_id: format!("spawn-{}", server_id),
+            pid,
+            start_time: pid.map(|_| 1_700_000_000),
+            command: pid.map(|_| "node index.js".to_string()),
+            container_name: None,
+        }
+    }
+
+    #[test]
+    fn test_process_records_round_trip() {
+        let temp_dir = tempdir().expect("Failed to create temp directory");
+        let db = DBManager::with_path(temp_dir.path().join("mcp-dockmaster.db"))
+            .expect("Failed to create database");
+        db.apply_migrations().expect("Failed to apply migrations");
+
+        db.save_server_process(&record("a", Some(42))).unwrap();
+        db.save_server_process(&record("b", None)).unwrap();
+        // Saving again replaces the previous record of the server
+        db.save_server_process(&record("a", Some(43))).unwrap();
+
+        let mut records = db.get_server_processes().unwrap();
+        records.sort_by(|x, y| x.server_id.cmp(&y.server_id));
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].pid, Some(43));
+        assert_eq!(records[0].start_time, Some(1_700_000_000));
+        assert_eq!(records[1].pid, None);
+
+        db.delete_server_process("a").unwrap();
+        let records = db.get_server_processes().unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].server_id, "b");
+    }
+
+    #[test]
+    fn test_docker_container_name_injection() {
+        assert_eq!(
+            docker_container_name("github/server:1"),
+            "mcp-dockmaster-github-server-1"
+        );
+
+        let mut args: Vec<String> = ["run", "-i", "--rm", "mcp/fetch"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert!(inject_container_name(&mut args, "mcp-dockmaster-fetch"));
+        assert_eq!(
+            args,
+            vec![
+                "run",
+                "--name",
+                "mcp-dockmaster-fetch",
+                "-i",
+                "--rm",
+                "mcp/fetch"
+            ]
+        );
+
+        // A container named by the user is left alone
+        let mut named: Vec<String> = ["run", "--name=mine", "mcp/fetch"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert!(!inject_container_name(&mut named, "mcp-dockmaster-fetch"));
+        assert_eq!(named.len(), 3);
+
+        let mut not_run: Vec<String> = vec!["compose".to_string(), "up".to_string()];
+        assert!(!inject_container_name(&mut not_run, "mcp-dockmaster-fetch"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_spawned_process_is_found_and_killed() {
+        let spawn_id = uuid::Uuid::new_v4().to_string();
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .env(SPAWN_ID_ENV, &spawn_id)
+            .spawn()
+            .expect("Failed to spawn sleep");
+
+        let found = find_process_by_spawn_id(&spawn_id).expect("Process not found");
+        assert_eq!(found.pid, child.id());
+        assert!(found.command.contains("sleep"));
+
+        // An unrelated spawn id doesn't match anything
+        assert!(find_process_by_spawn_id("not-a-spawn-id").is_none());
+
+        let killed = kill_spawned_processes(
+            &spawn_id,
+            Some(found.pid),
+            Some(found.start_time),
+            Some(&found.command),
+        );
+        assert_eq!(killed, 1);
+        let status = child.wait().expect("Failed to wait for sleep");
+        assert!(!status.success());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_process_tree_is_killed() {
+        use std::io::{BufRead, BufReader};
+
+        let spawn_id = uuid::Uuid::new_v4().to_string();
+        // The grandchild has a clean environment, only its parent tells it apart
+        let mut child = std::process::Command::new("sh")
+            .args(["-c", "env -i sleep 30 & echo $!; wait"])
+            .env(SPAWN_ID_ENV, &spawn_id)
+            .stdout(std::process::Stdio::piped())
+            .spawn()
+            .expect("Failed to spawn sh");
+        let mut line = String::new();
+        BufReader::new(child.stdout.take().unwrap())
+            .read_line(&mut line)
+            .unwrap();
+        let grandchild: u32 = line.trim().parse().unwrap();
+
+        let tree = spawned_process_tree(&spawn_id, None, None, None);
+        assert!(tree.iter().any(|process| process.pid == child.id()));
+        assert!(tree.iter().any(|process| process.pid == grandchild));
+
+        assert_eq!(kill_spawned_processes(&spawn_id, None, None, None), 2);
+        assert!(!child.wait().expect("Failed to wait for sh").success());
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_console_process_tree_is_killed() {
+        use mcp_core::utils::process::is_process_alive;
+
+        let spawn_id = uuid::Uuid::new_v4().to_string();
+        // Like a `.cmd` shim, cmd.exe runs the server as a child console process
+        let mut child = std::process::Command::new("cmd")
+            .args(["/C", "ping -n 30 127.0.0.1 > NUL"])
+            .env(SPAWN_ID_ENV, &spawn_id)
+            .spawn()
+            .expect("Failed to spawn cmd");
+        std::thread::sleep(std::time::Duration::from_millis(500));
+        let tree = spawned_process_tree(&spawn_id, None, None, None);
+        assert!(tree.len() >= 2, "{:?}", tree);
+
+        assert!(kill_spawned_processes(&spawn_id, None, None, None) >= 2);
+        assert!(!child.wait().expect("Failed to wait for cmd").success());
+        std::thread::sleep(std::time::Duration::from_millis(500));
+        assert!(!tree.iter().any(is_process_alive));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_cleanup_orphans_on_demand() {
+        let temp_dir = tempdir().expect("Failed to create temp directory");
+        let db = DBManager::with_path(temp_dir.path().join("mcp-dockmaster.db"))
+            .expect("Failed to create database");
+        db.apply_migrations().expect("Failed to apply migrations");
+
+        let spawn_id = uuid::Uuid::new_v4().to_string();
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .env(SPAWN_ID_ENV, &spawn_id)
+            .spawn()
+            .expect("Failed to spawn sleep");
+        // The pid was recycled by another program, only the spawn id matches
+        db.save_server_process(&SpawnedProcessRecord {
+            spawn_id,
+            ..record("crashed", Some(child.id()))
+        })
+        .unwrap();
+
+        let core = MCPCoreBuilder::new(db.clone()).build();
+        let report = core.cleanup_orphans().await;
+        assert_eq!(report.processes_terminated, 1);
+        assert_eq!(report.servers, vec!["crashed"]);
+        assert!(db.get_server_processes().unwrap().is_empty());
+        assert!(!child.wait().expect("Failed to wait for sleep").success());
+
+        // Nothing is left for a second run
+        assert!(core.cleanup_orphans().await.is_empty());
+    }
+}
@@ -0,0 +1,171 @@
+#[cfg(test)]
+mod tests {
+    use mcp_core::{
+        database::db_manager::DBManager,
+        models::types::{ServerDefinition, ServerToolInfo},
+        utils::json_schema::{check_structured_content, declared_output_schema, validate},
+    };
+    use serde_json::{json, Value};
+    use tempfile::tempdir;
+
+    fn output_schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "temperature": { "type": "number", "minimum": -100, "maximum": 100 },
+                "unit": { "enum": ["celsius", "fahrenheit"] },
+                "days": {
+                    "type": "array",
+                    "items": { "type": "integer" },
+                    "maxItems": 2
+                },
+                "station": { "type": ["string", "null"], "minLength": 3 }
+            },
+            "required": ["temperature", "unit"],
+            "additionalProperties": false
+        })
+    }
+
+    #[test]
+    fn test_validate() {
+        let schema = output_schema();
+        let cases = vec![
+            (json!({ "temperature": 21.5, "unit": "celsius" }), vec![]),
+            (
+                json!({ "temperature": 21.5, "unit": "celsius", "station": null }),
+                vec![],
+            ),
+            (
+                json!({ "temperature": "21", "unit": "kelvin" }),
+                vec![
+                    "$.temperature should be number, got string",
+                    "$.unit is not one of the allowed values",
+                ],
+            ),
+            (
+                json!({ "unit": "celsius", "wind": 3 }),
+                vec!["$.temperature is missing", "$.wind is not allowed"],
+            ),
+            (
+                json!({ "temperature": 120, "unit": "celsius", "days": [1, 2.5, 3] }),
+                vec![
+                    "$.days[1] should be integer, got number",
+                    "$.days should have at most 2 items",
+                    "$.temperature should be at most 100",
+                ],
+            ),
+            (
+                json!({ "temperature": 0, "unit": "celsius", "station": "ab" }),
+                vec!["$.station should have at least 3 characters"],
+            ),
+            (json!([]), vec!["$ should be object, got array"]),
+        ];
+
+        for (value, expected) in cases {
+            let mut errors = validate(&value, &schema, "$");
+            errors.sort();
+            let mut expected: Vec<String> = expected.into_iter().map(String::from).collect();
+            expected.sort();
+            assert_eq!(errors, expected, "{}", value);
+        }
+    }
+
+    #[test]
+    fn test_validate_combinators() {
+        let schema = json!({
+            "oneOf": [{ "type": "string" }, { "type": "integer" }, { "type": "number" }]
+        });
+        assert!(validate(&json!("text"), &schema, "$").is_empty());
+        // An integer is also a number
+        assert_eq!(
+            validate(&json!(3), &schema, "$"),
+            vec!["$ should match exactly one schema, matches 2"]
+        );
+
+        let schema = json!({ "anyOf": [{ "const": "a" }, { "const": "b" }] });
+        assert!(validate(&json!("b"), &schema, "$").is_empty());
+        assert_eq!(
+            validate(&json!("c"), &schema, "$"),
+            vec!["$ doesn't match any of the allowed schemas"]
+        );
+    }
+
+    #[test]
+    fn test_check_structured_content() {
+        let schema = output_schema();
+        let result = |structured: Option<Value>| {
+            let mut result = json!({ "content": [{ "type": "text", "text": "21.5" }] });
+            if let Some(structured) = structured {
+                result["structuredContent"] = structured;
+            }
+            result
+        };
+
+        assert!(check_structured_content(
+            &result(Some(json!({ "temperature": 21.5, "unit": "celsius" }))),
+            &schema
+        )
+        .is_empty());
+        assert_eq!(
+            check_structured_content(&result(None), &schema),
+            vec!["structuredContent is missing"]
+        );
+        assert_eq!(
+            check_structured_content(&result(Some(json!({ "unit": "celsius" }))), &schema),
+            vec!["structuredContent.temperature is missing"]
+        );
+
+        // Failed calls don't have to follow the schema
+        let mut error = result(None);
+        error["isError"] = json!(true);
+        assert!(check_structured_content(&error, &schema).is_empty());
+    }
+
+    #[test]
+    fn test_discovered_output_schema() {
+        let tool = json!({ "name": "weather", "inputSchema": {}, "outputSchema": output_schema() });
+        assert_eq!(declared_output_schema(&tool), Some(output_schema()));
+        assert_eq!(declared_output_schema(&json!({ "name": "weather" })), None);
+        assert_eq!(
+            declared_output_schema(&json!({ "name": "weather", "outputSchema": "object" })),
+            None
+        );
+    }
+
+    #[test]
+    fn test_output_schema_is_stored() {
+        let temp_dir = tempdir().expect("Failed to create temp directory");
+        let db = DBManager::with_path(temp_dir.path().join("mcp-dockmaster.db"))
+            .expect("Failed to create database");
+        db.apply_migrations().expect("Failed to apply migrations");
+
+        let server: ServerDefinition = serde_json::from_value(json!({
+            "name": "Weather",
+            "description": "",
+            "enabled": true,
+            "tools_type": "node",
+            "configuration": { "command": "node", "strict_output_schema": true }
+        }))
+        .unwrap();
+        db.save_server("weather", &server).unwrap();
+
+        let mut tool = ServerToolInfo::from_value(
+            json!({ "name": "forecast", "description": "", "outputSchema": output_schema() }),
+            "weather".to_string(),
+        )
+        .unwrap();
+        db.save_server_tool(&tool).unwrap();
+        let stored = db.get_server_tool("forecast", "weather").unwrap();
+        assert_eq!(stored.output_schema, Some(output_schema()));
+
+        tool.output_schema = None;
+        db.save_server_tool(&tool).unwrap();
+        assert_eq!(
+            db.get_server_tools("weather").unwrap()[0].output_schema,
+            None
+        );
+
+        let configuration = db.get_server("weather").unwrap().configuration.unwrap();
+        assert_eq!(configuration.strict_output_schema, Some(true));
+    }
+}
@@ -0,0 +1,99 @@
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    use mcp_core::{
+        diagnostics::REDACTED,
+        utils::payload::{payload_summary, redact_payload},
+    };
+    use serde_json::json;
+
+    #[test]
+    fn test_payload_summary_hides_values() {
+        let arguments = json!({ "path": "/home/me/.ssh/id_rsa", "encoding": "utf-8" });
+        let summary = payload_summary(&arguments);
+
+        assert!(summary.starts_with("{"), "{}", summary);
+        assert!(summary.contains("path") && summary.contains("encoding"));
+        assert!(summary.ends_with(&format!(
+            "({} bytes)",
+            serde_json::to_vec(&arguments).unwrap().len()
+        )));
+        assert!(!summary.contains("id_rsa"));
+
+        assert_eq!(payload_summary(&json!("secret")), "string (8 bytes)");
+        assert_eq!(payload_summary(&json!([1, 2])), "array of 2 (5 bytes)");
+    }
+
+    #[test]
+    fn test_redact_payload() {
+        let payload = json!({
+            "query": "weather",
+            "apiKey": "sk-123",
+            "options": { "auth_token": "abc", "retries": 3, "password": null },
+            "headers": [{ "Authorization": "Bearer abc" }]
+        });
+
+        assert_eq!(
+            redact_payload(&payload),
+            json!({
+                "query": "weather",
+                "apiKey": REDACTED,
+                "options": { "auth_token": REDACTED, "retries": 3, "password": null },
+                "headers": [{ "Authorization": REDACTED }]
+            })
+        );
+    }
+
+    fn rust_files(dir: &Path, files: &mut Vec<PathBuf>) {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                rust_files(&path, files);
+            } else if path.extension().is_some_and(|ext| ext == "rs") {
+                files.push(path);
+            }
+        }
+    }
+
+    /// Library code logs through `log`, printing bypasses the log level and the
+    /// redaction of payloads
+    #[test]
+    fn test_no_printing_in_library_code() {
+        let src = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+        let mut files = Vec::new();
+        rust_files(&src, &mut files);
+
+        let mut offenders = Vec::new();
+        for file in files {
+            let content = fs::read_to_string(&file).unwrap();
+            for (number, line) in content.lines().enumerate() {
+                if line.trim_start().starts_with("//") {
+                    continue;
+                }
+                if ["println!", "print!", "eprintln!", "eprint!", "dbg!"]
+                    .iter()
+                    .any(|mac| {
+                        line.match_indices(mac).any(|(start, _)| {
+                            // Skip longer names such as `eprintln!` when looking for `println!`
+                            !line[..start].ends_with(|c: char| c.is_alphanumeric() || c == '_')
+                        })
+                    })
+                {
+                    offenders.push(format!(
+                        "{}:{}",
+                        file.strip_prefix(&src).unwrap().display(),
+                        number + 1
+                    ));
+                }
+            }
+        }
+
+        assert!(
+            offenders.is_empty(),
+            "use the log macros instead of printing: {}",
+            offenders.join(", ")
+        );
+    }
+}
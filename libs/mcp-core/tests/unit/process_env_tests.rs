@@ -0,0 +1,211 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use mcp_core::{
+        core::{mcp_core_builder::MCPCoreBuilder, mcp_core_proxy_ext::McpCoreProxyExt},
+        database::db_manager::DBManager,
+        diagnostics::REDACTED,
+        mcp_state::{
+            launch_plan::LaunchPlan,
+            process_env::{is_baseline_env_var, isolate_command, ProcessEnv},
+        },
+        models::types::{ServerConfigUpdateRequest, ServerDefinition},
+    };
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    fn parent() -> Vec<(String, String)> {
+        [
+            ("PATH", "/usr/bin:/bin"),
+            ("HOME", "/home/me"),
+            ("LC_ALL", "C.UTF-8"),
+            ("GITHUB_TOKEN", "ghp_parent"),
+            ("TAURI_SIGNING_PRIVATE_KEY", "updater-key"),
+            ("HTTPS_PROXY", "http://proxy:3128"),
+        ]
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect()
+    }
+
+    fn isolated(passthrough: &[&str]) -> ProcessEnv {
+        ProcessEnv {
+            inherit: false,
+            passthrough: passthrough.iter().map(|name| name.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_baseline() {
+        assert!(is_baseline_env_var("PATH"));
+        assert!(is_baseline_env_var("LC_CTYPE"));
+        assert!(!is_baseline_env_var("GITHUB_TOKEN"));
+    }
+
+    #[test]
+    fn test_removed_vars() {
+        let configured = HashMap::from([("GITHUB_TOKEN".to_string(), "ghp_own".to_string())]);
+
+        let removed = isolated(&[]).removed_vars(parent(), &HashMap::new());
+        assert_eq!(
+            removed,
+            vec!["GITHUB_TOKEN", "HTTPS_PROXY", "TAURI_SIGNING_PRIVATE_KEY"]
+        );
+
+        // Configured variables are set on the process, passed-through ones are kept
+        let removed = isolated(&["HTTPS_PROXY"]).removed_vars(parent(), &configured);
+        assert_eq!(removed, vec!["TAURI_SIGNING_PRIVATE_KEY"]);
+
+        let inherit = ProcessEnv {
+            inherit: true,
+            passthrough: Vec::new(),
+        };
+        assert!(inherit.removed_vars(parent(), &configured).is_empty());
+    }
+
+    #[test]
+    fn test_preview_is_redacted() {
+        let configured = HashMap::from([("API_KEY".to_string(), "sk-configured".to_string())]);
+
+        let preview = isolated(&["HTTPS_PROXY"]).preview(parent(), &configured);
+        assert_eq!(preview["HOME"], "/home/me");
+        assert_eq!(preview["API_KEY"], REDACTED);
+        assert_eq!(preview["HTTPS_PROXY"], REDACTED);
+        assert!(!preview.contains_key("GITHUB_TOKEN"));
+
+        let inherit = ProcessEnv {
+            inherit: true,
+            passthrough: Vec::new(),
+        };
+        let preview = inherit.preview(parent(), &configured);
+        assert_eq!(preview["GITHUB_TOKEN"], REDACTED);
+        assert_eq!(preview["PATH"], "/usr/bin:/bin");
+    }
+
+    #[test]
+    fn test_launch_plan_environment() {
+        let server: ServerDefinition = serde_json::from_value(json!({
+            "name": "Test server",
+            "description": "",
+            "enabled": true,
+            "tools_type": "node",
+            "configuration": {
+                "command": "npx",
+                "args": ["server"],
+                "env": { "API_KEY": "sk-configured" },
+                "env_passthrough": ["HTTPS_PROXY"]
+            }
+        }))
+        .unwrap();
+
+        let plan = LaunchPlan::build_with_parent_env("test", &server, parent()).unwrap();
+        assert!(!plan.inherit_env);
+        assert_eq!(
+            plan.removed_env,
+            vec!["GITHUB_TOKEN", "TAURI_SIGNING_PRIVATE_KEY"]
+        );
+
+        let preview = serde_json::to_value(&plan).unwrap();
+        assert_eq!(preview["effective_env"]["API_KEY"], json!(REDACTED));
+        assert!(preview.get("removed_env").is_none());
+        for secret in ["sk-configured", "ghp_parent", "updater-key", "proxy:3128"] {
+            assert!(!preview.to_string().contains(secret), "{}", secret);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_config_update_sets_inherit_env() {
+        let dir = tempdir().unwrap();
+        let db = DBManager::with_path(dir.path().join("mcp-dockmaster.db")).unwrap();
+        db.apply_migrations().unwrap();
+        let server: ServerDefinition = serde_json::from_value(json!({
+            "name": "Test server",
+            "description": "",
+            "enabled": false,
+            "tools_type": "node",
+            "configuration": { "command": "npx", "args": ["server"], "inherit_env": true }
+        }))
+        .unwrap();
+        db.save_server("test", &server).unwrap();
+        let core = MCPCoreBuilder::new(db.clone()).build();
+
+        let response = core
+            .update_server_config(ServerConfigUpdateRequest {
+                server_id: "test".to_string(),
+                config: HashMap::new(),
+                inherit_env: Some(false),
+                cwd: None,
+            })
+            .await
+            .unwrap();
+        assert!(response.success);
+
+        let server = db.get_server("test").unwrap();
+        let plan = LaunchPlan::build_with_parent_env("test", &server, parent()).unwrap();
+        assert!(!plan.inherit_env);
+        assert!(plan.removed_env.contains(&"GITHUB_TOKEN".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_config_update_sets_inherit_env_without_configuration() {
+        let dir = tempdir().unwrap();
+        let db = DBManager::with_path(dir.path().join("mcp-dockmaster.db")).unwrap();
+        db.apply_migrations().unwrap();
+        let server: ServerDefinition = serde_json::from_value(json!({
+            "name": "Test server",
+            "description": "",
+            "enabled": false,
+            "tools_type": "node",
+            "entry_point": "index.js"
+        }))
+        .unwrap();
+        db.save_server("test", &server).unwrap();
+        let core = MCPCoreBuilder::new(db.clone()).build();
+
+        let response = core
+            .update_server_config(ServerConfigUpdateRequest {
+                server_id: "test".to_string(),
+                config: HashMap::new(),
+                inherit_env: Some(true),
+                cwd: None,
+            })
+            .await
+            .unwrap();
+        assert!(response.success);
+
+        let configuration = db.get_server("test").unwrap().configuration.unwrap();
+        assert_eq!(configuration.inherit_env, Some(true));
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn test_isolated_command_drops_parent_secrets() {
+        let removed = vec!["DM_TEST_SECRET".to_string()];
+        let (program, args) = isolate_command(
+            "sh".to_string(),
+            vec![
+                "-c".to_string(),
+                "echo \"[$DM_TEST_SECRET][$DM_TEST_KEPT]\"".to_string(),
+            ],
+            &removed,
+        );
+        assert_eq!(program, "/usr/bin/env");
+        assert_eq!(&args[..3], &["-u", "DM_TEST_SECRET", "sh"]);
+
+        let output = std::process::Command::new(program)
+            .args(args)
+            .env("DM_TEST_SECRET", "leaked")
+            .env("DM_TEST_KEPT", "kept")
+            .output()
+            .unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "[][kept]");
+    }
+
+    #[test]
+    fn test_nothing_removed_keeps_command() {
+        let (program, args) = isolate_command("node".to_string(), vec!["index.js".into()], &[]);
+        assert_eq!(program, "node");
+        assert_eq!(args, vec!["index.js"]);
+    }
+}
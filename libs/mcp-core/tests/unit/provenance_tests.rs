@@ -0,0 +1,83 @@
+#[cfg(test)]
+mod tests {
+    use mcp_core::{
+        database::db_manager::DBManager,
+        models::types::{ProvenanceSource, ServerDefinition, ServerProvenance},
+    };
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    fn server() -> ServerDefinition {
+        ServerDefinition {
+            name: "Filesystem".to_string(),
+            description: "".to_string(),
+            enabled: true,
+            tools_type: "node".to_string(),
+            entry_point: None,
+            configuration: None,
+            distribution: None,
+        }
+    }
+
+    #[test]
+    fn test_provenance_round_trip() {
+        let temp_dir = tempdir().expect("Failed to create temp directory");
+        let db = DBManager::with_path(temp_dir.path().join("mcp-dockmaster.db"))
+            .expect("Failed to create database");
+        db.apply_migrations().expect("Failed to apply migrations");
+
+        db.save_server("filesystem", &server()).unwrap();
+        db.save_server("fetch", &server()).unwrap();
+
+        let registry =
+            ServerProvenance::new(ProvenanceSource::Registry, Some("filesystem".to_string()))
+                .stamped();
+        let llm = ServerProvenance::llm(Some("claude-ai 0.1.0".to_string()), None).stamped();
+        db.save_server_provenance("filesystem", &registry).unwrap();
+        db.save_server_provenance("fetch", &llm).unwrap();
+
+        let provenances = db.get_server_provenances().unwrap();
+        assert_eq!(provenances.len(), 2);
+        assert_eq!(provenances["filesystem"], registry);
+        assert!(provenances["fetch"].is_llm_initiated());
+        assert_eq!(
+            provenances["fetch"].client.as_deref(),
+            Some("claude-ai 0.1.0")
+        );
+
+        // Uninstalling a server forgets where it came from
+        db.delete_server("fetch").unwrap();
+        let provenances = db.get_server_provenances().unwrap();
+        assert_eq!(provenances.len(), 1);
+        assert!(provenances.contains_key("filesystem"));
+    }
+
+    #[test]
+    fn test_stamped_provenance() {
+        let provenance = ServerProvenance::new(
+            ProvenanceSource::Url,
+            Some("https://github.com/owner/repo".to_string()),
+        )
+        .stamped();
+        assert!(provenance.installed_at > 0);
+        assert_eq!(provenance.app_version, env!("CARGO_PKG_VERSION"));
+        assert!(!provenance.is_llm_initiated());
+
+        assert_eq!(
+            serde_json::to_value(&provenance).unwrap()["source"],
+            json!("url")
+        );
+    }
+
+    #[test]
+    fn test_registration_request_provenance() {
+        let provenance: ServerProvenance =
+            serde_json::from_value(json!({ "source": "registry", "source_id": "fetch" })).unwrap();
+        assert_eq!(provenance.source, ProvenanceSource::Registry);
+        assert_eq!(provenance.installed_at, 0);
+
+        assert!(
+            serde_json::from_value::<ServerProvenance>(json!({ "source": "unknown" })).is_err()
+        );
+    }
+}
@@ -0,0 +1,165 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    use mcp_core::{
+        core::{
+            mcp_core_builder::MCPCoreBuilder, mcp_core_proxy_ext::McpCoreProxyExt,
+            mcp_core_rate_limits_ext::McpCoreRateLimitsExt,
+        },
+        database::db_manager::DBManager,
+        models::types::{ServerDefinition, ToolExecutionRequest},
+        rate_limits::{RateLimit, RateLimitKind, RateLimiter},
+        utils::clock::ManualClock,
+    };
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    fn per_minute(calls: u32) -> RateLimit {
+        RateLimit {
+            per_minute: Some(calls),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_rate_limit_validation() {
+        assert!(RateLimit::default().is_unlimited());
+        assert!(per_minute(10).validate().is_ok());
+        assert!(per_minute(0).validate().is_err());
+
+        let parsed: RateLimit =
+            serde_json::from_value(json!({ "per_hour": 100, "max_concurrent": 2 })).unwrap();
+        assert_eq!(parsed.per_minute, None);
+        assert_eq!(parsed.per_hour, Some(100));
+        assert_eq!(parsed.max_concurrent, Some(2));
+    }
+
+    #[test]
+    fn test_calls_per_minute_refill() {
+        let limiter = RateLimiter::new();
+        let limit = per_minute(2);
+        let start = Instant::now();
+
+        // A burst up to the limit goes through
+        limiter.acquire("search", "query", &limit, start).unwrap();
+        limiter.acquire("search", "query", &limit, start).unwrap();
+        let refused = limiter
+            .acquire("search", "query", &limit, start)
+            .unwrap_err();
+        assert_eq!(refused.limit, RateLimitKind::PerMinute);
+        let retry_after = refused.retry_after_ms.unwrap();
+        assert!((29_000..=30_001).contains(&retry_after));
+        assert!(refused.reset_at.is_some());
+
+        // Other tools are counted apart
+        limiter.acquire("search", "fetch", &limit, start).unwrap();
+
+        // One call every 30 s
+        let later = start + Duration::from_secs(30);
+        limiter.acquire("search", "query", &limit, later).unwrap();
+        assert!(limiter.acquire("search", "query", &limit, later).is_err());
+    }
+
+    #[test]
+    fn test_calls_per_hour() {
+        let limiter = RateLimiter::new();
+        let limit = RateLimit {
+            per_minute: Some(10),
+            per_hour: Some(3),
+            ..Default::default()
+        };
+        let start = Instant::now();
+        for minute in 0..3 {
+            let now = start + Duration::from_secs(60 * minute);
+            limiter.acquire("search", "query", &limit, now).unwrap();
+        }
+        let now = start + Duration::from_secs(180);
+        let refused = limiter.acquire("search", "query", &limit, now).unwrap_err();
+        assert_eq!(refused.limit, RateLimitKind::PerHour);
+        assert!(refused.to_string().contains("calls per hour"));
+
+        // A new limit starts from a full bucket
+        limiter.reset("search", "query");
+        limiter.acquire("search", "query", &limit, now).unwrap();
+    }
+
+    #[test]
+    fn test_max_concurrent() {
+        let limiter = RateLimiter::new();
+        let limit = RateLimit {
+            max_concurrent: Some(1),
+            ..Default::default()
+        };
+        let now = Instant::now();
+        let permit = limiter.acquire("search", "query", &limit, now).unwrap();
+        let refused = limiter.acquire("search", "query", &limit, now).unwrap_err();
+        assert_eq!(refused.limit, RateLimitKind::MaxConcurrent);
+        assert_eq!(refused.retry_after_ms, None);
+
+        drop(permit);
+        limiter.acquire("search", "query", &limit, now).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_tool_calls_are_rate_limited() {
+        let dir = tempdir().unwrap();
+        let db = DBManager::with_path(dir.path().join("mcp-dockmaster.db")).unwrap();
+        db.apply_migrations().unwrap();
+        let server: ServerDefinition = serde_json::from_value(json!({
+            "name": "Search",
+            "description": "",
+            "enabled": true,
+            "tools_type": "node",
+            "configuration": { "command": "node" }
+        }))
+        .unwrap();
+        db.save_server("search", &server).unwrap();
+        let clock = Arc::new(ManualClock::new());
+        let core = MCPCoreBuilder::new(db).clock(clock.clone()).build();
+
+        assert!(core
+            .set_tool_rate_limit("unknown", "query", per_minute(1))
+            .await
+            .is_err());
+        core.set_tool_rate_limit("search", "query", per_minute(1))
+            .await
+            .unwrap();
+        assert_eq!(
+            core.tool_rate_limit("search", "query").await,
+            Some(per_minute(1))
+        );
+
+        let call = || {
+            core.execute_proxy_tool(ToolExecutionRequest {
+                tool_id: "search:query".to_string(),
+                parameters: json!({}),
+            })
+        };
+        // Let through, and failing since the server isn't running
+        assert!(call().await.is_err());
+        let refused = call().await.unwrap();
+        assert!(!refused.success);
+        let rate_limited = refused.rate_limited.unwrap();
+        assert_eq!(rate_limited.limit, RateLimitKind::PerMinute);
+        assert_eq!(rate_limited.tool_name, "query");
+        let usage = core
+            .mcp_state
+            .read()
+            .await
+            .tool_stats
+            .get("search", "query");
+        assert_eq!(usage.rate_limited, 1);
+
+        clock.advance(Duration::from_secs(60));
+        assert!(call().await.is_err());
+
+        // Removing every limit removes the rate limit
+        core.set_tool_rate_limit("search", "query", RateLimit::default())
+            .await
+            .unwrap();
+        assert!(core.tool_rate_limits().await.is_empty());
+        assert!(call().await.is_err());
+    }
+}
@@ -0,0 +1,109 @@
+#[cfg(test)]
+mod tests {
+    use mcp_core::{
+        core::{mcp_core_builder::MCPCoreBuilder, mcp_core_read_only_ext::McpCoreReadOnlyExt},
+        database::db_manager::DBManager,
+        read_only::{
+            allowed_in_read_only, is_admin_token, read_only_error, ADMIN_TOKEN_ENV,
+            READ_ONLY_REASON,
+        },
+    };
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_methods_allowed_in_read_only() {
+        for method in [
+            "initialize",
+            "tools/list",
+            "tools/call",
+            "tools/describe",
+            "prompts/list",
+            "resources/read",
+            "registry/list",
+            "server/status",
+            "server/info",
+            "operations/get",
+            "schedules/list",
+            "webhooks/list",
+            "read_only/set",
+        ] {
+            assert!(allowed_in_read_only(method), "{} should be allowed", method);
+        }
+    }
+
+    #[test]
+    fn test_methods_refused_in_read_only() {
+        for method in [
+            "registry/install",
+            "registry/import",
+            "server/config",
+            "server/restart",
+            "server/set_mode",
+            "servers/bulk_update",
+            "servers/restart_all",
+            "server/restart_all",
+            "hooks/set",
+            "schedules/create",
+            "schedules/update",
+            "schedules/delete",
+            "webhooks/create",
+            "webhooks/update",
+            "webhooks/delete",
+            "webhooks/test",
+            "operations/cancel",
+            "database/migrate_legacy",
+            "diagnostics/export",
+            // Methods added later are refused until allowed
+            "nope/nope",
+        ] {
+            assert!(
+                !allowed_in_read_only(method),
+                "{} should be refused",
+                method
+            );
+        }
+    }
+
+    #[test]
+    fn test_read_only_error() {
+        let error = read_only_error("server/config");
+        assert_eq!(error["code"], json!(-32000));
+        assert_eq!(error["data"], json!({ "reason": READ_ONLY_REASON }));
+        assert!(error["message"]
+            .as_str()
+            .unwrap()
+            .contains("'server/config'"));
+    }
+
+    #[test]
+    fn test_admin_token() {
+        std::env::set_var(ADMIN_TOKEN_ENV, "");
+        // An empty token never matches
+        assert!(!is_admin_token(""));
+        std::env::set_var(ADMIN_TOKEN_ENV, "d0ckm4ster-admin");
+        assert!(is_admin_token("d0ckm4ster-admin"));
+        assert!(!is_admin_token("d0ckm4ster"));
+        assert!(!is_admin_token("d0ckm4ster-admin "));
+        std::env::remove_var(ADMIN_TOKEN_ENV);
+        assert!(!is_admin_token("d0ckm4ster-admin"));
+    }
+
+    #[tokio::test]
+    async fn test_read_only_ext() {
+        let dir = tempdir().unwrap();
+        let db = DBManager::with_path(dir.path().join("mcp-dockmaster.db")).unwrap();
+        db.apply_migrations().unwrap();
+        let core = MCPCoreBuilder::new(db.clone()).build();
+
+        assert!(!core.is_read_only().await);
+        core.set_read_only(true).await.unwrap();
+        assert!(core.is_read_only().await);
+
+        // The mode is kept across restarts
+        let restarted = MCPCoreBuilder::new(db).build();
+        assert!(restarted.is_read_only().await);
+        restarted.set_read_only(false).await.unwrap();
+        assert!(!restarted.is_read_only().await);
+    }
+}
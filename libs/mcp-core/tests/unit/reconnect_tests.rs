@@ -0,0 +1,83 @@
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use mcp_core::{
+        core::mcp_core_builder::MCPCoreBuilder,
+        database::db_manager::DBManager,
+        mcp_state::reconnect::{
+            DeadConnection, ReconnectBudget, RECONNECT_LIMIT, RECONNECT_WINDOW,
+        },
+    };
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_dead_connection_of() {
+        assert_eq!(
+            DeadConnection::of("Tool execution error: Broken pipe (os error 32)"),
+            Some(DeadConnection::WriteFailed)
+        );
+        assert_eq!(
+            DeadConnection::of("Failed to write to process stdin: closed"),
+            Some(DeadConnection::WriteFailed)
+        );
+        assert_eq!(
+            DeadConnection::of("Transport error: Channel closed"),
+            Some(DeadConnection::Closed)
+        );
+        assert_eq!(
+            DeadConnection::of("Tool execution error: Server process closed connection"),
+            Some(DeadConnection::Closed)
+        );
+        assert_eq!(
+            DeadConnection::of("Tool execution error: invalid params"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_retry_policy() {
+        // The server never got the request
+        assert!(DeadConnection::WriteFailed.may_retry(false));
+        // It may have run the call already
+        assert!(!DeadConnection::Closed.may_retry(false));
+        assert!(DeadConnection::Closed.may_retry(true));
+    }
+
+    #[test]
+    fn test_reconnect_budget() {
+        let budget = ReconnectBudget::new();
+        let start = Instant::now();
+        for _ in 0..RECONNECT_LIMIT {
+            assert!(budget.try_take("search", start));
+        }
+        assert!(!budget.try_take("search", start));
+        assert!(budget.try_take("other", start));
+
+        // Reconnects older than the window don't count anymore
+        let later = start + RECONNECT_WINDOW + Duration::from_secs(1);
+        for _ in 0..RECONNECT_LIMIT {
+            assert!(budget.try_take("search", later));
+        }
+        assert!(!budget.try_take("search", later));
+    }
+
+    #[tokio::test]
+    async fn test_replaced_process_is_not_reconnected() {
+        let dir = tempdir().unwrap();
+        let db = DBManager::with_path(dir.path().join("mcp-dockmaster.db")).unwrap();
+        db.apply_migrations().unwrap();
+        let core = MCPCoreBuilder::new(db).build();
+        let mut events = core.event_bus.subscribe();
+
+        let mcp_state = core.mcp_state.read().await;
+        mcp_state
+            .reconnect_server("search", "spawn", "Broken pipe", true)
+            .await
+            .unwrap();
+        assert!(events.try_recv().is_err());
+        assert!(mcp_state
+            .reconnect_budget
+            .try_take("search", Instant::now()));
+    }
+}
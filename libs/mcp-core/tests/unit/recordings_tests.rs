@@ -0,0 +1,148 @@
+#[cfg(test)]
+mod tests {
+    use mcp_core::{
+        core::{
+            mcp_core_builder::MCPCoreBuilder, mcp_core_proxy_ext::McpCoreProxyExt,
+            mcp_core_recordings_ext::McpCoreRecordingsExt,
+        },
+        models::types::{
+            CallMode, ReplayMiss, ServerCallMode, ServerDefinition, ToolExecutionRequest,
+        },
+        recordings::{call_key, canonicalize, RecordedCall, RecordingStore},
+    };
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    use crate::common::db;
+
+    fn server() -> ServerDefinition {
+        serde_json::from_value(json!({
+            "name": "Weather",
+            "description": "",
+            "enabled": false,
+            "tools_type": "node",
+            "configuration": { "command": "node", "args": ["weather.js"] }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_call_key_ignores_key_order() {
+        let a = json!({ "city": "Paris", "units": { "temp": "c", "wind": "kmh" } });
+        let b = json!({ "units": { "wind": "kmh", "temp": "c" }, "city": "Paris" });
+        assert_eq!(canonicalize(&a).to_string(), canonicalize(&b).to_string());
+        assert_eq!(call_key("forecast", &a), call_key("forecast", &b));
+        assert_ne!(call_key("forecast", &a), call_key("alerts", &a));
+        assert_ne!(
+            call_key("forecast", &a),
+            call_key("forecast", &json!({ "city": "Lyon" }))
+        );
+    }
+
+    #[test]
+    fn test_store_replays_the_latest_recording() {
+        let dir = tempdir().unwrap();
+        let store = RecordingStore::new(dir.path(), "weather");
+        let args = json!({ "city": "Paris" });
+        assert!(store.find("forecast", &args).is_none());
+
+        store
+            .append(&RecordedCall::new("forecast", &args, json!("rain")))
+            .unwrap();
+        store
+            .append(&RecordedCall::new("forecast", &args, json!("sun")))
+            .unwrap();
+        assert!(store.path().ends_with("recordings/weather.jsonl"));
+        assert_eq!(store.calls().len(), 2);
+        assert_eq!(store.find("forecast", &args), Some(json!("sun")));
+        assert!(store.find("forecast", &json!({ "city": "Lyon" })).is_none());
+    }
+
+    #[test]
+    fn test_call_modes_follow_the_server() {
+        let dir = tempdir().unwrap();
+        let db = db(&dir);
+        db.save_server("weather", &server()).unwrap();
+        let replay = ServerCallMode {
+            mode: CallMode::Replay,
+            on_miss: ReplayMiss::PassThrough,
+        };
+        db.save_server_call_mode("weather", &replay).unwrap();
+        assert_eq!(db.get_server_call_modes().unwrap()["weather"], replay);
+
+        db.rename_server("weather", "forecasts").unwrap();
+        let modes = db.get_server_call_modes().unwrap();
+        assert_eq!(modes["forecasts"], replay);
+        assert!(!modes.contains_key("weather"));
+
+        db.delete_server("forecasts").unwrap();
+        assert!(db.get_server_call_modes().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_call_mode_wire_format() {
+        let mode: ServerCallMode = serde_json::from_value(json!({ "mode": "replay" })).unwrap();
+        assert_eq!(mode.on_miss, ReplayMiss::Error);
+        assert_eq!(
+            serde_json::to_value(ServerCallMode {
+                mode: CallMode::Record,
+                on_miss: ReplayMiss::PassThrough,
+            })
+            .unwrap(),
+            json!({ "mode": "record", "on_miss": "pass_through" })
+        );
+        assert!(serde_json::from_value::<ServerCallMode>(json!({ "mode": "mock" })).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_replay_answers_without_the_server() {
+        let dir = tempdir().unwrap();
+        let db = db(&dir);
+        db.save_server("weather", &server()).unwrap();
+        let core = MCPCoreBuilder::new(db).build();
+
+        let replay = ServerCallMode {
+            mode: CallMode::Replay,
+            on_miss: ReplayMiss::Error,
+        };
+        core.set_server_call_mode("weather", replay).await.unwrap();
+        assert!(core.set_server_call_mode("missing", replay).await.is_err());
+        core.recording_store("weather")
+            .await
+            .append(&RecordedCall::new(
+                "forecast",
+                &json!({ "city": "Paris", "days": 2 }),
+                json!({ "content": [{ "type": "text", "text": "rain" }] }),
+            ))
+            .unwrap();
+
+        // The server is stopped, the answer comes from the recording
+        let response = core
+            .execute_proxy_tool(ToolExecutionRequest {
+                tool_id: "weather:forecast".to_string(),
+                parameters: json!({ "days": 2, "city": "Paris" }),
+            })
+            .await
+            .unwrap();
+        assert_eq!(response.result.unwrap()["content"][0]["text"], "rain");
+        assert_eq!(
+            response.warnings,
+            vec!["Replayed from the recordings of weather"]
+        );
+
+        let error = core
+            .execute_proxy_tool(ToolExecutionRequest {
+                tool_id: "weather:forecast".to_string(),
+                parameters: json!({ "city": "Lyon" }),
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error,
+            "No recording of forecast on weather with these arguments"
+        );
+
+        let servers = core.list_servers().await.unwrap();
+        assert_eq!(servers[0].call_mode, replay);
+    }
+}
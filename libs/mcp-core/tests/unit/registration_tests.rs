@@ -0,0 +1,226 @@
+#[cfg(test)]
+mod tests {
+    use mcp_core::{
+        core::{
+            mcp_core::MCPCore, mcp_core_builder::MCPCoreBuilder,
+            mcp_core_proxy_ext::McpCoreProxyExt,
+        },
+        database::db_manager::DBManager,
+        http_server::handlers::{handle_register_tool, InstallOrigin},
+        models::{
+            registration::{parse_registration_request, ResolvedRegistration},
+            types::{EnvValue, ProvenanceSource, ServerRegistrationRequest, ServerStatus},
+        },
+    };
+    use serde_json::{json, Value};
+    use tempfile::{tempdir, TempDir};
+
+    /// A registration as the app sends it
+    fn app_payload() -> Value {
+        json!({
+            "server_id": "Weather Server",
+            "server_name": "Weather",
+            "description": "Forecasts",
+            "tools_type": "node",
+            "configuration": {
+                "command": "npx",
+                "args": ["-y", "weather-mcp"],
+                "env": { "units": "metric" },
+                "comand": "typo"
+            },
+            "authentication": {
+                "env": { "api_key": { "description": "Weather API key", "descripton": "" } },
+                "token_url": "https://example.com/token"
+            },
+            "auto_start": false
+        })
+    }
+
+    /// The same registration by name, as JSON-RPC clients send it
+    fn by_name_payload() -> Value {
+        let mut payload = app_payload();
+        let obj = payload.as_object_mut().unwrap();
+        for (from, to) in [
+            ("server_id", "id"),
+            ("server_name", "name"),
+            ("tools_type", "type"),
+        ] {
+            let value = obj.remove(from).unwrap();
+            obj.insert(to.to_string(), value);
+        }
+        payload
+    }
+
+    fn core() -> (MCPCore, TempDir) {
+        let dir = tempdir().unwrap();
+        let db = DBManager::with_path(dir.path().join("mcp-dockmaster.db")).unwrap();
+        db.apply_migrations().unwrap();
+        (MCPCoreBuilder::new(db).build(), dir)
+    }
+
+    fn sorted(mut warnings: Vec<String>) -> Vec<String> {
+        warnings.sort();
+        warnings
+    }
+
+    #[test]
+    fn test_resolve() {
+        let request: ServerRegistrationRequest = serde_json::from_value(app_payload()).unwrap();
+        let registration = ResolvedRegistration::resolve(request).unwrap();
+        assert!(!registration.enabled);
+        assert_eq!(registration.provenance.source, ProvenanceSource::Manual);
+        assert!(registration.provenance.installed_at > 0);
+        assert_eq!(
+            registration.warnings,
+            vec!["Field 'authentication.token_url' was ignored"]
+        );
+        let env = registration.configuration.env.as_ref().unwrap();
+        assert_eq!(env["UNITS"].default, Some(EnvValue::from("metric")));
+        assert!(env.contains_key("API_KEY"));
+        let definition = registration.definition();
+        assert!(!definition.enabled);
+        assert_eq!(definition.name, "Weather");
+
+        let mut payload = app_payload();
+        payload["configuration"]["command"] = json!(" ");
+        let request: ServerRegistrationRequest = serde_json::from_value(payload).unwrap();
+        let error = ResolvedRegistration::resolve(request).unwrap_err();
+        assert!(
+            error.contains("configuration.command is empty"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn test_parse_registration_request() {
+        let (request, warnings) = parse_registration_request(app_payload()).unwrap();
+        assert_eq!(request.enabled, Some(false));
+        assert_eq!(
+            sorted(warnings),
+            vec![
+                "Unknown field 'authentication.env.api_key.descripton' was ignored",
+                "Unknown field 'configuration.comand' was ignored",
+            ]
+        );
+
+        let mut payload = app_payload();
+        payload["configuration"] = json!({ "args": "-y" });
+        let error = parse_registration_request(payload).unwrap_err();
+        assert!(
+            error.contains("configuration.command is missing"),
+            "{}",
+            error
+        );
+        assert!(error.contains("configuration.args"), "{}", error);
+
+        let mut payload = app_payload();
+        payload.as_object_mut().unwrap().remove("server_name");
+        let error = parse_registration_request(payload).unwrap_err();
+        assert!(
+            error.starts_with("Invalid registration request"),
+            "{}",
+            error
+        );
+    }
+
+    #[tokio::test]
+    async fn test_entry_points_register_the_same_server() {
+        let (app_core, _app_dir) = core();
+        let (request, unknown) = parse_registration_request(app_payload()).unwrap();
+        let mut app_response = app_core.register_server(request).await.unwrap();
+        app_response.warnings.extend(unknown);
+
+        let (rpc_core, _rpc_dir) = core();
+        let rpc_response = handle_register_tool(
+            rpc_core.clone(),
+            by_name_payload(),
+            InstallOrigin::McpSession {
+                client: Some("claude".to_string()),
+            },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(app_response.tool_id.as_deref(), Some("weather-server"));
+        assert_eq!(rpc_response.tool_id, app_response.tool_id);
+        assert!(matches!(app_response.status, Some(ServerStatus::Stopped)));
+        assert!(matches!(rpc_response.status, Some(ServerStatus::Stopped)));
+        assert_eq!(
+            app_response.message,
+            "Tool 'Weather' registered, not started"
+        );
+        assert_eq!(rpc_response.message, "Tool installed successfully");
+        assert_eq!(sorted(rpc_response.warnings), sorted(app_response.warnings));
+
+        let app_server = app_core
+            .tool_registry
+            .read()
+            .await
+            .get_server("weather-server")
+            .unwrap();
+        let rpc_server = rpc_core
+            .tool_registry
+            .read()
+            .await
+            .get_server("weather-server")
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(&app_server).unwrap(),
+            serde_json::to_value(&rpc_server).unwrap()
+        );
+
+        // Only the provenance tells them apart
+        let provenance = |core: &MCPCore| {
+            let core = core.clone();
+            async move {
+                core.tool_registry
+                    .read()
+                    .await
+                    .get_server_provenances()
+                    .unwrap()
+                    .remove("weather-server")
+                    .unwrap()
+            }
+        };
+        assert_eq!(provenance(&app_core).await.source, ProvenanceSource::Manual);
+        let rpc_provenance = provenance(&rpc_core).await;
+        assert_eq!(rpc_provenance.source, ProvenanceSource::Llm);
+        assert_eq!(rpc_provenance.client.as_deref(), Some("claude"));
+    }
+
+    #[tokio::test]
+    async fn test_entry_points_refuse_the_same_requests() {
+        let (core, _dir) = core();
+        let mut payload = app_payload();
+        payload["authentication"] = json!("token");
+        let (request, _) = parse_registration_request(payload).unwrap();
+        let app_error = core.register_server(request).await.unwrap_err();
+
+        let mut payload = by_name_payload();
+        payload["authentication"] = json!("token");
+        let rpc_error = handle_register_tool(core.clone(), payload, InstallOrigin::Api)
+            .await
+            .unwrap_err();
+        assert_eq!(rpc_error.code, -32000);
+        assert_eq!(
+            rpc_error.message,
+            format!("Failed to install tool: {}", app_error)
+        );
+
+        let mut payload = by_name_payload();
+        payload["configuration"] = json!({ "command": 1 });
+        let rpc_error = handle_register_tool(core.clone(), payload, InstallOrigin::Api)
+            .await
+            .unwrap_err();
+        assert_eq!(rpc_error.code, -32602);
+        let mut payload = app_payload();
+        payload["configuration"] = json!({ "command": 1 });
+        assert_eq!(
+            parse_registration_request(payload).unwrap_err(),
+            rpc_error.message
+        );
+
+        assert!(core.list_servers().await.unwrap().is_empty());
+    }
+}
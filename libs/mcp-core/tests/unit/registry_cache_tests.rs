@@ -0,0 +1,170 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use async_trait::async_trait;
+    use mcp_core::{
+        core::mcp_core_builder::MCPCoreBuilder,
+        database::db_manager::DBManager,
+        metrics::MetricsRegistry,
+        models::types::ErrorResponse,
+        registry::{
+            registry_cache::{RegistryCache, REFRESH_RETRY_INTERVAL},
+            registry_client::RegistryClient,
+        },
+        utils::clock::ManualClock,
+    };
+    use serde_json::{json, Value};
+    use tempfile::tempdir;
+
+    /// Registry whose response can be changed, counting the fetches
+    struct FakeRegistry {
+        response: Mutex<Result<Value, String>>,
+        fetches: AtomicUsize,
+    }
+
+    impl FakeRegistry {
+        fn new(response: Result<Value, String>) -> Arc<Self> {
+            Arc::new(Self {
+                response: Mutex::new(response),
+                fetches: AtomicUsize::new(0),
+            })
+        }
+
+        fn respond(&self, response: Result<Value, String>) {
+            *self.response.lock().unwrap() = response;
+        }
+
+        fn fetches(&self) -> usize {
+            self.fetches.load(Ordering::SeqCst)
+        }
+    }
+
+    #[async_trait]
+    impl RegistryClient for FakeRegistry {
+        async fn fetch_registry(&self) -> Result<Value, ErrorResponse> {
+            self.fetches.fetch_add(1, Ordering::SeqCst);
+            self.response
+                .lock()
+                .unwrap()
+                .clone()
+                .map_err(|message| ErrorResponse {
+                    code: -32000,
+                    message,
+                })
+        }
+    }
+
+    fn registry(version: u32) -> Value {
+        json!({ "count": 0, "version": version, "categories": {}, "tags": {}, "tools": [] })
+    }
+
+    fn cache(
+        response: Result<Value, String>,
+    ) -> (
+        RegistryCache,
+        Arc<FakeRegistry>,
+        Arc<ManualClock>,
+        MetricsRegistry,
+    ) {
+        let client = FakeRegistry::new(response);
+        let clock = Arc::new(ManualClock::new());
+        let metrics = MetricsRegistry::new();
+        let cache = RegistryCache::new(client.clone(), clock.clone()).with_metrics(metrics.clone());
+        (cache, client, clock, metrics)
+    }
+
+    fn counter(metrics: &MetricsRegistry, name: &str) -> String {
+        let text = metrics.render(&Default::default(), 0);
+        text.lines()
+            .find_map(|line| line.strip_prefix(&format!("{} ", name)))
+            .unwrap_or_else(|| panic!("{} missing in\n{}", name, text))
+            .to_string()
+    }
+
+    #[tokio::test]
+    async fn test_hits_share_the_parsed_registry() {
+        let (cache, client, _, metrics) = cache(Ok(registry(1)));
+
+        let first = cache.get_registry_tools().await.unwrap();
+        let second = cache.get_registry_tools().await.unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(client.fetches(), 1);
+        assert_eq!(
+            counter(&metrics, "dockmaster_registry_cache_hits_total"),
+            "1"
+        );
+        assert_eq!(
+            counter(&metrics, "dockmaster_registry_cache_misses_total"),
+            "1"
+        );
+        assert_eq!(
+            counter(&metrics, "dockmaster_registry_cache_parse_failures_total"),
+            "0"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failed_refresh_keeps_the_registry() {
+        let (cache, client, clock, _) = cache(Ok(registry(1)));
+        let fetched = cache.get_registry_tools().await.unwrap();
+
+        clock.advance(Duration::from_secs(601));
+        client.respond(Err("registry unreachable".to_string()));
+        let served = cache.get_registry_tools().await.unwrap();
+        assert!(Arc::ptr_eq(&fetched, &served));
+        assert_eq!(client.fetches(), 2);
+
+        // Not fetched again right away
+        cache.get_registry_tools().await.unwrap();
+        assert_eq!(client.fetches(), 2);
+
+        clock.advance(REFRESH_RETRY_INTERVAL);
+        client.respond(Ok(registry(2)));
+        assert_eq!(cache.get_registry_tools().await.unwrap().version, 2);
+        assert_eq!(client.fetches(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_malformed_registry_is_counted() {
+        let (cache, client, clock, metrics) = cache(Ok(json!({ "tools": "none" })));
+
+        let error = cache.get_registry_tools().await.unwrap_err();
+        assert!(error
+            .message
+            .starts_with("Failed to parse tools from registry"));
+        assert_eq!(
+            counter(&metrics, "dockmaster_registry_cache_parse_failures_total"),
+            "1"
+        );
+
+        client.respond(Ok(registry(1)));
+        cache.get_registry_tools().await.unwrap();
+        clock.advance(Duration::from_secs(601));
+        client.respond(Ok(json!({ "tools": "none" })));
+        assert_eq!(cache.get_registry_tools().await.unwrap().version, 1);
+        assert_eq!(
+            counter(&metrics, "dockmaster_registry_cache_parse_failures_total"),
+            "2"
+        );
+        assert_eq!(
+            counter(&metrics, "dockmaster_registry_cache_misses_total"),
+            "3"
+        );
+    }
+
+    #[test]
+    fn test_cores_have_their_own_cache() {
+        let dir = tempdir().unwrap();
+        let core = |name: &str| {
+            let db = DBManager::with_path(dir.path().join(name)).unwrap();
+            MCPCoreBuilder::new(db).build()
+        };
+        let first = core("first.db");
+        let second = core("second.db");
+        assert!(!Arc::ptr_eq(&first.registry_cache, &second.registry_cache));
+        assert_eq!(first.registry_cache.age(), None);
+    }
+}
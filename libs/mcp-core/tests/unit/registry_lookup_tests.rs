@@ -0,0 +1,135 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    use async_trait::async_trait;
+    use mcp_core::{
+        core::{
+            mcp_core::MCPCore, mcp_core_builder::MCPCoreBuilder,
+            mcp_core_registry_sources_ext::McpCoreRegistrySourcesExt,
+        },
+        database::db_manager::DBManager,
+        http_server::handlers::handle_list_all_tools,
+        models::types::{ErrorResponse, ServerDefinition},
+        registry::{registry_client::RegistryClient, storage::REGISTRY_LOOKUP_TIMEOUT},
+    };
+    use serde_json::{json, Value};
+    use tempfile::{tempdir, TempDir};
+
+    /// Longer than any request handler may wait
+    const STALL: Duration = Duration::from_secs(3);
+
+    /// What `registry/list` may take while the database stalls
+    const SLA: Duration = Duration::from_secs(2);
+
+    struct FakeRegistry;
+
+    #[async_trait]
+    impl RegistryClient for FakeRegistry {
+        async fn fetch_registry(&self) -> Result<Value, ErrorResponse> {
+            let tools: Vec<Value> = ["fetch", "files"]
+                .into_iter()
+                .map(|id| {
+                    json!({
+                        "id": id,
+                        "name": id,
+                        "description": "",
+                        "short_description": "",
+                        "publisher": {
+                            "id": "acme",
+                            "name": "Acme",
+                            "url": "https://acme.internal"
+                        },
+                        "is_official": false,
+                        "source_url": null,
+                        "distribution": { "type": "npm", "package": format!("@acme/{}", id) },
+                        "license": "UNLICENSED",
+                        "runtime": "node",
+                        "config": { "command": "npx", "args": ["-y", format!("@acme/{}", id)] },
+                        "categories": ["Utilities"],
+                        "tags": []
+                    })
+                })
+                .collect();
+            Ok(json!({
+                "count": tools.len(),
+                "version": 1,
+                "categories": {},
+                "tags": {},
+                "tools": tools
+            }))
+        }
+    }
+
+    fn core() -> (TempDir, DBManager, MCPCore) {
+        let dir = tempdir().unwrap();
+        let db = DBManager::with_path(dir.path().join("mcp-dockmaster.db")).unwrap();
+        db.apply_migrations().unwrap();
+        let core = MCPCoreBuilder::new(db.clone())
+            .registry_client(Arc::new(FakeRegistry))
+            .build();
+        (dir, db, core)
+    }
+
+    fn installed(tools: &Value, id: &str) -> bool {
+        tools["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|tool| tool["id"] == id)
+            .unwrap()["installed"]
+            .as_bool()
+            .unwrap()
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_list_answers_from_memory_while_the_database_stalls() {
+        let (_dir, db, core) = core();
+        let server: ServerDefinition = serde_json::from_value(json!({
+            "name": "Fetch",
+            "description": "",
+            "enabled": true,
+            "tools_type": "node",
+            "configuration": { "command": "npx", "args": ["-y", "@acme/fetch"] }
+        }))
+        .unwrap();
+        core.tool_registry
+            .read()
+            .await
+            .save_server("fetch", &server)
+            .unwrap();
+        // Loaded once, as at startup
+        assert_eq!(core.installed_servers().await.unwrap().len(), 1);
+        core.merged_registry().await.unwrap();
+
+        db.delay_queries(STALL);
+        let started = Instant::now();
+        let tools = handle_list_all_tools(core.clone()).await.unwrap();
+        assert!(
+            started.elapsed() < SLA,
+            "answered in {:?}",
+            started.elapsed()
+        );
+        assert!(installed(&tools, "fetch"));
+        assert!(!installed(&tools, "files"));
+        db.delay_queries(Duration::ZERO);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_installed_servers_give_up_on_a_stalled_database() {
+        let (_dir, db, core) = core();
+
+        // Nothing was loaded yet
+        db.delay_queries(STALL);
+        let started = Instant::now();
+        assert!(core.installed_servers().await.unwrap().is_empty());
+        let elapsed = started.elapsed();
+        assert!(
+            elapsed >= REGISTRY_LOOKUP_TIMEOUT && elapsed < SLA,
+            "{:?}",
+            elapsed
+        );
+        db.delay_queries(Duration::ZERO);
+    }
+}
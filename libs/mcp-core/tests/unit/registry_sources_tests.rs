@@ -0,0 +1,359 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    use async_trait::async_trait;
+    use mcp_core::{
+        core::{
+            mcp_core_builder::MCPCoreBuilder,
+            mcp_core_registry_sources_ext::McpCoreRegistrySourcesExt,
+        },
+        database::db_manager::DBManager,
+        models::types::{
+            ErrorResponse, ProvenanceSource, RegistryTool, RegistryToolsResponse, ServerDefinition,
+            ServerProvenance,
+        },
+        registry::{
+            registry_client::RegistryClient,
+            registry_sources::{
+                fetch_source_tools, index_entry_urls, merge_registries, RegistryCredentials,
+                RegistrySource, RegistrySourceKind, DEFAULT_SOURCE_ID, REDACTED_HEADER_VALUE,
+            },
+        },
+    };
+    use serde_json::{json, Value};
+    use tempfile::tempdir;
+
+    fn tool_json(id: &str, category: &str) -> Value {
+        json!({
+            "id": id,
+            "name": id,
+            "description": "",
+            "short_description": "",
+            "publisher": { "id": "acme", "name": "Acme", "url": "https://acme.internal" },
+            "is_official": false,
+            "source_url": null,
+            "distribution": { "type": "npm", "package": format!("@acme/{}", id) },
+            "license": "UNLICENSED",
+            "runtime": "node",
+            "config": { "command": "npx", "args": ["-y", format!("@acme/{}", id)] },
+            "categories": [category],
+            "tags": []
+        })
+    }
+
+    fn tool(id: &str, category: &str) -> RegistryTool {
+        serde_json::from_value(tool_json(id, category)).unwrap()
+    }
+
+    fn registry_json(tools: Vec<Value>) -> Value {
+        json!({
+            "count": tools.len(),
+            "version": 1,
+            "categories": {},
+            "tags": {},
+            "tools": tools
+        })
+    }
+
+    fn source(id: &str, url: &str, kind: RegistrySourceKind) -> RegistrySource {
+        RegistrySource {
+            id: id.to_string(),
+            url: url.to_string(),
+            kind,
+            headers: [("X-Api-Key".to_string(), "k3y".to_string())].into(),
+            credentials: Some(RegistryCredentials::Bearer {
+                token: "t0ken".to_string(),
+            }),
+        }
+    }
+
+    /// Client serving documents by URL, recording the source of each request
+    #[derive(Default)]
+    struct FakeClient {
+        registry: Option<Value>,
+        documents: HashMap<String, Value>,
+        requests: Mutex<Vec<(String, RegistrySource)>>,
+    }
+
+    #[async_trait]
+    impl RegistryClient for FakeClient {
+        async fn fetch_registry(&self) -> Result<Value, ErrorResponse> {
+            self.registry.clone().ok_or(ErrorResponse {
+                code: -32000,
+                message: "offline".to_string(),
+            })
+        }
+
+        async fn fetch_source_json(
+            &self,
+            source: &RegistrySource,
+            url: &str,
+        ) -> Result<Value, ErrorResponse> {
+            self.requests
+                .lock()
+                .unwrap()
+                .push((url.to_string(), source.clone()));
+            self.documents.get(url).cloned().ok_or(ErrorResponse {
+                code: -32000,
+                message: format!("{} answered 404", url),
+            })
+        }
+    }
+
+    #[test]
+    fn test_registry_source_definition() {
+        let parsed: RegistrySource = serde_json::from_value(json!({
+            "id": "acme",
+            "url": "https://registry.acme.internal/registry.json",
+            "credentials": { "type": "basic", "username": "me", "password": "pa55" }
+        }))
+        .unwrap();
+        assert_eq!(parsed.kind, RegistrySourceKind::Registry);
+        assert!(parsed.headers.is_empty());
+        assert!(parsed.validate().is_ok());
+        // Credentials are accepted but never sent back
+        assert!(serde_json::to_value(&parsed)
+            .unwrap()
+            .get("credentials")
+            .is_none());
+        // Nor are header values
+        let mut with_headers = parsed.clone();
+        with_headers
+            .headers
+            .insert("X-Api-Key".to_string(), "k3y".to_string());
+        assert_eq!(
+            serde_json::to_value(&with_headers).unwrap()["headers"],
+            json!({ "X-Api-Key": REDACTED_HEADER_VALUE })
+        );
+
+        let mut bad = parsed.clone();
+        bad.id = DEFAULT_SOURCE_ID.to_string();
+        assert!(bad.validate().is_err());
+        let mut bad = parsed.clone();
+        bad.id = "acme corp".to_string();
+        assert!(bad.validate().is_err());
+        let mut bad = parsed.clone();
+        bad.url = "registry.acme.internal".to_string();
+        assert!(bad.validate().is_err());
+        let mut bad = parsed.clone();
+        bad.headers
+            .insert("X-Key".to_string(), "a\r\nHost: evil".to_string());
+        assert!(bad.validate().is_err());
+        let mut bad = parsed;
+        bad.headers.insert("X Key".to_string(), "a".to_string());
+        assert!(bad.validate().is_err());
+    }
+
+    #[test]
+    fn test_index_entry_urls() {
+        let index = json!({ "tools": [
+            "weather.json",
+            "tools/search.json",
+            "/shared/files.json",
+            "https://cdn.acme.internal/fetch.json"
+        ]});
+        assert_eq!(
+            index_entry_urls("https://acme.internal/mcp/index.json?v=2", &index).unwrap(),
+            vec![
+                "https://acme.internal/mcp/weather.json",
+                "https://acme.internal/mcp/tools/search.json",
+                "https://acme.internal/shared/files.json",
+                "https://cdn.acme.internal/fetch.json",
+            ]
+        );
+        assert!(index_entry_urls("https://acme.internal/index.json", &json!({})).is_err());
+        assert!(
+            index_entry_urls("https://acme.internal/index.json", &json!({ "tools": [1] })).is_err()
+        );
+    }
+
+    #[test]
+    fn test_merge_registries() {
+        let default: RegistryToolsResponse =
+            serde_json::from_value(registry_json(vec![tool_json("fetch", "web")])).unwrap();
+        let merged = merge_registries(
+            default,
+            vec![
+                (
+                    "acme".to_string(),
+                    vec![tool("weather", "data"), tool("fetch", "web")],
+                ),
+                ("lab".to_string(), vec![tool("weather", "data")]),
+            ],
+        );
+
+        let sources: Vec<(&str, &str)> = merged
+            .tools
+            .iter()
+            .map(|t| (t.id.as_str(), t.source.as_deref().unwrap()))
+            .collect();
+        assert_eq!(sources, vec![("fetch", "default"), ("weather", "acme")]);
+        assert_eq!(merged.count, 2);
+        assert_eq!(merged.categories["data"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_index_source() {
+        let index_url = "https://acme.internal/mcp/index.json";
+        let client = FakeClient {
+            documents: HashMap::from([
+                (
+                    index_url.to_string(),
+                    json!({ "tools": ["weather.json", "missing.json", "broken.json"] }),
+                ),
+                (
+                    "https://acme.internal/mcp/weather.json".to_string(),
+                    tool_json("weather", "data"),
+                ),
+                (
+                    "https://acme.internal/mcp/broken.json".to_string(),
+                    json!({ "id": "broken" }),
+                ),
+            ]),
+            ..Default::default()
+        };
+        let acme = source("acme", index_url, RegistrySourceKind::Index);
+
+        // Entries that can't be fetched or parsed are skipped
+        let tools = fetch_source_tools(&client, &acme).await.unwrap();
+        assert_eq!(tools.len(), 1);
+        assert_eq!(tools[0].id, "weather");
+
+        // Every request carries the headers and credentials of the source
+        {
+            let requests = client.requests.lock().unwrap();
+            assert_eq!(requests.len(), 4);
+            assert!(requests.iter().all(|(_, sent)| sent == &acme));
+        }
+
+        let missing = source(
+            "lab",
+            "https://lab.internal/index.json",
+            RegistrySourceKind::Index,
+        );
+        assert!(fetch_source_tools(&client, &missing).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_registry_sources_ext() {
+        let dir = tempdir().unwrap();
+        let db = DBManager::with_path(dir.path().join("mcp-dockmaster.db")).unwrap();
+        db.apply_migrations().unwrap();
+        let acme_url = "https://registry.acme.internal/registry.json";
+        let client = Arc::new(FakeClient {
+            registry: Some(registry_json(vec![tool_json("fetch", "web")])),
+            documents: HashMap::from([(
+                acme_url.to_string(),
+                registry_json(vec![tool_json("weather", "data")]),
+            )]),
+            ..Default::default()
+        });
+        let core = MCPCoreBuilder::new(db.clone())
+            .registry_client(client.clone())
+            .build();
+
+        let acme = source("acme", acme_url, RegistrySourceKind::Registry);
+        let saved = core.save_registry_source(acme.clone()).await.unwrap();
+        assert_eq!(saved.credentials, None);
+        assert_eq!(saved.headers, acme.headers);
+        assert!(core
+            .save_registry_source(RegistrySource {
+                url: "ftp://acme.internal".to_string(),
+                ..acme.clone()
+            })
+            .await
+            .is_err());
+
+        // Saving without credentials keeps the current ones
+        core.save_registry_source(RegistrySource {
+            credentials: None,
+            ..acme.clone()
+        })
+        .await
+        .unwrap();
+        assert_eq!(db.get_registry_sources().unwrap(), vec![acme.clone()]);
+        // As does giving back a redacted header value
+        let mut redacted = acme.clone();
+        redacted
+            .headers
+            .insert("X-Api-Key".to_string(), REDACTED_HEADER_VALUE.to_string());
+        core.save_registry_source(redacted.clone()).await.unwrap();
+        assert_eq!(db.get_registry_sources().unwrap(), vec![acme.clone()]);
+        redacted
+            .headers
+            .insert("X-Other".to_string(), REDACTED_HEADER_VALUE.to_string());
+        assert!(core.save_registry_source(redacted).await.is_err());
+        assert_eq!(
+            core.list_registry_sources().await.unwrap()[0].credentials,
+            None
+        );
+
+        let merged = core.merged_registry().await.unwrap();
+        let ids: Vec<(&str, Option<&str>)> = merged
+            .tools
+            .iter()
+            .map(|t| (t.id.as_str(), t.source.as_deref()))
+            .collect();
+        assert_eq!(
+            ids,
+            vec![("fetch", Some("default")), ("weather", Some("acme"))]
+        );
+        assert_eq!(client.requests.lock().unwrap()[0].1, acme);
+
+        core.delete_registry_source("acme").await.unwrap();
+        assert!(core.list_registry_sources().await.unwrap().is_empty());
+        assert!(core.delete_registry_source("acme").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sources_listed_when_the_default_registry_fails() {
+        let dir = tempdir().unwrap();
+        let db = DBManager::with_path(dir.path().join("mcp-dockmaster.db")).unwrap();
+        db.apply_migrations().unwrap();
+        let acme_url = "https://registry.acme.internal/registry.json";
+        let client = Arc::new(FakeClient {
+            documents: HashMap::from([(
+                acme_url.to_string(),
+                registry_json(vec![tool_json("weather", "data")]),
+            )]),
+            ..Default::default()
+        });
+        let core = MCPCoreBuilder::new(db).registry_client(client).build();
+
+        assert_eq!(core.merged_registry().await.unwrap_err().message, "offline");
+        core.save_registry_source(source("acme", acme_url, RegistrySourceKind::Registry))
+            .await
+            .unwrap();
+        let merged = core.merged_registry().await.unwrap();
+        assert_eq!(merged.tools.len(), 1);
+        assert_eq!(merged.tools[0].source.as_deref(), Some("acme"));
+    }
+
+    #[test]
+    fn test_provenance_keeps_the_registry_source() {
+        let dir = tempdir().unwrap();
+        let db = DBManager::with_path(dir.path().join("mcp-dockmaster.db")).unwrap();
+        db.apply_migrations().unwrap();
+        db.save_server(
+            "weather",
+            &ServerDefinition {
+                name: "Weather".to_string(),
+                description: "".to_string(),
+                enabled: true,
+                tools_type: "node".to_string(),
+                entry_point: None,
+                configuration: None,
+                distribution: None,
+            },
+        )
+        .unwrap();
+
+        let mut provenance =
+            ServerProvenance::new(ProvenanceSource::Registry, Some("weather".to_string()));
+        provenance.registry_source = Some("acme".to_string());
+        db.save_server_provenance("weather", &provenance).unwrap();
+        assert_eq!(db.get_server_provenances().unwrap()["weather"], provenance);
+    }
+}
@@ -0,0 +1,193 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::path::Path;
+
+    use mcp_core::{
+        core::{
+            mcp_core_builder::MCPCoreBuilder, mcp_core_proxy_ext::McpCoreProxyExt,
+            mcp_core_relocation_ext::McpCoreRelocationExt,
+        },
+        database::db_manager::DBManager,
+        events::DockmasterEvent,
+        models::types::{ServerDefinition, ServerRegistrationRequest},
+        relocation::{
+            find_missing_paths, rebase_home, rewrite_server_paths, server_paths,
+            EnvironmentFingerprint, ENVIRONMENT_FINGERPRINT_SETTING,
+        },
+    };
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    const OLD_HOME: &str = "/home/dockmaster-old-user";
+
+    fn fingerprint(home_dir: &str) -> EnvironmentFingerprint {
+        EnvironmentFingerprint {
+            hostname: "old-laptop".to_string(),
+            home_dir: home_dir.to_string(),
+            os: "macos".to_string(),
+        }
+    }
+
+    fn server(configuration: serde_json::Value) -> ServerDefinition {
+        serde_json::from_value(json!({
+            "name": "Files",
+            "description": "",
+            "tools_type": "node",
+            "enabled": true,
+            "configuration": configuration,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_rebase_home() {
+        assert_eq!(
+            rebase_home("/Users/ann/bin/server", "/Users/ann", "/home/ann").as_deref(),
+            Some("/home/ann/bin/server")
+        );
+        assert_eq!(
+            rebase_home("/Users/ann/bin", "/Users/ann/", "/home/ann/").as_deref(),
+            Some("/home/ann/bin")
+        );
+        assert_eq!(
+            rebase_home(r"C:\Users\ann\server.exe", r"C:\Users\ann", r"D:\ann").as_deref(),
+            Some(r"D:\ann\server.exe")
+        );
+        // Only whole directories
+        assert_eq!(
+            rebase_home("/Users/anna/bin", "/Users/ann", "/home/ann"),
+            None
+        );
+        assert_eq!(rebase_home("/opt/server", "/Users/ann", "/home/ann"), None);
+        assert_eq!(rebase_home("/Users/ann/bin", "", "/home/ann"), None);
+    }
+
+    #[test]
+    fn test_missing_paths() {
+        let files = server(json!({
+            "command": "/Users/ann/.local/bin/files-server",
+            "args": ["--root", "/Users/ann/Documents", "/opt/shared/config.json"],
+            "env": { "CACHE": "/tmp/cache", "LEVEL": "debug", "PORT": 8080 }
+        }));
+        assert_eq!(
+            server_paths(&files)
+                .into_iter()
+                .map(|(field, _)| field)
+                .collect::<Vec<_>>(),
+            vec!["command", "args[1]", "args[2]", "env.CACHE"]
+        );
+
+        let mut servers = HashMap::new();
+        servers.insert("files".to_string(), files);
+        servers.insert("fetch".to_string(), server(json!({ "command": "npx" })));
+        let existing = ["/tmp/cache", "/home/ann/Documents"];
+        let missing = find_missing_paths(
+            &servers,
+            &fingerprint("/Users/ann"),
+            &fingerprint("/home/ann"),
+            |path: &Path| existing.iter().any(|existing| Path::new(existing) == path),
+        );
+
+        assert_eq!(missing.len(), 3);
+        assert_eq!(missing[0].field, "command");
+        assert_eq!(
+            missing[0].rewrite.as_deref(),
+            Some("/home/ann/.local/bin/files-server")
+        );
+        assert!(!missing[0].rewrite_exists);
+        assert_eq!(missing[1].rewrite.as_deref(), Some("/home/ann/Documents"));
+        assert!(missing[1].rewrite_exists);
+        assert_eq!(missing[2].path, "/opt/shared/config.json");
+        assert_eq!(missing[2].rewrite, None);
+
+        let mut files = servers.remove("files").unwrap();
+        assert_eq!(rewrite_server_paths(&mut files, &missing), 2);
+        let configuration = files.configuration.unwrap();
+        assert_eq!(
+            configuration.command.as_deref(),
+            Some("/home/ann/.local/bin/files-server")
+        );
+        assert_eq!(
+            configuration.args.unwrap(),
+            vec!["--root", "/home/ann/Documents", "/opt/shared/config.json"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_environment_change_is_reported_and_migrated() {
+        let dir = tempdir().unwrap();
+        let db = DBManager::with_path(dir.path().join("mcp-dockmaster.db")).unwrap();
+        db.apply_migrations().unwrap();
+        let core = MCPCoreBuilder::new(db).build();
+        let current = EnvironmentFingerprint::current();
+
+        // The first run only remembers the machine
+        assert!(core.check_environment().await.unwrap().is_none());
+        assert!(core.check_environment().await.unwrap().is_none());
+        assert!(core.migration_report().await.unwrap().is_none());
+
+        let request: ServerRegistrationRequest = serde_json::from_value(json!({
+            "server_id": "files",
+            "server_name": "Files",
+            "description": "",
+            "tools_type": "node",
+            "configuration": {
+                "command": format!("{}/bin/files-server", OLD_HOME),
+                "args": ["--cache", dir.path().to_string_lossy()],
+            },
+            "enabled": false
+        }))
+        .unwrap();
+        core.register_server(request).await.unwrap();
+
+        // The data directory comes from another machine
+        let previous = serde_json::to_string(&fingerprint(OLD_HOME)).unwrap();
+        core.tool_registry
+            .read()
+            .await
+            .save_setting(ENVIRONMENT_FINGERPRINT_SETTING, &previous)
+            .unwrap();
+        let mut events = core.event_bus.subscribe();
+
+        let report = core.check_environment().await.unwrap().unwrap();
+        assert_eq!(report.previous.home_dir, OLD_HOME);
+        assert_eq!(report.current, current);
+        assert_eq!(report.server_ids(), vec!["files"]);
+        assert_eq!(report.missing_paths.len(), 1);
+        assert_eq!(report.missing_paths[0].field, "command");
+        assert_eq!(report.rewritable(), 1);
+        match events.try_recv().unwrap().event {
+            DockmasterEvent::EnvironmentChanged(event) => {
+                assert_eq!(event.missing_paths, report.missing_paths)
+            }
+            event => panic!("unexpected event {:?}", event),
+        }
+        let saved = core.migration_report().await.unwrap().unwrap();
+        assert_eq!(saved.missing_paths, report.missing_paths);
+        // Reported once
+        assert!(core.check_environment().await.unwrap().is_none());
+
+        let results = core.migrate_server_paths(None).await.unwrap();
+        let result = &results["files"];
+        assert!(result.success, "{}", result.message);
+        assert!(result.snapshot_id.is_some());
+        let server = core.tool_registry.read().await.get_server("files").unwrap();
+        assert_eq!(
+            server.configuration.unwrap().command,
+            Some(format!(
+                "{}/bin/files-server",
+                current.home_dir.trim_end_matches('/')
+            ))
+        );
+        let report = core.migration_report().await.unwrap().unwrap();
+        assert!(report.missing_paths.is_empty());
+
+        // Nothing left to move
+        let results = core
+            .migrate_server_paths(Some(vec!["files".to_string()]))
+            .await
+            .unwrap();
+        assert!(!results["files"].success);
+    }
+}
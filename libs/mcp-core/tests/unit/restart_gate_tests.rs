@@ -0,0 +1,88 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use mcp_core::mcp_state::restart_gate::RestartGate;
+
+    #[tokio::test]
+    async fn test_concurrent_restarts_are_coalesced() {
+        let gate = RestartGate::new();
+        let executions = Arc::new(AtomicUsize::new(0));
+        let running = Arc::new(AtomicUsize::new(0));
+        let max_running = Arc::new(AtomicUsize::new(0));
+
+        let requests = (0..10).map(|_| {
+            let gate = gate.clone();
+            let executions = executions.clone();
+            let running = running.clone();
+            let max_running = max_running.clone();
+            tokio::spawn(async move {
+                gate.run("server", || async {
+                    executions.fetch_add(1, Ordering::SeqCst);
+                    let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    max_running.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                    running.fetch_sub(1, Ordering::SeqCst);
+                    Ok(())
+                })
+                .await
+            })
+        });
+        let results = futures::future::join_all(requests).await;
+
+        assert!(results.into_iter().all(|r| r.unwrap().is_ok()));
+        assert_eq!(max_running.load(Ordering::SeqCst), 1);
+        let executions = executions.load(Ordering::SeqCst);
+        assert!(
+            (1..=2).contains(&executions),
+            "expected at most one follow-up restart, got {} restarts",
+            executions
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sequential_restarts_all_run() {
+        let gate = RestartGate::new();
+        let executions = AtomicUsize::new(0);
+        for _ in 0..3 {
+            gate.run("server", || async {
+                executions.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            })
+            .await
+            .unwrap();
+        }
+        assert_eq!(executions.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_coalesced_restart_shares_error() {
+        let gate = RestartGate::new();
+        let first = {
+            let gate = gate.clone();
+            tokio::spawn(async move {
+                gate.run("server", || async {
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                    Err("spawn failed".to_string())
+                })
+                .await
+            })
+        };
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let followers = (0..3).map(|_| {
+            let gate = gate.clone();
+            tokio::spawn(async move {
+                gate.run("server", || async { Err("follow-up failed".to_string()) })
+                    .await
+            })
+        });
+        let followers = futures::future::join_all(followers).await;
+
+        assert_eq!(first.await.unwrap(), Err("spawn failed".to_string()));
+        for result in followers {
+            assert_eq!(result.unwrap(), Err("follow-up failed".to_string()));
+        }
+    }
+}
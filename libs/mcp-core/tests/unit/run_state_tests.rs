@@ -0,0 +1,279 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::sync::Arc;
+
+    use mcp_core::{
+        core::{mcp_core_builder::MCPCoreBuilder, mcp_core_proxy_ext::McpCoreProxyExt},
+        events::DockmasterEvent,
+        models::types::{
+            ServerDefinition, ServerRegistrationRequest, ServerStatus, ServerUpdateRequest,
+        },
+        registry::server_registry::ServerRegistry,
+        utils::github::GitHubRepo,
+    };
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    use crate::common::{db, FailingLauncher};
+
+    fn server(enabled: bool) -> ServerDefinition {
+        serde_json::from_value(json!({
+            "name": "Echo",
+            "description": "",
+            "enabled": enabled,
+            "tools_type": "node",
+            "configuration": { "command": "node", "args": ["echo.js"] }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_run_state_defaults_to_running() {
+        let dir = tempdir().unwrap();
+        let registry = ServerRegistry::with_db_manager(db(&dir));
+        registry.save_server("echo", &server(true)).unwrap();
+
+        assert!(registry.get_server_run_states().unwrap().is_empty());
+        assert!(registry.desired_running("echo"));
+
+        registry.save_server_run_state("echo", false).unwrap();
+        assert!(!registry.desired_running("echo"));
+    }
+
+    #[test]
+    fn test_run_state_follows_the_server() {
+        let dir = tempdir().unwrap();
+        let db = db(&dir);
+        db.save_server("Echo", &server(true)).unwrap();
+        db.save_server_run_state("Echo", false).unwrap();
+
+        db.rename_server("Echo", "echo").unwrap();
+        assert!(!db.get_server_run_states().unwrap()["echo"]);
+
+        db.delete_server("echo").unwrap();
+        assert!(db.get_server_run_states().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_startup_restores_desired_run_state() {
+        let dir = tempdir().unwrap();
+        let db = db(&dir);
+        for (id, enabled, desired_running) in [
+            ("stopped", true, Some(false)),
+            ("running", true, Some(true)),
+            ("legacy", true, None),
+            ("disabled", false, Some(true)),
+        ] {
+            db.save_server(id, &server(enabled)).unwrap();
+            if let Some(desired_running) = desired_running {
+                db.save_server_run_state(id, desired_running).unwrap();
+            }
+        }
+        let launcher = Arc::new(FailingLauncher::new());
+        let core = MCPCoreBuilder::new(db)
+            .process_launcher(launcher.clone())
+            .build();
+
+        core.init_mcp_server().await.unwrap();
+
+        let mut launched = launcher.launched();
+        launched.sort();
+        assert_eq!(launched, vec!["legacy", "running"]);
+
+        let servers = core.list_servers().await.unwrap();
+        let stopped = servers.iter().find(|s| s.id.as_str() == "stopped").unwrap();
+        assert!(!stopped.desired_running);
+        let legacy = servers.iter().find(|s| s.id.as_str() == "legacy").unwrap();
+        assert!(legacy.desired_running);
+    }
+
+    #[tokio::test]
+    async fn test_startup_reports_each_server() {
+        let dir = tempdir().unwrap();
+        let db = db(&dir);
+        db.save_server("first", &server(true)).unwrap();
+        db.save_server("second", &server(true)).unwrap();
+        let core = MCPCoreBuilder::new(db)
+            .process_launcher(Arc::new(FailingLauncher::new()))
+            .build();
+        let mut events = core.event_bus.subscribe();
+
+        core.init_mcp_server().await.unwrap();
+
+        let mut reported = Vec::new();
+        while let Ok(envelope) = events.try_recv() {
+            if let DockmasterEvent::StartupProgress(progress) = envelope.event {
+                reported.push(progress);
+            }
+        }
+        assert_eq!(reported.len(), 2);
+        let mut server_ids: Vec<&str> = reported.iter().map(|p| p.server_id.as_str()).collect();
+        server_ids.sort();
+        assert_eq!(server_ids, vec!["first", "second"]);
+        let mut done: Vec<usize> = reported.iter().map(|p| p.done).collect();
+        done.sort();
+        assert_eq!(done, vec![1, 2]);
+        for progress in &reported {
+            assert_eq!(progress.total, 2);
+            assert!(!progress.success);
+            assert_eq!(progress.error.as_deref(), Some("not started in tests"));
+            assert_eq!(progress.tool_count, 0);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stopped_server_stays_enabled() {
+        let dir = tempdir().unwrap();
+        let db = db(&dir);
+        db.save_server("echo", &server(true)).unwrap();
+        let core = MCPCoreBuilder::new(db)
+            .process_launcher(Arc::new(FailingLauncher::new()))
+            .build();
+
+        // Not running, so nothing to stop
+        let response = core.stop_server("echo".to_string()).await.unwrap();
+        assert!(response.success);
+
+        let servers = core.list_servers().await.unwrap();
+        assert!(servers[0].definition.enabled);
+        assert!(!servers[0].desired_running);
+
+        let response = core.stop_server("unknown".to_string()).await.unwrap();
+        assert!(!response.success);
+        assert!(response.message.contains("'unknown'"));
+    }
+
+    #[tokio::test]
+    async fn test_start_wants_the_server_running() {
+        let dir = tempdir().unwrap();
+        let db = db(&dir);
+        db.save_server("echo", &server(true)).unwrap();
+        db.save_server_run_state("echo", false).unwrap();
+        db.save_server("disabled", &server(false)).unwrap();
+        let launcher = Arc::new(FailingLauncher::new());
+        let core = MCPCoreBuilder::new(db)
+            .process_launcher(launcher.clone())
+            .build();
+
+        let response = core.start_server("echo".to_string()).await.unwrap();
+        assert!(!response.success);
+        assert!(response.message.contains("not started in tests"));
+        assert_eq!(launcher.launched(), vec!["echo"]);
+        let servers = core.list_servers().await.unwrap();
+        let echo = servers.iter().find(|s| s.id.as_str() == "echo").unwrap();
+        assert!(echo.desired_running);
+
+        // Disabled servers have to be enabled first
+        let response = core.start_server("disabled".to_string()).await.unwrap();
+        assert!(!response.success);
+        assert!(response.message.contains("disabled"));
+        assert_eq!(launcher.launched().len(), 1);
+
+        let response = core.start_server("unknown".to_string()).await.unwrap();
+        assert!(!response.success);
+    }
+
+    #[tokio::test]
+    async fn test_registration_wants_the_server_running() {
+        let dir = tempdir().unwrap();
+        let db = db(&dir);
+        db.save_server("echo", &server(true)).unwrap();
+        db.save_server_run_state("echo", false).unwrap();
+        let core = MCPCoreBuilder::new(db)
+            .process_launcher(Arc::new(FailingLauncher::new()))
+            .build();
+
+        let request: ServerRegistrationRequest = serde_json::from_value(json!({
+            "server_id": "echo",
+            "server_name": "Echo",
+            "description": "",
+            "tools_type": "node",
+            "configuration": { "command": "node", "args": ["echo.js"] }
+        }))
+        .unwrap();
+        core.register_server(request).await.unwrap();
+
+        let servers = core.list_servers().await.unwrap();
+        assert!(servers[0].desired_running);
+    }
+
+    #[tokio::test]
+    async fn test_disabled_registration_is_not_started() {
+        let dir = tempdir().unwrap();
+        let launcher = Arc::new(FailingLauncher::new());
+        let core = MCPCoreBuilder::new(db(&dir))
+            .process_launcher(launcher.clone())
+            .build();
+
+        let request: ServerRegistrationRequest = serde_json::from_value(json!({
+            "server_id": "echo",
+            "server_name": "Echo",
+            "description": "",
+            "tools_type": "node",
+            "configuration": { "command": "node", "args": ["echo.js"] },
+            "auto_start": false
+        }))
+        .unwrap();
+        let response = core.register_server(request).await.unwrap();
+        assert!(response.success);
+        assert!(matches!(response.status, Some(ServerStatus::Stopped)));
+        assert_eq!(response.tool_count, Some(0));
+        assert!(launcher.launched().is_empty());
+
+        let servers = core.list_servers().await.unwrap();
+        assert!(!servers[0].definition.enabled);
+        assert!(!servers[0].desired_running);
+
+        // Enabling it later starts it as usual
+        core.update_server_status(ServerUpdateRequest {
+            server_id: "echo".to_string(),
+            enabled: true,
+        })
+        .await
+        .unwrap();
+        assert!(launcher.launched().contains(&"echo".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_disabled_import_is_not_started() {
+        let dir = tempdir().unwrap();
+        let launcher = Arc::new(FailingLauncher::new());
+        let core = MCPCoreBuilder::new(db(&dir))
+            .process_launcher(launcher.clone())
+            .build();
+        let repo = |repo: &str| GitHubRepo {
+            owner: "acme".to_string(),
+            repo: repo.to_string(),
+        };
+
+        let response = core
+            .process_nodejs_project(
+                json!({ "name": "fetch-mcp" }).to_string(),
+                &repo("fetch"),
+                HashSet::new(),
+                Some(false),
+            )
+            .await
+            .unwrap();
+        assert!(response.success);
+        assert!(matches!(response.status, Some(ServerStatus::Stopped)));
+
+        let response = core
+            .process_python_project(
+                "[project]\nname = \"time-mcp\"\n".to_string(),
+                &repo("time"),
+                HashSet::new(),
+                Some(false),
+            )
+            .await
+            .unwrap();
+        assert!(response.success);
+        assert!(matches!(response.status, Some(ServerStatus::Stopped)));
+
+        assert!(launcher.launched().is_empty());
+        let servers = core.list_servers().await.unwrap();
+        assert_eq!(servers.len(), 2);
+        assert!(servers.iter().all(|server| !server.definition.enabled));
+    }
+}
@@ -0,0 +1,123 @@
+#[cfg(test)]
+mod tests {
+    use mcp_core::{
+        mcp_state::{
+            launch_plan::LaunchPlan,
+            sandbox::{
+                inject_docker_network_none, sandbox_exec_profile, wrap_command, SandboxMechanism,
+                SecurityMode,
+            },
+        },
+        models::types::{SandboxOptions, ServerDefinition},
+    };
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_servers_are_unrestricted_by_default() {
+        let mode = SecurityMode::resolve("node", None).unwrap();
+        assert_eq!(mode, SecurityMode::Unrestricted);
+        assert_eq!(
+            serde_json::to_value(&mode).unwrap(),
+            json!({ "mode": "unrestricted" })
+        );
+
+        let (command, args) = wrap_command(&mode, "npx", &strings(&["server"]));
+        assert_eq!(command, "npx");
+        assert_eq!(args, vec!["server"]);
+    }
+
+    #[test]
+    fn test_sandbox_paths_are_validated() {
+        let relative = SandboxOptions {
+            allowed_paths: strings(&["data"]),
+            network: false,
+        };
+        assert!(SecurityMode::resolve("docker", Some(&relative))
+            .unwrap_err()
+            .contains("must be absolute"));
+
+        let missing = SandboxOptions {
+            allowed_paths: strings(&["/nonexistent/mcp-dockmaster-sandbox"]),
+            network: false,
+        };
+        assert!(SecurityMode::resolve("docker", Some(&missing))
+            .unwrap_err()
+            .contains("does not exist"));
+    }
+
+    #[test]
+    fn test_docker_servers_get_no_network() {
+        let dir = tempdir().unwrap();
+        let server: ServerDefinition = serde_json::from_value(json!({
+            "name": "Fetch",
+            "description": "",
+            "enabled": true,
+            "tools_type": "docker",
+            "configuration": {
+                "command": "docker",
+                "args": ["run", "-i", "--rm", "mcp/fetch"],
+                "sandbox": { "allowed_paths": [dir.path().to_str().unwrap()] }
+            }
+        }))
+        .unwrap();
+        let plan = LaunchPlan::build("fetch", &server).unwrap();
+
+        assert!(matches!(
+            plan.security_mode,
+            SecurityMode::Restricted {
+                mechanism: SandboxMechanism::Docker,
+                network: false,
+                ..
+            }
+        ));
+        assert_eq!(plan.command, "docker");
+        assert!(plan.args.contains(&"--network=none".to_string()));
+
+        let mut declared = strings(&["run", "--network", "host", "mcp/fetch"]);
+        inject_docker_network_none(&mut declared);
+        assert_eq!(declared, vec!["run", "--network", "host", "mcp/fetch"]);
+    }
+
+    #[test]
+    fn test_bubblewrap_wrapping() {
+        let mode = SecurityMode::Restricted {
+            mechanism: SandboxMechanism::Bubblewrap,
+            allowed_paths: strings(&["/home/user/my notes"]),
+            network: false,
+        };
+        let (command, args) = wrap_command(&mode, "npx", &strings(&["server"]));
+
+        assert_eq!(command, "bwrap");
+        let bind = args.iter().position(|arg| arg == "--bind").unwrap();
+        assert_eq!(args[bind + 1], "'/home/user/my notes'");
+        assert!(args.contains(&"--unshare-net".to_string()));
+        assert_eq!(&args[args.len() - 3..], &strings(&["--", "npx", "server"]));
+    }
+
+    #[test]
+    fn test_sandbox_exec_profile() {
+        let profile = sandbox_exec_profile(&strings(&["/Users/me/data"]), false);
+        assert!(profile.contains("(deny file-write*)"));
+        assert!(profile.contains("(subpath \"/Users/me/data\")"));
+        assert!(profile.ends_with("(deny network*)"));
+
+        let with_network = sandbox_exec_profile(&[], true);
+        assert!(!with_network.contains("network"));
+
+        let mode = SecurityMode::Restricted {
+            mechanism: SandboxMechanism::SandboxExec,
+            allowed_paths: Vec::new(),
+            network: true,
+        };
+        let (command, args) = wrap_command(&mode, "uvx", &strings(&["mcp-server-git"]));
+        assert_eq!(command, "sandbox-exec");
+        assert_eq!(args[0], "-p");
+        assert!(args[1].starts_with('\'') && args[1].ends_with('\''));
+        assert_eq!(&args[2..], &strings(&["uvx", "mcp-server-git"]));
+    }
+}
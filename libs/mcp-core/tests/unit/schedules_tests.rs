@@ -0,0 +1,276 @@
+#[cfg(test)]
+mod tests {
+    use mcp_core::{
+        core::{mcp_core_builder::MCPCoreBuilder, mcp_core_schedules_ext::McpCoreSchedulesExt},
+        database::db_manager::DBManager,
+        schedules::{
+            cron::CronExpr, due_schedules, misfired_schedules, MisfirePolicy, Schedule,
+            ScheduleDefinition, ScheduleRun, ScheduleStatus, SCHEDULE_RUNS_KEPT,
+        },
+    };
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    // 2025-01-01T00:00:00Z, a Wednesday
+    const NEW_YEAR: u64 = 1_735_689_600_000;
+    const MINUTE: u64 = 60_000;
+    const HOUR: u64 = 60 * MINUTE;
+    const DAY: u64 = 24 * HOUR;
+
+    fn definition(id: &str, cron: &str) -> ScheduleDefinition {
+        serde_json::from_value(json!({
+            "id": id,
+            "cron": cron,
+            "tool_id": "cache:purge"
+        }))
+        .unwrap()
+    }
+
+    fn schedule(definition: ScheduleDefinition, last_run_at: Option<u64>) -> Schedule {
+        Schedule {
+            definition,
+            status: ScheduleStatus {
+                last_run_at,
+                ..Default::default()
+            },
+        }
+    }
+
+    fn run(schedule_id: &str, started_at: u64, error: Option<&str>) -> ScheduleRun {
+        ScheduleRun {
+            id: 0,
+            schedule_id: schedule_id.to_string(),
+            started_at,
+            duration_ms: 12,
+            success: error.is_none(),
+            error: error.map(str::to_string),
+        }
+    }
+
+    fn next(cron: &str, after: u64) -> Option<u64> {
+        cron.parse::<CronExpr>().unwrap().next_after(after)
+    }
+
+    #[test]
+    fn test_cron_next_run() {
+        assert_eq!(
+            next("*/15 * * * *", NEW_YEAR + 7 * MINUTE),
+            Some(NEW_YEAR + 15 * MINUTE)
+        );
+        // Strictly after the given time
+        assert_eq!(next("*/15 * * * *", NEW_YEAR), Some(NEW_YEAR + 15 * MINUTE));
+        assert_eq!(next("@hourly", NEW_YEAR + 1), Some(NEW_YEAR + HOUR));
+        assert_eq!(
+            next("30 2 * jan-mar *", NEW_YEAR),
+            Some(NEW_YEAR + 2 * HOUR + 30 * MINUTE)
+        );
+        assert_eq!(next("0 0 1 feb *", NEW_YEAR), Some(NEW_YEAR + 31 * DAY));
+        // Sunday is both 0 and 7
+        assert_eq!(next("0 0 * * 7", NEW_YEAR), Some(NEW_YEAR + 4 * DAY));
+        assert_eq!(next("0 0 * * sun", NEW_YEAR), Some(NEW_YEAR + 4 * DAY));
+        assert_eq!(next("0 0 30 2 *", NEW_YEAR), None);
+    }
+
+    #[test]
+    fn test_cron_day_of_month_or_week() {
+        // Runs on the 1st and on Mondays
+        let first = next("0 9 1 * mon", NEW_YEAR).unwrap();
+        assert_eq!(first, NEW_YEAR + 9 * HOUR);
+        assert_eq!(
+            next("0 9 1 * mon", first),
+            Some(NEW_YEAR + 5 * DAY + 9 * HOUR)
+        );
+    }
+
+    #[test]
+    fn test_invalid_cron() {
+        let error = "* * *".parse::<CronExpr>().unwrap_err();
+        assert_eq!(
+            error,
+            "Invalid cron expression '* * *': expected 5 fields, found 3"
+        );
+        assert!("61 * * * *".parse::<CronExpr>().is_err());
+        assert!("*/0 * * * *".parse::<CronExpr>().is_err());
+        assert!("5-1 * * * *".parse::<CronExpr>().is_err());
+        assert!("* * * foo *".parse::<CronExpr>().is_err());
+        assert_eq!("@daily".parse::<CronExpr>().unwrap().to_string(), "@daily");
+    }
+
+    #[test]
+    fn test_schedule_definition() {
+        let purge = definition("purge", "@daily");
+        assert_eq!(purge.arguments, json!({}));
+        assert!(purge.enabled);
+        assert_eq!(purge.misfire, MisfirePolicy::Skip);
+        assert!(purge.validate().is_ok());
+
+        let mut bad = purge.clone();
+        bad.tool_id = "purge".to_string();
+        assert!(bad.validate().unwrap_err().contains("server_id:tool_id"));
+        let mut bad = purge.clone();
+        bad.arguments = json!([1]);
+        assert!(bad.validate().is_err());
+        let mut bad = purge.clone();
+        bad.cron = "daily".to_string();
+        assert!(bad.validate().is_err());
+        assert!(serde_json::from_value::<ScheduleDefinition>(json!({
+            "id": "purge", "cron": "@daily", "tool_id": "cache:purge", "every": 5
+        }))
+        .is_err());
+    }
+
+    #[test]
+    fn test_due_schedules() {
+        let hourly = schedule(definition("hourly", "@hourly"), None);
+        let mut disabled = schedule(definition("disabled", "* * * * *"), None);
+        disabled.definition.enabled = false;
+        let daily = schedule(definition("daily", "@daily"), None);
+        let schedules = vec![hourly, disabled, daily];
+
+        let due = due_schedules(&schedules, NEW_YEAR + HOUR - 5_000, NEW_YEAR + HOUR);
+        let ids: Vec<&str> = due.iter().map(|s| s.definition.id.as_str()).collect();
+        assert_eq!(ids, vec!["hourly"]);
+        assert!(due_schedules(&schedules, NEW_YEAR + HOUR, NEW_YEAR + HOUR + 5_000).is_empty());
+
+        let status = schedules[2].clone().with_next_run(NEW_YEAR);
+        assert_eq!(status.status.next_run_at, Some(NEW_YEAR + DAY));
+        assert_eq!(
+            schedules[1]
+                .clone()
+                .with_next_run(NEW_YEAR)
+                .status
+                .next_run_at,
+            None
+        );
+    }
+
+    #[test]
+    fn test_misfired_schedules() {
+        let mut catch_up = definition("catch_up", "@daily");
+        catch_up.misfire = MisfirePolicy::RunOnce;
+        let schedules = vec![
+            schedule(catch_up.clone(), Some(NEW_YEAR)),
+            schedule(definition("skip", "@daily"), Some(NEW_YEAR)),
+            // Never ran, nothing to make up for
+            schedule(catch_up.clone(), None),
+        ];
+
+        let missed = misfired_schedules(&schedules, NEW_YEAR + 3 * DAY);
+        assert_eq!(missed.len(), 1);
+        assert_eq!(missed[0].definition.id, "catch_up");
+        assert!(misfired_schedules(&schedules, NEW_YEAR + HOUR).is_empty());
+    }
+
+    #[test]
+    fn test_schedules_db() {
+        let dir = tempdir().unwrap();
+        let db = DBManager::with_path(dir.path().join("mcp-dockmaster.db")).unwrap();
+        db.apply_migrations().unwrap();
+
+        let mut purge = definition("purge", "@daily");
+        purge.arguments = json!({ "older_than": "7d" });
+        purge.misfire = MisfirePolicy::RunOnce;
+        db.save_schedule(&purge).unwrap();
+        let saved = db.get_schedules().unwrap();
+        assert_eq!(saved.len(), 1);
+        assert_eq!(saved[0].definition, purge);
+        assert_eq!(saved[0].status, ScheduleStatus::default());
+
+        db.record_schedule_run(&run("purge", NEW_YEAR, Some("timeout")))
+            .unwrap();
+        db.record_schedule_run(&run("purge", NEW_YEAR + DAY, Some("timeout")))
+            .unwrap();
+        let status = &db.get_schedules().unwrap()[0].status;
+        assert_eq!(status.last_run_at, Some(NEW_YEAR + DAY));
+        assert_eq!(status.last_success, Some(false));
+        assert_eq!(status.last_error.as_deref(), Some("timeout"));
+        assert_eq!(status.consecutive_failures, 2);
+
+        db.record_schedule_run(&run("purge", NEW_YEAR + 2 * DAY, None))
+            .unwrap();
+        let status = &db.get_schedules().unwrap()[0].status;
+        assert_eq!(status.last_success, Some(true));
+        assert_eq!(status.last_error, None);
+        assert_eq!(status.consecutive_failures, 0);
+
+        let runs = db.get_schedule_runs("purge", 2).unwrap();
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].started_at, NEW_YEAR + 2 * DAY);
+        assert!(runs[0].success);
+        assert_eq!(runs[1].error.as_deref(), Some("timeout"));
+
+        // Saving the definition again keeps the status
+        purge.enabled = false;
+        db.save_schedule(&purge).unwrap();
+        let saved = &db.get_schedules().unwrap()[0];
+        assert!(!saved.definition.enabled);
+        assert_eq!(saved.status.last_run_at, Some(NEW_YEAR + 2 * DAY));
+
+        db.delete_schedule("purge").unwrap();
+        assert!(db.get_schedules().unwrap().is_empty());
+        assert!(db.get_schedule_runs("purge", 10).unwrap().is_empty());
+        assert_eq!(
+            db.delete_schedule("purge").unwrap_err(),
+            "Schedule 'purge' not found"
+        );
+    }
+
+    #[test]
+    fn test_schedule_runs_are_pruned() {
+        let dir = tempdir().unwrap();
+        let db = DBManager::with_path(dir.path().join("mcp-dockmaster.db")).unwrap();
+        db.apply_migrations().unwrap();
+        db.save_schedule(&definition("purge", "* * * * *")).unwrap();
+
+        let runs = SCHEDULE_RUNS_KEPT as u64 + 5;
+        for i in 0..runs {
+            db.record_schedule_run(&run("purge", NEW_YEAR + i * MINUTE, None))
+                .unwrap();
+        }
+        let kept = db.get_schedule_runs("purge", 1_000).unwrap();
+        assert_eq!(kept.len() as i64, SCHEDULE_RUNS_KEPT);
+        assert_eq!(kept[0].started_at, NEW_YEAR + (runs - 1) * MINUTE);
+    }
+
+    #[tokio::test]
+    async fn test_schedules_ext() {
+        let dir = tempdir().unwrap();
+        let db = DBManager::with_path(dir.path().join("mcp-dockmaster.db")).unwrap();
+        db.apply_migrations().unwrap();
+        let core = MCPCoreBuilder::new(db).build();
+
+        let purge = definition("purge", "0 3 * * *");
+        let created = core.create_schedule(purge.clone()).await.unwrap();
+        assert!(created.status.next_run_at.is_some());
+        assert_eq!(
+            core.create_schedule(purge.clone()).await.unwrap_err(),
+            "Schedule 'purge' already exists"
+        );
+        assert!(core
+            .create_schedule(definition("broken", "every day"))
+            .await
+            .is_err());
+        assert!(core
+            .update_schedule(definition("missing", "@daily"))
+            .await
+            .is_err());
+
+        let mut hourly = purge.clone();
+        hourly.cron = "@hourly".to_string();
+        let updated = core.update_schedule(hourly).await.unwrap();
+        assert_eq!(updated.definition.cron, "@hourly");
+        assert_eq!(core.list_schedules().await.unwrap().len(), 1);
+
+        // The server isn't installed, so the run fails and is recorded as such
+        let failed = core.run_schedule(&updated.definition).await;
+        assert!(!failed.success);
+        assert!(failed.error.is_some());
+        let status = core.get_schedule("purge").await.unwrap().status;
+        assert_eq!(status.consecutive_failures, 1);
+        assert_eq!(status.last_success, Some(false));
+        assert_eq!(core.schedule_runs("purge", 10).await.unwrap().len(), 1);
+
+        core.delete_schedule("purge").await.unwrap();
+        assert!(core.get_schedule("purge").await.is_err());
+    }
+}
@@ -0,0 +1,108 @@
+#[cfg(test)]
+mod tests {
+    use mcp_core::{
+        core::{
+            mcp_core_builder::MCPCoreBuilder, mcp_core_proxy_ext::McpCoreProxyExt,
+            mcp_core_selftest_ext::McpCoreSelftestExt, mcp_core_undo_ext::McpCoreUndoExt,
+        },
+        diagnose::{DiagnosisStatus, DiagnosisStep},
+        models::types::ServerRegistrationRequest,
+        selftest::{SelftestReport, SelftestStep, SELFTEST_SERVER_ID},
+    };
+    use serde_json::json;
+    use std::sync::Arc;
+    use tempfile::tempdir;
+
+    use crate::common::{db, FailingLauncher};
+
+    fn step(step: DiagnosisStep, duration_ms: u64) -> SelftestStep {
+        SelftestStep { step, duration_ms }
+    }
+
+    #[test]
+    fn test_report_text() {
+        let report = SelftestReport::new(
+            "mcp-dockmaster",
+            0,
+            vec![
+                step(
+                    DiagnosisStep::pass("mock_server", "Mock server", "Node.js v20".into()),
+                    3,
+                ),
+                step(
+                    DiagnosisStep::fail(
+                        "register",
+                        "Registered",
+                        "node exited".into(),
+                        "Check the logs".into(),
+                    ),
+                    120,
+                ),
+                step(
+                    DiagnosisStep::skipped("initialize", "Handshake", "Skipped".into()),
+                    0,
+                ),
+            ],
+        );
+        assert!(!report.passed);
+
+        let text = report.to_text();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "Dockmaster self-test: FAILED");
+        assert!(lines[1].starts_with("mcp-dockmaster (core "));
+        assert_eq!(lines[2], "[pass] Mock server (3 ms): Node.js v20");
+        assert_eq!(lines[3], "[FAIL] Registered (120 ms): node exited");
+        assert_eq!(lines[4], "       hint: Check the logs");
+        assert_eq!(lines[5], "[skip] Handshake (0 ms): Skipped");
+
+        // Steps are flattened next to their duration
+        let value = serde_json::to_value(&report).unwrap();
+        assert_eq!(value["steps"][1]["status"], "fail");
+        assert_eq!(value["steps"][1]["duration_ms"], 120);
+    }
+
+    #[tokio::test]
+    async fn test_failed_selftest_leaves_nothing_behind() {
+        let dir = tempdir().unwrap();
+        let db = db(&dir);
+        let core = MCPCoreBuilder::new(db)
+            .process_launcher(Arc::new(FailingLauncher::new()))
+            .build();
+
+        // Left by a self-test that was interrupted
+        let leftover: ServerRegistrationRequest = serde_json::from_value(json!({
+            "server_id": SELFTEST_SERVER_ID,
+            "server_name": "Dockmaster self-test",
+            "description": "Temporary server of the self-test",
+            "tools_type": "node",
+            "configuration": { "command": "node", "args": ["mock_server.cjs"] },
+            "enabled": false
+        }))
+        .unwrap();
+        core.register_server(leftover).await.unwrap();
+
+        // Whether Node.js is installed or not, the mock server never starts
+        let report = core.run_selftest().await;
+        assert!(!report.passed);
+        let failed = report
+            .steps
+            .iter()
+            .position(|step| step.step.status == DiagnosisStatus::Fail)
+            .unwrap();
+        assert!(report.steps[failed + 1..report.steps.len() - 1]
+            .iter()
+            .all(|step| step.step.status == DiagnosisStatus::Skipped));
+
+        let cleanup = report.steps.last().unwrap();
+        assert_eq!(cleanup.step.id, "cleanup");
+        assert_eq!(cleanup.step.status, DiagnosisStatus::Pass);
+        assert!(core
+            .tool_registry
+            .read()
+            .await
+            .get_server(SELFTEST_SERVER_ID)
+            .is_err());
+        assert!(!dir.path().join("selftest").exists());
+        assert!(core.undoable_changes().await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,152 @@
+#[cfg(test)]
+mod tests {
+    use mcp_core::{
+        database::db_manager::DBManager,
+        models::{
+            server_id::{
+                normalize_server_id, slugify_server_id, unique_server_id, validate_server_id,
+                MAX_SERVER_ID_LEN,
+            },
+            types::{ServerDefinition, ServerToolInfo},
+        },
+        registry::server_registry::ServerRegistry,
+    };
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_validate_server_id() {
+        for id in ["filesystem", "hello_world", "server-2", "7zip"] {
+            assert!(validate_server_id(id).is_ok(), "{}", id);
+        }
+        for id in [
+            "",
+            "builtin",
+            "all",
+            "auto",
+            "My Server",
+            "owner/repo",
+            "-dash",
+            "server:tool",
+            "café",
+        ] {
+            assert!(validate_server_id(id).is_err(), "{}", id);
+        }
+        assert!(validate_server_id(&"a".repeat(MAX_SERVER_ID_LEN)).is_ok());
+        assert!(validate_server_id(&"a".repeat(MAX_SERVER_ID_LEN + 1)).is_err());
+    }
+
+    #[test]
+    fn test_slugify_server_id() {
+        assert_eq!(slugify_server_id("My Cool Server!"), "my-cool-server");
+        assert_eq!(slugify_server_id("owner/repo.js"), "owner-repo-js");
+        assert_eq!(slugify_server_id("  --Weather__ "), "weather");
+        assert_eq!(slugify_server_id("Café"), "caf");
+        assert_eq!(slugify_server_id("???"), "server");
+        assert_eq!(slugify_server_id("Builtin"), "builtin-server");
+
+        let long = slugify_server_id(&"word ".repeat(40));
+        assert!(long.len() <= MAX_SERVER_ID_LEN);
+        assert!(validate_server_id(&long).is_ok());
+
+        assert_eq!(normalize_server_id("hello_world"), "hello_world");
+        assert_eq!(normalize_server_id("Hello World"), "hello-world");
+    }
+
+    #[test]
+    fn test_unique_server_id() {
+        let taken = ["github", "github-2"];
+        assert_eq!(unique_server_id("slack", |id| taken.contains(&id)), "slack");
+        assert_eq!(
+            unique_server_id("github", |id| taken.contains(&id)),
+            "github-3"
+        );
+
+        let base = "a".repeat(MAX_SERVER_ID_LEN);
+        let id = unique_server_id(&base, |id| id == base);
+        assert_eq!(id.len(), MAX_SERVER_ID_LEN);
+        assert!(id.ends_with("-2"));
+    }
+
+    fn server(name: &str) -> ServerDefinition {
+        serde_json::from_value(json!({
+            "name": name,
+            "description": "",
+            "enabled": true,
+            "tools_type": "node",
+            "configuration": { "command": "node" }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_existing_ids_are_migrated() {
+        let temp_dir = tempdir().expect("Failed to create temp directory");
+        let db = DBManager::with_path(temp_dir.path().join("mcp-dockmaster.db"))
+            .expect("Failed to create database");
+        db.apply_migrations().expect("Failed to apply migrations");
+
+        db.save_server("my-server", &server("Mine")).unwrap();
+        db.save_server("My Server", &server("Legacy")).unwrap();
+        let tool = ServerToolInfo::from_value(
+            json!({ "name": "ping", "description": "" }),
+            "My Server".to_string(),
+        )
+        .unwrap();
+        db.save_server_tool(&tool).unwrap();
+
+        let renames = db.normalize_server_ids().unwrap();
+        assert_eq!(
+            renames,
+            vec![("My Server".to_string(), "my-server-2".to_string())]
+        );
+        assert!(db.get_server("My Server").is_err());
+        assert_eq!(db.get_server("my-server-2").unwrap().name, "Legacy");
+        assert_eq!(db.get_server("my-server").unwrap().name, "Mine");
+        assert_eq!(db.get_server_tools("my-server-2").unwrap().len(), 1);
+        assert_eq!(
+            db.get_server_id_alias("My Server").unwrap().as_deref(),
+            Some("my-server-2")
+        );
+
+        // Old references resolve, and registering the same entry again reuses the id
+        let registry = ServerRegistry::with_db_manager(db.clone());
+        assert_eq!(registry.resolve_server_id("My Server"), "my-server-2");
+        assert_eq!(registry.resolve_server_id("my-server"), "my-server");
+        assert_eq!(
+            registry.assign_server_id("My Server", "Legacy").unwrap(),
+            "my-server-2"
+        );
+
+        assert!(db.normalize_server_ids().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_assign_server_id() {
+        let temp_dir = tempdir().expect("Failed to create temp directory");
+        let db = DBManager::with_path(temp_dir.path().join("mcp-dockmaster.db"))
+            .expect("Failed to create database");
+        db.apply_migrations().expect("Failed to apply migrations");
+        db.save_server("weather", &server("Weather")).unwrap();
+        let registry = ServerRegistry::with_db_manager(db.clone());
+
+        // Valid ids are kept, registering again replaces the server
+        assert_eq!(
+            registry.assign_server_id("weather", "Weather").unwrap(),
+            "weather"
+        );
+        assert_eq!(
+            registry.assign_server_id("Weather", "Weather").unwrap(),
+            "weather-2"
+        );
+        assert_eq!(registry.resolve_server_id("Weather"), "weather-2");
+        assert_eq!(
+            registry.assign_server_id("builtin", "Builtin").unwrap(),
+            "builtin-server"
+        );
+        assert_eq!(
+            registry.assign_server_id("", "Slack Tools").unwrap(),
+            "slack-tools"
+        );
+    }
+}
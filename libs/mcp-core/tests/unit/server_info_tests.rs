@@ -0,0 +1,253 @@
+#[cfg(test)]
+mod tests {
+    use mcp_core::{
+        core::{
+            mcp_core_builder::MCPCoreBuilder, mcp_core_proxy_ext::McpCoreProxyExt,
+            mcp_core_server_info_ext::McpCoreServerInfoExt,
+        },
+        database::db_manager::DBManager,
+        models::types::{ServerDefinition, ServerInitInfo},
+        server_info::{shared_instructions, MAX_SERVER_INSTRUCTIONS, MAX_SHARED_INSTRUCTIONS},
+    };
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    fn server(share_instructions: Option<bool>) -> ServerDefinition {
+        serde_json::from_value(json!({
+            "name": "Weather",
+            "description": "",
+            "enabled": true,
+            "tools_type": "node",
+            "configuration": { "command": "node", "share_instructions": share_instructions }
+        }))
+        .unwrap()
+    }
+
+    fn info(instructions: Option<&str>) -> ServerInitInfo {
+        ServerInitInfo {
+            name: "weather-mcp".to_string(),
+            version: "1.2.0".to_string(),
+            protocol_version: Some("2024-11-05".to_string()),
+            instructions: instructions.map(str::to_string),
+            initialized_at: 1_700_000_000_000,
+            capabilities: Some(json!({ "tools": {} })),
+        }
+    }
+
+    #[test]
+    fn test_from_initialize_result() {
+        let result = json!({
+            "protocolVersion": "2024-11-05",
+            "capabilities": { "tools": {} },
+            "serverInfo": { "name": "weather-mcp", "version": "1.2.0" },
+            "instructions": "Call get_forecast with a city name."
+        });
+        let parsed = ServerInitInfo::from_initialize_result(&result, 1_700_000_000_000).unwrap();
+        assert_eq!(parsed, info(Some("Call get_forecast with a city name.")));
+
+        // Blank instructions are no instructions
+        let result = json!({ "serverInfo": { "name": "weather-mcp" }, "instructions": " " });
+        let parsed = ServerInitInfo::from_initialize_result(&result, 0).unwrap();
+        assert_eq!(parsed.version, "");
+        assert_eq!(parsed.instructions, None);
+        assert_eq!(parsed.protocol_version, None);
+        // Servers declaring no capabilities at all are still asked for their tools
+        assert!(parsed.declares_tools());
+
+        let result = json!({
+            "capabilities": { "resources": {} },
+            "serverInfo": { "name": "files-mcp" }
+        });
+        let parsed = ServerInitInfo::from_initialize_result(&result, 0).unwrap();
+        assert!(!parsed.declares_tools());
+
+        assert!(ServerInitInfo::from_initialize_result(&json!({}), 0).is_none());
+    }
+
+    #[test]
+    fn test_shared_instructions() {
+        assert_eq!(shared_instructions(&[]), "");
+        assert_eq!(
+            shared_instructions(&[("weather".to_string(), info(None))]),
+            ""
+        );
+
+        let shared = shared_instructions(&[
+            ("forecast".to_string(), info(Some("Use metric units."))),
+            ("weather".to_string(), info(None)),
+        ]);
+        assert_eq!(
+            shared,
+            "\n\n# Instructions of the installed servers\n\n## forecast\nUse metric units."
+        );
+
+        // Each server and all of them together are capped, at char boundaries
+        let long = "é".repeat(MAX_SERVER_INSTRUCTIONS * 2);
+        let servers: Vec<(String, ServerInitInfo)> = (0..10)
+            .map(|i| (format!("server-{}", i), info(Some(&long))))
+            .collect();
+        let shared = shared_instructions(&servers);
+        let kept = MAX_SHARED_INSTRUCTIONS / MAX_SERVER_INSTRUCTIONS;
+        assert_eq!(shared.matches("\n## ").count(), kept);
+        assert!(shared.contains(&format!("## server-{}\n", kept - 1)));
+        assert!(!shared.contains(&format!("## server-{}\n", kept)));
+        assert_eq!(
+            shared.chars().filter(|c| *c == 'é').count() + kept,
+            MAX_SHARED_INSTRUCTIONS
+        );
+    }
+
+    #[test]
+    fn test_saved_server_info() {
+        let dir = tempdir().unwrap();
+        let mut db = DBManager::with_path(dir.path().join("mcp-dockmaster.db")).unwrap();
+        db.apply_migrations().unwrap();
+        db.save_server("weather", &server(Some(true))).unwrap();
+        let saved = db.get_server("weather").unwrap();
+        assert_eq!(saved.configuration.unwrap().share_instructions, Some(true));
+
+        db.save_server_info("weather", &info(Some("Use metric units.")))
+            .unwrap();
+        db.save_server_info("weather", &info(None)).unwrap();
+        assert_eq!(db.get_server_infos().unwrap()["weather"], info(None));
+
+        db.rename_server("weather", "forecast").unwrap();
+        let infos = db.get_server_infos().unwrap();
+        assert!(infos.contains_key("forecast"));
+        assert!(!infos.contains_key("weather"));
+
+        // Goes with the server
+        db.delete_server("forecast").unwrap();
+        assert!(db.get_server_infos().unwrap().is_empty());
+
+        db.save_server("weather", &server(None)).unwrap();
+        db.save_server_info("weather", &info(None)).unwrap();
+        db.clear_database().unwrap();
+        assert!(db.get_server_infos().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_servers_share_their_instructions() {
+        let dir = tempdir().unwrap();
+        let db = DBManager::with_path(dir.path().join("mcp-dockmaster.db")).unwrap();
+        db.apply_migrations().unwrap();
+        db.save_server("weather", &server(Some(true))).unwrap();
+        db.save_server("forecast", &server(None)).unwrap();
+        db.save_server("tides", &server(Some(true))).unwrap();
+        db.save_server_info("weather", &info(Some("Use metric units.")))
+            .unwrap();
+        db.save_server_info("forecast", &info(Some("Ask for a city first.")))
+            .unwrap();
+        let core = MCPCoreBuilder::new(db).build();
+
+        // Only what the servers that opted in said, a server that never started says nothing
+        let shared = core.shared_server_instructions().await;
+        assert!(shared.contains("Use metric units."), "{}", shared);
+        assert!(!shared.contains("Ask for a city first."), "{}", shared);
+        assert!(!shared.contains("tides"), "{}", shared);
+
+        core.set_share_instructions("forecast", true).await.unwrap();
+        core.set_share_instructions("weather", false).await.unwrap();
+        let shared = core.shared_server_instructions().await;
+        assert!(!shared.contains("Use metric units."), "{}", shared);
+        assert!(shared.contains("Ask for a city first."), "{}", shared);
+
+        assert_eq!(
+            core.server_info("weather").await.unwrap(),
+            Some(info(Some("Use metric units.")))
+        );
+        assert_eq!(core.server_info("tides").await.unwrap(), None);
+        assert!(core.server_info("unknown").await.is_err());
+        assert!(core.set_share_instructions("unknown", true).await.is_err());
+
+        let servers = core.list_servers().await.unwrap();
+        let weather = servers
+            .iter()
+            .find(|server| server.id.as_str() == "weather")
+            .unwrap();
+        assert_eq!(weather.server_info.as_ref().unwrap().version, "1.2.0");
+        let tides = servers
+            .iter()
+            .find(|server| server.id.as_str() == "tides")
+            .unwrap();
+        assert!(tides.server_info.is_none());
+    }
+
+    #[cfg(not(windows))]
+    mod lenient_initialize_server {
+        use mcp_core::{
+            core::{
+                mcp_core_builder::MCPCoreBuilder, mcp_core_proxy_ext::McpCoreProxyExt,
+                mcp_core_server_info_ext::McpCoreServerInfoExt,
+            },
+            models::types::{ServerConfiguration, ServerRegistrationRequest},
+        };
+        use tempfile::tempdir;
+
+        use crate::common::db;
+
+        /// Answers initialize with an error, lists its tool all the same
+        const REFUSING_SERVER: &str = r#"
+while read -r line; do
+  id=$(echo "$line" | sed -n 's/.*"id":\([0-9]*\).*/\1/p')
+  [ -z "$id" ] && continue
+  case "$line" in
+    *'"initialize"'*)
+      echo "{\"jsonrpc\":\"2.0\",\"id\":$id,\"error\":{\"code\":-32601,\"message\":\"Method not found\"}}"
+      continue ;;
+    *'"tools/list"'*) result='{"tools":[{"name":"echo","inputSchema":{"type":"object"}}]}' ;;
+    *) result='{}' ;;
+  esac
+  echo "{\"jsonrpc\":\"2.0\",\"id\":$id,\"result\":$result}"
+done
+"#;
+
+        #[tokio::test]
+        async fn test_lenient_server_starts_without_initialize_answer() {
+            let dir = tempdir().unwrap();
+            let script = dir.path().join("server.sh");
+            std::fs::write(&script, REFUSING_SERVER).unwrap();
+            let core = MCPCoreBuilder::new(db(&dir)).build();
+
+            let register = |server_id: &str, lenient_initialize: Option<bool>| {
+                core.register_server(ServerRegistrationRequest {
+                    server_id: server_id.to_string(),
+                    server_name: server_id.to_string(),
+                    description: String::new(),
+                    tools_type: "node".to_string(),
+                    configuration: Some(ServerConfiguration {
+                        command: Some("sh".to_string()),
+                        args: Some(vec![script.display().to_string()]),
+                        lenient_initialize,
+                        ..Default::default()
+                    }),
+                    distribution: None,
+                    provenance: None,
+                    authentication: None,
+                    enabled: None,
+                })
+            };
+
+            let response = register("strict", None).await.unwrap();
+            assert!(
+                response.message.contains("failed to start"),
+                "{}",
+                response.message
+            );
+            assert_eq!(response.tool_count, Some(0));
+
+            let response = register("lenient", Some(true)).await.unwrap();
+            assert!(response.success, "{}", response.message);
+            assert!(
+                !response.message.contains("failed to start"),
+                "{}",
+                response.message
+            );
+            assert_eq!(response.tool_count, Some(1));
+            // Nothing was answered to keep
+            assert_eq!(core.server_info("lenient").await.unwrap(), None);
+
+            core.kill_all_processes().await.unwrap();
+        }
+    }
+}
@@ -0,0 +1,158 @@
+#[cfg(test)]
+mod tests {
+    use std::fs::OpenOptions;
+    use std::io::Write;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use async_trait::async_trait;
+    use mcp_core::{
+        core::{
+            mcp_core::MCPCore, mcp_core_builder::MCPCoreBuilder,
+            mcp_core_proxy_ext::McpCoreProxyExt,
+        },
+        mcp_state::{
+            launch_plan::LaunchPlan,
+            process_launcher::{LaunchedServer, ProcessLauncher},
+            startup_capture::StartupCapture,
+        },
+        models::types::{ServerConfiguration, ServerRegistrationRequest},
+        server_logs::{
+            ServerLogs, SERVER_LOG_MAX_BYTES, SERVER_LOG_MAX_LINE_BYTES, SERVER_LOG_POLL_INTERVAL,
+        },
+    };
+    use tempfile::{tempdir, TempDir};
+
+    use crate::common::db;
+
+    /// Writes to the stderr of the spawn, then fails
+    struct CrashingLauncher;
+
+    #[async_trait]
+    impl ProcessLauncher for CrashingLauncher {
+        async fn launch(&self, _: &LaunchPlan, spawn_id: &str) -> Result<LaunchedServer, String> {
+            let capture = StartupCapture::create(spawn_id).unwrap();
+            std::fs::write(capture.stderr_path(), "starting\nError: missing API key").unwrap();
+            Err("Server exited during startup with exit code 1".to_string())
+        }
+    }
+
+    fn core() -> (TempDir, MCPCore) {
+        let dir = tempdir().unwrap();
+        let db = db(&dir);
+        let core = MCPCoreBuilder::new(db)
+            .process_launcher(Arc::new(CrashingLauncher))
+            .build();
+        (dir, core)
+    }
+
+    fn append(path: &std::path::Path, text: &str) {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .unwrap();
+        file.write_all(text.as_bytes()).unwrap();
+    }
+
+    #[test]
+    fn test_buffer_is_capped() {
+        let logs = ServerLogs::new();
+        let line = "x".repeat(1000);
+        let count = SERVER_LOG_MAX_BYTES / line.len() + 10;
+        for _ in 0..count {
+            logs.push("chatty", &line);
+        }
+        logs.push("chatty", &"y".repeat(SERVER_LOG_MAX_LINE_BYTES * 2));
+
+        let snapshot = logs.snapshot("chatty", None);
+        let bytes: usize = snapshot.lines.iter().map(|line| line.line.len()).sum();
+        assert!(bytes <= SERVER_LOG_MAX_BYTES);
+        assert_eq!(
+            snapshot.dropped_lines as usize + snapshot.lines.len(),
+            count + 1
+        );
+        let last = snapshot.lines.last().unwrap();
+        assert_eq!(last.line.len(), SERVER_LOG_MAX_LINE_BYTES);
+        assert!(last.timestamp > 0);
+        assert!(!snapshot.following);
+
+        assert!(logs.snapshot("quiet", None).lines.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_follow_reads_new_lines() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("stderr.log");
+        let logs = ServerLogs::new();
+        logs.follow("fetch", path.clone());
+        assert!(logs.snapshot("fetch", None).following);
+
+        append(&path, "first\r\nsec");
+        let lines = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                tokio::time::sleep(SERVER_LOG_POLL_INTERVAL).await;
+                let snapshot = logs.snapshot("fetch", None);
+                if !snapshot.lines.is_empty() {
+                    return snapshot.lines;
+                }
+            }
+        })
+        .await
+        .expect("the new line wasn't read");
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].line, "first");
+
+        // Stopping reads the rest, the partial last line included
+        append(&path, "ond\nthird");
+        logs.stop("fetch");
+        let snapshot = logs.snapshot("fetch", None);
+        let lines: Vec<&str> = snapshot
+            .lines
+            .iter()
+            .map(|line| line.line.as_str())
+            .collect();
+        assert_eq!(lines, vec!["first", "second", "third"]);
+        assert!(!snapshot.following);
+
+        let since = snapshot.lines[2].timestamp + 1;
+        assert!(logs.snapshot("fetch", Some(since)).lines.is_empty());
+
+        logs.remove("fetch");
+        assert!(logs.snapshot("fetch", None).lines.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_stderr_of_a_failed_start_is_kept() {
+        let (_dir, core) = core();
+        let request = ServerRegistrationRequest {
+            server_id: "crashing".to_string(),
+            server_name: "Crashing".to_string(),
+            description: String::new(),
+            tools_type: "node".to_string(),
+            configuration: Some(ServerConfiguration {
+                command: Some("node".to_string()),
+                args: Some(vec!["index.js".to_string()]),
+                ..Default::default()
+            }),
+            distribution: None,
+            provenance: None,
+            authentication: None,
+            enabled: None,
+        };
+        core.register_server(request).await.unwrap();
+
+        let logs = core
+            .get_server_logs("crashing".to_string(), None)
+            .await
+            .unwrap();
+        let lines: Vec<&str> = logs.lines.iter().map(|line| line.line.as_str()).collect();
+        assert_eq!(lines, vec!["starting", "Error: missing API key"]);
+        assert!(!logs.following);
+
+        assert!(core
+            .get_server_logs("unknown".to_string(), None)
+            .await
+            .is_err());
+    }
+}
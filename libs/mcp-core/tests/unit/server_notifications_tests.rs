@@ -0,0 +1,443 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use async_trait::async_trait;
+    use mcp_core::{
+        core::mcp_core_builder::MCPCoreBuilder,
+        database::db_manager::DBManager,
+        mcp_state::server_notifications::{
+            parse_progress_topic, progress_topic, requested_progress_token, ServerNotification,
+        },
+        sessions::{ClientSession, SessionTransport},
+    };
+    use serde_json::{json, Value};
+    use tempfile::tempdir;
+
+    /// Transport keeping what was sent
+    #[derive(Default)]
+    struct RecordingTransport {
+        sent: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl SessionTransport for RecordingTransport {
+        fn kind(&self) -> &'static str {
+            "test"
+        }
+
+        async fn send(&self, message: &str) -> Result<(), String> {
+            self.sent.lock().unwrap().push(message.to_string());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_notifications_are_parsed() {
+        assert_eq!(
+            ServerNotification::parse(&json!({
+                "jsonrpc": "2.0",
+                "method": "notifications/message",
+                "params": { "level": "warning", "logger": "config", "data": "missing key" }
+            })),
+            Some(ServerNotification::Message {
+                level: "warning".to_string(),
+                logger: Some("config".to_string()),
+                data: json!("missing key"),
+            })
+        );
+        assert_eq!(
+            ServerNotification::parse(&json!({
+                "method": "notifications/tools/list_changed"
+            })),
+            Some(ServerNotification::ToolsListChanged)
+        );
+        assert_eq!(
+            ServerNotification::parse(&json!({ "method": "notifications/resources/updated" })),
+            Some(ServerNotification::Other(
+                "notifications/resources/updated".to_string()
+            ))
+        );
+
+        // Responses and requests aren't notifications
+        assert_eq!(
+            ServerNotification::parse(&json!({ "id": 1, "result": {} })),
+            None
+        );
+        assert_eq!(
+            ServerNotification::parse(&json!({ "id": 2, "method": "roots/list" })),
+            None
+        );
+        // Progress without a token can't be routed
+        assert_eq!(
+            ServerNotification::parse(&json!({
+                "method": "notifications/progress",
+                "params": { "progress": 1 }
+            })),
+            None
+        );
+    }
+
+    #[test]
+    fn test_progress_tokens_of_requests() {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 7,
+            "method": "tools/call",
+            "params": { "name": "build", "_meta": { "progressToken": "build-1" } }
+        });
+        let token = requested_progress_token(&request).unwrap();
+        let topic = progress_topic("session", token);
+        assert_eq!(topic, "progress/session/\"build-1\"");
+        assert_eq!(
+            parse_progress_topic(&topic),
+            Some(("session", json!("build-1")))
+        );
+        assert_eq!(progress_topic("session", &json!(42)), "progress/session/42");
+        assert_eq!(parse_progress_topic("build-1"), None);
+
+        let notification = json!({
+            "method": "notifications/cancelled",
+            "params": { "_meta": { "progressToken": "build-1" } }
+        });
+        assert_eq!(requested_progress_token(&notification), None);
+    }
+
+    #[tokio::test]
+    async fn test_progress_reaches_subscribed_sessions() {
+        let dir = tempdir().unwrap();
+        let db = DBManager::with_path(dir.path().join("mcp-dockmaster.db")).unwrap();
+        db.apply_migrations().unwrap();
+        let core = MCPCoreBuilder::new(db).build();
+
+        let waiting = Arc::new(RecordingTransport::default());
+        let other = Arc::new(RecordingTransport::default());
+        let sessions = core.mcp_state.read().await.sessions.clone();
+        sessions
+            .register(ClientSession::new("waiting".to_string(), waiting.clone()))
+            .await;
+        sessions
+            .register(ClientSession::new("other".to_string(), other.clone()))
+            .await;
+        // Both use the same token, only the session the server reports for gets it
+        let token = json!("build-1");
+        let topic = progress_topic("waiting", &token);
+        assert!(sessions.subscribe("waiting", &topic).await);
+        assert!(
+            sessions
+                .subscribe("other", &progress_topic("other", &token))
+                .await
+        );
+
+        let progress = json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/progress",
+            "params": { "progressToken": topic, "progress": 50, "total": 100 }
+        });
+        let dispatched = core
+            .dispatch_server_notification("builder", &progress)
+            .await;
+        assert!(matches!(
+            dispatched,
+            Some(ServerNotification::Progress { .. })
+        ));
+
+        let sent = waiting.sent.lock().unwrap().clone();
+        assert_eq!(sent.len(), 1);
+        let forwarded: Value = serde_json::from_str(&sent[0]).unwrap();
+        assert_eq!(forwarded["method"], "notifications/progress");
+        assert_eq!(forwarded["params"]["progressToken"], "build-1");
+        assert_eq!(forwarded["params"]["progress"], 50);
+        assert!(other.sent.lock().unwrap().is_empty());
+
+        // Log messages are only logged
+        let message = json!({
+            "method": "notifications/message",
+            "params": { "level": "info", "data": "ready" }
+        });
+        assert!(core
+            .dispatch_server_notification("builder", &message)
+            .await
+            .is_some());
+        assert_eq!(waiting.sent.lock().unwrap().len(), 1);
+        assert_eq!(
+            core.dispatch_server_notification("builder", &json!({ "id": 1, "result": {} }))
+                .await,
+            None
+        );
+    }
+
+    #[cfg(not(windows))]
+    mod progress_server {
+        use std::sync::Arc;
+
+        use mcp_core::{
+            core::{mcp_core_builder::MCPCoreBuilder, mcp_core_proxy_ext::McpCoreProxyExt},
+            mcp_server::mcp_router::MCPDockmasterRouter,
+            mcp_state::server_notifications::{progress_topic, requested_progress_token},
+            models::types::{ServerConfiguration, ServerRegistrationRequest},
+            sessions::{ClientSession, PostedToolCall},
+        };
+        use mcp_sdk_server::Router;
+        use serde_json::{json, Value};
+        use tempfile::tempdir;
+
+        use super::RecordingTransport;
+        use crate::common::db;
+
+        /// MCP server whose tool reports three steps of progress for the token of the
+        /// call, and waits a little for them to be relayed before answering
+        const PROGRESS_SERVER: &str = r#"
+while read -r line; do
+  id=$(echo "$line" | sed -n 's/.*"id":\([0-9]*\).*/\1/p')
+  [ -z "$id" ] && continue
+  case "$line" in
+    *'"initialize"'*) result='{"capabilities":{"tools":{}}}' ;;
+    *'"tools/list"'*)
+      result='{"tools":[{"name":"build","inputSchema":{"type":"object"}}]}' ;;
+    *'"tools/call"'*)
+      token=$(echo "$line" | sed 's/.*"progressToken":\("[^}]*"\)}.*/\1/')
+      for step in 1 2 3; do
+        echo "{\"jsonrpc\":\"2.0\",\"method\":\"notifications/progress\",\"params\":{\"progressToken\":$token,\"progress\":$step,\"total\":3}}"
+      done
+      sleep 1
+      result='{"content":[]}' ;;
+    *) result='{}' ;;
+  esac
+  echo "{\"jsonrpc\":\"2.0\",\"id\":$id,\"result\":$result}"
+done
+"#;
+
+        #[tokio::test]
+        async fn test_progress_is_relayed_until_the_call_returns() {
+            let dir = tempdir().unwrap();
+            let script = dir.path().join("server.sh");
+            std::fs::write(&script, PROGRESS_SERVER).unwrap();
+            let core = MCPCoreBuilder::new(db(&dir)).build();
+            let response = core
+                .register_server(ServerRegistrationRequest {
+                    server_id: "builder".to_string(),
+                    server_name: "Builder".to_string(),
+                    description: String::new(),
+                    tools_type: "node".to_string(),
+                    configuration: Some(ServerConfiguration {
+                        command: Some("sh".to_string()),
+                        args: Some(vec![script.display().to_string()]),
+                        ..Default::default()
+                    }),
+                    distribution: None,
+                    provenance: None,
+                    authentication: None,
+                    enabled: None,
+                })
+                .await
+                .unwrap();
+            assert!(response.success, "{}", response.message);
+
+            let waiting = Arc::new(RecordingTransport::default());
+            let sessions = core.mcp_state.read().await.sessions.clone();
+            sessions
+                .register(ClientSession::new("waiting".to_string(), waiting.clone()))
+                .await;
+
+            // What the SSE endpoint does with a posted call
+            let mut request = json!({
+                "jsonrpc": "2.0",
+                "id": 7,
+                "method": "tools/call",
+                "params": { "name": "build", "_meta": { "progressToken": "build-1" } }
+            });
+            let token = requested_progress_token(&request).unwrap().clone();
+            let topic = progress_topic("waiting", &token);
+            assert!(sessions.subscribe("waiting", &topic).await);
+            let call = PostedToolCall {
+                id: json!(7),
+                progress_token: Some(token),
+            };
+            assert!(call.attach(&mut request));
+
+            let router = MCPDockmasterRouter::new(core.clone()).await;
+            router
+                .for_session("waiting")
+                .call_tool("build", request["params"]["arguments"].clone())
+                .await
+                .unwrap();
+
+            let sent: Vec<Value> = waiting
+                .sent
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|message| serde_json::from_str(message).unwrap())
+                .collect();
+            assert_eq!(sent.len(), 3);
+            for (step, message) in sent.iter().enumerate() {
+                assert_eq!(
+                    message,
+                    &json!({
+                        "jsonrpc": "2.0",
+                        "method": "notifications/progress",
+                        "params": { "progressToken": "build-1", "progress": step + 1, "total": 3 }
+                    })
+                );
+            }
+            // No more progress comes for a finished call
+            assert!(sessions.subscribers(&topic).await.is_empty());
+
+            core.kill_all_processes().await.unwrap();
+        }
+    }
+
+    #[cfg(not(windows))]
+    mod tools_changed_server {
+        use std::time::Duration;
+
+        use mcp_core::{
+            core::{mcp_core_builder::MCPCoreBuilder, mcp_core_proxy_ext::McpCoreProxyExt},
+            models::types::{ServerConfiguration, ServerRegistrationRequest},
+        };
+        use serde_json::json;
+        use tempfile::tempdir;
+
+        use crate::common::db;
+
+        /// Lists one tool, then tells its tools changed and lists two from then on
+        const CHANGING_SERVER: &str = r#"
+tool() { echo "{\"name\":\"$1\",\"inputSchema\":{\"type\":\"object\"}}"; }
+while read -r line; do
+  id=$(echo "$line" | sed -n 's/.*"id":\([0-9]*\).*/\1/p')
+  [ -z "$id" ] && continue
+  case "$line" in
+    *'"initialize"'*) result='{"capabilities":{"tools":{"listChanged":true}}}' ;;
+    *'"tools/list"'*)
+      if [ -f "$1" ]; then
+        result="{\"tools\":[$(tool first),$(tool second)]}"
+      else
+        result="{\"tools\":[$(tool first)]}"
+      fi ;;
+    *) result='{}' ;;
+  esac
+  echo "{\"jsonrpc\":\"2.0\",\"id\":$id,\"result\":$result}"
+  case "$line" in
+    *'"tools/list"'*) if [ ! -f "$1" ]; then
+      touch "$1"
+      echo '{"jsonrpc":"2.0","method":"notifications/tools/list_changed"}'
+    fi ;;
+  esac
+done
+"#;
+
+        #[tokio::test]
+        async fn test_changed_tools_are_discovered_again() {
+            let dir = tempdir().unwrap();
+            let script = dir.path().join("server.sh");
+            std::fs::write(&script, CHANGING_SERVER).unwrap();
+            let marker = dir.path().join("changed");
+            let core = MCPCoreBuilder::new(db(&dir)).build();
+
+            let response = core
+                .register_server(ServerRegistrationRequest {
+                    server_id: "changing".to_string(),
+                    server_name: "Changing".to_string(),
+                    description: String::new(),
+                    tools_type: "node".to_string(),
+                    configuration: Some(ServerConfiguration {
+                        command: Some("sh".to_string()),
+                        args: Some(vec![
+                            script.display().to_string(),
+                            marker.display().to_string(),
+                        ]),
+                        ..Default::default()
+                    }),
+                    distribution: None,
+                    provenance: None,
+                    authentication: None,
+                    enabled: None,
+                })
+                .await
+                .unwrap();
+            assert!(response.success, "{}", response.message);
+
+            // The notification comes after the first listing, it is handled on its own
+            let server_tools = core.mcp_state.read().await.server_tools.clone();
+            let mut tool_count = 0;
+            for _ in 0..50 {
+                tool_count = server_tools.read().await["changing"].len();
+                if tool_count == 2 {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+            assert_eq!(tool_count, 2);
+
+            core.kill_all_processes().await.unwrap();
+        }
+
+        /// Lists one tool, then never answers tools/list again
+        const STALLING_SERVER: &str = r#"
+listed=
+while read -r line; do
+  id=$(echo "$line" | sed -n 's/.*"id":\([0-9]*\).*/\1/p')
+  [ -z "$id" ] && continue
+  case "$line" in
+    *'"initialize"'*) result='{"capabilities":{"tools":{"listChanged":true}}}' ;;
+    *'"tools/list"'*)
+      [ -n "$listed" ] && continue
+      listed=1
+      result='{"tools":[{"name":"first","inputSchema":{"type":"object"}}]}' ;;
+    *) result='{}' ;;
+  esac
+  echo "{\"jsonrpc\":\"2.0\",\"id\":$id,\"result\":$result}"
+done
+"#;
+
+        #[tokio::test]
+        async fn test_changed_tools_are_discovered_in_the_background() {
+            let dir = tempdir().unwrap();
+            let script = dir.path().join("server.sh");
+            std::fs::write(&script, STALLING_SERVER).unwrap();
+            let core = MCPCoreBuilder::new(db(&dir)).build();
+
+            let response = core
+                .register_server(ServerRegistrationRequest {
+                    server_id: "stalling".to_string(),
+                    server_name: "Stalling".to_string(),
+                    description: String::new(),
+                    tools_type: "node".to_string(),
+                    configuration: Some(ServerConfiguration {
+                        command: Some("sh".to_string()),
+                        args: Some(vec![script.display().to_string()]),
+                        ..Default::default()
+                    }),
+                    distribution: None,
+                    provenance: None,
+                    authentication: None,
+                    enabled: None,
+                })
+                .await
+                .unwrap();
+            assert!(response.success, "{}", response.message);
+
+            // Handled right away while the discovery waits, the changes collapse into one
+            let mcp_state = core.mcp_state.read().await.clone();
+            let changed = json!({
+                "jsonrpc": "2.0",
+                "method": "notifications/tools/list_changed"
+            });
+            for _ in 0..3 {
+                tokio::time::timeout(
+                    Duration::from_secs(1),
+                    mcp_state.dispatch_server_notification("stalling", &changed),
+                )
+                .await
+                .unwrap();
+            }
+            assert_eq!(
+                mcp_state.changed_tools.lock().unwrap().get("stalling"),
+                Some(&true)
+            );
+
+            core.kill_all_processes().await.unwrap();
+        }
+    }
+}
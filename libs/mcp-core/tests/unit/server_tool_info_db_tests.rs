@@ -77,6 +77,8 @@ mod tests {
             server_id: server_id.to_string(),
             proxy_id: Some("proxy1".to_string()),
             is_active: true,
+            output_schema: None,
+            annotations: None,
         };
 
         // Save the tool
@@ -130,6 +132,8 @@ mod tests {
             server_id: server_id.to_string(),
             proxy_id: None,
             is_active: true,
+            output_schema: None,
+            annotations: None,
         };
 
         let tool2 = ServerToolInfo {
@@ -140,6 +144,8 @@ mod tests {
             server_id: server_id.to_string(),
             proxy_id: None,
             is_active: true,
+            output_schema: None,
+            annotations: None,
         };
 
         // Save the tools
@@ -181,6 +187,8 @@ mod tests {
             server_id: server_id.to_string(),
             proxy_id: None,
             is_active: true,
+            output_schema: None,
+            annotations: None,
         };
 
         // Save the tool
@@ -221,6 +229,8 @@ mod tests {
             server_id: server_id.to_string(),
             proxy_id: None,
             is_active: true,
+            output_schema: None,
+            annotations: None,
         };
 
         // Save the tool
@@ -235,6 +245,8 @@ mod tests {
             server_id: server_id.to_string(),
             proxy_id: Some("new_proxy".to_string()),
             is_active: true,
+            output_schema: None,
+            annotations: None,
         };
 
         db.save_server_tool(&updated_tool).unwrap();
@@ -275,6 +287,8 @@ mod tests {
             server_id: server_id.to_string(),
             proxy_id: None,
             is_active: false,
+            output_schema: None,
+            annotations: None,
         };
 
         // Save the tool
@@ -295,6 +309,8 @@ mod tests {
             server_id: server_id.to_string(),
             proxy_id: None,
             is_active: true,
+            output_schema: None,
+            annotations: None,
         };
 
         db.save_server_tool(&updated_tool).unwrap();
@@ -333,6 +349,8 @@ mod tests {
             server_id: server_id.to_string(),
             proxy_id: None,
             is_active: true,
+            output_schema: None,
+            annotations: None,
         };
 
         // Save the tool
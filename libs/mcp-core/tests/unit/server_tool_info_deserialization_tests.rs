@@ -493,7 +493,7 @@ mod tests {
             schema.schema,
             Some("http://json-schema.org/draft-07/schema#".to_string())
         );
-        assert_eq!(schema.additional_properties, Some(false));
+        assert_eq!(schema.additional_properties, Some(json!(false)));
         assert_eq!(schema.required, vec!["url"]);
 
         // Check url property
@@ -0,0 +1,152 @@
+#[cfg(test)]
+mod tests {
+    use mcp_core::{
+        database::db_manager::DBManager,
+        mcp_server::tools::{
+            get_builtin_tool_infos, get_builtin_tools, BUILTIN_SERVER_ID, REGISTER_SERVER_TOOL,
+        },
+        models::types::{ServerDefinition, ServerToolInfo},
+    };
+    use serde_json::{json, Value};
+    use tempfile::tempdir;
+
+    const ANNOTATED: &str = include_str!("../fixtures/tools/annotated.json");
+    const MISSING_DESCRIPTION: &str = include_str!("../fixtures/tools/missing_description.json");
+    const NESTED_DEFINITIONS: &str = include_str!("../fixtures/tools/nested_definitions.json");
+    const FIXTURES: [&str; 3] = [ANNOTATED, MISSING_DESCRIPTION, NESTED_DEFINITIONS];
+
+    /// The tool as listed by a server, and the tool as Dockmaster lists it
+    fn fixture(text: &str) -> (Value, Value) {
+        let golden: Value = serde_json::from_str(text).expect("fixture should be valid JSON");
+        (golden["spec"].clone(), golden["tool"].clone())
+    }
+
+    #[test]
+    fn test_spec_tools_match_fixtures() {
+        for text in FIXTURES {
+            let (spec, expected) = fixture(text);
+            let tool = ServerToolInfo::from_spec_value("filesystem", spec).unwrap();
+            assert_eq!(serde_json::to_value(&tool).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_nested_definitions_reach_clients() {
+        let (spec, _) = fixture(NESTED_DEFINITIONS);
+        let tool = ServerToolInfo::from_spec_value("filesystem", spec.clone()).unwrap();
+        assert_eq!(tool.input_schema_value(), spec["inputSchema"]);
+
+        let bare = ServerToolInfo::from_spec_value("filesystem", json!({ "name": "ping" }));
+        assert_eq!(
+            bare.unwrap().input_schema_value(),
+            json!({ "type": "object" })
+        );
+    }
+
+    #[test]
+    fn test_invalid_spec_tools_are_reported() {
+        let error = ServerToolInfo::from_spec_value("filesystem", json!({ "description": "x" }))
+            .unwrap_err();
+        assert!(error.contains("filesystem"), "{}", error);
+        assert!(error.contains("name"), "{}", error);
+
+        let error =
+            ServerToolInfo::from_spec_value("filesystem", json!({ "name": " " })).unwrap_err();
+        assert!(error.contains("empty name"), "{}", error);
+
+        for (field, tool) in [
+            (
+                "inputSchema",
+                json!({ "name": "read", "inputSchema": "object" }),
+            ),
+            (
+                "inputSchema",
+                json!({ "name": "read", "inputSchema": { "type": "object", "required": "path" } }),
+            ),
+            (
+                "outputSchema",
+                json!({ "name": "read", "outputSchema": [] }),
+            ),
+        ] {
+            let error = ServerToolInfo::from_spec_value("filesystem", tool).unwrap_err();
+            assert!(error.contains(field), "{}", error);
+            assert!(error.contains("'read'"), "{}", error);
+        }
+    }
+
+    #[test]
+    fn test_saved_tools_read_back_unchanged() {
+        let dir = tempdir().unwrap();
+        let db = DBManager::with_path(dir.path().join("mcp-dockmaster.db")).unwrap();
+        db.apply_migrations().unwrap();
+        let server: ServerDefinition = serde_json::from_value(json!({
+            "name": "Filesystem",
+            "description": "",
+            "enabled": true,
+            "tools_type": "node",
+            "configuration": { "command": "node" }
+        }))
+        .unwrap();
+        db.save_server("filesystem", &server).unwrap();
+
+        for text in FIXTURES {
+            let (spec, expected) = fixture(text);
+            let tool = ServerToolInfo::from_spec_value("filesystem", spec).unwrap();
+            db.save_server_tool(&tool).unwrap();
+            let saved = db.get_server_tool(&tool.id, "filesystem").unwrap();
+            assert_eq!(serde_json::to_value(&saved).unwrap(), expected);
+        }
+        assert_eq!(
+            db.get_server_tools("filesystem").unwrap().len(),
+            FIXTURES.len()
+        );
+    }
+
+    #[test]
+    fn test_builtin_tools_keep_their_schema() {
+        let infos = get_builtin_tool_infos();
+        let tools = get_builtin_tools();
+        assert_eq!(infos.len(), tools.len());
+        for (info, tool) in infos.iter().zip(tools) {
+            assert_eq!(info.id, tool.name);
+            assert_eq!(info.server_id, BUILTIN_SERVER_ID);
+            assert_eq!(info.proxy_id, None);
+            assert_eq!(info.description, tool.description);
+            let schema = info.input_schema.as_ref().unwrap();
+            // Tools without arguments have neither list
+            let required = tool.input_schema["required"].as_array().map_or(0, Vec::len);
+            assert_eq!(schema.required.len(), required);
+            let properties = tool.input_schema["properties"]
+                .as_object()
+                .map_or(0, |p| p.len());
+            assert_eq!(schema.properties.len(), properties);
+            assert_eq!(tool.input_schema["type"], "object");
+        }
+    }
+
+    #[test]
+    fn test_builtin_tools_have_one_schema() {
+        for (info, tool) in get_builtin_tool_infos().iter().zip(get_builtin_tools()) {
+            assert_eq!(info.name, tool.name);
+            assert_eq!(
+                info.input_schema_value(),
+                tool.input_schema,
+                "{}",
+                tool.name
+            );
+        }
+
+        // Argument descriptions reach both lists
+        let tool = REGISTER_SERVER_TOOL.to_tool();
+        let info = REGISTER_SERVER_TOOL.to_tool_info();
+        assert_eq!(
+            tool.input_schema["properties"]["tool_id"]["description"],
+            "ID of the tool in the registry to install"
+        );
+        assert_eq!(
+            info.input_schema.unwrap().properties["tool_id"].description,
+            "ID of the tool in the registry to install"
+        );
+        assert_eq!(tool.input_schema["required"], json!(["tool_id"]));
+    }
+}
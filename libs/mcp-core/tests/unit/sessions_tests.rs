@@ -0,0 +1,112 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use async_trait::async_trait;
+    use mcp_core::sessions::{ClientIdentity, ClientSession, SessionRegistry, SessionTransport};
+    use serde_json::json;
+
+    /// Transport keeping what was sent, or failing every send
+    #[derive(Default)]
+    struct RecordingTransport {
+        sent: Mutex<Vec<String>>,
+        broken: bool,
+    }
+
+    #[async_trait]
+    impl SessionTransport for RecordingTransport {
+        fn kind(&self) -> &'static str {
+            "test"
+        }
+
+        async fn send(&self, message: &str) -> Result<(), String> {
+            if self.broken {
+                return Err("pipe closed".to_string());
+            }
+            self.sent.lock().unwrap().push(message.to_string());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_client_identity_from_initialize() {
+        let (client, capabilities) = ClientIdentity::from_initialize(&json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "initialize",
+            "params": {
+                "protocolVersion": "2024-11-05",
+                "capabilities": { "sampling": {} },
+                "clientInfo": { "name": "claude-ai", "version": "0.1.0" }
+            }
+        }))
+        .unwrap();
+        assert_eq!(client.display_name(), "claude-ai 0.1.0");
+        assert_eq!(capabilities, json!({ "sampling": {} }));
+
+        let (client, _) = ClientIdentity::from_initialize(&json!({
+            "method": "initialize",
+            "params": { "clientInfo": { "name": "cursor" } }
+        }))
+        .unwrap();
+        assert_eq!(client.display_name(), "cursor");
+
+        assert!(ClientIdentity::from_initialize(&json!({ "method": "tools/list" })).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_session_lifecycle() {
+        let sessions = SessionRegistry::new();
+        let transport = Arc::new(RecordingTransport::default());
+        sessions
+            .register(ClientSession::new("a".to_string(), transport.clone()))
+            .await;
+        assert_eq!(sessions.client_name("a").await, None);
+
+        let client = ClientIdentity {
+            name: "cursor".to_string(),
+            version: Some("1.0".to_string()),
+        };
+        sessions.set_client("a", client, json!({})).await;
+        assert_eq!(
+            sessions.client_name("a").await.as_deref(),
+            Some("cursor 1.0")
+        );
+        assert!(sessions.subscribe("a", "file:///notes.md").await);
+        let session = sessions.get("a").await.unwrap();
+        assert!(session.subscriptions.contains("file:///notes.md"));
+        assert_eq!(session.transport.kind(), "test");
+
+        sessions.send("a", "hello").await.unwrap();
+        assert_eq!(*transport.sent.lock().unwrap(), vec!["hello"]);
+
+        sessions.remove("a").await;
+        assert!(sessions.get("a").await.is_none());
+        assert!(!sessions.subscribe("a", "file:///notes.md").await);
+        assert_eq!(
+            sessions.send("a", "hello").await.unwrap_err(),
+            "Session a not found"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_reports_failed_sessions() {
+        let sessions = SessionRegistry::new();
+        let healthy = Arc::new(RecordingTransport::default());
+        let broken = Arc::new(RecordingTransport {
+            broken: true,
+            ..Default::default()
+        });
+        sessions
+            .register(ClientSession::new("healthy".to_string(), healthy.clone()))
+            .await;
+        sessions
+            .register(ClientSession::new("broken".to_string(), broken))
+            .await;
+
+        let failed = sessions.broadcast("changed").await;
+
+        assert_eq!(failed, vec!["broken"]);
+        assert_eq!(*healthy.sent.lock().unwrap(), vec!["changed"]);
+    }
+}
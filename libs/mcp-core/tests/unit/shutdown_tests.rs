@@ -0,0 +1,77 @@
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use mcp_core::{
+        core::{
+            mcp_core::MCPCore, mcp_core_builder::MCPCoreBuilder,
+            mcp_core_proxy_ext::McpCoreProxyExt,
+        },
+        database::db_manager::DBManager,
+        mcp_state::shutdown::{
+            ShutdownOutcome, DEFAULT_SHUTDOWN_GRACE_SECS, SHUTDOWN_GRACE_SETTING,
+        },
+    };
+    use serde_json::json;
+    use tempfile::{tempdir, TempDir};
+
+    fn core() -> (TempDir, MCPCore) {
+        let dir = tempdir().unwrap();
+        let db = DBManager::with_path(dir.path().join("mcp-dockmaster.db")).unwrap();
+        db.apply_migrations().unwrap();
+        (dir, MCPCoreBuilder::new(db).build())
+    }
+
+    #[test]
+    fn test_outcomes_serialize() {
+        assert_eq!(
+            serde_json::to_value(ShutdownOutcome::Graceful).unwrap(),
+            json!("graceful")
+        );
+        assert_eq!(
+            serde_json::to_value(ShutdownOutcome::Forced).unwrap(),
+            json!("forced")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_grace_period_is_configurable() {
+        let (_dir, core) = core();
+        assert_eq!(
+            core.shutdown_grace_secs().await,
+            DEFAULT_SHUTDOWN_GRACE_SECS
+        );
+
+        core.set_shutdown_grace_secs(12).await.unwrap();
+        assert_eq!(core.shutdown_grace_secs().await, 12);
+        let mcp_state = core.mcp_state.read().await;
+        assert_eq!(
+            mcp_state.shutdown_grace_period().await,
+            Duration::from_secs(12)
+        );
+
+        // Killing right away is allowed
+        core.set_shutdown_grace_secs(0).await.unwrap();
+        assert_eq!(mcp_state.shutdown_grace_period().await, Duration::ZERO);
+
+        // An unreadable setting falls back to the default
+        mcp_state
+            .tool_registry
+            .read()
+            .await
+            .save_setting(SHUTDOWN_GRACE_SETTING, "soon")
+            .unwrap();
+        assert_eq!(
+            core.shutdown_grace_secs().await,
+            DEFAULT_SHUTDOWN_GRACE_SECS
+        );
+    }
+
+    #[tokio::test]
+    async fn test_nothing_running_to_stop() {
+        let (_dir, core) = core();
+        assert!(core.kill_all_processes().await.unwrap().is_empty());
+        let mcp_state = core.mcp_state.read().await;
+        assert!(mcp_state.kill_process("unknown").await.is_err());
+    }
+}
@@ -0,0 +1,98 @@
+#[cfg(test)]
+#[cfg(not(windows))]
+mod tests {
+    use std::collections::HashMap;
+    use std::time::{Duration, Instant};
+
+    use mcp_core::{models::types::ServerId, MCPError, SpawnedProcess};
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    /// Reads two requests, then logs, notifies and answers the second one first
+    const OUT_OF_ORDER_SERVER: &str = r#"
+read first
+read second
+id() { echo "$1" | sed 's/.*"id":\([0-9]*\).*/\1/'; }
+method() { echo "$1" | sed 's/.*"method":"\([^"]*\)".*/\1/'; }
+echo "server starting"
+echo '{"jsonrpc":"2.0","method":"notifications/message","params":{"level":"info"}}'
+echo "{\"jsonrpc\":\"2.0\",\"id\":$(id "$second"),\"result\":{\"method\":\"$(method "$second")\"}}"
+echo "{\"jsonrpc\":\"2.0\",\"id\":$(id "$first"),\"result\":{\"method\":\"$(method "$first")\"}}"
+"#;
+
+    /// Answers initialize, then echoes what it reads until its stdin closes
+    const HANDSHAKE_SERVER: &str = r#"
+read request
+echo '{"jsonrpc":"2.0","id":1,"result":{"serverInfo":{"name":"echo","version":"1"}}}'
+while read line; do echo "{\"jsonrpc\":\"2.0\",\"method\":\"echo\",\"params\":$line}"; done
+"#;
+
+    /// Reads requests and never answers them
+    const SILENT_SERVER: &str = "while read line; do :; done";
+
+    async fn spawn(server_id: &str, args: Vec<String>) -> SpawnedProcess {
+        SpawnedProcess::spawn(
+            &ServerId::new(server_id.to_string()),
+            "sh".to_string(),
+            args,
+            HashMap::new(),
+            Duration::from_secs(5),
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_responses_are_matched_by_id() {
+        let dir = tempdir().unwrap();
+        let script = dir.path().join("server.sh");
+        std::fs::write(&script, OUT_OF_ORDER_SERVER).unwrap();
+        let process = spawn("out_of_order", vec![script.display().to_string()]).await;
+
+        let (first, second) = tokio::join!(
+            process.send_command("first", json!({})),
+            process.send_command("second", json!({}))
+        );
+        assert_eq!(first.unwrap(), json!({ "method": "first" }));
+        assert_eq!(second.unwrap(), json!({ "method": "second" }));
+
+        let notification = process.next_notification().await.unwrap();
+        assert_eq!(notification["method"], "notifications/message");
+        // The server exited, nothing else comes
+        assert_eq!(process.next_notification().await, None);
+        assert!(process.send_command("third", json!({})).await.is_err());
+
+        let _ = process.kill().await;
+    }
+
+    #[tokio::test]
+    async fn test_initialize_then_close() {
+        let process = spawn("echo", vec!["-c".to_string(), HANDSHAKE_SERVER.to_string()]).await;
+
+        let result = process.initialize().await.unwrap();
+        assert_eq!(result["serverInfo"]["name"], "echo");
+        let initialized = process.next_notification().await.unwrap();
+        assert_eq!(initialized["params"]["method"], "notifications/initialized");
+
+        // The server exits on EOF, and nothing can be sent anymore
+        process.close().await;
+        assert_eq!(process.next_notification().await, None);
+        let error = process.list_tools(None).await.unwrap_err();
+        assert!(error.to_string().contains("stdin closed"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn test_kill_fails_requests_in_flight() {
+        let process = spawn("silent", vec!["-c".to_string(), SILENT_SERVER.to_string()]).await;
+
+        // Fails right away instead of waiting for the response timeout
+        let started = Instant::now();
+        let (result, killed) = tokio::join!(process.list_tools(None), async {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            process.kill().await
+        });
+        killed.unwrap();
+        assert!(matches!(result, Err(MCPError::ServerClosedConnection)));
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+}
@@ -0,0 +1,129 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    use mcp_core::{
+        core::{
+            mcp_core::MCPCore, mcp_core_builder::MCPCoreBuilder,
+            mcp_core_proxy_ext::McpCoreProxyExt,
+        },
+        mcp_state::start_failures::{StartFailures, DEFAULT_MAX_FAILED_STARTS},
+        models::types::{
+            ServerConfigUpdateRequest, ServerConfiguration, ServerRegistrationRequest, ServerStatus,
+        },
+    };
+    use tempfile::{tempdir, TempDir};
+
+    use crate::common::{db, FailingLauncher, EXITED_AT_STARTUP};
+
+    async fn core_with_server(dir: &TempDir, launcher: Arc<FailingLauncher>) -> MCPCore {
+        let core = MCPCoreBuilder::new(db(dir))
+            .process_launcher(launcher)
+            .build();
+        let request = ServerRegistrationRequest {
+            server_id: "broken".to_string(),
+            server_name: "Broken".to_string(),
+            description: String::new(),
+            tools_type: "node".to_string(),
+            configuration: Some(ServerConfiguration {
+                command: Some("node".to_string()),
+                args: Some(vec!["missing.js".to_string()]),
+                ..Default::default()
+            }),
+            distribution: None,
+            provenance: None,
+            authentication: None,
+            enabled: None,
+        };
+        core.register_server(request).await.unwrap();
+        core
+    }
+
+    async fn status(core: &MCPCore) -> ServerStatus {
+        core.list_servers().await.unwrap()[0].status.clone()
+    }
+
+    #[test]
+    fn test_failed_starts_are_counted() {
+        let failures = StartFailures::new();
+        assert_eq!(failures.record_failure("broken", "first"), 1);
+        assert_eq!(failures.record_failure("broken", "second"), 2);
+        assert_eq!(failures.fault("broken", 3), None);
+        assert_eq!(failures.record_failure("broken", "third"), 3);
+
+        let failed = failures.fault("broken", 3).unwrap();
+        assert_eq!(failed.count, 3);
+        assert_eq!(failed.last_error, "third");
+        assert!(failed.describe("broken").ends_with("Last error: third"));
+        assert_eq!(failures.fault("other", 3), None);
+
+        failures.reset("broken");
+        assert_eq!(failures.fault("broken", 1), None);
+        assert_eq!(failures.record_failure("broken", "again"), 1);
+    }
+
+    #[tokio::test]
+    async fn test_server_is_faulted_after_failed_starts() {
+        let dir = tempdir().unwrap();
+        let launcher = Arc::new(FailingLauncher::with_error(EXITED_AT_STARTUP));
+        let core = core_with_server(&dir, launcher.clone()).await;
+        // Registering started it once
+        for _ in 1..DEFAULT_MAX_FAILED_STARTS {
+            let response = core
+                .restart_server_command("broken".to_string(), false)
+                .await
+                .unwrap();
+            assert!(!response.success);
+        }
+        let limit = DEFAULT_MAX_FAILED_STARTS as usize;
+        assert_eq!(launcher.plans().len(), limit);
+        assert!(matches!(
+            status(&core).await,
+            ServerStatus::Faulted(error) if error.contains("exit code 1")
+        ));
+
+        // Neither restarts nor starts launch it anymore
+        let response = core
+            .restart_server_command("broken".to_string(), false)
+            .await
+            .unwrap();
+        assert!(!response.success);
+        assert!(response.message.contains("restarted with force"));
+        let response = core.start_server("broken".to_string()).await.unwrap();
+        assert!(!response.success);
+        assert_eq!(launcher.plans().len(), limit);
+
+        // Forcing it tries once more
+        core.restart_server_command("broken".to_string(), true)
+            .await
+            .unwrap();
+        assert_eq!(launcher.plans().len(), limit + 1);
+        assert!(matches!(status(&core).await, ServerStatus::Error(_)));
+    }
+
+    #[tokio::test]
+    async fn test_config_change_clears_the_fault() {
+        let dir = tempdir().unwrap();
+        let launcher = Arc::new(FailingLauncher::with_error(EXITED_AT_STARTUP));
+        let core = core_with_server(&dir, launcher.clone()).await;
+        core.set_max_failed_starts(1).await.unwrap();
+        assert_eq!(core.max_failed_starts().await, 1);
+        assert!(matches!(status(&core).await, ServerStatus::Faulted(_)));
+        assert!(core.set_max_failed_starts(0).await.is_err());
+
+        core.update_server_config(ServerConfigUpdateRequest {
+            server_id: "broken".to_string(),
+            config: HashMap::new(),
+            inherit_env: None,
+            cwd: None,
+        })
+        .await
+        .unwrap();
+        assert!(matches!(status(&core).await, ServerStatus::Error(_)));
+        core.restart_server_command("broken".to_string(), false)
+            .await
+            .unwrap();
+        assert_eq!(launcher.plans().len(), 2);
+    }
+}
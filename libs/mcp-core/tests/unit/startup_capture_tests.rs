@@ -0,0 +1,129 @@
+#[cfg(test)]
+mod tests {
+    use mcp_core::mcp_state::startup_capture::{StartupCapture, StartupFailure};
+    use tempfile::tempdir;
+
+    /// Run `command` the way the launcher does, with `sh -c`
+    #[cfg(unix)]
+    fn run_wrapped(capture: &StartupCapture, command: &str) {
+        let status = std::process::Command::new("/bin/sh")
+            .arg("-c")
+            .arg(capture.wrap_command("/bin/sh", command))
+            .status()
+            .unwrap();
+        // The shell reports the exit code of the server instead of failing itself
+        assert!(status.success());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_captures_exit_code_and_stderr() {
+        let dir = tempdir().unwrap();
+        let capture = StartupCapture::create_in(dir.path(), "spawn").unwrap();
+        assert_eq!(capture.exit_code(), None);
+
+        run_wrapped(
+            &capture,
+            "sh -c 'echo starting; echo \"Traceback: boom\" >&2; exit 3'",
+        );
+
+        assert_eq!(capture.wait_for_exit().await, Some(3));
+        assert_eq!(
+            capture.failure(Some(3)),
+            StartupFailure {
+                exit_code: Some(3),
+                stderr_tail: "Traceback: boom".to_string(),
+            }
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_captures_command_not_found() {
+        let dir = tempdir().unwrap();
+        let capture = StartupCapture::create_in(dir.path(), "spawn").unwrap();
+
+        run_wrapped(&capture, "some-package-that-does-not-exist --stdio");
+
+        assert_eq!(capture.exit_code(), Some(Some(127)));
+        assert!(capture
+            .stderr_tail()
+            .contains("some-package-that-does-not-exist"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_stderr_tail_is_capped() {
+        let dir = tempdir().unwrap();
+        let capture = StartupCapture::create_in(dir.path(), "spawn").unwrap();
+
+        run_wrapped(
+            &capture,
+            "i=0; while [ $i -lt 2000 ]; do echo \"line $i\" >&2; i=$((i+1)); done",
+        );
+
+        let tail = capture.stderr_tail();
+        assert!(tail.len() <= 4096);
+        assert!(tail.starts_with("line "));
+        assert!(tail.ends_with("line 1999"));
+    }
+
+    #[test]
+    fn test_wrap_command_follows_the_shell() {
+        let dir = tempdir().unwrap();
+        let capture = StartupCapture::create_in(dir.path(), "spawn").unwrap();
+
+        let exit_code_path = capture.exit_code_path();
+        let posix = capture.wrap_command("/bin/zsh", "npx server");
+        assert!(posix.starts_with("{ npx server\n} 2>> "));
+        assert!(posix.ends_with(&format!("; echo $? > '{}'", exit_code_path.display())));
+        assert!(capture
+            .wrap_command("/usr/bin/fish", "npx server")
+            .starts_with("begin; npx server\nend 2>> "));
+        assert!(capture
+            .wrap_command("powershell", "npx server")
+            .starts_with("& { npx server } 2>> "));
+    }
+
+    #[test]
+    fn test_failure_message() {
+        let failure = StartupFailure {
+            exit_code: Some(1),
+            stderr_tail: "sh: 1: uvx: not found".to_string(),
+        };
+        assert_eq!(
+            failure.to_string(),
+            "Server exited during startup with exit code 1, stderr:\nsh: 1: uvx: not found"
+        );
+        let failure = StartupFailure {
+            exit_code: None,
+            stderr_tail: String::new(),
+        };
+        assert_eq!(
+            failure.to_string(),
+            "Server exited during startup without writing to stderr"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_exit_of_a_running_server() {
+        let spawn_id = uuid::Uuid::new_v4().to_string();
+        let capture = StartupCapture::create(&spawn_id).unwrap();
+        assert_eq!(StartupCapture::open(&spawn_id).exit_code(), None);
+
+        run_wrapped(&capture, "sh -c 'echo \"out of memory\" >&2; exit 137'");
+
+        let reopened = StartupCapture::open(&spawn_id);
+        assert_eq!(reopened.exit_code(), Some(Some(137)));
+        assert_eq!(
+            reopened.exit_message(Some(137)),
+            "Server exited with exit code 137, stderr:\nout of memory"
+        );
+        StartupCapture::remove(&spawn_id);
+        assert_eq!(
+            StartupCapture::open(&spawn_id).exit_message(None),
+            "Server exited"
+        );
+    }
+}
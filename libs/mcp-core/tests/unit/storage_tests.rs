@@ -0,0 +1,111 @@
+#[cfg(test)]
+mod tests {
+    use mcp_core::{
+        database::db_manager::DBManager,
+        registry::{
+            server_registry::ServerRegistry,
+            storage::{
+                is_storage_error, PendingWrite, StorageHealth, StorageUnavailable,
+                MAX_PENDING_WRITES,
+            },
+        },
+    };
+    use tempfile::tempdir;
+
+    fn setting(key: &str) -> PendingWrite {
+        PendingWrite::SaveSetting {
+            key: key.to_string(),
+            value: "value".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_storage_errors() {
+        assert!(is_storage_error(
+            "Failed to get database connection: timed out waiting for connection"
+        ));
+        assert!(is_storage_error("database is locked"));
+        assert!(is_storage_error("attempt to write a readonly database"));
+        assert!(!is_storage_error("Record not found"));
+        assert!(!is_storage_error("UNIQUE constraint failed: servers.id"));
+
+        let error = format!("Failed to save server: {}", StorageUnavailable("x".into()));
+        assert!(StorageUnavailable::is(&error));
+        assert!(!StorageUnavailable::is("Failed to save server"));
+    }
+
+    #[test]
+    fn test_pending_writes_are_capped() {
+        let storage = StorageHealth::new();
+        for i in 0..MAX_PENDING_WRITES {
+            storage.enqueue(setting(&i.to_string())).unwrap();
+        }
+        assert!(storage.enqueue(setting("one too many")).is_err());
+
+        let status = storage.status();
+        assert_eq!(status.pending_writes, MAX_PENDING_WRITES);
+        assert_eq!(status.dropped_writes, 1);
+    }
+
+    #[test]
+    fn test_flush() {
+        let storage = StorageHealth::new();
+        storage.mark_unavailable("database is locked");
+        for key in ["a", "b", "c", "d"] {
+            storage.enqueue(setting(key)).unwrap();
+        }
+
+        // Stops at the first write the database still can't take
+        let mut seen = Vec::new();
+        let applied = storage.flush(|write| {
+            let PendingWrite::SaveSetting { key, .. } = write else {
+                unreachable!()
+            };
+            seen.push(key.clone());
+            match key.as_str() {
+                "b" => Err("Record not found".to_string()),
+                "c" => Err("database is locked".to_string()),
+                _ => Ok(()),
+            }
+        });
+        assert_eq!(applied, 1);
+        assert_eq!(seen, vec!["a", "b", "c"]);
+        // "b" failed for another reason and was dropped
+        assert_eq!(storage.status().pending_writes, 2);
+
+        // Still degraded while writes are pending
+        storage.mark_available();
+        assert!(storage.is_degraded());
+
+        assert_eq!(storage.flush(|_| Ok(())), 2);
+        storage.mark_available();
+        let status = storage.status();
+        assert!(!status.degraded);
+        assert_eq!(status.since, None);
+        assert_eq!(status.last_error, None);
+    }
+
+    #[test]
+    fn test_registry_queues_writes_while_degraded() {
+        let temp_dir = tempdir().expect("Failed to create temp directory");
+        let db = DBManager::with_path(temp_dir.path().join("mcp-dockmaster.db"))
+            .expect("Failed to create database");
+        db.apply_migrations().expect("Failed to apply migrations");
+        let registry = ServerRegistry::with_db_manager(db.clone());
+
+        registry.save_setting("theme", "dark").unwrap();
+        assert_eq!(registry.get_setting("theme").unwrap(), "dark");
+        assert_eq!(registry.retry_storage(), 0);
+
+        registry.storage().mark_unavailable("database is locked");
+        registry.save_setting("theme", "light").unwrap();
+        // Served from memory, the database doesn't have it yet
+        assert_eq!(registry.get_setting("theme").unwrap(), "light");
+        assert_eq!(db.get_setting("theme").unwrap(), "dark");
+        assert_eq!(registry.storage().status().pending_writes, 1);
+
+        assert_eq!(registry.retry_storage(), 1);
+        assert!(!registry.storage().is_degraded());
+        assert_eq!(db.get_setting("theme").unwrap(), "light");
+    }
+}
@@ -0,0 +1,173 @@
+#[cfg(test)]
+mod tests {
+    use mcp_core::{
+        core::{
+            mcp_core::MCPCore, mcp_core_approvals_ext::McpCoreApprovalsExt,
+            mcp_core_builder::MCPCoreBuilder, mcp_core_proxy_ext::McpCoreProxyExt,
+            mcp_core_tools_ext::McpCoreToolsExt,
+        },
+        database::db_manager::DBManager,
+        mcp_server::{mcp_router::MCPDockmasterRouter, mcp_tools_service::MCPToolsService},
+        models::types::{
+            ActionPolicy, AuditAction, AuditDecision, GatedAction, ServerDefinition,
+            ServerToolInfo, ToolAnnotations,
+        },
+    };
+    use mcp_sdk_server::Router;
+    use serde_json::json;
+    use tempfile::{tempdir, TempDir};
+
+    fn destructive() -> ToolAnnotations {
+        ToolAnnotations {
+            destructive_hint: Some(true),
+            ..Default::default()
+        }
+    }
+
+    /// Core with the server `files` providing `delete`, marked destructive by its server
+    async fn core_with_tool() -> (MCPCore, TempDir) {
+        let dir = tempdir().unwrap();
+        let db = DBManager::with_path(dir.path().join("mcp-dockmaster.db")).unwrap();
+        db.apply_migrations().unwrap();
+        let server: ServerDefinition = serde_json::from_value(json!({
+            "name": "Files",
+            "description": "",
+            "enabled": true,
+            "tools_type": "node",
+            "configuration": { "command": "node" }
+        }))
+        .unwrap();
+        db.save_server("files", &server).unwrap();
+        let core = MCPCoreBuilder::new(db).build();
+
+        let tool = ServerToolInfo::from_spec_value(
+            "files",
+            json!({ "name": "delete", "annotations": { "destructiveHint": true } }),
+        )
+        .unwrap();
+        core.mcp_state
+            .read()
+            .await
+            .server_tools
+            .write()
+            .await
+            .insert("files".to_string(), vec![tool]);
+        (core, dir)
+    }
+
+    #[test]
+    fn test_annotation_hints() {
+        let annotations: ToolAnnotations = serde_json::from_value(json!({
+            "title": "Delete file",
+            "destructiveHint": true,
+            "costHint": "high"
+        }))
+        .unwrap();
+        assert!(annotations.is_destructive());
+        assert_eq!(annotations.other["costHint"], json!("high"));
+
+        // Read-only tools are never destructive, whatever their other hints say
+        let overrides = ToolAnnotations {
+            read_only_hint: Some(true),
+            ..Default::default()
+        };
+        let merged = annotations.merged(&overrides);
+        assert!(!merged.is_destructive());
+        assert_eq!(merged.title.as_deref(), Some("Delete file"));
+        assert_eq!(
+            serde_json::to_value(&merged).unwrap()["costHint"],
+            json!("high")
+        );
+
+        assert!(ToolAnnotations::default().is_empty());
+        assert!(!ToolAnnotations::default().is_destructive());
+    }
+
+    #[test]
+    fn test_destructive_tool_policy() {
+        let action: GatedAction = serde_json::from_value(json!("destructive_tool")).unwrap();
+        assert_eq!(action, GatedAction::DestructiveTool);
+        assert_eq!(
+            GatedAction::from_stored("destructive_tool"),
+            Some(GatedAction::DestructiveTool)
+        );
+        assert_eq!(action.default_policy(), ActionPolicy::Allow);
+        assert!(GatedAction::ALL.contains(&action));
+    }
+
+    #[tokio::test]
+    async fn test_annotation_overrides() {
+        let (core, _dir) = core_with_tool().await;
+        assert!(core
+            .set_tool_annotations("unknown", "delete", destructive())
+            .await
+            .is_err());
+
+        let tools = core.list_all_server_tools().await.unwrap();
+        assert!(tools[0].annotations.as_ref().unwrap().is_destructive());
+
+        // The user knows better than the server
+        let harmless = ToolAnnotations {
+            destructive_hint: Some(false),
+            ..Default::default()
+        };
+        core.set_tool_annotations("files", "delete", harmless)
+            .await
+            .unwrap();
+        assert_eq!(core.tool_annotation_overrides().await.len(), 1);
+        let tools = core.list_all_server_tools().await.unwrap();
+        assert!(!tools[0].annotations.as_ref().unwrap().is_destructive());
+        let description = core.describe_tool("files", "delete").await.unwrap();
+        assert_eq!(
+            description.annotations.unwrap().destructive_hint,
+            Some(false)
+        );
+
+        // Empty annotations remove the override
+        core.set_tool_annotations("files", "delete", ToolAnnotations::default())
+            .await
+            .unwrap();
+        assert!(core.tool_annotation_overrides().await.is_empty());
+        let description = core.describe_tool("files", "delete").await.unwrap();
+        assert!(description.annotations.unwrap().is_destructive());
+    }
+
+    #[tokio::test]
+    async fn test_tools_list_carries_annotations() {
+        let (core, _dir) = core_with_tool().await;
+        let service = MCPToolsService::new(core);
+        service.update_cache().await.unwrap();
+
+        let mut message = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": { "tools": [{ "name": "delete" }, { "name": "register_server" }] }
+        });
+        assert!(service.annotate_tools_list(&mut message));
+        let tools = &message["result"]["tools"];
+        assert_eq!(tools[0]["annotations"], json!({ "destructiveHint": true }));
+        assert!(tools[1].get("annotations").is_none());
+
+        // Other messages are left alone
+        let mut other = json!({ "jsonrpc": "2.0", "id": 2, "result": {} });
+        assert!(!service.annotate_tools_list(&mut other));
+    }
+
+    #[tokio::test]
+    async fn test_destructive_calls_follow_the_policy() {
+        let (core, _dir) = core_with_tool().await;
+        core.set_action_policy(GatedAction::DestructiveTool, ActionPolicy::Deny)
+            .await
+            .unwrap();
+        let router = MCPDockmasterRouter::new(core.clone()).await;
+
+        let content = router.call_tool("delete", json!({})).await.unwrap();
+        let text = serde_json::to_value(&content).unwrap().to_string();
+        assert!(text.contains("denied"), "{}", text);
+
+        let log = core.audit_log(10).await.unwrap();
+        assert_eq!(log[0].action, AuditAction::ToolCall);
+        assert_eq!(log[0].decision, AuditDecision::Denied);
+        assert_eq!(log[0].server_id.as_deref(), Some("files"));
+    }
+}
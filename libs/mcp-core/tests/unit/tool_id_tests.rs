@@ -0,0 +1,138 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use mcp_core::{
+        mcp_server::tools::get_builtin_tool_infos,
+        models::{
+            tool_id::{find_tool, parse_proxy_tool_id, proxy_tool_id},
+            types::ServerToolInfo,
+        },
+    };
+    use serde_json::json;
+
+    fn tool(server_id: &str, name: &str) -> ServerToolInfo {
+        ServerToolInfo::from_spec_value(server_id, json!({ "name": name })).unwrap()
+    }
+
+    /// A tool as older versions saved it, with the id the server gave or none
+    fn saved_tool(server_id: &str, id: &str, name: &str) -> ServerToolInfo {
+        let mut tool: ServerToolInfo = serde_json::from_value(json!({
+            "id": id,
+            "name": name,
+            "description": "",
+            "server_id": server_id,
+            "proxy_id": server_id,
+        }))
+        .unwrap();
+        tool.normalize_ids();
+        tool
+    }
+
+    fn servers(tools: Vec<ServerToolInfo>) -> HashMap<String, Vec<ServerToolInfo>> {
+        let mut servers: HashMap<String, Vec<ServerToolInfo>> = HashMap::new();
+        for tool in tools {
+            servers
+                .entry(tool.server_id.clone())
+                .or_default()
+                .push(tool);
+        }
+        servers
+    }
+
+    fn found(servers: &HashMap<String, Vec<ServerToolInfo>>, key: &str) -> Option<String> {
+        find_tool(servers, key).map(|tool| proxy_tool_id(&tool.server_id, &tool.name))
+    }
+
+    #[test]
+    fn test_proxy_ids() {
+        assert_eq!(proxy_tool_id("files", "read"), "files:read");
+        assert_eq!(parse_proxy_tool_id("files:read"), Some(("files", "read")));
+        // Tool names may have a colon, server ids can't
+        assert_eq!(
+            parse_proxy_tool_id("files:read:all"),
+            Some(("files", "read:all"))
+        );
+        assert_eq!(parse_proxy_tool_id("files:"), None);
+        assert_eq!(parse_proxy_tool_id(":read"), None);
+        assert_eq!(parse_proxy_tool_id("read"), None);
+    }
+
+    #[test]
+    fn test_discovered_tools_are_keyed_by_name() {
+        let tool = tool("files", "read_file");
+        assert_eq!(tool.id, "read_file");
+        assert_eq!(tool.proxy_id.as_deref(), Some("files:read_file"));
+
+        for tool in get_builtin_tool_infos() {
+            assert_eq!(tool.proxy_id, None);
+        }
+    }
+
+    #[test]
+    fn test_saved_tools_are_normalized() {
+        // The server gave an id of its own, which is kept
+        let tool = saved_tool("github", "gh-42", "create_issue");
+        assert_eq!(tool.id, "gh-42");
+        assert_eq!(tool.proxy_id.as_deref(), Some("github:create_issue"));
+
+        // No id at all, never an empty proxy id like `github:`
+        let tool = saved_tool("github", "", "search");
+        assert_eq!(tool.id, "search");
+        assert_eq!(tool.proxy_id.as_deref(), Some("github:search"));
+    }
+
+    #[test]
+    fn test_routing_by_name_and_proxy_id() {
+        let servers = servers(vec![
+            tool("files", "read"),
+            saved_tool("github", "gh-42", "create_issue"),
+            saved_tool("weather", "", "forecast"),
+        ]);
+
+        assert_eq!(found(&servers, "read").as_deref(), Some("files:read"));
+        assert_eq!(
+            found(&servers, "forecast").as_deref(),
+            Some("weather:forecast")
+        );
+        assert_eq!(
+            found(&servers, "github:create_issue").as_deref(),
+            Some("github:create_issue")
+        );
+        // Ids still route, for clients that sent them
+        assert_eq!(
+            found(&servers, "gh-42").as_deref(),
+            Some("github:create_issue")
+        );
+        assert_eq!(found(&servers, ""), None);
+        assert_eq!(found(&servers, "files:"), None);
+        assert_eq!(found(&servers, "write"), None);
+    }
+
+    #[test]
+    fn test_name_wins_over_the_id_of_another_server() {
+        // `search` is the id of a tool of alpha and the name of a tool of zeta
+        let servers = servers(vec![
+            saved_tool("alpha", "search", "alpha_search"),
+            tool("zeta", "search"),
+        ]);
+        for _ in 0..10 {
+            assert_eq!(found(&servers, "search").as_deref(), Some("zeta:search"));
+        }
+        assert_eq!(
+            found(&servers, "alpha_search").as_deref(),
+            Some("alpha:alpha_search")
+        );
+    }
+
+    #[test]
+    fn test_same_name_on_several_servers() {
+        let servers = servers(vec![tool("zeta", "search"), tool("alpha", "search")]);
+        // Always the same server for a bare name, the proxy id picks one
+        assert_eq!(found(&servers, "search").as_deref(), Some("alpha:search"));
+        assert_eq!(
+            found(&servers, "zeta:search").as_deref(),
+            Some("zeta:search")
+        );
+    }
+}
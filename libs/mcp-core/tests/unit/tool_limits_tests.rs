@@ -0,0 +1,158 @@
+#[cfg(test)]
+mod tests {
+    use mcp_core::{
+        database::db_manager::DBManager,
+        models::{
+            types::{ServerConfiguration, ServerDefinition, ServerToolInfo},
+            unknown_fields::KnownFields,
+        },
+        tool_limits::{
+            apply_tool_limits, glob_match, ToolFilter, DEFAULT_MAX_TOOLS, TOOLS_TRUNCATED_WARNING,
+        },
+    };
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    fn tool(name: &str) -> ServerToolInfo {
+        serde_json::from_value(json!({
+            "id": name,
+            "name": name,
+            "description": format!("Calls {}", name),
+            "inputSchema": { "type": "object", "properties": {} },
+            "serverId": "chatty",
+            "proxyId": format!("chatty:{}", name),
+        }))
+        .unwrap()
+    }
+
+    fn names(tools: &[ServerToolInfo]) -> Vec<&str> {
+        tools.iter().map(|tool| tool.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("get_*", "get_user"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("*_user", "delete_user"));
+        assert!(glob_match("get_?", "get_a"));
+        assert!(glob_match("a*b*c", "aXbYbZc"));
+        assert!(!glob_match("get_?", "get_ab"));
+        assert!(!glob_match("get_*", "list_users"));
+        assert!(!glob_match("user", "users"));
+    }
+
+    #[test]
+    fn test_filter_patterns() {
+        let filter = ToolFilter {
+            include: vec!["repos_*".to_string(), "issues_*".to_string()],
+            exclude: vec!["*_delete".to_string()],
+            max_tools: None,
+        };
+        assert!(filter.allows("repos_get"));
+        assert!(filter.allows("issues_create"));
+        assert!(!filter.allows("repos_delete"));
+        assert!(!filter.allows("gists_get"));
+        assert!(ToolFilter::default().allows("anything"));
+        assert_eq!(filter.max_tools(), DEFAULT_MAX_TOOLS);
+
+        assert!(filter.validate().is_ok());
+        let no_tools = ToolFilter {
+            max_tools: Some(0),
+            ..Default::default()
+        };
+        assert!(no_tools.validate().is_err());
+        let empty_pattern = ToolFilter {
+            exclude: vec![" ".to_string()],
+            ..Default::default()
+        };
+        assert!(empty_pattern.validate().is_err());
+    }
+
+    #[test]
+    fn test_truncation_keeps_the_same_tools_whatever_the_order() {
+        let filter = ToolFilter {
+            max_tools: Some(2),
+            ..Default::default()
+        };
+        let listed = vec![tool("c"), tool("a"), tool("d"), tool("b")];
+        let (kept, report) = apply_tool_limits(Some(&filter), listed);
+        assert_eq!(names(&kept), vec!["a", "b"]);
+        let (kept, _) = apply_tool_limits(
+            Some(&filter),
+            vec![tool("d"), tool("b"), tool("a"), tool("c")],
+        );
+        assert_eq!(names(&kept), vec!["a", "b"]);
+
+        assert_eq!(report.listed, 4);
+        assert_eq!(report.filtered, 0);
+        assert_eq!(report.truncated, 2);
+        let warnings = report.warnings();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0]["code"], TOOLS_TRUNCATED_WARNING);
+
+        // Left out tools can still be called, but don't keep their schemas in memory
+        let left_out = report.left_out_tool("d").unwrap();
+        assert_eq!(left_out.proxy_id.as_deref(), Some("chatty:d"));
+        assert!(left_out.input_schema.is_none());
+        assert!(left_out.description.is_empty());
+        assert!(report.left_out_tool("a").is_none());
+    }
+
+    #[test]
+    fn test_default_limit() {
+        let listed: Vec<_> = (0..DEFAULT_MAX_TOOLS + 10)
+            .map(|i| tool(&format!("endpoint_{:04}", i)))
+            .collect();
+        let (kept, report) = apply_tool_limits(None, listed);
+        assert_eq!(kept.len(), DEFAULT_MAX_TOOLS);
+        assert_eq!(report.truncated, 10);
+
+        let (kept, report) = apply_tool_limits(None, vec![tool("a"), tool("b")]);
+        assert_eq!(kept.len(), 2);
+        assert!(report.warnings().is_empty());
+        assert!(report.left_out.is_empty());
+    }
+
+    #[test]
+    fn test_filtered_tools_are_not_truncated() {
+        let filter = ToolFilter {
+            exclude: vec!["admin_*".to_string()],
+            max_tools: Some(2),
+            ..Default::default()
+        };
+        let listed = vec![tool("admin_reset"), tool("b"), tool("a")];
+        let (kept, report) = apply_tool_limits(Some(&filter), listed);
+        // Not sorted when nothing is truncated
+        assert_eq!(names(&kept), vec!["b", "a"]);
+        assert_eq!(report.filtered, 1);
+        assert_eq!(report.truncated, 0);
+        assert!(report.warnings().is_empty());
+        assert!(report.left_out_tool("admin_reset").is_some());
+    }
+
+    #[test]
+    fn test_tool_filter_is_saved_with_the_configuration() {
+        let dir = tempdir().unwrap();
+        let db = DBManager::with_path(dir.path().join("mcp-dockmaster.db")).unwrap();
+        db.apply_migrations().unwrap();
+
+        let value = json!({
+            "name": "Chatty",
+            "description": "",
+            "enabled": true,
+            "tools_type": "node",
+            "configuration": {
+                "command": "npx",
+                "tool_filter": { "include": ["repos_*"], "max_tools": 50 }
+            }
+        });
+        assert!(ServerConfiguration::unknown_fields(&value["configuration"]).is_empty());
+        let server: ServerDefinition = serde_json::from_value(value).unwrap();
+        db.save_server("chatty", &server).unwrap();
+
+        let saved = db.get_server("chatty").unwrap();
+        let filter = saved.configuration.unwrap().tool_filter.unwrap();
+        assert_eq!(filter.include, vec!["repos_*".to_string()]);
+        assert_eq!(filter.max_tools, Some(50));
+    }
+}
@@ -0,0 +1,128 @@
+#[cfg(test)]
+#[cfg(not(windows))]
+mod tests {
+    use std::collections::HashSet;
+
+    use mcp_core::{
+        core::{mcp_core_builder::MCPCoreBuilder, mcp_core_proxy_ext::McpCoreProxyExt},
+        database::db_manager::DBManager,
+        models::types::{ServerConfiguration, ServerRegistrationRequest, ToolExecutionRequest},
+    };
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    /// MCP server listing one tool on each of three pages, writing the id of each
+    /// request to the file in its first argument if given
+    const PAGED_SERVER: &str = r#"
+tool() { echo "{\"name\":\"$1\",\"description\":\"Tool $1\",\"inputSchema\":{\"type\":\"object\"}}"; }
+while read -r line; do
+  id=$(echo "$line" | sed -n 's/.*"id":\([0-9]*\).*/\1/p')
+  [ -z "$id" ] && continue
+  [ -n "$1" ] && echo "$id" >> "$1"
+  case "$line" in
+    *'"tools/call"'*) result="{\"content\":[{\"type\":\"text\",\"text\":\"$id\"}]}" ;;
+    *'"initialize"'*)
+      result='{"protocolVersion":"2024-11-05","capabilities":{"tools":{}},"serverInfo":{"name":"paged","version":"1.0.0"}}' ;;
+    *'"cursor":"page-2"'*) result="{\"tools\":[$(tool second)],\"nextCursor\":\"page-3\"}" ;;
+    *'"cursor":"page-3"'*) result="{\"tools\":[$(tool third)]}" ;;
+    *'"tools/list"'*) result="{\"tools\":[$(tool first)],\"nextCursor\":\"page-2\"}" ;;
+    *) result='{}' ;;
+  esac
+  echo "{\"jsonrpc\":\"2.0\",\"id\":$id,\"result\":$result}"
+done
+"#;
+
+    #[tokio::test]
+    async fn test_tools_of_every_page_are_discovered() {
+        let dir = tempdir().unwrap();
+        let script = dir.path().join("server.sh");
+        std::fs::write(&script, PAGED_SERVER).unwrap();
+        let db = DBManager::with_path(dir.path().join("mcp-dockmaster.db")).unwrap();
+        db.apply_migrations().unwrap();
+        let core = MCPCoreBuilder::new(db).build();
+
+        let response = core
+            .register_server(ServerRegistrationRequest {
+                server_id: "paged".to_string(),
+                server_name: "Paged".to_string(),
+                description: String::new(),
+                tools_type: "node".to_string(),
+                configuration: Some(ServerConfiguration {
+                    command: Some("sh".to_string()),
+                    args: Some(vec![script.display().to_string()]),
+                    ..Default::default()
+                }),
+                distribution: None,
+                provenance: None,
+                authentication: None,
+                enabled: None,
+            })
+            .await
+            .unwrap();
+        assert!(response.success, "{}", response.message);
+        assert_eq!(response.tool_count, Some(3));
+
+        let server_tools = core.mcp_state.read().await.server_tools.clone();
+        let names: Vec<String> = server_tools.read().await["paged"]
+            .iter()
+            .map(|tool| tool.name.clone())
+            .collect();
+        assert_eq!(names, ["first", "second", "third"]);
+
+        core.kill_all_processes().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_requests_of_a_connection_get_distinct_ids() {
+        let dir = tempdir().unwrap();
+        let script = dir.path().join("server.sh");
+        std::fs::write(&script, PAGED_SERVER).unwrap();
+        let ids = dir.path().join("ids");
+        let db = DBManager::with_path(dir.path().join("mcp-dockmaster.db")).unwrap();
+        db.apply_migrations().unwrap();
+        let core = MCPCoreBuilder::new(db).build();
+
+        let response = core
+            .register_server(ServerRegistrationRequest {
+                server_id: "paged".to_string(),
+                server_name: "Paged".to_string(),
+                description: String::new(),
+                tools_type: "node".to_string(),
+                configuration: Some(ServerConfiguration {
+                    command: Some("sh".to_string()),
+                    args: Some(vec![
+                        script.display().to_string(),
+                        ids.display().to_string(),
+                    ]),
+                    ..Default::default()
+                }),
+                distribution: None,
+                provenance: None,
+                authentication: None,
+                enabled: None,
+            })
+            .await
+            .unwrap();
+        assert!(response.success, "{}", response.message);
+
+        // Discovery and concurrent calls of the same tool share one counter
+        let call = || {
+            core.execute_proxy_tool(ToolExecutionRequest {
+                tool_id: "paged:first".to_string(),
+                parameters: json!({}),
+            })
+        };
+        let (first, second) = tokio::join!(call(), call());
+        assert!(first.unwrap().success);
+        assert!(second.unwrap().success);
+
+        let ids = std::fs::read_to_string(&ids).unwrap();
+        let ids: Vec<&str> = ids.lines().collect();
+        // initialize, three pages and two calls
+        assert_eq!(ids.len(), 6, "{:?}", ids);
+        let distinct: HashSet<&str> = ids.iter().copied().collect();
+        assert_eq!(distinct.len(), ids.len(), "{:?}", ids);
+
+        core.kill_all_processes().await.unwrap();
+    }
+}
@@ -0,0 +1,89 @@
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use mcp_core::{
+        core::{
+            mcp_core_builder::MCPCoreBuilder, mcp_core_tools_snapshot_ext::McpCoreToolsSnapshotExt,
+        },
+        database::db_manager::DBManager,
+        models::types::ServerToolInfo,
+        tools_snapshot::{load_snapshot, save_snapshot, snapshot_path, TOOLS_SNAPSHOT_VERSION},
+    };
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    fn tool(server_id: &str, name: &str) -> ServerToolInfo {
+        ServerToolInfo::from_spec_value(server_id, json!({ "name": name })).unwrap()
+    }
+
+    fn names(tools: &[ServerToolInfo]) -> Vec<&str> {
+        tools.iter().map(|tool| tool.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_snapshot_file() {
+        let dir = tempdir().unwrap();
+        let path = snapshot_path(&dir.path().join("mcp-dockmaster.db"));
+        assert!(load_snapshot(&path).is_none());
+
+        let tools = vec![tool("files", "read")];
+        assert!(save_snapshot(&path, &tools, 1).unwrap());
+        assert!(!save_snapshot(&path, &tools, 2).unwrap());
+        assert_eq!(names(&load_snapshot(&path).unwrap()), vec!["read"]);
+
+        fs::write(&path, "{\"version\":").unwrap();
+        assert!(load_snapshot(&path).is_none());
+        let other_version = json!({
+            "version": TOOLS_SNAPSHOT_VERSION + 1,
+            "saved_at": 0,
+            "tools": tools,
+        });
+        fs::write(&path, other_version.to_string()).unwrap();
+        assert!(load_snapshot(&path).is_none());
+        // Files that can't be used are replaced
+        assert!(save_snapshot(&path, &tools, 3).unwrap());
+        assert!(load_snapshot(&path).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_stale_tools_served_until_replaced() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("mcp-dockmaster.db");
+        let path = snapshot_path(&db_path);
+        save_snapshot(&path, &[tool("files", "read")], 1).unwrap();
+        let db = DBManager::with_path(db_path).unwrap();
+        db.apply_migrations().unwrap();
+        let core = MCPCoreBuilder::new(db).build();
+
+        assert_eq!(core.load_tools_snapshot().await, 1);
+        let (tools, stale) = core.served_server_tools().await.unwrap();
+        assert!(stale);
+        assert_eq!(names(&tools), vec!["read"]);
+
+        // The servers starting don't overwrite the snapshot
+        core.mcp_state
+            .read()
+            .await
+            .server_tools
+            .write()
+            .await
+            .insert("notes".to_string(), vec![tool("notes", "add")]);
+        core.save_tools_snapshot().await.unwrap();
+        assert_eq!(names(&load_snapshot(&path).unwrap()), vec!["read"]);
+
+        core.replace_stale_tools().await;
+        let (tools, stale) = core.served_server_tools().await.unwrap();
+        assert!(!stale);
+        assert_eq!(names(&tools), vec!["add"]);
+        assert_eq!(names(&load_snapshot(&path).unwrap()), vec!["add"]);
+
+        core.set_tools_snapshot_enabled(false).await.unwrap();
+        assert!(!core.is_tools_snapshot_enabled().await);
+        assert!(!path.exists());
+        core.save_tools_snapshot().await.unwrap();
+        assert!(!path.exists());
+        save_snapshot(&path, &[tool("files", "read")], 1).unwrap();
+        assert_eq!(core.load_tools_snapshot().await, 0);
+    }
+}
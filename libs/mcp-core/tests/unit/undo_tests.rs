@@ -0,0 +1,236 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use mcp_core::{
+        core::{
+            mcp_core::MCPCore, mcp_core_builder::MCPCoreBuilder,
+            mcp_core_proxy_ext::McpCoreProxyExt, mcp_core_tools_ext::McpCoreToolsExt,
+            mcp_core_undo_ext::McpCoreUndoExt,
+        },
+        database::db_manager::DBManager,
+        models::types::{
+            EnvValue, ProvenanceSource, ServerConfigUpdateRequest, ServerDefinition,
+            ServerProvenance, ToolAnnotations, ToolUninstallRequest,
+        },
+        sealed::{is_sealed, SealingKey, SEALING_KEY_FILE},
+        undo::{UndoAction, UndoEntry, UNDO_JOURNAL_KEPT},
+    };
+    use serde_json::json;
+    use tempfile::{tempdir, TempDir};
+
+    /// A disabled server, so that nothing is started
+    fn weather() -> ServerDefinition {
+        serde_json::from_value(json!({
+            "name": "Weather",
+            "description": "Forecasts",
+            "enabled": false,
+            "tools_type": "node",
+            "configuration": {
+                "command": "npx",
+                "args": ["-y", "weather-mcp"],
+                "env": { "API_KEY": { "description": "Weather API key", "default": "sk-1234" } }
+            }
+        }))
+        .unwrap()
+    }
+
+    fn core() -> (MCPCore, TempDir) {
+        let dir = tempdir().unwrap();
+        let db = DBManager::with_path(dir.path().join("mcp-dockmaster.db")).unwrap();
+        db.apply_migrations().unwrap();
+        db.save_server("weather", &weather()).unwrap();
+        db.save_server_provenance(
+            "weather",
+            &ServerProvenance::new(ProvenanceSource::Registry, None),
+        )
+        .unwrap();
+        (MCPCoreBuilder::new(db).build(), dir)
+    }
+
+    fn api_key(server: &ServerDefinition) -> Option<EnvValue> {
+        server.configuration.as_ref().unwrap().env.as_ref().unwrap()["API_KEY"]
+            .default
+            .clone()
+    }
+
+    fn entry(server_id: &str, created_at: u64, expires_at: u64) -> UndoEntry {
+        UndoEntry {
+            id: 0,
+            action: UndoAction::Disable,
+            server_id: server_id.to_string(),
+            summary: format!("Disabled {}", server_id),
+            created_at,
+            expires_at,
+        }
+    }
+
+    #[test]
+    fn test_sealed_values() {
+        let dir = tempdir().unwrap();
+        let key = SealingKey::load_or_create(dir.path()).unwrap();
+        assert!(dir.path().join(SEALING_KEY_FILE).exists());
+
+        let sealed = key.seal("API_KEY=sk-1234");
+        assert!(is_sealed(&sealed));
+        assert!(!sealed.contains("sk-1234"));
+        assert_ne!(key.seal("API_KEY=sk-1234"), sealed);
+        assert_eq!(key.open(&sealed).unwrap(), "API_KEY=sk-1234");
+        // The key is kept for the next start
+        let reloaded = SealingKey::load_or_create(dir.path()).unwrap();
+        assert_eq!(reloaded.open(&sealed).unwrap(), "API_KEY=sk-1234");
+
+        let other = SealingKey::load_or_create(tempdir().unwrap().path()).unwrap();
+        assert!(other.open(&sealed).is_err());
+        let mut changed = sealed.clone();
+        let last = changed.pop().unwrap();
+        changed.push(if last == '0' { '1' } else { '0' });
+        assert!(key.open(&changed).is_err());
+        assert!(key.open("API_KEY=sk-1234").is_err());
+    }
+
+    #[test]
+    fn test_journal_is_bounded() {
+        let dir = tempdir().unwrap();
+        let db = DBManager::with_path(dir.path().join("mcp-dockmaster.db")).unwrap();
+        db.apply_migrations().unwrap();
+
+        let expired = db
+            .record_undo_entry(&entry("old", 100, 200), "image")
+            .unwrap();
+        assert_eq!(db.get_undo_entries(150).unwrap().len(), 1);
+        assert!(db.get_undo_entries(200).unwrap().is_empty());
+        for i in 0..UNDO_JOURNAL_KEPT + 5 {
+            let server_id = format!("server-{}", i);
+            db.record_undo_entry(&entry(&server_id, 300, 10_000), "image")
+                .unwrap();
+        }
+
+        let entries = db.get_undo_entries(300).unwrap();
+        assert_eq!(entries.len() as i64, UNDO_JOURNAL_KEPT);
+        assert_eq!(
+            entries[0].server_id,
+            format!("server-{}", UNDO_JOURNAL_KEPT + 4)
+        );
+        assert_eq!(entries[0].action, UndoAction::Disable);
+        assert!(db.get_undo_entry(expired).is_err());
+
+        let (newest, image) = db.get_undo_entry(entries[0].id).unwrap();
+        assert_eq!(newest, entries[0]);
+        assert_eq!(image, "image");
+        db.delete_undo_entry(newest.id).unwrap();
+        assert!(db.get_undo_entry(newest.id).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_undo_uninstall() {
+        let (core, dir) = core();
+        let response = core
+            .uninstall_server(ToolUninstallRequest {
+                server_id: "weather".to_string(),
+            })
+            .await
+            .unwrap();
+        assert!(response.success);
+
+        let entries = core.undoable_changes().await.unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].action, UndoAction::Uninstall);
+        assert_eq!(entries[0].summary, "Uninstalled Weather (weather)");
+        // The env values of the before-image are sealed
+        let (_, image) = DBManager::with_path(dir.path().join("mcp-dockmaster.db"))
+            .unwrap()
+            .get_undo_entry(entries[0].id)
+            .unwrap();
+        assert!(is_sealed(&image));
+        assert!(!image.contains("sk-1234"));
+
+        let result = core.undo_change(entries[0].id).await.unwrap();
+        assert!(result.success, "{}", result.message);
+        let registry = core.tool_registry.read().await;
+        let server = registry.get_server("weather").unwrap();
+        assert!(!server.enabled);
+        assert_eq!(server.description, "Forecasts");
+        assert_eq!(api_key(&server), Some(EnvValue::from("sk-1234")));
+        let provenance = registry.get_server_provenances().unwrap().remove("weather");
+        assert_eq!(provenance.unwrap().source, ProvenanceSource::Registry);
+        drop(registry);
+
+        // Undone entries are gone
+        assert!(core.undoable_changes().await.unwrap().is_empty());
+        assert!(core.undo_change(entries[0].id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_undo_configuration_and_annotations() {
+        let (core, _dir) = core();
+        let response = core
+            .update_server_config(ServerConfigUpdateRequest {
+                server_id: "weather".to_string(),
+                config: HashMap::from([("API_KEY".to_string(), json!("sk-5678"))]),
+                inherit_env: None,
+                cwd: None,
+            })
+            .await
+            .unwrap();
+        assert!(response.success);
+        let harmless = ToolAnnotations {
+            read_only_hint: Some(true),
+            ..Default::default()
+        };
+        core.set_tool_annotations("weather", "forecast", harmless.clone())
+            .await
+            .unwrap();
+
+        let entries = core.undoable_changes().await.unwrap();
+        let actions: Vec<UndoAction> = entries.iter().map(|entry| entry.action).collect();
+        assert_eq!(
+            actions,
+            vec![UndoAction::Annotations, UndoAction::Configure]
+        );
+
+        let result = core.undo_change(entries[0].id).await.unwrap();
+        assert!(result.success, "{}", result.message);
+        assert!(core.tool_annotation_overrides().await.is_empty());
+
+        let result = core.undo_change(entries[1].id).await.unwrap();
+        assert!(result.success, "{}", result.message);
+        let server = core
+            .tool_registry
+            .read()
+            .await
+            .get_server("weather")
+            .unwrap();
+        assert_eq!(api_key(&server), Some(EnvValue::from("sk-1234")));
+
+        // Putting things back can be undone too
+        let entries = core.undoable_changes().await.unwrap();
+        let actions: Vec<UndoAction> = entries.iter().map(|entry| entry.action).collect();
+        assert_eq!(
+            actions,
+            vec![UndoAction::Configure, UndoAction::Annotations]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_retention() {
+        let (core, _dir) = core();
+        assert_eq!(core.undo_retention_hours().await, 24);
+        assert!(core.set_undo_retention_hours(0).await.is_err());
+        core.set_undo_retention_hours(2).await.unwrap();
+        assert_eq!(core.undo_retention_hours().await, 2);
+
+        core.set_tool_annotations(
+            "weather",
+            "forecast",
+            ToolAnnotations {
+                title: Some("Forecast".to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let entry = &core.undoable_changes().await.unwrap()[0];
+        assert_eq!(entry.expires_at - entry.created_at, 2 * 60 * 60 * 1000);
+    }
+}
@@ -0,0 +1,117 @@
+#[cfg(test)]
+mod tests {
+    use mcp_core::models::{
+        types::{
+            ConfigUpdateRequest, RegistryToolsResponse, ServerImportRequest,
+            ServerRegistrationRequest,
+        },
+        unknown_fields::{parse_with_unknown_fields, unknown_field_warnings, KnownFields},
+    };
+    use serde_json::json;
+
+    #[test]
+    fn test_registration_typos_are_reported() {
+        let payload = json!({
+            "server_id": "fetch",
+            "server_name": "Fetch",
+            "description": "Fetches URLs",
+            "tools_type": "node",
+            "configuration": {
+                "comand": "npx",
+                "args": ["-y", "fetch"],
+                "evn": { "A": "b" },
+                "env": {
+                    "TOKEN": { "description": "Token", "requird": true },
+                    "PLAIN": "value"
+                }
+            },
+            "distribution": { "type": "npm", "package": "fetch", "version": "1" }
+        });
+
+        let (request, mut unknown) =
+            parse_with_unknown_fields::<ServerRegistrationRequest>(payload).unwrap();
+
+        assert_eq!(request.server_id, "fetch");
+        unknown.sort();
+        assert_eq!(
+            unknown,
+            vec![
+                "configuration.comand",
+                "configuration.env.TOKEN.requird",
+                "configuration.evn",
+                "distribution.version",
+            ]
+        );
+        assert_eq!(
+            unknown_field_warnings(&["configuration.comand".to_string()]),
+            vec!["Unknown field 'configuration.comand' was ignored"]
+        );
+    }
+
+    #[test]
+    fn test_known_payloads_have_no_warnings() {
+        let payload = json!({
+            "server_id": "fetch",
+            "server_name": "Fetch",
+            "description": "Fetches URLs",
+            "tools_type": "node",
+            "authentication": null,
+            "configuration": { "command": "npx", "args": [], "env": {} }
+        });
+        assert!(ServerRegistrationRequest::unknown_fields(&payload).is_empty());
+
+        let config = json!({ "tool_id": "fetch", "config": { "ANY_KEY": "value" } });
+        assert!(ConfigUpdateRequest::unknown_fields(&config).is_empty());
+
+        let config = json!({ "tool_id": "fetch", "confg": {} });
+        assert_eq!(ConfigUpdateRequest::unknown_fields(&config), vec!["confg"]);
+    }
+
+    #[test]
+    fn test_import_takes_the_enabled_flag() {
+        let payload = json!({ "url": "https://github.com/acme/fetch", "auto_start": false });
+        let (request, unknown) = parse_with_unknown_fields::<ServerImportRequest>(payload).unwrap();
+        assert_eq!(request.url, "https://github.com/acme/fetch");
+        assert_eq!(request.enabled, Some(false));
+        assert!(unknown.is_empty());
+
+        let payload = json!({ "url": "https://github.com/acme/fetch", "enabld": false });
+        let (request, unknown) = parse_with_unknown_fields::<ServerImportRequest>(payload).unwrap();
+        assert_eq!(request.enabled, None);
+        assert_eq!(unknown, vec!["enabld"]);
+    }
+
+    #[test]
+    fn test_registry_unknown_fields_are_reported_once() {
+        let tool = |id: &str| {
+            json!({
+                "id": id,
+                "name": id,
+                "description": "",
+                "short_description": "",
+                "publisher": { "id": "p", "name": "p", "url": "" },
+                "distribution": { "type": "npm", "package": id },
+                "license": "MIT",
+                "runtime": "node",
+                "config": { "command": "npx", "args": [], "env": {} },
+                "categories": [],
+                "tags": [],
+                "featured": true
+            })
+        };
+        let payload = json!({
+            "count": 2,
+            "version": 1,
+            "categories": {},
+            "tags": {},
+            "tools": [tool("a"), tool("b")]
+        });
+
+        assert_eq!(
+            RegistryToolsResponse::unknown_fields(&payload),
+            vec!["tools[].featured"]
+        );
+        // Parsing stays lenient
+        assert!(serde_json::from_value::<RegistryToolsResponse>(payload).is_ok());
+    }
+}
@@ -0,0 +1,294 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use async_trait::async_trait;
+    use mcp_core::{
+        core::{mcp_core_builder::MCPCoreBuilder, mcp_core_webhooks_ext::McpCoreWebhooksExt},
+        database::db_manager::DBManager,
+        events::{AppUpdate, DockmasterEvent, InstallProgress, InstallStage},
+        models::types::ServerStatus,
+        utils::clock::{Clock, ManualClock},
+        webhooks::{
+            deliver, sign, status_changes, webhook_event, Webhook, WebhookEventKind,
+            WebhookPayload, WebhookSender, SIGNATURE_HEADER, WEBHOOK_RETRY_DELAYS,
+        },
+    };
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    /// Request received by a [`FakeSender`]
+    #[derive(Clone, Debug)]
+    struct Sent {
+        url: String,
+        headers: HashMap<String, String>,
+        body: String,
+    }
+
+    /// Sender failing its first `failures` requests
+    #[derive(Default)]
+    struct FakeSender {
+        failures: Mutex<usize>,
+        sent: Mutex<Vec<Sent>>,
+    }
+
+    impl FakeSender {
+        fn failing(failures: usize) -> Arc<Self> {
+            Arc::new(Self {
+                failures: Mutex::new(failures),
+                ..Default::default()
+            })
+        }
+
+        fn sent(&self) -> Vec<Sent> {
+            self.sent.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait]
+    impl WebhookSender for FakeSender {
+        async fn send(
+            &self,
+            url: &str,
+            headers: &[(&str, String)],
+            body: &str,
+        ) -> Result<(), String> {
+            self.sent.lock().unwrap().push(Sent {
+                url: url.to_string(),
+                headers: headers
+                    .iter()
+                    .map(|(name, value)| (name.to_string(), value.clone()))
+                    .collect(),
+                body: body.to_string(),
+            });
+            let mut failures = self.failures.lock().unwrap();
+            if *failures == 0 {
+                return Ok(());
+            }
+            *failures -= 1;
+            Err("The webhook answered 503 Service Unavailable".to_string())
+        }
+    }
+
+    fn webhook(id: &str, events: Vec<WebhookEventKind>) -> Webhook {
+        Webhook {
+            id: id.to_string(),
+            url: "https://hooks.example.com/dockmaster".to_string(),
+            events,
+            enabled: true,
+            secret: Some("s3cret".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_sign() {
+        // RFC 4231, test case 2
+        assert_eq!(
+            sign("Jefe", "what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_webhook_definition() {
+        let crashes = webhook("slack", vec![WebhookEventKind::Crash]);
+        assert!(crashes.validate().is_ok());
+        assert!(crashes.wants(WebhookEventKind::Crash));
+        assert!(!crashes.wants(WebhookEventKind::Install));
+        assert!(webhook("all", vec![]).wants(WebhookEventKind::Install));
+
+        let mut disabled = crashes.clone();
+        disabled.enabled = false;
+        assert!(!disabled.wants(WebhookEventKind::Crash));
+
+        let mut bad = crashes.clone();
+        bad.url = "hooks.example.com".to_string();
+        assert!(bad.validate().is_err());
+
+        // Secrets are accepted but never sent back
+        let parsed: Webhook = serde_json::from_value(json!({
+            "id": "slack",
+            "url": "https://hooks.example.com/dockmaster",
+            "events": ["crash"],
+            "secret": "s3cret"
+        }))
+        .unwrap();
+        assert_eq!(parsed, crashes);
+        assert!(serde_json::to_value(&parsed)
+            .unwrap()
+            .get("secret")
+            .is_none());
+    }
+
+    #[test]
+    fn test_webhook_events() {
+        let progress = |stage| {
+            DockmasterEvent::InstallProgress(InstallProgress {
+                operation_id: "op".to_string(),
+                server_id: "weather".to_string(),
+                stage,
+                detail: Some("npm failed".to_string()),
+            })
+        };
+        assert!(webhook_event(&progress(InstallStage::Pulling)).is_none());
+        let (event, data) = webhook_event(&progress(InstallStage::Failed)).unwrap();
+        assert_eq!(event, WebhookEventKind::Install);
+        assert_eq!(data["success"], json!(false));
+        assert_eq!(
+            WebhookPayload::new(event, data).text,
+            "Failed to install server weather: npm failed"
+        );
+
+        let update = DockmasterEvent::UpdateAvailable(AppUpdate {
+            version: "0.9.0".to_string(),
+        });
+        let (event, data) = webhook_event(&update).unwrap();
+        assert_eq!(event, WebhookEventKind::UpdateAvailable);
+        assert_eq!(data, json!({ "version": "0.9.0" }));
+    }
+
+    #[test]
+    fn test_status_changes() {
+        let previous = HashMap::from([
+            ("files".to_string(), ServerStatus::Stopped),
+            ("weather".to_string(), ServerStatus::Running),
+            ("search".to_string(), ServerStatus::Running),
+        ]);
+        let current = HashMap::from([
+            ("files".to_string(), ServerStatus::Running),
+            (
+                "weather".to_string(),
+                ServerStatus::Error("exit code 1".to_string()),
+            ),
+            ("search".to_string(), ServerStatus::Running),
+            ("new".to_string(), ServerStatus::Running),
+        ]);
+
+        let changes = status_changes(&previous, &current);
+        let events: Vec<WebhookEventKind> = changes.iter().map(|(event, _)| *event).collect();
+        assert_eq!(
+            events,
+            vec![
+                WebhookEventKind::StatusChange,
+                WebhookEventKind::StatusChange,
+                WebhookEventKind::Crash
+            ]
+        );
+        assert_eq!(
+            changes[0].1,
+            json!({ "server_id": "files", "from": "Stopped", "to": "Running" })
+        );
+        assert_eq!(
+            changes[2].1,
+            json!({ "server_id": "weather", "error": "exit code 1" })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_deliver_retries_signed_payloads() {
+        let sender = FakeSender::failing(2);
+        let clock = ManualClock::new();
+        let started = clock.now();
+        let hook = webhook("slack", vec![]);
+        let payload = WebhookPayload::new(WebhookEventKind::Test, json!({}));
+
+        let delivery = deliver(
+            sender.as_ref(),
+            &clock,
+            &hook,
+            &payload,
+            &WEBHOOK_RETRY_DELAYS,
+        )
+        .await;
+        assert!(delivery.success);
+        assert_eq!(delivery.attempts, 3);
+        assert_eq!(
+            clock.now() - started,
+            WEBHOOK_RETRY_DELAYS[0] + WEBHOOK_RETRY_DELAYS[1]
+        );
+
+        let sent = sender.sent();
+        assert_eq!(sent[0].url, hook.url);
+        assert_eq!(sent[0].body, sent[2].body);
+        assert_eq!(
+            sent[0].headers[SIGNATURE_HEADER],
+            sign("s3cret", &sent[0].body)
+        );
+        let body: WebhookPayload = serde_json::from_str(&sent[0].body).unwrap();
+        assert_eq!(body, payload);
+
+        let sender = FakeSender::failing(10);
+        let delivery = deliver(sender.as_ref(), &clock, &hook, &payload, &[]).await;
+        assert!(!delivery.success);
+        assert_eq!(delivery.attempts, 1);
+        assert!(delivery.error.unwrap().contains("503"));
+    }
+
+    #[tokio::test]
+    async fn test_webhooks_ext() {
+        let dir = tempdir().unwrap();
+        let db = DBManager::with_path(dir.path().join("mcp-dockmaster.db")).unwrap();
+        db.apply_migrations().unwrap();
+        let sender = FakeSender::failing(usize::MAX);
+        let core = MCPCoreBuilder::new(db.clone())
+            .clock(Arc::new(ManualClock::new()))
+            .webhook_sender(sender.clone())
+            .build();
+
+        let mut slack = webhook("slack", vec![WebhookEventKind::Crash]);
+        slack.secret = None;
+        let created = core.create_webhook(slack.clone()).await.unwrap();
+        assert!(created.secret.starts_with("whsec_"));
+        assert_eq!(created.webhook.secret, None);
+        assert_eq!(
+            core.create_webhook(slack.clone()).await.unwrap_err(),
+            "Webhook 'slack' already exists"
+        );
+
+        // Updating without a secret keeps the current one
+        slack.events = vec![WebhookEventKind::Crash, WebhookEventKind::Install];
+        core.update_webhook(slack.clone()).await.unwrap();
+        let saved = db.get_webhooks().unwrap();
+        assert_eq!(saved[0].secret.as_deref(), Some(created.secret.as_str()));
+        assert_eq!(saved[0].events.len(), 2);
+        assert_eq!(core.list_webhooks().await.unwrap()[0].secret, None);
+
+        let test = core.test_webhook("slack").await.unwrap();
+        assert!(!test.success);
+        assert_eq!(test.attempts, 1);
+        assert!(core.webhook_dead_letters(10).await.unwrap().is_empty());
+
+        // Events the webhook doesn't want are not sent
+        core.notify_webhooks(WebhookEventKind::StatusChange, json!({}))
+            .await;
+        core.notify_webhooks(
+            WebhookEventKind::Crash,
+            json!({ "server_id": "weather", "error": "exit code 1" }),
+        )
+        .await;
+        let mut dead_letters = Vec::new();
+        for _ in 0..100 {
+            dead_letters = core.webhook_dead_letters(10).await.unwrap();
+            if !dead_letters.is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(dead_letters.len(), 1);
+        let dead_letter = &dead_letters[0];
+        assert_eq!(dead_letter.webhook_id, "slack");
+        assert_eq!(dead_letter.payload.event, WebhookEventKind::Crash);
+        assert_eq!(
+            dead_letter.payload.text,
+            "Server weather crashed: exit code 1"
+        );
+        assert_eq!(dead_letter.attempts, WEBHOOK_RETRY_DELAYS.len() as u32 + 1);
+        assert_eq!(sender.sent().len(), 1 + WEBHOOK_RETRY_DELAYS.len() + 1);
+
+        core.delete_webhook("slack").await.unwrap();
+        assert!(core.list_webhooks().await.unwrap().is_empty());
+        assert!(core.test_webhook("slack").await.is_err());
+    }
+}
@@ -0,0 +1,121 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use mcp_core::models::types::{
+        InputSchema, ServerStatus, ServerToolInfo, ServerToolsResponse, ToolDescription, ToolUsage,
+    };
+    use serde_json::{json, Value};
+
+    const TOOLS_LIST: &str = include_str!("../fixtures/wire/tools_list.json");
+    const TOOL_DESCRIPTION: &str = include_str!("../fixtures/wire/tool_description.json");
+
+    fn fixture(text: &str) -> Value {
+        serde_json::from_str(text).expect("fixture should be valid JSON")
+    }
+
+    #[test]
+    fn test_tools_list_matches_fixture() {
+        let golden = fixture(TOOLS_LIST);
+        let response = ServerToolsResponse {
+            tools: serde_json::from_value(golden["tools"].clone()).unwrap(),
+        };
+
+        assert_eq!(response.tools.len(), 2);
+        assert_eq!(response.tools[0].server_id, "filesystem");
+        assert_eq!(
+            response.tools[0].proxy_id.as_deref(),
+            Some("filesystem:read_file")
+        );
+        assert!(!response.tools[1].is_active);
+        assert_eq!(serde_json::to_value(&response).unwrap(), golden);
+    }
+
+    #[test]
+    fn test_tool_description_matches_fixture() {
+        let description = ToolDescription {
+            proxy_id: "filesystem:read_file".to_string(),
+            server_id: "filesystem".to_string(),
+            id: "read_file".to_string(),
+            name: "read_file".to_string(),
+            description: "Read a file from disk".to_string(),
+            input_schema: Some(
+                serde_json::from_value::<InputSchema>(json!({
+                    "type": "object",
+                    "properties": {
+                        "encoding": { "type": "string", "default": "utf-8" }
+                    }
+                }))
+                .unwrap(),
+            ),
+            output_schema: Some(json!({
+                "type": "object",
+                "properties": { "content": { "type": "string" } },
+                "required": ["content"]
+            })),
+            defaults: BTreeMap::from([("encoding".to_string(), json!("utf-8"))]),
+            is_active: true,
+            server_status: ServerStatus::Running,
+            usage: ToolUsage {
+                calls: 3,
+                errors: 1,
+                total_duration_ms: 42,
+                last_called_at: None,
+                rate_limited: 2,
+            },
+            rate_limit: None,
+            annotations: None,
+        };
+
+        assert_eq!(
+            serde_json::to_value(&description).unwrap(),
+            fixture(TOOL_DESCRIPTION)
+        );
+    }
+
+    #[test]
+    fn test_snake_case_tools_are_still_read() {
+        let legacy = json!({
+            "id": "read_file",
+            "name": "read_file",
+            "description": "Read a file from disk",
+            "input_schema": { "type": "object" },
+            "server_id": "filesystem",
+            "proxy_id": "filesystem:read_file",
+            "is_active": false
+        });
+        let tool: ServerToolInfo = serde_json::from_value(legacy).unwrap();
+        assert_eq!(tool.server_id, "filesystem");
+        assert_eq!(tool.proxy_id.as_deref(), Some("filesystem:read_file"));
+        assert!(tool.input_schema.is_some());
+        assert!(!tool.is_active);
+
+        // Written back with the protocol names only
+        let written = serde_json::to_value(&tool).unwrap();
+        for key in ["serverId", "proxyId", "isActive", "inputSchema"] {
+            assert!(written.get(key).is_some(), "{} missing", key);
+        }
+        for key in ["server_id", "proxy_id", "is_active", "input_schema"] {
+            assert!(written.get(key).is_none(), "{} present", key);
+        }
+    }
+
+    #[test]
+    fn test_discovered_tools_use_protocol_names() {
+        let tool = ServerToolInfo::from_value(
+            json!({
+                "name": "Read File",
+                "description": "Read a file from disk",
+                "inputSchema": { "type": "object" }
+            }),
+            "filesystem".to_string(),
+        )
+        .unwrap();
+
+        let written = serde_json::to_value(&tool).unwrap();
+        assert_eq!(written["id"], "read_file");
+        assert_eq!(written["serverId"], "filesystem");
+        assert_eq!(written["isActive"], true);
+        assert_eq!(written["inputSchema"]["type"], "object");
+    }
+}